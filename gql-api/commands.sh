@@ -4,6 +4,15 @@ RUST_BACKTRACE=full ENV=dev RUST_LOG=info,gql,gqli cargo run --bin gql-api -- --
 # start the test client locally if you want to test
 RUST_BACKTRACE=full PROTO_DIR="../../protos/nearapiservice.proto" AWS_CONFIG_FILE="~/.aws/config" AWS_SHARED_CREDENTIALS_FILE="~/.aws/credentials" AWS_PROFILE=default  ENV=dev RUST_LOG=info,gql,gqli cargo run --bin gql-api -- --config ./config.toml
 
+# fill the local db with fake data, see `gql-api seed --help` for the counts (dev or test only)
+ENV=dev cargo run --bin gql-api -- --config ./config.toml seed --seed 1
+
+# rewrite the phone numbers stored before normalization to E.164, once after upgrading
+cargo run --bin gql-api -- --config ./config.toml backfill-phone-numbers
+
+# run the route tests, against the test db and the in-memory fakes
+cargo test --features test-harness --test routes
+
 # deploy gql-api to aws registry
 DOCKER_BUILDKIT=1 docker build --file Dockerfile --tag gql-api --target release --build-arg BUILD_ENV=release --build-arg RUST_VERSION=stable --build-arg RUSTC_WRAPPER="sccache" .
 aws ecr get-login-password --region eu-west-2 | docker login --username XXX --password-stdin XXXX
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE promo_code_usages;
+DROP TABLE promo_codes
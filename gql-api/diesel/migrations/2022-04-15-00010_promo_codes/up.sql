@@ -0,0 +1,30 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists promo_codes (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  code VARCHAR NOT NULL,
+  discount_type SMALLINT NOT NULL DEFAULT 0,
+  discount_value VARCHAR NOT NULL,
+  max_uses INTEGER,
+  used_count INTEGER NOT NULL DEFAULT 0,
+  valid_from TIMESTAMP,
+  valid_until TIMESTAMP,
+  is_active BOOLEAN NOT NULL DEFAULT 't',
+  event_id UUID NOT NULL REFERENCES public.events (id) ON DELETE CASCADE,
+  created_by_user UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  PRIMARY KEY (id),
+  UNIQUE (code, event_id)
+);
+
+CREATE TABLE if not exists promo_code_usages (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  verification_code VARCHAR NOT NULL,
+  original_price VARCHAR,
+  discounted_price VARCHAR,
+  promo_code_id UUID NOT NULL REFERENCES public.promo_codes (id) ON DELETE CASCADE,
+  ticket_id UUID NOT NULL REFERENCES public.tickets (id) ON DELETE CASCADE,
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  PRIMARY KEY (id)
+)
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists ticket_transfers (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  price VARCHAR,
+  tx_hash VARCHAR NOT NULL,
+  reservation_id UUID NOT NULL REFERENCES public.ticket_reservations (id) ON DELETE CASCADE,
+  ticket_id UUID NOT NULL REFERENCES public.tickets (id) ON DELETE CASCADE,
+  from_user UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  to_user UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  PRIMARY KEY (id)
+)
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX if exists jobs_pending_idx;
+
+DROP TABLE jobs
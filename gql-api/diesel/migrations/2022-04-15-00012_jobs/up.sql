@@ -0,0 +1,17 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists jobs (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  run_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  job_type SMALLINT NOT NULL,
+  payload TEXT NOT NULL,
+  job_status SMALLINT NOT NULL DEFAULT 0,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  max_attempts INTEGER NOT NULL,
+  last_error VARCHAR,
+  PRIMARY KEY (id)
+);
+
+CREATE INDEX if not exists jobs_pending_idx ON jobs (job_status, run_at)
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE asset_files
+  DROP COLUMN if exists content_type,
+  DROP COLUMN if exists is_confirmed
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+ALTER TABLE asset_files
+  ADD COLUMN if not exists content_type VARCHAR NULL,
+  ADD COLUMN if not exists is_confirmed BOOLEAN NOT NULL DEFAULT 't'
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE events
+  DROP COLUMN if exists cover_photo_ipfs_url,
+  DROP COLUMN if exists thumbnail_ipfs_url
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+ALTER TABLE events
+  ADD COLUMN if not exists cover_photo_ipfs_url VARCHAR NULL,
+  ADD COLUMN if not exists thumbnail_ipfs_url VARCHAR NULL
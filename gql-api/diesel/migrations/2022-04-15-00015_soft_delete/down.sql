@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE tickets
+  DROP COLUMN if exists archived,
+  DROP COLUMN if exists deleted_at;
+
+ALTER TABLE events
+  DROP COLUMN if exists archived,
+  DROP COLUMN if exists deleted_at
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+ALTER TABLE events
+  ADD COLUMN if not exists archived BOOLEAN NOT NULL DEFAULT 'f',
+  ADD COLUMN if not exists deleted_at TIMESTAMP NULL;
+
+ALTER TABLE tickets
+  ADD COLUMN if not exists archived BOOLEAN NOT NULL DEFAULT 'f',
+  ADD COLUMN if not exists deleted_at TIMESTAMP NULL
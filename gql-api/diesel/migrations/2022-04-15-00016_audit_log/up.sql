@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists audit_log (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  actor_id UUID,
+  action VARCHAR NOT NULL,
+  entity_type VARCHAR NOT NULL,
+  entity_id UUID NOT NULL,
+  diff TEXT,
+  PRIMARY KEY (id)
+);
+
+CREATE INDEX if not exists audit_log_actor_id_idx ON audit_log (actor_id, created_at);
+CREATE INDEX if not exists audit_log_entity_id_idx ON audit_log (entity_id, created_at)
@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE buyer_recovery_sessions
+  DROP COLUMN if exists expires_at,
+  DROP COLUMN if exists attempts,
+  DROP COLUMN if exists is_consumed;
+
+ALTER TABLE buyer_signup_sessions
+  DROP COLUMN if exists expires_at,
+  DROP COLUMN if exists attempts,
+  DROP COLUMN if exists is_consumed
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+ALTER TABLE buyer_signup_sessions
+  ADD COLUMN if not exists expires_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  ADD COLUMN if not exists attempts INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN if not exists is_consumed BOOLEAN NOT NULL DEFAULT 'f';
+
+ALTER TABLE buyer_recovery_sessions
+  ADD COLUMN if not exists expires_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  ADD COLUMN if not exists attempts INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN if not exists is_consumed BOOLEAN NOT NULL DEFAULT 'f'
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX if exists users_wallet_balance_updated_at_idx;
+
+ALTER TABLE users
+  DROP COLUMN if exists wallet_balance_updated_at
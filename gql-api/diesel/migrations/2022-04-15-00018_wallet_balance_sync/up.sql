@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+ALTER TABLE users
+  ADD COLUMN if not exists wallet_balance_updated_at TIMESTAMP NULL;
+
+CREATE INDEX if not exists users_wallet_balance_updated_at_idx ON users (wallet_balance_updated_at NULLS FIRST)
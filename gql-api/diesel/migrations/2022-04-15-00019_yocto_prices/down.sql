@@ -0,0 +1,25 @@
+-- This file should undo anything in `up.sql`
+
+UPDATE promo_codes
+  SET discount_value = (discount_value::numeric / 1000000000000000000000000)::text
+  WHERE discount_type = 1 AND discount_value ~ '^[0-9]+$';
+
+UPDATE promo_code_usages
+  SET discounted_price = (discounted_price::numeric / 1000000000000000000000000)::text
+  WHERE discounted_price ~ '^[0-9]+$';
+
+UPDATE promo_code_usages
+  SET original_price = (original_price::numeric / 1000000000000000000000000)::text
+  WHERE original_price ~ '^[0-9]+$';
+
+UPDATE ticket_transfers
+  SET price = (price::numeric / 1000000000000000000000000)::text
+  WHERE price ~ '^[0-9]+$';
+
+UPDATE tickets
+  SET max_release_price = (max_release_price::numeric / 1000000000000000000000000)::text
+  WHERE max_release_price ~ '^[0-9]+$';
+
+UPDATE tickets
+  SET price = (price::numeric / 1000000000000000000000000)::text
+  WHERE price ~ '^[0-9]+$'
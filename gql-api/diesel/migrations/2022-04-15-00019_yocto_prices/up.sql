@@ -0,0 +1,28 @@
+-- Your SQL goes here
+
+-- prices were stored as decimal NEAR strings, they are now yoctoNEAR integer strings
+
+UPDATE tickets
+  SET price = trunc(price::numeric * 1000000000000000000000000)::numeric(78, 0)::text
+  WHERE price ~ '^[0-9]+(\.[0-9]+)?$';
+
+UPDATE tickets
+  SET max_release_price = trunc(max_release_price::numeric * 1000000000000000000000000)::numeric(78, 0)::text
+  WHERE max_release_price ~ '^[0-9]+(\.[0-9]+)?$';
+
+UPDATE ticket_transfers
+  SET price = trunc(price::numeric * 1000000000000000000000000)::numeric(78, 0)::text
+  WHERE price ~ '^[0-9]+(\.[0-9]+)?$';
+
+UPDATE promo_code_usages
+  SET original_price = trunc(original_price::numeric * 1000000000000000000000000)::numeric(78, 0)::text
+  WHERE original_price ~ '^[0-9]+(\.[0-9]+)?$';
+
+UPDATE promo_code_usages
+  SET discounted_price = trunc(discounted_price::numeric * 1000000000000000000000000)::numeric(78, 0)::text
+  WHERE discounted_price ~ '^[0-9]+(\.[0-9]+)?$';
+
+-- fixed discounts are amounts too, percentages are left as they are
+UPDATE promo_codes
+  SET discount_value = trunc(discount_value::numeric * 1000000000000000000000000)::numeric(78, 0)::text
+  WHERE discount_type = 1 AND discount_value ~ '^[0-9]+(\.[0-9]+)?$'
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE tickets ADD COLUMN if not exists currency SMALLINT NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE if exists payout_requests;
+DROP TABLE if exists payout_accounts;
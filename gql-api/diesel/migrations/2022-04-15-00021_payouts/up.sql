@@ -0,0 +1,27 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists payout_accounts (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  wallet_id VARCHAR NOT NULL,
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  PRIMARY KEY (id),
+  UNIQUE (user_id)
+);
+
+CREATE TABLE if not exists payout_requests (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  amount VARCHAR NOT NULL,
+  wallet_id VARCHAR NOT NULL,
+  payout_status SMALLINT NOT NULL DEFAULT 0,
+  reviewed_by UUID REFERENCES public.users (id) ON DELETE SET NULL,
+  reviewed_at TIMESTAMP,
+  tx_hash VARCHAR,
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  PRIMARY KEY (id)
+);
+
+CREATE INDEX if not exists payout_requests_user_id_idx ON payout_requests (user_id);
+CREATE INDEX if not exists payout_requests_payout_status_idx ON payout_requests (payout_status);
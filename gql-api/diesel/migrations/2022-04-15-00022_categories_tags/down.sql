@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE if exists event_tags;
+DROP INDEX if exists events_category_id_idx;
+ALTER TABLE events DROP COLUMN if exists category_id;
+DROP TABLE if exists categories;
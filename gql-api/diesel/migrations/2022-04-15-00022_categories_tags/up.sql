@@ -0,0 +1,22 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists categories (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  name VARCHAR NOT NULL,
+  slug VARCHAR NOT NULL,
+  PRIMARY KEY (id),
+  UNIQUE (slug)
+);
+
+ALTER TABLE events ADD COLUMN if not exists category_id UUID REFERENCES public.categories (id) ON DELETE SET NULL;
+CREATE INDEX if not exists events_category_id_idx ON events (category_id);
+
+CREATE TABLE if not exists event_tags (
+  event_id UUID NOT NULL REFERENCES public.events (id) ON DELETE CASCADE,
+  tag VARCHAR NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (event_id, tag)
+);
+
+CREATE INDEX if not exists event_tags_tag_idx ON event_tags (tag);
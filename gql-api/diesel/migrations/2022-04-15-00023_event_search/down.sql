@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX if exists events_venue_name_trgm_idx;
+DROP INDEX if exists events_event_name_trgm_idx;
+DROP INDEX if exists events_search_vector_idx;
+ALTER TABLE events DROP COLUMN if exists search_vector;
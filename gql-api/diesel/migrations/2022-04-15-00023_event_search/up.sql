@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE EXTENSION if not exists pg_trgm;
+
+ALTER TABLE events ADD COLUMN if not exists search_vector TSVECTOR GENERATED ALWAYS AS (
+  setweight(to_tsvector('english', coalesce(event_name, '')), 'A') ||
+  setweight(to_tsvector('english', coalesce(venue_name, '') || ' ' || coalesce(venue_location, '')), 'B') ||
+  setweight(to_tsvector('english', coalesce(description, '')), 'C')
+) STORED;
+
+CREATE INDEX if not exists events_search_vector_idx ON events USING GIN (search_vector);
+CREATE INDEX if not exists events_event_name_trgm_idx ON events USING GIN (event_name gin_trgm_ops);
+CREATE INDEX if not exists events_venue_name_trgm_idx ON events USING GIN (venue_name gin_trgm_ops);
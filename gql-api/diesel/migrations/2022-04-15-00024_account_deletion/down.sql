@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE users DROP COLUMN if exists wallet_flagged;
+ALTER TABLE users DROP COLUMN if exists deleted_at;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE users ADD COLUMN if not exists deleted_at TIMESTAMP;
+ALTER TABLE users ADD COLUMN if not exists wallet_flagged BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+CREATE INDEX if not exists users_phone_number_idx ON users (phone_number);
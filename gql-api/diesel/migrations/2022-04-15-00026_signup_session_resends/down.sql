@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE buyer_signup_sessions
+  DROP COLUMN if exists resend_count,
+  DROP COLUMN if exists last_sent_at
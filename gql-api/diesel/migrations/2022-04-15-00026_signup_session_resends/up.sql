@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+ALTER TABLE buyer_signup_sessions
+  ADD COLUMN if not exists resend_count INTEGER NOT NULL DEFAULT 0,
+  ADD COLUMN if not exists last_sent_at TIMESTAMP NOT NULL DEFAULT NOW()
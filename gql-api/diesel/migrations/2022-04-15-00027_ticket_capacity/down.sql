@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX if exists ticket_reservations_ticket_id_user_id_idx;
+
+ALTER TABLE tickets
+  DROP COLUMN if exists quantity_reserved;
+
+ALTER TABLE ticket_reservations
+  DROP COLUMN if exists quantity
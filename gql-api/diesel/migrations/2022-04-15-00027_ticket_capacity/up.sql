@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+ALTER TABLE ticket_reservations
+  ADD COLUMN if not exists quantity INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE tickets
+  ADD COLUMN if not exists quantity_reserved INTEGER NOT NULL DEFAULT 0;
+
+UPDATE tickets SET quantity_reserved = reserved.quantity
+  FROM (
+    SELECT ticket_id, SUM(quantity) AS quantity FROM ticket_reservations GROUP BY ticket_id
+  ) AS reserved
+  WHERE tickets.id = reserved.ticket_id;
+
+CREATE INDEX if not exists ticket_reservations_ticket_id_user_id_idx
+  ON ticket_reservations (ticket_id, user_id)
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE ticket_reservations
+  DROP COLUMN if exists checked_in_at;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+ALTER TABLE ticket_reservations
+  ADD COLUMN if not exists checked_in_at TIMESTAMP;
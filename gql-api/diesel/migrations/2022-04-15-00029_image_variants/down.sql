@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE events
+  DROP COLUMN if exists og_image_url,
+  DROP COLUMN if exists cover_variant_url,
+  DROP COLUMN if exists thumbnail_variant_url;
+
+ALTER TABLE asset_files
+  DROP COLUMN if exists og_image_key,
+  DROP COLUMN if exists cover_key,
+  DROP COLUMN if exists thumbnail_key;
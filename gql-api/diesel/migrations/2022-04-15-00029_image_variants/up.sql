@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+ALTER TABLE asset_files
+  ADD COLUMN if not exists thumbnail_key VARCHAR,
+  ADD COLUMN if not exists cover_key VARCHAR,
+  ADD COLUMN if not exists og_image_key VARCHAR;
+
+ALTER TABLE events
+  ADD COLUMN if not exists thumbnail_variant_url VARCHAR,
+  ADD COLUMN if not exists cover_variant_url VARCHAR,
+  ADD COLUMN if not exists og_image_url VARCHAR;
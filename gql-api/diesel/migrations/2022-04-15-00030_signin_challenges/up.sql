@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists signin_challenges (
+  id UUID,
+  created_at TIMESTAMP NOT NULL,
+  wallet_id VARCHAR NOT NULL,
+  nonce VARCHAR NOT NULL UNIQUE,
+  expires_at TIMESTAMP NOT NULL,
+  is_consumed BOOLEAN NOT NULL DEFAULT 'f',
+  PRIMARY KEY (id)
+)
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists waitlist (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  ticket_id UUID NOT NULL REFERENCES public.tickets (id) ON DELETE CASCADE,
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  notified_at TIMESTAMP,
+  PRIMARY KEY (id),
+  UNIQUE (ticket_id, user_id)
+)
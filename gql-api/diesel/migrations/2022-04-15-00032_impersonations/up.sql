@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists impersonations (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  admin_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  expires_at TIMESTAMP NOT NULL,
+  revoked_at TIMESTAMP,
+  PRIMARY KEY (id)
+)
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX if exists events_organization_id_idx;
+ALTER TABLE events DROP COLUMN if exists organization_id;
+DROP TABLE if exists organization_members;
+DROP TABLE if exists organizations;
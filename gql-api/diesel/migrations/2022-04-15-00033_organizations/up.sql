@@ -0,0 +1,24 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists organizations (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  name VARCHAR NOT NULL,
+  slug VARCHAR NOT NULL,
+  created_by_user UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  PRIMARY KEY (id),
+  UNIQUE (slug)
+);
+
+CREATE TABLE if not exists organization_members (
+  organization_id UUID NOT NULL REFERENCES public.organizations (id) ON DELETE CASCADE,
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  member_role SMALLINT NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (organization_id, user_id)
+);
+
+CREATE INDEX if not exists organization_members_user_id_idx ON organization_members (user_id);
+
+ALTER TABLE events ADD COLUMN if not exists organization_id UUID REFERENCES public.organizations (id) ON DELETE SET NULL;
+CREATE INDEX if not exists events_organization_id_idx ON events (organization_id);
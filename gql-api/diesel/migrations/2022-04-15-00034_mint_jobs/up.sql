@@ -0,0 +1,21 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists mint_jobs (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  event_id UUID NOT NULL REFERENCES public.events (id) ON DELETE CASCADE,
+  ticket_id UUID NOT NULL REFERENCES public.tickets (id) ON DELETE CASCADE,
+  wallet_id VARCHAR NOT NULL,
+  tx_hash VARCHAR NOT NULL,
+  number_of_tickets INTEGER NOT NULL,
+  mint_status SMALLINT NOT NULL,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  checked_at TIMESTAMP,
+  error VARCHAR,
+  PRIMARY KEY (id),
+  UNIQUE (tx_hash)
+);
+
+CREATE INDEX if not exists mint_jobs_event_id_idx ON mint_jobs (event_id);
+CREATE INDEX if not exists mint_jobs_mint_status_idx ON mint_jobs (mint_status, checked_at);
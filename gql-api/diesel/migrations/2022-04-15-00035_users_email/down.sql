@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX if exists users_email_idx;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+CREATE UNIQUE INDEX if not exists users_email_idx ON users (LOWER(email))
+  WHERE email IS NOT NULL AND deleted_at IS NULL;
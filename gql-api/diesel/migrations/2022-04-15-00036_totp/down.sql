@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE if exists totp_challenges;
+DROP TABLE if exists user_totps
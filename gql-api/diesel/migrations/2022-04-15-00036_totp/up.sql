@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists user_totps (
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  encrypted_secret VARCHAR NOT NULL,
+  enabled_at TIMESTAMP,
+  last_used_step BIGINT NOT NULL DEFAULT 0,
+  PRIMARY KEY (user_id)
+);
+
+CREATE TABLE if not exists totp_challenges (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  expires_at TIMESTAMP NOT NULL,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  is_consumed BOOLEAN NOT NULL DEFAULT 'f',
+  PRIMARY KEY (id)
+)
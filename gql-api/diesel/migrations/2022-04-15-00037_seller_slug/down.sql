@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX if exists users_seller_slug_idx;
+
+ALTER TABLE users DROP COLUMN if exists seller_slug;
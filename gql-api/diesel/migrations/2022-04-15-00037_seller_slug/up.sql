@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE users ADD COLUMN if not exists seller_slug VARCHAR NULL;
+
+CREATE UNIQUE INDEX if not exists users_seller_slug_idx ON users (seller_slug) WHERE seller_slug IS NOT NULL;
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists wallet_top_ups (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  wallet_id VARCHAR NOT NULL,
+  amount VARCHAR NOT NULL,
+  tx_hash VARCHAR,
+  triggered_by UUID REFERENCES public.users (id) ON DELETE SET NULL,
+  PRIMARY KEY (id)
+);
+
+CREATE INDEX if not exists wallet_top_ups_user_id_created_at_idx ON wallet_top_ups (user_id, created_at);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE if exists event_daily_stats;
+DROP TABLE if exists event_views;
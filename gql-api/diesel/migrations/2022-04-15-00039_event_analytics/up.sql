@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+-- raw page views, folded into the daily stats by the analytics worker
+CREATE TABLE if not exists event_views (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  event_id UUID NOT NULL REFERENCES public.events (id) ON DELETE CASCADE,
+  PRIMARY KEY (id)
+);
+
+CREATE INDEX if not exists event_views_created_at_idx ON event_views (created_at);
+
+CREATE TABLE if not exists event_daily_stats (
+  event_id UUID NOT NULL REFERENCES public.events (id) ON DELETE CASCADE,
+  day DATE NOT NULL,
+  views INTEGER NOT NULL DEFAULT 0,
+  reservations INTEGER NOT NULL DEFAULT 0,
+  purchases INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (event_id, day)
+);
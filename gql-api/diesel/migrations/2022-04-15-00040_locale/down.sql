@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE buyer_signup_sessions DROP COLUMN if exists locale;
+
+ALTER TABLE users DROP COLUMN if exists locale;
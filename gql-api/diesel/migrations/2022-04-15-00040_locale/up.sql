@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE users ADD COLUMN if not exists locale VARCHAR NULL;
+
+ALTER TABLE buyer_signup_sessions ADD COLUMN if not exists locale VARCHAR NULL;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER event_tags_notify_changed ON event_tags;
+DROP TRIGGER tickets_notify_changed ON tickets;
+DROP TRIGGER events_notify_changed ON events;
+DROP FUNCTION notify_event_changed();
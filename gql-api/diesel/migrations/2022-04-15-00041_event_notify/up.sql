@@ -0,0 +1,30 @@
+-- Your SQL goes here
+CREATE OR REPLACE FUNCTION notify_event_changed() RETURNS TRIGGER AS $$
+DECLARE
+    changed JSONB;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        changed := to_jsonb(OLD);
+    ELSE
+        changed := to_jsonb(NEW);
+    END IF;
+    IF TG_TABLE_NAME = 'events' THEN
+        PERFORM pg_notify('event_changed', changed->>'id');
+    ELSE
+        PERFORM pg_notify('event_changed', changed->>'event_id');
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER events_notify_changed
+    AFTER INSERT OR UPDATE OR DELETE ON events
+    FOR EACH ROW EXECUTE PROCEDURE notify_event_changed();
+
+CREATE TRIGGER tickets_notify_changed
+    AFTER INSERT OR UPDATE OR DELETE ON tickets
+    FOR EACH ROW EXECUTE PROCEDURE notify_event_changed();
+
+CREATE TRIGGER event_tags_notify_changed
+    AFTER INSERT OR UPDATE OR DELETE ON event_tags
+    FOR EACH ROW EXECUTE PROCEDURE notify_event_changed();
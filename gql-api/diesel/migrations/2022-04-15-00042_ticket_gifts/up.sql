@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists ticket_gifts (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  reservation_id UUID NOT NULL REFERENCES public.ticket_reservations (id) ON DELETE CASCADE,
+  from_user UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  to_user UUID NULL REFERENCES public.users (id) ON DELETE SET NULL,
+  to_phone_number VARCHAR NOT NULL,
+  claim_code VARCHAR NOT NULL UNIQUE,
+  claimed_by UUID NULL REFERENCES public.users (id) ON DELETE SET NULL,
+  claimed_at TIMESTAMP NULL,
+  PRIMARY KEY (id),
+  UNIQUE (reservation_id)
+);
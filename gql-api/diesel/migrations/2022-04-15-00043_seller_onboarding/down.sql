@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE seller_documents;
+DROP INDEX users_seller_status_idx;
+ALTER TABLE users DROP COLUMN seller_rejection_reason;
+ALTER TABLE users DROP COLUMN seller_status;
@@ -0,0 +1,22 @@
+-- Your SQL goes here
+ALTER TABLE users ADD COLUMN seller_status SMALLINT NULL;
+ALTER TABLE users ADD COLUMN seller_rejection_reason VARCHAR NULL;
+
+-- the sellers active before their onboarding was reviewed stay approved
+UPDATE users SET seller_status = 2 WHERE user_type = 1;
+
+CREATE INDEX users_seller_status_idx ON users (seller_status) WHERE seller_status IS NOT NULL;
+
+CREATE TABLE if not exists seller_documents (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  document_kind SMALLINT NOT NULL,
+  s3_bucket VARCHAR NOT NULL,
+  s3_absolute_key VARCHAR NOT NULL,
+  content_type VARCHAR NOT NULL,
+  is_confirmed BOOLEAN NOT NULL DEFAULT FALSE,
+  PRIMARY KEY (id)
+);
+
+CREATE INDEX seller_documents_user_id_idx ON seller_documents (user_id);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE ticket_reservations DROP COLUMN price_tier_id;
+DROP TRIGGER ticket_price_tiers_notify_changed ON ticket_price_tiers;
+DROP TABLE ticket_price_tiers;
@@ -0,0 +1,26 @@
+-- Your SQL goes here
+CREATE TABLE if not exists ticket_price_tiers (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  ticket_id UUID NOT NULL REFERENCES public.tickets (id) ON DELETE CASCADE,
+  event_id UUID NOT NULL REFERENCES public.events (id) ON DELETE CASCADE,
+  tier_name VARCHAR NOT NULL,
+  price VARCHAR NOT NULL,
+  starts_at TIMESTAMP NOT NULL,
+  ends_at TIMESTAMP NULL,
+  quantity_cap INTEGER NULL,
+  quantity_sold INTEGER NOT NULL DEFAULT 0,
+  PRIMARY KEY (id),
+  CHECK (ends_at IS NULL OR ends_at > starts_at),
+  CHECK (quantity_cap IS NULL OR quantity_sold <= quantity_cap)
+);
+
+CREATE INDEX ticket_price_tiers_ticket_id_idx ON ticket_price_tiers (ticket_id, starts_at);
+
+CREATE TRIGGER ticket_price_tiers_notify_changed
+    AFTER INSERT OR UPDATE OR DELETE ON ticket_price_tiers
+    FOR EACH ROW EXECUTE PROCEDURE notify_event_changed();
+
+-- the tier the tickets were reserved at, their ticket's price if not set
+ALTER TABLE ticket_reservations
+    ADD COLUMN price_tier_id UUID NULL REFERENCES public.ticket_price_tiers (id) ON DELETE SET NULL;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE if exists event_reminders;
+
+ALTER TABLE users DROP COLUMN if exists event_reminders_opt_out;
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+ALTER TABLE users ADD COLUMN if not exists event_reminders_opt_out BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE if not exists event_reminders (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  event_id UUID NOT NULL REFERENCES public.events (id) ON DELETE CASCADE,
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  window_secs BIGINT NOT NULL,
+  PRIMARY KEY (id),
+  UNIQUE (event_id, user_id, window_secs)
+);
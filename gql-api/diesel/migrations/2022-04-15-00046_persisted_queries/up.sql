@@ -0,0 +1,8 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists persisted_queries (
+  sha256_hash VARCHAR(64),
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  query TEXT NOT NULL,
+  PRIMARY KEY (sha256_hash)
+)
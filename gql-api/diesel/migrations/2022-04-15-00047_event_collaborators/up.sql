@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists event_collaborators (
+  event_id UUID NOT NULL REFERENCES public.events (id) ON DELETE CASCADE,
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  collaborator_role SMALLINT NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (event_id, user_id)
+);
+
+CREATE INDEX if not exists event_collaborators_user_id_idx ON event_collaborators (user_id);
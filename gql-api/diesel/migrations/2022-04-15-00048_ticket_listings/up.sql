@@ -0,0 +1,17 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists ticket_listings (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  asking_price VARCHAR NOT NULL,
+  reservation_id UUID NOT NULL REFERENCES public.ticket_reservations (id) ON DELETE CASCADE,
+  ticket_id UUID NOT NULL REFERENCES public.tickets (id) ON DELETE CASCADE,
+  event_id UUID NOT NULL REFERENCES public.events (id) ON DELETE CASCADE,
+  seller_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  cancelled_at TIMESTAMP,
+  PRIMARY KEY (id)
+);
+
+-- a reservation is listed for sale once at a time
+CREATE UNIQUE INDEX if not exists ticket_listings_reservation_id_idx ON ticket_listings (reservation_id) WHERE cancelled_at IS NULL;
+CREATE INDEX if not exists ticket_listings_event_id_idx ON ticket_listings (event_id);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX if exists tickets_ticket_slug_idx;
+DROP INDEX if exists events_event_slug_idx;
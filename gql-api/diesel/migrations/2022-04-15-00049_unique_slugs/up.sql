@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- slugs told apart by name only get a numeric suffix before they become unique
+UPDATE events e SET event_slug = e.event_slug || '-' || d.n
+  FROM (SELECT id, ROW_NUMBER() OVER (PARTITION BY event_slug ORDER BY created_at, id) AS n FROM events) d
+  WHERE d.id = e.id AND d.n > 1;
+UPDATE tickets t SET ticket_slug = t.ticket_slug || '-' || d.n
+  FROM (SELECT id, ROW_NUMBER() OVER (PARTITION BY ticket_slug ORDER BY created_at, id) AS n FROM tickets) d
+  WHERE d.id = t.id AND d.n > 1;
+
+CREATE UNIQUE INDEX if not exists events_event_slug_idx ON events (event_slug);
+CREATE UNIQUE INDEX if not exists tickets_ticket_slug_idx ON tickets (ticket_slug);
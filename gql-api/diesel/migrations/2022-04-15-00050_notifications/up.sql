@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists notifications (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  kind SMALLINT NOT NULL,
+  message VARCHAR NOT NULL,
+  data TEXT,
+  read_at TIMESTAMP,
+  PRIMARY KEY (id)
+);
+
+CREATE INDEX if not exists notifications_user_id_idx ON notifications (user_id, created_at);
+CREATE INDEX if not exists notifications_unread_idx ON notifications (user_id) WHERE read_at IS NULL;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX if exists events_coordinates_idx;
+ALTER TABLE events DROP CONSTRAINT if exists events_coordinates_check;
+ALTER TABLE events
+  DROP COLUMN if exists longitude,
+  DROP COLUMN if exists latitude;
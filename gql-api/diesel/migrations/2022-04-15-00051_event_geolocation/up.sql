@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+ALTER TABLE events
+  ADD COLUMN if not exists latitude DOUBLE PRECISION,
+  ADD COLUMN if not exists longitude DOUBLE PRECISION;
+
+ALTER TABLE events ADD CONSTRAINT events_coordinates_check
+  CHECK ((latitude IS NULL) = (longitude IS NULL));
+
+CREATE INDEX if not exists events_coordinates_idx ON events (latitude, longitude)
+  WHERE latitude IS NOT NULL;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX if exists asset_files_gallery_idx;
+ALTER TABLE asset_files
+  DROP COLUMN if exists position,
+  DROP COLUMN if exists asset_role;
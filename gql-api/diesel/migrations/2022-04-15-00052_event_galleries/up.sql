@@ -0,0 +1,8 @@
+-- Your SQL goes here
+
+ALTER TABLE asset_files
+  ADD COLUMN if not exists asset_role SMALLINT,
+  ADD COLUMN if not exists position INTEGER;
+
+CREATE INDEX if not exists asset_files_gallery_idx ON asset_files (event_id, position)
+  WHERE asset_role = 2;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+ALTER TABLE tickets
+  ADD COLUMN if not exists version INTEGER NOT NULL DEFAULT 0;
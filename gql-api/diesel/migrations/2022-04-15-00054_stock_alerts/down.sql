@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE if exists ticket_stock_alerts;
+DROP TABLE if exists seller_webhooks;
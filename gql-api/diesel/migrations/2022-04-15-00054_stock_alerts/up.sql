@@ -0,0 +1,18 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists seller_webhooks (
+  seller_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  url TEXT NOT NULL,
+  secret TEXT NOT NULL,
+  PRIMARY KEY (seller_id)
+);
+
+CREATE TABLE if not exists ticket_stock_alerts (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  ticket_id UUID NOT NULL REFERENCES public.tickets (id) ON DELETE CASCADE,
+  threshold_percent SMALLINT NOT NULL,
+  PRIMARY KEY (id),
+  UNIQUE (ticket_id, threshold_percent)
+);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE if exists ticket_cancellations;
+ALTER TABLE events
+  DROP COLUMN if exists cancellation_window_hours;
@@ -0,0 +1,24 @@
+-- Your SQL goes here
+
+-- hours before the start until which buyers may cancel, no buyer cancellations when null
+ALTER TABLE events
+  ADD COLUMN if not exists cancellation_window_hours INTEGER;
+
+CREATE TABLE if not exists ticket_cancellations (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  reserved_at TIMESTAMP NOT NULL,
+  verification_code VARCHAR NOT NULL,
+  event_id UUID NOT NULL REFERENCES public.events (id) ON DELETE CASCADE,
+  ticket_id UUID NOT NULL REFERENCES public.tickets (id) ON DELETE CASCADE,
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  quantity INTEGER NOT NULL,
+  cancelled_by UUID REFERENCES public.users (id) ON DELETE SET NULL,
+  refund_amount TEXT NOT NULL,
+  refunded_at TIMESTAMP,
+  refund_tx_hash TEXT,
+  PRIMARY KEY (id)
+);
+
+CREATE INDEX if not exists ticket_cancellations_user_id_idx ON ticket_cancellations (user_id);
+CREATE INDEX if not exists ticket_cancellations_event_id_idx ON ticket_cancellations (event_id);
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+
+-- a single row, the maintenance mode every api instance follows
+CREATE TABLE if not exists maintenance (
+  id BOOLEAN NOT NULL DEFAULT TRUE CHECK (id),
+  enabled BOOLEAN NOT NULL DEFAULT FALSE,
+  message TEXT,
+  retry_after_secs INTEGER,
+  updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  updated_by UUID REFERENCES public.users (id) ON DELETE SET NULL,
+  PRIMARY KEY (id)
+);
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER asset_files_touch_event ON asset_files;
+DROP TRIGGER ticket_price_tiers_touch_event ON ticket_price_tiers;
+DROP TRIGGER event_tags_touch_event ON event_tags;
+DROP TRIGGER tickets_touch_event ON tickets;
+DROP TRIGGER events_set_modified_at ON events;
+DROP FUNCTION touch_event_modified_at();
+DROP FUNCTION set_event_modified_at();
+ALTER TABLE events DROP COLUMN modified_at;
@@ -0,0 +1,47 @@
+-- Your SQL goes here
+
+-- when the public event last changed, along with its tickets, tags, price tiers and gallery.
+-- Served as the Last-Modified of the public event routes
+ALTER TABLE events
+  ADD COLUMN if not exists modified_at TIMESTAMP NOT NULL DEFAULT NOW();
+
+CREATE OR REPLACE FUNCTION set_event_modified_at() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.modified_at := NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION touch_event_modified_at() RETURNS TRIGGER AS $$
+DECLARE
+    changed JSONB;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        changed := to_jsonb(OLD);
+    ELSE
+        changed := to_jsonb(NEW);
+    END IF;
+    UPDATE events SET modified_at = NOW() WHERE id = (changed->>'event_id')::UUID;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER events_set_modified_at
+    BEFORE UPDATE ON events
+    FOR EACH ROW EXECUTE PROCEDURE set_event_modified_at();
+
+CREATE TRIGGER tickets_touch_event
+    AFTER INSERT OR UPDATE OR DELETE ON tickets
+    FOR EACH ROW EXECUTE PROCEDURE touch_event_modified_at();
+
+CREATE TRIGGER event_tags_touch_event
+    AFTER INSERT OR UPDATE OR DELETE ON event_tags
+    FOR EACH ROW EXECUTE PROCEDURE touch_event_modified_at();
+
+CREATE TRIGGER ticket_price_tiers_touch_event
+    AFTER INSERT OR UPDATE OR DELETE ON ticket_price_tiers
+    FOR EACH ROW EXECUTE PROCEDURE touch_event_modified_at();
+
+CREATE TRIGGER asset_files_touch_event
+    AFTER INSERT OR UPDATE OR DELETE ON asset_files
+    FOR EACH ROW EXECUTE PROCEDURE touch_event_modified_at();
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists login_attempts (
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  ip_address VARCHAR NOT NULL,
+  failed_attempts INTEGER NOT NULL DEFAULT 0,
+  last_failed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  expires_at TIMESTAMP NOT NULL,
+  PRIMARY KEY (user_id, ip_address)
+)
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists outbox (
+  id UUID PRIMARY KEY,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  payload VARCHAR NOT NULL,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  available_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  dispatched_at TIMESTAMP,
+  last_error VARCHAR
+);
+
+CREATE INDEX if not exists outbox_undispatched_idx ON outbox (available_at) WHERE dispatched_at IS NULL;
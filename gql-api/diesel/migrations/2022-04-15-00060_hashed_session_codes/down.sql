@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE buyer_signup_sessions RENAME COLUMN verification_code_hash TO verification_code;
+ALTER TABLE buyer_recovery_sessions RENAME COLUMN recovery_code_hash TO recovery_code;
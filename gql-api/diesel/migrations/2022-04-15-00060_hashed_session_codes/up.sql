@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+ALTER TABLE buyer_signup_sessions RENAME COLUMN verification_code TO verification_code_hash;
+ALTER TABLE buyer_recovery_sessions RENAME COLUMN recovery_code TO recovery_code_hash;
@@ -0,0 +1,28 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists signup_attempts (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  session_id UUID NOT NULL,
+  user_id UUID NOT NULL,
+  name VARCHAR,
+  username VARCHAR NOT NULL,
+  phone_number VARCHAR NOT NULL,
+  email VARCHAR,
+  password VARCHAR,
+  encrypted_secret_key VARCHAR NOT NULL,
+  locale VARCHAR,
+  wallet_id VARCHAR NOT NULL,
+  public_key VARCHAR NOT NULL,
+  wallet_created_at TIMESTAMP,
+  tx_hash VARCHAR,
+  signup_status SMALLINT NOT NULL,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  next_attempt_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  error VARCHAR,
+  PRIMARY KEY (id),
+  UNIQUE (user_id)
+);
+
+CREATE INDEX if not exists signup_attempts_signup_status_idx ON signup_attempts (signup_status, next_attempt_at);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE events
+  DROP COLUMN if exists series_id;
+DROP TABLE if exists event_series;
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+
+CREATE TABLE if not exists event_series (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  series_name VARCHAR NOT NULL,
+  rrule VARCHAR NOT NULL,
+  starts_at TIMESTAMP NOT NULL,
+  created_by_user UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  PRIMARY KEY (id)
+);
+
+-- the series an event is an occurrence of, events outlive their series
+ALTER TABLE events
+  ADD COLUMN if not exists series_id UUID REFERENCES public.event_series (id) ON DELETE SET NULL;
+
+CREATE INDEX if not exists events_series_id_idx ON events (series_id, start_date);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE if exists seats;
+DROP TABLE if exists seat_sections;
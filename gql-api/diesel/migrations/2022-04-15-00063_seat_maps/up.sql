@@ -0,0 +1,32 @@
+-- Your SQL goes here
+
+-- the sections of an event's seat map, their seats sold as tickets of a single ticket type
+CREATE TABLE if not exists seat_sections (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  event_id UUID NOT NULL REFERENCES public.events (id) ON DELETE CASCADE,
+  ticket_id UUID NOT NULL REFERENCES public.tickets (id) ON DELETE CASCADE,
+  section_name VARCHAR NOT NULL,
+  position INTEGER NOT NULL,
+  PRIMARY KEY (id),
+  UNIQUE (event_id, section_name)
+);
+
+-- a seat is held by a buyer until `held_until`, then reserved along with their reservation
+CREATE TABLE if not exists seats (
+  id UUID,
+  section_id UUID NOT NULL REFERENCES public.seat_sections (id) ON DELETE CASCADE,
+  event_id UUID NOT NULL REFERENCES public.events (id) ON DELETE CASCADE,
+  ticket_id UUID NOT NULL REFERENCES public.tickets (id) ON DELETE CASCADE,
+  row_label VARCHAR NOT NULL,
+  seat_number INTEGER NOT NULL,
+  held_by UUID REFERENCES public.users (id) ON DELETE SET NULL,
+  held_until TIMESTAMP,
+  reservation_id UUID REFERENCES public.ticket_reservations (id) ON DELETE SET NULL,
+  PRIMARY KEY (id),
+  UNIQUE (section_id, row_label, seat_number)
+);
+
+CREATE INDEX if not exists seats_event_id_idx ON seats (event_id);
+CREATE INDEX if not exists seats_ticket_id_idx ON seats (ticket_id);
+CREATE INDEX if not exists seats_reservation_id_idx ON seats (reservation_id);
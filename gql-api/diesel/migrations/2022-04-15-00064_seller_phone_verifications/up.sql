@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+-- the codes sent to sellers verifying their phone, their account is limited until verified
+CREATE TABLE if not exists seller_phone_verifications (
+  id UUID,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  user_id UUID NOT NULL REFERENCES public.users (id) ON DELETE CASCADE,
+  phone_number VARCHAR NOT NULL,
+  verification_code_hash VARCHAR NOT NULL,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  expires_at TIMESTAMP NOT NULL,
+  verified_at TIMESTAMP,
+  PRIMARY KEY (id)
+);
+
+CREATE INDEX if not exists seller_phone_verifications_user_id_idx ON seller_phone_verifications (user_id, created_at);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX if exists events_pending_review_idx;
+ALTER TABLE events DROP COLUMN if exists review_rejection_reason;
+ALTER TABLE events DROP COLUMN if exists review_submitted_at;
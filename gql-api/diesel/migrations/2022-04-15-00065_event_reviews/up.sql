@@ -0,0 +1,7 @@
+-- Your SQL goes here
+
+-- published events wait in PENDING_REVIEW (4) until an admin approves them
+ALTER TABLE events ADD COLUMN if not exists review_submitted_at TIMESTAMP;
+ALTER TABLE events ADD COLUMN if not exists review_rejection_reason VARCHAR;
+
+CREATE INDEX if not exists events_pending_review_idx ON events (review_submitted_at) WHERE event_status = 4;
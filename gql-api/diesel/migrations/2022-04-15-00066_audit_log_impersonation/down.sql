@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE audit_log DROP COLUMN if exists impersonated_by;
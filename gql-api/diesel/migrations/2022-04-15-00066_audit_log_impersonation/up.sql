@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- the super admin that made the change while impersonating the actor
+ALTER TABLE audit_log ADD COLUMN if not exists impersonated_by UUID;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- the jwts issued before the last password change are rejected
+ALTER TABLE users ADD COLUMN if not exists password_changed_at TIMESTAMP;
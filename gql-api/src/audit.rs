@@ -0,0 +1,91 @@
+use crate::db::{models::DbAuditLog, sql::db_insert_audit_log};
+use std::fmt;
+use tokio_postgres::Client;
+use uuid::Uuid;
+
+/// The entity a state change was applied to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AuditEntity {
+    User(Uuid),
+    Event(Uuid),
+    Ticket(Uuid),
+    TicketReservation(Uuid),
+    PromoCode(Uuid),
+    Asset(Uuid),
+    Session(Uuid),
+    PayoutAccount(Uuid),
+    PayoutRequest(Uuid),
+    Category(Uuid),
+    Organization(Uuid),
+}
+
+impl AuditEntity {
+    pub fn id(&self) -> Uuid {
+        match *self {
+            AuditEntity::User(id)
+            | AuditEntity::Event(id)
+            | AuditEntity::Ticket(id)
+            | AuditEntity::TicketReservation(id)
+            | AuditEntity::PromoCode(id)
+            | AuditEntity::Asset(id)
+            | AuditEntity::Session(id)
+            | AuditEntity::PayoutAccount(id)
+            | AuditEntity::PayoutRequest(id)
+            | AuditEntity::Category(id)
+            | AuditEntity::Organization(id) => id,
+        }
+    }
+}
+
+impl fmt::Display for AuditEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditEntity::User(_) => write!(f, "user"),
+            AuditEntity::Event(_) => write!(f, "event"),
+            AuditEntity::Ticket(_) => write!(f, "ticket"),
+            AuditEntity::TicketReservation(_) => write!(f, "ticket_reservation"),
+            AuditEntity::PromoCode(_) => write!(f, "promo_code"),
+            AuditEntity::Asset(_) => write!(f, "asset"),
+            AuditEntity::Session(_) => write!(f, "session"),
+            AuditEntity::PayoutAccount(_) => write!(f, "payout_account"),
+            AuditEntity::PayoutRequest(_) => write!(f, "payout_request"),
+            AuditEntity::Category(_) => write!(f, "category"),
+            AuditEntity::Organization(_) => write!(f, "organization"),
+        }
+    }
+}
+
+/// Records a state change in the audit log. `diff` is the submitted change or the resulting state.
+///
+/// Failures are logged and swallowed, an audit write must never fail the operation it describes.
+pub async fn record(
+    db_client: &Client,
+    actor: Option<Uuid>,
+    action: &str,
+    entity: AuditEntity,
+    diff: Option<serde_json::Value>,
+) {
+    record_impersonated(db_client, actor, None, action, entity, diff).await
+}
+
+/// Like [`record`], for a change the `impersonated_by` super admin made as the actor
+pub async fn record_impersonated(
+    db_client: &Client,
+    actor: Option<Uuid>,
+    impersonated_by: Option<Uuid>,
+    action: &str,
+    entity: AuditEntity,
+    diff: Option<serde_json::Value>,
+) {
+    let diff = diff.map(|diff| diff.to_string());
+    let db_audit_log = DbAuditLog::new(actor, impersonated_by, action, &entity, diff);
+    if let Err(e) = db_insert_audit_log(db_client, &db_audit_log).await {
+        log::error!(
+            "Failed to record audit entry {} on {} {}: {}",
+            action,
+            entity,
+            entity.id(),
+            e
+        );
+    }
+}
@@ -16,6 +16,12 @@ struct Claims {
     sub: String,
     role: String,
     exp: usize,
+    /// when the jwt was issued, 0 on the jwts issued before it was added
+    #[serde(default)]
+    iat: usize,
+    /// the impersonation the jwt was minted for, absent on the users' own jwts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    imp: Option<String>,
 }
 
 /// A user role
@@ -124,6 +130,49 @@ impl fmt::Display for UserStatus {
     }
 }
 
+/// Where a seller is in their onboarding, only approved sellers may publish and mint
+#[repr(i16)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SellerStatus {
+    /// signed up, their documents not submitted yet
+    Onboarding = 0,
+    PendingReview = 1,
+    Approved = 2,
+    /// may submit their documents again
+    Rejected = 3,
+}
+
+impl From<SellerStatus> for i16 {
+    fn from(seller_status: SellerStatus) -> i16 {
+        seller_status as i16
+    }
+}
+
+impl TryFrom<i16> for SellerStatus {
+    type Error = Error;
+
+    fn try_from(n: i16) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(SellerStatus::Onboarding),
+            1 => Ok(SellerStatus::PendingReview),
+            2 => Ok(SellerStatus::Approved),
+            3 => Ok(SellerStatus::Rejected),
+            _ => Err(Error::User(UserError::UnknownSellerStatus(n.to_string()))),
+        }
+    }
+}
+
+impl fmt::Display for SellerStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SellerStatus::Onboarding => write!(f, "onboarding"),
+            SellerStatus::PendingReview => write!(f, "pending_review"),
+            SellerStatus::Approved => write!(f, "approved"),
+            SellerStatus::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
 pub fn create_jwt(uid: &str, role: &Role) -> Result<String, AuthError> {
     let expiration = Utc::now()
         .checked_add_signed(chrono::Duration::minutes(60))
@@ -134,9 +183,32 @@ pub fn create_jwt(uid: &str, role: &Role) -> Result<String, AuthError> {
         sub: uid.to_owned(),
         role: role.to_string(),
         exp: expiration as usize,
+        iat: Utc::now().timestamp() as usize,
+        imp: None,
     };
+    encode_claims(&claims)
+}
+
+/// Creates the jwt of an impersonation, flagged with its id and expiring with it
+pub fn create_impersonation_jwt(
+    uid: &str,
+    role: &Role,
+    impersonation_id: &Uuid,
+    expires_at: i64,
+) -> Result<String, AuthError> {
+    let claims = Claims {
+        sub: uid.to_owned(),
+        role: role.to_string(),
+        exp: expires_at as usize,
+        iat: Utc::now().timestamp() as usize,
+        imp: Some(impersonation_id.to_string()),
+    };
+    encode_claims(&claims)
+}
+
+fn encode_claims(claims: &Claims) -> Result<String, AuthError> {
     let header = Header::new(Algorithm::HS512);
-    encode(&header, &claims, &EncodingKey::from_secret(JWT_SECRET))
+    encode(&header, claims, &EncodingKey::from_secret(JWT_SECRET))
         .map_err(|_| AuthError::JWTTokenCreationError)
 }
 
@@ -159,29 +231,67 @@ pub async fn authorize(
     (roles, headers): (Vec<Role>, HeaderMap<HeaderValue>),
 ) -> Result<Uuid, Rejection> {
     match jwt_from_header(&headers) {
-        Ok(jwt) => {
-            let decoded = decode::<Claims>(
-                &jwt,
-                &DecodingKey::from_secret(JWT_SECRET),
-                &Validation::new(Algorithm::HS512),
-            )
-            .map_err(|_| reject::custom(Error::Auth(AuthError::JWTTokenError)))?;
-
-            let token_role = Role::try_from(decoded.claims.role.as_str()).map_err(|_| {
-                reject::custom(Error::Auth(AuthError::BadEncodedUserRole(
-                    decoded.claims.role,
-                )))
-            })?;
-            let token_role = roles.iter().find(|&role| role.eq(&token_role));
-            if token_role.is_none() {
-                return Err(reject::custom(Error::Auth(AuthError::NoPermissionError)));
-            }
-
-            let user_id = Uuid::parse_str(&decoded.claims.sub).map_err(|_| {
-                reject::custom(Error::UnparsableUuid(decoded.claims.sub.to_string()))
-            })?;
-            Ok(user_id)
-        }
+        Ok(jwt) => authorize_own_jwt(&jwt, &roles).map_err(reject::custom),
+        Err(e) => return Err(reject::custom(Error::Auth(e))),
+    }
+}
+
+/// Authorizes the user's own jwt or an impersonation jwt, returning the user id, the
+/// impersonation id and when the jwt was issued. The caller checks the impersonation was not
+/// revoked
+pub async fn authorize_impersonable(
+    (roles, headers): (Vec<Role>, HeaderMap<HeaderValue>),
+) -> Result<(Uuid, Option<Uuid>, i64), Rejection> {
+    match jwt_from_header(&headers) {
+        Ok(jwt) => authorize_jwt(&jwt, &roles).map_err(reject::custom),
         Err(e) => return Err(reject::custom(Error::Auth(e))),
     }
 }
+
+/// Authorizes a `Bearer <jwt>` value sent outside of the http headers, e.g. on websocket init
+pub fn authorize_bearer(bearer: &str, roles: &[Role]) -> Result<Uuid, Error> {
+    if !bearer.starts_with(BEARER) {
+        return Err(Error::Auth(AuthError::InvalidAuthHeaderError));
+    }
+    authorize_own_jwt(bearer.trim_start_matches(BEARER), roles)
+}
+
+/// Whether the request carries an admin's own bearer jwt
+pub fn is_admin(headers: &HeaderMap<HeaderValue>) -> bool {
+    jwt_from_header(headers)
+        .map(|jwt| authorize_own_jwt(&jwt, &[Role::Admin, Role::SuperAdmin]).is_ok())
+        .unwrap_or(false)
+}
+
+/// Impersonation jwts are only accepted where their revocation is checked
+fn authorize_own_jwt(jwt: &str, roles: &[Role]) -> Result<Uuid, Error> {
+    match authorize_jwt(jwt, roles)? {
+        (user_id, None, _) => Ok(user_id),
+        (_, Some(_), _) => Err(Error::Auth(AuthError::ImpersonationNotAllowed)),
+    }
+}
+
+fn authorize_jwt(jwt: &str, roles: &[Role]) -> Result<(Uuid, Option<Uuid>, i64), Error> {
+    let decoded = decode::<Claims>(
+        jwt,
+        &DecodingKey::from_secret(JWT_SECRET),
+        &Validation::new(Algorithm::HS512),
+    )
+    .map_err(|_| Error::Auth(AuthError::JWTTokenError))?;
+
+    let token_role = Role::try_from(decoded.claims.role.as_str())
+        .map_err(|_| Error::Auth(AuthError::BadEncodedUserRole(decoded.claims.role)))?;
+    let token_role = roles.iter().find(|&role| role.eq(&token_role));
+    if token_role.is_none() {
+        return Err(Error::Auth(AuthError::NoPermissionError));
+    }
+
+    let user_id = Uuid::parse_str(&decoded.claims.sub)
+        .map_err(|_| Error::UnparsableUuid(decoded.claims.sub.to_string()))?;
+    let impersonation_id = decoded
+        .claims
+        .imp
+        .map(|imp| Uuid::parse_str(&imp).map_err(|_| Error::UnparsableUuid(imp)))
+        .transpose()?;
+    Ok((user_id, impersonation_id, decoded.claims.iat as i64))
+}
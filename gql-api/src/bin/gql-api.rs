@@ -1,28 +1,62 @@
 use anyhow::{Context, Result};
 use argh::{self, FromArgs};
-use gql_api::config::{db_client_from_config, Config, ServerEnv};
+use gql_api::cache::{drive_connection, listen, EventCache};
+use gql_api::chain::{ChainClient, MockChainClient, NearChainClient};
+use gql_api::config::{
+    db_client_from_config, ChainKind, Config, GeocoderKind, NotifierKind, ServerEnv,
+};
+use gql_api::db::sql::sql_timestamp;
 use gql_api::error::{handle_rejection, Error};
-use gql_api::filters::with_cors;
+use gql_api::filters::{with_cors, with_maintenance, with_metrics, with_security_headers};
+use gql_api::geo::{Geocoder, NoGeocoder, NominatimGeocoder};
 use gql_api::gql::{
-    mutations::{PrivateMutationRoot, PublicMutationRoot},
-    quiries::{PrivateQueryRoot, PublicQueryRoot},
-    routes::{graphql_private_route, graphql_public_route, public_graphiql_route},
-    schema::{Context as ResourcesContext, PrivateSchema, PublicSchema},
+    mutations::{
+        AdminMutationRoot, BuyerMutationRoot, PrivateMutationRoot, PublicMutationRoot,
+        SellerMutationRoot,
+    },
+    quiries::{AdminQueryRoot, BuyerQueryRoot, PrivateQueryRoot, PublicQueryRoot, SellerQueryRoot},
+    routes::{
+        graphql_admin_route, graphql_admin_schema_route, graphql_buyer_route,
+        graphql_buyer_schema_route, graphql_private_route, graphql_private_schema_route,
+        graphql_private_subscriptions_route, graphql_public_route, graphql_public_schema_route,
+        graphql_public_subscriptions_route, graphql_seller_route, graphql_seller_schema_route,
+        public_graphiql_route,
+    },
+    schema::{
+        AdminSchema, BuyerSchema, Context as ResourcesContext, PrivateSchema, PublicSchema,
+        Resources, SellerSchema,
+    },
     subscriptions::{PrivateSubscriptionRoot, PublicSubscriptionRoot},
 };
+use gql_api::grpc::NearClient;
 use gql_api::http::routes::{
-    buyer_create_recovery_code_route, buyer_register_phone_route, buyer_signup_route,
-    buyer_verify_phone_route, buyer_verify_recovery_code_route, check_username_route,
-    create_login_code_route, event_ticket_get_verification_code_route,
-    get_event_from_verification_code_route, healthcheck_route, homepage_route, signin_route,
-    signin_with_password_route, verify_login_code_route,
+    buyer_create_recovery_code_route, buyer_register_phone_route, buyer_resend_phone_code_route,
+    buyer_signup_route, buyer_verify_phone_route, buyer_verify_recovery_code_route,
+    check_username_route, create_login_code_route, event_attendees_csv_route,
+    event_ticket_get_verification_code_route, event_view_route, export_my_data_route,
+    get_event_from_verification_code_route, health_live_route, health_ready_route,
+    healthcheck_route, homepage_route, import_events_route, metrics_route, openapi_route,
+    public_event_route, pusher_auth_route, signin_challenge_route, signin_route,
+    signin_with_password_route, signin_with_password_verify_totp_route, swagger_ui_route,
+    ticket_pdf_route, upload_event_assets_route, verify_login_code_route,
 };
+use gql_api::i18n::SmsLocales;
+use gql_api::ipfs::IpfsClient;
+use gql_api::maintenance::Maintenance;
+use gql_api::notifier::{LogNotifier, Notifier, TwilioNotifier};
+use gql_api::security::{codes::Codes, pusher_auth::PusherAuth};
+use gql_api::seed::SeedConfig;
+use gql_api::shutdown::{drain_server, join_workers};
+use gql_api::storage::S3Storage;
+use gql_api::webhooks::HttpWebhookSender;
+use juniper::EmptySubscription;
 use pusher_client::client::PusherClient;
 use s3_uploader::DEFAULT_REGION;
 use s3_uploader::{s3::S3Client, AwsContext};
-use std::{env, net::SocketAddr, sync::Arc};
+use std::{env, net::SocketAddr, sync::Arc, time::Duration};
 use tokio::signal::unix::{signal, SignalKind};
 use tokio::sync::{broadcast, Mutex};
+use tokio_postgres::Client;
 use twilio_client::client::TwilioClient;
 use warp::Filter;
 
@@ -36,6 +70,23 @@ async fn main() -> Result<()> {
         .await
         .context("Failed to load config")?;
 
+    // check the deployment and exit, without serving anything
+    if args.check {
+        let report = gql_api::check::self_check(&config).await;
+        println!("{}", report);
+        if !report.is_ok() {
+            anyhow::bail!("Self-check failed");
+        }
+        return Ok(());
+    }
+
+    // run the one-off command and exit
+    match args.command {
+        Some(Command::Seed(seed_args)) => return seed(&config, seed_args).await,
+        Some(Command::BackfillPhoneNumbers(_)) => return backfill_phone_numbers(&config).await,
+        None => {}
+    }
+
     // init logging
     pretty_env_logger::init();
     env::set_var("RUST_LOG", "info,gql,gqli,http");
@@ -47,17 +98,24 @@ async fn main() -> Result<()> {
 
     // stop signals
     let (stop_tx, mut stop_rx) = broadcast::channel(1);
+    let drain_rx = stop_tx.subscribe();
     tokio::spawn(stop_signal(stop_tx.clone()));
 
     gql_api::migrations::run(&config.postgres);
 
+    // slow queries are logged, and explained in dev when configured to
+    gql_api::db::slow_queries::configure(config.slow_queries.for_env(server_env));
+
     let (db_client, connection) = db_client_from_config(&config.postgres)
         .await
         .expect("unable to establish a db connection");
 
+    // the connection also delivers the event change notifications to the cache
+    let event_cache = Arc::new(EventCache::new(&config.cache));
     let db_stop_tx = stop_tx.clone();
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
+    let db_event_cache = event_cache.clone();
+    let db_connection = tokio::spawn(async move {
+        if let Err(e) = drive_connection(connection, &db_event_cache).await {
             log::error!("DB Connection Error: {}", e);
             db_stop_tx
                 .send(())
@@ -65,6 +123,12 @@ async fn main() -> Result<()> {
         }
     });
 
+    if event_cache.is_enabled() {
+        listen(&db_client)
+            .await
+            .expect("unable to listen to the event changes");
+    }
+
     // server url
     let server_addr = format!("{}:{}", config.api.bind_host, config.api.bind_port)
         .parse::<SocketAddr>()
@@ -81,12 +145,36 @@ async fn main() -> Result<()> {
         PrivateMutationRoot,
         PrivateSubscriptionRoot,
     ));
+    // every role has its own schema, without the fields of the other roles
+    let seller_gql_schema = Arc::new(SellerSchema::new(
+        SellerQueryRoot,
+        SellerMutationRoot,
+        EmptySubscription::new(),
+    ));
+    let buyer_gql_schema = Arc::new(BuyerSchema::new(
+        BuyerQueryRoot,
+        BuyerMutationRoot,
+        EmptySubscription::new(),
+    ));
+    let admin_gql_schema = Arc::new(AdminSchema::new(
+        AdminQueryRoot,
+        AdminMutationRoot,
+        EmptySubscription::new(),
+    ));
 
     // create grpc client for near
     let grpc_near_client = gql_api::grpc::new(&config.near_api)
         .await
         .map_err(Error::Grpc)?;
 
+    // create the chain of the wallets (the mock chain keeps local dev off the near api wallets)
+    let grpc_near_client: Arc<Mutex<Box<dyn NearClient>>> =
+        Arc::new(Mutex::new(Box::new(grpc_near_client)));
+    let chain: Arc<dyn ChainClient> = match config.chain.kind {
+        ChainKind::Near => Arc::new(NearChainClient::new(grpc_near_client.clone())),
+        ChainKind::Mock => Arc::new(MockChainClient::default()),
+    };
+
     // create pusher client (NOTE: this trick is required as the latest builder in the lib does not support clusters!)
     let pusher_client = PusherClient::new(&config.pusher).map_err(Error::Pusher)?;
 
@@ -98,31 +186,175 @@ async fn main() -> Result<()> {
     )
     .await;
     let aws_s3_client = S3Client::new_from_context(&aws_client_ctx);
+    let storage =
+        S3Storage::new(aws_s3_client, aws_client_ctx).with_access(config.asset_urls.access);
+
+    // create the notifier (the log notifier keeps local dev off the external apis)
+    let notifier: Arc<dyn Notifier> = match config.notifier.kind {
+        NotifierKind::Twilio => {
+            let twilio = config
+                .twilio
+                .clone()
+                .context("The twilio notifier requires the [twilio] config")?;
+            let twilio_client = TwilioClient::new(twilio.api, twilio.sms).map_err(Error::Twilio)?;
+            Arc::new(TwilioNotifier::new(
+                twilio_client,
+                SmsLocales::new(&config.business),
+            ))
+        }
+        NotifierKind::Log => Arc::new(LogNotifier::new(SmsLocales::new(&config.business))),
+    };
+
+    // create the geocoder (events get no coordinates without one)
+    let geocoder: Arc<dyn Geocoder> = match config.geocoder.kind {
+        GeocoderKind::Nominatim => Arc::new(
+            NominatimGeocoder::new(&config.geocoder).context("Failed to create the geocoder")?,
+        ),
+        GeocoderKind::None => Arc::new(NoGeocoder),
+    };
 
-    // create twilio config
-    let twilio_client = TwilioClient::new(config.twilio.api.clone(), config.twilio.sms.clone())
-        .map_err(Error::Twilio)?;
+    // create the webhook sender of the sellers' deliveries
+    let webhooks =
+        HttpWebhookSender::new(&config.webhooks).context("Failed to create the webhook sender")?;
+
+    // create ipfs client (pinning is skipped when not configured)
+    let ipfs_client = config.ipfs.as_ref().map(IpfsClient::new);
+
+    // introspection, graphiql and any origin are always allowed in dev
+    let graphql_config = config.graphql.for_env(server_env);
+    let cors_config = config.cors.for_env(server_env);
 
     // Create context
-    let resources_ctx = Arc::new(ResourcesContext {
+    let resources_ctx = Arc::new(ResourcesContext::new(Resources {
         db_client,
-        grpc_near_client: Mutex::new(grpc_near_client),
-        user_id: Mutex::new(None),
-        pusher_client,
-        twilio_client,
-        aws_s3_client,
-        aws_context: aws_client_ctx,
-    });
+        grpc_near_client,
+        chain,
+        publisher: Arc::new(pusher_client),
+        notifier,
+        storage: Arc::new(storage),
+        asset_urls: config.asset_urls.clone(),
+        geocoder,
+        webhooks: Arc::new(webhooks),
+        ipfs_client,
+        health: config.health.clone(),
+        jobs: config.jobs.clone(),
+        sessions: config.sessions.clone(),
+        graphql: graphql_config.clone(),
+        near: config.near.clone(),
+        business: config.business.clone(),
+        totp: config.totp.clone(),
+        ticket_pdf: config.ticket_pdf.clone(),
+        body_limits: config.body_limits.clone(),
+        http_cache: config.http_cache.clone(),
+        usernames: config.usernames.clone(),
+        sanitation: config.sanitation.clone(),
+        password_policy: config.password_policy.clone(),
+        login_lockout: config.login_lockout.clone(),
+        pusher_auth: PusherAuth::new(&config.pusher.key, &config.pusher.secret),
+        codes: Codes::new(&config.codes.secret, &config.codes.previous_secrets),
+        top_ups: config.top_ups.clone(),
+        signups: config.signups.clone(),
+        stock_alerts: config.stock_alerts.clone(),
+        series: config.series.clone(),
+        seats: config.seats.clone(),
+        event_cache,
+        maintenance: Arc::new(Maintenance::new(&config.maintenance)),
+    }));
+
+    // background workers, each stops at the stop signal once done with its current work
+    let workers = vec![
+        // background job worker (notifications, pusher and s3 side effects)
+        tokio::spawn(gql_api::jobs::worker::run(
+            resources_ctx.clone(),
+            config.jobs.clone(),
+            stop_tx.subscribe(),
+        )),
+        // deliver the pusher events and webhooks written to the outbox along with their changes
+        tokio::spawn(gql_api::jobs::outbox::run(
+            resources_ctx.clone(),
+            config.outbox.clone(),
+            stop_tx.subscribe(),
+        )),
+        // keep the stored wallet balances in sync with the chain
+        tokio::spawn(gql_api::jobs::balances::run(
+            resources_ctx.clone(),
+            config.balances.clone(),
+            stop_tx.subscribe(),
+        )),
+        // keep the NEAR exchange rates used for display prices fresh
+        tokio::spawn(gql_api::jobs::fx::run(
+            config.fx.clone(),
+            stop_tx.subscribe(),
+        )),
+        // purge expired login, signup and recovery sessions
+        tokio::spawn(gql_api::jobs::cleanup::run(
+            resources_ctx.clone(),
+            config.sessions.clone(),
+            stop_tx.subscribe(),
+        )),
+        // notify waitlisted buyers when their sold out tickets free up
+        tokio::spawn(gql_api::jobs::waitlist::run(
+            resources_ctx.clone(),
+            config.waitlist.clone(),
+            stop_tx.subscribe(),
+        )),
+        // remind ticket holders of the events starting soon
+        tokio::spawn(gql_api::jobs::reminders::run(
+            resources_ctx.clone(),
+            config.reminders.clone(),
+            stop_tx.subscribe(),
+        )),
+        // follow the submitted mint transactions until they are final
+        tokio::spawn(gql_api::jobs::mints::run(
+            resources_ctx.clone(),
+            config.mints.clone(),
+            stop_tx.subscribe(),
+        )),
+        // retry the buyer signups that failed halfway
+        tokio::spawn(gql_api::jobs::signups::run(
+            resources_ctx.clone(),
+            config.signups.clone(),
+            stop_tx.subscribe(),
+        )),
+        // keep enough funds in the buyer wallets to pay for their gas
+        tokio::spawn(gql_api::jobs::top_ups::run(
+            resources_ctx.clone(),
+            config.top_ups.clone(),
+            stop_tx.subscribe(),
+        )),
+        // aggregate the event views and reservations shown to sellers
+        tokio::spawn(gql_api::jobs::analytics::run(
+            resources_ctx.clone(),
+            config.analytics.clone(),
+            stop_tx.subscribe(),
+        )),
+        // follow the maintenance mode the admins set
+        tokio::spawn(gql_api::jobs::maintenance::run(
+            resources_ctx.clone(),
+            config.maintenance.clone(),
+            stop_tx.subscribe(),
+        )),
+    ];
 
     // unprotected routes
     let check_username_route = check_username_route(resources_ctx.clone(), http_logger);
+    let public_event_route = public_event_route(resources_ctx.clone(), http_logger);
+    let event_view_route = event_view_route(resources_ctx.clone(), http_logger);
     let healthcheck_route = healthcheck_route(resources_ctx.clone(), http_logger);
+    let health_live_route = health_live_route(http_logger);
+    let health_ready_route = health_ready_route(resources_ctx.clone(), http_logger);
+    let metrics_route = metrics_route(http_logger);
+    let openapi_route = openapi_route(config.http_cache.policy("openapi"), http_logger);
+    // swagger UI of the REST routes, in dev only
+    let swagger_ui_route = swagger_ui_route(matches!(server_env, ServerEnv::Dev), http_logger);
     let _homepage_route = homepage_route(http_logger);
 
     // buyer http routes
     let buyer_signup_route = buyer_signup_route(resources_ctx.clone(), http_logger);
     let buyer_register_phone_route = buyer_register_phone_route(resources_ctx.clone(), http_logger);
     let buyer_verify_phone_route = buyer_verify_phone_route(resources_ctx.clone(), http_logger);
+    let buyer_resend_phone_code_route =
+        buyer_resend_phone_code_route(resources_ctx.clone(), http_logger);
     let buyer_create_recovery_code_route =
         buyer_create_recovery_code_route(resources_ctx.clone(), http_logger);
     let buyer_verify_recovery_code_route =
@@ -130,13 +362,22 @@ async fn main() -> Result<()> {
 
     // seller http routes
     let signin_route = signin_route(resources_ctx.clone(), http_logger);
+    let signin_challenge_route = signin_challenge_route(resources_ctx.clone(), http_logger);
     let signin_with_password_route = signin_with_password_route(resources_ctx.clone(), http_logger);
+    let signin_with_password_verify_totp_route =
+        signin_with_password_verify_totp_route(resources_ctx.clone(), http_logger);
     let create_login_code_route = create_login_code_route(resources_ctx.clone(), http_logger);
     let verify_login_code_route = verify_login_code_route(resources_ctx.clone(), http_logger);
+    let pusher_auth_route = pusher_auth_route(resources_ctx.clone(), http_logger);
     let event_ticket_get_verification_code =
         event_ticket_get_verification_code_route(resources_ctx.clone(), http_logger);
     let get_event_from_verification_code =
         get_event_from_verification_code_route(resources_ctx.clone(), http_logger);
+    let export_my_data_route = export_my_data_route(resources_ctx.clone(), http_logger);
+    let import_events_route = import_events_route(resources_ctx.clone(), http_logger);
+    let upload_event_assets_route = upload_event_assets_route(resources_ctx.clone(), http_logger);
+    let event_attendees_csv_route = event_attendees_csv_route(resources_ctx.clone(), http_logger);
+    let ticket_pdf_route = ticket_pdf_route(resources_ctx.clone(), http_logger);
 
     // create gql routes (protected and unprotected)
     let graphql_private_route = graphql_private_route(
@@ -144,35 +385,121 @@ async fn main() -> Result<()> {
         private_gql_schema.clone(),
         graphql_logger,
     );
+    let graphql_seller_route = graphql_seller_route(
+        resources_ctx.clone(),
+        seller_gql_schema.clone(),
+        graphql_logger,
+    );
+    let graphql_buyer_route = graphql_buyer_route(
+        resources_ctx.clone(),
+        buyer_gql_schema.clone(),
+        graphql_logger,
+    );
+    let graphql_admin_route = graphql_admin_route(
+        resources_ctx.clone(),
+        admin_gql_schema.clone(),
+        graphql_logger,
+    );
     let graphql_public_route = graphql_public_route(
         resources_ctx.clone(),
         public_gql_schema.clone(),
         graphql_logger,
     );
 
-    // public graphiql route (only for DEV purposes, in fact not required as we have a POSTMAN collection!)
-    let _public_graphiql_route = public_graphiql_route(server_addr.clone(), graphiql_logger);
+    let graphql_public_subscriptions_route = graphql_public_subscriptions_route(
+        resources_ctx.clone(),
+        public_gql_schema.clone(),
+        graphql_logger,
+    );
+    let graphql_private_subscriptions_route = graphql_private_subscriptions_route(
+        resources_ctx.clone(),
+        private_gql_schema.clone(),
+        graphql_logger,
+    );
+    let graphql_public_schema_route = graphql_public_schema_route(
+        public_gql_schema.clone(),
+        graphql_config.introspection,
+        graphql_logger,
+    );
+    let graphql_private_schema_route = graphql_private_schema_route(
+        private_gql_schema.clone(),
+        graphql_config.introspection,
+        graphql_logger,
+    );
+    let graphql_seller_schema_route = graphql_seller_schema_route(
+        seller_gql_schema.clone(),
+        graphql_config.introspection,
+        graphql_logger,
+    );
+    let graphql_buyer_schema_route = graphql_buyer_schema_route(
+        buyer_gql_schema.clone(),
+        graphql_config.introspection,
+        graphql_logger,
+    );
+    let graphql_admin_schema_route = graphql_admin_schema_route(
+        admin_gql_schema.clone(),
+        graphql_config.introspection,
+        graphql_logger,
+    );
 
-    // bundle routes
-    let routes = check_username_route
-        .or(healthcheck_route)
+    // public graphiql route (disabled in release unless configured)
+    let public_graphiql_route = public_graphiql_route(
+        server_addr.clone(),
+        graphql_config.graphiql,
+        graphiql_logger,
+    );
+
+    // bundle routes, all but the health and metrics ones are down in maintenance mode
+    let maintained_routes = check_username_route
+        .or(public_event_route)
+        .or(event_view_route)
+        .or(openapi_route)
+        .or(swagger_ui_route)
         .or(buyer_signup_route)
         .or(buyer_register_phone_route)
         .or(buyer_verify_phone_route)
+        .or(buyer_resend_phone_code_route)
         .or(signin_route)
+        .or(signin_challenge_route)
         .or(signin_with_password_route)
+        .or(signin_with_password_verify_totp_route)
         .or(buyer_create_recovery_code_route)
         .or(buyer_verify_recovery_code_route)
         .or(create_login_code_route)
         .or(verify_login_code_route)
+        .or(pusher_auth_route)
         .or(event_ticket_get_verification_code)
         .or(get_event_from_verification_code)
+        .or(export_my_data_route)
+        .or(import_events_route)
+        .or(upload_event_assets_route)
+        .or(event_attendees_csv_route)
+        .or(ticket_pdf_route)
         .or(graphql_private_route)
+        .or(graphql_seller_route)
+        .or(graphql_buyer_route)
+        .or(graphql_admin_route)
         .or(graphql_public_route)
-        .with(with_cors())
-        .recover(handle_rejection);
+        .or(graphql_public_subscriptions_route)
+        .or(graphql_private_subscriptions_route)
+        .or(graphql_public_schema_route)
+        .or(graphql_private_schema_route)
+        .or(graphql_seller_schema_route)
+        .or(graphql_buyer_schema_route)
+        .or(graphql_admin_schema_route)
+        .or(public_graphiql_route);
+    let routes = health_live_route
+        .or(health_ready_route)
+        .or(healthcheck_route)
+        .or(metrics_route)
+        .or(with_maintenance(resources_ctx.clone()).and(maintained_routes))
+        .with(with_cors(&cors_config))
+        .recover(handle_rejection)
+        .with(with_security_headers(server_env))
+        .with(with_metrics());
 
-    // run the server
+    // run the server, it stops accepting connections at the stop signal
+    let drain_timeout = Duration::from_secs(config.shutdown.drain_timeout_secs);
     match server_env {
         ServerEnv::Dev => {
             // dev mode: no certs needed
@@ -180,16 +507,10 @@ async fn main() -> Result<()> {
                 warp::serve(routes).bind_with_graceful_shutdown(server_addr, async move {
                     log::info!("waiting for a signal...");
                     _ = stop_rx.recv().await;
-
-                    log::info!("cleaning up resources..."); //TODO ???
-                    log::info!("done cleaning resources!");
-                    log::info!("Exiting...!")
+                    log::info!("Stopped accepting connections");
                 });
             log::info!("Graphql server listening on {}", server_addr.to_string());
-            match tokio::join!(tokio::task::spawn(server)).0 {
-                Ok(()) => log::info!(""),
-                Err(e) => log::info!("Main Thread join error {}", e),
-            };
+            drain_server(tokio::spawn(server), drain_rx, drain_timeout).await;
         }
         ServerEnv::Release => {
             let cert = config.api.tls.ok_or(Error::MissingCertificate)?;
@@ -203,27 +524,35 @@ async fn main() -> Result<()> {
                 .bind_with_graceful_shutdown(server_addr, async move {
                     log::info!("waiting for a signal...");
                     _ = stop_rx.recv().await;
-
-                    log::info!("cleaning up resources..."); //TODO ???
-                    log::info!("done cleaning resources!");
-                    log::info!("Exiting...!")
+                    log::info!("Stopped accepting connections");
                 });
             log::info!("Graphql server listening on {}", server_addr.to_string());
-            match tokio::join!(tokio::task::spawn(server)).0 {
-                Ok(()) => log::info!(""),
-                Err(e) => log::info!("Main Thread join error {}", e),
-            };
+            drain_server(tokio::spawn(server), drain_rx, drain_timeout).await;
         }
     }
 
+    // let the background workers finish their current work
+    let workers_timeout = Duration::from_secs(config.shutdown.workers_timeout_secs);
+    join_workers(workers, workers_timeout).await;
+
+    // dropping the last reference to the resources closes the db and grpc clients
+    log::info!("Closing clients...");
+    drop(resources_ctx);
+    match tokio::time::timeout(workers_timeout, db_connection).await {
+        Ok(_) => log::info!("DB connection closed"),
+        Err(_) => log::warn!("DB connection did not close in time"),
+    }
+
+    log::info!("Exiting...!");
     Ok(())
 }
 
 async fn stop_signal(stop_tx: broadcast::Sender<()>) {
-    let _ = signal(SignalKind::terminate())
-        .expect("shutdown_listener")
-        .recv()
-        .await;
+    let mut terminate = signal(SignalKind::terminate()).expect("shutdown_listener");
+    tokio::select! {
+        _ = terminate.recv() => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
 
     log::info!("Received shutdown signal...");
     let _ = stop_tx.send(());
@@ -235,4 +564,112 @@ struct Args {
     /// path to the config file
     #[argh(option, short = 'c')]
     config: String,
+    /// check the config and the connections to the dependencies, report the pending migrations
+    /// and exit
+    #[argh(switch)]
+    check: bool,
+    #[argh(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum Command {
+    Seed(SeedArgs),
+    BackfillPhoneNumbers(BackfillPhoneNumbersArgs),
+}
+
+/// Fill the database with fake sellers, buyers, events, tickets and reservations, for development
+#[derive(FromArgs)]
+#[argh(subcommand, name = "seed")]
+struct SeedArgs {
+    /// the seed of the fake data, the same seed generates the same data
+    #[argh(option, default = "1")]
+    seed: u64,
+    /// how many sellers to create
+    #[argh(option, default = "5")]
+    sellers: usize,
+    /// how many buyers to create
+    #[argh(option, default = "20")]
+    buyers: usize,
+    /// how many events every seller has
+    #[argh(option, default = "3")]
+    events_per_seller: usize,
+    /// how many tickets every event has
+    #[argh(option, default = "2")]
+    tickets_per_event: usize,
+    /// how many reservations every buyer makes
+    #[argh(option, default = "3")]
+    reservations_per_buyer: usize,
+    /// the password of every created user
+    #[argh(option, default = "String::from(\"seed-password\")")]
+    password: String,
+    /// seed even when the ENV variable is not dev or test
+    #[argh(switch)]
+    allow_non_dev: bool,
+}
+
+/// Rewrite the phone numbers stored before normalization to E.164, once after upgrading
+#[derive(FromArgs)]
+#[argh(subcommand, name = "backfill-phone-numbers")]
+struct BackfillPhoneNumbersArgs {}
+
+/// Runs the migrations and connects to the db of a one-off command
+async fn command_db_client(config: &Config) -> Result<Client> {
+    gql_api::migrations::run(&config.postgres);
+
+    let (db_client, connection) = db_client_from_config(&config.postgres)
+        .await
+        .context("Failed to connect to the db")?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            eprintln!("DB Connection Error: {}", e);
+        }
+    });
+    Ok(db_client)
+}
+
+/// Runs the migrations and inserts the fake data of the seed
+async fn seed(config: &Config, seed_args: SeedArgs) -> Result<()> {
+    // the fake users and events must not land in a real db by mistake, an unset ENV included
+    let env = env::var("ENV").unwrap_or_default().to_lowercase();
+    if !matches!(env.as_str(), "dev" | "test") && !seed_args.allow_non_dev {
+        anyhow::bail!(
+            "Refusing to seed with ENV={:?}, seed in dev or test or pass --allow-non-dev",
+            env
+        );
+    }
+
+    let db_client = command_db_client(config).await?;
+
+    let seed_config = SeedConfig {
+        seed: seed_args.seed,
+        sellers: seed_args.sellers,
+        buyers: seed_args.buyers,
+        events_per_seller: seed_args.events_per_seller,
+        tickets_per_event: seed_args.tickets_per_event,
+        reservations_per_buyer: seed_args.reservations_per_buyer,
+        password: seed_args.password,
+    };
+    let report = gql_api::seed::seed(
+        &db_client,
+        &seed_config,
+        config.near.network,
+        sql_timestamp(None),
+    )
+    .await
+    .context("Failed to seed the db")?;
+    println!("{}", report);
+    Ok(())
+}
+
+/// Runs the migrations and rewrites the stored phone numbers to E.164
+async fn backfill_phone_numbers(config: &Config) -> Result<()> {
+    let db_client = command_db_client(config).await?;
+
+    let report = gql_api::phone::backfill_phone_numbers(&db_client)
+        .await
+        .context("Failed to backfill the phone numbers")?;
+    println!("{:?}", report);
+    Ok(())
 }
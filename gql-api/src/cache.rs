@@ -0,0 +1,157 @@
+//! In-memory read cache of the public events looked up by id or slug.
+//!
+//! Event pages query a single event over and over, each lookup costing an events, a tickets and a
+//! tags query. The cached events expire after a short ttl, and are dropped earlier whenever they
+//! change: the mutations of this instance invalidate them directly, and the database triggers
+//! `NOTIFY` every instance of the changed event ids on the [`EVENTS_CHANNEL`].
+use crate::{config::CacheConfig, gql::models::Event, metrics::observe_cache_lookup};
+use futures::{stream, StreamExt};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tokio_postgres::{tls::NoTlsStream, AsyncMessage, Client, Connection, Socket};
+use uuid::Uuid;
+
+/// The channel notified with the id of every inserted, updated or deleted event, ticket and tag
+pub const EVENTS_CHANNEL: &str = "event_changed";
+
+/// How a public event is looked up
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EventKey {
+    Id(Uuid),
+    Slug(String),
+}
+
+struct CachedEvent {
+    event: Event,
+    cached_at: Instant,
+}
+
+pub struct EventCache {
+    ttl: Duration,
+    max_events: usize,
+    events: Mutex<HashMap<EventKey, CachedEvent>>,
+}
+
+impl EventCache {
+    pub fn new(config: &CacheConfig) -> Self {
+        EventCache {
+            ttl: Duration::from_secs(config.event_ttl_secs),
+            max_events: config.max_events,
+            events: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero() && self.max_events > 0
+    }
+
+    /// The cached event, `None` if it is not cached or has expired
+    pub fn get(&self, key: &EventKey) -> Option<Event> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let mut events = self.events.lock().expect("event cache lock poisoned");
+        let event = match events.get(key) {
+            Some(cached) if cached.cached_at.elapsed() < self.ttl => Some(cached.event.clone()),
+            Some(_) => {
+                events.remove(key);
+                None
+            }
+            None => None,
+        };
+        observe_cache_lookup("events", event.is_some());
+        event
+    }
+
+    pub fn insert(&self, key: EventKey, event: Event) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut events = self.events.lock().expect("event cache lock poisoned");
+        if events.len() >= self.max_events && !events.contains_key(&key) {
+            let ttl = self.ttl;
+            events.retain(|_, cached| cached.cached_at.elapsed() < ttl);
+        }
+        if events.len() >= self.max_events && !events.contains_key(&key) {
+            let oldest = events
+                .iter()
+                .min_by_key(|(_, cached)| cached.cached_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                events.remove(&oldest);
+            }
+        }
+        events.insert(
+            key,
+            CachedEvent {
+                event,
+                cached_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Drops the event, whichever way it was looked up
+    pub fn invalidate(&self, event_id: &Uuid) {
+        self.events
+            .lock()
+            .expect("event cache lock poisoned")
+            .retain(|_, cached| cached.event.id != *event_id);
+    }
+
+    pub fn clear(&self) {
+        self.events
+            .lock()
+            .expect("event cache lock poisoned")
+            .clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.lock().expect("event cache lock poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Applies a notification of the [`EVENTS_CHANNEL`], everything is dropped when its payload is
+    /// not an event id
+    pub fn notified(&self, payload: &str) {
+        match Uuid::parse_str(payload) {
+            Ok(event_id) => self.invalidate(&event_id),
+            Err(_) => {
+                log::warn!("Unexpected {} payload: {}", EVENTS_CHANNEL, payload);
+                self.clear();
+            }
+        }
+    }
+}
+
+/// Subscribes the connection of the client to the event changes
+pub async fn listen(db_client: &Client) -> Result<(), tokio_postgres::Error> {
+    db_client
+        .batch_execute(&format!("LISTEN {}", EVENTS_CHANNEL))
+        .await
+}
+
+/// Drives the db connection until it closes, applying its event notifications to the cache
+pub async fn drive_connection(
+    mut connection: Connection<Socket, NoTlsStream>,
+    cache: &EventCache,
+) -> Result<(), tokio_postgres::Error> {
+    let mut messages = stream::poll_fn(move |cx| connection.poll_message(cx));
+    while let Some(message) = messages.next().await {
+        match message? {
+            AsyncMessage::Notification(notification)
+                if notification.channel() == EVENTS_CHANNEL =>
+            {
+                cache.notified(notification.payload())
+            }
+            AsyncMessage::Notice(notice) => log::info!("DB notice: {}", notice),
+            _ => {}
+        }
+    }
+    Ok(())
+}
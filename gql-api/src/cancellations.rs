@@ -0,0 +1,164 @@
+//! Cancellations of reservations, by their buyer or along with their event by its seller.
+//!
+//! A cancelled reservation is removed and its tickets go back on sale, a
+//! [`DbTicketCancellation`] keeps what the buyer paid. Buyers may cancel until the event's
+//! cancellation window, hours before its start, which the seller sets; without one they cannot.
+//! The buyer is told in their inbox and refunded by a job, which sends what they paid back to
+//! their wallet through the near api and is retried while the transfer fails. A cancellation is
+//! refunded once.
+use crate::{
+    db::{
+        models::{DbEvent, DbTicketCancellation},
+        sql::{
+            db_cancel_event, db_cancel_event_reservations, db_cancel_ticket_reservation,
+            db_complete_ticket_refund, db_get_ticket_cancellation_by_id, db_get_user_by_id,
+            sql_timestamp,
+        },
+    },
+    error::Error,
+    gql::{models::NotificationKind, schema::Context as ResourcesContext},
+    inbox,
+    jobs::{models::JobPayload, queue::enqueue},
+};
+use uuid::Uuid;
+
+/// Cancels the buyer's reservation of the event, `None` if there is no such reservation
+pub async fn cancel_reservation(
+    ctx: &ResourcesContext,
+    db_event: &DbEvent,
+    reservation_id: &Uuid,
+    cancelled_by: &Uuid,
+) -> Result<Option<DbTicketCancellation>, Error> {
+    let db_cancellation = db_cancel_ticket_reservation(
+        &ctx.db_client,
+        reservation_id,
+        cancelled_by,
+        &sql_timestamp(None),
+    )
+    .await
+    .map_err(Error::Postgres)?;
+    if let Some(db_cancellation) = &db_cancellation {
+        settle(
+            ctx,
+            db_cancellation,
+            format!("Your reservation for {} was cancelled", db_event.event_name),
+        )
+        .await?;
+    }
+    Ok(db_cancellation)
+}
+
+/// Cancels the event and every reservation of it, `None` if it was cancelled already
+pub async fn cancel_event(
+    ctx: &ResourcesContext,
+    db_event: &DbEvent,
+    cancelled_by: &Uuid,
+) -> Result<Option<(DbEvent, Vec<DbTicketCancellation>)>, Error> {
+    // cancelled first, so that no reservation is made while the others are cancelled
+    let db_event = match db_cancel_event(&ctx.db_client, &db_event.id)
+        .await
+        .map_err(Error::Postgres)?
+    {
+        Some(db_event) => db_event,
+        None => return Ok(None),
+    };
+    let db_cancellations = db_cancel_event_reservations(
+        &ctx.db_client,
+        &db_event.id,
+        cancelled_by,
+        &sql_timestamp(None),
+    )
+    .await
+    .map_err(Error::Postgres)?;
+    for db_cancellation in &db_cancellations {
+        settle(
+            ctx,
+            db_cancellation,
+            format!("{} was cancelled by its organizer", db_event.event_name),
+        )
+        .await?;
+    }
+    Ok(Some((db_event, db_cancellations)))
+}
+
+/// Enqueues the refund of the cancellation, if the buyer paid anything, and tells the buyer
+async fn settle(
+    ctx: &ResourcesContext,
+    db_cancellation: &DbTicketCancellation,
+    message: String,
+) -> Result<(), Error> {
+    let refund_due = db_cancellation.is_refund_due();
+    if refund_due {
+        enqueue(
+            &ctx.db_client,
+            JobPayload::Refund {
+                cancellation_id: db_cancellation.id,
+            },
+            ctx.jobs.max_attempts,
+        )
+        .await
+        .map_err(Error::Postgres)?;
+    }
+
+    let message = if refund_due {
+        format!(
+            "{}, {} NEAR are refunded to your wallet",
+            message,
+            db_cancellation.refund_amount.to_near_string()
+        )
+    } else {
+        message
+    };
+    inbox::notify(
+        &ctx.db_client,
+        db_cancellation.user_id,
+        NotificationKind::ReservationCancelled,
+        message,
+        Some(serde_json::json!({
+            "eventId": db_cancellation.event_id,
+            "ticketId": db_cancellation.ticket_id,
+            "reservationId": db_cancellation.id,
+            "quantity": db_cancellation.quantity,
+            "refundAmount": db_cancellation.refund_amount,
+        })),
+    )
+    .await;
+    Ok(())
+}
+
+/// Sends the buyer what they paid for the cancelled reservation. Returns the refunded
+/// cancellation, `None` if there was nothing left to refund
+pub async fn refund(
+    ctx: &ResourcesContext,
+    cancellation_id: &Uuid,
+) -> Result<Option<DbTicketCancellation>, Error> {
+    let db_cancellation = match db_get_ticket_cancellation_by_id(&ctx.db_client, cancellation_id)
+        .await
+        .map_err(Error::Postgres)?
+    {
+        Some(db_cancellation) if db_cancellation.is_refund_due() => db_cancellation,
+        _ => return Ok(None),
+    };
+    let db_user = db_get_user_by_id(&ctx.db_client, &db_cancellation.user_id)
+        .await
+        .map_err(Error::Postgres)?;
+
+    let refund_payment_tx = ctx
+        .chain
+        .refund_payment(
+            &db_user.wallet_id,
+            &db_cancellation.refund_amount.to_string(),
+            &db_cancellation.id.to_string(),
+        )
+        .await
+        .map_err(Error::Chain)?;
+
+    db_complete_ticket_refund(
+        &ctx.db_client,
+        &db_cancellation.id,
+        &refund_payment_tx.tx_hash,
+        &sql_timestamp(None),
+    )
+    .await
+    .map_err(Error::Postgres)
+}
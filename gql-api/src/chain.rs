@@ -0,0 +1,367 @@
+//! The blockchain the wallets and nft tickets live on.
+//!
+//! The wallet logic goes through [`ChainClient`] rather than the near api, so that the crate can
+//! target other chains: NEAR through the near api service, or an in-memory [`MockChainClient`]
+//! for local development, as the `[chain]` config selects. Every transfer of funds, i.e. top-ups,
+//! payouts and refunds, goes through it as well. What has no counterpart on other chains, e.g. the
+//! near api's encryption, stays on the [`NearClient`].
+use crate::{
+    error::ChainError,
+    gql::mint::MintPayload,
+    grpc::{near_api::TxStatus, NearClient},
+    security::crypto::{verify_signature_with_pub_key, NearAccount},
+};
+use async_trait::async_trait;
+use ed25519_dalek::PublicKey;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex as StdMutex},
+};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// The prefix of the NEAR encoded ed25519 keys
+const ED25519_PREFIX: &str = "ed25519:";
+
+/// A key pair of a new account, base58 encoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainKeyPair {
+    pub public_key: String,
+    pub secret_key: String,
+}
+
+/// A submitted transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainTx {
+    pub tx_hash: String,
+    /// whether the chain already rejected it
+    pub failed: bool,
+}
+
+/// The wallet operations of a blockchain
+#[async_trait]
+pub trait ChainClient: Send + Sync {
+    /// Generates the key pair of a new account
+    async fn generate_key_pair(&self) -> Result<ChainKeyPair, ChainError>;
+
+    /// Creates the account, controlled by the public key and funded with the deposit
+    async fn create_account(
+        &self,
+        account_id: &str,
+        public_key: &str,
+        deposit_amount: &str,
+    ) -> Result<ChainTx, ChainError>;
+
+    /// The public keys controlling the account, none if it does not exist
+    async fn account_keys(&self, account_id: &str) -> Result<Vec<String>, ChainError>;
+
+    /// Whether the signature of the message was made with the public key's secret key
+    async fn verify_signature(
+        &self,
+        message: &[u8],
+        public_key: &str,
+        signature: &str,
+    ) -> Result<bool, ChainError>;
+
+    /// Mints the ticket's nfts to the seller's wallet
+    async fn mint_nfts(
+        &self,
+        seller_wallet_id: &str,
+        payload: MintPayload,
+    ) -> Result<ChainTx, ChainError>;
+
+    /// Transfers an nft of the ticket between wallets
+    async fn transfer_nft(
+        &self,
+        sender_wallet_id: &str,
+        receiver_wallet_id: &str,
+        ticket_slug: &str,
+        price: &str,
+    ) -> Result<ChainTx, ChainError>;
+
+    /// Transfers the amount from the platform's account to the account
+    async fn fund_account(&self, account_id: &str, amount: &str) -> Result<ChainTx, ChainError>;
+
+    /// Refunds the amount to the wallet, the reference identifies the refund so that it is not sent
+    /// twice
+    async fn refund_payment(
+        &self,
+        receiver_wallet_id: &str,
+        amount: &str,
+        reference: &str,
+    ) -> Result<ChainTx, ChainError>;
+}
+
+/// NEAR, through the near api service. Shares the near api client with the rest of the crate
+pub struct NearChainClient {
+    near_client: Arc<Mutex<Box<dyn NearClient>>>,
+}
+
+impl NearChainClient {
+    pub fn new(near_client: Arc<Mutex<Box<dyn NearClient>>>) -> Self {
+        NearChainClient { near_client }
+    }
+}
+
+#[async_trait]
+impl ChainClient for NearChainClient {
+    async fn generate_key_pair(&self) -> Result<ChainKeyPair, ChainError> {
+        let implicit_account = self
+            .near_client
+            .lock()
+            .await
+            .generate_implicit_account()
+            .await
+            .map_err(ChainError::Near)?;
+        Ok(ChainKeyPair {
+            public_key: implicit_account.public_key,
+            secret_key: implicit_account.secret_key,
+        })
+    }
+
+    async fn create_account(
+        &self,
+        account_id: &str,
+        public_key: &str,
+        deposit_amount: &str,
+    ) -> Result<ChainTx, ChainError> {
+        let create_account_response = self
+            .near_client
+            .lock()
+            .await
+            .create_account(account_id, public_key, deposit_amount)
+            .await
+            .map_err(ChainError::Near)?;
+        Ok(ChainTx {
+            failed: TxStatus::from_i32(create_account_response.status) == Some(TxStatus::Failed),
+            tx_hash: create_account_response.tx_hash,
+        })
+    }
+
+    async fn account_keys(&self, account_id: &str) -> Result<Vec<String>, ChainError> {
+        let account_keys = self
+            .near_client
+            .lock()
+            .await
+            .get_account_keys(account_id)
+            .await
+            .map_err(ChainError::Near)?;
+        Ok(account_keys
+            .data
+            .into_iter()
+            .map(|key| key.public_key)
+            .collect())
+    }
+
+    async fn verify_signature(
+        &self,
+        message: &[u8],
+        public_key: &str,
+        signature: &str,
+    ) -> Result<bool, ChainError> {
+        // the near api takes the message base58 encoded
+        let b58_encoded_message = bs58::encode(message).into_string();
+        let verify_signature_response = self
+            .near_client
+            .lock()
+            .await
+            .verify_signature(&b58_encoded_message, public_key, signature)
+            .await
+            .map_err(ChainError::Near)?;
+        Ok(verify_signature_response.is_verified)
+    }
+
+    async fn mint_nfts(
+        &self,
+        seller_wallet_id: &str,
+        payload: MintPayload,
+    ) -> Result<ChainTx, ChainError> {
+        let mint_nfts_response = self
+            .near_client
+            .lock()
+            .await
+            .mint_nfts(
+                seller_wallet_id.to_string(),
+                payload.title,
+                payload.ticket_slug,
+                payload.description,
+                payload.media,
+                payload.media_hash,
+                payload.number_of_tickets,
+                payload.extra,
+                "0".to_string(),
+            )
+            .await
+            .map_err(ChainError::Near)?;
+        Ok(ChainTx {
+            tx_hash: mint_nfts_response.tx_hash,
+            failed: false,
+        })
+    }
+
+    async fn transfer_nft(
+        &self,
+        sender_wallet_id: &str,
+        receiver_wallet_id: &str,
+        ticket_slug: &str,
+        price: &str,
+    ) -> Result<ChainTx, ChainError> {
+        let transfer_nft_response = self
+            .near_client
+            .lock()
+            .await
+            .transfer_nft(
+                sender_wallet_id.to_string(),
+                receiver_wallet_id.to_string(),
+                ticket_slug.to_string(),
+                price.to_string(),
+            )
+            .await
+            .map_err(ChainError::Near)?;
+        Ok(ChainTx {
+            tx_hash: transfer_nft_response.tx_hash,
+            failed: false,
+        })
+    }
+
+    async fn fund_account(&self, account_id: &str, amount: &str) -> Result<ChainTx, ChainError> {
+        let fund_account_response = self
+            .near_client
+            .lock()
+            .await
+            .fund_account(account_id, amount)
+            .await
+            .map_err(ChainError::Near)?;
+        Ok(ChainTx {
+            tx_hash: fund_account_response.tx_hash,
+            failed: false,
+        })
+    }
+
+    async fn refund_payment(
+        &self,
+        receiver_wallet_id: &str,
+        amount: &str,
+        reference: &str,
+    ) -> Result<ChainTx, ChainError> {
+        let refund_payment_response = self
+            .near_client
+            .lock()
+            .await
+            .refund_payment(receiver_wallet_id, amount, reference)
+            .await
+            .map_err(ChainError::Near)?;
+        Ok(ChainTx {
+            tx_hash: refund_payment_response.tx_hash,
+            failed: false,
+        })
+    }
+}
+
+/// An in-memory chain: accounts only live as long as the client, every transaction succeeds and
+/// signatures are verified locally, as ed25519 signatures. The mint reconciler still follows the
+/// mint transactions on the near api
+#[derive(Default)]
+pub struct MockChainClient {
+    /// the public keys of every account created
+    accounts: StdMutex<HashMap<String, Vec<String>>>,
+}
+
+impl MockChainClient {
+    fn mock_tx() -> ChainTx {
+        ChainTx {
+            tx_hash: bs58::encode(Uuid::new_v4().as_bytes()).into_string(),
+            failed: false,
+        }
+    }
+}
+
+/// Decodes a base58 ed25519 public key, with or without its NEAR prefix
+fn decode_public_key(public_key: &str) -> Option<PublicKey> {
+    let public_key = public_key
+        .strip_prefix(ED25519_PREFIX)
+        .unwrap_or(public_key);
+    let bytes = bs58::decode(public_key).into_vec().ok()?;
+    PublicKey::from_bytes(&bytes).ok()
+}
+
+#[async_trait]
+impl ChainClient for MockChainClient {
+    async fn generate_key_pair(&self) -> Result<ChainKeyPair, ChainError> {
+        let account = NearAccount::new_implicit().map_err(|e| ChainError::Mock(e.to_string()))?;
+        Ok(ChainKeyPair {
+            public_key: format!("{}{}", ED25519_PREFIX, account.pub_key_b58_encoded()),
+            secret_key: format!("{}{}", ED25519_PREFIX, account.secret_key_b58_encoded()),
+        })
+    }
+
+    async fn create_account(
+        &self,
+        account_id: &str,
+        public_key: &str,
+        _deposit_amount: &str,
+    ) -> Result<ChainTx, ChainError> {
+        let mut accounts = self.accounts.lock().expect("mock chain lock poisoned");
+        if accounts.contains_key(account_id) {
+            return Ok(ChainTx {
+                failed: true,
+                ..MockChainClient::mock_tx()
+            });
+        }
+        accounts.insert(account_id.to_string(), vec![public_key.to_string()]);
+        Ok(MockChainClient::mock_tx())
+    }
+
+    async fn account_keys(&self, account_id: &str) -> Result<Vec<String>, ChainError> {
+        let accounts = self.accounts.lock().expect("mock chain lock poisoned");
+        Ok(accounts.get(account_id).cloned().unwrap_or_default())
+    }
+
+    async fn verify_signature(
+        &self,
+        message: &[u8],
+        public_key: &str,
+        signature: &str,
+    ) -> Result<bool, ChainError> {
+        let public_key = match decode_public_key(public_key) {
+            Some(public_key) => public_key,
+            None => return Ok(false),
+        };
+        let signature = match bs58::decode(signature).into_vec() {
+            Ok(signature) => signature,
+            Err(_) => return Ok(false),
+        };
+        Ok(verify_signature_with_pub_key(&public_key, message, &signature).is_ok())
+    }
+
+    async fn mint_nfts(
+        &self,
+        _seller_wallet_id: &str,
+        _payload: MintPayload,
+    ) -> Result<ChainTx, ChainError> {
+        Ok(MockChainClient::mock_tx())
+    }
+
+    async fn transfer_nft(
+        &self,
+        _sender_wallet_id: &str,
+        _receiver_wallet_id: &str,
+        _ticket_slug: &str,
+        _price: &str,
+    ) -> Result<ChainTx, ChainError> {
+        Ok(MockChainClient::mock_tx())
+    }
+
+    async fn fund_account(&self, _account_id: &str, _amount: &str) -> Result<ChainTx, ChainError> {
+        Ok(MockChainClient::mock_tx())
+    }
+
+    async fn refund_payment(
+        &self,
+        _receiver_wallet_id: &str,
+        _amount: &str,
+        _reference: &str,
+    ) -> Result<ChainTx, ChainError> {
+        Ok(MockChainClient::mock_tx())
+    }
+}
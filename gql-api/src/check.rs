@@ -0,0 +1,157 @@
+//! The `--check` self-check of a deployment.
+//!
+//! A misconfigured deployment otherwise fails at whichever request first needs the broken
+//! dependency. The check connects to every dependency the server needs, the same way the server
+//! does, and reports the migrations the server would run on start.
+use crate::{
+    config::{db_client_from_config, Config, NotifierKind},
+    db::sql::db_select_one,
+    grpc::NearClient,
+    http::{
+        health::{http_probe, probe, STATUS_UP},
+        models::DependencyStatus,
+    },
+    migrations,
+    storage::{S3Storage, Storage},
+};
+use pusher_client::client::PusherClient;
+use s3_uploader::{s3::S3Client, AwsContext, DEFAULT_REGION};
+use std::{fmt, time::Duration};
+
+/// The outcome of the self-check
+#[derive(Debug)]
+pub struct CheckReport {
+    pub dependencies: Vec<DependencyStatus>,
+    /// the migrations the server would run on start, empty if they could not be listed
+    pub pending_migrations: Vec<String>,
+}
+
+impl CheckReport {
+    /// Whether every dependency is up
+    pub fn is_ok(&self) -> bool {
+        self.dependencies.iter().all(|d| d.status.eq(STATUS_UP))
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for d in &self.dependencies {
+            write!(f, "{:<12} {:<5} {:>6}ms", d.name, d.status, d.latency_ms)?;
+            if let Some(error) = &d.error {
+                write!(f, "  {}", error)?;
+            }
+            writeln!(f)?;
+        }
+        if self.pending_migrations.is_empty() {
+            writeln!(f, "no pending migrations")?;
+        } else {
+            writeln!(f, "pending migrations:")?;
+            for migration in &self.pending_migrations {
+                writeln!(f, "  {}", migration)?;
+            }
+        }
+        write!(f, "{}", if self.is_ok() { "OK" } else { "FAILED" })
+    }
+}
+
+/// Checks the config and connects to the dependencies of the server, each check bounded by the
+/// health timeout
+pub async fn self_check(config: &Config) -> CheckReport {
+    let timeout = Duration::from_millis(config.health.timeout_ms);
+    let http_client = reqwest::Client::new();
+    let healthcheck_account_id = config.near.network.account_id("healthcheck");
+
+    let config_status = probe("config", timeout, async { check_config(config) }).await;
+    let postgres = probe("postgres", timeout, async {
+        let (db_client, connection) = db_client_from_config(&config.postgres)
+            .await
+            .map_err(|e| e.to_string())?;
+        let connection = tokio::spawn(connection);
+        let res = db_select_one(&db_client)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+        connection.abort();
+        res
+    })
+    .await;
+
+    let mut pending_migrations = vec![];
+    let migrations_status = probe("migrations", timeout, async {
+        let postgres_config = config.postgres.clone();
+        pending_migrations =
+            tokio::task::spawn_blocking(move || migrations::pending(&postgres_config))
+                .await
+                .map_err(|e| e.to_string())??;
+        Ok(())
+    })
+    .await;
+
+    let near_api = probe("near_api", timeout, async {
+        let mut near_client = crate::grpc::new(&config.near_api)
+            .await
+            .map_err(|e| e.to_string())?;
+        near_client
+            .check_available_account_id(&healthcheck_account_id)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+    .await;
+
+    let s3 = probe("s3", timeout, async {
+        let aws_context = AwsContext::build(
+            config
+                .s3
+                .region
+                .clone()
+                .or_else(|| Some(DEFAULT_REGION.to_string())),
+            config.s3.bucket.clone(),
+            config.s3.prefix.clone(),
+        )
+        .await;
+        let storage = S3Storage::new(S3Client::new_from_context(&aws_context), aws_context);
+        http_probe(&http_client, &storage.asset_url(String::new())).await
+    })
+    .await;
+
+    let pusher = probe("pusher", timeout, async {
+        PusherClient::new(&config.pusher).map_err(|e| e.to_string())?;
+        http_probe(&http_client, &config.health.pusher_url).await
+    })
+    .await;
+
+    let mut dependencies = vec![
+        config_status,
+        postgres,
+        migrations_status,
+        near_api,
+        s3,
+        pusher,
+    ];
+    // the log notifier sends nothing, twilio is only needed behind the twilio notifier
+    if config.notifier.kind == NotifierKind::Twilio {
+        dependencies.push(
+            probe("twilio", timeout, async {
+                http_probe(&http_client, &config.health.twilio_url).await
+            })
+            .await,
+        );
+    }
+
+    CheckReport {
+        dependencies,
+        pending_migrations,
+    }
+}
+
+/// The settings the config parses but the server would only reject when using them
+fn check_config(config: &Config) -> Result<(), String> {
+    format!("{}:{}", config.api.bind_host, config.api.bind_port)
+        .parse::<std::net::SocketAddr>()
+        .map_err(|e| format!("invalid api bind address: {}", e))?;
+    if config.notifier.kind == NotifierKind::Twilio && config.twilio.is_none() {
+        return Err("the twilio notifier requires the [twilio] config".to_string());
+    }
+    Ok(())
+}
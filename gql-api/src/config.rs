@@ -1,7 +1,9 @@
+use crate::{fx::Currency, sanitize::TextField, security::password_policy::CharacterClass};
 use displaydoc::Display as DisplayDoc;
 use pusher_client::config::PusherConfig;
 use serde::Deserialize;
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
     str::FromStr,
     time::Duration,
@@ -40,6 +42,27 @@ pub struct GrpcConfig {
     pub bind_host: String,
     pub bind_port: u32,
     pub tls: Option<TlsConfig>,
+    #[serde(default)]
+    pub reconnect: ReconnectConfig,
+}
+
+/// How a broken channel to the near api is reconnected
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ReconnectConfig {
+    /// the wait after the first failed reconnect attempt, doubled after every further one
+    pub initial_backoff_ms: u64,
+    /// the longest wait between two reconnect attempts
+    pub max_backoff_ms: u64,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        ReconnectConfig {
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -69,6 +92,14 @@ pub struct S3Config {
     pub region: Option<String>,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct IpfsConfig {
+    /// ipfs node (or pinning service) http api, e.g. http://127.0.0.1:5001
+    pub api_url: String,
+    pub auth_token: Option<String>,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct TlsConfig {
@@ -83,6 +114,1072 @@ pub struct TwilioConfig {
     pub sms: TwilioSmsConfig,
 }
 
+/// The channel notifications are sent through
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotifierKind {
+    /// sms through twilio, requires the `[twilio]` config
+    Twilio,
+    /// only logs them, for local development and tests
+    Log,
+}
+
+impl Default for NotifierKind {
+    fn default() -> Self {
+        NotifierKind::Twilio
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct NotifierConfig {
+    pub kind: NotifierKind,
+}
+
+/// How clients read the stored assets
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AssetAccess {
+    /// straight from the bucket, which is publicly readable
+    Public,
+    /// through presigned urls, the bucket being private
+    Presigned,
+}
+
+impl Default for AssetAccess {
+    fn default() -> Self {
+        AssetAccess::Public
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct AssetUrlsConfig {
+    pub access: AssetAccess,
+    /// how long the presigned urls stay valid, longer than the event cache keeps them
+    pub presigned_ttl_secs: u64,
+}
+
+impl Default for AssetUrlsConfig {
+    fn default() -> Self {
+        AssetUrlsConfig {
+            access: AssetAccess::Public,
+            presigned_ttl_secs: 3600,
+        }
+    }
+}
+
+/// The blockchain the wallets and nft tickets live on
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChainKind {
+    /// NEAR, through the near api service
+    Near,
+    /// an in-memory chain, for local development without a near api
+    Mock,
+}
+
+impl Default for ChainKind {
+    fn default() -> Self {
+        ChainKind::Near
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ChainConfig {
+    pub kind: ChainKind,
+}
+
+/// The provider looking up the coordinates of event venues
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GeocoderKind {
+    /// a nominatim compatible search api
+    Nominatim,
+    /// no provider, events only get coordinates from a clone
+    None,
+}
+
+impl Default for GeocoderKind {
+    fn default() -> Self {
+        GeocoderKind::None
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct GeocoderConfig {
+    pub kind: GeocoderKind,
+    /// nominatim compatible `search` endpoint
+    pub url: String,
+    /// sent along every request, nominatim asks clients to identify themselves
+    pub user_agent: String,
+}
+
+impl Default for GeocoderConfig {
+    fn default() -> Self {
+        GeocoderConfig {
+            kind: GeocoderKind::default(),
+            url: "https://nominatim.openstreetmap.org/search".to_string(),
+            user_agent: "gql-api".to_string(),
+        }
+    }
+}
+
+/// The near network the wallets are created on
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum NearNetwork {
+    Testnet,
+    Mainnet,
+}
+
+impl Default for NearNetwork {
+    fn default() -> Self {
+        NearNetwork::Testnet
+    }
+}
+
+impl NearNetwork {
+    /// the top level account of the network, the parent of every account created on it
+    pub fn top_level_account(&self) -> &'static str {
+        match self {
+            NearNetwork::Testnet => "testnet",
+            NearNetwork::Mainnet => "near",
+        }
+    }
+
+    /// the id of the named sub-account of the top level account
+    pub fn account_id(&self, name: &str) -> String {
+        format!("{}.{}", name, self.top_level_account())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct NearConfig {
+    pub network: NearNetwork,
+    /// funds sent to the wallet of a new buyer, in NEAR (e.g. "0.2")
+    pub wallet_deposit: String,
+}
+
+impl Default for NearConfig {
+    fn default() -> Self {
+        NearConfig {
+            network: NearNetwork::Testnet,
+            wallet_deposit: "0.2".to_string(),
+        }
+    }
+}
+
+/// The texts of the sms notifications, missing ones default to english
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SmsTemplates {
+    /// `{code}` is replaced with the verification code
+    pub verification: String,
+    /// `{code}` is replaced with the recovery code
+    pub recovery: String,
+    /// `{quantity}`, `{ticket_name}`, `{event_name}` and `{code}` are replaced with the
+    /// reservation
+    pub receipt: String,
+    /// `{ticket_name}` and `{event_name}` are replaced with the ticket a waitlisted buyer may
+    /// reserve again
+    pub waitlist: String,
+    /// `{sender}`, `{quantity}`, `{ticket_name}`, `{event_name}` and `{code}` are replaced with
+    /// the gifted tickets and their claim code
+    pub gift: String,
+    /// `{event_name}`, `{venue}`, `{starts_at}` and `{entry_time}` are replaced with the event a
+    /// ticket holder is reminded of
+    pub reminder: String,
+}
+
+impl Default for SmsTemplates {
+    fn default() -> Self {
+        SmsTemplates {
+            verification: "Your verification code is: {code}".to_string(),
+            recovery: "Your recovery code is: {code}".to_string(),
+            receipt: "You reserved {quantity} x {ticket_name} for {event_name}. \
+                      Your verification code is: {code}"
+                .to_string(),
+            waitlist: "{ticket_name} for {event_name} is available again, reserve it before \
+                       it is gone"
+                .to_string(),
+            gift: "{sender} sent you {quantity} x {ticket_name} for {event_name}. Sign up and \
+                   claim them with the code: {code}"
+                .to_string(),
+            reminder: "{event_name} starts at {starts_at} at {venue}, doors open at {entry_time}"
+                .to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct BusinessConfig {
+    /// the start of the message sellers sign with their wallet key to sign in, followed by the
+    /// nonce of their challenge
+    pub signin_message: String,
+    /// default language of the sms notifications and messages, for users without a locale
+    pub language: String,
+    /// sms templates per language
+    #[serde(default)]
+    pub sms: HashMap<String, SmsTemplates>,
+    /// translations of the user-facing error messages per language, by english message
+    #[serde(default)]
+    pub messages: HashMap<String, HashMap<String, String>>,
+}
+
+impl Default for BusinessConfig {
+    fn default() -> Self {
+        BusinessConfig {
+            signin_message: "Sign in with nonce: ".to_string(),
+            language: "en".to_string(),
+            sms: HashMap::new(),
+            messages: HashMap::new(),
+        }
+    }
+}
+
+impl BusinessConfig {
+    /// The sms templates of the configured language, english when it has none
+    pub fn sms_templates(&self) -> SmsTemplates {
+        self.sms.get(&self.language).cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct HealthConfig {
+    /// timeout applied to each dependency probe
+    pub timeout_ms: u64,
+    /// pusher rest api endpoint probed for reachability
+    pub pusher_url: String,
+    /// twilio rest api endpoint probed for reachability
+    pub twilio_url: String,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        HealthConfig {
+            timeout_ms: 2000,
+            pusher_url: "https://api-eu.pusher.com".to_string(),
+            twilio_url: "https://api.twilio.com".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct JobsConfig {
+    /// how long the worker sleeps when the queue is empty
+    pub poll_interval_ms: u64,
+    /// attempts before a job is parked as failed
+    pub max_attempts: i32,
+    /// base delay of the exponential retry backoff
+    pub retry_backoff_secs: i64,
+    /// running jobs not updated for this long are considered abandoned and requeued
+    pub stale_after_secs: i64,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        JobsConfig {
+            poll_interval_ms: 1000,
+            max_attempts: 5,
+            retry_backoff_secs: 10,
+            stale_after_secs: 300,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct OutboxConfig {
+    /// how long the dispatcher sleeps when no message is due
+    pub poll_interval_ms: u64,
+    /// messages claimed per poll
+    pub batch_size: i64,
+    /// how long a claimed message is left to its dispatcher before it is claimed again
+    pub lease_secs: i64,
+    /// base delay of the exponential retry backoff, messages are retried until delivered
+    pub retry_backoff_secs: i64,
+    /// upper bound of the retry backoff
+    pub max_retry_backoff_secs: i64,
+    /// delivered messages are kept this long before being purged
+    pub retention_secs: i64,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        OutboxConfig {
+            poll_interval_ms: 500,
+            batch_size: 100,
+            lease_secs: 60,
+            retry_backoff_secs: 5,
+            max_retry_backoff_secs: 600,
+            retention_secs: 604800,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SessionsConfig {
+    /// lifetime of a signup verification or recovery code
+    pub code_ttl_secs: i64,
+    /// verification attempts allowed per code
+    pub max_attempts: i32,
+    /// lifetime of a seller sign-in challenge
+    pub challenge_ttl_secs: i64,
+    /// lifetime of an impersonation jwt
+    pub impersonation_ttl_secs: i64,
+    /// minimum wait before a signup verification code can be re-sent
+    pub resend_cooldown_secs: i64,
+    /// re-sends allowed per signup session
+    pub max_resends: i32,
+    /// how often expired sessions are purged
+    pub cleanup_interval_secs: u64,
+    /// expired sessions are kept this long before being purged
+    pub retention_secs: i64,
+    /// wallet exports a buyer may attempt per day, failed re-verifications included
+    pub max_wallet_exports_per_day: i64,
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        SessionsConfig {
+            code_ttl_secs: 900,
+            max_attempts: 5,
+            challenge_ttl_secs: 300,
+            impersonation_ttl_secs: 900,
+            resend_cooldown_secs: 60,
+            max_resends: 3,
+            cleanup_interval_secs: 3600,
+            retention_secs: 86400,
+            max_wallet_exports_per_day: 3,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct BalancesConfig {
+    /// how often a batch of wallet balances is refreshed
+    pub sync_interval_secs: u64,
+    /// wallets refreshed per run, the least recently synced first
+    pub batch_size: i64,
+}
+
+impl Default for BalancesConfig {
+    fn default() -> Self {
+        BalancesConfig {
+            sync_interval_secs: 300,
+            batch_size: 100,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct TopUpsConfig {
+    /// whether the buyer wallets are topped up automatically, manual top-ups are always allowed
+    pub enabled: bool,
+    /// how often a batch of buyer wallets is checked
+    pub check_interval_secs: u64,
+    /// wallets checked per run, the least recently synced first
+    pub batch_size: i64,
+    /// wallets with less than this are topped up, in NEAR (e.g. "0.05")
+    pub threshold: String,
+    /// funds sent per top-up, in NEAR
+    pub amount: String,
+    /// most funds sent to a single wallet within 24 hours, in NEAR
+    pub daily_cap: String,
+}
+
+impl Default for TopUpsConfig {
+    fn default() -> Self {
+        TopUpsConfig {
+            enabled: false,
+            check_interval_secs: 600,
+            batch_size: 100,
+            threshold: "0.05".to_string(),
+            amount: "0.1".to_string(),
+            daily_cap: "0.3".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct AnalyticsConfig {
+    /// how often the event views and reservations are aggregated into the daily stats
+    pub aggregate_interval_secs: u64,
+    /// days of reservations recounted per run, today included, to follow late cancellations
+    pub recount_days: i64,
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        AnalyticsConfig {
+            aggregate_interval_secs: 300,
+            recount_days: 2,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CacheConfig {
+    /// how long a public event stays cached, 0 disables the cache
+    pub event_ttl_secs: u64,
+    /// most events kept in memory, the oldest are evicted first
+    pub max_events: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            event_ttl_secs: 30,
+            max_events: 1000,
+        }
+    }
+}
+
+/// The `Cache-Control` of a public route
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CachePolicy {
+    /// how long clients and proxies reuse a response without asking again, 0 to always revalidate
+    pub max_age_secs: u64,
+    /// how long after that a stale response may still be served while it is revalidated
+    pub stale_while_revalidate_secs: u64,
+}
+
+impl CachePolicy {
+    pub fn cache_control(&self) -> String {
+        match (self.max_age_secs, self.stale_while_revalidate_secs) {
+            (0, _) => "no-cache".to_string(),
+            (max_age, 0) => format!("public, max-age={}", max_age),
+            (max_age, stale) => format!(
+                "public, max-age={}, stale-while-revalidate={}",
+                max_age, stale
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct HttpCacheConfig {
+    /// the policy per route name, the routes not listed are always revalidated
+    pub routes: HashMap<String, CachePolicy>,
+}
+
+impl Default for HttpCacheConfig {
+    fn default() -> Self {
+        let routes = [
+            (
+                "event",
+                CachePolicy {
+                    max_age_secs: 30,
+                    stale_while_revalidate_secs: 300,
+                },
+            ),
+            (
+                "openapi",
+                CachePolicy {
+                    max_age_secs: 3600,
+                    stale_while_revalidate_secs: 0,
+                },
+            ),
+        ];
+        HttpCacheConfig {
+            routes: routes
+                .into_iter()
+                .map(|(route, policy)| (route.to_string(), policy))
+                .collect(),
+        }
+    }
+}
+
+impl HttpCacheConfig {
+    pub fn policy(&self, route: &str) -> CachePolicy {
+        self.routes.get(route).cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct MintsConfig {
+    /// how often the pending mint transactions are checked
+    pub check_interval_secs: u64,
+    /// mint jobs checked per run, the least recently checked first
+    pub batch_size: i64,
+    /// checks after which a still pending mint is given up as failed
+    pub max_attempts: i32,
+}
+
+impl Default for MintsConfig {
+    fn default() -> Self {
+        MintsConfig {
+            check_interval_secs: 15,
+            batch_size: 50,
+            max_attempts: 40,
+        }
+    }
+}
+
+/// The retries of the buyer signups that failed after they started creating the wallet
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SignupsConfig {
+    /// how often the failed signups due for a retry are looked for
+    pub check_interval_secs: u64,
+    /// signups retried per run
+    pub batch_size: i64,
+    /// how long a signup is left to the request or retry carrying it through
+    pub lease_secs: i64,
+    /// the delay before the first retry, doubled after every further failure
+    pub retry_backoff_secs: i64,
+    /// the longest delay between two retries
+    pub max_retry_backoff_secs: i64,
+    /// failures after which a signup is left for an admin to resume or roll back
+    pub max_attempts: i32,
+}
+
+impl Default for SignupsConfig {
+    fn default() -> Self {
+        SignupsConfig {
+            check_interval_secs: 30,
+            batch_size: 20,
+            lease_secs: 120,
+            retry_backoff_secs: 30,
+            max_retry_backoff_secs: 3600,
+            max_attempts: 10,
+        }
+    }
+}
+
+/// Two-factor authentication of password sign-ins, enrollment is refused without it
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct TotpConfig {
+    /// the name authenticator apps show next to the account
+    pub issuer: String,
+    /// 32 characters the totp secrets are stored encrypted with
+    pub encryption_secret: String,
+}
+
+/// The hashing of the verification and recovery codes sent to buyers
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CodesConfig {
+    /// key of the hmac the codes are stored hashed with
+    pub secret: String,
+    /// keys the codes were hashed with before the current one, until their sessions expire
+    #[serde(default)]
+    pub previous_secrets: Vec<String>,
+}
+
+/// Downloadable pdf tickets, they are not served without it
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct TicketPdfConfig {
+    /// key of the hmac signing the reservations in the tickets' qr codes
+    pub signing_secret: String,
+    /// the storage prefix the rendered tickets are cached under
+    pub storage_prefix: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ShutdownConfig {
+    /// how long in-flight requests may take to finish once the stop signal is received
+    pub drain_timeout_secs: u64,
+    /// how long the background workers may take to finish their current work
+    pub workers_timeout_secs: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        ShutdownConfig {
+            drain_timeout_secs: 30,
+            workers_timeout_secs: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct WaitlistConfig {
+    /// how often waitlisted tickets are checked for tickets left to reserve
+    pub check_interval_secs: u64,
+    /// how long notified buyers have to reserve before the next ones are notified
+    pub offer_window_secs: i64,
+    /// buyers notified per ticket and run, at most
+    pub batch_size: i64,
+}
+
+impl Default for WaitlistConfig {
+    fn default() -> Self {
+        WaitlistConfig {
+            check_interval_secs: 60,
+            offer_window_secs: 900,
+            batch_size: 50,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct StockAlertsConfig {
+    /// shares of a ticket's available quantity, in percent, its seller is alerted of when the
+    /// tickets left reach them, 0 being sold out
+    pub thresholds_percent: Vec<u8>,
+}
+
+impl Default for StockAlertsConfig {
+    fn default() -> Self {
+        StockAlertsConfig {
+            thresholds_percent: vec![10, 0],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct MaintenanceConfig {
+    /// forces the maintenance mode on whatever the admins set, e.g. while the db is migrated
+    pub enabled: bool,
+    /// how long clients are told to wait before retrying, unless the admins set otherwise
+    pub retry_after_secs: u32,
+    /// how often the maintenance mode the admins set is read from the db
+    pub refresh_interval_secs: u64,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        MaintenanceConfig {
+            enabled: false,
+            retry_after_secs: 300,
+            refresh_interval_secs: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct WebhooksConfig {
+    /// how long a seller's endpoint has to answer a delivery
+    pub timeout_ms: u64,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        WebhooksConfig { timeout_ms: 5000 }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RemindersConfig {
+    /// how often the events starting soon are checked for ticket holders to remind
+    pub check_interval_secs: u64,
+    /// how long before the start of an event its ticket holders are reminded, once per window
+    pub windows_secs: Vec<i64>,
+}
+
+impl Default for RemindersConfig {
+    fn default() -> Self {
+        RemindersConfig {
+            check_interval_secs: 300,
+            windows_secs: vec![86400, 3600],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SeriesConfig {
+    /// events a series materializes at most, the rules without COUNT or UNTIL stop there
+    pub max_occurrences: usize,
+}
+
+impl Default for SeriesConfig {
+    fn default() -> Self {
+        SeriesConfig {
+            max_occurrences: 52,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SeatsConfig {
+    /// how long a buyer holds the seats they selected before reserving them
+    pub hold_secs: i64,
+    /// seats a buyer holds at once at most
+    pub max_held_seats: usize,
+}
+
+impl Default for SeatsConfig {
+    fn default() -> Self {
+        SeatsConfig {
+            hold_secs: 600,
+            max_held_seats: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct FxConfig {
+    /// coingecko compatible `simple/price` endpoint, only the fixed rates are used when unset
+    pub url: Option<String>,
+    /// how often the rates are fetched
+    pub refresh_interval_secs: u64,
+    /// fetched rates older than this are no longer displayed
+    pub max_age_secs: u64,
+    /// price of 1 NEAR per currency, used until (or instead of) fetched rates
+    pub fixed_rates: HashMap<Currency, f64>,
+}
+
+impl Default for FxConfig {
+    fn default() -> Self {
+        FxConfig {
+            url: None,
+            refresh_interval_secs: 300,
+            max_age_secs: 3600,
+            fixed_rates: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct UsernamesConfig {
+    /// usernames nobody may sign up with, compared ignoring case, separators and look-alikes
+    pub reserved: Vec<String>,
+    /// words no username may contain
+    pub blocked_words: Vec<String>,
+}
+
+impl Default for UsernamesConfig {
+    fn default() -> Self {
+        let reserved = [
+            "admin",
+            "administrator",
+            "root",
+            "superadmin",
+            "system",
+            "near",
+            "testnet",
+            "mainnet",
+            "support",
+            "help",
+            "api",
+            "www",
+            "tickets",
+            "official",
+            "moderator",
+            "null",
+            "undefined",
+        ];
+        let blocked_words = [
+            "fuck", "shit", "cunt", "bitch", "whore", "slut", "nigger", "faggot", "retard",
+        ];
+        UsernamesConfig {
+            reserved: reserved.iter().map(|word| word.to_string()).collect(),
+            blocked_words: blocked_words.iter().map(|word| word.to_string()).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct PasswordPolicyConfig {
+    /// in characters
+    pub min_length: usize,
+    pub max_length: usize,
+    /// the character classes every password must contain, none by default: length and the
+    /// deny-list keep out weak passwords better than composition rules do
+    pub required_classes: Vec<CharacterClass>,
+    /// passwords nobody may choose besides the common ones, compared ignoring case
+    pub denied: Vec<String>,
+    /// the passwords of known data breaches are refused too, not checked without it
+    pub breach_check: Option<BreachCheckConfig>,
+}
+
+impl Default for PasswordPolicyConfig {
+    fn default() -> Self {
+        PasswordPolicyConfig {
+            min_length: 8,
+            max_length: 50,
+            required_classes: vec![],
+            denied: vec![],
+            breach_check: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct BreachCheckConfig {
+    /// the k-anonymity range api, the SHA-1 prefix of the password is appended
+    pub api_url: String,
+    /// how long the api has to answer, the password is accepted otherwise
+    pub timeout_ms: u64,
+}
+
+impl Default for BreachCheckConfig {
+    fn default() -> Self {
+        BreachCheckConfig {
+            api_url: "https://api.pwnedpasswords.com/range".to_string(),
+            timeout_ms: 2000,
+        }
+    }
+}
+
+/// Backoff and lockout of repeated failed password sign-ins
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct LoginLockoutConfig {
+    /// failed attempts from an address before each further one makes it wait
+    pub free_attempts: i32,
+    /// the first wait, doubled by every further failed attempt
+    pub backoff_base_secs: i64,
+    pub backoff_max_secs: i64,
+    /// failed attempts of a user, from all addresses, before the account is locked
+    pub lockout_attempts: i32,
+    pub lockout_secs: i64,
+    /// failed attempts are forgotten this long after the last one
+    pub window_secs: i64,
+    /// whether the client address is taken from `X-Forwarded-For`, only behind a trusted proxy
+    pub trust_forwarded_for: bool,
+}
+
+impl Default for LoginLockoutConfig {
+    fn default() -> Self {
+        LoginLockoutConfig {
+            free_attempts: 3,
+            backoff_base_secs: 1,
+            backoff_max_secs: 300,
+            lockout_attempts: 10,
+            lockout_secs: 900,
+            window_secs: 3600,
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SanitationConfig {
+    /// html-escape the free text, for clients rendering it as html (usernames are never escaped)
+    pub escape_html: bool,
+    pub max_lengths: MaxLengthsConfig,
+}
+
+/// Max lengths of the user-generated text, in characters
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct MaxLengthsConfig {
+    pub event_name: usize,
+    pub event_description: usize,
+    pub venue_name: usize,
+    pub venue_location: usize,
+    pub ticket_name: usize,
+    pub ticket_description: usize,
+    pub username: usize,
+}
+
+impl Default for MaxLengthsConfig {
+    fn default() -> Self {
+        MaxLengthsConfig {
+            event_name: 20,
+            event_description: 2000,
+            venue_name: 200,
+            venue_location: 200,
+            ticket_name: 20,
+            ticket_description: 500,
+            username: 20,
+        }
+    }
+}
+
+impl MaxLengthsConfig {
+    pub const fn max_len(&self, field: TextField) -> usize {
+        match field {
+            TextField::EventName => self.event_name,
+            TextField::EventDescription => self.event_description,
+            TextField::VenueName => self.venue_name,
+            TextField::VenueLocation => self.venue_location,
+            TextField::TicketName => self.ticket_name,
+            TextField::TicketDescription => self.ticket_description,
+            TextField::Username => self.username,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct BodyLimitsConfig {
+    /// largest accepted body of the json rest routes, in bytes
+    pub json_bytes: u64,
+    /// largest accepted body of the graphql routes, in bytes (mutations may carry base64 images)
+    pub graphql_bytes: u64,
+    /// largest accepted file of the multipart asset uploads, in bytes
+    pub asset_bytes: u64,
+    /// files accepted per multipart asset upload, at most
+    pub asset_files: usize,
+}
+
+impl Default for BodyLimitsConfig {
+    fn default() -> Self {
+        BodyLimitsConfig {
+            json_bytes: 64 * 1024,
+            graphql_bytes: 8 * 1024 * 1024,
+            asset_bytes: 10 * 1024 * 1024,
+            asset_files: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SlowQueriesConfig {
+    /// queries taking longer are logged and counted, none when 0
+    pub threshold_ms: u64,
+    /// log the plans of the slow statements, in dev only
+    pub explain: bool,
+}
+
+impl Default for SlowQueriesConfig {
+    fn default() -> Self {
+        SlowQueriesConfig {
+            threshold_ms: 200,
+            explain: false,
+        }
+    }
+}
+
+impl SlowQueriesConfig {
+    /// Explaining runs the slow statements again, release servers never do
+    pub fn for_env(&self, server_env: ServerEnv) -> SlowQueriesConfig {
+        SlowQueriesConfig {
+            threshold_ms: self.threshold_ms,
+            explain: self.explain && server_env == ServerEnv::Dev,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct GraphqlConfig {
+    /// answer introspection queries and serve the schema SDL in release mode
+    pub introspection: bool,
+    /// serve the graphiql playground in release mode
+    pub graphiql: bool,
+    /// the public endpoint only executes queries already persisted, see
+    /// [`crate::gql::persisted`]
+    pub persisted_queries_only: bool,
+    /// requests executed per batched http request, at most
+    pub max_batch_size: usize,
+}
+
+impl Default for GraphqlConfig {
+    fn default() -> Self {
+        GraphqlConfig {
+            introspection: false,
+            graphiql: false,
+            persisted_queries_only: false,
+            max_batch_size: 10,
+        }
+    }
+}
+
+impl GraphqlConfig {
+    /// Dev servers always expose the schema, release servers only when configured to
+    pub fn for_env(&self, server_env: ServerEnv) -> GraphqlConfig {
+        match server_env {
+            ServerEnv::Dev => GraphqlConfig {
+                introspection: true,
+                graphiql: true,
+                persisted_queries_only: self.persisted_queries_only,
+                max_batch_size: self.max_batch_size,
+            },
+            ServerEnv::Release => self.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct CorsConfig {
+    /// origins allowed to call the api in release mode, any origin when empty
+    pub allowed_origins: Vec<String>,
+    /// request headers allowed in cross-origin requests
+    pub allowed_headers: Vec<String>,
+    /// how long browsers may cache a preflight response
+    pub max_age_secs: u64,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        let allowed_headers = [
+            "Sec-Fetch-Mode",
+            "Sec-Fetch-Dest",
+            "Sec-Fetch-Site",
+            "Mode",
+            "Credentials",
+            reqwest::header::ACCEPT.as_str(),
+            reqwest::header::ACCEPT_CHARSET.as_str(),
+            reqwest::header::ACCEPT_ENCODING.as_str(),
+            reqwest::header::ACCEPT_LANGUAGE.as_str(),
+            reqwest::header::ACCEPT_RANGES.as_str(),
+            reqwest::header::USER_AGENT.as_str(),
+            reqwest::header::REFERER.as_str(),
+            reqwest::header::REFERRER_POLICY.as_str(),
+            reqwest::header::ORIGIN.as_str(),
+            reqwest::header::ALLOW.as_str(),
+            reqwest::header::COOKIE.as_str(),
+            reqwest::header::HOST.as_str(),
+            reqwest::header::ACCESS_CONTROL_REQUEST_METHOD.as_str(),
+            reqwest::header::ACCESS_CONTROL_REQUEST_HEADERS.as_str(),
+            reqwest::header::ACCESS_CONTROL_EXPOSE_HEADERS.as_str(),
+            reqwest::header::ACCESS_CONTROL_MAX_AGE.as_str(),
+            reqwest::header::ACCESS_CONTROL_ALLOW_METHODS.as_str(),
+            reqwest::header::ACCESS_CONTROL_ALLOW_CREDENTIALS.as_str(),
+            reqwest::header::ACCESS_CONTROL_ALLOW_ORIGIN.as_str(),
+            reqwest::header::ACCESS_CONTROL_ALLOW_HEADERS.as_str(),
+            reqwest::header::CONTENT_TYPE.as_str(),
+            reqwest::header::AUTHORIZATION.as_str(),
+            reqwest::header::UPGRADE.as_str(),
+            reqwest::header::UPGRADE_INSECURE_REQUESTS.as_str(),
+        ];
+        CorsConfig {
+            allowed_origins: Vec::new(),
+            allowed_headers: allowed_headers.iter().map(|h| h.to_string()).collect(),
+            max_age_secs: 3600,
+        }
+    }
+}
+
+impl CorsConfig {
+    /// Dev servers allow any origin, release servers only the configured ones
+    pub fn for_env(&self, server_env: ServerEnv) -> CorsConfig {
+        match server_env {
+            ServerEnv::Dev => CorsConfig {
+                allowed_origins: Vec::new(),
+                ..self.clone()
+            },
+            ServerEnv::Release => self.clone(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct Config {
@@ -90,8 +1187,80 @@ pub struct Config {
     pub postgres: PostgresConfig,
     pub near_api: GrpcConfig,
     pub pusher: PusherConfig,
-    pub twilio: TwilioConfig,
+    pub codes: CodesConfig,
+    pub twilio: Option<TwilioConfig>,
     pub s3: S3Config,
+    #[serde(default)]
+    pub asset_urls: AssetUrlsConfig,
+    pub ipfs: Option<IpfsConfig>,
+    pub totp: Option<TotpConfig>,
+    pub ticket_pdf: Option<TicketPdfConfig>,
+    #[serde(default)]
+    pub notifier: NotifierConfig,
+    #[serde(default)]
+    pub chain: ChainConfig,
+    #[serde(default)]
+    pub geocoder: GeocoderConfig,
+    #[serde(default)]
+    pub near: NearConfig,
+    #[serde(default)]
+    pub business: BusinessConfig,
+    #[serde(default)]
+    pub health: HealthConfig,
+    #[serde(default)]
+    pub jobs: JobsConfig,
+    #[serde(default)]
+    pub outbox: OutboxConfig,
+    #[serde(default)]
+    pub sessions: SessionsConfig,
+    #[serde(default)]
+    pub balances: BalancesConfig,
+    #[serde(default)]
+    pub graphql: GraphqlConfig,
+    #[serde(default)]
+    pub cors: CorsConfig,
+    #[serde(default)]
+    pub body_limits: BodyLimitsConfig,
+    #[serde(default)]
+    pub usernames: UsernamesConfig,
+    #[serde(default)]
+    pub sanitation: SanitationConfig,
+    #[serde(default)]
+    pub password_policy: PasswordPolicyConfig,
+    #[serde(default)]
+    pub login_lockout: LoginLockoutConfig,
+    #[serde(default)]
+    pub fx: FxConfig,
+    #[serde(default)]
+    pub waitlist: WaitlistConfig,
+    #[serde(default)]
+    pub reminders: RemindersConfig,
+    #[serde(default)]
+    pub series: SeriesConfig,
+    #[serde(default)]
+    pub seats: SeatsConfig,
+    #[serde(default)]
+    pub stock_alerts: StockAlertsConfig,
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+    #[serde(default)]
+    pub mints: MintsConfig,
+    #[serde(default)]
+    pub signups: SignupsConfig,
+    #[serde(default)]
+    pub top_ups: TopUpsConfig,
+    #[serde(default)]
+    pub analytics: AnalyticsConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub http_cache: HttpCacheConfig,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    #[serde(default)]
+    pub slow_queries: SlowQueriesConfig,
 }
 
 impl Config {
@@ -1,2 +1,4 @@
 pub mod models;
+pub mod query;
+pub mod slow_queries;
 pub mod sql;
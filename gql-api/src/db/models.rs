@@ -1,13 +1,22 @@
 use crate::{
-    auth::{Role, UserStatus},
-    gql::models::{EventStatus, NewTicket},
+    audit::AuditEntity,
+    auth::{Role, SellerStatus, UserStatus},
+    error::TicketError,
+    fx::Currency,
+    gql::models::{
+        AssetRole, DiscountType, DocumentKind, EventStatus, MemberRole, MintStatus, NewPriceTier,
+        NewPromoCode, NewTicket, NotificationKind, PayoutStatus, SeatStatus, SignupStatus,
+    },
+    jobs::models::{JobPayload, JobStatus, JobType, OutboxMessage},
+    near::{parse_basis_points, NearAmount},
 };
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use slugify::slugify;
 use std::convert::TryFrom;
 use uuid::Uuid;
 
+use super::query::Table;
 use super::sql::sql_timestamp;
 
 // ------------USERS----------------
@@ -26,6 +35,21 @@ pub struct DbUser {
     pub wallet_balance: String,
     pub user_type: Role,
     pub user_status: UserStatus,
+    pub wallet_balance_updated_at: Option<NaiveDateTime>,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub wallet_flagged: bool,
+    /// the seller's public storefront address
+    pub seller_slug: Option<String>,
+    /// the language of the user's sms, the configured one if `None`
+    pub locale: Option<String>,
+    /// the onboarding of sellers, `None` for the other roles
+    pub seller_status: Option<SellerStatus>,
+    /// why the seller's documents were rejected
+    pub seller_rejection_reason: Option<String>,
+    /// the user does not want reminders of the events they hold tickets for
+    pub event_reminders_opt_out: bool,
+    /// the jwts issued until then are no longer accepted
+    pub password_changed_at: Option<NaiveDateTime>,
 }
 
 impl DbUser {
@@ -55,41 +79,97 @@ impl DbUser {
             wallet_id,
             wallet_balance,
             user_status,
+            wallet_balance_updated_at: None,
+            deleted_at: None,
+            wallet_flagged: false,
+            seller_slug: None,
+            locale: None,
+            seller_status: (user_type == Role::Seller).then(|| SellerStatus::Onboarding),
+            seller_rejection_reason: None,
+            event_reminders_opt_out: false,
+            password_changed_at: None,
         }
     }
+
+    /// The username a deleted account is renamed to, freeing the original
+    pub fn anonymized_username(&self) -> String {
+        format!("deleted-{}", self.id.to_simple())
+    }
 }
 
 impl TryFrom<tokio_postgres::row::Row> for DbUser {
     type Error = tokio_postgres::Error;
 
     fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
-        let created_at: NaiveDateTime = row.try_get(7)?;
+        let created_at: NaiveDateTime = row.try_get("created_at")?;
 
         // user role
-        let user_role: i16 = row.try_get(10)?;
+        let user_role: i16 = row.try_get("user_type")?;
         let user_role = Role::try_from(user_role).expect("must be a valid role");
 
         // user status
-        let user_status: i16 = row.try_get(11)?;
+        let user_status: i16 = row.try_get("user_status")?;
         let user_status = UserStatus::try_from(user_status).expect("must be a valid user status");
 
+        // seller status
+        let seller_status: Option<i16> = row.try_get("seller_status")?;
+        let seller_status = seller_status.map(|seller_status| {
+            SellerStatus::try_from(seller_status).expect("must be a valid seller status")
+        });
+
         let user = DbUser {
-            id: row.try_get(0)?,
-            name: row.try_get(1).ok(),
-            username: row.try_get(2)?,
-            phone_number: row.try_get(3).ok(),
-            email: row.try_get(4).ok(),
-            password: row.try_get(5).ok(),
-            encrypted_secret_key: row.try_get(6).ok(),
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            username: row.try_get("username")?,
+            phone_number: row.try_get("phone_number")?,
+            email: row.try_get("email")?,
+            password: row.try_get("password")?,
+            encrypted_secret_key: row.try_get("encrypted_secret_key")?,
             created_at,
-            wallet_id: row.try_get(8)?,
-            wallet_balance: row.try_get(9)?,
+            wallet_id: row.try_get("wallet_id")?,
+            wallet_balance: row.try_get("wallet_balance")?,
             user_type: user_role,
             user_status,
+            wallet_balance_updated_at: row.try_get("wallet_balance_updated_at")?,
+            deleted_at: row.try_get("deleted_at")?,
+            wallet_flagged: row.try_get("wallet_flagged")?,
+            seller_slug: row.try_get("seller_slug")?,
+            locale: row.try_get("locale")?,
+            seller_status,
+            seller_rejection_reason: row.try_get("seller_rejection_reason")?,
+            event_reminders_opt_out: row.try_get("event_reminders_opt_out")?,
+            password_changed_at: row.try_get("password_changed_at")?,
         };
         Ok(user)
     }
 }
+
+impl Table for DbUser {
+    const TABLE: &'static str = "users";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "username",
+        "phone_number",
+        "email",
+        "password",
+        "encrypted_secret_key",
+        "created_at",
+        "wallet_id",
+        "wallet_balance",
+        "user_type",
+        "user_status",
+        "wallet_balance_updated_at",
+        "deleted_at",
+        "wallet_flagged",
+        "seller_slug",
+        "locale",
+        "seller_status",
+        "seller_rejection_reason",
+        "event_reminders_opt_out",
+        "password_changed_at",
+    ];
+}
 // ------------EVENTS----------------
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -110,6 +190,36 @@ pub struct DbEvent {
     pub thumbnail_url: Option<String>,
     pub event_status: EventStatus,
     pub created_by_user: uuid::Uuid,
+    pub cover_photo_ipfs_url: Option<String>,
+    pub thumbnail_ipfs_url: Option<String>,
+    pub archived: bool,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub category_id: Option<uuid::Uuid>,
+    pub thumbnail_variant_url: Option<String>,
+    pub cover_variant_url: Option<String>,
+    pub og_image_url: Option<String>,
+    pub organization_id: Option<uuid::Uuid>,
+    /// the venue's coordinates, both set or none
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// hours before the start until which buyers may cancel, buyers cannot cancel if not set
+    pub cancellation_window_hours: Option<i32>,
+    /// when the event, its tickets, tags, price tiers or gallery last changed, kept by triggers
+    pub modified_at: NaiveDateTime,
+    /// the series the event is an occurrence of
+    pub series_id: Option<uuid::Uuid>,
+    /// when the event was last submitted for review
+    pub review_submitted_at: Option<NaiveDateTime>,
+    /// why the event was last sent back by its reviewer
+    pub review_rejection_reason: Option<String>,
+}
+
+/// The `attempt`th slug to try for `slug`, attempts starting at 1: `slug`, `slug-2`, `slug-3`...
+pub fn suffixed_slug(slug: &str, attempt: usize) -> String {
+    match attempt {
+        1 => slug.to_string(),
+        n => format!("{}-{}", slug, n),
+    }
 }
 
 impl DbEvent {
@@ -131,6 +241,33 @@ impl DbEvent {
             thumbnail_url: None,
             event_status: EventStatus::Draft,
             created_by_user,
+            cover_photo_ipfs_url: None,
+            thumbnail_ipfs_url: None,
+            archived: false,
+            deleted_at: None,
+            category_id: None,
+            thumbnail_variant_url: None,
+            cover_variant_url: None,
+            og_image_url: None,
+            organization_id: None,
+            latitude: None,
+            longitude: None,
+            cancellation_window_hours: None,
+            modified_at: sql_timestamp(None),
+            series_id: None,
+            review_submitted_at: None,
+            review_rejection_reason: None,
+        }
+    }
+
+    /// Whether buyers may still cancel their reservations at `now`
+    pub fn is_cancellable_at(&self, now: &NaiveDateTime) -> bool {
+        match (self.cancellation_window_hours, self.start_date) {
+            (Some(window_hours), Some(start_date)) => {
+                self.event_status != EventStatus::Cancelled
+                    && *now <= start_date - chrono::Duration::hours(i64::from(window_hours))
+            }
+            _ => false,
         }
     }
 }
@@ -139,35 +276,151 @@ impl TryFrom<tokio_postgres::row::Row> for DbEvent {
     type Error = tokio_postgres::Error;
 
     fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
-        let start_date: Option<NaiveDateTime> = row.try_get(3).ok();
-        let end_date: Option<NaiveDateTime> = row.try_get(4).ok();
-        let entry_time: Option<NaiveDateTime> = row.try_get(5).ok();
-        let created_at: NaiveDateTime = row.try_get(6)?;
+        let start_date: Option<NaiveDateTime> = row.try_get("start_date")?;
+        let end_date: Option<NaiveDateTime> = row.try_get("end_date")?;
+        let entry_time: Option<NaiveDateTime> = row.try_get("entry_time")?;
+        let created_at: NaiveDateTime = row.try_get("created_at")?;
 
-        let event_status: i16 = row.try_get(14)?;
+        let event_status: i16 = row.try_get("event_status")?;
         let event_status =
             EventStatus::try_from(event_status).expect("must be a valid event status");
 
         Ok(DbEvent {
-            id: row.try_get(0)?,
-            event_name: row.try_get(1)?,
-            event_slug: row.try_get(2)?,
+            id: row.try_get("id")?,
+            event_name: row.try_get("event_name")?,
+            event_slug: row.try_get("event_slug")?,
             start_date,
             end_date,
             entry_time,
             created_at,
-            description: row.try_get(7).ok(),
-            is_virtual: row.try_get(8).ok(),
-            is_featured: row.try_get(9).ok(),
-            venue_name: row.try_get(10).ok(),
-            venue_location: row.try_get(11).ok(),
-            cover_photo_url: row.try_get(12).ok(),
-            thumbnail_url: row.try_get(13).ok(),
+            description: row.try_get("description")?,
+            is_virtual: row.try_get("is_virtual")?,
+            is_featured: row.try_get("is_featured")?,
+            venue_name: row.try_get("venue_name")?,
+            venue_location: row.try_get("venue_location")?,
+            cover_photo_url: row.try_get("cover_photo_url")?,
+            thumbnail_url: row.try_get("thumbnail_url")?,
             event_status,
-            created_by_user: row.try_get(15)?,
+            created_by_user: row.try_get("created_by_user")?,
+            cover_photo_ipfs_url: row.try_get("cover_photo_ipfs_url")?,
+            thumbnail_ipfs_url: row.try_get("thumbnail_ipfs_url")?,
+            archived: row.try_get("archived")?,
+            deleted_at: row.try_get("deleted_at")?,
+            category_id: row.try_get("category_id")?,
+            thumbnail_variant_url: row.try_get("thumbnail_variant_url")?,
+            cover_variant_url: row.try_get("cover_variant_url")?,
+            og_image_url: row.try_get("og_image_url")?,
+            organization_id: row.try_get("organization_id")?,
+            latitude: row.try_get("latitude")?,
+            longitude: row.try_get("longitude")?,
+            cancellation_window_hours: row.try_get("cancellation_window_hours")?,
+            modified_at: row.try_get("modified_at")?,
+            series_id: row.try_get("series_id")?,
+            review_submitted_at: row.try_get("review_submitted_at")?,
+            review_rejection_reason: row.try_get("review_rejection_reason")?,
+        })
+    }
+}
+
+impl Table for DbEvent {
+    const TABLE: &'static str = "events";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "event_name",
+        "event_slug",
+        "start_date",
+        "end_date",
+        "entry_time",
+        "created_at",
+        "description",
+        "is_virtual",
+        "is_featured",
+        "venue_name",
+        "venue_location",
+        "cover_photo_url",
+        "thumbnail_url",
+        "event_status",
+        "created_by_user",
+        "cover_photo_ipfs_url",
+        "thumbnail_ipfs_url",
+        "archived",
+        "deleted_at",
+        "category_id",
+        "thumbnail_variant_url",
+        "cover_variant_url",
+        "og_image_url",
+        "organization_id",
+        "latitude",
+        "longitude",
+        "cancellation_window_hours",
+        "modified_at",
+        "series_id",
+        "review_submitted_at",
+        "review_rejection_reason",
+    ];
+}
+
+// -------------EVENT SERIES----------------
+/// Recurring events, materialized as events starting at the occurrences of the rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbEventSeries {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    /// the name of the event the series repeats
+    pub series_name: String,
+    /// the recurrence rule, an RRULE subset
+    pub rrule: String,
+    /// the start of the first occurrence
+    pub starts_at: NaiveDateTime,
+    pub created_by_user: uuid::Uuid,
+}
+
+impl DbEventSeries {
+    pub fn new(
+        series_name: &str,
+        rrule: &str,
+        starts_at: NaiveDateTime,
+        created_by_user: uuid::Uuid,
+    ) -> Self {
+        DbEventSeries {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            series_name: series_name.to_string(),
+            rrule: rrule.to_string(),
+            starts_at,
+            created_by_user,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbEventSeries {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbEventSeries {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            series_name: row.try_get("series_name")?,
+            rrule: row.try_get("rrule")?,
+            starts_at: row.try_get("starts_at")?,
+            created_by_user: row.try_get("created_by_user")?,
         })
     }
 }
+
+impl Table for DbEventSeries {
+    const TABLE: &'static str = "event_series";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "series_name",
+        "rrule",
+        "starts_at",
+        "created_by_user",
+    ];
+}
+
 // -------------TICKETS----------------
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -177,16 +430,24 @@ pub struct DbTicket {
     pub ticket_name: String,
     pub ticket_slug: String,
     pub description: Option<String>,
-    pub price: Option<String>,
-    pub max_release_price: Option<String>,
+    pub price: Option<NearAmount>,
+    pub max_release_price: Option<NearAmount>,
     pub quantity_available: Option<i32>,
     pub min_purchase_quantity: Option<i32>,
     pub max_purchase_quantity: Option<i32>,
     pub allow_transfers: Option<bool>,
     pub event_id: uuid::Uuid,
+    pub archived: bool,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub currency: Currency,
+    pub quantity_reserved: i32,
+    /// bumped by every edit, an edit based on an older version is refused
+    pub version: i32,
 }
 
 impl DbTicket {
+    /// The ticket's slug is the event's slug followed by the ticket's name, a taken slug is given
+    /// a numeric suffix when the ticket is stored
     pub fn new(ticket: NewTicket, db_event: &DbEvent) -> Self {
         let ticket_slug = format!(
             "{}-{}",
@@ -206,7 +467,46 @@ impl DbTicket {
             max_purchase_quantity: ticket.max_purchase_quantity,
             allow_transfers: ticket.allow_transfers,
             event_id: db_event.id,
+            archived: false,
+            deleted_at: None,
+            currency: ticket.currency.unwrap_or_default(),
+            quantity_reserved: 0,
+            version: 0,
+        }
+    }
+
+    /// tickets left to reserve, `None` if the quantity is unlimited
+    pub fn quantity_remaining(&self) -> Option<i32> {
+        self.quantity_available
+            .map(|quantity_available| (quantity_available - self.quantity_reserved).max(0))
+    }
+
+    /// checks a user may reserve `quantity` more tickets, having already reserved `reserved_by_user`
+    pub fn check_purchase_quantity(
+        &self,
+        quantity: i32,
+        reserved_by_user: i64,
+    ) -> Result<(), TicketError> {
+        let ticket_id = self.id.to_string();
+        if quantity < 1 {
+            return Err(TicketError::InvalidQuantity(ticket_id));
+        }
+        if let Some(min_purchase_quantity) = self.min_purchase_quantity {
+            if quantity < min_purchase_quantity {
+                return Err(TicketError::BelowMinPurchaseQuantity(ticket_id));
+            }
+        }
+        if let Some(max_purchase_quantity) = self.max_purchase_quantity {
+            if reserved_by_user + i64::from(quantity) > i64::from(max_purchase_quantity) {
+                return Err(TicketError::AboveMaxPurchaseQuantity(ticket_id));
+            }
+        }
+        if let Some(quantity_remaining) = self.quantity_remaining() {
+            if quantity > quantity_remaining {
+                return Err(TicketError::InsufficientQuantity(ticket_id));
+            }
         }
+        Ok(())
     }
 }
 
@@ -214,25 +514,145 @@ impl TryFrom<tokio_postgres::row::Row> for DbTicket {
     type Error = tokio_postgres::Error;
 
     fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
-        let created_at: NaiveDateTime = row.try_get(1)?;
+        let created_at: NaiveDateTime = row.try_get("created_at")?;
+        let currency: i16 = row.try_get("currency")?;
+        let currency = Currency::try_from(currency).expect("must be a valid currency");
 
         Ok(DbTicket {
-            id: row.try_get(0)?,
+            id: row.try_get("id")?,
             created_at,
-            ticket_name: row.try_get(2)?,
-            ticket_slug: row.try_get(3)?,
-            description: row.try_get(4).ok(),
-            price: row.try_get(5).ok(),
-            max_release_price: row.try_get(6).ok(),
-            quantity_available: row.try_get(7).ok(),
-            min_purchase_quantity: row.try_get(8).ok(),
-            max_purchase_quantity: row.try_get(9).ok(),
-            allow_transfers: row.try_get(10).ok(),
-            event_id: row.try_get(11)?,
+            ticket_name: row.try_get("ticket_name")?,
+            ticket_slug: row.try_get("ticket_slug")?,
+            description: row.try_get("description")?,
+            price: row.try_get("price")?,
+            max_release_price: row.try_get("max_release_price")?,
+            quantity_available: row.try_get("quantity_available")?,
+            min_purchase_quantity: row.try_get("min_purchase_quantity")?,
+            max_purchase_quantity: row.try_get("max_purchase_quantity")?,
+            allow_transfers: row.try_get("allow_transfers")?,
+            event_id: row.try_get("event_id")?,
+            archived: row.try_get("archived")?,
+            deleted_at: row.try_get("deleted_at")?,
+            currency,
+            quantity_reserved: row.try_get("quantity_reserved")?,
+            version: row.try_get("version")?,
+        })
+    }
+}
+
+impl Table for DbTicket {
+    const TABLE: &'static str = "tickets";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "ticket_name",
+        "ticket_slug",
+        "description",
+        "price",
+        "max_release_price",
+        "quantity_available",
+        "min_purchase_quantity",
+        "max_purchase_quantity",
+        "allow_transfers",
+        "event_id",
+        "archived",
+        "deleted_at",
+        "currency",
+        "quantity_reserved",
+        "version",
+    ];
+}
+
+// -------------TICKET PRICE TIERS---------------
+/// A price of a ticket for a time window, e.g. an early-bird price, optionally capped to a
+/// quantity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbTicketPriceTier {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub ticket_id: uuid::Uuid,
+    pub event_id: uuid::Uuid,
+    pub tier_name: String,
+    pub price: NearAmount,
+    pub starts_at: NaiveDateTime,
+    pub ends_at: Option<NaiveDateTime>,
+    pub quantity_cap: Option<i32>,
+    pub quantity_sold: i32,
+}
+
+impl DbTicketPriceTier {
+    pub fn new(price_tier: NewPriceTier, db_ticket: &DbTicket) -> Self {
+        DbTicketPriceTier {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            ticket_id: db_ticket.id,
+            event_id: db_ticket.event_id,
+            tier_name: price_tier.tier_name.trim().to_string(),
+            price: price_tier.price,
+            starts_at: price_tier.starts_at,
+            ends_at: price_tier.ends_at,
+            quantity_cap: price_tier.quantity_cap,
+            quantity_sold: 0,
+        }
+    }
+
+    /// checks whether the tier's window contains the given time
+    pub fn is_open_at(&self, now: &NaiveDateTime) -> bool {
+        let started = self.starts_at.le(now);
+        let not_ended = self.ends_at.map(|ends_at| ends_at.gt(now)).unwrap_or(true);
+        started && not_ended
+    }
+
+    /// tickets left at the tier's price, `None` if it is not capped
+    pub fn quantity_remaining(&self) -> Option<i32> {
+        self.quantity_cap
+            .map(|quantity_cap| (quantity_cap - self.quantity_sold).max(0))
+    }
+
+    /// checks whether `quantity` tickets may still be sold at the tier's price
+    pub fn has_quantity_for(&self, quantity: i32) -> bool {
+        self.quantity_remaining()
+            .map(|quantity_remaining| quantity <= quantity_remaining)
+            .unwrap_or(true)
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbTicketPriceTier {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbTicketPriceTier {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            ticket_id: row.try_get("ticket_id")?,
+            event_id: row.try_get("event_id")?,
+            tier_name: row.try_get("tier_name")?,
+            price: row.try_get("price")?,
+            starts_at: row.try_get("starts_at")?,
+            ends_at: row.try_get("ends_at")?,
+            quantity_cap: row.try_get("quantity_cap")?,
+            quantity_sold: row.try_get("quantity_sold")?,
         })
     }
 }
 
+impl Table for DbTicketPriceTier {
+    const TABLE: &'static str = "ticket_price_tiers";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "ticket_id",
+        "event_id",
+        "tier_name",
+        "price",
+        "starts_at",
+        "ends_at",
+        "quantity_cap",
+        "quantity_sold",
+    ];
+}
+
 // -------------SELLER LOGIN SESSIONS---------------
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -266,42 +686,62 @@ impl TryFrom<tokio_postgres::row::Row> for DbSession {
     type Error = tokio_postgres::Error;
 
     fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
-        let expires_at: NaiveDateTime = row.try_get(1)?;
+        let expires_at: NaiveDateTime = row.try_get("expires_at")?;
         Ok(DbSession {
-            id: row.try_get(0)?,
+            id: row.try_get("id")?,
             expires_at,
-            login_code: row.try_get(2)?,
-            is_used: row.try_get(3)?,
-            user_id: row.try_get(4).ok(),
+            login_code: row.try_get("login_code")?,
+            is_used: row.try_get("is_used")?,
+            user_id: row.try_get("user_id")?,
         })
     }
 }
 
+impl Table for DbSession {
+    const TABLE: &'static str = "sessions";
+    const FIELDS: &'static [&'static str] =
+        &["id", "expires_at", "login_code", "is_used", "user_id"];
+}
+
 // -------------BUYER SIGNUP SESSIONS---------------
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DbBuyerSignupSession {
     pub id: uuid::Uuid,
     pub created_at: NaiveDateTime,
-    pub verification_code: String,
+    pub verification_code_hash: String,
     pub phone_number: String,
     pub is_verified: bool,
+    pub expires_at: NaiveDateTime,
+    pub attempts: i32,
+    pub is_consumed: bool,
+    pub resend_count: i32,
+    pub last_sent_at: NaiveDateTime,
+    /// the locale the buyer picked, kept on their account once signed up
+    pub locale: Option<String>,
 }
 
 impl DbBuyerSignupSession {
     pub fn new(
         id: uuid::Uuid,
         created_at: NaiveDateTime,
-        verification_code: String,
+        verification_code_hash: String,
         phone_number: String,
         is_verified: bool,
+        expires_at: NaiveDateTime,
     ) -> Self {
         DbBuyerSignupSession {
             id,
             created_at,
-            verification_code,
+            verification_code_hash,
             phone_number,
             is_verified,
+            expires_at,
+            attempts: 0,
+            is_consumed: false,
+            resend_count: 0,
+            last_sent_at: created_at,
+            locale: None,
         }
     }
 }
@@ -310,45 +750,75 @@ impl TryFrom<tokio_postgres::row::Row> for DbBuyerSignupSession {
     type Error = tokio_postgres::Error;
 
     fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
-        let created_at: NaiveDateTime = row.try_get(1)?;
+        let created_at: NaiveDateTime = row.try_get("created_at")?;
         Ok(DbBuyerSignupSession {
-            id: row.try_get(0)?,
+            id: row.try_get("id")?,
             created_at,
-            verification_code: row.try_get(2)?,
-            phone_number: row.try_get(3)?,
-            is_verified: row.try_get(4)?,
+            verification_code_hash: row.try_get("verification_code_hash")?,
+            phone_number: row.try_get("phone_number")?,
+            is_verified: row.try_get("is_verified")?,
+            expires_at: row.try_get("expires_at")?,
+            attempts: row.try_get("attempts")?,
+            is_consumed: row.try_get("is_consumed")?,
+            resend_count: row.try_get("resend_count")?,
+            last_sent_at: row.try_get("last_sent_at")?,
+            locale: row.try_get("locale")?,
         })
     }
 }
 
+impl Table for DbBuyerSignupSession {
+    const TABLE: &'static str = "buyer_signup_sessions";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "verification_code_hash",
+        "phone_number",
+        "is_verified",
+        "expires_at",
+        "attempts",
+        "is_consumed",
+        "resend_count",
+        "last_sent_at",
+        "locale",
+    ];
+}
+
 // -------------BUYER RECOVERY SESSIONS---------------
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DbBuyerRecoverySession {
     pub id: uuid::Uuid,
     pub created_at: NaiveDateTime,
-    pub recovery_code: String,
+    pub recovery_code_hash: String,
     pub phone_number: String,
     pub is_recovered: bool,
     pub created_by_user: uuid::Uuid,
+    pub expires_at: NaiveDateTime,
+    pub attempts: i32,
+    pub is_consumed: bool,
 }
 
 impl DbBuyerRecoverySession {
     pub fn new(
         id: uuid::Uuid,
         created_at: NaiveDateTime,
-        recovery_code: String,
+        recovery_code_hash: String,
         phone_number: String,
         is_recovered: bool,
         created_by_user: uuid::Uuid,
+        expires_at: NaiveDateTime,
     ) -> Self {
         DbBuyerRecoverySession {
             id,
             created_at,
-            recovery_code,
+            recovery_code_hash,
             phone_number,
             is_recovered,
             created_by_user,
+            expires_at,
+            attempts: 0,
+            is_consumed: false,
         }
     }
 }
@@ -357,18 +827,278 @@ impl TryFrom<tokio_postgres::row::Row> for DbBuyerRecoverySession {
     type Error = tokio_postgres::Error;
 
     fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
-        let created_at: NaiveDateTime = row.try_get(1)?;
+        let created_at: NaiveDateTime = row.try_get("created_at")?;
         Ok(DbBuyerRecoverySession {
-            id: row.try_get(0)?,
+            id: row.try_get("id")?,
             created_at,
-            recovery_code: row.try_get(2)?,
-            phone_number: row.try_get(3)?,
-            is_recovered: row.try_get(4)?,
-            created_by_user: row.try_get(5)?,
+            recovery_code_hash: row.try_get("recovery_code_hash")?,
+            phone_number: row.try_get("phone_number")?,
+            is_recovered: row.try_get("is_recovered")?,
+            created_by_user: row.try_get("created_by_user")?,
+            expires_at: row.try_get("expires_at")?,
+            attempts: row.try_get("attempts")?,
+            is_consumed: row.try_get("is_consumed")?,
+        })
+    }
+}
+
+impl Table for DbBuyerRecoverySession {
+    const TABLE: &'static str = "buyer_recovery_sessions";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "recovery_code_hash",
+        "phone_number",
+        "is_recovered",
+        "created_by_user",
+        "expires_at",
+        "attempts",
+        "is_consumed",
+    ];
+}
+
+// -------------SIGNIN CHALLENGES---------------
+/// A nonce a seller signs with their wallet key to sign in, used once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbSigninChallenge {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub wallet_id: String,
+    pub nonce: String,
+    pub expires_at: NaiveDateTime,
+    pub is_consumed: bool,
+}
+
+impl DbSigninChallenge {
+    pub fn new(wallet_id: String, nonce: String, expires_at: NaiveDateTime) -> Self {
+        DbSigninChallenge {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            wallet_id,
+            nonce,
+            expires_at,
+            is_consumed: false,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbSigninChallenge {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbSigninChallenge {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            wallet_id: row.try_get("wallet_id")?,
+            nonce: row.try_get("nonce")?,
+            expires_at: row.try_get("expires_at")?,
+            is_consumed: row.try_get("is_consumed")?,
+        })
+    }
+}
+
+impl Table for DbSigninChallenge {
+    const TABLE: &'static str = "signin_challenges";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "wallet_id",
+        "nonce",
+        "expires_at",
+        "is_consumed",
+    ];
+}
+
+/// A super admin acting as another user, e.g. to reproduce their issues
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbImpersonation {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub admin_id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub expires_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+impl DbImpersonation {
+    pub fn new(admin_id: uuid::Uuid, user_id: uuid::Uuid, expires_at: NaiveDateTime) -> Self {
+        DbImpersonation {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            admin_id,
+            user_id,
+            expires_at,
+            revoked_at: None,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbImpersonation {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbImpersonation {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            admin_id: row.try_get("admin_id")?,
+            user_id: row.try_get("user_id")?,
+            expires_at: row.try_get("expires_at")?,
+            revoked_at: row.try_get("revoked_at")?,
+        })
+    }
+}
+
+impl Table for DbImpersonation {
+    const TABLE: &'static str = "impersonations";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "admin_id",
+        "user_id",
+        "expires_at",
+        "revoked_at",
+    ];
+}
+
+/// The totp secret of a user, two-factor authentication is on once it is enabled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbUserTotp {
+    pub user_id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub encrypted_secret: String,
+    pub enabled_at: Option<NaiveDateTime>,
+    /// the step of the last accepted code, a code is accepted once only
+    pub last_used_step: i64,
+}
+
+impl DbUserTotp {
+    pub fn new(user_id: uuid::Uuid, encrypted_secret: String) -> Self {
+        DbUserTotp {
+            user_id,
+            created_at: sql_timestamp(None),
+            encrypted_secret,
+            enabled_at: None,
+            last_used_step: 0,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbUserTotp {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbUserTotp {
+            user_id: row.try_get("user_id")?,
+            created_at: row.try_get("created_at")?,
+            encrypted_secret: row.try_get("encrypted_secret")?,
+            enabled_at: row.try_get("enabled_at")?,
+            last_used_step: row.try_get("last_used_step")?,
+        })
+    }
+}
+
+impl Table for DbUserTotp {
+    const TABLE: &'static str = "user_totps";
+    const FIELDS: &'static [&'static str] = &[
+        "user_id",
+        "created_at",
+        "encrypted_secret",
+        "enabled_at",
+        "last_used_step",
+    ];
+}
+
+/// The second step of a password sign-in of a user with two-factor authentication
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbTotpChallenge {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub user_id: uuid::Uuid,
+    pub expires_at: NaiveDateTime,
+    pub attempts: i32,
+    pub is_consumed: bool,
+}
+
+impl DbTotpChallenge {
+    pub fn new(user_id: uuid::Uuid, expires_at: NaiveDateTime) -> Self {
+        DbTotpChallenge {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            user_id,
+            expires_at,
+            attempts: 0,
+            is_consumed: false,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbTotpChallenge {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbTotpChallenge {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            user_id: row.try_get("user_id")?,
+            expires_at: row.try_get("expires_at")?,
+            attempts: row.try_get("attempts")?,
+            is_consumed: row.try_get("is_consumed")?,
+        })
+    }
+}
+
+impl Table for DbTotpChallenge {
+    const TABLE: &'static str = "totp_challenges";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "user_id",
+        "expires_at",
+        "attempts",
+        "is_consumed",
+    ];
+}
+
+/// The recent failed password sign-ins of a user from an address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbLoginAttempt {
+    pub user_id: uuid::Uuid,
+    pub ip_address: String,
+    pub failed_attempts: i32,
+    pub last_failed_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbLoginAttempt {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbLoginAttempt {
+            user_id: row.try_get("user_id")?,
+            ip_address: row.try_get("ip_address")?,
+            failed_attempts: row.try_get("failed_attempts")?,
+            last_failed_at: row.try_get("last_failed_at")?,
+            expires_at: row.try_get("expires_at")?,
         })
     }
 }
 
+impl Table for DbLoginAttempt {
+    const TABLE: &'static str = "login_attempts";
+    const FIELDS: &'static [&'static str] = &[
+        "user_id",
+        "ip_address",
+        "failed_attempts",
+        "last_failed_at",
+        "expires_at",
+    ];
+}
+
 // ------------TICKET RESERVATIONS----------------
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -379,6 +1109,10 @@ pub struct DbTicketReservation {
     pub event_id: uuid::Uuid,
     pub ticket_id: uuid::Uuid,
     pub user_id: uuid::Uuid,
+    pub quantity: i32,
+    pub checked_in_at: Option<NaiveDateTime>,
+    /// the tier the tickets were reserved at, the ticket's own price if not set
+    pub price_tier_id: Option<uuid::Uuid>,
 }
 
 impl DbTicketReservation {
@@ -389,6 +1123,7 @@ impl DbTicketReservation {
         event_id: uuid::Uuid,
         ticket_id: uuid::Uuid,
         user_id: uuid::Uuid,
+        quantity: i32,
     ) -> Self {
         DbTicketReservation {
             id,
@@ -397,6 +1132,9 @@ impl DbTicketReservation {
             event_id,
             ticket_id,
             user_id,
+            quantity,
+            checked_in_at: None,
+            price_tier_id: None,
         }
     }
 }
@@ -405,17 +1143,65 @@ impl TryFrom<tokio_postgres::row::Row> for DbTicketReservation {
     type Error = tokio_postgres::Error;
 
     fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
-        let created_at: NaiveDateTime = row.try_get(1)?;
+        let created_at: NaiveDateTime = row.try_get("created_at")?;
         Ok(DbTicketReservation {
-            id: row.try_get(0)?,
+            id: row.try_get("id")?,
             created_at,
-            verification_code: row.try_get(2)?,
-            event_id: row.try_get(3)?,
-            ticket_id: row.try_get(4)?,
-            user_id: row.try_get(5)?,
+            verification_code: row.try_get("verification_code")?,
+            event_id: row.try_get("event_id")?,
+            ticket_id: row.try_get("ticket_id")?,
+            user_id: row.try_get("user_id")?,
+            quantity: row.try_get("quantity")?,
+            checked_in_at: row.try_get("checked_in_at")?,
+            price_tier_id: row.try_get("price_tier_id")?,
+        })
+    }
+}
+
+impl Table for DbTicketReservation {
+    const TABLE: &'static str = "ticket_reservations";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "verification_code",
+        "event_id",
+        "ticket_id",
+        "user_id",
+        "quantity",
+        "checked_in_at",
+        "price_tier_id",
+    ];
+}
+
+/// A reservation of an event with its buyer and ticket, as listed to the event's seller
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbAttendee {
+    pub reservation_id: uuid::Uuid,
+    pub reserved_at: NaiveDateTime,
+    pub username: String,
+    pub phone_number: Option<String>,
+    pub ticket_name: String,
+    pub quantity: i32,
+    pub checked_in_at: Option<NaiveDateTime>,
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbAttendee {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbAttendee {
+            reservation_id: row.try_get("reservation_id")?,
+            reserved_at: row.try_get("reserved_at")?,
+            username: row.try_get("username")?,
+            phone_number: row.try_get("phone_number")?,
+            ticket_name: row.try_get("ticket_name")?,
+            quantity: row.try_get("quantity")?,
+            checked_in_at: row.try_get("checked_in_at")?,
         })
     }
 }
+
 // -----------S3 FILES-----------------
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -425,6 +1211,15 @@ pub struct AssetFile {
     pub s3_absolute_key: String,
     pub ipfs_hash: Option<String>,
     pub event_id: uuid::Uuid,
+    pub content_type: Option<String>,
+    pub is_confirmed: bool,
+    pub thumbnail_key: Option<String>,
+    pub cover_key: Option<String>,
+    pub og_image_key: Option<String>,
+    /// what the asset is to its event, none until it is attached
+    pub asset_role: Option<AssetRole>,
+    /// the position in the event's gallery, gallery assets only
+    pub position: Option<i32>,
 }
 
 impl AssetFile {
@@ -456,6 +1251,29 @@ impl AssetFile {
             s3_absolute_key: s3_absolute_key.into(),
             ipfs_hash,
             event_id,
+            content_type: None,
+            is_confirmed: true,
+            thumbnail_key: None,
+            cover_key: None,
+            og_image_key: None,
+            asset_role: None,
+            position: None,
+        }
+    }
+
+    /// An asset the client is about to upload through a presigned url. It is only attached
+    /// to its event once the upload is confirmed.
+    pub fn new_pending(
+        id: Uuid,
+        s3_bucket: impl Into<String>,
+        s3_absolute_key: impl Into<String>,
+        content_type: impl Into<String>,
+        event_id: uuid::Uuid,
+    ) -> Self {
+        Self {
+            content_type: Some(content_type.into()),
+            is_confirmed: false,
+            ..Self::new_with_id(id, s3_bucket, s3_absolute_key, None, event_id)
         }
     }
 }
@@ -464,12 +1282,2131 @@ impl TryFrom<tokio_postgres::row::Row> for AssetFile {
     type Error = tokio_postgres::Error;
 
     fn try_from(value: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let asset_role: Option<i16> = value.try_get("asset_role")?;
+        let asset_role = asset_role
+            .map(|asset_role| AssetRole::try_from(asset_role).expect("must be a valid asset role"));
+
         Ok(Self {
-            id: value.try_get(0)?,
-            s3_bucket: value.try_get(1)?,
-            s3_absolute_key: value.try_get(2)?,
-            ipfs_hash: value.try_get(3)?,
-            event_id: value.try_get(4)?,
+            id: value.try_get("id")?,
+            s3_bucket: value.try_get("s3_bucket")?,
+            s3_absolute_key: value.try_get("s3_absolute_key")?,
+            ipfs_hash: value.try_get("ipfs_hash")?,
+            event_id: value.try_get("event_id")?,
+            content_type: value.try_get("content_type")?,
+            is_confirmed: value.try_get("is_confirmed")?,
+            thumbnail_key: value.try_get("thumbnail_key")?,
+            cover_key: value.try_get("cover_key")?,
+            og_image_key: value.try_get("og_image_key")?,
+            asset_role,
+            position: value.try_get("position")?,
         })
     }
 }
+
+impl Table for AssetFile {
+    const TABLE: &'static str = "asset_files";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "s3_bucket",
+        "s3_absolute_key",
+        "ipfs_hash",
+        "event_id",
+        "content_type",
+        "is_confirmed",
+        "thumbnail_key",
+        "cover_key",
+        "og_image_key",
+        "asset_role",
+        "position",
+    ];
+}
+
+// -------------PROMO CODES----------------
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbPromoCode {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub code: String,
+    pub discount_type: DiscountType,
+    pub discount_value: String,
+    pub max_uses: Option<i32>,
+    pub used_count: i32,
+    pub valid_from: Option<NaiveDateTime>,
+    pub valid_until: Option<NaiveDateTime>,
+    pub is_active: bool,
+    pub event_id: uuid::Uuid,
+    pub created_by_user: uuid::Uuid,
+}
+
+impl DbPromoCode {
+    pub fn new(
+        promo_code: NewPromoCode,
+        event_id: uuid::Uuid,
+        created_by_user: uuid::Uuid,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            code: promo_code.code.to_uppercase(),
+            discount_type: promo_code.discount_type,
+            discount_value: promo_code.discount_value,
+            max_uses: promo_code.max_uses,
+            used_count: 0,
+            valid_from: promo_code.valid_from,
+            valid_until: promo_code.valid_until,
+            is_active: true,
+            event_id,
+            created_by_user,
+        }
+    }
+
+    /// checks whether the code is within its validity window at the given time
+    pub fn is_valid_at(&self, now: &NaiveDateTime) -> bool {
+        let started = self.valid_from.map(|from| from.le(now)).unwrap_or(true);
+        let not_ended = self.valid_until.map(|until| until.gt(now)).unwrap_or(true);
+        started && not_ended
+    }
+
+    /// checks whether the code still has uses left
+    pub fn has_uses_left(&self) -> bool {
+        self.max_uses
+            .map(|max_uses| self.used_count < max_uses)
+            .unwrap_or(true)
+    }
+
+    /// applies the discount to a price, never going below zero
+    pub fn apply_discount(&self, price: NearAmount) -> NearAmount {
+        match self.discount_type {
+            DiscountType::Percentage => {
+                // percentages may have up to two decimals, e.g. 12.5
+                let basis_points = parse_basis_points(&self.discount_value).unwrap_or_default();
+                price.saturating_sub(price.basis_points(basis_points))
+            }
+            DiscountType::Fixed => {
+                let discount_value = self
+                    .discount_value
+                    .parse::<NearAmount>()
+                    .unwrap_or_default();
+                price.saturating_sub(discount_value)
+            }
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbPromoCode {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let created_at: NaiveDateTime = row.try_get("created_at")?;
+
+        let discount_type: i16 = row.try_get("discount_type")?;
+        let discount_type =
+            DiscountType::try_from(discount_type).expect("must be a valid discount type");
+
+        Ok(DbPromoCode {
+            id: row.try_get("id")?,
+            created_at,
+            code: row.try_get("code")?,
+            discount_type,
+            discount_value: row.try_get("discount_value")?,
+            max_uses: row.try_get("max_uses")?,
+            used_count: row.try_get("used_count")?,
+            valid_from: row.try_get("valid_from")?,
+            valid_until: row.try_get("valid_until")?,
+            is_active: row.try_get("is_active")?,
+            event_id: row.try_get("event_id")?,
+            created_by_user: row.try_get("created_by_user")?,
+        })
+    }
+}
+
+impl Table for DbPromoCode {
+    const TABLE: &'static str = "promo_codes";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "code",
+        "discount_type",
+        "discount_value",
+        "max_uses",
+        "used_count",
+        "valid_from",
+        "valid_until",
+        "is_active",
+        "event_id",
+        "created_by_user",
+    ];
+}
+
+// -------------PROMO CODE USAGES----------------
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbPromoCodeUsage {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub verification_code: String,
+    pub original_price: Option<NearAmount>,
+    pub discounted_price: Option<NearAmount>,
+    pub promo_code_id: uuid::Uuid,
+    pub ticket_id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+}
+
+impl DbPromoCodeUsage {
+    pub fn new(
+        verification_code: &str,
+        original_price: Option<NearAmount>,
+        discounted_price: Option<NearAmount>,
+        promo_code_id: uuid::Uuid,
+        ticket_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+    ) -> Self {
+        DbPromoCodeUsage {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            verification_code: verification_code.to_owned(),
+            original_price,
+            discounted_price,
+            promo_code_id,
+            ticket_id,
+            user_id,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbPromoCodeUsage {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let created_at: NaiveDateTime = row.try_get("created_at")?;
+        Ok(DbPromoCodeUsage {
+            id: row.try_get("id")?,
+            created_at,
+            verification_code: row.try_get("verification_code")?,
+            original_price: row.try_get("original_price")?,
+            discounted_price: row.try_get("discounted_price")?,
+            promo_code_id: row.try_get("promo_code_id")?,
+            ticket_id: row.try_get("ticket_id")?,
+            user_id: row.try_get("user_id")?,
+        })
+    }
+}
+
+impl Table for DbPromoCodeUsage {
+    const TABLE: &'static str = "promo_code_usages";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "verification_code",
+        "original_price",
+        "discounted_price",
+        "promo_code_id",
+        "ticket_id",
+        "user_id",
+    ];
+}
+
+// -------------TICKET TRANSFERS----------------
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbTicketTransfer {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub price: Option<NearAmount>,
+    pub tx_hash: String,
+    pub reservation_id: uuid::Uuid,
+    pub ticket_id: uuid::Uuid,
+    pub from_user: uuid::Uuid,
+    pub to_user: uuid::Uuid,
+}
+
+impl DbTicketTransfer {
+    pub fn new(
+        price: Option<NearAmount>,
+        tx_hash: String,
+        reservation_id: uuid::Uuid,
+        ticket_id: uuid::Uuid,
+        from_user: uuid::Uuid,
+        to_user: uuid::Uuid,
+    ) -> Self {
+        DbTicketTransfer {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            price,
+            tx_hash,
+            reservation_id,
+            ticket_id,
+            from_user,
+            to_user,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbTicketTransfer {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let created_at: NaiveDateTime = row.try_get("created_at")?;
+        Ok(DbTicketTransfer {
+            id: row.try_get("id")?,
+            created_at,
+            price: row.try_get("price")?,
+            tx_hash: row.try_get("tx_hash")?,
+            reservation_id: row.try_get("reservation_id")?,
+            ticket_id: row.try_get("ticket_id")?,
+            from_user: row.try_get("from_user")?,
+            to_user: row.try_get("to_user")?,
+        })
+    }
+}
+
+impl Table for DbTicketTransfer {
+    const TABLE: &'static str = "ticket_transfers";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "price",
+        "tx_hash",
+        "reservation_id",
+        "ticket_id",
+        "from_user",
+        "to_user",
+    ];
+}
+
+// -------------TICKET LISTINGS----------------
+/// A reservation its owner offers for resale, until cancelled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbTicketListing {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub asking_price: NearAmount,
+    pub reservation_id: uuid::Uuid,
+    pub ticket_id: uuid::Uuid,
+    pub event_id: uuid::Uuid,
+    pub seller_id: uuid::Uuid,
+    pub cancelled_at: Option<NaiveDateTime>,
+}
+
+impl DbTicketListing {
+    pub fn new(asking_price: NearAmount, db_reservation: &DbTicketReservation) -> Self {
+        DbTicketListing {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            asking_price,
+            reservation_id: db_reservation.id,
+            ticket_id: db_reservation.ticket_id,
+            event_id: db_reservation.event_id,
+            seller_id: db_reservation.user_id,
+            cancelled_at: None,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbTicketListing {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbTicketListing {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            asking_price: row.try_get("asking_price")?,
+            reservation_id: row.try_get("reservation_id")?,
+            ticket_id: row.try_get("ticket_id")?,
+            event_id: row.try_get("event_id")?,
+            seller_id: row.try_get("seller_id")?,
+            cancelled_at: row.try_get("cancelled_at")?,
+        })
+    }
+}
+
+impl Table for DbTicketListing {
+    const TABLE: &'static str = "ticket_listings";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "asking_price",
+        "reservation_id",
+        "ticket_id",
+        "event_id",
+        "seller_id",
+        "cancelled_at",
+    ];
+}
+
+// -------------TICKET GIFTS----------------
+/// A reservation bought for someone else, held by its buyer until the recipient claims it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbTicketGift {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub reservation_id: uuid::Uuid,
+    pub from_user: uuid::Uuid,
+    /// the recipient, when gifted by username
+    pub to_user: Option<uuid::Uuid>,
+    /// the phone number the claim code was sent to
+    pub to_phone_number: String,
+    pub claim_code: String,
+    pub claimed_by: Option<uuid::Uuid>,
+    pub claimed_at: Option<NaiveDateTime>,
+}
+
+impl DbTicketGift {
+    pub fn new(
+        reservation_id: uuid::Uuid,
+        from_user: uuid::Uuid,
+        to_user: Option<uuid::Uuid>,
+        to_phone_number: &str,
+        claim_code: &str,
+    ) -> Self {
+        DbTicketGift {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            reservation_id,
+            from_user,
+            to_user,
+            to_phone_number: to_phone_number.to_owned(),
+            claim_code: claim_code.to_owned(),
+            claimed_by: None,
+            claimed_at: None,
+        }
+    }
+
+    /// Whether the user is the recipient: the gifted user, or the owner of the gifted phone
+    pub fn is_for(&self, db_user: &DbUser) -> bool {
+        match self.to_user {
+            Some(to_user) => to_user.eq(&db_user.id),
+            None => db_user.phone_number.as_deref() == Some(self.to_phone_number.as_str()),
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbTicketGift {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbTicketGift {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            reservation_id: row.try_get("reservation_id")?,
+            from_user: row.try_get("from_user")?,
+            to_user: row.try_get("to_user")?,
+            to_phone_number: row.try_get("to_phone_number")?,
+            claim_code: row.try_get("claim_code")?,
+            claimed_by: row.try_get("claimed_by")?,
+            claimed_at: row.try_get("claimed_at")?,
+        })
+    }
+}
+
+impl Table for DbTicketGift {
+    const TABLE: &'static str = "ticket_gifts";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "reservation_id",
+        "from_user",
+        "to_user",
+        "to_phone_number",
+        "claim_code",
+        "claimed_by",
+        "claimed_at",
+    ];
+}
+
+// -------------SELLER DOCUMENTS----------------
+/// A document a seller uploads for their onboarding review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbSellerDocument {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub user_id: uuid::Uuid,
+    pub document_kind: DocumentKind,
+    pub s3_bucket: String,
+    pub s3_absolute_key: String,
+    pub content_type: String,
+    pub is_confirmed: bool,
+}
+
+impl DbSellerDocument {
+    /// A document pending until the seller confirms its upload
+    pub fn new_pending(
+        id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        document_kind: DocumentKind,
+        s3_bucket: String,
+        s3_absolute_key: String,
+        content_type: String,
+    ) -> Self {
+        DbSellerDocument {
+            id,
+            created_at: sql_timestamp(None),
+            user_id,
+            document_kind,
+            s3_bucket,
+            s3_absolute_key,
+            content_type,
+            is_confirmed: false,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbSellerDocument {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let document_kind: i16 = row.try_get("document_kind")?;
+        let document_kind =
+            DocumentKind::try_from(document_kind).expect("must be a valid document kind");
+
+        Ok(DbSellerDocument {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            user_id: row.try_get("user_id")?,
+            document_kind,
+            s3_bucket: row.try_get("s3_bucket")?,
+            s3_absolute_key: row.try_get("s3_absolute_key")?,
+            content_type: row.try_get("content_type")?,
+            is_confirmed: row.try_get("is_confirmed")?,
+        })
+    }
+}
+
+impl Table for DbSellerDocument {
+    const TABLE: &'static str = "seller_documents";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "user_id",
+        "document_kind",
+        "s3_bucket",
+        "s3_absolute_key",
+        "content_type",
+        "is_confirmed",
+    ];
+}
+
+// -------------JOBS----------------
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbJob {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub run_at: NaiveDateTime,
+    pub job_type: JobType,
+    pub payload: String,
+    pub job_status: JobStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub last_error: Option<String>,
+}
+
+impl DbJob {
+    pub fn new(payload: &JobPayload, max_attempts: i32) -> Self {
+        let now = sql_timestamp(None);
+        DbJob {
+            id: uuid::Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            run_at: now,
+            job_type: payload.job_type(),
+            payload: serde_json::to_string(payload).expect("job payload should serialize"),
+            job_status: JobStatus::Pending,
+            attempts: 0,
+            max_attempts,
+            last_error: None,
+        }
+    }
+
+    pub fn payload(&self) -> Result<JobPayload, serde_json::Error> {
+        serde_json::from_str(&self.payload)
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbJob {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let job_type: i16 = row.try_get("job_type")?;
+        let job_type = JobType::try_from(job_type).expect("must be a valid job type");
+        let job_status: i16 = row.try_get("job_status")?;
+        let job_status = JobStatus::try_from(job_status).expect("must be a valid job status");
+        Ok(DbJob {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            run_at: row.try_get("run_at")?,
+            job_type,
+            payload: row.try_get("payload")?,
+            job_status,
+            attempts: row.try_get("attempts")?,
+            max_attempts: row.try_get("max_attempts")?,
+            last_error: row.try_get("last_error")?,
+        })
+    }
+}
+
+impl Table for DbJob {
+    const TABLE: &'static str = "jobs";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "updated_at",
+        "run_at",
+        "job_type",
+        "payload",
+        "job_status",
+        "attempts",
+        "max_attempts",
+        "last_error",
+    ];
+}
+
+// -------------OUTBOX----------------
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbOutboxMessage {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub payload: String,
+    pub attempts: i32,
+    /// when the message is due, pushed back while it is being delivered or after a failure
+    pub available_at: NaiveDateTime,
+    pub dispatched_at: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+}
+
+impl DbOutboxMessage {
+    pub fn new(message: &OutboxMessage) -> Self {
+        let now = sql_timestamp(None);
+        DbOutboxMessage {
+            id: uuid::Uuid::new_v4(),
+            created_at: now,
+            payload: serde_json::to_string(message).expect("outbox message should serialize"),
+            attempts: 0,
+            available_at: now,
+            dispatched_at: None,
+            last_error: None,
+        }
+    }
+
+    pub fn message(&self) -> Result<OutboxMessage, serde_json::Error> {
+        serde_json::from_str(&self.payload)
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbOutboxMessage {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbOutboxMessage {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            payload: row.try_get("payload")?,
+            attempts: row.try_get("attempts")?,
+            available_at: row.try_get("available_at")?,
+            dispatched_at: row.try_get("dispatched_at")?,
+            last_error: row.try_get("last_error")?,
+        })
+    }
+}
+
+impl Table for DbOutboxMessage {
+    const TABLE: &'static str = "outbox";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "payload",
+        "attempts",
+        "available_at",
+        "dispatched_at",
+        "last_error",
+    ];
+}
+
+// -------------AUDIT LOG----------------
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbAuditLog {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub actor_id: Option<uuid::Uuid>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: uuid::Uuid,
+    pub diff: Option<String>,
+    pub impersonated_by: Option<uuid::Uuid>,
+}
+
+impl DbAuditLog {
+    pub fn new(
+        actor_id: Option<uuid::Uuid>,
+        impersonated_by: Option<uuid::Uuid>,
+        action: &str,
+        entity: &AuditEntity,
+        diff: Option<String>,
+    ) -> Self {
+        DbAuditLog {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            actor_id,
+            action: action.to_string(),
+            entity_type: entity.to_string(),
+            entity_id: entity.id(),
+            diff,
+            impersonated_by,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbAuditLog {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let created_at: NaiveDateTime = row.try_get("created_at")?;
+        Ok(DbAuditLog {
+            id: row.try_get("id")?,
+            created_at,
+            actor_id: row.try_get("actor_id")?,
+            action: row.try_get("action")?,
+            entity_type: row.try_get("entity_type")?,
+            entity_id: row.try_get("entity_id")?,
+            diff: row.try_get("diff")?,
+            impersonated_by: row.try_get("impersonated_by")?,
+        })
+    }
+}
+
+impl Table for DbAuditLog {
+    const TABLE: &'static str = "audit_log";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "actor_id",
+        "action",
+        "entity_type",
+        "entity_id",
+        "diff",
+        "impersonated_by",
+    ];
+}
+
+// -------------PAYOUTS----------------
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbPayoutAccount {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub wallet_id: String,
+    pub user_id: uuid::Uuid,
+}
+
+impl DbPayoutAccount {
+    pub fn new(wallet_id: &str, user_id: uuid::Uuid) -> Self {
+        let now = sql_timestamp(None);
+        DbPayoutAccount {
+            id: uuid::Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            wallet_id: wallet_id.to_string(),
+            user_id,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbPayoutAccount {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbPayoutAccount {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            wallet_id: row.try_get("wallet_id")?,
+            user_id: row.try_get("user_id")?,
+        })
+    }
+}
+
+impl Table for DbPayoutAccount {
+    const TABLE: &'static str = "payout_accounts";
+    const FIELDS: &'static [&'static str] =
+        &["id", "created_at", "updated_at", "wallet_id", "user_id"];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbPayoutRequest {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub amount: NearAmount,
+    pub wallet_id: String,
+    pub payout_status: PayoutStatus,
+    pub reviewed_by: Option<uuid::Uuid>,
+    pub reviewed_at: Option<NaiveDateTime>,
+    pub tx_hash: Option<String>,
+    pub user_id: uuid::Uuid,
+}
+
+impl DbPayoutRequest {
+    pub fn new(amount: NearAmount, wallet_id: &str, user_id: uuid::Uuid) -> Self {
+        DbPayoutRequest {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            amount,
+            wallet_id: wallet_id.to_string(),
+            payout_status: PayoutStatus::Pending,
+            reviewed_by: None,
+            reviewed_at: None,
+            tx_hash: None,
+            user_id,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbPayoutRequest {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let payout_status: i16 = row.try_get("payout_status")?;
+        let payout_status =
+            PayoutStatus::try_from(payout_status).expect("must be a valid payout status");
+
+        Ok(DbPayoutRequest {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            amount: row.try_get("amount")?,
+            wallet_id: row.try_get("wallet_id")?,
+            payout_status,
+            reviewed_by: row.try_get("reviewed_by")?,
+            reviewed_at: row.try_get("reviewed_at")?,
+            tx_hash: row.try_get("tx_hash")?,
+            user_id: row.try_get("user_id")?,
+        })
+    }
+}
+
+impl Table for DbPayoutRequest {
+    const TABLE: &'static str = "payout_requests";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "amount",
+        "wallet_id",
+        "payout_status",
+        "reviewed_by",
+        "reviewed_at",
+        "tx_hash",
+        "user_id",
+    ];
+}
+
+/// A seller's sales against the payouts requested so far
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbPayoutBalance {
+    pub total_sales: NearAmount,
+    pub total_requested: NearAmount,
+}
+
+impl DbPayoutBalance {
+    /// the amount that can still be requested
+    pub fn available(&self) -> NearAmount {
+        self.total_sales.saturating_sub(self.total_requested)
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbPayoutBalance {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbPayoutBalance {
+            total_sales: row.try_get("total_sales")?,
+            total_requested: row.try_get("total_requested")?,
+        })
+    }
+}
+
+// -------------ORGANIZATIONS----------------
+/// A team of sellers managing events together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbOrganization {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub name: String,
+    pub slug: String,
+    pub created_by_user: uuid::Uuid,
+}
+
+impl DbOrganization {
+    pub fn new(name: &str, created_by_user: uuid::Uuid) -> Self {
+        DbOrganization {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            name: name.to_string(),
+            slug: slugify!(name, separator = "-"),
+            created_by_user,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbOrganization {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbOrganization {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            name: row.try_get("name")?,
+            slug: row.try_get("slug")?,
+            created_by_user: row.try_get("created_by_user")?,
+        })
+    }
+}
+
+impl Table for DbOrganization {
+    const TABLE: &'static str = "organizations";
+    const FIELDS: &'static [&'static str] =
+        &["id", "created_at", "name", "slug", "created_by_user"];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbOrganizationMember {
+    pub organization_id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub member_role: MemberRole,
+    pub created_at: NaiveDateTime,
+}
+
+impl DbOrganizationMember {
+    pub fn new(organization_id: uuid::Uuid, user_id: uuid::Uuid, member_role: MemberRole) -> Self {
+        DbOrganizationMember {
+            organization_id,
+            user_id,
+            member_role,
+            created_at: sql_timestamp(None),
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbOrganizationMember {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let member_role: i16 = row.try_get("member_role")?;
+        let member_role = MemberRole::try_from(member_role).expect("must be a valid member role");
+
+        Ok(DbOrganizationMember {
+            organization_id: row.try_get("organization_id")?,
+            user_id: row.try_get("user_id")?,
+            member_role,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl Table for DbOrganizationMember {
+    const TABLE: &'static str = "organization_members";
+    const FIELDS: &'static [&'static str] =
+        &["organization_id", "user_id", "member_role", "created_at"];
+}
+
+// -------------EVENT COLLABORATORS----------------
+/// A seller other than the creator who edits or checks in at the event, per their role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbEventCollaborator {
+    pub event_id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub collaborator_role: MemberRole,
+    pub created_at: NaiveDateTime,
+}
+
+impl DbEventCollaborator {
+    pub fn new(event_id: uuid::Uuid, user_id: uuid::Uuid, collaborator_role: MemberRole) -> Self {
+        DbEventCollaborator {
+            event_id,
+            user_id,
+            collaborator_role,
+            created_at: sql_timestamp(None),
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbEventCollaborator {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let collaborator_role: i16 = row.try_get("collaborator_role")?;
+        let collaborator_role =
+            MemberRole::try_from(collaborator_role).expect("must be a valid member role");
+
+        Ok(DbEventCollaborator {
+            event_id: row.try_get("event_id")?,
+            user_id: row.try_get("user_id")?,
+            collaborator_role,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl Table for DbEventCollaborator {
+    const TABLE: &'static str = "event_collaborators";
+    const FIELDS: &'static [&'static str] =
+        &["event_id", "user_id", "collaborator_role", "created_at"];
+}
+
+// -------------MINT JOBS----------------
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbMintJob {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub event_id: uuid::Uuid,
+    pub ticket_id: uuid::Uuid,
+    pub wallet_id: String,
+    pub tx_hash: String,
+    pub number_of_tickets: i32,
+    pub mint_status: MintStatus,
+    pub attempts: i32,
+    pub checked_at: Option<NaiveDateTime>,
+    pub error: Option<String>,
+}
+
+impl DbMintJob {
+    /// A pending mint of the ticket submitted from `wallet_id` in transaction `tx_hash`
+    pub fn new(
+        db_ticket: &DbTicket,
+        wallet_id: &str,
+        tx_hash: &str,
+        number_of_tickets: i32,
+    ) -> Self {
+        let created_at = sql_timestamp(None);
+        DbMintJob {
+            id: uuid::Uuid::new_v4(),
+            created_at,
+            updated_at: created_at,
+            event_id: db_ticket.event_id,
+            ticket_id: db_ticket.id,
+            wallet_id: wallet_id.to_string(),
+            tx_hash: tx_hash.to_string(),
+            number_of_tickets,
+            mint_status: MintStatus::Pending,
+            attempts: 0,
+            checked_at: None,
+            error: None,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbMintJob {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let mint_status: i16 = row.try_get("mint_status")?;
+        let mint_status = MintStatus::try_from(mint_status).expect("must be a valid mint status");
+
+        Ok(DbMintJob {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            event_id: row.try_get("event_id")?,
+            ticket_id: row.try_get("ticket_id")?,
+            wallet_id: row.try_get("wallet_id")?,
+            tx_hash: row.try_get("tx_hash")?,
+            number_of_tickets: row.try_get("number_of_tickets")?,
+            mint_status,
+            attempts: row.try_get("attempts")?,
+            checked_at: row.try_get("checked_at")?,
+            error: row.try_get("error")?,
+        })
+    }
+}
+
+impl Table for DbMintJob {
+    const TABLE: &'static str = "mint_jobs";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "updated_at",
+        "event_id",
+        "ticket_id",
+        "wallet_id",
+        "tx_hash",
+        "number_of_tickets",
+        "mint_status",
+        "attempts",
+        "checked_at",
+        "error",
+    ];
+}
+
+// -------------SIGNUP ATTEMPTS----------------
+/// The progress of a buyer signup, keeping what the user is created from until they are
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbSignupAttempt {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub session_id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub name: Option<String>,
+    pub username: String,
+    pub phone_number: String,
+    pub email: Option<String>,
+    /// the password hash
+    pub password: Option<String>,
+    pub encrypted_secret_key: String,
+    pub locale: Option<String>,
+    pub wallet_id: String,
+    pub public_key: String,
+    pub wallet_created_at: Option<NaiveDateTime>,
+    pub tx_hash: Option<String>,
+    pub signup_status: SignupStatus,
+    pub attempts: i32,
+    pub next_attempt_at: NaiveDateTime,
+    pub error: Option<String>,
+}
+
+impl DbSignupAttempt {
+    /// A pending signup of the session's buyer as `db_user`, with a wallet controlled by
+    /// `public_key`. It is not retried before `next_attempt_at`
+    pub fn new(
+        session_id: uuid::Uuid,
+        db_user: &DbUser,
+        public_key: &str,
+        next_attempt_at: NaiveDateTime,
+    ) -> Self {
+        let created_at = sql_timestamp(None);
+        DbSignupAttempt {
+            id: uuid::Uuid::new_v4(),
+            created_at,
+            updated_at: created_at,
+            session_id,
+            user_id: db_user.id,
+            name: db_user.name.clone(),
+            username: db_user.username.clone(),
+            phone_number: db_user.phone_number.clone().unwrap_or_default(),
+            email: db_user.email.clone(),
+            password: db_user.password.clone(),
+            encrypted_secret_key: db_user.encrypted_secret_key.clone().unwrap_or_default(),
+            locale: db_user.locale.clone(),
+            wallet_id: db_user.wallet_id.clone(),
+            public_key: public_key.to_string(),
+            wallet_created_at: None,
+            tx_hash: None,
+            signup_status: SignupStatus::Pending,
+            attempts: 0,
+            next_attempt_at,
+            error: None,
+        }
+    }
+
+    /// The buyer the signup creates, holding the wallet's balance
+    pub fn user(&self, wallet_balance: String) -> DbUser {
+        DbUser {
+            locale: self.locale.clone(),
+            ..DbUser::new(
+                self.user_id,
+                self.name.clone(),
+                self.username.clone(),
+                Some(self.phone_number.clone()),
+                self.email.clone(),
+                self.password.clone(),
+                Some(self.encrypted_secret_key.clone()),
+                Role::Buyer,
+                self.wallet_id.clone(),
+                wallet_balance,
+                UserStatus::PhoneVerified,
+            )
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbSignupAttempt {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let signup_status: i16 = row.try_get("signup_status")?;
+        let signup_status =
+            SignupStatus::try_from(signup_status).expect("must be a valid signup status");
+
+        Ok(DbSignupAttempt {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            session_id: row.try_get("session_id")?,
+            user_id: row.try_get("user_id")?,
+            name: row.try_get("name")?,
+            username: row.try_get("username")?,
+            phone_number: row.try_get("phone_number")?,
+            email: row.try_get("email")?,
+            password: row.try_get("password")?,
+            encrypted_secret_key: row.try_get("encrypted_secret_key")?,
+            locale: row.try_get("locale")?,
+            wallet_id: row.try_get("wallet_id")?,
+            public_key: row.try_get("public_key")?,
+            wallet_created_at: row.try_get("wallet_created_at")?,
+            tx_hash: row.try_get("tx_hash")?,
+            signup_status,
+            attempts: row.try_get("attempts")?,
+            next_attempt_at: row.try_get("next_attempt_at")?,
+            error: row.try_get("error")?,
+        })
+    }
+}
+
+impl Table for DbSignupAttempt {
+    const TABLE: &'static str = "signup_attempts";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "updated_at",
+        "session_id",
+        "user_id",
+        "name",
+        "username",
+        "phone_number",
+        "email",
+        "password",
+        "encrypted_secret_key",
+        "locale",
+        "wallet_id",
+        "public_key",
+        "wallet_created_at",
+        "tx_hash",
+        "signup_status",
+        "attempts",
+        "next_attempt_at",
+        "error",
+    ];
+}
+
+// -------------CATEGORIES & TAGS----------------
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbCategory {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub name: String,
+    pub slug: String,
+}
+
+impl DbCategory {
+    pub fn new(name: &str) -> Self {
+        DbCategory {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            name: name.to_string(),
+            slug: slugify!(name, separator = "-"),
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbCategory {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbCategory {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            name: row.try_get("name")?,
+            slug: row.try_get("slug")?,
+        })
+    }
+}
+
+impl Table for DbCategory {
+    const TABLE: &'static str = "categories";
+    const FIELDS: &'static [&'static str] = &["id", "created_at", "name", "slug"];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbEventTag {
+    pub event_id: uuid::Uuid,
+    pub tag: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbEventTag {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbEventTag {
+            event_id: row.try_get("event_id")?,
+            tag: row.try_get("tag")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl Table for DbEventTag {
+    const TABLE: &'static str = "event_tags";
+    const FIELDS: &'static [&'static str] = &["event_id", "tag", "created_at"];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbTagCount {
+    pub tag: String,
+    pub event_count: i64,
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbTagCount {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbTagCount {
+            tag: row.try_get("tag")?,
+            event_count: row.try_get("event_count")?,
+        })
+    }
+}
+
+// -------------WAITLIST----------------
+/// A buyer waiting for a sold out ticket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbWaitlistEntry {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub ticket_id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    /// when the buyer was told a ticket is available again
+    pub notified_at: Option<NaiveDateTime>,
+}
+
+impl DbWaitlistEntry {
+    pub fn new(ticket_id: uuid::Uuid, user_id: uuid::Uuid) -> Self {
+        DbWaitlistEntry {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            ticket_id,
+            user_id,
+            notified_at: None,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbWaitlistEntry {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbWaitlistEntry {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            ticket_id: row.try_get("ticket_id")?,
+            user_id: row.try_get("user_id")?,
+            notified_at: row.try_get("notified_at")?,
+        })
+    }
+}
+
+impl Table for DbWaitlistEntry {
+    const TABLE: &'static str = "waitlist";
+    const FIELDS: &'static [&'static str] =
+        &["id", "created_at", "ticket_id", "user_id", "notified_at"];
+}
+
+/// A waitlisted ticket with tickets left to reserve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbWaitlistOpening {
+    pub ticket_id: uuid::Uuid,
+    pub event_id: uuid::Uuid,
+    /// tickets left to reserve, `None` if the quantity is unlimited
+    pub quantity_remaining: Option<i32>,
+    /// buyers notified recently, who may still reserve the tickets left
+    pub pending_offers: i64,
+}
+
+impl DbWaitlistOpening {
+    /// how many more waitlisted buyers to notify, at most `limit`
+    pub fn openings(&self, limit: i64) -> i64 {
+        self.quantity_remaining
+            .map_or(limit, |quantity_remaining| {
+                i64::from(quantity_remaining) - self.pending_offers
+            })
+            .clamp(0, limit)
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbWaitlistOpening {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbWaitlistOpening {
+            ticket_id: row.try_get("ticket_id")?,
+            event_id: row.try_get("event_id")?,
+            quantity_remaining: row.try_get("quantity_remaining")?,
+            pending_offers: row.try_get("pending_offers")?,
+        })
+    }
+}
+
+// -------------EVENT REMINDERS----------------
+/// A reminder sent to a ticket holder, at most one per event and reminder window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbEventReminder {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub event_id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    /// how long before the start of the event the reminder is sent
+    pub window_secs: i64,
+}
+
+impl DbEventReminder {
+    pub fn new(event_id: uuid::Uuid, user_id: uuid::Uuid, window_secs: i64) -> Self {
+        DbEventReminder {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            event_id,
+            user_id,
+            window_secs,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbEventReminder {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbEventReminder {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            event_id: row.try_get("event_id")?,
+            user_id: row.try_get("user_id")?,
+            window_secs: row.try_get("window_secs")?,
+        })
+    }
+}
+
+impl Table for DbEventReminder {
+    const TABLE: &'static str = "event_reminders";
+    const FIELDS: &'static [&'static str] =
+        &["id", "created_at", "event_id", "user_id", "window_secs"];
+}
+
+/// A holder of tickets for an event starting soon, who has not been reminded of it yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbReminderRecipient {
+    pub event_id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub phone_number: Option<String>,
+    pub locale: Option<String>,
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbReminderRecipient {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbReminderRecipient {
+            event_id: row.try_get("event_id")?,
+            user_id: row.try_get("user_id")?,
+            phone_number: row.try_get("phone_number")?,
+            locale: row.try_get("locale")?,
+        })
+    }
+}
+
+// -------------PERSISTED QUERIES----------------
+/// A graphql query clients send by its sha256 hash only, see [`crate::gql::persisted`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbPersistedQuery {
+    pub sha256_hash: String,
+    pub created_at: NaiveDateTime,
+    pub query: String,
+}
+
+impl DbPersistedQuery {
+    pub fn new(sha256_hash: &str, query: &str) -> Self {
+        DbPersistedQuery {
+            sha256_hash: sha256_hash.to_string(),
+            created_at: sql_timestamp(None),
+            query: query.to_string(),
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbPersistedQuery {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbPersistedQuery {
+            sha256_hash: row.try_get("sha256_hash")?,
+            created_at: row.try_get("created_at")?,
+            query: row.try_get("query")?,
+        })
+    }
+}
+
+impl Table for DbPersistedQuery {
+    const TABLE: &'static str = "persisted_queries";
+    const FIELDS: &'static [&'static str] = &["sha256_hash", "created_at", "query"];
+}
+
+// -------------WALLET TOP-UPS----------------
+/// NEAR sent to a buyer's custodial wallet to pay for its gas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbWalletTopUp {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub user_id: uuid::Uuid,
+    pub wallet_id: String,
+    pub amount: NearAmount,
+    /// set once the funding transaction is submitted
+    pub tx_hash: Option<String>,
+    /// the admin who triggered a manual top-up, `None` for automatic ones
+    pub triggered_by: Option<uuid::Uuid>,
+}
+
+impl DbWalletTopUp {
+    pub fn new(db_user: &DbUser, amount: NearAmount, triggered_by: Option<uuid::Uuid>) -> Self {
+        DbWalletTopUp {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            user_id: db_user.id,
+            wallet_id: db_user.wallet_id.clone(),
+            amount,
+            tx_hash: None,
+            triggered_by,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbWalletTopUp {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbWalletTopUp {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            user_id: row.try_get("user_id")?,
+            wallet_id: row.try_get("wallet_id")?,
+            amount: row.try_get("amount")?,
+            tx_hash: row.try_get("tx_hash")?,
+            triggered_by: row.try_get("triggered_by")?,
+        })
+    }
+}
+
+impl Table for DbWalletTopUp {
+    const TABLE: &'static str = "wallet_top_ups";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "user_id",
+        "wallet_id",
+        "amount",
+        "tx_hash",
+        "triggered_by",
+    ];
+}
+
+/// A page view of an event, folded into its daily stats by the analytics worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbEventView {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub event_id: uuid::Uuid,
+}
+
+impl DbEventView {
+    pub fn new(event_id: uuid::Uuid) -> Self {
+        DbEventView {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            event_id,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbEventView {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbEventView {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            event_id: row.try_get("event_id")?,
+        })
+    }
+}
+
+impl Table for DbEventView {
+    const TABLE: &'static str = "event_views";
+    const FIELDS: &'static [&'static str] = &["id", "created_at", "event_id"];
+}
+
+/// The counters of an event for a day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbEventDailyStats {
+    pub event_id: uuid::Uuid,
+    pub day: NaiveDate,
+    pub views: i32,
+    /// reservations of any of the event's tickets, free ones included
+    pub reservations: i32,
+    /// reservations of priced tickets
+    pub purchases: i32,
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbEventDailyStats {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbEventDailyStats {
+            event_id: row.try_get("event_id")?,
+            day: row.try_get("day")?,
+            views: row.try_get("views")?,
+            reservations: row.try_get("reservations")?,
+            purchases: row.try_get("purchases")?,
+        })
+    }
+}
+
+impl Table for DbEventDailyStats {
+    const TABLE: &'static str = "event_daily_stats";
+    const FIELDS: &'static [&'static str] =
+        &["event_id", "day", "views", "reservations", "purchases"];
+}
+
+// -------------SYSTEM METRICS----------------
+
+/// The number of users of a role and status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbUserCount {
+    pub user_type: Role,
+    pub user_status: UserStatus,
+    pub total: i32,
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbUserCount {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let user_role: i16 = row.try_get("user_type")?;
+        let user_status: i16 = row.try_get("user_status")?;
+        Ok(DbUserCount {
+            user_type: Role::try_from(user_role).expect("must be a valid role"),
+            user_status: UserStatus::try_from(user_status).expect("must be a valid user status"),
+            total: row.try_get("total")?,
+        })
+    }
+}
+
+/// The number of events of a status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbEventCount {
+    pub event_status: EventStatus,
+    pub total: i32,
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbEventCount {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let event_status: i16 = row.try_get("event_status")?;
+        Ok(DbEventCount {
+            event_status: EventStatus::try_from(event_status)
+                .expect("must be a valid event status"),
+            total: row.try_get("total")?,
+        })
+    }
+}
+
+/// The reservations made on a day, across all events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbDailyReservations {
+    pub day: NaiveDate,
+    pub reservations: i32,
+    /// the tickets reserved, a reservation may be of several
+    pub tickets: i32,
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbDailyReservations {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbDailyReservations {
+            day: row.try_get("day")?,
+            reservations: row.try_get("reservations")?,
+            tickets: row.try_get("tickets")?,
+        })
+    }
+}
+
+// -------------NOTIFICATIONS----------------
+/// A notification of a user's inbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbNotification {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub user_id: uuid::Uuid,
+    pub kind: NotificationKind,
+    pub message: String,
+    /// the details as json
+    pub data: Option<String>,
+    pub read_at: Option<NaiveDateTime>,
+}
+
+impl DbNotification {
+    pub fn new(
+        user_id: uuid::Uuid,
+        kind: NotificationKind,
+        message: String,
+        data: Option<serde_json::Value>,
+    ) -> Self {
+        DbNotification {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            user_id,
+            kind,
+            message,
+            data: data.map(|data| data.to_string()),
+            read_at: None,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbNotification {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        let kind: i16 = row.try_get("kind")?;
+        let kind = NotificationKind::try_from(kind).expect("must be a valid notification kind");
+
+        Ok(DbNotification {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            user_id: row.try_get("user_id")?,
+            kind,
+            message: row.try_get("message")?,
+            data: row.try_get("data")?,
+            read_at: row.try_get("read_at")?,
+        })
+    }
+}
+
+impl Table for DbNotification {
+    const TABLE: &'static str = "notifications";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "user_id",
+        "kind",
+        "message",
+        "data",
+        "read_at",
+    ];
+}
+
+// -------------SELLER WEBHOOKS----------------
+/// The endpoint a seller receives webhook deliveries on, signed with the secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbSellerWebhook {
+    pub seller_id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+}
+
+impl DbSellerWebhook {
+    pub fn new(seller_id: uuid::Uuid, url: &str, secret: &str) -> Self {
+        DbSellerWebhook {
+            seller_id,
+            created_at: sql_timestamp(None),
+            url: url.to_string(),
+            secret: secret.to_string(),
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbSellerWebhook {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbSellerWebhook {
+            seller_id: row.try_get("seller_id")?,
+            created_at: row.try_get("created_at")?,
+            url: row.try_get("url")?,
+            secret: row.try_get("secret")?,
+        })
+    }
+}
+
+impl Table for DbSellerWebhook {
+    const TABLE: &'static str = "seller_webhooks";
+    const FIELDS: &'static [&'static str] = &["seller_id", "created_at", "url", "secret"];
+}
+
+// -------------TICKET STOCK ALERTS----------------
+/// A low-stock threshold a ticket reached, its seller is alerted once per threshold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbStockAlert {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub ticket_id: uuid::Uuid,
+    /// the share of the available quantity left, 0 when sold out
+    pub threshold_percent: i16,
+}
+
+impl DbStockAlert {
+    pub fn new(ticket_id: uuid::Uuid, threshold_percent: i16) -> Self {
+        DbStockAlert {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            ticket_id,
+            threshold_percent,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbStockAlert {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbStockAlert {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            ticket_id: row.try_get("ticket_id")?,
+            threshold_percent: row.try_get("threshold_percent")?,
+        })
+    }
+}
+
+impl Table for DbStockAlert {
+    const TABLE: &'static str = "ticket_stock_alerts";
+    const FIELDS: &'static [&'static str] = &["id", "created_at", "ticket_id", "threshold_percent"];
+}
+
+// -------------TICKET CANCELLATIONS----------------
+/// A cancelled reservation. The reservation is removed and its tickets put back on sale, this
+/// keeps what it was and the refund of what the buyer paid
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbTicketCancellation {
+    /// the id of the cancelled reservation
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub reserved_at: NaiveDateTime,
+    pub verification_code: String,
+    pub event_id: uuid::Uuid,
+    pub ticket_id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub quantity: i32,
+    /// the buyer, or the seller when the event was cancelled
+    pub cancelled_by: Option<uuid::Uuid>,
+    /// what the buyer paid, at the reservation's tier and after promo discounts, in yoctoNEAR
+    pub refund_amount: NearAmount,
+    pub refunded_at: Option<NaiveDateTime>,
+    pub refund_tx_hash: Option<String>,
+}
+
+impl DbTicketCancellation {
+    /// Whether the buyer is owed a refund still
+    pub fn is_refund_due(&self) -> bool {
+        self.refunded_at.is_none() && self.refund_amount > NearAmount::default()
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbTicketCancellation {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbTicketCancellation {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            reserved_at: row.try_get("reserved_at")?,
+            verification_code: row.try_get("verification_code")?,
+            event_id: row.try_get("event_id")?,
+            ticket_id: row.try_get("ticket_id")?,
+            user_id: row.try_get("user_id")?,
+            quantity: row.try_get("quantity")?,
+            cancelled_by: row.try_get("cancelled_by")?,
+            refund_amount: row.try_get("refund_amount")?,
+            refunded_at: row.try_get("refunded_at")?,
+            refund_tx_hash: row.try_get("refund_tx_hash")?,
+        })
+    }
+}
+
+impl Table for DbTicketCancellation {
+    const TABLE: &'static str = "ticket_cancellations";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "reserved_at",
+        "verification_code",
+        "event_id",
+        "ticket_id",
+        "user_id",
+        "quantity",
+        "cancelled_by",
+        "refund_amount",
+        "refunded_at",
+        "refund_tx_hash",
+    ];
+}
+
+// -------------SEAT MAPS----------------
+/// A section of an event's seat map, its seats are sold as tickets of a single ticket type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbSeatSection {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub event_id: uuid::Uuid,
+    pub ticket_id: uuid::Uuid,
+    pub section_name: String,
+    /// the order of the section on the map
+    pub position: i32,
+}
+
+impl DbSeatSection {
+    pub fn new(db_ticket: &DbTicket, section_name: &str, position: i32) -> Self {
+        DbSeatSection {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            event_id: db_ticket.event_id,
+            ticket_id: db_ticket.id,
+            section_name: section_name.to_string(),
+            position,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbSeatSection {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbSeatSection {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            event_id: row.try_get("event_id")?,
+            ticket_id: row.try_get("ticket_id")?,
+            section_name: row.try_get("section_name")?,
+            position: row.try_get("position")?,
+        })
+    }
+}
+
+impl Table for DbSeatSection {
+    const TABLE: &'static str = "seat_sections";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "event_id",
+        "ticket_id",
+        "section_name",
+        "position",
+    ];
+}
+
+/// A seat of a section, held by a buyer for a while before they reserve it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbSeat {
+    pub id: uuid::Uuid,
+    pub section_id: uuid::Uuid,
+    pub event_id: uuid::Uuid,
+    pub ticket_id: uuid::Uuid,
+    pub row_label: String,
+    pub seat_number: i32,
+    pub held_by: Option<uuid::Uuid>,
+    /// the hold is over afterwards, the seat is available again unless reserved
+    pub held_until: Option<NaiveDateTime>,
+    pub reservation_id: Option<uuid::Uuid>,
+}
+
+impl DbSeat {
+    pub fn new(section: &DbSeatSection, row_label: &str, seat_number: i32) -> Self {
+        DbSeat {
+            id: uuid::Uuid::new_v4(),
+            section_id: section.id,
+            event_id: section.event_id,
+            ticket_id: section.ticket_id,
+            row_label: row_label.to_string(),
+            seat_number,
+            held_by: None,
+            held_until: None,
+            reservation_id: None,
+        }
+    }
+
+    pub fn status(&self, now: &NaiveDateTime) -> SeatStatus {
+        if self.reservation_id.is_some() {
+            SeatStatus::Reserved
+        } else if self
+            .held_until
+            .map_or(false, |held_until| held_until > *now)
+        {
+            SeatStatus::Held
+        } else {
+            SeatStatus::Available
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbSeat {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbSeat {
+            id: row.try_get("id")?,
+            section_id: row.try_get("section_id")?,
+            event_id: row.try_get("event_id")?,
+            ticket_id: row.try_get("ticket_id")?,
+            row_label: row.try_get("row_label")?,
+            seat_number: row.try_get("seat_number")?,
+            held_by: row.try_get("held_by")?,
+            held_until: row.try_get("held_until")?,
+            reservation_id: row.try_get("reservation_id")?,
+        })
+    }
+}
+
+impl Table for DbSeat {
+    const TABLE: &'static str = "seats";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "section_id",
+        "event_id",
+        "ticket_id",
+        "row_label",
+        "seat_number",
+        "held_by",
+        "held_until",
+        "reservation_id",
+    ];
+}
+
+// -------------SELLER PHONE VERIFICATIONS----------------
+/// A code sent to a seller's phone, verifying it upgrades their account's status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbSellerPhoneVerification {
+    pub id: uuid::Uuid,
+    pub created_at: NaiveDateTime,
+    pub user_id: uuid::Uuid,
+    /// stored on the seller's account once verified
+    pub phone_number: String,
+    pub verification_code_hash: String,
+    pub attempts: i32,
+    pub expires_at: NaiveDateTime,
+    pub verified_at: Option<NaiveDateTime>,
+}
+
+impl DbSellerPhoneVerification {
+    pub fn new(
+        user_id: uuid::Uuid,
+        phone_number: String,
+        verification_code_hash: String,
+        expires_at: NaiveDateTime,
+    ) -> Self {
+        DbSellerPhoneVerification {
+            id: uuid::Uuid::new_v4(),
+            created_at: sql_timestamp(None),
+            user_id,
+            phone_number,
+            verification_code_hash,
+            attempts: 0,
+            expires_at,
+            verified_at: None,
+        }
+    }
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbSellerPhoneVerification {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbSellerPhoneVerification {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            user_id: row.try_get("user_id")?,
+            phone_number: row.try_get("phone_number")?,
+            verification_code_hash: row.try_get("verification_code_hash")?,
+            attempts: row.try_get("attempts")?,
+            expires_at: row.try_get("expires_at")?,
+            verified_at: row.try_get("verified_at")?,
+        })
+    }
+}
+
+impl Table for DbSellerPhoneVerification {
+    const TABLE: &'static str = "seller_phone_verifications";
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "user_id",
+        "phone_number",
+        "verification_code_hash",
+        "attempts",
+        "expires_at",
+        "verified_at",
+    ];
+}
+
+// -------------MAINTENANCE----------------
+/// The maintenance mode the admins set, a single row every api instance follows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbMaintenance {
+    pub enabled: bool,
+    /// shown to the clients turned away
+    pub message: Option<String>,
+    /// the configured delay applies without one
+    pub retry_after_secs: Option<i32>,
+    pub updated_at: NaiveDateTime,
+    pub updated_by: Option<uuid::Uuid>,
+}
+
+impl TryFrom<tokio_postgres::row::Row> for DbMaintenance {
+    type Error = tokio_postgres::Error;
+
+    fn try_from(row: tokio_postgres::row::Row) -> Result<Self, Self::Error> {
+        Ok(DbMaintenance {
+            enabled: row.try_get("enabled")?,
+            message: row.try_get("message")?,
+            retry_after_secs: row.try_get("retry_after_secs")?,
+            updated_at: row.try_get("updated_at")?,
+            updated_by: row.try_get("updated_by")?,
+        })
+    }
+}
+
+impl Table for DbMaintenance {
+    const TABLE: &'static str = "maintenance";
+    const FIELDS: &'static [&'static str] = &[
+        "enabled",
+        "message",
+        "retry_after_secs",
+        "updated_at",
+        "updated_by",
+    ];
+}
@@ -0,0 +1,472 @@
+//! A small typed query builder over the [`Table`] metadata of the db models.
+//!
+//! Conditions, values and assignments write `{}` where a parameter goes. The placeholders are
+//! numbered when the statement is built, in the order the parameters were bound, so a condition
+//! can be added or left out without renumbering the others.
+use super::slow_queries::explain;
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+use std::time::Instant;
+use tokio_postgres::{types::ToSql, Client, Row};
+
+/// A bound query parameter
+pub type Param<'a> = &'a (dyn ToSql + Sync);
+
+/// Table metadata of a db model
+pub trait Table: TryFrom<Row, Error = tokio_postgres::Error> {
+    /// the table name
+    const TABLE: &'static str;
+    /// the columns, in the order the model is read from a row
+    const FIELDS: &'static [&'static str];
+
+    /// the comma separated columns
+    fn fields() -> String {
+        Self::FIELDS.join(", ")
+    }
+}
+
+/// A generic query with parameters
+pub struct Query<'a> {
+    pub statement: Cow<'a, str>,
+    pub params: Vec<Param<'a>>,
+}
+
+/// Create a new query
+#[must_use]
+pub fn query<'a>(statement: impl Into<Cow<'a, str>>) -> Query<'a> {
+    Query::new(statement.into())
+}
+
+impl<'a> Query<'a> {
+    /// creates a new query
+    pub fn new(statement: Cow<'a, str>) -> Self {
+        Self {
+            statement,
+            params: Vec::new(),
+        }
+    }
+
+    /// Bind an unnamed parameter
+    pub fn bind(mut self, value: Param<'a>) -> Self {
+        self.params.push(value);
+        self
+    }
+
+    /// Binds multiple unnamed parameters
+    pub fn bind_all(mut self, values: impl IntoIterator<Item = Param<'a>>) -> Self {
+        self.params.extend(values);
+        self
+    }
+
+    /// allows us to query one row only
+    pub async fn query_one(self, db: &Client) -> Result<Row, tokio_postgres::Error> {
+        let started = Instant::now();
+        let row = db.query_one(self.statement.as_ref(), &self.params).await;
+        self.explain_if_slow(db, started).await;
+        row
+    }
+
+    /// queries at most one row
+    pub async fn query_opt(self, db: &Client) -> Result<Option<Row>, tokio_postgres::Error> {
+        let started = Instant::now();
+        let row = db.query_opt(self.statement.as_ref(), &self.params).await;
+        self.explain_if_slow(db, started).await;
+        row
+    }
+
+    /// queries all rows
+    pub async fn query(self, db: &Client) -> Result<Vec<Row>, tokio_postgres::Error> {
+        let started = Instant::now();
+        let rows = db.query(self.statement.as_ref(), &self.params).await;
+        self.explain_if_slow(db, started).await;
+        rows
+    }
+
+    /// executes the statement, returning the number of rows modified
+    pub async fn execute(self, db: &Client) -> Result<u64, tokio_postgres::Error> {
+        let started = Instant::now();
+        let modified = db.execute(self.statement.as_ref(), &self.params).await;
+        self.explain_if_slow(db, started).await;
+        modified
+    }
+
+    /// logs the statement's plan when it was slow, in dev only
+    async fn explain_if_slow(&self, db: &Client, started: Instant) {
+        explain(db, self.statement.as_ref(), &self.params, started.elapsed()).await;
+    }
+
+    /// queries exactly one row as a model
+    pub async fn fetch_one<T>(self, db: &Client) -> Result<T, tokio_postgres::Error>
+    where
+        T: TryFrom<Row, Error = tokio_postgres::Error>,
+    {
+        T::try_from(self.query_one(db).await?)
+    }
+
+    /// queries at most one row as a model
+    pub async fn fetch_opt<T>(self, db: &Client) -> Result<Option<T>, tokio_postgres::Error>
+    where
+        T: TryFrom<Row, Error = tokio_postgres::Error>,
+    {
+        self.query_opt(db).await?.map(T::try_from).transpose()
+    }
+
+    /// queries all rows as models
+    pub async fn fetch_all<T>(self, db: &Client) -> Result<Vec<T>, tokio_postgres::Error>
+    where
+        T: TryFrom<Row, Error = tokio_postgres::Error>,
+    {
+        self.query(db).await?.into_iter().map(T::try_from).collect()
+    }
+}
+
+/// A WHERE condition
+pub struct Condition<'a> {
+    sql: String,
+    params: Vec<Param<'a>>,
+}
+
+/// Create a new condition
+#[must_use]
+pub fn cond<'a>(sql: impl Into<String>) -> Condition<'a> {
+    Condition {
+        sql: sql.into(),
+        params: Vec::new(),
+    }
+}
+
+impl<'a> Condition<'a> {
+    /// binds the value of the next `{}`
+    pub fn bind(mut self, value: Param<'a>) -> Self {
+        self.params.push(value);
+        self
+    }
+
+    /// both conditions hold
+    pub fn and(self, other: Condition<'a>) -> Self {
+        self.join("AND", other)
+    }
+
+    /// either condition holds
+    pub fn or(self, other: Condition<'a>) -> Self {
+        self.join("OR", other)
+    }
+
+    /// the condition does not hold
+    pub fn not(self) -> Self {
+        Condition {
+            sql: format!("NOT ({})", self.sql),
+            params: self.params,
+        }
+    }
+
+    /// all of the conditions hold, true if there are none
+    pub fn all(conditions: impl IntoIterator<Item = Condition<'a>>) -> Self {
+        conditions
+            .into_iter()
+            .reduce(Condition::and)
+            .unwrap_or_else(|| cond("TRUE"))
+    }
+
+    /// any of the conditions holds, false if there are none
+    pub fn any(conditions: impl IntoIterator<Item = Condition<'a>>) -> Self {
+        conditions
+            .into_iter()
+            .reduce(Condition::or)
+            .unwrap_or_else(|| cond("FALSE"))
+    }
+
+    fn join(mut self, operator: &str, other: Condition<'a>) -> Self {
+        self.params.extend(other.params);
+        Condition {
+            sql: format!("({}) {} ({})", self.sql, operator, other.sql),
+            params: self.params,
+        }
+    }
+}
+
+/// Appends a fragment to a statement, numbering its `{}` placeholders after the parameters bound
+/// so far
+fn push<'a>(
+    statement: &mut String,
+    params: &mut Vec<Param<'a>>,
+    sql: &str,
+    values: Vec<Param<'a>>,
+) {
+    assert_eq!(
+        sql.matches("{}").count(),
+        values.len(),
+        "placeholders and parameters of `{}` must match",
+        sql
+    );
+    let mut parts = sql.split("{}");
+    statement.push_str(parts.next().unwrap_or_default());
+    for (part, value) in parts.zip(values) {
+        params.push(value);
+        statement.push_str(&format!("${}", params.len()));
+        statement.push_str(part);
+    }
+}
+
+/// A SELECT of a model
+pub struct Select<'a, T> {
+    condition: Option<Condition<'a>>,
+    order_by: Option<&'a str>,
+    limit: Option<Param<'a>>,
+    offset: Option<Param<'a>>,
+    model: PhantomData<fn() -> T>,
+}
+
+/// Select models from their table
+#[must_use]
+pub fn select<'a, T: Table>() -> Select<'a, T> {
+    Select {
+        condition: None,
+        order_by: None,
+        limit: None,
+        offset: None,
+        model: PhantomData,
+    }
+}
+
+impl<'a, T: Table> Select<'a, T> {
+    /// adds a condition, all conditions must hold
+    pub fn filter(mut self, condition: Condition<'a>) -> Self {
+        self.condition = Some(match self.condition {
+            Some(current) => current.and(condition),
+            None => condition,
+        });
+        self
+    }
+
+    /// orders the rows
+    pub fn order_by(mut self, order_by: &'a str) -> Self {
+        self.order_by = Some(order_by);
+        self
+    }
+
+    /// limits the number of rows
+    pub fn limit(mut self, limit: &'a i64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// skips rows
+    pub fn offset(mut self, offset: &'a i64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// builds the statement
+    pub fn build(self) -> Query<'a> {
+        let mut statement = format!("SELECT {} FROM {}", T::fields(), T::TABLE);
+        let mut params = Vec::new();
+        if let Some(condition) = self.condition {
+            push(
+                &mut statement,
+                &mut params,
+                &format!(" WHERE {}", condition.sql),
+                condition.params,
+            );
+        }
+        if let Some(order_by) = self.order_by {
+            statement.push_str(&format!(" ORDER BY {}", order_by));
+        }
+        if let Some(limit) = self.limit {
+            push(&mut statement, &mut params, " LIMIT {}", vec![limit]);
+        }
+        if let Some(offset) = self.offset {
+            push(&mut statement, &mut params, " OFFSET {}", vec![offset]);
+        }
+        query(statement).bind_all(params)
+    }
+
+    /// queries exactly one model
+    pub async fn fetch_one(self, db: &Client) -> Result<T, tokio_postgres::Error> {
+        self.build().fetch_one(db).await
+    }
+
+    /// queries at most one model
+    pub async fn fetch_opt(self, db: &Client) -> Result<Option<T>, tokio_postgres::Error> {
+        self.build().fetch_opt(db).await
+    }
+
+    /// queries all models
+    pub async fn fetch_all(self, db: &Client) -> Result<Vec<T>, tokio_postgres::Error> {
+        self.build().fetch_all(db).await
+    }
+}
+
+/// An INSERT of a model, its values bound in the order of [`Table::FIELDS`]
+pub struct Insert<'a, T> {
+    values: Vec<Param<'a>>,
+    on_conflict: Option<&'a str>,
+    model: PhantomData<fn() -> T>,
+}
+
+/// Insert a model into its table
+#[must_use]
+pub fn insert<'a, T: Table>() -> Insert<'a, T> {
+    Insert {
+        values: Vec::new(),
+        on_conflict: None,
+        model: PhantomData,
+    }
+}
+
+impl<'a, T: Table> Insert<'a, T> {
+    /// binds the values of all fields
+    pub fn values(mut self, values: &[Param<'a>]) -> Self {
+        self.values.extend_from_slice(values);
+        self
+    }
+
+    /// adds an `ON CONFLICT` clause, e.g. `(user_id) DO NOTHING`
+    pub fn on_conflict(mut self, on_conflict: &'a str) -> Self {
+        self.on_conflict = Some(on_conflict);
+        self
+    }
+
+    /// builds the statement
+    pub fn build(self) -> Query<'a> {
+        assert_eq!(
+            T::FIELDS.len(),
+            self.values.len(),
+            "an insert into {} binds every field",
+            T::TABLE
+        );
+        let placeholders = (1..=self.values.len())
+            .map(|i| format!("${}", i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut statement = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            T::TABLE,
+            T::fields(),
+            placeholders
+        );
+        if let Some(on_conflict) = self.on_conflict {
+            statement.push_str(&format!(" ON CONFLICT {}", on_conflict));
+        }
+        query(statement).bind_all(self.values)
+    }
+
+    /// builds the statement, returning the stored row
+    pub fn returning(self) -> Query<'a> {
+        returning::<T>(self.build())
+    }
+
+    /// inserts the model, returning the number of rows inserted
+    pub async fn execute(self, db: &Client) -> Result<u64, tokio_postgres::Error> {
+        self.build().execute(db).await
+    }
+
+    /// inserts the model, returning the stored row
+    pub async fn fetch_one(self, db: &Client) -> Result<T, tokio_postgres::Error> {
+        self.returning().fetch_one(db).await
+    }
+}
+
+/// An UPDATE of a model's table
+pub struct Update<'a, T> {
+    assignments: Vec<Condition<'a>>,
+    condition: Option<Condition<'a>>,
+    model: PhantomData<fn() -> T>,
+}
+
+/// Update rows of a model's table
+#[must_use]
+pub fn update<'a, T: Table>() -> Update<'a, T> {
+    Update {
+        assignments: Vec::new(),
+        condition: None,
+        model: PhantomData,
+    }
+}
+
+impl<'a, T: Table> Update<'a, T> {
+    /// sets a column to a value
+    pub fn set(self, column: &str, value: Param<'a>) -> Self {
+        self.set_expr(cond(format!("{} = {{}}", column)).bind(value))
+    }
+
+    /// sets a column with an sql assignment, e.g. `cond("attempts = attempts + 1")`
+    pub fn set_expr(mut self, assignment: Condition<'a>) -> Self {
+        self.assignments.push(assignment);
+        self
+    }
+
+    /// adds a condition, all conditions must hold
+    pub fn filter(mut self, condition: Condition<'a>) -> Self {
+        self.condition = Some(match self.condition {
+            Some(current) => current.and(condition),
+            None => condition,
+        });
+        self
+    }
+
+    /// builds the statement
+    pub fn build(self) -> Query<'a> {
+        assert!(
+            !self.assignments.is_empty(),
+            "an update of {} sets a column",
+            T::TABLE
+        );
+        let mut statement = format!("UPDATE {} SET ", T::TABLE);
+        let mut params = Vec::new();
+        for (i, assignment) in self.assignments.into_iter().enumerate() {
+            if i > 0 {
+                statement.push_str(", ");
+            }
+            push(
+                &mut statement,
+                &mut params,
+                &assignment.sql,
+                assignment.params,
+            );
+        }
+        if let Some(condition) = self.condition {
+            push(
+                &mut statement,
+                &mut params,
+                &format!(" WHERE {}", condition.sql),
+                condition.params,
+            );
+        }
+        query(statement).bind_all(params)
+    }
+
+    /// builds the statement, returning the updated rows
+    pub fn returning(self) -> Query<'a> {
+        returning::<T>(self.build())
+    }
+
+    /// updates the rows, returning the number of rows updated
+    pub async fn execute(self, db: &Client) -> Result<u64, tokio_postgres::Error> {
+        self.build().execute(db).await
+    }
+
+    /// updates exactly one row, returning it
+    pub async fn fetch_one(self, db: &Client) -> Result<T, tokio_postgres::Error> {
+        self.returning().fetch_one(db).await
+    }
+
+    /// updates at most one row, returning it
+    pub async fn fetch_opt(self, db: &Client) -> Result<Option<T>, tokio_postgres::Error> {
+        self.returning().fetch_opt(db).await
+    }
+
+    /// updates the rows, returning them
+    pub async fn fetch_all(self, db: &Client) -> Result<Vec<T>, tokio_postgres::Error> {
+        self.returning().fetch_all(db).await
+    }
+}
+
+/// Returns the model's fields from a modifying statement
+fn returning<T: Table>(query: Query<'_>) -> Query<'_> {
+    Query {
+        statement: format!("{} RETURNING {}", query.statement, T::fields()).into(),
+        params: query.params,
+    }
+}
@@ -0,0 +1,66 @@
+//! Slow query detection. The query functions are timed by [`crate::metrics::db_timer`], the ones
+//! over the threshold are logged and counted by their label. In dev the statements run through
+//! the [`super::query`] builder also log their plan when slow.
+use super::query::Param;
+use crate::config::SlowQueriesConfig;
+use lazy_static::lazy_static;
+use std::{sync::RwLock, time::Duration};
+use tokio_postgres::Client;
+
+lazy_static! {
+    /// The slow query settings, configured once at startup
+    static ref SETTINGS: RwLock<SlowQueriesConfig> = RwLock::new(SlowQueriesConfig::default());
+}
+
+/// Replaces the slow query settings
+pub fn configure(config: SlowQueriesConfig) {
+    *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = config;
+}
+
+fn settings() -> SlowQueriesConfig {
+    SETTINGS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Whether a query that took `elapsed` is slow, never when the threshold is 0
+pub fn is_slow(elapsed: Duration) -> bool {
+    let threshold_ms = settings().threshold_ms;
+    threshold_ms > 0 && elapsed >= Duration::from_millis(threshold_ms)
+}
+
+/// Whether the statement only reads, a `WITH` may hide a write so only plain selects count
+pub fn is_read_only(statement: &str) -> bool {
+    let statement = statement.trim_start().to_uppercase();
+    statement.starts_with("SELECT")
+        && !["INSERT ", "UPDATE ", "DELETE "]
+            .iter()
+            .any(|keyword| statement.contains(keyword))
+}
+
+/// Logs the plan of a slow statement, when explaining is enabled. Only the reads are run again by
+/// `EXPLAIN ANALYZE`, the writes are planned without running them.
+pub async fn explain(db: &Client, statement: &str, params: &[Param<'_>], elapsed: Duration) {
+    if !settings().explain || !is_slow(elapsed) {
+        return;
+    }
+    let explain = if is_read_only(statement) {
+        format!("EXPLAIN ANALYZE {}", statement)
+    } else {
+        format!("EXPLAIN {}", statement)
+    };
+    match db.query(explain.as_str(), params).await {
+        Ok(rows) => {
+            let plan = rows
+                .iter()
+                .filter_map(|row| row.try_get::<_, String>(0).ok())
+                .collect::<Vec<_>>()
+                .join("\n");
+            log::warn!(
+                "Slow statement took {}ms: {}\n{}",
+                elapsed.as_millis(),
+                statement,
+                plan
+            );
+        }
+        Err(e) => log::warn!("Failed to explain a slow statement: {}", e),
+    }
+}
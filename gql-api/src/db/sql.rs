@@ -1,985 +1,5281 @@
+use super::models::suffixed_slug;
 use super::models::{
-    AssetFile, DbBuyerRecoverySession, DbBuyerSignupSession, DbEvent, DbSession, DbTicket,
-    DbTicketReservation, DbUser,
+    AssetFile, DbAttendee, DbAuditLog, DbBuyerRecoverySession, DbBuyerSignupSession, DbCategory,
+    DbDailyReservations, DbEvent, DbEventCollaborator, DbEventCount, DbEventDailyStats,
+    DbEventReminder, DbEventSeries, DbEventTag, DbEventView, DbImpersonation, DbJob,
+    DbLoginAttempt, DbMaintenance, DbMintJob, DbNotification, DbOrganization, DbOrganizationMember,
+    DbOutboxMessage, DbPayoutAccount, DbPayoutBalance, DbPayoutRequest, DbPersistedQuery,
+    DbPromoCode, DbPromoCodeUsage, DbReminderRecipient, DbSeat, DbSeatSection, DbSellerDocument,
+    DbSellerPhoneVerification, DbSellerWebhook, DbSession, DbSigninChallenge, DbSignupAttempt,
+    DbStockAlert, DbTagCount, DbTicket, DbTicketCancellation, DbTicketGift, DbTicketListing,
+    DbTicketPriceTier, DbTicketReservation, DbTicketTransfer, DbTotpChallenge, DbUser, DbUserCount,
+    DbUserTotp, DbWaitlistEntry, DbWaitlistOpening, DbWalletTopUp,
 };
-use crate::gql::models::EventFilter;
-use chrono::{Duration, NaiveDateTime, Utc};
-use std::borrow::Cow;
+use super::query::{cond, insert, select, update, Condition, Insert, Param, Table};
+pub use super::query::{query, Query};
+use crate::auth::{Role, SellerStatus, UserStatus};
+use crate::error::{ConflictError, TicketUpdateError};
+use crate::geo::{Coordinates, EARTH_RADIUS_KM};
+use crate::gql::models::{
+    AssetRole, EventFilter, EventStatus, MemberRole, MintStatus, PayoutStatus, SignupStatus,
+};
+use crate::images::ImageVariant;
+use crate::jobs::models::{EventAssetKind, JobStatus};
+use crate::metrics::db_timer;
+use crate::near::NearAmount;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
 use std::convert::TryFrom;
+use tokio_postgres::error::SqlState;
 use tokio_postgres::types::ToSql;
 use tokio_postgres::Client;
 
+/// How many suffixed slugs are tried before a slug conflict is returned
+pub const MAX_SLUG_ATTEMPTS: usize = 50;
+
+/// The conflict behind a unique violation, for the constraints whose values users choose.
+///
+/// The checks before the writes cover the common case, this covers the race between two
+/// writes of the same value, which the db settles.
+pub fn unique_violation(e: &tokio_postgres::Error) -> Option<ConflictError> {
+    if e.code() != Some(&SqlState::UNIQUE_VIOLATION) {
+        return None;
+    }
+    e.as_db_error()
+        .and_then(|db_error| db_error.constraint())
+        .and_then(ConflictError::from_constraint)
+}
+
 lazy_static::lazy_static! {
 
     // events table
-    pub static ref EVENTS_TABLE: String = "events".to_string();
-    pub static ref EVENTS_TABLE_FIELDS: String = "id,
-                                                event_name,
-                                                event_slug,
-                                                start_date,
-                                                end_date,
-                                                entry_time,
-                                                created_at,
-                                                description,
-                                                is_virtual,
-                                                is_featured,
-                                                venue_name,
-                                                venue_location,
-                                                cover_photo_url,
-                                                thumbnail_url,
-                                                event_status,
-                                                created_by_user".to_string();
+    pub static ref EVENTS_TABLE: String = DbEvent::TABLE.to_string();
+    pub static ref EVENTS_TABLE_FIELDS: String = DbEvent::fields();
 
     // tickets table
-    pub static ref TICKETS_TABLE: String = "tickets".to_string();
-    pub static ref TICKETS_TABLE_FIELDS: String = "id,
-                                                    created_at,
-                                                    ticket_name,
-                                                    ticket_slug,
-                                                    description,
-                                                    price,
-                                                    max_release_price,
-                                                    quantity_available,
-                                                    min_purchase_quantity,
-                                                    max_purchase_quantity,
-                                                    allow_transfers,
-                                                    event_id".to_string();
+    pub static ref TICKETS_TABLE: String = DbTicket::TABLE.to_string();
+    pub static ref TICKETS_TABLE_FIELDS: String = DbTicket::fields();
 
     // users table
-    pub static ref USERS_TABLE: String = "users".to_string();
-    pub static ref USERS_TABLE_FIELDS: String = "id,
-                                                name,
-                                                username,
-                                                phone_number,
-                                                email,
-                                                password,
-                                                encrypted_secret_key,
-                                                created_at,
-                                                wallet_id,
-                                                wallet_balance,
-                                                user_type,
-                                                user_status".to_string();
+    pub static ref USERS_TABLE: String = DbUser::TABLE.to_string();
+    pub static ref USERS_TABLE_FIELDS: String = DbUser::fields();
 
     // buyer login sessions table
-    pub static ref SESSIONS_TABLE: String = "sessions".to_string();
-    pub static ref SESSIONS_TABLE_FIELDS: String = "id,
-                                                    expires_at,
-                                                    login_code,
-                                                    is_used,
-                                                    user_id".to_string();
+    pub static ref SESSIONS_TABLE: String = DbSession::TABLE.to_string();
+    pub static ref SESSIONS_TABLE_FIELDS: String = DbSession::fields();
 
     // buyer signup sessions table
-    pub static ref BUYER_SIGNUP_SESSIONS_TABLE: String = "buyer_signup_sessions".to_string();
-    pub static ref BUYER_SIGNUP_SESSIONS_TABLE_FIELDS: String = "id,
-                                                                created_at,
-                                                                verification_code,
-                                                                phone_number,
-                                                                is_verified".to_string();
+    pub static ref BUYER_SIGNUP_SESSIONS_TABLE: String = DbBuyerSignupSession::TABLE.to_string();
+    pub static ref BUYER_SIGNUP_SESSIONS_TABLE_FIELDS: String = DbBuyerSignupSession::fields();
 
     // buyer recovery sessions table
-    pub static ref BUYER_RECOVERY_SESSIONS_TABLE: String = "buyer_recovery_sessions".to_string();
-    pub static ref BUYER_RECOVERY_SESSIONS_TABLE_FIELDS: String = "id,
-                                                                    created_at,
-                                                                    recovery_code,
-                                                                    phone_number,
-                                                                    is_recovered,
-                                                                    created_by_user".to_string();
+    pub static ref BUYER_RECOVERY_SESSIONS_TABLE: String = DbBuyerRecoverySession::TABLE.to_string();
+    pub static ref BUYER_RECOVERY_SESSIONS_TABLE_FIELDS: String = DbBuyerRecoverySession::fields();
+
+    // seller signin challenges table
+    pub static ref SIGNIN_CHALLENGES_TABLE: String = DbSigninChallenge::TABLE.to_string();
+
+    // password sign-in totp challenges table
+    pub static ref TOTP_CHALLENGES_TABLE: String = DbTotpChallenge::TABLE.to_string();
+
+    // failed password sign-ins table
+    pub static ref LOGIN_ATTEMPTS_TABLE: String = DbLoginAttempt::TABLE.to_string();
 
     // ticket reservations table
-    pub static ref TICKET_RESERVATIONS_TABLE: String = "ticket_reservations".to_string();
-    pub static ref TICKET_RESERVATIONS_TABLE_FIELDS: String = "id,
-                                                                created_at,
-                                                                verification_code,
-                                                                event_id,
-                                                                ticket_id,
-                                                                user_id".to_string();
+    pub static ref TICKET_RESERVATIONS_TABLE: String = DbTicketReservation::TABLE.to_string();
+    pub static ref TICKET_RESERVATIONS_TABLE_FIELDS: String = DbTicketReservation::fields();
 
     // s3 files table
-    pub static ref ASSET_FILES_TABLE: String = "asset_files".to_string();
-    pub static ref ASSET_FILES_SELECT_FIELDS: String = "id,
-                                                    s3_bucket,
-                                                    s3_absolute_key,
-                                                    ipfs_hash,
-                                                    event_id
-                                                    ".to_string();
+    pub static ref ASSET_FILES_TABLE: String = AssetFile::TABLE.to_string();
+    pub static ref ASSET_FILES_SELECT_FIELDS: String = AssetFile::fields();
+
+    // promo codes table
+    pub static ref PROMO_CODES_TABLE: String = DbPromoCode::TABLE.to_string();
+    pub static ref PROMO_CODES_TABLE_FIELDS: String = DbPromoCode::fields();
+
+    // promo code usages table
+    pub static ref PROMO_CODE_USAGES_TABLE: String = DbPromoCodeUsage::TABLE.to_string();
+    pub static ref PROMO_CODE_USAGES_TABLE_FIELDS: String = DbPromoCodeUsage::fields();
+
+    // ticket transfers table
+    pub static ref TICKET_TRANSFERS_TABLE: String = DbTicketTransfer::TABLE.to_string();
+    pub static ref TICKET_TRANSFERS_TABLE_FIELDS: String = DbTicketTransfer::fields();
+
+    // jobs table
+    pub static ref JOBS_TABLE: String = DbJob::TABLE.to_string();
+    pub static ref JOBS_TABLE_FIELDS: String = DbJob::fields();
+
+    // outbox messages table
+    pub static ref OUTBOX_TABLE: String = DbOutboxMessage::TABLE.to_string();
+
+    // audit log table
+    pub static ref AUDIT_LOG_TABLE: String = DbAuditLog::TABLE.to_string();
+    pub static ref AUDIT_LOG_TABLE_FIELDS: String = DbAuditLog::fields();
+
+    // payout accounts table
+    pub static ref PAYOUT_ACCOUNTS_TABLE: String = DbPayoutAccount::TABLE.to_string();
+    pub static ref PAYOUT_ACCOUNTS_TABLE_FIELDS: String = DbPayoutAccount::fields();
+
+    // payout requests table
+    pub static ref PAYOUT_REQUESTS_TABLE: String = DbPayoutRequest::TABLE.to_string();
+    pub static ref PAYOUT_REQUESTS_TABLE_FIELDS: String = DbPayoutRequest::fields();
+
+    // categories table
+    pub static ref CATEGORIES_TABLE: String = DbCategory::TABLE.to_string();
+    pub static ref CATEGORIES_TABLE_FIELDS: String = DbCategory::fields();
+
+    // event tags table
+    pub static ref EVENT_TAGS_TABLE: String = DbEventTag::TABLE.to_string();
+    pub static ref EVENT_TAGS_TABLE_FIELDS: String = DbEventTag::fields();
 }
 
 pub async fn db_insert_event(
     db_client: &Client,
     new_event: &DbEvent,
 ) -> Result<u64, tokio_postgres::Error> {
-    let insert_query = format!(
-        "INSERT INTO {} 
-                ({})
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)",
-        *EVENTS_TABLE, *EVENTS_TABLE_FIELDS
-    );
-    let create_event_statement = db_client.prepare(&insert_query).await?;
+    let _timer = db_timer("db_insert_event");
+    insert::<DbEvent>()
+        .values(&[
+            &new_event.id,
+            &new_event.event_name,
+            &new_event.event_slug,
+            &new_event.start_date,
+            &new_event.end_date,
+            &new_event.entry_time,
+            &new_event.created_at,
+            &new_event.description,
+            &new_event.is_virtual,
+            &new_event.is_featured,
+            &new_event.venue_name,
+            &new_event.venue_location,
+            &new_event.cover_photo_url,
+            &new_event.thumbnail_url,
+            &(new_event.event_status as i16),
+            &new_event.created_by_user,
+            &new_event.cover_photo_ipfs_url,
+            &new_event.thumbnail_ipfs_url,
+            &new_event.archived,
+            &new_event.deleted_at,
+            &new_event.category_id,
+            &new_event.thumbnail_variant_url,
+            &new_event.cover_variant_url,
+            &new_event.og_image_url,
+            &new_event.organization_id,
+            &new_event.latitude,
+            &new_event.longitude,
+            &new_event.cancellation_window_hours,
+            &new_event.modified_at,
+            &new_event.series_id,
+            &new_event.review_submitted_at,
+            &new_event.review_rejection_reason,
+        ])
+        .execute(db_client)
+        .await
+}
 
-    let res_create_event = db_client
-        .execute(
-            &create_event_statement,
-            &[
-                &new_event.id,
-                &new_event.event_name,
-                &new_event.event_slug,
-                &new_event.start_date,
-                &new_event.end_date,
-                &new_event.entry_time,
-                &new_event.created_at,
-                &new_event.description,
-                &new_event.is_virtual,
-                &new_event.is_featured,
-                &new_event.venue_name,
-                &new_event.venue_location,
-                &new_event.cover_photo_url,
-                &new_event.thumbnail_url,
-                &(new_event.event_status as i16),
-                &new_event.created_by_user,
-            ],
-        )
-        .await;
-    res_create_event
+/// Inserts the event, suffixing its slug with `-2`, `-3`... while the slug is taken. The event
+/// keeps the slug it was stored with
+pub async fn db_insert_event_with_free_slug(
+    db_client: &Client,
+    new_event: &mut DbEvent,
+) -> Result<u64, tokio_postgres::Error> {
+    let slug = new_event.event_slug.clone();
+    let mut attempt = 1;
+    loop {
+        new_event.event_slug = suffixed_slug(&slug, attempt);
+        match db_insert_event(db_client, new_event).await {
+            Err(e)
+                if attempt < MAX_SLUG_ATTEMPTS
+                    && unique_violation(&e) == Some(ConflictError::EventSlug) =>
+            {
+                attempt += 1
+            }
+            result => return result,
+        }
+    }
 }
 
 pub async fn db_update_event(
     db_client: &Client,
     new_event: &DbEvent,
 ) -> Result<DbEvent, tokio_postgres::Error> {
-    let update_query = format!(
-        "UPDATE {} 
-         SET event_name = $1::VARCHAR,
-            event_slug = $2::VARCHAR,
-            start_date = $3::TIMESTAMP,
-            end_date = $4::TIMESTAMP,
-            entry_time = $5::TIMESTAMP,
-            description = $6::VARCHAR,
-            is_virtual = $7::BOOLEAN,
-            is_featured = $8::BOOLEAN,
-            venue_name = $9::VARCHAR,
-            venue_location = $10::VARCHAR,
-            cover_photo_url = $11::VARCHAR,
-            thumbnail_url = $12::VARCHAR,
-            created_by_user = $13::UUID
-         WHERE id = $14::UUID
-         RETURNING {}",
-        *EVENTS_TABLE, *EVENTS_TABLE_FIELDS
-    );
-
-    let update_stmt = db_client.prepare(&update_query).await?;
-
-    let x = db_client
-        .query_one(
-            &update_stmt,
-            &[
-                &new_event.event_name,
-                &new_event.event_slug,
-                &new_event.start_date,
-                &new_event.end_date,
-                &new_event.entry_time,
-                &new_event.description,
-                &new_event.is_virtual,
-                &new_event.is_featured,
-                &new_event.venue_name,
-                &new_event.venue_location,
-                &new_event.cover_photo_url,
-                &new_event.thumbnail_url,
-                &new_event.created_by_user,
-                &new_event.id,
-            ],
-        )
-        .await?;
-
-    x.try_into()
+    let _timer = db_timer("db_update_event");
+    update::<DbEvent>()
+        .set("event_name", &new_event.event_name)
+        .set("event_slug", &new_event.event_slug)
+        .set("start_date", &new_event.start_date)
+        .set("end_date", &new_event.end_date)
+        .set("entry_time", &new_event.entry_time)
+        .set("description", &new_event.description)
+        .set("is_virtual", &new_event.is_virtual)
+        .set("is_featured", &new_event.is_featured)
+        .set("venue_name", &new_event.venue_name)
+        .set("venue_location", &new_event.venue_location)
+        .set("cover_photo_url", &new_event.cover_photo_url)
+        .set("thumbnail_url", &new_event.thumbnail_url)
+        .set("latitude", &new_event.latitude)
+        .set("longitude", &new_event.longitude)
+        .set("created_by_user", &new_event.created_by_user)
+        .filter(cond("id = {}::UUID").bind(&new_event.id))
+        .fetch_one(db_client)
+        .await
 }
 
+/// Updates the ticket if it is still at `version`, bumping its version. A ticket edited meanwhile
+/// is left as is, the conflict carries its current values
 pub async fn db_update_ticket(
     db_client: &Client,
     new_ticket: &DbTicket,
-) -> Result<DbTicket, tokio_postgres::Error> {
-    let update_query = format!(
-        "UPDATE {} 
-            SET ticket_name = $1::VARCHAR,
-            ticket_slug = $2::VARCHAR,
-            description = $3::VARCHAR,
-            price = $4::VARCHAR,
-            max_release_price = $5::VARCHAR,
-            quantity_available = $6::INTEGER,
-            min_purchase_quantity = $7::INTEGER,
-            max_purchase_quantity = $8::INTEGER,
-            allow_transfers = $9::BOOLEAN
-         WHERE id = $10::UUID
-         RETURNING {}",
-        *TICKETS_TABLE, *TICKETS_TABLE_FIELDS
-    );
-
-    let update_stmt = db_client.prepare(&update_query).await?;
-
-    let x = db_client
-        .query_one(
-            &update_stmt,
-            &[
-                &new_ticket.ticket_name,
-                &new_ticket.ticket_slug,
-                &new_ticket.description,
-                &new_ticket.price,
-                &new_ticket.max_release_price,
-                &new_ticket.quantity_available,
-                &new_ticket.min_purchase_quantity,
-                &new_ticket.max_purchase_quantity,
-                &new_ticket.allow_transfers,
-                &new_ticket.id,
-            ],
+    version: i32,
+) -> Result<DbTicket, TicketUpdateError> {
+    let _timer = db_timer("db_update_ticket");
+    let updated = update::<DbTicket>()
+        .set("ticket_name", &new_ticket.ticket_name)
+        .set("ticket_slug", &new_ticket.ticket_slug)
+        .set("description", &new_ticket.description)
+        .set("price", &new_ticket.price)
+        .set("max_release_price", &new_ticket.max_release_price)
+        .set("quantity_available", &new_ticket.quantity_available)
+        .set("min_purchase_quantity", &new_ticket.min_purchase_quantity)
+        .set("max_purchase_quantity", &new_ticket.max_purchase_quantity)
+        .set("allow_transfers", &new_ticket.allow_transfers)
+        .set("currency", &i16::from(new_ticket.currency))
+        .set_expr(cond("version = version + 1"))
+        .filter(
+            cond("id = {}::UUID AND version = {}::INTEGER")
+                .bind(&new_ticket.id)
+                .bind(&version),
         )
-        .await?;
-
-    x.try_into()
+        .fetch_opt(db_client)
+        .await
+        .map_err(TicketUpdateError::Postgres)?;
+    match updated {
+        Some(db_ticket) => Ok(db_ticket),
+        None => {
+            let current = db_get_ticket_by_id(db_client, &new_ticket.id)
+                .await
+                .map_err(TicketUpdateError::Postgres)?;
+            Err(TicketUpdateError::VersionConflict(Box::new(current)))
+        }
+    }
 }
 
 pub async fn db_insert_ticket(
     db_client: &Client,
     db_ticket: &DbTicket,
 ) -> Result<u64, tokio_postgres::Error> {
-    let insert_query = format!(
-        "INSERT INTO {} 
-                ({})
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
-        *TICKETS_TABLE, *TICKETS_TABLE_FIELDS
+    let _timer = db_timer("db_insert_ticket");
+    let currency = i16::from(db_ticket.currency);
+    insert_ticket(db_ticket, &currency).execute(db_client).await
+}
+
+fn insert_ticket<'a>(db_ticket: &'a DbTicket, currency: &'a i16) -> Insert<'a, DbTicket> {
+    insert::<DbTicket>().values(&[
+        &db_ticket.id,
+        &db_ticket.created_at,
+        &db_ticket.ticket_name,
+        &db_ticket.ticket_slug,
+        &db_ticket.description,
+        &db_ticket.price,
+        &db_ticket.max_release_price,
+        &db_ticket.quantity_available,
+        &db_ticket.min_purchase_quantity,
+        &db_ticket.max_purchase_quantity,
+        &db_ticket.allow_transfers,
+        &db_ticket.event_id,
+        &db_ticket.archived,
+        &db_ticket.deleted_at,
+        currency,
+        &db_ticket.quantity_reserved,
+        &db_ticket.version,
+    ])
+}
+
+/// Inserts the ticket, suffixing its slug with `-2`, `-3`... while the slug is taken. The ticket
+/// keeps the slug it was stored with. The outbox messages are written along with the ticket
+pub async fn db_insert_ticket_with_free_slug(
+    db_client: &Client,
+    db_ticket: &mut DbTicket,
+    outbox: &[DbOutboxMessage],
+) -> Result<u64, tokio_postgres::Error> {
+    let slug = db_ticket.ticket_slug.clone();
+    let currency = i16::from(db_ticket.currency);
+    let mut attempt = 1;
+    loop {
+        db_ticket.ticket_slug = suffixed_slug(&slug, attempt);
+        let _timer = db_timer("db_insert_ticket");
+        let change = insert_ticket(db_ticket, &currency).returning();
+        match with_outbox(change, outbox).execute(db_client).await {
+            Err(e)
+                if attempt < MAX_SLUG_ATTEMPTS
+                    && unique_violation(&e) == Some(ConflictError::TicketSlug) =>
+            {
+                attempt += 1
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Inserts an event and its tickets in a single statement, so that either all or none of them
+/// are stored. Returns the number of tickets inserted
+pub async fn db_insert_event_with_tickets(
+    db_client: &Client,
+    new_event: &DbEvent,
+    db_tickets: &[DbTicket],
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_event_with_tickets");
+    if db_tickets.is_empty() {
+        return db_insert_event(db_client, new_event).await.map(|_| 0);
+    }
+
+    let event_status = new_event.event_status as i16;
+    let currencies = db_tickets
+        .iter()
+        .map(|db_ticket| i16::from(db_ticket.currency))
+        .collect::<Vec<_>>();
+    let mut values: Vec<&(dyn ToSql + Sync)> = vec![
+        &new_event.id,
+        &new_event.event_name,
+        &new_event.event_slug,
+        &new_event.start_date,
+        &new_event.end_date,
+        &new_event.entry_time,
+        &new_event.created_at,
+        &new_event.description,
+        &new_event.is_virtual,
+        &new_event.is_featured,
+        &new_event.venue_name,
+        &new_event.venue_location,
+        &new_event.cover_photo_url,
+        &new_event.thumbnail_url,
+        &event_status,
+        &new_event.created_by_user,
+        &new_event.cover_photo_ipfs_url,
+        &new_event.thumbnail_ipfs_url,
+        &new_event.archived,
+        &new_event.deleted_at,
+        &new_event.category_id,
+        &new_event.thumbnail_variant_url,
+        &new_event.cover_variant_url,
+        &new_event.og_image_url,
+        &new_event.organization_id,
+        &new_event.latitude,
+        &new_event.longitude,
+        &new_event.cancellation_window_hours,
+        &new_event.modified_at,
+        &new_event.series_id,
+        &new_event.review_submitted_at,
+        &new_event.review_rejection_reason,
+    ];
+    let event_row = placeholders(0, values.len());
+
+    let mut ticket_rows = vec![];
+    for (db_ticket, currency) in db_tickets.iter().zip(currencies.iter()) {
+        ticket_rows.push(placeholders(values.len(), DbTicket::FIELDS.len()));
+        values.extend_from_slice(&[
+            &db_ticket.id,
+            &db_ticket.created_at,
+            &db_ticket.ticket_name,
+            &db_ticket.ticket_slug,
+            &db_ticket.description,
+            &db_ticket.price,
+            &db_ticket.max_release_price,
+            &db_ticket.quantity_available,
+            &db_ticket.min_purchase_quantity,
+            &db_ticket.max_purchase_quantity,
+            &db_ticket.allow_transfers,
+            &db_ticket.event_id,
+            &db_ticket.archived,
+            &db_ticket.deleted_at,
+            currency,
+            &db_ticket.quantity_reserved,
+            &db_ticket.version,
+        ]);
+    }
+
+    let statement = format!(
+        "WITH new_event AS (INSERT INTO {} ({}) VALUES {}) INSERT INTO {} ({}) VALUES {}",
+        *EVENTS_TABLE,
+        *EVENTS_TABLE_FIELDS,
+        event_row,
+        *TICKETS_TABLE,
+        *TICKETS_TABLE_FIELDS,
+        ticket_rows.join(", ")
     );
-    let insert_stmt = db_client.prepare(&insert_query).await?;
-    let res_ticket = db_client
-        .execute(
-            &insert_stmt,
-            &[
-                &db_ticket.id,
-                &db_ticket.created_at,
-                &db_ticket.ticket_name,
-                &db_ticket.ticket_slug,
-                &db_ticket.description,
-                &db_ticket.price,
-                &db_ticket.max_release_price,
-                &db_ticket.quantity_available,
-                &db_ticket.min_purchase_quantity,
-                &db_ticket.max_purchase_quantity,
-                &db_ticket.allow_transfers,
-                &db_ticket.event_id,
-            ],
-        )
-        .await;
-    res_ticket
+    query(statement).bind_all(values).execute(db_client).await
+}
+
+/// `($n, ..)` placeholders of a row of `len` values, numbered after the `offset` already bound
+fn placeholders(offset: usize, len: usize) -> String {
+    let placeholders = (offset + 1..=offset + len)
+        .map(|i| format!("${}", i))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("({})", placeholders)
 }
 
 pub async fn db_insert_user(
     db_client: &Client,
     new_user: &DbUser,
 ) -> Result<u64, tokio_postgres::Error> {
-    let insert_query = format!(
-        "INSERT INTO {} 
-                ({})
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
-        *USERS_TABLE, *USERS_TABLE_FIELDS
-    );
-    let create_user_statement = db_client.prepare(&insert_query).await?;
-
-    let res_user_event = db_client
-        .execute(
-            &create_user_statement,
-            &[
-                &new_user.id,
-                &new_user.name,
-                &new_user.username,
-                &new_user.phone_number,
-                &new_user.email,
-                &new_user.password,
-                &new_user.encrypted_secret_key,
-                &new_user.created_at,
-                &new_user.wallet_id,
-                &new_user.wallet_balance,
-                &(new_user.user_type as i16),
-                &(new_user.user_status as i16),
-            ],
-        )
-        .await;
-    res_user_event
+    let _timer = db_timer("db_insert_user");
+    insert::<DbUser>()
+        .values(&[
+            &new_user.id,
+            &new_user.name,
+            &new_user.username,
+            &new_user.phone_number,
+            &new_user.email,
+            &new_user.password,
+            &new_user.encrypted_secret_key,
+            &new_user.created_at,
+            &new_user.wallet_id,
+            &new_user.wallet_balance,
+            &(new_user.user_type as i16),
+            &(new_user.user_status as i16),
+            &new_user.wallet_balance_updated_at,
+            &new_user.deleted_at,
+            &new_user.wallet_flagged,
+            &new_user.seller_slug,
+            &new_user.locale,
+            &new_user.seller_status.map(i16::from),
+            &new_user.seller_rejection_reason,
+            &new_user.event_reminders_opt_out,
+            &new_user.password_changed_at,
+        ])
+        .execute(db_client)
+        .await
 }
 
 pub async fn db_insert_session(
     db_client: &Client,
     new_session: &DbSession,
 ) -> Result<u64, tokio_postgres::Error> {
-    let insert_query = format!(
-        "INSERT INTO {} 
-                ({})
-            VALUES ($1, $2, $3, $4, $5)",
-        *SESSIONS_TABLE, *SESSIONS_TABLE_FIELDS
-    );
-    let create_session_statement = db_client.prepare(&insert_query).await?;
-
-    let res_session_event = db_client
-        .execute(
-            &create_session_statement,
-            &[
-                &new_session.id,
-                &new_session.expires_at,
-                &new_session.login_code,
-                &new_session.is_used,
-                &new_session.user_id,
-            ],
-        )
-        .await;
-    res_session_event
+    let _timer = db_timer("db_insert_session");
+    insert::<DbSession>()
+        .values(&[
+            &new_session.id,
+            &new_session.expires_at,
+            &new_session.login_code,
+            &new_session.is_used,
+            &new_session.user_id,
+        ])
+        .execute(db_client)
+        .await
 }
 
 pub async fn db_insert_buyer_recovery_session(
     db_client: &Client,
     db_buyer_recovery_session: &DbBuyerRecoverySession,
 ) -> Result<u64, tokio_postgres::Error> {
-    let insert_query = format!(
-        "INSERT INTO {} 
-                ({})
-            VALUES ($1, $2, $3, $4, $5, $6)",
-        *BUYER_RECOVERY_SESSIONS_TABLE, *BUYER_RECOVERY_SESSIONS_TABLE_FIELDS
-    );
-    let create_buyer_recovery_session_statement = db_client.prepare(&insert_query).await?;
-
-    let res = db_client
-        .execute(
-            &create_buyer_recovery_session_statement,
-            &[
-                &db_buyer_recovery_session.id,
-                &db_buyer_recovery_session.created_at,
-                &db_buyer_recovery_session.recovery_code,
-                &db_buyer_recovery_session.phone_number,
-                &db_buyer_recovery_session.is_recovered,
-                &db_buyer_recovery_session.created_by_user,
-            ],
-        )
-        .await;
-    res
+    let _timer = db_timer("db_insert_buyer_recovery_session");
+    insert::<DbBuyerRecoverySession>()
+        .values(&[
+            &db_buyer_recovery_session.id,
+            &db_buyer_recovery_session.created_at,
+            &db_buyer_recovery_session.recovery_code_hash,
+            &db_buyer_recovery_session.phone_number,
+            &db_buyer_recovery_session.is_recovered,
+            &db_buyer_recovery_session.created_by_user,
+            &db_buyer_recovery_session.expires_at,
+            &db_buyer_recovery_session.attempts,
+            &db_buyer_recovery_session.is_consumed,
+        ])
+        .execute(db_client)
+        .await
 }
 
 pub async fn db_insert_buyer_signup_session(
     db_client: &Client,
     db_buyer_signup_session: &DbBuyerSignupSession,
 ) -> Result<u64, tokio_postgres::Error> {
-    let insert_query = format!(
-        "INSERT INTO {} 
-                ({})
-            VALUES ($1, $2, $3, $4, $5)",
-        *BUYER_SIGNUP_SESSIONS_TABLE, *BUYER_SIGNUP_SESSIONS_TABLE_FIELDS
-    );
-    let create_buyer_signup_session_statement = db_client.prepare(&insert_query).await?;
-
-    let res_session_event = db_client
-        .execute(
-            &create_buyer_signup_session_statement,
-            &[
-                &db_buyer_signup_session.id,
-                &db_buyer_signup_session.created_at,
-                &db_buyer_signup_session.verification_code,
-                &db_buyer_signup_session.phone_number,
-                &db_buyer_signup_session.is_verified,
-            ],
-        )
-        .await;
-    res_session_event
+    let _timer = db_timer("db_insert_buyer_signup_session");
+    insert::<DbBuyerSignupSession>()
+        .values(&[
+            &db_buyer_signup_session.id,
+            &db_buyer_signup_session.created_at,
+            &db_buyer_signup_session.verification_code_hash,
+            &db_buyer_signup_session.phone_number,
+            &db_buyer_signup_session.is_verified,
+            &db_buyer_signup_session.expires_at,
+            &db_buyer_signup_session.attempts,
+            &db_buyer_signup_session.is_consumed,
+            &db_buyer_signup_session.resend_count,
+            &db_buyer_signup_session.last_sent_at,
+            &db_buyer_signup_session.locale,
+        ])
+        .execute(db_client)
+        .await
 }
 
 pub async fn db_update_buyer_signup_session(
     db_client: &Client,
     buyer_signup_session: &DbBuyerSignupSession,
 ) -> Result<DbBuyerSignupSession, tokio_postgres::Error> {
-    let update_query = format!(
-        "UPDATE {} 
-            SET verification_code = $1::VARCHAR,
-            phone_number = $2::VARCHAR,
-            is_verified = $3::BOOLEAN
-         WHERE id = $4::UUID
-         RETURNING {}",
-        *BUYER_SIGNUP_SESSIONS_TABLE, *BUYER_SIGNUP_SESSIONS_TABLE_FIELDS
-    );
-
-    let update_stmt = db_client.prepare(&update_query).await?;
-
-    let x = db_client
-        .query_one(
-            &update_stmt,
-            &[
-                &buyer_signup_session.verification_code,
-                &buyer_signup_session.phone_number,
-                &buyer_signup_session.is_verified,
-                &buyer_signup_session.id,
-            ],
+    let _timer = db_timer("db_update_buyer_signup_session");
+    update::<DbBuyerSignupSession>()
+        .set(
+            "verification_code_hash",
+            &buyer_signup_session.verification_code_hash,
         )
-        .await?;
-
-    x.try_into()
+        .set("phone_number", &buyer_signup_session.phone_number)
+        .set("is_verified", &buyer_signup_session.is_verified)
+        .filter(cond("id = {}::UUID").bind(&buyer_signup_session.id))
+        .fetch_one(db_client)
+        .await
 }
 
 pub async fn db_update_buyer_recovery_session(
     db_client: &Client,
     buyer_recovery_session: &DbBuyerRecoverySession,
 ) -> Result<DbBuyerRecoverySession, tokio_postgres::Error> {
-    let update_query = format!(
-        "UPDATE {} 
-            SET recovery_code = $1::VARCHAR,
-            phone_number = $2::VARCHAR,
-            is_recovered = $3::BOOLEAN
-         WHERE id = $4::UUID
-         RETURNING {}",
-        *BUYER_RECOVERY_SESSIONS_TABLE, *BUYER_RECOVERY_SESSIONS_TABLE_FIELDS
-    );
-
-    let update_stmt = db_client.prepare(&update_query).await?;
-
-    let x = db_client
-        .query_one(
-            &update_stmt,
-            &[
-                &buyer_recovery_session.recovery_code,
-                &buyer_recovery_session.phone_number,
-                &buyer_recovery_session.is_recovered,
-                &buyer_recovery_session.id,
-            ],
+    let _timer = db_timer("db_update_buyer_recovery_session");
+    update::<DbBuyerRecoverySession>()
+        .set(
+            "recovery_code_hash",
+            &buyer_recovery_session.recovery_code_hash,
         )
-        .await?;
-
-    x.try_into()
+        .set("phone_number", &buyer_recovery_session.phone_number)
+        .set("is_recovered", &buyer_recovery_session.is_recovered)
+        .filter(cond("id = {}::UUID").bind(&buyer_recovery_session.id))
+        .fetch_one(db_client)
+        .await
 }
 
 pub async fn db_get_buyer_signup_session_by_id(
     db_client: &Client,
     session_id: &uuid::Uuid,
 ) -> Result<DbBuyerSignupSession, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE id = $1::UUID",
-        *BUYER_SIGNUP_SESSIONS_TABLE_FIELDS, *BUYER_SIGNUP_SESSIONS_TABLE
-    );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&session_id];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbBuyerSignupSession::try_from(row)
+    let _timer = db_timer("db_get_buyer_signup_session_by_id");
+    select::<DbBuyerSignupSession>()
+        .filter(cond("id = {}::UUID").bind(&session_id))
+        .fetch_one(db_client)
+        .await
 }
 
 pub async fn db_get_buyer_recovery_session_by_id(
     db_client: &Client,
     session_id: &uuid::Uuid,
 ) -> Result<DbBuyerRecoverySession, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE id = $1::UUID",
-        *BUYER_RECOVERY_SESSIONS_TABLE_FIELDS, *BUYER_RECOVERY_SESSIONS_TABLE
+    let _timer = db_timer("db_get_buyer_recovery_session_by_id");
+    select::<DbBuyerRecoverySession>()
+        .filter(cond("id = {}::UUID").bind(&session_id))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_increment_buyer_signup_session_attempts(
+    db_client: &Client,
+    session_id: &uuid::Uuid,
+) -> Result<i32, tokio_postgres::Error> {
+    let _timer = db_timer("db_increment_buyer_signup_session_attempts");
+    let update_query = format!(
+        "UPDATE {} SET attempts = attempts + 1 WHERE id = $1::UUID RETURNING attempts",
+        *BUYER_SIGNUP_SESSIONS_TABLE
     );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&session_id];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbBuyerRecoverySession::try_from(row)
+    let row = db_client.query_one(&update_query, &[&session_id]).await?;
+    row.try_get(0)
 }
 
-pub async fn db_get_session_by_login_code(
+pub async fn db_increment_buyer_recovery_session_attempts(
     db_client: &Client,
-    login_code: &str,
-) -> Result<DbSession, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE login_code = $1::VARCHAR",
-        *SESSIONS_TABLE_FIELDS, *SESSIONS_TABLE
+    session_id: &uuid::Uuid,
+) -> Result<i32, tokio_postgres::Error> {
+    let _timer = db_timer("db_increment_buyer_recovery_session_attempts");
+    let update_query = format!(
+        "UPDATE {} SET attempts = attempts + 1 WHERE id = $1::UUID RETURNING attempts",
+        *BUYER_RECOVERY_SESSIONS_TABLE
     );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&login_code];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbSession::try_from(row)
+    let row = db_client.query_one(&update_query, &[&session_id]).await?;
+    row.try_get(0)
 }
 
-pub async fn db_update_session_info(
+/// Makes a consumed signup session usable again until `expires_at`, so that its buyer can sign up
+/// once more after their signup was rolled back
+pub async fn db_release_buyer_signup_session(
     db_client: &Client,
     session_id: &uuid::Uuid,
-    user_id: &uuid::Uuid,
-    is_used: bool,
+    expires_at: &NaiveDateTime,
 ) -> Result<u64, tokio_postgres::Error> {
-    let res = db_client
-        .execute(
-            &format!(
-                "UPDATE {} SET is_used = $1::BOOLEAN, user_id = $2::UUID WHERE id = $3::UUID",
-                *SESSIONS_TABLE
-            ),
-            &[&is_used, &user_id, &session_id],
-        )
-        .await;
-    res
+    let _timer = db_timer("db_release_buyer_signup_session");
+    update::<DbBuyerSignupSession>()
+        .set("is_consumed", &false)
+        .set("expires_at", expires_at)
+        .filter(cond("id = {}::UUID").bind(&session_id))
+        .filter(cond("is_consumed"))
+        .execute(db_client)
+        .await
 }
 
-pub async fn db_get_events(
+/// Marks a verified, unexpired signup session as used. Returns `None` if it was already consumed
+pub async fn db_consume_buyer_signup_session(
     db_client: &Client,
-    event_id: Option<uuid::Uuid>,
-    event_slug: Option<String>,
-    event_filter: Option<EventFilter>,
-) -> Result<Vec<DbEvent>, tokio_postgres::Error> {
-    let mut query = format!(
-        "SELECT {} FROM {} WHERE ($1::UUID is NULL OR id = $1::UUID) AND ($2::VARCHAR is NULL OR event_slug = $2::VARCHAR)",
-        *EVENTS_TABLE_FIELDS, *EVENTS_TABLE
+    session_id: &uuid::Uuid,
+) -> Result<Option<DbBuyerSignupSession>, tokio_postgres::Error> {
+    let _timer = db_timer("db_consume_buyer_signup_session");
+    let update_query = format!(
+        "UPDATE {}
+            SET is_consumed = 't'
+         WHERE id = $1::UUID AND is_verified AND NOT is_consumed AND expires_at > $2::TIMESTAMP
+         RETURNING {}",
+        *BUYER_SIGNUP_SESSIONS_TABLE, *BUYER_SIGNUP_SESSIONS_TABLE_FIELDS
     );
-    let mut query_values: Vec<&(dyn ToSql + Sync)> = vec![&event_id, &event_slug];
-    if let Some(event_filter) = event_filter {
-        match event_filter {
-            EventFilter::Featured => {
-                query = format!("{} AND (is_featured = $2::BOOLEAN)", query);
-                query_values.extend_from_slice(&[&true]);
-            }
-            EventFilter::NoneFeatured => {
-                query = format!("{} AND (is_featured = $2::BOOLEAN)", query);
-                query_values.extend_from_slice(&[&false]);
-            }
-            EventFilter::All => (),
-        }
-    }
-    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
-    let events: Result<Vec<_>, _> = rows.into_iter().map(|r| DbEvent::try_from(r)).collect();
-    events
+    let row = db_client
+        .query_opt(&update_query, &[&session_id, &sql_timestamp(None)])
+        .await?;
+    row.map(DbBuyerSignupSession::try_from).transpose()
 }
 
-pub async fn db_get_user_by_id(
+/// Replaces the code hash of an unverified signup session that was last sent before
+/// `sent_before` and has resends left, restarting its attempts and expiry. Returns `None`
+/// otherwise
+pub async fn db_resend_buyer_signup_session(
     db_client: &Client,
-    user_id: &uuid::Uuid,
-) -> Result<DbUser, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE ($1::UUID is NULL OR id = $1::UUID)",
-        *USERS_TABLE_FIELDS, *USERS_TABLE
+    session_id: &uuid::Uuid,
+    verification_code_hash: &str,
+    expires_at: &NaiveDateTime,
+    sent_before: &NaiveDateTime,
+    max_resends: i32,
+) -> Result<Option<DbBuyerSignupSession>, tokio_postgres::Error> {
+    let _timer = db_timer("db_resend_buyer_signup_session");
+    let update_query = format!(
+        "UPDATE {}
+            SET verification_code_hash = $2::VARCHAR,
+            expires_at = $3::TIMESTAMP,
+            attempts = 0,
+            resend_count = resend_count + 1,
+            last_sent_at = $4::TIMESTAMP
+         WHERE id = $1::UUID AND NOT is_verified AND NOT is_consumed
+            AND last_sent_at <= $5::TIMESTAMP AND resend_count < $6::INTEGER
+         RETURNING {}",
+        *BUYER_SIGNUP_SESSIONS_TABLE, *BUYER_SIGNUP_SESSIONS_TABLE_FIELDS
     );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&user_id];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbUser::try_from(row)
+    let row = db_client
+        .query_opt(
+            &update_query,
+            &[
+                &session_id,
+                &verification_code_hash,
+                &expires_at,
+                &sql_timestamp(None),
+                &sent_before,
+                &max_resends,
+            ],
+        )
+        .await?;
+    row.map(DbBuyerSignupSession::try_from).transpose()
 }
 
-pub async fn db_get_users_by_username(
+/// Marks an unexpired recovery session as recovered and used. Returns `None` if it was already consumed
+pub async fn db_consume_buyer_recovery_session(
     db_client: &Client,
-    username: &str,
-) -> Result<Vec<DbUser>, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE username = $1::VARCHAR",
-        *USERS_TABLE_FIELDS, *USERS_TABLE
+    session_id: &uuid::Uuid,
+) -> Result<Option<DbBuyerRecoverySession>, tokio_postgres::Error> {
+    let _timer = db_timer("db_consume_buyer_recovery_session");
+    let update_query = format!(
+        "UPDATE {}
+            SET is_recovered = 't', is_consumed = 't'
+         WHERE id = $1::UUID AND NOT is_consumed AND expires_at > $2::TIMESTAMP
+         RETURNING {}",
+        *BUYER_RECOVERY_SESSIONS_TABLE, *BUYER_RECOVERY_SESSIONS_TABLE_FIELDS
     );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&username];
-    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
-    let users: Result<Vec<_>, _> = rows.into_iter().map(|r| DbUser::try_from(r)).collect();
-    users
+    let row = db_client
+        .query_opt(&update_query, &[&session_id, &sql_timestamp(None)])
+        .await?;
+    row.map(DbBuyerRecoverySession::try_from).transpose()
 }
 
-pub async fn db_get_user_by_username(
+pub async fn db_insert_signin_challenge(
     db_client: &Client,
-    username: &str,
-) -> Result<DbUser, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE username = $1::VARCHAR",
-        *USERS_TABLE_FIELDS, *USERS_TABLE
-    );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&username];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbUser::try_from(row)
+    challenge: &DbSigninChallenge,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_signin_challenge");
+    insert::<DbSigninChallenge>()
+        .values(&[
+            &challenge.id,
+            &challenge.created_at,
+            &challenge.wallet_id,
+            &challenge.nonce,
+            &challenge.expires_at,
+            &challenge.is_consumed,
+        ])
+        .execute(db_client)
+        .await
 }
 
-pub async fn db_get_user_by_email(
+/// Marks the unexpired challenge of the wallet with the nonce as used. Returns `None` if there is
+/// none or it was already consumed
+pub async fn db_consume_signin_challenge(
     db_client: &Client,
-    email: &str,
-) -> Result<DbUser, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE email = $1::VARCHAR",
-        *USERS_TABLE_FIELDS, *USERS_TABLE
-    );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&email];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbUser::try_from(row)
+    wallet_id: &str,
+    nonce: &str,
+) -> Result<Option<DbSigninChallenge>, tokio_postgres::Error> {
+    let _timer = db_timer("db_consume_signin_challenge");
+    let now = sql_timestamp(None);
+    update::<DbSigninChallenge>()
+        .set("is_consumed", &true)
+        .filter(cond("wallet_id = {}").bind(&wallet_id))
+        .filter(cond("nonce = {}").bind(&nonce))
+        .filter(cond("NOT is_consumed"))
+        .filter(cond("expires_at > {}::TIMESTAMP").bind(&now))
+        .fetch_opt(db_client)
+        .await
 }
 
-pub async fn db_get_user_by_name(
+/// Stores a new totp secret of the user, replacing one not enabled yet. Returns `None` if
+/// two-factor authentication is already enabled
+pub async fn db_upsert_user_totp(
     db_client: &Client,
-    name: &str,
-) -> Result<DbUser, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE name = $1::VARCHAR",
-        *USERS_TABLE_FIELDS, *USERS_TABLE
-    );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&name];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbUser::try_from(row)
+    user_totp: &DbUserTotp,
+) -> Result<Option<DbUserTotp>, tokio_postgres::Error> {
+    let _timer = db_timer("db_upsert_user_totp");
+    query(format!(
+        "INSERT INTO {table} ({fields})
+            VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (user_id) DO UPDATE
+            SET created_at = EXCLUDED.created_at,
+            encrypted_secret = EXCLUDED.encrypted_secret,
+            last_used_step = EXCLUDED.last_used_step
+            WHERE {table}.enabled_at IS NULL
+         RETURNING {fields}",
+        table = DbUserTotp::TABLE,
+        fields = DbUserTotp::fields()
+    ))
+    .bind(&user_totp.user_id)
+    .bind(&user_totp.created_at)
+    .bind(&user_totp.encrypted_secret)
+    .bind(&user_totp.enabled_at)
+    .bind(&user_totp.last_used_step)
+    .fetch_opt(db_client)
+    .await
 }
 
-pub async fn db_get_user_by_phone_number(
+pub async fn db_get_user_totp(
     db_client: &Client,
-    phone_number: &str,
-) -> Result<DbUser, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE phone_number = $1::VARCHAR",
-        *USERS_TABLE_FIELDS, *USERS_TABLE
-    );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&phone_number];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbUser::try_from(row)
+    user_id: &uuid::Uuid,
+) -> Result<Option<DbUserTotp>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_user_totp");
+    select::<DbUserTotp>()
+        .filter(cond("user_id = {}::UUID").bind(&user_id))
+        .fetch_opt(db_client)
+        .await
 }
 
-pub async fn db_get_user_by_wallet_id(
+/// Enables two-factor authentication once a code of the step was verified. Returns `None` if it
+/// is already enabled or the step was used
+pub async fn db_enable_user_totp(
     db_client: &Client,
-    wallet_id: &str,
-) -> Result<DbUser, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE wallet_id = $1::VARCHAR",
-        *USERS_TABLE_FIELDS, *USERS_TABLE
-    );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&wallet_id];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbUser::try_from(row)
+    user_id: &uuid::Uuid,
+    step: i64,
+) -> Result<Option<DbUserTotp>, tokio_postgres::Error> {
+    let _timer = db_timer("db_enable_user_totp");
+    let now = sql_timestamp(None);
+    update::<DbUserTotp>()
+        .set("enabled_at", &now)
+        .set("last_used_step", &step)
+        .filter(cond("user_id = {}::UUID").bind(&user_id))
+        .filter(cond("enabled_at IS NULL"))
+        .filter(cond("last_used_step < {}").bind(&step))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Records the step of an accepted sign-in code. Returns `None` if the step, or a later one, was
+/// used already
+pub async fn db_use_user_totp_step(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    step: i64,
+) -> Result<Option<DbUserTotp>, tokio_postgres::Error> {
+    let _timer = db_timer("db_use_user_totp_step");
+    update::<DbUserTotp>()
+        .set("last_used_step", &step)
+        .filter(cond("user_id = {}::UUID").bind(&user_id))
+        .filter(cond("enabled_at IS NOT NULL"))
+        .filter(cond("last_used_step < {}").bind(&step))
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_insert_totp_challenge(
+    db_client: &Client,
+    challenge: &DbTotpChallenge,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_totp_challenge");
+    insert::<DbTotpChallenge>()
+        .values(&[
+            &challenge.id,
+            &challenge.created_at,
+            &challenge.user_id,
+            &challenge.expires_at,
+            &challenge.attempts,
+            &challenge.is_consumed,
+        ])
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_get_totp_challenge(
+    db_client: &Client,
+    challenge_id: &uuid::Uuid,
+) -> Result<Option<DbTotpChallenge>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_totp_challenge");
+    select::<DbTotpChallenge>()
+        .filter(cond("id = {}::UUID").bind(&challenge_id))
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_increment_totp_challenge_attempts(
+    db_client: &Client,
+    challenge_id: &uuid::Uuid,
+) -> Result<i32, tokio_postgres::Error> {
+    let _timer = db_timer("db_increment_totp_challenge_attempts");
+    let update_query = format!(
+        "UPDATE {} SET attempts = attempts + 1 WHERE id = $1::UUID RETURNING attempts",
+        *TOTP_CHALLENGES_TABLE
+    );
+    let row = db_client.query_one(&update_query, &[&challenge_id]).await?;
+    row.try_get(0)
+}
+
+/// Marks the unexpired challenge as used. Returns `None` if it expired or was already consumed
+pub async fn db_consume_totp_challenge(
+    db_client: &Client,
+    challenge_id: &uuid::Uuid,
+) -> Result<Option<DbTotpChallenge>, tokio_postgres::Error> {
+    let _timer = db_timer("db_consume_totp_challenge");
+    let now = sql_timestamp(None);
+    update::<DbTotpChallenge>()
+        .set("is_consumed", &true)
+        .filter(cond("id = {}::UUID").bind(&challenge_id))
+        .filter(cond("NOT is_consumed"))
+        .filter(cond("expires_at > {}::TIMESTAMP").bind(&now))
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_insert_impersonation(
+    db_client: &Client,
+    impersonation: &DbImpersonation,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_impersonation");
+    insert::<DbImpersonation>()
+        .values(&[
+            &impersonation.id,
+            &impersonation.created_at,
+            &impersonation.admin_id,
+            &impersonation.user_id,
+            &impersonation.expires_at,
+            &impersonation.revoked_at,
+        ])
+        .execute(db_client)
+        .await
+}
+
+/// The impersonation if it is neither revoked nor expired
+pub async fn db_get_active_impersonation(
+    db_client: &Client,
+    impersonation_id: &uuid::Uuid,
+) -> Result<Option<DbImpersonation>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_active_impersonation");
+    let now = sql_timestamp(None);
+    select::<DbImpersonation>()
+        .filter(cond("id = {}::UUID").bind(&impersonation_id))
+        .filter(cond("revoked_at IS NULL"))
+        .filter(cond("expires_at > {}::TIMESTAMP").bind(&now))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Revokes an active impersonation. Returns `None` if there is none
+pub async fn db_revoke_impersonation(
+    db_client: &Client,
+    impersonation_id: &uuid::Uuid,
+) -> Result<Option<DbImpersonation>, tokio_postgres::Error> {
+    let _timer = db_timer("db_revoke_impersonation");
+    let now = sql_timestamp(None);
+    update::<DbImpersonation>()
+        .set("revoked_at", &now)
+        .filter(cond("id = {}::UUID").bind(&impersonation_id))
+        .filter(cond("revoked_at IS NULL"))
+        .filter(cond("expires_at > {}::TIMESTAMP").bind(&now))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Deletes login, signup and recovery sessions and sign-in and totp challenges that expired before
+/// `expired_before`
+pub async fn db_purge_stale_sessions(
+    db_client: &Client,
+    expired_before: &NaiveDateTime,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_purge_stale_sessions");
+    let mut purged = 0;
+    for table in [
+        &*SESSIONS_TABLE,
+        &*BUYER_SIGNUP_SESSIONS_TABLE,
+        &*BUYER_RECOVERY_SESSIONS_TABLE,
+        &*SIGNIN_CHALLENGES_TABLE,
+        &*TOTP_CHALLENGES_TABLE,
+        &*LOGIN_ATTEMPTS_TABLE,
+    ] {
+        purged += db_client
+            .execute(
+                &format!("DELETE FROM {} WHERE expires_at < $1::TIMESTAMP", table),
+                &[&expired_before],
+            )
+            .await?;
+    }
+    Ok(purged)
+}
+
+/// The user's failed password sign-ins not forgotten yet, per address
+pub async fn db_get_login_attempts(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    now: &NaiveDateTime,
+) -> Result<Vec<DbLoginAttempt>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_login_attempts");
+    select::<DbLoginAttempt>()
+        .filter(cond("user_id = {}::UUID").bind(&user_id))
+        .filter(cond("expires_at > {}::TIMESTAMP").bind(&now))
+        .fetch_all(db_client)
+        .await
+}
+
+/// Counts a failed password sign-in of the user from the address, starting over once the
+/// previous ones are forgotten
+pub async fn db_record_failed_login(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    ip_address: &str,
+    failed_at: &NaiveDateTime,
+    expires_at: &NaiveDateTime,
+) -> Result<DbLoginAttempt, tokio_postgres::Error> {
+    let _timer = db_timer("db_record_failed_login");
+    query(format!(
+        "INSERT INTO {table} ({fields})
+            VALUES ($1::UUID, $2::VARCHAR, 1, $3::TIMESTAMP, $4::TIMESTAMP)
+         ON CONFLICT (user_id, ip_address) DO UPDATE
+            SET failed_attempts = CASE WHEN {table}.expires_at > EXCLUDED.last_failed_at
+                THEN {table}.failed_attempts + 1 ELSE 1 END,
+            last_failed_at = EXCLUDED.last_failed_at,
+            expires_at = EXCLUDED.expires_at
+         RETURNING {fields}",
+        table = DbLoginAttempt::TABLE,
+        fields = DbLoginAttempt::fields()
+    ))
+    .bind(&user_id)
+    .bind(&ip_address)
+    .bind(&failed_at)
+    .bind(&expires_at)
+    .fetch_one(db_client)
+    .await
+}
+
+/// Forgets the user's failed password sign-ins, from the address or from all of them
+pub async fn db_clear_login_attempts(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    ip_address: Option<&str>,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_clear_login_attempts");
+    match &ip_address {
+        Some(ip_address) => query(format!(
+            "DELETE FROM {} WHERE user_id = $1::UUID AND ip_address = $2::VARCHAR",
+            DbLoginAttempt::TABLE
+        ))
+        .bind(&user_id)
+        .bind(ip_address),
+        None => query(format!(
+            "DELETE FROM {} WHERE user_id = $1::UUID",
+            DbLoginAttempt::TABLE
+        ))
+        .bind(&user_id),
+    }
+    .execute(db_client)
+    .await
+}
+
+pub async fn db_get_session_by_login_code(
+    db_client: &Client,
+    login_code: &str,
+) -> Result<DbSession, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_session_by_login_code");
+    select::<DbSession>()
+        .filter(cond("login_code = {}::VARCHAR").bind(&login_code))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_update_session_info(
+    db_client: &Client,
+    session_id: &uuid::Uuid,
+    user_id: &uuid::Uuid,
+    is_used: bool,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_session_info");
+    update::<DbSession>()
+        .set("is_used", &is_used)
+        .set("user_id", &user_id)
+        .filter(cond("id = {}::UUID").bind(&session_id))
+        .execute(db_client)
+        .await
+}
+
+/// The filters of an events listing, all of them combined with AND
+#[derive(Debug, Clone, Default)]
+pub struct EventsFilter {
+    pub id: Option<uuid::Uuid>,
+    pub event_slug: Option<String>,
+    pub filter: Option<EventFilter>,
+    /// Empty matches any status
+    pub statuses: Vec<EventStatus>,
+    pub created_by_user: Option<uuid::Uuid>,
+    /// Matches the events the user created or that an organization the user is a member of manages
+    pub managed_by_user: Option<uuid::Uuid>,
+    pub starts_after: Option<NaiveDateTime>,
+    pub starts_before: Option<NaiveDateTime>,
+    pub category_slug: Option<String>,
+    pub tag: Option<String>,
+}
+
+pub async fn db_get_events(
+    db_client: &Client,
+    events_filter: &EventsFilter,
+) -> Result<Vec<DbEvent>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_events");
+    let statuses = events_filter
+        .statuses
+        .iter()
+        .map(|status| i16::from(*status))
+        .collect::<Vec<_>>();
+
+    let mut conditions = vec![cond("deleted_at IS NULL")];
+    if let Some(id) = events_filter.id.as_ref() {
+        conditions.push(cond("id = {}::UUID").bind(id));
+    }
+    if let Some(event_slug) = events_filter.event_slug.as_ref() {
+        conditions.push(cond("event_slug = {}::VARCHAR").bind(event_slug));
+    }
+    match events_filter.filter {
+        Some(EventFilter::Featured) => {
+            conditions.push(cond("is_featured = {}::BOOLEAN").bind(&true))
+        }
+        Some(EventFilter::NoneFeatured) => {
+            conditions.push(cond("is_featured = {}::BOOLEAN").bind(&false))
+        }
+        Some(EventFilter::Archived) | Some(EventFilter::All) | None => (),
+    }
+    // archived events are only listed on request
+    if events_filter.filter == Some(EventFilter::Archived) {
+        conditions.push(cond("archived"));
+    } else {
+        conditions.push(cond("NOT archived"));
+    }
+    if !statuses.is_empty() {
+        conditions.push(cond("event_status = ANY({}::SMALLINT[])").bind(&statuses));
+    }
+    if let Some(created_by_user) = events_filter.created_by_user.as_ref() {
+        conditions.push(cond("created_by_user = {}::UUID").bind(created_by_user));
+    }
+    let member_condition = format!(
+        "organization_id IN (SELECT organization_id FROM {} WHERE user_id = {{}}::UUID)",
+        DbOrganizationMember::TABLE
+    );
+    if let Some(managed_by_user) = events_filter.managed_by_user.as_ref() {
+        conditions.push(
+            cond("created_by_user = {}::UUID")
+                .bind(managed_by_user)
+                .or(cond(member_condition).bind(managed_by_user)),
+        );
+    }
+    if let Some(starts_after) = events_filter.starts_after.as_ref() {
+        conditions.push(cond("start_date >= {}::TIMESTAMP").bind(starts_after));
+    }
+    if let Some(starts_before) = events_filter.starts_before.as_ref() {
+        conditions.push(cond("start_date < {}::TIMESTAMP").bind(starts_before));
+    }
+    let category_condition = format!(
+        "category_id IN (SELECT id FROM {} WHERE slug = {{}}::VARCHAR)",
+        *CATEGORIES_TABLE
+    );
+    if let Some(category_slug) = events_filter.category_slug.as_ref() {
+        conditions.push(cond(category_condition).bind(category_slug));
+    }
+    let tag_condition = format!(
+        "EXISTS (SELECT 1 FROM {} WHERE event_tags.event_id = events.id AND event_tags.tag = {{}}::VARCHAR)",
+        *EVENT_TAGS_TABLE
+    );
+    if let Some(tag) = events_filter.tag.as_ref() {
+        conditions.push(cond(tag_condition).bind(tag));
+    }
+
+    select::<DbEvent>()
+        .filter(Condition::all(conditions))
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_get_user_by_id(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+) -> Result<DbUser, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_user_by_id");
+    select::<DbUser>()
+        .filter(cond("id = {}::UUID").bind(&user_id))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_users_by_ids(
+    db_client: &Client,
+    user_ids: &[uuid::Uuid],
+) -> Result<Vec<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_users_by_ids");
+    select::<DbUser>()
+        .filter(cond("id = ANY({}::UUID[])").bind(&user_ids))
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_get_users_by_username(
+    db_client: &Client,
+    username: &str,
+) -> Result<Vec<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_users_by_username");
+    select::<DbUser>()
+        .filter(cond("username = {}::VARCHAR").bind(&username))
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_get_user_by_username(
+    db_client: &Client,
+    username: &str,
+) -> Result<DbUser, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_user_by_username");
+    select::<DbUser>()
+        .filter(cond("username = {}::VARCHAR").bind(&username))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_user_by_email(
+    db_client: &Client,
+    email: &str,
+) -> Result<DbUser, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_user_by_email");
+    select::<DbUser>()
+        .filter(cond("email = {}::VARCHAR").bind(&email))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_user_by_name(
+    db_client: &Client,
+    name: &str,
+) -> Result<DbUser, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_user_by_name");
+    select::<DbUser>()
+        .filter(cond("name = {}::VARCHAR").bind(&name))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_user_by_phone_number(
+    db_client: &Client,
+    phone_number: &str,
+) -> Result<DbUser, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_user_by_phone_number");
+    select::<DbUser>()
+        .filter(cond("phone_number = {}::VARCHAR").bind(&phone_number))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_user_by_wallet_id(
+    db_client: &Client,
+    wallet_id: &str,
+) -> Result<DbUser, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_user_by_wallet_id");
+    select::<DbUser>()
+        .filter(cond("wallet_id = {}::VARCHAR").bind(&wallet_id))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_users(
+    db_client: &Client,
+    user_id: &Option<uuid::Uuid>,
+) -> Result<Vec<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_users");
+    select::<DbUser>()
+        .filter(
+            cond("({}::UUID is NULL OR id = {}::UUID)")
+                .bind(user_id)
+                .bind(user_id),
+        )
+        .fetch_all(db_client)
+        .await
+}
+
+/// Users whose wallet balance was synced the longest time ago, never synced ones first
+pub async fn db_get_users_for_balance_sync(
+    db_client: &Client,
+    limit: i64,
+) -> Result<Vec<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_users_for_balance_sync");
+    select::<DbUser>()
+        .order_by("wallet_balance_updated_at ASC NULLS FIRST")
+        .limit(&limit)
+        .fetch_all(db_client)
+        .await
+}
+
+/// The active buyers whose last synced wallet balance is below `threshold`, the least recently
+/// synced first
+pub async fn db_get_buyers_below_balance(
+    db_client: &Client,
+    threshold: &NearAmount,
+    limit: i64,
+) -> Result<Vec<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_buyers_below_balance");
+    select::<DbUser>()
+        .filter(cond("user_type = {}::SMALLINT").bind(&(Role::Buyer as i16)))
+        .filter(cond("deleted_at IS NULL"))
+        .filter(cond("wallet_flagged = FALSE"))
+        .filter(cond("wallet_balance::NUMERIC < {}::VARCHAR::NUMERIC").bind(threshold))
+        .order_by("wallet_balance_updated_at ASC NULLS FIRST")
+        .limit(&limit)
+        .fetch_all(db_client)
+        .await
+}
+
+/// Stores a synced wallet balance. A `None` balance only marks the user as synced
+pub async fn db_update_user_wallet_balance(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    wallet_balance: Option<&str>,
+) -> Result<DbUser, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_user_wallet_balance");
+    update::<DbUser>()
+        .set_expr(
+            cond("wallet_balance = COALESCE({}::VARCHAR, wallet_balance)").bind(&wallet_balance),
+        )
+        .set("wallet_balance_updated_at", &sql_timestamp(None))
+        .filter(cond("id = {}::UUID").bind(&user_id))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_event_by_name(
+    db_client: &Client,
+    event_name: &str,
+) -> Result<DbEvent, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_event_by_name");
+    select::<DbEvent>()
+        .filter(cond("event_name = {}::VARCHAR").bind(&event_name))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_event_by_slug(
+    db_client: &Client,
+    event_slug: &str,
+) -> Result<DbEvent, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_event_by_slug");
+    select::<DbEvent>()
+        .filter(cond("event_slug = {}::VARCHAR").bind(&event_slug))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_event_by_id(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<DbEvent, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_event_by_id");
+    select::<DbEvent>()
+        .filter(cond("id = {}::UUID AND deleted_at IS NULL").bind(&id))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_soft_delete_event_by_id(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_soft_delete_event_by_id");
+    update::<DbEvent>()
+        .set("deleted_at", &sql_timestamp(None))
+        .filter(cond("id = {}::UUID AND deleted_at IS NULL").bind(&id))
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_soft_delete_ticket_by_id(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_soft_delete_ticket_by_id");
+    update::<DbTicket>()
+        .set("deleted_at", &sql_timestamp(None))
+        .filter(cond("id = {}::UUID AND deleted_at IS NULL").bind(&id))
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_update_event_archived(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    archived: bool,
+) -> Result<DbEvent, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_event_archived");
+    update::<DbEvent>()
+        .set("archived", &archived)
+        .filter(cond("id = {}::UUID AND deleted_at IS NULL").bind(&id))
+        .fetch_one(db_client)
+        .await
+}
+
+/// Moves an event from the status `from` to `to`. Returns `None` if it is not in `from` (anymore)
+pub async fn db_update_event_status(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    from: EventStatus,
+    to: EventStatus,
+) -> Result<Option<DbEvent>, tokio_postgres::Error> {
+    db_update_event_status_with_outbox(db_client, id, from, to, &[]).await
+}
+
+/// Moves an event from one status to another, writing the outbox messages along with the change
+pub async fn db_update_event_status_with_outbox(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    from: EventStatus,
+    to: EventStatus,
+    outbox: &[DbOutboxMessage],
+) -> Result<Option<DbEvent>, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_event_status");
+    let (from, to) = (i16::from(from), i16::from(to));
+    let change = update::<DbEvent>()
+        .set("event_status", &to)
+        .filter(cond("id = {}::UUID AND deleted_at IS NULL").bind(&id))
+        .filter(cond("event_status = {}").bind(&from))
+        .returning();
+    with_outbox(change, outbox).fetch_opt(db_client).await
+}
+
+/// Submits a minted event for review, the reason it was last rejected is cleared. Returns `None`
+/// if the event is not MINTING
+pub async fn db_submit_event_for_review(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    submitted_at: &NaiveDateTime,
+) -> Result<Option<DbEvent>, tokio_postgres::Error> {
+    let _timer = db_timer("db_submit_event_for_review");
+    let (from, to) = (
+        i16::from(EventStatus::Minting),
+        i16::from(EventStatus::PendingReview),
+    );
+    let no_reason: Option<&str> = None;
+    update::<DbEvent>()
+        .set("event_status", &to)
+        .set("review_submitted_at", submitted_at)
+        .set("review_rejection_reason", &no_reason)
+        .filter(cond("id = {}::UUID AND deleted_at IS NULL").bind(&id))
+        .filter(cond("event_status = {}").bind(&from))
+        .returning()
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Settles the review of an event: approved events go FINAL, rejected ones back to MINTING with
+/// the rejection reason, to be resubmitted. The outbox messages are written along with the
+/// change. Returns `None` if the event is not pending review
+pub async fn db_review_event(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    to: EventStatus,
+    rejection_reason: Option<&str>,
+    outbox: &[DbOutboxMessage],
+) -> Result<Option<DbEvent>, tokio_postgres::Error> {
+    let _timer = db_timer("db_review_event");
+    let (from, to) = (i16::from(EventStatus::PendingReview), i16::from(to));
+    let change = update::<DbEvent>()
+        .set("event_status", &to)
+        .set("review_rejection_reason", &rejection_reason)
+        .filter(cond("id = {}::UUID AND deleted_at IS NULL").bind(&id))
+        .filter(cond("event_status = {}").bind(&from))
+        .returning();
+    with_outbox(change, outbox).fetch_opt(db_client).await
+}
+
+/// The events waiting for their review, the longest waiting first
+pub async fn db_get_events_pending_review(
+    db_client: &Client,
+) -> Result<Vec<DbEvent>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_events_pending_review");
+    let pending_review = i16::from(EventStatus::PendingReview);
+    select::<DbEvent>()
+        .filter(cond("event_status = {}").bind(&pending_review))
+        .filter(cond("deleted_at IS NULL"))
+        .order_by("review_submitted_at")
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_update_event_tickets_archived(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    archived: bool,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_event_tickets_archived");
+    update::<DbTicket>()
+        .set("archived", &archived)
+        .filter(cond("event_id = {}::UUID AND deleted_at IS NULL").bind(&event_id))
+        .execute(db_client)
+        .await
+}
+
+/// Hard-deletes an event, cascading to its tickets, reservations and assets
+pub async fn db_purge_event_by_id(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_purge_event_by_id");
+    let res = db_client
+        .execute(
+            &format!("DELETE FROM {} WHERE id = $1::UUID", *EVENTS_TABLE),
+            &[&id],
+        )
+        .await;
+    res
+}
+
+pub async fn db_get_tickets_by_event_id(
+    db_client: &Client,
+    event_id: &Option<uuid::Uuid>,
+) -> Result<Vec<DbTicket>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_tickets_by_event_id");
+    select::<DbTicket>()
+        .filter(
+            cond("({}::UUID is NULL OR event_id = {}::UUID) AND deleted_at IS NULL")
+                .bind(event_id)
+                .bind(event_id),
+        )
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_get_tickets_by_event_ids(
+    db_client: &Client,
+    event_ids: &[uuid::Uuid],
+) -> Result<Vec<DbTicket>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_tickets_by_event_ids");
+    select::<DbTicket>()
+        .filter(cond("event_id = ANY({}::UUID[]) AND deleted_at IS NULL").bind(&event_ids))
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_insert_ticket_price_tier(
+    db_client: &Client,
+    db_price_tier: &DbTicketPriceTier,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_ticket_price_tier");
+    insert::<DbTicketPriceTier>()
+        .values(&[
+            &db_price_tier.id,
+            &db_price_tier.created_at,
+            &db_price_tier.ticket_id,
+            &db_price_tier.event_id,
+            &db_price_tier.tier_name,
+            &db_price_tier.price,
+            &db_price_tier.starts_at,
+            &db_price_tier.ends_at,
+            &db_price_tier.quantity_cap,
+            &db_price_tier.quantity_sold,
+        ])
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_get_ticket_price_tier(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<Option<DbTicketPriceTier>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_ticket_price_tier");
+    select::<DbTicketPriceTier>()
+        .filter(cond("id = {}::UUID").bind(&id))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// The price tiers of the tickets, the earliest starting first
+pub async fn db_get_price_tiers_by_ticket_ids(
+    db_client: &Client,
+    ticket_ids: &[uuid::Uuid],
+) -> Result<Vec<DbTicketPriceTier>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_price_tiers_by_ticket_ids");
+    select::<DbTicketPriceTier>()
+        .filter(cond("ticket_id = ANY({}::UUID[])").bind(&ticket_ids))
+        .order_by("starts_at, created_at")
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_delete_ticket_price_tier(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_delete_ticket_price_tier");
+    query(format!(
+        "DELETE FROM {} WHERE id = $1::UUID",
+        DbTicketPriceTier::TABLE
+    ))
+    .bind(&id)
+    .execute(db_client)
+    .await
+}
+
+/// Sells `quantity` tickets at the tier's price if it is open at `now` and not sold out. Returns
+/// `None` if it is not, e.g. a concurrent reservation took its last tickets
+pub async fn db_claim_ticket_price_tier(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    quantity: i32,
+    now: &NaiveDateTime,
+) -> Result<Option<DbTicketPriceTier>, tokio_postgres::Error> {
+    let _timer = db_timer("db_claim_ticket_price_tier");
+    update::<DbTicketPriceTier>()
+        .set_expr(cond("quantity_sold = quantity_sold + {}::INTEGER").bind(&quantity))
+        .filter(cond("id = {}::UUID").bind(&id))
+        .filter(cond("starts_at <= {}::TIMESTAMP").bind(&now))
+        .filter(cond("(ends_at IS NULL OR ends_at > {}::TIMESTAMP)").bind(&now))
+        .filter(
+            cond("(quantity_cap IS NULL OR quantity_sold + {}::INTEGER <= quantity_cap)")
+                .bind(&quantity),
+        )
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Gives back the tickets claimed at the tier's price, e.g. when their reservation failed
+pub async fn db_release_ticket_price_tier(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    quantity: i32,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_release_ticket_price_tier");
+    update::<DbTicketPriceTier>()
+        .set_expr(cond("quantity_sold = GREATEST(quantity_sold - {}::INTEGER, 0)").bind(&quantity))
+        .filter(cond("id = {}::UUID").bind(&id))
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_get_ticket_by_slug(
+    db_client: &Client,
+    ticket_slug: &str,
+) -> Result<DbTicket, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_ticket_by_slug");
+    select::<DbTicket>()
+        .filter(cond("ticket_slug = {}::VARCHAR").bind(&ticket_slug))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_ticket_by_id(
+    db_client: &Client,
+    ticket_id: &uuid::Uuid,
+) -> Result<DbTicket, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_ticket_by_id");
+    select::<DbTicket>()
+        .filter(cond("id = {}::UUID AND deleted_at IS NULL").bind(&ticket_id))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_asset_file(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<AssetFile, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_asset_file");
+    select::<AssetFile>()
+        .filter(cond("id = {}::UUID").bind(&id))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_files_for_event(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+) -> Result<Vec<AssetFile>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_files_for_event");
+    select::<AssetFile>()
+        .filter(cond("event_id = {}::UUID").bind(&event_id))
+        .fetch_all(db_client)
+        .await
+}
+
+/// Marks the uploaded asset as confirmed, in the role it now has for its event
+pub async fn db_confirm_asset_file(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    asset_role: AssetRole,
+) -> Result<AssetFile, tokio_postgres::Error> {
+    let _timer = db_timer("db_confirm_asset_file");
+    update::<AssetFile>()
+        .set_expr(cond("is_confirmed = 't'"))
+        .set("asset_role", &i16::from(asset_role))
+        .filter(cond("id = {}").bind(&id))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn update_file_ipfs_hash(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    hash: &String,
+) -> Result<AssetFile, tokio_postgres::Error> {
+    let _timer = db_timer("update_file_ipfs_hash");
+    update::<AssetFile>()
+        .set("ipfs_hash", hash)
+        .filter(cond("id = {} AND ipfs_hash is NULL").bind(&id))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn insert_asset_file(
+    db_client: &Client,
+    file: &AssetFile,
+) -> Result<AssetFile, tokio_postgres::Error> {
+    let _timer = db_timer("insert_asset_file");
+    insert::<AssetFile>()
+        .values(&[
+            &file.id,
+            &file.s3_bucket,
+            &file.s3_absolute_key,
+            &file.ipfs_hash,
+            &file.event_id,
+            &file.content_type,
+            &file.is_confirmed,
+            &file.thumbnail_key,
+            &file.cover_key,
+            &file.og_image_key,
+            &file.asset_role.map(i16::from),
+            &file.position,
+        ])
+        .execute(db_client)
+        .await?;
+
+    Ok(file.clone())
+}
+
+pub async fn db_get_files_by_event_ids(
+    db_client: &Client,
+    event_ids: &[uuid::Uuid],
+) -> Result<Vec<AssetFile>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_files_by_event_ids");
+    select::<AssetFile>()
+        .filter(cond("event_id = ANY({}::UUID[])").bind(&event_ids))
+        .fetch_all(db_client)
+        .await
+}
+
+/// The gallery assets of the events, ordered by event and position
+pub async fn db_get_gallery_assets_by_event_ids(
+    db_client: &Client,
+    event_ids: &[uuid::Uuid],
+) -> Result<Vec<AssetFile>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_gallery_assets_by_event_ids");
+    let asset_role = i16::from(AssetRole::Gallery);
+    select::<AssetFile>()
+        .filter(cond("event_id = ANY({}::UUID[])").bind(&event_ids))
+        .filter(cond("asset_role = {}::SMALLINT").bind(&asset_role))
+        .order_by("event_id, position")
+        .fetch_all(db_client)
+        .await
+}
+
+/// Confirms the uploaded asset as the last image of its event's gallery. `None` if the asset
+/// is not one of the event's or is attached already
+pub async fn db_add_gallery_asset(
+    db_client: &Client,
+    asset_id: &uuid::Uuid,
+    event_id: &uuid::Uuid,
+) -> Result<Option<AssetFile>, tokio_postgres::Error> {
+    let _timer = db_timer("db_add_gallery_asset");
+    let asset_role = i16::from(AssetRole::Gallery);
+    query(format!(
+        "UPDATE {table} SET is_confirmed = 't', asset_role = $3::SMALLINT,
+            position = (
+                SELECT COALESCE(MAX(position) + 1, 0) FROM {table}
+                WHERE event_id = $2::UUID AND asset_role = $3::SMALLINT
+            )
+         WHERE id = $1::UUID AND event_id = $2::UUID AND asset_role IS NULL
+         RETURNING {fields}",
+        table = *ASSET_FILES_TABLE,
+        fields = *ASSET_FILES_SELECT_FIELDS,
+    ))
+    .bind(&asset_id)
+    .bind(&event_id)
+    .bind(&asset_role)
+    .fetch_opt(db_client)
+    .await
+}
+
+/// Takes the asset out of its event's gallery, the images after it move up. `None` if the
+/// asset is not in the event's gallery
+pub async fn db_remove_gallery_asset(
+    db_client: &Client,
+    asset_id: &uuid::Uuid,
+    event_id: &uuid::Uuid,
+) -> Result<Option<AssetFile>, tokio_postgres::Error> {
+    let _timer = db_timer("db_remove_gallery_asset");
+    let asset_role = i16::from(AssetRole::Gallery);
+    query(format!(
+        "WITH removed AS (
+            SELECT id AS removed_id, position AS removed_position FROM {table}
+            WHERE id = $1::UUID AND event_id = $2::UUID AND asset_role = $3::SMALLINT
+            FOR UPDATE
+         ), shifted AS (
+            UPDATE {table} SET position = position - 1
+            FROM removed
+            WHERE event_id = $2::UUID AND asset_role = $3::SMALLINT
+                AND position > removed_position
+         )
+         UPDATE {table} SET asset_role = NULL, position = NULL
+         FROM removed
+         WHERE id = removed_id
+         RETURNING {fields}",
+        table = *ASSET_FILES_TABLE,
+        fields = *ASSET_FILES_SELECT_FIELDS,
+    ))
+    .bind(&asset_id)
+    .bind(&event_id)
+    .bind(&asset_role)
+    .fetch_opt(db_client)
+    .await
+}
+
+/// Moves the gallery images of the event to the positions of their ids in `asset_ids`.
+/// Returns the number of images moved
+pub async fn db_reorder_gallery_assets(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    asset_ids: &[uuid::Uuid],
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_reorder_gallery_assets");
+    let asset_role = i16::from(AssetRole::Gallery);
+    query(format!(
+        "UPDATE {table} SET position = (ordered.position - 1)::INTEGER
+         FROM unnest($2::UUID[]) WITH ORDINALITY AS ordered(id, position)
+         WHERE {table}.id = ordered.id AND {table}.event_id = $1::UUID
+            AND {table}.asset_role = $3::SMALLINT",
+        table = *ASSET_FILES_TABLE,
+    ))
+    .bind(&event_id)
+    .bind(&asset_ids)
+    .bind(&asset_role)
+    .execute(db_client)
+    .await
+}
+
+pub async fn db_insert_ticket_reservation(
+    db_client: &Client,
+    db_ticket_reservation: &DbTicketReservation,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_ticket_reservation");
+    insert::<DbTicketReservation>()
+        .values(&[
+            &db_ticket_reservation.id,
+            &db_ticket_reservation.created_at,
+            &db_ticket_reservation.verification_code,
+            &db_ticket_reservation.event_id,
+            &db_ticket_reservation.ticket_id,
+            &db_ticket_reservation.user_id,
+            &db_ticket_reservation.quantity,
+            &db_ticket_reservation.checked_in_at,
+            &db_ticket_reservation.price_tier_id,
+        ])
+        .execute(db_client)
+        .await
+}
+
+/// Reserves tickets in a single statement: the ticket's reserved quantity is only increased,
+/// and the reservation only inserted, while the ticket has enough left, the user stays within
+/// the maximum purchase quantity and the event is not cancelled. Returns `None` otherwise
+/// Reserves general admission tickets, returns `None` if there are not enough left, the buyer
+/// would exceed their purchase limit, the event is cancelled or the tickets are seated
+pub async fn db_reserve_ticket(
+    db_client: &Client,
+    db_ticket_reservation: &DbTicketReservation,
+) -> Result<Option<DbTicketReservation>, tokio_postgres::Error> {
+    let _timer = db_timer("db_reserve_ticket");
+    reserve_ticket(db_client, db_ticket_reservation, None).await
+}
+
+/// Reserves seated tickets along with their seats, one per ticket, which the buyer must hold.
+/// Returns `None` like [`db_reserve_ticket`], or if a seat is not held by the buyer anymore
+pub async fn db_reserve_seats(
+    db_client: &Client,
+    db_ticket_reservation: &DbTicketReservation,
+    seat_ids: &[uuid::Uuid],
+) -> Result<Option<DbTicketReservation>, tokio_postgres::Error> {
+    let _timer = db_timer("db_reserve_seats");
+    reserve_ticket(db_client, db_ticket_reservation, Some(seat_ids)).await
+}
+
+/// Reserves the tickets and the seats if given in a single statement, the limits being checked
+/// atomically with the insert
+async fn reserve_ticket(
+    db_client: &Client,
+    db_ticket_reservation: &DbTicketReservation,
+    seat_ids: Option<&[uuid::Uuid]>,
+) -> Result<Option<DbTicketReservation>, tokio_postgres::Error> {
+    let seated = seat_ids.is_some();
+    let seat_ids = seat_ids.unwrap_or_default();
+    let statement = format!(
+        "WITH held AS (
+            SELECT id FROM {seats}
+            WHERE id = ANY($10::UUID[]) AND ticket_id = $5::UUID AND reservation_id IS NULL
+                AND held_by = $6::UUID AND held_until > $2::TIMESTAMP
+            FOR UPDATE
+        ), claimed AS (
+            UPDATE {tickets}
+                SET quantity_reserved = quantity_reserved + $7::INTEGER
+            WHERE id = $5::UUID
+                AND (quantity_available IS NULL
+                    OR quantity_reserved + $7::INTEGER <= quantity_available)
+                AND (max_purchase_quantity IS NULL
+                    OR $7::INTEGER + (
+                        SELECT COALESCE(SUM(quantity), 0) FROM {reservations}
+                        WHERE ticket_id = $5::UUID AND user_id = $6::UUID
+                    ) <= max_purchase_quantity)
+                AND NOT EXISTS (
+                    SELECT 1 FROM {events}
+                    WHERE id = $4::UUID AND event_status = {cancelled}
+                )
+                AND CASE WHEN $11::BOOLEAN
+                    THEN CARDINALITY($10::UUID[]) = $7::INTEGER
+                        AND (SELECT COUNT(*) FROM held) = $7::INTEGER
+                    ELSE NOT EXISTS (SELECT 1 FROM {seats} WHERE ticket_id = $5::UUID)
+                END
+            RETURNING id
+        ), reserved AS (
+            INSERT INTO {reservations} ({fields})
+                SELECT $1::UUID, $2::TIMESTAMP, $3::VARCHAR, $4::UUID, claimed.id, $6::UUID,
+                    $7::INTEGER, $8::TIMESTAMP, $9::UUID
+                FROM claimed
+            RETURNING {fields}
+        ), seated AS (
+            UPDATE {seats} SET reservation_id = reserved.id, held_by = NULL, held_until = NULL
+            FROM reserved
+            WHERE {seats}.id IN (SELECT id FROM held)
+        )
+        SELECT {fields} FROM reserved",
+        seats = DbSeat::TABLE,
+        tickets = *TICKETS_TABLE,
+        reservations = *TICKET_RESERVATIONS_TABLE,
+        fields = *TICKET_RESERVATIONS_TABLE_FIELDS,
+        events = *EVENTS_TABLE,
+        cancelled = i16::from(EventStatus::Cancelled),
+    );
+
+    query(statement)
+        .bind(&db_ticket_reservation.id)
+        .bind(&db_ticket_reservation.created_at)
+        .bind(&db_ticket_reservation.verification_code)
+        .bind(&db_ticket_reservation.event_id)
+        .bind(&db_ticket_reservation.ticket_id)
+        .bind(&db_ticket_reservation.user_id)
+        .bind(&db_ticket_reservation.quantity)
+        .bind(&db_ticket_reservation.checked_in_at)
+        .bind(&db_ticket_reservation.price_tier_id)
+        .bind(&seat_ids)
+        .bind(&seated)
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Undoes reservations that did not go through as a whole, giving their tickets back and
+/// freeing their seats. Unlike a cancellation, nothing is refunded
+pub async fn db_undo_ticket_reservations(
+    db_client: &Client,
+    reservation_ids: &[uuid::Uuid],
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_undo_ticket_reservations");
+    query(format!(
+        "WITH undone AS (
+            DELETE FROM {reservations} WHERE id = ANY($1::UUID[]) RETURNING ticket_id, quantity
+        )
+        UPDATE {tickets} t
+            SET quantity_reserved = GREATEST(t.quantity_reserved - u.quantity, 0)
+        FROM (SELECT ticket_id, SUM(quantity)::INTEGER AS quantity
+            FROM undone GROUP BY ticket_id) u
+        WHERE t.id = u.ticket_id",
+        reservations = *TICKET_RESERVATIONS_TABLE,
+        tickets = *TICKETS_TABLE,
+    ))
+    .bind(&reservation_ids)
+    .execute(db_client)
+    .await
+}
+
+/// The reservations of an event with their buyer and ticket, oldest first
+pub async fn db_get_event_attendees(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+) -> Result<Vec<DbAttendee>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_event_attendees");
+    let query = format!(
+        "SELECT r.id AS reservation_id, r.created_at AS reserved_at, u.username, u.phone_number,
+                t.ticket_name, r.quantity, r.checked_in_at
+            FROM {} r
+            JOIN {} u ON u.id = r.user_id
+            JOIN {} t ON t.id = r.ticket_id
+         WHERE r.event_id = $1::UUID
+         ORDER BY r.created_at, r.id",
+        *TICKET_RESERVATIONS_TABLE, *USERS_TABLE, *TICKETS_TABLE
+    );
+    let query_values: Vec<&(dyn ToSql + Sync)> = vec![event_id];
+    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
+    let attendees: Result<Vec<_>, _> = rows.into_iter().map(DbAttendee::try_from).collect();
+    attendees
+}
+
+/// A page of the reservations of an event with their buyer and ticket, oldest first, only the
+/// checked in or only the not checked in ones if `redeemed` is given
+pub async fn db_get_event_reservations(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    redeemed: Option<bool>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<DbAttendee>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_event_reservations");
+    query(format!(
+        "SELECT r.id AS reservation_id, r.created_at AS reserved_at, u.username, u.phone_number,
+                t.ticket_name, r.quantity, r.checked_in_at
+            FROM {} r
+            JOIN {} u ON u.id = r.user_id
+            JOIN {} t ON t.id = r.ticket_id
+         WHERE r.event_id = $1::UUID
+            AND ($2::BOOLEAN IS NULL OR (r.checked_in_at IS NOT NULL) = $2::BOOLEAN)
+         ORDER BY r.created_at, r.id
+         LIMIT $3::BIGINT OFFSET $4::BIGINT",
+        *TICKET_RESERVATIONS_TABLE, *USERS_TABLE, *TICKETS_TABLE
+    ))
+    .bind(&event_id)
+    .bind(&redeemed)
+    .bind(&limit)
+    .bind(&offset)
+    .fetch_all(db_client)
+    .await
+}
+
+/*
+pub enum TicketReservationQueryItem {
+    VerificationCode(String),
+    UserId(i32),
+}
+
+pub async fn db_get_ticket_reservation(
+    db_client: &Client,
+    query_item: TicketReservationQueryItem,
+) -> Result<DbTicketReservation, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_ticket_reservation");
+    let (query, query_values) = match query_item {
+        TicketReservationQueryItem::VerificationCode(verification_code) => {
+            let query = format!(
+                "SELECT {} FROM {} WHERE verification_code = $1::VARCHAR",
+                *TICKET_RESERVATIONS_TABLE_FIELDS, *TICKET_RESERVATIONS_TABLE
+            );
+            let query_values: Vec<Box<(dyn ToSql + Sync)>> = vec![Box::new(verification_code)];
+            (query, query_values)
+        }
+        TicketReservationQueryItem::UserId(user_id) => {
+            let query = format!(
+                "SELECT {} FROM {} WHERE user_id = $1::INT",
+                *TICKET_RESERVATIONS_TABLE_FIELDS, *TICKET_RESERVATIONS_TABLE
+            );
+            let query_values: Vec<Box<(dyn ToSql + Sync)>> = vec![Box::new(user_id)];
+            (query, query_values)
+        }
+    };
+    let vec_of_refs = query_values
+        .iter()
+        .map(|x| &**x)
+        .collect::<Vec<&(dyn ToSql + Sync)>>();
+
+    let row: tokio_postgres::Row = db_client.query_one(&query, vec_of_refs.as_slice()).await?;
+    DbTicketReservation::try_from(row)
+}
+*/
+
+pub async fn db_get_ticket_reservations_by_code(
+    db_client: &Client,
+    verification_code: &str,
+) -> Result<Vec<DbTicketReservation>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_ticket_reservations_by_code");
+    select::<DbTicketReservation>()
+        .filter(cond("verification_code = {}::VARCHAR").bind(&verification_code))
+        .fetch_all(db_client)
+        .await
+}
+
+/// Checks in the reservations of the event for the verification code that are not checked in yet.
+/// Returns the reservations checked in
+pub async fn db_check_in_ticket_reservations(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    verification_code: &str,
+) -> Result<Vec<DbTicketReservation>, tokio_postgres::Error> {
+    let _timer = db_timer("db_check_in_ticket_reservations");
+    let now = sql_timestamp(None);
+    query(format!(
+        "UPDATE {} SET checked_in_at = $1::TIMESTAMP
+         WHERE event_id = $2::UUID AND verification_code = $3::VARCHAR AND checked_in_at IS NULL
+         RETURNING {}",
+        *TICKET_RESERVATIONS_TABLE, *TICKET_RESERVATIONS_TABLE_FIELDS
+    ))
+    .bind(&now)
+    .bind(&event_id)
+    .bind(&verification_code)
+    .fetch_all(db_client)
+    .await
+}
+
+pub async fn db_get_ticket_reservations_by_user_id(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+) -> Result<Vec<DbTicketReservation>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_ticket_reservations_by_user_id");
+    select::<DbTicketReservation>()
+        .filter(cond("user_id = {}::UUID").bind(&user_id))
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_get_ticket_reservation_by_id(
+    db_client: &Client,
+    reservation_id: &uuid::Uuid,
+) -> Result<DbTicketReservation, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_ticket_reservation_by_id");
+    select::<DbTicketReservation>()
+        .filter(cond("id = {}::UUID").bind(&reservation_id))
+        .fetch_one(db_client)
+        .await
+}
+
+/// Moves a reservation to a new owner. The current owner is part of the condition,
+/// so two concurrent transfers of the same reservation cannot both succeed.
+pub async fn db_update_ticket_reservation_owner(
+    db_client: &Client,
+    reservation_id: &uuid::Uuid,
+    from_user: &uuid::Uuid,
+    to_user: &uuid::Uuid,
+) -> Result<Option<DbTicketReservation>, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_ticket_reservation_owner");
+    update::<DbTicketReservation>()
+        .set("user_id", &to_user)
+        .filter(
+            cond("id = {}::UUID AND user_id = {}::UUID")
+                .bind(&reservation_id)
+                .bind(&from_user),
+        )
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_insert_ticket_transfer(
+    db_client: &Client,
+    db_ticket_transfer: &DbTicketTransfer,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_ticket_transfer");
+    insert::<DbTicketTransfer>()
+        .values(&[
+            &db_ticket_transfer.id,
+            &db_ticket_transfer.created_at,
+            &db_ticket_transfer.price,
+            &db_ticket_transfer.tx_hash,
+            &db_ticket_transfer.reservation_id,
+            &db_ticket_transfer.ticket_id,
+            &db_ticket_transfer.from_user,
+            &db_ticket_transfer.to_user,
+        ])
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_insert_ticket_listing(
+    db_client: &Client,
+    db_ticket_listing: &DbTicketListing,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_ticket_listing");
+    insert::<DbTicketListing>()
+        .values(&[
+            &db_ticket_listing.id,
+            &db_ticket_listing.created_at,
+            &db_ticket_listing.asking_price,
+            &db_ticket_listing.reservation_id,
+            &db_ticket_listing.ticket_id,
+            &db_ticket_listing.event_id,
+            &db_ticket_listing.seller_id,
+            &db_ticket_listing.cancelled_at,
+        ])
+        .execute(db_client)
+        .await
+}
+
+/// The listing of the reservation not cancelled yet, if any
+pub async fn db_get_active_ticket_listing_by_reservation_id(
+    db_client: &Client,
+    reservation_id: &uuid::Uuid,
+) -> Result<Option<DbTicketListing>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_active_ticket_listing_by_reservation_id");
+    select::<DbTicketListing>()
+        .filter(cond("reservation_id = {}::UUID AND cancelled_at IS NULL").bind(&reservation_id))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// The listings of the event's tickets not cancelled yet, the cheapest first
+pub async fn db_get_active_ticket_listings_by_event_id(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+) -> Result<Vec<DbTicketListing>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_active_ticket_listings_by_event_id");
+    select::<DbTicketListing>()
+        .filter(cond("event_id = {}::UUID AND cancelled_at IS NULL").bind(&event_id))
+        .order_by("asking_price::NUMERIC, created_at")
+        .fetch_all(db_client)
+        .await
+}
+
+/// Cancels the seller's listing, `None` if it is not theirs or cancelled already
+pub async fn db_cancel_ticket_listing(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    seller_id: &uuid::Uuid,
+) -> Result<Option<DbTicketListing>, tokio_postgres::Error> {
+    let _timer = db_timer("db_cancel_ticket_listing");
+    let cancelled_at = sql_timestamp(None);
+    update::<DbTicketListing>()
+        .set("cancelled_at", &cancelled_at)
+        .filter(cond("id = {}::UUID AND cancelled_at IS NULL").bind(&id))
+        .filter(cond("seller_id = {}::UUID").bind(&seller_id))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Cancels the reservation's listing, once the reservation changed hands
+pub async fn db_cancel_ticket_listings_by_reservation_id(
+    db_client: &Client,
+    reservation_id: &uuid::Uuid,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_cancel_ticket_listings_by_reservation_id");
+    let cancelled_at = sql_timestamp(None);
+    update::<DbTicketListing>()
+        .set("cancelled_at", &cancelled_at)
+        .filter(cond("reservation_id = {}::UUID AND cancelled_at IS NULL").bind(&reservation_id))
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_get_ticket_transfers_by_reservation_id(
+    db_client: &Client,
+    reservation_id: &uuid::Uuid,
+) -> Result<Vec<DbTicketTransfer>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_ticket_transfers_by_reservation_id");
+    select::<DbTicketTransfer>()
+        .filter(cond("reservation_id = {}::UUID").bind(&reservation_id))
+        .order_by("created_at")
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_insert_ticket_gift(
+    db_client: &Client,
+    db_ticket_gift: &DbTicketGift,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_ticket_gift");
+    insert::<DbTicketGift>()
+        .values(&[
+            &db_ticket_gift.id,
+            &db_ticket_gift.created_at,
+            &db_ticket_gift.reservation_id,
+            &db_ticket_gift.from_user,
+            &db_ticket_gift.to_user,
+            &db_ticket_gift.to_phone_number,
+            &db_ticket_gift.claim_code,
+            &db_ticket_gift.claimed_by,
+            &db_ticket_gift.claimed_at,
+        ])
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_insert_seller_document(
+    db_client: &Client,
+    db_seller_document: &DbSellerDocument,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_seller_document");
+    insert::<DbSellerDocument>()
+        .values(&[
+            &db_seller_document.id,
+            &db_seller_document.created_at,
+            &db_seller_document.user_id,
+            &(db_seller_document.document_kind as i16),
+            &db_seller_document.s3_bucket,
+            &db_seller_document.s3_absolute_key,
+            &db_seller_document.content_type,
+            &db_seller_document.is_confirmed,
+        ])
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_get_seller_document(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<Option<DbSellerDocument>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_seller_document");
+    select::<DbSellerDocument>()
+        .filter(cond("id = {}::UUID").bind(&id))
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_get_seller_documents_by_user_id(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+) -> Result<Vec<DbSellerDocument>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_seller_documents_by_user_id");
+    select::<DbSellerDocument>()
+        .filter(cond("user_id = {}::UUID").bind(&user_id))
+        .order_by("created_at")
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_confirm_seller_document(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<DbSellerDocument, tokio_postgres::Error> {
+    let _timer = db_timer("db_confirm_seller_document");
+    update::<DbSellerDocument>()
+        .set_expr(cond("is_confirmed = 't'"))
+        .filter(cond("id = {}::UUID").bind(&id))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_ticket_gift_by_claim_code(
+    db_client: &Client,
+    claim_code: &str,
+) -> Result<Option<DbTicketGift>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_ticket_gift_by_claim_code");
+    select::<DbTicketGift>()
+        .filter(cond("claim_code = {}").bind(&claim_code))
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_get_ticket_gift_by_reservation_id(
+    db_client: &Client,
+    reservation_id: &uuid::Uuid,
+) -> Result<Option<DbTicketGift>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_ticket_gift_by_reservation_id");
+    select::<DbTicketGift>()
+        .filter(cond("reservation_id = {}::UUID").bind(&reservation_id))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Marks the gift claimed by the user, `None` if it was already claimed
+pub async fn db_claim_ticket_gift(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    user_id: &uuid::Uuid,
+) -> Result<Option<DbTicketGift>, tokio_postgres::Error> {
+    let _timer = db_timer("db_claim_ticket_gift");
+    let claimed_at = sql_timestamp(None);
+    update::<DbTicketGift>()
+        .set("claimed_by", &user_id)
+        .set("claimed_at", &claimed_at)
+        .filter(cond("id = {}::UUID AND claimed_at IS NULL").bind(&id))
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_insert_promo_code(
+    db_client: &Client,
+    db_promo_code: &DbPromoCode,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_promo_code");
+    insert::<DbPromoCode>()
+        .values(&[
+            &db_promo_code.id,
+            &db_promo_code.created_at,
+            &db_promo_code.code,
+            &(db_promo_code.discount_type as i16),
+            &db_promo_code.discount_value,
+            &db_promo_code.max_uses,
+            &db_promo_code.used_count,
+            &db_promo_code.valid_from,
+            &db_promo_code.valid_until,
+            &db_promo_code.is_active,
+            &db_promo_code.event_id,
+            &db_promo_code.created_by_user,
+        ])
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_get_promo_code_by_id(
+    db_client: &Client,
+    promo_code_id: &uuid::Uuid,
+) -> Result<DbPromoCode, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_promo_code_by_id");
+    select::<DbPromoCode>()
+        .filter(cond("id = {}::UUID").bind(&promo_code_id))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_promo_code_by_code(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    code: &str,
+) -> Result<DbPromoCode, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_promo_code_by_code");
+    select::<DbPromoCode>()
+        .filter(cond("event_id = {}::UUID").bind(&event_id))
+        .filter(cond("code = {}::VARCHAR").bind(&code.to_uppercase()))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_promo_codes_by_event_id(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+) -> Result<Vec<DbPromoCode>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_promo_codes_by_event_id");
+    select::<DbPromoCode>()
+        .filter(cond("event_id = {}::UUID").bind(&event_id))
+        .order_by("created_at")
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_update_promo_code_is_active(
+    db_client: &Client,
+    promo_code_id: &uuid::Uuid,
+    is_active: bool,
+) -> Result<DbPromoCode, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_promo_code_is_active");
+    let update_query = format!(
+        "UPDATE {}
+            SET is_active = $1::BOOLEAN
+         WHERE id = $2::UUID
+         RETURNING {}",
+        *PROMO_CODES_TABLE, *PROMO_CODES_TABLE_FIELDS
+    );
+
+    let update_stmt = db_client.prepare(&update_query).await?;
+
+    let x = db_client
+        .query_one(&update_stmt, &[&is_active, &promo_code_id])
+        .await?;
+
+    x.try_into()
+}
+
+/// Atomically consumes `uses` usages of a promo code. Returns `None` when the code
+/// is inactive or would exceed its maximum number of uses.
+pub async fn db_consume_promo_code(
+    db_client: &Client,
+    promo_code_id: &uuid::Uuid,
+    uses: i32,
+) -> Result<Option<DbPromoCode>, tokio_postgres::Error> {
+    let _timer = db_timer("db_consume_promo_code");
+    let update_query = format!(
+        "UPDATE {}
+            SET used_count = used_count + $1::INTEGER
+         WHERE id = $2::UUID
+            AND is_active
+            AND (max_uses IS NULL OR used_count + $1::INTEGER <= max_uses)
+         RETURNING {}",
+        *PROMO_CODES_TABLE, *PROMO_CODES_TABLE_FIELDS
+    );
+
+    let update_stmt = db_client.prepare(&update_query).await?;
+
+    let row = db_client
+        .query_opt(&update_stmt, &[&uses, &promo_code_id])
+        .await?;
+
+    row.map(DbPromoCode::try_from).transpose()
+}
+
+/// Gives back `uses` usages consumed by a reservation that did not go through.
+pub async fn db_release_promo_code(
+    db_client: &Client,
+    promo_code_id: &uuid::Uuid,
+    uses: i32,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_release_promo_code");
+    let update_query = format!(
+        "UPDATE {}
+            SET used_count = GREATEST(used_count - $1::INTEGER, 0)
+         WHERE id = $2::UUID",
+        *PROMO_CODES_TABLE
+    );
+
+    let update_stmt = db_client.prepare(&update_query).await?;
+
+    db_client
+        .execute(&update_stmt, &[&uses, &promo_code_id])
+        .await
+}
+
+pub async fn db_insert_promo_code_usage(
+    db_client: &Client,
+    db_promo_code_usage: &DbPromoCodeUsage,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_promo_code_usage");
+    let insert_query = format!(
+        "INSERT INTO {}
+                ({})
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        *PROMO_CODE_USAGES_TABLE, *PROMO_CODE_USAGES_TABLE_FIELDS
+    );
+    let insert_stmt = db_client.prepare(&insert_query).await?;
+    let res = db_client
+        .execute(
+            &insert_stmt,
+            &[
+                &db_promo_code_usage.id,
+                &db_promo_code_usage.created_at,
+                &db_promo_code_usage.verification_code,
+                &db_promo_code_usage.original_price,
+                &db_promo_code_usage.discounted_price,
+                &db_promo_code_usage.promo_code_id,
+                &db_promo_code_usage.ticket_id,
+                &db_promo_code_usage.user_id,
+            ],
+        )
+        .await;
+    res
+}
+
+/// Drops the usages recorded for a reservation that did not go through
+pub async fn db_delete_promo_code_usages(
+    db_client: &Client,
+    verification_code: &str,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_delete_promo_code_usages");
+    query(format!(
+        "DELETE FROM {} WHERE verification_code = $1::VARCHAR",
+        *PROMO_CODE_USAGES_TABLE
+    ))
+    .bind(&verification_code)
+    .execute(db_client)
+    .await
+}
+
+pub async fn db_get_promo_code_usages_by_code(
+    db_client: &Client,
+    promo_code_id: &uuid::Uuid,
+) -> Result<Vec<DbPromoCodeUsage>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_promo_code_usages_by_code");
+    let query = format!(
+        "SELECT {} FROM {} WHERE promo_code_id = $1::UUID",
+        *PROMO_CODE_USAGES_TABLE_FIELDS, *PROMO_CODE_USAGES_TABLE
+    );
+    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&promo_code_id];
+    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
+    let usages: Result<Vec<_>, _> = rows
+        .into_iter()
+        .map(|r| DbPromoCodeUsage::try_from(r))
+        .collect();
+    usages
+}
+
+pub async fn db_update_event_asset_url(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    kind: EventAssetKind,
+    url: &str,
+) -> Result<DbEvent, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_event_asset_url");
+    // a new image invalidates the previously pinned one and its variants
+    let (column, ipfs_column) = match kind {
+        EventAssetKind::CoverPhoto => ("cover_photo_url", "cover_photo_ipfs_url"),
+        EventAssetKind::Thumbnail => ("thumbnail_url", "thumbnail_ipfs_url"),
+    };
+    let variant_columns = ImageVariant::for_kind(kind)
+        .iter()
+        .map(|variant| format!(", {} = NULL", variant_url_column(*variant)))
+        .collect::<String>();
+    let update_query = format!(
+        "UPDATE {}
+            SET {} = $1::VARCHAR,
+                {} = NULL{}
+         WHERE id = $2::UUID
+         RETURNING {}",
+        *EVENTS_TABLE, column, ipfs_column, variant_columns, *EVENTS_TABLE_FIELDS
+    );
+
+    let update_stmt = db_client.prepare(&update_query).await?;
+
+    let x = db_client
+        .query_one(&update_stmt, &[&url, &event_id])
+        .await?;
+
+    x.try_into()
+}
+
+/// Records where an event image was pinned, unless the image was replaced in the meantime
+pub async fn db_update_event_ipfs_url(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    kind: EventAssetKind,
+    asset_url: &str,
+    ipfs_url: &str,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_event_ipfs_url");
+    let (column, ipfs_column) = match kind {
+        EventAssetKind::CoverPhoto => ("cover_photo_url", "cover_photo_ipfs_url"),
+        EventAssetKind::Thumbnail => ("thumbnail_url", "thumbnail_ipfs_url"),
+    };
+    let update_query = format!(
+        "UPDATE {}
+            SET {} = $1::VARCHAR
+         WHERE id = $2::UUID AND {} = $3::VARCHAR",
+        *EVENTS_TABLE, ipfs_column, column
+    );
+    db_client
+        .execute(&update_query, &[&ipfs_url, &event_id, &asset_url])
+        .await
+}
+
+/// Records the keys of an asset's variants
+pub async fn db_update_asset_file_variant_keys(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    variant_keys: &[(ImageVariant, String)],
+) -> Result<AssetFile, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_asset_file_variant_keys");
+    variant_keys
+        .iter()
+        .fold(update::<AssetFile>(), |update, (variant, key)| {
+            update.set(variant_key_column(*variant), key)
+        })
+        .filter(cond("id = {}::UUID").bind(&id))
+        .fetch_one(db_client)
+        .await
+}
+
+/// Records the urls of an event image's variants, unless the image was replaced in the meantime
+pub async fn db_update_event_variant_urls(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    kind: EventAssetKind,
+    asset_url: &str,
+    variant_urls: &[(ImageVariant, String)],
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_event_variant_urls");
+    let column = match kind {
+        EventAssetKind::CoverPhoto => "cover_photo_url",
+        EventAssetKind::Thumbnail => "thumbnail_url",
+    };
+    variant_urls
+        .iter()
+        .fold(update::<DbEvent>(), |update, (variant, url)| {
+            update.set(variant_url_column(*variant), url)
+        })
+        .filter(cond("id = {}::UUID").bind(&event_id))
+        .filter(cond(format!("{} = {{}}", column)).bind(&asset_url))
+        .execute(db_client)
+        .await
+}
+
+fn variant_key_column(variant: ImageVariant) -> &'static str {
+    match variant {
+        ImageVariant::Thumbnail => "thumbnail_key",
+        ImageVariant::Cover => "cover_key",
+        ImageVariant::OgImage => "og_image_key",
+    }
+}
+
+fn variant_url_column(variant: ImageVariant) -> &'static str {
+    match variant {
+        ImageVariant::Thumbnail => "thumbnail_variant_url",
+        ImageVariant::Cover => "cover_variant_url",
+        ImageVariant::OgImage => "og_image_url",
+    }
+}
+
+pub async fn db_insert_job(
+    db_client: &Client,
+    db_job: &DbJob,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_job");
+    insert::<DbJob>()
+        .values(&[
+            &db_job.id,
+            &db_job.created_at,
+            &db_job.updated_at,
+            &db_job.run_at,
+            &i16::from(db_job.job_type),
+            &db_job.payload,
+            &i16::from(db_job.job_status),
+            &db_job.attempts,
+            &db_job.max_attempts,
+            &db_job.last_error,
+        ])
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_get_job_by_id(
+    db_client: &Client,
+    job_id: &uuid::Uuid,
+) -> Result<DbJob, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_job_by_id");
+    select::<DbJob>()
+        .filter(cond("id = {}::UUID").bind(&job_id))
+        .fetch_one(db_client)
+        .await
+}
+
+/// Picks the next due pending job and marks it as running in a single statement.
+/// `SKIP LOCKED` lets several workers poll the same table without handing out a job twice.
+pub async fn db_claim_next_job(db_client: &Client) -> Result<Option<DbJob>, tokio_postgres::Error> {
+    let _timer = db_timer("db_claim_next_job");
+    let now = sql_timestamp(None);
+    let update_query = format!(
+        "UPDATE {table}
+            SET job_status = $1::SMALLINT,
+                attempts = attempts + 1,
+                updated_at = $2::TIMESTAMP
+         WHERE id = (
+            SELECT id FROM {table}
+            WHERE job_status = $3::SMALLINT AND run_at <= $2::TIMESTAMP
+            ORDER BY run_at
+            FOR UPDATE SKIP LOCKED
+            LIMIT 1
+         )
+         RETURNING {fields}",
+        table = *JOBS_TABLE,
+        fields = *JOBS_TABLE_FIELDS
+    );
+
+    let update_stmt = db_client.prepare(&update_query).await?;
+
+    let row = db_client
+        .query_opt(
+            &update_stmt,
+            &[
+                &i16::from(JobStatus::Running),
+                &now,
+                &i16::from(JobStatus::Pending),
+            ],
+        )
+        .await?;
+
+    row.map(DbJob::try_from).transpose()
+}
+
+pub async fn db_complete_job(
+    db_client: &Client,
+    job_id: &uuid::Uuid,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_complete_job");
+    let update_query = format!(
+        "UPDATE {}
+            SET job_status = $1::SMALLINT,
+                updated_at = $2::TIMESTAMP,
+                last_error = NULL
+         WHERE id = $3::UUID",
+        *JOBS_TABLE
+    );
+    db_client
+        .execute(
+            &update_query,
+            &[&i16::from(JobStatus::Done), &sql_timestamp(None), &job_id],
+        )
+        .await
+}
+
+/// Records a failed attempt. The job goes back to pending at `retry_at` until it runs out
+/// of attempts, at which point it is parked as failed.
+pub async fn db_fail_job(
+    db_client: &Client,
+    job_id: &uuid::Uuid,
+    error: &str,
+    retry_at: &NaiveDateTime,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_fail_job");
+    let update_query = format!(
+        "UPDATE {}
+            SET job_status = CASE WHEN attempts >= max_attempts THEN $1::SMALLINT ELSE $2::SMALLINT END,
+                run_at = $3::TIMESTAMP,
+                updated_at = $4::TIMESTAMP,
+                last_error = $5::VARCHAR
+         WHERE id = $6::UUID",
+        *JOBS_TABLE
+    );
+    db_client
+        .execute(
+            &update_query,
+            &[
+                &i16::from(JobStatus::Failed),
+                &i16::from(JobStatus::Pending),
+                &retry_at,
+                &sql_timestamp(None),
+                &error,
+                &job_id,
+            ],
+        )
+        .await
+}
+
+/// Puts jobs left running by a crashed or killed worker back in the queue.
+pub async fn db_requeue_stale_jobs(
+    db_client: &Client,
+    stale_before: &NaiveDateTime,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_requeue_stale_jobs");
+    let update_query = format!(
+        "UPDATE {}
+            SET job_status = $1::SMALLINT
+         WHERE job_status = $2::SMALLINT AND updated_at < $3::TIMESTAMP",
+        *JOBS_TABLE
+    );
+    db_client
+        .execute(
+            &update_query,
+            &[
+                &i16::from(JobStatus::Pending),
+                &i16::from(JobStatus::Running),
+                &stale_before,
+            ],
+        )
+        .await
+}
+
+/// Writes the outbox messages in the same statement as `change`, and so in the same transaction:
+/// the messages are stored if and only if the change is. They are only written when `change`
+/// returns any row, it must end with `RETURNING`. The rows of `change` are returned as they are
+pub fn with_outbox<'a>(change: Query<'a>, outbox: &'a [DbOutboxMessage]) -> Query<'a> {
+    if outbox.is_empty() {
+        return change;
+    }
+
+    let mut params = change.params;
+    let mut rows = Vec::with_capacity(outbox.len());
+    for db_message in outbox {
+        let values: [(Param<'a>, &str); 7] = [
+            (&db_message.id, "UUID"),
+            (&db_message.created_at, "TIMESTAMP"),
+            (&db_message.payload, "VARCHAR"),
+            (&db_message.attempts, "INTEGER"),
+            (&db_message.available_at, "TIMESTAMP"),
+            (&db_message.dispatched_at, "TIMESTAMP"),
+            (&db_message.last_error, "VARCHAR"),
+        ];
+        let placeholders = values
+            .iter()
+            .map(|(value, sql_type)| {
+                params.push(*value);
+                format!("${}::{}", params.len(), sql_type)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        rows.push(format!("({})", placeholders));
+    }
+
+    let statement = format!(
+        "WITH changed AS ({statement}), \
+         outbox_messages AS (\
+            INSERT INTO {table} ({fields}) \
+            SELECT * FROM (VALUES {rows}) AS messages ({fields}) \
+            WHERE EXISTS (SELECT 1 FROM changed)\
+         ) \
+         SELECT * FROM changed",
+        statement = change.statement,
+        table = *OUTBOX_TABLE,
+        fields = DbOutboxMessage::fields(),
+        rows = rows.join(", "),
+    );
+    query(statement).bind_all(params)
+}
+
+/// Claims a batch of due outbox messages, leasing them until `lease_until`. A message whose
+/// dispatcher dies before settling it is claimed again once its lease is over, so every message
+/// is delivered at least once
+pub async fn db_claim_outbox_messages(
+    db_client: &Client,
+    lease_until: &NaiveDateTime,
+    batch_size: &i64,
+) -> Result<Vec<DbOutboxMessage>, tokio_postgres::Error> {
+    let _timer = db_timer("db_claim_outbox_messages");
+    let now = sql_timestamp(None);
+    let due = format!(
+        "id IN (\
+            SELECT id FROM {} \
+            WHERE dispatched_at IS NULL AND available_at <= {{}}::TIMESTAMP \
+            ORDER BY created_at \
+            LIMIT {{}} \
+            FOR UPDATE SKIP LOCKED\
+         )",
+        *OUTBOX_TABLE
+    );
+    let mut db_messages = update::<DbOutboxMessage>()
+        .set("available_at", lease_until)
+        .set_expr(cond("attempts = attempts + 1"))
+        .filter(cond(due).bind(&now).bind(batch_size))
+        .fetch_all(db_client)
+        .await?;
+    // delivered in the order they were written
+    db_messages.sort_by_key(|db_message| db_message.created_at);
+    Ok(db_messages)
+}
+
+/// Marks an outbox message as delivered
+pub async fn db_complete_outbox_message(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_complete_outbox_message");
+    let now = sql_timestamp(None);
+    update::<DbOutboxMessage>()
+        .set("dispatched_at", &now)
+        .set_expr(cond("last_error = NULL"))
+        .filter(cond("id = {}::UUID").bind(&id))
+        .execute(db_client)
+        .await
+}
+
+/// Records a failed delivery of an outbox message, which is due again at `retry_at`
+pub async fn db_fail_outbox_message(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    error: &str,
+    retry_at: &NaiveDateTime,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_fail_outbox_message");
+    update::<DbOutboxMessage>()
+        .set("available_at", retry_at)
+        .set("last_error", &error)
+        .filter(cond("id = {}::UUID AND dispatched_at IS NULL").bind(&id))
+        .execute(db_client)
+        .await
+}
+
+/// Hard-deletes the outbox messages delivered before `dispatched_before`
+pub async fn db_purge_dispatched_outbox_messages(
+    db_client: &Client,
+    dispatched_before: &NaiveDateTime,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_purge_dispatched_outbox_messages");
+    query(format!(
+        "DELETE FROM {} WHERE dispatched_at < $1::TIMESTAMP",
+        *OUTBOX_TABLE
+    ))
+    .bind(&dispatched_before)
+    .execute(db_client)
+    .await
+}
+
+pub async fn db_select_one(db_client: &Client) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_select_one");
+    db_client.execute("SELECT 1", &[]).await
+}
+
+pub fn sql_timestamp(sec_to_add: Option<i64>) -> NaiveDateTime {
+    let timestamp: i64 = Utc::now()
+        .checked_add_signed(Duration::seconds(sec_to_add.unwrap_or_default()))
+        .and_then(|r| Some(r.timestamp_millis()))
+        .expect("bad current timestamp");
+
+    let created_at =
+        NaiveDateTime::from_timestamp_opt(timestamp / 1000, (timestamp % 1000) as u32 * 1_000_000)
+            .expect("bad native data time conversion");
+    created_at
+}
+
+pub async fn db_insert_audit_log(
+    db_client: &Client,
+    db_audit_log: &DbAuditLog,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_audit_log");
+    let insert_query = format!(
+        "INSERT INTO {}
+                ({})
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        *AUDIT_LOG_TABLE, *AUDIT_LOG_TABLE_FIELDS
+    );
+    let insert_stmt = db_client.prepare(&insert_query).await?;
+    let res = db_client
+        .execute(
+            &insert_stmt,
+            &[
+                &db_audit_log.id,
+                &db_audit_log.created_at,
+                &db_audit_log.actor_id,
+                &db_audit_log.action,
+                &db_audit_log.entity_type,
+                &db_audit_log.entity_id,
+                &db_audit_log.diff,
+                &db_audit_log.impersonated_by,
+            ],
+        )
+        .await;
+    res
+}
+
+pub async fn db_get_audit_logs(
+    db_client: &Client,
+    actor_id: &Option<uuid::Uuid>,
+    entity_id: &Option<uuid::Uuid>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<DbAuditLog>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_audit_logs");
+    let query = format!(
+        "SELECT {} FROM {}
+         WHERE ($1::UUID is NULL OR actor_id = $1::UUID) AND ($2::UUID is NULL OR entity_id = $2::UUID)
+         ORDER BY created_at DESC
+         LIMIT $3::BIGINT OFFSET $4::BIGINT",
+        *AUDIT_LOG_TABLE_FIELDS, *AUDIT_LOG_TABLE
+    );
+    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&actor_id, &entity_id, &limit, &offset];
+    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
+    let audit_logs: Result<Vec<_>, _> = rows.into_iter().map(|r| DbAuditLog::try_from(r)).collect();
+    audit_logs
+}
+
+/// How often the actor performed the audited action since the given time, to rate limit it
+pub async fn db_count_audit_logs_since(
+    db_client: &Client,
+    actor_id: &uuid::Uuid,
+    action: &str,
+    since: &NaiveDateTime,
+) -> Result<i64, tokio_postgres::Error> {
+    let _timer = db_timer("db_count_audit_logs_since");
+    let query = format!(
+        "SELECT COUNT(*) FROM {}
+         WHERE actor_id = $1::UUID AND action = $2::VARCHAR AND created_at >= $3::TIMESTAMP",
+        *AUDIT_LOG_TABLE
+    );
+    let row = db_client
+        .query_one(&query, &[&actor_id, &action, &since])
+        .await?;
+    row.try_get(0)
+}
+
+/// Registers the seller's payout account, or replaces its wallet when already registered
+pub async fn db_upsert_payout_account(
+    db_client: &Client,
+    db_payout_account: &DbPayoutAccount,
+) -> Result<DbPayoutAccount, tokio_postgres::Error> {
+    let _timer = db_timer("db_upsert_payout_account");
+    let upsert_query = format!(
+        "INSERT INTO {}
+                ({})
+            VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (user_id) DO UPDATE
+            SET wallet_id = EXCLUDED.wallet_id,
+            updated_at = EXCLUDED.updated_at
+         RETURNING {}",
+        *PAYOUT_ACCOUNTS_TABLE, *PAYOUT_ACCOUNTS_TABLE_FIELDS, *PAYOUT_ACCOUNTS_TABLE_FIELDS
+    );
+    let upsert_stmt = db_client.prepare(&upsert_query).await?;
+    let row = db_client
+        .query_one(
+            &upsert_stmt,
+            &[
+                &db_payout_account.id,
+                &db_payout_account.created_at,
+                &db_payout_account.updated_at,
+                &db_payout_account.wallet_id,
+                &db_payout_account.user_id,
+            ],
+        )
+        .await?;
+    row.try_into()
+}
+
+pub async fn db_get_payout_account_by_user_id(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+) -> Result<Option<DbPayoutAccount>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_payout_account_by_user_id");
+    let query = format!(
+        "SELECT {} FROM {} WHERE user_id = $1::UUID",
+        *PAYOUT_ACCOUNTS_TABLE_FIELDS, *PAYOUT_ACCOUNTS_TABLE
+    );
+    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&user_id];
+    let row = db_client.query_opt(&query, query_values.as_slice()).await?;
+    row.map(DbPayoutAccount::try_from).transpose()
+}
+
+/// Sums the seller's ticket sales (at their tier's price, after promo discounts, per reserved
+/// ticket) and the payouts requested so far, rejected requests excluded
+pub async fn db_get_payout_balance(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+) -> Result<DbPayoutBalance, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_payout_balance");
+    let query = format!(
+        "SELECT
+            GREATEST(
+                COALESCE((SELECT SUM(COALESCE(pt.price, t.price)::NUMERIC * r.quantity)
+                    FROM {reservations} r
+                    JOIN {tickets} t ON t.id = r.ticket_id
+                    LEFT JOIN {price_tiers} pt ON pt.id = r.price_tier_id
+                    JOIN {events} e ON e.id = t.event_id
+                    WHERE e.created_by_user = $1::UUID), 0)
+                - COALESCE((SELECT SUM(
+                        (u.original_price::NUMERIC - u.discounted_price::NUMERIC) * r.quantity)
+                    FROM {usages} u
+                    JOIN {reservations} r ON r.verification_code = u.verification_code
+                        AND r.ticket_id = u.ticket_id
+                    JOIN {tickets} t ON t.id = u.ticket_id
+                    JOIN {events} e ON e.id = t.event_id
+                    WHERE e.created_by_user = $1::UUID), 0),
+                0)::NUMERIC(78, 0)::TEXT AS total_sales,
+            COALESCE((SELECT SUM(amount::NUMERIC)
+                FROM {payouts}
+                WHERE user_id = $1::UUID AND payout_status <> $2::SMALLINT), 0)::NUMERIC(78, 0)::TEXT
+                AS total_requested",
+        reservations = *TICKET_RESERVATIONS_TABLE,
+        tickets = *TICKETS_TABLE,
+        events = *EVENTS_TABLE,
+        usages = *PROMO_CODE_USAGES_TABLE,
+        payouts = *PAYOUT_REQUESTS_TABLE,
+        price_tiers = DbTicketPriceTier::TABLE,
+    );
+    let rejected = i16::from(PayoutStatus::Rejected);
+    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&user_id, &rejected];
+    let row = db_client.query_one(&query, query_values.as_slice()).await?;
+    DbPayoutBalance::try_from(row)
+}
+
+pub async fn db_insert_payout_request(
+    db_client: &Client,
+    db_payout_request: &DbPayoutRequest,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_payout_request");
+    let insert_query = format!(
+        "INSERT INTO {}
+                ({})
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        *PAYOUT_REQUESTS_TABLE, *PAYOUT_REQUESTS_TABLE_FIELDS
+    );
+    let insert_stmt = db_client.prepare(&insert_query).await?;
+    let res = db_client
+        .execute(
+            &insert_stmt,
+            &[
+                &db_payout_request.id,
+                &db_payout_request.created_at,
+                &db_payout_request.amount,
+                &db_payout_request.wallet_id,
+                &i16::from(db_payout_request.payout_status),
+                &db_payout_request.reviewed_by,
+                &db_payout_request.reviewed_at,
+                &db_payout_request.tx_hash,
+                &db_payout_request.user_id,
+            ],
+        )
+        .await;
+    res
+}
+
+pub async fn db_get_payout_request_by_id(
+    db_client: &Client,
+    payout_request_id: &uuid::Uuid,
+) -> Result<DbPayoutRequest, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_payout_request_by_id");
+    select::<DbPayoutRequest>()
+        .filter(cond("id = {}::UUID").bind(&payout_request_id))
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_payout_requests(
+    db_client: &Client,
+    user_id: &Option<uuid::Uuid>,
+    payout_status: Option<PayoutStatus>,
+) -> Result<Vec<DbPayoutRequest>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_payout_requests");
+    let payout_status = payout_status.map(i16::from);
+    select::<DbPayoutRequest>()
+        .filter(
+            cond("({}::UUID is NULL OR user_id = {}::UUID)")
+                .bind(user_id)
+                .bind(user_id),
+        )
+        .filter(
+            cond("({}::SMALLINT is NULL OR payout_status = {}::SMALLINT)")
+                .bind(&payout_status)
+                .bind(&payout_status),
+        )
+        .order_by("created_at DESC")
+        .fetch_all(db_client)
+        .await
+}
+
+/// Atomically moves a payout request from one status to another, recording the reviewer.
+/// Returns `None` when the request is not in the expected status anymore.
+pub async fn db_update_payout_request_status(
+    db_client: &Client,
+    payout_request_id: &uuid::Uuid,
+    from_status: PayoutStatus,
+    to_status: PayoutStatus,
+    reviewed_by: Option<&uuid::Uuid>,
+) -> Result<Option<DbPayoutRequest>, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_payout_request_status");
+    let update_query = format!(
+        "UPDATE {}
+            SET payout_status = $1::SMALLINT,
+            reviewed_by = $2::UUID,
+            reviewed_at = CASE WHEN $2::UUID IS NULL THEN NULL ELSE NOW() END
+         WHERE id = $3::UUID AND payout_status = $4::SMALLINT
+         RETURNING {}",
+        *PAYOUT_REQUESTS_TABLE, *PAYOUT_REQUESTS_TABLE_FIELDS
+    );
+
+    let update_stmt = db_client.prepare(&update_query).await?;
+
+    let row = db_client
+        .query_opt(
+            &update_stmt,
+            &[
+                &i16::from(to_status),
+                &reviewed_by,
+                &payout_request_id,
+                &i16::from(from_status),
+            ],
+        )
+        .await?;
+
+    row.map(DbPayoutRequest::try_from).transpose()
+}
+
+/// Marks an approved payout request as paid with the transfer's transaction hash
+pub async fn db_complete_payout_request(
+    db_client: &Client,
+    payout_request_id: &uuid::Uuid,
+    tx_hash: &str,
+) -> Result<DbPayoutRequest, tokio_postgres::Error> {
+    let _timer = db_timer("db_complete_payout_request");
+    let update_query = format!(
+        "UPDATE {}
+            SET payout_status = $1::SMALLINT,
+            tx_hash = $2::VARCHAR
+         WHERE id = $3::UUID AND payout_status = $4::SMALLINT
+         RETURNING {}",
+        *PAYOUT_REQUESTS_TABLE, *PAYOUT_REQUESTS_TABLE_FIELDS
+    );
+
+    let update_stmt = db_client.prepare(&update_query).await?;
+
+    let x = db_client
+        .query_one(
+            &update_stmt,
+            &[
+                &i16::from(PayoutStatus::Paid),
+                &tx_hash,
+                &payout_request_id,
+                &i16::from(PayoutStatus::Approved),
+            ],
+        )
+        .await?;
+
+    x.try_into()
+}
+
+/// Inserts an organization with its creator as the owner in a single statement
+pub async fn db_insert_organization(
+    db_client: &Client,
+    db_organization: &DbOrganization,
+) -> Result<DbOrganization, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_organization");
+    let owner = DbOrganizationMember::new(
+        db_organization.id,
+        db_organization.created_by_user,
+        MemberRole::Owner,
+    );
+    query(format!(
+        "WITH organization AS (
+            INSERT INTO {organizations} ({fields})
+                VALUES ($1::UUID, $2::TIMESTAMP, $3::VARCHAR, $4::VARCHAR, $5::UUID)
+            RETURNING {fields}
+        ), owner AS (
+            INSERT INTO {members} ({member_fields})
+                SELECT id, created_by_user, $6::SMALLINT, created_at FROM organization
+        )
+        SELECT {fields} FROM organization",
+        organizations = DbOrganization::TABLE,
+        fields = DbOrganization::fields(),
+        members = DbOrganizationMember::TABLE,
+        member_fields = DbOrganizationMember::fields(),
+    ))
+    .bind(&db_organization.id)
+    .bind(&db_organization.created_at)
+    .bind(&db_organization.name)
+    .bind(&db_organization.slug)
+    .bind(&db_organization.created_by_user)
+    .bind(&i16::from(owner.member_role))
+    .fetch_one(db_client)
+    .await
+}
+
+pub async fn db_get_organization_by_id(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<Option<DbOrganization>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_organization_by_id");
+    select::<DbOrganization>()
+        .filter(cond("id = {}::UUID").bind(&id))
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_get_organization_by_slug(
+    db_client: &Client,
+    slug: &str,
+) -> Result<Option<DbOrganization>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_organization_by_slug");
+    select::<DbOrganization>()
+        .filter(cond("slug = {}::VARCHAR").bind(&slug))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// The organizations the user is a member of
+pub async fn db_get_organizations_by_user_id(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+) -> Result<Vec<DbOrganization>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_organizations_by_user_id");
+    select::<DbOrganization>()
+        .filter(
+            cond(format!(
+                "id IN (SELECT organization_id FROM {} WHERE user_id = {{}}::UUID)",
+                DbOrganizationMember::TABLE
+            ))
+            .bind(&user_id),
+        )
+        .order_by("name")
+        .fetch_all(db_client)
+        .await
+}
+
+/// Adds the user to the organization, or changes their role if they are a member already
+pub async fn db_upsert_organization_member(
+    db_client: &Client,
+    db_member: &DbOrganizationMember,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_upsert_organization_member");
+    let member_role = i16::from(db_member.member_role);
+    insert::<DbOrganizationMember>()
+        .values(&[
+            &db_member.organization_id,
+            &db_member.user_id,
+            &member_role,
+            &db_member.created_at,
+        ])
+        .on_conflict("(organization_id, user_id) DO UPDATE SET member_role = EXCLUDED.member_role")
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_get_organization_member(
+    db_client: &Client,
+    organization_id: &uuid::Uuid,
+    user_id: &uuid::Uuid,
+) -> Result<Option<DbOrganizationMember>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_organization_member");
+    select::<DbOrganizationMember>()
+        .filter(cond("organization_id = {}::UUID").bind(&organization_id))
+        .filter(cond("user_id = {}::UUID").bind(&user_id))
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_get_organization_members(
+    db_client: &Client,
+    organization_id: &uuid::Uuid,
+) -> Result<Vec<DbOrganizationMember>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_organization_members");
+    select::<DbOrganizationMember>()
+        .filter(cond("organization_id = {}::UUID").bind(&organization_id))
+        .order_by("created_at")
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_delete_organization_member(
+    db_client: &Client,
+    organization_id: &uuid::Uuid,
+    user_id: &uuid::Uuid,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_delete_organization_member");
+    query(format!(
+        "DELETE FROM {} WHERE organization_id = $1::UUID AND user_id = $2::UUID",
+        DbOrganizationMember::TABLE
+    ))
+    .bind(&organization_id)
+    .bind(&user_id)
+    .execute(db_client)
+    .await
+}
+
+/// Adds the collaborator to the event, or changes their role if they collaborate already
+pub async fn db_upsert_event_collaborator(
+    db_client: &Client,
+    db_collaborator: &DbEventCollaborator,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_upsert_event_collaborator");
+    let collaborator_role = i16::from(db_collaborator.collaborator_role);
+    insert::<DbEventCollaborator>()
+        .values(&[
+            &db_collaborator.event_id,
+            &db_collaborator.user_id,
+            &collaborator_role,
+            &db_collaborator.created_at,
+        ])
+        .on_conflict(
+            "(event_id, user_id) DO UPDATE SET collaborator_role = EXCLUDED.collaborator_role",
+        )
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_get_event_collaborator(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    user_id: &uuid::Uuid,
+) -> Result<Option<DbEventCollaborator>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_event_collaborator");
+    select::<DbEventCollaborator>()
+        .filter(cond("event_id = {}::UUID").bind(&event_id))
+        .filter(cond("user_id = {}::UUID").bind(&user_id))
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_get_event_collaborators(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+) -> Result<Vec<DbEventCollaborator>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_event_collaborators");
+    select::<DbEventCollaborator>()
+        .filter(cond("event_id = {}::UUID").bind(&event_id))
+        .order_by("created_at")
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_delete_event_collaborator(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    user_id: &uuid::Uuid,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_delete_event_collaborator");
+    query(format!(
+        "DELETE FROM {} WHERE event_id = $1::UUID AND user_id = $2::UUID",
+        DbEventCollaborator::TABLE
+    ))
+    .bind(&event_id)
+    .bind(&user_id)
+    .execute(db_client)
+    .await
+}
+
+pub async fn db_insert_mint_job(
+    db_client: &Client,
+    db_mint_job: &DbMintJob,
+) -> Result<DbMintJob, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_mint_job");
+    let mint_status = i16::from(db_mint_job.mint_status);
+    insert::<DbMintJob>()
+        .values(&[
+            &db_mint_job.id,
+            &db_mint_job.created_at,
+            &db_mint_job.updated_at,
+            &db_mint_job.event_id,
+            &db_mint_job.ticket_id,
+            &db_mint_job.wallet_id,
+            &db_mint_job.tx_hash,
+            &db_mint_job.number_of_tickets,
+            &mint_status,
+            &db_mint_job.attempts,
+            &db_mint_job.checked_at,
+            &db_mint_job.error,
+        ])
+        .fetch_one(db_client)
+        .await
+}
+
+/// The pending mint jobs, the least recently checked first
+pub async fn db_get_pending_mint_jobs(
+    db_client: &Client,
+    limit: &i64,
+) -> Result<Vec<DbMintJob>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_pending_mint_jobs");
+    let pending = i16::from(MintStatus::Pending);
+    select::<DbMintJob>()
+        .filter(cond("mint_status = {}::SMALLINT").bind(&pending))
+        .order_by("checked_at NULLS FIRST, created_at")
+        .limit(limit)
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_get_mint_jobs_by_event_id(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+) -> Result<Vec<DbMintJob>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_mint_jobs_by_event_id");
+    select::<DbMintJob>()
+        .filter(cond("event_id = {}::UUID").bind(&event_id))
+        .order_by("created_at DESC")
+        .fetch_all(db_client)
+        .await
+}
+
+/// Records a status check of a pending mint job. Returns `None` when the job is not pending
+/// anymore
+pub async fn db_update_mint_job_status(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    mint_status: MintStatus,
+    error: Option<&str>,
+    outbox: &[DbOutboxMessage],
+) -> Result<Option<DbMintJob>, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_mint_job_status");
+    let (mint_status, pending) = (i16::from(mint_status), i16::from(MintStatus::Pending));
+    let now = sql_timestamp(None);
+    let change = update::<DbMintJob>()
+        .set("mint_status", &mint_status)
+        .set("error", &error)
+        .set("checked_at", &now)
+        .set("updated_at", &now)
+        .set_expr(cond("attempts = attempts + 1"))
+        .filter(cond("id = {}::UUID").bind(&id))
+        .filter(cond("mint_status = {}::SMALLINT").bind(&pending))
+        .returning();
+    with_outbox(change, outbox).fetch_opt(db_client).await
+}
+
+/// Moves a MINTING event back to DRAFT once none of its mints is pending or succeeded, so that
+/// the seller can mint again. Returns `None` when the event stays as it is
+pub async fn db_revert_event_minting(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+) -> Result<Option<DbEvent>, tokio_postgres::Error> {
+    let _timer = db_timer("db_revert_event_minting");
+    let (minting, draft) = (
+        i16::from(EventStatus::Minting),
+        i16::from(EventStatus::Draft),
+    );
+    let failed = i16::from(MintStatus::Failed);
+    update::<DbEvent>()
+        .set("event_status", &draft)
+        .filter(cond("id = {}::UUID").bind(&event_id))
+        .filter(cond("event_status = {}::SMALLINT").bind(&minting))
+        .filter(
+            cond(format!(
+                "NOT EXISTS (SELECT 1 FROM {} WHERE event_id = {{}}::UUID AND mint_status <> {{}}::SMALLINT)",
+                DbMintJob::TABLE
+            ))
+            .bind(&event_id)
+            .bind(&failed),
+        )
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_insert_signup_attempt(
+    db_client: &Client,
+    db_signup_attempt: &DbSignupAttempt,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_signup_attempt");
+    let signup_status = i16::from(db_signup_attempt.signup_status);
+    insert::<DbSignupAttempt>()
+        .values(&[
+            &db_signup_attempt.id,
+            &db_signup_attempt.created_at,
+            &db_signup_attempt.updated_at,
+            &db_signup_attempt.session_id,
+            &db_signup_attempt.user_id,
+            &db_signup_attempt.name,
+            &db_signup_attempt.username,
+            &db_signup_attempt.phone_number,
+            &db_signup_attempt.email,
+            &db_signup_attempt.password,
+            &db_signup_attempt.encrypted_secret_key,
+            &db_signup_attempt.locale,
+            &db_signup_attempt.wallet_id,
+            &db_signup_attempt.public_key,
+            &db_signup_attempt.wallet_created_at,
+            &db_signup_attempt.tx_hash,
+            &signup_status,
+            &db_signup_attempt.attempts,
+            &db_signup_attempt.next_attempt_at,
+            &db_signup_attempt.error,
+        ])
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_get_signup_attempt_by_id(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<Option<DbSignupAttempt>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_signup_attempt_by_id");
+    select::<DbSignupAttempt>()
+        .filter(cond("id = {}::UUID").bind(&id))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// The signup attempts, all or those with the status, the latest first
+pub async fn db_get_signup_attempts(
+    db_client: &Client,
+    signup_status: Option<SignupStatus>,
+) -> Result<Vec<DbSignupAttempt>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_signup_attempts");
+    let signup_status = signup_status.map(i16::from);
+    let mut attempts = select::<DbSignupAttempt>().order_by("created_at DESC");
+    if let Some(signup_status) = &signup_status {
+        attempts = attempts.filter(cond("signup_status = {}::SMALLINT").bind(signup_status));
+    }
+    attempts.fetch_all(db_client).await
+}
+
+/// Leases a batch of the pending signup attempts due for a retry until `lease_until`, so that
+/// nothing else retries them meanwhile
+pub async fn db_claim_signup_attempts(
+    db_client: &Client,
+    lease_until: &NaiveDateTime,
+    batch_size: &i64,
+) -> Result<Vec<DbSignupAttempt>, tokio_postgres::Error> {
+    let _timer = db_timer("db_claim_signup_attempts");
+    let (now, pending) = (sql_timestamp(None), i16::from(SignupStatus::Pending));
+    let due = format!(
+        "id IN (\
+            SELECT id FROM {} \
+            WHERE signup_status = {{}}::SMALLINT AND next_attempt_at <= {{}}::TIMESTAMP \
+            ORDER BY next_attempt_at \
+            LIMIT {{}} \
+            FOR UPDATE SKIP LOCKED\
+         )",
+        DbSignupAttempt::TABLE
+    );
+    update::<DbSignupAttempt>()
+        .set("next_attempt_at", lease_until)
+        .filter(cond(due).bind(&pending).bind(&now).bind(batch_size))
+        .fetch_all(db_client)
+        .await
+}
+
+/// Leases a failed signup attempt, or a pending one that nothing is retrying, until
+/// `lease_until`, making it pending again. Returns `None` otherwise
+pub async fn db_claim_signup_attempt(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    lease_until: &NaiveDateTime,
+) -> Result<Option<DbSignupAttempt>, tokio_postgres::Error> {
+    let _timer = db_timer("db_claim_signup_attempt");
+    let (now, pending, failed) = (
+        sql_timestamp(None),
+        i16::from(SignupStatus::Pending),
+        i16::from(SignupStatus::Failed),
+    );
+    update::<DbSignupAttempt>()
+        .set("signup_status", &pending)
+        .set("next_attempt_at", lease_until)
+        .filter(cond("id = {}::UUID").bind(&id))
+        .filter(
+            cond("signup_status = {}::SMALLINT").bind(&failed).or(cond(
+                "signup_status = {}::SMALLINT AND next_attempt_at <= {}::TIMESTAMP",
+            )
+            .bind(&pending)
+            .bind(&now)),
+        )
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Records the creation of the signup's wallet, `tx_hash` unknown when it was found created
+pub async fn db_set_signup_attempt_wallet_created(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    tx_hash: Option<&str>,
+) -> Result<DbSignupAttempt, tokio_postgres::Error> {
+    let _timer = db_timer("db_set_signup_attempt_wallet_created");
+    let now = sql_timestamp(None);
+    update::<DbSignupAttempt>()
+        .set("wallet_created_at", &now)
+        .set("tx_hash", &tx_hash)
+        .set("updated_at", &now)
+        .filter(cond("id = {}::UUID").bind(&id))
+        .fetch_one(db_client)
+        .await
+}
+
+/// Moves a pending signup attempt to COMPLETED or ROLLED_BACK. Returns `None` when it is not
+/// pending anymore
+pub async fn db_settle_signup_attempt(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    signup_status: SignupStatus,
+) -> Result<Option<DbSignupAttempt>, tokio_postgres::Error> {
+    let _timer = db_timer("db_settle_signup_attempt");
+    let (signup_status, pending) = (i16::from(signup_status), i16::from(SignupStatus::Pending));
+    let now = sql_timestamp(None);
+    update::<DbSignupAttempt>()
+        .set("signup_status", &signup_status)
+        .set("updated_at", &now)
+        .filter(cond("id = {}::UUID").bind(&id))
+        .filter(cond("signup_status = {}::SMALLINT").bind(&pending))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Records a failure of a pending signup attempt, retried from `retry_at` or given up as FAILED
+/// once it failed `max_attempts` times. Returns `None` when it is not pending anymore
+pub async fn db_fail_signup_attempt(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    error: &str,
+    retry_at: &NaiveDateTime,
+    max_attempts: i32,
+) -> Result<Option<DbSignupAttempt>, tokio_postgres::Error> {
+    let _timer = db_timer("db_fail_signup_attempt");
+    let (pending, failed) = (
+        i16::from(SignupStatus::Pending),
+        i16::from(SignupStatus::Failed),
+    );
+    let now = sql_timestamp(None);
+    update::<DbSignupAttempt>()
+        .set("error", &error)
+        .set("next_attempt_at", retry_at)
+        .set("updated_at", &now)
+        .set_expr(cond("attempts = attempts + 1"))
+        .set_expr(
+            cond("signup_status = CASE WHEN attempts + 1 >= {}::INTEGER THEN {}::SMALLINT ELSE signup_status END")
+                .bind(&max_attempts)
+                .bind(&failed),
+        )
+        .filter(cond("id = {}::UUID").bind(&id))
+        .filter(cond("signup_status = {}::SMALLINT").bind(&pending))
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_insert_category(
+    db_client: &Client,
+    db_category: &DbCategory,
+) -> Result<DbCategory, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_category");
+    insert::<DbCategory>()
+        .values(&[
+            &db_category.id,
+            &db_category.created_at,
+            &db_category.name,
+            &db_category.slug,
+        ])
+        .fetch_one(db_client)
+        .await
+}
+
+pub async fn db_get_categories(
+    db_client: &Client,
+) -> Result<Vec<DbCategory>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_categories");
+    select::<DbCategory>()
+        .order_by("name")
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_get_category_by_slug(
+    db_client: &Client,
+    slug: &str,
+) -> Result<Option<DbCategory>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_category_by_slug");
+    select::<DbCategory>()
+        .filter(cond("slug = {}::VARCHAR").bind(&slug))
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_update_event_category(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    category_id: Option<&uuid::Uuid>,
+) -> Result<DbEvent, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_event_category");
+    let update_query = format!(
+        "UPDATE {}
+            SET category_id = $1::UUID
+         WHERE id = $2::UUID
+         RETURNING {}",
+        *EVENTS_TABLE, *EVENTS_TABLE_FIELDS
+    );
+    let update_stmt = db_client.prepare(&update_query).await?;
+    let x = db_client
+        .query_one(&update_stmt, &[&category_id, &id])
+        .await?;
+    x.try_into()
+}
+
+/// Moves the event into the organization, or out of its organization if `None`
+pub async fn db_update_event_organization(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    organization_id: Option<&uuid::Uuid>,
+) -> Result<DbEvent, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_event_organization");
+    update::<DbEvent>()
+        .set("organization_id", &organization_id)
+        .filter(cond("id = {}::UUID").bind(&id))
+        .fetch_one(db_client)
+        .await
+}
+
+/// Replaces the event's tags with `tags`. Tags already on the event keep their creation date
+pub async fn db_set_event_tags(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    tags: &[String],
+) -> Result<(), tokio_postgres::Error> {
+    let _timer = db_timer("db_set_event_tags");
+    db_client
+        .execute(
+            format!(
+                "DELETE FROM {} WHERE event_id = $1::UUID AND NOT (tag = ANY($2::VARCHAR[]))",
+                *EVENT_TAGS_TABLE
+            )
+            .as_str(),
+            &[&event_id, &tags],
+        )
+        .await?;
+    db_client
+        .execute(
+            format!(
+                "INSERT INTO {} (event_id, tag)
+                    SELECT $1::UUID, UNNEST($2::VARCHAR[])
+                 ON CONFLICT (event_id, tag) DO NOTHING",
+                *EVENT_TAGS_TABLE
+            )
+            .as_str(),
+            &[&event_id, &tags],
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn db_get_event_tags(
+    db_client: &Client,
+    event_ids: &[uuid::Uuid],
+) -> Result<Vec<DbEventTag>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_event_tags");
+    let query = format!(
+        "SELECT {} FROM {} WHERE event_id = ANY($1::UUID[]) ORDER BY tag",
+        *EVENT_TAGS_TABLE_FIELDS, *EVENT_TAGS_TABLE
+    );
+    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&event_ids];
+    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
+    let tags: Result<Vec<_>, _> = rows.into_iter().map(DbEventTag::try_from).collect();
+    tags
+}
+
+/// The most used tags across listed events, i.e. neither archived nor deleted
+pub async fn db_get_popular_tags(
+    db_client: &Client,
+    limit: i64,
+) -> Result<Vec<DbTagCount>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_popular_tags");
+    let query = format!(
+        "SELECT t.tag, COUNT(*) AS event_count
+            FROM {} t
+            JOIN {} e ON e.id = t.event_id
+         WHERE e.deleted_at IS NULL AND NOT e.archived
+         GROUP BY t.tag
+         ORDER BY event_count DESC, t.tag
+         LIMIT $1::BIGINT",
+        *EVENT_TAGS_TABLE, *EVENTS_TABLE
+    );
+    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&limit];
+    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
+    let tags: Result<Vec<_>, _> = rows.into_iter().map(DbTagCount::try_from).collect();
+    tags
+}
+
+/// Full-text search over the listed events' name, venue and description.
+///
+/// Full-text matches rank first by relevance, events whose name or venue are merely similar
+/// to `text` (trigram similarity) follow, so typos still find something.
+pub async fn db_search_events(
+    db_client: &Client,
+    text: &str,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<DbEvent>, tokio_postgres::Error> {
+    let _timer = db_timer("db_search_events");
+    let query = format!(
+        "SELECT {} FROM {}, websearch_to_tsquery('english', $1::VARCHAR) AS search_query
+         WHERE deleted_at IS NULL AND NOT archived
+            AND (search_vector @@ search_query
+                OR event_name % $1::VARCHAR
+                OR venue_name % $1::VARCHAR)
+         ORDER BY (search_vector @@ search_query) DESC,
+            ts_rank(search_vector, search_query) DESC,
+            GREATEST(similarity(event_name, $1::VARCHAR), similarity(COALESCE(venue_name, ''), $1::VARCHAR)) DESC,
+            created_at DESC
+         LIMIT $2::BIGINT OFFSET $3::BIGINT",
+        *EVENTS_TABLE_FIELDS, *EVENTS_TABLE
+    );
+    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&text, &limit, &offset];
+    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
+    let events: Result<Vec<_>, _> = rows.into_iter().map(DbEvent::try_from).collect();
+    events
+}
+
+/// The listed published events with coordinates within `radius_km` of `center`, nearest first,
+/// each with its distance in km.
+///
+/// The distance is the haversine great-circle distance, see [`crate::geo::distance_km`]. A
+/// latitude band around the center narrows the rows down before it is computed.
+pub async fn db_get_events_near(
+    db_client: &Client,
+    center: &Coordinates,
+    radius_km: f64,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<(DbEvent, f64)>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_events_near");
+    let query = format!(
+        "SELECT {}, distance_km FROM (
+            SELECT *, 2 * $4::FLOAT8 * ASIN(LEAST(1, SQRT(
+                POWER(SIN(RADIANS(latitude - $1::FLOAT8) / 2), 2)
+                + COS(RADIANS($1::FLOAT8)) * COS(RADIANS(latitude))
+                * POWER(SIN(RADIANS(longitude - $2::FLOAT8) / 2), 2)
+            ))) AS distance_km
+            FROM {}
+            WHERE deleted_at IS NULL AND NOT archived
+                AND event_status = $5::SMALLINT
+                AND latitude IS NOT NULL
+                AND latitude BETWEEN $1::FLOAT8 - DEGREES($3::FLOAT8 / $4::FLOAT8)
+                    AND $1::FLOAT8 + DEGREES($3::FLOAT8 / $4::FLOAT8)
+         ) AS nearby
+         WHERE distance_km <= $3::FLOAT8
+         ORDER BY distance_km, start_date
+         LIMIT $6::BIGINT OFFSET $7::BIGINT",
+        *EVENTS_TABLE_FIELDS, *EVENTS_TABLE
+    );
+    let event_status = i16::from(EventStatus::Final);
+    let query_values: Vec<&(dyn ToSql + Sync)> = vec![
+        &center.latitude,
+        &center.longitude,
+        &radius_km,
+        &EARTH_RADIUS_KM,
+        &event_status,
+        &limit,
+        &offset,
+    ];
+    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
+    rows.into_iter()
+        .map(|row| {
+            let distance_km: f64 = row.try_get("distance_km")?;
+            Ok((DbEvent::try_from(row)?, distance_km))
+        })
+        .collect()
+}
+
+/// Strips the user's personal data and flags the wallet for review. The row stays so that
+/// reservations, events and payouts keep their owner
+pub async fn db_anonymize_user(
+    db_client: &Client,
+    db_user: &DbUser,
+) -> Result<Option<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_anonymize_user");
+    let update_query = format!(
+        "UPDATE {}
+            SET name = NULL,
+            username = $1::VARCHAR,
+            phone_number = NULL,
+            email = NULL,
+            password = NULL,
+            wallet_flagged = TRUE,
+            seller_slug = NULL,
+            deleted_at = $2::TIMESTAMP
+         WHERE id = $3::UUID AND deleted_at IS NULL
+         RETURNING {}",
+        *USERS_TABLE, *USERS_TABLE_FIELDS
+    );
+    let update_stmt = db_client.prepare(&update_query).await?;
+    let row = db_client
+        .query_opt(
+            &update_stmt,
+            &[
+                &db_user.anonymized_username(),
+                &sql_timestamp(None),
+                &db_user.id,
+            ],
+        )
+        .await?;
+    row.map(DbUser::try_from).transpose()
+}
+
+/// Replaces the user's encrypted wallet secret key, unless it changed since it was read
+pub async fn db_update_user_encrypted_secret_key(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    current_encrypted_secret_key: &str,
+    encrypted_secret_key: &str,
+) -> Result<Option<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_user_encrypted_secret_key");
+    update::<DbUser>()
+        .set("encrypted_secret_key", &encrypted_secret_key)
+        .filter(cond("id = {}::UUID").bind(&user_id))
+        .filter(cond("encrypted_secret_key = {}").bind(&current_encrypted_secret_key))
+        .filter(cond("deleted_at IS NULL"))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Whether a user other than `user_id` has the username
+pub async fn db_is_username_taken(
+    db_client: &Client,
+    username: &str,
+    user_id: &uuid::Uuid,
+) -> Result<bool, tokio_postgres::Error> {
+    let _timer = db_timer("db_is_username_taken");
+    let db_user = select::<DbUser>()
+        .filter(cond("username = {}::VARCHAR").bind(&username))
+        .filter(cond("id <> {}::UUID").bind(&user_id))
+        .fetch_opt(db_client)
+        .await?;
+    Ok(db_user.is_some())
+}
+
+/// Whether an active user other than `user_id` has the email, compared case-insensitively
+pub async fn db_is_email_taken(
+    db_client: &Client,
+    email: &str,
+    user_id: &uuid::Uuid,
+) -> Result<bool, tokio_postgres::Error> {
+    let _timer = db_timer("db_is_email_taken");
+    let db_user = select::<DbUser>()
+        .filter(cond("LOWER(email) = LOWER({}::VARCHAR)").bind(&email))
+        .filter(cond("deleted_at IS NULL"))
+        .filter(cond("id <> {}::UUID").bind(&user_id))
+        .fetch_opt(db_client)
+        .await?;
+    Ok(db_user.is_some())
+}
+
+/// The active seller with the storefront slug
+pub async fn db_get_seller_by_slug(
+    db_client: &Client,
+    seller_slug: &str,
+) -> Result<Option<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_seller_by_slug");
+    select::<DbUser>()
+        .filter(cond("seller_slug = {}::VARCHAR").bind(&seller_slug))
+        .filter(cond("user_type = {}::SMALLINT").bind(&(Role::Seller as i16)))
+        .filter(cond("deleted_at IS NULL"))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Whether a user other than `user_id` has the storefront slug
+pub async fn db_is_seller_slug_taken(
+    db_client: &Client,
+    seller_slug: &str,
+    user_id: &uuid::Uuid,
+) -> Result<bool, tokio_postgres::Error> {
+    let _timer = db_timer("db_is_seller_slug_taken");
+    let db_user = select::<DbUser>()
+        .filter(cond("seller_slug = {}::VARCHAR").bind(&seller_slug))
+        .filter(cond("id <> {}::UUID").bind(&user_id))
+        .fetch_opt(db_client)
+        .await?;
+    Ok(db_user.is_some())
+}
+
+/// Sets the seller's storefront slug. Returns `None` if the user has been deleted
+pub async fn db_update_user_seller_slug(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    seller_slug: &str,
+) -> Result<Option<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_user_seller_slug");
+    update::<DbUser>()
+        .set("seller_slug", &seller_slug)
+        .filter(cond("id = {}::UUID").bind(&user_id))
+        .filter(cond("deleted_at IS NULL"))
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_update_user_event_reminders_opt_out(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    opt_out: bool,
+) -> Result<Option<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_user_event_reminders_opt_out");
+    update::<DbUser>()
+        .set("event_reminders_opt_out", &opt_out)
+        .filter(cond("id = {}::UUID").bind(&user_id))
+        .filter(cond("deleted_at IS NULL"))
+        .fetch_opt(db_client)
+        .await
+}
+
+pub async fn db_update_user_locale(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    locale: &str,
+) -> Result<Option<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_user_locale");
+    update::<DbUser>()
+        .set("locale", &locale)
+        .filter(cond("id = {}::UUID").bind(&user_id))
+        .filter(cond("deleted_at IS NULL"))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Moves the seller to the `to` status if they are in one of the `from` statuses, the rejection
+/// reason is replaced. Returns `None` if the seller is in another status or has been deleted
+pub async fn db_update_seller_status(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    from: &[SellerStatus],
+    to: SellerStatus,
+    rejection_reason: Option<&str>,
+) -> Result<Option<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_seller_status");
+    let from: Vec<i16> = from.iter().copied().map(i16::from).collect();
+    update::<DbUser>()
+        .set("seller_status", &i16::from(to))
+        .set("seller_rejection_reason", &rejection_reason)
+        .filter(cond("id = {}::UUID").bind(&user_id))
+        .filter(cond("seller_status = ANY({}::SMALLINT[])").bind(&from))
+        .filter(cond("deleted_at IS NULL"))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// The sellers in the onboarding status, the longest registered first
+pub async fn db_get_users_by_seller_status(
+    db_client: &Client,
+    seller_status: SellerStatus,
+) -> Result<Vec<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_users_by_seller_status");
+    select::<DbUser>()
+        .filter(cond("seller_status = {}::SMALLINT").bind(&i16::from(seller_status)))
+        .filter(cond("deleted_at IS NULL"))
+        .order_by("created_at")
+        .fetch_all(db_client)
+        .await
+}
+
+/// Sets the given profile fields of the user, the others are kept. Returns `None` if the user
+/// has been deleted
+pub async fn db_update_user_profile(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    name: Option<&str>,
+    username: Option<&str>,
+    email: Option<&str>,
+) -> Result<Option<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_user_profile");
+    let mut update = update::<DbUser>();
+    if let Some(name) = &name {
+        update = update.set("name", name);
+    }
+    if let Some(username) = &username {
+        update = update.set("username", username);
+    }
+    if let Some(email) = &email {
+        update = update.set("email", email);
+    }
+    update
+        .filter(cond("id = {}::UUID").bind(&user_id))
+        .filter(cond("deleted_at IS NULL"))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Replaces the user's password hash, unless it changed since it was read
+pub async fn db_update_user_password(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    current_password: &str,
+    password: &str,
+) -> Result<Option<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_user_password");
+    update::<DbUser>()
+        .set("password", &password)
+        .set("password_changed_at", &sql_timestamp(None))
+        .filter(cond("id = {}::UUID").bind(&user_id))
+        .filter(cond("password = {}").bind(&current_password))
+        .filter(cond("deleted_at IS NULL"))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Burns the user's login sessions and drops the signup/recovery sessions holding the
+/// user's phone number, along with the user's signup attempt
+pub async fn db_revoke_user_sessions(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    phone_number: Option<&str>,
+) -> Result<(), tokio_postgres::Error> {
+    let _timer = db_timer("db_revoke_user_sessions");
+    db_client
+        .execute(
+            format!(
+                "UPDATE {} SET is_used = TRUE, expires_at = NOW() WHERE user_id = $1::UUID",
+                *SESSIONS_TABLE
+            )
+            .as_str(),
+            &[&user_id],
+        )
+        .await?;
+    db_client
+        .execute(
+            format!(
+                "DELETE FROM {} WHERE created_by_user = $1::UUID OR phone_number = $2::VARCHAR",
+                *BUYER_RECOVERY_SESSIONS_TABLE
+            )
+            .as_str(),
+            &[&user_id, &phone_number],
+        )
+        .await?;
+    if let Some(phone_number) = phone_number {
+        db_client
+            .execute(
+                format!(
+                    "DELETE FROM {} WHERE phone_number = $1::VARCHAR",
+                    *BUYER_SIGNUP_SESSIONS_TABLE
+                )
+                .as_str(),
+                &[&phone_number],
+            )
+            .await?;
+    }
+    db_client
+        .execute(
+            format!(
+                "DELETE FROM {} WHERE user_id = $1::UUID",
+                DbSignupAttempt::TABLE
+            )
+            .as_str(),
+            &[&user_id],
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn db_get_events_by_creator(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+) -> Result<Vec<DbEvent>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_events_by_creator");
+    select::<DbEvent>()
+        .filter(cond("created_by_user = {}::UUID AND deleted_at IS NULL").bind(&user_id))
+        .order_by("created_at")
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_get_users_with_phone_number(
+    db_client: &Client,
+) -> Result<Vec<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_users_with_phone_number");
+    select::<DbUser>()
+        .filter(cond("phone_number IS NOT NULL"))
+        .order_by("created_at")
+        .fetch_all(db_client)
+        .await
+}
+
+pub async fn db_update_user_phone_number(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    phone_number: &str,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_user_phone_number");
+    update::<DbUser>()
+        .set("phone_number", &phone_number)
+        .filter(cond("id = {}::UUID").bind(&user_id))
+        .execute(db_client)
+        .await
+}
+
+/// Adds the user to the waitlist of the ticket. Returns 0 if they are already on it
+pub async fn db_insert_waitlist_entry(
+    db_client: &Client,
+    entry: &DbWaitlistEntry,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_waitlist_entry");
+    insert::<DbWaitlistEntry>()
+        .values(&[
+            &entry.id,
+            &entry.created_at,
+            &entry.ticket_id,
+            &entry.user_id,
+            &entry.notified_at,
+        ])
+        .on_conflict("(ticket_id, user_id) DO NOTHING")
+        .execute(db_client)
+        .await
+}
+
+pub async fn db_get_waitlist_entry(
+    db_client: &Client,
+    ticket_id: &uuid::Uuid,
+    user_id: &uuid::Uuid,
+) -> Result<Option<DbWaitlistEntry>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_waitlist_entry");
+    select::<DbWaitlistEntry>()
+        .filter(cond("ticket_id = {}::UUID").bind(&ticket_id))
+        .filter(cond("user_id = {}::UUID").bind(&user_id))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// Removes the user from the waitlist of the ticket
+pub async fn db_delete_waitlist_entry(
+    db_client: &Client,
+    ticket_id: &uuid::Uuid,
+    user_id: &uuid::Uuid,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_delete_waitlist_entry");
+    query(format!(
+        "DELETE FROM {} WHERE ticket_id = $1::UUID AND user_id = $2::UUID",
+        DbWaitlistEntry::TABLE
+    ))
+    .bind(&ticket_id)
+    .bind(&user_id)
+    .execute(db_client)
+    .await
+}
+
+/// The tickets with buyers still waiting for them and tickets left to reserve. The buyers notified
+/// after `offered_after` are counted as pending offers
+pub async fn db_get_waitlist_openings(
+    db_client: &Client,
+    offered_after: &NaiveDateTime,
+) -> Result<Vec<DbWaitlistOpening>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_waitlist_openings");
+    query(format!(
+        "SELECT t.id AS ticket_id, t.event_id,
+                t.quantity_available - t.quantity_reserved AS quantity_remaining,
+                COUNT(*) FILTER (WHERE w.notified_at > $1::TIMESTAMP) AS pending_offers
+            FROM {} t
+            JOIN {} w ON w.ticket_id = t.id
+         WHERE t.deleted_at IS NULL AND NOT t.archived
+         GROUP BY t.id
+         HAVING COUNT(*) FILTER (WHERE w.notified_at IS NULL) > 0
+            AND (t.quantity_available IS NULL OR t.quantity_reserved < t.quantity_available)",
+        *TICKETS_TABLE,
+        DbWaitlistEntry::TABLE
+    ))
+    .bind(&offered_after)
+    .fetch_all(db_client)
+    .await
+}
+
+/// Marks the `limit` longest waiting buyers of the ticket as notified and returns them
+pub async fn db_notify_waitlist_entries(
+    db_client: &Client,
+    ticket_id: &uuid::Uuid,
+    limit: i64,
+) -> Result<Vec<DbWaitlistEntry>, tokio_postgres::Error> {
+    let _timer = db_timer("db_notify_waitlist_entries");
+    let now = sql_timestamp(None);
+    query(format!(
+        "UPDATE {table} SET notified_at = $1::TIMESTAMP
+         WHERE id IN (
+            SELECT id FROM {table}
+            WHERE ticket_id = $2::UUID AND notified_at IS NULL
+            ORDER BY created_at
+            LIMIT $3::BIGINT
+            FOR UPDATE SKIP LOCKED
+         )
+         RETURNING {fields}",
+        table = DbWaitlistEntry::TABLE,
+        fields = DbWaitlistEntry::fields()
+    ))
+    .bind(&now)
+    .bind(&ticket_id)
+    .bind(&limit)
+    .fetch_all(db_client)
+    .await
+}
+
+/// The holders of tickets for the published events starting within `(starts_after,
+/// starts_before]` who have not opted out and have not been reminded within `window_secs` yet
+pub async fn db_get_reminder_recipients(
+    db_client: &Client,
+    window_secs: i64,
+    starts_after: &NaiveDateTime,
+    starts_before: &NaiveDateTime,
+) -> Result<Vec<DbReminderRecipient>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_reminder_recipients");
+    let published = EventStatus::Final as i16;
+    query(format!(
+        "SELECT DISTINCT e.id AS event_id, u.id AS user_id, u.phone_number, u.locale
+            FROM {events} e
+            JOIN {reservations} r ON r.event_id = e.id
+            JOIN {users} u ON u.id = r.user_id
+         WHERE e.event_status = $1::SMALLINT AND e.deleted_at IS NULL AND NOT e.archived
+            AND e.start_date > $2::TIMESTAMP AND e.start_date <= $3::TIMESTAMP
+            AND u.deleted_at IS NULL AND NOT u.event_reminders_opt_out
+            AND NOT EXISTS (
+                SELECT 1 FROM {reminders} er
+                WHERE er.event_id = e.id AND er.user_id = u.id AND er.window_secs = $4::BIGINT
+            )",
+        events = *EVENTS_TABLE,
+        reservations = *TICKET_RESERVATIONS_TABLE,
+        users = *USERS_TABLE,
+        reminders = DbEventReminder::TABLE,
+    ))
+    .bind(&published)
+    .bind(&starts_after)
+    .bind(&starts_before)
+    .bind(&window_secs)
+    .fetch_all(db_client)
+    .await
 }
 
-pub async fn db_get_users(
+/// Records the reminder, returns `false` if it was already sent
+pub async fn db_insert_event_reminder(
     db_client: &Client,
-    user_id: &Option<uuid::Uuid>,
-) -> Result<Vec<DbUser>, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE ($1::UUID is NULL OR id = $1::UUID)",
-        *USERS_TABLE_FIELDS, *USERS_TABLE
-    );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&user_id];
-    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
-    let users: Result<Vec<_>, _> = rows.into_iter().map(|r| DbUser::try_from(r)).collect();
-    users
+    reminder: &DbEventReminder,
+) -> Result<bool, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_event_reminder");
+    insert::<DbEventReminder>()
+        .values(&[
+            &reminder.id,
+            &reminder.created_at,
+            &reminder.event_id,
+            &reminder.user_id,
+            &reminder.window_secs,
+        ])
+        .on_conflict("(event_id, user_id, window_secs) DO NOTHING")
+        .execute(db_client)
+        .await
+        .map(|inserted| inserted > 0)
 }
 
-pub async fn db_get_event_by_name(
+pub async fn db_get_persisted_query(
     db_client: &Client,
-    event_name: &str,
-) -> Result<DbEvent, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE event_name = $1::VARCHAR",
-        *EVENTS_TABLE_FIELDS, *EVENTS_TABLE
-    );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&event_name];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbEvent::try_from(row)
+    sha256_hash: &str,
+) -> Result<Option<DbPersistedQuery>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_persisted_query");
+    select::<DbPersistedQuery>()
+        .filter(cond("sha256_hash = {}::VARCHAR").bind(&sha256_hash))
+        .fetch_opt(db_client)
+        .await
 }
 
-pub async fn db_get_event_by_slug(
+/// Stores the query, a query already stored under the hash is kept
+pub async fn db_insert_persisted_query(
     db_client: &Client,
-    event_slug: &str,
-) -> Result<DbEvent, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE event_slug = $1::VARCHAR",
-        *EVENTS_TABLE_FIELDS, *EVENTS_TABLE
-    );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&event_slug];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbEvent::try_from(row)
+    persisted_query: &DbPersistedQuery,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_persisted_query");
+    insert::<DbPersistedQuery>()
+        .values(&[
+            &persisted_query.sha256_hash,
+            &persisted_query.created_at,
+            &persisted_query.query,
+        ])
+        .on_conflict("(sha256_hash) DO NOTHING")
+        .execute(db_client)
+        .await
 }
 
-pub async fn db_get_event_by_id(
+pub async fn db_insert_wallet_top_up(
     db_client: &Client,
-    id: &uuid::Uuid,
-) -> Result<DbEvent, tokio_postgres::Error> {
+    db_wallet_top_up: &DbWalletTopUp,
+) -> Result<DbWalletTopUp, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_wallet_top_up");
+    insert::<DbWalletTopUp>()
+        .values(&[
+            &db_wallet_top_up.id,
+            &db_wallet_top_up.created_at,
+            &db_wallet_top_up.user_id,
+            &db_wallet_top_up.wallet_id,
+            &db_wallet_top_up.amount,
+            &db_wallet_top_up.tx_hash,
+            &db_wallet_top_up.triggered_by,
+        ])
+        .fetch_one(db_client)
+        .await
+}
+
+/// Records the transaction of a submitted top-up
+pub async fn db_complete_wallet_top_up(
+    db_client: &Client,
+    wallet_top_up_id: &uuid::Uuid,
+    tx_hash: &str,
+) -> Result<DbWalletTopUp, tokio_postgres::Error> {
+    let _timer = db_timer("db_complete_wallet_top_up");
+    update::<DbWalletTopUp>()
+        .set("tx_hash", &tx_hash)
+        .filter(cond("id = {}::UUID").bind(&wallet_top_up_id))
+        .fetch_one(db_client)
+        .await
+}
+
+/// Drops a top-up whose funding failed, so that it no longer counts against the daily cap
+pub async fn db_delete_wallet_top_up(
+    db_client: &Client,
+    wallet_top_up_id: &uuid::Uuid,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_delete_wallet_top_up");
+    query(format!(
+        "DELETE FROM {} WHERE id = $1::UUID",
+        DbWalletTopUp::TABLE
+    ))
+    .bind(&wallet_top_up_id)
+    .execute(db_client)
+    .await
+}
+
+/// The total amount the user's wallet was topped up with since `since`
+pub async fn db_get_wallet_top_ups_total(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+    since: &NaiveDateTime,
+) -> Result<NearAmount, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_wallet_top_ups_total");
     let query = format!(
-        "SELECT {} FROM {} WHERE id = $1::UUID",
-        *EVENTS_TABLE_FIELDS, *EVENTS_TABLE
+        "SELECT COALESCE(SUM(amount::NUMERIC), 0)::NUMERIC(78, 0)::TEXT AS total
+            FROM {}
+            WHERE user_id = $1::UUID AND created_at >= $2::TIMESTAMP",
+        DbWalletTopUp::TABLE
     );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&id];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbEvent::try_from(row)
+    let row = db_client.query_one(&query, &[user_id, since]).await?;
+    row.try_get("total")
 }
 
-pub async fn db_delete_event_by_id(
+pub async fn db_insert_event_view(
     db_client: &Client,
-    id: &uuid::Uuid,
+    db_event_view: &DbEventView,
+) -> Result<DbEventView, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_event_view");
+    insert::<DbEventView>()
+        .values(&[
+            &db_event_view.id,
+            &db_event_view.created_at,
+            &db_event_view.event_id,
+        ])
+        .fetch_one(db_client)
+        .await
+}
+
+/// Folds the views recorded before `until` into the daily stats and drops them, in a single
+/// statement so that no view is counted twice. Returns the number of daily stats updated
+pub async fn db_aggregate_event_views(
+    db_client: &Client,
+    until: &NaiveDateTime,
 ) -> Result<u64, tokio_postgres::Error> {
-    let res = db_client
-        .execute(
-            &format!("DELETE FROM {} WHERE id = $1::UUID", *EVENTS_TABLE),
-            &[&id],
+    let _timer = db_timer("db_aggregate_event_views");
+    query(format!(
+        "WITH seen AS (
+            DELETE FROM {views} WHERE created_at < $1::TIMESTAMP
+            RETURNING event_id, created_at
         )
-        .await;
-    res
+        INSERT INTO {stats} (event_id, day, views)
+            SELECT event_id, created_at::DATE, COUNT(*)::INTEGER
+            FROM seen
+            GROUP BY event_id, created_at::DATE
+        ON CONFLICT (event_id, day) DO UPDATE
+            SET views = {stats}.views + EXCLUDED.views",
+        views = DbEventView::TABLE,
+        stats = DbEventDailyStats::TABLE
+    ))
+    .bind(&until)
+    .execute(db_client)
+    .await
 }
 
-pub async fn db_delete_ticket_by_id(
+/// Recounts the reservations and purchases of the events per day since `since`. Returns the
+/// number of daily stats updated
+pub async fn db_aggregate_event_reservations(
     db_client: &Client,
-    id: &uuid::Uuid,
+    since: &NaiveDate,
 ) -> Result<u64, tokio_postgres::Error> {
-    let res = db_client
-        .execute(
-            &format!("DELETE FROM {} WHERE id = $1::UUID", *TICKETS_TABLE),
-            &[&id],
-        )
-        .await;
-    res
+    let _timer = db_timer("db_aggregate_event_reservations");
+    query(format!(
+        "INSERT INTO {stats} (event_id, day, reservations, purchases)
+            SELECT r.event_id, r.created_at::DATE, COUNT(*)::INTEGER,
+                (COUNT(*) FILTER (WHERE t.price IS NOT NULL AND t.price::NUMERIC > 0))::INTEGER
+            FROM {reservations} r
+            JOIN {tickets} t ON t.id = r.ticket_id
+            WHERE r.created_at >= $1::DATE
+            GROUP BY r.event_id, r.created_at::DATE
+        ON CONFLICT (event_id, day) DO UPDATE
+            SET reservations = EXCLUDED.reservations, purchases = EXCLUDED.purchases",
+        stats = DbEventDailyStats::TABLE,
+        reservations = *TICKET_RESERVATIONS_TABLE,
+        tickets = *TICKETS_TABLE
+    ))
+    .bind(&since)
+    .execute(db_client)
+    .await
 }
 
-pub async fn db_get_tickets_by_event_id(
+/// The daily stats of the event from `from` to `to`, both included, the earliest first
+pub async fn db_get_event_daily_stats(
     db_client: &Client,
-    event_id: &Option<uuid::Uuid>,
-) -> Result<Vec<DbTicket>, tokio_postgres::Error> {
+    event_id: &uuid::Uuid,
+    from: &NaiveDate,
+    to: &NaiveDate,
+) -> Result<Vec<DbEventDailyStats>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_event_daily_stats");
+    select::<DbEventDailyStats>()
+        .filter(cond("event_id = {}::UUID").bind(&event_id))
+        .filter(cond("day >= {}::DATE").bind(&from))
+        .filter(cond("day <= {}::DATE").bind(&to))
+        .order_by("day")
+        .fetch_all(db_client)
+        .await
+}
+
+/// The number of users per role and status, deleted accounts left out
+pub async fn db_count_users(db_client: &Client) -> Result<Vec<DbUserCount>, tokio_postgres::Error> {
+    let _timer = db_timer("db_count_users");
     let query = format!(
-        "SELECT {} FROM {} WHERE ($1::UUID is NULL OR event_id = $1::UUID)",
-        *TICKETS_TABLE_FIELDS, *TICKETS_TABLE
+        "SELECT user_type, user_status, COUNT(*)::INTEGER AS total
+            FROM {}
+         WHERE deleted_at IS NULL
+         GROUP BY user_type, user_status
+         ORDER BY user_type, user_status",
+        *USERS_TABLE
     );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&event_id];
-    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
-    let tickets: Result<Vec<_>, _> = rows.into_iter().map(|r| DbTicket::try_from(r)).collect();
-    tickets
+    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, &[]).await?;
+    let counts: Result<Vec<_>, _> = rows.into_iter().map(DbUserCount::try_from).collect();
+    counts
 }
 
-pub async fn db_get_ticket_by_slug(
+/// The number of events per status, deleted events left out
+pub async fn db_count_events(
     db_client: &Client,
-    ticket_slug: &str,
-) -> Result<DbTicket, tokio_postgres::Error> {
+) -> Result<Vec<DbEventCount>, tokio_postgres::Error> {
+    let _timer = db_timer("db_count_events");
     let query = format!(
-        "SELECT {} FROM {} WHERE ticket_slug = $1::VARCHAR",
-        *TICKETS_TABLE_FIELDS, *TICKETS_TABLE
+        "SELECT event_status, COUNT(*)::INTEGER AS total
+            FROM {}
+         WHERE deleted_at IS NULL
+         GROUP BY event_status
+         ORDER BY event_status",
+        *EVENTS_TABLE
     );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&ticket_slug];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbTicket::try_from(row)
+    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, &[]).await?;
+    let counts: Result<Vec<_>, _> = rows.into_iter().map(DbEventCount::try_from).collect();
+    counts
 }
 
-pub async fn db_get_ticket_by_id(
+/// The reservations across all events per day since `since`, the earliest first and the days
+/// without any left out
+pub async fn db_get_daily_reservations(
     db_client: &Client,
-    ticket_id: &uuid::Uuid,
-) -> Result<DbTicket, tokio_postgres::Error> {
+    since: &NaiveDate,
+) -> Result<Vec<DbDailyReservations>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_daily_reservations");
     let query = format!(
-        "SELECT {} FROM {} WHERE id = $1::UUID",
-        *TICKETS_TABLE_FIELDS, *TICKETS_TABLE
+        "SELECT created_at::DATE AS day, COUNT(*)::INTEGER AS reservations,
+                SUM(quantity)::INTEGER AS tickets
+            FROM {}
+         WHERE created_at >= $1::DATE
+         GROUP BY created_at::DATE
+         ORDER BY day",
+        *TICKET_RESERVATIONS_TABLE
     );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&ticket_id];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    DbTicket::try_from(row)
+    let query_values: Vec<&(dyn ToSql + Sync)> = vec![since];
+    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
+    let days: Result<Vec<_>, _> = rows
+        .into_iter()
+        .map(DbDailyReservations::try_from)
+        .collect();
+    days
 }
 
-pub async fn db_get_asset_file(
+pub async fn db_insert_notification(
     db_client: &Client,
-    id: &uuid::Uuid,
-) -> Result<AssetFile, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE id = $1::UUID",
-        *ASSET_FILES_SELECT_FIELDS, *ASSET_FILES_TABLE
-    );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&id];
-    let row: tokio_postgres::Row = db_client.query_one(&query, query_values.as_slice()).await?;
-    AssetFile::try_from(row)
+    db_notification: &DbNotification,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_notification");
+    let kind = i16::from(db_notification.kind);
+    insert::<DbNotification>()
+        .values(&[
+            &db_notification.id,
+            &db_notification.created_at,
+            &db_notification.user_id,
+            &kind,
+            &db_notification.message,
+            &db_notification.data,
+            &db_notification.read_at,
+        ])
+        .execute(db_client)
+        .await
 }
 
-pub async fn db_get_files_for_event(
+/// A page of the user's notifications, the latest first. Only the unread ones if `unread_only`
+pub async fn db_get_notifications_by_user_id(
     db_client: &Client,
-    event_id: &uuid::Uuid,
-) -> Result<Vec<AssetFile>, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE event_id = $1::UUID",
-        *ASSET_FILES_SELECT_FIELDS, *ASSET_FILES_TABLE
-    );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&event_id];
-    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
+    user_id: &uuid::Uuid,
+    unread_only: bool,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<DbNotification>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_notifications_by_user_id");
+    select::<DbNotification>()
+        .filter(cond("user_id = {}::UUID").bind(&user_id))
+        .filter(cond("(NOT {}::BOOLEAN OR read_at IS NULL)").bind(&unread_only))
+        .order_by("created_at DESC")
+        .limit(&limit)
+        .offset(&offset)
+        .fetch_all(db_client)
+        .await
+}
 
-    rows.into_iter().map(|r| AssetFile::try_from(r)).collect()
+pub async fn db_count_unread_notifications(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+) -> Result<i64, tokio_postgres::Error> {
+    let _timer = db_timer("db_count_unread_notifications");
+    let row = query(format!(
+        "SELECT COUNT(*) FROM {} WHERE user_id = $1::UUID AND read_at IS NULL",
+        DbNotification::TABLE
+    ))
+    .bind(&user_id)
+    .query_one(db_client)
+    .await?;
+    row.try_get(0)
 }
 
-pub async fn update_file_ipfs_hash(
+/// Marks the user's notification as read, keeping when it was first read. Returns `None` if the
+/// user has no such notification
+pub async fn db_mark_notification_read(
     db_client: &Client,
-    id: &uuid::Uuid,
-    hash: &String,
-) -> Result<AssetFile, tokio_postgres::Error> {
-    let update_query = format!(
-        "UPDATE {} 
-         SET ipfs_hash = $1
-         WHERE id = $2 AND ipfs_hash is NULL
+    notification_id: &uuid::Uuid,
+    user_id: &uuid::Uuid,
+) -> Result<Option<DbNotification>, tokio_postgres::Error> {
+    let _timer = db_timer("db_mark_notification_read");
+    let read_at = sql_timestamp(None);
+    query(format!(
+        "UPDATE {} SET read_at = COALESCE(read_at, $3::TIMESTAMP)
+         WHERE id = $1::UUID AND user_id = $2::UUID
          RETURNING {}",
-        *ASSET_FILES_TABLE, *ASSET_FILES_SELECT_FIELDS
-    );
+        DbNotification::TABLE,
+        DbNotification::fields()
+    ))
+    .bind(&notification_id)
+    .bind(&user_id)
+    .bind(&read_at)
+    .fetch_opt(db_client)
+    .await
+}
 
-    let update_stmt = db_client.prepare(&update_query).await?;
+/// Registers the seller's webhook, replacing the url and secret of a previous one
+pub async fn db_upsert_seller_webhook(
+    db_client: &Client,
+    db_webhook: &DbSellerWebhook,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_upsert_seller_webhook");
+    insert::<DbSellerWebhook>()
+        .values(&[
+            &db_webhook.seller_id,
+            &db_webhook.created_at,
+            &db_webhook.url,
+            &db_webhook.secret,
+        ])
+        .on_conflict(
+            "(seller_id) DO UPDATE SET created_at = EXCLUDED.created_at, url = EXCLUDED.url, \
+             secret = EXCLUDED.secret",
+        )
+        .execute(db_client)
+        .await
+}
 
-    let x = db_client.query_one(&update_stmt, &[&hash, &id]).await?;
+pub async fn db_get_seller_webhook(
+    db_client: &Client,
+    seller_id: &uuid::Uuid,
+) -> Result<Option<DbSellerWebhook>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_seller_webhook");
+    select::<DbSellerWebhook>()
+        .filter(cond("seller_id = {}::UUID").bind(&seller_id))
+        .fetch_opt(db_client)
+        .await
+}
 
-    x.try_into()
+/// Removes the seller's webhook, returns whether they had one
+pub async fn db_delete_seller_webhook(
+    db_client: &Client,
+    seller_id: &uuid::Uuid,
+) -> Result<bool, tokio_postgres::Error> {
+    let _timer = db_timer("db_delete_seller_webhook");
+    query(format!(
+        "DELETE FROM {} WHERE seller_id = $1::UUID",
+        DbSellerWebhook::TABLE
+    ))
+    .bind(&seller_id)
+    .execute(db_client)
+    .await
+    .map(|deleted| deleted > 0)
 }
 
-pub async fn insert_asset_file(
+/// Records that the ticket reached a stock threshold, returns false if it was recorded already
+pub async fn db_insert_stock_alert(
     db_client: &Client,
-    file: &AssetFile,
-) -> Result<AssetFile, tokio_postgres::Error> {
-    let insert_query = format!(
-        "INSERT INTO {} 
-                ({})
-            VALUES ($1, $2, $3, $4, $5)",
-        *ASSET_FILES_TABLE, *ASSET_FILES_SELECT_FIELDS
-    );
-    let create_s3_file_stmt = db_client.prepare(&insert_query).await?;
+    db_stock_alert: &DbStockAlert,
+) -> Result<bool, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_stock_alert");
+    insert::<DbStockAlert>()
+        .values(&[
+            &db_stock_alert.id,
+            &db_stock_alert.created_at,
+            &db_stock_alert.ticket_id,
+            &db_stock_alert.threshold_percent,
+        ])
+        .on_conflict("(ticket_id, threshold_percent) DO NOTHING")
+        .execute(db_client)
+        .await
+        .map(|inserted| inserted > 0)
+}
 
-    db_client
-        .execute(
-            &create_s3_file_stmt,
-            &[
-                &file.id,
-                &file.s3_bucket,
-                &file.s3_absolute_key,
-                &file.ipfs_hash,
-                &file.event_id,
-            ],
-        )
-        .await?;
+/// Sets the hours before the start until which buyers may cancel, `None` for no cancellations
+pub async fn db_update_event_cancellation_window(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    cancellation_window_hours: Option<i32>,
+) -> Result<DbEvent, tokio_postgres::Error> {
+    let _timer = db_timer("db_update_event_cancellation_window");
+    update::<DbEvent>()
+        .set("cancellation_window_hours", &cancellation_window_hours)
+        .filter(cond("id = {}::UUID").bind(&event_id))
+        .fetch_one(db_client)
+        .await
+}
 
-    Ok(file.clone())
+/// Marks the event cancelled, no more tickets of it are reserved. Returns `None` if it was
+/// cancelled already
+pub async fn db_cancel_event(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+) -> Result<Option<DbEvent>, tokio_postgres::Error> {
+    let _timer = db_timer("db_cancel_event");
+    let cancelled = i16::from(EventStatus::Cancelled);
+    update::<DbEvent>()
+        .set("event_status", &cancelled)
+        .filter(
+            cond("id = {}::UUID")
+                .bind(&event_id)
+                .and(cond("event_status <> {}::SMALLINT").bind(&cancelled)),
+        )
+        .fetch_opt(db_client)
+        .await
 }
 
-pub async fn db_insert_ticket_reservation(
+/// Cancels the reservations `condition` matches, binding `$1`, in a single statement: they are
+/// removed, their tickets put back on sale at the ticket and at their tier, and a cancellation
+/// recording what the buyer paid is inserted for each
+async fn cancel_ticket_reservations(
     db_client: &Client,
-    db_ticket_reservation: &DbTicketReservation,
-) -> Result<u64, tokio_postgres::Error> {
-    let insert_query = format!(
-        "INSERT INTO {}
-                ({})
-            VALUES ($1, $2, $3, $4, $5, $6)",
-        *TICKET_RESERVATIONS_TABLE, *TICKET_RESERVATIONS_TABLE_FIELDS
+    condition: &str,
+    key: &uuid::Uuid,
+    cancelled_by: &uuid::Uuid,
+    now: &NaiveDateTime,
+) -> Result<Vec<DbTicketCancellation>, tokio_postgres::Error> {
+    let statement = format!(
+        "WITH cancelled AS (
+            DELETE FROM {reservations} WHERE {condition} RETURNING *
+        ), restored AS (
+            UPDATE {tickets} t
+                SET quantity_reserved = GREATEST(t.quantity_reserved - c.quantity, 0)
+            FROM (SELECT ticket_id, SUM(quantity)::INTEGER AS quantity
+                FROM cancelled GROUP BY ticket_id) c
+            WHERE t.id = c.ticket_id
+        ), released AS (
+            UPDATE {price_tiers} pt
+                SET quantity_sold = GREATEST(pt.quantity_sold - c.quantity, 0)
+            FROM (SELECT price_tier_id, SUM(quantity)::INTEGER AS quantity
+                FROM cancelled WHERE price_tier_id IS NOT NULL GROUP BY price_tier_id) c
+            WHERE pt.id = c.price_tier_id
+        )
+        INSERT INTO {cancellations} ({fields})
+            SELECT c.id, $2::TIMESTAMP, c.created_at, c.verification_code, c.event_id,
+                c.ticket_id, c.user_id, c.quantity, $3::UUID,
+                GREATEST(
+                    COALESCE(pt.price, t.price, '0')::NUMERIC * c.quantity
+                    - COALESCE(
+                        (u.original_price::NUMERIC - u.discounted_price::NUMERIC) * c.quantity, 0),
+                    0)::NUMERIC(78, 0)::TEXT,
+                NULL, NULL
+            FROM cancelled c
+            JOIN {tickets} t ON t.id = c.ticket_id
+            LEFT JOIN {price_tiers} pt ON pt.id = c.price_tier_id
+            LEFT JOIN {usages} u ON u.verification_code = c.verification_code
+                AND u.ticket_id = c.ticket_id
+        RETURNING {fields}",
+        reservations = *TICKET_RESERVATIONS_TABLE,
+        tickets = *TICKETS_TABLE,
+        price_tiers = DbTicketPriceTier::TABLE,
+        usages = *PROMO_CODE_USAGES_TABLE,
+        cancellations = DbTicketCancellation::TABLE,
+        fields = DbTicketCancellation::fields(),
     );
-    let create_statement = db_client.prepare(&insert_query).await?;
+    query(statement)
+        .bind(&key)
+        .bind(&now)
+        .bind(&cancelled_by)
+        .fetch_all(db_client)
+        .await
+}
 
-    let res_ticket_reservation = db_client
-        .execute(
-            &create_statement,
-            &[
-                &db_ticket_reservation.id,
-                &db_ticket_reservation.created_at,
-                &db_ticket_reservation.verification_code,
-                &db_ticket_reservation.event_id,
-                &db_ticket_reservation.ticket_id,
-                &db_ticket_reservation.user_id,
-            ],
+/// Cancels the reservation, returns `None` if there is no such reservation, e.g. it was
+/// cancelled already
+pub async fn db_cancel_ticket_reservation(
+    db_client: &Client,
+    reservation_id: &uuid::Uuid,
+    cancelled_by: &uuid::Uuid,
+    now: &NaiveDateTime,
+) -> Result<Option<DbTicketCancellation>, tokio_postgres::Error> {
+    let _timer = db_timer("db_cancel_ticket_reservation");
+    cancel_ticket_reservations(
+        db_client,
+        "id = $1::UUID",
+        reservation_id,
+        cancelled_by,
+        now,
+    )
+    .await
+    .map(|cancellations| cancellations.into_iter().next())
+}
+
+/// Cancels every reservation of the event
+pub async fn db_cancel_event_reservations(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    cancelled_by: &uuid::Uuid,
+    now: &NaiveDateTime,
+) -> Result<Vec<DbTicketCancellation>, tokio_postgres::Error> {
+    let _timer = db_timer("db_cancel_event_reservations");
+    cancel_ticket_reservations(
+        db_client,
+        "event_id = $1::UUID",
+        event_id,
+        cancelled_by,
+        now,
+    )
+    .await
+}
+
+pub async fn db_get_ticket_cancellation_by_id(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<Option<DbTicketCancellation>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_ticket_cancellation_by_id");
+    select::<DbTicketCancellation>()
+        .filter(cond("id = {}::UUID").bind(&id))
+        .fetch_opt(db_client)
+        .await
+}
+
+/// The buyer's cancelled reservations, the latest first
+pub async fn db_get_ticket_cancellations_by_user_id(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+) -> Result<Vec<DbTicketCancellation>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_ticket_cancellations_by_user_id");
+    select::<DbTicketCancellation>()
+        .filter(cond("user_id = {}::UUID").bind(&user_id))
+        .order_by("created_at DESC, id")
+        .fetch_all(db_client)
+        .await
+}
+
+/// Records the transfer refunding the cancellation, returns `None` if it was refunded already
+pub async fn db_complete_ticket_refund(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    tx_hash: &str,
+    now: &NaiveDateTime,
+) -> Result<Option<DbTicketCancellation>, tokio_postgres::Error> {
+    let _timer = db_timer("db_complete_ticket_refund");
+    update::<DbTicketCancellation>()
+        .set("refunded_at", &now)
+        .set("refund_tx_hash", &tx_hash)
+        .filter(
+            cond("id = {}::UUID")
+                .bind(&id)
+                .and(cond("refunded_at IS NULL")),
         )
-        .await;
-    res_ticket_reservation
+        .fetch_opt(db_client)
+        .await
 }
 
-/*
-pub enum TicketReservationQueryItem {
-    VerificationCode(String),
-    UserId(i32),
+/// The maintenance mode the admins set, `None` if they never did
+pub async fn db_get_maintenance(
+    db_client: &Client,
+) -> Result<Option<DbMaintenance>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_maintenance");
+    select::<DbMaintenance>().fetch_opt(db_client).await
 }
 
-pub async fn db_get_ticket_reservation(
+/// Sets the maintenance mode, its single row is created the first time
+pub async fn db_upsert_maintenance(
     db_client: &Client,
-    query_item: TicketReservationQueryItem,
-) -> Result<DbTicketReservation, tokio_postgres::Error> {
-    let (query, query_values) = match query_item {
-        TicketReservationQueryItem::VerificationCode(verification_code) => {
-            let query = format!(
-                "SELECT {} FROM {} WHERE verification_code = $1::VARCHAR",
-                *TICKET_RESERVATIONS_TABLE_FIELDS, *TICKET_RESERVATIONS_TABLE
-            );
-            let query_values: Vec<Box<(dyn ToSql + Sync)>> = vec![Box::new(verification_code)];
-            (query, query_values)
-        }
-        TicketReservationQueryItem::UserId(user_id) => {
-            let query = format!(
-                "SELECT {} FROM {} WHERE user_id = $1::INT",
-                *TICKET_RESERVATIONS_TABLE_FIELDS, *TICKET_RESERVATIONS_TABLE
-            );
-            let query_values: Vec<Box<(dyn ToSql + Sync)>> = vec![Box::new(user_id)];
-            (query, query_values)
-        }
-    };
-    let vec_of_refs = query_values
-        .iter()
-        .map(|x| &**x)
-        .collect::<Vec<&(dyn ToSql + Sync)>>();
+    db_maintenance: &DbMaintenance,
+) -> Result<DbMaintenance, tokio_postgres::Error> {
+    let _timer = db_timer("db_upsert_maintenance");
+    insert::<DbMaintenance>()
+        .values(&[
+            &db_maintenance.enabled,
+            &db_maintenance.message,
+            &db_maintenance.retry_after_secs,
+            &db_maintenance.updated_at,
+            &db_maintenance.updated_by,
+        ])
+        .on_conflict(
+            "(id) DO UPDATE SET enabled = EXCLUDED.enabled, message = EXCLUDED.message, \
+             retry_after_secs = EXCLUDED.retry_after_secs, updated_at = EXCLUDED.updated_at, \
+             updated_by = EXCLUDED.updated_by",
+        )
+        .fetch_one(db_client)
+        .await
+}
 
-    let row: tokio_postgres::Row = db_client.query_one(&query, vec_of_refs.as_slice()).await?;
-    DbTicketReservation::try_from(row)
+/// Inserts the series and makes the event its first occurrence, in a single statement. Returns
+/// `None`, inserting nothing, if the event is an occurrence of a series already
+pub async fn db_insert_event_series(
+    db_client: &Client,
+    db_series: &DbEventSeries,
+    event_id: &uuid::Uuid,
+) -> Result<Option<DbEvent>, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_event_series");
+    query(format!(
+        "WITH series AS (
+            INSERT INTO {series_table} ({series_fields})
+            SELECT $1::UUID, $2::TIMESTAMP, $3::VARCHAR, $4::VARCHAR, $5::TIMESTAMP, $6::UUID
+             WHERE EXISTS (SELECT 1 FROM {events_table} WHERE id = $7::UUID AND series_id IS NULL)
+            RETURNING id
+         )
+         UPDATE {events_table} SET series_id = (SELECT id FROM series)
+          WHERE id = $7::UUID AND series_id IS NULL AND EXISTS (SELECT 1 FROM series)
+         RETURNING {events_fields}",
+        series_table = DbEventSeries::TABLE,
+        series_fields = DbEventSeries::fields(),
+        events_table = *EVENTS_TABLE,
+        events_fields = *EVENTS_TABLE_FIELDS,
+    ))
+    .bind(&db_series.id)
+    .bind(&db_series.created_at)
+    .bind(&db_series.series_name)
+    .bind(&db_series.rrule)
+    .bind(&db_series.starts_at)
+    .bind(&db_series.created_by_user)
+    .bind(event_id)
+    .fetch_opt(db_client)
+    .await
 }
-*/
 
-pub async fn db_get_ticket_reservations_by_code(
+pub async fn db_get_event_series_by_id(
     db_client: &Client,
-    verification_code: &str,
-) -> Result<Vec<DbTicketReservation>, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE verification_code = $1::VARCHAR",
-        *TICKET_RESERVATIONS_TABLE_FIELDS, *TICKET_RESERVATIONS_TABLE
-    );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&verification_code];
-    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
-    let reservations: Result<Vec<_>, _> = rows
-        .into_iter()
-        .map(|r| DbTicketReservation::try_from(r))
-        .collect();
-    reservations
+    id: &uuid::Uuid,
+) -> Result<Option<DbEventSeries>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_event_series_by_id");
+    select::<DbEventSeries>()
+        .filter(cond("id = {}::UUID").bind(&id))
+        .fetch_opt(db_client)
+        .await
 }
 
-pub async fn db_get_ticket_reservations_by_user_id(
+/// The occurrences of the series in one of the statuses, any if empty, earliest first. Deleted
+/// and archived occurrences are left out
+pub async fn db_get_events_by_series_id(
     db_client: &Client,
-    user_id: &uuid::Uuid,
-) -> Result<Vec<DbTicketReservation>, tokio_postgres::Error> {
-    let query = format!(
-        "SELECT {} FROM {} WHERE user_id = $1::UUID",
-        *TICKET_RESERVATIONS_TABLE_FIELDS, *TICKET_RESERVATIONS_TABLE
-    );
-    let query_values: Vec<&(dyn ToSql + Sync)> = vec![&user_id];
-    let rows: Vec<tokio_postgres::Row> = db_client.query(&query, query_values.as_slice()).await?;
-    let reservations: Result<Vec<_>, _> = rows
-        .into_iter()
-        .map(|r| DbTicketReservation::try_from(r))
-        .collect();
-    reservations
+    series_id: &uuid::Uuid,
+    statuses: &[EventStatus],
+) -> Result<Vec<DbEvent>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_events_by_series_id");
+    let statuses = statuses
+        .iter()
+        .map(|status| i16::from(*status))
+        .collect::<Vec<_>>();
+    let mut conditions = vec![
+        cond("series_id = {}::UUID").bind(series_id),
+        cond("deleted_at IS NULL AND NOT archived"),
+    ];
+    if !statuses.is_empty() {
+        conditions.push(cond("event_status = ANY({}::SMALLINT[])").bind(&statuses));
+    }
+    select::<DbEvent>()
+        .filter(Condition::all(conditions))
+        .order_by("start_date, id")
+        .fetch_all(db_client)
+        .await
 }
 
-pub async fn db_select_one(db_client: &Client) -> Result<u64, tokio_postgres::Error> {
-    db_client.execute("SELECT 1", &[]).await
+/// Inserts the section along with its seats and sets the quantity of its ticket type to the seats
+/// of all its sections, in a single statement. Returns `None`, inserting nothing, if the event
+/// has a section of that name already
+pub async fn db_insert_seat_section(
+    db_client: &Client,
+    db_section: &DbSeatSection,
+    db_seats: &[DbSeat],
+) -> Result<Option<DbTicket>, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_seat_section");
+    let ids = db_seats.iter().map(|seat| seat.id).collect::<Vec<_>>();
+    let row_labels = db_seats
+        .iter()
+        .map(|seat| seat.row_label.as_str())
+        .collect::<Vec<_>>();
+    let seat_numbers = db_seats
+        .iter()
+        .map(|seat| seat.seat_number)
+        .collect::<Vec<_>>();
+    query(format!(
+        "WITH section AS (
+            INSERT INTO {sections_table} ({sections_fields})
+            VALUES ($1::UUID, $2::TIMESTAMP, $3::UUID, $4::UUID, $5::VARCHAR, $6::INTEGER)
+            ON CONFLICT (event_id, section_name) DO NOTHING
+            RETURNING id
+         ), inserted AS (
+            INSERT INTO {seats_table} ({seats_fields})
+            SELECT seat.id, section.id, $3::UUID, $4::UUID, seat.row_label, seat.seat_number,
+                NULL, NULL, NULL
+              FROM section, UNNEST($7::UUID[], $8::VARCHAR[], $9::INTEGER[])
+                AS seat (id, row_label, seat_number)
+            RETURNING id
+         )
+         UPDATE {tickets_table}
+            SET quantity_available = ((SELECT COUNT(*) FROM {seats_table} WHERE ticket_id = $4::UUID)
+                + (SELECT COUNT(*) FROM inserted))::INTEGER,
+                version = version + 1
+          WHERE id = $4::UUID AND EXISTS (SELECT 1 FROM section)
+         RETURNING {tickets_fields}",
+        sections_table = DbSeatSection::TABLE,
+        sections_fields = DbSeatSection::fields(),
+        seats_table = DbSeat::TABLE,
+        seats_fields = DbSeat::fields(),
+        tickets_table = *TICKETS_TABLE,
+        tickets_fields = *TICKETS_TABLE_FIELDS,
+    ))
+    .bind(&db_section.id)
+    .bind(&db_section.created_at)
+    .bind(&db_section.event_id)
+    .bind(&db_section.ticket_id)
+    .bind(&db_section.section_name)
+    .bind(&db_section.position)
+    .bind(&ids)
+    .bind(&row_labels)
+    .bind(&seat_numbers)
+    .fetch_opt(db_client)
+    .await
 }
 
-pub fn sql_timestamp(sec_to_add: Option<i64>) -> NaiveDateTime {
-    let timestamp: i64 = Utc::now()
-        .checked_add_signed(Duration::seconds(sec_to_add.unwrap_or_default()))
-        .and_then(|r| Some(r.timestamp_millis()))
-        .expect("bad current timestamp");
+pub async fn db_get_seat_sections_by_event_id(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+) -> Result<Vec<DbSeatSection>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_seat_sections_by_event_id");
+    select::<DbSeatSection>()
+        .filter(cond("event_id = {}::UUID").bind(&event_id))
+        .order_by("position, section_name")
+        .fetch_all(db_client)
+        .await
+}
 
-    let created_at =
-        NaiveDateTime::from_timestamp_opt(timestamp / 1000, (timestamp % 1000) as u32 * 1_000_000)
-            .expect("bad native data time conversion");
-    created_at
+pub async fn db_get_seats_by_event_id(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+) -> Result<Vec<DbSeat>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_seats_by_event_id");
+    select::<DbSeat>()
+        .filter(cond("event_id = {}::UUID").bind(&event_id))
+        .order_by("section_id, row_label, seat_number")
+        .fetch_all(db_client)
+        .await
 }
 
-/// A generic query with parameters
-pub struct Query<'a> {
-    pub statement: Cow<'a, str>,
-    pub params: Vec<&'a (dyn ToSql + Sync)>,
+/// Holds the event's seats for the user until `held_until`, all of them or none. A seat is held
+/// if it is not reserved and either not held, its hold is over or the user holds it already.
+/// Returns the seats held, empty if any could not be
+pub async fn db_hold_seats(
+    db_client: &Client,
+    event_id: &uuid::Uuid,
+    seat_ids: &[uuid::Uuid],
+    user_id: &uuid::Uuid,
+    held_until: &NaiveDateTime,
+    now: &NaiveDateTime,
+) -> Result<Vec<DbSeat>, tokio_postgres::Error> {
+    let _timer = db_timer("db_hold_seats");
+    query(format!(
+        "WITH requested AS (
+            SELECT id FROM {table}
+             WHERE id = ANY($1::UUID[]) AND event_id = $2::UUID AND reservation_id IS NULL
+               AND (held_until IS NULL OR held_until <= $5::TIMESTAMP OR held_by = $3::UUID)
+            FOR UPDATE
+         )
+         UPDATE {table} SET held_by = $3::UUID, held_until = $4::TIMESTAMP
+          WHERE id IN (SELECT id FROM requested)
+            AND (SELECT COUNT(*) FROM requested) = CARDINALITY($1::UUID[])
+         RETURNING {fields}",
+        table = DbSeat::TABLE,
+        fields = DbSeat::fields(),
+    ))
+    .bind(&seat_ids)
+    .bind(&event_id)
+    .bind(&user_id)
+    .bind(&held_until)
+    .bind(&now)
+    .fetch_all(db_client)
+    .await
 }
 
-/// Create a new query
-#[must_use]
-pub fn query<'a>(statement: impl Into<Cow<'a, str>>) -> Query<'a> {
-    Query::new(statement.into())
+/// Releases the seats the user holds, returns how many were
+pub async fn db_release_seats(
+    db_client: &Client,
+    seat_ids: &[uuid::Uuid],
+    user_id: &uuid::Uuid,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_release_seats");
+    update::<DbSeat>()
+        .set_expr(cond("held_by = NULL"))
+        .set_expr(cond("held_until = NULL"))
+        .filter(cond("id = ANY({}::UUID[])").bind(&seat_ids))
+        .filter(cond("held_by = {}::UUID").bind(&user_id))
+        .filter(cond("reservation_id IS NULL"))
+        .execute(db_client)
+        .await
 }
 
-impl<'a> Query<'a> {
-    /// creates a new query
-    pub fn new(statement: Cow<'a, str>) -> Self {
-        Self {
-            statement,
-            params: Vec::new(),
-        }
-    }
+/// Whether the ticket type is sold as seats of a seat map rather than general admission
+pub async fn db_is_ticket_seated(
+    db_client: &Client,
+    ticket_id: &uuid::Uuid,
+) -> Result<bool, tokio_postgres::Error> {
+    let _timer = db_timer("db_is_ticket_seated");
+    let row = query(format!(
+        "SELECT EXISTS (SELECT 1 FROM {} WHERE ticket_id = $1::UUID)",
+        DbSeat::TABLE
+    ))
+    .bind(&ticket_id)
+    .query_one(db_client)
+    .await?;
+    row.try_get(0)
+}
 
-    /// Bind an unnamed parameter
-    pub fn bind<T: Into<&'a (dyn ToSql + Sync)>>(mut self, value: T) -> Self {
-        self.params.push(value.into());
-        self
-    }
+pub async fn db_insert_seller_phone_verification(
+    db_client: &Client,
+    db_verification: &DbSellerPhoneVerification,
+) -> Result<u64, tokio_postgres::Error> {
+    let _timer = db_timer("db_insert_seller_phone_verification");
+    insert::<DbSellerPhoneVerification>()
+        .values(&[
+            &db_verification.id,
+            &db_verification.created_at,
+            &db_verification.user_id,
+            &db_verification.phone_number,
+            &db_verification.verification_code_hash,
+            &db_verification.attempts,
+            &db_verification.expires_at,
+            &db_verification.verified_at,
+        ])
+        .execute(db_client)
+        .await
+}
 
-    /// Binds multiple unnamed parameters
-    pub fn bind_all<T: Into<&'a (dyn ToSql + Sync)>>(
-        mut self,
-        value: impl IntoIterator<Item = T>,
-    ) -> Self {
-        self.params.extend(value.into_iter().map(Into::into));
-        self
-    }
+/// The code last sent to the seller, if any
+pub async fn db_get_latest_seller_phone_verification(
+    db_client: &Client,
+    user_id: &uuid::Uuid,
+) -> Result<Option<DbSellerPhoneVerification>, tokio_postgres::Error> {
+    let _timer = db_timer("db_get_latest_seller_phone_verification");
+    select::<DbSellerPhoneVerification>()
+        .filter(cond("user_id = {}::UUID").bind(&user_id))
+        .order_by("created_at DESC")
+        .limit(&1)
+        .fetch_opt(db_client)
+        .await
+}
 
-    /// allows us to query one row only
-    pub async fn query_one<T>(
-        self,
-        db: &Client,
-    ) -> Result<tokio_postgres::Row, tokio_postgres::Error>
-    where
-        T: Send + Unpin + 'static,
-    {
-        db.query_one(&self.statement.to_string(), &self.params)
-            .await
-    }
+pub async fn db_increment_seller_phone_verification_attempts(
+    db_client: &Client,
+    id: &uuid::Uuid,
+) -> Result<i32, tokio_postgres::Error> {
+    let _timer = db_timer("db_increment_seller_phone_verification_attempts");
+    let row = query(format!(
+        "UPDATE {} SET attempts = attempts + 1 WHERE id = $1::UUID RETURNING attempts",
+        DbSellerPhoneVerification::TABLE
+    ))
+    .bind(&id)
+    .query_one(db_client)
+    .await?;
+    row.try_get(0)
+}
+
+/// Marks the code verified and stores its phone number on the seller's verified account, in a
+/// single statement. Returns `None`, changing nothing, if the code was verified already or
+/// another user took the phone number meanwhile
+pub async fn db_complete_seller_phone_verification(
+    db_client: &Client,
+    id: &uuid::Uuid,
+    verified_at: &NaiveDateTime,
+) -> Result<Option<DbUser>, tokio_postgres::Error> {
+    let _timer = db_timer("db_complete_seller_phone_verification");
+    query(format!(
+        "WITH verified AS (
+            UPDATE {verifications} SET verified_at = $2::TIMESTAMP
+             WHERE id = $1::UUID AND verified_at IS NULL
+               AND NOT EXISTS (
+                   SELECT 1 FROM {users} other
+                    WHERE other.phone_number = {verifications}.phone_number
+                      AND other.id <> {verifications}.user_id
+               )
+            RETURNING user_id, phone_number
+         )
+         UPDATE {users} SET user_status = $3::SMALLINT, phone_number = verified.phone_number
+           FROM verified
+          WHERE {users}.id = verified.user_id
+         RETURNING {fields}",
+        verifications = DbSellerPhoneVerification::TABLE,
+        users = *USERS_TABLE,
+        fields = DbUser::FIELDS
+            .iter()
+            .map(|field| format!("{}.{}", *USERS_TABLE, field))
+            .collect::<Vec<_>>()
+            .join(", "),
+    ))
+    .bind(&id)
+    .bind(&verified_at)
+    .bind(&i16::from(UserStatus::PhoneVerified))
+    .fetch_opt(db_client)
+    .await
 }
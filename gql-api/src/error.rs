@@ -1,8 +1,15 @@
+use crate::db::models::DbTicket;
+use crate::db::sql::unique_violation;
+use crate::gql::models::MaintenanceMode;
 use crate::http::models::{ErrorResponse, FieldError};
+use crate::security::password_policy::CharacterClass;
 use displaydoc::Display as DisplayDoc;
 use near_account_id::ParseAccountError;
 use pusher_client::error::PusherError;
-use reqwest::StatusCode;
+use reqwest::{
+    header::{HeaderValue, RETRY_AFTER},
+    StatusCode,
+};
 use std::{convert::Infallible, error::Error as StdError, net::AddrParseError};
 use thiserror::Error;
 use twilio_client::error::TwilioError;
@@ -30,6 +37,8 @@ pub enum Error {
     Event(EventError),
     /// Ticket error: `{0}`
     Ticket(TicketError),
+    /// Promo code error: `{0}`
+    PromoCode(PromoCodeError),
     /// Request error: `{0}`
     Request(RequestError),
     /// Signature error: `{0}`
@@ -38,12 +47,32 @@ pub enum Error {
     Base58(bs58::decode::Error),
     /// Grpc error: `{0}`
     Grpc(GrpcError),
+    /// Chain error: `{0}`
+    Chain(ChainError),
+    /// Storage error: `{0}`
+    Storage(StorageError),
+    /// Ticket pdf error: `{0}`
+    TicketPdf(qrcode::types::QrError),
     /// Session error: `{0}`
     Session(SessionError),
     /// Pusher error: `{0}`
     Pusher(PusherError),
     /// Twilio error: `{0}`
     Twilio(TwilioError),
+    /// Job error: `{0}`
+    Job(JobError),
+    /// Ipfs error: `{0}`
+    Ipfs(IpfsError),
+    /// NEAR amount error: `{0}`
+    NearAmount(NearAmountError),
+    /// Exchange rate error: `{0}`
+    Fx(FxError),
+    /// Sanitation error: `{0}`
+    Sanitize(SanitizeError),
+    /// Password error: `{0}`
+    Password(PasswordError),
+    /// Under maintenance
+    Maintenance(MaintenanceMode),
 }
 
 impl warp::reject::Reject for Error {}
@@ -76,6 +105,20 @@ pub enum AuthError {
     NoPermissionError,
     /// Bad Encoded User Role: `{0}`
     BadEncodedUserRole(String),
+    /// Impersonation tokens are not accepted here
+    ImpersonationNotAllowed,
+    /// Impersonation has been revoked or has expired
+    ImpersonationRevoked,
+    /// Two-factor authentication is not available
+    TotpUnavailable,
+    /// Too many failed sign-ins, retry in `{0}` seconds
+    TooManyFailedSignins(i64),
+    /// Not allowed to subscribe to channel `{0}`
+    ChannelNotAllowed(String),
+    /// Invalid socket id: `{0}`
+    InvalidSocketId(String),
+    /// The account must be verified first
+    UnverifiedUser,
 }
 
 impl warp::reject::Reject for AuthError {}
@@ -85,12 +128,18 @@ impl warp::reject::Reject for AuthError {}
 pub enum UserError {
     /// User not found
     UserNotFound,
+    /// User account has been deleted
+    AccountDeleted,
+    /// Invalid phone number: `{0}`
+    InvalidPhoneNumber(String),
     /// User has no password
     NoPassword,
     /// Unknown User Role: `{0}`
     UnknownUserRole(String),
     /// Unknown User Status: `{0}`
     UnknownUserStatus(String),
+    /// Unknown Seller Status: `{0}`
+    UnknownSellerStatus(String),
     /// Unallowed User Role: `{0}`
     UnallowedUserRole(String),
     /// Only sellers allowed/User is not a seller
@@ -115,12 +164,16 @@ pub enum UserError {
     MissingPassword,
     /// Missing pubic key
     MissingPubKey,
+    /// Missing sign-in nonce
+    MissingNonce,
     /// User wallet creating Failed
     WalletCreationFailed,
     /// Bad signature
     BadSignature,
     /// Unavailable Username
     UnavailableUsername,
+    /// Disallowed username: `{0}`
+    DisallowedUsername(String),
     /// Unavailable Name
     UnavailableName,
     /// Unavailable Email
@@ -138,6 +191,14 @@ impl warp::reject::Reject for UserError {}
 pub enum EventError {
     /// Non-existing event with uuid: `{0}`
     NoExistEventUuid(String),
+    /// Not the creator of the event with uuid: `{0}`
+    NotEventCreator(String),
+    /// Cancelled event with uuid: `{0}`
+    CancelledEvent(String),
+    /// Event is not published, uuid: `{0}`
+    NotPublishedEvent(String),
+    /// Only events with status DRAFT could be edited, uuid: `{0}`
+    NotDraftEvent(String),
 }
 
 impl warp::reject::Reject for EventError {}
@@ -157,10 +218,63 @@ pub enum TicketError {
     NoTicketReservationsForCode(String),
     /// Ticket has already been reserved for the user: `{0}`
     AlreadyReservedForUser(String),
+    /// Ticket quantity must be at least one: `{0}`
+    InvalidQuantity(String),
+    /// Ticket quantity is below the minimum purchase quantity: `{0}`
+    BelowMinPurchaseQuantity(String),
+    /// Ticket quantity is above the maximum purchase quantity per user: `{0}`
+    AboveMaxPurchaseQuantity(String),
+    /// Not enough tickets left: `{0}`
+    InsufficientQuantity(String),
+    /// Seated tickets need one held seat per ticket: `{0}`
+    SeatsRequired(String),
+    /// Ticket is general admission, seats cannot be selected: `{0}`
+    NotSeated(String),
+    /// Seats are not held by the user anymore: `{0}`
+    SeatsNotHeld(String),
 }
 
 impl warp::reject::Reject for TicketError {}
 
+/// Ticket update errors
+#[derive(Debug, DisplayDoc, Error)]
+pub enum TicketUpdateError {
+    /// Postgres error: `{0}`
+    Postgres(tokio_postgres::Error),
+    /// Ticket was edited meanwhile, the update is based on an older version
+    VersionConflict(Box<DbTicket>),
+}
+
+/// Promo code-related errors
+#[derive(Clone, Debug, DisplayDoc, Error, PartialEq)]
+pub enum PromoCodeError {
+    /// Non-existing promo code: `{0}`
+    NoExistPromoCode(String),
+    /// Promo code is not active: `{0}`
+    InactivePromoCode(String),
+    /// Promo code is not valid at this time: `{0}`
+    OutsideValidityWindow(String),
+    /// Promo code usage limit reached: `{0}`
+    UsageLimitReached(String),
+}
+
+impl warp::reject::Reject for PromoCodeError {}
+
+/// Background job errors
+#[derive(Clone, Debug, DisplayDoc, Error, PartialEq)]
+pub enum JobError {
+    /// Unknown job type: `{0}`
+    UnknownJobType(String),
+    /// Unknown job status: `{0}`
+    UnknownJobStatus(String),
+    /// Unreadable job payload: `{0}`
+    Payload(String),
+    /// Job execution failed: `{0}`
+    Execution(String),
+}
+
+impl warp::reject::Reject for JobError {}
+
 /// Request-related errors
 #[derive(Clone, Debug, DisplayDoc, Error, PartialEq)]
 pub enum RequestError {
@@ -168,6 +282,10 @@ pub enum RequestError {
     JSONPathError(String),
     /// validation error: `{0}`
     ValidationError(ValidationErrors),
+    /// invalid upload: `{0}`
+    InvalidUpload(String),
+    /// unsupported content type, expected application/json: `{0}`
+    UnsupportedContentType(String),
 }
 
 impl warp::reject::Reject for RequestError {}
@@ -187,6 +305,16 @@ pub enum SessionError {
     UsedSession(String),
     /// Expired session for token: `{0}`
     ExpiredSession(String),
+    /// Too many attempts for session: `{0}`
+    TooManyAttempts(String),
+    /// Verification code for the session was sent too recently: `{0}`
+    ResendCooldown(String),
+    /// Too many verification codes sent for session: `{0}`
+    TooManyResends(String),
+    /// Verified session for token: `{0}`
+    VerifiedSession(String),
+    /// No unused sign-in challenge for nonce: `{0}`
+    NoChallengeForNonce(String),
 }
 
 impl warp::reject::Reject for SessionError {}
@@ -198,14 +326,234 @@ pub enum GrpcError {
     Transport(tonic::transport::Error),
     /// tonic call error: `{0}`
     Call(tonic::Status),
+    /// near api is disconnected, reconnecting in `{0}`ms
+    Disconnected(u128),
 }
 
 impl warp::reject::Reject for GrpcError {}
 
+/// blockchain-related errors
+#[derive(Debug, DisplayDoc, Error)]
+pub enum ChainError {
+    /// near api error: `{0}`
+    Near(GrpcError),
+    /// mock chain error: `{0}`
+    Mock(String),
+}
+
+impl warp::reject::Reject for ChainError {}
+
+/// ipfs-related errors
+#[derive(Clone, Debug, DisplayDoc, Error, PartialEq)]
+pub enum IpfsError {
+    /// ipfs request error: `{0}`
+    Request(String),
+    /// ipfs response error: `{0}`
+    Response(String),
+}
+
+impl warp::reject::Reject for IpfsError {}
+
+/// Exchange rate errors
+#[derive(Debug, DisplayDoc, Error)]
+pub enum FxError {
+    /// unknown currency: `{0}`
+    UnknownCurrency(String),
+    /// exchange rate request error: `{0}`
+    Request(String),
+    /// exchange rate response error: `{0}`
+    Response(String),
+}
+
+impl warp::reject::Reject for FxError {}
+
+/// Seeding errors
+#[derive(Debug, DisplayDoc, Error)]
+pub enum SeedError {
+    /// seed `{0}` was inserted already, seed again with another seed
+    AlreadySeeded(u64),
+    /// Postgres error: `{0}`
+    Postgres(tokio_postgres::Error),
+    /// Hash error: `{0}`
+    Hash(HashError),
+}
+
+/// Geocoding errors
+#[derive(Clone, Debug, DisplayDoc, Error, PartialEq)]
+pub enum GeoError {
+    /// geocoding request error: `{0}`
+    Request(String),
+    /// geocoding response error: `{0}`
+    Response(String),
+}
+
+/// Webhook delivery errors
+#[derive(Clone, Debug, DisplayDoc, Error, PartialEq)]
+pub enum WebhookError {
+    /// webhook request error: `{0}`
+    Request(String),
+    /// webhook response error: `{0}`
+    Response(String),
+}
+
+/// Notification errors
+#[derive(Clone, Debug, DisplayDoc, Error, PartialEq)]
+pub enum NotifierError {
+    /// notification could not be sent: `{0}`
+    Send(String),
+}
+
+/// Image processing errors
+#[derive(Clone, Debug, DisplayDoc, Error, PartialEq)]
+pub enum ImageError {
+    /// image could not be decoded: `{0}`
+    Decode(String),
+    /// image could not be encoded: `{0}`
+    Encode(String),
+}
+
+/// Realtime publishing errors
+#[derive(Clone, Debug, DisplayDoc, Error, PartialEq)]
+pub enum PublisherError {
+    /// event could not be published: `{0}`
+    Send(String),
+}
+
+/// Object storage errors
+#[derive(Clone, Debug, DisplayDoc, Error, PartialEq)]
+pub enum StorageError {
+    /// storage request failed: `{0}`
+    Request(String),
+}
+
+/// NEAR amount parsing errors
+#[derive(Clone, Debug, DisplayDoc, Error, PartialEq)]
+pub enum NearAmountError {
+    /// invalid NEAR amount: `{0}`
+    Invalid(String),
+    /// NEAR amount out of range: `{0}`
+    Overflow(String),
+}
+
+/// User-generated text not passing the sanitation
+#[derive(Clone, Copy, Debug, DisplayDoc, Error, PartialEq, Eq)]
+pub enum SanitizeError {
+    /// {0} must not be empty
+    Empty(&'static str),
+    /// {0} must be at most {1} characters
+    TooLong(&'static str, usize),
+}
+
+impl SanitizeError {
+    /// The field the error is about, named as in the api inputs
+    pub const fn field(&self) -> &'static str {
+        match self {
+            SanitizeError::Empty(field) | SanitizeError::TooLong(field, _) => field,
+        }
+    }
+}
+
+/// Passwords not passing the password policy
+#[derive(Clone, Copy, Debug, DisplayDoc, Error, PartialEq, Eq)]
+pub enum PasswordError {
+    /// password must be at least {0} characters
+    TooShort(usize),
+    /// password must be at most {0} characters
+    TooLong(usize),
+    /// password must contain {0}
+    MissingClass(CharacterClass),
+    /// password is too common
+    Common,
+    /// password has appeared in a data breach
+    Breached,
+}
+
+/// Unique constraint violations, the value is already someone else's
+#[derive(Clone, Copy, Debug, DisplayDoc, Error, PartialEq, Eq)]
+pub enum ConflictError {
+    /// Username is already taken
+    Username,
+    /// Email is already taken
+    Email,
+    /// Seller slug is already taken
+    SellerSlug,
+    /// Wallet is already registered
+    WalletId,
+    /// Event with the same slug already exists
+    EventSlug,
+    /// Ticket with the same slug already exists
+    TicketSlug,
+    /// Promo code already exists for this event
+    PromoCode,
+    /// A category with this name already exists
+    CategorySlug,
+    /// Organization with the same slug already exists
+    OrganizationSlug,
+    /// Reservation is already listed for sale
+    TicketListing,
+}
+
+impl ConflictError {
+    /// The conflict of a unique constraint, by its name
+    pub fn from_constraint(constraint: &str) -> Option<Self> {
+        match constraint {
+            "users_username_key" => Some(ConflictError::Username),
+            "users_email_idx" => Some(ConflictError::Email),
+            "users_seller_slug_idx" => Some(ConflictError::SellerSlug),
+            "users_wallet_id_key" => Some(ConflictError::WalletId),
+            "events_event_name_event_slug_key" | "events_event_slug_idx" => {
+                Some(ConflictError::EventSlug)
+            }
+            "tickets_ticket_name_ticket_slug_event_id_key" | "tickets_ticket_slug_idx" => {
+                Some(ConflictError::TicketSlug)
+            }
+            "promo_codes_code_event_id_key" => Some(ConflictError::PromoCode),
+            "categories_slug_key" => Some(ConflictError::CategorySlug),
+            "organizations_slug_key" => Some(ConflictError::OrganizationSlug),
+            "ticket_listings_reservation_id_idx" => Some(ConflictError::TicketListing),
+            _ => None,
+        }
+    }
+
+    /// The field the conflict is about, named as in the api inputs
+    pub fn field(&self) -> &'static str {
+        match self {
+            ConflictError::Username => "username",
+            ConflictError::Email => "email",
+            ConflictError::SellerSlug => "seller_slug",
+            ConflictError::WalletId => "wallet_id",
+            ConflictError::EventSlug => "event_slug",
+            ConflictError::TicketSlug => "ticket_slug",
+            ConflictError::PromoCode => "promo_code",
+            ConflictError::CategorySlug => "category_name",
+            ConflictError::OrganizationSlug => "name",
+            ConflictError::TicketListing => "reservation_id",
+        }
+    }
+}
+
 pub async fn handle_rejection(err: Rejection) -> std::result::Result<impl Reply, Infallible> {
-    let (code, message, errors) = if err.is_not_found() {
+    let (code, message, errors) = if let Some(Error::Maintenance(mode)) = err.find::<Error>() {
+        eprintln!("maintenance mode");
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            mode.message
+                .clone()
+                .unwrap_or_else(|| "Service Unavailable for maintenance".to_string()),
+            None,
+        )
+    } else if err.is_not_found() {
         eprintln!("NOT FOUND error");
         (StatusCode::NOT_FOUND, "Not Found".to_string(), None)
+    } else if let Some(e) = err.find::<warp::reject::PayloadTooLarge>() {
+        eprintln!("Payload too large error");
+        (StatusCode::PAYLOAD_TOO_LARGE, e.to_string(), None)
+    } else if let Some(e) = err.find::<warp::reject::LengthRequired>() {
+        eprintln!("Length required error");
+        (StatusCode::LENGTH_REQUIRED, e.to_string(), None)
+    } else if let Some(e) = err.find::<warp::reject::UnsupportedMediaType>() {
+        eprintln!("Unsupported media type error");
+        (StatusCode::UNSUPPORTED_MEDIA_TYPE, e.to_string(), None)
     } else if let Some(e) = err.find::<warp::filters::body::BodyDeserializeError>() {
         eprintln!("Invalid body error");
         (
@@ -221,7 +569,14 @@ pub async fn handle_rejection(err: Rejection) -> std::result::Result<impl Reply,
             AuthError::NoPermissionError => (StatusCode::UNAUTHORIZED, e.to_string(), None),
             AuthError::JWTTokenError => (StatusCode::UNAUTHORIZED, e.to_string(), None),
             AuthError::BadEncodedUserRole(_) => (StatusCode::UNAUTHORIZED, e.to_string(), None),
-            AuthError::JWTTokenCreationError => (
+            AuthError::ImpersonationNotAllowed => (StatusCode::FORBIDDEN, e.to_string(), None),
+            AuthError::UnverifiedUser => (StatusCode::FORBIDDEN, e.to_string(), None),
+            AuthError::ChannelNotAllowed(_) => (StatusCode::FORBIDDEN, e.to_string(), None),
+            AuthError::ImpersonationRevoked => (StatusCode::UNAUTHORIZED, e.to_string(), None),
+            AuthError::TooManyFailedSignins(_) => {
+                (StatusCode::TOO_MANY_REQUESTS, e.to_string(), None)
+            }
+            AuthError::JWTTokenCreationError | AuthError::TotpUnavailable => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Internal Server Error".to_string(),
                 None,
@@ -243,7 +598,12 @@ pub async fn handle_rejection(err: Rejection) -> std::result::Result<impl Reply,
     } else if let Some(Error::Request(e)) = err.find::<Error>() {
         eprintln!("request error: {:?}", e.to_string());
         match e {
-            RequestError::JSONPathError(_) => (StatusCode::BAD_REQUEST, e.to_string(), None),
+            RequestError::JSONPathError(_) | RequestError::InvalidUpload(_) => {
+                (StatusCode::BAD_REQUEST, e.to_string(), None)
+            }
+            RequestError::UnsupportedContentType(_) => {
+                (StatusCode::UNSUPPORTED_MEDIA_TYPE, e.to_string(), None)
+            }
             RequestError::ValidationError(val_errs) => {
                 let errors: Vec<FieldError> = val_errs
                     .errors()
@@ -278,15 +638,68 @@ pub async fn handle_rejection(err: Rejection) -> std::result::Result<impl Reply,
                 )
             }
         }
+    } else if let Some(Error::Sanitize(e)) = err.find::<Error>() {
+        eprintln!("sanitize error: {:?}", e.to_string());
+        (
+            StatusCode::BAD_REQUEST,
+            e.to_string(),
+            Some(vec![FieldError {
+                field: e.field().to_string(),
+                field_errors: vec![e.to_string()],
+            }]),
+        )
+    } else if let Some(Error::Password(e)) = err.find::<Error>() {
+        eprintln!("password error: {:?}", e.to_string());
+        (
+            StatusCode::BAD_REQUEST,
+            e.to_string(),
+            Some(vec![FieldError {
+                field: "password".to_string(),
+                field_errors: vec![e.to_string()],
+            }]),
+        )
     } else if let Some(Error::Postgres(e)) = err.find::<Error>() {
-        eprintln!("postgres error: {:?}", e.to_string());
+        if let Some(conflict) = unique_violation(e) {
+            eprintln!("unique violation error: {:?}", e.to_string());
+            (
+                StatusCode::CONFLICT,
+                conflict.to_string(),
+                Some(vec![FieldError {
+                    field: conflict.field().to_string(),
+                    field_errors: vec![conflict.to_string()],
+                }]),
+            )
+        } else {
+            eprintln!("postgres error: {:?}", e.to_string());
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error".to_string(),
+                None,
+            )
+        }
+    } else if let Some(Error::Grpc(e)) = err.find::<Error>() {
+        eprintln!("grpc error: {:?}", e.to_string());
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             "Internal Server Error".to_string(),
             None,
         )
-    } else if let Some(Error::Grpc(e)) = err.find::<Error>() {
-        eprintln!("grpc error: {:?}", e.to_string());
+    } else if let Some(Error::Chain(e)) = err.find::<Error>() {
+        eprintln!("chain error: {:?}", e.to_string());
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal Server Error".to_string(),
+            None,
+        )
+    } else if let Some(Error::Storage(e)) = err.find::<Error>() {
+        eprintln!("storage error: {:?}", e.to_string());
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal Server Error".to_string(),
+            None,
+        )
+    } else if let Some(Error::TicketPdf(e)) = err.find::<Error>() {
+        eprintln!("ticket pdf error: {:?}", e.to_string());
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             "Internal Server Error".to_string(),
@@ -306,6 +719,34 @@ pub async fn handle_rejection(err: Rejection) -> std::result::Result<impl Reply,
             "Internal Server Error".to_string(),
             None,
         )
+    } else if let Some(Error::Ipfs(e)) = err.find::<Error>() {
+        eprintln!("ipfs error: {:?}", e.to_string());
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal Server Error".to_string(),
+            None,
+        )
+    } else if let Some(Error::Fx(e)) = err.find::<Error>() {
+        eprintln!("fx error: {:?}", e.to_string());
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal Server Error".to_string(),
+            None,
+        )
+    } else if let Some(Error::NearAmount(e)) = err.find::<Error>() {
+        eprintln!("near amount error: {:?}", e.to_string());
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal Server Error".to_string(),
+            None,
+        )
+    } else if let Some(Error::Job(e)) = err.find::<Error>() {
+        eprintln!("job error: {:?}", e.to_string());
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal Server Error".to_string(),
+            None,
+        )
     } else if let Some(Error::Hash(e)) = err.find::<Error>() {
         eprintln!("hashing error: {:?}", e.to_string());
         (
@@ -322,6 +763,9 @@ pub async fn handle_rejection(err: Rejection) -> std::result::Result<impl Reply,
     } else if let Some(Error::Ticket(e)) = err.find::<Error>() {
         eprintln!("ticket error: {:?}", e.to_string());
         (StatusCode::FORBIDDEN, e.to_string(), None)
+    } else if let Some(Error::PromoCode(e)) = err.find::<Error>() {
+        eprintln!("promo code error: {:?}", e.to_string());
+        (StatusCode::FORBIDDEN, e.to_string(), None)
     } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
         eprintln!("MethodNotAllowed error");
         (
@@ -344,7 +788,22 @@ pub async fn handle_rejection(err: Rejection) -> std::result::Result<impl Reply,
         errors: errors,
     });
 
-    Ok(warp::reply::with_status(json, code))
+    let mut response = warp::reply::with_status(json, code).into_response();
+    // the clients turned away retry once the maintenance is expected to be over
+    if let Some(Error::Maintenance(mode)) = err.find::<Error>() {
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(mode.retry_after_secs));
+    }
+    // as do those who failed to sign in too often, once they may try again
+    if let Some(Error::Auth(AuthError::TooManyFailedSignins(retry_after_secs))) =
+        err.find::<Error>()
+    {
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(*retry_after_secs));
+    }
+    Ok(response)
 }
 
 fn validation_errs_to_str_vec(ve: &ValidationErrors) -> Vec<String> {
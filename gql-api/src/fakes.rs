@@ -0,0 +1,631 @@
+//! In-memory fakes of the external clients, for hermetic integration tests.
+//!
+//! Each fake records what it was asked to do, so that a test can assert on the side effects of a
+//! handler without reaching the NEAR api, pusher, twilio, S3, the geocoder or sellers' webhooks.
+use crate::{
+    error::{GeoError, GrpcError, NotifierError, PublisherError, StorageError, WebhookError},
+    geo::{Coordinates, Geocoder},
+    grpc::{
+        near_api::{
+            AesDecryptDataResponse, AesEncryptDataResponse, CheckAvailableAccountIdResponse,
+            CreateAccountResponse, FundAccountResponse, GenerateImplicitAccountResponse,
+            GetAccountBalanceResponse, GetAccountKeysResponse, GetTxStatusResponse,
+            MintNftsResponse, RefundPaymentResponse, TransferNftResponse, VerifySignatureResponse,
+        },
+        NearClient,
+    },
+    jobs::models::{PusherChannel, PusherEvent},
+    notifier::{Gift, Notification, Notifier, Receipt, Reminder, WaitlistSpot},
+    publisher::Publisher,
+    storage::{ByteStream, Storage},
+    webhooks::{webhook_signature, WebhookSender},
+};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use uuid::Uuid;
+
+/// A NEAR api answering from its settings, the fields not set being the proto defaults
+#[derive(Debug, Clone)]
+pub struct FakeNearClient {
+    /// whether account ids are available
+    pub is_available: bool,
+    /// whether signatures verify
+    pub is_verified: bool,
+    /// whether account fundings go through, the call errors otherwise
+    pub is_funding: bool,
+    /// the available balance of every account
+    pub balance: String,
+    /// the keys of an account, none when missing
+    pub account_keys: HashMap<String, GetAccountKeysResponse>,
+    /// the status of every transaction
+    pub tx_status: String,
+    /// why every failed transaction failed
+    pub tx_error: String,
+    /// the names of the called methods, shared with the clones of the fake
+    pub calls: Arc<Mutex<Vec<String>>>,
+}
+
+impl Default for FakeNearClient {
+    fn default() -> Self {
+        FakeNearClient {
+            is_available: true,
+            is_verified: true,
+            is_funding: true,
+            balance: "0".to_string(),
+            account_keys: HashMap::new(),
+            tx_status: "SUCCESS".to_string(),
+            tx_error: String::new(),
+            calls: Arc::new(Mutex::new(vec![])),
+        }
+    }
+}
+
+impl FakeNearClient {
+    /// the names of the methods called so far
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().expect("poisoned lock").clone()
+    }
+
+    fn record(&self, method: &str) {
+        self.calls
+            .lock()
+            .expect("poisoned lock")
+            .push(method.to_string());
+    }
+}
+
+fn fake_tx_hash() -> String {
+    format!("fake-tx-{}", Uuid::new_v4())
+}
+
+/// The fake "encryption" prefixes the data with its secret, so that only it decrypts it
+pub fn fake_cypher(secret: &str, data: &str) -> String {
+    format!("{}:{}", secret, data)
+}
+
+#[async_trait]
+impl NearClient for FakeNearClient {
+    async fn get_account_balance(
+        &mut self,
+        _account_id: &str,
+    ) -> Result<GetAccountBalanceResponse, GrpcError> {
+        self.record("get_account_balance");
+        Ok(GetAccountBalanceResponse {
+            available: self.balance.clone(),
+            ..Default::default()
+        })
+    }
+
+    async fn fund_account(
+        &mut self,
+        _account_id: &str,
+        _fund_amount: &str,
+    ) -> Result<FundAccountResponse, GrpcError> {
+        self.record("fund_account");
+        if !self.is_funding {
+            return Err(GrpcError::Call(tonic::Status::unavailable(
+                "funding is down",
+            )));
+        }
+        Ok(FundAccountResponse {
+            tx_hash: fake_tx_hash(),
+            ..Default::default()
+        })
+    }
+
+    async fn create_account(
+        &mut self,
+        _account_id: &str,
+        _public_key: &str,
+        _deposit_amount: &str,
+    ) -> Result<CreateAccountResponse, GrpcError> {
+        self.record("create_account");
+        Ok(CreateAccountResponse {
+            tx_hash: fake_tx_hash(),
+            ..Default::default()
+        })
+    }
+
+    async fn mint_nfts(
+        &mut self,
+        _seller_wallet_id: String,
+        _title: String,
+        _ticket_slug: String,
+        _description: String,
+        _media: String,
+        _media_hash: String,
+        _number_of_tickets: i32,
+        _extra: String,
+        _amount_to_send: String,
+    ) -> Result<MintNftsResponse, GrpcError> {
+        self.record("mint_nfts");
+        Ok(MintNftsResponse {
+            tx_hash: fake_tx_hash(),
+            ..Default::default()
+        })
+    }
+
+    async fn transfer_nft(
+        &mut self,
+        _sender_wallet_id: String,
+        _receiver_wallet_id: String,
+        _ticket_slug: String,
+        _price: String,
+    ) -> Result<TransferNftResponse, GrpcError> {
+        self.record("transfer_nft");
+        Ok(TransferNftResponse {
+            tx_hash: fake_tx_hash(),
+            ..Default::default()
+        })
+    }
+
+    async fn check_available_account_id(
+        &mut self,
+        _account_id: &str,
+    ) -> Result<CheckAvailableAccountIdResponse, GrpcError> {
+        self.record("check_available_account_id");
+        Ok(CheckAvailableAccountIdResponse {
+            is_available: self.is_available,
+            ..Default::default()
+        })
+    }
+
+    async fn generate_implicit_account(
+        &mut self,
+    ) -> Result<GenerateImplicitAccountResponse, GrpcError> {
+        self.record("generate_implicit_account");
+        Ok(GenerateImplicitAccountResponse {
+            public_key: format!("ed25519:{}", Uuid::new_v4().to_simple()),
+            secret_key: format!("ed25519:{}", Uuid::new_v4().to_simple()),
+            ..Default::default()
+        })
+    }
+
+    async fn verify_signature(
+        &mut self,
+        _message: &str,
+        _pub_key: &str,
+        _signature: &str,
+    ) -> Result<VerifySignatureResponse, GrpcError> {
+        self.record("verify_signature");
+        Ok(VerifySignatureResponse {
+            is_verified: self.is_verified,
+            ..Default::default()
+        })
+    }
+
+    async fn get_account_keys(
+        &mut self,
+        account_id: &str,
+    ) -> Result<GetAccountKeysResponse, GrpcError> {
+        self.record("get_account_keys");
+        Ok(self
+            .account_keys
+            .get(account_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn aes_encrypt_data(
+        &mut self,
+        secret: &str,
+        data: &str,
+    ) -> Result<AesEncryptDataResponse, GrpcError> {
+        self.record("aes_encrypt_data");
+        Ok(AesEncryptDataResponse {
+            cypher: fake_cypher(secret, data),
+            ..Default::default()
+        })
+    }
+
+    async fn aes_decrypt_data(
+        &mut self,
+        cypher: &str,
+        secret: &str,
+    ) -> Result<AesDecryptDataResponse, GrpcError> {
+        self.record("aes_decrypt_data");
+        let data = cypher
+            .strip_prefix(&fake_cypher(secret, ""))
+            .ok_or_else(|| GrpcError::Call(tonic::Status::invalid_argument("wrong secret")))?;
+        Ok(AesDecryptDataResponse {
+            data: data.to_string(),
+            ..Default::default()
+        })
+    }
+
+    async fn get_tx_status(
+        &mut self,
+        _tx_hash: &str,
+        _account_id: &str,
+    ) -> Result<GetTxStatusResponse, GrpcError> {
+        self.record("get_tx_status");
+        Ok(GetTxStatusResponse {
+            status: self.tx_status.clone(),
+            error: self.tx_error.clone(),
+            ..Default::default()
+        })
+    }
+
+    async fn refund_payment(
+        &mut self,
+        _receiver_wallet_id: &str,
+        _amount: &str,
+        _reference: &str,
+    ) -> Result<RefundPaymentResponse, GrpcError> {
+        self.record("refund_payment");
+        Ok(RefundPaymentResponse {
+            tx_hash: fake_tx_hash(),
+            ..Default::default()
+        })
+    }
+}
+
+/// A published realtime event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishedEvent {
+    pub channel: PusherChannel,
+    pub event: PusherEvent,
+    pub data: String,
+}
+
+/// Records the published events
+#[derive(Debug, Default)]
+pub struct FakePublisher {
+    published: Mutex<Vec<PublishedEvent>>,
+}
+
+impl FakePublisher {
+    pub fn published(&self) -> Vec<PublishedEvent> {
+        self.published.lock().expect("poisoned lock").clone()
+    }
+}
+
+#[async_trait]
+impl Publisher for FakePublisher {
+    async fn publish(
+        &self,
+        channel: PusherChannel,
+        event: PusherEvent,
+        data: &str,
+    ) -> Result<(), PublisherError> {
+        self.published
+            .lock()
+            .expect("poisoned lock")
+            .push(PublishedEvent {
+                channel,
+                event,
+                data: data.to_string(),
+            });
+        Ok(())
+    }
+}
+
+/// Records the sent notifications with their receivers and locales
+#[derive(Debug, Default)]
+pub struct FakeNotifier {
+    sent: Mutex<Vec<(String, Notification, Option<String>)>>,
+}
+
+impl FakeNotifier {
+    pub fn sent(&self) -> Vec<(String, Notification)> {
+        self.sent
+            .lock()
+            .expect("poisoned lock")
+            .iter()
+            .map(|(receiver, notification, _)| (receiver.clone(), notification.clone()))
+            .collect()
+    }
+
+    /// the locales of the sent notifications, in the order they were sent
+    pub fn sent_locales(&self) -> Vec<Option<String>> {
+        self.sent
+            .lock()
+            .expect("poisoned lock")
+            .iter()
+            .map(|(_, _, locale)| locale.clone())
+            .collect()
+    }
+
+    fn record(&self, receiver: &str, locale: Option<&str>, notification: Notification) {
+        self.sent.lock().expect("poisoned lock").push((
+            receiver.to_string(),
+            notification,
+            locale.map(str::to_string),
+        ));
+    }
+}
+
+#[async_trait]
+impl Notifier for FakeNotifier {
+    async fn send_verification(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        code: &str,
+    ) -> Result<(), NotifierError> {
+        self.record(
+            receiver,
+            locale,
+            Notification::Verification {
+                code: code.to_string(),
+            },
+        );
+        Ok(())
+    }
+
+    async fn send_recovery(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        code: &str,
+    ) -> Result<(), NotifierError> {
+        self.record(
+            receiver,
+            locale,
+            Notification::Recovery {
+                code: code.to_string(),
+            },
+        );
+        Ok(())
+    }
+
+    async fn send_receipt(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        receipt: &Receipt,
+    ) -> Result<(), NotifierError> {
+        self.record(receiver, locale, Notification::Receipt(receipt.clone()));
+        Ok(())
+    }
+
+    async fn send_waitlist_spot(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        spot: &WaitlistSpot,
+    ) -> Result<(), NotifierError> {
+        self.record(receiver, locale, Notification::WaitlistSpot(spot.clone()));
+        Ok(())
+    }
+
+    async fn send_gift(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        gift: &Gift,
+    ) -> Result<(), NotifierError> {
+        self.record(receiver, locale, Notification::Gift(gift.clone()));
+        Ok(())
+    }
+
+    async fn send_reminder(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        reminder: &Reminder,
+    ) -> Result<(), NotifierError> {
+        self.record(receiver, locale, Notification::Reminder(reminder.clone()));
+        Ok(())
+    }
+}
+
+/// A stored object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredObject {
+    pub content_type: Option<String>,
+    pub content: Vec<u8>,
+}
+
+/// Keeps the objects in memory, its urls point to nowhere
+#[derive(Debug)]
+pub struct FakeStorage {
+    bucket: String,
+    objects: Mutex<HashMap<String, StoredObject>>,
+}
+
+impl Default for FakeStorage {
+    fn default() -> Self {
+        FakeStorage::new("fake-bucket")
+    }
+}
+
+impl FakeStorage {
+    pub fn new(bucket: impl Into<String>) -> Self {
+        FakeStorage {
+            bucket: bucket.into(),
+            objects: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// the object under the key
+    pub fn object(&self, key: &str) -> Option<StoredObject> {
+        self.objects
+            .lock()
+            .expect("poisoned lock")
+            .get(key)
+            .cloned()
+    }
+
+    /// stores an object, e.g. as a client would through a presigned url
+    pub fn insert(&self, key: impl Into<String>, object: StoredObject) {
+        self.objects
+            .lock()
+            .expect("poisoned lock")
+            .insert(key.into(), object);
+    }
+
+    fn presigned_url(&self, method: &str, key: &str, expiry: Duration) -> String {
+        format!(
+            "{}?method={}&expires={}",
+            self.asset_url(key.to_string()),
+            method,
+            expiry.as_secs()
+        )
+    }
+}
+
+#[async_trait]
+impl Storage for FakeStorage {
+    fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    fn asset_url(&self, key: String) -> String {
+        format!("https://{}.storage.invalid/{}", self.bucket, key)
+    }
+
+    async fn upload(&self, content: Vec<u8>) -> Result<String, StorageError> {
+        let key = Uuid::new_v4().to_string();
+        self.insert(
+            key.clone(),
+            StoredObject {
+                content_type: None,
+                content,
+            },
+        );
+        Ok(key)
+    }
+
+    async fn put(
+        &self,
+        key: &str,
+        content_type: &str,
+        content: Vec<u8>,
+    ) -> Result<(), StorageError> {
+        self.insert(
+            key,
+            StoredObject {
+                content_type: Some(content_type.to_string()),
+                content,
+            },
+        );
+        Ok(())
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        content_type: &str,
+        content_length: u64,
+        content: ByteStream,
+    ) -> Result<(), StorageError> {
+        let content = content
+            .try_fold(Vec::new(), |mut stored, chunk| async move {
+                stored.extend_from_slice(&chunk);
+                Ok(stored)
+            })
+            .await?;
+        // a wrong length fails the put, as it does on S3
+        if content.len() as u64 != content_length {
+            return Err(StorageError::Request(format!(
+                "{} bytes streamed, {} announced",
+                content.len(),
+                content_length
+            )));
+        }
+        self.put(key, content_type, content).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        self.object(key)
+            .map(|object| object.content)
+            .ok_or_else(|| StorageError::Request(format!("no object under {}", key)))
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        Ok(self.object(key).is_some())
+    }
+
+    async fn presigned_put_url(
+        &self,
+        key: &str,
+        _content_type: &str,
+        expiry: Duration,
+    ) -> Result<String, StorageError> {
+        Ok(self.presigned_url("PUT", key, expiry))
+    }
+
+    async fn presigned_get_url(&self, key: &str, expiry: Duration) -> Result<String, StorageError> {
+        Ok(self.presigned_url("GET", key, expiry))
+    }
+}
+
+/// Knows the coordinates of the places it is given, records the addresses it was asked for
+#[derive(Debug, Default)]
+pub struct FakeGeocoder {
+    places: HashMap<String, Coordinates>,
+    lookups: Mutex<Vec<String>>,
+}
+
+impl FakeGeocoder {
+    /// knows the coordinates of the address
+    pub fn place(mut self, address: impl Into<String>, coordinates: Coordinates) -> Self {
+        self.places.insert(address.into(), coordinates);
+        self
+    }
+
+    /// the addresses looked up so far, in order
+    pub fn lookups(&self) -> Vec<String> {
+        self.lookups.lock().expect("poisoned lock").clone()
+    }
+}
+
+#[async_trait]
+impl Geocoder for FakeGeocoder {
+    async fn geocode(&self, address: &str) -> Result<Option<Coordinates>, GeoError> {
+        self.lookups
+            .lock()
+            .expect("poisoned lock")
+            .push(address.to_string());
+        Ok(self.places.get(address).copied())
+    }
+}
+
+/// A webhook delivery, with the signature the seller's endpoint would have received
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveredWebhook {
+    pub url: String,
+    pub event: String,
+    pub body: String,
+    pub signature: String,
+}
+
+/// Records the delivered webhooks
+#[derive(Debug, Default)]
+pub struct FakeWebhookSender {
+    delivered: Mutex<Vec<DeliveredWebhook>>,
+}
+
+impl FakeWebhookSender {
+    pub fn delivered(&self) -> Vec<DeliveredWebhook> {
+        self.delivered.lock().expect("poisoned lock").clone()
+    }
+}
+
+#[async_trait]
+impl WebhookSender for FakeWebhookSender {
+    async fn deliver(
+        &self,
+        url: &str,
+        secret: &str,
+        event: &str,
+        body: &str,
+    ) -> Result<(), WebhookError> {
+        self.delivered
+            .lock()
+            .expect("poisoned lock")
+            .push(DeliveredWebhook {
+                url: url.to_string(),
+                event: event.to_string(),
+                body: body.to_string(),
+                signature: webhook_signature(secret, body),
+            });
+        Ok(())
+    }
+}
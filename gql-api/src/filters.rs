@@ -1,58 +1,125 @@
 use crate::{
-    auth::{authorize, Role},
+    auth::{authorize, authorize_impersonable, is_admin},
+    config::{CorsConfig, ServerEnv},
+    db::sql::db_get_user_by_id,
+    error::{AuthError, Error, RequestError},
     gql::schema::Context as ResourcesContext,
+    metrics::observe_http_request,
+    policy::{policy, Operation},
 };
 use reqwest::{
     header::{HeaderMap, HeaderValue},
     Method,
 };
-use std::{convert::Infallible, sync::Arc};
-use warp::{filters::cors::Builder, header::headers_cloned};
-use warp::{Filter, Rejection};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
+use warp::{
+    filters::cors::Builder,
+    header::headers_cloned,
+    log::{Info, Log},
+    reply::with::WithHeaders,
+};
+use warp::{hyper::body::Buf, reject, Filter, Rejection};
+
+pub fn with_metrics() -> Log<impl Fn(Info<'_>) + Copy> {
+    warp::log::custom(observe_http_request)
+}
 
-pub fn with_cors() -> Builder {
+pub fn with_cors(config: &CorsConfig) -> Builder {
     let cors = warp::cors()
-        .allow_any_origin()
-        .allow_headers(vec![
-            "Sec-Fetch-Mode",
-            "Sec-Fetch-Dest",
-            "Sec-Fetch-Site",
-            "Mode",
-            "Credentials",
-            reqwest::header::ACCEPT.as_str(),
-            reqwest::header::ACCEPT_CHARSET.as_str(),
-            reqwest::header::ACCEPT_ENCODING.as_str(),
-            reqwest::header::ACCEPT_LANGUAGE.as_str(),
-            reqwest::header::ACCEPT_RANGES.as_str(),
-            reqwest::header::USER_AGENT.as_str(),
-            reqwest::header::REFERER.as_str(),
-            reqwest::header::REFERRER_POLICY.as_str(),
-            reqwest::header::ORIGIN.as_str(),
-            reqwest::header::ALLOW.as_str(),
-            reqwest::header::COOKIE.as_str(),
-            reqwest::header::HOST.as_str(),
-            reqwest::header::ACCESS_CONTROL_REQUEST_METHOD.as_str(),
-            reqwest::header::ACCESS_CONTROL_REQUEST_HEADERS.as_str(),
-            reqwest::header::ACCESS_CONTROL_EXPOSE_HEADERS.as_str(),
-            reqwest::header::ACCESS_CONTROL_MAX_AGE.as_str(),
-            reqwest::header::ACCESS_CONTROL_ALLOW_METHODS.as_str(),
-            reqwest::header::ACCESS_CONTROL_ALLOW_CREDENTIALS.as_str(),
-            reqwest::header::ACCESS_CONTROL_ALLOW_ORIGIN.as_str(),
-            reqwest::header::ACCESS_CONTROL_ALLOW_HEADERS.as_str(),
-            reqwest::header::CONTENT_TYPE.as_str(),
-            reqwest::header::AUTHORIZATION.as_str(),
-            reqwest::header::UPGRADE.as_str(),
-            reqwest::header::UPGRADE_INSECURE_REQUESTS.as_str(),
-        ])
+        .allow_headers(config.allowed_headers.iter().map(String::as_str))
         .allow_methods(vec![
             Method::GET,
             Method::POST,
             Method::DELETE,
             Method::OPTIONS,
             Method::PUT,
-        ]);
+        ])
+        .max_age(Duration::from_secs(config.max_age_secs));
+
+    if config.allowed_origins.is_empty() {
+        cors.allow_any_origin()
+    } else {
+        cors.allow_origins(config.allowed_origins.iter().map(String::as_str))
+    }
+}
+
+/// The security headers of every response. Release servers are only reachable over tls, so they
+/// also tell browsers to never downgrade to plain http
+pub fn security_headers(server_env: ServerEnv) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        reqwest::header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    headers.insert(
+        reqwest::header::X_FRAME_OPTIONS,
+        HeaderValue::from_static("DENY"),
+    );
+    headers.insert(
+        reqwest::header::REFERRER_POLICY,
+        HeaderValue::from_static("no-referrer"),
+    );
+    if server_env == ServerEnv::Release {
+        headers.insert(
+            reqwest::header::STRICT_TRANSPORT_SECURITY,
+            HeaderValue::from_static("max-age=31536000; includeSubDomains"),
+        );
+    }
+    headers
+}
 
-    cors
+pub fn with_security_headers(server_env: ServerEnv) -> WithHeaders {
+    warp::reply::with::headers(security_headers(server_env))
+}
+
+/// Rejects requests whose content type is not json
+pub fn with_json_content_type() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("content-type")
+        .and_then(|content_type: Option<String>| async move {
+            let content_type = content_type.unwrap_or_default();
+            let is_json = content_type
+                .split(';')
+                .next()
+                .map(|mime| mime.trim().eq_ignore_ascii_case("application/json"))
+                .unwrap_or(false);
+            if is_json {
+                Ok(())
+            } else {
+                Err(reject::custom(Error::Request(
+                    RequestError::UnsupportedContentType(content_type),
+                )))
+            }
+        })
+        .untuple_one()
+}
+
+/// The json body of the request, rejected when larger than `limit` bytes
+pub fn with_json_body(limit: u64) -> impl Filter<Extract = (impl Buf,), Error = Rejection> + Clone {
+    with_json_content_type()
+        .and(warp::body::content_length_limit(limit))
+        .and(warp::body::aggregate())
+}
+
+/// The address of the client, the first of `X-Forwarded-For` when trusted, the peer's otherwise
+pub fn with_client_ip(
+    trust_forwarded_for: bool,
+) -> impl Filter<Extract = (String,), Error = Infallible> + Clone {
+    warp::header::optional::<String>("x-forwarded-for")
+        .or(warp::any().map(|| None))
+        .unify()
+        .and(warp::addr::remote())
+        .map(
+            move |forwarded_for: Option<String>, remote: Option<SocketAddr>| {
+                forwarded_for
+                    .filter(|_| trust_forwarded_for)
+                    .and_then(|forwarded_for| {
+                        let client = forwarded_for.split(',').next()?.trim().to_string();
+                        (!client.is_empty()).then(|| client)
+                    })
+                    .or_else(|| remote.map(|remote| remote.ip().to_string()))
+                    .unwrap_or_else(|| "unknown".to_string())
+            },
+        )
 }
 
 pub fn with_resources_context(
@@ -61,10 +128,100 @@ pub fn with_resources_context(
     warp::any().map(move || Arc::clone(&resources_ctx))
 }
 
+/// Authorizes the bearer jwt against the roles of the operation's policy, and the user's current
+/// status in the database against its statuses
 pub fn with_auth(
-    roles: Vec<Role>,
+    operation: Operation,
+    resources_ctx: Arc<ResourcesContext>,
+) -> impl Filter<Extract = (uuid::Uuid,), Error = Rejection> + Clone {
+    with_role_auth(operation)
+        .and(with_resources_context(resources_ctx))
+        .and_then(move |user_id, resources_ctx| authorize_status(operation, user_id, resources_ctx))
+}
+
+/// Like [`with_auth`], also accepting impersonation jwts and extracting their impersonation id,
+/// along with when the jwt was issued
+pub fn with_impersonable_auth(
+    operation: Operation,
+    resources_ctx: Arc<ResourcesContext>,
+) -> impl Filter<Extract = (uuid::Uuid, Option<uuid::Uuid>, i64), Error = Rejection> + Clone {
+    let roles = policy(operation).roles.to_vec();
+    headers_cloned()
+        .map(move |headers: HeaderMap<HeaderValue>| (roles.clone(), headers))
+        .and_then(authorize_impersonable)
+        .untuple_one()
+        .and(with_resources_context(resources_ctx))
+        .and_then(
+            move |user_id,
+                  impersonation_id: Option<uuid::Uuid>,
+                  issued_at: i64,
+                  resources_ctx| async move {
+                authorize_status(operation, user_id, resources_ctx)
+                    .await
+                    .map(|user_id| (user_id, impersonation_id, issued_at))
+            },
+        )
+        .untuple_one()
+}
+
+/// Authorizes the bearer jwt against the roles of the operation's policy only, where the
+/// user's status does not matter, e.g. the schemas
+pub fn with_role_auth(
+    operation: Operation,
 ) -> impl Filter<Extract = (uuid::Uuid,), Error = Rejection> + Clone {
+    let roles = policy(operation).roles.to_vec();
     headers_cloned()
         .map(move |headers: HeaderMap<HeaderValue>| (roles.clone(), headers))
         .and_then(authorize)
 }
+
+/// Checks the user's status allows the operation, without a lookup when any status does
+async fn authorize_status(
+    operation: Operation,
+    user_id: uuid::Uuid,
+    resources_ctx: Arc<ResourcesContext>,
+) -> Result<uuid::Uuid, Rejection> {
+    let policy = policy(operation);
+    if !policy.requires_status() {
+        return Ok(user_id);
+    }
+
+    let db_user = db_get_user_by_id(&resources_ctx.db_client, &user_id)
+        .await
+        .map_err(|_| reject::custom(Error::Auth(AuthError::NoPermissionError)))?;
+    if !policy.statuses.contains(&db_user.user_status) {
+        return Err(reject::custom(Error::Auth(AuthError::UnverifiedUser)));
+    }
+    Ok(user_id)
+}
+
+/// Rejects as unavailable while in maintenance mode, unless the request carries an admin's jwt
+pub fn with_maintenance(
+    resources_ctx: Arc<ResourcesContext>,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    headers_cloned()
+        .and_then(move |headers: HeaderMap<HeaderValue>| {
+            let mode = resources_ctx.maintenance.mode();
+            async move {
+                if !mode.enabled || is_admin(&headers) {
+                    Ok(())
+                } else {
+                    Err(reject::custom(Error::Maintenance(mode)))
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Rejects as not found unless the route is enabled
+pub fn with_enabled(enabled: bool) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::any()
+        .and_then(move || async move {
+            if enabled {
+                Ok(())
+            } else {
+                Err(warp::reject::not_found())
+            }
+        })
+        .untuple_one()
+}
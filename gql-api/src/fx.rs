@@ -0,0 +1,160 @@
+use crate::{error::FxError, near::NearAmount};
+use juniper::GraphQLEnum;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+/// Id of NEAR on coingecko compatible price apis
+const NEAR_PRICE_ID: &str = "near";
+
+/// Decimals shown for fiat display prices
+const FIAT_DECIMALS: usize = 2;
+
+lazy_static! {
+    /// NEAR exchange rates, shared by every request and refreshed by the fx job
+    static ref RATES: RwLock<HashMap<Currency, CachedRate>> = RwLock::new(HashMap::new());
+}
+
+/// Ticket pricing currency
+#[repr(i16)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, GraphQLEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Currency {
+    #[graphql(name = "NEAR")]
+    Near = 0,
+    #[graphql(name = "USD")]
+    Usd = 1,
+    #[graphql(name = "EUR")]
+    Eur = 2,
+}
+
+impl Currency {
+    pub const ALL: [Currency; 3] = [Currency::Near, Currency::Usd, Currency::Eur];
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Currency::Near
+    }
+}
+
+impl From<Currency> for i16 {
+    fn from(currency: Currency) -> i16 {
+        currency as i16
+    }
+}
+
+impl TryFrom<i16> for Currency {
+    type Error = FxError;
+
+    fn try_from(n: i16) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(Currency::Near),
+            1 => Ok(Currency::Usd),
+            2 => Ok(Currency::Eur),
+            _ => Err(FxError::UnknownCurrency(n.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Currency::Near => write!(f, "near"),
+            Currency::Usd => write!(f, "usd"),
+            Currency::Eur => write!(f, "eur"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct CachedRate {
+    rate: f64,
+    /// fixed rates never expire
+    expires_at: Option<Instant>,
+}
+
+/// Replaces the cached rates (price of 1 NEAR in each currency), valid for `ttl` when given
+pub fn update_rates(rates: &HashMap<Currency, f64>, ttl: Option<Duration>) {
+    let expires_at = ttl.map(|ttl| Instant::now() + ttl);
+    let mut cache = RATES.write().unwrap_or_else(|e| e.into_inner());
+    for (currency, rate) in rates {
+        cache.insert(
+            *currency,
+            CachedRate {
+                rate: *rate,
+                expires_at,
+            },
+        );
+    }
+}
+
+/// The price of 1 NEAR in the currency, if a fresh rate is known
+pub fn rate(currency: Currency) -> Option<f64> {
+    if currency == Currency::Near {
+        return Some(1.0);
+    }
+    let cache = RATES.read().unwrap_or_else(|e| e.into_inner());
+    cache
+        .get(&currency)
+        .filter(|cached| {
+            cached
+                .expires_at
+                .map(|expires_at| expires_at > Instant::now())
+                .unwrap_or(true)
+        })
+        .map(|cached| cached.rate)
+}
+
+/// Formats the amount in the currency for display, e.g. "2.5" NEAR or "7.85" USD
+pub fn convert(amount: NearAmount, currency: Currency) -> Option<String> {
+    match currency {
+        Currency::Near => Some(amount.to_near_string()),
+        _ => {
+            rate(currency).map(|rate| format!("{:.*}", FIAT_DECIMALS, amount.as_near_f64() * rate))
+        }
+    }
+}
+
+/// Fetches the NEAR rates from a coingecko compatible `simple/price` endpoint
+pub async fn fetch_rates(
+    http_client: &reqwest::Client,
+    url: &str,
+) -> Result<HashMap<Currency, f64>, FxError> {
+    let response = http_client
+        .get(url)
+        .query(&[
+            ("ids", NEAR_PRICE_ID.to_string()),
+            ("vs_currencies", "usd,eur".to_string()),
+        ])
+        .send()
+        .await
+        .map_err(|e| FxError::Request(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(FxError::Response(response.status().to_string()));
+    }
+
+    // e.g. {"near":{"usd":3.14,"eur":2.89}}
+    let prices: HashMap<String, HashMap<String, f64>> = response
+        .json()
+        .await
+        .map_err(|e| FxError::Response(e.to_string()))?;
+    let near_prices = prices
+        .get(NEAR_PRICE_ID)
+        .ok_or_else(|| FxError::Response(format!("missing {} prices", NEAR_PRICE_ID)))?;
+
+    Ok(Currency::ALL
+        .iter()
+        .filter_map(|currency| {
+            near_prices
+                .get(&currency.to_string())
+                .filter(|rate| rate.is_finite() && **rate > 0.0)
+                .map(|rate| (*currency, *rate))
+        })
+        .collect())
+}
@@ -0,0 +1,152 @@
+//! Coordinates of event venues, to list the events near a place.
+//!
+//! The venue location of an event is free text. When it changes, the configured [`Geocoder`]
+//! looks up its coordinates. The [`NominatimGeocoder`] asks a nominatim compatible search api,
+//! the [`NoGeocoder`] finds nothing so that local development and tests do not hit external
+//! apis. Distances are great-circle distances, see [`distance_km`].
+use crate::{config::GeocoderConfig, error::GeoError};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+/// Mean radius of the earth, in km
+pub const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// A point on earth, in degrees
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Coordinates {
+    /// The coordinates, none when they are not on earth
+    pub fn new(latitude: f64, longitude: f64) -> Option<Self> {
+        let on_earth = latitude.is_finite()
+            && longitude.is_finite()
+            && (-90.0..=90.0).contains(&latitude)
+            && (-180.0..=180.0).contains(&longitude);
+        on_earth.then(|| Coordinates {
+            latitude,
+            longitude,
+        })
+    }
+
+    /// The coordinates stored on a row, none unless both are set
+    pub fn from_columns(latitude: Option<f64>, longitude: Option<f64>) -> Option<Self> {
+        latitude
+            .zip(longitude)
+            .and_then(|(latitude, longitude)| Coordinates::new(latitude, longitude))
+    }
+}
+
+/// The great-circle distance between two points (haversine formula), in km. The nearby events
+/// query computes the same in SQL
+pub fn distance_km(from: &Coordinates, to: &Coordinates) -> f64 {
+    let d_latitude = (to.latitude - from.latitude).to_radians();
+    let d_longitude = (to.longitude - from.longitude).to_radians();
+    let a = (d_latitude / 2.0).sin().powi(2)
+        + from.latitude.to_radians().cos()
+            * to.latitude.to_radians().cos()
+            * (d_longitude / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+/// Looks up the coordinates of addresses
+#[async_trait]
+pub trait Geocoder: Send + Sync {
+    /// the coordinates of the address, none when it is not found
+    async fn geocode(&self, address: &str) -> Result<Option<Coordinates>, GeoError>;
+}
+
+/// The coordinates of a venue location, none when it is empty or not found.
+///
+/// Failures are logged and swallowed, an event is saved without coordinates rather than not
+/// at all.
+pub async fn geocode_venue(
+    geocoder: &dyn Geocoder,
+    venue_location: Option<&str>,
+) -> Option<Coordinates> {
+    let venue_location = venue_location
+        .map(str::trim)
+        .filter(|venue_location| !venue_location.is_empty())?;
+    match geocoder.geocode(venue_location).await {
+        Ok(coordinates) => coordinates,
+        Err(e) => {
+            log::warn!("Failed to geocode {:?}: {}", venue_location, e);
+            None
+        }
+    }
+}
+
+/// A place found by a nominatim search, its coordinates as strings
+#[derive(Debug, Deserialize)]
+struct NominatimPlace {
+    lat: String,
+    lon: String,
+}
+
+/// Geocodes through a nominatim compatible `search` endpoint
+pub struct NominatimGeocoder {
+    http_client: reqwest::Client,
+    url: String,
+}
+
+impl NominatimGeocoder {
+    pub fn new(config: &GeocoderConfig) -> Result<Self, GeoError> {
+        // nominatim rejects requests without an identifying user agent
+        let http_client = reqwest::Client::builder()
+            .user_agent(config.user_agent.clone())
+            .build()
+            .map_err(|e| GeoError::Request(e.to_string()))?;
+        Ok(NominatimGeocoder {
+            http_client,
+            url: config.url.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl Geocoder for NominatimGeocoder {
+    async fn geocode(&self, address: &str) -> Result<Option<Coordinates>, GeoError> {
+        let response = self
+            .http_client
+            .get(&self.url)
+            .query(&[("q", address), ("format", "json"), ("limit", "1")])
+            .send()
+            .await
+            .map_err(|e| GeoError::Request(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(GeoError::Response(response.status().to_string()));
+        }
+
+        // e.g. [{"lat":"42.6977","lon":"23.3219",...}]
+        let places: Vec<NominatimPlace> = response
+            .json()
+            .await
+            .map_err(|e| GeoError::Response(e.to_string()))?;
+        let place = match places.into_iter().next() {
+            Some(place) => place,
+            None => return Ok(None),
+        };
+        let latitude = place
+            .lat
+            .parse::<f64>()
+            .map_err(|e| GeoError::Response(e.to_string()))?;
+        let longitude = place
+            .lon
+            .parse::<f64>()
+            .map_err(|e| GeoError::Response(e.to_string()))?;
+        Ok(Coordinates::new(latitude, longitude))
+    }
+}
+
+/// Finds no address, events only get coordinates through another provider
+#[derive(Debug, Default)]
+pub struct NoGeocoder;
+
+#[async_trait]
+impl Geocoder for NoGeocoder {
+    async fn geocode(&self, _address: &str) -> Result<Option<Coordinates>, GeoError> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,68 @@
+//! Batched graphql requests.
+//!
+//! Clients like Apollo send a json array of requests in a single http request. Every request of
+//! a batch is executed on its own, its errors are its own, and the responses are returned as an
+//! array in the same order.
+use super::{
+    error::{field_error, ErrorCode},
+    persisted::PersistedGraphQLRequest,
+};
+use juniper::{http::GraphQLResponse, DefaultScalarValue, FieldError};
+use serde::Deserialize;
+
+/// A single graphql request or a batch of them
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum GraphQLBatchRequest {
+    Batch(Vec<PersistedGraphQLRequest>),
+    Single(PersistedGraphQLRequest),
+}
+
+impl GraphQLBatchRequest {
+    pub fn is_batch(&self) -> bool {
+        matches!(self, GraphQLBatchRequest::Batch(_))
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            GraphQLBatchRequest::Single(_) => 1,
+            GraphQLBatchRequest::Batch(requests) => requests.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The requests to execute, an error response if the batch is empty or larger than
+    /// `max_batch_size`
+    pub fn into_requests(
+        self,
+        max_batch_size: usize,
+    ) -> Result<Vec<PersistedGraphQLRequest>, GraphQLResponse> {
+        match self {
+            GraphQLBatchRequest::Single(req) => Ok(vec![req]),
+            GraphQLBatchRequest::Batch(requests) if requests.is_empty() => {
+                Err(batch_error("Batch is empty"))
+            }
+            GraphQLBatchRequest::Batch(requests) if requests.len() > max_batch_size => Err(
+                batch_error(&format!("Batch is larger than {} requests", max_batch_size)),
+            ),
+            GraphQLBatchRequest::Batch(requests) => Ok(requests),
+        }
+    }
+}
+
+/// The responses as json, an array for a batch
+pub fn batch_response(mut responses: Vec<serde_json::Value>, is_batch: bool) -> serde_json::Value {
+    if !is_batch && responses.len() == 1 {
+        return responses.remove(0);
+    }
+    serde_json::Value::Array(responses)
+}
+
+fn batch_error(message: &str) -> GraphQLResponse {
+    let error: FieldError<DefaultScalarValue> =
+        field_error(ErrorCode::Validation, Some("batch"), message);
+    GraphQLResponse::error(error)
+}
@@ -0,0 +1,128 @@
+//! Duplication of an event, its tickets and asset references into a new DRAFT event, for
+//! organizers running recurring events.
+use crate::{
+    config::SanitationConfig,
+    db::{
+        models::{AssetFile, DbEvent, DbTicket},
+        sql::sql_timestamp,
+    },
+    gql::{
+        error::GqlError,
+        models::{CloneEventOverrides, EventStatus, NewTicket, UpdateEvent},
+        validations::update_event_mutation_payload,
+    },
+};
+use chrono::NaiveDateTime;
+use slugify::slugify;
+
+/// How many names are tried for a copy without a name override
+pub const MAX_CLONE_NAME_ATTEMPTS: usize = 10;
+
+/// The name of the `attempt`th copy of an event, attempts starting at 1
+pub fn clone_event_name(event_name: &str, attempt: usize) -> String {
+    match attempt {
+        1 => format!("{} (copy)", event_name),
+        n => format!("{} (copy {})", event_name, n),
+    }
+}
+
+/// A DRAFT copy of the event named `event_name` and of its tickets, created by
+/// `created_by_user`. The date overrides are validated like an event update.
+///
+/// The copy keeps the source's images, category and organization, its tickets start without
+/// reservations.
+pub fn clone_event(
+    source: &DbEvent,
+    source_tickets: &[DbTicket],
+    event_name: &str,
+    overrides: CloneEventOverrides,
+    created_by_user: uuid::Uuid,
+) -> Result<(DbEvent, Vec<DbTicket>), GqlError> {
+    let mut db_event = DbEvent {
+        id: uuid::Uuid::new_v4(),
+        event_name: event_name.to_string(),
+        event_slug: slugify!(event_name, separator = "-"),
+        created_at: sql_timestamp(None),
+        modified_at: sql_timestamp(None),
+        event_status: EventStatus::Draft,
+        created_by_user,
+        archived: false,
+        deleted_at: None,
+        series_id: None,
+        review_submitted_at: None,
+        review_rejection_reason: None,
+        ..source.clone()
+    };
+    // only the dates are overridden, there is no text to sanitize
+    update_event_mutation_payload(
+        &SanitationConfig::default(),
+        UpdateEvent {
+            id: db_event.id,
+            event_name: None,
+            start_date: overrides.start_date,
+            end_date: overrides.end_date,
+            entry_time: overrides.entry_time,
+            description: None,
+            is_virtual: None,
+            is_featured: None,
+            venue_name: None,
+            venue_location: None,
+            cover_photo_base64: None,
+            thumbnail_base64: None,
+        },
+        &mut db_event,
+    )?;
+
+    let db_tickets = source_tickets
+        .iter()
+        .filter(|db_ticket| db_ticket.deleted_at.is_none())
+        .map(|db_ticket| {
+            DbTicket::new(
+                NewTicket {
+                    ticket_name: db_ticket.ticket_name.clone(),
+                    description: db_ticket.description.clone(),
+                    price: db_ticket.price,
+                    max_release_price: db_ticket.max_release_price,
+                    quantity_available: db_ticket.quantity_available,
+                    min_purchase_quantity: db_ticket.min_purchase_quantity,
+                    max_purchase_quantity: db_ticket.max_purchase_quantity,
+                    allow_transfers: db_ticket.allow_transfers,
+                    currency: Some(db_ticket.currency),
+                    event_id: db_event.id,
+                },
+                &db_event,
+            )
+        })
+        .collect();
+
+    Ok((db_event, db_tickets))
+}
+
+/// The name of the occurrence of a series starting at `start_date`
+pub fn occurrence_event_name(event_name: &str, start_date: &NaiveDateTime) -> String {
+    format!("{} ({})", event_name, start_date.format("%Y-%m-%d"))
+}
+
+/// The dates of the copy of `source` starting at `start_date`, its end and entry time as far from
+/// its start as the source's
+pub fn occurrence_overrides(source: &DbEvent, start_date: NaiveDateTime) -> CloneEventOverrides {
+    let shift = source
+        .start_date
+        .map(|source_start_date| start_date - source_start_date);
+    let shifted = |date: Option<NaiveDateTime>| date.zip(shift).map(|(date, shift)| date + shift);
+    CloneEventOverrides {
+        event_name: None,
+        start_date: Some(start_date),
+        end_date: shifted(source.end_date),
+        entry_time: shifted(source.entry_time),
+    }
+}
+
+/// A reference to the same stored asset from the cloned event
+pub fn clone_asset_file(file: &AssetFile, event_id: uuid::Uuid) -> AssetFile {
+    AssetFile {
+        id: uuid::Uuid::new_v4(),
+        event_id,
+        ..file.clone()
+    }
+}
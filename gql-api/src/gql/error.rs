@@ -1,9 +1,67 @@
-use crate::error::GrpcError;
+//! GraphQL errors and their extensions.
+//!
+//! Every error carries a machine-readable [`ErrorCode`] in its extensions, the field it is
+//! about (if any) and, added by the handlers, the id of the request. A conflict with a newer
+//! version of an entity also carries the entity's current values.
+use crate::db::models::DbTicket;
+use crate::db::sql::unique_violation;
+use crate::error::{ChainError, GrpcError};
 use displaydoc::Display as DisplayDoc;
-use juniper::{graphql_value, FieldError, GraphQLObject, ScalarValue};
+use juniper::{FieldError, GraphQLObject, Object, ScalarValue, Value};
 use std::fmt::{self, Display};
 use thiserror::Error;
 
+/// Extension key of the error code
+pub const CODE_EXTENSION: &str = "code";
+/// Extension key of the field an error is about
+pub const FIELD_EXTENSION: &str = "field";
+/// Extension key of the request id
+pub const REQUEST_ID_EXTENSION: &str = "requestId";
+/// Extension key of the current values of an entity edited meanwhile
+pub const CURRENT_EXTENSION: &str = "current";
+
+/// The machine-readable kind of an error
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// the request is malformed or its values are invalid
+    Validation,
+    /// the requested entity does not exist
+    NotFound,
+    /// the caller may not perform the operation
+    Forbidden,
+    /// the entity is not in a state that allows the operation
+    Conflict,
+    /// the server failed
+    Internal,
+    /// a service the server depends on failed
+    Upstream,
+    /// the query of a persisted query hash is not known, it has to be sent along
+    PersistedQueryNotFound,
+    /// the persisted query protocol version is not supported
+    PersistedQueryNotSupported,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::Validation => "VALIDATION",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::Forbidden => "FORBIDDEN",
+            ErrorCode::Conflict => "CONFLICT",
+            ErrorCode::Internal => "INTERNAL",
+            ErrorCode::Upstream => "UPSTREAM",
+            ErrorCode::PersistedQueryNotFound => "PERSISTED_QUERY_NOT_FOUND",
+            ErrorCode::PersistedQueryNotSupported => "PERSISTED_QUERY_NOT_SUPPORTED",
+        }
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 #[derive(Debug, GraphQLObject)]
 pub struct ValidationError {
     field: String,
@@ -17,6 +75,14 @@ impl ValidationError {
             message: message.to_string(),
         }
     }
+
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
 }
 
 impl Display for ValidationError {
@@ -29,65 +95,202 @@ impl Display for ValidationError {
 pub enum GqlError {
     /// Unknown event error: `{0}`
     UnknownEventStatus(String),
-    /// Parse UUID error
-    ParseUUID,
+    /// Unknown discount type error: `{0}`
+    UnknownDiscountType(String),
+    /// Unknown payout status error: `{0}`
+    UnknownPayoutStatus(String),
+    /// Unknown member role error: `{0}`
+    UnknownMemberRole(String),
+    /// Unknown mint status error: `{0}`
+    UnknownMintStatus(String),
+    /// Unknown signup status error: `{0}`
+    UnknownSignupStatus(String),
+    /// Unknown notification kind error: `{0}`
+    UnknownNotificationKind(String),
+    /// Unknown document kind error: `{0}`
+    UnknownDocumentKind(String),
+    /// Unknown asset role error: `{0}`
+    UnknownAssetRole(String),
     /// Unexpected Internal error
     UnexpectedInternal,
     /// Validation error: `{0}`
     Validation(ValidationError),
+    /// Not found error: `{0}`
+    NotFound(ValidationError),
+    /// Forbidden error: `{0}`
+    Forbidden(ValidationError),
+    /// Conflict error: `{0}`
+    Conflict(ValidationError),
+    /// Stale ticket error, the ticket was edited meanwhile
+    StaleTicket(Box<DbTicket>),
     /// Database error: `{0}`
     Database(tokio_postgres::Error),
     /// Grpc error: `{0}`
     Grpc(GrpcError),
+    /// Chain error: `{0}`
+    Chain(ChainError),
+    /// Storage error: `{0}`
+    Storage(String),
 }
 
-impl<S: ScalarValue> juniper::IntoFieldError<S> for GqlError {
-    fn into_field_error(self) -> FieldError<S> {
+impl GqlError {
+    pub fn code(&self) -> ErrorCode {
         match self {
-            GqlError::UnknownEventStatus(status) => FieldError::new(
-                format!("Unknown event status ({status}) error"),
-                graphql_value!({
-                    "type": "PARSE"
-                }),
-            ),
-            GqlError::ParseUUID => FieldError::new(
-                "Parse UUID error",
-                graphql_value!({
-                    "type": "PARSE"
-                }),
-            ),
-            GqlError::Validation(error) => FieldError::new(
-                error.to_string(),
-                graphql_value!({
-                    "type": "VALIDATION"
-                }),
-            ),
-            GqlError::Database(error) => {
-                let msg = error.to_string();
-                FieldError::new(
-                    "Database error",
-                    graphql_value!({
-                        "type": "DATABASE",
-                        "error": msg
-                    }),
-                )
+            GqlError::UnknownEventStatus(_)
+            | GqlError::UnknownDiscountType(_)
+            | GqlError::UnknownPayoutStatus(_)
+            | GqlError::UnknownMemberRole(_)
+            | GqlError::UnknownMintStatus(_)
+            | GqlError::UnknownSignupStatus(_)
+            | GqlError::UnknownNotificationKind(_)
+            | GqlError::UnknownDocumentKind(_)
+            | GqlError::UnknownAssetRole(_)
+            | GqlError::Validation(_) => ErrorCode::Validation,
+            GqlError::NotFound(_) => ErrorCode::NotFound,
+            GqlError::Forbidden(_) => ErrorCode::Forbidden,
+            GqlError::Conflict(_) | GqlError::StaleTicket(_) => ErrorCode::Conflict,
+            GqlError::Database(e) if unique_violation(e).is_some() => ErrorCode::Conflict,
+            GqlError::UnexpectedInternal | GqlError::Database(_) => ErrorCode::Internal,
+            GqlError::Grpc(_) | GqlError::Chain(_) | GqlError::Storage(_) => ErrorCode::Upstream,
+        }
+    }
+
+    /// the field the error is about, if any
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            GqlError::Validation(error)
+            | GqlError::NotFound(error)
+            | GqlError::Forbidden(error)
+            | GqlError::Conflict(error) => Some(error.field()),
+            GqlError::StaleTicket(_) => Some("version"),
+            GqlError::Database(e) => unique_violation(e).map(|conflict| conflict.field()),
+            _ => None,
+        }
+    }
+
+    /// the message shown to clients, internal details are only logged
+    pub fn message(&self) -> String {
+        match self {
+            GqlError::UnknownEventStatus(status) => format!("Unknown event status ({status})"),
+            GqlError::UnknownDiscountType(discount_type) => {
+                format!("Unknown discount type ({discount_type})")
+            }
+            GqlError::UnknownPayoutStatus(payout_status) => {
+                format!("Unknown payout status ({payout_status})")
+            }
+            GqlError::UnknownMemberRole(member_role) => {
+                format!("Unknown member role ({member_role})")
+            }
+            GqlError::UnknownMintStatus(mint_status) => {
+                format!("Unknown mint status ({mint_status})")
+            }
+            GqlError::UnknownSignupStatus(signup_status) => {
+                format!("Unknown signup status ({signup_status})")
             }
-            GqlError::UnexpectedInternal => FieldError::new(
-                "Unexpected Error",
-                graphql_value!({
-                    "type": "INTERNAL"
-                }),
+            GqlError::UnknownNotificationKind(kind) => {
+                format!("Unknown notification kind ({kind})")
+            }
+            GqlError::UnknownDocumentKind(document_kind) => {
+                format!("Unknown document kind ({document_kind})")
+            }
+            GqlError::UnknownAssetRole(asset_role) => {
+                format!("Unknown asset role ({asset_role})")
+            }
+            GqlError::Validation(error)
+            | GqlError::NotFound(error)
+            | GqlError::Forbidden(error)
+            | GqlError::Conflict(error) => error.message().to_string(),
+            GqlError::StaleTicket(current) => format!(
+                "Ticket {} was edited meanwhile, it is at version {}",
+                current.id, current.version
             ),
-            GqlError::Grpc(error) => {
-                let msg = error.to_string();
-                FieldError::new(
-                    "Grpc Error",
-                    graphql_value!({
-                        "type": "INTERNAL",
-                        "error": msg
-                    }),
-                )
+            GqlError::Database(e) => match unique_violation(e) {
+                Some(conflict) => conflict.to_string(),
+                None => "Unexpected error".to_string(),
+            },
+            GqlError::UnexpectedInternal => "Unexpected error".to_string(),
+            GqlError::Grpc(_) => "Near api error".to_string(),
+            GqlError::Chain(_) => "Blockchain error".to_string(),
+            GqlError::Storage(_) => "Storage error".to_string(),
+        }
+    }
+}
+
+impl<S: ScalarValue> juniper::IntoFieldError<S> for GqlError {
+    fn into_field_error(self) -> FieldError<S> {
+        match &self {
+            // a unique violation is the client's conflict, not a failure
+            GqlError::Database(e) if unique_violation(e).is_some() => (),
+            GqlError::Database(_)
+            | GqlError::Grpc(_)
+            | GqlError::Chain(_)
+            | GqlError::Storage(_) => {
+                log::error!("GraphQL {} error: {}", self.code(), self)
             }
+            _ => (),
         }
+        let error = field_error(self.code(), self.field(), self.message());
+        match &self {
+            GqlError::StaleTicket(current) => {
+                with_extension(error, CURRENT_EXTENSION, current_ticket_values(current))
+            }
+            _ => error,
+        }
+    }
+}
+
+/// The values of a ticket a client needs to retry an edit
+fn current_ticket_values<S: ScalarValue>(db_ticket: &DbTicket) -> Value<S> {
+    let optional = |value: Option<i32>| value.map_or_else(Value::null, Value::scalar);
+    let mut values = Object::with_capacity(7);
+    values.add_field("id", Value::scalar(db_ticket.id.to_string()));
+    values.add_field("version", Value::scalar(db_ticket.version));
+    values.add_field("quantityAvailable", optional(db_ticket.quantity_available));
+    values.add_field(
+        "quantityReserved",
+        Value::scalar(db_ticket.quantity_reserved),
+    );
+    values.add_field(
+        "minPurchaseQuantity",
+        optional(db_ticket.min_purchase_quantity),
+    );
+    values.add_field(
+        "maxPurchaseQuantity",
+        optional(db_ticket.max_purchase_quantity),
+    );
+    values.add_field(
+        "price",
+        db_ticket
+            .price
+            .map_or_else(Value::null, |price| Value::scalar(price.to_string())),
+    );
+    Value::Object(values)
+}
+
+/// The field error with one more extension
+fn with_extension<S: ScalarValue>(
+    error: FieldError<S>,
+    key: &str,
+    value: Value<S>,
+) -> FieldError<S> {
+    let mut extensions = match error.extensions() {
+        Value::Object(extensions) => extensions.clone(),
+        _ => Object::with_capacity(1),
+    };
+    extensions.add_field(key, value);
+    FieldError::new(error.message(), Value::Object(extensions))
+}
+
+/// A field error with the code and field in its extensions
+pub fn field_error<S: ScalarValue>(
+    code: ErrorCode,
+    field: Option<&str>,
+    message: impl Display,
+) -> FieldError<S> {
+    let mut extensions = Object::with_capacity(2);
+    extensions.add_field(CODE_EXTENSION, Value::scalar(code.as_str().to_string()));
+    if let Some(field) = field {
+        extensions.add_field(FIELD_EXTENSION, Value::scalar(field.to_string()));
     }
+    FieldError::new(message, Value::Object(extensions))
 }
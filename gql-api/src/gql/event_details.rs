@@ -0,0 +1,258 @@
+//! A single event as seen by a signed in user.
+//!
+//! Unlike [`Event`](super::models::Event), which is built eagerly with its tickets and gallery,
+//! the nested fields of [`EventDetails`] are only resolved when selected: a query asking for the
+//! event's name does not load its reservations. Each nested field checks the caller may see it
+//! on its own, so a caller denied the reservations still gets the fields they may see.
+use super::{
+    error::{GqlError, ValidationError},
+    guard::guard_event,
+    models::{EventAsset, EventCollaborator, EventReservation, EventStatus, MintJob, Ticket},
+    quiries::EVENT_RESERVATIONS_PAGE_SIZE,
+    schema::Context as ResourcesContext,
+};
+use crate::{
+    db::{
+        models::DbEvent,
+        sql::{
+            db_get_event_by_id, db_get_event_collaborators, db_get_event_reservations,
+            db_get_mint_jobs_by_event_id, db_get_price_tiers_by_ticket_ids, sql_timestamp,
+        },
+    },
+    policy::EventAccess,
+    storage::readable_url,
+};
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+/// The event and the user it is resolved for
+pub struct EventDetails {
+    db_event: DbEvent,
+    user_id: Uuid,
+}
+
+impl EventDetails {
+    /// The event the user may see: published ones, those not approved yet only to those who may
+    /// access them
+    pub async fn load(
+        ctx: &ResourcesContext,
+        user_id: Uuid,
+        event_id: &Uuid,
+    ) -> Result<Self, GqlError> {
+        let db_event = db_get_event_by_id(&ctx.db_client, event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+        let unpublished = matches!(
+            db_event.event_status,
+            EventStatus::Draft | EventStatus::Minting | EventStatus::PendingReview
+        );
+        if unpublished {
+            // unpublished events are as good as unknown to everyone else
+            guard_event(ctx, &user_id, &db_event, EventAccess::CheckIn)
+                .await
+                .map_err(|_| {
+                    GqlError::NotFound(ValidationError::new(
+                        "event_id",
+                        "Event with submitted id does not exist",
+                    ))
+                })?;
+        }
+        Ok(EventDetails { db_event, user_id })
+    }
+
+    async fn guard(&self, ctx: &ResourcesContext, access: EventAccess) -> Result<(), GqlError> {
+        guard_event(ctx, &self.user_id, &self.db_event, access).await
+    }
+}
+
+#[juniper::graphql_object(
+    Context = ResourcesContext,
+    description = "Gql type for an event, its nested fields resolved when selected"
+)]
+impl EventDetails {
+    /// the event's id
+    fn id(&self) -> Uuid {
+        self.db_event.id
+    }
+
+    /// the event's name
+    fn event_name(&self) -> &str {
+        &self.db_event.event_name
+    }
+
+    /// the event's slug
+    fn event_slug(&self) -> &str {
+        &self.db_event.event_slug
+    }
+
+    /// the event's status
+    fn event_status(&self) -> EventStatus {
+        self.db_event.event_status
+    }
+
+    /// the event's description
+    fn description(&self) -> Option<&str> {
+        self.db_event.description.as_deref()
+    }
+
+    /// the event's starting date
+    fn start_date(&self) -> Option<NaiveDateTime> {
+        self.db_event.start_date
+    }
+
+    /// the event's end date
+    fn end_date(&self) -> Option<NaiveDateTime> {
+        self.db_event.end_date
+    }
+
+    /// the event's venue name
+    fn venue_name(&self) -> Option<&str> {
+        self.db_event.venue_name.as_deref()
+    }
+
+    /// the event's venue location
+    fn venue_location(&self) -> Option<&str> {
+        self.db_event.venue_location.as_deref()
+    }
+
+    /// the event's creator id
+    fn created_by_user(&self) -> Uuid {
+        self.db_event.created_by_user
+    }
+
+    /// the username of the event's creator
+    async fn creator_username(&self, ctx: &ResourcesContext) -> Result<Option<String>, GqlError> {
+        let creator = ctx
+            .loaders
+            .user_by_id(&ctx.db_client, self.db_event.created_by_user)
+            .await?;
+        Ok(creator.map(|creator| creator.username))
+    }
+
+    /// the id of the organization managing the event, if any
+    fn organization_id(&self) -> Option<Uuid> {
+        self.db_event.organization_id
+    }
+
+    /// when the event, its tickets, tags or gallery last changed
+    fn modified_at(&self) -> NaiveDateTime {
+        self.db_event.modified_at
+    }
+
+    /// the event's cover photo url
+    async fn cover_photo_url(&self, ctx: &ResourcesContext) -> Result<Option<String>, GqlError> {
+        match self.db_event.cover_photo_url.clone() {
+            Some(url) => readable_url(ctx.storage.as_ref(), &ctx.asset_urls, url)
+                .await
+                .map(Some)
+                .map_err(|e| GqlError::Storage(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// the event's tickets with their price tiers
+    async fn tickets(&self, ctx: &ResourcesContext) -> Result<Vec<Ticket>, GqlError> {
+        let db_tickets = ctx
+            .loaders
+            .tickets_by_event(&ctx.db_client, self.db_event.id)
+            .await?;
+        let ticket_ids = db_tickets
+            .iter()
+            .map(|ticket| ticket.id)
+            .collect::<Vec<_>>();
+        let tiers = db_get_price_tiers_by_ticket_ids(&ctx.db_client, &ticket_ids)
+            .await
+            .map_err(GqlError::Database)?;
+        let now = sql_timestamp(None);
+        let tickets = db_tickets
+            .into_iter()
+            .map(|db_ticket| {
+                let ticket_tiers = tiers
+                    .iter()
+                    .filter(|tier| tier.ticket_id.eq(&db_ticket.id))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                Ticket::from(db_ticket).with_price_tiers(&ticket_tiers, &now)
+            })
+            .collect();
+        Ok(tickets)
+    }
+
+    /// the event's reservations, a page at a time, to those who may check its attendees in
+    async fn reservations(
+        &self,
+        ctx: &ResourcesContext,
+        page: Option<i32>,
+        redeemed: Option<bool>,
+    ) -> Result<Option<Vec<EventReservation>>, GqlError> {
+        self.guard(ctx, EventAccess::CheckIn).await?;
+
+        let offset = (i64::from(page.unwrap_or(1).max(1)) - 1) * EVENT_RESERVATIONS_PAGE_SIZE;
+        let reservations = db_get_event_reservations(
+            &ctx.db_client,
+            &self.db_event.id,
+            redeemed,
+            EVENT_RESERVATIONS_PAGE_SIZE,
+            offset,
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .into_iter()
+        .map(EventReservation::from)
+        .collect();
+        Ok(Some(reservations))
+    }
+
+    /// the mints of the event's tickets, the latest first, to its editors
+    async fn mint_jobs(&self, ctx: &ResourcesContext) -> Result<Option<Vec<MintJob>>, GqlError> {
+        self.guard(ctx, EventAccess::Edit).await?;
+
+        let mint_jobs = db_get_mint_jobs_by_event_id(&ctx.db_client, &self.db_event.id)
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(MintJob::from)
+            .collect();
+        Ok(Some(mint_jobs))
+    }
+
+    /// the event's uploaded assets, attached or not, to its editors
+    async fn assets(&self, ctx: &ResourcesContext) -> Result<Option<Vec<EventAsset>>, GqlError> {
+        self.guard(ctx, EventAccess::Edit).await?;
+
+        let asset_files = ctx
+            .loaders
+            .assets_by_event(&ctx.db_client, self.db_event.id)
+            .await?;
+        let mut assets = Vec::with_capacity(asset_files.len());
+        for asset_file in asset_files {
+            let url = ctx.storage.asset_url(asset_file.s3_absolute_key.clone());
+            let url = readable_url(ctx.storage.as_ref(), &ctx.asset_urls, url)
+                .await
+                .map_err(|e| GqlError::Storage(e.to_string()))?;
+            assets.push(EventAsset::new(asset_file, url));
+        }
+        Ok(Some(assets))
+    }
+
+    /// the sellers co-hosting the event, to its editors
+    async fn collaborators(
+        &self,
+        ctx: &ResourcesContext,
+    ) -> Result<Option<Vec<EventCollaborator>>, GqlError> {
+        self.guard(ctx, EventAccess::Edit).await?;
+
+        let collaborators = db_get_event_collaborators(&ctx.db_client, &self.db_event.id)
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(EventCollaborator::from)
+            .collect();
+        Ok(Some(collaborators))
+    }
+}
@@ -1,7 +1,8 @@
 use crate::gql::{
-    mutations::{PrivateMutationRoot, PublicMutationRoot},
-    quiries::{PrivateQueryRoot, PublicQueryRoot},
-    subscriptions::{PrivateSubscriptionRoot, PublicSubscriptionRoot},
+    mutations::PublicMutationRoot,
+    quiries::PublicQueryRoot,
+    schema::{AuthenticatedSchema, GqlRoot, GqlSubscriptionRoot},
+    subscriptions::PublicSubscriptionRoot,
 };
 use juniper::RootNode;
 use std::{convert::Infallible, sync::Arc};
@@ -16,11 +17,9 @@ pub fn with_public_gql_schema(
     warp::any().map(move || Arc::clone(&gql_schema))
 }
 
-pub fn with_private_gql_schema(
-    gql_schema: Arc<RootNode<'_, PrivateQueryRoot, PrivateMutationRoot, PrivateSubscriptionRoot>>,
-) -> impl warp::Filter<
-    Extract = (Arc<RootNode<'_, PrivateQueryRoot, PrivateMutationRoot, PrivateSubscriptionRoot>>,),
-    Error = Infallible,
-> + Clone {
+/// The schema of an authenticated graphql route: the private one or the one of a role
+pub fn with_authenticated_gql_schema<Q: GqlRoot, M: GqlRoot, S: GqlSubscriptionRoot>(
+    gql_schema: Arc<AuthenticatedSchema<Q, M, S>>,
+) -> impl warp::Filter<Extract = (Arc<AuthenticatedSchema<Q, M, S>>,), Error = Infallible> + Clone {
     warp::any().map(move || Arc::clone(&gql_schema))
 }
@@ -0,0 +1,124 @@
+use super::{
+    error::{GqlError, ValidationError},
+    schema::Context as ResourcesContext,
+};
+use crate::{
+    db::{
+        models::{DbEvent, DbUser},
+        sql::{db_get_event_collaborator, db_get_organization_member, db_get_user_by_id},
+    },
+    policy::{can_access_event, can_collaborate, policy, EventAccess, Operation},
+};
+
+/// Loads the calling user and checks it against the operation's policy.
+///
+/// The private route already authorized the jwt, this also covers the user's current
+/// role and status in the database.
+pub async fn guard(ctx: &ResourcesContext, operation: Operation) -> Result<DbUser, GqlError> {
+    let user_id = {
+        let guard = ctx.user_id.lock().await;
+        let user_id = guard.ok_or(GqlError::UnexpectedInternal)?;
+        drop(guard);
+        user_id
+    };
+
+    let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
+        .await
+        .map_err(|_| {
+            GqlError::NotFound(ValidationError::new(
+                "user_id",
+                "User not found in the database",
+            ))
+        })?;
+
+    // a deleted account's jwt may still be valid
+    if db_user.deleted_at.is_some() {
+        return Err(GqlError::Forbidden(ValidationError::new(
+            "user_id",
+            "User account has been deleted",
+        )));
+    }
+
+    // so are the jwts issued before the password changed, a jwt issued within the same second is
+    // rejected too as its issue time is in seconds
+    if let (Some(issued_at), Some(password_changed_at)) =
+        (ctx.jwt_issued_at, db_user.password_changed_at)
+    {
+        if issued_at <= password_changed_at.timestamp() {
+            return Err(GqlError::Forbidden(ValidationError::new(
+                "jwt",
+                "Jwt was issued before the password changed, sign in again",
+            )));
+        }
+    }
+
+    if !policy(operation).allows_role(&db_user.user_type) {
+        return Err(GqlError::Forbidden(ValidationError::new(
+            "user_type",
+            &format!(
+                "Operation {} is not allowed for {} users",
+                operation, db_user.user_type
+            ),
+        )));
+    }
+
+    if !policy(operation).allows(&db_user.user_type, &db_user.user_status) {
+        return Err(GqlError::Forbidden(ValidationError::new(
+            "user_status",
+            &format!(
+                "Operation {} is not allowed for {} users, verify the phone number first",
+                operation, db_user.user_status
+            ),
+        )));
+    }
+
+    if !policy(operation).allows_seller(&db_user.user_type, db_user.seller_status.as_ref()) {
+        return Err(GqlError::Forbidden(ValidationError::new(
+            "seller_status",
+            "Seller onboarding is not approved yet",
+        )));
+    }
+
+    // a super admin acting as the user does not take over their credentials
+    if policy(operation).owner_only && ctx.impersonated_by.is_some() {
+        return Err(GqlError::Forbidden(ValidationError::new(
+            "impersonation",
+            &format!("Operation {} is not allowed while impersonating", operation),
+        )));
+    }
+
+    Ok(db_user)
+}
+
+/// Checks the user may access the event, as its creator, as a member of the organization
+/// managing it or as its collaborator.
+pub async fn guard_event(
+    ctx: &ResourcesContext,
+    user_id: &uuid::Uuid,
+    db_event: &DbEvent,
+    access: EventAccess,
+) -> Result<(), GqlError> {
+    let member = match db_event.organization_id {
+        Some(organization_id) if !user_id.eq(&db_event.created_by_user) => {
+            db_get_organization_member(&ctx.db_client, &organization_id, user_id)
+                .await
+                .map_err(GqlError::Database)?
+        }
+        _ => None,
+    };
+
+    if can_access_event(user_id, db_event, member.as_ref(), access) {
+        return Ok(());
+    }
+
+    let collaborator = db_get_event_collaborator(&ctx.db_client, &db_event.id, user_id)
+        .await
+        .map_err(GqlError::Database)?;
+    if !can_collaborate(user_id, db_event, collaborator.as_ref(), access) {
+        return Err(GqlError::Forbidden(ValidationError::new(
+            "event_creator",
+            &format!("Calling user is not allowed to {} the event", access),
+        )));
+    }
+    Ok(())
+}
@@ -1,49 +1,270 @@
-use crate::gql::schema::{Context as ResourcesContext, PrivateSchema, PublicSchema};
-use juniper::http::GraphQLRequest;
+use crate::auth::authorize_bearer;
+use crate::config::BusinessConfig;
+use crate::db::sql::db_get_active_impersonation;
+use crate::error::{AuthError, Error};
+use crate::gql::batch::{batch_response, GraphQLBatchRequest};
+use crate::gql::error::{field_error, ErrorCode, REQUEST_ID_EXTENSION};
+use crate::gql::persisted::{resolve_request, PersistedGraphQLRequest};
+use crate::gql::schema::{
+    AuthenticatedSchema, Context as ResourcesContext, GqlRoot, GqlSubscriptionRoot, PrivateSchema,
+    PublicSchema,
+};
+use crate::gql::schema_language::is_introspection_query;
+use crate::i18n::{negotiate_locale, translate};
+use crate::metrics::{observe_gql_batch, observe_gql_operation};
+use crate::policy::{policy, Operation};
+use juniper::{http::GraphQLResponse, Variables};
+use juniper_graphql_ws::ConnectionConfig;
+use juniper_warp::subscriptions::serve_graphql_ws;
 use std::sync::Arc;
 use tokio::time::Instant;
 use uuid::Uuid;
-use warp::Rejection;
+use warp::ws::{WebSocket, Ws};
+use warp::{reject, Rejection};
 
 pub async fn graphql_public(
     schema: Arc<PublicSchema>,
     ctx: Arc<ResourcesContext>,
-    req: GraphQLRequest,
+    batch: GraphQLBatchRequest,
+    accept_language: Option<String>,
 ) -> Result<impl warp::Reply, Rejection> {
+    let is_batch = batch.is_batch();
+    let requests = match batch.into_requests(ctx.graphql.max_batch_size) {
+        Ok(requests) => requests,
+        Err(res) => return Ok(warp::reply::json(&res)),
+    };
+    observe_gql_batch("public", requests.len());
+
+    // one after the other, a failing request does not fail the rest of the batch
+    let mut responses = Vec::with_capacity(requests.len());
+    for req in requests {
+        responses.push(execute_public(&schema, &ctx, req, accept_language.as_deref()).await);
+    }
+    Ok(warp::reply::json(&batch_response(responses, is_batch)))
+}
+
+async fn execute_public(
+    schema: &PublicSchema,
+    ctx: &ResourcesContext,
+    req: PersistedGraphQLRequest,
+    accept_language: Option<&str>,
+) -> serde_json::Value {
+    let req = match resolve_request(&ctx.db_client, req, ctx.graphql.persisted_queries_only).await {
+        Ok(req) => req,
+        Err(e) => return serde_json::to_value(&e.response()).unwrap_or_default(),
+    };
+    if !ctx.graphql.introspection && is_introspection_query(&req.query) {
+        return serde_json::to_value(&introspection_disabled()).unwrap_or_default();
+    }
     let request_uuid = Uuid::new_v4();
     let start = Instant::now();
-    let res = req.execute(&schema, &ctx).await;
+    // the loaders of the request only
+    let res = req.execute(schema, &ctx.for_user(None)).await;
     log::info!(
         "\nUUID: {:?}\ntime: {:?} milliseconds\noperation: {:?}",
         request_uuid.to_string(),
         start.elapsed().as_millis(),
         req.operation_name().clone().unwrap_or_default()
     );
-    let json = warp::reply::json(&res);
-    Ok(json)
+    observe_gql_operation(
+        "public",
+        req.operation_name().unwrap_or_default(),
+        start.elapsed().as_secs_f64(),
+    );
+    let mut json = with_request_id(&res, &request_uuid);
+    translate_errors(&mut json, &ctx.business, accept_language);
+    json
 }
 
-pub async fn graphql_private(
-    schema: Arc<PrivateSchema>,
+/// Executes the requests of a signed in user against the private schema or the one of their
+/// role, `schema_name` labels the metrics
+pub async fn graphql_authenticated<Q: GqlRoot, M: GqlRoot, S: GqlSubscriptionRoot>(
+    schema_name: &'static str,
+    schema: Arc<AuthenticatedSchema<Q, M, S>>,
     ctx: Arc<ResourcesContext>,
-    req: GraphQLRequest,
+    batch: GraphQLBatchRequest,
+    accept_language: Option<String>,
     user_id: uuid::Uuid, // authenticated user id calling the gql point
+    impersonation_id: Option<uuid::Uuid>,
+    issued_at: i64, // when the jwt was issued
 ) -> Result<impl warp::Reply, Rejection> {
-    {
-        let mut lock = ctx.user_id.lock().await;
-        *lock = Some(user_id);
-        drop(lock);
+    // an impersonation jwt stops working as soon as the impersonation is revoked
+    let impersonated_by = match impersonation_id {
+        Some(impersonation_id) => {
+            let db_impersonation = db_get_active_impersonation(&ctx.db_client, &impersonation_id)
+                .await
+                .map_err(|e| reject::custom(Error::Postgres(e)))?
+                .filter(|db_impersonation| db_impersonation.user_id.eq(&user_id))
+                .ok_or_else(|| reject::custom(Error::Auth(AuthError::ImpersonationRevoked)))?;
+            Some(db_impersonation.admin_id)
+        }
+        None => None,
+    };
+    let is_batch = batch.is_batch();
+    let requests = match batch.into_requests(ctx.graphql.max_batch_size) {
+        Ok(requests) => requests,
+        Err(res) => return Ok(warp::reply::json(&res)),
+    };
+    observe_gql_batch(schema_name, requests.len());
+
+    let mut responses = Vec::with_capacity(requests.len());
+    for req in requests {
+        responses.push(
+            execute_authenticated(
+                schema_name,
+                &schema,
+                &ctx,
+                req,
+                accept_language.as_deref(),
+                &user_id,
+                &impersonated_by,
+                issued_at,
+            )
+            .await,
+        );
+    }
+    Ok(warp::reply::json(&batch_response(responses, is_batch)))
+}
+
+async fn execute_authenticated<Q: GqlRoot, M: GqlRoot, S: GqlSubscriptionRoot>(
+    schema_name: &'static str,
+    schema: &AuthenticatedSchema<Q, M, S>,
+    ctx: &ResourcesContext,
+    req: PersistedGraphQLRequest,
+    accept_language: Option<&str>,
+    user_id: &uuid::Uuid,
+    impersonated_by: &Option<uuid::Uuid>,
+    issued_at: i64,
+) -> serde_json::Value {
+    // authenticated clients may always register their queries
+    let req = match resolve_request(&ctx.db_client, req, false).await {
+        Ok(req) => req,
+        Err(e) => return serde_json::to_value(&e.response()).unwrap_or_default(),
+    };
+    if !ctx.graphql.introspection && is_introspection_query(&req.query) {
+        return serde_json::to_value(&introspection_disabled()).unwrap_or_default();
     }
     let request_uuid = Uuid::new_v4();
     let start = Instant::now();
-    let res = req.execute(&schema, &ctx).await;
+    // the user and the loaders of the request only
+    let res = req
+        .execute(
+            schema,
+            &ctx.for_user(Some(*user_id))
+                .impersonated(*impersonated_by)
+                .jwt_issued_at(Some(issued_at)),
+        )
+        .await;
     log::info!(
-        "\nUUID: {:?}\nUserID: {:?}\ntime: {:?} milliseconds\noperation: {:?}",
+        "\nUUID: {:?}\nUserID: {:?}\nImpersonatedBy: {:?}\ntime: {:?} milliseconds\noperation: {:?}",
         request_uuid.to_string(),
         user_id,
+        impersonated_by,
         start.elapsed().as_millis(),
         req.operation_name().clone().unwrap_or_default()
     );
-    let json = warp::reply::json(&res);
-    Ok(json)
+    observe_gql_operation(
+        schema_name,
+        req.operation_name().unwrap_or_default(),
+        start.elapsed().as_secs_f64(),
+    );
+    let mut json = with_request_id(&res, &request_uuid);
+    translate_errors(&mut json, &ctx.business, accept_language);
+    json
+}
+
+/// The response as json, the request id added to the extensions of its errors so that clients
+/// can report them
+pub fn with_request_id(res: &GraphQLResponse, request_id: &Uuid) -> serde_json::Value {
+    let mut json = serde_json::to_value(res).unwrap_or_default();
+    if let Some(errors) = json
+        .get_mut("errors")
+        .and_then(|errors| errors.as_array_mut())
+    {
+        for error in errors.iter_mut().filter_map(|error| error.as_object_mut()) {
+            let extensions = error
+                .entry("extensions")
+                .or_insert_with(|| serde_json::json!({}));
+            if let Some(extensions) = extensions.as_object_mut() {
+                extensions.insert(
+                    REQUEST_ID_EXTENSION.to_string(),
+                    serde_json::Value::String(request_id.to_string()),
+                );
+            }
+        }
+    }
+    json
+}
+
+/// Translates the messages of the response's errors to the locale preferred by the
+/// `Accept-Language` header, to the configured language without one
+pub fn translate_errors(
+    json: &mut serde_json::Value,
+    business: &BusinessConfig,
+    accept_language: Option<&str>,
+) {
+    let locale = accept_language.and_then(|header| negotiate_locale(business, header));
+    if let Some(errors) = json
+        .get_mut("errors")
+        .and_then(|errors| errors.as_array_mut())
+    {
+        for message in errors
+            .iter_mut()
+            .filter_map(|error| error.get_mut("message"))
+        {
+            if let Some(translated) = message
+                .as_str()
+                .map(|text| translate(business, locale.as_deref(), text))
+            {
+                *message = serde_json::Value::String(translated);
+            }
+        }
+    }
+}
+
+fn introspection_disabled() -> GraphQLResponse {
+    GraphQLResponse::error(field_error(
+        ErrorCode::Forbidden,
+        None,
+        "Introspection is disabled",
+    ))
+}
+
+/// Key of the `connection_init` payload carrying the `Bearer <jwt>` of private subscriptions
+const SUBSCRIPTION_AUTH_KEY: &str = "authorization";
+
+pub fn graphql_public_subscriptions(
+    ws: Ws,
+    schema: Arc<PublicSchema>,
+    ctx: Arc<ResourcesContext>,
+) -> impl warp::Reply {
+    ws.on_upgrade(move |websocket: WebSocket| async move {
+        let init = move |_params: Variables| async move {
+            Ok::<_, Error>(ConnectionConfig::new(ctx.for_user(None)))
+        };
+        if let Err(e) = serve_graphql_ws(websocket, schema, init).await {
+            log::warn!("Public subscriptions connection error: {}", e);
+        }
+    })
+}
+
+pub fn graphql_private_subscriptions(
+    ws: Ws,
+    schema: Arc<PrivateSchema>,
+    ctx: Arc<ResourcesContext>,
+) -> impl warp::Reply {
+    ws.on_upgrade(move |websocket: WebSocket| async move {
+        // the jwt comes with connection_init, browsers cannot set headers on websockets
+        let init = move |params: Variables| async move {
+            let bearer = params
+                .get(SUBSCRIPTION_AUTH_KEY)
+                .and_then(|value| value.as_string_value())
+                .unwrap_or_default();
+            let user_id = authorize_bearer(bearer, policy(Operation::PrivateSubscriptions).roles)?;
+            Ok::<_, Error>(ConnectionConfig::new(ctx.for_user(Some(user_id))))
+        };
+        if let Err(e) = serve_graphql_ws(websocket, schema, init).await {
+            log::warn!("Private subscriptions connection error: {}", e);
+        }
+    })
 }
@@ -0,0 +1,189 @@
+//! Batched loading of the records nested resolvers need.
+//!
+//! Juniper resolves the items of a list, and the fields of an object, concurrently. A resolver
+//! loading through a [`Loader`] queues its key and yields, letting its siblings queue theirs,
+//! then the first of them to resume fetches every queued key in a single query. The loaded
+//! records are kept for the rest of the request, each request gets its own [`Loaders`] with its
+//! context.
+use super::error::GqlError;
+use crate::db::{
+    models::{AssetFile, DbTicket, DbUser},
+    sql::{db_get_files_by_event_ids, db_get_tickets_by_event_ids, db_get_users_by_ids},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    hash::Hash,
+};
+use tokio::sync::Mutex;
+use tokio_postgres::Client;
+use uuid::Uuid;
+
+struct LoaderState<K, V> {
+    /// the loaded records, `None` for the keys found nothing
+    loaded: HashMap<K, Option<V>>,
+    /// the keys to load with the next batch
+    queued: HashSet<K>,
+}
+
+/// Loads records by key in batches, caching them
+pub struct Loader<K, V> {
+    state: Mutex<LoaderState<K, V>>,
+}
+
+impl<K, V> Default for Loader<K, V> {
+    fn default() -> Self {
+        Loader {
+            state: Mutex::new(LoaderState {
+                loaded: HashMap::new(),
+                queued: HashSet::new(),
+            }),
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Loader<K, V> {
+    /// The record of the key, batched with the keys its siblings load meanwhile. `fetch` gets the
+    /// queued keys and returns the records found, by key
+    pub async fn load<F, Fut>(&self, key: K, fetch: F) -> Result<Option<V>, GqlError>
+    where
+        F: FnOnce(Vec<K>) -> Fut,
+        Fut: Future<Output = Result<HashMap<K, V>, tokio_postgres::Error>>,
+    {
+        {
+            let mut state = self.state.lock().await;
+            if let Some(value) = state.loaded.get(&key) {
+                return Ok(value.clone());
+            }
+            state.queued.insert(key.clone());
+        }
+
+        // the siblings queue their keys meanwhile
+        tokio::task::yield_now().await;
+
+        // the lock is held while fetching, the siblings wait for the batch their keys are in
+        let mut state = self.state.lock().await;
+        if let Some(value) = state.loaded.get(&key) {
+            return Ok(value.clone());
+        }
+        // queued again if the batch it was in failed
+        state.queued.insert(key.clone());
+        let keys = state.queued.drain().collect::<Vec<_>>();
+        let mut found = fetch(keys.clone()).await.map_err(GqlError::Database)?;
+        for key in keys {
+            let value = found.remove(&key);
+            state.loaded.insert(key, value);
+        }
+        Ok(state.loaded.get(&key).cloned().flatten())
+    }
+
+    /// The records of the keys, loaded in a single batch with the ones not loaded yet
+    pub async fn load_many<F, Fut>(&self, keys: &[K], fetch: F) -> Result<HashMap<K, V>, GqlError>
+    where
+        F: FnOnce(Vec<K>) -> Fut,
+        Fut: Future<Output = Result<HashMap<K, V>, tokio_postgres::Error>>,
+    {
+        let mut state = self.state.lock().await;
+        let missing = keys
+            .iter()
+            .filter(|key| !state.loaded.contains_key(key))
+            .cloned()
+            .collect::<HashSet<_>>();
+        if !missing.is_empty() {
+            let missing = missing.into_iter().collect::<Vec<_>>();
+            let mut found = fetch(missing.clone()).await.map_err(GqlError::Database)?;
+            for key in missing {
+                let value = found.remove(&key);
+                state.loaded.insert(key, value);
+            }
+        }
+        Ok(keys
+            .iter()
+            .filter_map(|key| {
+                let value = state.loaded.get(key).cloned().flatten()?;
+                Some((key.clone(), value))
+            })
+            .collect())
+    }
+}
+
+/// Groups the records by the key they belong to
+fn group_by<V>(records: Vec<V>, key: impl Fn(&V) -> Uuid) -> HashMap<Uuid, Vec<V>> {
+    let mut grouped: HashMap<Uuid, Vec<V>> = HashMap::new();
+    for record in records {
+        grouped.entry(key(&record)).or_default().push(record);
+    }
+    grouped
+}
+
+/// The loaders of a request
+#[derive(Default)]
+pub struct Loaders {
+    tickets_by_event: Loader<Uuid, Vec<DbTicket>>,
+    users_by_id: Loader<Uuid, DbUser>,
+    assets_by_event: Loader<Uuid, Vec<AssetFile>>,
+}
+
+impl Loaders {
+    /// The event's tickets, deleted ones excluded
+    pub async fn tickets_by_event(
+        &self,
+        db_client: &Client,
+        event_id: Uuid,
+    ) -> Result<Vec<DbTicket>, GqlError> {
+        let tickets = self
+            .tickets_by_event
+            .load(event_id, |event_ids| fetch_tickets(db_client, event_ids))
+            .await?;
+        Ok(tickets.unwrap_or_default())
+    }
+
+    /// The tickets of the events, by event, the events without any left out
+    pub async fn tickets_by_events(
+        &self,
+        db_client: &Client,
+        event_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<DbTicket>>, GqlError> {
+        self.tickets_by_event
+            .load_many(event_ids, |event_ids| fetch_tickets(db_client, event_ids))
+            .await
+    }
+
+    /// The user, `None` if there is no such user
+    pub async fn user_by_id(
+        &self,
+        db_client: &Client,
+        user_id: Uuid,
+    ) -> Result<Option<DbUser>, GqlError> {
+        self.users_by_id
+            .load(user_id, |user_ids| async move {
+                let users = db_get_users_by_ids(db_client, &user_ids).await?;
+                Ok(users.into_iter().map(|user| (user.id, user)).collect())
+            })
+            .await
+    }
+
+    /// The event's uploaded assets, attached or not
+    pub async fn assets_by_event(
+        &self,
+        db_client: &Client,
+        event_id: Uuid,
+    ) -> Result<Vec<AssetFile>, GqlError> {
+        let assets = self
+            .assets_by_event
+            .load(event_id, |event_ids| async move {
+                let assets = db_get_files_by_event_ids(db_client, &event_ids).await?;
+                Ok(group_by(assets, |asset| asset.event_id))
+            })
+            .await?;
+        Ok(assets.unwrap_or_default())
+    }
+}
+
+async fn fetch_tickets(
+    db_client: &Client,
+    event_ids: Vec<Uuid>,
+) -> Result<HashMap<Uuid, Vec<DbTicket>>, tokio_postgres::Error> {
+    let tickets = db_get_tickets_by_event_ids(db_client, &event_ids).await?;
+    Ok(group_by(tickets, |ticket| ticket.event_id))
+}
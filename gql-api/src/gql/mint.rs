@@ -0,0 +1,162 @@
+//! The NFT metadata minted for a ticket and a local estimate of what minting it costs, so
+//! sellers can preview a mint before spending NEAR.
+use crate::{
+    db::{
+        models::{DbEvent, DbTicket},
+        sql::{db_get_event_by_id, db_get_ticket_by_id},
+    },
+    gql::{
+        error::{GqlError, ValidationError},
+        guard::guard_event,
+        models::{EventStatus, MintEstimate},
+        schema::Context as ResourcesContext,
+    },
+    near::NearAmount,
+    policy::EventAccess,
+};
+use uuid::Uuid;
+
+/// Storage staking price on NEAR, 1 NEAR per 100kb
+pub const STORAGE_PRICE_PER_BYTE: NearAmount = NearAmount::from_yocto(10_000_000_000_000_000_000);
+
+/// Bytes stored per token besides its metadata: token id, owner and approvals
+pub const TOKEN_STORAGE_OVERHEAD_BYTES: usize = 320;
+
+/// Gas of a mint call regardless of the number of tokens
+pub const BASE_TERA_GAS: u128 = 5;
+
+/// Gas per minted token
+pub const TERA_GAS_PER_TOKEN: u128 = 2;
+
+/// Minimum gas price on NEAR, in yoctoNEAR per TGas
+pub const GAS_PRICE_PER_TERA_GAS: NearAmount = NearAmount::from_yocto(100_000_000_000_000_000_000);
+
+/// Everything submitted to the near api service for minting a ticket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MintPayload {
+    pub title: String,
+    pub ticket_slug: String,
+    pub description: String,
+    pub media: String,
+    pub media_hash: String,
+    pub number_of_tickets: i32,
+    pub extra: String,
+}
+
+impl MintPayload {
+    /// The payload minting the ticket's available quantity, with the event cover as media
+    pub fn new(db_event: &DbEvent, db_ticket: &DbTicket) -> Result<Self, GqlError> {
+        let price = db_ticket.price.ok_or_else(|| {
+            GqlError::Validation(ValidationError::new(
+                "price",
+                "Ticket price must be set before minting",
+            ))
+        })?;
+        //FIXME: this should be the image from the FE
+        let media = db_event.cover_photo_url.clone().ok_or_else(|| {
+            GqlError::Validation(ValidationError::new(
+                "cover_photo",
+                "Event cover photo must be set before minting",
+            ))
+        })?;
+        let number_of_tickets = db_ticket
+            .quantity_available
+            .filter(|quantity| *quantity > 0)
+            .ok_or_else(|| {
+                GqlError::Validation(ValidationError::new(
+                    "quantity_available",
+                    "Ticket quantity available must be set before minting",
+                ))
+            })?;
+
+        Ok(MintPayload {
+            title: db_ticket.ticket_name.clone(),
+            ticket_slug: db_ticket.ticket_slug.clone(),
+            description: db_ticket.description.clone().unwrap_or_default(),
+            media_hash: sha256::digest(&media),
+            media,
+            number_of_tickets,
+            extra: serde_json::json!({ "price": price }).to_string(),
+        })
+    }
+
+    /// The token metadata stored with every minted nft
+    pub fn metadata(&self) -> String {
+        serde_json::json!({
+            "title": self.title,
+            "description": self.description,
+            "media": self.media,
+            "media_hash": self.media_hash,
+            "copies": self.number_of_tickets,
+            "extra": self.extra,
+        })
+        .to_string()
+    }
+}
+
+/// The ticket and its event, when the calling user may mint the ticket now
+pub async fn mintable_ticket(
+    ctx: &ResourcesContext,
+    user_id: &Uuid,
+    ticket_id: &Uuid,
+) -> Result<(DbEvent, DbTicket), GqlError> {
+    let db_ticket = db_get_ticket_by_id(&ctx.db_client, ticket_id)
+        .await
+        .map_err(|_| {
+            GqlError::NotFound(ValidationError::new(
+                "ticket_id",
+                "Ticket with submitted id does not exist",
+            ))
+        })?;
+
+    let db_event = db_get_event_by_id(&ctx.db_client, &db_ticket.event_id)
+        .await
+        .map_err(|_| {
+            GqlError::NotFound(ValidationError::new(
+                "event_id",
+                "Event with submitted id does not exist",
+            ))
+        })?;
+
+    // make sure the event is in a DRAFT or MINTING states only
+    let allowed_states = vec![EventStatus::Draft, EventStatus::Minting];
+    if !allowed_states.contains(&db_event.event_status) {
+        return Err(GqlError::Conflict(ValidationError::new(
+            "event_status",
+            "Minting could only be applied to events with status DRAFT or MINTING",
+        )));
+    }
+
+    // check caller is the event creator or a member of its organization
+    guard_event(ctx, user_id, &db_event, EventAccess::Edit).await?;
+
+    Ok((db_event, db_ticket))
+}
+
+/// The expected storage and gas cost of minting the payload, nothing is submitted
+pub fn estimate_mint(ticket_id: Uuid, payload: &MintPayload) -> MintEstimate {
+    let metadata = payload.metadata();
+    let tokens = payload.number_of_tickets.max(0) as u128;
+    let storage_bytes = (metadata.len() + TOKEN_STORAGE_OVERHEAD_BYTES) as u128 * tokens;
+    let tera_gas = BASE_TERA_GAS + TERA_GAS_PER_TOKEN * tokens;
+
+    let storage_cost = STORAGE_PRICE_PER_BYTE
+        .checked_mul(storage_bytes)
+        .unwrap_or_else(|| NearAmount::from_yocto(u128::MAX));
+    let gas_cost = GAS_PRICE_PER_TERA_GAS
+        .checked_mul(tera_gas)
+        .unwrap_or_else(|| NearAmount::from_yocto(u128::MAX));
+
+    MintEstimate {
+        ticket_id,
+        number_of_tickets: payload.number_of_tickets,
+        metadata,
+        storage_bytes: i32::try_from(storage_bytes).unwrap_or(i32::MAX),
+        storage_cost,
+        tera_gas: i32::try_from(tera_gas).unwrap_or(i32::MAX),
+        gas_cost,
+        total_cost: storage_cost
+            .checked_add(gas_cost)
+            .unwrap_or_else(|| NearAmount::from_yocto(u128::MAX)),
+    }
+}
@@ -1,10 +1,18 @@
+pub mod batch;
+pub mod clone;
 pub mod error;
+pub mod event_details;
 pub mod filters;
+pub mod guard;
 pub mod handlers;
+pub mod loaders;
+pub mod mint;
 pub mod models;
 pub mod mutations;
+pub mod persisted;
 pub mod quiries;
 pub mod routes;
 pub mod schema;
+pub mod schema_language;
 pub mod subscriptions;
 pub mod validations;
@@ -1,9 +1,28 @@
 use super::error::GqlError;
-use crate::db::models::{DbEvent, DbTicket, DbUser};
-use chrono::NaiveDateTime;
+use super::schema::Context as ResourcesContext;
+use crate::config::AssetUrlsConfig;
+use crate::db::models::{
+    AssetFile, DbAttendee, DbAuditLog, DbCategory, DbDailyReservations, DbEvent,
+    DbEventCollaborator, DbEventCount, DbEventDailyStats, DbEventSeries, DbMintJob, DbNotification,
+    DbOrganization, DbOrganizationMember, DbPayoutAccount, DbPayoutBalance, DbPayoutRequest,
+    DbPromoCode, DbSeat, DbSeatSection, DbSellerDocument, DbSellerPhoneVerification,
+    DbSellerWebhook, DbSignupAttempt, DbTagCount, DbTicket, DbTicketCancellation, DbTicketGift,
+    DbTicketListing, DbTicketPriceTier, DbTicketReservation, DbTicketTransfer, DbUser, DbUserCount,
+    DbWaitlistEntry, DbWalletTopUp,
+};
+use crate::error::StorageError;
+use crate::fx::{self, Currency};
+use crate::jobs::models::EventAssetKind;
+use crate::metrics::GrpcCallCounts;
+use crate::near::NearAmount;
+use crate::phone::mask_phone_number;
+use crate::pricing::{active_tier, upcoming_tiers};
+use crate::storage::{readable_url, Storage};
+use chrono::{NaiveDate, NaiveDateTime};
 use juniper::GraphQLEnum;
 use serde::{Deserialize, Serialize};
 use std::{convert::From, fmt};
+use uuid::Uuid;
 
 //--------------------------NFTS---------------------------------
 
@@ -13,16 +32,543 @@ use std::{convert::From, fmt};
 #[serde(rename_all = "camelCase")]
 pub struct NewMintNftsRequest {
     #[graphql(description = "Ticket id to mint tickets for")]
-    pub ticket_id: String,
+    pub ticket_id: Uuid,
 }
 
-#[derive(juniper::GraphQLObject)]
-#[graphql(description = "Gql response type for minting nfts")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NewMintNftsResponse {
-    #[graphql(description = "Tx hash")]
     pub tx_hash: String,
+    /// the seller's wallet, which sent the transaction
+    pub wallet_id: String,
+}
+
+#[juniper::graphql_object(
+    Context = ResourcesContext,
+    description = "Gql response type for minting nfts"
+)]
+impl NewMintNftsResponse {
+    /// Tx hash
+    fn tx_hash(&self) -> &str {
+        &self.tx_hash
+    }
+
+    /// the mint transaction's status, looked up on the chain when selected
+    async fn transaction_status(
+        &self,
+        ctx: &ResourcesContext,
+    ) -> Result<TransactionStatus, GqlError> {
+        TransactionStatus::fetch(ctx, &self.tx_hash, &self.wallet_id).await
+    }
+}
+
+/// Status of a NEAR transaction
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, GraphQLEnum)]
+pub enum TransactionState {
+    #[graphql(name = "PENDING")]
+    Pending,
+    #[graphql(name = "SUCCESS")]
+    Success,
+    #[graphql(name = "FAILURE")]
+    Failure,
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the status of a NEAR transaction")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionStatus {
+    #[graphql(description = "The transaction hash")]
+    pub tx_hash: String,
+    #[graphql(description = "The transaction's status, pending until it is final")]
+    pub status: TransactionState,
+    #[graphql(description = "Why the transaction failed")]
+    pub failure_reason: Option<String>,
+}
+
+impl TransactionStatus {
+    /// The status the near api reports, along with its error for failed transactions
+    pub fn new(tx_hash: String, status: &str, error: &str) -> Self {
+        let status = match status {
+            "SUCCESS" => TransactionState::Success,
+            "FAILURE" => TransactionState::Failure,
+            _ => TransactionState::Pending,
+        };
+        let failure_reason = match status {
+            TransactionState::Failure if error.is_empty() => Some("Transaction failed".to_string()),
+            TransactionState::Failure => Some(error.to_string()),
+            _ => None,
+        };
+        TransactionStatus {
+            tx_hash,
+            status,
+            failure_reason,
+        }
+    }
+
+    /// Looks up the status of a transaction sent by `account_id` on the near api
+    pub async fn fetch(
+        ctx: &ResourcesContext,
+        tx_hash: &str,
+        account_id: &str,
+    ) -> Result<Self, GqlError> {
+        let response = {
+            let mut lock = ctx.grpc_near_client.lock().await;
+            let response = lock
+                .get_tx_status(tx_hash, account_id)
+                .await
+                .map_err(GqlError::Grpc)?;
+            drop(lock);
+            response
+        };
+        Ok(TransactionStatus::new(
+            tx_hash.to_string(),
+            &response.status,
+            &response.error,
+        ))
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the expected cost of minting a ticket, nothing is submitted")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintEstimate {
+    #[graphql(description = "Ticket id the estimate is for")]
+    pub ticket_id: Uuid,
+    #[graphql(description = "The number of nfts that would be minted")]
+    pub number_of_tickets: i32,
+    #[graphql(description = "The token metadata that would be submitted, as JSON")]
+    pub metadata: String,
+    #[graphql(description = "The size in bytes of the stored tokens")]
+    pub storage_bytes: i32,
+    #[graphql(description = "The storage staking cost")]
+    pub storage_cost: NearAmount,
+    #[graphql(description = "The prepaid gas in TGas")]
+    pub tera_gas: i32,
+    #[graphql(description = "The cost of the prepaid gas, at most")]
+    pub gas_cost: NearAmount,
+    #[graphql(description = "The storage and gas costs together")]
+    pub total_cost: NearAmount,
+}
+
+/// Status of a submitted mint transaction
+#[repr(i16)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, GraphQLEnum)]
+pub enum MintStatus {
+    #[graphql(name = "PENDING")]
+    Pending = 0,
+    #[graphql(name = "SUCCEEDED")]
+    Succeeded = 1,
+    #[graphql(name = "FAILED")]
+    Failed = 2,
+}
+
+impl From<MintStatus> for i16 {
+    fn from(mint_status: MintStatus) -> i16 {
+        mint_status as i16
+    }
+}
+
+impl TryFrom<i16> for MintStatus {
+    type Error = GqlError;
+
+    fn try_from(n: i16) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(MintStatus::Pending),
+            1 => Ok(MintStatus::Succeeded),
+            2 => Ok(MintStatus::Failed),
+            _ => Err(GqlError::UnknownMintStatus(n.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for MintStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MintStatus::Pending => write!(f, "pending"),
+            MintStatus::Succeeded => write!(f, "succeeded"),
+            MintStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// Progress of a buyer signup, a stuck one is retried until it completes or an admin rolls it
+/// back
+#[repr(i16)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, GraphQLEnum)]
+pub enum SignupStatus {
+    #[graphql(name = "PENDING")]
+    Pending = 0,
+    #[graphql(name = "COMPLETED")]
+    Completed = 1,
+    #[graphql(name = "FAILED")]
+    Failed = 2,
+    #[graphql(name = "ROLLED_BACK")]
+    RolledBack = 3,
+}
+
+/// Availability of a seat, derived from its hold and reservation
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, GraphQLEnum)]
+pub enum SeatStatus {
+    #[graphql(name = "AVAILABLE")]
+    Available,
+    #[graphql(name = "HELD")]
+    Held,
+    #[graphql(name = "RESERVED")]
+    Reserved,
+}
+
+impl From<SignupStatus> for i16 {
+    fn from(signup_status: SignupStatus) -> i16 {
+        signup_status as i16
+    }
+}
+
+impl TryFrom<i16> for SignupStatus {
+    type Error = GqlError;
+
+    fn try_from(n: i16) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(SignupStatus::Pending),
+            1 => Ok(SignupStatus::Completed),
+            2 => Ok(SignupStatus::Failed),
+            3 => Ok(SignupStatus::RolledBack),
+            _ => Err(GqlError::UnknownSignupStatus(n.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for SignupStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignupStatus::Pending => write!(f, "pending"),
+            SignupStatus::Completed => write!(f, "completed"),
+            SignupStatus::Failed => write!(f, "failed"),
+            SignupStatus::RolledBack => write!(f, "rolled_back"),
+        }
+    }
+}
+
+/// What an inbox notification is about
+#[repr(i16)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, GraphQLEnum)]
+pub enum NotificationKind {
+    #[graphql(name = "WALLET_CREATED")]
+    WalletCreated = 0,
+    #[graphql(name = "TICKETS_MINTED")]
+    TicketsMinted = 1,
+    #[graphql(name = "EVENT_REMINDER")]
+    EventReminder = 2,
+    #[graphql(name = "RESERVATION_CANCELLED")]
+    ReservationCancelled = 3,
+    #[graphql(name = "EVENT_APPROVED")]
+    EventApproved = 4,
+    #[graphql(name = "EVENT_REJECTED")]
+    EventRejected = 5,
+}
+
+impl From<NotificationKind> for i16 {
+    fn from(kind: NotificationKind) -> i16 {
+        kind as i16
+    }
+}
+
+impl TryFrom<i16> for NotificationKind {
+    type Error = GqlError;
+
+    fn try_from(n: i16) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(NotificationKind::WalletCreated),
+            1 => Ok(NotificationKind::TicketsMinted),
+            2 => Ok(NotificationKind::EventReminder),
+            3 => Ok(NotificationKind::ReservationCancelled),
+            4 => Ok(NotificationKind::EventApproved),
+            5 => Ok(NotificationKind::EventRejected),
+            _ => Err(GqlError::UnknownNotificationKind(n.to_string())),
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the progress of a submitted mint of a ticket")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintJob {
+    #[graphql(description = "The mint job's id")]
+    pub id: Uuid,
+    #[graphql(description = "When the mint was submitted")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "The minted ticket id")]
+    pub ticket_id: Uuid,
+    #[graphql(description = "The mint transaction hash")]
+    pub tx_hash: String,
+    #[graphql(description = "The number of nfts minted")]
+    pub number_of_tickets: i32,
+    #[graphql(description = "The mint transaction's status")]
+    pub mint_status: MintStatus,
+    #[graphql(description = "How many times the transaction status was checked")]
+    pub attempts: i32,
+    #[graphql(description = "When the transaction status was last checked")]
+    pub checked_at: Option<NaiveDateTime>,
+    #[graphql(description = "Why the mint failed")]
+    pub error: Option<String>,
+}
+
+impl From<DbMintJob> for MintJob {
+    fn from(mint_job: DbMintJob) -> Self {
+        MintJob {
+            id: mint_job.id,
+            created_at: mint_job.created_at,
+            ticket_id: mint_job.ticket_id,
+            tx_hash: mint_job.tx_hash,
+            number_of_tickets: mint_job.number_of_tickets,
+            mint_status: mint_job.mint_status,
+            attempts: mint_job.attempts,
+            checked_at: mint_job.checked_at,
+            error: mint_job.error,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the progress of a buyer signup")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignupAttempt {
+    #[graphql(description = "The signup attempt's id")]
+    pub id: Uuid,
+    #[graphql(description = "When the buyer signed up")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "When the signup last progressed")]
+    pub updated_at: NaiveDateTime,
+    #[graphql(description = "The id of the user the signup creates")]
+    pub user_id: Uuid,
+    #[graphql(description = "The username the buyer picked")]
+    pub username: String,
+    #[graphql(description = "The verified phone number of the buyer")]
+    pub phone_number: String,
+    #[graphql(description = "The account id of the buyer's wallet")]
+    pub wallet_id: String,
+    #[graphql(description = "When the wallet was created, if it was")]
+    pub wallet_created_at: Option<NaiveDateTime>,
+    #[graphql(description = "The wallet creation transaction hash")]
+    pub tx_hash: Option<String>,
+    #[graphql(description = "The signup's progress")]
+    pub signup_status: SignupStatus,
+    #[graphql(description = "How many times the signup failed")]
+    pub attempts: i32,
+    #[graphql(description = "When a pending signup is retried next")]
+    pub next_attempt_at: NaiveDateTime,
+    #[graphql(description = "Why the signup last failed")]
+    pub error: Option<String>,
+}
+
+impl From<DbSignupAttempt> for SignupAttempt {
+    fn from(signup_attempt: DbSignupAttempt) -> Self {
+        SignupAttempt {
+            id: signup_attempt.id,
+            created_at: signup_attempt.created_at,
+            updated_at: signup_attempt.updated_at,
+            user_id: signup_attempt.user_id,
+            username: signup_attempt.username,
+            phone_number: signup_attempt.phone_number,
+            wallet_id: signup_attempt.wallet_id,
+            wallet_created_at: signup_attempt.wallet_created_at,
+            tx_hash: signup_attempt.tx_hash,
+            signup_status: signup_attempt.signup_status,
+            attempts: signup_attempt.attempts,
+            next_attempt_at: signup_attempt.next_attempt_at,
+            error: signup_attempt.error,
+        }
+    }
+}
+
+//--------------------------ANALYTICS---------------------------------
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the counters of an event for a day")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDailyStats {
+    #[graphql(description = "The day, in UTC")]
+    pub day: NaiveDate,
+    #[graphql(description = "The event page views")]
+    pub views: i32,
+    #[graphql(description = "The ticket reservations, free tickets included")]
+    pub reservations: i32,
+    #[graphql(description = "The reservations of priced tickets")]
+    pub purchases: i32,
+}
+
+impl From<DbEventDailyStats> for EventDailyStats {
+    fn from(stats: DbEventDailyStats) -> Self {
+        EventDailyStats {
+            day: stats.day,
+            views: stats.views,
+            reservations: stats.reservations,
+            purchases: stats.purchases,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the counters of an event over a date range")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventAnalytics {
+    #[graphql(description = "The event id")]
+    pub event_id: Uuid,
+    #[graphql(description = "The first day of the range")]
+    pub from: NaiveDate,
+    #[graphql(description = "The last day of the range, included")]
+    pub to: NaiveDate,
+    #[graphql(description = "The page views over the range")]
+    pub views: i32,
+    #[graphql(description = "The ticket reservations over the range")]
+    pub reservations: i32,
+    #[graphql(description = "The reservations of priced tickets over the range")]
+    pub purchases: i32,
+    #[graphql(description = "Reservations per page view, none without views")]
+    pub conversion_rate: Option<f64>,
+    #[graphql(description = "The counters per day, the days without any left out")]
+    pub days: Vec<EventDailyStats>,
+}
+
+impl EventAnalytics {
+    pub fn new(
+        event_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+        days: Vec<DbEventDailyStats>,
+    ) -> Self {
+        let views = days.iter().map(|day| day.views).sum::<i32>();
+        let reservations = days.iter().map(|day| day.reservations).sum::<i32>();
+        let purchases = days.iter().map(|day| day.purchases).sum::<i32>();
+        EventAnalytics {
+            event_id,
+            from,
+            to,
+            views,
+            reservations,
+            purchases,
+            conversion_rate: (views > 0).then(|| f64::from(reservations) / f64::from(views)),
+            days: days.into_iter().map(EventDailyStats::from).collect(),
+        }
+    }
+}
+
+//--------------------------SYSTEM METRICS---------------------------------
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the number of users of a role and status")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserCount {
+    #[graphql(description = "The users' role")]
+    pub user_type: String,
+    #[graphql(description = "The users' status")]
+    pub user_status: String,
+    #[graphql(description = "The number of users")]
+    pub total: i32,
+}
+
+impl From<DbUserCount> for UserCount {
+    fn from(count: DbUserCount) -> Self {
+        UserCount {
+            user_type: count.user_type.to_string(),
+            user_status: count.user_status.to_string(),
+            total: count.total,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the number of events of a status")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventCount {
+    #[graphql(description = "The events' status")]
+    pub event_status: EventStatus,
+    #[graphql(description = "The number of events")]
+    pub total: i32,
+}
+
+impl From<DbEventCount> for EventCount {
+    fn from(count: DbEventCount) -> Self {
+        EventCount {
+            event_status: count.event_status,
+            total: count.total,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the reservations made on a day, across all events")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyReservations {
+    #[graphql(description = "The day, in UTC")]
+    pub day: NaiveDate,
+    #[graphql(description = "The reservations made")]
+    pub reservations: i32,
+    #[graphql(description = "The tickets reserved")]
+    pub tickets: i32,
+}
+
+impl From<DbDailyReservations> for DailyReservations {
+    fn from(day: DbDailyReservations) -> Self {
+        DailyReservations {
+            day: day.day,
+            reservations: day.reservations,
+            tickets: day.tickets,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the calls of a near api gRPC method")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcCallStats {
+    #[graphql(description = "The gRPC method")]
+    pub method: String,
+    #[graphql(description = "The calls, the failed ones included")]
+    pub calls: f64,
+    #[graphql(description = "The failed calls")]
+    pub errors: f64,
+    #[graphql(description = "Failed calls per call, none without calls")]
+    pub error_rate: Option<f64>,
+}
+
+impl From<GrpcCallCounts> for GrpcCallStats {
+    fn from(counts: GrpcCallCounts) -> Self {
+        GrpcCallStats {
+            method: counts.method,
+            calls: counts.calls as f64,
+            errors: counts.errors as f64,
+            error_rate: (counts.calls > 0).then(|| counts.errors as f64 / counts.calls as f64),
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the operational counters of the api")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemMetrics {
+    #[graphql(description = "The users per role and status, deleted accounts left out")]
+    pub users: Vec<UserCount>,
+    #[graphql(description = "The events per status, deleted events left out")]
+    pub events: Vec<EventCount>,
+    #[graphql(description = "The reservations per day, the days without any left out")]
+    pub reservations_per_day: Vec<DailyReservations>,
+    #[graphql(description = "The sms sent since this api instance started")]
+    pub sms_sent: f64,
+    #[graphql(description = "The sms failing to send since this api instance started")]
+    pub sms_failed: f64,
+    #[graphql(description = "The near api gRPC calls per method since this api instance started")]
+    pub grpc_calls: Vec<GrpcCallStats>,
 }
 
 //--------------------------USERS---------------------------------
@@ -33,7 +579,7 @@ pub struct NewMintNftsResponse {
 #[serde(rename_all = "camelCase")]
 pub struct User {
     #[graphql(description = "The user's id")]
-    pub id: String,
+    pub id: Uuid,
     #[graphql(description = "The user's name")]
     pub name: Option<String>,
     #[graphql(description = "The user's username")]
@@ -52,12 +598,22 @@ pub struct User {
     pub user_type: String,
     #[graphql(description = "The users's status")]
     pub user_status: String,
+    #[graphql(description = "The seller's storefront slug")]
+    pub seller_slug: Option<String>,
+    #[graphql(description = "The language of the user's sms, the default one if none")]
+    pub locale: Option<String>,
+    #[graphql(description = "The seller's onboarding status, none for the other roles")]
+    pub seller_status: Option<String>,
+    #[graphql(description = "Why the seller's documents were rejected, if they were")]
+    pub seller_rejection_reason: Option<String>,
+    #[graphql(description = "Whether the user is reminded of the events they hold tickets for")]
+    pub event_reminders: bool,
 }
 
 impl From<DbUser> for User {
     fn from(user: DbUser) -> Self {
         User {
-            id: user.id.to_string(),
+            id: user.id,
             name: user.name,
             username: user.username,
             email: user.email,
@@ -67,10 +623,149 @@ impl From<DbUser> for User {
             wallet_balance: user.wallet_balance,
             user_type: user.user_type.to_string(),
             user_status: user.user_status.to_string(),
+            seller_slug: user.seller_slug,
+            locale: user.locale,
+            seller_status: user
+                .seller_status
+                .map(|seller_status| seller_status.to_string()),
+            seller_rejection_reason: user.seller_rejection_reason,
+            event_reminders: !user.event_reminders_opt_out,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the public storefront of a seller")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Seller {
+    #[graphql(description = "The seller's id")]
+    pub id: Uuid,
+    #[graphql(description = "The seller's name")]
+    pub name: Option<String>,
+    #[graphql(description = "The seller's username")]
+    pub username: String,
+    #[graphql(description = "The seller's storefront slug")]
+    pub seller_slug: String,
+    #[graphql(description = "The seller's registration date")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "The seller's published events")]
+    pub events: Vec<Event>,
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a verification code sent to a seller's phone")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellerPhoneVerification {
+    #[graphql(description = "The verification's id")]
+    pub id: Uuid,
+    #[graphql(description = "The masked phone number the code was sent to")]
+    pub phone_number: String,
+    #[graphql(description = "The code could not be verified afterwards")]
+    pub expires_at: NaiveDateTime,
+}
+
+impl From<DbSellerPhoneVerification> for SellerPhoneVerification {
+    fn from(verification: DbSellerPhoneVerification) -> Self {
+        SellerPhoneVerification {
+            id: verification.id,
+            phone_number: mask_phone_number(&verification.phone_number),
+            expires_at: verification.expires_at,
         }
     }
 }
 
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql request type for re-encrypting the wallet secret key")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RotateWalletSecret {
+    #[graphql(description = "The secret the wallet secret key is encrypted with")]
+    pub current_secret: String,
+    #[graphql(description = "The secret to encrypt the wallet secret key with")]
+    pub new_secret: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql request type for updating the caller's profile")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProfile {
+    #[graphql(description = "The user's new name")]
+    pub name: Option<String>,
+    #[graphql(description = "The user's new username")]
+    pub username: Option<String>,
+    #[graphql(description = "The user's new email")]
+    pub email: Option<String>,
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a pending two-factor authentication enrollment")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TotpEnrollment {
+    #[graphql(description = "The base32 secret to enter in an authenticator app")]
+    pub secret: String,
+    #[graphql(description = "The otpauth uri of the secret, to show as a qr code")]
+    pub provisioning_uri: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql request type for changing the caller's password")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePassword {
+    #[graphql(description = "The user's current password")]
+    pub current_password: String,
+    #[graphql(description = "The password replacing it")]
+    pub new_password: String,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(
+    description = "Gql request type for exporting the caller's wallet, re-verified with a recovery code or the password"
+)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportWallet {
+    #[graphql(description = "The secret the wallet secret key is encrypted with")]
+    pub secret: String,
+    #[graphql(description = "The caller's recovery session, created with a recovery code")]
+    pub recovery_session_id: Option<Uuid>,
+    #[graphql(description = "The recovery code sent to the caller's phone")]
+    pub recovery_code: Option<String>,
+    #[graphql(description = "The caller's password, instead of a recovery code")]
+    pub password: Option<String>,
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a wallet exported to be imported in another app")]
+#[derive(Clone)]
+pub struct WalletExport {
+    #[graphql(description = "The NEAR account of the wallet")]
+    pub wallet_id: String,
+    #[graphql(description = "The plain secret key of the wallet")]
+    pub secret_key: String,
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a super admin acting as another user")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Impersonation {
+    #[graphql(description = "The impersonation's id, used to revoke it")]
+    pub id: Uuid,
+    #[graphql(description = "The impersonated user id")]
+    pub user_id: Uuid,
+    #[graphql(description = "The impersonating super admin id")]
+    pub admin_id: Uuid,
+    #[graphql(description = "The jwt to act as the user with, flagged as an impersonation")]
+    pub token: String,
+    #[graphql(description = "When the jwt expires")]
+    pub expires_at: NaiveDateTime,
+}
+
 //--------------------------EVENTS---------------------------------
 
 #[derive(juniper::GraphQLObject)]
@@ -79,7 +774,7 @@ impl From<DbUser> for User {
 #[serde(rename_all = "camelCase")]
 pub struct Event {
     #[graphql(description = "The event's id")]
-    pub id: String,
+    pub id: Uuid,
     #[graphql(description = "The event's name")]
     pub event_name: String,
     #[graphql(description = "The event's slug")]
@@ -106,10 +801,44 @@ pub struct Event {
     pub cover_photo_url: Option<String>,
     #[graphql(description = "The event's thumbnail url")]
     pub thumbnail_url: Option<String>,
+    #[graphql(description = "The event's cover photo ipfs url, once pinned")]
+    pub cover_photo_ipfs_url: Option<String>,
+    #[graphql(description = "The event's thumbnail ipfs url, once pinned")]
+    pub thumbnail_ipfs_url: Option<String>,
+    #[graphql(description = "The event's resized image variants, once generated")]
+    pub image_variants: EventImageVariants,
+    #[graphql(description = "Is the event archived?")]
+    pub archived: bool,
     #[graphql(description = "The event's status")]
     pub event_status: String,
     #[graphql(description = "The event's creator id")]
-    pub created_by_user: String,
+    pub created_by_user: Uuid,
+    #[graphql(description = "The id of the organization managing the event, if any")]
+    pub organization_id: Option<Uuid>,
+    #[graphql(description = "The event's category id")]
+    pub category_id: Option<Uuid>,
+    #[graphql(description = "The venue's latitude, once its location is geocoded")]
+    pub latitude: Option<f64>,
+    #[graphql(description = "The venue's longitude, once its location is geocoded")]
+    pub longitude: Option<f64>,
+    #[graphql(
+        description = "Hours before the start until which buyers may cancel, none if they cannot"
+    )]
+    pub cancellation_window_hours: Option<i32>,
+    #[graphql(description = "When the event, its tickets, tags or gallery last changed")]
+    pub modified_at: NaiveDateTime,
+    #[graphql(description = "The id of the series the event is an occurrence of, if any")]
+    pub series_id: Option<Uuid>,
+    #[graphql(description = "When the event was last submitted for review")]
+    pub review_submitted_at: Option<NaiveDateTime>,
+    #[graphql(description = "Why the reviewer sent the event back, until it is resubmitted")]
+    pub review_rejection_reason: Option<String>,
+    #[graphql(description = "The distance to the searched place in km, on nearby events only")]
+    pub distance_km: Option<f64>,
+    #[graphql(description = "The event's tags")]
+    pub tags: Vec<String>,
+    #[graphql(description = "The event's gallery images, in order")]
+    pub gallery: Vec<GalleryImage>,
     #[graphql(description = "The event's tickets")]
     pub tickets: Vec<Ticket>,
 }
@@ -117,7 +846,7 @@ pub struct Event {
 impl Event {
     pub fn new(event: DbEvent, tickets: Vec<DbTicket>) -> Self {
         Event {
-            id: event.id.to_string(),
+            id: event.id,
             event_name: event.event_name,
             event_slug: event.event_slug,
             start_date: event.start_date,
@@ -131,11 +860,107 @@ impl Event {
             venue_location: event.venue_location,
             cover_photo_url: event.cover_photo_url,
             thumbnail_url: event.thumbnail_url,
+            cover_photo_ipfs_url: event.cover_photo_ipfs_url,
+            thumbnail_ipfs_url: event.thumbnail_ipfs_url,
+            image_variants: EventImageVariants {
+                thumbnail_url: event.thumbnail_variant_url,
+                cover_url: event.cover_variant_url,
+                og_image_url: event.og_image_url,
+            },
+            archived: event.archived,
             event_status: event.event_status.to_string(),
-            created_by_user: event.created_by_user.to_string(),
+            created_by_user: event.created_by_user,
+            organization_id: event.organization_id,
+            category_id: event.category_id,
+            latitude: event.latitude,
+            longitude: event.longitude,
+            cancellation_window_hours: event.cancellation_window_hours,
+            modified_at: event.modified_at,
+            series_id: event.series_id,
+            review_submitted_at: event.review_submitted_at,
+            review_rejection_reason: event.review_rejection_reason,
+            distance_km: None,
+            tags: vec![],
+            gallery: vec![],
             tickets: tickets.into_iter().map(Ticket::from).collect(),
         }
     }
+
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Sets the gallery from the event's assets, ordered by position
+    pub fn with_gallery(mut self, asset_files: &[AssetFile], storage: &dyn Storage) -> Self {
+        let mut gallery = asset_files
+            .iter()
+            .filter(|asset_file| asset_file.event_id == self.id)
+            .filter_map(|asset_file| GalleryImage::new(asset_file, storage))
+            .collect::<Vec<_>>();
+        gallery.sort_by_key(|image| image.position);
+        self.gallery = gallery;
+        self
+    }
+
+    pub fn with_distance_km(mut self, distance_km: f64) -> Self {
+        self.distance_km = Some(distance_km);
+        self
+    }
+
+    /// Sets the current and upcoming tiers of the event's tickets
+    pub fn with_price_tiers(mut self, tiers: &[DbTicketPriceTier], now: &NaiveDateTime) -> Self {
+        self.tickets = self
+            .tickets
+            .into_iter()
+            .map(|ticket| {
+                let ticket_tiers = tiers
+                    .iter()
+                    .filter(|tier| tier.ticket_id == ticket.id)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                ticket.with_price_tiers(&ticket_tiers, now)
+            })
+            .collect();
+        self
+    }
+
+    /// Swaps the urls of the event's images for the urls clients read them at, presigned ones
+    /// when the bucket is private
+    pub async fn with_readable_urls(
+        mut self,
+        storage: &dyn Storage,
+        config: &AssetUrlsConfig,
+    ) -> Result<Self, StorageError> {
+        for url in [
+            &mut self.cover_photo_url,
+            &mut self.thumbnail_url,
+            &mut self.image_variants.thumbnail_url,
+            &mut self.image_variants.cover_url,
+            &mut self.image_variants.og_image_url,
+        ] {
+            if let Some(stored_url) = url.take() {
+                *url = Some(readable_url(storage, config, stored_url).await?);
+            }
+        }
+        for image in &mut self.gallery {
+            image.url = readable_url(storage, config, std::mem::take(&mut image.url)).await?;
+        }
+        Ok(self)
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the webp variants of an event's images")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventImageVariants {
+    #[graphql(description = "The thumbnail resized to 400x400")]
+    pub thumbnail_url: Option<String>,
+    #[graphql(description = "The cover photo resized to 1600x900")]
+    pub cover_url: Option<String>,
+    #[graphql(description = "The cover photo resized to 1200x630 for link previews")]
+    pub og_image_url: Option<String>,
 }
 
 #[derive(juniper::GraphQLInputObject)]
@@ -147,14 +972,190 @@ pub struct NewEvent {
     pub event_name: String,
 }
 
-#[derive(juniper::GraphQLInputObject)]
-#[graphql(description = "Gql type for an update event")]
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for recurring events, each occurrence being an event")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct UpdateEvent {
-    #[graphql(description = "The event's id")]
-    pub id: String,
-    #[graphql(description = "The event's name")]
+pub struct EventSeries {
+    #[graphql(description = "The series' id")]
+    pub id: Uuid,
+    #[graphql(description = "The name of the event the series repeats")]
+    pub series_name: String,
+    #[graphql(description = "The series' recurrence rule, an RRULE subset")]
+    pub rrule: String,
+    #[graphql(description = "The start of the series' first occurrence")]
+    pub starts_at: NaiveDateTime,
+    #[graphql(description = "The series' creator id")]
+    pub created_by_user: Uuid,
+    #[graphql(description = "The series' timestamp")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "The series' occurrences, earliest first")]
+    pub occurrences: Vec<Event>,
+    #[graphql(description = "The series' first occurrence not started yet, if any")]
+    pub next_occurrence: Option<Event>,
+}
+
+impl EventSeries {
+    /// The series with its occurrences, ordered by their start, the next one being the first
+    /// starting after `now`
+    pub fn new(series: DbEventSeries, occurrences: Vec<Event>, now: &NaiveDateTime) -> Self {
+        let next_occurrence = occurrences
+            .iter()
+            .find(|event| {
+                event
+                    .start_date
+                    .map_or(false, |start_date| start_date > *now)
+            })
+            .cloned();
+        EventSeries {
+            id: series.id,
+            series_name: series.series_name,
+            rrule: series.rrule,
+            starts_at: series.starts_at,
+            created_by_user: series.created_by_user,
+            created_at: series.created_at,
+            occurrences,
+            next_occurrence,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql type for the fields to change on a cloned event")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneEventOverrides {
+    #[graphql(description = "The copy's name, the source's name marked as a copy if not given")]
+    pub event_name: Option<String>,
+    #[graphql(description = "The copy's starting date")]
+    pub start_date: Option<NaiveDateTime>,
+    #[graphql(description = "The copy's end date")]
+    pub end_date: Option<NaiveDateTime>,
+    #[graphql(description = "The copy's entry time")]
+    pub entry_time: Option<NaiveDateTime>,
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a seat of a seat map section")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Seat {
+    #[graphql(description = "The seat's id")]
+    pub id: Uuid,
+    #[graphql(description = "The label of the seat's row")]
+    pub row_label: String,
+    #[graphql(description = "The seat's number in its row")]
+    pub seat_number: i32,
+    #[graphql(description = "Whether the seat is available, held or reserved")]
+    pub status: SeatStatus,
+}
+
+impl Seat {
+    pub fn new(seat: &DbSeat, now: &NaiveDateTime) -> Self {
+        Seat {
+            id: seat.id,
+            row_label: seat.row_label.clone(),
+            seat_number: seat.seat_number,
+            status: seat.status(now),
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a section of an event's seat map and its availability")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeatSection {
+    #[graphql(description = "The section's id")]
+    pub id: Uuid,
+    #[graphql(description = "The section's event id")]
+    pub event_id: Uuid,
+    #[graphql(description = "The id of the ticket type the section's seats are sold as")]
+    pub ticket_id: Uuid,
+    #[graphql(description = "The section's name")]
+    pub section_name: String,
+    #[graphql(description = "The section's order on the seat map")]
+    pub position: i32,
+    #[graphql(description = "The section's number of seats")]
+    pub seats_total: i32,
+    #[graphql(description = "The section's number of available seats")]
+    pub seats_available: i32,
+    #[graphql(description = "The section's number of seats held by buyers")]
+    pub seats_held: i32,
+    #[graphql(description = "The section's number of reserved seats")]
+    pub seats_reserved: i32,
+    #[graphql(description = "The section's seats, by row and number")]
+    pub seats: Vec<Seat>,
+}
+
+impl SeatSection {
+    /// The section with its seats among `seats`, their status as of `now`
+    pub fn new(section: DbSeatSection, seats: &[DbSeat], now: &NaiveDateTime) -> Self {
+        let seats = seats
+            .iter()
+            .filter(|seat| seat.section_id == section.id)
+            .map(|seat| Seat::new(seat, now))
+            .collect::<Vec<_>>();
+        let count =
+            |status: SeatStatus| seats.iter().filter(|seat| seat.status == status).count() as i32;
+        SeatSection {
+            id: section.id,
+            event_id: section.event_id,
+            ticket_id: section.ticket_id,
+            section_name: section.section_name,
+            position: section.position,
+            seats_total: seats.len() as i32,
+            seats_available: count(SeatStatus::Available),
+            seats_held: count(SeatStatus::Held),
+            seats_reserved: count(SeatStatus::Reserved),
+            seats,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for seats held by a buyer until they reserve them")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeatHold {
+    #[graphql(description = "The ids of the seats held")]
+    pub seat_ids: Vec<Uuid>,
+    #[graphql(description = "The seats are available again afterwards unless reserved")]
+    pub held_until: NaiveDateTime,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql type for a row of a new seat map section")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewSeatRow {
+    #[graphql(description = "The row's label")]
+    pub row_label: String,
+    #[graphql(description = "The row's number of seats, numbered from 1")]
+    pub seats: i32,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql type for a new seat map section")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewSeatSection {
+    #[graphql(description = "The id of the ticket type the section's seats are sold as")]
+    pub ticket_id: Uuid,
+    #[graphql(description = "The section's name, unique in the event")]
+    pub section_name: String,
+    #[graphql(description = "The section's rows")]
+    pub rows: Vec<NewSeatRow>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql type for an update event")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateEvent {
+    #[graphql(description = "The event's id")]
+    pub id: Uuid,
+    #[graphql(description = "The event's name")]
     pub event_name: Option<String>,
     #[graphql(description = "The event's starting date")]
     pub start_date: Option<NaiveDateTime>,
@@ -172,9 +1173,11 @@ pub struct UpdateEvent {
     pub venue_name: Option<String>,
     #[graphql(description = "The event's venue location")]
     pub venue_location: Option<String>,
-    #[graphql(description = "The event's cover photo (base64)")]
+    #[graphql(
+        description = "The event's cover photo (base64). Prefer createUploadUrl/confirmAsset"
+    )]
     pub cover_photo_base64: Option<String>,
-    #[graphql(description = "The event's thumbnail (base64)")]
+    #[graphql(description = "The event's thumbnail (base64). Prefer createUploadUrl/confirmAsset")]
     pub thumbnail_base64: Option<String>,
 }
 
@@ -186,6 +1189,8 @@ pub enum EventFilter {
     NoneFeatured,
     #[graphql(name = "ALL")]
     All,
+    #[graphql(name = "ARCHIVED")]
+    Archived,
 }
 
 /// Event Status
@@ -198,6 +1203,10 @@ pub enum EventStatus {
     Minting = 1,
     #[graphql(name = "FINAL")]
     Final = 2,
+    #[graphql(name = "CANCELLED")]
+    Cancelled = 3,
+    #[graphql(name = "PENDING_REVIEW")]
+    PendingReview = 4,
 }
 
 impl From<EventStatus> for i16 {
@@ -214,6 +1223,8 @@ impl TryFrom<i16> for EventStatus {
             0 => Ok(EventStatus::Draft),
             1 => Ok(EventStatus::Minting),
             2 => Ok(EventStatus::Final),
+            3 => Ok(EventStatus::Cancelled),
+            4 => Ok(EventStatus::PendingReview),
             _ => Err(GqlError::UnknownEventStatus(n.to_string())),
         }
     }
@@ -227,6 +1238,8 @@ impl EventStatus {
             "draft" => EventStatus::Draft,
             "minting" => EventStatus::Minting,
             "final" => EventStatus::Final,
+            "cancelled" => EventStatus::Cancelled,
+            "pending_review" => EventStatus::PendingReview,
             _ => EventStatus::Draft,
         }
     }
@@ -238,6 +1251,8 @@ impl fmt::Display for EventStatus {
             EventStatus::Draft => write!(f, "draft"),
             EventStatus::Minting => write!(f, "minting"),
             EventStatus::Final => write!(f, "final"),
+            EventStatus::Cancelled => write!(f, "cancelled"),
+            EventStatus::PendingReview => write!(f, "pending_review"),
         }
     }
 }
@@ -248,7 +1263,7 @@ impl fmt::Display for EventStatus {
 #[serde(rename_all = "camelCase")]
 pub struct Ticket {
     #[graphql(description = "The ticket's id")]
-    pub id: String,
+    pub id: Uuid,
     #[graphql(description = "The ticket's creation date")]
     pub created_at: NaiveDateTime,
     #[graphql(description = "The ticket's name")]
@@ -257,10 +1272,10 @@ pub struct Ticket {
     pub ticket_slug: String,
     #[graphql(description = "The tickets's description")]
     pub description: Option<String>,
-    #[graphql(description = "The tickets's price")]
-    pub price: Option<String>,
-    #[graphql(description = "The tickets's max release price")]
-    pub max_release_price: Option<String>,
+    #[graphql(description = "The tickets's price in yoctoNEAR")]
+    pub price: Option<NearAmount>,
+    #[graphql(description = "The tickets's max release price in yoctoNEAR")]
+    pub max_release_price: Option<NearAmount>,
     #[graphql(description = "The ticket's available quantity")]
     pub quantity_available: Option<i32>,
     #[graphql(description = "The ticket's minimum purchase quantity")]
@@ -270,13 +1285,63 @@ pub struct Ticket {
     #[graphql(description = "Are transfers for that ticket allowed?")]
     pub allow_transfers: Option<bool>,
     #[graphql(description = "The ticket's associated event id")]
-    pub event_id: String,
+    pub event_id: Uuid,
+    #[graphql(description = "Is the ticket archived?")]
+    pub archived: bool,
+    #[graphql(description = "The ticket's display currency (payments are always in NEAR)")]
+    pub currency: Currency,
+    #[graphql(description = "The ticket's version, bumped by every edit")]
+    pub version: i32,
+    #[graphql(description = "The ticket's price in every currency with a known exchange rate")]
+    pub display_prices: Vec<DisplayPrice>,
+    #[graphql(description = "The price tier the ticket sells at now, if any")]
+    pub current_tier: Option<PriceTier>,
+    #[graphql(description = "The price tiers that have not started yet, the earliest first")]
+    pub upcoming_tiers: Vec<PriceTier>,
+}
+
+impl Ticket {
+    /// Sets the ticket's current and upcoming tiers out of its tiers, ordered by their start
+    pub fn with_price_tiers(mut self, tiers: &[DbTicketPriceTier], now: &NaiveDateTime) -> Self {
+        self.current_tier = active_tier(tiers, now).cloned().map(PriceTier::from);
+        self.upcoming_tiers = upcoming_tiers(tiers, now)
+            .cloned()
+            .map(PriceTier::from)
+            .collect();
+        self
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a price converted for display")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayPrice {
+    #[graphql(description = "The price's currency")]
+    pub currency: Currency,
+    #[graphql(description = "The price as a decimal amount, e.g. 2.5")]
+    pub amount: String,
+}
+
+impl DisplayPrice {
+    /// The amount converted to every currency with a known exchange rate
+    pub fn all(amount: NearAmount) -> Vec<DisplayPrice> {
+        Currency::ALL
+            .iter()
+            .filter_map(|currency| {
+                fx::convert(amount, *currency).map(|converted| DisplayPrice {
+                    currency: *currency,
+                    amount: converted,
+                })
+            })
+            .collect()
+    }
 }
 
 impl From<DbTicket> for Ticket {
     fn from(ticket: DbTicket) -> Self {
         Ticket {
-            id: ticket.id.to_string(),
+            id: ticket.id,
             created_at: ticket.created_at,
             ticket_name: ticket.ticket_name,
             ticket_slug: ticket.ticket_slug,
@@ -287,11 +1352,74 @@ impl From<DbTicket> for Ticket {
             min_purchase_quantity: ticket.min_purchase_quantity,
             max_purchase_quantity: ticket.max_purchase_quantity,
             allow_transfers: ticket.allow_transfers,
-            event_id: ticket.event_id.to_string(),
+            event_id: ticket.event_id,
+            archived: ticket.archived,
+            currency: ticket.currency,
+            version: ticket.version,
+            display_prices: ticket.price.map(DisplayPrice::all).unwrap_or_default(),
+            current_tier: None,
+            upcoming_tiers: vec![],
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a price of a ticket for a time window")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceTier {
+    #[graphql(description = "The tier's id")]
+    pub id: Uuid,
+    #[graphql(description = "The tier's ticket id")]
+    pub ticket_id: Uuid,
+    #[graphql(description = "The tier's name, e.g. Early bird")]
+    pub tier_name: String,
+    #[graphql(description = "The tier's price in yoctoNEAR")]
+    pub price: NearAmount,
+    #[graphql(description = "When the tier starts")]
+    pub starts_at: NaiveDateTime,
+    #[graphql(description = "When the tier ends, it lasts until sold out if not set")]
+    pub ends_at: Option<NaiveDateTime>,
+    #[graphql(description = "The most tickets sold at the tier's price")]
+    pub quantity_cap: Option<i32>,
+    #[graphql(description = "The tickets left at the tier's price, unlimited if not set")]
+    pub quantity_remaining: Option<i32>,
+}
+
+impl From<DbTicketPriceTier> for PriceTier {
+    fn from(tier: DbTicketPriceTier) -> Self {
+        PriceTier {
+            id: tier.id,
+            ticket_id: tier.ticket_id,
+            quantity_remaining: tier.quantity_remaining(),
+            tier_name: tier.tier_name,
+            price: tier.price,
+            starts_at: tier.starts_at,
+            ends_at: tier.ends_at,
+            quantity_cap: tier.quantity_cap,
         }
     }
 }
 
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql type for adding a price tier to a ticket")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewPriceTier {
+    #[graphql(description = "The tier's ticket id")]
+    pub ticket_id: Uuid,
+    #[graphql(description = "The tier's name, e.g. Early bird")]
+    pub tier_name: String,
+    #[graphql(description = "The tier's price in yoctoNEAR")]
+    pub price: NearAmount,
+    #[graphql(description = "When the tier starts")]
+    pub starts_at: NaiveDateTime,
+    #[graphql(description = "When the tier ends, it lasts until sold out if not set")]
+    pub ends_at: Option<NaiveDateTime>,
+    #[graphql(description = "The most tickets sold at the tier's price")]
+    pub quantity_cap: Option<i32>,
+}
+
 #[derive(juniper::GraphQLInputObject)]
 #[graphql(description = "Gql type for creating a new event ticket")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -301,10 +1429,10 @@ pub struct NewTicket {
     pub ticket_name: String,
     #[graphql(description = "The tickets's description")]
     pub description: Option<String>,
-    #[graphql(description = "The tickets's price")]
-    pub price: Option<String>,
-    #[graphql(description = "The tickets's max release price")]
-    pub max_release_price: Option<String>,
+    #[graphql(description = "The tickets's price in yoctoNEAR")]
+    pub price: Option<NearAmount>,
+    #[graphql(description = "The tickets's max release price in yoctoNEAR")]
+    pub max_release_price: Option<NearAmount>,
     #[graphql(description = "The ticket's available quantity")]
     pub quantity_available: Option<i32>,
     #[graphql(description = "The ticket's minimum purchase quantity")]
@@ -313,8 +1441,10 @@ pub struct NewTicket {
     pub max_purchase_quantity: Option<i32>,
     #[graphql(description = "Are transfers for that ticket allowed?")]
     pub allow_transfers: Option<bool>,
+    #[graphql(description = "The ticket's display currency, NEAR by default")]
+    pub currency: Option<Currency>,
     #[graphql(description = "The ticket's associated event id")]
-    pub event_id: String,
+    pub event_id: Uuid,
 }
 
 #[derive(juniper::GraphQLInputObject)]
@@ -323,15 +1453,15 @@ pub struct NewTicket {
 #[serde(rename_all = "camelCase")]
 pub struct UpdateTicket {
     #[graphql(description = "The ticket's id")]
-    pub id: String,
+    pub id: Uuid,
     #[graphql(description = "The ticket's name")]
     pub ticket_name: Option<String>,
     #[graphql(description = "The tickets's description")]
     pub description: Option<String>,
-    #[graphql(description = "The tickets's price")]
-    pub price: Option<String>,
-    #[graphql(description = "The tickets's max release price")]
-    pub max_release_price: Option<String>,
+    #[graphql(description = "The tickets's price in yoctoNEAR")]
+    pub price: Option<NearAmount>,
+    #[graphql(description = "The tickets's max release price in yoctoNEAR")]
+    pub max_release_price: Option<NearAmount>,
     #[graphql(description = "The ticket's available quantity")]
     pub quantity_available: Option<i32>,
     #[graphql(description = "The ticket's minimum purchase quantity")]
@@ -340,4 +1470,1186 @@ pub struct UpdateTicket {
     pub max_purchase_quantity: Option<i32>,
     #[graphql(description = "Are transfers for that ticket allowed?")]
     pub allow_transfers: Option<bool>,
+    #[graphql(description = "The ticket's display currency")]
+    pub currency: Option<Currency>,
+    #[graphql(
+        description = "The version of the ticket the update is based on, refused if the ticket was edited meanwhile"
+    )]
+    pub version: i32,
+}
+
+//-------------------------------PROMO CODES---------------------------------------//
+
+/// Promo Code Discount Type
+#[repr(i16)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, GraphQLEnum)]
+pub enum DiscountType {
+    #[graphql(name = "PERCENTAGE")]
+    Percentage = 0,
+    #[graphql(name = "FIXED")]
+    Fixed = 1,
+}
+
+impl From<DiscountType> for i16 {
+    fn from(discount_type: DiscountType) -> i16 {
+        discount_type as i16
+    }
+}
+
+impl TryFrom<i16> for DiscountType {
+    type Error = GqlError;
+
+    fn try_from(n: i16) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(DiscountType::Percentage),
+            1 => Ok(DiscountType::Fixed),
+            _ => Err(GqlError::UnknownDiscountType(n.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for DiscountType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscountType::Percentage => write!(f, "percentage"),
+            DiscountType::Fixed => write!(f, "fixed"),
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for an existing promo code")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromoCode {
+    #[graphql(description = "The promo code's id")]
+    pub id: Uuid,
+    #[graphql(description = "The promo code's creation date")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "The promo code itself")]
+    pub code: String,
+    #[graphql(description = "The promo code's discount type")]
+    pub discount_type: DiscountType,
+    #[graphql(
+        description = "The promo code's discount value (percent, or fixed amount in yoctoNEAR)"
+    )]
+    pub discount_value: String,
+    #[graphql(description = "The promo code's maximum number of uses")]
+    pub max_uses: Option<i32>,
+    #[graphql(description = "The promo code's number of uses so far")]
+    pub used_count: i32,
+    #[graphql(description = "The promo code's validity start")]
+    pub valid_from: Option<NaiveDateTime>,
+    #[graphql(description = "The promo code's validity end")]
+    pub valid_until: Option<NaiveDateTime>,
+    #[graphql(description = "Is the promo code active?")]
+    pub is_active: bool,
+    #[graphql(description = "The promo code's associated event id")]
+    pub event_id: Uuid,
+}
+
+impl From<DbPromoCode> for PromoCode {
+    fn from(promo_code: DbPromoCode) -> Self {
+        PromoCode {
+            id: promo_code.id,
+            created_at: promo_code.created_at,
+            code: promo_code.code,
+            discount_type: promo_code.discount_type,
+            discount_value: promo_code.discount_value,
+            max_uses: promo_code.max_uses,
+            used_count: promo_code.used_count,
+            valid_from: promo_code.valid_from,
+            valid_until: promo_code.valid_until,
+            is_active: promo_code.is_active,
+            event_id: promo_code.event_id,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql type for creating a new promo code")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewPromoCode {
+    #[graphql(description = "The promo code itself")]
+    pub code: String,
+    #[graphql(description = "The promo code's discount type")]
+    pub discount_type: DiscountType,
+    #[graphql(
+        description = "The promo code's discount value (percent, or fixed amount in yoctoNEAR)"
+    )]
+    pub discount_value: String,
+    #[graphql(description = "The promo code's maximum number of uses")]
+    pub max_uses: Option<i32>,
+    #[graphql(description = "The promo code's validity start")]
+    pub valid_from: Option<NaiveDateTime>,
+    #[graphql(description = "The promo code's validity end")]
+    pub valid_until: Option<NaiveDateTime>,
+    #[graphql(description = "The promo code's associated event id")]
+    pub event_id: Uuid,
+}
+
+//-------------------------------TICKET TRANSFERS---------------------------------------//
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketTransfer {
+    pub id: Uuid,
+    pub created_at: NaiveDateTime,
+    pub price: Option<NearAmount>,
+    pub tx_hash: String,
+    pub reservation_id: Uuid,
+    pub ticket_id: Uuid,
+    pub from_user: Uuid,
+    pub to_user: Uuid,
+}
+
+#[juniper::graphql_object(
+    Context = ResourcesContext,
+    description = "Gql type for a ticket transfer"
+)]
+impl TicketTransfer {
+    /// The transfer's id
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// The transfer's creation date
+    fn created_at(&self) -> NaiveDateTime {
+        self.created_at
+    }
+
+    /// The price the ticket was transferred for in yoctoNEAR, if any
+    fn price(&self) -> Option<NearAmount> {
+        self.price
+    }
+
+    /// The transfer's transaction hash
+    fn tx_hash(&self) -> &str {
+        &self.tx_hash
+    }
+
+    /// The transferred reservation id
+    fn reservation_id(&self) -> Uuid {
+        self.reservation_id
+    }
+
+    /// The transferred ticket id
+    fn ticket_id(&self) -> Uuid {
+        self.ticket_id
+    }
+
+    /// The sending user id
+    fn from_user(&self) -> Uuid {
+        self.from_user
+    }
+
+    /// The receiving user id
+    fn to_user(&self) -> Uuid {
+        self.to_user
+    }
+
+    /// The transfer transaction's status, looked up on the chain when selected
+    async fn transaction_status(
+        &self,
+        ctx: &ResourcesContext,
+    ) -> Result<TransactionStatus, GqlError> {
+        // the sender's wallet sent the transaction
+        let sender = ctx
+            .loaders
+            .user_by_id(&ctx.db_client, self.from_user)
+            .await?
+            .ok_or(GqlError::UnexpectedInternal)?;
+        TransactionStatus::fetch(ctx, &self.tx_hash, &sender.wallet_id).await
+    }
+}
+
+impl From<DbTicketTransfer> for TicketTransfer {
+    fn from(transfer: DbTicketTransfer) -> Self {
+        TicketTransfer {
+            id: transfer.id,
+            created_at: transfer.created_at,
+            price: transfer.price,
+            tx_hash: transfer.tx_hash,
+            reservation_id: transfer.reservation_id,
+            ticket_id: transfer.ticket_id,
+            from_user: transfer.from_user,
+            to_user: transfer.to_user,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a buyer's place on the waitlist of a sold out ticket")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitlistEntry {
+    #[graphql(description = "The entry's id")]
+    pub id: Uuid,
+    #[graphql(description = "The waitlisted ticket id")]
+    pub ticket_id: Uuid,
+    #[graphql(description = "When the buyer joined the waitlist")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "When the buyer was told the ticket is available again, if yet")]
+    pub notified_at: Option<NaiveDateTime>,
+}
+
+impl From<DbWaitlistEntry> for WaitlistEntry {
+    fn from(entry: DbWaitlistEntry) -> Self {
+        WaitlistEntry {
+            id: entry.id,
+            ticket_id: entry.ticket_id,
+            created_at: entry.created_at,
+            notified_at: entry.notified_at,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a reservation of an event, as listed to its seller")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventReservation {
+    #[graphql(description = "The reservation's id")]
+    pub id: Uuid,
+    #[graphql(description = "When the tickets were reserved")]
+    pub reserved_at: NaiveDateTime,
+    #[graphql(description = "The buyer's username")]
+    pub username: String,
+    #[graphql(description = "The buyer's masked phone number")]
+    pub phone_number: Option<String>,
+    #[graphql(description = "The reserved ticket's name")]
+    pub ticket_name: String,
+    #[graphql(description = "The number of tickets reserved")]
+    pub quantity: i32,
+    #[graphql(description = "Whether the reservation was checked in")]
+    pub redeemed: bool,
+    #[graphql(description = "When the reservation was checked in")]
+    pub checked_in_at: Option<NaiveDateTime>,
+}
+
+impl From<DbAttendee> for EventReservation {
+    fn from(attendee: DbAttendee) -> Self {
+        EventReservation {
+            id: attendee.reservation_id,
+            reserved_at: attendee.reserved_at,
+            username: attendee.username,
+            phone_number: attendee.phone_number.as_deref().map(mask_phone_number),
+            ticket_name: attendee.ticket_name,
+            quantity: attendee.quantity,
+            redeemed: attendee.checked_in_at.is_some(),
+            checked_in_at: attendee.checked_in_at,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a ticket reservation checked in at its event")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckedInReservation {
+    #[graphql(description = "The reservation's id")]
+    pub id: Uuid,
+    #[graphql(description = "The reserved ticket id")]
+    pub ticket_id: Uuid,
+    #[graphql(description = "The number of tickets reserved")]
+    pub quantity: i32,
+    #[graphql(description = "When the reservation was checked in")]
+    pub checked_in_at: Option<NaiveDateTime>,
+}
+
+impl From<DbTicketReservation> for CheckedInReservation {
+    fn from(reservation: DbTicketReservation) -> Self {
+        CheckedInReservation {
+            id: reservation.id,
+            ticket_id: reservation.ticket_id,
+            quantity: reservation.quantity,
+            checked_in_at: reservation.checked_in_at,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql request type for transferring a ticket to another user")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTicketTransfer {
+    #[graphql(description = "The reservation id of the ticket to transfer")]
+    pub reservation_id: Uuid,
+    #[graphql(description = "The username of the receiving user")]
+    pub to_username: String,
+    #[graphql(description = "The price the ticket is transferred for in yoctoNEAR, if any")]
+    pub price: Option<NearAmount>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql request type for listing a ticket for resale")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTicketListing {
+    #[graphql(description = "The reservation id of the ticket to resell")]
+    pub reservation_id: Uuid,
+    #[graphql(description = "The asking price in yoctoNEAR, up to the ticket's max release price")]
+    pub asking_price: NearAmount,
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a ticket offered for resale")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketListing {
+    #[graphql(description = "The listing's id")]
+    pub id: Uuid,
+    #[graphql(description = "The listing's creation date")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "The asking price in yoctoNEAR")]
+    pub asking_price: NearAmount,
+    #[graphql(description = "The listed reservation id")]
+    pub reservation_id: Uuid,
+    #[graphql(description = "The listed ticket id")]
+    pub ticket_id: Uuid,
+    #[graphql(description = "The ticket's event id")]
+    pub event_id: Uuid,
+    #[graphql(description = "The reselling user id")]
+    pub seller_id: Uuid,
+    #[graphql(description = "When the listing was cancelled, if it was")]
+    pub cancelled_at: Option<NaiveDateTime>,
+}
+
+impl From<DbTicketListing> for TicketListing {
+    fn from(listing: DbTicketListing) -> Self {
+        TicketListing {
+            id: listing.id,
+            created_at: listing.created_at,
+            asking_price: listing.asking_price,
+            reservation_id: listing.reservation_id,
+            ticket_id: listing.ticket_id,
+            event_id: listing.event_id,
+            seller_id: listing.seller_id,
+            cancelled_at: listing.cancelled_at,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql request type for buying tickets as a gift")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTicketGift {
+    #[graphql(description = "The id of the gifted ticket")]
+    pub ticket_id: Uuid,
+    #[graphql(description = "The number of tickets gifted")]
+    pub quantity: i32,
+    #[graphql(description = "The username of the recipient, if not gifted by phone number")]
+    pub to_username: Option<String>,
+    #[graphql(description = "The phone number of the recipient, if not gifted by username")]
+    pub to_phone_number: Option<String>,
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for tickets bought as a gift")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketGift {
+    #[graphql(description = "The gift's id")]
+    pub id: Uuid,
+    #[graphql(description = "The gift's creation date")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "The gifted reservation id")]
+    pub reservation_id: Uuid,
+    #[graphql(description = "The sending user id")]
+    pub from_user: Uuid,
+    #[graphql(description = "The receiving user id, if gifted by username")]
+    pub to_user: Option<Uuid>,
+    #[graphql(description = "The masked phone number the claim code was sent to")]
+    pub to_phone_number: String,
+    #[graphql(description = "The user who claimed the gift, once claimed")]
+    pub claimed_by: Option<Uuid>,
+    #[graphql(description = "When the gift was claimed")]
+    pub claimed_at: Option<NaiveDateTime>,
+}
+
+impl From<DbTicketGift> for TicketGift {
+    fn from(gift: DbTicketGift) -> Self {
+        TicketGift {
+            id: gift.id,
+            created_at: gift.created_at,
+            reservation_id: gift.reservation_id,
+            from_user: gift.from_user,
+            to_user: gift.to_user,
+            to_phone_number: mask_phone_number(&gift.to_phone_number),
+            claimed_by: gift.claimed_by,
+            claimed_at: gift.claimed_at,
+        }
+    }
+}
+
+//-------------------------------ASSET UPLOADS---------------------------------------//
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql request type for a presigned asset upload url")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewUploadUrl {
+    #[graphql(description = "The event the asset belongs to")]
+    pub event_id: Uuid,
+    #[graphql(description = "The asset's mime type (image/jpeg, image/png or image/webp)")]
+    pub content_type: String,
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql response type for a presigned asset upload url")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadUrl {
+    #[graphql(description = "The asset id to confirm once the upload is done")]
+    pub asset_id: Uuid,
+    #[graphql(description = "The presigned url to PUT the asset to")]
+    pub upload_url: String,
+    #[graphql(description = "The content type header the PUT request must carry")]
+    pub content_type: String,
+    #[graphql(description = "The upload url's expiry date")]
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql response type for a presigned download url")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadUrl {
+    #[graphql(description = "The presigned url to GET the file from")]
+    pub download_url: String,
+    #[graphql(description = "The download url's expiry date")]
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql request type for attaching an uploaded asset to its event")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmAsset {
+    #[graphql(description = "The uploaded asset id")]
+    pub asset_id: Uuid,
+    #[graphql(description = "Which event image the asset becomes")]
+    pub kind: EventAssetKind,
+}
+
+/// What an asset is to its event
+#[repr(i16)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, GraphQLEnum)]
+pub enum AssetRole {
+    #[graphql(name = "COVER_PHOTO")]
+    CoverPhoto = 0,
+    #[graphql(name = "THUMBNAIL")]
+    Thumbnail = 1,
+    #[graphql(name = "GALLERY")]
+    Gallery = 2,
+}
+
+impl From<AssetRole> for i16 {
+    fn from(asset_role: AssetRole) -> i16 {
+        asset_role as i16
+    }
+}
+
+impl TryFrom<i16> for AssetRole {
+    type Error = GqlError;
+
+    fn try_from(n: i16) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(AssetRole::CoverPhoto),
+            1 => Ok(AssetRole::Thumbnail),
+            2 => Ok(AssetRole::Gallery),
+            _ => Err(GqlError::UnknownAssetRole(n.to_string())),
+        }
+    }
+}
+
+impl From<EventAssetKind> for AssetRole {
+    fn from(kind: EventAssetKind) -> Self {
+        match kind {
+            EventAssetKind::CoverPhoto => AssetRole::CoverPhoto,
+            EventAssetKind::Thumbnail => AssetRole::Thumbnail,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a seller's webhook, its secret only shown when registered")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellerWebhook {
+    #[graphql(description = "The url deliveries are posted to")]
+    pub url: String,
+    #[graphql(description = "The webhook's registration date")]
+    pub created_at: NaiveDateTime,
+    #[graphql(
+        description = "The key of the HMAC-SHA256 signature of every delivery, in the X-Webhook-Signature header"
+    )]
+    pub secret: Option<String>,
+}
+
+impl From<DbSellerWebhook> for SellerWebhook {
+    fn from(db_webhook: DbSellerWebhook) -> Self {
+        SellerWebhook {
+            url: db_webhook.url,
+            created_at: db_webhook.created_at,
+            secret: None,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a cancelled reservation and its refund")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketCancellation {
+    #[graphql(description = "The cancelled reservation's id")]
+    pub id: Uuid,
+    #[graphql(description = "When the reservation was cancelled")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "When the tickets were reserved")]
+    pub reserved_at: NaiveDateTime,
+    #[graphql(description = "The event's id")]
+    pub event_id: Uuid,
+    #[graphql(description = "The ticket's id")]
+    pub ticket_id: Uuid,
+    #[graphql(description = "The number of tickets cancelled")]
+    pub quantity: i32,
+    #[graphql(description = "What the buyer paid and is refunded, in yoctoNEAR")]
+    pub refund_amount: NearAmount,
+    #[graphql(description = "When the refund was sent, none while it is pending")]
+    pub refunded_at: Option<NaiveDateTime>,
+    #[graphql(description = "The refund transaction hash")]
+    pub refund_tx_hash: Option<String>,
+}
+
+impl From<DbTicketCancellation> for TicketCancellation {
+    fn from(db_cancellation: DbTicketCancellation) -> Self {
+        TicketCancellation {
+            id: db_cancellation.id,
+            created_at: db_cancellation.created_at,
+            reserved_at: db_cancellation.reserved_at,
+            event_id: db_cancellation.event_id,
+            ticket_id: db_cancellation.ticket_id,
+            quantity: db_cancellation.quantity,
+            refund_amount: db_cancellation.refund_amount,
+            refunded_at: db_cancellation.refunded_at,
+            refund_tx_hash: db_cancellation.refund_tx_hash,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for the maintenance mode of the api")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceMode {
+    #[graphql(description = "Whether the api turns away everyone but the admins")]
+    pub enabled: bool,
+    #[graphql(description = "Whether the config forces the maintenance mode on")]
+    pub forced: bool,
+    #[graphql(description = "The message shown to the clients turned away")]
+    pub message: Option<String>,
+    #[graphql(description = "How long the clients turned away wait before retrying")]
+    pub retry_after_secs: i32,
+    #[graphql(description = "When the admins last set the maintenance mode")]
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a cancelled event and the reservations cancelled with it")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelledEvent {
+    #[graphql(description = "The cancelled event")]
+    pub event: Event,
+    #[graphql(description = "The cancelled reservations, their buyers are refunded")]
+    pub cancellations: Vec<TicketCancellation>,
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for an image of an event's gallery")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GalleryImage {
+    #[graphql(description = "The image's asset id")]
+    pub asset_id: Uuid,
+    #[graphql(description = "The image's url")]
+    pub url: String,
+    #[graphql(description = "The image's position in the gallery, starting at 0")]
+    pub position: i32,
+}
+
+impl GalleryImage {
+    /// The gallery image of the asset, none unless it is in a gallery
+    pub fn new(asset_file: &AssetFile, storage: &dyn Storage) -> Option<Self> {
+        match (asset_file.asset_role, asset_file.position) {
+            (Some(AssetRole::Gallery), Some(position)) => Some(GalleryImage {
+                asset_id: asset_file.id,
+                url: storage.asset_url(asset_file.s3_absolute_key.clone()),
+                position,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for an uploaded asset of an event, as listed to its editors")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventAsset {
+    #[graphql(description = "The asset's id")]
+    pub id: Uuid,
+    #[graphql(description = "The asset's url")]
+    pub url: String,
+    #[graphql(description = "The asset's content type")]
+    pub content_type: Option<String>,
+    #[graphql(description = "Whether the upload was confirmed")]
+    pub is_confirmed: bool,
+    #[graphql(description = "The asset's ipfs hash, once pinned")]
+    pub ipfs_hash: Option<String>,
+    #[graphql(description = "What the asset is to its event, none until it is attached")]
+    pub asset_role: Option<AssetRole>,
+    #[graphql(description = "The asset's position in the gallery, gallery assets only")]
+    pub position: Option<i32>,
+}
+
+impl EventAsset {
+    pub fn new(asset_file: AssetFile, url: String) -> Self {
+        EventAsset {
+            id: asset_file.id,
+            url,
+            content_type: asset_file.content_type,
+            is_confirmed: asset_file.is_confirmed,
+            ipfs_hash: asset_file.ipfs_hash,
+            asset_role: asset_file.asset_role,
+            position: asset_file.position,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql request type for reordering an event's gallery")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorderGallery {
+    #[graphql(description = "The event whose gallery is reordered")]
+    pub event_id: Uuid,
+    #[graphql(description = "Every asset id of the gallery, in their new order")]
+    pub asset_ids: Vec<Uuid>,
+}
+
+//-------------------------------AUDIT LOG---------------------------------------//
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for an audit log entry")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    #[graphql(description = "The entry's id")]
+    pub id: Uuid,
+    #[graphql(description = "When the change happened")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "The user that made the change, if known")]
+    pub actor_id: Option<Uuid>,
+    #[graphql(description = "The performed action, e.g. update_event")]
+    pub action: String,
+    #[graphql(description = "The changed entity's type, e.g. event")]
+    pub entity_type: String,
+    #[graphql(description = "The changed entity's id")]
+    pub entity_id: Uuid,
+    #[graphql(description = "The submitted change as json, if any")]
+    pub diff: Option<String>,
+    #[graphql(description = "The super admin that made the change as the actor, if any")]
+    pub impersonated_by: Option<Uuid>,
+}
+
+impl From<DbAuditLog> for AuditEntry {
+    fn from(audit_log: DbAuditLog) -> Self {
+        AuditEntry {
+            id: audit_log.id,
+            created_at: audit_log.created_at,
+            actor_id: audit_log.actor_id,
+            action: audit_log.action,
+            entity_type: audit_log.entity_type,
+            entity_id: audit_log.entity_id,
+            diff: audit_log.diff,
+            impersonated_by: audit_log.impersonated_by,
+        }
+    }
+}
+
+//--------------------------ORGANIZATIONS---------------------------------
+
+/// The role of an organization member
+#[repr(i16)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, GraphQLEnum)]
+pub enum MemberRole {
+    #[graphql(name = "OWNER")]
+    Owner = 0,
+    #[graphql(name = "EDITOR")]
+    Editor = 1,
+    #[graphql(name = "SCANNER")]
+    Scanner = 2,
+}
+
+impl From<MemberRole> for i16 {
+    fn from(member_role: MemberRole) -> i16 {
+        member_role as i16
+    }
+}
+
+impl TryFrom<i16> for MemberRole {
+    type Error = GqlError;
+
+    fn try_from(n: i16) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(MemberRole::Owner),
+            1 => Ok(MemberRole::Editor),
+            2 => Ok(MemberRole::Scanner),
+            _ => Err(GqlError::UnknownMemberRole(n.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for MemberRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemberRole::Owner => write!(f, "owner"),
+            MemberRole::Editor => write!(f, "editor"),
+            MemberRole::Scanner => write!(f, "scanner"),
+        }
+    }
+}
+
+//-------------------------------SELLER ONBOARDING---------------------------------------//
+
+/// The kind of a document a seller submits for review
+#[repr(i16)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, GraphQLEnum)]
+pub enum DocumentKind {
+    #[graphql(name = "IDENTITY")]
+    Identity = 0,
+    #[graphql(name = "PROOF_OF_ADDRESS")]
+    ProofOfAddress = 1,
+    #[graphql(name = "BUSINESS_REGISTRATION")]
+    BusinessRegistration = 2,
+}
+
+impl From<DocumentKind> for i16 {
+    fn from(document_kind: DocumentKind) -> i16 {
+        document_kind as i16
+    }
+}
+
+impl TryFrom<i16> for DocumentKind {
+    type Error = GqlError;
+
+    fn try_from(n: i16) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(DocumentKind::Identity),
+            1 => Ok(DocumentKind::ProofOfAddress),
+            2 => Ok(DocumentKind::BusinessRegistration),
+            _ => Err(GqlError::UnknownDocumentKind(n.to_string())),
+        }
+    }
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Gql request type for a presigned seller document upload url")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewSellerDocument {
+    #[graphql(description = "What the document proves")]
+    pub kind: DocumentKind,
+    #[graphql(description = "The document's mime type (application/pdf, image/jpeg or image/png)")]
+    pub content_type: String,
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a document a seller submitted for review")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SellerDocument {
+    #[graphql(description = "The document's id")]
+    pub id: Uuid,
+    #[graphql(description = "The document's upload date")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "What the document proves")]
+    pub kind: DocumentKind,
+    #[graphql(description = "The document's mime type")]
+    pub content_type: String,
+    #[graphql(description = "Whether the upload was confirmed")]
+    pub is_confirmed: bool,
+    #[graphql(description = "A presigned url to review the document, for admins only")]
+    pub download_url: Option<String>,
+}
+
+impl From<DbSellerDocument> for SellerDocument {
+    fn from(document: DbSellerDocument) -> Self {
+        SellerDocument {
+            id: document.id,
+            created_at: document.created_at,
+            kind: document.document_kind,
+            content_type: document.content_type,
+            is_confirmed: document.is_confirmed,
+            download_url: None,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for an organization of sellers managing events together")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Organization {
+    #[graphql(description = "The organization's id")]
+    pub id: Uuid,
+    #[graphql(description = "The organization's name")]
+    pub name: String,
+    #[graphql(description = "The organization's slug")]
+    pub slug: String,
+    #[graphql(description = "The organization's creation date")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "The organization's creator id")]
+    pub created_by_user: Uuid,
+}
+
+impl From<DbOrganization> for Organization {
+    fn from(organization: DbOrganization) -> Self {
+        Organization {
+            id: organization.id,
+            name: organization.name,
+            slug: organization.slug,
+            created_at: organization.created_at,
+            created_by_user: organization.created_by_user,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a member of an organization")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationMember {
+    #[graphql(description = "The organization's id")]
+    pub organization_id: Uuid,
+    #[graphql(description = "The member's user id")]
+    pub user_id: Uuid,
+    #[graphql(description = "The member's role")]
+    pub member_role: MemberRole,
+    #[graphql(description = "When the user joined the organization")]
+    pub created_at: NaiveDateTime,
+}
+
+impl From<DbOrganizationMember> for OrganizationMember {
+    fn from(member: DbOrganizationMember) -> Self {
+        OrganizationMember {
+            organization_id: member.organization_id,
+            user_id: member.user_id,
+            member_role: member.member_role,
+            created_at: member.created_at,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a seller co-hosting an event with its creator")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventCollaborator {
+    #[graphql(description = "The event's id")]
+    pub event_id: Uuid,
+    #[graphql(description = "The collaborator's user id")]
+    pub user_id: Uuid,
+    #[graphql(description = "The collaborator's role, EDITOR or SCANNER")]
+    pub collaborator_role: MemberRole,
+    #[graphql(description = "When the user became a collaborator")]
+    pub created_at: NaiveDateTime,
+}
+
+impl From<DbEventCollaborator> for EventCollaborator {
+    fn from(collaborator: DbEventCollaborator) -> Self {
+        EventCollaborator {
+            event_id: collaborator.event_id,
+            user_id: collaborator.user_id,
+            collaborator_role: collaborator.collaborator_role,
+            created_at: collaborator.created_at,
+        }
+    }
+}
+
+//--------------------------PAYOUTS---------------------------------
+
+/// Payout request status
+#[repr(i16)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, GraphQLEnum)]
+pub enum PayoutStatus {
+    #[graphql(name = "PENDING")]
+    Pending = 0,
+    #[graphql(name = "APPROVED")]
+    Approved = 1,
+    #[graphql(name = "PAID")]
+    Paid = 2,
+    #[graphql(name = "REJECTED")]
+    Rejected = 3,
+    /// the transfer errored, whether it went through is for an admin to check on chain
+    #[graphql(name = "FAILED")]
+    Failed = 4,
+}
+
+impl From<PayoutStatus> for i16 {
+    fn from(payout_status: PayoutStatus) -> i16 {
+        payout_status as i16
+    }
+}
+
+impl TryFrom<i16> for PayoutStatus {
+    type Error = GqlError;
+
+    fn try_from(n: i16) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(PayoutStatus::Pending),
+            1 => Ok(PayoutStatus::Approved),
+            2 => Ok(PayoutStatus::Paid),
+            3 => Ok(PayoutStatus::Rejected),
+            4 => Ok(PayoutStatus::Failed),
+            _ => Err(GqlError::UnknownPayoutStatus(n.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for PayoutStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayoutStatus::Pending => write!(f, "pending"),
+            PayoutStatus::Approved => write!(f, "approved"),
+            PayoutStatus::Paid => write!(f, "paid"),
+            PayoutStatus::Rejected => write!(f, "rejected"),
+            PayoutStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a seller's payout account")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutAccount {
+    #[graphql(description = "The payout account's id")]
+    pub id: Uuid,
+    #[graphql(description = "The payout account's creation date")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "The payout account's last update date")]
+    pub updated_at: NaiveDateTime,
+    #[graphql(description = "The NEAR wallet payouts are sent to")]
+    pub wallet_id: String,
+    #[graphql(description = "The payout account's owner id")]
+    pub user_id: Uuid,
+}
+
+impl From<DbPayoutAccount> for PayoutAccount {
+    fn from(payout_account: DbPayoutAccount) -> Self {
+        PayoutAccount {
+            id: payout_account.id,
+            created_at: payout_account.created_at,
+            updated_at: payout_account.updated_at,
+            wallet_id: payout_account.wallet_id,
+            user_id: payout_account.user_id,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a seller's payout request")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutRequest {
+    #[graphql(description = "The payout request's id")]
+    pub id: Uuid,
+    #[graphql(description = "The payout request's creation date")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "The requested amount in yoctoNEAR")]
+    pub amount: NearAmount,
+    #[graphql(description = "The NEAR wallet the payout is sent to")]
+    pub wallet_id: String,
+    #[graphql(description = "The payout request's status")]
+    pub payout_status: PayoutStatus,
+    #[graphql(description = "The id of the admin who reviewed the request")]
+    pub reviewed_by: Option<Uuid>,
+    #[graphql(description = "The payout request's review date")]
+    pub reviewed_at: Option<NaiveDateTime>,
+    #[graphql(description = "The payout transaction hash")]
+    pub tx_hash: Option<String>,
+    #[graphql(description = "The requesting seller's id")]
+    pub user_id: Uuid,
+}
+
+impl From<DbPayoutRequest> for PayoutRequest {
+    fn from(payout_request: DbPayoutRequest) -> Self {
+        PayoutRequest {
+            id: payout_request.id,
+            created_at: payout_request.created_at,
+            amount: payout_request.amount,
+            wallet_id: payout_request.wallet_id,
+            payout_status: payout_request.payout_status,
+            reviewed_by: payout_request.reviewed_by,
+            reviewed_at: payout_request.reviewed_at,
+            tx_hash: payout_request.tx_hash,
+            user_id: payout_request.user_id,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for NEAR sent to a buyer's wallet to pay for its gas")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletTopUp {
+    #[graphql(description = "The top-up's id")]
+    pub id: Uuid,
+    #[graphql(description = "The top-up's date")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "The topped up buyer's id")]
+    pub user_id: Uuid,
+    #[graphql(description = "The NEAR wallet topped up")]
+    pub wallet_id: String,
+    #[graphql(description = "The sent amount in yoctoNEAR")]
+    pub amount: NearAmount,
+    #[graphql(description = "The funding transaction hash")]
+    pub tx_hash: Option<String>,
+    #[graphql(description = "The id of the admin who triggered the top-up, if manual")]
+    pub triggered_by: Option<Uuid>,
+}
+
+impl From<DbWalletTopUp> for WalletTopUp {
+    fn from(wallet_top_up: DbWalletTopUp) -> Self {
+        WalletTopUp {
+            id: wallet_top_up.id,
+            created_at: wallet_top_up.created_at,
+            user_id: wallet_top_up.user_id,
+            wallet_id: wallet_top_up.wallet_id,
+            amount: wallet_top_up.amount,
+            tx_hash: wallet_top_up.tx_hash,
+            triggered_by: wallet_top_up.triggered_by,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a seller's payout balance")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayoutBalance {
+    #[graphql(description = "Total ticket sales after promo discounts, in yoctoNEAR")]
+    pub total_sales: NearAmount,
+    #[graphql(description = "Total of the pending, approved and paid requests, in yoctoNEAR")]
+    pub total_requested: NearAmount,
+    #[graphql(description = "Amount that can still be requested, in yoctoNEAR")]
+    pub available: NearAmount,
+}
+
+impl From<DbPayoutBalance> for PayoutBalance {
+    fn from(payout_balance: DbPayoutBalance) -> Self {
+        PayoutBalance {
+            total_sales: payout_balance.total_sales,
+            total_requested: payout_balance.total_requested,
+            available: payout_balance.available(),
+        }
+    }
+}
+
+//--------------------------CATEGORIES & TAGS---------------------------------
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for an event category")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Category {
+    #[graphql(description = "The category's id")]
+    pub id: Uuid,
+    #[graphql(description = "The category's creation date")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "The category's name")]
+    pub name: String,
+    #[graphql(description = "The category's slug, used to filter events")]
+    pub slug: String,
+}
+
+impl From<DbCategory> for Category {
+    fn from(category: DbCategory) -> Self {
+        Category {
+            id: category.id,
+            created_at: category.created_at,
+            name: category.name,
+            slug: category.slug,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a tag and the number of listed events using it")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    #[graphql(description = "The tag")]
+    pub tag: String,
+    #[graphql(description = "The number of listed events with the tag")]
+    pub event_count: i32,
+}
+
+impl From<DbTagCount> for TagCount {
+    fn from(tag_count: DbTagCount) -> Self {
+        TagCount {
+            tag: tag_count.tag,
+            event_count: tag_count.event_count as i32,
+        }
+    }
+}
+
+//--------------------------NOTIFICATIONS---------------------------------
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a notification of the user's inbox")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboxNotification {
+    #[graphql(description = "The notification's id")]
+    pub id: Uuid,
+    #[graphql(description = "When the notification was sent")]
+    pub created_at: NaiveDateTime,
+    #[graphql(description = "What the notification is about")]
+    pub kind: NotificationKind,
+    #[graphql(description = "The notification's text")]
+    pub message: String,
+    #[graphql(description = "The details of the notification as json, e.g. the event id")]
+    pub data: Option<String>,
+    #[graphql(description = "When the user read the notification, unread if missing")]
+    pub read_at: Option<NaiveDateTime>,
+}
+
+impl From<DbNotification> for InboxNotification {
+    fn from(notification: DbNotification) -> Self {
+        InboxNotification {
+            id: notification.id,
+            created_at: notification.created_at,
+            kind: notification.kind,
+            message: notification.message,
+            data: notification.data,
+            read_at: notification.read_at,
+        }
+    }
+}
+
+#[derive(juniper::GraphQLObject)]
+#[graphql(description = "Gql type for a page of the user's inbox")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Inbox {
+    #[graphql(description = "The notifications of the page, the latest first")]
+    pub notifications: Vec<InboxNotification>,
+    #[graphql(description = "The number of unread notifications, on any page")]
+    pub unread_count: i32,
 }
@@ -3,30 +3,161 @@ use super::{
     models::{Event, NewEvent, UpdateEvent},
 };
 use crate::{
-    auth::Role,
+    audit::AuditEntity,
+    auth::{create_impersonation_jwt, Role, SellerStatus},
+    cancellations,
+    config::TotpConfig,
     db::{
-        models::{AssetFile, DbEvent, DbTicket},
+        models::{
+            AssetFile, DbCategory, DbEvent, DbEventCollaborator, DbEventSeries, DbImpersonation,
+            DbMintJob, DbOrganization, DbOrganizationMember, DbPayoutAccount, DbPayoutRequest,
+            DbPromoCode, DbSeat, DbSeatSection, DbSellerDocument, DbSellerPhoneVerification,
+            DbSellerWebhook, DbTicket, DbTicketGift, DbTicketListing, DbTicketPriceTier,
+            DbTicketReservation, DbTicketTransfer, DbUser, DbUserTotp, DbWaitlistEntry,
+        },
         sql::{
-            db_delete_event_by_id, db_delete_ticket_by_id, db_get_event_by_id,
-            db_get_event_by_name, db_get_event_by_slug, db_get_ticket_by_id, db_get_ticket_by_slug,
-            db_get_tickets_by_event_id, db_get_user_by_id, db_insert_event, db_insert_ticket,
-            db_update_event, db_update_ticket, insert_asset_file,
+            db_add_gallery_asset, db_anonymize_user, db_cancel_ticket_listing,
+            db_cancel_ticket_listings_by_reservation_id, db_check_in_ticket_reservations,
+            db_claim_ticket_gift, db_clear_login_attempts, db_complete_payout_request,
+            db_complete_seller_phone_verification, db_confirm_asset_file,
+            db_confirm_seller_document, db_consume_buyer_recovery_session,
+            db_count_audit_logs_since, db_delete_event_collaborator, db_delete_organization_member,
+            db_delete_seller_webhook, db_delete_ticket_price_tier, db_delete_waitlist_entry,
+            db_enable_user_totp, db_get_active_ticket_listing_by_reservation_id, db_get_asset_file,
+            db_get_buyer_recovery_session_by_id, db_get_category_by_slug, db_get_event_by_id,
+            db_get_event_by_name, db_get_event_by_slug, db_get_event_tags, db_get_files_for_event,
+            db_get_gallery_assets_by_event_ids, db_get_latest_seller_phone_verification,
+            db_get_organization_by_id, db_get_organization_by_slug, db_get_organization_member,
+            db_get_payout_account_by_user_id, db_get_payout_balance,
+            db_get_price_tiers_by_ticket_ids, db_get_promo_code_by_code, db_get_promo_code_by_id,
+            db_get_seat_sections_by_event_id, db_get_seller_document,
+            db_get_seller_documents_by_user_id, db_get_ticket_by_id,
+            db_get_ticket_gift_by_claim_code, db_get_ticket_gift_by_reservation_id,
+            db_get_ticket_price_tier, db_get_ticket_reservation_by_id,
+            db_get_ticket_reservations_by_code, db_get_ticket_reservations_by_user_id,
+            db_get_tickets_by_event_id, db_get_user_by_id, db_get_user_by_phone_number,
+            db_get_user_by_username, db_get_user_totp, db_get_waitlist_entry, db_hold_seats,
+            db_increment_buyer_recovery_session_attempts,
+            db_increment_seller_phone_verification_attempts, db_insert_category,
+            db_insert_event_series, db_insert_event_with_free_slug, db_insert_event_with_tickets,
+            db_insert_impersonation, db_insert_mint_job, db_insert_organization,
+            db_insert_payout_request, db_insert_promo_code, db_insert_seat_section,
+            db_insert_seller_document, db_insert_seller_phone_verification, db_insert_ticket_gift,
+            db_insert_ticket_listing, db_insert_ticket_price_tier, db_insert_ticket_transfer,
+            db_insert_ticket_with_free_slug, db_insert_waitlist_entry, db_is_email_taken,
+            db_is_seller_slug_taken, db_is_ticket_seated, db_is_username_taken,
+            db_mark_notification_read, db_purge_event_by_id, db_release_seats,
+            db_remove_gallery_asset, db_reorder_gallery_assets, db_reserve_ticket, db_review_event,
+            db_revoke_impersonation, db_revoke_user_sessions, db_set_event_tags,
+            db_soft_delete_event_by_id, db_soft_delete_ticket_by_id, db_submit_event_for_review,
+            db_update_event, db_update_event_archived, db_update_event_asset_url,
+            db_update_event_cancellation_window, db_update_event_category,
+            db_update_event_organization, db_update_event_tickets_archived,
+            db_update_payout_request_status, db_update_promo_code_is_active,
+            db_update_seller_status, db_update_ticket, db_update_ticket_reservation_owner,
+            db_update_user_encrypted_secret_key, db_update_user_event_reminders_opt_out,
+            db_update_user_locale, db_update_user_password, db_update_user_profile,
+            db_update_user_seller_slug, db_upsert_event_collaborator,
+            db_upsert_organization_member, db_upsert_payout_account, db_upsert_seller_webhook,
+            db_upsert_user_totp, insert_asset_file, sql_timestamp,
         },
     },
+    error::{Error, TicketUpdateError},
+    geo::geocode_venue,
     gql::{
+        clone::{
+            clone_asset_file, clone_event, clone_event_name, occurrence_event_name,
+            occurrence_overrides, MAX_CLONE_NAME_ATTEMPTS,
+        },
         error::ValidationError,
+        guard::{guard, guard_event},
+        mint::{mintable_ticket, MintPayload},
         models::{
-            EventStatus, NewMintNftsRequest, NewMintNftsResponse, NewTicket, Ticket, UpdateTicket,
+            AssetRole, CancelledEvent, Category, ChangePassword, CheckedInReservation,
+            CloneEventOverrides, ConfirmAsset, EventCollaborator, EventSeries, EventStatus,
+            ExportWallet, Impersonation, InboxNotification, MaintenanceMode, MemberRole,
+            NewMintNftsRequest, NewMintNftsResponse, NewPriceTier, NewPromoCode, NewSeatSection,
+            NewSellerDocument, NewTicket, NewTicketGift, NewTicketListing, NewTicketTransfer,
+            NewUploadUrl, NotificationKind, Organization, OrganizationMember, PayoutAccount,
+            PayoutRequest, PayoutStatus, PriceTier, PromoCode, ReorderGallery, RotateWalletSecret,
+            SeatHold, SeatSection, SellerDocument, SellerPhoneVerification, SellerWebhook,
+            SignupAttempt, Ticket, TicketCancellation, TicketGift, TicketListing, TicketTransfer,
+            TotpEnrollment, UpdateProfile, UpdateTicket, UploadUrl, User, WaitlistEntry,
+            WalletExport, WalletTopUp,
         },
         schema::Context as ResourcesContext,
         validations::{
-            check_new_ticket_payload, update_event_mutation_payload, update_ticket_mutation_payload,
+            check_cancellation_window, check_category_name, check_change_password_payload,
+            check_document_content_type, check_event_tags, check_export_wallet_payload,
+            check_gallery_order, check_maintenance_payload, check_new_price_tier_payload,
+            check_new_promo_code_payload, check_new_seat_section_payload, check_new_ticket_payload,
+            check_organization_name, check_payout_wallet_id, check_rejection_reason,
+            check_rotate_wallet_secret_payload, check_seller_slug, check_ticket_gift_payload,
+            check_ticket_listing_payload, check_ticket_transfer_payload,
+            check_update_profile_payload, check_upload_content_type, check_webhook_url,
+            sanitize_text_field, update_event_mutation_payload, update_ticket_mutation_payload,
+            GiftRecipient, WalletExportVerification, MAX_GALLERY_IMAGES, MAX_PRICE_TIERS,
+        },
+    },
+    i18n::supported_locale,
+    inbox,
+    jobs::{
+        balances::sync_wallet_balance,
+        models::{EventAssetKind, JobPayload},
+        queue::enqueue,
+        top_ups::{top_up_wallet, wallet_top_ups_total, TopUpLimits},
+    },
+    maintenance,
+    near::NearAmount,
+    notifier::{Gift, Notification},
+    phone::normalize_phone_number,
+    policy::{event_policy, EventAccess, Operation},
+    pricing::{claim_ticket_price, release_ticket_price},
+    realtime::{outbox_message, EventUpdate},
+    recurrence::Recurrence,
+    sanitize::TextField,
+    security::{
+        password::{hash_password, verify_password},
+        password_policy::check_password,
+        totp::{
+            decrypt_secret, encrypt_secret, gen_secret, provisioning_uri, verify as verify_totp,
         },
     },
-    grpc::near_api::MintNftsResponse,
+    signups,
+    stock_alerts::alert_stock,
+    usernames::check_username as check_username_allowed,
+    webhooks::gen_webhook_secret,
 };
 use slugify::slugify;
+use std::time::Duration;
 use uuid::Uuid;
+use wasmium_random::WasmiumRandom;
+
+/// How long a presigned upload url stays valid
+const UPLOAD_URL_EXPIRY_SECS: u64 = 900;
+/// The period the wallet exports of a buyer are limited per
+const WALLET_EXPORTS_WINDOW_SECS: i64 = 86400;
+
+/// The totp settings, two-factor authentication is unavailable without them
+fn totp_config(ctx: &ResourcesContext) -> Result<&TotpConfig, GqlError> {
+    ctx.totp.as_ref().ok_or_else(|| {
+        GqlError::Forbidden(ValidationError::new(
+            "totp",
+            "Two-factor authentication is not available",
+        ))
+    })
+}
+
+/// Sellers change their documents until they submit them, and again once rejected
+fn check_seller_documents_editable(db_user: &DbUser) -> Result<(), GqlError> {
+    match db_user.seller_status {
+        Some(SellerStatus::Onboarding) | Some(SellerStatus::Rejected) => Ok(()),
+        _ => Err(GqlError::Conflict(ValidationError::new(
+            "seller_status",
+            "Seller onboarding has already been submitted",
+        ))),
+    }
+}
 
 #[derive(Copy, Clone, Default)]
 pub struct PublicMutationRoot;
@@ -37,6 +168,8 @@ impl PublicMutationRoot {
         Ok("v1.0".into())
     }
 }
+
+/// The account of any signed in user, served on `/graphql/private`
 #[derive(Copy, Clone, Default)]
 pub struct PrivateMutationRoot;
 
@@ -46,13 +179,8 @@ impl PrivateMutationRoot {
         Ok("v1.0".into())
     }
 
-    // -------------------------- NFTS ------------------- //
-
-    // seller mint nft tickets
-    async fn mint_nfts(
-        request: NewMintNftsRequest,
-        ctx: &ResourcesContext,
-    ) -> Result<NewMintNftsResponse, GqlError> {
+    // -------------------------- USERS ------------------- //
+    async fn refresh_my_balance(ctx: &ResourcesContext) -> Result<User, GqlError> {
         // get the requesting user_id
         let user_id = {
             let lock = ctx.user_id.lock().await;
@@ -66,100 +194,500 @@ impl PrivateMutationRoot {
         let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
             .await
             .map_err(|_| {
-                GqlError::Validation(ValidationError::new(
+                GqlError::NotFound(ValidationError::new(
                     "user_id",
                     "User not found in the database",
                 ))
             })?;
 
-        // check user is a seller
-        if !db_user.user_type.eq(&Role::Seller) {
-            return Err(GqlError::Validation(ValidationError::new(
-                "user_role",
-                "User role is not seller. Minting is only allowed for sellers",
-            )));
-        }
+        // fetch the on-chain balance and store it
+        let updated_db_user = sync_wallet_balance(ctx, &db_user)
+            .await
+            .map_err(|e| match e {
+                Error::Grpc(e) => GqlError::Grpc(e),
+                Error::Postgres(e) => GqlError::Database(e),
+                _ => GqlError::UnexpectedInternal,
+            })?;
 
-        // get the ticket from db
-        let ticket_id = Uuid::parse_str(&request.ticket_id).map_err(|_| GqlError::ParseUUID)?;
-        let db_ticket = db_get_ticket_by_id(&ctx.db_client, &ticket_id)
+        Ok(User::from(updated_db_user))
+    }
+
+    /// Deletes the caller's account: personal data is removed, sessions are revoked and the
+    /// wallet is flagged for review
+    async fn delete_my_account(ctx: &ResourcesContext) -> Result<bool, GqlError> {
+        let db_user = guard(ctx, Operation::DeleteMyAccount).await?;
+
+        let deleted_db_user = db_anonymize_user(&ctx.db_client, &db_user)
             .await
-            .map_err(|_| {
+            .map_err(GqlError::Database)?
+            .ok_or_else(|| {
                 GqlError::Validation(ValidationError::new(
-                    "ticket_id",
-                    "Ticket with submitted id does not exist",
+                    "user_id",
+                    "User account has been deleted",
                 ))
             })?;
 
-        // get the event id for that ticket
-        let mut db_event = db_get_event_by_id(&ctx.db_client, &db_ticket.event_id)
+        db_revoke_user_sessions(&ctx.db_client, &db_user.id, db_user.phone_number.as_deref())
             .await
-            .map_err(|_| {
-                GqlError::Validation(ValidationError::new(
-                    "event_id",
-                    "Event with submitted id does not exist",
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(db_user.id),
+            "delete_my_account",
+            AuditEntity::User(db_user.id),
+            Some(serde_json::json!({ "wallet_id": deleted_db_user.wallet_id })),
+        )
+        .await;
+
+        Ok(true)
+    }
+
+    /// Updates the given fields of the caller's profile, the username and email must not be
+    /// taken by another user
+    async fn update_profile(
+        ctx: &ResourcesContext,
+        update_profile: UpdateProfile,
+    ) -> Result<User, GqlError> {
+        let db_user = guard(ctx, Operation::UpdateProfile).await?;
+
+        let update_profile = check_update_profile_payload(&ctx.sanitation, update_profile)?;
+
+        if let Some(username) = &update_profile.username {
+            check_username_allowed(&ctx.usernames, username).map_err(|e| {
+                GqlError::Validation(ValidationError::new("username", &e.to_string()))
+            })?;
+            if db_is_username_taken(&ctx.db_client, username, &db_user.id)
+                .await
+                .map_err(GqlError::Database)?
+            {
+                return Err(GqlError::Conflict(ValidationError::new(
+                    "username",
+                    "Username is already taken",
+                )));
+            }
+        }
+        if let Some(email) = &update_profile.email {
+            if db_is_email_taken(&ctx.db_client, email, &db_user.id)
+                .await
+                .map_err(GqlError::Database)?
+            {
+                return Err(GqlError::Conflict(ValidationError::new(
+                    "email",
+                    "Email is already taken",
+                )));
+            }
+        }
+
+        let updated_db_user = db_update_user_profile(
+            &ctx.db_client,
+            &db_user.id,
+            update_profile.name.as_deref(),
+            update_profile.username.as_deref(),
+            update_profile.email.as_deref(),
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(|| {
+            GqlError::Validation(ValidationError::new(
+                "user_id",
+                "User account has been deleted",
+            ))
+        })?;
+        // only which fields changed, the profile is personal data
+        ctx.audit(
+            Some(db_user.id),
+            "update_profile",
+            AuditEntity::User(db_user.id),
+            Some(serde_json::json!({
+                "name": update_profile.name.is_some(),
+                "username": update_profile.username.is_some(),
+                "email": update_profile.email.is_some(),
+            })),
+        )
+        .await;
+
+        Ok(User::from(updated_db_user))
+    }
+
+    /// Sends a verification code to the seller's phone, verifying it unlocks the seller
+    /// operations
+    async fn send_seller_phone_code(
+        ctx: &ResourcesContext,
+        phone_number: String,
+    ) -> Result<SellerPhoneVerification, GqlError> {
+        let db_user = guard(ctx, Operation::SendSellerPhoneCode).await?;
+
+        let phone_number = normalize_phone_number(&phone_number).map_err(|_| {
+            GqlError::Validation(ValidationError::new("phone_number", "Invalid phone number"))
+        })?;
+        if let Ok(other_db_user) = db_get_user_by_phone_number(&ctx.db_client, &phone_number).await
+        {
+            if other_db_user.id.ne(&db_user.id) {
+                return Err(GqlError::Conflict(ValidationError::new(
+                    "phone_number",
+                    "Phone number is already taken",
+                )));
+            }
+        }
+
+        if let Some(latest) = db_get_latest_seller_phone_verification(&ctx.db_client, &db_user.id)
+            .await
+            .map_err(GqlError::Database)?
+        {
+            if latest.created_at > sql_timestamp(Some(-ctx.sessions.resend_cooldown_secs)) {
+                return Err(GqlError::Conflict(ValidationError::new(
+                    "phone_number",
+                    "A code was sent recently, try again later",
+                )));
+            }
+        }
+
+        let verification_code = WasmiumRandom::secure_numeric12()
+            .into_iter()
+            .take(6)
+            .map(|item| item.to_string())
+            .collect::<String>();
+        let db_verification = DbSellerPhoneVerification::new(
+            db_user.id,
+            phone_number.clone(),
+            ctx.codes.hash(&verification_code),
+            sql_timestamp(Some(ctx.sessions.code_ttl_secs)),
+        );
+        db_insert_seller_phone_verification(&ctx.db_client, &db_verification)
+            .await
+            .map_err(GqlError::Database)?;
+
+        enqueue(
+            &ctx.db_client,
+            JobPayload::Notify {
+                receiver: phone_number,
+                notification: Notification::Verification {
+                    code: verification_code,
+                },
+                locale: db_user.locale.clone(),
+            },
+            ctx.jobs.max_attempts,
+        )
+        .await
+        .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(db_user.id),
+            "send_seller_phone_code",
+            AuditEntity::User(db_user.id),
+            None,
+        )
+        .await;
+
+        Ok(SellerPhoneVerification::from(db_verification))
+    }
+
+    /// Verifies the code last sent to the seller's phone, the phone number is stored and the
+    /// seller becomes verified
+    async fn verify_seller_phone(ctx: &ResourcesContext, code: String) -> Result<User, GqlError> {
+        let db_user = guard(ctx, Operation::VerifySellerPhone).await?;
+
+        let db_verification = db_get_latest_seller_phone_verification(&ctx.db_client, &db_user.id)
+            .await
+            .map_err(GqlError::Database)?
+            .filter(|db_verification| db_verification.verified_at.is_none())
+            .ok_or_else(|| {
+                GqlError::NotFound(ValidationError::new(
+                    "code",
+                    "No verification code was sent",
                 ))
             })?;
+        let now = sql_timestamp(None);
+        if db_verification.expires_at < now {
+            return Err(GqlError::Validation(ValidationError::new(
+                "code",
+                "Verification code has expired",
+            )));
+        }
 
-        // make sure the event is in a DRAFT or MINTING states only
-        let allowed_states = vec![EventStatus::Draft, EventStatus::Minting];
-        if !allowed_states.contains(&db_event.event_status) {
+        // counted before checking, so concurrent guesses cannot go over the limit
+        let attempts =
+            db_increment_seller_phone_verification_attempts(&ctx.db_client, &db_verification.id)
+                .await
+                .map_err(GqlError::Database)?;
+        if attempts > ctx.sessions.max_attempts {
+            return Err(GqlError::Forbidden(ValidationError::new(
+                "code",
+                "Too many attempts, request a new code",
+            )));
+        }
+        if !ctx
+            .codes
+            .verify(&db_verification.verification_code_hash, &code)
+        {
             return Err(GqlError::Validation(ValidationError::new(
-                "event_status",
-                "Minting could only be applied to events with status DRAFT or MINTING",
+                "code",
+                "Invalid verification code",
             )));
         }
 
-        // check the user is also the event creator
-        if !db_user.id.eq(&db_event.created_by_user) {
+        let updated_db_user =
+            db_complete_seller_phone_verification(&ctx.db_client, &db_verification.id, &now)
+                .await
+                .map_err(GqlError::Database)?
+                .ok_or_else(|| {
+                    GqlError::Conflict(ValidationError::new(
+                        "phone_number",
+                        "Phone number is already taken",
+                    ))
+                })?;
+        ctx.audit(
+            Some(db_user.id),
+            "verify_seller_phone",
+            AuditEntity::User(db_user.id),
+            None,
+        )
+        .await;
+
+        Ok(User::from(updated_db_user))
+    }
+
+    /// Sets the language of the caller's sms, one of the configured ones
+    async fn set_locale(ctx: &ResourcesContext, locale: String) -> Result<User, GqlError> {
+        let db_user = guard(ctx, Operation::UpdateProfile).await?;
+
+        let locale = supported_locale(&ctx.business, &locale).ok_or_else(|| {
+            GqlError::Validation(ValidationError::new("locale", "Locale is not supported"))
+        })?;
+
+        let updated_db_user = db_update_user_locale(&ctx.db_client, &db_user.id, &locale)
+            .await
+            .map_err(GqlError::Database)?
+            .ok_or_else(|| {
+                GqlError::Validation(ValidationError::new(
+                    "user_id",
+                    "User account has been deleted",
+                ))
+            })?;
+        ctx.audit(
+            Some(db_user.id),
+            "set_locale",
+            AuditEntity::User(db_user.id),
+            Some(serde_json::json!({ "locale": locale })),
+        )
+        .await;
+
+        Ok(User::from(updated_db_user))
+    }
+
+    /// Turns the reminders of the events the caller holds tickets for on or off
+    async fn set_event_reminders(ctx: &ResourcesContext, enabled: bool) -> Result<User, GqlError> {
+        let db_user = guard(ctx, Operation::UpdateProfile).await?;
+
+        let updated_db_user =
+            db_update_user_event_reminders_opt_out(&ctx.db_client, &db_user.id, !enabled)
+                .await
+                .map_err(GqlError::Database)?
+                .ok_or_else(|| {
+                    GqlError::Validation(ValidationError::new(
+                        "user_id",
+                        "User account has been deleted",
+                    ))
+                })?;
+        ctx.audit(
+            Some(db_user.id),
+            "set_event_reminders",
+            AuditEntity::User(db_user.id),
+            Some(serde_json::json!({ "enabled": enabled })),
+        )
+        .await;
+
+        Ok(User::from(updated_db_user))
+    }
+
+    /// Marks one of the caller's notifications as read, a no-op if it was read already
+    async fn mark_notification_read(
+        ctx: &ResourcesContext,
+        notification_id: Uuid,
+    ) -> Result<InboxNotification, GqlError> {
+        let db_user = guard(ctx, Operation::MyNotifications).await?;
+
+        let db_notification =
+            db_mark_notification_read(&ctx.db_client, &notification_id, &db_user.id)
+                .await
+                .map_err(GqlError::Database)?
+                .ok_or_else(|| {
+                    GqlError::NotFound(ValidationError::new(
+                        "notification_id",
+                        "Notification with submitted id does not exist",
+                    ))
+                })?;
+
+        Ok(InboxNotification::from(db_notification))
+    }
+
+    /// Replaces the caller's password once the current one is verified. The caller's login and
+    /// recovery sessions are revoked
+    async fn change_password(
+        ctx: &ResourcesContext,
+        change_password: ChangePassword,
+    ) -> Result<bool, GqlError> {
+        let db_user = guard(ctx, Operation::ChangePassword).await?;
+
+        check_change_password_payload(&change_password)?;
+        check_password(&ctx.password_policy, &change_password.new_password)
+            .await
+            .map_err(|e| {
+                GqlError::Validation(ValidationError::new("new_password", &e.to_string()))
+            })?;
+
+        let current_hash = db_user.password.clone().ok_or_else(|| {
+            GqlError::Validation(ValidationError::new(
+                "current_password",
+                "User has no password",
+            ))
+        })?;
+        let is_verified =
+            verify_password(&current_hash, change_password.current_password.as_bytes())
+                .map_err(|_| GqlError::UnexpectedInternal)?;
+        if !is_verified {
             return Err(GqlError::Validation(ValidationError::new(
-                "event_creator",
-                "Event creator and calling user are not the same",
+                "current_password",
+                "Current password is wrong",
             )));
         }
+        let new_hash = hash_password(change_password.new_password.as_bytes())
+            .map_err(|_| GqlError::UnexpectedInternal)?;
+
+        // a concurrent change wins, this one must then be retried with its password
+        db_update_user_password(&ctx.db_client, &db_user.id, &current_hash, &new_hash)
+            .await
+            .map_err(GqlError::Database)?
+            .ok_or_else(|| {
+                GqlError::Conflict(ValidationError::new(
+                    "current_password",
+                    "Password has been changed concurrently",
+                ))
+            })?;
+
+        db_revoke_user_sessions(&ctx.db_client, &db_user.id, db_user.phone_number.as_deref())
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(db_user.id),
+            "change_password",
+            AuditEntity::User(db_user.id),
+            None,
+        )
+        .await;
+
+        Ok(true)
+    }
 
-        // mint the tickets TODO: error handling
-        let price = db_ticket
-            .price
-            .map(|price| price.parse::<f64>())
-            .transpose()
-            .expect("Price should be parsable!")
-            .expect("Price should not be empty!");
+    /// Starts two-factor authentication of the caller's password sign-ins. The secret is added to
+    /// an authenticator app and enabled with `confirm_totp`, until then enrolling again replaces it
+    async fn enroll_totp(ctx: &ResourcesContext) -> Result<TotpEnrollment, GqlError> {
+        let db_user = guard(ctx, Operation::EnrollTotp).await?;
+        let totp = totp_config(ctx)?;
 
-        let extra = serde_json::json!({
-            "price": price,
+        let secret = gen_secret();
+        db_upsert_user_totp(
+            &ctx.db_client,
+            &DbUserTotp::new(db_user.id, encrypt_secret(&totp.encryption_secret, &secret)),
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(|| {
+            GqlError::Conflict(ValidationError::new(
+                "totp",
+                "Two-factor authentication is already enabled",
+            ))
+        })?;
+        ctx.audit(
+            Some(db_user.id),
+            "enroll_totp",
+            AuditEntity::User(db_user.id),
+            None,
+        )
+        .await;
+
+        Ok(TotpEnrollment {
+            provisioning_uri: provisioning_uri(&totp.issuer, &db_user.username, &secret),
+            secret,
         })
-        .to_string();
+    }
 
-        // media photo and hash
-        let media = db_event
-            .cover_photo_url
-            .clone()
-            .expect("Media should not be empty!"); //FIXME: this should be the image from the FE
+    /// Enables two-factor authentication with a code of the enrolled secret, password sign-ins
+    /// then ask for a code too
+    async fn confirm_totp(ctx: &ResourcesContext, code: String) -> Result<bool, GqlError> {
+        let db_user = guard(ctx, Operation::ConfirmTotp).await?;
+        let totp = totp_config(ctx)?;
 
-        let media_hash = sha256::digest(&media);
+        let db_user_totp = db_get_user_totp(&ctx.db_client, &db_user.id)
+            .await
+            .map_err(GqlError::Database)?
+            .filter(|db_user_totp| db_user_totp.enabled_at.is_none())
+            .ok_or_else(|| {
+                GqlError::NotFound(ValidationError::new(
+                    "totp",
+                    "No two-factor authentication enrollment is pending",
+                ))
+            })?;
+        let secret = decrypt_secret(&totp.encryption_secret, &db_user_totp.encrypted_secret)
+            .ok_or(GqlError::UnexpectedInternal)?;
+        let step = verify_totp(
+            &secret,
+            &code,
+            sql_timestamp(None).timestamp(),
+            db_user_totp.last_used_step,
+        )
+        .ok_or_else(|| {
+            GqlError::Validation(ValidationError::new("code", "Code is wrong or expired"))
+        })?;
 
-        let mint_nfts_response = {
-            let mut lock = ctx.grpc_near_client.lock().await;
-            let mint_nfts_response: MintNftsResponse = lock
-                .mint_nfts(
-                    db_user.wallet_id,
-                    db_ticket.ticket_name,
-                    db_ticket.ticket_slug,
-                    db_ticket.description.unwrap_or_default(),
-                    media,
-                    media_hash,
-                    db_ticket
-                        .quantity_available
-                        .expect("Quantity available should not be 0!"),
-                    extra,
-                    "0".to_string(),
-                )
-                .await
-                .map_err(GqlError::Grpc)?;
-            drop(lock);
-            mint_nfts_response
-        };
+        db_enable_user_totp(&ctx.db_client, &db_user.id, step)
+            .await
+            .map_err(GqlError::Database)?
+            .ok_or_else(|| {
+                GqlError::Conflict(ValidationError::new(
+                    "totp",
+                    "Two-factor authentication has been enabled concurrently",
+                ))
+            })?;
+        ctx.audit(
+            Some(db_user.id),
+            "confirm_totp",
+            AuditEntity::User(db_user.id),
+            None,
+        )
+        .await;
+
+        Ok(true)
+    }
+}
+
+/// Served on `/graphql/seller` to sellers only
+#[derive(Copy, Clone, Default)]
+pub struct SellerMutationRoot;
+
+#[juniper::graphql_object(Context = ResourcesContext)]
+impl SellerMutationRoot {
+    async fn api_version() -> juniper::FieldResult<&'static str> {
+        Ok("v1.0".into())
+    }
+
+    // -------------------------- NFTS ------------------- //
+
+    // seller mint nft tickets
+    async fn mint_nfts(
+        request: NewMintNftsRequest,
+        ctx: &ResourcesContext,
+    ) -> Result<NewMintNftsResponse, GqlError> {
+        let db_user = guard(ctx, Operation::MintNfts).await?;
+        let user_id = db_user.id;
+
+        let (mut db_event, db_ticket) = mintable_ticket(ctx, &user_id, &request.ticket_id).await?;
+        let payload = MintPayload::new(&db_event, &db_ticket)?;
+        let number_of_tickets = payload.number_of_tickets;
+
+        let mint_nfts_tx = ctx
+            .chain
+            .mint_nfts(&db_user.wallet_id, payload)
+            .await
+            .map_err(GqlError::Chain)?;
 
         // change the status of the event from DRAFT to MINTING
         if db_event.event_status.eq(&EventStatus::Draft) {
@@ -170,66 +698,281 @@ impl PrivateMutationRoot {
                 .map_err(GqlError::Database)?;
         }
 
+        ctx.audit(
+            Some(user_id),
+            "mint_nfts",
+            AuditEntity::Ticket(db_ticket.id),
+            serde_json::to_value(&mint_nfts_tx.tx_hash).ok(),
+        )
+        .await;
+
+        // the mint reconciler follows the transaction until it is final
+        db_insert_mint_job(
+            &ctx.db_client,
+            &DbMintJob::new(
+                &db_ticket,
+                &db_user.wallet_id,
+                &mint_nfts_tx.tx_hash,
+                number_of_tickets,
+            ),
+        )
+        .await
+        .map_err(GqlError::Database)?;
         // return the tx hash
         Ok(NewMintNftsResponse {
-            tx_hash: mint_nfts_response.tx_hash,
+            tx_hash: mint_nfts_tx.tx_hash,
+            wallet_id: db_user.wallet_id,
         })
     }
 
-    // -------------------------- EVENTS ------------------- //
-    async fn register_event(
-        new_event: NewEvent,
+    /// Sets the address of the caller's public storefront, see the public `seller` query
+    async fn set_seller_slug(
         ctx: &ResourcesContext,
-    ) -> Result<Event, GqlError> {
-        // get the requesting user_id
-        let user_id = {
-            let lock = ctx.user_id.lock().await;
-            let user_id = *lock;
-            drop(lock);
-            user_id
+        seller_slug: String,
+    ) -> Result<User, GqlError> {
+        let db_user = guard(ctx, Operation::SetSellerSlug).await?;
+
+        let seller_slug = check_seller_slug(&seller_slug)?;
+        if db_is_seller_slug_taken(&ctx.db_client, &seller_slug, &db_user.id)
+            .await
+            .map_err(GqlError::Database)?
+        {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "seller_slug",
+                "Seller slug is already taken",
+            )));
         }
-        .expect("Should have a uuid due to authenticated private gql route");
 
-        // check caller is a seller ?
-        let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
+        let updated_db_user = db_update_user_seller_slug(&ctx.db_client, &db_user.id, &seller_slug)
             .await
-            .map_err(|_| {
+            .map_err(GqlError::Database)?
+            .ok_or_else(|| {
                 GqlError::Validation(ValidationError::new(
                     "user_id",
-                    "User not found in the database",
+                    "User account has been deleted",
                 ))
             })?;
+        ctx.audit(
+            Some(db_user.id),
+            "set_seller_slug",
+            AuditEntity::User(db_user.id),
+            Some(serde_json::json!({ "seller_slug": seller_slug })),
+        )
+        .await;
 
-        if !db_user.user_type.eq(&Role::Seller) {
-            return Err(GqlError::Validation(ValidationError::new(
-                "user type",
-                "Calling user is not a seller",
-            )));
-        }
+        Ok(User::from(updated_db_user))
+    }
 
-        // check for unique event slug
-        let slug = slugify!(&new_event.event_name, separator = "-");
-        if let Ok(_event) = db_get_event_by_slug(&ctx.db_client, &slug).await {
-            return Err(GqlError::Validation(ValidationError::new(
-                "event_slug",
-                "Event with the same slug already exists",
-            )));
+    // -------------------------- EVENTS ------------------- //
+    /// registers a draft event, managed by the organization if one is given
+    async fn register_event(
+        mut new_event: NewEvent,
+        organization_id: Option<Uuid>,
+        ctx: &ResourcesContext,
+    ) -> Result<Event, GqlError> {
+        let user_id = guard(ctx, Operation::RegisterEvent).await?.id;
+
+        if let Some(organization_id) = organization_id.as_ref() {
+            organization_editor(ctx, organization_id, &user_id).await?;
         }
-        // check for unique event name
+        new_event.event_name =
+            sanitize_text_field(&ctx.sanitation, TextField::EventName, &new_event.event_name)?;
+
+        // check for unique event name, a taken slug gets a numeric suffix
         if let Ok(_event) = db_get_event_by_name(&ctx.db_client, &new_event.event_name).await {
-            return Err(GqlError::Validation(ValidationError::new(
+            return Err(GqlError::Conflict(ValidationError::new(
                 "event_name",
                 "Event with the same name already exists",
             )));
         }
 
         // save the event into the db (automatically set created date and status to DRAFT)
-        let db_event = DbEvent::new(&new_event.event_name, user_id);
-        db_insert_event(&ctx.db_client, &db_event)
+        let mut db_event = DbEvent::new(&new_event.event_name, user_id);
+        db_event.organization_id = organization_id;
+        db_insert_event_with_free_slug(&ctx.db_client, &mut db_event)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "register_event",
+            AuditEntity::Event(db_event.id),
+            serde_json::to_value(&new_event).ok(),
+        )
+        .await;
+
+        ctx.readable_event(Event::new(db_event, vec![])).await
+    }
+
+    /// copies the event, its tickets, tags and asset references into a new DRAFT event
+    async fn clone_event(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+        overrides: Option<CloneEventOverrides>,
+    ) -> Result<Event, GqlError> {
+        let user_id = guard(ctx, Operation::CloneEvent).await?.id;
+
+        let source = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+        guard_event(ctx, &user_id, &source, EventAccess::Edit).await?;
+
+        // an overridden name must be free, otherwise the first free copy name is taken
+        let overrides = overrides.unwrap_or_default();
+        let candidates = match overrides.event_name.as_ref() {
+            Some(event_name) => vec![sanitize_text_field(
+                &ctx.sanitation,
+                TextField::EventName,
+                event_name,
+            )?],
+            None => (1..=MAX_CLONE_NAME_ATTEMPTS)
+                .map(|attempt| clone_event_name(&source.event_name, attempt))
+                .collect(),
+        };
+        let mut event_name = None;
+        for candidate in candidates {
+            let slug = slugify!(&candidate, separator = "-");
+            if db_get_event_by_slug(&ctx.db_client, &slug).await.is_err()
+                && db_get_event_by_name(&ctx.db_client, &candidate)
+                    .await
+                    .is_err()
+            {
+                event_name = Some(candidate);
+                break;
+            }
+        }
+        let event_name = event_name.ok_or_else(|| {
+            GqlError::Conflict(ValidationError::new(
+                "event_name",
+                "Event with the same name already exists",
+            ))
+        })?;
+
+        let source_tickets = db_get_tickets_by_event_id(&ctx.db_client, &Some(event_id))
             .await
             .map_err(GqlError::Database)?;
+        let (db_event, db_tickets) =
+            clone_event(&source, &source_tickets, &event_name, overrides, user_id)?;
+        db_insert_event_with_tickets(&ctx.db_client, &db_event, &db_tickets)
+            .await
+            .map_err(GqlError::Database)?;
+        let tags = copy_tags_and_assets(ctx, &event_id, &db_event.id).await?;
+
+        ctx.audit(
+            Some(user_id),
+            "clone_event",
+            AuditEntity::Event(db_event.id),
+            Some(serde_json::json!({ "source_event_id": event_id, "event_name": event_name })),
+        )
+        .await;
+
+        ctx.readable_event(Event::new(db_event, db_tickets).with_tags(tags))
+            .await
+    }
+
+    /// Makes the event the first occurrence of a series repeating it by the recurrence rule, its
+    /// later occurrences being DRAFT copies of it named after their date
+    async fn create_event_series(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+        rrule: String,
+    ) -> Result<EventSeries, GqlError> {
+        let user_id = guard(ctx, Operation::CreateEventSeries).await?.id;
+        let source = accessible_event(ctx, &user_id, &event_id, EventAccess::Edit).await?;
+        let in_series = || {
+            GqlError::Conflict(ValidationError::new(
+                "event_id",
+                "Event is already part of a series",
+            ))
+        };
+        if source.series_id.is_some() {
+            return Err(in_series());
+        }
+        let start_date = source.start_date.ok_or_else(|| {
+            GqlError::Validation(ValidationError::new(
+                "start_date",
+                "Event needs a starting date to recur",
+            ))
+        })?;
+
+        let recurrence = rrule
+            .parse::<Recurrence>()
+            .map_err(|e| GqlError::Validation(ValidationError::new("rrule", &e.to_string())))?;
+        let occurrences = recurrence.occurrences(start_date, ctx.series.max_occurrences);
+        if occurrences.len() < 2 {
+            return Err(GqlError::Validation(ValidationError::new(
+                "rrule",
+                "Rule has no occurrence after the event's start",
+            )));
+        }
+
+        // the names of all the occurrences must be free before any is stored
+        let event_names = occurrences[1..]
+            .iter()
+            .map(|start_date| occurrence_event_name(&source.event_name, start_date))
+            .collect::<Vec<_>>();
+        for event_name in &event_names {
+            let slug = slugify!(event_name, separator = "-");
+            if db_get_event_by_slug(&ctx.db_client, &slug).await.is_ok()
+                || db_get_event_by_name(&ctx.db_client, event_name)
+                    .await
+                    .is_ok()
+            {
+                return Err(GqlError::Conflict(ValidationError::new(
+                    "event_name",
+                    "Event with the same name as an occurrence already exists",
+                )));
+            }
+        }
+
+        let db_series = DbEventSeries::new(&source.event_name, rrule.trim(), start_date, user_id);
+        let source = db_insert_event_series(&ctx.db_client, &db_series, &event_id)
+            .await
+            .map_err(GqlError::Database)?
+            .ok_or_else(in_series)?;
+        ctx.event_cache.invalidate(&event_id);
+
+        let source_tickets = db_get_tickets_by_event_id(&ctx.db_client, &Some(event_id))
+            .await
+            .map_err(GqlError::Database)?;
+        let tags = db_get_event_tags(&ctx.db_client, &[event_id])
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(|tag| tag.tag)
+            .collect::<Vec<_>>();
+        let mut events = vec![];
+        for (start_date, event_name) in occurrences[1..].iter().zip(event_names) {
+            let overrides = occurrence_overrides(&source, *start_date);
+            let (mut db_event, db_tickets) =
+                clone_event(&source, &source_tickets, &event_name, overrides, user_id)?;
+            db_event.series_id = Some(db_series.id);
+            db_insert_event_with_tickets(&ctx.db_client, &db_event, &db_tickets)
+                .await
+                .map_err(GqlError::Database)?;
+            copy_tags_and_assets(ctx, &event_id, &db_event.id).await?;
+            events.push(Event::new(db_event, db_tickets).with_tags(tags.clone()));
+        }
+        events.insert(0, Event::new(source, source_tickets).with_tags(tags));
+
+        ctx.audit(
+            Some(user_id),
+            "create_event_series",
+            AuditEntity::Event(event_id),
+            Some(serde_json::json!({
+                "series_id": db_series.id,
+                "rrule": db_series.rrule,
+                "occurrences": occurrences.len(),
+            })),
+        )
+        .await;
 
-        Ok(Event::new(db_event, vec![]))
+        let events = ctx.readable_events(events).await?;
+        Ok(EventSeries::new(db_series, events, &sql_timestamp(None)))
     }
 
     async fn update_event(
@@ -249,20 +992,20 @@ impl PrivateMutationRoot {
         let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
             .await
             .map_err(|_| {
-                GqlError::Validation(ValidationError::new(
+                GqlError::NotFound(ValidationError::new(
                     "user_id",
                     "User not found in the database",
                 ))
             })?;
 
         // get the event id that we want to modify
-        let event_id = Uuid::parse_str(&update_event.id).map_err(|_| GqlError::ParseUUID)?;
+        let event_id = update_event.id;
 
         // search for event by id
         let mut db_event = db_get_event_by_id(&ctx.db_client, &event_id)
             .await
             .map_err(|_| {
-                GqlError::Validation(ValidationError::new(
+                GqlError::NotFound(ValidationError::new(
                     "event_id",
                     "Event with submitted id does not exist",
                 ))
@@ -270,83 +1013,74 @@ impl PrivateMutationRoot {
 
         // make sure the event is in a DRAFT state only
         if !db_event.event_status.eq(&EventStatus::Draft) {
-            return Err(GqlError::Validation(ValidationError::new(
+            return Err(GqlError::Conflict(ValidationError::new(
                 "event_status",
                 "Only event with status DRAFT could be edited",
             )));
         }
 
-        // check caller is event creator ?
-        if !db_user.id.eq(&db_event.created_by_user) {
-            return Err(GqlError::Validation(ValidationError::new(
-                "event_creator",
-                "Event creator and calling user are not the same",
-            )));
-        }
+        // check caller is the event creator or a member of its organization
+        guard_event(ctx, &db_user.id, &db_event, EventAccess::Edit).await?;
 
         let cover_photo_base64 = update_event.cover_photo_base64.clone();
         let thumbnail_base64 = update_event.thumbnail_base64.clone();
+        let venue_location = db_event.venue_location.clone();
 
         // validate and update the event mutation
-        let db_event = update_event_mutation_payload(update_event, &mut db_event)?;
-
-        // if uploaded images, send to aws s3
-        // TODO: send to worker to do the async sending
-        // TODO: do proper error handling
-        if let Some(cover_photo) = cover_photo_base64 {
-            let path = ctx
-                .aws_s3_client
-                .upload(None, cover_photo.into_bytes())
-                .await
-                .expect("failed to upload file to s3");
-            db_event.cover_photo_url = Some(ctx.aws_context.get_asset_url(path.clone()));
-
-            // persist the asset in the db and attach it to the event
-            let asset_file = AssetFile::new(
-                ctx.aws_context.bucket.clone(),
-                path,
-                None,
-                db_event.id.clone(),
-            );
-            insert_asset_file(&ctx.db_client, &asset_file)
-                .await
-                .map_err(GqlError::Database)?;
-        }
-
-        if let Some(thumbnail) = thumbnail_base64 {
-            let path = ctx
-                .aws_s3_client
-                .upload(None, thumbnail.into_bytes())
-                .await
-                .expect("failed to upload file to s3");
-            db_event.thumbnail_url = Some(ctx.aws_context.get_asset_url(path.clone()));
+        let db_event = update_event_mutation_payload(&ctx.sanitation, update_event, &mut db_event)?;
 
-            // persist the asset in the db and attach it to the event
-            let asset_file = AssetFile::new(
-                ctx.aws_context.bucket.clone(),
-                path,
-                None,
-                db_event.id.clone(),
-            );
-            insert_asset_file(&ctx.db_client, &asset_file)
-                .await
-                .map_err(GqlError::Database)?;
+        // the coordinates follow the venue location
+        if db_event.venue_location != venue_location {
+            let coordinates =
+                geocode_venue(ctx.geocoder.as_ref(), db_event.venue_location.as_deref()).await;
+            db_event.latitude = coordinates.map(|coordinates| coordinates.latitude);
+            db_event.longitude = coordinates.map(|coordinates| coordinates.longitude);
         }
 
         // update the db with the event data
         let updated_db_event = db_update_event(&ctx.db_client, &db_event)
             .await
             .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "update_event",
+            AuditEntity::Event(updated_db_event.id),
+            serde_json::to_value(&updated_db_event).ok(),
+        )
+        .await;
+
+        // if uploaded images, send to aws s3 in the background. The urls are set on the event
+        // once the uploads went through
+        let uploads = vec![
+            (EventAssetKind::CoverPhoto, cover_photo_base64),
+            (EventAssetKind::Thumbnail, thumbnail_base64),
+        ];
+        for (kind, content) in uploads {
+            if let Some(content) = content {
+                let _ = enqueue(
+                    &ctx.db_client,
+                    JobPayload::S3Upload {
+                        event_id: updated_db_event.id,
+                        kind,
+                        content,
+                    },
+                    ctx.jobs.max_attempts,
+                )
+                .await
+                .map_err(GqlError::Database)?;
+            }
+        }
 
         // get the related event tickets
         let tickets = db_get_tickets_by_event_id(&ctx.db_client, &Some(updated_db_event.id))
             .await
             .map_err(GqlError::Database)?;
 
-        Ok(Event::new(updated_db_event, tickets))
+        ctx.readable_event(Event::new(updated_db_event, tickets))
+            .await
     }
 
-    async fn delete_event(ctx: &ResourcesContext, id: String) -> Result<bool, GqlError> {
+    async fn delete_event(ctx: &ResourcesContext, id: Uuid) -> Result<bool, GqlError> {
         // get the requesting user_id
         let user_id = {
             let lock = ctx.user_id.lock().await;
@@ -360,47 +1094,140 @@ impl PrivateMutationRoot {
         let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
             .await
             .map_err(|_| {
-                GqlError::Validation(ValidationError::new(
+                GqlError::NotFound(ValidationError::new(
                     "user_id",
                     "User not found in the database",
                 ))
             })?;
 
-        // get the event id that we want to delete
-        let event_id = Uuid::parse_str(&id).map_err(|_| GqlError::ParseUUID)?;
-
-        let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
-            .await
-            .map_err(|_| {
-                GqlError::Validation(ValidationError::new(
-                    "event_id",
-                    "Event with submitted id does not exist",
-                ))
-            })?;
+        let db_event = db_get_event_by_id(&ctx.db_client, &id).await.map_err(|_| {
+            GqlError::NotFound(ValidationError::new(
+                "event_id",
+                "Event with submitted id does not exist",
+            ))
+        })?;
 
         // make sure the event is in a DRAFT state only. Deleting Minting and Final states not allowed!
         if !db_event.event_status.eq(&EventStatus::Draft) {
-            return Err(GqlError::Validation(ValidationError::new(
+            return Err(GqlError::Conflict(ValidationError::new(
                 "event_status",
                 "Only event with status DRAFT could be edited",
             )));
         }
 
-        // check caller is event creator ?
-        if !db_user.id.eq(&db_event.created_by_user) {
-            return Err(GqlError::Validation(ValidationError::new(
-                "event_creator",
-                "Event creator and calling user are not the same",
-            )));
-        }
+        // check caller is the event creator or a member of its organization
+        guard_event(ctx, &db_user.id, &db_event, EventAccess::Edit).await?;
 
-        // delete event by id
-        db_delete_event_by_id(&ctx.db_client, &event_id)
+        // soft-delete event by id, keeping its history and reservations
+        db_soft_delete_event_by_id(&ctx.db_client, &id)
             .await
             .map_err(GqlError::Database)?;
+        ctx.audit(Some(user_id), "delete_event", AuditEntity::Event(id), None)
+            .await;
         Ok(true)
     }
 
+    async fn archive_event(
+        ctx: &ResourcesContext,
+        id: Uuid,
+        archived: bool,
+    ) -> Result<Event, GqlError> {
+        // get the requesting user_id
+        let user_id = {
+            let lock = ctx.user_id.lock().await;
+            let user_id = *lock;
+            drop(lock);
+            user_id
+        }
+        .expect("Should have a uuid due to authenticated private gql route");
+
+        // find user in the db
+        let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "user_id",
+                    "User not found in the database",
+                ))
+            })?;
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &id).await.map_err(|_| {
+            GqlError::NotFound(ValidationError::new(
+                "event_id",
+                "Event with submitted id does not exist",
+            ))
+        })?;
+
+        // check caller is the event creator or a member of its organization
+        guard_event(ctx, &db_user.id, &db_event, EventAccess::Edit).await?;
+
+        let updated_db_event = db_update_event_archived(&ctx.db_client, &id, archived)
+            .await
+            .map_err(GqlError::Database)?;
+
+        // tickets follow the archived state of their event
+        db_update_event_tickets_archived(&ctx.db_client, &id, archived)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.event_cache.invalidate(&id);
+        ctx.audit(
+            Some(user_id),
+            "archive_event",
+            AuditEntity::Event(id),
+            Some(serde_json::json!({ "archived": archived })),
+        )
+        .await;
+
+        // get the related event tickets
+        let tickets = db_get_tickets_by_event_id(&ctx.db_client, &Some(updated_db_event.id))
+            .await
+            .map_err(GqlError::Database)?;
+
+        ctx.readable_event(Event::new(updated_db_event, tickets))
+            .await
+    }
+
+    // seller submits a minted event for review, it is public once an admin approves it
+    async fn publish_event(ctx: &ResourcesContext, id: Uuid) -> Result<Event, GqlError> {
+        let db_user = guard(ctx, Operation::PublishEvent).await?;
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &id).await.map_err(|_| {
+            GqlError::NotFound(ValidationError::new(
+                "event_id",
+                "Event with submitted id does not exist",
+            ))
+        })?;
+
+        // check caller is the event creator or a member of its organization
+        guard_event(ctx, &db_user.id, &db_event, EventAccess::Edit).await?;
+
+        // only events whose tickets were minted are submitted, rejected ones again once fixed
+        let updated_db_event =
+            db_submit_event_for_review(&ctx.db_client, &id, &sql_timestamp(None))
+                .await
+                .map_err(GqlError::Database)?
+                .ok_or_else(|| {
+                    GqlError::Conflict(ValidationError::new(
+                        "event_status",
+                        "Only events with status MINTING could be published",
+                    ))
+                })?;
+        ctx.audit(
+            Some(db_user.id),
+            "publish_event",
+            AuditEntity::Event(id),
+            None,
+        )
+        .await;
+
+        let tickets = db_get_tickets_by_event_id(&ctx.db_client, &Some(id))
+            .await
+            .map_err(GqlError::Database)?;
+
+        ctx.readable_event(Event::new(updated_db_event, tickets))
+            .await
+    }
+
     // -------------------------- TICKETS ------------------- //
 
     async fn add_event_tickets(
@@ -420,7 +1247,7 @@ impl PrivateMutationRoot {
         let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
             .await
             .map_err(|_| {
-                GqlError::Validation(ValidationError::new(
+                GqlError::NotFound(ValidationError::new(
                     "user_id",
                     "User not found in the database",
                 ))
@@ -428,54 +1255,52 @@ impl PrivateMutationRoot {
 
         let mut tickets: Vec<Ticket> = vec![];
 
-        for new_ticket in new_tickets.into_iter() {
+        for mut new_ticket in new_tickets.into_iter() {
             // check new ticket data
-            check_new_ticket_payload(&new_ticket)?;
+            check_new_ticket_payload(&ctx.sanitation, &mut new_ticket)?;
 
             // get ticket event uuid
-            let event_id =
-                Uuid::parse_str(&new_ticket.event_id).map_err(|_| GqlError::ParseUUID)?;
+            let event_id = new_ticket.event_id;
 
             // check for event id that the ticket will be attached to
             let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
                 .await
                 .map_err(|_| {
-                    GqlError::Validation(ValidationError::new(
+                    GqlError::NotFound(ValidationError::new(
                         "event_id",
                         "Event with submitted id does not exist",
                     ))
                 })?;
 
-            // check the user is also the event creator
-            if !db_user.id.eq(&db_event.created_by_user) {
-                return Err(GqlError::Validation(ValidationError::new(
-                    "event_creator",
-                    "Event creator and calling user are not the same",
-                )));
-            }
+            // check caller is the event creator or a member of its organization
+            guard_event(ctx, &db_user.id, &db_event, EventAccess::Edit).await?;
 
             // make sure the event is in a DRAFT state only when adding new tickets
             if !db_event.event_status.eq(&EventStatus::Draft) {
-                return Err(GqlError::Validation(ValidationError::new(
+                return Err(GqlError::Conflict(ValidationError::new(
                     "event_status",
                     "Tickets could only be added to an event with status DRAFT",
                 )));
             }
 
-            // check we don't have a ticket with a similar slug and name
-            let db_ticket = DbTicket::new(new_ticket, &db_event);
-            if let Ok(_ticket) = db_get_ticket_by_slug(&ctx.db_client, &db_ticket.ticket_slug).await
-            {
-                return Err(GqlError::Validation(ValidationError::new(
-                    "ticket_slug",
-                    "Ticket with the same slug already exists",
-                )));
-            }
-
-            // save the ticket into the db
-            db_insert_ticket(&ctx.db_client, &db_ticket)
+            // save the ticket into the db, a taken slug gets a numeric suffix
+            let mut db_ticket = DbTicket::new(new_ticket, &db_event);
+            let ticket_added = outbox_message(
+                event_id,
+                &EventUpdate::TicketAdded {
+                    ticket_id: db_ticket.id,
+                },
+            );
+            db_insert_ticket_with_free_slug(&ctx.db_client, &mut db_ticket, &[ticket_added])
                 .await
                 .map_err(GqlError::Database)?;
+            ctx.audit(
+                Some(user_id),
+                "add_event_ticket",
+                AuditEntity::Ticket(db_ticket.id),
+                serde_json::to_value(&db_ticket).ok(),
+            )
+            .await;
 
             tickets.push(Ticket::from(db_ticket));
         }
@@ -487,14 +1312,7 @@ impl PrivateMutationRoot {
         ctx: &ResourcesContext,
         ids: Vec<String>,
     ) -> Result<bool, GqlError> {
-        /*
         // TODO: optimize the deletion in 1 sql statement using IN or using a commit tx ?
-        let tickets_to_delete = ids
-            .iter()
-            .map(|id| Uuid::parse_str(&id))
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|_| GqlError::ParseUUID)?;
-        */
 
         // get the requesting user_id
         let user_id = {
@@ -509,7 +1327,7 @@ impl PrivateMutationRoot {
         let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
             .await
             .map_err(|_| {
-                GqlError::Validation(ValidationError::new(
+                GqlError::NotFound(ValidationError::new(
                     "user_id",
                     "User not found in the database",
                 ))
@@ -517,14 +1335,11 @@ impl PrivateMutationRoot {
 
         // loop over ticket ids and delete them one by one
         for id in ids.into_iter() {
-            // get the ticket id that we want to delete
-            let ticket_id = Uuid::parse_str(&id).map_err(|_| GqlError::ParseUUID)?;
-
             // get ticket data
-            let db_ticket = db_get_ticket_by_id(&ctx.db_client, &ticket_id)
+            let db_ticket = db_get_ticket_by_id(&ctx.db_client, &id)
                 .await
                 .map_err(|_| {
-                    GqlError::Validation(ValidationError::new(
+                    GqlError::NotFound(ValidationError::new(
                         "ticket_id",
                         "Ticket with submitted id does not exist",
                     ))
@@ -534,7 +1349,7 @@ impl PrivateMutationRoot {
             let db_event = db_get_event_by_id(&ctx.db_client, &db_ticket.event_id)
                 .await
                 .map_err(|_| {
-                    GqlError::Validation(ValidationError::new(
+                    GqlError::NotFound(ValidationError::new(
                         "event_ticket_id",
                         "Ticket with event id does not exist",
                     ))
@@ -542,24 +1357,26 @@ impl PrivateMutationRoot {
 
             // make sure the event is in a DRAFT state only when deleting tickets
             if !db_event.event_status.eq(&EventStatus::Draft) {
-                return Err(GqlError::Validation(ValidationError::new(
+                return Err(GqlError::Conflict(ValidationError::new(
                     "event_status",
                     "Tickets could only be deleted for an event with status DRAFT",
                 )));
             }
 
-            // check the user is also the event creator
-            if !db_user.id.eq(&db_event.created_by_user) {
-                return Err(GqlError::Validation(ValidationError::new(
-                    "event_creator",
-                    "Event creator and calling user are not the same",
-                )));
-            }
+            // check caller is the event creator or a member of its organization
+            guard_event(ctx, &db_user.id, &db_event, EventAccess::Edit).await?;
 
-            // delete ticket by id
-            db_delete_ticket_by_id(&ctx.db_client, &ticket_id)
+            // soft-delete ticket by id
+            db_soft_delete_ticket_by_id(&ctx.db_client, &id)
                 .await
                 .map_err(GqlError::Database)?;
+            ctx.audit(
+                Some(user_id),
+                "delete_event_ticket",
+                AuditEntity::Ticket(id),
+                None,
+            )
+            .await;
         }
 
         Ok(true)
@@ -582,23 +1399,24 @@ impl PrivateMutationRoot {
         let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
             .await
             .map_err(|_| {
-                GqlError::Validation(ValidationError::new(
+                GqlError::NotFound(ValidationError::new(
                     "user_id",
                     "User not found in the database",
                 ))
             })?;
 
-        let mut tickets: Vec<Ticket> = vec![];
+        // every ticket is checked before any is written, a stale or invalid one leaves all as is
+        let mut updates: Vec<(DbTicket, i32)> = vec![];
 
         for update_ticket in update_tickets.into_iter() {
             // get ticket uuid
-            let ticket_id = Uuid::parse_str(&update_ticket.id).map_err(|_| GqlError::ParseUUID)?;
+            let ticket_id = update_ticket.id;
 
             // check we have a db ticket with such uuid
             let mut db_ticket = db_get_ticket_by_id(&ctx.db_client, &ticket_id)
                 .await
                 .map_err(|_| {
-                    GqlError::Validation(ValidationError::new(
+                    GqlError::NotFound(ValidationError::new(
                         "ticket_id",
                         "Ticket with submitted id does not exist",
                     ))
@@ -608,7 +1426,7 @@ impl PrivateMutationRoot {
             let db_event = db_get_event_by_id(&ctx.db_client, &db_ticket.event_id)
                 .await
                 .map_err(|_| {
-                    GqlError::Validation(ValidationError::new(
+                    GqlError::NotFound(ValidationError::new(
                         "event_ticket_id",
                         "Ticket with event id does not exist",
                     ))
@@ -616,32 +1434,3095 @@ impl PrivateMutationRoot {
 
             // make sure the event is in a DRAFT state only when editing tickets
             if !db_event.event_status.eq(&EventStatus::Draft) {
-                return Err(GqlError::Validation(ValidationError::new(
+                return Err(GqlError::Conflict(ValidationError::new(
                     "event_status",
                     "Tickets could only be edited for an event with status DRAFT",
                 )));
             }
 
-            // check the user is also the event creator
-            if !db_user.id.eq(&db_event.created_by_user) {
-                return Err(GqlError::Validation(ValidationError::new(
-                    "event_creator",
-                    "Event creator and calling user are not the same",
-                )));
+            // check caller is the event creator or a member of its organization
+            guard_event(ctx, &db_user.id, &db_event, EventAccess::Edit).await?;
+
+            // the update must be based on the ticket as it is now
+            let version = update_ticket.version;
+            if db_ticket.version != version {
+                return Err(GqlError::StaleTicket(Box::new(db_ticket)));
             }
 
             // validate and update the ticket mutation payload
-            let db_ticket =
-                update_ticket_mutation_payload(update_ticket, &db_event, &mut db_ticket)?;
+            update_ticket_mutation_payload(
+                &ctx.sanitation,
+                update_ticket,
+                &db_event,
+                &mut db_ticket,
+            )?;
+            updates.push((db_ticket, version));
+        }
 
-            // update the db with the ticket data
-            let updated_db_ticket = db_update_ticket(&ctx.db_client, &db_ticket)
+        let mut tickets: Vec<Ticket> = vec![];
+
+        for (db_ticket, version) in updates {
+            // the version is checked again when writing, the ticket may have been edited since
+            let updated_db_ticket = db_update_ticket(&ctx.db_client, &db_ticket, version)
                 .await
-                .map_err(GqlError::Database)?;
+                .map_err(|e| match e {
+                    TicketUpdateError::Postgres(e) => GqlError::Database(e),
+                    TicketUpdateError::VersionConflict(current) => GqlError::StaleTicket(current),
+                })?;
+            ctx.audit(
+                Some(user_id),
+                "update_event_ticket",
+                AuditEntity::Ticket(updated_db_ticket.id),
+                serde_json::to_value(&updated_db_ticket).ok(),
+            )
+            .await;
 
             tickets.push(Ticket::from(updated_db_ticket));
         }
 
         Ok(tickets)
     }
+
+    // -------------------------- PRICE TIERS ------------------- //
+    /// Adds a price tier to a ticket of a DRAFT event, e.g. an early-bird price
+    async fn create_price_tier(
+        ctx: &ResourcesContext,
+        new_price_tier: NewPriceTier,
+    ) -> Result<PriceTier, GqlError> {
+        let user_id = guard(ctx, Operation::ManagePriceTiers).await?.id;
+
+        check_new_price_tier_payload(&new_price_tier)?;
+        let ticket_id = new_price_tier.ticket_id;
+        let db_ticket = draft_ticket(ctx, &user_id, &ticket_id).await?;
+
+        let tiers = db_get_price_tiers_by_ticket_ids(&ctx.db_client, &[ticket_id])
+            .await
+            .map_err(GqlError::Database)?;
+        if tiers.len() >= MAX_PRICE_TIERS {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "ticket_id",
+                &format!("A ticket may have at most {} price tiers", MAX_PRICE_TIERS),
+            )));
+        }
+
+        let db_price_tier = DbTicketPriceTier::new(new_price_tier, &db_ticket);
+        db_insert_ticket_price_tier(&ctx.db_client, &db_price_tier)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "create_price_tier",
+            AuditEntity::Ticket(ticket_id),
+            serde_json::to_value(&db_price_tier).ok(),
+        )
+        .await;
+
+        Ok(PriceTier::from(db_price_tier))
+    }
+
+    /// Adds a section of seats to the event's seat map, the ticket type being sold as its seats
+    /// from then on, as many as the seats of all its sections
+    async fn create_seat_section(
+        ctx: &ResourcesContext,
+        new_seat_section: NewSeatSection,
+    ) -> Result<SeatSection, GqlError> {
+        let user_id = guard(ctx, Operation::ManageSeatMap).await?.id;
+
+        check_new_seat_section_payload(&new_seat_section)?;
+        let ticket_id = new_seat_section.ticket_id;
+        let db_ticket = draft_ticket(ctx, &user_id, &ticket_id).await?;
+
+        let sections = db_get_seat_sections_by_event_id(&ctx.db_client, &db_ticket.event_id)
+            .await
+            .map_err(GqlError::Database)?;
+        let db_section = DbSeatSection::new(
+            &db_ticket,
+            new_seat_section.section_name.trim(),
+            sections.len() as i32,
+        );
+        let db_seats = new_seat_section
+            .rows
+            .iter()
+            .flat_map(|row| {
+                let db_section = &db_section;
+                (1..=row.seats).map(move |seat_number| {
+                    DbSeat::new(db_section, row.row_label.trim(), seat_number)
+                })
+            })
+            .collect::<Vec<_>>();
+        db_insert_seat_section(&ctx.db_client, &db_section, &db_seats)
+            .await
+            .map_err(GqlError::Database)?
+            .ok_or_else(|| {
+                GqlError::Conflict(ValidationError::new(
+                    "section_name",
+                    "Event already has a section with the same name",
+                ))
+            })?;
+        ctx.event_cache.invalidate(&db_ticket.event_id);
+
+        ctx.audit(
+            Some(user_id),
+            "create_seat_section",
+            AuditEntity::Ticket(ticket_id),
+            Some(serde_json::json!({
+                "section_id": db_section.id,
+                "section_name": db_section.section_name,
+                "seats": db_seats.len(),
+            })),
+        )
+        .await;
+
+        Ok(SeatSection::new(
+            db_section,
+            &db_seats,
+            &sql_timestamp(None),
+        ))
+    }
+
+    async fn delete_price_tier(ctx: &ResourcesContext, id: Uuid) -> Result<PriceTier, GqlError> {
+        let user_id = guard(ctx, Operation::ManagePriceTiers).await?.id;
+
+        let db_price_tier = db_get_ticket_price_tier(&ctx.db_client, &id)
+            .await
+            .map_err(GqlError::Database)?
+            .ok_or_else(|| {
+                GqlError::NotFound(ValidationError::new(
+                    "price_tier_id",
+                    "Price tier with submitted id does not exist",
+                ))
+            })?;
+        draft_ticket(ctx, &user_id, &db_price_tier.ticket_id).await?;
+
+        db_delete_ticket_price_tier(&ctx.db_client, &id)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "delete_price_tier",
+            AuditEntity::Ticket(db_price_tier.ticket_id),
+            serde_json::to_value(&db_price_tier).ok(),
+        )
+        .await;
+
+        Ok(PriceTier::from(db_price_tier))
+    }
+
+    // -------------------------- ASSET UPLOADS ------------------- //
+    async fn create_upload_url(
+        new_upload_url: NewUploadUrl,
+        ctx: &ResourcesContext,
+    ) -> Result<UploadUrl, GqlError> {
+        // get the requesting user_id
+        let user_id = {
+            let lock = ctx.user_id.lock().await;
+            let user_id = *lock;
+            drop(lock);
+            user_id
+        }
+        .expect("Should have a uuid due to authenticated private gql route");
+
+        // get the event the asset is uploaded for
+        let event_id = new_upload_url.event_id;
+        let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+
+        // check caller is the event creator or a member of its organization
+        guard_event(ctx, &user_id, &db_event, EventAccess::Edit).await?;
+
+        // make sure the event is in a DRAFT state only
+        if !db_event.event_status.eq(&EventStatus::Draft) {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "event_status",
+                "Only event with status DRAFT could be edited",
+            )));
+        }
+
+        let extension = check_upload_content_type(&new_upload_url.content_type)?;
+
+        let asset_id = Uuid::new_v4();
+        let key = format!("events/{}/{}.{}", event_id, asset_id, extension);
+
+        let upload_url = ctx
+            .storage
+            .presigned_put_url(
+                &key,
+                &new_upload_url.content_type,
+                Duration::from_secs(UPLOAD_URL_EXPIRY_SECS),
+            )
+            .await
+            .map_err(|e| GqlError::Storage(e.to_string()))?;
+
+        // persist the asset as pending until the client confirms the upload
+        let asset_file = AssetFile::new_pending(
+            asset_id,
+            ctx.storage.bucket().to_string(),
+            key,
+            new_upload_url.content_type.clone(),
+            event_id,
+        );
+        insert_asset_file(&ctx.db_client, &asset_file)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "create_upload_url",
+            AuditEntity::Asset(asset_id),
+            serde_json::to_value(&new_upload_url).ok(),
+        )
+        .await;
+
+        Ok(UploadUrl {
+            asset_id,
+            upload_url,
+            content_type: new_upload_url.content_type,
+            expires_at: sql_timestamp(Some(UPLOAD_URL_EXPIRY_SECS as i64)),
+        })
+    }
+
+    async fn confirm_asset(
+        confirm_asset: ConfirmAsset,
+        ctx: &ResourcesContext,
+    ) -> Result<Event, GqlError> {
+        // get the requesting user_id
+        let user_id = {
+            let lock = ctx.user_id.lock().await;
+            let user_id = *lock;
+            drop(lock);
+            user_id
+        }
+        .expect("Should have a uuid due to authenticated private gql route");
+
+        let asset_id = confirm_asset.asset_id;
+        let asset_file = db_get_asset_file(&ctx.db_client, &asset_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "asset_id",
+                    "Asset with submitted id does not exist",
+                ))
+            })?;
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &asset_file.event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+
+        // check caller is the event creator or a member of its organization
+        guard_event(ctx, &user_id, &db_event, EventAccess::Edit).await?;
+
+        // make sure the event is in a DRAFT state only
+        if !db_event.event_status.eq(&EventStatus::Draft) {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "event_status",
+                "Only event with status DRAFT could be edited",
+            )));
+        }
+
+        // check the object actually landed in the bucket
+        let is_uploaded = ctx
+            .storage
+            .exists(&asset_file.s3_absolute_key)
+            .await
+            .map_err(|e| GqlError::Storage(e.to_string()))?;
+        if !is_uploaded {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "asset_id",
+                "Asset has not been uploaded yet",
+            )));
+        }
+
+        let _ = db_confirm_asset_file(
+            &ctx.db_client,
+            &asset_id,
+            AssetRole::from(confirm_asset.kind),
+        )
+        .await
+        .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "confirm_asset",
+            AuditEntity::Asset(asset_id),
+            serde_json::to_value(&confirm_asset).ok(),
+        )
+        .await;
+
+        let asset_url = ctx.storage.asset_url(asset_file.s3_absolute_key.clone());
+        let updated_db_event =
+            db_update_event_asset_url(&ctx.db_client, &db_event.id, confirm_asset.kind, &asset_url)
+                .await
+                .map_err(GqlError::Database)?;
+
+        // resize the image to its variants in the background
+        let _ = enqueue(
+            &ctx.db_client,
+            JobPayload::ImageVariants {
+                asset_id,
+                kind: confirm_asset.kind,
+            },
+            ctx.jobs.max_attempts,
+        )
+        .await
+        .map_err(GqlError::Database)?;
+
+        // pin the asset to ipfs in the background
+        if ctx.ipfs_client.is_some() {
+            let _ = enqueue(
+                &ctx.db_client,
+                JobPayload::IpfsPin {
+                    asset_id,
+                    kind: confirm_asset.kind,
+                },
+                ctx.jobs.max_attempts,
+            )
+            .await
+            .map_err(GqlError::Database)?;
+        }
+
+        // get the related event tickets
+        let tickets = db_get_tickets_by_event_id(&ctx.db_client, &Some(updated_db_event.id))
+            .await
+            .map_err(GqlError::Database)?;
+
+        ctx.readable_event(Event::new(updated_db_event, tickets))
+            .await
+    }
+
+    /// Adds an uploaded image to the end of its event's gallery
+    async fn add_gallery_image(ctx: &ResourcesContext, asset_id: Uuid) -> Result<Event, GqlError> {
+        let user_id = guard(ctx, Operation::ManageGallery).await?.id;
+
+        let asset_file = db_get_asset_file(&ctx.db_client, &asset_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "asset_id",
+                    "Asset with submitted id does not exist",
+                ))
+            })?;
+        let db_event = draft_event(ctx, &user_id, &asset_file.event_id).await?;
+
+        let gallery = db_get_gallery_assets_by_event_ids(&ctx.db_client, &[db_event.id])
+            .await
+            .map_err(GqlError::Database)?;
+        if gallery.len() >= MAX_GALLERY_IMAGES {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "event_id",
+                &format!(
+                    "An event gallery may have at most {} images",
+                    MAX_GALLERY_IMAGES
+                ),
+            )));
+        }
+
+        // check the object actually landed in the bucket
+        let is_uploaded = ctx
+            .storage
+            .exists(&asset_file.s3_absolute_key)
+            .await
+            .map_err(|e| GqlError::Storage(e.to_string()))?;
+        if !is_uploaded {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "asset_id",
+                "Asset has not been uploaded yet",
+            )));
+        }
+
+        let gallery_asset = db_add_gallery_asset(&ctx.db_client, &asset_id, &db_event.id)
+            .await
+            .map_err(GqlError::Database)?
+            .ok_or_else(|| {
+                GqlError::Conflict(ValidationError::new(
+                    "asset_id",
+                    "Asset is attached to its event already",
+                ))
+            })?;
+        ctx.audit(
+            Some(user_id),
+            "add_gallery_image",
+            AuditEntity::Asset(asset_id),
+            Some(serde_json::json!({
+                "eventId": db_event.id,
+                "position": gallery_asset.position,
+            })),
+        )
+        .await;
+
+        event_with_gallery(ctx, db_event).await
+    }
+
+    /// Takes an image out of its event's gallery, the images after it move up
+    async fn remove_gallery_image(
+        ctx: &ResourcesContext,
+        asset_id: Uuid,
+    ) -> Result<Event, GqlError> {
+        let user_id = guard(ctx, Operation::ManageGallery).await?.id;
+
+        let asset_file = db_get_asset_file(&ctx.db_client, &asset_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "asset_id",
+                    "Asset with submitted id does not exist",
+                ))
+            })?;
+        let db_event = draft_event(ctx, &user_id, &asset_file.event_id).await?;
+
+        db_remove_gallery_asset(&ctx.db_client, &asset_id, &db_event.id)
+            .await
+            .map_err(GqlError::Database)?
+            .ok_or_else(|| {
+                GqlError::NotFound(ValidationError::new(
+                    "asset_id",
+                    "Asset is not in the event's gallery",
+                ))
+            })?;
+        ctx.audit(
+            Some(user_id),
+            "remove_gallery_image",
+            AuditEntity::Asset(asset_id),
+            Some(serde_json::json!({ "eventId": db_event.id })),
+        )
+        .await;
+
+        event_with_gallery(ctx, db_event).await
+    }
+
+    /// Puts the images of an event's gallery in a new order
+    async fn reorder_gallery(
+        ctx: &ResourcesContext,
+        reorder_gallery: ReorderGallery,
+    ) -> Result<Event, GqlError> {
+        let user_id = guard(ctx, Operation::ManageGallery).await?.id;
+
+        let event_id = reorder_gallery.event_id;
+        let db_event = draft_event(ctx, &user_id, &event_id).await?;
+
+        let gallery = db_get_gallery_assets_by_event_ids(&ctx.db_client, &[event_id])
+            .await
+            .map_err(GqlError::Database)?
+            .iter()
+            .map(|asset_file| asset_file.id)
+            .collect::<Vec<_>>();
+        let asset_ids = check_gallery_order(&gallery, &reorder_gallery.asset_ids)?;
+
+        db_reorder_gallery_assets(&ctx.db_client, &event_id, &asset_ids)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "reorder_gallery",
+            AuditEntity::Event(event_id),
+            serde_json::to_value(&reorder_gallery).ok(),
+        )
+        .await;
+
+        event_with_gallery(ctx, db_event).await
+    }
+
+    // -------------------------- WEBHOOKS ------------------- //
+    /// Registers the url the caller's webhook deliveries are posted to, e.g. low-stock alerts.
+    /// A new secret is returned, only this once, replacing the one of a previous registration
+    async fn set_webhook(ctx: &ResourcesContext, url: String) -> Result<SellerWebhook, GqlError> {
+        let user_id = guard(ctx, Operation::ManageWebhook).await?.id;
+
+        let url = check_webhook_url(&url)?;
+        let secret = gen_webhook_secret();
+        let db_webhook = DbSellerWebhook::new(user_id, &url, &secret);
+        db_upsert_seller_webhook(&ctx.db_client, &db_webhook)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "set_webhook",
+            AuditEntity::User(user_id),
+            serde_json::to_value(&db_webhook).ok(),
+        )
+        .await;
+
+        Ok(SellerWebhook {
+            secret: Some(secret),
+            ..SellerWebhook::from(db_webhook)
+        })
+    }
+
+    /// Removes the caller's webhook, pending deliveries are dropped
+    async fn remove_webhook(ctx: &ResourcesContext) -> Result<bool, GqlError> {
+        let user_id = guard(ctx, Operation::ManageWebhook).await?.id;
+
+        let removed = db_delete_seller_webhook(&ctx.db_client, &user_id)
+            .await
+            .map_err(GqlError::Database)?;
+        if removed {
+            ctx.audit(
+                Some(user_id),
+                "remove_webhook",
+                AuditEntity::User(user_id),
+                None,
+            )
+            .await;
+        }
+
+        Ok(removed)
+    }
+
+    // -------------------------- CANCELLATIONS ------------------- //
+    /// Sets the hours before the event's start until which buyers may cancel their
+    /// reservations, buyers cannot cancel when not set
+    async fn set_cancellation_window(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+        hours: Option<i32>,
+    ) -> Result<Event, GqlError> {
+        let user_id = guard(ctx, Operation::ManageCancellationWindow).await?.id;
+
+        if let Some(hours) = hours {
+            check_cancellation_window(hours)?;
+        }
+        let db_event = accessible_event(ctx, &user_id, &event_id, EventAccess::Edit).await?;
+        if db_event.event_status.eq(&EventStatus::Cancelled) {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "event_status",
+                "Event has been cancelled",
+            )));
+        }
+
+        let db_event = db_update_event_cancellation_window(&ctx.db_client, &event_id, hours)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "set_cancellation_window",
+            AuditEntity::Event(event_id),
+            Some(serde_json::json!({ "cancellationWindowHours": hours })),
+        )
+        .await;
+
+        event_with_gallery(ctx, db_event).await
+    }
+
+    /// Cancels the event and every reservation of it, no more tickets are reserved. The buyers
+    /// are told and refunded what they paid
+    async fn cancel_event(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+    ) -> Result<CancelledEvent, GqlError> {
+        let user_id = guard(ctx, Operation::CancelEvent).await?.id;
+
+        let db_event = accessible_event(ctx, &user_id, &event_id, EventAccess::Cancel).await?;
+
+        let (db_event, db_cancellations) = cancellations::cancel_event(ctx, &db_event, &user_id)
+            .await
+            .map_err(|e| match e {
+                Error::Postgres(e) => GqlError::Database(e),
+                _ => GqlError::UnexpectedInternal,
+            })?
+            .ok_or_else(|| {
+                GqlError::Conflict(ValidationError::new(
+                    "event_status",
+                    "Event has already been cancelled",
+                ))
+            })?;
+        ctx.audit(
+            Some(user_id),
+            "cancel_event",
+            AuditEntity::Event(event_id),
+            Some(serde_json::json!({
+                "cancelledReservations": db_cancellations
+                    .iter()
+                    .map(|db_cancellation| db_cancellation.id.to_string())
+                    .collect::<Vec<_>>(),
+            })),
+        )
+        .await;
+
+        Ok(CancelledEvent {
+            event: event_with_gallery(ctx, db_event).await?,
+            cancellations: db_cancellations
+                .into_iter()
+                .map(TicketCancellation::from)
+                .collect(),
+        })
+    }
+
+    // -------------------------- PROMO CODES ------------------- //
+
+    async fn create_promo_code(
+        new_promo_code: NewPromoCode,
+        ctx: &ResourcesContext,
+    ) -> Result<PromoCode, GqlError> {
+        // get the requesting user_id
+        let user_id = {
+            let lock = ctx.user_id.lock().await;
+            let user_id = *lock;
+            drop(lock);
+            user_id
+        }
+        .expect("Should have a uuid due to authenticated private gql route");
+
+        // find user in the db
+        let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "user_id",
+                    "User not found in the database",
+                ))
+            })?;
+
+        // check new promo code data
+        check_new_promo_code_payload(&new_promo_code)?;
+
+        // get promo code event uuid
+        let event_id = new_promo_code.event_id;
+
+        // check for event id that the promo code will be attached to
+        let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+
+        // check caller is the event creator or a member of its organization
+        guard_event(ctx, &db_user.id, &db_event, EventAccess::Edit).await?;
+
+        // check we don't have the same code for the event already
+        if let Ok(_promo_code) =
+            db_get_promo_code_by_code(&ctx.db_client, &event_id, &new_promo_code.code).await
+        {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "promo_code",
+                "Promo code already exists for this event",
+            )));
+        }
+
+        // save the promo code into the db
+        let db_promo_code = DbPromoCode::new(new_promo_code, event_id, user_id);
+        db_insert_promo_code(&ctx.db_client, &db_promo_code)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "create_promo_code",
+            AuditEntity::PromoCode(db_promo_code.id),
+            serde_json::to_value(&db_promo_code).ok(),
+        )
+        .await;
+
+        Ok(PromoCode::from(db_promo_code))
+    }
+
+    async fn disable_promo_code(ctx: &ResourcesContext, id: Uuid) -> Result<PromoCode, GqlError> {
+        // get the requesting user_id
+        let user_id = {
+            let lock = ctx.user_id.lock().await;
+            let user_id = *lock;
+            drop(lock);
+            user_id
+        }
+        .expect("Should have a uuid due to authenticated private gql route");
+
+        let db_promo_code = db_get_promo_code_by_id(&ctx.db_client, &id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "promo_code_id",
+                    "Promo code with submitted id does not exist",
+                ))
+            })?;
+
+        // check caller is the promo code creator or may edit its event
+        if !user_id.eq(&db_promo_code.created_by_user) {
+            let db_event = db_get_event_by_id(&ctx.db_client, &db_promo_code.event_id)
+                .await
+                .map_err(|_| {
+                    GqlError::NotFound(ValidationError::new(
+                        "event_id",
+                        "Event with submitted id does not exist",
+                    ))
+                })?;
+            guard_event(ctx, &user_id, &db_event, EventAccess::Edit).await?;
+        }
+
+        let updated_db_promo_code = db_update_promo_code_is_active(&ctx.db_client, &id, false)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "disable_promo_code",
+            AuditEntity::PromoCode(id),
+            None,
+        )
+        .await;
+
+        Ok(PromoCode::from(updated_db_promo_code))
+    }
+
+    // -------------------------- ORGANIZATIONS ------------------- //
+    async fn create_organization(
+        ctx: &ResourcesContext,
+        name: String,
+    ) -> Result<Organization, GqlError> {
+        let user_id = guard(ctx, Operation::CreateOrganization).await?.id;
+
+        check_organization_name(&name)?;
+        let db_organization = DbOrganization::new(&name, user_id);
+        if db_get_organization_by_slug(&ctx.db_client, &db_organization.slug)
+            .await
+            .map_err(GqlError::Database)?
+            .is_some()
+        {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "name",
+                "Organization with the same slug already exists",
+            )));
+        }
+
+        // the creator is the organization's first owner
+        let db_organization = db_insert_organization(&ctx.db_client, &db_organization)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "create_organization",
+            AuditEntity::Organization(db_organization.id),
+            serde_json::to_value(&db_organization).ok(),
+        )
+        .await;
+
+        Ok(Organization::from(db_organization))
+    }
+
+    /// adds a seller to the organization, or changes the role of a member
+    async fn add_organization_member(
+        ctx: &ResourcesContext,
+        organization_id: Uuid,
+        username: String,
+        member_role: MemberRole,
+    ) -> Result<OrganizationMember, GqlError> {
+        let user_id = guard(ctx, Operation::AddOrganizationMember).await?.id;
+
+        let db_organization = organization_owned_by(ctx, &organization_id, &user_id).await?;
+
+        let db_member_user = db_get_user_by_username(&ctx.db_client, &username)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "username",
+                    "User with submitted username does not exist",
+                ))
+            })?;
+        // members act on the organization's events through the seller operations
+        if !db_member_user.user_type.eq(&Role::Seller) {
+            return Err(GqlError::Validation(ValidationError::new(
+                "username",
+                "Only sellers can be organization members",
+            )));
+        }
+        // the creator stays an owner, so that an organization always has one
+        if db_member_user.id.eq(&db_organization.created_by_user) {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "username",
+                "The organization creator's role cannot be changed",
+            )));
+        }
+
+        let db_member = DbOrganizationMember::new(organization_id, db_member_user.id, member_role);
+        db_upsert_organization_member(&ctx.db_client, &db_member)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "add_organization_member",
+            AuditEntity::Organization(organization_id),
+            serde_json::to_value(&db_member).ok(),
+        )
+        .await;
+
+        Ok(OrganizationMember::from(db_member))
+    }
+
+    /// removes a member from the organization, owners remove anyone and members themselves
+    async fn remove_organization_member(
+        ctx: &ResourcesContext,
+        organization_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, GqlError> {
+        let caller_id = guard(ctx, Operation::RemoveOrganizationMember).await?.id;
+
+        let db_organization = if caller_id.eq(&user_id) {
+            db_get_organization_by_id(&ctx.db_client, &organization_id)
+                .await
+                .map_err(GqlError::Database)?
+                .ok_or_else(|| {
+                    GqlError::NotFound(ValidationError::new(
+                        "organization_id",
+                        "Organization with submitted id does not exist",
+                    ))
+                })?
+        } else {
+            organization_owned_by(ctx, &organization_id, &caller_id).await?
+        };
+        if user_id.eq(&db_organization.created_by_user) {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "user_id",
+                "The organization creator cannot be removed",
+            )));
+        }
+
+        let deleted = db_delete_organization_member(&ctx.db_client, &organization_id, &user_id)
+            .await
+            .map_err(GqlError::Database)?;
+        if deleted == 0 {
+            return Err(GqlError::NotFound(ValidationError::new(
+                "user_id",
+                "User is not a member of the organization",
+            )));
+        }
+        ctx.audit(
+            Some(caller_id),
+            "remove_organization_member",
+            AuditEntity::Organization(organization_id),
+            Some(serde_json::json!({ "user_id": user_id })),
+        )
+        .await;
+        Ok(true)
+    }
+
+    /// moves the event into an organization the caller edits events of, or out of its
+    /// organization if none is given. Only the event creator may move an event
+    async fn set_event_organization(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+        organization_id: Option<Uuid>,
+    ) -> Result<Event, GqlError> {
+        let user_id = guard(ctx, Operation::RegisterEvent).await?.id;
+
+        event_created_by(ctx, &event_id, &user_id).await?;
+        if let Some(organization_id) = organization_id.as_ref() {
+            organization_editor(ctx, organization_id, &user_id).await?;
+        }
+
+        let updated_db_event =
+            db_update_event_organization(&ctx.db_client, &event_id, organization_id.as_ref())
+                .await
+                .map_err(GqlError::Database)?;
+        ctx.event_cache.invalidate(&event_id);
+        ctx.audit(
+            Some(user_id),
+            "set_event_organization",
+            AuditEntity::Event(event_id),
+            Some(serde_json::json!({ "organization_id": updated_db_event.organization_id })),
+        )
+        .await;
+
+        let tickets = db_get_tickets_by_event_id(&ctx.db_client, &Some(event_id))
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.readable_event(Event::new(updated_db_event, tickets))
+            .await
+    }
+
+    /// adds a seller as a collaborator of the event, or changes the role of a collaborator.
+    /// Editors edit the event like its creator, scanners check in its attendees. Only the event
+    /// creator manages its collaborators
+    async fn add_event_collaborator(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+        username: String,
+        collaborator_role: MemberRole,
+    ) -> Result<EventCollaborator, GqlError> {
+        let user_id = guard(ctx, Operation::ManageEventCollaborators).await?.id;
+
+        let db_event = event_created_by(ctx, &event_id, &user_id).await?;
+
+        // the creator is the event's only owner
+        if collaborator_role.eq(&MemberRole::Owner) {
+            return Err(GqlError::Validation(ValidationError::new(
+                "collaborator_role",
+                "Collaborators are either editors or scanners",
+            )));
+        }
+        let db_collaborator_user = db_get_user_by_username(&ctx.db_client, &username)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "username",
+                    "User with submitted username does not exist",
+                ))
+            })?;
+        if !db_collaborator_user.user_type.eq(&Role::Seller) {
+            return Err(GqlError::Validation(ValidationError::new(
+                "username",
+                "Only sellers can be event collaborators",
+            )));
+        }
+        if db_collaborator_user.id.eq(&db_event.created_by_user) {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "username",
+                "The event creator cannot be a collaborator",
+            )));
+        }
+
+        let db_collaborator =
+            DbEventCollaborator::new(event_id, db_collaborator_user.id, collaborator_role);
+        db_upsert_event_collaborator(&ctx.db_client, &db_collaborator)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "add_event_collaborator",
+            AuditEntity::Event(event_id),
+            serde_json::to_value(&db_collaborator).ok(),
+        )
+        .await;
+
+        Ok(EventCollaborator::from(db_collaborator))
+    }
+
+    /// removes a collaborator from the event, the event creator removes anyone and
+    /// collaborators themselves
+    async fn remove_event_collaborator(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, GqlError> {
+        let caller_id = guard(ctx, Operation::ManageEventCollaborators).await?.id;
+
+        if !caller_id.eq(&user_id) {
+            event_created_by(ctx, &event_id, &caller_id).await?;
+        }
+
+        let deleted = db_delete_event_collaborator(&ctx.db_client, &event_id, &user_id)
+            .await
+            .map_err(GqlError::Database)?;
+        if deleted == 0 {
+            return Err(GqlError::NotFound(ValidationError::new(
+                "user_id",
+                "User is not a collaborator of the event",
+            )));
+        }
+        ctx.audit(
+            Some(caller_id),
+            "remove_event_collaborator",
+            AuditEntity::Event(event_id),
+            Some(serde_json::json!({ "user_id": user_id })),
+        )
+        .await;
+        Ok(true)
+    }
+
+    // -------------------------- CHECK-IN ------------------- //
+    /// checks in the event's reservations for the verification code the buyer shows at the door
+    async fn check_in_tickets(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+        verification_code: String,
+    ) -> Result<Vec<CheckedInReservation>, GqlError> {
+        let user_id = guard(ctx, Operation::CheckInTickets).await?.id;
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+        guard_event(ctx, &user_id, &db_event, EventAccess::CheckIn).await?;
+
+        let checked_in =
+            db_check_in_ticket_reservations(&ctx.db_client, &event_id, &verification_code)
+                .await
+                .map_err(GqlError::Database)?;
+        if checked_in.is_empty() {
+            let reservations =
+                db_get_ticket_reservations_by_code(&ctx.db_client, &verification_code)
+                    .await
+                    .map_err(GqlError::Database)?;
+            if reservations
+                .iter()
+                .any(|reservation| reservation.event_id.eq(&event_id))
+            {
+                return Err(GqlError::Conflict(ValidationError::new(
+                    "verification_code",
+                    "Tickets for the verification code are already checked in",
+                )));
+            }
+            return Err(GqlError::NotFound(ValidationError::new(
+                "verification_code",
+                "No tickets of the event for the verification code",
+            )));
+        }
+
+        for reservation in checked_in.iter() {
+            ctx.audit(
+                Some(user_id),
+                "check_in_tickets",
+                AuditEntity::TicketReservation(reservation.id),
+                None,
+            )
+            .await;
+        }
+        Ok(checked_in
+            .into_iter()
+            .map(CheckedInReservation::from)
+            .collect())
+    }
+
+    // -------------------------- SELLER ONBOARDING ------------------- //
+    /// A presigned url the calling seller uploads an onboarding document to, its `assetId` is the
+    /// document's id to confirm
+    async fn create_seller_document_upload_url(
+        ctx: &ResourcesContext,
+        new_seller_document: NewSellerDocument,
+    ) -> Result<UploadUrl, GqlError> {
+        let db_user = guard(ctx, Operation::SubmitSellerOnboarding).await?;
+        check_seller_documents_editable(&db_user)?;
+
+        let extension = check_document_content_type(&new_seller_document.content_type)?;
+        let document_id = Uuid::new_v4();
+        let key = format!("sellers/{}/{}.{}", db_user.id, document_id, extension);
+
+        let upload_url = ctx
+            .storage
+            .presigned_put_url(
+                &key,
+                &new_seller_document.content_type,
+                Duration::from_secs(UPLOAD_URL_EXPIRY_SECS),
+            )
+            .await
+            .map_err(|e| GqlError::Storage(e.to_string()))?;
+
+        // persist the document as pending until the seller confirms the upload
+        let db_seller_document = DbSellerDocument::new_pending(
+            document_id,
+            db_user.id,
+            new_seller_document.kind,
+            ctx.storage.bucket().to_string(),
+            key,
+            new_seller_document.content_type.clone(),
+        );
+        db_insert_seller_document(&ctx.db_client, &db_seller_document)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(db_user.id),
+            "create_seller_document_upload_url",
+            AuditEntity::User(db_user.id),
+            Some(serde_json::json!({ "document_id": document_id })),
+        )
+        .await;
+
+        Ok(UploadUrl {
+            asset_id: document_id,
+            upload_url,
+            content_type: new_seller_document.content_type,
+            expires_at: sql_timestamp(Some(UPLOAD_URL_EXPIRY_SECS as i64)),
+        })
+    }
+
+    async fn confirm_seller_document(
+        ctx: &ResourcesContext,
+        document_id: Uuid,
+    ) -> Result<SellerDocument, GqlError> {
+        let db_user = guard(ctx, Operation::SubmitSellerOnboarding).await?;
+        check_seller_documents_editable(&db_user)?;
+
+        let db_seller_document = db_get_seller_document(&ctx.db_client, &document_id)
+            .await
+            .map_err(GqlError::Database)?
+            .filter(|document| document.user_id.eq(&db_user.id))
+            .ok_or_else(|| {
+                GqlError::NotFound(ValidationError::new(
+                    "document_id",
+                    "Document with submitted id does not exist",
+                ))
+            })?;
+
+        // check the object actually landed in the bucket
+        let is_uploaded = ctx
+            .storage
+            .exists(&db_seller_document.s3_absolute_key)
+            .await
+            .map_err(|e| GqlError::Storage(e.to_string()))?;
+        if !is_uploaded {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "document_id",
+                "Document has not been uploaded yet",
+            )));
+        }
+
+        let confirmed_db_seller_document = db_confirm_seller_document(&ctx.db_client, &document_id)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(db_user.id),
+            "confirm_seller_document",
+            AuditEntity::User(db_user.id),
+            Some(serde_json::json!({ "document_id": document_id })),
+        )
+        .await;
+
+        Ok(SellerDocument::from(confirmed_db_seller_document))
+    }
+
+    /// Submits the calling seller's confirmed documents for an admin's review
+    async fn submit_seller_onboarding(ctx: &ResourcesContext) -> Result<User, GqlError> {
+        let db_user = guard(ctx, Operation::SubmitSellerOnboarding).await?;
+        check_seller_documents_editable(&db_user)?;
+
+        let has_documents = db_get_seller_documents_by_user_id(&ctx.db_client, &db_user.id)
+            .await
+            .map_err(GqlError::Database)?
+            .iter()
+            .any(|document| document.is_confirmed);
+        if !has_documents {
+            return Err(GqlError::Validation(ValidationError::new(
+                "documents",
+                "At least one confirmed document is required",
+            )));
+        }
+
+        let updated_db_user = db_update_seller_status(
+            &ctx.db_client,
+            &db_user.id,
+            &[SellerStatus::Onboarding, SellerStatus::Rejected],
+            SellerStatus::PendingReview,
+            None,
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(|| {
+            GqlError::Conflict(ValidationError::new(
+                "seller_status",
+                "Seller onboarding has already been submitted",
+            ))
+        })?;
+        ctx.audit(
+            Some(db_user.id),
+            "submit_seller_onboarding",
+            AuditEntity::User(db_user.id),
+            None,
+        )
+        .await;
+
+        Ok(User::from(updated_db_user))
+    }
+
+    // -------------------------- PAYOUTS ------------------- //
+    async fn register_payout_account(
+        ctx: &ResourcesContext,
+        wallet_id: String,
+    ) -> Result<PayoutAccount, GqlError> {
+        let user_id = guard(ctx, Operation::RegisterPayoutAccount).await?.id;
+
+        check_payout_wallet_id(&wallet_id)?;
+
+        let db_payout_account =
+            db_upsert_payout_account(&ctx.db_client, &DbPayoutAccount::new(&wallet_id, user_id))
+                .await
+                .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "register_payout_account",
+            AuditEntity::PayoutAccount(db_payout_account.id),
+            serde_json::to_value(&db_payout_account).ok(),
+        )
+        .await;
+
+        Ok(PayoutAccount::from(db_payout_account))
+    }
+
+    async fn request_payout(
+        ctx: &ResourcesContext,
+        amount: Option<NearAmount>,
+    ) -> Result<PayoutRequest, GqlError> {
+        // get the requesting user_id
+        let user_id = {
+            let lock = ctx.user_id.lock().await;
+            let user_id = *lock;
+            drop(lock);
+            user_id
+        }
+        .expect("Should have a uuid due to authenticated private gql route");
+
+        // payouts are sent to the registered payout account
+        let db_payout_account = db_get_payout_account_by_user_id(&ctx.db_client, &user_id)
+            .await
+            .map_err(GqlError::Database)?
+            .ok_or_else(|| {
+                GqlError::NotFound(ValidationError::new(
+                    "payout_account",
+                    "No payout account registered",
+                ))
+            })?;
+
+        // request everything available unless a lower amount is given
+        let db_payout_balance = db_get_payout_balance(&ctx.db_client, &user_id)
+            .await
+            .map_err(GqlError::Database)?;
+        let available = db_payout_balance.available();
+        let amount = amount.unwrap_or(available);
+
+        if amount.eq(&NearAmount::default()) {
+            return Err(GqlError::Validation(ValidationError::new(
+                "amount",
+                "Payout amount must be positive",
+            )));
+        }
+
+        if amount > available {
+            return Err(GqlError::Validation(ValidationError::new(
+                "amount",
+                "Payout amount exceeds the available balance",
+            )));
+        }
+
+        let db_payout_request = DbPayoutRequest::new(amount, &db_payout_account.wallet_id, user_id);
+        db_insert_payout_request(&ctx.db_client, &db_payout_request)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "request_payout",
+            AuditEntity::PayoutRequest(db_payout_request.id),
+            serde_json::to_value(&db_payout_request).ok(),
+        )
+        .await;
+
+        Ok(PayoutRequest::from(db_payout_request))
+    }
+
+    async fn set_event_category(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+        category: Option<String>,
+    ) -> Result<Event, GqlError> {
+        // get the requesting user_id
+        let user_id = {
+            let lock = ctx.user_id.lock().await;
+            let user_id = *lock;
+            drop(lock);
+            user_id
+        }
+        .expect("Should have a uuid due to authenticated private gql route");
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+
+        // check caller is the event creator or a member of its organization
+        guard_event(ctx, &user_id, &db_event, EventAccess::Edit).await?;
+
+        // no category clears the event's category
+        let db_category = match category {
+            Some(slug) => Some(
+                db_get_category_by_slug(&ctx.db_client, &slug)
+                    .await
+                    .map_err(GqlError::Database)?
+                    .ok_or_else(|| {
+                        GqlError::NotFound(ValidationError::new(
+                            "category",
+                            "Category with submitted slug does not exist",
+                        ))
+                    })?,
+            ),
+            None => None,
+        };
+
+        let updated_db_event = db_update_event_category(
+            &ctx.db_client,
+            &event_id,
+            db_category.as_ref().map(|category| &category.id),
+        )
+        .await
+        .map_err(GqlError::Database)?;
+        ctx.event_cache.invalidate(&event_id);
+        ctx.audit(
+            Some(user_id),
+            "set_event_category",
+            AuditEntity::Event(event_id),
+            Some(serde_json::json!({ "category_id": updated_db_event.category_id })),
+        )
+        .await;
+
+        let tickets = db_get_tickets_by_event_id(&ctx.db_client, &Some(event_id))
+            .await
+            .map_err(GqlError::Database)?;
+        let tags = db_get_event_tags(&ctx.db_client, &[event_id])
+            .await
+            .map_err(GqlError::Database)?;
+
+        ctx.readable_event(
+            Event::new(updated_db_event, tickets)
+                .with_tags(tags.into_iter().map(|tag| tag.tag).collect()),
+        )
+        .await
+    }
+
+    async fn set_event_tags(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+        tags: Vec<String>,
+    ) -> Result<Event, GqlError> {
+        // get the requesting user_id
+        let user_id = {
+            let lock = ctx.user_id.lock().await;
+            let user_id = *lock;
+            drop(lock);
+            user_id
+        }
+        .expect("Should have a uuid due to authenticated private gql route");
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+
+        // check caller is the event creator or a member of its organization
+        guard_event(ctx, &user_id, &db_event, EventAccess::Edit).await?;
+
+        let tags = check_event_tags(&tags)?;
+
+        db_set_event_tags(&ctx.db_client, &event_id, &tags)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.event_cache.invalidate(&event_id);
+        ctx.audit(
+            Some(user_id),
+            "set_event_tags",
+            AuditEntity::Event(event_id),
+            Some(serde_json::json!({ "tags": tags })),
+        )
+        .await;
+
+        let tickets = db_get_tickets_by_event_id(&ctx.db_client, &Some(event_id))
+            .await
+            .map_err(GqlError::Database)?;
+
+        ctx.readable_event(Event::new(db_event, tickets).with_tags(tags))
+            .await
+    }
+}
+
+/// Served on `/graphql/buyer` to buyers only
+#[derive(Copy, Clone, Default)]
+pub struct BuyerMutationRoot;
+
+#[juniper::graphql_object(Context = ResourcesContext)]
+impl BuyerMutationRoot {
+    async fn api_version() -> juniper::FieldResult<&'static str> {
+        Ok("v1.0".into())
+    }
+
+    /// Re-encrypts the caller's wallet secret key with a new secret, e.g. when the device
+    /// holding the current one is compromised
+    async fn rotate_wallet_secret(
+        ctx: &ResourcesContext,
+        rotate_wallet_secret: RotateWalletSecret,
+    ) -> Result<User, GqlError> {
+        let db_user = guard(ctx, Operation::RotateWalletSecret).await?;
+
+        check_rotate_wallet_secret_payload(&rotate_wallet_secret)?;
+
+        let encrypted_secret_key = db_user.encrypted_secret_key.clone().ok_or_else(|| {
+            GqlError::Validation(ValidationError::new(
+                "encrypted_secret_key",
+                "User has no wallet secret key",
+            ))
+        })?;
+
+        // decrypt with the current secret and encrypt with the new one
+        let encrypted_data = {
+            let mut lock = ctx.grpc_near_client.lock().await;
+            let secret_key = lock
+                .aes_decrypt_data(&encrypted_secret_key, &rotate_wallet_secret.current_secret)
+                .await
+                .map_err(|_| {
+                    GqlError::Validation(ValidationError::new(
+                        "current_secret",
+                        "Wallet secret key cannot be decrypted with the current secret",
+                    ))
+                })?
+                .data;
+            if secret_key.is_empty() {
+                return Err(GqlError::Validation(ValidationError::new(
+                    "current_secret",
+                    "Wallet secret key cannot be decrypted with the current secret",
+                )));
+            }
+            let encrypted_data = lock
+                .aes_encrypt_data(&rotate_wallet_secret.new_secret, &secret_key)
+                .await
+                .map_err(GqlError::Grpc)?;
+            drop(lock);
+            encrypted_data
+        };
+
+        // a concurrent rotation wins, this one must then be retried with its secret
+        let updated_db_user = db_update_user_encrypted_secret_key(
+            &ctx.db_client,
+            &db_user.id,
+            &encrypted_secret_key,
+            &encrypted_data.cypher,
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(|| {
+            GqlError::Conflict(ValidationError::new(
+                "encrypted_secret_key",
+                "Wallet secret key has been changed concurrently",
+            ))
+        })?;
+        ctx.audit(
+            Some(db_user.id),
+            "rotate_wallet_secret",
+            AuditEntity::User(db_user.id),
+            None,
+        )
+        .await;
+
+        Ok(User::from(updated_db_user))
+    }
+
+    /// Returns the caller's wallet secret key, to import the wallet in another app. The caller
+    /// re-verifies with a recovery code or their password, every attempt is audited and counts
+    /// against a daily limit
+    async fn export_wallet(
+        ctx: &ResourcesContext,
+        export_wallet: ExportWallet,
+    ) -> Result<WalletExport, GqlError> {
+        let db_user = guard(ctx, Operation::ExportWallet).await?;
+
+        let verification = check_export_wallet_payload(&export_wallet)?;
+
+        // failed attempts count too, the password or recovery code is not guessed through here
+        let attempts = db_count_audit_logs_since(
+            &ctx.db_client,
+            &db_user.id,
+            "export_wallet",
+            &sql_timestamp(Some(-WALLET_EXPORTS_WINDOW_SECS)),
+        )
+        .await
+        .map_err(GqlError::Database)?;
+        if attempts >= ctx.sessions.max_wallet_exports_per_day {
+            return Err(GqlError::Forbidden(ValidationError::new(
+                "export_wallet",
+                "Too many wallet exports, try again tomorrow",
+            )));
+        }
+
+        let method = match verification {
+            WalletExportVerification::RecoveryCode { .. } => "recovery_code",
+            WalletExportVerification::Password(_) => "password",
+        };
+        let verified = verify_wallet_export(ctx, &db_user, verification).await;
+        ctx.audit(
+            Some(db_user.id),
+            "export_wallet",
+            AuditEntity::User(db_user.id),
+            Some(serde_json::json!({ "method": method, "verified": verified.is_ok() })),
+        )
+        .await;
+        verified?;
+
+        let encrypted_secret_key = db_user.encrypted_secret_key.clone().ok_or_else(|| {
+            GqlError::Validation(ValidationError::new(
+                "encrypted_secret_key",
+                "User has no wallet secret key",
+            ))
+        })?;
+        let secret_key = {
+            let mut lock = ctx.grpc_near_client.lock().await;
+            let secret_key = lock
+                .aes_decrypt_data(&encrypted_secret_key, &export_wallet.secret)
+                .await
+                .map(|response| response.data)
+                .unwrap_or_default();
+            drop(lock);
+            secret_key
+        };
+        if secret_key.is_empty() {
+            return Err(GqlError::Validation(ValidationError::new(
+                "secret",
+                "Wallet secret key cannot be decrypted with the secret",
+            )));
+        }
+
+        Ok(WalletExport {
+            wallet_id: db_user.wallet_id,
+            secret_key,
+        })
+    }
+
+    // -------------------------- TICKET TRANSFERS ------------------- //
+    async fn transfer_ticket(
+        new_ticket_transfer: NewTicketTransfer,
+        ctx: &ResourcesContext,
+    ) -> Result<TicketTransfer, GqlError> {
+        let db_user = guard(ctx, Operation::TransferTicket).await?;
+        let user_id = db_user.id;
+
+        // get the reservation and check the caller owns it
+        let reservation_id = new_ticket_transfer.reservation_id;
+        let db_reservation = db_get_ticket_reservation_by_id(&ctx.db_client, &reservation_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "reservation_id",
+                    "Reservation with submitted id does not exist",
+                ))
+            })?;
+
+        if !db_user.id.eq(&db_reservation.user_id) {
+            return Err(GqlError::Forbidden(ValidationError::new(
+                "reservation_owner",
+                "Reservation owner and calling user are not the same",
+            )));
+        }
+
+        // a gift is its recipient's to claim, not its sender's to transfer
+        let pending_gift = db_get_ticket_gift_by_reservation_id(&ctx.db_client, &reservation_id)
+            .await
+            .map_err(GqlError::Database)?
+            .filter(|db_gift| db_gift.claimed_at.is_none());
+        if pending_gift.is_some() {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "reservation_id",
+                "Reservation is a gift not claimed yet",
+            )));
+        }
+
+        // get the ticket and check it can be transferred for the given price
+        let db_ticket = db_get_ticket_by_id(&ctx.db_client, &db_reservation.ticket_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "ticket_id",
+                    "Ticket with submitted id does not exist",
+                ))
+            })?;
+
+        check_ticket_transfer_payload(&new_ticket_transfer, &db_ticket)?;
+
+        // find the receiving user
+        let db_receiver = db_get_user_by_username(&ctx.db_client, &new_ticket_transfer.to_username)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "to_username",
+                    "Receiving user not found in the database",
+                ))
+            })?;
+
+        if !db_receiver.user_type.eq(&Role::Buyer) {
+            return Err(GqlError::Validation(ValidationError::new(
+                "to_username",
+                "Receiving user is not a buyer",
+            )));
+        }
+
+        if db_receiver.id.eq(&db_user.id) {
+            return Err(GqlError::Validation(ValidationError::new(
+                "to_username",
+                "Tickets cannot be transferred to oneself",
+            )));
+        }
+
+        // move the reservation first so a concurrent transfer of the same reservation fails
+        db_update_ticket_reservation_owner(
+            &ctx.db_client,
+            &reservation_id,
+            &db_user.id,
+            &db_receiver.id,
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(|| {
+            GqlError::Conflict(ValidationError::new(
+                "reservation_owner",
+                "Reservation has already been transferred",
+            ))
+        })?;
+
+        // transfer the nft on chain
+        let transfer_nft_tx = ctx
+            .chain
+            .transfer_nft(
+                &db_user.wallet_id,
+                &db_receiver.wallet_id,
+                &db_ticket.ticket_slug,
+                &new_ticket_transfer.price.unwrap_or_default().to_string(),
+            )
+            .await;
+
+        let transfer_nft_tx = match transfer_nft_tx {
+            Ok(tx) => tx,
+            Err(error) => {
+                // give the reservation back to the sender
+                let _ = db_update_ticket_reservation_owner(
+                    &ctx.db_client,
+                    &reservation_id,
+                    &db_receiver.id,
+                    &db_user.id,
+                )
+                .await;
+                return Err(GqlError::Chain(error));
+            }
+        };
+
+        // record the transfer
+        let db_ticket_transfer = DbTicketTransfer::new(
+            new_ticket_transfer.price,
+            transfer_nft_tx.tx_hash,
+            reservation_id,
+            db_ticket.id,
+            db_user.id,
+            db_receiver.id,
+        );
+
+        let _ = db_insert_ticket_transfer(&ctx.db_client, &db_ticket_transfer)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "transfer_ticket",
+            AuditEntity::TicketReservation(reservation_id),
+            serde_json::to_value(&db_ticket_transfer).ok(),
+        )
+        .await;
+
+        // the sender's listing is not theirs to sell anymore
+        db_cancel_ticket_listings_by_reservation_id(&ctx.db_client, &reservation_id)
+            .await
+            .map_err(GqlError::Database)?;
+
+        Ok(TicketTransfer::from(db_ticket_transfer))
+    }
+
+    // -------------------------- CANCELLATIONS ------------------- //
+
+    /// Cancels the caller's reservation, until the event's cancellation window closes. The
+    /// tickets go back on sale and what was paid for them is refunded to the caller's wallet
+    async fn cancel_reservation(
+        ctx: &ResourcesContext,
+        reservation_id: Uuid,
+    ) -> Result<TicketCancellation, GqlError> {
+        let user_id = guard(ctx, Operation::CancelReservation).await?.id;
+
+        // get the reservation and check the caller owns it
+        let db_reservation = db_get_ticket_reservation_by_id(&ctx.db_client, &reservation_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "reservation_id",
+                    "Reservation with submitted id does not exist",
+                ))
+            })?;
+        if !user_id.eq(&db_reservation.user_id) {
+            return Err(GqlError::Forbidden(ValidationError::new(
+                "reservation_owner",
+                "Reservation owner and calling user are not the same",
+            )));
+        }
+        if db_reservation.checked_in_at.is_some() {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "reservation_id",
+                "Reservation has already been checked in",
+            )));
+        }
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &db_reservation.event_id)
+            .await
+            .map_err(GqlError::Database)?;
+        if !db_event.is_cancellable_at(&sql_timestamp(None)) {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "reservation_id",
+                "Reservations of the event can no longer be cancelled",
+            )));
+        }
+
+        let db_cancellation =
+            cancellations::cancel_reservation(ctx, &db_event, &reservation_id, &user_id)
+                .await
+                .map_err(|e| match e {
+                    Error::Postgres(e) => GqlError::Database(e),
+                    _ => GqlError::UnexpectedInternal,
+                })?
+                // cancelled meanwhile
+                .ok_or_else(|| {
+                    GqlError::NotFound(ValidationError::new(
+                        "reservation_id",
+                        "Reservation with submitted id does not exist",
+                    ))
+                })?;
+        ctx.audit(
+            Some(user_id),
+            "cancel_reservation",
+            AuditEntity::TicketReservation(reservation_id),
+            serde_json::to_value(&db_cancellation).ok(),
+        )
+        .await;
+
+        Ok(TicketCancellation::from(db_cancellation))
+    }
+
+    // -------------------------- RESALE ------------------- //
+
+    /// lists the caller's reservation for resale, the asking price up to the ticket's max
+    /// release price
+    async fn list_ticket_for_sale(
+        new_ticket_listing: NewTicketListing,
+        ctx: &ResourcesContext,
+    ) -> Result<TicketListing, GqlError> {
+        let user_id = guard(ctx, Operation::ListTicketForSale).await?.id;
+
+        // get the reservation and check the caller owns it
+        let reservation_id = new_ticket_listing.reservation_id;
+        let db_reservation = db_get_ticket_reservation_by_id(&ctx.db_client, &reservation_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "reservation_id",
+                    "Reservation with submitted id does not exist",
+                ))
+            })?;
+        if !user_id.eq(&db_reservation.user_id) {
+            return Err(GqlError::Forbidden(ValidationError::new(
+                "reservation_owner",
+                "Reservation owner and calling user are not the same",
+            )));
+        }
+        if db_reservation.checked_in_at.is_some() {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "reservation_id",
+                "Reservation has already been checked in",
+            )));
+        }
+
+        // a gift is its recipient's to claim, not its sender's to sell
+        let pending_gift = db_get_ticket_gift_by_reservation_id(&ctx.db_client, &reservation_id)
+            .await
+            .map_err(GqlError::Database)?
+            .filter(|db_gift| db_gift.claimed_at.is_none());
+        if pending_gift.is_some() {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "reservation_id",
+                "Reservation is a gift not claimed yet",
+            )));
+        }
+
+        // get the ticket and check it can be resold for the asking price
+        let db_ticket = db_get_ticket_by_id(&ctx.db_client, &db_reservation.ticket_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "ticket_id",
+                    "Ticket with submitted id does not exist",
+                ))
+            })?;
+        check_ticket_listing_payload(&new_ticket_listing, &db_ticket)?;
+
+        let active_listing =
+            db_get_active_ticket_listing_by_reservation_id(&ctx.db_client, &reservation_id)
+                .await
+                .map_err(GqlError::Database)?;
+        if active_listing.is_some() {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "reservation_id",
+                "Reservation is already listed for sale",
+            )));
+        }
+
+        let db_ticket_listing =
+            DbTicketListing::new(new_ticket_listing.asking_price, &db_reservation);
+        db_insert_ticket_listing(&ctx.db_client, &db_ticket_listing)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "list_ticket_for_sale",
+            AuditEntity::TicketReservation(reservation_id),
+            serde_json::to_value(&db_ticket_listing).ok(),
+        )
+        .await;
+
+        Ok(TicketListing::from(db_ticket_listing))
+    }
+
+    /// withdraws the caller's resale listing
+    async fn cancel_ticket_listing(
+        ctx: &ResourcesContext,
+        listing_id: Uuid,
+    ) -> Result<TicketListing, GqlError> {
+        let user_id = guard(ctx, Operation::ListTicketForSale).await?.id;
+
+        let db_ticket_listing = db_cancel_ticket_listing(&ctx.db_client, &listing_id, &user_id)
+            .await
+            .map_err(GqlError::Database)?
+            .ok_or_else(|| {
+                GqlError::NotFound(ValidationError::new(
+                    "listing_id",
+                    "Active listing of the calling user with submitted id does not exist",
+                ))
+            })?;
+        ctx.audit(
+            Some(user_id),
+            "cancel_ticket_listing",
+            AuditEntity::TicketReservation(db_ticket_listing.reservation_id),
+            Some(serde_json::json!({ "listing_id": listing_id })),
+        )
+        .await;
+
+        Ok(TicketListing::from(db_ticket_listing))
+    }
+
+    // -------------------------- GIFTS ------------------- //
+
+    // buyer reserves tickets for someone else, who claims them with the code sent to their phone
+    async fn gift_tickets(
+        new_ticket_gift: NewTicketGift,
+        ctx: &ResourcesContext,
+    ) -> Result<TicketGift, GqlError> {
+        let db_user = guard(ctx, Operation::GiftTickets).await?;
+        let recipient = check_ticket_gift_payload(&new_ticket_gift)?;
+
+        // gifts by username are sent to the recipient's phone, and only they may claim them
+        let (to_user, to_phone_number, locale) = match recipient {
+            GiftRecipient::Username(username) => {
+                let db_receiver = db_get_user_by_username(&ctx.db_client, &username)
+                    .await
+                    .map_err(|_| {
+                        GqlError::NotFound(ValidationError::new(
+                            "to_username",
+                            "Receiving user not found in the database",
+                        ))
+                    })?;
+                if !db_receiver.user_type.eq(&Role::Buyer) {
+                    return Err(GqlError::Validation(ValidationError::new(
+                        "to_username",
+                        "Receiving user is not a buyer",
+                    )));
+                }
+                if db_receiver.id.eq(&db_user.id) {
+                    return Err(GqlError::Validation(ValidationError::new(
+                        "to_username",
+                        "Tickets cannot be gifted to oneself",
+                    )));
+                }
+                let phone_number = db_receiver.phone_number.ok_or_else(|| {
+                    GqlError::Validation(ValidationError::new(
+                        "to_username",
+                        "Receiving user has no phone number",
+                    ))
+                })?;
+                (Some(db_receiver.id), phone_number, db_receiver.locale)
+            }
+            GiftRecipient::PhoneNumber(phone_number) => {
+                if db_user.phone_number.as_deref() == Some(phone_number.as_str()) {
+                    return Err(GqlError::Validation(ValidationError::new(
+                        "to_phone_number",
+                        "Tickets cannot be gifted to oneself",
+                    )));
+                }
+                (None, phone_number, None)
+            }
+        };
+
+        let ticket_id = new_ticket_gift.ticket_id;
+        let db_ticket = db_get_ticket_by_id(&ctx.db_client, &ticket_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "ticket_id",
+                    "Ticket with submitted id does not exist",
+                ))
+            })?;
+        let db_event = db_get_event_by_id(&ctx.db_client, &db_ticket.event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+        if !db_event.event_status.eq(&EventStatus::Final) {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "event_status",
+                "Only tickets of published events could be gifted",
+            )));
+        }
+        if db_is_ticket_seated(&ctx.db_client, &ticket_id)
+            .await
+            .map_err(GqlError::Database)?
+        {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "ticket_id",
+                "Seated tickets could not be gifted",
+            )));
+        }
+
+        // the gifted tickets count against the sender's purchase limits
+        let reserved_by_user: i64 =
+            db_get_ticket_reservations_by_user_id(&ctx.db_client, &db_user.id)
+                .await
+                .map_err(GqlError::Database)?
+                .iter()
+                .filter(|reservation| reservation.ticket_id.eq(&ticket_id))
+                .map(|reservation| i64::from(reservation.quantity))
+                .sum();
+        db_ticket
+            .check_purchase_quantity(new_ticket_gift.quantity, reserved_by_user)
+            .map_err(|e| GqlError::Conflict(ValidationError::new("quantity", &e.to_string())))?;
+
+        // the reservation is the sender's until the gift is claimed
+        let verification_code = WasmiumRandom::secure_numeric12()
+            .into_iter()
+            .take(6)
+            .map(|item| item.to_string())
+            .collect::<String>();
+        let mut new_db_reservation = DbTicketReservation::new(
+            Uuid::new_v4(),
+            sql_timestamp(None),
+            &verification_code,
+            db_event.id,
+            ticket_id,
+            db_user.id,
+            new_ticket_gift.quantity,
+        );
+        // gifts are sold at the current tier's price too
+        let claimed_price = claim_ticket_price(
+            &ctx.db_client,
+            &db_ticket,
+            new_ticket_gift.quantity,
+            &new_db_reservation.created_at,
+        )
+        .await
+        .map_err(GqlError::Database)?;
+        new_db_reservation.price_tier_id = claimed_price.tier_id;
+        let db_reservation = match db_reserve_ticket(&ctx.db_client, &new_db_reservation)
+            .await
+            .map_err(GqlError::Database)?
+        {
+            Some(db_reservation) => db_reservation,
+            None => {
+                release_ticket_price(&ctx.db_client, &claimed_price, new_ticket_gift.quantity)
+                    .await
+                    .map_err(GqlError::Database)?;
+                return Err(GqlError::Conflict(ValidationError::new(
+                    "quantity",
+                    "Not enough tickets left to gift",
+                )));
+            }
+        };
+        alert_stock(ctx, &db_event, &ticket_id).await;
+
+        let claim_code: String = WasmiumRandom::secure_alphabet12()
+            .into_iter()
+            .take(8)
+            .map(char::from)
+            .collect();
+        let db_gift = DbTicketGift::new(
+            db_reservation.id,
+            db_user.id,
+            to_user,
+            &to_phone_number,
+            &claim_code,
+        );
+        db_insert_ticket_gift(&ctx.db_client, &db_gift)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(db_user.id),
+            "gift_tickets",
+            AuditEntity::TicketReservation(db_reservation.id),
+            Some(serde_json::json!({
+                "ticketId": ticket_id.to_string(),
+                "quantity": db_reservation.quantity,
+                "toUser": to_user.map(|to_user| to_user.to_string()),
+            })),
+        )
+        .await;
+
+        let _ = enqueue(
+            &ctx.db_client,
+            JobPayload::Notify {
+                receiver: to_phone_number,
+                notification: Notification::Gift(Gift {
+                    sender: db_user.name.unwrap_or(db_user.username),
+                    event_name: db_event.event_name,
+                    ticket_name: db_ticket.ticket_name,
+                    quantity: db_reservation.quantity,
+                    claim_code,
+                }),
+                locale,
+            },
+            ctx.jobs.max_attempts,
+        )
+        .await
+        .map_err(GqlError::Database)?;
+
+        Ok(TicketGift::from(db_gift))
+    }
+
+    // the recipient of a gift takes over its reservation, once signed up
+    async fn claim_gift(
+        ctx: &ResourcesContext,
+        claim_code: String,
+    ) -> Result<TicketGift, GqlError> {
+        let db_user = guard(ctx, Operation::ClaimGift).await?;
+
+        // gifts for someone else are as good as unknown
+        let db_gift = db_get_ticket_gift_by_claim_code(&ctx.db_client, claim_code.trim())
+            .await
+            .map_err(GqlError::Database)?
+            .filter(|db_gift| db_gift.is_for(&db_user))
+            .ok_or_else(|| {
+                GqlError::NotFound(ValidationError::new(
+                    "claim_code",
+                    "Gift with submitted claim code does not exist",
+                ))
+            })?;
+        let already_claimed = || {
+            GqlError::Conflict(ValidationError::new(
+                "claim_code",
+                "Gift has already been claimed",
+            ))
+        };
+        if db_gift.claimed_at.is_some() {
+            return Err(already_claimed());
+        }
+
+        // move the reservation first so a concurrent claim of the same gift fails
+        db_update_ticket_reservation_owner(
+            &ctx.db_client,
+            &db_gift.reservation_id,
+            &db_gift.from_user,
+            &db_user.id,
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(already_claimed)?;
+        let db_gift = db_claim_ticket_gift(&ctx.db_client, &db_gift.id, &db_user.id)
+            .await
+            .map_err(GqlError::Database)?
+            .ok_or_else(already_claimed)?;
+        ctx.audit(
+            Some(db_user.id),
+            "claim_gift",
+            AuditEntity::TicketReservation(db_gift.reservation_id),
+            None,
+        )
+        .await;
+
+        Ok(TicketGift::from(db_gift))
+    }
+
+    // -------------------------- WAITLIST ------------------- //
+
+    // buyer waits for a sold out ticket to be available again
+    async fn join_waitlist(
+        ctx: &ResourcesContext,
+        ticket_id: Uuid,
+    ) -> Result<WaitlistEntry, GqlError> {
+        let user_id = guard(ctx, Operation::JoinWaitlist).await?.id;
+
+        let db_ticket = db_get_ticket_by_id(&ctx.db_client, &ticket_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "ticket_id",
+                    "Ticket with submitted id does not exist",
+                ))
+            })?;
+
+        // tickets left can be reserved right away
+        if db_ticket.archived || db_ticket.quantity_remaining() != Some(0) {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "ticket_id",
+                "Only sold out tickets have a waitlist",
+            )));
+        }
+
+        // joining twice keeps the original place
+        let inserted =
+            db_insert_waitlist_entry(&ctx.db_client, &DbWaitlistEntry::new(ticket_id, user_id))
+                .await
+                .map_err(GqlError::Database)?;
+        let db_entry = db_get_waitlist_entry(&ctx.db_client, &ticket_id, &user_id)
+            .await
+            .map_err(GqlError::Database)?
+            .ok_or_else(|| {
+                GqlError::NotFound(ValidationError::new(
+                    "ticket_id",
+                    "Ticket with submitted id does not exist",
+                ))
+            })?;
+        if inserted > 0 {
+            ctx.audit(
+                Some(user_id),
+                "join_waitlist",
+                AuditEntity::Ticket(ticket_id),
+                None,
+            )
+            .await;
+        }
+
+        Ok(WaitlistEntry::from(db_entry))
+    }
+
+    async fn leave_waitlist(ctx: &ResourcesContext, ticket_id: Uuid) -> Result<bool, GqlError> {
+        let user_id = guard(ctx, Operation::LeaveWaitlist).await?.id;
+
+        let deleted = db_delete_waitlist_entry(&ctx.db_client, &ticket_id, &user_id)
+            .await
+            .map_err(GqlError::Database)?;
+        if deleted == 0 {
+            return Err(GqlError::NotFound(ValidationError::new(
+                "ticket_id",
+                "Not on the waitlist of the ticket",
+            )));
+        }
+        ctx.audit(
+            Some(user_id),
+            "leave_waitlist",
+            AuditEntity::Ticket(ticket_id),
+            None,
+        )
+        .await;
+        Ok(true)
+    }
+
+    /// Holds the seats of a published event for a while, all of them or none, the buyer then
+    /// reserves them like any ticket. Holding seats already held renews their hold
+    async fn hold_seats(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+        seat_ids: Vec<Uuid>,
+    ) -> Result<SeatHold, GqlError> {
+        let user_id = guard(ctx, Operation::HoldSeats).await?.id;
+
+        let mut seat_ids = seat_ids;
+        seat_ids.sort();
+        seat_ids.dedup();
+        if seat_ids.is_empty() || seat_ids.len() > ctx.seats.max_held_seats {
+            return Err(GqlError::Validation(ValidationError::new(
+                "seat_ids",
+                &format!(
+                    "Between 1 and {} seats could be held at once",
+                    ctx.seats.max_held_seats
+                ),
+            )));
+        }
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+        if !db_event.event_status.eq(&EventStatus::Final) {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "event_status",
+                "Only seats of published events could be held",
+            )));
+        }
+
+        let now = sql_timestamp(None);
+        let held_until = sql_timestamp(Some(ctx.seats.hold_secs));
+        let held = db_hold_seats(
+            &ctx.db_client,
+            &event_id,
+            &seat_ids,
+            &user_id,
+            &held_until,
+            &now,
+        )
+        .await
+        .map_err(GqlError::Database)?;
+        if held.is_empty() {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "seat_ids",
+                "Some of the seats are not available",
+            )));
+        }
+
+        Ok(SeatHold {
+            seat_ids,
+            held_until,
+        })
+    }
+
+    /// Releases the seats the buyer holds, returns how many were
+    async fn release_seats(ctx: &ResourcesContext, seat_ids: Vec<Uuid>) -> Result<i32, GqlError> {
+        let user_id = guard(ctx, Operation::HoldSeats).await?.id;
+
+        let released = db_release_seats(&ctx.db_client, &seat_ids, &user_id)
+            .await
+            .map_err(GqlError::Database)?;
+        Ok(released as i32)
+    }
+}
+
+/// Served on `/graphql/admin` to admins only
+#[derive(Copy, Clone, Default)]
+pub struct AdminMutationRoot;
+
+#[juniper::graphql_object(Context = ResourcesContext)]
+impl AdminMutationRoot {
+    async fn api_version() -> juniper::FieldResult<&'static str> {
+        Ok("v1.0".into())
+    }
+
+    /// Mints a short-lived jwt acting as the user, for support to reproduce their issues
+    async fn impersonate_user(
+        ctx: &ResourcesContext,
+        user_id: Uuid,
+    ) -> Result<Impersonation, GqlError> {
+        let admin_id = guard(ctx, Operation::ImpersonateUser).await?.id;
+
+        let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "user_id",
+                    "User not found in the database",
+                ))
+            })?;
+        if db_user.deleted_at.is_some() {
+            return Err(GqlError::Validation(ValidationError::new(
+                "user_id",
+                "User account has been deleted",
+            )));
+        }
+        // only buyers and sellers, an admin's privileges are not lent out
+        if !matches!(db_user.user_type, Role::Buyer | Role::Seller) {
+            return Err(GqlError::Forbidden(ValidationError::new(
+                "user_type",
+                "Only buyers and sellers can be impersonated",
+            )));
+        }
+
+        let db_impersonation = DbImpersonation::new(
+            admin_id,
+            user_id,
+            sql_timestamp(Some(ctx.sessions.impersonation_ttl_secs)),
+        );
+        let token = create_impersonation_jwt(
+            &user_id.to_string(),
+            &db_user.user_type,
+            &db_impersonation.id,
+            db_impersonation.expires_at.timestamp(),
+        )
+        .map_err(|_| GqlError::UnexpectedInternal)?;
+        db_insert_impersonation(&ctx.db_client, &db_impersonation)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(admin_id),
+            "impersonate_user",
+            AuditEntity::User(user_id),
+            Some(serde_json::json!({
+                "impersonationId": db_impersonation.id,
+                "expiresAt": db_impersonation.expires_at,
+            })),
+        )
+        .await;
+
+        Ok(Impersonation {
+            id: db_impersonation.id,
+            user_id,
+            admin_id,
+            token,
+            expires_at: db_impersonation.expires_at,
+        })
+    }
+
+    /// Revokes an impersonation, its jwt is rejected from then on
+    async fn revoke_impersonation(ctx: &ResourcesContext, id: Uuid) -> Result<bool, GqlError> {
+        let admin_id = guard(ctx, Operation::RevokeImpersonation).await?.id;
+
+        let db_impersonation = db_revoke_impersonation(&ctx.db_client, &id)
+            .await
+            .map_err(GqlError::Database)?
+            .ok_or_else(|| {
+                GqlError::NotFound(ValidationError::new(
+                    "impersonation_id",
+                    "No active impersonation with submitted id",
+                ))
+            })?;
+        ctx.audit(
+            Some(admin_id),
+            "revoke_impersonation",
+            AuditEntity::User(db_impersonation.user_id),
+            Some(serde_json::json!({ "impersonationId": id })),
+        )
+        .await;
+
+        Ok(true)
+    }
+
+    async fn purge_event(ctx: &ResourcesContext, id: Uuid) -> Result<bool, GqlError> {
+        let user_id = guard(ctx, Operation::PurgeEvent).await?.id;
+
+        // hard-delete the event, including soft-deleted ones. Tickets and reservations cascade
+        let purged = db_purge_event_by_id(&ctx.db_client, &id)
+            .await
+            .map_err(GqlError::Database)?;
+        if purged == 0 {
+            return Err(GqlError::NotFound(ValidationError::new(
+                "event_id",
+                "Event with submitted id does not exist",
+            )));
+        }
+        ctx.event_cache.invalidate(&id);
+        ctx.audit(Some(user_id), "purge_event", AuditEntity::Event(id), None)
+            .await;
+        Ok(true)
+    }
+
+    async fn approve_seller(ctx: &ResourcesContext, user_id: Uuid) -> Result<User, GqlError> {
+        let admin_id = guard(ctx, Operation::ReviewSellers).await?.id;
+
+        let updated_db_user = db_update_seller_status(
+            &ctx.db_client,
+            &user_id,
+            &[SellerStatus::PendingReview],
+            SellerStatus::Approved,
+            None,
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(|| {
+            GqlError::NotFound(ValidationError::new(
+                "seller_status",
+                "Seller does not exist or is not pending review",
+            ))
+        })?;
+        ctx.audit(
+            Some(admin_id),
+            "approve_seller",
+            AuditEntity::User(user_id),
+            None,
+        )
+        .await;
+
+        Ok(User::from(updated_db_user))
+    }
+
+    /// Sends the seller back to onboarding, the reason tells them what to resubmit
+    async fn reject_seller(
+        ctx: &ResourcesContext,
+        user_id: Uuid,
+        reason: String,
+    ) -> Result<User, GqlError> {
+        let admin_id = guard(ctx, Operation::ReviewSellers).await?.id;
+
+        let reason = check_rejection_reason(&reason)?;
+        let updated_db_user = db_update_seller_status(
+            &ctx.db_client,
+            &user_id,
+            &[SellerStatus::PendingReview],
+            SellerStatus::Rejected,
+            Some(&reason),
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(|| {
+            GqlError::NotFound(ValidationError::new(
+                "seller_status",
+                "Seller does not exist or is not pending review",
+            ))
+        })?;
+        ctx.audit(
+            Some(admin_id),
+            "reject_seller",
+            AuditEntity::User(user_id),
+            Some(serde_json::json!({ "reason": reason })),
+        )
+        .await;
+
+        Ok(User::from(updated_db_user))
+    }
+
+    /// Makes an event pending review public
+    async fn approve_event(ctx: &ResourcesContext, id: Uuid) -> Result<Event, GqlError> {
+        let admin_id = guard(ctx, Operation::ReviewEvents).await?.id;
+
+        let updated_db_event = db_review_event(
+            &ctx.db_client,
+            &id,
+            EventStatus::Final,
+            None,
+            &[outbox_message(id, &EventUpdate::Published)],
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(|| {
+            GqlError::NotFound(ValidationError::new(
+                "event_status",
+                "Event does not exist or is not pending review",
+            ))
+        })?;
+        ctx.event_cache.invalidate(&id);
+        ctx.audit(
+            Some(admin_id),
+            "approve_event",
+            AuditEntity::Event(id),
+            None,
+        )
+        .await;
+        inbox::notify(
+            &ctx.db_client,
+            updated_db_event.created_by_user,
+            NotificationKind::EventApproved,
+            format!(
+                "{} was approved and is now public",
+                updated_db_event.event_name
+            ),
+            Some(serde_json::json!({ "eventId": id })),
+        )
+        .await;
+
+        let tickets = db_get_tickets_by_event_id(&ctx.db_client, &Some(id))
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.readable_event(Event::new(updated_db_event, tickets))
+            .await
+    }
+
+    /// Sends an event pending review back to its seller, the reason tells them what to fix
+    /// before publishing it again
+    async fn reject_event(
+        ctx: &ResourcesContext,
+        id: Uuid,
+        reason: String,
+    ) -> Result<Event, GqlError> {
+        let admin_id = guard(ctx, Operation::ReviewEvents).await?.id;
+
+        let reason = check_rejection_reason(&reason)?;
+        let updated_db_event = db_review_event(
+            &ctx.db_client,
+            &id,
+            EventStatus::Minting,
+            Some(&reason),
+            &[],
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(|| {
+            GqlError::NotFound(ValidationError::new(
+                "event_status",
+                "Event does not exist or is not pending review",
+            ))
+        })?;
+        ctx.audit(
+            Some(admin_id),
+            "reject_event",
+            AuditEntity::Event(id),
+            Some(serde_json::json!({ "reason": reason })),
+        )
+        .await;
+        inbox::notify(
+            &ctx.db_client,
+            updated_db_event.created_by_user,
+            NotificationKind::EventRejected,
+            format!(
+                "{} was not approved: {}",
+                updated_db_event.event_name, reason
+            ),
+            Some(serde_json::json!({ "eventId": id, "reason": reason })),
+        )
+        .await;
+
+        let tickets = db_get_tickets_by_event_id(&ctx.db_client, &Some(id))
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.readable_event(Event::new(updated_db_event, tickets))
+            .await
+    }
+
+    async fn approve_payout(ctx: &ResourcesContext, id: Uuid) -> Result<PayoutRequest, GqlError> {
+        let user_id = guard(ctx, Operation::ApprovePayout).await?.id;
+
+        // claim the request first so it cannot be paid twice
+        let db_payout_request = db_update_payout_request_status(
+            &ctx.db_client,
+            &id,
+            PayoutStatus::Pending,
+            PayoutStatus::Approved,
+            Some(&user_id),
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(|| {
+            GqlError::NotFound(ValidationError::new(
+                "payout_status",
+                "Payout request does not exist or is not pending",
+            ))
+        })?;
+
+        // send the payout on chain
+        let fund_account_tx = ctx
+            .chain
+            .fund_account(
+                &db_payout_request.wallet_id,
+                &db_payout_request.amount.to_string(),
+            )
+            .await;
+
+        let fund_account_tx = match fund_account_tx {
+            Ok(tx) => tx,
+            Err(error) => {
+                // the transfer may still have gone through, so the request is not put back in
+                // the queue where it could be paid twice, and its amount stays requested
+                match db_update_payout_request_status(
+                    &ctx.db_client,
+                    &id,
+                    PayoutStatus::Approved,
+                    PayoutStatus::Failed,
+                    Some(&user_id),
+                )
+                .await
+                {
+                    Ok(Some(_)) => log::warn!("Payout request {} failed: {}", id, error),
+                    Ok(None) => log::error!("Payout request {} failed and is not approved", id),
+                    Err(e) => log::error!("Failed to mark payout request {} failed: {}", id, e),
+                }
+                return Err(GqlError::Chain(error));
+            }
+        };
+
+        let paid_db_payout_request =
+            db_complete_payout_request(&ctx.db_client, &id, &fund_account_tx.tx_hash)
+                .await
+                .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "approve_payout",
+            AuditEntity::PayoutRequest(id),
+            serde_json::to_value(&paid_db_payout_request).ok(),
+        )
+        .await;
+
+        Ok(PayoutRequest::from(paid_db_payout_request))
+    }
+
+    /// Tops up a buyer's wallet right away, by `amount` or the configured top-up amount. The
+    /// daily cap of the automatic top-ups applies
+    async fn top_up_wallet(
+        ctx: &ResourcesContext,
+        user_id: Uuid,
+        amount: Option<NearAmount>,
+    ) -> Result<WalletTopUp, GqlError> {
+        let admin_id = guard(ctx, Operation::TopUpWallet).await?.id;
+
+        let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new("user_id", "User does not exist"))
+            })?;
+        if db_user.user_type != Role::Buyer || db_user.deleted_at.is_some() {
+            return Err(GqlError::Validation(ValidationError::new(
+                "user_id",
+                "Only the wallets of active buyers are topped up",
+            )));
+        }
+
+        let limits =
+            TopUpLimits::from_config(&ctx.top_ups).map_err(|_| GqlError::UnexpectedInternal)?;
+        let topped_up = wallet_top_ups_total(ctx, &db_user)
+            .await
+            .map_err(|_| GqlError::UnexpectedInternal)?;
+        let amount = limits
+            .capped(amount.unwrap_or(limits.amount), topped_up)
+            .ok_or_else(|| {
+                GqlError::Conflict(ValidationError::new(
+                    "amount",
+                    "Daily top-up cap of the wallet is reached",
+                ))
+            })?;
+
+        let db_wallet_top_up = top_up_wallet(ctx, &db_user, amount, Some(admin_id))
+            .await
+            .map_err(|e| match e {
+                Error::Chain(e) => GqlError::Chain(e),
+                Error::Postgres(e) => GqlError::Database(e),
+                _ => GqlError::UnexpectedInternal,
+            })?;
+        ctx.audit(
+            Some(admin_id),
+            "top_up_wallet",
+            AuditEntity::User(db_user.id),
+            serde_json::to_value(&db_wallet_top_up).ok(),
+        )
+        .await;
+
+        Ok(WalletTopUp::from(db_wallet_top_up))
+    }
+
+    async fn reject_payout(ctx: &ResourcesContext, id: Uuid) -> Result<PayoutRequest, GqlError> {
+        let user_id = guard(ctx, Operation::RejectPayout).await?.id;
+
+        // a rejected request no longer counts against the seller's balance
+        let db_payout_request = db_update_payout_request_status(
+            &ctx.db_client,
+            &id,
+            PayoutStatus::Pending,
+            PayoutStatus::Rejected,
+            Some(&user_id),
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(|| {
+            GqlError::NotFound(ValidationError::new(
+                "payout_status",
+                "Payout request does not exist or is not pending",
+            ))
+        })?;
+        ctx.audit(
+            Some(user_id),
+            "reject_payout",
+            AuditEntity::PayoutRequest(id),
+            None,
+        )
+        .await;
+
+        Ok(PayoutRequest::from(db_payout_request))
+    }
+
+    // -------------------------- CATEGORIES & TAGS ------------------- //
+    async fn create_category(ctx: &ResourcesContext, name: String) -> Result<Category, GqlError> {
+        let user_id = guard(ctx, Operation::CreateCategory).await?.id;
+
+        check_category_name(&name)?;
+
+        let new_db_category = DbCategory::new(name.trim());
+        if db_get_category_by_slug(&ctx.db_client, &new_db_category.slug)
+            .await
+            .map_err(GqlError::Database)?
+            .is_some()
+        {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "category_name",
+                "A category with this name already exists",
+            )));
+        }
+
+        let db_category = db_insert_category(&ctx.db_client, &new_db_category)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(user_id),
+            "create_category",
+            AuditEntity::Category(db_category.id),
+            serde_json::to_value(&db_category).ok(),
+        )
+        .await;
+
+        Ok(Category::from(db_category))
+    }
+
+    /// Takes the api down for everyone but the admins, or brings it back up. The clients are told
+    /// on the maintenance pusher channel
+    async fn set_maintenance_mode(
+        ctx: &ResourcesContext,
+        enabled: bool,
+        message: Option<String>,
+        retry_after_secs: Option<i32>,
+    ) -> Result<MaintenanceMode, GqlError> {
+        let admin_id = guard(ctx, Operation::ManageMaintenance).await?.id;
+        let message = check_maintenance_payload(message.as_deref(), retry_after_secs)?;
+
+        let mode = maintenance::set_mode(ctx, enabled, message, retry_after_secs, &admin_id)
+            .await
+            .map_err(|e| match e {
+                Error::Postgres(e) => GqlError::Database(e),
+                _ => GqlError::UnexpectedInternal,
+            })?;
+        ctx.audit(
+            Some(admin_id),
+            "set_maintenance_mode",
+            AuditEntity::User(admin_id),
+            serde_json::to_value(&mode).ok(),
+        )
+        .await;
+
+        Ok(mode)
+    }
+
+    /// Lifts the backoff and lockout of the user's failed password sign-ins. Whether they had any
+    async fn unlock_user(ctx: &ResourcesContext, user_id: Uuid) -> Result<bool, GqlError> {
+        let admin_id = guard(ctx, Operation::UnlockUser).await?.id;
+
+        let cleared = db_clear_login_attempts(&ctx.db_client, &user_id, None)
+            .await
+            .map_err(GqlError::Database)?;
+        ctx.audit(
+            Some(admin_id),
+            "unlock_user",
+            AuditEntity::User(user_id),
+            None,
+        )
+        .await;
+
+        Ok(cleared > 0)
+    }
+
+    /// Retries a failed buyer signup right away, the signup tells whether it completed
+    async fn resume_signup(ctx: &ResourcesContext, id: Uuid) -> Result<SignupAttempt, GqlError> {
+        let admin_id = guard(ctx, Operation::ReconcileSignups).await?.id;
+
+        let db_signup_attempt = signups::resume(ctx, &id)
+            .await
+            .map_err(|e| match e {
+                Error::Postgres(e) => GqlError::Database(e),
+                _ => GqlError::UnexpectedInternal,
+            })?
+            .ok_or_else(|| {
+                GqlError::NotFound(ValidationError::new(
+                    "signup_status",
+                    "Signup does not exist, is not stuck or is being retried",
+                ))
+            })?;
+        ctx.audit(
+            Some(admin_id),
+            "resume_signup",
+            AuditEntity::User(db_signup_attempt.user_id),
+            Some(serde_json::json!({
+                "signupStatus": db_signup_attempt.signup_status.to_string(),
+            })),
+        )
+        .await;
+
+        Ok(SignupAttempt::from(db_signup_attempt))
+    }
+
+    /// Gives up a failed buyer signup, the buyer may sign up again with their verified phone. A
+    /// wallet it created stays on the chain
+    async fn roll_back_signup(ctx: &ResourcesContext, id: Uuid) -> Result<SignupAttempt, GqlError> {
+        let admin_id = guard(ctx, Operation::ReconcileSignups).await?.id;
+
+        let db_signup_attempt = signups::roll_back_stuck(ctx, &id)
+            .await
+            .map_err(|e| match e {
+                Error::Postgres(e) => GqlError::Database(e),
+                _ => GqlError::UnexpectedInternal,
+            })?
+            .ok_or_else(|| {
+                GqlError::NotFound(ValidationError::new(
+                    "signup_status",
+                    "Signup does not exist, is not stuck or is being retried",
+                ))
+            })?;
+        ctx.audit(
+            Some(admin_id),
+            "roll_back_signup",
+            AuditEntity::User(db_signup_attempt.user_id),
+            Some(serde_json::json!({
+                "walletId": db_signup_attempt.wallet_id,
+                "walletCreated": db_signup_attempt.wallet_created_at.is_some(),
+            })),
+        )
+        .await;
+
+        Ok(SignupAttempt::from(db_signup_attempt))
+    }
+}
+
+/// Re-verifies the user before a wallet export, a recovery code is used up by it
+async fn verify_wallet_export(
+    ctx: &ResourcesContext,
+    db_user: &DbUser,
+    verification: WalletExportVerification,
+) -> Result<(), GqlError> {
+    match verification {
+        WalletExportVerification::RecoveryCode { session_id, code } => {
+            // the recovery sessions of other users are as good as unknown
+            let db_session = db_get_buyer_recovery_session_by_id(&ctx.db_client, &session_id)
+                .await
+                .ok()
+                .filter(|db_session| db_session.created_by_user.eq(&db_user.id))
+                .ok_or_else(|| {
+                    GqlError::NotFound(ValidationError::new(
+                        "recovery_session_id",
+                        "Recovery session not found",
+                    ))
+                })?;
+            if db_session.is_consumed || db_session.expires_at < sql_timestamp(None) {
+                return Err(GqlError::Validation(ValidationError::new(
+                    "recovery_code",
+                    "Recovery code has expired or was used",
+                )));
+            }
+
+            // count the attempt before checking the code, like the recovery route
+            let attempts =
+                db_increment_buyer_recovery_session_attempts(&ctx.db_client, &session_id)
+                    .await
+                    .map_err(GqlError::Database)?;
+            if attempts > ctx.sessions.max_attempts {
+                return Err(GqlError::Forbidden(ValidationError::new(
+                    "recovery_code",
+                    "Too many attempts",
+                )));
+            }
+            if !ctx.codes.verify(&db_session.recovery_code_hash, &code) {
+                return Err(GqlError::Validation(ValidationError::new(
+                    "recovery_code",
+                    "Recovery code is wrong",
+                )));
+            }
+
+            db_consume_buyer_recovery_session(&ctx.db_client, &session_id)
+                .await
+                .map_err(GqlError::Database)?
+                .ok_or_else(|| {
+                    GqlError::Validation(ValidationError::new(
+                        "recovery_code",
+                        "Recovery code has expired or was used",
+                    ))
+                })?;
+            Ok(())
+        }
+        WalletExportVerification::Password(password) => {
+            let hash = db_user.password.as_deref().ok_or_else(|| {
+                GqlError::Validation(ValidationError::new("password", "User has no password"))
+            })?;
+            let is_verified = verify_password(hash, password.as_bytes())
+                .map_err(|_| GqlError::UnexpectedInternal)?;
+            if !is_verified {
+                return Err(GqlError::Validation(ValidationError::new(
+                    "password",
+                    "Password is wrong",
+                )));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Loads the organization, checking the user is one of its owners
+async fn organization_owned_by(
+    ctx: &ResourcesContext,
+    organization_id: &Uuid,
+    user_id: &Uuid,
+) -> Result<DbOrganization, GqlError> {
+    let db_organization = db_get_organization_by_id(&ctx.db_client, organization_id)
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(|| {
+            GqlError::NotFound(ValidationError::new(
+                "organization_id",
+                "Organization with submitted id does not exist",
+            ))
+        })?;
+    let db_member = db_get_organization_member(&ctx.db_client, organization_id, user_id)
+        .await
+        .map_err(GqlError::Database)?;
+    if !db_member.map_or(false, |db_member| {
+        db_member.member_role.eq(&MemberRole::Owner)
+    }) {
+        return Err(GqlError::Forbidden(ValidationError::new(
+            "organization_id",
+            "Only the organization owners may manage its members",
+        )));
+    }
+    Ok(db_organization)
+}
+
+/// The event, if the user created it
+async fn event_created_by(
+    ctx: &ResourcesContext,
+    event_id: &Uuid,
+    user_id: &Uuid,
+) -> Result<DbEvent, GqlError> {
+    let db_event = db_get_event_by_id(&ctx.db_client, event_id)
+        .await
+        .map_err(|_| {
+            GqlError::NotFound(ValidationError::new(
+                "event_id",
+                "Event with submitted id does not exist",
+            ))
+        })?;
+    if !user_id.eq(&db_event.created_by_user) {
+        return Err(GqlError::Forbidden(ValidationError::new(
+            "event_creator",
+            "Event creator and calling user are not the same",
+        )));
+    }
+    Ok(db_event)
+}
+
+/// Checks the user may edit the events of the organization
+async fn organization_editor(
+    ctx: &ResourcesContext,
+    organization_id: &Uuid,
+    user_id: &Uuid,
+) -> Result<DbOrganizationMember, GqlError> {
+    let db_member = db_get_organization_member(&ctx.db_client, organization_id, user_id)
+        .await
+        .map_err(GqlError::Database)?
+        .ok_or_else(|| {
+            GqlError::Forbidden(ValidationError::new(
+                "organization_id",
+                "Calling user is not a member of the organization",
+            ))
+        })?;
+    if !event_policy(EventAccess::Edit).contains(&db_member.member_role) {
+        return Err(GqlError::Forbidden(ValidationError::new(
+            "organization_id",
+            &format!(
+                "Organization members with role {} may not edit its events",
+                db_member.member_role
+            ),
+        )));
+    }
+    Ok(db_member)
+}
+
+/// The event the user may access, whatever its status
+async fn accessible_event(
+    ctx: &ResourcesContext,
+    user_id: &Uuid,
+    event_id: &Uuid,
+    access: EventAccess,
+) -> Result<DbEvent, GqlError> {
+    let db_event = db_get_event_by_id(&ctx.db_client, event_id)
+        .await
+        .map_err(|_| {
+            GqlError::NotFound(ValidationError::new(
+                "event_id",
+                "Event with submitted id does not exist",
+            ))
+        })?;
+    guard_event(ctx, user_id, &db_event, access).await?;
+    Ok(db_event)
+}
+
+/// The DRAFT event the user may edit, galleries are only changed before publishing
+async fn draft_event(
+    ctx: &ResourcesContext,
+    user_id: &Uuid,
+    event_id: &Uuid,
+) -> Result<DbEvent, GqlError> {
+    let db_event = db_get_event_by_id(&ctx.db_client, event_id)
+        .await
+        .map_err(|_| {
+            GqlError::NotFound(ValidationError::new(
+                "event_id",
+                "Event with submitted id does not exist",
+            ))
+        })?;
+
+    // check caller is the event creator or a member of its organization
+    guard_event(ctx, user_id, &db_event, EventAccess::Edit).await?;
+
+    if !db_event.event_status.eq(&EventStatus::Draft) {
+        return Err(GqlError::Conflict(ValidationError::new(
+            "event_status",
+            "Only event with status DRAFT could be edited",
+        )));
+    }
+    Ok(db_event)
+}
+
+/// Copies the tags and the confirmed assets of the source event to its copy, returning the tags.
+/// The copy refers to the same stored images, uploads still pending are left behind
+async fn copy_tags_and_assets(
+    ctx: &ResourcesContext,
+    source_id: &Uuid,
+    event_id: &Uuid,
+) -> Result<Vec<String>, GqlError> {
+    let tags = db_get_event_tags(&ctx.db_client, &[*source_id])
+        .await
+        .map_err(GqlError::Database)?
+        .into_iter()
+        .map(|tag| tag.tag)
+        .collect::<Vec<_>>();
+    db_set_event_tags(&ctx.db_client, event_id, &tags)
+        .await
+        .map_err(GqlError::Database)?;
+    let files = db_get_files_for_event(&ctx.db_client, source_id)
+        .await
+        .map_err(GqlError::Database)?;
+    for file in files.iter().filter(|file| file.is_confirmed) {
+        insert_asset_file(&ctx.db_client, &clone_asset_file(file, *event_id))
+            .await
+            .map_err(GqlError::Database)?;
+    }
+    Ok(tags)
+}
+
+/// The event with its tickets and gallery
+async fn event_with_gallery(ctx: &ResourcesContext, db_event: DbEvent) -> Result<Event, GqlError> {
+    let tickets = db_get_tickets_by_event_id(&ctx.db_client, &Some(db_event.id))
+        .await
+        .map_err(GqlError::Database)?;
+    let gallery = db_get_gallery_assets_by_event_ids(&ctx.db_client, &[db_event.id])
+        .await
+        .map_err(GqlError::Database)?;
+    ctx.readable_event(Event::new(db_event, tickets).with_gallery(&gallery, ctx.storage.as_ref()))
+        .await
+}
+
+/// The ticket of a DRAFT event the user may edit, its price tiers and seat map are only managed
+/// before publishing
+async fn draft_ticket(
+    ctx: &ResourcesContext,
+    user_id: &Uuid,
+    ticket_id: &Uuid,
+) -> Result<DbTicket, GqlError> {
+    let db_ticket = db_get_ticket_by_id(&ctx.db_client, ticket_id)
+        .await
+        .map_err(|_| {
+            GqlError::NotFound(ValidationError::new(
+                "ticket_id",
+                "Ticket with submitted id does not exist",
+            ))
+        })?;
+    let db_event = db_get_event_by_id(&ctx.db_client, &db_ticket.event_id)
+        .await
+        .map_err(|_| {
+            GqlError::NotFound(ValidationError::new(
+                "event_id",
+                "Event with submitted id does not exist",
+            ))
+        })?;
+
+    // check caller is the event creator or a member of its organization
+    guard_event(ctx, user_id, &db_event, EventAccess::Edit).await?;
+
+    if !db_event.event_status.eq(&EventStatus::Draft) {
+        return Err(GqlError::Conflict(ValidationError::new(
+            "event_status",
+            "Tickets could only be changed for an event with status DRAFT",
+        )));
+    }
+    Ok(db_ticket)
 }
@@ -0,0 +1,148 @@
+//! Automatic persisted queries.
+//!
+//! Clients send the sha256 hash of a query in the `persistedQuery` extension instead of the
+//! query. The first time a hash is sent its query is not known yet, the `PersistedQueryNotFound`
+//! error tells the client to send the hash and the query along, which are then stored in the
+//! `persisted_queries` table for every later request.
+//!
+//! With `persisted-queries-only` the public endpoint does not store queries anymore and only
+//! executes the ones already stored, e.g. registered through the private endpoint.
+use super::error::{field_error, ErrorCode};
+use crate::db::{
+    models::DbPersistedQuery,
+    sql::{db_get_persisted_query, db_insert_persisted_query},
+};
+use displaydoc::Display as DisplayDoc;
+use juniper::{
+    http::{GraphQLRequest, GraphQLResponse},
+    DefaultScalarValue, FieldError, InputValue,
+};
+use serde::Deserialize;
+use thiserror::Error;
+use tokio_postgres::Client;
+
+/// The persisted query protocol version supported
+pub const PERSISTED_QUERY_VERSION: i64 = 1;
+
+/// A graphql request whose query may be sent by hash only
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedGraphQLRequest {
+    pub query: Option<String>,
+    pub operation_name: Option<String>,
+    pub variables: Option<InputValue>,
+    pub extensions: Option<RequestExtensions>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestExtensions {
+    pub persisted_query: Option<PersistedQuery>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedQuery {
+    pub version: i64,
+    pub sha256_hash: String,
+}
+
+#[derive(Debug, DisplayDoc, Error)]
+pub enum PersistedQueryError {
+    /// PersistedQueryNotFound
+    NotFound,
+    /// PersistedQueryNotSupported
+    NotSupported,
+    /// Provided sha does not match query
+    HashMismatch,
+    /// Only persisted queries are allowed
+    Required,
+    /// Query is missing
+    MissingQuery,
+    /// Database error: `{0}`
+    Database(tokio_postgres::Error),
+}
+
+impl PersistedQueryError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PersistedQueryError::NotFound => ErrorCode::PersistedQueryNotFound,
+            PersistedQueryError::NotSupported => ErrorCode::PersistedQueryNotSupported,
+            PersistedQueryError::HashMismatch | PersistedQueryError::MissingQuery => {
+                ErrorCode::Validation
+            }
+            PersistedQueryError::Required => ErrorCode::Forbidden,
+            PersistedQueryError::Database(_) => ErrorCode::Internal,
+        }
+    }
+
+    /// the error as a graphql response, clients retry with the query on `PersistedQueryNotFound`
+    pub fn response(&self) -> GraphQLResponse {
+        let message = match self {
+            PersistedQueryError::Database(e) => {
+                log::error!("GraphQL persisted query error: {}", e);
+                "Unexpected error".to_string()
+            }
+            _ => self.to_string(),
+        };
+        let error: FieldError<DefaultScalarValue> = field_error(self.code(), None, message);
+        GraphQLResponse::error(error)
+    }
+}
+
+/// The hash clients send for the query, lowercase hex
+pub fn query_hash(query: &str) -> String {
+    sha256::digest(query)
+}
+
+/// The graphql request to execute, its query looked up by hash or stored under it.
+/// `persisted_only` refuses the queries not stored yet.
+pub async fn resolve_request(
+    db_client: &Client,
+    req: PersistedGraphQLRequest,
+    persisted_only: bool,
+) -> Result<GraphQLRequest, PersistedQueryError> {
+    let persisted_query = req
+        .extensions
+        .and_then(|extensions| extensions.persisted_query);
+
+    let query = match (req.query, persisted_query) {
+        (_, None) if persisted_only => return Err(PersistedQueryError::Required),
+        (Some(query), None) => query,
+        (None, None) => return Err(PersistedQueryError::MissingQuery),
+        (_, Some(persisted_query)) if persisted_query.version != PERSISTED_QUERY_VERSION => {
+            return Err(PersistedQueryError::NotSupported)
+        }
+        (None, Some(persisted_query)) => {
+            db_get_persisted_query(db_client, &persisted_query.sha256_hash.to_lowercase())
+                .await
+                .map_err(PersistedQueryError::Database)?
+                .ok_or(PersistedQueryError::NotFound)?
+                .query
+        }
+        (Some(query), Some(persisted_query)) => {
+            let sha256_hash = persisted_query.sha256_hash.to_lowercase();
+            if query_hash(&query).ne(&sha256_hash) {
+                return Err(PersistedQueryError::HashMismatch);
+            }
+            if persisted_only {
+                // the query is only executed if it is already stored
+                db_get_persisted_query(db_client, &sha256_hash)
+                    .await
+                    .map_err(PersistedQueryError::Database)?
+                    .ok_or(PersistedQueryError::Required)?;
+            } else {
+                db_insert_persisted_query(db_client, &DbPersistedQuery::new(&sha256_hash, &query))
+                    .await
+                    .map_err(PersistedQueryError::Database)?;
+            }
+            query
+        }
+    };
+
+    Ok(GraphQLRequest::new(
+        query,
+        req.operation_name,
+        req.variables,
+    ))
+}
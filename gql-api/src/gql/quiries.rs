@@ -1,10 +1,68 @@
-use super::models::{Event, EventFilter, User};
+use super::models::{
+    AuditEntry, Category, DailyReservations, DownloadUrl, Event, EventAnalytics, EventCollaborator,
+    EventCount, EventFilter, EventReservation, EventSeries, EventStatus, GrpcCallStats, Inbox,
+    InboxNotification, MaintenanceMode, MintEstimate, MintJob, Organization, PayoutAccount,
+    PayoutBalance, PayoutRequest, PayoutStatus, PromoCode, SeatSection, Seller, SellerDocument,
+    SellerWebhook, SignupAttempt, SignupStatus, SystemMetrics, TagCount, TicketCancellation,
+    TicketListing, TransactionStatus, User, UserCount,
+};
 use crate::{
-    db::sql::{db_get_events, db_get_tickets_by_event_id, db_get_user_by_id, db_get_users},
-    gql::{error::GqlError, schema::Context as ResourcesContext},
+    audit::AuditEntity,
+    auth::SellerStatus,
+    cache::EventKey,
+    db::{
+        models::DbEvent,
+        sql::{
+            db_count_events, db_count_unread_notifications, db_count_users,
+            db_get_active_ticket_listings_by_event_id, db_get_audit_logs, db_get_categories,
+            db_get_daily_reservations, db_get_event_attendees, db_get_event_by_id,
+            db_get_event_collaborator, db_get_event_collaborators, db_get_event_daily_stats,
+            db_get_event_reservations, db_get_event_series_by_id, db_get_event_tags, db_get_events,
+            db_get_events_by_series_id, db_get_events_near, db_get_events_pending_review,
+            db_get_gallery_assets_by_event_ids, db_get_mint_jobs_by_event_id,
+            db_get_notifications_by_user_id, db_get_organization_member,
+            db_get_organizations_by_user_id, db_get_payout_account_by_user_id,
+            db_get_payout_balance, db_get_payout_requests, db_get_popular_tags,
+            db_get_price_tiers_by_ticket_ids, db_get_promo_codes_by_event_id,
+            db_get_seat_sections_by_event_id, db_get_seats_by_event_id, db_get_seller_by_slug,
+            db_get_seller_documents_by_user_id, db_get_seller_webhook, db_get_signup_attempts,
+            db_get_ticket_cancellations_by_user_id, db_get_user_by_id, db_get_users,
+            db_get_users_by_seller_status, db_search_events, sql_timestamp, EventsFilter,
+        },
+    },
+    gql::{
+        error::{GqlError, ValidationError},
+        event_details::EventDetails,
+        guard::{guard, guard_event},
+        mint::{estimate_mint, mintable_ticket, MintPayload},
+        schema::Context as ResourcesContext,
+        validations::{check_nearby_payload, check_search_text},
+    },
+    http::export::{attendees_csv, can_export_attendees, ATTENDEES_CSV_CONTENT_TYPE},
+    metrics::{grpc_call_counts, sms_counts},
+    policy::{EventAccess, Operation},
 };
+use chrono::{NaiveDate, NaiveDateTime};
+use slugify::slugify;
+use std::time::Duration;
 use uuid::Uuid;
 
+const AUDIT_LOG_PAGE_SIZE: i32 = 50;
+const AUDIT_LOG_MAX_PAGE_SIZE: i32 = 500;
+const POPULAR_TAGS_LIMIT: i32 = 20;
+const POPULAR_TAGS_MAX_LIMIT: i32 = 100;
+const SEARCH_PAGE_SIZE: i32 = 20;
+const SEARCH_MAX_PAGE_SIZE: i32 = 100;
+pub(crate) const EVENT_RESERVATIONS_PAGE_SIZE: i64 = 50;
+const NOTIFICATIONS_PAGE_SIZE: i64 = 50;
+/// The longest date range of the event analytics, in days
+const ANALYTICS_MAX_DAYS: i64 = 366;
+/// The days of reservations in the system metrics, today included
+const SYSTEM_METRICS_DAYS: i32 = 30;
+const SYSTEM_METRICS_MAX_DAYS: i32 = 366;
+/// How long a presigned download url stays valid
+const DOWNLOAD_URL_EXPIRY_SECS: u64 = 900;
+
 #[derive(Copy, Clone, Default)]
 pub struct PublicQueryRoot;
 
@@ -14,39 +72,232 @@ impl PublicQueryRoot {
         Ok("v1.0".into())
     }
 
+    /// the published events, all the given filters combined
     async fn events(
         ctx: &ResourcesContext,
-        id: Option<String>,
+        id: Option<Uuid>,
         event_slug: Option<String>,
         filter: Option<EventFilter>,
+        category: Option<String>,
+        tag: Option<String>,
+        starts_after: Option<NaiveDateTime>,
+        starts_before: Option<NaiveDateTime>,
+    ) -> Result<Vec<Event>, GqlError> {
+        // single event pages are served from the cache
+        let unfiltered = filter.is_none()
+            && category.is_none()
+            && tag.is_none()
+            && starts_after.is_none()
+            && starts_before.is_none();
+        let cache_key = match (id, &event_slug) {
+            (Some(id), None) if unfiltered => Some(EventKey::Id(id)),
+            (None, Some(event_slug)) if unfiltered => Some(EventKey::Slug(event_slug.clone())),
+            _ => None,
+        };
+        if let Some(event) = cache_key.as_ref().and_then(|key| ctx.event_cache.get(key)) {
+            return Ok(vec![event]);
+        }
+
+        let db_events = db_get_events(
+            &ctx.db_client,
+            &EventsFilter {
+                id,
+                event_slug,
+                filter,
+                // drafts and events still minting are only listed to their sellers
+                statuses: vec![EventStatus::Final],
+                starts_after,
+                starts_before,
+                category_slug: category,
+                tag: tag.map(|tag| slugify!(&tag, separator = "-")),
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(GqlError::Database)?;
+
+        let events = events_with_tickets_and_tags(ctx, db_events).await?;
+        if let (Some(key), [event]) = (cache_key, events.as_slice()) {
+            ctx.event_cache.insert(key, event.clone());
+        }
+        Ok(events)
+    }
+
+    async fn search_events(
+        ctx: &ResourcesContext,
+        text: String,
+        limit: Option<i32>,
+        offset: Option<i32>,
     ) -> Result<Vec<Event>, GqlError> {
-        let event_id = id
-            .map(|s| Uuid::parse_str(&s))
-            .transpose()
-            .map_err(|_| GqlError::ParseUUID)?;
+        let text = check_search_text(&text)?;
+        let limit = i64::from(
+            limit
+                .unwrap_or(SEARCH_PAGE_SIZE)
+                .clamp(1, SEARCH_MAX_PAGE_SIZE),
+        );
+        let offset = i64::from(offset.unwrap_or(0).max(0));
 
-        let tickets = db_get_tickets_by_event_id(&ctx.db_client, &event_id)
+        let db_events = db_search_events(&ctx.db_client, text, limit, offset)
             .await
             .map_err(GqlError::Database)?;
 
-        let events: Vec<Event> = db_get_events(&ctx.db_client, event_id, event_slug, filter)
+        // keeps the ranking order of the search
+        events_with_tickets_and_tags(ctx, db_events).await
+    }
+
+    /// the published events within `radius_km` of a place, nearest first
+    async fn events_near(
+        ctx: &ResourcesContext,
+        latitude: f64,
+        longitude: f64,
+        radius_km: f64,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<Vec<Event>, GqlError> {
+        let (center, radius_km) = check_nearby_payload(latitude, longitude, radius_km)?;
+        let limit = i64::from(
+            limit
+                .unwrap_or(SEARCH_PAGE_SIZE)
+                .clamp(1, SEARCH_MAX_PAGE_SIZE),
+        );
+        let offset = i64::from(offset.unwrap_or(0).max(0));
+
+        let (db_events, distances): (Vec<_>, Vec<_>) =
+            db_get_events_near(&ctx.db_client, &center, radius_km, limit, offset)
+                .await
+                .map_err(GqlError::Database)?
+                .into_iter()
+                .unzip();
+
+        // keeps the distance order
+        let events = events_with_tickets_and_tags(ctx, db_events).await?;
+        Ok(events
+            .into_iter()
+            .zip(distances)
+            .map(|(event, distance_km)| event.with_distance_km(distance_km))
+            .collect())
+    }
+
+    /// the storefront of the seller with the slug, with their published events
+    async fn seller(ctx: &ResourcesContext, slug: String) -> Result<Option<Seller>, GqlError> {
+        let db_user = match db_get_seller_by_slug(&ctx.db_client, slug.trim())
             .await
             .map_err(GqlError::Database)?
+        {
+            Some(db_user) => db_user,
+            None => return Ok(None),
+        };
+
+        let db_events = db_get_events(
+            &ctx.db_client,
+            &EventsFilter {
+                created_by_user: Some(db_user.id),
+                statuses: vec![EventStatus::Final],
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(GqlError::Database)?;
+        let events = events_with_tickets_and_tags(ctx, db_events).await?;
+
+        Ok(Some(Seller {
+            id: db_user.id,
+            name: db_user.name,
+            username: db_user.username,
+            seller_slug: db_user.seller_slug.unwrap_or(slug),
+            created_at: db_user.created_at,
+            events,
+        }))
+    }
+
+    /// the series with its published occurrences, earliest first, and the next one to attend
+    async fn event_series(
+        ctx: &ResourcesContext,
+        id: Uuid,
+    ) -> Result<Option<EventSeries>, GqlError> {
+        let db_series = match db_get_event_series_by_id(&ctx.db_client, &id)
+            .await
+            .map_err(GqlError::Database)?
+        {
+            Some(db_series) => db_series,
+            None => return Ok(None),
+        };
+
+        let db_events = db_get_events_by_series_id(&ctx.db_client, &id, &[EventStatus::Final])
+            .await
+            .map_err(GqlError::Database)?;
+        let events = events_with_tickets_and_tags(ctx, db_events).await?;
+        Ok(Some(EventSeries::new(
+            db_series,
+            events,
+            &sql_timestamp(None),
+        )))
+    }
+
+    /// the seat map of a published event by section, with the availability of their seats, empty
+    /// for general admission events
+    async fn seat_map(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+    ) -> Result<Vec<SeatSection>, GqlError> {
+        let published = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_or(false, |db_event| {
+                db_event.event_status == EventStatus::Final && db_event.deleted_at.is_none()
+            });
+        if !published {
+            return Ok(vec![]);
+        }
+
+        let sections = db_get_seat_sections_by_event_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(GqlError::Database)?;
+        let seats = db_get_seats_by_event_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(GqlError::Database)?;
+        let now = sql_timestamp(None);
+        Ok(sections
             .into_iter()
-            .map(|event| {
-                let tickets = tickets
-                    .iter()
-                    .cloned()
-                    .filter(|ticket| ticket.event_id.eq(&event.id))
-                    .collect::<Vec<_>>();
-                Event::new(event, tickets)
-            })
-            .collect();
+            .map(|section| SeatSection::new(section, &seats, &now))
+            .collect())
+    }
 
-        Ok(events)
+    async fn categories(ctx: &ResourcesContext) -> Result<Vec<Category>, GqlError> {
+        let categories = db_get_categories(&ctx.db_client)
+            .await
+            .map_err(GqlError::Database)?;
+        Ok(categories.into_iter().map(Category::from).collect())
+    }
+
+    async fn popular_tags(
+        ctx: &ResourcesContext,
+        limit: Option<i32>,
+    ) -> Result<Vec<TagCount>, GqlError> {
+        let limit = i64::from(
+            limit
+                .unwrap_or(POPULAR_TAGS_LIMIT)
+                .clamp(1, POPULAR_TAGS_MAX_LIMIT),
+        );
+
+        let tags = db_get_popular_tags(&ctx.db_client, limit)
+            .await
+            .map_err(GqlError::Database)?;
+        Ok(tags.into_iter().map(TagCount::from).collect())
+    }
+
+    /// the tickets of an event offered for resale, the cheapest first
+    async fn ticket_listings(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+    ) -> Result<Vec<TicketListing>, GqlError> {
+        let listings = db_get_active_ticket_listings_by_event_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(GqlError::Database)?;
+        Ok(listings.into_iter().map(TicketListing::from).collect())
     }
 }
 
+/// The account of any signed in user, served on `/graphql/private`
 #[derive(Copy, Clone, Default)]
 pub struct PrivateQueryRoot;
 
@@ -70,12 +321,474 @@ impl PrivateQueryRoot {
         Ok(User::from(user))
     }
 
-    async fn users(ctx: &ResourcesContext, id: Option<String>) -> Result<Vec<User>, GqlError> {
-        let id = id
-            .map(|s| Uuid::parse_str(&s))
-            .transpose()
-            .map_err(|_| GqlError::ParseUUID)?;
+    /// the caller's notifications, newest first, along with how many are unread
+    async fn my_notifications(
+        ctx: &ResourcesContext,
+        page: Option<i32>,
+        unread_only: Option<bool>,
+    ) -> Result<Inbox, GqlError> {
+        let db_user = guard(ctx, Operation::MyNotifications).await?;
+
+        let offset = (i64::from(page.unwrap_or(1).max(1)) - 1) * NOTIFICATIONS_PAGE_SIZE;
+        let notifications = db_get_notifications_by_user_id(
+            &ctx.db_client,
+            &db_user.id,
+            unread_only.unwrap_or(false),
+            NOTIFICATIONS_PAGE_SIZE,
+            offset,
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .into_iter()
+        .map(InboxNotification::from)
+        .collect();
+        let unread_count = db_count_unread_notifications(&ctx.db_client, &db_user.id)
+            .await
+            .map_err(GqlError::Database)?;
+
+        Ok(Inbox {
+            notifications,
+            unread_count: unread_count as i32,
+        })
+    }
+
+    /// a single event, its nested fields resolved and authorized one by one when selected
+    async fn event(ctx: &ResourcesContext, id: Uuid) -> Result<EventDetails, GqlError> {
+        let user_id = guard(ctx, Operation::ViewEvent).await?.id;
+
+        EventDetails::load(ctx, user_id, &id).await
+    }
+
+    /// the status of a NEAR transaction, e.g. of a mint or a transfer. `account_id` is the
+    /// account that sent it, the caller's wallet by default
+    async fn transaction_status(
+        ctx: &ResourcesContext,
+        tx_hash: String,
+        account_id: Option<String>,
+    ) -> Result<TransactionStatus, GqlError> {
+        let db_user = guard(ctx, Operation::TransactionStatus).await?;
+
+        // NEAR transaction hashes are base58 encoded sha256 hashes
+        let is_tx_hash = bs58::decode(&tx_hash)
+            .into_vec()
+            .map(|hash| hash.len() == 32)
+            .unwrap_or(false);
+        if !is_tx_hash {
+            return Err(GqlError::Validation(ValidationError::new(
+                "tx_hash",
+                "Transaction hash is not a valid NEAR transaction hash",
+            )));
+        }
+
+        let account_id = account_id.unwrap_or(db_user.wallet_id);
+        TransactionStatus::fetch(ctx, &tx_hash, &account_id).await
+    }
+
+    /// a presigned url to download the attendees of an event as CSV
+    async fn event_attendees_url(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+    ) -> Result<DownloadUrl, GqlError> {
+        let db_user = guard(ctx, Operation::ExportAttendees).await?;
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+        let member = match db_event.organization_id {
+            Some(organization_id) => {
+                db_get_organization_member(&ctx.db_client, &organization_id, &db_user.id)
+                    .await
+                    .map_err(GqlError::Database)?
+            }
+            None => None,
+        };
+        let collaborator = db_get_event_collaborator(&ctx.db_client, &event_id, &db_user.id)
+            .await
+            .map_err(GqlError::Database)?;
+        if !can_export_attendees(
+            &db_user.user_type,
+            &db_user.id,
+            &db_event,
+            member.as_ref(),
+            collaborator.as_ref(),
+        ) {
+            return Err(GqlError::Forbidden(ValidationError::new(
+                "event_creator",
+                "Event creator and calling user are not the same",
+            )));
+        }
+
+        let attendees = db_get_event_attendees(&ctx.db_client, &event_id)
+            .await
+            .map_err(GqlError::Database)?;
+        let content = attendees_csv(&attendees);
+
+        let key = format!(
+            "exports/events/{}/attendees-{}.csv",
+            event_id,
+            Uuid::new_v4()
+        );
+        let expiry = Duration::from_secs(DOWNLOAD_URL_EXPIRY_SECS);
+        ctx.storage
+            .put(&key, ATTENDEES_CSV_CONTENT_TYPE, content)
+            .await
+            .map_err(|e| GqlError::Storage(e.to_string()))?;
+        let download_url = ctx
+            .storage
+            .presigned_get_url(&key, expiry)
+            .await
+            .map_err(|e| GqlError::Storage(e.to_string()))?;
+
+        ctx.audit(
+            Some(db_user.id),
+            "export_attendees",
+            AuditEntity::Event(event_id),
+            None,
+        )
+        .await;
+
+        Ok(DownloadUrl {
+            download_url,
+            expires_at: sql_timestamp(Some(DOWNLOAD_URL_EXPIRY_SECS as i64)),
+        })
+    }
+}
+
+/// Served on `/graphql/seller` to sellers only
+#[derive(Copy, Clone, Default)]
+pub struct SellerQueryRoot;
+
+#[juniper::graphql_object(Context = ResourcesContext)]
+impl SellerQueryRoot {
+    async fn api_version() -> juniper::FieldResult<&'static str> {
+        Ok("v1.0".into())
+    }
+
+    /// the caller's own events and their organizations' events in any status, drafts included
+    async fn my_events(
+        ctx: &ResourcesContext,
+        filter: Option<EventFilter>,
+        status: Option<Vec<EventStatus>>,
+        starts_after: Option<NaiveDateTime>,
+        starts_before: Option<NaiveDateTime>,
+    ) -> Result<Vec<Event>, GqlError> {
+        guard(ctx, Operation::MyEvents).await?;
+
+        let user_id = {
+            let guard = ctx.user_id.lock().await;
+            let user_id = guard.ok_or(GqlError::UnexpectedInternal)?;
+            drop(guard);
+            user_id
+        };
+
+        let db_events = db_get_events(
+            &ctx.db_client,
+            &EventsFilter {
+                filter,
+                statuses: status.unwrap_or_default(),
+                managed_by_user: Some(user_id),
+                starts_after,
+                starts_before,
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(GqlError::Database)?;
+
+        events_with_tickets_and_tags(ctx, db_events).await
+    }
+
+    /// the organizations the caller is a member of
+    async fn my_organizations(ctx: &ResourcesContext) -> Result<Vec<Organization>, GqlError> {
+        let user_id = guard(ctx, Operation::MyOrganizations).await?.id;
+
+        let organizations = db_get_organizations_by_user_id(&ctx.db_client, &user_id)
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(Organization::from)
+            .collect();
+        Ok(organizations)
+    }
+
+    /// the caller's webhook, if registered, without its secret
+    async fn my_webhook(ctx: &ResourcesContext) -> Result<Option<SellerWebhook>, GqlError> {
+        let user_id = guard(ctx, Operation::ManageWebhook).await?.id;
+
+        let db_webhook = db_get_seller_webhook(&ctx.db_client, &user_id)
+            .await
+            .map_err(GqlError::Database)?;
+        Ok(db_webhook.map(SellerWebhook::from))
+    }
+
+    /// the collaborators of an event the calling user may edit
+    async fn event_collaborators(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+    ) -> Result<Vec<EventCollaborator>, GqlError> {
+        let user_id = guard(ctx, Operation::ManageEventCollaborators).await?.id;
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+        guard_event(ctx, &user_id, &db_event, EventAccess::Edit).await?;
+
+        let collaborators = db_get_event_collaborators(&ctx.db_client, &event_id)
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(EventCollaborator::from)
+            .collect();
+        Ok(collaborators)
+    }
+
+    /// a page of the reservations of an event the calling user may check in, oldest first, pages
+    /// starting at 1. Only the checked in or the not checked in ones if `redeemed` is given.
+    async fn event_reservations(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+        page: Option<i32>,
+        redeemed: Option<bool>,
+    ) -> Result<Vec<EventReservation>, GqlError> {
+        let user_id = guard(ctx, Operation::EventReservations).await?.id;
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+        guard_event(ctx, &user_id, &db_event, EventAccess::CheckIn).await?;
+
+        let offset = (i64::from(page.unwrap_or(1).max(1)) - 1) * EVENT_RESERVATIONS_PAGE_SIZE;
+        let reservations = db_get_event_reservations(
+            &ctx.db_client,
+            &event_id,
+            redeemed,
+            EVENT_RESERVATIONS_PAGE_SIZE,
+            offset,
+        )
+        .await
+        .map_err(GqlError::Database)?
+        .into_iter()
+        .map(EventReservation::from)
+        .collect();
+        Ok(reservations)
+    }
+
+    /// the expected cost and metadata of minting a ticket, without submitting anything
+    async fn estimate_mint(
+        ctx: &ResourcesContext,
+        ticket_id: Uuid,
+    ) -> Result<MintEstimate, GqlError> {
+        let user_id = guard(ctx, Operation::EstimateMint).await?.id;
+
+        let (db_event, db_ticket) = mintable_ticket(ctx, &user_id, &ticket_id).await?;
+        let payload = MintPayload::new(&db_event, &db_ticket)?;
+        Ok(estimate_mint(db_ticket.id, &payload))
+    }
+
+    /// the submitted mints of an event's tickets and their progress, the latest first
+    async fn mint_jobs(ctx: &ResourcesContext, event_id: Uuid) -> Result<Vec<MintJob>, GqlError> {
+        let user_id = guard(ctx, Operation::MintJobs).await?.id;
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+        guard_event(ctx, &user_id, &db_event, EventAccess::Edit).await?;
+
+        let mint_jobs = db_get_mint_jobs_by_event_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(MintJob::from)
+            .collect();
+        Ok(mint_jobs)
+    }
+
+    /// the views, reservations and purchases of an event per day, from `from` to `to` included.
+    /// The views are aggregated periodically, the latest ones may not be counted yet
+    async fn event_analytics(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<EventAnalytics, GqlError> {
+        let user_id = guard(ctx, Operation::EventAnalytics).await?.id;
+
+        if to < from {
+            return Err(GqlError::Validation(ValidationError::new(
+                "to",
+                "The range must not end before it starts",
+            )));
+        }
+        if (to - from).num_days() >= ANALYTICS_MAX_DAYS {
+            return Err(GqlError::Validation(ValidationError::new(
+                "to",
+                &format!("The range must not exceed {} days", ANALYTICS_MAX_DAYS),
+            )));
+        }
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+        guard_event(ctx, &user_id, &db_event, EventAccess::Edit).await?;
+
+        let days = db_get_event_daily_stats(&ctx.db_client, &event_id, &from, &to)
+            .await
+            .map_err(GqlError::Database)?;
+        Ok(EventAnalytics::new(event_id, from, to, days))
+    }
+
+    async fn promo_codes(
+        ctx: &ResourcesContext,
+        event_id: Uuid,
+    ) -> Result<Vec<PromoCode>, GqlError> {
+        let user_id = {
+            let guard = ctx.user_id.lock().await;
+            let user_id = guard.ok_or(GqlError::UnexpectedInternal)?;
+            drop(guard);
+            user_id
+        };
+
+        let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(|_| {
+                GqlError::NotFound(ValidationError::new(
+                    "event_id",
+                    "Event with submitted id does not exist",
+                ))
+            })?;
+
+        // only the event creator or its organization members may list its promo codes
+        guard_event(ctx, &user_id, &db_event, EventAccess::Edit).await?;
+
+        let promo_codes = db_get_promo_codes_by_event_id(&ctx.db_client, &event_id)
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(PromoCode::from)
+            .collect();
+        Ok(promo_codes)
+    }
+
+    async fn my_payout_account(ctx: &ResourcesContext) -> Result<Option<PayoutAccount>, GqlError> {
+        let user_id = {
+            let guard = ctx.user_id.lock().await;
+            let user_id = guard.ok_or(GqlError::UnexpectedInternal)?;
+            drop(guard);
+            user_id
+        };
+
+        let payout_account = db_get_payout_account_by_user_id(&ctx.db_client, &user_id)
+            .await
+            .map_err(GqlError::Database)?
+            .map(PayoutAccount::from);
+        Ok(payout_account)
+    }
+
+    async fn my_payout_balance(ctx: &ResourcesContext) -> Result<PayoutBalance, GqlError> {
+        let user_id = {
+            let guard = ctx.user_id.lock().await;
+            let user_id = guard.ok_or(GqlError::UnexpectedInternal)?;
+            drop(guard);
+            user_id
+        };
+
+        let payout_balance = db_get_payout_balance(&ctx.db_client, &user_id)
+            .await
+            .map_err(GqlError::Database)?;
+        Ok(PayoutBalance::from(payout_balance))
+    }
+
+    async fn my_payout_requests(ctx: &ResourcesContext) -> Result<Vec<PayoutRequest>, GqlError> {
+        let user_id = {
+            let guard = ctx.user_id.lock().await;
+            let user_id = guard.ok_or(GqlError::UnexpectedInternal)?;
+            drop(guard);
+            user_id
+        };
+
+        let payout_requests = db_get_payout_requests(&ctx.db_client, &Some(user_id), None)
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(PayoutRequest::from)
+            .collect();
+        Ok(payout_requests)
+    }
+
+    /// The calling seller's onboarding documents
+    async fn my_seller_documents(ctx: &ResourcesContext) -> Result<Vec<SellerDocument>, GqlError> {
+        let db_user = guard(ctx, Operation::SubmitSellerOnboarding).await?;
+
+        let documents = db_get_seller_documents_by_user_id(&ctx.db_client, &db_user.id)
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(SellerDocument::from)
+            .collect();
+        Ok(documents)
+    }
+}
+
+/// Served on `/graphql/buyer` to buyers only
+#[derive(Copy, Clone, Default)]
+pub struct BuyerQueryRoot;
+
+#[juniper::graphql_object(Context = ResourcesContext)]
+impl BuyerQueryRoot {
+    async fn api_version() -> juniper::FieldResult<&'static str> {
+        Ok("v1.0".into())
+    }
+
+    /// The caller's cancelled reservations and their refunds, the latest first
+    async fn my_cancellations(ctx: &ResourcesContext) -> Result<Vec<TicketCancellation>, GqlError> {
+        let user_id = guard(ctx, Operation::CancelReservation).await?.id;
+
+        let cancellations = db_get_ticket_cancellations_by_user_id(&ctx.db_client, &user_id)
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(TicketCancellation::from)
+            .collect();
+        Ok(cancellations)
+    }
+}
+
+/// Served on `/graphql/admin` to admins only
+#[derive(Copy, Clone, Default)]
+pub struct AdminQueryRoot;
 
+#[juniper::graphql_object(Context = ResourcesContext)]
+impl AdminQueryRoot {
+    async fn api_version() -> juniper::FieldResult<&'static str> {
+        Ok("v1.0".into())
+    }
+
+    async fn users(ctx: &ResourcesContext, id: Option<Uuid>) -> Result<Vec<User>, GqlError> {
         let users = db_get_users(&ctx.db_client, &id)
             .await
             .map_err(GqlError::Database)?
@@ -84,4 +797,209 @@ impl PrivateQueryRoot {
             .collect();
         Ok(users)
     }
+
+    async fn audit_logs(
+        ctx: &ResourcesContext,
+        user_id: Option<Uuid>,
+        entity_id: Option<Uuid>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<Vec<AuditEntry>, GqlError> {
+        guard(ctx, Operation::AuditLogs).await?;
+
+        let limit = i64::from(
+            limit
+                .unwrap_or(AUDIT_LOG_PAGE_SIZE)
+                .clamp(1, AUDIT_LOG_MAX_PAGE_SIZE),
+        );
+        let offset = i64::from(offset.unwrap_or(0).max(0));
+
+        let entries = db_get_audit_logs(&ctx.db_client, &user_id, &entity_id, limit, offset)
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(AuditEntry::from)
+            .collect();
+        Ok(entries)
+    }
+
+    /// The maintenance mode this api instance follows
+    async fn maintenance_mode(ctx: &ResourcesContext) -> Result<MaintenanceMode, GqlError> {
+        guard(ctx, Operation::ManageMaintenance).await?;
+
+        Ok(ctx.maintenance.mode())
+    }
+
+    async fn payout_requests(
+        ctx: &ResourcesContext,
+        user_id: Option<Uuid>,
+        payout_status: Option<PayoutStatus>,
+    ) -> Result<Vec<PayoutRequest>, GqlError> {
+        guard(ctx, Operation::PayoutRequests).await?;
+
+        let payout_requests = db_get_payout_requests(&ctx.db_client, &user_id, payout_status)
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(PayoutRequest::from)
+            .collect();
+        Ok(payout_requests)
+    }
+
+    /// The sellers waiting for their onboarding review, the longest waiting first
+    async fn sellers_pending_review(ctx: &ResourcesContext) -> Result<Vec<User>, GqlError> {
+        guard(ctx, Operation::ReviewSellers).await?;
+
+        let sellers = db_get_users_by_seller_status(&ctx.db_client, SellerStatus::PendingReview)
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(User::from)
+            .collect();
+        Ok(sellers)
+    }
+
+    /// The events waiting for their review, the longest waiting first
+    async fn events_pending_review(ctx: &ResourcesContext) -> Result<Vec<Event>, GqlError> {
+        guard(ctx, Operation::ReviewEvents).await?;
+
+        let db_events = db_get_events_pending_review(&ctx.db_client)
+            .await
+            .map_err(GqlError::Database)?;
+        events_with_tickets_and_tags(ctx, db_events).await
+    }
+
+    /// The buyer signups, all or those with the status, the latest first
+    async fn signup_attempts(
+        ctx: &ResourcesContext,
+        signup_status: Option<SignupStatus>,
+    ) -> Result<Vec<SignupAttempt>, GqlError> {
+        guard(ctx, Operation::ReconcileSignups).await?;
+
+        let signup_attempts = db_get_signup_attempts(&ctx.db_client, signup_status)
+            .await
+            .map_err(GqlError::Database)?
+            .into_iter()
+            .map(SignupAttempt::from)
+            .collect();
+        Ok(signup_attempts)
+    }
+
+    /// The seller's onboarding documents, with presigned urls to review them
+    async fn seller_documents(
+        ctx: &ResourcesContext,
+        user_id: Uuid,
+    ) -> Result<Vec<SellerDocument>, GqlError> {
+        guard(ctx, Operation::ReviewSellers).await?;
+
+        let db_seller_documents = db_get_seller_documents_by_user_id(&ctx.db_client, &user_id)
+            .await
+            .map_err(GqlError::Database)?;
+
+        let expiry = Duration::from_secs(DOWNLOAD_URL_EXPIRY_SECS);
+        let mut documents = Vec::with_capacity(db_seller_documents.len());
+        for db_seller_document in db_seller_documents {
+            let download_url = if db_seller_document.is_confirmed {
+                let download_url = ctx
+                    .storage
+                    .presigned_get_url(&db_seller_document.s3_absolute_key, expiry)
+                    .await
+                    .map_err(|e| GqlError::Storage(e.to_string()))?;
+                Some(download_url)
+            } else {
+                None
+            };
+            documents.push(SellerDocument {
+                download_url,
+                ..SellerDocument::from(db_seller_document)
+            });
+        }
+        Ok(documents)
+    }
+
+    /// Operational counters for the internal dashboard: the users, events and reservations
+    /// counted in the database, the sms and gRPC calls counted by this api instance
+    async fn system_metrics(
+        ctx: &ResourcesContext,
+        days: Option<i32>,
+    ) -> Result<SystemMetrics, GqlError> {
+        guard(ctx, Operation::SystemMetrics).await?;
+
+        let days = days
+            .unwrap_or(SYSTEM_METRICS_DAYS)
+            .clamp(1, SYSTEM_METRICS_MAX_DAYS);
+        let since = sql_timestamp(None).date() - chrono::Duration::days(i64::from(days - 1));
+
+        let users = db_count_users(&ctx.db_client)
+            .await
+            .map_err(GqlError::Database)?;
+        let events = db_count_events(&ctx.db_client)
+            .await
+            .map_err(GqlError::Database)?;
+        let reservations_per_day = db_get_daily_reservations(&ctx.db_client, &since)
+            .await
+            .map_err(GqlError::Database)?;
+        let (sms_sent, sms_failed) = sms_counts();
+
+        Ok(SystemMetrics {
+            users: users.into_iter().map(UserCount::from).collect(),
+            events: events.into_iter().map(EventCount::from).collect(),
+            reservations_per_day: reservations_per_day
+                .into_iter()
+                .map(DailyReservations::from)
+                .collect(),
+            sms_sent: sms_sent as f64,
+            sms_failed: sms_failed as f64,
+            grpc_calls: grpc_call_counts()
+                .into_iter()
+                .map(GrpcCallStats::from)
+                .collect(),
+        })
+    }
+}
+
+/// Attaches their tickets, tags and galleries to the events, keeping their order. The urls of
+/// their images are the readable ones, which the event cache keeps for less than they stay valid
+async fn events_with_tickets_and_tags(
+    ctx: &ResourcesContext,
+    db_events: Vec<DbEvent>,
+) -> Result<Vec<Event>, GqlError> {
+    let event_ids = db_events.iter().map(|event| event.id).collect::<Vec<_>>();
+    let mut tickets = ctx
+        .loaders
+        .tickets_by_events(&ctx.db_client, &event_ids)
+        .await?;
+    let tags = db_get_event_tags(&ctx.db_client, &event_ids)
+        .await
+        .map_err(GqlError::Database)?;
+    let gallery = db_get_gallery_assets_by_event_ids(&ctx.db_client, &event_ids)
+        .await
+        .map_err(GqlError::Database)?;
+    let ticket_ids = tickets
+        .values()
+        .flatten()
+        .map(|ticket| ticket.id)
+        .collect::<Vec<_>>();
+    let price_tiers = db_get_price_tiers_by_ticket_ids(&ctx.db_client, &ticket_ids)
+        .await
+        .map_err(GqlError::Database)?;
+    let now = sql_timestamp(None);
+
+    let events: Vec<Event> = db_events
+        .into_iter()
+        .map(|event| {
+            let tickets = tickets.remove(&event.id).unwrap_or_default();
+            let event_tags = tags
+                .iter()
+                .filter(|tag| tag.event_id.eq(&event.id))
+                .map(|tag| tag.tag.clone())
+                .collect::<Vec<_>>();
+            Event::new(event, tickets)
+                .with_tags(event_tags)
+                .with_price_tiers(&price_tiers, &now)
+                .with_gallery(&gallery, ctx.storage.as_ref())
+        })
+        .collect();
+
+    ctx.readable_events(events).await
 }
@@ -1,15 +1,25 @@
 use std::{net::SocketAddr, sync::Arc};
 
 use super::{
-    filters::{with_private_gql_schema, with_public_gql_schema},
+    filters::{with_authenticated_gql_schema, with_public_gql_schema},
     handlers::{
-        graphql_private as graphql_private_handler, graphql_public as graphql_public_handler,
+        graphql_authenticated as graphql_authenticated_handler,
+        graphql_private_subscriptions as graphql_private_subscriptions_handler,
+        graphql_public as graphql_public_handler,
+        graphql_public_subscriptions as graphql_public_subscriptions_handler,
     },
-    schema::{Context as ResourcesContext, PrivateSchema, PublicSchema},
+    schema::{
+        AdminSchema, AuthenticatedSchema, BuyerSchema, Context as ResourcesContext, GqlRoot,
+        GqlSubscriptionRoot, PrivateSchema, PublicSchema, SellerSchema,
+    },
+    schema_language::{authenticated_schema_language, public_schema_language},
 };
 use crate::{
-    auth::Role,
-    filters::{with_auth, with_resources_context},
+    filters::{
+        with_enabled, with_impersonable_auth, with_json_content_type, with_resources_context,
+        with_role_auth,
+    },
+    policy::Operation,
 };
 use juniper::http::graphiql::graphiql_source;
 use warp::{
@@ -24,11 +34,15 @@ pub fn graphql_public_route(
     gql_schema: Arc<PublicSchema>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.graphql_bytes;
     let graphql_route = warp::post()
         .and(warp::path!("api" / "v1" / "graphql" / "public"))
         .and(with_public_gql_schema(gql_schema))
         .and(with_resources_context(resources_ctx))
+        .and(with_json_content_type())
+        .and(warp::body::content_length_limit(body_limit))
         .and(warp::body::json())
+        .and(warp::header::optional::<String>("accept-language"))
         .and_then(graphql_public_handler)
         .with(logger);
     graphql_route
@@ -40,37 +54,239 @@ pub fn graphql_private_route(
     gql_schema: Arc<PrivateSchema>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    graphql_authenticated_route(
+        "private",
+        Operation::PrivateGraphql,
+        resources_ctx,
+        gql_schema,
+        logger,
+    )
+}
+
+/// POST /graphql/seller
+pub fn graphql_seller_route(
+    resources_ctx: Arc<ResourcesContext>,
+    gql_schema: Arc<SellerSchema>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    graphql_authenticated_route(
+        "seller",
+        Operation::SellerGraphql,
+        resources_ctx,
+        gql_schema,
+        logger,
+    )
+}
+
+/// POST /graphql/buyer
+pub fn graphql_buyer_route(
+    resources_ctx: Arc<ResourcesContext>,
+    gql_schema: Arc<BuyerSchema>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    graphql_authenticated_route(
+        "buyer",
+        Operation::BuyerGraphql,
+        resources_ctx,
+        gql_schema,
+        logger,
+    )
+}
+
+/// POST /graphql/admin
+pub fn graphql_admin_route(
+    resources_ctx: Arc<ResourcesContext>,
+    gql_schema: Arc<AdminSchema>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    graphql_authenticated_route(
+        "admin",
+        Operation::AdminGraphql,
+        resources_ctx,
+        gql_schema,
+        logger,
+    )
+}
+
+/// POST /graphql/<schema_name>, for the roles the operation allows. A schema only has the fields
+/// of its roles, the others are not even part of it
+fn graphql_authenticated_route<Q: GqlRoot, M: GqlRoot, S: GqlSubscriptionRoot>(
+    schema_name: &'static str,
+    operation: Operation,
+    resources_ctx: Arc<ResourcesContext>,
+    gql_schema: Arc<AuthenticatedSchema<Q, M, S>>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.graphql_bytes;
     let graphql_route = warp::post()
-        .and(warp::path!("api" / "v1" / "graphql" / "private"))
-        .and(with_private_gql_schema(gql_schema))
-        .and(with_resources_context(resources_ctx))
+        .and(warp::path("api"))
+        .and(warp::path("v1"))
+        .and(warp::path("graphql"))
+        .and(warp::path(schema_name))
+        .and(warp::path::end())
+        .and(warp::any().map(move || schema_name))
+        .and(with_authenticated_gql_schema(gql_schema))
+        .and(with_resources_context(resources_ctx.clone()))
+        .and(with_json_content_type())
+        .and(warp::body::content_length_limit(body_limit))
         .and(warp::body::json())
-        .and(with_auth(vec![
-            Role::Admin,
-            Role::Buyer,
-            Role::Seller,
-            Role::SuperAdmin,
-        ]))
-        .and_then(graphql_private_handler)
+        .and(warp::header::optional::<String>("accept-language"))
+        .and(with_impersonable_auth(operation, resources_ctx))
+        .and_then(graphql_authenticated_handler::<Q, M, S>)
         .with(logger);
     graphql_route
 }
 
-/// POST /graphiql
+/// GET /subscriptions (websocket, graphql-ws protocol)
+pub fn graphql_public_subscriptions_route(
+    resources_ctx: Arc<ResourcesContext>,
+    gql_schema: Arc<PublicSchema>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let subscriptions_route = warp::path!("api" / "v1" / "subscriptions")
+        .and(warp::ws())
+        .and(with_public_gql_schema(gql_schema))
+        .and(with_resources_context(resources_ctx))
+        .map(graphql_public_subscriptions_handler)
+        .map(|reply| warp::reply::with_header(reply, "Sec-WebSocket-Protocol", "graphql-ws"))
+        .with(logger);
+    subscriptions_route
+}
+
+/// GET /subscriptions/private (websocket, graphql-ws protocol, jwt sent on connection init)
+pub fn graphql_private_subscriptions_route(
+    resources_ctx: Arc<ResourcesContext>,
+    gql_schema: Arc<PrivateSchema>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let subscriptions_route = warp::path!("api" / "v1" / "subscriptions" / "private")
+        .and(warp::ws())
+        .and(with_authenticated_gql_schema(gql_schema))
+        .and(with_resources_context(resources_ctx))
+        .map(graphql_private_subscriptions_handler)
+        .map(|reply| warp::reply::with_header(reply, "Sec-WebSocket-Protocol", "graphql-ws"))
+        .with(logger);
+    subscriptions_route
+}
+
+/// GET /graphql/schema/public
+pub fn graphql_public_schema_route(
+    gql_schema: Arc<PublicSchema>,
+    enabled: bool,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    // the schema does not change at runtime, render it once
+    let sdl = public_schema_language(&gql_schema);
+    let schema_route = warp::get()
+        .and(warp::path!("api" / "v1" / "graphql" / "schema" / "public"))
+        .and(with_enabled(enabled))
+        .map(move || sdl.clone())
+        .with(logger);
+    schema_route
+}
+
+/// GET /graphql/schema/private
+pub fn graphql_private_schema_route(
+    gql_schema: Arc<PrivateSchema>,
+    enabled: bool,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    graphql_authenticated_schema_route(
+        "private",
+        Operation::PrivateSchema,
+        &gql_schema,
+        enabled,
+        logger,
+    )
+}
+
+/// GET /graphql/schema/seller
+pub fn graphql_seller_schema_route(
+    gql_schema: Arc<SellerSchema>,
+    enabled: bool,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    graphql_authenticated_schema_route(
+        "seller",
+        Operation::SellerGraphql,
+        &gql_schema,
+        enabled,
+        logger,
+    )
+}
+
+/// GET /graphql/schema/buyer
+pub fn graphql_buyer_schema_route(
+    gql_schema: Arc<BuyerSchema>,
+    enabled: bool,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    graphql_authenticated_schema_route(
+        "buyer",
+        Operation::BuyerGraphql,
+        &gql_schema,
+        enabled,
+        logger,
+    )
+}
+
+/// GET /graphql/schema/admin
+pub fn graphql_admin_schema_route(
+    gql_schema: Arc<AdminSchema>,
+    enabled: bool,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    graphql_authenticated_schema_route(
+        "admin",
+        Operation::AdminGraphql,
+        &gql_schema,
+        enabled,
+        logger,
+    )
+}
+
+/// GET /graphql/schema/<schema_name>, for the roles the operation allows
+fn graphql_authenticated_schema_route<Q: GqlRoot, M: GqlRoot, S: GqlSubscriptionRoot>(
+    schema_name: &'static str,
+    operation: Operation,
+    gql_schema: &AuthenticatedSchema<Q, M, S>,
+    enabled: bool,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let sdl = authenticated_schema_language(gql_schema);
+    let schema_route = warp::get()
+        .and(warp::path("api"))
+        .and(warp::path("v1"))
+        .and(warp::path("graphql"))
+        .and(warp::path("schema"))
+        .and(warp::path(schema_name))
+        .and(warp::path::end())
+        .and(with_enabled(enabled))
+        .and(with_role_auth(operation))
+        .map(move |_user_id| sdl.clone())
+        .with(logger);
+    schema_route
+}
+
+/// GET /graphiql
 pub fn public_graphiql_route(
     server_addr: SocketAddr,
+    enabled: bool,
     logger: Log<impl Fn(Info<'_>) + Copy + Send>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     let gql_endpoint = format!(
         "http://localhost:{}/api/v1/graphql/public",
         server_addr.port()
     );
+    let subscriptions_endpoint =
+        format!("ws://localhost:{}/api/v1/subscriptions", server_addr.port());
     let graphiql_route = warp::get()
         .and(warp::path!("api" / "v1" / "graphiql"))
+        .and(with_enabled(enabled))
         .map(move || {
             warp::reply::html(graphiql_source(
                 &gql_endpoint,
-                None, //Some(format!("ws://{}/api/v1/subscriptions", server_addr.to_string()).as_str())
+                Some(&subscriptions_endpoint),
             ))
         })
         .with(logger);
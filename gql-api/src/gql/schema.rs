@@ -1,32 +1,209 @@
 use crate::{
+    audit::{self, AuditEntity},
+    cache::EventCache,
+    chain::ChainClient,
+    config::{
+        AssetUrlsConfig, BodyLimitsConfig, BusinessConfig, GraphqlConfig, HealthConfig,
+        HttpCacheConfig, JobsConfig, LoginLockoutConfig, NearConfig, PasswordPolicyConfig,
+        SanitationConfig, SeatsConfig, SeriesConfig, SessionsConfig, SignupsConfig,
+        StockAlertsConfig, TicketPdfConfig, TopUpsConfig, TotpConfig, UsernamesConfig,
+    },
+    geo::Geocoder,
     gql::{
-        mutations::{PrivateMutationRoot, PublicMutationRoot},
-        quiries::{PrivateQueryRoot, PublicQueryRoot},
+        error::GqlError,
+        loaders::Loaders,
+        models::Event,
+        mutations::{
+            AdminMutationRoot, BuyerMutationRoot, PrivateMutationRoot, PublicMutationRoot,
+            SellerMutationRoot,
+        },
+        quiries::{
+            AdminQueryRoot, BuyerQueryRoot, PrivateQueryRoot, PublicQueryRoot, SellerQueryRoot,
+        },
         subscriptions::{PrivateSubscriptionRoot, PublicSubscriptionRoot},
     },
-    grpc::GrpcNearClient,
+    grpc::NearClient,
+    ipfs::IpfsClient,
+    maintenance::Maintenance,
+    notifier::Notifier,
+    publisher::Publisher,
+    security::{codes::Codes, pusher_auth::PusherAuth},
+    storage::Storage,
+    webhooks::WebhookSender,
 };
-use juniper::RootNode;
-use pusher_client::client::PusherClient;
-use s3_uploader::{s3::S3Client, AwsContext};
+use juniper::{DefaultScalarValue, EmptySubscription, GraphQLType, GraphQLTypeAsync, RootNode};
+use std::{ops::Deref, sync::Arc};
 use tokio::sync::Mutex;
 use tokio_postgres::Client;
-use twilio_client::client::TwilioClient;
 use uuid::Uuid;
 
 pub type PublicSchema =
     RootNode<'static, PublicQueryRoot, PublicMutationRoot, PublicSubscriptionRoot>;
 pub type PrivateSchema =
     RootNode<'static, PrivateQueryRoot, PrivateMutationRoot, PrivateSubscriptionRoot>;
+pub type SellerSchema =
+    RootNode<'static, SellerQueryRoot, SellerMutationRoot, EmptySubscription<Context>>;
+pub type BuyerSchema =
+    RootNode<'static, BuyerQueryRoot, BuyerMutationRoot, EmptySubscription<Context>>;
+pub type AdminSchema =
+    RootNode<'static, AdminQueryRoot, AdminMutationRoot, EmptySubscription<Context>>;
 
-pub struct Context {
+/// A query or mutation root the authenticated graphql routes serve
+pub trait GqlRoot:
+    GraphQLTypeAsync<DefaultScalarValue, Context = Context, TypeInfo = ()> + Send + Sync + 'static
+{
+}
+
+impl<T> GqlRoot for T where
+    T: GraphQLTypeAsync<DefaultScalarValue, Context = Context, TypeInfo = ()>
+        + Send
+        + Sync
+        + 'static
+{
+}
+
+/// A subscription root of the schemas the authenticated graphql routes serve, the role schemas
+/// have none
+pub trait GqlSubscriptionRoot:
+    GraphQLType<DefaultScalarValue, Context = Context, TypeInfo = ()> + Send + Sync + 'static
+{
+}
+
+impl<T> GqlSubscriptionRoot for T where
+    T: GraphQLType<DefaultScalarValue, Context = Context, TypeInfo = ()> + Send + Sync + 'static
+{
+}
+
+/// A schema of the authenticated graphql routes
+pub type AuthenticatedSchema<Q, M, S> = RootNode<'static, Q, M, S>;
+
+/// Clients and settings shared by every request
+pub struct Resources {
     pub db_client: Client,
-    pub grpc_near_client: Mutex<GrpcNearClient>,
+    pub grpc_near_client: Arc<Mutex<Box<dyn NearClient>>>,
+    pub chain: Arc<dyn ChainClient>,
+    pub publisher: Arc<dyn Publisher>,
+    pub notifier: Arc<dyn Notifier>,
+    pub storage: Arc<dyn Storage>,
+    pub asset_urls: AssetUrlsConfig,
+    pub geocoder: Arc<dyn Geocoder>,
+    pub webhooks: Arc<dyn WebhookSender>,
+    pub ipfs_client: Option<IpfsClient>,
+    pub health: HealthConfig,
+    pub jobs: JobsConfig,
+    pub sessions: SessionsConfig,
+    pub graphql: GraphqlConfig,
+    pub near: NearConfig,
+    pub business: BusinessConfig,
+    pub totp: Option<TotpConfig>,
+    pub ticket_pdf: Option<TicketPdfConfig>,
+    pub body_limits: BodyLimitsConfig,
+    pub http_cache: HttpCacheConfig,
+    pub usernames: UsernamesConfig,
+    pub sanitation: SanitationConfig,
+    pub password_policy: PasswordPolicyConfig,
+    pub login_lockout: LoginLockoutConfig,
+    pub pusher_auth: PusherAuth,
+    pub codes: Codes,
+    pub top_ups: TopUpsConfig,
+    pub signups: SignupsConfig,
+    pub stock_alerts: StockAlertsConfig,
+    pub series: SeriesConfig,
+    pub seats: SeatsConfig,
+    pub event_cache: Arc<EventCache>,
+    pub maintenance: Arc<Maintenance>,
+}
+
+pub struct Context {
+    resources: Arc<Resources>,
     pub user_id: Mutex<Option<Uuid>>,
-    pub pusher_client: PusherClient,
-    pub twilio_client: TwilioClient,
-    pub aws_s3_client: S3Client,
-    pub aws_context: AwsContext,
+    /// the super admin acting as the user, on requests with an impersonation jwt
+    pub impersonated_by: Option<Uuid>,
+    /// when the jwt of the request was issued, checked against the user's last password change
+    pub jwt_issued_at: Option<i64>,
+    /// the records loaded by the nested resolvers, kept as long as the context
+    pub loaders: Loaders,
+}
+
+impl Context {
+    pub fn new(resources: Resources) -> Self {
+        Context {
+            resources: Arc::new(resources),
+            user_id: Mutex::new(None),
+            impersonated_by: None,
+            jwt_issued_at: None,
+            loaders: Loaders::default(),
+        }
+    }
+
+    /// A context sharing the same resources, owned by a single request or connection of the
+    /// given user
+    pub fn for_user(&self, user_id: Option<Uuid>) -> Self {
+        Context {
+            resources: Arc::clone(&self.resources),
+            user_id: Mutex::new(user_id),
+            impersonated_by: None,
+            jwt_issued_at: None,
+            loaders: Loaders::default(),
+        }
+    }
+
+    /// The context of a request made by a super admin impersonating the user
+    pub fn impersonated(self, admin_id: Option<Uuid>) -> Self {
+        Context {
+            impersonated_by: admin_id,
+            ..self
+        }
+    }
+
+    /// The context of a request authorized with a jwt issued at the given time
+    pub fn jwt_issued_at(self, issued_at: Option<i64>) -> Self {
+        Context {
+            jwt_issued_at: issued_at,
+            ..self
+        }
+    }
+
+    /// Records a state change in the audit log, along with the super admin impersonating the
+    /// actor if any
+    pub async fn audit(
+        &self,
+        actor: Option<Uuid>,
+        action: &str,
+        entity: AuditEntity,
+        diff: Option<serde_json::Value>,
+    ) {
+        audit::record_impersonated(
+            &self.db_client,
+            actor,
+            self.impersonated_by,
+            action,
+            entity,
+            diff,
+        )
+        .await
+    }
+
+    /// The event as served, with the urls clients read its images at
+    pub async fn readable_event(&self, event: Event) -> Result<Event, GqlError> {
+        event
+            .with_readable_urls(self.storage.as_ref(), &self.asset_urls)
+            .await
+            .map_err(|e| GqlError::Storage(e.to_string()))
+    }
+
+    pub async fn readable_events(&self, events: Vec<Event>) -> Result<Vec<Event>, GqlError> {
+        futures::future::try_join_all(events.into_iter().map(|event| self.readable_event(event)))
+            .await
+    }
+}
+
+impl Deref for Context {
+    type Target = Resources;
+
+    fn deref(&self) -> &Self::Target {
+        &self.resources
+    }
 }
 
 impl juniper::Context for Context {}
@@ -1,35 +1,30 @@
-#![allow(missing_docs)]
+use crate::gql::schema::{
+    AuthenticatedSchema, GqlRoot, GqlSubscriptionRoot, PrivateSchema, PublicSchema,
+};
 
-/// The schema as a static/hardcoded GraphQL Schema Language.
-pub const STATIC_GRAPHQL_SCHEMA_DEFINITION: &str = include_str!("../schemas/schema.graphql");
+/// Introspection meta fields, `__typename` is not one of them as clients rely on it
+const INTROSPECTION_FIELDS: [&str; 2] = ["__schema", "__type"];
 
-#[cfg(test)]
-mod tests {
-    use juniper::{EmptyMutation, EmptySubscription, RootNode};
-    use pretty_assertions::assert_eq;
-
-    use crate::{
-        mutations::MutationRoot, quiries::QueryRoot,
-        schema_language::STATIC_GRAPHQL_SCHEMA_DEFINITION, subscriptions::SubscriptionRoot,
-    };
-
-    #[test]
-    fn dynamic_schema_language_matches_static() {
-        let schema = RootNode::new(QueryRoot, MutationRoot, SubscriptionRoot);
-
-        dbg!("{}", schema.as_schema_language());
+/// The public schema as GraphQL Schema Language (SDL)
+pub fn public_schema_language(schema: &PublicSchema) -> String {
+    schema.as_schema_language()
+}
 
-        // `include_str()` keeps line endings. `git` will sadly by default
-        // convert them, making this test fail without runtime tweaks on
-        // Windows.
-        //
-        // See https://github.com/rust-lang/rust/pull/63681.
-        #[cfg(windows)]
-        let expected = &STATIC_GRAPHQL_SCHEMA_DEFINITION.replace("\r\n", "\n");
+/// The private schema as GraphQL Schema Language (SDL)
+pub fn private_schema_language(schema: &PrivateSchema) -> String {
+    authenticated_schema_language(schema)
+}
 
-        #[cfg(not(windows))]
-        let expected = STATIC_GRAPHQL_SCHEMA_DEFINITION;
+/// The private schema or the one of a role as GraphQL Schema Language (SDL)
+pub fn authenticated_schema_language<Q: GqlRoot, M: GqlRoot, S: GqlSubscriptionRoot>(
+    schema: &AuthenticatedSchema<Q, M, S>,
+) -> String {
+    schema.as_schema_language()
+}
 
-        assert_eq!(expected, &schema.as_schema_language());
-    }
+/// Whether the query selects any of the introspection meta fields
+pub fn is_introspection_query(query: &str) -> bool {
+    query
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .any(|token| INTROSPECTION_FIELDS.contains(&token))
 }
@@ -1,28 +1,47 @@
-use super::models::{Event, EventFilter};
-use crate::{db::sql::db_get_events, gql::schema::Context as ResourcesContext};
+use super::{
+    error::GqlError,
+    models::{Event, EventFilter, EventStatus},
+};
+use crate::{
+    db::sql::{db_get_events, EventsFilter},
+    gql::schema::Context as ResourcesContext,
+};
 use std::pin::Pin;
 use uuid::Uuid;
 
-type EventStream = Pin<Box<dyn futures::Stream<Item = Vec<Event>> + Send>>;
+type EventStream = Pin<Box<dyn futures::Stream<Item = Result<Vec<Event>, GqlError>> + Send>>;
+
+/// The events with urls the clients can read. A failure is logged and yielded, it must not end
+/// the subscription task
+async fn readable_events(ctx: &ResourcesContext, events: Vec<Event>) -> EventStream {
+    let events = ctx.readable_events(events).await.map_err(|e| {
+        log::error!("Failed to make the subscribed events readable: {}", e);
+        e
+    });
+    Box::pin(futures::stream::once(futures::future::ready(events)))
+}
 
 #[derive(Copy, Clone, Default)]
 pub struct PublicSubscriptionRoot;
 
 #[juniper::graphql_subscription(Context = ResourcesContext)]
 impl PublicSubscriptionRoot {
-    async fn event_sub(ctx: &ResourcesContext, id: Option<String>) -> EventStream {
-        let id = id
-            .map(|s| Uuid::parse_str(&s))
-            .transpose()
-            .expect("Bad uuid");
-
-        let events = db_get_events(&ctx.db_client, id, None, Some(EventFilter::All))
-            .await
-            .unwrap()
-            .into_iter()
-            .map(|event| Event::new(event, vec![]))
-            .collect();
-        Box::pin(futures::stream::once(futures::future::ready(events)))
+    async fn event_sub(ctx: &ResourcesContext, id: Option<Uuid>) -> EventStream {
+        let events = db_get_events(
+            &ctx.db_client,
+            &EventsFilter {
+                id,
+                filter: Some(EventFilter::All),
+                statuses: vec![EventStatus::Final],
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|event| Event::new(event, vec![]))
+        .collect();
+        readable_events(ctx, events).await
     }
 }
 
@@ -31,18 +50,29 @@ pub struct PrivateSubscriptionRoot;
 
 #[juniper::graphql_subscription(Context = ResourcesContext)]
 impl PrivateSubscriptionRoot {
-    async fn event_sub(ctx: &ResourcesContext, id: Option<String>) -> EventStream {
-        let id = id
-            .map(|s| Uuid::parse_str(&s))
-            .transpose()
-            .expect("Bad uuid");
+    /// the events the caller manages, as `myEvents` lists them
+    async fn event_sub(ctx: &ResourcesContext, id: Option<Uuid>) -> Result<EventStream, GqlError> {
+        let user_id = {
+            let guard = ctx.user_id.lock().await;
+            let user_id = guard.ok_or(GqlError::UnexpectedInternal)?;
+            drop(guard);
+            user_id
+        };
 
-        let events = db_get_events(&ctx.db_client, id, None, Some(EventFilter::All))
-            .await
-            .unwrap()
-            .into_iter()
-            .map(|event| Event::new(event, vec![]))
-            .collect();
-        Box::pin(futures::stream::once(futures::future::ready(events)))
+        let events = db_get_events(
+            &ctx.db_client,
+            &EventsFilter {
+                id,
+                filter: Some(EventFilter::All),
+                managed_by_user: Some(user_id),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|event| Event::new(event, vec![]))
+        .collect();
+        Ok(readable_events(ctx, events).await)
     }
 }
@@ -1,29 +1,98 @@
 use super::{error::GqlError, models::UpdateEvent};
 use crate::{
+    config::SanitationConfig,
     db::models::{DbEvent, DbTicket},
+    geo::Coordinates,
     gql::{
         error::ValidationError,
-        models::{NewTicket, UpdateTicket},
+        models::{
+            ChangePassword, DiscountType, ExportWallet, NewPriceTier, NewPromoCode, NewSeatSection,
+            NewTicket, NewTicketGift, NewTicketListing, NewTicketTransfer, RotateWalletSecret,
+            UpdateProfile, UpdateTicket,
+        },
     },
+    near::{parse_basis_points, NearAmount},
+    phone::normalize_phone_number,
+    sanitize::{sanitize_field, TextField},
+    webhooks::{is_valid_webhook_url, MAX_WEBHOOK_URL_LENGTH},
 };
+use near_account_id::AccountId;
 use slugify::slugify;
+use uuid::Uuid;
+
+/// Upper bound for any ticket price, 1 billion NEAR
+pub const MAX_TICKET_PRICE: NearAmount = NearAmount::from_whole_near(1_000_000_000);
+/// How many tags a single event may carry
+pub const MAX_EVENT_TAGS: usize = 10;
+const MAX_TAG_LEN: usize = 32;
+const MAX_CATEGORY_NAME_LEN: usize = 64;
+const MAX_ORGANIZATION_NAME_LEN: usize = 64;
+const MAX_REJECTION_REASON_LEN: usize = 500;
+const MAX_TIER_NAME_LEN: usize = 64;
+/// How many price tiers a single ticket may have
+pub const MAX_PRICE_TIERS: usize = 10;
+/// How many images the gallery of a single event may have
+pub const MAX_GALLERY_IMAGES: usize = 20;
+/// Buyers may be allowed to cancel until at most a year before the start
+pub const MAX_CANCELLATION_WINDOW_HOURS: i32 = 24 * 365;
+const MAX_SEARCH_TEXT_LEN: usize = 200;
+const MAX_MAINTENANCE_MESSAGE_LEN: usize = 500;
+/// Clients are told to retry within a day at the latest
+pub const MAX_RETRY_AFTER_SECS: i32 = 24 * 60 * 60;
+/// The widest radius of the nearby events, in km
+pub const MAX_NEARBY_RADIUS_KM: f64 = 500.0;
+const MIN_WALLET_SECRET_LEN: usize = 4;
+const MAX_WALLET_SECRET_LEN: usize = 32;
+const MIN_PROFILE_NAME_LEN: usize = 2;
+const MAX_PROFILE_NAME_LEN: usize = 20;
+const MIN_SELLER_SLUG_LEN: usize = 3;
+const MAX_SELLER_SLUG_LEN: usize = 30;
+const MAX_SECTION_NAME_LEN: usize = 64;
+const MAX_ROW_LABEL_LEN: usize = 8;
+/// How many seats a single row of a seat map section may have
+pub const MAX_SEATS_PER_ROW: i32 = 200;
+/// How many seats a single seat map section may have
+pub const MAX_SEATS_PER_SECTION: i32 = 2000;
+
+/// The sanitized user-generated text, a validation error if it is empty or too long
+pub fn sanitize_text_field(
+    config: &SanitationConfig,
+    field: TextField,
+    text: &str,
+) -> Result<String, GqlError> {
+    sanitize_field(config, field, text)
+        .map_err(|e| GqlError::Validation(ValidationError::new(e.field(), &e.to_string())))
+}
+
+fn sanitize_optional_field(
+    config: &SanitationConfig,
+    field: TextField,
+    text: Option<String>,
+) -> Result<Option<String>, GqlError> {
+    text.map(|text| sanitize_text_field(config, field, &text))
+        .transpose()
+}
 
 pub fn update_event_mutation_payload<'a>(
-    update_event: UpdateEvent,
+    config: &SanitationConfig,
+    mut update_event: UpdateEvent,
     db_event: &'a mut DbEvent,
 ) -> Result<&'a mut DbEvent, GqlError> {
-    // check event name
-    if update_event
-        .event_name
-        .as_ref()
-        .and_then(|f| Some(f.is_empty() || f.len() > 20))
-        .unwrap_or_default()
-    {
-        return Err(GqlError::Validation(ValidationError::new(
-            "event_name",
-            "Event name does not cover length requirements (max 20 chars)",
-        )));
-    }
+    // sanitize the event name, description and venue
+    update_event.event_name =
+        sanitize_optional_field(config, TextField::EventName, update_event.event_name)?;
+    update_event.description = sanitize_optional_field(
+        config,
+        TextField::EventDescription,
+        update_event.description,
+    )?;
+    update_event.venue_name =
+        sanitize_optional_field(config, TextField::VenueName, update_event.venue_name)?;
+    update_event.venue_location = sanitize_optional_field(
+        config,
+        TextField::VenueLocation,
+        update_event.venue_location,
+    )?;
 
     // check start date
     if update_event
@@ -107,45 +176,6 @@ pub fn update_event_mutation_payload<'a>(
         _ => (),
     }
 
-    // check description
-    if update_event
-        .description
-        .as_ref()
-        .and_then(|f| Some(f.is_empty() || f.len() > 20))
-        .unwrap_or_default()
-    {
-        return Err(GqlError::Validation(ValidationError::new(
-            "event_description",
-            "Event description does not cover length requirements (max 20 chars)",
-        )));
-    }
-
-    // check venue name
-    if update_event
-        .venue_name
-        .as_ref()
-        .and_then(|f| Some(f.is_empty()))
-        .unwrap_or_default()
-    {
-        return Err(GqlError::Validation(ValidationError::new(
-            "event_venue_name",
-            "Event venue_name does not cover length requirements (should not be empty)",
-        )));
-    }
-
-    // check venue location
-    if update_event
-        .venue_location
-        .as_ref()
-        .and_then(|f| Some(f.is_empty()))
-        .unwrap_or_default()
-    {
-        return Err(GqlError::Validation(ValidationError::new(
-            "event_venue_location",
-            "Event venue_location does not cover length requirements (should not be empty)",
-        )));
-    }
-
     // check cover photo url
     if update_event
         .cover_photo_base64
@@ -205,27 +235,18 @@ pub fn update_event_mutation_payload<'a>(
     Ok(db_event)
 }
 
-pub fn check_new_ticket_payload(new_ticket: &NewTicket) -> Result<(), GqlError> {
-    // check ticket name
-    if new_ticket.ticket_name.len() > 20 {
-        return Err(GqlError::Validation(ValidationError::new(
-            "ticket_name",
-            "Ticket name does not cover length requirements (max 20 chars)",
-        )));
-    }
-
-    // check ticket description
-    if new_ticket
-        .description
-        .as_ref()
-        .and_then(|f| Some(f.is_empty()))
-        .unwrap_or_default()
-    {
-        return Err(GqlError::Validation(ValidationError::new(
-            "ticket_description",
-            "Ticket description does not cover length requirements (should not be empty)",
-        )));
-    }
+pub fn check_new_ticket_payload(
+    config: &SanitationConfig,
+    new_ticket: &mut NewTicket,
+) -> Result<(), GqlError> {
+    // sanitize the ticket name and description
+    new_ticket.ticket_name =
+        sanitize_text_field(config, TextField::TicketName, &new_ticket.ticket_name)?;
+    new_ticket.description = sanitize_optional_field(
+        config,
+        TextField::TicketDescription,
+        new_ticket.description.take(),
+    )?;
 
     // check quantity available
     if new_ticket
@@ -282,67 +303,29 @@ pub fn check_new_ticket_payload(new_ticket: &NewTicket) -> Result<(), GqlError>
         _ => (),
     }
 
-    // check ticket price
-    if new_ticket
-        .price
-        .as_ref()
-        .map(|f| f.parse::<f64>())
-        .transpose()
-        .is_err()
-    {
-        return Err(GqlError::Validation(ValidationError::new(
-            "ticket_price",
-            "Ticket price is unparsable",
-        )));
-    }
-
-    // check ticket max release price
-    if new_ticket
-        .max_release_price
-        .as_ref()
-        .map(|f| f.parse::<f64>())
-        .transpose()
-        .is_err()
-    {
-        return Err(GqlError::Validation(ValidationError::new(
-            "ticket_max_release_price",
-            "Ticket max. release price is unparsable",
-        )));
-    }
+    // check ticket prices
+    check_ticket_prices(
+        new_ticket.price.as_ref(),
+        new_ticket.max_release_price.as_ref(),
+    )?;
 
     Ok(())
 }
 
 pub fn update_ticket_mutation_payload<'a>(
-    update_ticket: UpdateTicket,
+    config: &SanitationConfig,
+    mut update_ticket: UpdateTicket,
     db_event: &DbEvent,
     db_ticket: &'a mut DbTicket,
 ) -> Result<&'a mut DbTicket, GqlError> {
-    // check ticket name
-    if update_ticket
-        .ticket_name
-        .as_ref()
-        .and_then(|f| Some(f.is_empty() || f.len() > 20))
-        .unwrap_or_default()
-    {
-        return Err(GqlError::Validation(ValidationError::new(
-            "ticket_name",
-            "Ticket name does not cover length requirements (max 20 chars)",
-        )));
-    }
-
-    // check ticket description
-    if update_ticket
-        .description
-        .as_ref()
-        .and_then(|f| Some(f.is_empty()))
-        .unwrap_or_default()
-    {
-        return Err(GqlError::Validation(ValidationError::new(
-            "ticket_description",
-            "Ticket description does not cover length requirements (should not be empty)",
-        )));
-    }
+    // sanitize the ticket name and description
+    update_ticket.ticket_name =
+        sanitize_optional_field(config, TextField::TicketName, update_ticket.ticket_name)?;
+    update_ticket.description = sanitize_optional_field(
+        config,
+        TextField::TicketDescription,
+        update_ticket.description,
+    )?;
 
     // check quantity available
     if update_ticket
@@ -399,33 +382,14 @@ pub fn update_ticket_mutation_payload<'a>(
         _ => (),
     }
 
-    // check ticket price
-    if update_ticket
-        .price
-        .as_ref()
-        .map(|f| f.parse::<f64>())
-        .transpose()
-        .is_err()
-    {
-        return Err(GqlError::Validation(ValidationError::new(
-            "ticket_price",
-            "Ticket price is unparsable",
-        )));
-    }
-
-    // check ticket max release price
-    if update_ticket
-        .max_release_price
-        .as_ref()
-        .map(|f| f.parse::<f64>())
-        .transpose()
-        .is_err()
-    {
-        return Err(GqlError::Validation(ValidationError::new(
-            "ticket_max_release_price",
-            "Ticket max. release price is unparsable",
-        )));
-    }
+    // check ticket prices, against the current ones when only one of them changes
+    check_ticket_prices(
+        update_ticket.price.as_ref().or(db_ticket.price.as_ref()),
+        update_ticket
+            .max_release_price
+            .as_ref()
+            .or(db_ticket.max_release_price.as_ref()),
+    )?;
 
     // update the current db record
     if let Some(ticket_name) = update_ticket.ticket_name.as_ref() {
@@ -455,8 +419,686 @@ pub fn update_ticket_mutation_payload<'a>(
     if update_ticket.max_purchase_quantity.is_some() {
         db_ticket.max_purchase_quantity = update_ticket.max_purchase_quantity;
     }
+    if let Some(currency) = update_ticket.currency {
+        db_ticket.currency = currency;
+    }
     if update_ticket.allow_transfers.is_some() {
         db_ticket.allow_transfers = update_ticket.allow_transfers;
     }
     Ok(db_ticket)
 }
+
+pub fn check_new_promo_code_payload(new_promo_code: &NewPromoCode) -> Result<(), GqlError> {
+    // check promo code
+    if new_promo_code.code.len() < 3
+        || new_promo_code.code.len() > 20
+        || !new_promo_code
+            .code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(GqlError::Validation(ValidationError::new(
+            "promo_code",
+            "Promo code must be 3-20 alphanumeric chars (dashes and underscores allowed)",
+        )));
+    }
+
+    // check discount value
+    match new_promo_code.discount_type {
+        DiscountType::Percentage => {
+            // in basis points, percentages have up to two decimals
+            let discount_value =
+                parse_basis_points(&new_promo_code.discount_value).ok_or_else(|| {
+                    GqlError::Validation(ValidationError::new(
+                        "promo_code_discount_value",
+                        "Promo code discount value is unparsable",
+                    ))
+                })?;
+
+            if discount_value == 0 {
+                return Err(GqlError::Validation(ValidationError::new(
+                    "promo_code_discount_value",
+                    "Promo code discount value must be positive",
+                )));
+            }
+
+            if discount_value > 10_000 {
+                return Err(GqlError::Validation(ValidationError::new(
+                    "promo_code_discount_value",
+                    "Promo code percentage discount must not exceed 100",
+                )));
+            }
+        }
+        DiscountType::Fixed => {
+            let discount_value = new_promo_code
+                .discount_value
+                .parse::<NearAmount>()
+                .map_err(|_| {
+                    GqlError::Validation(ValidationError::new(
+                        "promo_code_discount_value",
+                        "Promo code fixed discount must be a yoctoNEAR amount",
+                    ))
+                })?;
+
+            if discount_value.eq(&NearAmount::default()) || discount_value > MAX_TICKET_PRICE {
+                return Err(GqlError::Validation(ValidationError::new(
+                    "promo_code_discount_value",
+                    "Promo code fixed discount is out of range",
+                )));
+            }
+        }
+    }
+
+    // check max uses
+    if new_promo_code
+        .max_uses
+        .as_ref()
+        .and_then(|f| Some(f <= &0))
+        .unwrap_or_default()
+    {
+        return Err(GqlError::Validation(ValidationError::new(
+            "promo_code_max_uses",
+            "Promo code max. uses does not cover requirements (should be positive)",
+        )));
+    }
+
+    // check valid_from < valid_until
+    match (
+        new_promo_code.valid_from.as_ref(),
+        new_promo_code.valid_until.as_ref(),
+    ) {
+        (Some(valid_from), Some(valid_until)) => {
+            if valid_from.timestamp_millis() >= valid_until.timestamp_millis() {
+                return Err(GqlError::Validation(ValidationError::new(
+                    "promo_code_validity",
+                    "Promo code validity end must be after the validity start",
+                )));
+            }
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
+
+pub fn check_ticket_transfer_payload(
+    new_ticket_transfer: &NewTicketTransfer,
+    db_ticket: &DbTicket,
+) -> Result<(), GqlError> {
+    // check the ticket may be transferred at all
+    if !db_ticket.allow_transfers.unwrap_or_default() {
+        return Err(GqlError::Validation(ValidationError::new(
+            "allow_transfers",
+            "Ticket does not allow transfers",
+        )));
+    }
+
+    // check transfer price against the max release price
+    if let Some(price) = new_ticket_transfer.price.as_ref() {
+        let exceeds_max_release_price = db_ticket
+            .max_release_price
+            .as_ref()
+            .map(|max_release_price| price > max_release_price)
+            .unwrap_or_default();
+        if exceeds_max_release_price {
+            return Err(GqlError::Validation(ValidationError::new(
+                "transfer_price",
+                "Transfer price exceeds the ticket max release price",
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the ticket may be resold for the asking price: a positive amount up to the ticket's
+/// max release price
+pub fn check_ticket_listing_payload(
+    new_ticket_listing: &NewTicketListing,
+    db_ticket: &DbTicket,
+) -> Result<(), GqlError> {
+    // a resale changes the ticket's owner like a transfer does
+    if !db_ticket.allow_transfers.unwrap_or_default() {
+        return Err(GqlError::Validation(ValidationError::new(
+            "allow_transfers",
+            "Ticket does not allow transfers",
+        )));
+    }
+
+    // check the asking price is within the floor and the ceiling
+    let asking_price = &new_ticket_listing.asking_price;
+    if asking_price.eq(&NearAmount::default()) {
+        return Err(GqlError::Validation(ValidationError::new(
+            "asking_price",
+            "Asking price must be positive, give the ticket away with a transfer instead",
+        )));
+    }
+    let max_release_price = db_ticket
+        .max_release_price
+        .as_ref()
+        .unwrap_or(&MAX_TICKET_PRICE);
+    if asking_price > max_release_price {
+        return Err(GqlError::Validation(ValidationError::new(
+            "asking_price",
+            "Asking price exceeds the ticket max release price",
+        )));
+    }
+
+    Ok(())
+}
+
+/// How the caller re-verifies before exporting their wallet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalletExportVerification {
+    RecoveryCode { session_id: Uuid, code: String },
+    Password(String),
+}
+
+/// Checks the export is re-verified one way, either a recovery code or the password
+pub fn check_export_wallet_payload(
+    export_wallet: &ExportWallet,
+) -> Result<WalletExportVerification, GqlError> {
+    match (
+        &export_wallet.recovery_session_id,
+        &export_wallet.recovery_code,
+        &export_wallet.password,
+    ) {
+        (Some(session_id), Some(code), None) => Ok(WalletExportVerification::RecoveryCode {
+            session_id: *session_id,
+            code: code.trim().to_string(),
+        }),
+        (None, None, Some(password)) => Ok(WalletExportVerification::Password(password.clone())),
+        _ => Err(GqlError::Validation(ValidationError::new(
+            "export_wallet",
+            "Either a recovery code with its session or the password is required",
+        ))),
+    }
+}
+
+/// Who tickets are gifted to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GiftRecipient {
+    Username(String),
+    /// normalized to E.164
+    PhoneNumber(String),
+}
+
+/// Checks the gift has a single recipient, normalizing their phone number
+pub fn check_ticket_gift_payload(
+    new_ticket_gift: &NewTicketGift,
+) -> Result<GiftRecipient, GqlError> {
+    let to_username = new_ticket_gift
+        .to_username
+        .as_deref()
+        .map(str::trim)
+        .filter(|username| !username.is_empty());
+    match (to_username, new_ticket_gift.to_phone_number.as_deref()) {
+        (Some(username), None) => Ok(GiftRecipient::Username(username.to_string())),
+        (None, Some(phone_number)) => normalize_phone_number(phone_number)
+            .map(GiftRecipient::PhoneNumber)
+            .map_err(|_| {
+                GqlError::Validation(ValidationError::new(
+                    "to_phone_number",
+                    "Invalid phone number",
+                ))
+            }),
+        _ => Err(GqlError::Validation(ValidationError::new(
+            "recipient",
+            "Either a username or a phone number is required, not both",
+        ))),
+    }
+}
+
+/// Returns the file extension for an accepted upload content type
+pub fn check_upload_content_type(content_type: &str) -> Result<&'static str, GqlError> {
+    match content_type {
+        "image/jpeg" => Ok("jpg"),
+        "image/png" => Ok("png"),
+        "image/webp" => Ok("webp"),
+        _ => Err(GqlError::Validation(ValidationError::new(
+            "content_type",
+            "Only image/jpeg, image/png and image/webp uploads are allowed",
+        ))),
+    }
+}
+
+pub fn check_new_price_tier_payload(new_price_tier: &NewPriceTier) -> Result<(), GqlError> {
+    let tier_name = new_price_tier.tier_name.trim();
+    if tier_name.is_empty() || tier_name.chars().count() > MAX_TIER_NAME_LEN {
+        return Err(GqlError::Validation(ValidationError::new(
+            "tier_name",
+            "Tier name must be between 1 and 64 characters",
+        )));
+    }
+
+    if new_price_tier.price > MAX_TICKET_PRICE {
+        return Err(GqlError::Validation(ValidationError::new(
+            "price",
+            "Tier price is out of range",
+        )));
+    }
+
+    if let Some(ends_at) = new_price_tier.ends_at.as_ref() {
+        if ends_at.le(&new_price_tier.starts_at) {
+            return Err(GqlError::Validation(ValidationError::new(
+                "ends_at",
+                "Tier must end after it starts",
+            )));
+        }
+    }
+
+    if new_price_tier
+        .quantity_cap
+        .map(|quantity_cap| quantity_cap <= 0)
+        .unwrap_or_default()
+    {
+        return Err(GqlError::Validation(ValidationError::new(
+            "quantity_cap",
+            "Tier quantity cap should be positive",
+        )));
+    }
+
+    Ok(())
+}
+
+/// Returns the file extension for an accepted seller document content type
+pub fn check_document_content_type(content_type: &str) -> Result<&'static str, GqlError> {
+    match content_type {
+        "application/pdf" => Ok("pdf"),
+        "image/jpeg" => Ok("jpg"),
+        "image/png" => Ok("png"),
+        _ => Err(GqlError::Validation(ValidationError::new(
+            "content_type",
+            "Only application/pdf, image/jpeg and image/png documents are allowed",
+        ))),
+    }
+}
+
+fn check_ticket_prices(
+    price: Option<&NearAmount>,
+    max_release_price: Option<&NearAmount>,
+) -> Result<(), GqlError> {
+    if price
+        .map(|price| price > &MAX_TICKET_PRICE)
+        .unwrap_or_default()
+    {
+        return Err(GqlError::Validation(ValidationError::new(
+            "ticket_price",
+            "Ticket price is out of range",
+        )));
+    }
+
+    if let Some(max_release_price) = max_release_price {
+        if max_release_price > &MAX_TICKET_PRICE {
+            return Err(GqlError::Validation(ValidationError::new(
+                "ticket_max_release_price",
+                "Ticket max. release price is out of range",
+            )));
+        }
+        if price
+            .map(|price| price > max_release_price)
+            .unwrap_or_default()
+        {
+            return Err(GqlError::Validation(ValidationError::new(
+                "ticket_max_release_price",
+                "Ticket max. release price must not be lower than the price",
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn check_rotate_wallet_secret_payload(
+    rotate_wallet_secret: &RotateWalletSecret,
+) -> Result<(), GqlError> {
+    // the same bounds as the secret chosen on signup
+    let len = rotate_wallet_secret.new_secret.chars().count();
+    if !(MIN_WALLET_SECRET_LEN..=MAX_WALLET_SECRET_LEN).contains(&len) {
+        return Err(GqlError::Validation(ValidationError::new(
+            "new_secret",
+            "Secret must be between 4 and 32 characters",
+        )));
+    }
+
+    if rotate_wallet_secret
+        .new_secret
+        .eq(&rotate_wallet_secret.current_secret)
+    {
+        return Err(GqlError::Validation(ValidationError::new(
+            "new_secret",
+            "New secret must differ from the current one",
+        )));
+    }
+
+    Ok(())
+}
+
+/// Trims the submitted profile fields, the same bounds as on signup apply
+pub fn check_update_profile_payload(
+    config: &SanitationConfig,
+    update_profile: UpdateProfile,
+) -> Result<UpdateProfile, GqlError> {
+    let update_profile = UpdateProfile {
+        name: update_profile.name.map(|name| name.trim().to_string()),
+        username: sanitize_optional_field(config, TextField::Username, update_profile.username)?,
+        email: update_profile.email.map(|email| email.trim().to_string()),
+    };
+
+    if update_profile.name.is_none()
+        && update_profile.username.is_none()
+        && update_profile.email.is_none()
+    {
+        return Err(GqlError::Validation(ValidationError::new(
+            "profile",
+            "At least one of name, username or email must be given",
+        )));
+    }
+
+    let name_len = MIN_PROFILE_NAME_LEN..=MAX_PROFILE_NAME_LEN;
+    if let Some(name) = &update_profile.name {
+        if !name_len.contains(&name.chars().count()) {
+            return Err(GqlError::Validation(ValidationError::new(
+                "name",
+                "Name must be between 2 and 20 characters",
+            )));
+        }
+    }
+    if let Some(username) = &update_profile.username {
+        if username.chars().count() < MIN_PROFILE_NAME_LEN {
+            return Err(GqlError::Validation(ValidationError::new(
+                "username",
+                "Username must be at least 2 characters",
+            )));
+        }
+    }
+    if let Some(email) = &update_profile.email {
+        if !validator::validate_email(email.as_str()) {
+            return Err(GqlError::Validation(ValidationError::new(
+                "email",
+                "Email is not a valid email address",
+            )));
+        }
+    }
+
+    Ok(update_profile)
+}
+
+/// The new password must differ from the current one, its strength is up to the password policy
+pub fn check_change_password_payload(change_password: &ChangePassword) -> Result<(), GqlError> {
+    if change_password
+        .new_password
+        .eq(&change_password.current_password)
+    {
+        return Err(GqlError::Validation(ValidationError::new(
+            "new_password",
+            "New password must differ from the current one",
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn check_payout_wallet_id(wallet_id: &str) -> Result<(), GqlError> {
+    // payouts may go to named or implicit accounts, but never to system accounts
+    let account_id = wallet_id.parse::<AccountId>().map_err(|_| {
+        GqlError::Validation(ValidationError::new(
+            "wallet_id",
+            "Payout wallet is not a valid NEAR account id",
+        ))
+    })?;
+
+    if account_id.is_system() {
+        return Err(GqlError::Validation(ValidationError::new(
+            "wallet_id",
+            "Payout wallet cannot be a system account",
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn check_category_name(name: &str) -> Result<(), GqlError> {
+    if slugify!(name).is_empty() || name.chars().count() > MAX_CATEGORY_NAME_LEN {
+        return Err(GqlError::Validation(ValidationError::new(
+            "category_name",
+            "Category name must be between 1 and 64 characters",
+        )));
+    }
+
+    Ok(())
+}
+
+/// Storefront slugs are typed in urls, so they must already be lowercase slugs
+pub fn check_seller_slug(seller_slug: &str) -> Result<String, GqlError> {
+    let seller_slug = seller_slug.trim();
+    if seller_slug != slugify!(seller_slug, separator = "-") {
+        return Err(GqlError::Validation(ValidationError::new(
+            "seller_slug",
+            "Seller slug may only contain lowercase letters, digits and single hyphens",
+        )));
+    }
+    if !(MIN_SELLER_SLUG_LEN..=MAX_SELLER_SLUG_LEN).contains(&seller_slug.len()) {
+        return Err(GqlError::Validation(ValidationError::new(
+            "seller_slug",
+            "Seller slug must be between 3 and 30 characters",
+        )));
+    }
+
+    Ok(seller_slug.to_string())
+}
+
+pub fn check_organization_name(name: &str) -> Result<(), GqlError> {
+    if slugify!(name).is_empty() || name.chars().count() > MAX_ORGANIZATION_NAME_LEN {
+        return Err(GqlError::Validation(ValidationError::new(
+            "name",
+            "Organization name must be between 1 and 64 characters",
+        )));
+    }
+
+    Ok(())
+}
+
+/// Trims the reason a seller's onboarding or an event's review is rejected for
+pub fn check_rejection_reason(reason: &str) -> Result<String, GqlError> {
+    let reason = reason.trim();
+    if reason.is_empty() || reason.chars().count() > MAX_REJECTION_REASON_LEN {
+        return Err(GqlError::Validation(ValidationError::new(
+            "reason",
+            "Rejection reason must be between 1 and 500 characters",
+        )));
+    }
+
+    Ok(reason.to_string())
+}
+
+/// Normalizes the submitted tags to sorted, deduplicated slugs
+pub fn check_event_tags(tags: &[String]) -> Result<Vec<String>, GqlError> {
+    let mut normalized = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = slugify!(tag, separator = "-");
+        if tag.is_empty() || tag.len() > MAX_TAG_LEN {
+            return Err(GqlError::Validation(ValidationError::new(
+                "tags",
+                "Tags must be between 1 and 32 characters",
+            )));
+        }
+        normalized.push(tag);
+    }
+    normalized.sort();
+    normalized.dedup();
+
+    if normalized.len() > MAX_EVENT_TAGS {
+        return Err(GqlError::Validation(ValidationError::new(
+            "tags",
+            "An event can have at most 10 tags",
+        )));
+    }
+
+    Ok(normalized)
+}
+
+/// Returns the trimmed search text
+pub fn check_search_text(text: &str) -> Result<&str, GqlError> {
+    let text = text.trim();
+    if text.is_empty() || text.chars().count() > MAX_SEARCH_TEXT_LEN {
+        return Err(GqlError::Validation(ValidationError::new(
+            "text",
+            "Search text must be between 1 and 200 characters",
+        )));
+    }
+
+    Ok(text)
+}
+
+/// Returns the center and the radius in km of a nearby events search
+pub fn check_nearby_payload(
+    latitude: f64,
+    longitude: f64,
+    radius_km: f64,
+) -> Result<(Coordinates, f64), GqlError> {
+    let center = Coordinates::new(latitude, longitude).ok_or_else(|| {
+        GqlError::Validation(ValidationError::new(
+            "latitude",
+            "Latitude must be between -90 and 90 and longitude between -180 and 180",
+        ))
+    })?;
+    if !(radius_km > 0.0 && radius_km <= MAX_NEARBY_RADIUS_KM) {
+        return Err(GqlError::Validation(ValidationError::new(
+            "radius_km",
+            "Radius must be greater than 0 and at most 500 km",
+        )));
+    }
+
+    Ok((center, radius_km))
+}
+
+/// Returns the gallery asset ids in their new order, which must be the `gallery` ids, each once
+pub fn check_gallery_order(gallery: &[Uuid], asset_ids: &[Uuid]) -> Result<Vec<Uuid>, GqlError> {
+    let mut ordered = asset_ids.to_vec();
+    ordered.sort_unstable();
+    let mut current = gallery.to_vec();
+    current.sort_unstable();
+    if ordered != current {
+        return Err(GqlError::Validation(ValidationError::new(
+            "asset_ids",
+            "Asset ids must be the images of the event's gallery, each once",
+        )));
+    }
+
+    Ok(asset_ids.to_vec())
+}
+
+/// Returns the trimmed webhook url, which must be an https url
+pub fn check_webhook_url(url: &str) -> Result<String, GqlError> {
+    let url = url.trim();
+    if !is_valid_webhook_url(url) {
+        return Err(GqlError::Validation(ValidationError::new(
+            "url",
+            &format!(
+                "Webhook url must be an https url of at most {} characters",
+                MAX_WEBHOOK_URL_LENGTH
+            ),
+        )));
+    }
+
+    Ok(url.to_string())
+}
+
+pub fn check_cancellation_window(hours: i32) -> Result<(), GqlError> {
+    if !(0..=MAX_CANCELLATION_WINDOW_HOURS).contains(&hours) {
+        return Err(GqlError::Validation(ValidationError::new(
+            "cancellation_window_hours",
+            &format!(
+                "Cancellation window must be between 0 and {} hours",
+                MAX_CANCELLATION_WINDOW_HOURS
+            ),
+        )));
+    }
+
+    Ok(())
+}
+
+/// Trims the maintenance message, a blank one is none
+/// Checks the section's name and rows, the row labels being unique once trimmed
+pub fn check_new_seat_section_payload(new_seat_section: &NewSeatSection) -> Result<(), GqlError> {
+    let section_name = new_seat_section.section_name.trim();
+    if section_name.is_empty() || section_name.chars().count() > MAX_SECTION_NAME_LEN {
+        return Err(GqlError::Validation(ValidationError::new(
+            "section_name",
+            "Section name must be between 1 and 64 characters",
+        )));
+    }
+
+    if new_seat_section.rows.is_empty() {
+        return Err(GqlError::Validation(ValidationError::new(
+            "rows",
+            "Section must have at least one row",
+        )));
+    }
+
+    let mut row_labels = Vec::with_capacity(new_seat_section.rows.len());
+    let mut seats = 0;
+    for row in &new_seat_section.rows {
+        let row_label = row.row_label.trim();
+        if row_label.is_empty() || row_label.chars().count() > MAX_ROW_LABEL_LEN {
+            return Err(GqlError::Validation(ValidationError::new(
+                "row_label",
+                "Row label must be between 1 and 8 characters",
+            )));
+        }
+        if row_labels.contains(&row_label) {
+            return Err(GqlError::Validation(ValidationError::new(
+                "row_label",
+                "Row labels must be unique",
+            )));
+        }
+        row_labels.push(row_label);
+
+        if !(1..=MAX_SEATS_PER_ROW).contains(&row.seats) {
+            return Err(GqlError::Validation(ValidationError::new(
+                "seats",
+                &format!("A row must have between 1 and {} seats", MAX_SEATS_PER_ROW),
+            )));
+        }
+        seats += row.seats;
+    }
+
+    if seats > MAX_SEATS_PER_SECTION {
+        return Err(GqlError::Validation(ValidationError::new(
+            "rows",
+            &format!("A section can have at most {} seats", MAX_SEATS_PER_SECTION),
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn check_maintenance_payload(
+    message: Option<&str>,
+    retry_after_secs: Option<i32>,
+) -> Result<Option<String>, GqlError> {
+    if let Some(retry_after_secs) = retry_after_secs {
+        if !(1..=MAX_RETRY_AFTER_SECS).contains(&retry_after_secs) {
+            return Err(GqlError::Validation(ValidationError::new(
+                "retry_after_secs",
+                &format!(
+                    "Retry after must be between 1 and {} seconds",
+                    MAX_RETRY_AFTER_SECS
+                ),
+            )));
+        }
+    }
+
+    let message = message.map(str::trim).filter(|message| !message.is_empty());
+    if message.map_or(false, |message| {
+        message.chars().count() > MAX_MAINTENANCE_MESSAGE_LEN
+    }) {
+        return Err(GqlError::Validation(ValidationError::new(
+            "message",
+            "Maintenance message must be at most 500 characters",
+        )));
+    }
+
+    Ok(message.map(str::to_string))
+}
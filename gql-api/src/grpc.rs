@@ -2,21 +2,122 @@ use self::near_api::{
     AesDecryptDataRequest, AesDecryptDataResponse, AesEncryptDataRequest, AesEncryptDataResponse,
     CheckAvailableAccountIdRequest, CheckAvailableAccountIdResponse, CreateAccountRequest,
     CreateAccountResponse, GenerateImplicitAccountRequest, GenerateImplicitAccountResponse,
-    GetAccountKeysRequest, GetAccountKeysResponse, MintNftsRequest, MintNftsResponse,
-    VerifySignatureRequest, VerifySignatureResponse,
+    GetAccountKeysRequest, GetAccountKeysResponse, GetTxStatusRequest, GetTxStatusResponse,
+    MintNftsRequest, MintNftsResponse, RefundPaymentRequest, RefundPaymentResponse,
+    TransferNftRequest, TransferNftResponse, VerifySignatureRequest, VerifySignatureResponse,
 };
-use crate::config::GrpcConfig;
+use crate::config::{GrpcConfig, ReconnectConfig};
 use crate::error::GrpcError;
+use crate::metrics::{grpc_error, grpc_timer};
+use async_trait::async_trait;
 use near_api::near_api_engine_service_client::NearApiEngineServiceClient;
 use near_api::{
     FundAccountRequest, FundAccountResponse, GetAccountBalanceRequest, GetAccountBalanceResponse,
 };
+use serde::{Deserialize, Serialize};
+use std::error::Error as StdError;
+use std::time::{Duration, Instant};
+use tonic::transport::channel::Channel;
 pub mod near_api {
     tonic::include_proto!("com.project.near"); // this is the proto package name
 }
 
+/// The state of the connection to the near api service
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrpcStatus {
+    /// the last call went through
+    Connected,
+    /// the channel broke, the next call after the backoff reconnects
+    Disconnected,
+}
+
+/// The wait before the next reconnect attempt, after `failures` failed ones: none after the
+/// channel broke, then doubled from the initial backoff up to the max backoff
+pub fn reconnect_backoff(config: &ReconnectConfig, failures: u32) -> Duration {
+    if failures == 0 {
+        return Duration::ZERO;
+    }
+    let backoff_ms = config
+        .initial_backoff_ms
+        .saturating_mul(2u64.saturating_pow(failures - 1));
+    Duration::from_millis(backoff_ms.min(config.max_backoff_ms))
+}
+
+/// Whether a call failed because the channel is broken, rather than in the service
+pub fn is_connection_error(status: &tonic::Status) -> bool {
+    status.code() == tonic::Code::Unavailable
+        || status
+            .source()
+            .map_or(false, |source| source.is::<tonic::transport::Error>())
+}
+
+/// The near api client over a tonic channel. A channel broken by a restart of the service is
+/// dropped and reconnected lazily, on the next call, backing off while reconnecting fails
 pub struct GrpcNearClient {
-    near_api_client: NearApiEngineServiceClient<tonic::transport::channel::Channel>,
+    grpc_server_addr: String,
+    reconnect: ReconnectConfig,
+    near_api_client: Option<NearApiEngineServiceClient<Channel>>,
+    /// failed reconnect attempts since the channel broke
+    failures: u32,
+    /// no reconnect is attempted before
+    retry_at: Option<Instant>,
+}
+
+impl GrpcNearClient {
+    /// The client of the channel, reconnected if it broke and the backoff elapsed
+    async fn client(&mut self) -> Result<NearApiEngineServiceClient<Channel>, GrpcError> {
+        if let Some(near_api_client) = &self.near_api_client {
+            return Ok(near_api_client.clone());
+        }
+        if let Some(retry_at) = self.retry_at {
+            let now = Instant::now();
+            if now < retry_at {
+                return Err(GrpcError::Disconnected((retry_at - now).as_millis()));
+            }
+        }
+
+        match NearApiEngineServiceClient::connect(self.grpc_server_addr.clone()).await {
+            Ok(near_api_client) => {
+                log::info!("Reconnected to the near api at {}", self.grpc_server_addr);
+                self.near_api_client = Some(near_api_client.clone());
+                self.failures = 0;
+                self.retry_at = None;
+                Ok(near_api_client)
+            }
+            Err(e) => {
+                self.failures += 1;
+                let backoff = reconnect_backoff(&self.reconnect, self.failures);
+                log::warn!(
+                    "Failed to reconnect to the near api ({} attempts), retrying in {:?}: {}",
+                    self.failures,
+                    backoff,
+                    e
+                );
+                self.retry_at = Some(Instant::now() + backoff);
+                Err(GrpcError::Transport(e))
+            }
+        }
+    }
+
+    /// The response of a call, a channel broken by the call is dropped to be reconnected
+    fn outcome<T>(
+        &mut self,
+        method: &str,
+        result: Result<tonic::Response<T>, tonic::Status>,
+    ) -> Result<T, GrpcError> {
+        match result {
+            Ok(response) => Ok(response.into_inner()),
+            Err(status) => {
+                grpc_error(method);
+                if is_connection_error(&status) && self.near_api_client.take().is_some() {
+                    log::warn!("Lost the connection to the near api: {}", status);
+                    self.retry_at = None;
+                }
+                Err(GrpcError::Call(status))
+            }
+        }
+    }
 }
 
 pub async fn new(config: &GrpcConfig) -> Result<GrpcNearClient, GrpcError> {
@@ -36,74 +137,169 @@ pub async fn new(config: &GrpcConfig) -> Result<GrpcNearClient, GrpcError> {
         .map_err(GrpcError::Transport)?;
     let near_api_client = NearApiEngineServiceClient::new(channel);
     */
-    let near_api_client = NearApiEngineServiceClient::connect(grpc_server_addr)
+    let near_api_client = NearApiEngineServiceClient::connect(grpc_server_addr.clone())
         .await
         .map_err(GrpcError::Transport)?;
-    Ok(GrpcNearClient { near_api_client })
+    Ok(GrpcNearClient {
+        grpc_server_addr,
+        reconnect: config.reconnect.clone(),
+        near_api_client: Some(near_api_client),
+        failures: 0,
+        retry_at: None,
+    })
 }
 
-impl GrpcNearClient {
-    pub async fn get_account_balance(
+/// The NEAR api, behind a trait so that tests can run against an in-memory fake
+#[async_trait]
+pub trait NearClient: Send {
+    async fn get_account_balance(
+        &mut self,
+        account_id: &str,
+    ) -> Result<GetAccountBalanceResponse, GrpcError>;
+
+    async fn fund_account(
+        &mut self,
+        account_id: &str,
+        fund_amount: &str,
+    ) -> Result<FundAccountResponse, GrpcError>;
+
+    async fn create_account(
+        &mut self,
+        account_id: &str,
+        public_key: &str,
+        deposit_amount: &str,
+    ) -> Result<CreateAccountResponse, GrpcError>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn mint_nfts(
+        &mut self,
+        seller_wallet_id: String,
+        title: String,
+        ticket_slug: String,
+        description: String,
+        media: String,
+        media_hash: String,
+        number_of_tickets: i32,
+        extra: String,
+        amount_to_send: String,
+    ) -> Result<MintNftsResponse, GrpcError>;
+
+    async fn transfer_nft(
+        &mut self,
+        sender_wallet_id: String,
+        receiver_wallet_id: String,
+        ticket_slug: String,
+        price: String,
+    ) -> Result<TransferNftResponse, GrpcError>;
+
+    async fn check_available_account_id(
+        &mut self,
+        account_id: &str,
+    ) -> Result<CheckAvailableAccountIdResponse, GrpcError>;
+
+    async fn generate_implicit_account(
+        &mut self,
+    ) -> Result<GenerateImplicitAccountResponse, GrpcError>;
+
+    async fn verify_signature(
+        &mut self,
+        message: &str,
+        pub_key: &str,
+        signature: &str,
+    ) -> Result<VerifySignatureResponse, GrpcError>;
+
+    async fn get_account_keys(
+        &mut self,
+        account_id: &str,
+    ) -> Result<GetAccountKeysResponse, GrpcError>;
+
+    async fn aes_encrypt_data(
+        &mut self,
+        secret: &str,
+        data: &str,
+    ) -> Result<AesEncryptDataResponse, GrpcError>;
+
+    async fn aes_decrypt_data(
+        &mut self,
+        cypher: &str,
+        secret: &str,
+    ) -> Result<AesDecryptDataResponse, GrpcError>;
+
+    /// The final status of a transaction sent by `account_id`, `PENDING` until it is final
+    async fn get_tx_status(
+        &mut self,
+        tx_hash: &str,
+        account_id: &str,
+    ) -> Result<GetTxStatusResponse, GrpcError>;
+
+    /// Sends `amount` back to the buyer's wallet from the platform's account, for a cancelled
+    /// reservation. `reference` identifies the refund, the service sends it once per reference
+    async fn refund_payment(
+        &mut self,
+        receiver_wallet_id: &str,
+        amount: &str,
+        reference: &str,
+    ) -> Result<RefundPaymentResponse, GrpcError>;
+
+    /// The state of the connection, clients without one are always connected
+    fn status(&self) -> GrpcStatus {
+        GrpcStatus::Connected
+    }
+}
+
+#[async_trait]
+impl NearClient for GrpcNearClient {
+    fn status(&self) -> GrpcStatus {
+        if self.near_api_client.is_some() {
+            GrpcStatus::Connected
+        } else {
+            GrpcStatus::Disconnected
+        }
+    }
+
+    async fn get_account_balance(
         &mut self,
         account_id: &str,
     ) -> Result<GetAccountBalanceResponse, GrpcError> {
+        let _timer = grpc_timer("get_account_balance");
         let request = tonic::Request::new(GetAccountBalanceRequest {
             account_id: account_id.into(),
         });
-        match self.near_api_client.get_account_balance(request).await {
-            Ok(response) => {
-                let response = response.into_inner();
-                return Ok(response);
-            }
-            Err(status) => {
-                return Err(GrpcError::Call(status));
-            }
-        }
+        let result = self.client().await?.get_account_balance(request).await;
+        self.outcome("get_account_balance", result)
     }
 
-    pub async fn fund_account(
+    async fn fund_account(
         &mut self,
         account_id: &str,
         fund_amount: &str,
     ) -> Result<FundAccountResponse, GrpcError> {
+        let _timer = grpc_timer("fund_account");
         let request = tonic::Request::new(FundAccountRequest {
             account_id: account_id.into(),
             amount: fund_amount.into(),
         });
-        match self.near_api_client.fund_account(request).await {
-            Ok(response) => {
-                let response = response.into_inner();
-                return Ok(response);
-            }
-            Err(status) => {
-                return Err(GrpcError::Call(status));
-            }
-        }
+        let result = self.client().await?.fund_account(request).await;
+        self.outcome("fund_account", result)
     }
 
-    pub async fn create_account(
+    async fn create_account(
         &mut self,
         account_id: &str,
         public_key: &str,
         deposit_amount: &str,
     ) -> Result<CreateAccountResponse, GrpcError> {
+        let _timer = grpc_timer("create_account");
         let request = tonic::Request::new(CreateAccountRequest {
             account_id: account_id.into(),
             public_key: public_key.into(),
             deposit_amount: deposit_amount.into(),
         });
-        match self.near_api_client.create_account(request).await {
-            Ok(response) => {
-                let response = response.into_inner();
-                return Ok(response);
-            }
-            Err(status) => {
-                return Err(GrpcError::Call(status));
-            }
-        }
+        let result = self.client().await?.create_account(request).await;
+        self.outcome("create_account", result)
     }
 
-    pub async fn mint_nfts(
+    async fn mint_nfts(
         &mut self,
         seller_wallet_id: String,
         title: String,
@@ -115,6 +311,7 @@ impl GrpcNearClient {
         extra: String,
         amount_to_send: String,
     ) -> Result<MintNftsResponse, GrpcError> {
+        let _timer = grpc_timer("mint_nfts");
         let request = tonic::Request::new(MintNftsRequest {
             seller_wallet_id,
             title,
@@ -126,135 +323,140 @@ impl GrpcNearClient {
             extra,
             amount_to_send,
         });
-        match self.near_api_client.mint_nfts(request).await {
-            Ok(response) => {
-                let response = response.into_inner();
-                return Ok(response);
-            }
-            Err(status) => {
-                return Err(GrpcError::Call(status));
-            }
-        }
+        let result = self.client().await?.mint_nfts(request).await;
+        self.outcome("mint_nfts", result)
+    }
+
+    async fn transfer_nft(
+        &mut self,
+        sender_wallet_id: String,
+        receiver_wallet_id: String,
+        ticket_slug: String,
+        price: String,
+    ) -> Result<TransferNftResponse, GrpcError> {
+        let _timer = grpc_timer("transfer_nft");
+        let request = tonic::Request::new(TransferNftRequest {
+            sender_wallet_id,
+            receiver_wallet_id,
+            ticket_slug,
+            price,
+        });
+        let result = self.client().await?.transfer_nft(request).await;
+        self.outcome("transfer_nft", result)
     }
 
-    pub async fn check_available_account_id(
+    async fn check_available_account_id(
         &mut self,
         account_id: &str,
     ) -> Result<CheckAvailableAccountIdResponse, GrpcError> {
+        let _timer = grpc_timer("check_available_account_id");
         let request = tonic::Request::new(CheckAvailableAccountIdRequest {
             account_id: account_id.into(),
         });
-        match self
-            .near_api_client
+        let result = self
+            .client()
+            .await?
             .check_available_account_id(request)
-            .await
-        {
-            Ok(response) => {
-                let response = response.into_inner();
-                return Ok(response);
-            }
-            Err(status) => {
-                return Err(GrpcError::Call(status));
-            }
-        }
+            .await;
+        self.outcome("check_available_account_id", result)
     }
 
-    pub async fn generate_implicit_account(
+    async fn generate_implicit_account(
         &mut self,
     ) -> Result<GenerateImplicitAccountResponse, GrpcError> {
+        let _timer = grpc_timer("generate_implicit_account");
         let request = tonic::Request::new(GenerateImplicitAccountRequest {});
-        match self
-            .near_api_client
+        let result = self
+            .client()
+            .await?
             .generate_implicit_account(request)
-            .await
-        {
-            Ok(response) => {
-                let response = response.into_inner();
-                return Ok(response);
-            }
-            Err(status) => {
-                return Err(GrpcError::Call(status));
-            }
-        }
+            .await;
+        self.outcome("generate_implicit_account", result)
     }
 
-    pub async fn verify_signature(
+    async fn verify_signature(
         &mut self,
         message: &str,
         pub_key: &str,
         signature: &str,
     ) -> Result<VerifySignatureResponse, GrpcError> {
+        let _timer = grpc_timer("verify_signature");
         let request = tonic::Request::new(VerifySignatureRequest {
             message: message.into(),
             pub_key: pub_key.into(),
             signature: signature.into(),
         });
-        match self.near_api_client.verify_signature(request).await {
-            Ok(response) => {
-                let response = response.into_inner();
-                return Ok(response);
-            }
-            Err(status) => {
-                return Err(GrpcError::Call(status));
-            }
-        }
+        let result = self.client().await?.verify_signature(request).await;
+        self.outcome("verify_signature", result)
     }
 
-    pub async fn get_account_keys(
+    async fn get_account_keys(
         &mut self,
         account_id: &str,
     ) -> Result<GetAccountKeysResponse, GrpcError> {
+        let _timer = grpc_timer("get_account_keys");
         let request = tonic::Request::new(GetAccountKeysRequest {
             account_id: account_id.into(),
         });
-        match self.near_api_client.get_account_keys(request).await {
-            Ok(response) => {
-                let response = response.into_inner();
-                return Ok(response);
-            }
-            Err(status) => {
-                return Err(GrpcError::Call(status));
-            }
-        }
+        let result = self.client().await?.get_account_keys(request).await;
+        self.outcome("get_account_keys", result)
     }
 
-    pub async fn aes_encrypt_data(
+    async fn aes_encrypt_data(
         &mut self,
         secret: &str,
         data: &str,
     ) -> Result<AesEncryptDataResponse, GrpcError> {
+        let _timer = grpc_timer("aes_encrypt_data");
         let request = tonic::Request::new(AesEncryptDataRequest {
             secret: secret.into(),
             data: data.into(),
         });
-        match self.near_api_client.aes_encrypt_data(request).await {
-            Ok(response) => {
-                let response = response.into_inner();
-                return Ok(response);
-            }
-            Err(status) => {
-                return Err(GrpcError::Call(status));
-            }
-        }
+        let result = self.client().await?.aes_encrypt_data(request).await;
+        self.outcome("aes_encrypt_data", result)
     }
 
-    pub async fn aes_decrypt_data(
+    async fn aes_decrypt_data(
         &mut self,
         cypher: &str,
         secret: &str,
     ) -> Result<AesDecryptDataResponse, GrpcError> {
+        let _timer = grpc_timer("aes_decrypt_data");
         let request = tonic::Request::new(AesDecryptDataRequest {
             cypher: cypher.into(),
             secret: secret.into(),
         });
-        match self.near_api_client.aes_decrypt_data(request).await {
-            Ok(response) => {
-                let response = response.into_inner();
-                return Ok(response);
-            }
-            Err(status) => {
-                return Err(GrpcError::Call(status));
-            }
-        }
+        let result = self.client().await?.aes_decrypt_data(request).await;
+        self.outcome("aes_decrypt_data", result)
+    }
+
+    async fn get_tx_status(
+        &mut self,
+        tx_hash: &str,
+        account_id: &str,
+    ) -> Result<GetTxStatusResponse, GrpcError> {
+        let _timer = grpc_timer("get_tx_status");
+        let request = tonic::Request::new(GetTxStatusRequest {
+            tx_hash: tx_hash.into(),
+            account_id: account_id.into(),
+        });
+        let result = self.client().await?.get_tx_status(request).await;
+        self.outcome("get_tx_status", result)
+    }
+
+    async fn refund_payment(
+        &mut self,
+        receiver_wallet_id: &str,
+        amount: &str,
+        reference: &str,
+    ) -> Result<RefundPaymentResponse, GrpcError> {
+        let _timer = grpc_timer("refund_payment");
+        let request = tonic::Request::new(RefundPaymentRequest {
+            receiver_wallet_id: receiver_wallet_id.into(),
+            amount: amount.into(),
+            reference: reference.into(),
+        });
+        let result = self.client().await?.refund_payment(request).await;
+        self.outcome("refund_payment", result)
     }
 }
@@ -0,0 +1,111 @@
+//! HTTP caching of the public REST routes.
+//!
+//! The public responses carry a strong `ETag`, the sha256 of their body, a `Last-Modified` when
+//! the data has one and the `Cache-Control` of their route (see [`HttpCacheConfig`]). Clients
+//! revalidating with `If-None-Match` or `If-Modified-Since` are answered with a bodyless 304 when
+//! their copy is still current; `If-None-Match` wins when both are sent, as RFC 7232 requires.
+//!
+//! [`HttpCacheConfig`]: crate::config::HttpCacheConfig
+use crate::config::CachePolicy;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::convert::Infallible;
+use warp::{
+    http::{header, Response, StatusCode},
+    hyper::Body,
+    Filter,
+};
+
+/// The format of the http dates, always in GMT
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// The validators a client revalidates its cached copy with
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheValidators {
+    pub if_none_match: Option<String>,
+    pub if_modified_since: Option<String>,
+}
+
+pub fn with_cache_validators(
+) -> impl Filter<Extract = (CacheValidators,), Error = Infallible> + Clone {
+    warp::header::optional::<String>("if-none-match")
+        .and(warp::header::optional::<String>("if-modified-since"))
+        .map(|if_none_match, if_modified_since| CacheValidators {
+            if_none_match,
+            if_modified_since,
+        })
+        // unreadable validators are ignored, the full response is sent
+        .or(warp::any().map(CacheValidators::default))
+        .unify()
+}
+
+/// The strong entity tag of the body, quoted
+pub fn etag(body: &[u8]) -> String {
+    format!("\"{}\"", hex::encode(Sha256::digest(body)))
+}
+
+pub fn format_http_date(timestamp: &NaiveDateTime) -> String {
+    timestamp.format(HTTP_DATE_FORMAT).to_string()
+}
+
+pub fn parse_http_date(date: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(date.trim(), HTTP_DATE_FORMAT).ok()
+}
+
+impl CacheValidators {
+    /// Whether the client's copy, of the given entity tag and modification time, is still current
+    pub fn is_fresh(&self, etag: &str, last_modified: Option<&NaiveDateTime>) -> bool {
+        if let Some(if_none_match) = &self.if_none_match {
+            // weak comparison, the weak tags a proxy may have made of ours still match
+            return if_none_match
+                .split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag);
+        }
+        match (
+            self.if_modified_since.as_deref().and_then(parse_http_date),
+            last_modified,
+        ) {
+            // http dates have no sub-second precision
+            (Some(since), Some(last_modified)) => last_modified.timestamp() <= since.timestamp(),
+            _ => false,
+        }
+    }
+}
+
+/// Replies with the value as json under the route's cache policy, or with a 304 when the client's
+/// copy is still current
+pub fn cached_json_reply<T: Serialize>(
+    value: &T,
+    last_modified: Option<&NaiveDateTime>,
+    policy: &CachePolicy,
+    validators: &CacheValidators,
+) -> Response<Body> {
+    let body = match serde_json::to_vec(value) {
+        Ok(body) => body,
+        Err(e) => {
+            log::error!("Failed to serialize a cached reply: {}", e);
+            let mut response = Response::new(Body::empty());
+            *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            return response;
+        }
+    };
+    let etag = etag(&body);
+
+    let mut builder = Response::builder()
+        .header(header::ETAG, &etag)
+        .header(header::CACHE_CONTROL, policy.cache_control());
+    if let Some(last_modified) = last_modified {
+        builder = builder.header(header::LAST_MODIFIED, format_http_date(last_modified));
+    }
+    let response = if validators.is_fresh(&etag, last_modified) {
+        builder.status(StatusCode::NOT_MODIFIED).body(Body::empty())
+    } else {
+        builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+    };
+    response.expect("valid cached reply")
+}
@@ -1,57 +1,110 @@
+use super::caching::{cached_json_reply, CacheValidators};
+use super::export::{attendees_csv, can_export_attendees, ATTENDEES_CSV_CONTENT_TYPE};
+use super::health::{http_probe, probe, STATUS_DOWN, STATUS_UP};
+use super::import::{check_import_event, parse_import, ImportEvent, ImportFormat};
 use super::models::{
     BuyerCreateRecoveryCodeRequest, BuyerCreateRecoveryCodeResponse, BuyerRegisterPhoneRequest,
-    BuyerRegisterPhoneResponse, BuyerSignupRequest, BuyerSignupResponse, BuyerVerifyPhoneRequest,
-    BuyerVerifyPhoneResponse, BuyerVerifyRecoveryCodeRequest, BuyerVerifyRecoveryCodeResponse,
-    CheckUsernameRequest, CheckUsernameResponse, CreateLoginCodeRequest, CreateLoginCodeResponse,
+    BuyerRegisterPhoneResponse, BuyerResendPhoneCodeRequest, BuyerResendPhoneCodeResponse,
+    BuyerSignupRequest, BuyerSignupResponse, BuyerVerifyPhoneRequest, BuyerVerifyPhoneResponse,
+    BuyerVerifyRecoveryCodeRequest, BuyerVerifyRecoveryCodeResponse, CheckUsernameRequest,
+    CheckUsernameResponse, CreateLoginCodeRequest, CreateLoginCodeResponse,
     EventGetVerificationCodeResponse, EventTicketGetVerificationCodeRequest,
-    GetEventFromVerificationCodeRequest, GetEventFromVerificationCodeResponse, SigninRequest,
-    SigninResponse, SigninWithPasswordRequest, VerifyLoginCodeRequest, VerifyLoginCodeResponse,
+    EventTicketReservation, GetEventFromVerificationCodeRequest,
+    GetEventFromVerificationCodeResponse, HealthReadyResponse, ImportEventsResponse,
+    ImportEventsRow, PublicEventResponse, PusherAuthRequest, PusherAuthResponse,
+    ReservedTicketPrice, SigninChallengeRequest, SigninChallengeResponse, SigninRequest,
+    SigninResponse, SigninTotpRequiredResponse, SigninVerifyTotpRequest, SigninWithPasswordRequest,
+    UploadEventAssetsResponse, UploadedAsset, UserDataExportResponse, VerifyLoginCodeRequest,
+    VerifyLoginCodeResponse,
 };
+use super::ticket_pdf::{render_ticket_pdf, ticket_pdf_key, TicketPdf, TICKET_PDF_CONTENT_TYPE};
+use super::uploads::{spool_asset, ASSET_PART_NAME};
 use crate::{
-    auth::{create_jwt, Role, UserStatus},
+    audit::{self, AuditEntity},
+    auth::{authorize_bearer, create_jwt, Role, UserStatus},
     db::{
         models::{
-            DbBuyerRecoverySession, DbBuyerSignupSession, DbSession, DbTicketReservation, DbUser,
+            AssetFile, DbBuyerRecoverySession, DbBuyerSignupSession, DbEvent, DbEventView,
+            DbPromoCode, DbPromoCodeUsage, DbSession, DbSigninChallenge, DbSignupAttempt, DbTicket,
+            DbTicketReservation, DbTotpChallenge, DbUser,
         },
         sql::{
+            db_clear_login_attempts, db_consume_buyer_recovery_session,
+            db_consume_buyer_signup_session, db_consume_promo_code, db_consume_signin_challenge,
+            db_consume_totp_challenge, db_delete_promo_code_usages, db_delete_waitlist_entry,
             db_get_buyer_recovery_session_by_id, db_get_buyer_signup_session_by_id,
-            db_get_event_by_id, db_get_session_by_login_code, db_get_ticket_by_id,
+            db_get_event_attendees, db_get_event_by_id, db_get_event_by_name, db_get_event_by_slug,
+            db_get_event_collaborator, db_get_event_tags, db_get_events_by_creator,
+            db_get_login_attempts, db_get_organization_member, db_get_promo_code_by_code,
+            db_get_session_by_login_code, db_get_ticket_by_id, db_get_ticket_reservation_by_id,
             db_get_ticket_reservations_by_code, db_get_ticket_reservations_by_user_id,
-            db_get_tickets_by_event_id, db_get_user_by_email, db_get_user_by_id,
-            db_get_user_by_name, db_get_user_by_phone_number, db_get_user_by_username,
-            db_get_user_by_wallet_id, db_get_users_by_username, db_insert_buyer_recovery_session,
-            db_insert_buyer_signup_session, db_insert_session, db_insert_ticket_reservation,
-            db_insert_user, db_select_one, db_update_buyer_recovery_session,
-            db_update_buyer_signup_session, db_update_session_info, sql_timestamp,
+            db_get_tickets_by_event_id, db_get_tickets_by_event_ids, db_get_totp_challenge,
+            db_get_user_by_email, db_get_user_by_id, db_get_user_by_name,
+            db_get_user_by_phone_number, db_get_user_by_username, db_get_user_by_wallet_id,
+            db_get_user_totp, db_get_users_by_username,
+            db_increment_buyer_recovery_session_attempts,
+            db_increment_buyer_signup_session_attempts, db_increment_totp_challenge_attempts,
+            db_insert_buyer_recovery_session, db_insert_buyer_signup_session, db_insert_event_view,
+            db_insert_event_with_tickets, db_insert_promo_code_usage, db_insert_session,
+            db_insert_signin_challenge, db_insert_signup_attempt, db_insert_totp_challenge,
+            db_insert_user, db_is_ticket_seated, db_record_failed_login, db_release_promo_code,
+            db_resend_buyer_signup_session, db_reserve_seats, db_reserve_ticket, db_select_one,
+            db_undo_ticket_reservations, db_update_buyer_signup_session, db_update_session_info,
+            db_use_user_totp_step, insert_asset_file, sql_timestamp,
         },
     },
-    error::{AuthError, Error, EventError, RequestError, SessionError, TicketError, UserError},
-    gql::schema::Context as ResourcesContext,
-    grpc::near_api::{
-        AesEncryptDataResponse, CreateAccountResponse, GenerateImplicitAccountResponse, TxStatus,
+    error::{
+        AuthError, Error, EventError, PromoCodeError, RequestError, SessionError, TicketError,
+        UserError,
     },
+    gql::{
+        error::{GqlError, ValidationError},
+        guard::guard_event,
+        models::EventStatus,
+        schema::Context as ResourcesContext,
+    },
+    grpc::near_api::AesEncryptDataResponse,
+    i18n::supported_locale,
+    jobs::{
+        models::{JobPayload, PusherChannel, PusherEvent},
+        queue::enqueue,
+    },
+    near::NearAmount,
+    notifier::{Notification, Receipt},
+    phone::normalize_phone_number,
+    policy::{policy, EventAccess, Operation},
+    pricing::{claim_ticket_price, release_ticket_price, ClaimedPrice},
+    realtime::{broadcast, login_channel, parse_auth_channel, ChannelOwner, EventUpdate},
+    sanitize::{sanitize_field, TextField},
+    security::challenge::{challenge_message, gen_nonce},
     security::crypto::check_normal_account,
+    security::lockout::login_locked_until,
     security::password::{hash_password, verify_password},
+    security::password_policy::check_password,
+    security::pusher_auth::valid_socket_id,
+    security::ticket_qr::{ticket_payload, TicketPayload},
+    security::totp,
+    signups,
+    stock_alerts::alert_stock,
+    storage::readable_url,
+    usernames::check_username as check_username_allowed,
 };
-use bytes::buf::Buf;
+use bytes::buf::{Buf, BufMut};
 use chrono::Utc;
-use pusher_client::{channels::PusherChannels, events::PusherEvents};
+use futures::TryStreamExt;
 use reqwest::StatusCode;
+use std::collections::HashSet;
 use std::convert::From;
 use std::sync::Arc;
-use twilio_client::models::SmsMessage;
+use std::time::Duration;
 use uuid::Uuid;
 use validator::Validate;
-use warp::{reject, Rejection};
+use warp::{
+    multipart::{FormData, Part},
+    reject, Rejection,
+};
 use wasmium_random::WasmiumRandom;
 
-// TODO: put these in a config file or secret
-const MESSAGE: &'static str = "SECRET";
-const WALLET_CREATION_DEPOSIT_AMOUNT: &'static str = "0.2"; // near
-const NEAR_NETWORK_MODE: &'static str = "testnet";
-const VERIFICATION_SMS_TEXT: &'static str = "Your verification code is: ";
-const RECOVERY_SMS_TEXT: &'static str = "Your recovery code is: ";
-
 // healthcheck route
 pub async fn health(ctx: Arc<ResourcesContext>) -> Result<impl warp::Reply, Rejection> {
     db_select_one(&ctx.db_client)
@@ -60,19 +113,104 @@ pub async fn health(ctx: Arc<ResourcesContext>) -> Result<impl warp::Reply, Reje
     Ok(StatusCode::OK)
 }
 
+// liveness route: no dependency is touched, for load balancers
+pub async fn health_live() -> Result<impl warp::Reply, Rejection> {
+    Ok(StatusCode::OK)
+}
+
+// readiness route: probes every external dependency concurrently
+pub async fn health_ready(ctx: Arc<ResourcesContext>) -> Result<impl warp::Reply, Rejection> {
+    let timeout = Duration::from_millis(ctx.health.timeout_ms);
+    let http_client = reqwest::Client::new();
+
+    let healthcheck_account_id = ctx.near.network.account_id("healthcheck");
+    let s3_url = ctx.storage.asset_url(String::new());
+
+    let (postgres, near_api, pusher, twilio, s3) = tokio::join!(
+        probe("postgres", timeout, async {
+            db_select_one(&ctx.db_client)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }),
+        probe("near_api", timeout, async {
+            let mut lock = ctx.grpc_near_client.lock().await;
+            let res = lock
+                .check_available_account_id(&healthcheck_account_id)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string());
+            drop(lock);
+            res
+        }),
+        probe(
+            "pusher",
+            timeout,
+            http_probe(&http_client, &ctx.health.pusher_url)
+        ),
+        probe(
+            "twilio",
+            timeout,
+            http_probe(&http_client, &ctx.health.twilio_url)
+        ),
+        probe("s3", timeout, http_probe(&http_client, &s3_url)),
+    );
+
+    let grpc_status = ctx.grpc_near_client.lock().await.status();
+
+    let dependencies = vec![postgres, near_api, pusher, twilio, s3];
+    let is_ready = dependencies.iter().all(|d| d.status.eq(STATUS_UP));
+
+    let resp = HealthReadyResponse {
+        status: if is_ready { STATUS_UP } else { STATUS_DOWN }.to_string(),
+        dependencies,
+        grpc_status,
+    };
+    let status_code = if is_ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&resp),
+        status_code,
+    ))
+}
+
+// prometheus metrics route
+pub async fn metrics() -> Result<impl warp::Reply, Rejection> {
+    let (body, status_code) = match crate::metrics::gather() {
+        Ok(body) => (body, StatusCode::OK),
+        Err(e) => {
+            log::error!("Failed to encode metrics: {}", e);
+            (String::new(), StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::with_header(body, "content-type", "text/plain; version=0.0.4"),
+        status_code,
+    ))
+}
+
 // check if a given username exists
 pub async fn check_username(
     ctx: Arc<ResourcesContext>,
     buf: impl Buf,
 ) -> Result<impl warp::Reply, Rejection> {
     let des = &mut serde_json::Deserializer::from_reader(buf.reader());
-    let req_body: CheckUsernameRequest = serde_path_to_error::deserialize(des)
+    let mut req_body: CheckUsernameRequest = serde_path_to_error::deserialize(des)
         .map_err(|e| reject::custom(Error::Request(RequestError::JSONPathError(e.to_string()))))?;
+    req_body.username = sanitize_field(&ctx.sanitation, TextField::Username, &req_body.username)
+        .map_err(|e| reject::custom(Error::Sanitize(e)))?;
+
+    check_username_allowed(&ctx.usernames, &req_body.username)
+        .map_err(|e| reject::custom(Error::User(e)))?;
 
     let users = db_get_users_by_username(&ctx.db_client, &req_body.username)
         .await
         .map_err(Error::Postgres)?;
-    let near_account_id = format!("{}.{}", req_body.username, NEAR_NETWORK_MODE);
+    let near_account_id = ctx.near.network.account_id(&req_body.username);
 
     // check for available username
     let is_available = {
@@ -91,6 +229,45 @@ pub async fn check_username(
     }))
 }
 
+// seller signin challenge: a nonce to sign with the wallet key
+pub async fn signin_challenge(
+    role: String,
+    ctx: Arc<ResourcesContext>,
+    buf: impl Buf,
+) -> Result<impl warp::Reply, Rejection> {
+    // only for sellers ATM
+    let role = Role::try_from(role.as_str())
+        .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
+    if !policy(Operation::Signin).allows_role(&role) {
+        return Err(reject::custom(Error::User(UserError::OnlySeller)));
+    }
+
+    // check body errors
+    let des = &mut serde_json::Deserializer::from_reader(buf.reader());
+    let req_body: SigninChallengeRequest = serde_path_to_error::deserialize(des)
+        .map_err(|e| reject::custom(Error::Request(RequestError::JSONPathError(e.to_string()))))?;
+
+    req_body
+        .validate()
+        .map_err(|e| reject::custom(Error::Request(RequestError::ValidationError(e))))?;
+
+    // the wallet is not looked up, so that the challenge does not tell which wallets are users
+    let challenge = DbSigninChallenge::new(
+        req_body.wallet_id,
+        gen_nonce(),
+        sql_timestamp(Some(ctx.sessions.challenge_ttl_secs)),
+    );
+    db_insert_signin_challenge(&ctx.db_client, &challenge)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+
+    Ok(warp::reply::json(&SigninChallengeResponse {
+        message: challenge_message(&ctx.business.signin_message, &challenge.nonce),
+        nonce: challenge.nonce,
+        expires_at: challenge.expires_at,
+    }))
+}
+
 // seller signup/signin with wallet
 pub async fn signin(
     role: String,
@@ -100,14 +277,16 @@ pub async fn signin(
     // only for sellers ATM
     let role = Role::try_from(role.as_str())
         .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
-    if !role.eq(&Role::Seller) {
+    if !policy(Operation::Signin).allows_role(&role) {
         return Err(reject::custom(Error::User(UserError::OnlySeller)));
     }
 
     // check body errors
     let des = &mut serde_json::Deserializer::from_reader(buf.reader());
-    let req_body: SigninRequest = serde_path_to_error::deserialize(des)
+    let mut req_body: SigninRequest = serde_path_to_error::deserialize(des)
         .map_err(|e| reject::custom(Error::Request(RequestError::JSONPathError(e.to_string()))))?;
+    req_body.username = sanitize_field(&ctx.sanitation, TextField::Username, &req_body.username)
+        .map_err(|e| reject::custom(Error::Sanitize(e)))?;
 
     req_body
         .validate()
@@ -117,7 +296,12 @@ pub async fn signin(
     let email_lowercase = req_body.email.as_ref().map(|e| e.to_lowercase());
     let name = req_body.name.clone();
     let username = req_body.username.clone();
-    let phone_number = req_body.phone_number.clone();
+    let phone_number = req_body
+        .phone_number
+        .as_deref()
+        .map(normalize_phone_number)
+        .transpose()
+        .map_err(|e| reject::custom(Error::User(e)))?;
     let pwd = req_body.password.as_ref().map(|e| e.as_bytes());
     let pwd_hash = pwd
         .map(|pwd| hash_password(pwd))
@@ -132,6 +316,11 @@ pub async fn signin(
     match db_get_user_by_wallet_id(&ctx.db_client, &wallet_id).await {
         //user was found in DB
         Ok(db_user) => {
+            // deleted accounts cannot sign in again
+            if db_user.deleted_at.is_some() {
+                return Err(reject::custom(Error::User(UserError::AccountDeleted)));
+            }
+
             // check for public key
             let pub_key = req_body
                 .pub_key
@@ -142,43 +331,44 @@ pub async fn signin(
                 .signature
                 .ok_or(reject::custom(Error::User(UserError::MissingSignature)))?;
 
+            // check for the nonce of a challenge
+            let nonce = req_body
+                .nonce
+                .ok_or(reject::custom(Error::User(UserError::MissingNonce)))?;
+
             // check pub key on blockchain
-            let account_keys = {
-                let mut lock = ctx.grpc_near_client.lock().await;
-                let account_keys = lock
-                    .get_account_keys(&wallet_id)
-                    .await
-                    .map_err(|e| reject::custom(Error::Grpc(e)))?;
-                drop(lock);
-                account_keys
-            };
+            let account_keys = ctx
+                .chain
+                .account_keys(&wallet_id)
+                .await
+                .map_err(|e| reject::custom(Error::Chain(e)))?;
 
-            if account_keys
-                .data
-                .iter()
-                .find(|&key| key.public_key.eq(&pub_key))
-                .is_none()
-            {
+            if !account_keys.contains(&pub_key) {
                 return Err(reject::custom(Error::User(UserError::WrongWalletPubKey)));
             }
 
             // check user is a seller
-            if !db_user.user_type.eq(&Role::Seller) {
+            if !policy(Operation::Signin).allows_role(&db_user.user_type) {
                 return Err(reject::custom(Error::User(UserError::OnlySeller)));
             }
 
-            // validate signature
-            let b58_encode_message = bs58::encode(&MESSAGE).into_string();
-            let sig_verified = {
-                let mut lock = ctx.grpc_near_client.lock().await;
-                let sig_verified = lock
-                    .verify_signature(&b58_encode_message, &pub_key, &signature)
-                    .await
-                    .map_err(|e| reject::custom(Error::Grpc(e)))?
-                    .is_verified;
-                drop(lock);
-                sig_verified
-            };
+            // consume the challenge upfront, a nonce is good for a single attempt
+            db_consume_signin_challenge(&ctx.db_client, &wallet_id, &nonce)
+                .await
+                .map_err(|e| reject::custom(Error::Postgres(e)))?
+                .ok_or_else(|| {
+                    reject::custom(Error::Session(SessionError::NoChallengeForNonce(
+                        nonce.clone(),
+                    )))
+                })?;
+
+            // validate the signature of the challenge
+            let message = challenge_message(&ctx.business.signin_message, &nonce);
+            let sig_verified = ctx
+                .chain
+                .verify_signature(message.as_bytes(), &pub_key, &signature)
+                .await
+                .map_err(|e| reject::custom(Error::Chain(e)))?;
 
             // reject on bad signature
             if !sig_verified {
@@ -193,6 +383,15 @@ pub async fn signin(
         }
         //no user with wallet_id in the db
         Err(_err) => {
+            check_username_allowed(&ctx.usernames, &req_body.username)
+                .map_err(|e| reject::custom(Error::User(e)))?;
+            // only new accounts set their password, existing ones keep theirs
+            if let Some(password) = &req_body.password {
+                check_password(&ctx.password_policy, password)
+                    .await
+                    .map_err(|e| reject::custom(Error::Password(e)))?;
+            }
+
             // check username is available
             if let Ok(_db_user) = db_get_user_by_username(&ctx.db_client, &req_body.username).await
             {
@@ -245,22 +444,13 @@ pub async fn signin(
                 .ok_or(reject::custom(Error::User(UserError::MissingPubKey)))?;
 
             // check the submitted pub key is permissible acc. to blockchain
-            let account_keys = {
-                let mut lock = ctx.grpc_near_client.lock().await;
-                let account_keys = lock
-                    .get_account_keys(&wallet_id)
-                    .await
-                    .map_err(|e| reject::custom(Error::Grpc(e)))?;
-                drop(lock);
-                account_keys
-            };
+            let account_keys = ctx
+                .chain
+                .account_keys(&wallet_id)
+                .await
+                .map_err(|e| reject::custom(Error::Chain(e)))?;
 
-            if account_keys
-                .data
-                .iter()
-                .find(|&key| key.public_key.eq(&pub_key))
-                .is_none()
-            {
+            if !account_keys.contains(&pub_key) {
                 return Err(reject::custom(Error::User(UserError::WrongWalletPubKey)));
             }
 
@@ -275,6 +465,9 @@ pub async fn signin(
                 drop(lock);
                 wallet_balance
             };
+            let wallet_balance = wallet_balance
+                .parse::<NearAmount>()
+                .map_err(|e| reject::custom(Error::NearAmount(e)))?;
 
             // create a new db input user
             let new_db_user = DbUser::new(
@@ -287,14 +480,25 @@ pub async fn signin(
                 None,
                 role,
                 wallet_id.clone(),
-                wallet_balance,
-                UserStatus::PhoneVerified, // TODO: Check this for sellers ?
+                wallet_balance.to_string(),
+                UserStatus::Unverified, // until the seller verifies the phone
             );
 
             // insert user into db
             db_insert_user(&ctx.db_client, &new_db_user)
                 .await
                 .map_err(|e| reject::custom(Error::Postgres(e)))?;
+            audit::record(
+                &ctx.db_client,
+                Some(new_db_user.id),
+                "signup",
+                AuditEntity::User(new_db_user.id),
+                Some(serde_json::json!({
+                    "username": new_db_user.username,
+                    "userType": role.to_string(),
+                })),
+            )
+            .await;
 
             // return jwt token
             let jwt_token = create_jwt(&new_db_user.id.to_string(), &role)
@@ -307,14 +511,14 @@ pub async fn signin(
 // seller and admin signin with account password
 pub async fn signin_with_password(
     role: String,
+    ip_address: String,
     ctx: Arc<ResourcesContext>,
     buf: impl Buf,
 ) -> Result<impl warp::Reply, Rejection> {
     // only for sellers + admins ATM
     let role = Role::try_from(role.as_str())
         .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
-    let allowed_roles = vec![Role::Seller, Role::Admin];
-    if !allowed_roles.contains(&role) {
+    if !policy(Operation::SigninWithPassword).allows_role(&role) {
         return Err(reject::custom(Error::User(UserError::UnallowedUserRole(
             role.to_string(),
         ))));
@@ -335,12 +539,26 @@ pub async fn signin_with_password(
         .map_err(|_| reject::custom(Error::User(UserError::UserNotFound)))?;
 
     // check user is role is allowed
-    if !allowed_roles.contains(&db_user.user_type) {
+    if !policy(Operation::SigninWithPassword).allows_role(&db_user.user_type) {
         return Err(reject::custom(Error::User(UserError::UnallowedUserRole(
             db_user.user_type.to_string(),
         ))));
     }
 
+    // repeated failures make the address wait, and lock the account after too many
+    let now = sql_timestamp(None);
+    let login_attempts = db_get_login_attempts(&ctx.db_client, &db_user.id, &now)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    if let Some(locked_until) =
+        login_locked_until(&ctx.login_lockout, &login_attempts, &ip_address, &now)
+    {
+        let retry_after_secs = ((locked_until - now).num_milliseconds() + 999) / 1000;
+        return Err(reject::custom(Error::Auth(
+            AuthError::TooManyFailedSignins(retry_after_secs),
+        )));
+    }
+
     // get password salt from db for the user
     let db_passwd_hash: String = db_user
         .password
@@ -350,11 +568,73 @@ pub async fn signin_with_password(
         .map_err(|e| reject::custom(Error::Hash(e)))?;
 
     if !is_verified {
+        let login_attempt = db_record_failed_login(
+            &ctx.db_client,
+            &db_user.id,
+            &ip_address,
+            &now,
+            &sql_timestamp(Some(ctx.login_lockout.window_secs)),
+        )
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+        audit::record(
+            &ctx.db_client,
+            None,
+            "signin_failed",
+            AuditEntity::User(db_user.id),
+            Some(serde_json::json!({
+                "ipAddress": ip_address,
+                "failedAttempts": login_attempt.failed_attempts,
+            })),
+        )
+        .await;
+
+        // the attempt reaching the threshold locks the account
+        let failed_attempts = login_attempt.failed_attempts
+            + login_attempts
+                .iter()
+                .filter(|attempt| attempt.ip_address != ip_address)
+                .map(|attempt| attempt.failed_attempts)
+                .sum::<i32>();
+        if failed_attempts == ctx.login_lockout.lockout_attempts {
+            audit::record(
+                &ctx.db_client,
+                None,
+                "signin_locked",
+                AuditEntity::User(db_user.id),
+                Some(serde_json::json!({ "failedAttempts": failed_attempts })),
+            )
+            .await;
+        }
+
         return Err(reject::custom(Error::Auth(
             AuthError::WrongCredentialsError,
         )));
     }
 
+    db_clear_login_attempts(&ctx.db_client, &db_user.id, Some(&ip_address))
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+
+    // with two-factor authentication the jwt is issued for a code by the second step
+    let totp_enabled = db_get_user_totp(&ctx.db_client, &db_user.id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?
+        .map_or(false, |db_user_totp| db_user_totp.enabled_at.is_some());
+    if totp_enabled {
+        let challenge = DbTotpChallenge::new(
+            db_user.id,
+            sql_timestamp(Some(ctx.sessions.challenge_ttl_secs)),
+        );
+        db_insert_totp_challenge(&ctx.db_client, &challenge)
+            .await
+            .map_err(|e| reject::custom(Error::Postgres(e)))?;
+        return Ok(warp::reply::json(&SigninTotpRequiredResponse {
+            totp_token: challenge.id.to_string(),
+            expires_at: challenge.expires_at,
+        }));
+    }
+
     // generate a jwt
     let jwt_token =
         create_jwt(&db_user.id.to_string(), &role).map_err(|e| reject::custom(Error::Auth(e)))?;
@@ -362,6 +642,124 @@ pub async fn signin_with_password(
     return Ok(warp::reply::json(&SigninResponse { token: jwt_token }));
 }
 
+// seller and admin second sign-in step, a code of their authenticator app
+pub async fn signin_with_password_verify_totp(
+    role: String,
+    ctx: Arc<ResourcesContext>,
+    buf: impl Buf,
+) -> Result<impl warp::Reply, Rejection> {
+    let role = Role::try_from(role.as_str())
+        .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
+    if !policy(Operation::SigninVerifyTotp).allows_role(&role) {
+        return Err(reject::custom(Error::User(UserError::UnallowedUserRole(
+            role.to_string(),
+        ))));
+    }
+
+    // check body errors
+    let des = &mut serde_json::Deserializer::from_reader(buf.reader());
+    let req_body: SigninVerifyTotpRequest = serde_path_to_error::deserialize(des)
+        .map_err(|e| reject::custom(Error::Request(RequestError::JSONPathError(e.to_string()))))?;
+
+    req_body
+        .validate()
+        .map_err(|e| reject::custom(Error::Request(RequestError::ValidationError(e))))?;
+
+    let totp_config = ctx
+        .totp
+        .as_ref()
+        .ok_or_else(|| reject::custom(Error::Auth(AuthError::TotpUnavailable)))?;
+
+    // parse challenge id
+    let challenge_id = Uuid::parse_str(&req_body.totp_token)
+        .map_err(|_| Error::UnparsableUuid(req_body.totp_token.clone()))?;
+
+    // a challenge is single-use and only valid until it expires
+    let challenge = db_get_totp_challenge(&ctx.db_client, &challenge_id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?
+        .ok_or_else(|| {
+            reject::custom(Error::Session(SessionError::NoSessionForToken(
+                req_body.totp_token.clone(),
+            )))
+        })?;
+    if challenge.is_consumed {
+        return Err(reject::custom(Error::Session(SessionError::UsedSession(
+            req_body.totp_token.clone(),
+        ))));
+    }
+    if challenge.expires_at < sql_timestamp(None) {
+        return Err(reject::custom(Error::Session(
+            SessionError::ExpiredSession(req_body.totp_token.clone()),
+        )));
+    }
+
+    // count the attempt before checking the code, so concurrent guesses are limited too
+    let attempts = db_increment_totp_challenge_attempts(&ctx.db_client, &challenge_id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    if attempts > ctx.sessions.max_attempts {
+        return Err(reject::custom(Error::Session(
+            SessionError::TooManyAttempts(req_body.totp_token.clone()),
+        )));
+    }
+
+    let db_user = db_get_user_by_id(&ctx.db_client, &challenge.user_id)
+        .await
+        .map_err(|_| reject::custom(Error::User(UserError::UserNotFound)))?;
+    if !policy(Operation::SigninVerifyTotp).allows_role(&db_user.user_type) {
+        return Err(reject::custom(Error::User(UserError::UnallowedUserRole(
+            db_user.user_type.to_string(),
+        ))));
+    }
+
+    // check the code, a code is accepted once only
+    let db_user_totp = db_get_user_totp(&ctx.db_client, &db_user.id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?
+        .filter(|db_user_totp| db_user_totp.enabled_at.is_some())
+        .ok_or_else(|| reject::custom(Error::Auth(AuthError::WrongCredentialsError)))?;
+    let secret = totp::decrypt_secret(
+        &totp_config.encryption_secret,
+        &db_user_totp.encrypted_secret,
+    )
+    .ok_or_else(|| reject::custom(Error::Auth(AuthError::TotpUnavailable)))?;
+    let step = totp::verify(
+        &secret,
+        &req_body.code,
+        sql_timestamp(None).timestamp(),
+        db_user_totp.last_used_step,
+    )
+    .ok_or_else(|| reject::custom(Error::Auth(AuthError::WrongCredentialsError)))?;
+    db_use_user_totp_step(&ctx.db_client, &db_user.id, step)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?
+        .ok_or_else(|| reject::custom(Error::Auth(AuthError::WrongCredentialsError)))?;
+
+    let challenge = db_consume_totp_challenge(&ctx.db_client, &challenge_id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?
+        .ok_or_else(|| {
+            reject::custom(Error::Session(SessionError::UsedSession(
+                req_body.totp_token.clone(),
+            )))
+        })?;
+    audit::record(
+        &ctx.db_client,
+        Some(db_user.id),
+        "signin_verify_totp",
+        AuditEntity::Session(challenge.id),
+        None,
+    )
+    .await;
+
+    // generate a jwt
+    let jwt_token =
+        create_jwt(&db_user.id.to_string(), &role).map_err(|e| reject::custom(Error::Auth(e)))?;
+
+    Ok(warp::reply::json(&SigninResponse { token: jwt_token }))
+}
+
 // buyer create recovery code
 pub async fn buyer_create_recovery_code(
     role: String,
@@ -371,7 +769,7 @@ pub async fn buyer_create_recovery_code(
     // only for buyers
     let role = Role::try_from(role.as_str())
         .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
-    if !role.eq(&Role::Buyer) {
+    if !policy(Operation::BuyerCreateRecoveryCode).allows_role(&role) {
         return Err(reject::custom(Error::User(UserError::OnlyBuyer)));
     }
     // check body errors
@@ -383,8 +781,12 @@ pub async fn buyer_create_recovery_code(
         .validate()
         .map_err(|e| reject::custom(Error::Request(RequestError::ValidationError(e))))?;
 
+    // phone numbers are stored in E.164
+    let phone_number = normalize_phone_number(&req_body.phone_number)
+        .map_err(|e| reject::custom(Error::User(e)))?;
+
     // find user in the db
-    let user_db = db_get_user_by_phone_number(&ctx.db_client, &req_body.phone_number)
+    let user_db = db_get_user_by_phone_number(&ctx.db_client, &phone_number)
         .await
         .map_err(|_e| reject::custom(Error::User(UserError::UserNotFound)))?;
 
@@ -395,34 +797,49 @@ pub async fn buyer_create_recovery_code(
         .map(char::from)
         .collect();
 
-    // create the sms
-    let sms = SmsMessage {
-        sender: None, // use the messaging service
-        receiver: req_body.phone_number.clone(),
-        body: Some(format!("{}{}", RECOVERY_SMS_TEXT, recovery_code.clone())),
-    };
-
-    // send recovery code via sms to buyer
-    let _ = ctx
-        .twilio_client
-        .send_sms(&sms)
-        .await
-        .map_err(|e| reject::custom(Error::Twilio(e)))?;
+    // send recovery code to buyer
+    let _ = enqueue(
+        &ctx.db_client,
+        JobPayload::Notify {
+            receiver: phone_number.clone(),
+            notification: Notification::Recovery {
+                code: recovery_code.clone(),
+            },
+            // the requested locale, or the one the buyer picked at signup
+            locale: req_body
+                .locale
+                .as_deref()
+                .and_then(|locale| supported_locale(&ctx.business, locale))
+                .or(user_db.locale),
+        },
+        ctx.jobs.max_attempts,
+    )
+    .await
+    .map_err(|e| reject::custom(Error::Postgres(e)))?;
 
     // create a new db buyer recovery session
     let new_db_buyer_recovery_session = DbBuyerRecoverySession::new(
         Uuid::new_v4(),
         sql_timestamp(None),
-        recovery_code,
-        req_body.phone_number,
+        ctx.codes.hash(&recovery_code),
+        phone_number,
         false,
         user_db.id,
+        sql_timestamp(Some(ctx.sessions.code_ttl_secs)),
     );
 
     // insert buyer recovery session into db
     db_insert_buyer_recovery_session(&ctx.db_client, &new_db_buyer_recovery_session)
         .await
         .map_err(|err| reject::custom(Error::Postgres(err)))?;
+    audit::record(
+        &ctx.db_client,
+        Some(user_db.id),
+        "create_recovery_code",
+        AuditEntity::Session(new_db_buyer_recovery_session.id),
+        None,
+    )
+    .await;
 
     // return the response
     let resp = BuyerCreateRecoveryCodeResponse::from(new_db_buyer_recovery_session);
@@ -438,7 +855,7 @@ pub async fn buyer_verify_recovery_code(
     // only for buyers
     let role = Role::try_from(role.as_str())
         .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
-    if !role.eq(&Role::Buyer) {
+    if !policy(Operation::BuyerVerifyRecoveryCode).allows_role(&role) {
         return Err(reject::custom(Error::User(UserError::OnlyBuyer)));
     }
 
@@ -456,7 +873,7 @@ pub async fn buyer_verify_recovery_code(
         .map_err(|_| Error::UnparsableUuid(req_body.session_id.clone()))?;
 
     // get session by id
-    let mut db_buyer_recovery_session =
+    let db_buyer_recovery_session =
         db_get_buyer_recovery_session_by_id(&ctx.db_client, &session_id)
             .await
             .map_err(|_err| {
@@ -465,11 +882,33 @@ pub async fn buyer_verify_recovery_code(
                 )))
             })?;
 
+    // a recovery code is single-use and only valid until it expires
+    if db_buyer_recovery_session.is_consumed {
+        return Err(reject::custom(Error::Session(SessionError::UsedSession(
+            req_body.session_id.clone(),
+        ))));
+    }
+    if db_buyer_recovery_session.expires_at < sql_timestamp(None) {
+        return Err(reject::custom(Error::Session(
+            SessionError::ExpiredSession(req_body.session_id.clone()),
+        )));
+    }
+
+    // count the attempt before checking the code, so concurrent guesses are limited too
+    let attempts = db_increment_buyer_recovery_session_attempts(&ctx.db_client, &session_id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    if attempts > ctx.sessions.max_attempts {
+        return Err(reject::custom(Error::Session(
+            SessionError::TooManyAttempts(req_body.session_id.clone()),
+        )));
+    }
+
     // check the recovery code
-    if !db_buyer_recovery_session
-        .recovery_code
-        .eq(&req_body.recovery_code)
-    {
+    if !ctx.codes.verify(
+        &db_buyer_recovery_session.recovery_code_hash,
+        &req_body.recovery_code,
+    ) {
         return Err(reject::custom(Error::Session(
             SessionError::SessionRecoveryCodeMismatch(req_body.recovery_code.clone()),
         )));
@@ -480,13 +919,23 @@ pub async fn buyer_verify_recovery_code(
         .await
         .map_err(|_e| reject::custom(Error::User(UserError::UserNotFound)))?;
 
-    // set the session to recovered
-    db_buyer_recovery_session.is_recovered = true;
-
-    // update db
-    db_update_buyer_recovery_session(&ctx.db_client, &db_buyer_recovery_session)
+    // set the session to recovered and consume it
+    let db_buyer_recovery_session = db_consume_buyer_recovery_session(&ctx.db_client, &session_id)
         .await
-        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+        .map_err(|e| reject::custom(Error::Postgres(e)))?
+        .ok_or_else(|| {
+            reject::custom(Error::Session(SessionError::UsedSession(
+                req_body.session_id.clone(),
+            )))
+        })?;
+    audit::record(
+        &ctx.db_client,
+        Some(db_user.id),
+        "verify_recovery_code",
+        AuditEntity::Session(db_buyer_recovery_session.id),
+        None,
+    )
+    .await;
 
     // create a new jwt
     let jwt_token =
@@ -507,7 +956,7 @@ pub async fn buyer_register_phone(
     // only for buyers
     let role = Role::try_from(role.as_str())
         .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
-    if !role.eq(&Role::Buyer) {
+    if !policy(Operation::BuyerRegisterPhone).allows_role(&role) {
         return Err(reject::custom(Error::User(UserError::OnlyBuyer)));
     }
 
@@ -520,6 +969,16 @@ pub async fn buyer_register_phone(
         .validate()
         .map_err(|e| reject::custom(Error::Request(RequestError::ValidationError(e))))?;
 
+    // phone numbers are stored in E.164
+    let phone_number = normalize_phone_number(&req_body.phone_number)
+        .map_err(|e| reject::custom(Error::User(e)))?;
+
+    // unsupported locales fall back to the configured language
+    let locale = req_body
+        .locale
+        .as_deref()
+        .and_then(|locale| supported_locale(&ctx.business, locale));
+
     // generate a new verification code
     let verification_code = WasmiumRandom::secure_numeric12()
         .into_iter()
@@ -527,37 +986,46 @@ pub async fn buyer_register_phone(
         .map(|item| item.to_string())
         .collect::<String>();
 
-    // create the sms
-    let sms = SmsMessage {
-        sender: None, // use the messaging service
-        receiver: req_body.phone_number.clone(),
-        body: Some(format!(
-            "{}{}",
-            VERIFICATION_SMS_TEXT,
-            verification_code.clone()
-        )),
-    };
-
-    // send verification code via sms to buyer
-    let _ = ctx
-        .twilio_client
-        .send_sms(&sms)
-        .await
-        .map_err(|e| reject::custom(Error::Twilio(e)))?;
+    // send verification code to buyer
+    let _ = enqueue(
+        &ctx.db_client,
+        JobPayload::Notify {
+            receiver: phone_number.clone(),
+            notification: Notification::Verification {
+                code: verification_code.clone(),
+            },
+            locale: locale.clone(),
+        },
+        ctx.jobs.max_attempts,
+    )
+    .await
+    .map_err(|e| reject::custom(Error::Postgres(e)))?;
 
     // create a new db buyer signup session
-    let new_db_buyer_signup_session = DbBuyerSignupSession::new(
-        Uuid::new_v4(),
-        sql_timestamp(None),
-        verification_code,
-        req_body.phone_number,
-        false,
-    );
+    let new_db_buyer_signup_session = DbBuyerSignupSession {
+        locale,
+        ..DbBuyerSignupSession::new(
+            Uuid::new_v4(),
+            sql_timestamp(None),
+            ctx.codes.hash(&verification_code),
+            phone_number,
+            false,
+            sql_timestamp(Some(ctx.sessions.code_ttl_secs)),
+        )
+    };
 
     // insert buyer signup session into db
     db_insert_buyer_signup_session(&ctx.db_client, &new_db_buyer_signup_session)
         .await
         .map_err(|err| reject::custom(Error::Postgres(err)))?;
+    audit::record(
+        &ctx.db_client,
+        None,
+        "register_phone",
+        AuditEntity::Session(new_db_buyer_signup_session.id),
+        None,
+    )
+    .await;
 
     // return the response
     let resp = BuyerRegisterPhoneResponse::from(new_db_buyer_signup_session);
@@ -573,7 +1041,7 @@ pub async fn buyer_verify_phone(
     // only for buyers
     let role = Role::try_from(role.as_str())
         .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
-    if !role.eq(&Role::Buyer) {
+    if !policy(Operation::BuyerVerifyPhone).allows_role(&role) {
         return Err(reject::custom(Error::User(UserError::OnlyBuyer)));
     }
 
@@ -600,11 +1068,38 @@ pub async fn buyer_verify_phone(
                 )))
             })?;
 
+    // a verification code is only valid until it expires and its session is used, once
+    if db_buyer_signup_session.is_consumed {
+        return Err(reject::custom(Error::Session(SessionError::UsedSession(
+            req_body.session_id.clone(),
+        ))));
+    }
+    if db_buyer_signup_session.is_verified {
+        return Err(reject::custom(Error::Session(
+            SessionError::VerifiedSession(req_body.session_id.clone()),
+        )));
+    }
+    if db_buyer_signup_session.expires_at < sql_timestamp(None) {
+        return Err(reject::custom(Error::Session(
+            SessionError::ExpiredSession(req_body.session_id.clone()),
+        )));
+    }
+
+    // count the attempt before checking the code, so concurrent guesses are limited too
+    let attempts = db_increment_buyer_signup_session_attempts(&ctx.db_client, &session_id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    if attempts > ctx.sessions.max_attempts {
+        return Err(reject::custom(Error::Session(
+            SessionError::TooManyAttempts(req_body.session_id.clone()),
+        )));
+    }
+
     // check the verification code
-    if !db_buyer_signup_session
-        .verification_code
-        .eq(&req_body.verification_code)
-    {
+    if !ctx.codes.verify(
+        &db_buyer_signup_session.verification_code_hash,
+        &req_body.verification_code,
+    ) {
         return Err(reject::custom(Error::Session(
             SessionError::SessionVerificationCodeMismatch(req_body.verification_code.clone()),
         )));
@@ -617,14 +1112,22 @@ pub async fn buyer_verify_phone(
     db_update_buyer_signup_session(&ctx.db_client, &db_buyer_signup_session)
         .await
         .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    audit::record(
+        &ctx.db_client,
+        None,
+        "verify_phone",
+        AuditEntity::Session(db_buyer_signup_session.id),
+        None,
+    )
+    .await;
 
     // return the response
     let resp = BuyerVerifyPhoneResponse::from(db_buyer_signup_session);
     Ok(warp::reply::json(&resp))
 }
 
-// buyer signup
-pub async fn buyer_signup(
+// buyer resend phone verification code
+pub async fn buyer_resend_phone_code(
     role: String,
     ctx: Arc<ResourcesContext>,
     buf: impl Buf,
@@ -632,29 +1135,19 @@ pub async fn buyer_signup(
     // only for buyers
     let role = Role::try_from(role.as_str())
         .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
-    if !role.eq(&Role::Buyer) {
+    if !policy(Operation::BuyerResendPhoneCode).allows_role(&role) {
         return Err(reject::custom(Error::User(UserError::OnlyBuyer)));
     }
 
     // check body errors
     let des = &mut serde_json::Deserializer::from_reader(buf.reader());
-    let req_body: BuyerSignupRequest = serde_path_to_error::deserialize(des)
+    let req_body: BuyerResendPhoneCodeRequest = serde_path_to_error::deserialize(des)
         .map_err(|e| reject::custom(Error::Request(RequestError::JSONPathError(e.to_string()))))?;
 
     req_body
         .validate()
         .map_err(|e| reject::custom(Error::Request(RequestError::ValidationError(e))))?;
 
-    // check for unique username
-    if db_get_users_by_username(&ctx.db_client, &req_body.username)
-        .await
-        .map_err(Error::Postgres)?
-        .len()
-        > 0
-    {
-        return Err(reject::custom(Error::User(UserError::UnavailableUsername)));
-    }
-
     // parse session id
     let session_id = Uuid::parse_str(&req_body.session_id)
         .map_err(|_| Error::UnparsableUuid(req_body.session_id.clone()))?;
@@ -668,116 +1161,232 @@ pub async fn buyer_signup(
             )))
         })?;
 
-    // check the session is verified
-    if !db_buyer_signup_session.is_verified {
-        return Err(reject::custom(Error::User(UserError::UnverifiedUser)));
+    // only a pending session gets a new code
+    if db_buyer_signup_session.is_consumed {
+        return Err(reject::custom(Error::Session(SessionError::UsedSession(
+            req_body.session_id.clone(),
+        ))));
+    }
+    if db_buyer_signup_session.is_verified {
+        return Err(reject::custom(Error::Session(
+            SessionError::VerifiedSession(req_body.session_id.clone()),
+        )));
+    }
+    if db_buyer_signup_session.resend_count >= ctx.sessions.max_resends {
+        return Err(reject::custom(Error::Session(
+            SessionError::TooManyResends(req_body.session_id.clone()),
+        )));
     }
 
-    // format input data
-    let email = req_body.email.as_ref().map(|e| e.to_lowercase());
-    let pwd = req_body.password.as_ref().map(|e| e.as_bytes());
-    let pwd_hash = pwd
-        .map(|pwd| hash_password(pwd))
-        .transpose()
-        .map_err(Error::Hash)?;
+    // generate a new verification code
+    let verification_code = WasmiumRandom::secure_numeric12()
+        .into_iter()
+        .take(6)
+        .map(|item| item.to_string())
+        .collect::<String>();
 
-    // create a near implicit account
-    // NOTE: the account id must have been already checked at this point
-    let generated_implicit_account = {
-        let mut lock = ctx.grpc_near_client.lock().await;
-        let implicit_account: GenerateImplicitAccountResponse = lock
-            .generate_implicit_account()
-            .await
-            .map_err(|e| reject::custom(Error::Grpc(e)))?;
-        drop(lock);
-        implicit_account
+    // the cooldown and resend count are checked again on update, so concurrent resends send one sms
+    let db_buyer_signup_session = db_resend_buyer_signup_session(
+        &ctx.db_client,
+        &session_id,
+        &ctx.codes.hash(&verification_code),
+        &sql_timestamp(Some(ctx.sessions.code_ttl_secs)),
+        &sql_timestamp(Some(-ctx.sessions.resend_cooldown_secs)),
+        ctx.sessions.max_resends,
+    )
+    .await
+    .map_err(|e| reject::custom(Error::Postgres(e)))?
+    .ok_or_else(|| {
+        reject::custom(Error::Session(SessionError::ResendCooldown(
+            req_body.session_id.clone(),
+        )))
+    })?;
+
+    // send verification code to buyer
+    let _ = enqueue(
+        &ctx.db_client,
+        JobPayload::Notify {
+            receiver: db_buyer_signup_session.phone_number.clone(),
+            notification: Notification::Verification {
+                code: verification_code,
+            },
+            locale: db_buyer_signup_session.locale.clone(),
+        },
+        ctx.jobs.max_attempts,
+    )
+    .await
+    .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    audit::record(
+        &ctx.db_client,
+        None,
+        "resend_phone_code",
+        AuditEntity::Session(db_buyer_signup_session.id),
+        None,
+    )
+    .await;
+
+    // return the response
+    let resp = BuyerResendPhoneCodeResponse {
+        session_id: db_buyer_signup_session.id.to_string(),
+        resends_left: ctx.sessions.max_resends - db_buyer_signup_session.resend_count,
     };
+    Ok(warp::reply::json(&resp))
+}
 
-    // allocate an account id
-    let user_account_id = format!("{}.{}", req_body.username, NEAR_NETWORK_MODE);
+// buyer signup
+pub async fn buyer_signup(
+    role: String,
+    ctx: Arc<ResourcesContext>,
+    buf: impl Buf,
+) -> Result<impl warp::Reply, Rejection> {
+    // only for buyers
+    let role = Role::try_from(role.as_str())
+        .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
+    if !policy(Operation::BuyerSignup).allows_role(&role) {
+        return Err(reject::custom(Error::User(UserError::OnlyBuyer)));
+    }
 
-    // create account and also send some funds to it (atomically)
-    let create_account_status = {
-        let mut lock = ctx.grpc_near_client.lock().await;
-        let create_account_status: CreateAccountResponse = lock
-            .create_account(
-                &user_account_id,
-                &generated_implicit_account.public_key,
-                WALLET_CREATION_DEPOSIT_AMOUNT,
-            )
+    // check body errors
+    let des = &mut serde_json::Deserializer::from_reader(buf.reader());
+    let mut req_body: BuyerSignupRequest = serde_path_to_error::deserialize(des)
+        .map_err(|e| reject::custom(Error::Request(RequestError::JSONPathError(e.to_string()))))?;
+    req_body.username = sanitize_field(&ctx.sanitation, TextField::Username, &req_body.username)
+        .map_err(|e| reject::custom(Error::Sanitize(e)))?;
+
+    req_body
+        .validate()
+        .map_err(|e| reject::custom(Error::Request(RequestError::ValidationError(e))))?;
+
+    check_username_allowed(&ctx.usernames, &req_body.username)
+        .map_err(|e| reject::custom(Error::User(e)))?;
+    if let Some(password) = &req_body.password {
+        check_password(&ctx.password_policy, password)
             .await
-            .map_err(|e| reject::custom(Error::Grpc(e)))?;
-        drop(lock);
-        create_account_status
-    };
+            .map_err(|e| reject::custom(Error::Password(e)))?;
+    }
 
-    if TxStatus::from_i32(create_account_status.status) == Some(TxStatus::Failed) {
-        return Err(reject::custom(Error::User(UserError::WalletCreationFailed)));
+    // check for unique username
+    if db_get_users_by_username(&ctx.db_client, &req_body.username)
+        .await
+        .map_err(Error::Postgres)?
+        .len()
+        > 0
+    {
+        return Err(reject::custom(Error::User(UserError::UnavailableUsername)));
     }
-    log::info!(
-        "Created wallet with account_id {}. Tx hash: {}",
-        user_account_id,
-        create_account_status.tx_hash
-    );
 
-    // send account created event over pusher TODO: spawn in a thread, error handling, retrial ???
-    let _ = ctx
-        .pusher_client
-        .send(
-            PusherChannels::Account,
-            PusherEvents::AccountCreated,
-            &user_account_id,
-        )
+    // parse session id
+    let session_id = Uuid::parse_str(&req_body.session_id)
+        .map_err(|_| Error::UnparsableUuid(req_body.session_id.clone()))?;
+
+    // get session by id
+    let db_buyer_signup_session = db_get_buyer_signup_session_by_id(&ctx.db_client, &session_id)
         .await
-        .map_err(|e| reject::custom(Error::Pusher(e)))?;
-
-    let _ = ctx
-        .pusher_client
-        .send(
-            PusherChannels::Account,
-            PusherEvents::AccountFunded,
-            &user_account_id,
-        )
+        .map_err(|_err| {
+            reject::custom(Error::Session(SessionError::SessionNotFoundForUuid(
+                req_body.session_id.clone(),
+            )))
+        })?;
+
+    // check the session is verified
+    if !db_buyer_signup_session.is_verified {
+        return Err(reject::custom(Error::User(UserError::UnverifiedUser)));
+    }
+    if db_buyer_signup_session.expires_at < sql_timestamp(None) {
+        return Err(reject::custom(Error::Session(
+            SessionError::ExpiredSession(req_body.session_id.clone()),
+        )));
+    }
+
+    // consume the session before creating the wallet, a verified phone signs up only once
+    let db_buyer_signup_session = db_consume_buyer_signup_session(&ctx.db_client, &session_id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?
+        .ok_or_else(|| {
+            reject::custom(Error::Session(SessionError::UsedSession(
+                req_body.session_id.clone(),
+            )))
+        })?;
+
+    // format input data
+    let email = req_body.email.as_ref().map(|e| e.to_lowercase());
+    let pwd = req_body.password.as_ref().map(|e| e.as_bytes());
+    let pwd_hash = pwd
+        .map(|pwd| hash_password(pwd))
+        .transpose()
+        .map_err(Error::Hash)?;
+
+    // generate the keys of the wallet
+    // NOTE: the account id must have been already checked at this point
+    let generated_key_pair = ctx
+        .chain
+        .generate_key_pair()
         .await
-        .map_err(|e| reject::custom(Error::Pusher(e)))?;
+        .map_err(|e| reject::custom(Error::Chain(e)))?;
+
+    // allocate an account id
+    let user_account_id = ctx.near.network.account_id(&req_body.username);
 
     // encrypt the generated wallet secret key
     let encrypted_data = {
         let mut lock = ctx.grpc_near_client.lock().await;
         let encrypted_data: AesEncryptDataResponse = lock
-            .aes_encrypt_data(&req_body.secret, &generated_implicit_account.secret_key)
+            .aes_encrypt_data(&req_body.secret, &generated_key_pair.secret_key)
             .await
             .map_err(|e| reject::custom(Error::Grpc(e)))?;
         drop(lock);
         encrypted_data
     };
 
-    // create a new db input user (verified + store the encrypted secret key to db)
-    let new_db_user = DbUser::new(
-        Uuid::new_v4(),
-        req_body.name,
-        req_body.username.clone(),
-        Some(db_buyer_signup_session.phone_number),
-        email,
-        pwd_hash,
-        Some(encrypted_data.cypher),
-        role,
-        user_account_id,
-        "200000000000000000000000".to_string(), // TODO: fix this with proper BigNum
-        UserStatus::PhoneVerified,
-    );
+    // the new wallet holds the creation deposit until the balance sync picks it up
+    let wallet_balance = NearAmount::from_near(&ctx.near.wallet_deposit)
+        .map_err(|e| reject::custom(Error::NearAmount(e)))?;
 
-    // insert user into db
-    db_insert_user(&ctx.db_client, &new_db_user)
+    // the user to create (verified + store the encrypted secret key to db)
+    let new_db_user = DbUser {
+        // the locale picked when registering the phone
+        locale: db_buyer_signup_session.locale,
+        ..DbUser::new(
+            Uuid::new_v4(),
+            req_body.name,
+            req_body.username.clone(),
+            Some(db_buyer_signup_session.phone_number),
+            email,
+            pwd_hash,
+            Some(encrypted_data.cypher),
+            role,
+            user_account_id,
+            wallet_balance.to_string(),
+            UserStatus::PhoneVerified,
+        )
+    };
+
+    // record the signup before creating the wallet, so that it is retried if it fails halfway
+    let db_signup_attempt = DbSignupAttempt::new(
+        db_buyer_signup_session.id,
+        &new_db_user,
+        &generated_key_pair.public_key,
+        sql_timestamp(Some(ctx.signups.lease_secs)),
+    );
+    db_insert_signup_attempt(&ctx.db_client, &db_signup_attempt)
         .await
         .map_err(|e| reject::custom(Error::Postgres(e)))?;
 
+    // create the wallet and the user
+    let new_db_user = match signups::complete(&ctx, &db_signup_attempt).await {
+        Ok(db_user) => db_user,
+        Err(e) => {
+            signups::fail(&ctx, &db_signup_attempt, &e).await;
+            return Err(reject::custom(e));
+        }
+    };
+
     // return the newly created user
     let jwt_token = create_jwt(&new_db_user.id.to_string(), &role)
         .map_err(|e| reject::custom(Error::Auth(e)))?;
     let mut resp = BuyerSignupResponse::from(new_db_user);
     resp.jwt = Some(jwt_token);
-    resp.wallet_pub_key = Some(generated_implicit_account.public_key); // NOTE: we do not store in db the pub key!
+    resp.wallet_pub_key = Some(generated_key_pair.public_key); // NOTE: only the signup attempt keeps the pub key
     Ok(warp::reply::json(&resp))
 }
 
@@ -790,7 +1399,7 @@ pub async fn create_login_code(
     // only for buyers
     let role = Role::try_from(role.as_str())
         .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
-    if !role.eq(&Role::Buyer) {
+    if !policy(Operation::CreateLoginCode).allows_role(&role) {
         return Err(reject::custom(Error::User(UserError::OnlyBuyer)));
     }
 
@@ -819,6 +1428,14 @@ pub async fn create_login_code(
     db_insert_session(&ctx.db_client, &new_db_session)
         .await
         .map_err(|err| reject::custom(Error::Postgres(err)))?;
+    audit::record(
+        &ctx.db_client,
+        None,
+        "create_login_code",
+        AuditEntity::Session(new_db_session.id),
+        None,
+    )
+    .await;
 
     let create_login_code_response = CreateLoginCodeResponse {
         code: login_code,
@@ -836,7 +1453,7 @@ pub async fn verify_login_code(
     // only for buyers
     let role = Role::try_from(role.as_str())
         .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
-    if !role.eq(&Role::Buyer) {
+    if !policy(Operation::VerifyLoginCode).allows_role(&role) {
         return Err(reject::custom(Error::User(UserError::OnlyBuyer)));
     }
 
@@ -874,21 +1491,15 @@ pub async fn verify_login_code(
     };
 
     // validate signature
-    let b58_encoded_login_code = bs58::encode(&db_session.login_code).into_string();
-    let sig_verified = {
-        let mut lock = ctx.grpc_near_client.lock().await;
-        let sig_verified = lock
-            .verify_signature(
-                &b58_encoded_login_code,
-                &req_body.pub_key,
-                &req_body.signature,
-            )
-            .await
-            .map_err(|e| reject::custom(Error::Grpc(e)))?
-            .is_verified;
-        drop(lock);
-        sig_verified
-    };
+    let sig_verified = ctx
+        .chain
+        .verify_signature(
+            db_session.login_code.as_bytes(),
+            &req_body.pub_key,
+            &req_body.signature,
+        )
+        .await
+        .map_err(|e| reject::custom(Error::Chain(e)))?;
 
     // reject on bad signature
     if !sig_verified {
@@ -899,28 +1510,24 @@ pub async fn verify_login_code(
     let db_user = match db_get_user_by_wallet_id(&ctx.db_client, &req_body.wallet_id).await {
         //user was found in DB
         Ok(db_user) => {
+            // deleted accounts cannot sign in again
+            if db_user.deleted_at.is_some() {
+                return Err(reject::custom(Error::User(UserError::AccountDeleted)));
+            }
+
             // check pub key in db
-            let account_keys = {
-                let mut lock = ctx.grpc_near_client.lock().await;
-                let account_keys = lock
-                    .get_account_keys(&db_user.wallet_id)
-                    .await
-                    .map_err(|e| reject::custom(Error::Grpc(e)))?;
-                drop(lock);
-                account_keys
-            };
+            let account_keys = ctx
+                .chain
+                .account_keys(&db_user.wallet_id)
+                .await
+                .map_err(|e| reject::custom(Error::Chain(e)))?;
 
-            if account_keys
-                .data
-                .iter()
-                .find(|&key| key.public_key.eq(&req_body.pub_key))
-                .is_none()
-            {
+            if !account_keys.contains(&req_body.pub_key) {
                 return Err(reject::custom(Error::User(UserError::WrongWalletPubKey)));
             }
 
             // check user is a buyer
-            if !db_user.user_type.eq(&Role::Buyer) {
+            if !policy(Operation::VerifyLoginCode).allows_role(&db_user.user_type) {
                 return Err(reject::custom(Error::User(UserError::OnlyBuyer)));
             }
 
@@ -940,24 +1547,99 @@ pub async fn verify_login_code(
     let _is_success = db_update_session_info(&ctx.db_client, &db_session.id, &db_user.id, true)
         .await
         .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    audit::record(
+        &ctx.db_client,
+        Some(db_user.id),
+        "verify_login_code",
+        AuditEntity::Session(db_session.id),
+        None,
+    )
+    .await;
+
+    // send jwt over pusher, on the private channel of the client that created the code
+    let db_job = enqueue(
+        &ctx.db_client,
+        JobPayload::PusherEvent {
+            channel: PusherChannel::Custom(login_channel(&db_session.login_code)),
+            event: PusherEvent::LoggedIn,
+            data: jwt_token,
+        },
+        ctx.jobs.max_attempts,
+    )
+    .await
+    .map_err(|e| reject::custom(Error::Postgres(e)))?;
 
-    // send jwt over pusher TODO: spawn in a thread, error handling, retrial ???
-    let events = ctx
-        .pusher_client
-        .send(
-            PusherChannels::Custom(db_session.login_code),
-            PusherEvents::LoggedIn,
-            &jwt_token,
-        )
-        .await
-        .map_err(|e| reject::custom(Error::Pusher(e)))?;
-
-    log::info!("Successfully sent login event: {:?}", events);
+    log::info!("Successfully enqueued login event: {}", db_job.id);
 
     let verify_login_code_response = VerifyLoginCodeResponse {};
     Ok(warp::reply::json(&verify_login_code_response))
 }
 
+// pusher client subscribes to a private or presence channel
+pub async fn pusher_auth(
+    authorization: Option<String>,
+    ctx: Arc<ResourcesContext>,
+    req_body: PusherAuthRequest,
+) -> Result<impl warp::Reply, Rejection> {
+    req_body
+        .validate()
+        .map_err(|e| reject::custom(Error::Request(RequestError::ValidationError(e))))?;
+    if !valid_socket_id(&req_body.socket_id) {
+        return Err(reject::custom(Error::Auth(AuthError::InvalidSocketId(
+            req_body.socket_id,
+        ))));
+    }
+
+    let not_allowed = || {
+        reject::custom(Error::Auth(AuthError::ChannelNotAllowed(
+            req_body.channel_name.clone(),
+        )))
+    };
+    let channel = parse_auth_channel(&req_body.channel_name).ok_or_else(not_allowed)?;
+    let channel_data = match &channel.owner {
+        // the user's own channels, of the user signed in with the jwt
+        ChannelOwner::User(owner_id) => {
+            let authorization = authorization
+                .ok_or_else(|| reject::custom(Error::Auth(AuthError::NoAuthHeaderError)))?;
+            let user_id = authorize_bearer(
+                &authorization,
+                policy(Operation::AuthorizePusherChannel).roles,
+            )
+            .map_err(reject::custom)?;
+            if user_id != *owner_id {
+                return Err(not_allowed());
+            }
+            channel
+                .presence
+                .then(|| serde_json::json!({ "user_id": user_id }).to_string())
+        }
+        // the client waiting for the jwt of its login code has none yet, the code it was handed
+        // proves the channel is its own until the code is used or expires
+        ChannelOwner::LoginCode(login_code) => {
+            if channel.presence {
+                return Err(not_allowed());
+            }
+            let db_session = db_get_session_by_login_code(&ctx.db_client, login_code)
+                .await
+                .map_err(|_| not_allowed())?;
+            if db_session.is_used || db_session.expires_at < sql_timestamp(None) {
+                return Err(not_allowed());
+            }
+            None
+        }
+    };
+
+    let auth = ctx.pusher_auth.sign(
+        &req_body.socket_id,
+        &req_body.channel_name,
+        channel_data.as_deref(),
+    );
+    Ok(warp::reply::json(&PusherAuthResponse {
+        auth,
+        channel_data,
+    }))
+}
+
 // buyer gets an event verification code
 pub async fn event_ticket_get_verification_code(
     role: String,
@@ -968,7 +1650,7 @@ pub async fn event_ticket_get_verification_code(
     // only for buyers ATM
     let role = Role::try_from(role.as_str())
         .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
-    if !role.eq(&Role::Buyer) {
+    if !policy(Operation::EventTicketGetVerificationCode).allows_role(&role) {
         return Err(reject::custom(Error::User(UserError::OnlyBuyer)));
     }
 
@@ -998,9 +1680,249 @@ pub async fn event_ticket_get_verification_code(
                 event_id.to_string(),
             )))
         })?;
+    if db_event.event_status == EventStatus::Cancelled {
+        return Err(reject::custom(Error::Event(EventError::CancelledEvent(
+            event_id.to_string(),
+        ))));
+    }
+    // only the published events sell, not the drafts nor the ones waiting for their review
+    if db_event.event_status != EventStatus::Final || db_event.deleted_at.is_some() {
+        return Err(reject::custom(Error::Event(EventError::NotPublishedEvent(
+            event_id.to_string(),
+        ))));
+    }
+
+    // look up and check the promo code (if any) before reserving anything
+    let db_promo_code = match req_body.promo_code.as_ref() {
+        Some(code) => {
+            let db_promo_code = db_get_promo_code_by_code(&ctx.db_client, &event_id, code)
+                .await
+                .map_err(|_| {
+                    reject::custom(Error::PromoCode(PromoCodeError::NoExistPromoCode(
+                        code.to_string(),
+                    )))
+                })?;
+
+            if !db_promo_code.is_active {
+                return Err(reject::custom(Error::PromoCode(
+                    PromoCodeError::InactivePromoCode(code.to_string()),
+                )));
+            }
+
+            if !db_promo_code.is_valid_at(&sql_timestamp(None)) {
+                return Err(reject::custom(Error::PromoCode(
+                    PromoCodeError::OutsideValidityWindow(code.to_string()),
+                )));
+            }
 
-    // loop over reservations and add them to db
-    for reservation in req_body.reservations.into_iter() {
+            if !db_promo_code.has_uses_left() {
+                return Err(reject::custom(Error::PromoCode(
+                    PromoCodeError::UsageLimitReached(code.to_string()),
+                )));
+            }
+
+            Some(db_promo_code)
+        }
+        None => None,
+    };
+
+    // reserve every ticket or none, what the request took is given back if one cannot be reserved
+    let mut claims = ReservationClaims::default();
+    let reserved_tickets = match reserve_tickets(
+        &ctx,
+        &db_event,
+        db_promo_code.as_ref(),
+        &verification_code,
+        &user_id,
+        req_body.reservations,
+        &mut claims,
+    )
+    .await
+    {
+        Ok(reserved_tickets) => reserved_tickets,
+        Err(e) => {
+            claims
+                .release(&ctx, db_promo_code.as_ref(), &verification_code)
+                .await;
+            return Err(e);
+        }
+    };
+
+    let mut prices: Vec<ReservedTicketPrice> = vec![];
+    let mut receipts: Vec<Receipt> = vec![];
+
+    // the tickets are reserved, the reservations stand if the follow-ups fail
+    for reserved_ticket in reserved_tickets.into_iter() {
+        let ticket_id = reserved_ticket.db_ticket.id;
+        audit::record(
+            &ctx.db_client,
+            Some(user_id),
+            "reserve_ticket",
+            AuditEntity::TicketReservation(reserved_ticket.reservation_id),
+            Some(serde_json::json!({
+                "eventId": db_event.id.to_string(),
+                "ticketId": ticket_id.to_string(),
+                "quantity": reserved_ticket.quantity,
+                "promoCode": db_promo_code.as_ref().map(|db_promo_code| db_promo_code.code.clone()),
+                "priceTierId": reserved_ticket.claimed_price.tier_id.map(|tier_id| tier_id.to_string()),
+            })),
+        )
+        .await;
+
+        // the buyer got the tickets they were waiting for
+        if let Err(e) = db_delete_waitlist_entry(&ctx.db_client, &ticket_id, &user_id).await {
+            log::error!(
+                "Failed to drop the waitlist entry of user {} for ticket {}: {}",
+                user_id,
+                ticket_id,
+                e
+            );
+        }
+
+        // tell the clients of the event when the reservation took the last tickets
+        match db_get_ticket_by_id(&ctx.db_client, &ticket_id).await {
+            Ok(reserved_db_ticket) if reserved_db_ticket.quantity_remaining() == Some(0) => {
+                if let Err(e) = broadcast(
+                    &ctx.db_client,
+                    db_event.id,
+                    EventUpdate::SoldOut { ticket_id },
+                    ctx.jobs.max_attempts,
+                )
+                .await
+                {
+                    log::error!("Failed to broadcast ticket {} sold out: {}", ticket_id, e);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to get the reserved ticket {}: {}", ticket_id, e),
+        }
+        // and the seller when they run low
+        alert_stock(&ctx, &db_event, &ticket_id).await;
+
+        prices.push(ReservedTicketPrice {
+            ticket_id: ticket_id.to_string(),
+            quantity: reserved_ticket.quantity,
+            price: reserved_ticket.claimed_price.price,
+            effective_price: reserved_ticket.effective_price,
+        });
+        receipts.push(Receipt {
+            event_name: db_event.event_name.clone(),
+            ticket_name: reserved_ticket.db_ticket.ticket_name.clone(),
+            quantity: reserved_ticket.quantity,
+            verification_code: verification_code.clone(),
+        });
+    }
+
+    // send the buyer a receipt per reserved ticket, the reservation stands if it cannot be sent
+    let receiver = db_get_user_by_id(&ctx.db_client, &user_id)
+        .await
+        .ok()
+        .and_then(|db_user| Some((db_user.phone_number?, db_user.locale)));
+    if let Some((phone_number, locale)) = receiver {
+        for receipt in receipts.into_iter() {
+            if let Err(e) = enqueue(
+                &ctx.db_client,
+                JobPayload::Notify {
+                    receiver: phone_number.clone(),
+                    notification: Notification::Receipt(receipt),
+                    locale: locale.clone(),
+                },
+                ctx.jobs.max_attempts,
+            )
+            .await
+            {
+                log::error!("Failed to enqueue a receipt for user {}: {}", user_id, e);
+            }
+        }
+    }
+
+    return Ok(warp::reply::json(&EventGetVerificationCodeResponse {
+        verification_code,
+        promo_code: db_promo_code.map(|db_promo_code| db_promo_code.code),
+        prices,
+    }));
+}
+
+// a reserved ticket of a reservation request
+struct ReservedTicket {
+    reservation_id: Uuid,
+    db_ticket: DbTicket,
+    quantity: i32,
+    claimed_price: ClaimedPrice,
+    effective_price: Option<NearAmount>,
+}
+
+// what a reservation request took so far, given back if one of its tickets cannot be reserved
+#[derive(Default)]
+struct ReservationClaims {
+    reservation_ids: Vec<Uuid>,
+    prices: Vec<(ClaimedPrice, i32)>,
+    promo_code_uses: i32,
+}
+
+impl ReservationClaims {
+    // gives everything back, failures are logged as the request fails anyway
+    async fn release(
+        self,
+        ctx: &ResourcesContext,
+        db_promo_code: Option<&DbPromoCode>,
+        verification_code: &str,
+    ) {
+        if !self.reservation_ids.is_empty() {
+            if let Err(e) = db_undo_ticket_reservations(&ctx.db_client, &self.reservation_ids).await
+            {
+                log::error!(
+                    "Failed to undo the reservations {:?}: {}",
+                    self.reservation_ids,
+                    e
+                );
+            }
+        }
+        for (claimed_price, quantity) in self.prices.iter() {
+            if let Err(e) = release_ticket_price(&ctx.db_client, claimed_price, *quantity).await {
+                log::error!("Failed to release the price {:?}: {}", claimed_price, e);
+            }
+        }
+        if let Some(db_promo_code) = db_promo_code {
+            if let Err(e) = db_delete_promo_code_usages(&ctx.db_client, verification_code).await {
+                log::error!(
+                    "Failed to drop the usages of promo code {}: {}",
+                    db_promo_code.code,
+                    e
+                );
+            }
+            if self.promo_code_uses > 0 {
+                if let Err(e) =
+                    db_release_promo_code(&ctx.db_client, &db_promo_code.id, self.promo_code_uses)
+                        .await
+                {
+                    log::error!(
+                        "Failed to release {} uses of promo code {}: {}",
+                        self.promo_code_uses,
+                        db_promo_code.code,
+                        e
+                    );
+                }
+            }
+        }
+    }
+}
+
+// reserves the tickets one by one, recording in `claims` what each step took
+async fn reserve_tickets(
+    ctx: &ResourcesContext,
+    db_event: &DbEvent,
+    db_promo_code: Option<&DbPromoCode>,
+    verification_code: &str,
+    user_id: &Uuid,
+    reservations: Vec<EventTicketReservation>,
+    claims: &mut ReservationClaims,
+) -> Result<Vec<ReservedTicket>, Rejection> {
+    let event_id = db_event.id;
+    let user_id = *user_id;
+    let mut reserved_tickets = vec![];
+
+    for reservation in reservations.into_iter() {
         // get ticket id
         let ticket_id = Uuid::parse_str(&reservation.ticket_id)
             .map_err(|_| Error::UnparsableUuid(reservation.ticket_id.clone()))?;
@@ -1030,32 +1952,174 @@ pub async fn event_ticket_get_verification_code(
             reservation.ticket_id.eq(&ticket_id)
                 && reservation.event_id.eq(&event_id)
                 && reservation.user_id.eq(&user_id)
-                && reservation.verification_code.eq(&verification_code)
+                && reservation.verification_code.eq(verification_code)
         }) {
             return Err(reject::custom(Error::Ticket(
                 TicketError::AlreadyReservedForUser(user_id.to_string()),
             )));
         }
 
+        // check the quantity against the purchase limits and the tickets left
+        let quantity = i32::try_from(reservation.quantity).map_err(|_| {
+            reject::custom(Error::Ticket(TicketError::InvalidQuantity(
+                ticket_id.to_string(),
+            )))
+        })?;
+        db_ticket
+            .check_purchase_quantity(
+                quantity,
+                reserved_quantity(&ticket_reservations, &ticket_id),
+            )
+            .map_err(|e| reject::custom(Error::Ticket(e)))?;
+
+        // the seats of a seated ticket, held by the user beforehand
+        let seated = db_is_ticket_seated(&ctx.db_client, &ticket_id)
+            .await
+            .map_err(|e| reject::custom(Error::Postgres(e)))?;
+        let seat_ids = match (seated, reservation.seat_ids) {
+            (true, Some(seat_ids)) => {
+                let mut seat_ids = seat_ids
+                    .iter()
+                    .map(|seat_id| {
+                        Uuid::parse_str(seat_id).map_err(|_| Error::UnparsableUuid(seat_id.clone()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                seat_ids.sort();
+                seat_ids.dedup();
+                if seat_ids.len() != quantity as usize {
+                    return Err(reject::custom(Error::Ticket(TicketError::SeatsRequired(
+                        ticket_id.to_string(),
+                    ))));
+                }
+                Some(seat_ids)
+            }
+            (true, None) => {
+                return Err(reject::custom(Error::Ticket(TicketError::SeatsRequired(
+                    ticket_id.to_string(),
+                ))))
+            }
+            (false, Some(_)) => {
+                return Err(reject::custom(Error::Ticket(TicketError::NotSeated(
+                    ticket_id.to_string(),
+                ))))
+            }
+            (false, None) => None,
+        };
+
         // create a new db ticket reservation
-        let new_db_ticket_reservation = DbTicketReservation::new(
+        let mut new_db_ticket_reservation = DbTicketReservation::new(
             Uuid::new_v4(),
             sql_timestamp(None),
-            &verification_code,
-            db_event.id,
+            verification_code,
+            event_id,
             ticket_id,
             user_id,
+            quantity,
         );
 
-        // insert ticket reservation into db
-        db_insert_ticket_reservation(&ctx.db_client, &new_db_ticket_reservation)
-            .await
-            .map_err(|e| reject::custom(Error::Postgres(e)))?;
+        // the price of the tier the tickets sell at now, if any
+        let claimed_price = claim_ticket_price(
+            &ctx.db_client,
+            &db_ticket,
+            quantity,
+            &new_db_ticket_reservation.created_at,
+        )
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+        new_db_ticket_reservation.price_tier_id = claimed_price.tier_id;
+        claims.prices.push((claimed_price.clone(), quantity));
+
+        // consume a use per ticket atomically before reserving, so concurrent reservations cannot
+        // exceed the limit and no reservation goes through without its discount
+        if let Some(db_promo_code) = db_promo_code {
+            db_consume_promo_code(&ctx.db_client, &db_promo_code.id, quantity)
+                .await
+                .map_err(|e| reject::custom(Error::Postgres(e)))?
+                .ok_or_else(|| {
+                    reject::custom(Error::PromoCode(PromoCodeError::UsageLimitReached(
+                        db_promo_code.code.clone(),
+                    )))
+                })?;
+            claims.promo_code_uses += quantity;
+        }
+
+        // reserve the tickets, the limits and holds are checked again atomically with the insert
+        let reserved = match seat_ids.as_deref() {
+            Some(seat_ids) => {
+                db_reserve_seats(&ctx.db_client, &new_db_ticket_reservation, seat_ids).await
+            }
+            None => db_reserve_ticket(&ctx.db_client, &new_db_ticket_reservation).await,
+        }
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+        if reserved.is_none() {
+            // a concurrent reservation took the tickets, report the limit it hit
+            let db_ticket = db_get_ticket_by_id(&ctx.db_client, &ticket_id)
+                .await
+                .map_err(|e| reject::custom(Error::Postgres(e)))?;
+            let ticket_reservations =
+                db_get_ticket_reservations_by_user_id(&ctx.db_client, &user_id)
+                    .await
+                    .map_err(|e| reject::custom(Error::Postgres(e)))?;
+            let e = db_ticket
+                .check_purchase_quantity(
+                    quantity,
+                    reserved_quantity(&ticket_reservations, &ticket_id),
+                )
+                .err()
+                .unwrap_or_else(|| {
+                    if seated {
+                        TicketError::SeatsNotHeld(ticket_id.to_string())
+                    } else {
+                        TicketError::InsufficientQuantity(ticket_id.to_string())
+                    }
+                });
+            return Err(reject::custom(Error::Ticket(e)));
+        }
+        claims.reservation_ids.push(new_db_ticket_reservation.id);
+
+        // apply the promo code (if any), its uses were consumed above, and record its usage
+        let effective_price = match db_promo_code {
+            Some(db_promo_code) => {
+                let discounted_price = claimed_price
+                    .price
+                    .map(|price| db_promo_code.apply_discount(price));
+
+                let new_db_promo_code_usage = DbPromoCodeUsage::new(
+                    verification_code,
+                    claimed_price.price,
+                    discounted_price,
+                    db_promo_code.id,
+                    ticket_id,
+                    user_id,
+                );
+                db_insert_promo_code_usage(&ctx.db_client, &new_db_promo_code_usage)
+                    .await
+                    .map_err(|e| reject::custom(Error::Postgres(e)))?;
+
+                discounted_price
+            }
+            None => claimed_price.price,
+        };
+
+        reserved_tickets.push(ReservedTicket {
+            reservation_id: new_db_ticket_reservation.id,
+            db_ticket,
+            quantity,
+            claimed_price,
+            effective_price,
+        });
     }
 
-    return Ok(warp::reply::json(&EventGetVerificationCodeResponse {
-        verification_code,
-    }));
+    Ok(reserved_tickets)
+}
+
+// the quantity of a ticket reserved across the reservations
+fn reserved_quantity(reservations: &[DbTicketReservation], ticket_id: &Uuid) -> i64 {
+    reservations
+        .iter()
+        .filter(|reservation| reservation.ticket_id.eq(ticket_id))
+        .map(|reservation| i64::from(reservation.quantity))
+        .sum()
 }
 
 // buyer gets an event verification code
@@ -1068,7 +2132,7 @@ pub async fn get_event_from_verification_code(
     // only for buyers ATM
     let role = Role::try_from(role.as_str())
         .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
-    if !role.eq(&Role::Buyer) {
+    if !policy(Operation::GetEventFromVerificationCode).allows_role(&role) {
         return Err(reject::custom(Error::User(UserError::OnlyBuyer)));
     }
 
@@ -1137,3 +2201,525 @@ pub async fn get_event_from_verification_code(
         &GetEventFromVerificationCodeResponse::new(db_event, tickets),
     ));
 }
+
+pub async fn export_my_data(
+    role: String,
+    ctx: Arc<ResourcesContext>,
+    user_id: uuid::Uuid, // authenticated user id calling the endpoint
+) -> Result<impl warp::Reply, Rejection> {
+    let role = Role::try_from(role.as_str())
+        .map_err(|_| reject::custom(Error::User(UserError::UnallowedUserRole(role))))?;
+
+    let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
+        .await
+        .map_err(|_| reject::custom(Error::User(UserError::UserNotFound)))?;
+    if db_user.deleted_at.is_some() {
+        return Err(reject::custom(Error::User(UserError::AccountDeleted)));
+    }
+
+    // the path role must be the caller's own
+    if !db_user.user_type.eq(&role) {
+        return Err(reject::custom(Error::User(UserError::UnallowedUserRole(
+            role.to_string(),
+        ))));
+    }
+
+    let reservations = db_get_ticket_reservations_by_user_id(&ctx.db_client, &user_id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+
+    let db_events = db_get_events_by_creator(&ctx.db_client, &user_id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    let event_ids = db_events.iter().map(|event| event.id).collect::<Vec<_>>();
+    let tickets = db_get_tickets_by_event_ids(&ctx.db_client, &event_ids)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    let events = db_events
+        .into_iter()
+        .map(|event| {
+            let event_tickets = tickets
+                .iter()
+                .cloned()
+                .filter(|ticket| ticket.event_id.eq(&event.id))
+                .collect::<Vec<_>>();
+            GetEventFromVerificationCodeResponse::new(event, event_tickets)
+        })
+        .collect();
+
+    audit::record(
+        &ctx.db_client,
+        Some(user_id),
+        "export_my_data",
+        AuditEntity::User(user_id),
+        None,
+    )
+    .await;
+
+    Ok(warp::reply::json(&UserDataExportResponse {
+        exported_at: Utc::now().timestamp_millis(),
+        profile: db_user.into(),
+        reservations: reservations.into_iter().map(Into::into).collect(),
+        events,
+    }))
+}
+
+pub async fn import_events(
+    ctx: Arc<ResourcesContext>,
+    form: FormData,
+    user_id: uuid::Uuid, // authenticated user id calling the endpoint
+) -> Result<impl warp::Reply, Rejection> {
+    let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
+        .await
+        .map_err(|_| reject::custom(Error::User(UserError::UserNotFound)))?;
+    if db_user.deleted_at.is_some() {
+        return Err(reject::custom(Error::User(UserError::AccountDeleted)));
+    }
+
+    // the events are read from the `file` part of the form
+    let parts: Vec<Part> = form
+        .try_collect()
+        .await
+        .map_err(|e| reject::custom(Error::Request(RequestError::InvalidUpload(e.to_string()))))?;
+    let part = parts
+        .into_iter()
+        .find(|part| part.name() == "file")
+        .ok_or_else(|| {
+            reject::custom(Error::Request(RequestError::InvalidUpload(
+                "missing file part".to_string(),
+            )))
+        })?;
+    let format = ImportFormat::detect(part.content_type(), part.filename()).ok_or_else(|| {
+        reject::custom(Error::Request(RequestError::InvalidUpload(
+            "the file must be a CSV or JSON file".to_string(),
+        )))
+    })?;
+    let content = part
+        .stream()
+        .try_fold(Vec::new(), |mut content, data| async move {
+            content.put(data);
+            Ok(content)
+        })
+        .await
+        .map_err(|e| reject::custom(Error::Request(RequestError::InvalidUpload(e.to_string()))))?;
+    let entries = parse_import(format, &content).map_err(|e| reject::custom(Error::Request(e)))?;
+
+    // every event is stored on its own, so a failing one does not abort the others
+    let mut event_slugs = HashSet::new();
+    let mut rows = vec![];
+    for entry in entries.into_iter() {
+        let event_name = entry.event.event_name.clone();
+        let result = import_event(&ctx, &mut event_slugs, entry.event, user_id).await;
+        rows.push(match result {
+            Ok((db_event, db_tickets)) => ImportEventsRow {
+                rows: entry.rows,
+                event_name,
+                event_id: Some(db_event.id.to_string()),
+                ticket_ids: db_tickets
+                    .iter()
+                    .map(|db_ticket| db_ticket.id.to_string())
+                    .collect(),
+                error: None,
+            },
+            Err(GqlError::Database(e)) => {
+                eprintln!("import event error: {:?}", e.to_string());
+                ImportEventsRow {
+                    rows: entry.rows,
+                    event_name,
+                    event_id: None,
+                    ticket_ids: vec![],
+                    error: Some("Event could not be stored".to_string()),
+                }
+            }
+            Err(e) => ImportEventsRow {
+                rows: entry.rows,
+                event_name,
+                event_id: None,
+                ticket_ids: vec![],
+                error: Some(e.to_string()),
+            },
+        });
+    }
+
+    let imported = rows.iter().filter(|row| row.error.is_none()).count();
+    Ok(warp::reply::json(&ImportEventsResponse {
+        imported,
+        failed: rows.len() - imported,
+        rows,
+    }))
+}
+
+/// Validates and stores an imported event with its tickets
+async fn import_event(
+    ctx: &ResourcesContext,
+    event_slugs: &mut HashSet<String>,
+    event: ImportEvent,
+    user_id: uuid::Uuid,
+) -> Result<(DbEvent, Vec<DbTicket>), GqlError> {
+    let (db_event, db_tickets) = check_import_event(&ctx.sanitation, event, user_id)?;
+
+    // check for unique event slug and name, within the upload too
+    if !event_slugs.insert(db_event.event_slug.clone())
+        || db_get_event_by_slug(&ctx.db_client, &db_event.event_slug)
+            .await
+            .is_ok()
+    {
+        return Err(GqlError::Conflict(ValidationError::new(
+            "event_slug",
+            "Event with the same slug already exists",
+        )));
+    }
+    if db_get_event_by_name(&ctx.db_client, &db_event.event_name)
+        .await
+        .is_ok()
+    {
+        return Err(GqlError::Conflict(ValidationError::new(
+            "event_name",
+            "Event with the same name already exists",
+        )));
+    }
+
+    db_insert_event_with_tickets(&ctx.db_client, &db_event, &db_tickets)
+        .await
+        .map_err(GqlError::Database)?;
+    audit::record(
+        &ctx.db_client,
+        Some(user_id),
+        "import_event",
+        AuditEntity::Event(db_event.id),
+        serde_json::to_value(&db_event).ok(),
+    )
+    .await;
+
+    Ok((db_event, db_tickets))
+}
+
+/// Stores the image files of the form's `file` parts as pending assets of a draft event. Every
+/// file is checked before any is stored, the assets are then attached as the event's images or
+/// gallery like the presigned uploads are
+pub async fn upload_event_assets(
+    event_id: uuid::Uuid,
+    ctx: Arc<ResourcesContext>,
+    mut form: FormData,
+    user_id: uuid::Uuid, // authenticated user id calling the endpoint
+) -> Result<impl warp::Reply, Rejection> {
+    let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+        .await
+        .map_err(|_| {
+            reject::custom(Error::Event(EventError::NoExistEventUuid(
+                event_id.to_string(),
+            )))
+        })?;
+    guard_event(&ctx, &user_id, &db_event, EventAccess::Edit)
+        .await
+        .map_err(|e| match e {
+            GqlError::Database(e) => reject::custom(Error::Postgres(e)),
+            _ => reject::custom(Error::Event(EventError::NotEventCreator(
+                event_id.to_string(),
+            ))),
+        })?;
+    if !db_event.event_status.eq(&EventStatus::Draft) {
+        return Err(reject::custom(Error::Event(EventError::NotDraftEvent(
+            event_id.to_string(),
+        ))));
+    }
+
+    // the parts are read as they stream in, each file to its temporary file
+    let mut spooled = vec![];
+    while let Some(part) = form
+        .try_next()
+        .await
+        .map_err(|e| reject::custom(Error::Request(RequestError::InvalidUpload(e.to_string()))))?
+    {
+        if part.name() != ASSET_PART_NAME {
+            continue;
+        }
+        if spooled.len() == ctx.body_limits.asset_files {
+            return Err(reject::custom(Error::Request(RequestError::InvalidUpload(
+                format!(
+                    "at most {} files are uploaded at once",
+                    ctx.body_limits.asset_files
+                ),
+            ))));
+        }
+        spooled.push(
+            spool_asset(part, ctx.body_limits.asset_bytes)
+                .await
+                .map_err(reject::custom)?,
+        );
+    }
+    if spooled.is_empty() {
+        return Err(reject::custom(Error::Request(RequestError::InvalidUpload(
+            "missing file part".to_string(),
+        ))));
+    }
+
+    let mut assets = vec![];
+    for file in spooled.iter() {
+        let asset_id = Uuid::new_v4();
+        let key = format!("events/{}/{}.{}", event_id, asset_id, file.extension);
+        let content = file
+            .stream()
+            .await
+            .map_err(|e| reject::custom(Error::Storage(e)))?;
+        ctx.storage
+            .put_stream(&key, &file.content_type, file.size, content)
+            .await
+            .map_err(|e| reject::custom(Error::Storage(e)))?;
+
+        let asset_file = AssetFile::new_pending(
+            asset_id,
+            ctx.storage.bucket().to_string(),
+            key.clone(),
+            file.content_type.clone(),
+            event_id,
+        );
+        insert_asset_file(&ctx.db_client, &asset_file)
+            .await
+            .map_err(|e| reject::custom(Error::Postgres(e)))?;
+        audit::record(
+            &ctx.db_client,
+            Some(user_id),
+            "upload_event_asset",
+            AuditEntity::Asset(asset_id),
+            Some(serde_json::json!({ "size": file.size })),
+        )
+        .await;
+
+        assets.push(UploadedAsset {
+            asset_id: asset_id.to_string(),
+            content_type: file.content_type.clone(),
+            size: file.size,
+            url: readable_url(
+                ctx.storage.as_ref(),
+                &ctx.asset_urls,
+                ctx.storage.asset_url(key),
+            )
+            .await
+            .map_err(|e| reject::custom(Error::Storage(e)))?,
+        });
+    }
+
+    Ok(warp::reply::json(&UploadEventAssetsResponse { assets }))
+}
+
+pub async fn event_attendees_csv(
+    event_id: uuid::Uuid,
+    ctx: Arc<ResourcesContext>,
+    user_id: uuid::Uuid, // authenticated user id calling the endpoint
+) -> Result<impl warp::Reply, Rejection> {
+    let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
+        .await
+        .map_err(|_| reject::custom(Error::User(UserError::UserNotFound)))?;
+
+    let db_event = db_get_event_by_id(&ctx.db_client, &event_id)
+        .await
+        .map_err(|_| {
+            reject::custom(Error::Event(EventError::NoExistEventUuid(
+                event_id.to_string(),
+            )))
+        })?;
+    let member = match db_event.organization_id {
+        Some(organization_id) => {
+            db_get_organization_member(&ctx.db_client, &organization_id, &user_id)
+                .await
+                .map_err(|e| reject::custom(Error::Postgres(e)))?
+        }
+        None => None,
+    };
+    let collaborator = db_get_event_collaborator(&ctx.db_client, &event_id, &user_id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    if !can_export_attendees(
+        &db_user.user_type,
+        &user_id,
+        &db_event,
+        member.as_ref(),
+        collaborator.as_ref(),
+    ) {
+        return Err(reject::custom(Error::Event(EventError::NotEventCreator(
+            event_id.to_string(),
+        ))));
+    }
+
+    let attendees = db_get_event_attendees(&ctx.db_client, &event_id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    let content = attendees_csv(&attendees);
+
+    audit::record(
+        &ctx.db_client,
+        Some(user_id),
+        "export_attendees",
+        AuditEntity::Event(event_id),
+        None,
+    )
+    .await;
+
+    let reply = warp::reply::with_header(
+        content,
+        warp::http::header::CONTENT_TYPE,
+        ATTENDEES_CSV_CONTENT_TYPE,
+    );
+    Ok(warp::reply::with_header(
+        reply,
+        warp::http::header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"attendees-{}.csv\"", event_id),
+    ))
+}
+
+// ticket pdf: the buyer's reservation as a printable ticket, rendered once per version of the
+// event and kept in the storage
+pub async fn ticket_pdf(
+    file_name: String,
+    ctx: Arc<ResourcesContext>,
+    user_id: uuid::Uuid, // authenticated user id calling the endpoint
+) -> Result<impl warp::Reply, Rejection> {
+    let config = ctx.ticket_pdf.as_ref().ok_or_else(reject::not_found)?;
+    let reservation_id = file_name
+        .strip_suffix(".pdf")
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .ok_or_else(reject::not_found)?;
+
+    // others' reservations are not found, rather than forbidden, not to tell which exist
+    let db_reservation = db_get_ticket_reservation_by_id(&ctx.db_client, &reservation_id)
+        .await
+        .map_err(|_| reject::not_found())?;
+    if db_reservation.user_id != user_id {
+        return Err(reject::not_found());
+    }
+    let db_event = db_get_event_by_id(&ctx.db_client, &db_reservation.event_id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    let db_ticket = db_get_ticket_by_id(&ctx.db_client, &db_reservation.ticket_id)
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    let db_user = db_get_user_by_id(&ctx.db_client, &user_id)
+        .await
+        .map_err(|_| reject::custom(Error::User(UserError::UserNotFound)))?;
+
+    let qr_payload = ticket_payload(
+        &config.signing_secret,
+        &TicketPayload {
+            reservation_id,
+            verification_code: db_reservation.verification_code.clone(),
+            quantity: db_reservation.quantity,
+        },
+    );
+    let key = ticket_pdf_key(
+        &config.storage_prefix,
+        &reservation_id,
+        &qr_payload,
+        &db_event.modified_at,
+    );
+
+    // a storage failure costs a rendering, not the download
+    let cached = match ctx.storage.exists(&key).await {
+        Ok(true) => ctx
+            .storage
+            .get(&key)
+            .await
+            .map_err(|e| log::warn!("Failed to get cached ticket {}: {}", key, e))
+            .ok(),
+        Ok(false) => None,
+        Err(e) => {
+            log::warn!("Failed to look up cached ticket {}: {}", key, e);
+            None
+        }
+    };
+    let content = match cached {
+        Some(content) => content,
+        None => {
+            let content = render_ticket_pdf(&TicketPdf {
+                event: &db_event,
+                ticket: &db_ticket,
+                reservation: &db_reservation,
+                holder: &db_user.username,
+                qr_payload: &qr_payload,
+            })
+            .map_err(|e| reject::custom(Error::TicketPdf(e)))?;
+            if let Err(e) = ctx
+                .storage
+                .put(&key, TICKET_PDF_CONTENT_TYPE, content.clone())
+                .await
+            {
+                log::warn!("Failed to cache ticket {}: {}", key, e);
+            }
+            content
+        }
+    };
+
+    let reply = warp::reply::with_header(
+        content,
+        warp::http::header::CONTENT_TYPE,
+        TICKET_PDF_CONTENT_TYPE,
+    );
+    Ok(warp::reply::with_header(
+        reply,
+        warp::http::header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"ticket-{}.pdf\"", reservation_id),
+    ))
+}
+
+// public event: a published event with its tickets and tags, revalidated by its etag or its
+// modification time
+pub async fn public_event(
+    event_slug: String,
+    ctx: Arc<ResourcesContext>,
+    validators: CacheValidators,
+) -> Result<impl warp::Reply, Rejection> {
+    let db_event = db_get_event_by_slug(&ctx.db_client, &event_slug)
+        .await
+        .map_err(|_| reject::not_found())?;
+    if db_event.event_status != EventStatus::Final || db_event.deleted_at.is_some() {
+        return Err(reject::not_found());
+    }
+
+    let tickets = db_get_tickets_by_event_id(&ctx.db_client, &Some(db_event.id))
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+    let tags = db_get_event_tags(&ctx.db_client, &[db_event.id])
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?
+        .into_iter()
+        .map(|tag| tag.tag)
+        .collect();
+
+    let modified_at = db_event.modified_at;
+    let mut event = PublicEventResponse::new(db_event, tickets, tags);
+    // presigned urls change the etag of every response, private buckets are revalidated in full
+    for url in [&mut event.cover_photo_url, &mut event.thumbnail_url] {
+        if let Some(stored_url) = url.take() {
+            *url = Some(
+                readable_url(ctx.storage.as_ref(), &ctx.asset_urls, stored_url)
+                    .await
+                    .map_err(|e| reject::custom(Error::Storage(e)))?,
+            );
+        }
+    }
+    Ok(cached_json_reply(
+        &event,
+        Some(&modified_at),
+        &ctx.http_cache.policy("event"),
+        &validators,
+    ))
+}
+
+// event view beacon: counts a page view of a published event, anonymously
+pub async fn event_view(
+    event_slug: String,
+    ctx: Arc<ResourcesContext>,
+) -> Result<impl warp::Reply, Rejection> {
+    let db_event = db_get_event_by_slug(&ctx.db_client, &event_slug)
+        .await
+        .map_err(|_| reject::not_found())?;
+    if db_event.event_status != EventStatus::Final {
+        return Err(reject::not_found());
+    }
+
+    db_insert_event_view(&ctx.db_client, &DbEventView::new(db_event.id))
+        .await
+        .map_err(|e| reject::custom(Error::Postgres(e)))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,45 @@
+use super::models::DependencyStatus;
+use std::{future::Future, time::Duration};
+use tokio::time::Instant;
+
+pub const STATUS_UP: &'static str = "up";
+pub const STATUS_DOWN: &'static str = "down";
+
+/// Runs a single dependency check, bounded by `timeout`, and reports its outcome and latency.
+pub async fn probe<F>(name: &str, timeout: Duration, check: F) -> DependencyStatus
+where
+    F: Future<Output = Result<(), String>>,
+{
+    let start = Instant::now();
+    let result = match tokio::time::timeout(timeout, check).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("timed out after {}ms", timeout.as_millis())),
+    };
+    let latency_ms = start.elapsed().as_millis();
+
+    match result {
+        Ok(()) => DependencyStatus {
+            name: name.to_string(),
+            status: STATUS_UP.to_string(),
+            latency_ms,
+            error: None,
+        },
+        Err(error) => DependencyStatus {
+            name: name.to_string(),
+            status: STATUS_DOWN.to_string(),
+            latency_ms,
+            error: Some(error),
+        },
+    }
+}
+
+/// Checks an http endpoint is reachable. Any http response counts, as the
+/// probes are unauthenticated and a 401/403/404 still proves connectivity.
+pub async fn http_probe(http_client: &reqwest::Client, url: &str) -> Result<(), String> {
+    http_client
+        .head(url)
+        .send()
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
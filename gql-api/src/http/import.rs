@@ -0,0 +1,253 @@
+//! Bulk import of a seller's events and their tickets from a CSV or JSON upload.
+//!
+//! A JSON upload is an array of events, each with its tickets. A CSV upload has one ticket per
+//! row, consecutive rows sharing an `event_name` being the tickets of one event. A row without a
+//! `ticket_name` only describes its event.
+use crate::{
+    config::SanitationConfig,
+    db::models::{DbEvent, DbTicket},
+    error::RequestError,
+    fx::Currency,
+    gql::{
+        error::{GqlError, ValidationError},
+        models::{NewTicket, UpdateEvent},
+        validations::{check_new_ticket_payload, update_event_mutation_payload},
+    },
+    near::NearAmount,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Upper bound for the size of an upload, 5 MB
+pub const MAX_IMPORT_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Upper bound for the events of an upload
+pub const MAX_IMPORT_EVENTS: usize = 500;
+
+/// The format of an upload
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    Json,
+}
+
+impl ImportFormat {
+    /// detects the format from the content type of the uploaded file, or else its extension
+    pub fn detect(content_type: Option<&str>, filename: Option<&str>) -> Option<Self> {
+        match content_type.map(|content_type| content_type.to_ascii_lowercase()) {
+            Some(content_type) if content_type.starts_with("text/csv") => {
+                return Some(ImportFormat::Csv)
+            }
+            Some(content_type) if content_type.starts_with("application/json") => {
+                return Some(ImportFormat::Json)
+            }
+            _ => (),
+        }
+        let filename = filename?.to_ascii_lowercase();
+        if filename.ends_with(".csv") {
+            Some(ImportFormat::Csv)
+        } else if filename.ends_with(".json") {
+            Some(ImportFormat::Json)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportEvent {
+    pub event_name: String,
+    pub description: Option<String>,
+    pub start_date: Option<NaiveDateTime>,
+    pub end_date: Option<NaiveDateTime>,
+    pub entry_time: Option<NaiveDateTime>,
+    pub is_virtual: Option<bool>,
+    pub venue_name: Option<String>,
+    pub venue_location: Option<String>,
+    #[serde(default)]
+    pub tickets: Vec<ImportTicket>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportTicket {
+    pub ticket_name: String,
+    pub description: Option<String>,
+    pub price: Option<NearAmount>,
+    pub max_release_price: Option<NearAmount>,
+    pub quantity_available: Option<i32>,
+    pub min_purchase_quantity: Option<i32>,
+    pub max_purchase_quantity: Option<i32>,
+    pub allow_transfers: Option<bool>,
+    pub currency: Option<Currency>,
+}
+
+/// A row of a CSV upload
+#[derive(Debug, Clone, Deserialize)]
+struct ImportCsvRow {
+    event_name: String,
+    event_description: Option<String>,
+    start_date: Option<NaiveDateTime>,
+    end_date: Option<NaiveDateTime>,
+    entry_time: Option<NaiveDateTime>,
+    is_virtual: Option<bool>,
+    venue_name: Option<String>,
+    venue_location: Option<String>,
+    ticket_name: Option<String>,
+    ticket_description: Option<String>,
+    price: Option<NearAmount>,
+    max_release_price: Option<NearAmount>,
+    quantity_available: Option<i32>,
+    min_purchase_quantity: Option<i32>,
+    max_purchase_quantity: Option<i32>,
+    allow_transfers: Option<bool>,
+    currency: Option<Currency>,
+}
+
+/// An event of an upload and the rows it was read from, 1-based and the CSV header excluded
+#[derive(Debug, Clone)]
+pub struct ImportEntry {
+    pub rows: Vec<usize>,
+    pub event: ImportEvent,
+}
+
+/// Parses an upload into its events
+pub fn parse_import(
+    format: ImportFormat,
+    content: &[u8],
+) -> Result<Vec<ImportEntry>, RequestError> {
+    let entries = match format {
+        ImportFormat::Csv => parse_csv(content)?,
+        ImportFormat::Json => parse_json(content)?,
+    };
+    if entries.is_empty() {
+        return Err(RequestError::InvalidUpload(
+            "the upload has no events".to_string(),
+        ));
+    }
+    if entries.len() > MAX_IMPORT_EVENTS {
+        return Err(RequestError::InvalidUpload(format!(
+            "the upload has more than {} events",
+            MAX_IMPORT_EVENTS
+        )));
+    }
+    Ok(entries)
+}
+
+fn parse_json(content: &[u8]) -> Result<Vec<ImportEntry>, RequestError> {
+    let events: Vec<ImportEvent> =
+        serde_json::from_slice(content).map_err(|e| RequestError::InvalidUpload(e.to_string()))?;
+    Ok(events
+        .into_iter()
+        .enumerate()
+        .map(|(i, event)| ImportEntry {
+            rows: vec![i + 1],
+            event,
+        })
+        .collect())
+}
+
+fn parse_csv(content: &[u8]) -> Result<Vec<ImportEntry>, RequestError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(content);
+
+    let mut entries: Vec<ImportEntry> = vec![];
+    for (i, row) in reader.deserialize::<ImportCsvRow>().enumerate() {
+        let row = row.map_err(|e| RequestError::InvalidUpload(format!("row {}: {}", i + 1, e)))?;
+
+        let ticket = row.ticket_name.map(|ticket_name| ImportTicket {
+            ticket_name,
+            description: row.ticket_description,
+            price: row.price,
+            max_release_price: row.max_release_price,
+            quantity_available: row.quantity_available,
+            min_purchase_quantity: row.min_purchase_quantity,
+            max_purchase_quantity: row.max_purchase_quantity,
+            allow_transfers: row.allow_transfers,
+            currency: row.currency,
+        });
+
+        // the event columns are taken from the first row of an event
+        match entries.last_mut() {
+            Some(entry) if entry.event.event_name.eq(&row.event_name) => {
+                entry.rows.push(i + 1);
+                entry.event.tickets.extend(ticket);
+            }
+            _ => entries.push(ImportEntry {
+                rows: vec![i + 1],
+                event: ImportEvent {
+                    event_name: row.event_name,
+                    description: row.event_description,
+                    start_date: row.start_date,
+                    end_date: row.end_date,
+                    entry_time: row.entry_time,
+                    is_virtual: row.is_virtual,
+                    venue_name: row.venue_name,
+                    venue_location: row.venue_location,
+                    tickets: ticket.into_iter().collect(),
+                },
+            }),
+        }
+    }
+    Ok(entries)
+}
+
+/// Validates an imported event and its tickets the same way as the GraphQL mutations do, and
+/// builds the DRAFT event to store
+pub fn check_import_event(
+    config: &SanitationConfig,
+    event: ImportEvent,
+    created_by_user: uuid::Uuid,
+) -> Result<(DbEvent, Vec<DbTicket>), GqlError> {
+    let mut db_event = DbEvent::new(&event.event_name, created_by_user);
+    update_event_mutation_payload(
+        config,
+        UpdateEvent {
+            id: db_event.id,
+            event_name: Some(event.event_name),
+            start_date: event.start_date,
+            end_date: event.end_date,
+            entry_time: event.entry_time,
+            description: event.description,
+            is_virtual: event.is_virtual,
+            is_featured: None,
+            venue_name: event.venue_name,
+            venue_location: event.venue_location,
+            cover_photo_base64: None,
+            thumbnail_base64: None,
+        },
+        &mut db_event,
+    )?;
+
+    let mut ticket_slugs = HashSet::new();
+    let mut db_tickets = vec![];
+    for ticket in event.tickets.into_iter() {
+        let mut new_ticket = NewTicket {
+            ticket_name: ticket.ticket_name,
+            description: ticket.description,
+            price: ticket.price,
+            max_release_price: ticket.max_release_price,
+            quantity_available: ticket.quantity_available,
+            min_purchase_quantity: ticket.min_purchase_quantity,
+            max_purchase_quantity: ticket.max_purchase_quantity,
+            allow_transfers: ticket.allow_transfers,
+            currency: ticket.currency,
+            event_id: db_event.id,
+        };
+        check_new_ticket_payload(config, &mut new_ticket)?;
+
+        let db_ticket = DbTicket::new(new_ticket, &db_event);
+        if !ticket_slugs.insert(db_ticket.ticket_slug.clone()) {
+            return Err(GqlError::Conflict(ValidationError::new(
+                "ticket_slug",
+                "Ticket with the same slug already exists",
+            )));
+        }
+        db_tickets.push(db_ticket);
+    }
+
+    Ok((db_event, db_tickets))
+}
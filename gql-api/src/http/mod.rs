@@ -1,3 +1,10 @@
+pub mod caching;
+pub mod export;
 pub mod handlers;
+pub mod health;
+pub mod import;
 pub mod models;
+pub mod openapi;
 pub mod routes;
+pub mod ticket_pdf;
+pub mod uploads;
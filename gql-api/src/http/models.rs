@@ -1,4 +1,10 @@
-use crate::db::models::{DbBuyerRecoverySession, DbBuyerSignupSession, DbEvent, DbTicket, DbUser};
+use crate::db::models::{
+    DbBuyerRecoverySession, DbBuyerSignupSession, DbEvent, DbTicket, DbTicketReservation, DbUser,
+};
+use crate::fx::Currency;
+use crate::grpc::GrpcStatus;
+use crate::near::NearAmount;
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use std::convert::From;
 use validator::Validate;
@@ -8,8 +14,9 @@ use validator::Validate;
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct BuyerCreateRecoveryCodeRequest {
-    #[validate(phone)]
     pub phone_number: String,
+    /// the language of the sms, the buyer's own if `None`
+    pub locale: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
@@ -59,8 +66,9 @@ impl From<DbUser> for BuyerVerifyRecoveryCodeResponse {
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct BuyerRegisterPhoneRequest {
-    #[validate(phone)]
     pub phone_number: String,
+    /// the language of the buyer's sms, e.g. `bg`
+    pub locale: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
@@ -101,6 +109,21 @@ impl From<DbBuyerSignupSession> for BuyerVerifyPhoneResponse {
     }
 }
 
+// -----------BUYER RESEND PHONE CODE--------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct BuyerResendPhoneCodeRequest {
+    pub session_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct BuyerResendPhoneCodeResponse {
+    pub session_id: String,
+    pub resends_left: i32,
+}
+
 // -----------BUYER SIGNUP--------------------
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
@@ -167,7 +190,6 @@ pub struct SigninRequest {
     pub name: Option<String>,
     #[validate(length(min = 5, max = 50))]
     pub username: String,
-    #[validate(phone)]
     pub phone_number: Option<String>,
     #[validate(length(min = 5, max = 50))]
     pub password: Option<String>,
@@ -176,6 +198,24 @@ pub struct SigninRequest {
     pub wallet_id: Option<String>,
     #[validate(length(min = 40, max = 45))]
     pub pub_key: Option<String>,
+    /// the nonce of the sign-in challenge the signature answers
+    pub nonce: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SigninChallengeRequest {
+    #[validate(length(min = 5, max = 64))]
+    pub wallet_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigninChallengeResponse {
+    pub nonce: String,
+    /// the message to sign with a key of the wallet
+    pub message: String,
+    pub expires_at: NaiveDateTime,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -192,6 +232,23 @@ pub struct SigninResponse {
     pub token: String,
 }
 
+/// Answers a password sign-in of a user with two-factor authentication, the jwt is issued by
+/// `/signin_with_pwd/verify_totp` for a code
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigninTotpRequiredResponse {
+    pub totp_token: String,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SigninVerifyTotpRequest {
+    pub totp_token: String,
+    #[validate(length(equal = 6))]
+    pub code: String,
+}
+
 // ---------------------------
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -239,11 +296,33 @@ pub struct VerifyLoginCodeResponse {}
 
 // ---------------------------
 
+/// The form pusher clients post to authorize a channel subscription, named as pusher names it
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct PusherAuthRequest {
+    #[validate(length(max = 64))]
+    pub socket_id: String,
+    #[validate(length(max = 200))]
+    pub channel_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PusherAuthResponse {
+    pub auth: String,
+    /// the member of a presence channel, as signed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_data: Option<String>,
+}
+
+// ---------------------------
+
 #[derive(Debug, Serialize, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct EventTicketReservation {
     pub ticket_id: String,
     pub quantity: i64,
+    /// the held seats of a seated ticket, one per ticket
+    #[serde(default)]
+    pub seat_ids: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -251,12 +330,25 @@ pub struct EventTicketReservation {
 pub struct EventTicketGetVerificationCodeRequest {
     pub event_id: String,
     pub reservations: Vec<EventTicketReservation>,
+    #[validate(length(min = 3, max = 20))]
+    pub promo_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReservedTicketPrice {
+    pub ticket_id: String,
+    pub quantity: i32,
+    pub price: Option<NearAmount>,
+    pub effective_price: Option<NearAmount>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct EventGetVerificationCodeResponse {
     pub verification_code: String,
+    pub promo_code: Option<String>,
+    pub prices: Vec<ReservedTicketPrice>,
 }
 
 // ---------------------------
@@ -294,13 +386,14 @@ pub struct Ticket {
     pub ticket_name: String,
     pub ticket_slug: String,
     pub description: Option<String>,
-    pub price: Option<String>,
-    pub max_release_price: Option<String>,
+    pub price: Option<NearAmount>,
+    pub max_release_price: Option<NearAmount>,
     pub quantity_available: Option<i32>,
     pub min_purchase_quantity: Option<i32>,
     pub max_purchase_quantity: Option<i32>,
     pub allow_transfers: Option<bool>,
     pub event_id: String,
+    pub currency: Currency,
 }
 
 impl From<DbTicket> for Ticket {
@@ -318,6 +411,7 @@ impl From<DbTicket> for Ticket {
             max_purchase_quantity: ticket.max_purchase_quantity,
             allow_transfers: ticket.allow_transfers,
             event_id: ticket.event_id.to_string(),
+            currency: ticket.currency,
         }
     }
 }
@@ -344,6 +438,178 @@ impl GetEventFromVerificationCodeResponse {
     }
 }
 
+// ---------------------------
+/// A published event, as served to everyone
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicEventResponse {
+    pub id: String,
+    pub event_name: String,
+    pub event_slug: String,
+    pub description: Option<String>,
+    pub start_date: Option<i64>,
+    pub end_date: Option<i64>,
+    pub entry_time: Option<i64>,
+    pub created_at: i64,
+    pub modified_at: i64,
+    pub is_virtual: Option<bool>,
+    pub is_featured: Option<bool>,
+    pub venue_name: Option<String>,
+    pub venue_location: Option<String>,
+    pub cover_photo_url: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub tags: Vec<String>,
+    pub tickets: Vec<Ticket>,
+}
+
+impl PublicEventResponse {
+    pub fn new(db_event: DbEvent, tickets: Vec<DbTicket>, tags: Vec<String>) -> Self {
+        PublicEventResponse {
+            id: db_event.id.to_string(),
+            event_name: db_event.event_name,
+            event_slug: db_event.event_slug,
+            description: db_event.description,
+            start_date: db_event.start_date.map(|date| date.timestamp_millis()),
+            end_date: db_event.end_date.map(|date| date.timestamp_millis()),
+            entry_time: db_event.entry_time.map(|date| date.timestamp_millis()),
+            created_at: db_event.created_at.timestamp_millis(),
+            modified_at: db_event.modified_at.timestamp_millis(),
+            is_virtual: db_event.is_virtual,
+            is_featured: db_event.is_featured,
+            venue_name: db_event.venue_name,
+            venue_location: db_event.venue_location,
+            cover_photo_url: db_event.cover_photo_url,
+            thumbnail_url: db_event.thumbnail_url,
+            tags,
+            tickets: tickets.into_iter().map(Ticket::from).collect(),
+        }
+    }
+}
+
+// -----------USER DATA EXPORT--------------------
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDataExportProfile {
+    pub id: String,
+    pub name: Option<String>,
+    pub username: String,
+    pub phone_number: Option<String>,
+    pub email: Option<String>,
+    pub wallet_id: String,
+    pub wallet_balance: String,
+    pub user_type: String,
+    pub user_status: String,
+    pub created_at: i64,
+}
+
+impl From<DbUser> for UserDataExportProfile {
+    fn from(db_user: DbUser) -> Self {
+        UserDataExportProfile {
+            id: db_user.id.to_string(),
+            name: db_user.name,
+            username: db_user.username,
+            phone_number: db_user.phone_number,
+            email: db_user.email,
+            wallet_id: db_user.wallet_id,
+            wallet_balance: db_user.wallet_balance,
+            user_type: db_user.user_type.to_string(),
+            user_status: db_user.user_status.to_string(),
+            created_at: db_user.created_at.timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDataExportReservation {
+    pub id: String,
+    pub created_at: i64,
+    pub verification_code: String,
+    pub event_id: String,
+    pub ticket_id: String,
+    pub quantity: i32,
+    pub checked_in_at: Option<i64>,
+}
+
+impl From<DbTicketReservation> for UserDataExportReservation {
+    fn from(db_reservation: DbTicketReservation) -> Self {
+        UserDataExportReservation {
+            id: db_reservation.id.to_string(),
+            created_at: db_reservation.created_at.timestamp_millis(),
+            verification_code: db_reservation.verification_code,
+            event_id: db_reservation.event_id.to_string(),
+            ticket_id: db_reservation.ticket_id.to_string(),
+            quantity: db_reservation.quantity,
+            checked_in_at: db_reservation
+                .checked_in_at
+                .map(|checked_in_at| checked_in_at.timestamp_millis()),
+        }
+    }
+}
+
+/// Everything stored about a user, the password and wallet secret excluded
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDataExportResponse {
+    pub exported_at: i64,
+    pub profile: UserDataExportProfile,
+    pub reservations: Vec<UserDataExportReservation>,
+    pub events: Vec<GetEventFromVerificationCodeResponse>,
+}
+
+/// The outcome of an imported event, read from `rows` of the upload
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportEventsRow {
+    pub rows: Vec<usize>,
+    pub event_name: String,
+    pub event_id: Option<String>,
+    pub ticket_ids: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportEventsResponse {
+    pub imported: usize,
+    pub failed: usize,
+    pub rows: Vec<ImportEventsRow>,
+}
+
+/// An uploaded event asset, pending until confirmed as an event image or added to the gallery
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadedAsset {
+    pub asset_id: String,
+    pub content_type: String,
+    pub size: u64,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadEventAssetsResponse {
+    pub assets: Vec<UploadedAsset>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyStatus {
+    pub name: String,
+    pub status: String,
+    pub latency_ms: u128,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReadyResponse {
+    pub status: String,
+    pub dependencies: Vec<DependencyStatus>,
+    /// the state of the channel to the near api, after its probe
+    pub grpc_status: GrpcStatus,
+}
+
 // ---------------------------
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
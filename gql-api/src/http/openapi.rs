@@ -0,0 +1,793 @@
+//! A hand-built OpenAPI 3 document of the `/api/v1/*` REST routes, so that the frontend has a
+//! machine-readable contract of them.
+//!
+//! Every request and response model of [`super::models`] describes itself with [`ApiSchema`],
+//! the routes are listed in [`API_ROUTES`]. Both have to follow the models and the routes.
+use super::models::{
+    BuyerCreateRecoveryCodeRequest, BuyerCreateRecoveryCodeResponse, BuyerRegisterPhoneRequest,
+    BuyerRegisterPhoneResponse, BuyerResendPhoneCodeRequest, BuyerResendPhoneCodeResponse,
+    BuyerSignupRequest, BuyerSignupResponse, BuyerVerifyPhoneRequest, BuyerVerifyPhoneResponse,
+    BuyerVerifyRecoveryCodeRequest, BuyerVerifyRecoveryCodeResponse, CheckUsernameRequest,
+    CheckUsernameResponse, CreateLoginCodeRequest, CreateLoginCodeResponse, ErrorResponse,
+    EventGetVerificationCodeResponse, EventTicketGetVerificationCodeRequest,
+    EventTicketReservation, FieldError, GetEventFromVerificationCodeRequest,
+    GetEventFromVerificationCodeResponse, ImportEventsResponse, ImportEventsRow,
+    PublicEventResponse, PusherAuthRequest, PusherAuthResponse, ReservedTicketPrice,
+    SigninChallengeRequest, SigninChallengeResponse, SigninRequest, SigninResponse,
+    SigninTotpRequiredResponse, SigninVerifyTotpRequest, SigninWithPasswordRequest, Ticket,
+    UploadEventAssetsResponse, UploadedAsset, UserDataExportProfile, UserDataExportReservation,
+    UserDataExportResponse, VerifyLoginCodeRequest, VerifyLoginCodeResponse,
+};
+use crate::{fx::Currency, policy::Operation};
+use serde_json::{json, Map, Value};
+
+const OPENAPI_VERSION: &str = "3.0.3";
+const SCHEMA_REF_PREFIX: &str = "#/components/schemas/";
+
+/// A model described in the components of the OpenAPI document
+pub trait ApiSchema {
+    /// the name the model is referenced by
+    const NAME: &'static str;
+
+    /// the JSON schema of the model's serialization
+    fn schema() -> Value;
+}
+
+/// A property of an object schema
+pub struct Property {
+    name: &'static str,
+    schema: Value,
+    required: bool,
+}
+
+/// A property always serialized
+pub fn required(name: &'static str, schema: Value) -> Property {
+    Property {
+        name,
+        schema,
+        required: true,
+    }
+}
+
+/// A property serialized as `null` or left out when missing
+pub fn optional(name: &'static str, mut schema: Value) -> Property {
+    schema["nullable"] = Value::Bool(true);
+    Property {
+        name,
+        schema,
+        required: false,
+    }
+}
+
+pub fn object(properties: Vec<Property>) -> Value {
+    let required: Vec<&str> = properties
+        .iter()
+        .filter(|property| property.required)
+        .map(|property| property.name)
+        .collect();
+    let properties: Map<String, Value> = properties
+        .into_iter()
+        .map(|property| (property.name.to_string(), property.schema))
+        .collect();
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = json!(required);
+    }
+    schema
+}
+
+pub fn string() -> Value {
+    json!({ "type": "string" })
+}
+
+pub fn uuid() -> Value {
+    json!({ "type": "string", "format": "uuid" })
+}
+
+pub fn boolean() -> Value {
+    json!({ "type": "boolean" })
+}
+
+pub fn int32() -> Value {
+    json!({ "type": "integer", "format": "int32" })
+}
+
+pub fn int64() -> Value {
+    json!({ "type": "integer", "format": "int64" })
+}
+
+/// Milliseconds since the unix epoch, unless stated otherwise
+pub fn timestamp() -> Value {
+    json!({ "type": "integer", "format": "int64", "description": "Unix timestamp in milliseconds" })
+}
+
+/// A date and time without timezone, in UTC
+pub fn date_time() -> Value {
+    json!({ "type": "string", "format": "date-time" })
+}
+
+/// A [`crate::near::NearAmount`]
+pub fn near_amount() -> Value {
+    json!({ "type": "string", "pattern": "^[0-9]+$", "description": "Amount in yoctoNEAR" })
+}
+
+pub fn currency() -> Value {
+    let currencies: Vec<Value> = Currency::ALL
+        .iter()
+        .map(|currency| json!(currency))
+        .collect();
+    json!({ "type": "string", "enum": currencies })
+}
+
+pub fn array(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+/// A reference to the schema of a model in the components
+pub fn schema_ref<T: ApiSchema>() -> Value {
+    json!({ "$ref": format!("{}{}", SCHEMA_REF_PREFIX, T::NAME) })
+}
+
+macro_rules! api_schema {
+    ($model:ident => $schema:expr) => {
+        impl ApiSchema for $model {
+            const NAME: &'static str = stringify!($model);
+
+            fn schema() -> Value {
+                $schema
+            }
+        }
+    };
+}
+
+api_schema!(BuyerCreateRecoveryCodeRequest => object(vec![
+    required("phoneNumber", string()),
+    optional("locale", string()),
+]));
+api_schema!(BuyerCreateRecoveryCodeResponse => object(vec![required("sessionId", uuid())]));
+api_schema!(BuyerVerifyRecoveryCodeRequest => object(vec![
+    required("sessionId", uuid()),
+    required("recoveryCode", string()),
+]));
+api_schema!(BuyerVerifyRecoveryCodeResponse => object(vec![
+    required("encryptedSecretKey", string()),
+    optional("jwt", string()),
+    required("walletId", string()),
+]));
+api_schema!(BuyerRegisterPhoneRequest => object(vec![
+    required("phoneNumber", string()),
+    optional("locale", string()),
+]));
+api_schema!(BuyerRegisterPhoneResponse => object(vec![required("sessionId", uuid())]));
+api_schema!(BuyerVerifyPhoneRequest => object(vec![
+    required("sessionId", uuid()),
+    required("verificationCode", string()),
+]));
+api_schema!(BuyerVerifyPhoneResponse => object(vec![required("isVerified", boolean())]));
+api_schema!(BuyerResendPhoneCodeRequest => object(vec![required("sessionId", uuid())]));
+api_schema!(BuyerResendPhoneCodeResponse => object(vec![
+    required("sessionId", uuid()),
+    required("resendsLeft", int32()),
+]));
+api_schema!(BuyerSignupRequest => object(vec![
+    optional("name", string()),
+    optional("email", string()),
+    required("username", string()),
+    optional("password", string()),
+    required("secret", string()),
+    required("sessionId", uuid()),
+]));
+api_schema!(BuyerSignupResponse => object(vec![
+    required("id", uuid()),
+    optional("name", string()),
+    required("username", string()),
+    optional("phoneNumber", string()),
+    optional("email", string()),
+    required("createdAt", int64()),
+    required("walletId", string()),
+    optional("walletPubKey", string()),
+    optional("walletEncryptedSecretKey", string()),
+    required("walletBalance", near_amount()),
+    required("userType", string()),
+    required("userStatus", string()),
+    optional("jwt", string()),
+]));
+api_schema!(SigninRequest => object(vec![
+    optional("email", string()),
+    optional("name", string()),
+    required("username", string()),
+    optional("phoneNumber", string()),
+    optional("password", string()),
+    optional("signature", string()),
+    optional("walletId", string()),
+    optional("pubKey", string()),
+    optional("nonce", string()),
+]));
+api_schema!(SigninChallengeRequest => object(vec![required("walletId", string())]));
+api_schema!(SigninChallengeResponse => object(vec![
+    required("nonce", string()),
+    required("message", string()),
+    required("expiresAt", date_time()),
+]));
+api_schema!(SigninWithPasswordRequest => object(vec![
+    required("username", string()),
+    required("password", string()),
+]));
+api_schema!(SigninResponse => object(vec![required("token", string())]));
+api_schema!(SigninTotpRequiredResponse => object(vec![
+    required("totpToken", uuid()),
+    required("expiresAt", date_time()),
+]));
+api_schema!(SigninVerifyTotpRequest => object(vec![
+    required("totpToken", uuid()),
+    required("code", string()),
+]));
+api_schema!(CheckUsernameRequest => object(vec![required("username", string())]));
+api_schema!(CheckUsernameResponse => object(vec![required("available", boolean())]));
+api_schema!(CreateLoginCodeRequest => object(vec![]));
+api_schema!(CreateLoginCodeResponse => object(vec![
+    required("code", string()),
+    required("expiresAt", int64()),
+]));
+api_schema!(VerifyLoginCodeRequest => object(vec![
+    required("code", string()),
+    required("signature", string()),
+    required("walletId", string()),
+    required("pubKey", string()),
+]));
+api_schema!(VerifyLoginCodeResponse => object(vec![]));
+api_schema!(PusherAuthRequest => object(vec![
+    required("socket_id", string()),
+    required("channel_name", string()),
+]));
+api_schema!(PusherAuthResponse => object(vec![
+    required("auth", string()),
+    optional("channel_data", string()),
+]));
+api_schema!(EventTicketReservation => object(vec![
+    required("ticketId", uuid()),
+    required("quantity", int64()),
+]));
+api_schema!(EventTicketGetVerificationCodeRequest => object(vec![
+    required("eventId", uuid()),
+    required("reservations", array(schema_ref::<EventTicketReservation>())),
+    optional("promoCode", string()),
+]));
+api_schema!(ReservedTicketPrice => object(vec![
+    required("ticketId", uuid()),
+    required("quantity", int32()),
+    optional("price", near_amount()),
+    optional("effectivePrice", near_amount()),
+]));
+api_schema!(EventGetVerificationCodeResponse => object(vec![
+    required("verificationCode", string()),
+    optional("promoCode", string()),
+    required("prices", array(schema_ref::<ReservedTicketPrice>())),
+]));
+api_schema!(GetEventFromVerificationCodeRequest => object(vec![
+    required("verificationCode", string()),
+]));
+api_schema!(Ticket => object(vec![
+    required("id", uuid()),
+    required("createdAt", timestamp()),
+    required("ticketName", string()),
+    required("ticketSlug", string()),
+    optional("description", string()),
+    optional("price", near_amount()),
+    optional("maxReleasePrice", near_amount()),
+    optional("quantityAvailable", int32()),
+    optional("minPurchaseQuantity", int32()),
+    optional("maxPurchaseQuantity", int32()),
+    optional("allowTransfers", boolean()),
+    required("eventId", uuid()),
+    required("currency", currency()),
+]));
+api_schema!(GetEventFromVerificationCodeResponse => object(vec![
+    required("id", uuid()),
+    required("eventName", string()),
+    required("eventSlug", string()),
+    optional("startDate", timestamp()),
+    optional("endDate", timestamp()),
+    optional("entryTime", timestamp()),
+    required("createdAt", timestamp()),
+    optional("isVirtual", boolean()),
+    optional("isFeatured", boolean()),
+    optional("venueName", string()),
+    optional("venueLocation", string()),
+    optional("coverPhotoUrl", string()),
+    optional("thumbnailUrl", string()),
+    required("eventStatus", string()),
+    required("tickets", array(schema_ref::<Ticket>())),
+]));
+api_schema!(PublicEventResponse => object(vec![
+    required("id", uuid()),
+    required("eventName", string()),
+    required("eventSlug", string()),
+    optional("description", string()),
+    optional("startDate", timestamp()),
+    optional("endDate", timestamp()),
+    optional("entryTime", timestamp()),
+    required("createdAt", timestamp()),
+    required("modifiedAt", timestamp()),
+    optional("isVirtual", boolean()),
+    optional("isFeatured", boolean()),
+    optional("venueName", string()),
+    optional("venueLocation", string()),
+    optional("coverPhotoUrl", string()),
+    optional("thumbnailUrl", string()),
+    required("tags", array(string())),
+    required("tickets", array(schema_ref::<Ticket>())),
+]));
+api_schema!(UserDataExportProfile => object(vec![
+    required("id", uuid()),
+    optional("name", string()),
+    required("username", string()),
+    optional("phoneNumber", string()),
+    optional("email", string()),
+    required("walletId", string()),
+    required("walletBalance", near_amount()),
+    required("userType", string()),
+    required("userStatus", string()),
+    required("createdAt", timestamp()),
+]));
+api_schema!(UserDataExportReservation => object(vec![
+    required("id", uuid()),
+    required("createdAt", timestamp()),
+    required("verificationCode", string()),
+    required("eventId", uuid()),
+    required("ticketId", uuid()),
+    required("quantity", int32()),
+    optional("checkedInAt", timestamp()),
+]));
+api_schema!(UserDataExportResponse => object(vec![
+    required("exportedAt", timestamp()),
+    required("profile", schema_ref::<UserDataExportProfile>()),
+    required("reservations", array(schema_ref::<UserDataExportReservation>())),
+    required("events", array(schema_ref::<GetEventFromVerificationCodeResponse>())),
+]));
+api_schema!(ImportEventsRow => object(vec![
+    required("rows", array(int64())),
+    required("eventName", string()),
+    optional("eventId", uuid()),
+    required("ticketIds", array(uuid())),
+    optional("error", string()),
+]));
+api_schema!(ImportEventsResponse => object(vec![
+    required("imported", int64()),
+    required("failed", int64()),
+    required("rows", array(schema_ref::<ImportEventsRow>())),
+]));
+api_schema!(UploadedAsset => object(vec![
+    required("assetId", uuid()),
+    required("contentType", string()),
+    required("size", int64()),
+    required("url", string()),
+]));
+api_schema!(UploadEventAssetsResponse => object(vec![
+    required("assets", array(schema_ref::<UploadedAsset>())),
+]));
+api_schema!(FieldError => object(vec![
+    required("field", string()),
+    required("fieldErrors", array(string())),
+]));
+api_schema!(ErrorResponse => object(vec![
+    required("message", string()),
+    required("status", string()),
+    optional("errors", array(schema_ref::<FieldError>())),
+]));
+
+fn component<T: ApiSchema>() -> (String, Value) {
+    (T::NAME.to_string(), T::schema())
+}
+
+/// The schemas of every model, by name
+pub fn components() -> Map<String, Value> {
+    vec![
+        component::<BuyerCreateRecoveryCodeRequest>(),
+        component::<BuyerCreateRecoveryCodeResponse>(),
+        component::<BuyerVerifyRecoveryCodeRequest>(),
+        component::<BuyerVerifyRecoveryCodeResponse>(),
+        component::<BuyerRegisterPhoneRequest>(),
+        component::<BuyerRegisterPhoneResponse>(),
+        component::<BuyerVerifyPhoneRequest>(),
+        component::<BuyerVerifyPhoneResponse>(),
+        component::<BuyerResendPhoneCodeRequest>(),
+        component::<BuyerResendPhoneCodeResponse>(),
+        component::<BuyerSignupRequest>(),
+        component::<BuyerSignupResponse>(),
+        component::<SigninRequest>(),
+        component::<SigninChallengeRequest>(),
+        component::<SigninChallengeResponse>(),
+        component::<SigninWithPasswordRequest>(),
+        component::<SigninResponse>(),
+        component::<SigninTotpRequiredResponse>(),
+        component::<SigninVerifyTotpRequest>(),
+        component::<CheckUsernameRequest>(),
+        component::<CheckUsernameResponse>(),
+        component::<CreateLoginCodeRequest>(),
+        component::<CreateLoginCodeResponse>(),
+        component::<VerifyLoginCodeRequest>(),
+        component::<VerifyLoginCodeResponse>(),
+        component::<PusherAuthRequest>(),
+        component::<PusherAuthResponse>(),
+        component::<EventTicketReservation>(),
+        component::<EventTicketGetVerificationCodeRequest>(),
+        component::<ReservedTicketPrice>(),
+        component::<EventGetVerificationCodeResponse>(),
+        component::<GetEventFromVerificationCodeRequest>(),
+        component::<Ticket>(),
+        component::<GetEventFromVerificationCodeResponse>(),
+        component::<PublicEventResponse>(),
+        component::<UserDataExportProfile>(),
+        component::<UserDataExportReservation>(),
+        component::<UserDataExportResponse>(),
+        component::<ImportEventsRow>(),
+        component::<ImportEventsResponse>(),
+        component::<UploadedAsset>(),
+        component::<UploadEventAssetsResponse>(),
+        component::<FieldError>(),
+        component::<ErrorResponse>(),
+    ]
+    .into_iter()
+    .collect()
+}
+
+/// The body of a request or of a successful response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiBody {
+    /// a JSON model, by schema name
+    Json(&'static str),
+    /// one of several JSON models, by schema names
+    OneOf(&'static [&'static str]),
+    /// a url encoded form, by schema name
+    Form(&'static str),
+    /// an uploaded file
+    Multipart,
+    /// uploaded files, one `file` part each
+    MultipartFiles,
+    Csv,
+    Pdf,
+    /// no content, answered with a 204
+    Empty,
+}
+
+/// A REST route of the api
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiRoute {
+    pub method: &'static str,
+    /// the path, with its parameters in braces
+    pub path: &'static str,
+    pub summary: &'static str,
+    /// the operation a bearer token must allow, `None` for public routes
+    pub operation: Option<Operation>,
+    pub request: Option<ApiBody>,
+    pub response: ApiBody,
+}
+
+/// The `/api/v1/*` REST routes, as served by [`super::routes`]
+pub const API_ROUTES: &[ApiRoute] = &[
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/check_username",
+        summary: "Check whether a username is available",
+        operation: None,
+        request: Some(ApiBody::Json("CheckUsernameRequest")),
+        response: ApiBody::Json("CheckUsernameResponse"),
+    },
+    ApiRoute {
+        method: "get",
+        path: "/api/v1/events/{slug}",
+        summary: "Get a published event, revalidated with If-None-Match or If-Modified-Since",
+        operation: None,
+        request: None,
+        response: ApiBody::Json("PublicEventResponse"),
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/events/{slug}/view",
+        summary: "Count a page view of a published event",
+        operation: None,
+        request: None,
+        response: ApiBody::Empty,
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/{role}/phone",
+        summary: "Send a verification code to a new buyer's phone",
+        operation: None,
+        request: Some(ApiBody::Json("BuyerRegisterPhoneRequest")),
+        response: ApiBody::Json("BuyerRegisterPhoneResponse"),
+    },
+    ApiRoute {
+        method: "put",
+        path: "/api/v1/{role}/phone",
+        summary: "Verify a buyer's phone with the sent code",
+        operation: None,
+        request: Some(ApiBody::Json("BuyerVerifyPhoneRequest")),
+        response: ApiBody::Json("BuyerVerifyPhoneResponse"),
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/{role}/phone/resend",
+        summary: "Send the phone verification code again",
+        operation: None,
+        request: Some(ApiBody::Json("BuyerResendPhoneCodeRequest")),
+        response: ApiBody::Json("BuyerResendPhoneCodeResponse"),
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/{role}/signup",
+        summary: "Sign up a buyer with a verified phone",
+        operation: None,
+        request: Some(ApiBody::Json("BuyerSignupRequest")),
+        response: ApiBody::Json("BuyerSignupResponse"),
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/{role}/signin",
+        summary: "Sign in with a wallet signature",
+        operation: None,
+        request: Some(ApiBody::Json("SigninRequest")),
+        response: ApiBody::Json("SigninResponse"),
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/{role}/signin/challenge",
+        summary: "Get a nonce to sign in with a wallet signature",
+        operation: None,
+        request: Some(ApiBody::Json("SigninChallengeRequest")),
+        response: ApiBody::Json("SigninChallengeResponse"),
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/{role}/signin_with_pwd",
+        summary: "Sign in with a username and password",
+        operation: None,
+        request: Some(ApiBody::Json("SigninWithPasswordRequest")),
+        response: ApiBody::OneOf(&["SigninResponse", "SigninTotpRequiredResponse"]),
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/{role}/signin_with_pwd/verify_totp",
+        summary: "Complete a password sign-in with a two-factor authentication code",
+        operation: None,
+        request: Some(ApiBody::Json("SigninVerifyTotpRequest")),
+        response: ApiBody::Json("SigninResponse"),
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/{role}/login",
+        summary: "Create a login code to sign with a wallet",
+        operation: None,
+        request: Some(ApiBody::Json("CreateLoginCodeRequest")),
+        response: ApiBody::Json("CreateLoginCodeResponse"),
+    },
+    ApiRoute {
+        method: "put",
+        path: "/api/v1/{role}/login",
+        summary: "Verify a signed login code",
+        operation: None,
+        request: Some(ApiBody::Json("VerifyLoginCodeRequest")),
+        response: ApiBody::Json("VerifyLoginCodeResponse"),
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/pusher/auth",
+        summary: "Authorize a pusher subscription to a private or presence channel, with a bearer \
+                  token for the user channels",
+        operation: None,
+        request: Some(ApiBody::Form("PusherAuthRequest")),
+        response: ApiBody::Json("PusherAuthResponse"),
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/{role}/event_ticket_get_verification_code",
+        summary: "Reserve tickets of an event",
+        operation: Some(Operation::EventTicketGetVerificationCode),
+        request: Some(ApiBody::Json("EventTicketGetVerificationCodeRequest")),
+        response: ApiBody::Json("EventGetVerificationCodeResponse"),
+    },
+    ApiRoute {
+        method: "put",
+        path: "/api/v1/{role}/get_event_from_verification_code",
+        summary: "Get the event and tickets of a reservation",
+        operation: Some(Operation::GetEventFromVerificationCode),
+        request: Some(ApiBody::Json("GetEventFromVerificationCodeRequest")),
+        response: ApiBody::Json("GetEventFromVerificationCodeResponse"),
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/{role}/recover",
+        summary: "Send a recovery code to a buyer's phone",
+        operation: None,
+        request: Some(ApiBody::Json("BuyerCreateRecoveryCodeRequest")),
+        response: ApiBody::Json("BuyerCreateRecoveryCodeResponse"),
+    },
+    ApiRoute {
+        method: "put",
+        path: "/api/v1/{role}/recover",
+        summary: "Recover a buyer's wallet with the sent code",
+        operation: None,
+        request: Some(ApiBody::Json("BuyerVerifyRecoveryCodeRequest")),
+        response: ApiBody::Json("BuyerVerifyRecoveryCodeResponse"),
+    },
+    ApiRoute {
+        method: "get",
+        path: "/api/v1/{role}/export",
+        summary: "Export everything stored about the calling user",
+        operation: Some(Operation::ExportMyData),
+        request: None,
+        response: ApiBody::Json("UserDataExportResponse"),
+    },
+    ApiRoute {
+        method: "get",
+        path: "/api/v1/seller/events/{event_id}/attendees.csv",
+        summary: "Download the attendees of an event",
+        operation: Some(Operation::ExportAttendees),
+        request: None,
+        response: ApiBody::Csv,
+    },
+    ApiRoute {
+        method: "get",
+        path: "/api/v1/buyer/tickets/{reservation_id}.pdf",
+        summary: "Download a reserved ticket as a PDF",
+        operation: Some(Operation::DownloadTicket),
+        request: None,
+        response: ApiBody::Pdf,
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/seller/import",
+        summary: "Import events and their tickets from a CSV upload",
+        operation: Some(Operation::ImportEvents),
+        request: Some(ApiBody::Multipart),
+        response: ApiBody::Json("ImportEventsResponse"),
+    },
+    ApiRoute {
+        method: "post",
+        path: "/api/v1/seller/events/{event_id}/assets",
+        summary: "Upload images of a draft event, attached once confirmed",
+        operation: Some(Operation::UploadEventAssets),
+        request: Some(ApiBody::MultipartFiles),
+        response: ApiBody::Json("UploadEventAssetsResponse"),
+    },
+];
+
+fn content(body: ApiBody) -> Value {
+    match body {
+        ApiBody::Json(name) => json!({
+            "application/json": { "schema": { "$ref": format!("{}{}", SCHEMA_REF_PREFIX, name) } }
+        }),
+        ApiBody::OneOf(names) => json!({
+            "application/json": {
+                "schema": {
+                    "oneOf": names
+                        .iter()
+                        .map(|name| json!({ "$ref": format!("{}{}", SCHEMA_REF_PREFIX, name) }))
+                        .collect::<Vec<_>>()
+                }
+            }
+        }),
+        ApiBody::Form(name) => json!({
+            "application/x-www-form-urlencoded": {
+                "schema": { "$ref": format!("{}{}", SCHEMA_REF_PREFIX, name) }
+            }
+        }),
+        ApiBody::Multipart => json!({
+            "multipart/form-data": {
+                "schema": object(vec![required("file", json!({ "type": "string", "format": "binary" }))])
+            }
+        }),
+        ApiBody::MultipartFiles => json!({
+            "multipart/form-data": {
+                "schema": object(vec![required(
+                    "file",
+                    array(json!({ "type": "string", "format": "binary" }))
+                )])
+            }
+        }),
+        ApiBody::Csv => json!({ "text/csv": { "schema": string() } }),
+        ApiBody::Pdf => json!({
+            "application/pdf": { "schema": { "type": "string", "format": "binary" } }
+        }),
+        ApiBody::Empty => json!({}),
+    }
+}
+
+fn parameters(path: &str) -> Vec<Value> {
+    path.split('/')
+        // a parameter may come with a file extension, as `{reservation_id}.pdf`
+        .filter_map(|segment| Some(segment.split_once('{')?.1.split_once('}')?.0))
+        .map(|name| {
+            let schema = match name {
+                "role" => {
+                    json!({ "type": "string", "enum": ["buyer", "seller", "admin", "superadmin"] })
+                }
+                "slug" => string(),
+                _ => uuid(),
+            };
+            json!({ "name": name, "in": "path", "required": true, "schema": schema })
+        })
+        .collect()
+}
+
+fn operation(route: &ApiRoute) -> Value {
+    let error = json!({ "content": content(ApiBody::Json(ErrorResponse::NAME)) });
+    let mut responses = json!({
+        "400": { "description": "Invalid request", "content": error["content"] },
+        "500": { "description": "Internal error", "content": error["content"] },
+    });
+    match route.response {
+        ApiBody::Empty => responses["204"] = json!({ "description": "No Content" }),
+        body => responses["200"] = json!({ "description": "OK", "content": content(body) }),
+    }
+    let mut operation = json!({
+        "summary": route.summary,
+        "parameters": parameters(route.path),
+    });
+    if let Some(body) = route.request {
+        operation["requestBody"] = json!({ "required": true, "content": content(body) });
+        if let ApiBody::Json(_) = body {
+            responses["413"] =
+                json!({ "description": "Body too large", "content": error["content"] });
+            responses["415"] =
+                json!({ "description": "Body is not json", "content": error["content"] });
+        }
+    }
+    if let Some(authorized) = route.operation {
+        operation["operationId"] = json!(authorized.to_string());
+        operation["security"] = json!([{ "bearerAuth": [] }]);
+        responses["401"] =
+            json!({ "description": "Missing or invalid token", "content": error["content"] });
+        responses["403"] = json!({ "description": "Not allowed", "content": error["content"] });
+    }
+    operation["responses"] = responses;
+    operation
+}
+
+/// The OpenAPI document of the REST routes
+pub fn openapi_document() -> Value {
+    let mut paths = Map::new();
+    for route in API_ROUTES {
+        let path = paths
+            .entry(route.path.to_string())
+            .or_insert_with(|| json!({}));
+        path[route.method] = operation(route);
+    }
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": "gql-api REST api",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": components(),
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+            },
+        },
+    })
+}
+
+/// A swagger UI page of the document served at `openapi_url`
+pub fn swagger_ui_html(openapi_url: &str) -> String {
+    format!(
+        r##"<!DOCTYPE html>
+<html>
+<head>
+  <title>gql-api REST api</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@4/swagger-ui.css" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@4/swagger-ui-bundle.js"></script>
+  <script>
+    window.ui = SwaggerUIBundle({{ url: "{}", dom_id: "#swagger-ui" }});
+  </script>
+</body>
+</html>"##,
+        openapi_url
+    )
+}
@@ -1,19 +1,32 @@
+use super::caching::{cached_json_reply, with_cache_validators};
 use super::handlers::{
     buyer_create_recovery_code as buyer_create_recovery_code_handler,
-    buyer_register_phone as buyer_register_phone_handler, buyer_signup as buyer_signup_handler,
-    buyer_verify_phone as buyer_verify_phone_handler,
+    buyer_register_phone as buyer_register_phone_handler,
+    buyer_resend_phone_code as buyer_resend_phone_code_handler,
+    buyer_signup as buyer_signup_handler, buyer_verify_phone as buyer_verify_phone_handler,
     buyer_verify_recovery_code as buyer_verify_recovery_code_handler,
     check_username as check_username_handler, create_login_code as create_login_code_handler,
+    event_attendees_csv as event_attendees_csv_handler,
     event_ticket_get_verification_code as event_ticket_get_verification_code_handler,
+    event_view as event_view_handler, export_my_data as export_my_data_handler,
     get_event_from_verification_code as get_event_from_verification_code_handler,
-    health as health_handler, signin as signin_handler,
+    health as health_handler, health_live as health_live_handler,
+    health_ready as health_ready_handler, import_events as import_events_handler,
+    metrics as metrics_handler, public_event as public_event_handler,
+    pusher_auth as pusher_auth_handler, signin as signin_handler,
+    signin_challenge as signin_challenge_handler,
     signin_with_password as signin_with_password_handler,
+    signin_with_password_verify_totp as signin_with_password_verify_totp_handler,
+    ticket_pdf as ticket_pdf_handler, upload_event_assets as upload_event_assets_handler,
     verify_login_code as verify_login_code_handler,
 };
+use super::import::MAX_IMPORT_BYTES;
+use super::openapi::{openapi_document, swagger_ui_html};
 use crate::{
-    auth::Role,
-    filters::{with_auth, with_resources_context},
+    config::CachePolicy,
+    filters::{with_auth, with_client_ip, with_enabled, with_json_body, with_resources_context},
     gql::schema::Context as ResourcesContext,
+    policy::Operation,
 };
 use std::sync::Arc;
 use warp::{
@@ -27,25 +40,56 @@ pub fn check_username_route(
     resources_ctx: Arc<ResourcesContext>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
     let check_username_route = warp::post()
         .and(warp::path!("api" / "v1" / "check_username"))
         .and(with_resources_context(resources_ctx))
-        .and(warp::body::aggregate())
+        .and(with_json_body(body_limit))
         .and_then(check_username_handler)
         .with(logger);
 
     check_username_route
 }
 
+/// GET /events/{slug}
+pub fn public_event_route(
+    resources_ctx: Arc<ResourcesContext>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let public_event_route = warp::get()
+        .and(warp::path!("api" / "v1" / "events" / String))
+        .and(with_resources_context(resources_ctx))
+        .and(with_cache_validators())
+        .and_then(public_event_handler)
+        .with(logger);
+
+    public_event_route
+}
+
+/// POST /events/{slug}/view
+pub fn event_view_route(
+    resources_ctx: Arc<ResourcesContext>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let event_view_route = warp::post()
+        .and(warp::path!("api" / "v1" / "events" / String / "view"))
+        .and(with_resources_context(resources_ctx))
+        .and_then(event_view_handler)
+        .with(logger);
+
+    event_view_route
+}
+
 /// POST /buyer/register-phone
 pub fn buyer_register_phone_route(
     resources_ctx: Arc<ResourcesContext>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
     let buyer_register_phone_route = warp::post()
         .and(warp::path!("api" / "v1" / String / "phone"))
         .and(with_resources_context(resources_ctx))
-        .and(warp::body::aggregate())
+        .and(with_json_body(body_limit))
         .and_then(buyer_register_phone_handler)
         .with(logger);
 
@@ -57,25 +101,43 @@ pub fn buyer_verify_phone_route(
     resources_ctx: Arc<ResourcesContext>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
     let buyer_verify_phone_route = warp::put()
         .and(warp::path!("api" / "v1" / String / "phone"))
         .and(with_resources_context(resources_ctx))
-        .and(warp::body::aggregate())
+        .and(with_json_body(body_limit))
         .and_then(buyer_verify_phone_handler)
         .with(logger);
 
     buyer_verify_phone_route
 }
 
+/// POST /buyer/phone/resend
+pub fn buyer_resend_phone_code_route(
+    resources_ctx: Arc<ResourcesContext>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
+    let buyer_resend_phone_code_route = warp::post()
+        .and(warp::path!("api" / "v1" / String / "phone" / "resend"))
+        .and(with_resources_context(resources_ctx))
+        .and(with_json_body(body_limit))
+        .and_then(buyer_resend_phone_code_handler)
+        .with(logger);
+
+    buyer_resend_phone_code_route
+}
+
 /// POST /buyer/signup
 pub fn buyer_signup_route(
     resources_ctx: Arc<ResourcesContext>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
     let signup_route = warp::post()
         .and(warp::path!("api" / "v1" / String / "signup"))
         .and(with_resources_context(resources_ctx))
-        .and(warp::body::aggregate())
+        .and(with_json_body(body_limit))
         .and_then(buyer_signup_handler)
         .with(logger);
 
@@ -87,40 +149,80 @@ pub fn signin_route(
     resources_ctx: Arc<ResourcesContext>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
     let signin_route = warp::post()
         .and(warp::path!("api" / "v1" / String / "signin"))
         .and(with_resources_context(resources_ctx))
-        .and(warp::body::aggregate())
+        .and(with_json_body(body_limit))
         .and_then(signin_handler)
         .with(logger);
 
     signin_route
 }
 
+/// POST /signin/challenge
+pub fn signin_challenge_route(
+    resources_ctx: Arc<ResourcesContext>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
+    let signin_challenge_route = warp::post()
+        .and(warp::path!("api" / "v1" / String / "signin" / "challenge"))
+        .and(with_resources_context(resources_ctx))
+        .and(with_json_body(body_limit))
+        .and_then(signin_challenge_handler)
+        .with(logger);
+
+    signin_challenge_route
+}
+
 /// POST /signin_with_pwd
 pub fn signin_with_password_route(
     resources_ctx: Arc<ResourcesContext>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
     let signin_with_pwd_route = warp::post()
         .and(warp::path!("api" / "v1" / String / "signin_with_pwd"))
+        .and(with_client_ip(
+            resources_ctx.login_lockout.trust_forwarded_for,
+        ))
         .and(with_resources_context(resources_ctx))
-        .and(warp::body::aggregate())
+        .and(with_json_body(body_limit))
         .and_then(signin_with_password_handler)
         .with(logger);
 
     signin_with_pwd_route
 }
 
+/// POST /signin_with_pwd/verify_totp
+pub fn signin_with_password_verify_totp_route(
+    resources_ctx: Arc<ResourcesContext>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
+    let verify_totp_route = warp::post()
+        .and(warp::path!(
+            "api" / "v1" / String / "signin_with_pwd" / "verify_totp"
+        ))
+        .and(with_resources_context(resources_ctx))
+        .and(with_json_body(body_limit))
+        .and_then(signin_with_password_verify_totp_handler)
+        .with(logger);
+
+    verify_totp_route
+}
+
 /// POST /login
 pub fn create_login_code_route(
     resources_ctx: Arc<ResourcesContext>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
     let create_login_code_route = warp::post()
         .and(warp::path!("api" / "v1" / String / "login"))
         .and(with_resources_context(resources_ctx))
-        .and(warp::body::aggregate())
+        .and(with_json_body(body_limit))
         .and_then(create_login_code_handler)
         .with(logger);
 
@@ -132,56 +234,161 @@ pub fn verify_login_code_route(
     resources_ctx: Arc<ResourcesContext>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
     let verify_login_code_route = warp::put()
         .and(warp::path!("api" / "v1" / String / "login"))
         .and(with_resources_context(resources_ctx))
-        .and(warp::body::aggregate())
+        .and(with_json_body(body_limit))
         .and_then(verify_login_code_handler)
         .with(logger);
 
     verify_login_code_route
 }
 
+/// POST /pusher/auth
+pub fn pusher_auth_route(
+    resources_ctx: Arc<ResourcesContext>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
+    let pusher_auth_route = warp::post()
+        .and(warp::path!("api" / "v1" / "pusher" / "auth"))
+        .and(warp::header::optional::<String>("authorization"))
+        .and(with_resources_context(resources_ctx))
+        .and(warp::body::content_length_limit(body_limit))
+        .and(warp::body::form())
+        .and_then(pusher_auth_handler)
+        .with(logger);
+
+    pusher_auth_route
+}
+
 /// POST /event_ticket_get_verification_code
 pub fn event_ticket_get_verification_code_route(
     resources_ctx: Arc<ResourcesContext>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
     let event_ticket_get_verification_code_route = warp::post()
         .and(warp::path!(
             "api" / "v1" / String / "event_ticket_get_verification_code"
         ))
-        .and(with_resources_context(resources_ctx))
-        .and(warp::body::aggregate())
-        .and(with_auth(vec![
-            Role::Admin,
-            Role::Buyer,
-            Role::Seller,
-            Role::SuperAdmin,
-        ]))
+        .and(with_resources_context(resources_ctx.clone()))
+        .and(with_json_body(body_limit))
+        .and(with_auth(
+            Operation::EventTicketGetVerificationCode,
+            resources_ctx,
+        ))
         .and_then(event_ticket_get_verification_code_handler)
         .with(logger);
 
     event_ticket_get_verification_code_route
 }
 
+/// GET /export
+pub fn export_my_data_route(
+    resources_ctx: Arc<ResourcesContext>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let export_my_data_route = warp::get()
+        .and(warp::path!("api" / "v1" / String / "export"))
+        .and(with_resources_context(resources_ctx.clone()))
+        .and(with_auth(Operation::ExportMyData, resources_ctx))
+        .and_then(export_my_data_handler)
+        .with(logger);
+
+    export_my_data_route
+}
+
+/// GET /seller/events/{id}/attendees.csv
+pub fn event_attendees_csv_route(
+    resources_ctx: Arc<ResourcesContext>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let event_attendees_csv_route = warp::get()
+        .and(warp::path!(
+            "api" / "v1" / "seller" / "events" / uuid::Uuid / "attendees.csv"
+        ))
+        .and(with_resources_context(resources_ctx.clone()))
+        .and(with_auth(Operation::ExportAttendees, resources_ctx))
+        .and_then(event_attendees_csv_handler)
+        .with(logger);
+
+    event_attendees_csv_route
+}
+
+/// GET /buyer/tickets/{reservation_id}.pdf
+pub fn ticket_pdf_route(
+    resources_ctx: Arc<ResourcesContext>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    // the reservation id comes with the file extension, the handler parses it
+    let ticket_pdf_route = warp::get()
+        .and(warp::path!("api" / "v1" / "buyer" / "tickets" / String))
+        .and(with_resources_context(resources_ctx.clone()))
+        .and(with_auth(Operation::DownloadTicket, resources_ctx))
+        .and_then(ticket_pdf_handler)
+        .with(logger);
+
+    ticket_pdf_route
+}
+
+/// POST /seller/import
+pub fn import_events_route(
+    resources_ctx: Arc<ResourcesContext>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let import_events_route = warp::post()
+        .and(warp::path!("api" / "v1" / "seller" / "import"))
+        .and(with_resources_context(resources_ctx.clone()))
+        .and(warp::multipart::form().max_length(MAX_IMPORT_BYTES))
+        .and(with_auth(Operation::ImportEvents, resources_ctx))
+        .and_then(import_events_handler)
+        .with(logger);
+
+    import_events_route
+}
+
+/// POST /seller/events/{event_id}/assets (multipart, one `file` part per image)
+pub fn upload_event_assets_route(
+    resources_ctx: Arc<ResourcesContext>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    // the files are limited one by one while streaming, the form as a whole has room for the
+    // part headers on top
+    let body_limits = &resources_ctx.body_limits;
+    let form_limit = body_limits
+        .asset_bytes
+        .saturating_mul(body_limits.asset_files as u64 + 1);
+    let upload_event_assets_route = warp::post()
+        .and(warp::path!(
+            "api" / "v1" / "seller" / "events" / uuid::Uuid / "assets"
+        ))
+        .and(with_resources_context(resources_ctx.clone()))
+        .and(warp::multipart::form().max_length(form_limit))
+        .and(with_auth(Operation::UploadEventAssets, resources_ctx))
+        .and_then(upload_event_assets_handler)
+        .with(logger);
+
+    upload_event_assets_route
+}
+
 /// PUT /get_event_from_verification_code
 pub fn get_event_from_verification_code_route(
     resources_ctx: Arc<ResourcesContext>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
     let get_event_from_verification_code_route = warp::put()
         .and(warp::path!(
             "api" / "v1" / String / "get_event_from_verification_code"
         ))
-        .and(with_resources_context(resources_ctx))
-        .and(warp::body::aggregate())
-        .and(with_auth(vec![
-            Role::Admin,
-            Role::Buyer,
-            Role::Seller,
-            Role::SuperAdmin,
-        ]))
+        .and(with_resources_context(resources_ctx.clone()))
+        .and(with_json_body(body_limit))
+        .and(with_auth(
+            Operation::GetEventFromVerificationCode,
+            resources_ctx,
+        ))
         .and_then(get_event_from_verification_code_handler)
         .with(logger);
 
@@ -193,10 +400,11 @@ pub fn buyer_create_recovery_code_route(
     resources_ctx: Arc<ResourcesContext>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
     let buyer_create_recovery_code_route = warp::post()
         .and(warp::path!("api" / "v1" / String / "recover"))
         .and(with_resources_context(resources_ctx))
-        .and(warp::body::aggregate())
+        .and(with_json_body(body_limit))
         .and_then(buyer_create_recovery_code_handler)
         .with(logger);
 
@@ -208,10 +416,11 @@ pub fn buyer_verify_recovery_code_route(
     resources_ctx: Arc<ResourcesContext>,
     logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
 ) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let body_limit = resources_ctx.body_limits.json_bytes;
     let buyer_verify_recovery_code_route = warp::put()
         .and(warp::path!("api" / "v1" / String / "recover"))
         .and(with_resources_context(resources_ctx))
-        .and(warp::body::aggregate())
+        .and(with_json_body(body_limit))
         .and_then(buyer_verify_recovery_code_handler)
         .with(logger);
 
@@ -231,6 +440,75 @@ pub fn healthcheck_route(
     healthcheck_route
 }
 
+/// GET /health/live
+pub fn health_live_route(
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let health_live_route = warp::get()
+        .and(warp::path!("health" / "live"))
+        .and_then(health_live_handler)
+        .with(logger);
+
+    health_live_route
+}
+
+/// GET /health/ready
+pub fn health_ready_route(
+    resources_ctx: Arc<ResourcesContext>,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let health_ready_route = warp::get()
+        .and(warp::path!("health" / "ready"))
+        .and(with_resources_context(resources_ctx))
+        .and_then(health_ready_handler)
+        .with(logger);
+
+    health_ready_route
+}
+
+/// GET /metrics
+pub fn metrics_route(
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let metrics_route = warp::get()
+        .and(warp::path!("metrics"))
+        .and_then(metrics_handler)
+        .with(logger);
+
+    metrics_route
+}
+
+/// GET /openapi.json
+pub fn openapi_route(
+    policy: CachePolicy,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    // the routes do not change at runtime, render the document once
+    let document = openapi_document();
+    let openapi_route = warp::get()
+        .and(warp::path!("api" / "v1" / "openapi.json"))
+        .and(with_cache_validators())
+        .map(move |validators| cached_json_reply(&document, None, &policy, &validators))
+        .with(logger);
+
+    openapi_route
+}
+
+/// GET /docs
+pub fn swagger_ui_route(
+    enabled: bool,
+    logger: Log<impl Fn(Info<'_>) + Copy + Send + 'static>,
+) -> impl Filter<Extract = impl warp::Reply + 'static, Error = warp::Rejection> + Clone + 'static {
+    let html = swagger_ui_html("/api/v1/openapi.json");
+    let swagger_ui_route = warp::get()
+        .and(warp::path!("api" / "v1" / "docs"))
+        .and(with_enabled(enabled))
+        .map(move || warp::reply::html(html.clone()))
+        .with(logger);
+
+    swagger_ui_route
+}
+
 /// GET /
 pub fn homepage_route(
     logger: Log<impl Fn(Info<'_>) + Copy + Send>,
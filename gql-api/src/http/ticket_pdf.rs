@@ -0,0 +1,210 @@
+//! PDF tickets for buyers, with the signed qr code the entrance scans.
+//!
+//! A ticket is a single A6 page written out by hand: its text is set in the standard Helvetica
+//! fonts, which every reader has, so that no font is embedded. Those only cover Latin-1, the
+//! other characters are printed as `?`. The qr code is drawn as vector squares.
+use crate::db::models::{DbEvent, DbTicket, DbTicketReservation};
+use chrono::NaiveDateTime;
+use qrcode::{types::QrError, Color, EcLevel, QrCode};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+
+/// Content type of the tickets
+pub const TICKET_PDF_CONTENT_TYPE: &str = "application/pdf";
+
+/// A6, in points
+const PAGE_WIDTH: f64 = 298.0;
+const PAGE_HEIGHT: f64 = 420.0;
+const MARGIN: f64 = 20.0;
+/// The side of the qr code, its quiet zone included
+const QR_SIZE: f64 = 170.0;
+/// Modules of blank around the qr code, as scanners expect
+const QR_QUIET_ZONE: usize = 4;
+
+/// What a ticket shows
+pub struct TicketPdf<'a> {
+    pub event: &'a DbEvent,
+    pub ticket: &'a DbTicket,
+    pub reservation: &'a DbTicketReservation,
+    /// the username of the buyer
+    pub holder: &'a str,
+    /// the signed payload of the qr code
+    pub qr_payload: &'a str,
+}
+
+/// The storage key the ticket is cached under. It changes along with the event, its tickets and
+/// the payload, so that a stale ticket is never served
+pub fn ticket_pdf_key(
+    prefix: &str,
+    reservation_id: &uuid::Uuid,
+    qr_payload: &str,
+    event_modified_at: &NaiveDateTime,
+) -> String {
+    let version = Sha256::new()
+        .chain_update(qr_payload.as_bytes())
+        .chain_update(event_modified_at.timestamp_millis().to_be_bytes())
+        .finalize();
+    format!(
+        "{}/{}/{}.pdf",
+        prefix.trim_end_matches('/'),
+        reservation_id,
+        &hex::encode(version)[..16]
+    )
+}
+
+/// A PDF string literal of the text, in the WinAnsi encoding of the standard fonts
+fn pdf_string(text: &str) -> Vec<u8> {
+    let mut string = vec![b'('];
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => string.extend_from_slice(&[b'\\', c as u8]),
+            // Latin-1 is the same in WinAnsi, but for the C1 controls
+            c if (' '..='~').contains(&c) || ('\u{a0}'..='\u{ff}').contains(&c) => {
+                string.push(c as u32 as u8)
+            }
+            _ => string.push(b'?'),
+        }
+    }
+    string.push(b')');
+    string
+}
+
+/// The text, cut to `max_chars` with an ellipsis
+fn fit(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut fitted = text.chars().take(max_chars - 3).collect::<String>();
+    fitted.push_str("...");
+    fitted
+}
+
+fn text_line(content: &mut Vec<u8>, font: &str, size: f64, y: f64, text: &str) {
+    write!(content, "BT /{} {} Tf {} {} Td ", font, size, MARGIN, y).expect("writes to memory");
+    content.extend_from_slice(&pdf_string(text));
+    content.extend_from_slice(b" Tj ET\n");
+}
+
+/// Draws the qr code, centered at the bottom of the page
+fn qr_code(content: &mut Vec<u8>, qr_payload: &str) -> Result<(), QrError> {
+    let code = QrCode::with_error_correction_level(qr_payload, EcLevel::M)?;
+    let width = code.width();
+    let module = QR_SIZE / (width + 2 * QR_QUIET_ZONE) as f64;
+    let left = (PAGE_WIDTH - QR_SIZE) / 2.0 + QR_QUIET_ZONE as f64 * module;
+    let top = MARGIN + QR_SIZE - QR_QUIET_ZONE as f64 * module;
+
+    content.extend_from_slice(b"0 g\n");
+    for (i, color) in code.to_colors().into_iter().enumerate() {
+        if color == Color::Dark {
+            let (x, y) = (i % width, i / width);
+            writeln!(
+                content,
+                "{:.2} {:.2} {:.2} {:.2} re",
+                left + x as f64 * module,
+                top - (y + 1) as f64 * module,
+                module,
+                module
+            )
+            .expect("writes to memory");
+        }
+    }
+    content.extend_from_slice(b"f\n");
+    Ok(())
+}
+
+/// Renders the ticket, an error if its payload does not fit in a qr code
+pub fn render_ticket_pdf(ticket: &TicketPdf) -> Result<Vec<u8>, QrError> {
+    let mut content = vec![];
+    let mut y = PAGE_HEIGHT - MARGIN - 16.0;
+    text_line(
+        &mut content,
+        "F2",
+        16.0,
+        y,
+        &fit(&ticket.event.event_name, 28),
+    );
+    y -= 22.0;
+    if let Some(start_date) = ticket.event.start_date {
+        let date = start_date.format("%a, %d %b %Y %H:%M").to_string();
+        text_line(&mut content, "F1", 10.0, y, &date);
+        y -= 14.0;
+    }
+    for venue in [&ticket.event.venue_name, &ticket.event.venue_location]
+        .into_iter()
+        .flatten()
+    {
+        text_line(&mut content, "F1", 10.0, y, &fit(venue, 48));
+        y -= 14.0;
+    }
+    y -= 8.0;
+    text_line(
+        &mut content,
+        "F2",
+        12.0,
+        y,
+        &fit(&ticket.ticket.ticket_name, 36),
+    );
+    y -= 16.0;
+    let admits = format!("Admits {}", ticket.reservation.quantity);
+    text_line(&mut content, "F1", 10.0, y, &admits);
+    y -= 14.0;
+    text_line(&mut content, "F1", 10.0, y, &fit(ticket.holder, 48));
+    y -= 14.0;
+    let code = format!("Code {}", ticket.reservation.verification_code);
+    text_line(&mut content, "F1", 10.0, y, &code);
+    qr_code(&mut content, ticket.qr_payload)?;
+
+    Ok(pdf_document(&content))
+}
+
+/// A one page document of the content stream
+fn pdf_document(content: &[u8]) -> Vec<u8> {
+    let objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 4 0 R /F2 5 0 R >> >> /Contents 6 0 R >>",
+            PAGE_WIDTH, PAGE_HEIGHT
+        )
+        .into_bytes(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+            .to_vec(),
+        [
+            format!("<< /Length {} >>\nstream\n", content.len()).as_bytes(),
+            content,
+            &b"\nendstream"[..],
+        ]
+        .concat(),
+    ];
+
+    // the binary comment tells transfer tools the file is not text
+    let mut document = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(document.len());
+        writeln!(document, "{} 0 obj", i + 1).expect("writes to memory");
+        document.extend_from_slice(object);
+        document.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = document.len();
+    writeln!(
+        document,
+        "xref\n0 {}\n0000000000 65535 f ",
+        objects.len() + 1
+    )
+    .expect("writes to memory");
+    for offset in offsets {
+        writeln!(document, "{:010} 00000 n ", offset).expect("writes to memory");
+    }
+    writeln!(
+        document,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF",
+        objects.len() + 1,
+        xref
+    )
+    .expect("writes to memory");
+    document
+}
@@ -0,0 +1,123 @@
+//! Multipart uploads of event assets.
+//!
+//! The form is parsed as it streams in. S3 needs the length of an object before taking it, which
+//! a part does not tell, so every file is written to a temporary file as it arrives and streamed
+//! from there to the storage. Only a chunk of a file is held in memory at a time.
+use crate::{
+    error::{Error, RequestError, StorageError},
+    storage::ByteStream,
+};
+use bytes::{Buf, BytesMut};
+use futures::{stream, TryStreamExt};
+use image::ImageFormat;
+use std::path::PathBuf;
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncWriteExt},
+};
+use warp::multipart::Part;
+
+/// The form part every uploaded file comes in
+pub const ASSET_PART_NAME: &str = "file";
+
+/// Bytes read back from a temporary file at once
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// Bytes the image format is recognized from
+const SNIFF_BYTES: usize = 16;
+
+/// An uploaded file, its temporary file is removed once dropped
+pub struct SpooledAsset {
+    path: PathBuf,
+    pub content_type: String,
+    /// the extension of the content type
+    pub extension: &'static str,
+    pub size: u64,
+}
+
+impl SpooledAsset {
+    /// the content, read back from the temporary file
+    pub async fn stream(&self) -> Result<ByteStream, StorageError> {
+        let file = File::open(&self.path)
+            .await
+            .map_err(|e| StorageError::Request(e.to_string()))?;
+        let content = stream::try_unfold(file, |mut file| async move {
+            let mut chunk = BytesMut::with_capacity(CHUNK_BYTES);
+            let read = file
+                .read_buf(&mut chunk)
+                .await
+                .map_err(|e| StorageError::Request(e.to_string()))?;
+            Ok((read > 0).then(|| (chunk.freeze(), file)))
+        });
+        Ok(Box::pin(content))
+    }
+}
+
+impl Drop for SpooledAsset {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// The extension of an accepted image content type, with the format its content must have
+pub fn asset_format(content_type: &str) -> Option<(&'static str, ImageFormat)> {
+    match content_type {
+        "image/jpeg" => Some(("jpg", ImageFormat::Jpeg)),
+        "image/png" => Some(("png", ImageFormat::Png)),
+        "image/webp" => Some(("webp", ImageFormat::WebP)),
+        _ => None,
+    }
+}
+
+fn invalid_upload(message: impl Into<String>) -> Error {
+    Error::Request(RequestError::InvalidUpload(message.into()))
+}
+
+/// Writes a file part to a temporary file. The file must be at most `max_bytes` long and its
+/// content must be the image its content type names
+pub async fn spool_asset(part: Part, max_bytes: u64) -> Result<SpooledAsset, Error> {
+    let content_type = part.content_type().unwrap_or_default().to_string();
+    let (extension, format) = asset_format(&content_type).ok_or_else(|| {
+        invalid_upload("only image/jpeg, image/png and image/webp files are allowed")
+    })?;
+
+    let mut spooled = SpooledAsset {
+        path: std::env::temp_dir().join(format!("asset-{}", uuid::Uuid::new_v4())),
+        content_type,
+        extension,
+        size: 0,
+    };
+    let storage_error = |e: std::io::Error| Error::Storage(StorageError::Request(e.to_string()));
+    let mut file = File::create(&spooled.path).await.map_err(storage_error)?;
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    let mut data = part.stream();
+    while let Some(mut buf) = data
+        .try_next()
+        .await
+        .map_err(|e| invalid_upload(e.to_string()))?
+    {
+        let chunk = buf.copy_to_bytes(buf.remaining());
+        spooled.size += chunk.len() as u64;
+        if spooled.size > max_bytes {
+            return Err(invalid_upload(format!(
+                "files must be at most {} bytes",
+                max_bytes
+            )));
+        }
+        let missing = SNIFF_BYTES.saturating_sub(head.len()).min(chunk.len());
+        head.extend_from_slice(&chunk[..missing]);
+        file.write_all(&chunk).await.map_err(storage_error)?;
+    }
+    file.flush().await.map_err(storage_error)?;
+
+    if spooled.size == 0 {
+        return Err(invalid_upload("files must not be empty"));
+    }
+    if image::guess_format(&head).ok() != Some(format) {
+        return Err(invalid_upload(format!(
+            "the content is not a {} image",
+            spooled.content_type
+        )));
+    }
+    Ok(spooled)
+}
@@ -0,0 +1,87 @@
+//! Localization of the sms notifications and user-facing messages.
+//!
+//! Buyers pick a locale when they register their phone. It is kept on their signup session, then
+//! on their account, and their sms use the templates of that locale. GraphQL error messages are
+//! translated to the locale of the request's `Accept-Language` header. The texts come from the
+//! `business` config: english is the default, and its messages are the keys of the translations.
+use crate::config::{BusinessConfig, SmsTemplates};
+use std::{cmp::Ordering, collections::HashMap};
+
+/// The locale of the built-in texts
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// The supported locale matching `locale` by its language (`fr` for `fr-CH`), `None` if there
+/// are no texts for it
+pub fn supported_locale(business: &BusinessConfig, locale: &str) -> Option<String> {
+    let language = locale
+        .trim()
+        .split(|c| c == '-' || c == '_')
+        .next()?
+        .to_ascii_lowercase();
+    let supported = language == DEFAULT_LOCALE
+        || business.sms.contains_key(&language)
+        || business.messages.contains_key(&language);
+    supported.then(|| language)
+}
+
+/// The supported locale preferred by an `Accept-Language` header, e.g. `fr-CH, fr;q=0.9, en;q=0.8`
+pub fn negotiate_locale(business: &BusinessConfig, accept_language: &str) -> Option<String> {
+    let mut ranges: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse::<f32>().ok())?;
+            (!tag.is_empty() && tag != "*" && quality > 0.0).then(|| (tag, quality))
+        })
+        .collect();
+    // stable, equally preferred locales keep their order
+    ranges.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+
+    ranges
+        .into_iter()
+        .find_map(|(tag, _)| supported_locale(business, tag))
+}
+
+/// The message translated to the locale, the configured language without one, and the message
+/// itself when it has no translation
+pub fn translate(business: &BusinessConfig, locale: Option<&str>, message: &str) -> String {
+    let language = locale.unwrap_or(&business.language);
+    business
+        .messages
+        .get(language)
+        .and_then(|messages| messages.get(message))
+        .cloned()
+        .unwrap_or_else(|| message.to_string())
+}
+
+/// The sms templates of every configured language
+#[derive(Debug, Clone, Default)]
+pub struct SmsLocales {
+    language: String,
+    templates: HashMap<String, SmsTemplates>,
+    english: SmsTemplates,
+}
+
+impl SmsLocales {
+    pub fn new(business: &BusinessConfig) -> Self {
+        SmsLocales {
+            language: business.language.clone(),
+            templates: business.sms.clone(),
+            english: SmsTemplates::default(),
+        }
+    }
+
+    /// The templates of the locale, those of the configured language without one, and english
+    /// when there are none either
+    pub fn templates(&self, locale: Option<&str>) -> &SmsTemplates {
+        match locale.and_then(|locale| self.templates.get(locale)) {
+            Some(templates) => templates,
+            // the built-in texts are english
+            None if locale == Some(DEFAULT_LOCALE) => &self.english,
+            None => self.templates.get(&self.language).unwrap_or(&self.english),
+        }
+    }
+}
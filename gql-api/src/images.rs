@@ -0,0 +1,113 @@
+//! Resized webp variants of the event images.
+//!
+//! Every uploaded cover photo gets a cover and an og-image variant, every thumbnail a thumbnail
+//! variant. The variants are stored next to the original under [`ImageVariant::key`].
+use crate::{error::ImageError, jobs::models::EventAssetKind};
+use image::{codecs::webp::WebPEncoder, imageops::FilterType, ColorType, DynamicImage};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Content type of the variants
+pub const WEBP_CONTENT_TYPE: &str = "image/webp";
+
+/// A resized variant of an event image
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageVariant {
+    Thumbnail,
+    Cover,
+    OgImage,
+}
+
+impl ImageVariant {
+    pub const ALL: [ImageVariant; 3] = [
+        ImageVariant::Thumbnail,
+        ImageVariant::Cover,
+        ImageVariant::OgImage,
+    ];
+
+    /// the width and height of the variant, the image is cropped to fill them
+    pub fn dimensions(&self) -> (u32, u32) {
+        match self {
+            ImageVariant::Thumbnail => (400, 400),
+            ImageVariant::Cover => (1600, 900),
+            ImageVariant::OgImage => (1200, 630),
+        }
+    }
+
+    /// the variants generated for an event image
+    pub fn for_kind(kind: EventAssetKind) -> &'static [ImageVariant] {
+        match kind {
+            EventAssetKind::CoverPhoto => &[ImageVariant::Cover, ImageVariant::OgImage],
+            EventAssetKind::Thumbnail => &[ImageVariant::Thumbnail],
+        }
+    }
+
+    /// the key of the variant of the image stored under `original_key`
+    pub fn key(&self, original_key: &str) -> String {
+        let stem = match original_key.rsplit_once('.') {
+            Some((stem, extension)) if !extension.contains('/') => stem,
+            _ => original_key,
+        };
+        format!("{}.{}.webp", stem, self)
+    }
+}
+
+impl fmt::Display for ImageVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageVariant::Thumbnail => write!(f, "thumbnail"),
+            ImageVariant::Cover => write!(f, "cover"),
+            ImageVariant::OgImage => write!(f, "og_image"),
+        }
+    }
+}
+
+/// Decodes an uploaded image. Images sent through `updateEvent` are stored as their base64
+/// text, so that is accepted as well.
+pub fn decode_image(content: &[u8]) -> Result<DynamicImage, ImageError> {
+    match image::load_from_memory(content) {
+        Ok(image) => Ok(image),
+        Err(e) => {
+            let content = base64::decode(trim_data_url(content))
+                .map_err(|_| ImageError::Decode(e.to_string()))?;
+            image::load_from_memory(&content).map_err(|e| ImageError::Decode(e.to_string()))
+        }
+    }
+}
+
+// drops a `data:image/png;base64,` prefix and surrounding whitespace
+fn trim_data_url(content: &[u8]) -> &[u8] {
+    let content = match content.iter().position(|b| *b == b',') {
+        Some(i) if content.starts_with(b"data:") => &content[i + 1..],
+        _ => content,
+    };
+    let start = content
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(content.len());
+    let end = content
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(start, |i| i + 1);
+    &content[start..end]
+}
+
+/// Resizes the image to the variant and encodes it as webp
+pub fn image_variant(image: &DynamicImage, variant: ImageVariant) -> Result<Vec<u8>, ImageError> {
+    let (width, height) = variant.dimensions();
+    let resized = image
+        .resize_to_fill(width, height, FilterType::Lanczos3)
+        .to_rgba8();
+
+    let mut content = vec![];
+    WebPEncoder::new_lossless(&mut content)
+        .encode(
+            resized.as_raw(),
+            resized.width(),
+            resized.height(),
+            ColorType::Rgba8,
+        )
+        .map_err(|e| ImageError::Encode(e.to_string()))?;
+    Ok(content)
+}
@@ -0,0 +1,32 @@
+//! The in-app notifications of a user, kept until they are read and beyond.
+//!
+//! Unlike the sms and pusher notifications an inbox entry is stored, so that a client which was
+//! not around when it was sent still lists it.
+use crate::{
+    db::{models::DbNotification, sql::db_insert_notification},
+    gql::models::NotificationKind,
+};
+use tokio_postgres::Client;
+use uuid::Uuid;
+
+/// Adds a notification to the user's inbox. `data` holds the details clients link to, e.g. the
+/// event id.
+///
+/// Failures are logged and swallowed, an inbox entry must never fail the flow it tells about.
+pub async fn notify(
+    db_client: &Client,
+    user_id: Uuid,
+    kind: NotificationKind,
+    message: String,
+    data: Option<serde_json::Value>,
+) {
+    let db_notification = DbNotification::new(user_id, kind, message, data);
+    if let Err(e) = db_insert_notification(db_client, &db_notification).await {
+        log::error!(
+            "Failed to add a {:?} notification to the inbox of {}: {}",
+            kind,
+            user_id,
+            e
+        );
+    }
+}
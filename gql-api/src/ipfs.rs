@@ -0,0 +1,58 @@
+use crate::{config::IpfsConfig, error::IpfsError};
+use reqwest::multipart::{Form, Part};
+use serde::Deserialize;
+
+/// Response of the ipfs http api `add` call
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct AddResponse {
+    hash: String,
+}
+
+pub struct IpfsClient {
+    http_client: reqwest::Client,
+    api_url: String,
+    auth_token: Option<String>,
+}
+
+impl IpfsClient {
+    pub fn new(config: &IpfsConfig) -> Self {
+        IpfsClient {
+            http_client: reqwest::Client::new(),
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+            auth_token: config.auth_token.clone(),
+        }
+    }
+
+    /// Adds and pins the content on the ipfs node, returning its CID
+    pub async fn pin(&self, name: &str, content: Vec<u8>) -> Result<String, IpfsError> {
+        let form = Form::new().part("file", Part::bytes(content).file_name(name.to_string()));
+
+        let mut request = self
+            .http_client
+            .post(format!("{}/api/v0/add", self.api_url))
+            .query(&[("pin", "true"), ("cid-version", "1")])
+            .multipart(form);
+        if let Some(auth_token) = self.auth_token.as_ref() {
+            request = request.bearer_auth(auth_token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| IpfsError::Request(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(IpfsError::Response(response.status().to_string()));
+        }
+
+        let add_response: AddResponse = response
+            .json()
+            .await
+            .map_err(|e| IpfsError::Response(e.to_string()))?;
+        Ok(add_response.hash)
+    }
+
+    pub fn ipfs_url(cid: &str) -> String {
+        format!("ipfs://{}", cid)
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{
+    config::AnalyticsConfig,
+    db::sql::{db_aggregate_event_reservations, db_aggregate_event_views, sql_timestamp},
+    error::Error,
+    gql::schema::Context as ResourcesContext,
+};
+use chrono::Duration as ChronoDuration;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+
+/// Periodically aggregates the event views and reservations into the daily stats until a stop
+/// signal is received.
+pub async fn run(
+    ctx: Arc<ResourcesContext>,
+    config: AnalyticsConfig,
+    mut stop_rx: broadcast::Receiver<()>,
+) {
+    log::info!("Event analytics started");
+
+    loop {
+        if let Err(e) = aggregate_event_stats(&ctx, &config).await {
+            log::error!("Failed to aggregate event stats: {}", e);
+        }
+
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                log::info!("Event analytics stopped");
+                return;
+            }
+            _ = tokio::time::sleep(Duration::from_secs(config.aggregate_interval_secs)) => {}
+        }
+    }
+}
+
+/// Folds the recorded views into the daily stats and recounts the reservations of the last
+/// `recount_days` days. Returns the number of daily stats updated
+pub async fn aggregate_event_stats(
+    ctx: &ResourcesContext,
+    config: &AnalyticsConfig,
+) -> Result<u64, Error> {
+    let now = sql_timestamp(None);
+    let views = db_aggregate_event_views(&ctx.db_client, &now)
+        .await
+        .map_err(Error::Postgres)?;
+
+    let since = (now - ChronoDuration::days(config.recount_days.max(1) - 1)).date();
+    let reservations = db_aggregate_event_reservations(&ctx.db_client, &since)
+        .await
+        .map_err(Error::Postgres)?;
+
+    Ok(views + reservations)
+}
@@ -0,0 +1,77 @@
+use crate::{
+    config::BalancesConfig,
+    db::{
+        models::DbUser,
+        sql::{db_get_users_for_balance_sync, db_update_user_wallet_balance},
+    },
+    error::Error,
+    gql::schema::Context as ResourcesContext,
+    near::NearAmount,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+
+/// Periodically refreshes the stored wallet balances until a stop signal is received.
+pub async fn run(
+    ctx: Arc<ResourcesContext>,
+    config: BalancesConfig,
+    mut stop_rx: broadcast::Receiver<()>,
+) {
+    log::info!("Wallet balance sync started");
+
+    loop {
+        match db_get_users_for_balance_sync(&ctx.db_client, config.batch_size).await {
+            Ok(db_users) => {
+                for db_user in db_users {
+                    if let Err(e) = sync_wallet_balance(&ctx, &db_user).await {
+                        log::warn!(
+                            "Failed to sync wallet balance of {}: {}",
+                            db_user.wallet_id,
+                            e
+                        );
+                        // mark it as synced anyway, so a broken wallet does not starve the others
+                        let _ =
+                            db_update_user_wallet_balance(&ctx.db_client, &db_user.id, None).await;
+                    }
+                }
+            }
+            Err(e) => log::error!("Failed to get users for wallet balance sync: {}", e),
+        }
+
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                log::info!("Wallet balance sync stopped");
+                return;
+            }
+            _ = tokio::time::sleep(Duration::from_secs(config.sync_interval_secs)) => {}
+        }
+    }
+}
+
+/// Fetches the available balance of the user's wallet and stores it.
+pub async fn sync_wallet_balance(
+    ctx: &ResourcesContext,
+    db_user: &DbUser,
+) -> Result<DbUser, Error> {
+    let wallet_balance = {
+        let mut lock = ctx.grpc_near_client.lock().await;
+        let wallet_balance = lock
+            .get_account_balance(&db_user.wallet_id)
+            .await
+            .map_err(Error::Grpc)?
+            .available;
+        drop(lock);
+        wallet_balance
+    };
+    let wallet_balance = wallet_balance
+        .parse::<NearAmount>()
+        .map_err(Error::NearAmount)?;
+
+    db_update_user_wallet_balance(
+        &ctx.db_client,
+        &db_user.id,
+        Some(&wallet_balance.to_string()),
+    )
+    .await
+    .map_err(Error::Postgres)
+}
@@ -0,0 +1,34 @@
+use crate::{
+    config::SessionsConfig,
+    db::sql::{db_purge_stale_sessions, sql_timestamp},
+    gql::schema::Context as ResourcesContext,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+
+/// Periodically purges expired login, signup and recovery sessions until a stop signal is received.
+pub async fn run(
+    ctx: Arc<ResourcesContext>,
+    config: SessionsConfig,
+    mut stop_rx: broadcast::Receiver<()>,
+) {
+    log::info!("Session cleanup started");
+
+    loop {
+        match db_purge_stale_sessions(&ctx.db_client, &sql_timestamp(Some(-config.retention_secs)))
+            .await
+        {
+            Ok(0) => {}
+            Ok(n) => log::info!("Purged {} stale sessions", n),
+            Err(e) => log::error!("Failed to purge stale sessions: {}", e),
+        }
+
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                log::info!("Session cleanup stopped");
+                return;
+            }
+            _ = tokio::time::sleep(Duration::from_secs(config.cleanup_interval_secs)) => {}
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::{config::FxConfig, fx};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Seeds the fixed exchange rates and keeps refreshing the configured provider's until a stop
+/// signal is received.
+pub async fn run(config: FxConfig, mut stop_rx: broadcast::Receiver<()>) {
+    fx::update_rates(&config.fixed_rates, None);
+
+    let url = match config.url {
+        Some(url) => url,
+        None => {
+            log::info!("No exchange rate provider configured, using the fixed rates");
+            return;
+        }
+    };
+
+    log::info!("Exchange rate sync started");
+    let http_client = reqwest::Client::new();
+    let ttl = Duration::from_secs(config.max_age_secs);
+
+    loop {
+        match fx::fetch_rates(&http_client, &url).await {
+            Ok(rates) => fx::update_rates(&rates, Some(ttl)),
+            Err(e) => log::warn!("Failed to fetch exchange rates: {}", e),
+        }
+
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                log::info!("Exchange rate sync stopped");
+                return;
+            }
+            _ = tokio::time::sleep(Duration::from_secs(config.refresh_interval_secs)) => {}
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{config::MaintenanceConfig, gql::schema::Context as ResourcesContext};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+
+/// Periodically reads back the maintenance mode the admins set, so that every api instance
+/// follows it, until a stop signal is received.
+pub async fn run(
+    ctx: Arc<ResourcesContext>,
+    config: MaintenanceConfig,
+    mut stop_rx: broadcast::Receiver<()>,
+) {
+    log::info!("Maintenance mode sync started");
+    let mut enabled = None;
+
+    loop {
+        // the last mode read is kept while the db is unreachable
+        match ctx.maintenance.refresh(&ctx.db_client).await {
+            Ok(mode) => {
+                if enabled != Some(mode.enabled) {
+                    log::info!(
+                        "Maintenance mode is {}",
+                        if mode.enabled { "on" } else { "off" }
+                    );
+                    enabled = Some(mode.enabled);
+                }
+            }
+            Err(e) => log::warn!("Failed to refresh the maintenance mode: {}", e),
+        }
+
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                log::info!("Maintenance mode sync stopped");
+                return;
+            }
+            _ = tokio::time::sleep(Duration::from_secs(config.refresh_interval_secs)) => {}
+        }
+    }
+}
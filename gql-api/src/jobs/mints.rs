@@ -0,0 +1,186 @@
+use crate::{
+    config::MintsConfig,
+    db::{
+        models::DbMintJob,
+        sql::{
+            db_get_event_by_id, db_get_pending_mint_jobs, db_revert_event_minting,
+            db_update_mint_job_status,
+        },
+    },
+    error::Error,
+    gql::{
+        models::{MintStatus, NotificationKind},
+        schema::Context as ResourcesContext,
+    },
+    inbox,
+    realtime::{outbox_message, EventUpdate},
+};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+
+/// Periodically checks the pending mint transactions and settles their tickets and events once
+/// the transactions are final, until a stop signal is received.
+pub async fn run(
+    ctx: Arc<ResourcesContext>,
+    config: MintsConfig,
+    mut stop_rx: broadcast::Receiver<()>,
+) {
+    log::info!("Mint reconciler started");
+
+    loop {
+        match reconcile_mints(&ctx, &config).await {
+            Ok(0) => {}
+            Ok(n) => log::info!("Settled {} mint jobs", n),
+            Err(e) => log::error!("Failed to reconcile mint jobs: {}", e),
+        }
+
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                log::info!("Mint reconciler stopped");
+                return;
+            }
+            _ = tokio::time::sleep(Duration::from_secs(config.check_interval_secs)) => {}
+        }
+    }
+}
+
+/// The mint status of a final NEAR transaction status, `None` while the transaction is pending
+pub fn tx_mint_status(tx_status: &str) -> Option<MintStatus> {
+    match tx_status {
+        "SUCCESS" => Some(MintStatus::Succeeded),
+        "FAILURE" => Some(MintStatus::Failed),
+        _ => None,
+    }
+}
+
+/// The status of a mint job after its `attempts`th check found the transaction in `tx_status`.
+/// A transaction still pending after `max_attempts` checks is given up as failed
+pub fn next_mint_status(
+    tx_status: Option<MintStatus>,
+    attempts: i32,
+    max_attempts: i32,
+) -> MintStatus {
+    match tx_status {
+        Some(mint_status) => mint_status,
+        None if attempts >= max_attempts => MintStatus::Failed,
+        None => MintStatus::Pending,
+    }
+}
+
+/// Checks the status of a batch of pending mint transactions. Returns the number of mint jobs
+/// that succeeded or failed
+pub async fn reconcile_mints(ctx: &ResourcesContext, config: &MintsConfig) -> Result<usize, Error> {
+    let db_mint_jobs = db_get_pending_mint_jobs(&ctx.db_client, &config.batch_size)
+        .await
+        .map_err(Error::Postgres)?;
+
+    let mut settled = 0;
+    for db_mint_job in db_mint_jobs {
+        let tx_status = {
+            let mut lock = ctx.grpc_near_client.lock().await;
+            let tx_status = lock
+                .get_tx_status(&db_mint_job.tx_hash, &db_mint_job.wallet_id)
+                .await;
+            drop(lock);
+            tx_status
+        };
+        // an unreachable near api counts as a check of a still pending transaction
+        let tx_status = match tx_status {
+            Ok(response) => tx_mint_status(&response.status),
+            Err(e) => {
+                log::warn!(
+                    "Failed to get the status of mint {}: {}",
+                    db_mint_job.tx_hash,
+                    e
+                );
+                None
+            }
+        };
+
+        let mint_status =
+            next_mint_status(tx_status, db_mint_job.attempts + 1, config.max_attempts);
+        let error = match (mint_status, tx_status) {
+            (MintStatus::Failed, Some(_)) => Some("Mint transaction failed"),
+            (MintStatus::Failed, None) => Some("Mint transaction was not final in time"),
+            _ => None,
+        };
+        // the clients are told about a settled mint along with its status change
+        let outbox = mint_update(mint_status, &db_mint_job)
+            .map(|update| outbox_message(db_mint_job.event_id, &update))
+            .into_iter()
+            .collect::<Vec<_>>();
+        let db_mint_job = match db_update_mint_job_status(
+            &ctx.db_client,
+            &db_mint_job.id,
+            mint_status,
+            error,
+            &outbox,
+        )
+        .await
+        .map_err(Error::Postgres)?
+        {
+            Some(db_mint_job) => db_mint_job,
+            // settled concurrently
+            None => continue,
+        };
+
+        if settle_mint_job(ctx, &db_mint_job).await? {
+            settled += 1;
+        }
+    }
+    Ok(settled)
+}
+
+/// The update the event clients are told about once a mint job settles with `mint_status`
+pub fn mint_update(mint_status: MintStatus, db_mint_job: &DbMintJob) -> Option<EventUpdate> {
+    let (ticket_id, tx_hash) = (db_mint_job.ticket_id, db_mint_job.tx_hash.clone());
+    match mint_status {
+        MintStatus::Pending => None,
+        MintStatus::Succeeded => Some(EventUpdate::MintingComplete { ticket_id, tx_hash }),
+        MintStatus::Failed => Some(EventUpdate::MintingFailed { ticket_id, tx_hash }),
+    }
+}
+
+/// Notifies the event creator of a succeeded mint, moving the event back to DRAFT once none of
+/// its mints is left after a failed one. Returns whether the mint job is settled
+async fn settle_mint_job(ctx: &ResourcesContext, db_mint_job: &DbMintJob) -> Result<bool, Error> {
+    match db_mint_job.mint_status {
+        MintStatus::Pending => return Ok(false),
+        MintStatus::Succeeded => notify_minted(ctx, db_mint_job).await?,
+        MintStatus::Failed => {
+            log::warn!(
+                "Mint {} of ticket {} failed",
+                db_mint_job.tx_hash,
+                db_mint_job.ticket_id
+            );
+            db_revert_event_minting(&ctx.db_client, &db_mint_job.event_id)
+                .await
+                .map_err(Error::Postgres)?;
+        }
+    }
+    Ok(true)
+}
+
+/// Tells the event creator their tickets are minted, in their inbox
+async fn notify_minted(ctx: &ResourcesContext, db_mint_job: &DbMintJob) -> Result<(), Error> {
+    let db_event = db_get_event_by_id(&ctx.db_client, &db_mint_job.event_id)
+        .await
+        .map_err(Error::Postgres)?;
+    inbox::notify(
+        &ctx.db_client,
+        db_event.created_by_user,
+        NotificationKind::TicketsMinted,
+        format!(
+            "{} tickets of {} were minted",
+            db_mint_job.number_of_tickets, db_event.event_name
+        ),
+        Some(serde_json::json!({
+            "eventId": db_mint_job.event_id,
+            "ticketId": db_mint_job.ticket_id,
+            "txHash": db_mint_job.tx_hash,
+            "numberOfTickets": db_mint_job.number_of_tickets,
+        })),
+    )
+    .await;
+    Ok(())
+}
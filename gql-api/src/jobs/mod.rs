@@ -0,0 +1,14 @@
+pub mod analytics;
+pub mod balances;
+pub mod cleanup;
+pub mod fx;
+pub mod maintenance;
+pub mod mints;
+pub mod models;
+pub mod outbox;
+pub mod queue;
+pub mod reminders;
+pub mod signups;
+pub mod top_ups;
+pub mod waitlist;
+pub mod worker;
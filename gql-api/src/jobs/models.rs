@@ -0,0 +1,235 @@
+use crate::{error::JobError, notifier::Notification, realtime::event_channel};
+use juniper::GraphQLEnum;
+use pusher_client::{channels::PusherChannels, events::PusherEvents};
+use serde::{Deserialize, Serialize};
+use std::{convert::From, fmt};
+
+/// Job Type
+#[repr(i16)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobType {
+    Notify = 0,
+    PusherEvent = 1,
+    S3Upload = 2,
+    IpfsPin = 3,
+    ImageVariants = 4,
+    Webhook = 5,
+    Refund = 6,
+}
+
+impl From<JobType> for i16 {
+    fn from(job_type: JobType) -> i16 {
+        job_type as i16
+    }
+}
+
+impl TryFrom<i16> for JobType {
+    type Error = JobError;
+
+    fn try_from(n: i16) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(JobType::Notify),
+            1 => Ok(JobType::PusherEvent),
+            2 => Ok(JobType::S3Upload),
+            3 => Ok(JobType::IpfsPin),
+            4 => Ok(JobType::ImageVariants),
+            5 => Ok(JobType::Webhook),
+            6 => Ok(JobType::Refund),
+            _ => Err(JobError::UnknownJobType(n.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for JobType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobType::Notify => write!(f, "notify"),
+            JobType::PusherEvent => write!(f, "pusher_event"),
+            JobType::S3Upload => write!(f, "s3_upload"),
+            JobType::IpfsPin => write!(f, "ipfs_pin"),
+            JobType::ImageVariants => write!(f, "image_variants"),
+            JobType::Webhook => write!(f, "webhook"),
+            JobType::Refund => write!(f, "refund"),
+        }
+    }
+}
+
+/// Job Status
+#[repr(i16)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobStatus {
+    Pending = 0,
+    Running = 1,
+    Done = 2,
+    Failed = 3,
+}
+
+impl From<JobStatus> for i16 {
+    fn from(status: JobStatus) -> i16 {
+        status as i16
+    }
+}
+
+impl TryFrom<i16> for JobStatus {
+    type Error = JobError;
+
+    fn try_from(n: i16) -> Result<Self, Self::Error> {
+        match n {
+            0 => Ok(JobStatus::Pending),
+            1 => Ok(JobStatus::Running),
+            2 => Ok(JobStatus::Done),
+            3 => Ok(JobStatus::Failed),
+            _ => Err(JobError::UnknownJobStatus(n.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobStatus::Pending => write!(f, "pending"),
+            JobStatus::Running => write!(f, "running"),
+            JobStatus::Done => write!(f, "done"),
+            JobStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// Serializable mirror of the pusher client channels
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PusherChannel {
+    Account,
+    Custom(String),
+    /// the channel of an event's updates
+    Event(uuid::Uuid),
+}
+
+impl From<PusherChannel> for PusherChannels {
+    fn from(channel: PusherChannel) -> Self {
+        match channel {
+            PusherChannel::Account => PusherChannels::Account,
+            PusherChannel::Custom(name) => PusherChannels::Custom(name),
+            PusherChannel::Event(event_id) => PusherChannels::Custom(event_channel(&event_id)),
+        }
+    }
+}
+
+/// Serializable mirror of the pusher client events
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PusherEvent {
+    AccountCreated,
+    AccountFunded,
+    LoggedIn,
+    EventPublished,
+    TicketAdded,
+    TicketSoldOut,
+    MintingComplete,
+    MintingFailed,
+    TicketAvailable,
+    EventReminder,
+    StockAlert,
+    MaintenanceMode,
+}
+
+impl From<PusherEvent> for PusherEvents {
+    fn from(event: PusherEvent) -> Self {
+        match event {
+            PusherEvent::AccountCreated => PusherEvents::AccountCreated,
+            PusherEvent::AccountFunded => PusherEvents::AccountFunded,
+            PusherEvent::LoggedIn => PusherEvents::LoggedIn,
+            // the event updates are sent under their own names
+            PusherEvent::EventPublished => PusherEvents::Custom("event-published".to_string()),
+            PusherEvent::TicketAdded => PusherEvents::Custom("ticket-added".to_string()),
+            PusherEvent::TicketSoldOut => PusherEvents::Custom("ticket-sold-out".to_string()),
+            PusherEvent::MintingComplete => PusherEvents::Custom("minting-complete".to_string()),
+            PusherEvent::MintingFailed => PusherEvents::Custom("minting-failed".to_string()),
+            PusherEvent::TicketAvailable => PusherEvents::Custom("ticket-available".to_string()),
+            PusherEvent::EventReminder => PusherEvents::Custom("event-reminder".to_string()),
+            PusherEvent::StockAlert => PusherEvents::Custom("ticket-stock-alert".to_string()),
+            PusherEvent::MaintenanceMode => PusherEvents::Custom("maintenance-mode".to_string()),
+        }
+    }
+}
+
+/// Which event image an uploaded asset is attached to
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, GraphQLEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum EventAssetKind {
+    #[graphql(name = "COVER_PHOTO")]
+    CoverPhoto,
+    #[graphql(name = "THUMBNAIL")]
+    Thumbnail,
+}
+
+/// The work a job has to carry out, persisted as json in the jobs table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobPayload {
+    Notify {
+        receiver: String,
+        notification: Notification,
+        /// the receiver's locale, the configured language if `None`
+        #[serde(default)]
+        locale: Option<String>,
+    },
+    PusherEvent {
+        channel: PusherChannel,
+        event: PusherEvent,
+        data: String,
+    },
+    S3Upload {
+        event_id: uuid::Uuid,
+        kind: EventAssetKind,
+        content: String,
+    },
+    IpfsPin {
+        asset_id: uuid::Uuid,
+        kind: EventAssetKind,
+    },
+    ImageVariants {
+        asset_id: uuid::Uuid,
+        kind: EventAssetKind,
+    },
+    /// a delivery to the seller's webhook, signed with its secret when delivered
+    Webhook {
+        seller_id: uuid::Uuid,
+        event: String,
+        body: String,
+    },
+    /// the refund of a cancelled reservation to its buyer's wallet
+    Refund { cancellation_id: uuid::Uuid },
+}
+
+/// A message written to the outbox in the same statement as the change it tells about, and
+/// delivered by the outbox dispatcher at least once
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutboxMessage {
+    PusherEvent {
+        channel: PusherChannel,
+        event: PusherEvent,
+        data: String,
+    },
+    /// a delivery to the seller's webhook, signed with its secret when delivered
+    Webhook {
+        seller_id: uuid::Uuid,
+        event: String,
+        body: String,
+    },
+}
+
+impl JobPayload {
+    pub fn job_type(&self) -> JobType {
+        match self {
+            JobPayload::Notify { .. } => JobType::Notify,
+            JobPayload::PusherEvent { .. } => JobType::PusherEvent,
+            JobPayload::S3Upload { .. } => JobType::S3Upload,
+            JobPayload::IpfsPin { .. } => JobType::IpfsPin,
+            JobPayload::ImageVariants { .. } => JobType::ImageVariants,
+            JobPayload::Webhook { .. } => JobType::Webhook,
+            JobPayload::Refund { .. } => JobType::Refund,
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! The dispatcher of the outbox.
+//!
+//! Pusher events and webhooks telling about a change are written to the outbox in the same
+//! statement as the change (see [`with_outbox`](crate::db::sql::with_outbox)), so that a crash
+//! right after the change cannot lose them. The dispatcher delivers them and marks them done,
+//! retrying failed deliveries until they go through.
+use super::models::{OutboxMessage, PusherChannel, PusherEvent};
+use crate::{
+    config::OutboxConfig,
+    db::{
+        models::DbOutboxMessage,
+        sql::{
+            db_claim_outbox_messages, db_complete_outbox_message, db_fail_outbox_message,
+            db_get_seller_webhook, db_purge_dispatched_outbox_messages, sql_timestamp,
+        },
+    },
+    error::JobError,
+    gql::schema::Context as ResourcesContext,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+
+/// Polls the outbox and delivers the due messages until a stop signal is received.
+pub async fn run(
+    ctx: Arc<ResourcesContext>,
+    config: OutboxConfig,
+    mut stop_rx: broadcast::Receiver<()>,
+) {
+    log::info!("Outbox dispatcher started");
+
+    loop {
+        match dispatch(&ctx, &config).await {
+            Ok(0) => {}
+            Ok(n) => log::info!("Dispatched {} outbox messages", n),
+            Err(e) => log::error!("Failed to claim outbox messages: {}", e),
+        }
+        match db_purge_dispatched_outbox_messages(
+            &ctx.db_client,
+            &sql_timestamp(Some(-config.retention_secs)),
+        )
+        .await
+        {
+            Ok(0) => {}
+            Ok(n) => log::info!("Purged {} dispatched outbox messages", n),
+            Err(e) => log::error!("Failed to purge dispatched outbox messages: {}", e),
+        }
+
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                log::info!("Outbox dispatcher stopped");
+                return;
+            }
+            _ = tokio::time::sleep(Duration::from_millis(config.poll_interval_ms)) => {}
+        }
+    }
+}
+
+/// Delivers every due outbox message, batch by batch. Returns the number of messages delivered
+pub async fn dispatch(
+    ctx: &ResourcesContext,
+    config: &OutboxConfig,
+) -> Result<usize, tokio_postgres::Error> {
+    let mut dispatched = 0;
+    loop {
+        let db_messages = db_claim_outbox_messages(
+            &ctx.db_client,
+            &sql_timestamp(Some(config.lease_secs)),
+            &config.batch_size,
+        )
+        .await?;
+        if db_messages.is_empty() {
+            return Ok(dispatched);
+        }
+        for db_message in db_messages {
+            if settle(ctx, config, &db_message).await {
+                dispatched += 1;
+            }
+        }
+    }
+}
+
+/// Delivers a claimed message and records the outcome. Returns whether it was delivered
+async fn settle(
+    ctx: &ResourcesContext,
+    config: &OutboxConfig,
+    db_message: &DbOutboxMessage,
+) -> bool {
+    match deliver(ctx, db_message).await {
+        Ok(()) => {
+            if let Err(e) = db_complete_outbox_message(&ctx.db_client, &db_message.id).await {
+                // delivered again once the lease is over
+                log::error!("Failed to complete outbox message {}: {}", db_message.id, e);
+            }
+            true
+        }
+        Err(error) => {
+            log::warn!(
+                "Outbox message {} failed on attempt {}: {}",
+                db_message.id,
+                db_message.attempts,
+                error
+            );
+            let retry_at = sql_timestamp(Some(retry_backoff_secs(config, db_message.attempts)));
+            if let Err(e) = db_fail_outbox_message(
+                &ctx.db_client,
+                &db_message.id,
+                &error.to_string(),
+                &retry_at,
+            )
+            .await
+            {
+                log::error!(
+                    "Failed to record outbox message {} failure: {}",
+                    db_message.id,
+                    e
+                );
+            }
+            false
+        }
+    }
+}
+
+/// The delay before the next delivery of a message that failed on its `attempts`th delivery
+pub fn retry_backoff_secs(config: &OutboxConfig, attempts: i32) -> i64 {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    config
+        .retry_backoff_secs
+        .saturating_mul(2_i64.pow(exponent))
+        .min(config.max_retry_backoff_secs)
+}
+
+async fn deliver(ctx: &ResourcesContext, db_message: &DbOutboxMessage) -> Result<(), JobError> {
+    let message = db_message
+        .message()
+        .map_err(|e| JobError::Payload(e.to_string()))?;
+
+    match message {
+        OutboxMessage::PusherEvent {
+            channel,
+            event,
+            data,
+        } => publish(ctx, channel, event, &data).await,
+        OutboxMessage::Webhook {
+            seller_id,
+            event,
+            body,
+        } => deliver_webhook(ctx, &seller_id, &event, &body).await,
+    }
+}
+
+/// Publishes an event to a pusher channel
+pub(crate) async fn publish(
+    ctx: &ResourcesContext,
+    channel: PusherChannel,
+    event: PusherEvent,
+    data: &str,
+) -> Result<(), JobError> {
+    ctx.publisher
+        .publish(channel, event, data)
+        .await
+        .map_err(|e| JobError::Execution(e.to_string()))
+}
+
+/// Delivers a webhook event to the seller's webhook
+pub(crate) async fn deliver_webhook(
+    ctx: &ResourcesContext,
+    seller_id: &uuid::Uuid,
+    event: &str,
+    body: &str,
+) -> Result<(), JobError> {
+    // a webhook removed since the message was written is not delivered to
+    let db_webhook = match db_get_seller_webhook(&ctx.db_client, seller_id)
+        .await
+        .map_err(|e| JobError::Execution(e.to_string()))?
+    {
+        Some(db_webhook) => db_webhook,
+        None => {
+            log::info!("Seller {} has no webhook, skipping {}", seller_id, event);
+            return Ok(());
+        }
+    };
+    ctx.webhooks
+        .deliver(&db_webhook.url, &db_webhook.secret, event, body)
+        .await
+        .map_err(|e| JobError::Execution(e.to_string()))
+}
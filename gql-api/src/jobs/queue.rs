@@ -0,0 +1,16 @@
+use super::models::JobPayload;
+use crate::db::{models::DbJob, sql::db_insert_job};
+use tokio_postgres::Client;
+
+/// Persists a job for the background worker to pick up.
+pub async fn enqueue(
+    db_client: &Client,
+    payload: JobPayload,
+    max_attempts: i32,
+) -> Result<DbJob, tokio_postgres::Error> {
+    let db_job = DbJob::new(&payload, max_attempts);
+    db_insert_job(db_client, &db_job).await?;
+
+    log::info!("Enqueued {} job {}", db_job.job_type, db_job.id);
+    Ok(db_job)
+}
@@ -0,0 +1,168 @@
+use crate::{
+    config::RemindersConfig,
+    db::{
+        models::{DbEvent, DbEventReminder},
+        sql::{
+            db_get_event_by_id, db_get_reminder_recipients, db_insert_event_reminder, sql_timestamp,
+        },
+    },
+    gql::{models::NotificationKind, schema::Context as ResourcesContext},
+    inbox,
+    jobs::{
+        models::{JobPayload, PusherChannel, PusherEvent},
+        queue::enqueue,
+    },
+    notifier::{Notification, Reminder},
+    realtime::user_channel,
+};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+
+const REMINDER_TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Periodically reminds the ticket holders of the events starting soon, until a stop signal is
+/// received.
+pub async fn run(
+    ctx: Arc<ResourcesContext>,
+    config: RemindersConfig,
+    mut stop_rx: broadcast::Receiver<()>,
+) {
+    log::info!("Event reminders started");
+
+    loop {
+        match send_reminders(&ctx, &config).await {
+            Ok(0) => {}
+            Ok(n) => log::info!("Reminded {} ticket holders", n),
+            Err(e) => log::error!("Failed to remind ticket holders: {}", e),
+        }
+
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                log::info!("Event reminders stopped");
+                return;
+            }
+            _ = tokio::time::sleep(Duration::from_secs(config.check_interval_secs)) => {}
+        }
+    }
+}
+
+/// Reminds the holders of tickets for the events starting within each window, once per window.
+/// An event is only in its shortest window, so that an event published an hour before it starts
+/// is not reminded of twice. Returns the number of reminders sent.
+pub async fn send_reminders(
+    ctx: &ResourcesContext,
+    config: &RemindersConfig,
+) -> Result<usize, tokio_postgres::Error> {
+    let mut windows_secs = config.windows_secs.clone();
+    windows_secs.sort_unstable();
+    windows_secs.dedup();
+
+    let mut events: HashMap<uuid::Uuid, Reminder> = HashMap::new();
+    let mut reminded = 0;
+    let mut starts_after_secs = 0;
+    for window_secs in windows_secs {
+        let recipients = db_get_reminder_recipients(
+            &ctx.db_client,
+            window_secs,
+            &sql_timestamp(Some(starts_after_secs)),
+            &sql_timestamp(Some(window_secs)),
+        )
+        .await?;
+        starts_after_secs = window_secs;
+
+        for recipient in recipients {
+            // a concurrent run may have sent it already
+            let db_reminder =
+                DbEventReminder::new(recipient.event_id, recipient.user_id, window_secs);
+            if !db_insert_event_reminder(&ctx.db_client, &db_reminder).await? {
+                continue;
+            }
+
+            let reminder = match events.get(&recipient.event_id) {
+                Some(reminder) => reminder.clone(),
+                None => {
+                    let db_event = db_get_event_by_id(&ctx.db_client, &recipient.event_id).await?;
+                    let reminder = event_reminder(&db_event);
+                    events.insert(recipient.event_id, reminder.clone());
+                    reminder
+                }
+            };
+
+            inbox::notify(
+                &ctx.db_client,
+                recipient.user_id,
+                NotificationKind::EventReminder,
+                format!("{} starts at {}", reminder.event_name, reminder.starts_at),
+                Some(serde_json::json!({
+                    "eventId": recipient.event_id,
+                    "startsAt": reminder.starts_at,
+                    "entryTime": reminder.entry_time,
+                    "venue": reminder.venue,
+                })),
+            )
+            .await;
+
+            // buyers without a phone number only get the pusher notification
+            if let Some(phone_number) = recipient.phone_number {
+                enqueue(
+                    &ctx.db_client,
+                    JobPayload::Notify {
+                        receiver: phone_number,
+                        notification: Notification::Reminder(reminder.clone()),
+                        locale: recipient.locale,
+                    },
+                    ctx.jobs.max_attempts,
+                )
+                .await?;
+            }
+            enqueue(
+                &ctx.db_client,
+                JobPayload::PusherEvent {
+                    channel: PusherChannel::Custom(user_channel(&recipient.user_id)),
+                    event: PusherEvent::EventReminder,
+                    data: serde_json::json!({
+                        "eventId": recipient.event_id,
+                        "eventName": reminder.event_name,
+                        "venue": reminder.venue,
+                        "startsAt": reminder.starts_at,
+                        "entryTime": reminder.entry_time,
+                    "venue": reminder.venue,
+                    })
+                    .to_string(),
+                },
+                ctx.jobs.max_attempts,
+            )
+            .await?;
+            reminded += 1;
+        }
+    }
+    Ok(reminded)
+}
+
+/// The reminder of an event, its venue `online` if it has none
+pub fn event_reminder(db_event: &DbEvent) -> Reminder {
+    let venue = [&db_event.venue_name, &db_event.venue_location]
+        .iter()
+        .filter_map(|part| part.as_deref().filter(|part| !part.trim().is_empty()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let starts_at = db_event
+        .start_date
+        .map(|start_date| start_date.format(REMINDER_TIME_FORMAT).to_string())
+        .unwrap_or_default();
+    let entry_time = db_event
+        .entry_time
+        .map(|entry_time| entry_time.format(REMINDER_TIME_FORMAT).to_string())
+        .unwrap_or_else(|| starts_at.clone());
+
+    Reminder {
+        event_name: db_event.event_name.clone(),
+        venue: if venue.is_empty() {
+            "online".to_string()
+        } else {
+            venue
+        },
+        starts_at,
+        entry_time,
+    }
+}
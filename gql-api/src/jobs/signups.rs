@@ -0,0 +1,55 @@
+use crate::{
+    config::SignupsConfig,
+    db::sql::{db_claim_signup_attempts, sql_timestamp},
+    error::Error,
+    gql::schema::Context as ResourcesContext,
+    signups,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+
+/// Periodically retries the buyer signups that failed halfway, until a stop signal is received.
+pub async fn run(
+    ctx: Arc<ResourcesContext>,
+    config: SignupsConfig,
+    mut stop_rx: broadcast::Receiver<()>,
+) {
+    log::info!("Signup retries started");
+
+    loop {
+        match retry_signups(&ctx, &config).await {
+            Ok(0) => {}
+            Ok(n) => log::info!("Completed {} signups", n),
+            Err(e) => log::error!("Failed to retry signups: {}", e),
+        }
+
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                log::info!("Signup retries stopped");
+                return;
+            }
+            _ = tokio::time::sleep(Duration::from_secs(config.check_interval_secs)) => {}
+        }
+    }
+}
+
+/// Retries a batch of the pending signups due for a retry. Returns the number of signups that
+/// completed
+pub async fn retry_signups(ctx: &ResourcesContext, config: &SignupsConfig) -> Result<usize, Error> {
+    let db_signup_attempts = db_claim_signup_attempts(
+        &ctx.db_client,
+        &sql_timestamp(Some(config.lease_secs)),
+        &config.batch_size,
+    )
+    .await
+    .map_err(Error::Postgres)?;
+
+    let mut completed = 0;
+    for db_signup_attempt in db_signup_attempts {
+        match signups::complete(ctx, &db_signup_attempt).await {
+            Ok(_) => completed += 1,
+            Err(e) => signups::fail(ctx, &db_signup_attempt, &e).await,
+        }
+    }
+    Ok(completed)
+}
@@ -0,0 +1,167 @@
+use crate::{
+    config::TopUpsConfig,
+    db::{
+        models::{DbUser, DbWalletTopUp},
+        sql::{
+            db_complete_wallet_top_up, db_delete_wallet_top_up, db_get_buyers_below_balance,
+            db_get_wallet_top_ups_total, db_insert_wallet_top_up, sql_timestamp,
+        },
+    },
+    error::{Error, NearAmountError},
+    gql::schema::Context as ResourcesContext,
+    jobs::balances::sync_wallet_balance,
+    near::NearAmount,
+};
+use chrono::Duration as ChronoDuration;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+
+/// The NEAR amounts of the top-up config
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TopUpLimits {
+    pub threshold: NearAmount,
+    pub amount: NearAmount,
+    pub daily_cap: NearAmount,
+}
+
+impl TopUpLimits {
+    pub fn from_config(config: &TopUpsConfig) -> Result<Self, NearAmountError> {
+        Ok(TopUpLimits {
+            threshold: NearAmount::from_near(&config.threshold)?,
+            amount: NearAmount::from_near(&config.amount)?,
+            daily_cap: NearAmount::from_near(&config.daily_cap)?,
+        })
+    }
+
+    /// How much of `amount` may still be sent to a wallet that received `topped_up` within the
+    /// last 24 hours, `None` once its cap is reached
+    pub fn capped(&self, amount: NearAmount, topped_up: NearAmount) -> Option<NearAmount> {
+        let amount = amount.min(self.daily_cap.saturating_sub(topped_up));
+        (amount > NearAmount::default()).then(|| amount)
+    }
+
+    /// How much to send to a wallet holding `balance`, `None` if it holds enough
+    pub fn top_up_amount(&self, balance: NearAmount, topped_up: NearAmount) -> Option<NearAmount> {
+        if balance >= self.threshold {
+            return None;
+        }
+        self.capped(self.amount, topped_up)
+    }
+}
+
+/// Periodically tops up the buyer wallets running low until a stop signal is received.
+pub async fn run(
+    ctx: Arc<ResourcesContext>,
+    config: TopUpsConfig,
+    mut stop_rx: broadcast::Receiver<()>,
+) {
+    if !config.enabled {
+        log::info!("Wallet top-ups disabled");
+        return;
+    }
+    log::info!("Wallet top-ups started");
+
+    loop {
+        match top_up_wallets(&ctx, &config).await {
+            Ok(0) => {}
+            Ok(n) => log::info!("Topped up {} wallets", n),
+            Err(e) => log::error!("Failed to top up wallets: {}", e),
+        }
+
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                log::info!("Wallet top-ups stopped");
+                return;
+            }
+            _ = tokio::time::sleep(Duration::from_secs(config.check_interval_secs)) => {}
+        }
+    }
+}
+
+/// Tops up a batch of the buyer wallets whose synced balance is below the threshold. Returns the
+/// number of wallets topped up
+pub async fn top_up_wallets(ctx: &ResourcesContext, config: &TopUpsConfig) -> Result<usize, Error> {
+    let limits = TopUpLimits::from_config(config).map_err(Error::NearAmount)?;
+    let db_users =
+        db_get_buyers_below_balance(&ctx.db_client, &limits.threshold, config.batch_size)
+            .await
+            .map_err(Error::Postgres)?;
+
+    let mut topped_up = 0;
+    for db_user in db_users {
+        // the synced balance may be stale, the wallet may have been funded since
+        let db_user = match sync_wallet_balance(ctx, &db_user).await {
+            Ok(db_user) => db_user,
+            Err(e) => {
+                log::warn!(
+                    "Failed to sync wallet balance of {}: {}",
+                    db_user.wallet_id,
+                    e
+                );
+                continue;
+            }
+        };
+        let balance = db_user
+            .wallet_balance
+            .parse::<NearAmount>()
+            .map_err(Error::NearAmount)?;
+        let total = wallet_top_ups_total(ctx, &db_user).await?;
+        let amount = match limits.top_up_amount(balance, total) {
+            Some(amount) => amount,
+            None => continue,
+        };
+
+        match top_up_wallet(ctx, &db_user, amount, None).await {
+            Ok(_) => topped_up += 1,
+            Err(e) => log::warn!("Failed to top up wallet {}: {}", db_user.wallet_id, e),
+        }
+    }
+    Ok(topped_up)
+}
+
+/// The total the user's wallet was topped up with within the last 24 hours
+pub async fn wallet_top_ups_total(
+    ctx: &ResourcesContext,
+    db_user: &DbUser,
+) -> Result<NearAmount, Error> {
+    let since = sql_timestamp(None) - ChronoDuration::days(1);
+    db_get_wallet_top_ups_total(&ctx.db_client, &db_user.id, &since)
+        .await
+        .map_err(Error::Postgres)
+}
+
+/// Sends `amount` to the user's wallet. The top-up is recorded before the funds are sent so that
+/// it counts against the daily cap, and dropped again if they could not be
+pub async fn top_up_wallet(
+    ctx: &ResourcesContext,
+    db_user: &DbUser,
+    amount: NearAmount,
+    triggered_by: Option<uuid::Uuid>,
+) -> Result<DbWalletTopUp, Error> {
+    let db_wallet_top_up = db_insert_wallet_top_up(
+        &ctx.db_client,
+        &DbWalletTopUp::new(db_user, amount, triggered_by),
+    )
+    .await
+    .map_err(Error::Postgres)?;
+
+    let fund_account_tx = match ctx
+        .chain
+        .fund_account(&db_user.wallet_id, &amount.to_string())
+        .await
+    {
+        Ok(tx) => tx,
+        Err(e) => {
+            let _ = db_delete_wallet_top_up(&ctx.db_client, &db_wallet_top_up.id).await;
+            return Err(Error::Chain(e));
+        }
+    };
+
+    db_complete_wallet_top_up(
+        &ctx.db_client,
+        &db_wallet_top_up.id,
+        &fund_account_tx.tx_hash,
+    )
+    .await
+    .map_err(Error::Postgres)
+}
@@ -0,0 +1,98 @@
+use crate::{
+    config::WaitlistConfig,
+    db::sql::{
+        db_get_event_by_id, db_get_ticket_by_id, db_get_user_by_id, db_get_waitlist_openings,
+        db_notify_waitlist_entries, sql_timestamp,
+    },
+    gql::schema::Context as ResourcesContext,
+    jobs::{models::JobPayload, queue::enqueue},
+    notifier::{Notification, WaitlistSpot},
+    realtime::{self, EventUpdate},
+};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+
+/// Periodically notifies the buyers waiting for sold out tickets that became available again,
+/// until a stop signal is received.
+pub async fn run(
+    ctx: Arc<ResourcesContext>,
+    config: WaitlistConfig,
+    mut stop_rx: broadcast::Receiver<()>,
+) {
+    log::info!("Waitlist notifier started");
+
+    loop {
+        match notify_waitlists(&ctx, &config).await {
+            Ok(0) => {}
+            Ok(n) => log::info!("Notified {} waitlisted buyers", n),
+            Err(e) => log::error!("Failed to notify waitlisted buyers: {}", e),
+        }
+
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                log::info!("Waitlist notifier stopped");
+                return;
+            }
+            _ = tokio::time::sleep(Duration::from_secs(config.check_interval_secs)) => {}
+        }
+    }
+}
+
+/// Notifies the longest waiting buyers of every ticket with tickets left to reserve, as many as
+/// there are tickets left that the buyers notified within the offer window may not take. Returns
+/// the number of buyers notified.
+pub async fn notify_waitlists(
+    ctx: &ResourcesContext,
+    config: &WaitlistConfig,
+) -> Result<usize, tokio_postgres::Error> {
+    let offered_after = sql_timestamp(Some(-config.offer_window_secs));
+    let openings = db_get_waitlist_openings(&ctx.db_client, &offered_after).await?;
+
+    let mut notified = 0;
+    for opening in openings {
+        let openings = opening.openings(config.batch_size);
+        if openings == 0 {
+            continue;
+        }
+        let entries =
+            db_notify_waitlist_entries(&ctx.db_client, &opening.ticket_id, openings).await?;
+        if entries.is_empty() {
+            continue;
+        }
+
+        let db_ticket = db_get_ticket_by_id(&ctx.db_client, &opening.ticket_id).await?;
+        let db_event = db_get_event_by_id(&ctx.db_client, &opening.event_id).await?;
+        let spot = WaitlistSpot {
+            event_name: db_event.event_name,
+            ticket_name: db_ticket.ticket_name,
+        };
+        for entry in entries.iter() {
+            let db_user = db_get_user_by_id(&ctx.db_client, &entry.user_id).await?;
+            // buyers without a phone number only get the pusher update
+            if let Some(phone_number) = db_user.phone_number {
+                enqueue(
+                    &ctx.db_client,
+                    JobPayload::Notify {
+                        receiver: phone_number,
+                        notification: Notification::WaitlistSpot(spot.clone()),
+                        locale: db_user.locale,
+                    },
+                    ctx.jobs.max_attempts,
+                )
+                .await?;
+            }
+        }
+        notified += entries.len();
+
+        realtime::broadcast(
+            &ctx.db_client,
+            opening.event_id,
+            EventUpdate::TicketAvailable {
+                ticket_id: opening.ticket_id,
+            },
+            ctx.jobs.max_attempts,
+        )
+        .await?;
+    }
+    Ok(notified)
+}
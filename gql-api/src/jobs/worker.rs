@@ -0,0 +1,306 @@
+use super::{
+    models::{EventAssetKind, JobPayload},
+    outbox::{deliver_webhook, publish},
+    queue::enqueue,
+};
+use crate::{
+    cancellations,
+    config::JobsConfig,
+    db::{
+        models::{AssetFile, DbJob},
+        sql::{
+            db_claim_next_job, db_complete_job, db_fail_job, db_get_asset_file,
+            db_requeue_stale_jobs, db_update_asset_file_variant_keys, db_update_event_asset_url,
+            db_update_event_ipfs_url, db_update_event_variant_urls, insert_asset_file,
+            sql_timestamp, update_file_ipfs_hash,
+        },
+    },
+    error::{ImageError, JobError},
+    gql::{models::AssetRole, schema::Context as ResourcesContext},
+    images::{decode_image, image_variant, ImageVariant, WEBP_CONTENT_TYPE},
+    ipfs::IpfsClient,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+
+/// Upper bound for the retry backoff, whatever the attempt count
+const MAX_RETRY_BACKOFF_SECS: i64 = 3600;
+
+/// Polls the jobs table and executes due jobs until a stop signal is received.
+pub async fn run(
+    ctx: Arc<ResourcesContext>,
+    config: JobsConfig,
+    mut stop_rx: broadcast::Receiver<()>,
+) {
+    log::info!("Job worker started");
+
+    // jobs a previous process was running when it died
+    match db_requeue_stale_jobs(
+        &ctx.db_client,
+        &sql_timestamp(Some(-config.stale_after_secs)),
+    )
+    .await
+    {
+        Ok(0) => {}
+        Ok(n) => log::warn!("Requeued {} stale jobs", n),
+        Err(e) => log::error!("Failed to requeue stale jobs: {}", e),
+    }
+
+    loop {
+        // drain every due job before sleeping again
+        loop {
+            let db_job = match db_claim_next_job(&ctx.db_client).await {
+                Ok(Some(db_job)) => db_job,
+                Ok(None) => break,
+                Err(e) => {
+                    log::error!("Failed to claim job: {}", e);
+                    break;
+                }
+            };
+            process(&ctx, &config, db_job).await;
+        }
+
+        tokio::select! {
+            _ = stop_rx.recv() => {
+                log::info!("Job worker stopped");
+                return;
+            }
+            _ = tokio::time::sleep(Duration::from_millis(config.poll_interval_ms)) => {}
+        }
+    }
+}
+
+async fn process(ctx: &ResourcesContext, config: &JobsConfig, db_job: DbJob) {
+    match execute(ctx, &db_job).await {
+        Ok(()) => {
+            if let Err(e) = db_complete_job(&ctx.db_client, &db_job.id).await {
+                log::error!("Failed to complete job {}: {}", db_job.id, e);
+            }
+        }
+        Err(error) => {
+            log::warn!(
+                "{} job {} failed on attempt {}/{}: {}",
+                db_job.job_type,
+                db_job.id,
+                db_job.attempts,
+                db_job.max_attempts,
+                error
+            );
+            let retry_at = sql_timestamp(Some(retry_backoff_secs(config, db_job.attempts)));
+            if let Err(e) =
+                db_fail_job(&ctx.db_client, &db_job.id, &error.to_string(), &retry_at).await
+            {
+                log::error!("Failed to record job {} failure: {}", db_job.id, e);
+            }
+        }
+    }
+}
+
+fn retry_backoff_secs(config: &JobsConfig, attempts: i32) -> i64 {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    config
+        .retry_backoff_secs
+        .saturating_mul(2_i64.pow(exponent))
+        .min(MAX_RETRY_BACKOFF_SECS)
+}
+
+async fn execute(ctx: &ResourcesContext, db_job: &DbJob) -> Result<(), JobError> {
+    let payload = db_job
+        .payload()
+        .map_err(|e| JobError::Payload(e.to_string()))?;
+
+    match payload {
+        JobPayload::Notify {
+            receiver,
+            notification,
+            locale,
+        } => {
+            notification
+                .send(ctx.notifier.as_ref(), &receiver, locale.as_deref())
+                .await
+                .map_err(|e| JobError::Execution(e.to_string()))?;
+        }
+        JobPayload::PusherEvent {
+            channel,
+            event,
+            data,
+        } => publish(ctx, channel, event, &data).await?,
+        JobPayload::S3Upload {
+            event_id,
+            kind,
+            content,
+        } => {
+            let path = ctx
+                .storage
+                .upload(content.into_bytes())
+                .await
+                .map_err(|e| JobError::Execution(e.to_string()))?;
+
+            // persist the asset in the db and attach it to the event
+            let asset_file = AssetFile {
+                asset_role: Some(AssetRole::from(kind)),
+                ..AssetFile::new(
+                    ctx.storage.bucket().to_string(),
+                    path.clone(),
+                    None,
+                    event_id,
+                )
+            };
+            insert_asset_file(&ctx.db_client, &asset_file)
+                .await
+                .map_err(|e| JobError::Execution(e.to_string()))?;
+
+            db_update_event_asset_url(
+                &ctx.db_client,
+                &event_id,
+                kind,
+                &ctx.storage.asset_url(path),
+            )
+            .await
+            .map_err(|e| JobError::Execution(e.to_string()))?;
+
+            enqueue(
+                &ctx.db_client,
+                JobPayload::ImageVariants {
+                    asset_id: asset_file.id,
+                    kind,
+                },
+                ctx.jobs.max_attempts,
+            )
+            .await
+            .map_err(|e| JobError::Execution(e.to_string()))?;
+
+            if ctx.ipfs_client.is_some() {
+                enqueue(
+                    &ctx.db_client,
+                    JobPayload::IpfsPin {
+                        asset_id: asset_file.id,
+                        kind,
+                    },
+                    ctx.jobs.max_attempts,
+                )
+                .await
+                .map_err(|e| JobError::Execution(e.to_string()))?;
+            }
+        }
+        JobPayload::IpfsPin { asset_id, kind } => {
+            let ipfs_client = ctx
+                .ipfs_client
+                .as_ref()
+                .ok_or_else(|| JobError::Execution("ipfs is not configured".to_string()))?;
+
+            let asset_file = db_get_asset_file(&ctx.db_client, &asset_id)
+                .await
+                .map_err(|e| JobError::Execution(e.to_string()))?;
+            let asset_url = ctx.storage.asset_url(asset_file.s3_absolute_key.clone());
+
+            // a retry after the hash was recorded must not pin again
+            let cid = match asset_file.ipfs_hash {
+                Some(cid) => cid,
+                None => {
+                    let content = reqwest::get(&asset_url)
+                        .await
+                        .and_then(|res| res.error_for_status())
+                        .map_err(|e| JobError::Execution(e.to_string()))?
+                        .bytes()
+                        .await
+                        .map_err(|e| JobError::Execution(e.to_string()))?;
+
+                    let cid = ipfs_client
+                        .pin(&asset_file.s3_absolute_key, content.to_vec())
+                        .await
+                        .map_err(|e| JobError::Execution(e.to_string()))?;
+
+                    update_file_ipfs_hash(&ctx.db_client, &asset_id, &cid)
+                        .await
+                        .map_err(|e| JobError::Execution(e.to_string()))?;
+                    cid
+                }
+            };
+
+            db_update_event_ipfs_url(
+                &ctx.db_client,
+                &asset_file.event_id,
+                kind,
+                &asset_url,
+                &IpfsClient::ipfs_url(&cid),
+            )
+            .await
+            .map_err(|e| JobError::Execution(e.to_string()))?;
+        }
+        JobPayload::ImageVariants { asset_id, kind } => {
+            let asset_file = db_get_asset_file(&ctx.db_client, &asset_id)
+                .await
+                .map_err(|e| JobError::Execution(e.to_string()))?;
+            let content = ctx
+                .storage
+                .get(&asset_file.s3_absolute_key)
+                .await
+                .map_err(|e| JobError::Execution(e.to_string()))?;
+
+            let mut variant_keys = vec![];
+            for (variant, content) in image_variants(content, kind).await? {
+                let key = variant.key(&asset_file.s3_absolute_key);
+                ctx.storage
+                    .put(&key, WEBP_CONTENT_TYPE, content)
+                    .await
+                    .map_err(|e| JobError::Execution(e.to_string()))?;
+                variant_keys.push((variant, key));
+            }
+            db_update_asset_file_variant_keys(&ctx.db_client, &asset_id, &variant_keys)
+                .await
+                .map_err(|e| JobError::Execution(e.to_string()))?;
+
+            let variant_urls = variant_keys
+                .into_iter()
+                .map(|(variant, key)| (variant, ctx.storage.asset_url(key)))
+                .collect::<Vec<_>>();
+            db_update_event_variant_urls(
+                &ctx.db_client,
+                &asset_file.event_id,
+                kind,
+                &ctx.storage.asset_url(asset_file.s3_absolute_key.clone()),
+                &variant_urls,
+            )
+            .await
+            .map_err(|e| JobError::Execution(e.to_string()))?;
+        }
+        JobPayload::Webhook {
+            seller_id,
+            event,
+            body,
+        } => deliver_webhook(ctx, &seller_id, &event, &body).await?,
+        JobPayload::Refund { cancellation_id } => {
+            // the refund is sent once, a retry after it went through finds nothing left to send
+            if let Some(db_cancellation) = cancellations::refund(ctx, &cancellation_id)
+                .await
+                .map_err(|e| JobError::Execution(e.to_string()))?
+            {
+                log::info!(
+                    "Refunded cancelled reservation {} in {:?}",
+                    db_cancellation.id,
+                    db_cancellation.refund_tx_hash
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Decodes and resizes an event image off the async runtime
+async fn image_variants(
+    content: Vec<u8>,
+    kind: EventAssetKind,
+) -> Result<Vec<(ImageVariant, Vec<u8>)>, JobError> {
+    tokio::task::spawn_blocking(move || -> Result<Vec<_>, ImageError> {
+        let image = decode_image(&content)?;
+        ImageVariant::for_kind(kind)
+            .iter()
+            .map(|variant| Ok((*variant, image_variant(&image, *variant)?)))
+            .collect()
+    })
+    .await
+    .map_err(|e| JobError::Execution(e.to_string()))?
+    .map_err(|e| JobError::Execution(e.to_string()))
+}
@@ -44,13 +44,46 @@
 #[macro_use]
 extern crate diesel_migrations;
 
+pub mod audit;
 pub mod auth;
+pub mod cache;
+pub mod cancellations;
+pub mod chain;
+pub mod check;
 pub mod config;
 pub mod db;
 pub mod error;
+pub mod fakes;
 pub mod filters;
+pub mod fx;
+pub mod geo;
 pub mod gql;
 pub mod grpc;
 pub mod http;
+pub mod i18n;
+pub mod images;
+pub mod inbox;
+pub mod ipfs;
+pub mod jobs;
+pub mod maintenance;
+pub mod metrics;
 pub mod migrations;
+pub mod near;
+pub mod notifier;
+pub mod phone;
+pub mod policy;
+pub mod pricing;
+pub mod publisher;
+pub mod realtime;
+pub mod recurrence;
+pub mod sanitize;
 pub mod security;
+pub mod seed;
+pub mod shutdown;
+pub mod signups;
+pub mod stock_alerts;
+pub mod storage;
+#[cfg(feature = "test-harness")]
+pub mod testing;
+pub mod usernames;
+pub mod webhooks;
@@ -0,0 +1,129 @@
+//! Maintenance mode, taking the api down for everyone but the admins.
+//!
+//! The admins set the mode with a mutation and it is stored in the db, every api instance reads
+//! it back periodically (see [`crate::jobs::maintenance`]). The config forces it on whatever the
+//! admins set, for when the db itself is down or migrated. While it is on, requests without an
+//! admin jwt are answered with 503 and a Retry-After header, the health and metrics routes stay
+//! up. The clients are told of the changes on the public [`MAINTENANCE_CHANNEL`].
+use crate::{
+    config::MaintenanceConfig,
+    db::{
+        models::DbMaintenance,
+        sql::{db_get_maintenance, db_upsert_maintenance, sql_timestamp},
+    },
+    error::Error,
+    gql::{models::MaintenanceMode, schema::Context as ResourcesContext},
+    jobs::{
+        models::{JobPayload, PusherChannel, PusherEvent},
+        queue::enqueue,
+    },
+};
+use std::{convert::TryFrom, sync::RwLock};
+use tokio_postgres::Client;
+use uuid::Uuid;
+
+/// The pusher channel of the maintenance mode changes, every client may subscribe to it
+pub const MAINTENANCE_CHANNEL: &str = "maintenance";
+
+/// The maintenance mode an api instance follows, refreshed from the db
+pub struct Maintenance {
+    config: MaintenanceConfig,
+    stored: RwLock<Option<DbMaintenance>>,
+}
+
+impl Maintenance {
+    pub fn new(config: &MaintenanceConfig) -> Self {
+        Maintenance {
+            config: config.clone(),
+            stored: RwLock::new(None),
+        }
+    }
+
+    /// The maintenance mode in effect
+    pub fn mode(&self) -> MaintenanceMode {
+        let stored = self.stored.read().expect("maintenance lock poisoned");
+        let default_retry_after_secs =
+            i32::try_from(self.config.retry_after_secs).unwrap_or(i32::MAX);
+        match stored.as_ref() {
+            Some(db_maintenance) => MaintenanceMode {
+                enabled: self.config.enabled || db_maintenance.enabled,
+                forced: self.config.enabled,
+                message: db_maintenance.message.clone(),
+                retry_after_secs: db_maintenance
+                    .retry_after_secs
+                    .unwrap_or(default_retry_after_secs),
+                updated_at: Some(db_maintenance.updated_at),
+            },
+            None => MaintenanceMode {
+                enabled: self.config.enabled,
+                forced: self.config.enabled,
+                message: None,
+                retry_after_secs: default_retry_after_secs,
+                updated_at: None,
+            },
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.mode().enabled
+    }
+
+    fn store(&self, db_maintenance: Option<DbMaintenance>) {
+        *self.stored.write().expect("maintenance lock poisoned") = db_maintenance;
+    }
+
+    /// Reads back the maintenance mode the admins set, e.g. on another api instance
+    pub async fn refresh(&self, db_client: &Client) -> Result<MaintenanceMode, Error> {
+        let db_maintenance = db_get_maintenance(db_client)
+            .await
+            .map_err(Error::Postgres)?;
+        self.store(db_maintenance);
+        Ok(self.mode())
+    }
+}
+
+/// Sets the maintenance mode and tells the clients when the mode in effect changed. The other api
+/// instances follow at their next refresh
+pub async fn set_mode(
+    ctx: &ResourcesContext,
+    enabled: bool,
+    message: Option<String>,
+    retry_after_secs: Option<i32>,
+    updated_by: &Uuid,
+) -> Result<MaintenanceMode, Error> {
+    let previous = ctx.maintenance.mode();
+    let db_maintenance = db_upsert_maintenance(
+        &ctx.db_client,
+        &DbMaintenance {
+            enabled,
+            message,
+            retry_after_secs,
+            updated_at: sql_timestamp(None),
+            updated_by: Some(*updated_by),
+        },
+    )
+    .await
+    .map_err(Error::Postgres)?;
+    ctx.maintenance.store(Some(db_maintenance));
+
+    let mode = ctx.maintenance.mode();
+    if mode.enabled != previous.enabled || mode.message != previous.message {
+        enqueue(
+            &ctx.db_client,
+            JobPayload::PusherEvent {
+                channel: PusherChannel::Custom(MAINTENANCE_CHANNEL.to_string()),
+                event: PusherEvent::MaintenanceMode,
+                data: serde_json::json!({
+                    "enabled": mode.enabled,
+                    "message": mode.message,
+                    "retryAfterSecs": mode.retry_after_secs,
+                })
+                .to_string(),
+            },
+            ctx.jobs.max_attempts,
+        )
+        .await
+        .map_err(Error::Postgres)?;
+    }
+    Ok(mode)
+}
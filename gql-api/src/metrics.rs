@@ -0,0 +1,227 @@
+use crate::db::slow_queries;
+use lazy_static::lazy_static;
+use prometheus::{
+    core::Collector, register_histogram_vec, register_int_counter_vec, Encoder, HistogramTimer,
+    HistogramVec, IntCounterVec, TextEncoder,
+};
+use std::{collections::BTreeMap, time::Instant};
+use warp::{http::StatusCode, log::Info};
+
+lazy_static! {
+    // http
+    pub static ref HTTP_REQUESTS_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "http_requests_total",
+        "Number of http requests by route, method and status",
+        &["route", "method", "status"]
+    )
+    .expect("http_requests_total should register");
+    pub static ref HTTP_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "http_request_duration_seconds",
+        "Http request durations by route and method",
+        &["route", "method"]
+    )
+    .expect("http_request_duration_seconds should register");
+
+    // graphql
+    pub static ref GQL_OPERATION_DURATION: HistogramVec = register_histogram_vec!(
+        "gql_operation_duration_seconds",
+        "GraphQL operation durations by schema and operation name",
+        &["schema", "operation"]
+    )
+    .expect("gql_operation_duration_seconds should register");
+    pub static ref GQL_BATCH_SIZE: HistogramVec = register_histogram_vec!(
+        "gql_batch_size",
+        "GraphQL requests per http request by schema",
+        &["schema"],
+        vec![1.0, 2.0, 5.0, 10.0, 20.0, 50.0]
+    )
+    .expect("gql_batch_size should register");
+
+    // db
+    pub static ref DB_QUERY_DURATION: HistogramVec = register_histogram_vec!(
+        "db_query_duration_seconds",
+        "Database query durations by query function",
+        &["query"]
+    )
+    .expect("db_query_duration_seconds should register");
+    pub static ref DB_SLOW_QUERIES: IntCounterVec = register_int_counter_vec!(
+        "db_slow_queries_total",
+        "Number of database queries over the slow query threshold by query function",
+        &["query"]
+    )
+    .expect("db_slow_queries_total should register");
+
+    // grpc
+    pub static ref GRPC_CALL_DURATION: HistogramVec = register_histogram_vec!(
+        "grpc_call_duration_seconds",
+        "Near api gRPC call durations by method",
+        &["method"]
+    )
+    .expect("grpc_call_duration_seconds should register");
+    pub static ref GRPC_CALL_ERRORS: IntCounterVec = register_int_counter_vec!(
+        "grpc_call_errors_total",
+        "Number of failed near api gRPC calls by method",
+        &["method"]
+    )
+    .expect("grpc_call_errors_total should register");
+
+    // sms
+    pub static ref SMS_SENT_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "sms_sent_total",
+        "Number of sms handed to twilio by result (sent or failed)",
+        &["result"]
+    )
+    .expect("sms_sent_total should register");
+
+    // cache
+    pub static ref CACHE_LOOKUPS_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "cache_lookups_total",
+        "Number of in-memory cache lookups by cache and result (hit or miss)",
+        &["cache", "result"]
+    )
+    .expect("cache_lookups_total should register");
+}
+
+/// Times a query function, recording into `db_query_duration_seconds` when dropped.
+pub fn db_timer(query: &str) -> QueryTimer<'_> {
+    QueryTimer {
+        query,
+        started: Instant::now(),
+    }
+}
+
+/// A query function's timer, the slow queries are also logged and counted in
+/// `db_slow_queries_total`
+pub struct QueryTimer<'a> {
+    query: &'a str,
+    started: Instant,
+}
+
+impl Drop for QueryTimer<'_> {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        DB_QUERY_DURATION
+            .with_label_values(&[self.query])
+            .observe(elapsed.as_secs_f64());
+        if slow_queries::is_slow(elapsed) {
+            DB_SLOW_QUERIES.with_label_values(&[self.query]).inc();
+            log::warn!("Slow query {} took {}ms", self.query, elapsed.as_millis());
+        }
+    }
+}
+
+/// Starts a timer recording into `grpc_call_duration_seconds` when dropped.
+pub fn grpc_timer(method: &str) -> HistogramTimer {
+    GRPC_CALL_DURATION
+        .with_label_values(&[method])
+        .start_timer()
+}
+
+pub fn grpc_error(method: &str) {
+    GRPC_CALL_ERRORS.with_label_values(&[method]).inc();
+}
+
+pub fn observe_sms(sent: bool) {
+    let result = if sent { "sent" } else { "failed" };
+    SMS_SENT_TOTAL.with_label_values(&[result]).inc();
+}
+
+/// The sms sent and failed since the start of this instance
+pub fn sms_counts() -> (u64, u64) {
+    (
+        SMS_SENT_TOTAL.with_label_values(&["sent"]).get(),
+        SMS_SENT_TOTAL.with_label_values(&["failed"]).get(),
+    )
+}
+
+/// The calls of a near api gRPC method since the start of this instance
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GrpcCallCounts {
+    pub method: String,
+    /// every call is timed, the failed ones included
+    pub calls: u64,
+    pub errors: u64,
+}
+
+/// The counts of the method the metric is labelled with
+fn method_counts<'a>(
+    counts: &'a mut BTreeMap<String, GrpcCallCounts>,
+    metric: &prometheus::proto::Metric,
+) -> &'a mut GrpcCallCounts {
+    let method = metric
+        .get_label()
+        .iter()
+        .find(|label| label.get_name() == "method")
+        .map(|label| label.get_value().to_string())
+        .unwrap_or_default();
+    counts
+        .entry(method.clone())
+        .or_insert_with(|| GrpcCallCounts {
+            method,
+            ..GrpcCallCounts::default()
+        })
+}
+
+/// The calls and failures per near api gRPC method, by method name
+pub fn grpc_call_counts() -> Vec<GrpcCallCounts> {
+    let mut counts = BTreeMap::new();
+    for family in GRPC_CALL_DURATION.collect() {
+        for metric in family.get_metric() {
+            method_counts(&mut counts, metric).calls = metric.get_histogram().get_sample_count();
+        }
+    }
+    for family in GRPC_CALL_ERRORS.collect() {
+        for metric in family.get_metric() {
+            method_counts(&mut counts, metric).errors = metric.get_counter().get_value() as u64;
+        }
+    }
+    counts.into_values().collect()
+}
+
+pub fn observe_cache_lookup(cache: &str, hit: bool) {
+    let result = if hit { "hit" } else { "miss" };
+    CACHE_LOOKUPS_TOTAL
+        .with_label_values(&[cache, result])
+        .inc();
+}
+
+pub fn observe_gql_operation(schema: &str, operation: &str, seconds: f64) {
+    let operation = if operation.is_empty() {
+        "anonymous"
+    } else {
+        operation
+    };
+    GQL_OPERATION_DURATION
+        .with_label_values(&[schema, operation])
+        .observe(seconds);
+}
+
+pub fn observe_gql_batch(schema: &str, size: usize) {
+    GQL_BATCH_SIZE
+        .with_label_values(&[schema])
+        .observe(size as f64);
+}
+
+/// Records a finished http request, to be used with `warp::log::custom`.
+pub fn observe_http_request(info: Info<'_>) {
+    // unmatched paths are collapsed so scanners cannot blow up the label cardinality
+    let route = if info.status() == StatusCode::NOT_FOUND {
+        "unmatched"
+    } else {
+        info.path()
+    };
+    let method = info.method().to_string();
+    HTTP_REQUESTS_TOTAL
+        .with_label_values(&[route, &method, info.status().as_str()])
+        .inc();
+    HTTP_REQUEST_DURATION
+        .with_label_values(&[route, &method])
+        .observe(info.elapsed().as_secs_f64());
+}
+
+/// Renders all registered metrics in the prometheus text format.
+pub fn gather() -> Result<String, prometheus::Error> {
+    let mut buffer = vec![];
+    TextEncoder::new().encode(&prometheus::gather(), &mut buffer)?;
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
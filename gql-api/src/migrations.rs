@@ -10,3 +10,29 @@ pub fn run(db_config: &PostgresConfig) {
     let connection = PgConnection::establish(&database_url).expect("Connection failed");
     embedded_migrations::run(&connection).expect("Migrations failed");
 }
+
+/// The migrations not run yet on the database, an error if one of them fails.
+///
+/// The migrations are run in a transaction that is rolled back, so the database is left as it was.
+pub fn pending(db_config: &PostgresConfig) -> Result<Vec<String>, String> {
+    let database_url = db_config.connection_string();
+    let connection = PgConnection::establish(&database_url).map_err(|e| e.to_string())?;
+
+    let mut output = Vec::new();
+    let mut failure = None;
+    let _rolled_back = connection.transaction::<(), diesel::result::Error, _>(|| {
+        if let Err(e) = embedded_migrations::run_with_output(&connection, &mut output) {
+            failure = Some(e.to_string());
+        }
+        Err(diesel::result::Error::RollbackTransaction)
+    });
+    if let Some(e) = failure {
+        return Err(e);
+    }
+
+    Ok(String::from_utf8_lossy(&output)
+        .lines()
+        .filter_map(|line| line.strip_prefix("Running migration "))
+        .map(str::to_string)
+        .collect())
+}
@@ -0,0 +1,202 @@
+use crate::error::NearAmountError;
+use bytes::BytesMut;
+use juniper::{InputValue, ParseScalarResult, ParseScalarValue, ScalarToken, ScalarValue, Value};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{error::Error as StdError, fmt, str::FromStr};
+use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+
+/// yoctoNEAR per NEAR (10^24)
+pub const YOCTO_PER_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+
+const NEAR_DECIMALS: usize = 24;
+
+/// Basis points in 100%
+const BASIS_POINTS: u128 = 10_000;
+
+/// An amount of NEAR in yoctoNEAR, represented on the wire as a base-10 integer string
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NearAmount(u128);
+
+impl NearAmount {
+    pub const fn from_yocto(yocto: u128) -> Self {
+        NearAmount(yocto)
+    }
+
+    pub const fn as_yocto(&self) -> u128 {
+        self.0
+    }
+
+    pub const fn from_whole_near(near: u128) -> Self {
+        NearAmount(near * YOCTO_PER_NEAR)
+    }
+
+    pub fn checked_add(self, other: NearAmount) -> Option<NearAmount> {
+        self.0.checked_add(other.0).map(NearAmount)
+    }
+
+    pub fn checked_mul(self, n: u128) -> Option<NearAmount> {
+        self.0.checked_mul(n).map(NearAmount)
+    }
+
+    pub fn saturating_sub(self, other: NearAmount) -> NearAmount {
+        NearAmount(self.0.saturating_sub(other.0))
+    }
+
+    /// The given share of this amount in basis points (1/100 of a percent), rounded down
+    pub fn basis_points(self, basis_points: u128) -> NearAmount {
+        let basis_points = basis_points.min(BASIS_POINTS);
+        let (quotient, remainder) = (self.0 / BASIS_POINTS, self.0 % BASIS_POINTS);
+        NearAmount(quotient * basis_points + remainder * basis_points / BASIS_POINTS)
+    }
+
+    /// Approximate amount in NEAR, for display conversions only
+    pub fn as_near_f64(&self) -> f64 {
+        self.0 as f64 / YOCTO_PER_NEAR as f64
+    }
+
+    /// Exact decimal NEAR representation without trailing zeros, e.g. "2.5"
+    pub fn to_near_string(&self) -> String {
+        let (whole, fraction) = (self.0 / YOCTO_PER_NEAR, self.0 % YOCTO_PER_NEAR);
+        if fraction == 0 {
+            return whole.to_string();
+        }
+        let fraction = format!("{:0>width$}", fraction, width = NEAR_DECIMALS);
+        format!("{}.{}", whole, fraction.trim_end_matches('0'))
+    }
+
+    /// Parses a decimal NEAR amount (e.g. "0.2") into yoctoNEAR
+    pub fn from_near(near: &str) -> Result<Self, NearAmountError> {
+        let invalid = || NearAmountError::Invalid(near.to_string());
+        let (whole, fraction) = near.split_once('.').unwrap_or((near, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid());
+        }
+        if fraction.len() > NEAR_DECIMALS
+            || !whole.bytes().all(|b| b.is_ascii_digit())
+            || !fraction.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let whole: u128 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| invalid())?
+        };
+        let fraction: u128 = if fraction.is_empty() {
+            0
+        } else {
+            format!("{:0<width$}", fraction, width = NEAR_DECIMALS)
+                .parse()
+                .map_err(|_| invalid())?
+        };
+
+        whole
+            .checked_mul(YOCTO_PER_NEAR)
+            .and_then(|yocto| yocto.checked_add(fraction))
+            .map(NearAmount)
+            .ok_or_else(|| NearAmountError::Overflow(near.to_string()))
+    }
+}
+
+/// Parses a decimal percentage with up to two decimals (e.g. "12.5") into basis points, without
+/// going through floats. `None` if it is not such a percentage
+pub fn parse_basis_points(percentage: &str) -> Option<u128> {
+    let (whole, fraction) = percentage.split_once('.').unwrap_or((percentage, ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > 2
+        || !whole.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let whole: u128 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    let fraction: u128 = format!("{:0<2}", fraction).parse().ok()?;
+    whole.checked_mul(100)?.checked_add(fraction)
+}
+
+impl FromStr for NearAmount {
+    type Err = NearAmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(NearAmountError::Invalid(s.to_string()));
+        }
+        s.parse::<u128>()
+            .map(NearAmount)
+            .map_err(|_| NearAmountError::Overflow(s.to_string()))
+    }
+}
+
+impl fmt::Display for NearAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Serialize for NearAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for NearAmount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[juniper::graphql_scalar(
+    name = "NearAmount",
+    description = "An amount of NEAR in yoctoNEAR (10^-24 NEAR), as a base-10 integer string"
+)]
+impl<S> GraphQLScalar for NearAmount
+where
+    S: ScalarValue,
+{
+    fn resolve(&self) -> Value {
+        Value::scalar(self.to_string())
+    }
+
+    fn from_input_value(value: &InputValue) -> Option<NearAmount> {
+        value.as_string_value().and_then(|s| s.parse().ok())
+    }
+
+    fn from_str<'a>(value: ScalarToken<'a>) -> ParseScalarResult<'a, S> {
+        <String as ParseScalarValue<S>>::from_str(value)
+    }
+}
+
+/// Stored as VARCHAR, postgres NUMERIC has no native driver support
+impl<'a> FromSql<'a> for NearAmount {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        let s = <&str as FromSql<'_>>::from_sql(ty, raw)?;
+        Ok(s.parse()?)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <&str as FromSql<'_>>::accepts(ty)
+    }
+}
+
+impl ToSql for NearAmount {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        self.to_string().to_sql(ty, out)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <String as ToSql>::accepts(ty)
+    }
+
+    to_sql_checked!();
+}
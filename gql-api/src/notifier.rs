@@ -0,0 +1,363 @@
+//! Notifications to users: verification and recovery codes, purchase receipts, waitlist spots,
+//! gifted tickets and event reminders.
+//!
+//! Handlers enqueue a [`Notification`] as a job, the job worker hands it to the configured
+//! [`Notifier`]. Twilio sends them as sms, the [`LogNotifier`] only logs them so that local
+//! development and tests do not hit external apis. Their texts are the configured
+//! [`SmsTemplates`] of the receiver's locale, see [`crate::i18n`].
+use crate::{config::SmsTemplates, error::NotifierError, i18n::SmsLocales, metrics::observe_sms};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use twilio_client::{client::TwilioClient, models::SmsMessage};
+
+/// A reservation confirmation sent to its buyer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Receipt {
+    pub event_name: String,
+    pub ticket_name: String,
+    pub quantity: i32,
+    pub verification_code: String,
+}
+
+/// A ticket available again, sent to the buyers waiting for it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaitlistSpot {
+    pub event_name: String,
+    pub ticket_name: String,
+}
+
+/// Tickets bought for the receiver, with the code to claim them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Gift {
+    pub sender: String,
+    pub event_name: String,
+    pub ticket_name: String,
+    pub quantity: i32,
+    pub claim_code: String,
+}
+
+/// An event starting soon, sent to the holders of its tickets
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reminder {
+    pub event_name: String,
+    pub venue: String,
+    pub starts_at: String,
+    /// when the doors open, the start of the event if it has no entry time
+    pub entry_time: String,
+}
+
+/// A notification, persisted as json in the jobs table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Notification {
+    Verification { code: String },
+    Recovery { code: String },
+    Receipt(Receipt),
+    WaitlistSpot(WaitlistSpot),
+    Gift(Gift),
+    Reminder(Reminder),
+}
+
+impl Notification {
+    /// sends the notification to `receiver` through the notifier, in the receiver's locale
+    pub async fn send(
+        &self,
+        notifier: &dyn Notifier,
+        receiver: &str,
+        locale: Option<&str>,
+    ) -> Result<(), NotifierError> {
+        match self {
+            Notification::Verification { code } => {
+                notifier.send_verification(receiver, locale, code).await
+            }
+            Notification::Recovery { code } => notifier.send_recovery(receiver, locale, code).await,
+            Notification::Receipt(receipt) => {
+                notifier.send_receipt(receiver, locale, receipt).await
+            }
+            Notification::WaitlistSpot(spot) => {
+                notifier.send_waitlist_spot(receiver, locale, spot).await
+            }
+            Notification::Gift(gift) => notifier.send_gift(receiver, locale, gift).await,
+            Notification::Reminder(reminder) => {
+                notifier.send_reminder(receiver, locale, reminder).await
+            }
+        }
+    }
+}
+
+/// A channel notifications are delivered through
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn send_verification(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        code: &str,
+    ) -> Result<(), NotifierError>;
+
+    async fn send_recovery(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        code: &str,
+    ) -> Result<(), NotifierError>;
+
+    async fn send_receipt(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        receipt: &Receipt,
+    ) -> Result<(), NotifierError>;
+
+    async fn send_waitlist_spot(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        spot: &WaitlistSpot,
+    ) -> Result<(), NotifierError>;
+
+    async fn send_gift(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        gift: &Gift,
+    ) -> Result<(), NotifierError>;
+
+    async fn send_reminder(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        reminder: &Reminder,
+    ) -> Result<(), NotifierError>;
+}
+
+/// Sends notifications as sms through twilio
+pub struct TwilioNotifier {
+    client: TwilioClient,
+    sms: SmsLocales,
+}
+
+impl TwilioNotifier {
+    pub fn new(client: TwilioClient, sms: SmsLocales) -> Self {
+        TwilioNotifier { client, sms }
+    }
+
+    async fn send_sms(&self, receiver: &str, body: String) -> Result<(), NotifierError> {
+        let sms = SmsMessage {
+            sender: None, // use the messaging service
+            receiver: receiver.to_string(),
+            body: Some(body),
+        };
+        let sent = self
+            .client
+            .send_sms(&sms)
+            .await
+            .map(|_| ())
+            .map_err(|e| NotifierError::Send(e.to_string()));
+        observe_sms(sent.is_ok());
+        sent
+    }
+}
+
+#[async_trait]
+impl Notifier for TwilioNotifier {
+    async fn send_verification(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        code: &str,
+    ) -> Result<(), NotifierError> {
+        self.send_sms(receiver, self.sms.templates(locale).verification_text(code))
+            .await
+    }
+
+    async fn send_recovery(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        code: &str,
+    ) -> Result<(), NotifierError> {
+        self.send_sms(receiver, self.sms.templates(locale).recovery_text(code))
+            .await
+    }
+
+    async fn send_receipt(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        receipt: &Receipt,
+    ) -> Result<(), NotifierError> {
+        self.send_sms(receiver, self.sms.templates(locale).receipt_text(receipt))
+            .await
+    }
+
+    async fn send_waitlist_spot(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        spot: &WaitlistSpot,
+    ) -> Result<(), NotifierError> {
+        self.send_sms(receiver, self.sms.templates(locale).waitlist_text(spot))
+            .await
+    }
+
+    async fn send_gift(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        gift: &Gift,
+    ) -> Result<(), NotifierError> {
+        self.send_sms(receiver, self.sms.templates(locale).gift_text(gift))
+            .await
+    }
+
+    async fn send_reminder(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        reminder: &Reminder,
+    ) -> Result<(), NotifierError> {
+        self.send_sms(receiver, self.sms.templates(locale).reminder_text(reminder))
+            .await
+    }
+}
+
+/// Only logs notifications, for local development and tests
+#[derive(Debug, Default, Clone)]
+pub struct LogNotifier {
+    sms: SmsLocales,
+}
+
+impl LogNotifier {
+    pub fn new(sms: SmsLocales) -> Self {
+        LogNotifier { sms }
+    }
+}
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn send_verification(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        code: &str,
+    ) -> Result<(), NotifierError> {
+        log::info!(
+            "Verification sms to {}: {}",
+            receiver,
+            self.sms.templates(locale).verification_text(code)
+        );
+        Ok(())
+    }
+
+    async fn send_recovery(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        code: &str,
+    ) -> Result<(), NotifierError> {
+        log::info!(
+            "Recovery sms to {}: {}",
+            receiver,
+            self.sms.templates(locale).recovery_text(code)
+        );
+        Ok(())
+    }
+
+    async fn send_receipt(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        receipt: &Receipt,
+    ) -> Result<(), NotifierError> {
+        log::info!(
+            "Receipt sms to {}: {}",
+            receiver,
+            self.sms.templates(locale).receipt_text(receipt)
+        );
+        Ok(())
+    }
+
+    async fn send_waitlist_spot(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        spot: &WaitlistSpot,
+    ) -> Result<(), NotifierError> {
+        log::info!(
+            "Waitlist sms to {}: {}",
+            receiver,
+            self.sms.templates(locale).waitlist_text(spot)
+        );
+        Ok(())
+    }
+
+    async fn send_gift(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        gift: &Gift,
+    ) -> Result<(), NotifierError> {
+        log::info!(
+            "Gift sms to {}: {}",
+            receiver,
+            self.sms.templates(locale).gift_text(gift)
+        );
+        Ok(())
+    }
+
+    async fn send_reminder(
+        &self,
+        receiver: &str,
+        locale: Option<&str>,
+        reminder: &Reminder,
+    ) -> Result<(), NotifierError> {
+        log::info!(
+            "Reminder sms to {}: {}",
+            receiver,
+            self.sms.templates(locale).reminder_text(reminder)
+        );
+        Ok(())
+    }
+}
+
+impl SmsTemplates {
+    pub fn verification_text(&self, code: &str) -> String {
+        self.verification.replace("{code}", code)
+    }
+
+    pub fn recovery_text(&self, code: &str) -> String {
+        self.recovery.replace("{code}", code)
+    }
+
+    pub fn receipt_text(&self, receipt: &Receipt) -> String {
+        self.receipt
+            .replace("{quantity}", &receipt.quantity.to_string())
+            .replace("{ticket_name}", &receipt.ticket_name)
+            .replace("{event_name}", &receipt.event_name)
+            .replace("{code}", &receipt.verification_code)
+    }
+
+    pub fn waitlist_text(&self, spot: &WaitlistSpot) -> String {
+        self.waitlist
+            .replace("{ticket_name}", &spot.ticket_name)
+            .replace("{event_name}", &spot.event_name)
+    }
+
+    pub fn gift_text(&self, gift: &Gift) -> String {
+        self.gift
+            .replace("{sender}", &gift.sender)
+            .replace("{quantity}", &gift.quantity.to_string())
+            .replace("{ticket_name}", &gift.ticket_name)
+            .replace("{event_name}", &gift.event_name)
+            .replace("{code}", &gift.claim_code)
+    }
+
+    pub fn reminder_text(&self, reminder: &Reminder) -> String {
+        self.reminder
+            .replace("{event_name}", &reminder.event_name)
+            .replace("{venue}", &reminder.venue)
+            .replace("{starts_at}", &reminder.starts_at)
+            .replace("{entry_time}", &reminder.entry_time)
+    }
+}
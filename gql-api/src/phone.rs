@@ -0,0 +1,107 @@
+//! Phone number normalization.
+//!
+//! Phone numbers are stored in E.164 (`+4915112345678`) so that the same number submitted as
+//! `+49 151 12345678` or `0049 151 12345678` resolves to the same user.
+use crate::{
+    db::sql::{db_get_users_with_phone_number, db_update_user_phone_number},
+    error::UserError,
+};
+use phonenumber::Mode;
+use std::collections::HashSet;
+use tokio_postgres::Client;
+
+/// Normalizes an internationally formatted phone number to E.164
+pub fn normalize_phone_number(raw: &str) -> Result<String, UserError> {
+    let invalid = || UserError::InvalidPhoneNumber(raw.to_string());
+
+    let digits: String = raw
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')' | '/'))
+        .collect();
+    // the "00" international call prefix is the "+" of E.164
+    let international = match digits.strip_prefix("00") {
+        Some(rest) => format!("+{}", rest),
+        None => digits,
+    };
+    if !international.starts_with('+') {
+        return Err(invalid());
+    }
+
+    let number = phonenumber::parse(None, &international).map_err(|_| invalid())?;
+    if !phonenumber::is_valid(&number) {
+        return Err(invalid());
+    }
+
+    Ok(number.format().mode(Mode::E164).to_string())
+}
+
+/// Masks all but the last 4 digits of a phone number, e.g. `+4915112345678` as `+*********5678`
+pub fn mask_phone_number(phone_number: &str) -> String {
+    let digits = phone_number.chars().filter(char::is_ascii_digit).count();
+    let mut masked = 0;
+    phone_number
+        .chars()
+        .map(|c| {
+            if c.is_ascii_digit() && masked + 4 < digits {
+                masked += 1;
+                '*'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Outcome of a phone number backfill
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PhoneBackfillReport {
+    pub normalized: usize,
+    pub invalid: usize,
+    pub duplicates: usize,
+}
+
+/// Rewrites the stored phone numbers to E.164.
+///
+/// Invalid numbers are left as they are, and so are numbers whose normalized form is already
+/// another user's, those are reported as duplicates to be merged by hand.
+pub async fn backfill_phone_numbers(
+    db_client: &Client,
+) -> Result<PhoneBackfillReport, tokio_postgres::Error> {
+    let mut report = PhoneBackfillReport::default();
+    let users = db_get_users_with_phone_number(db_client).await?;
+
+    let mut taken = users
+        .iter()
+        .filter_map(|user| user.phone_number.clone())
+        .collect::<HashSet<_>>();
+
+    for user in users {
+        let phone_number = user.phone_number.unwrap_or_default();
+        let normalized = match normalize_phone_number(&phone_number) {
+            Ok(normalized) => normalized,
+            Err(_) => {
+                log::warn!("User {} has an invalid phone number", user.id);
+                report.invalid += 1;
+                continue;
+            }
+        };
+        if normalized.eq(&phone_number) {
+            continue;
+        }
+        if !taken.insert(normalized.clone()) {
+            log::warn!(
+                "User {} phone number duplicates another user's once normalized",
+                user.id
+            );
+            report.duplicates += 1;
+            continue;
+        }
+
+        db_update_user_phone_number(db_client, &user.id, &normalized).await?;
+        taken.remove(&phone_number);
+        report.normalized += 1;
+    }
+
+    Ok(report)
+}
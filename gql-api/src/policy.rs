@@ -0,0 +1,531 @@
+//! Authorization policies, i.e. which user roles and statuses may perform an operation.
+//!
+//! Every role gated route, http handler and GraphQL field has an [`Operation`] and [`policy`]
+//! is the single place its allowed roles and statuses are defined. Access to an event is further
+//! limited to its creator and, per [`event_policy`], the members of its organization and its
+//! collaborators.
+use crate::{
+    auth::{Role, SellerStatus, UserStatus},
+    db::models::{DbEvent, DbEventCollaborator, DbOrganizationMember},
+    gql::models::MemberRole,
+};
+use std::fmt;
+
+const ALL_ROLES: &[Role] = &[Role::Admin, Role::Buyer, Role::Seller, Role::SuperAdmin];
+const ADMINS: &[Role] = &[Role::Admin, Role::SuperAdmin];
+const SELLERS: &[Role] = &[Role::Seller];
+const BUYERS: &[Role] = &[Role::Buyer];
+const SUPER_ADMINS: &[Role] = &[Role::SuperAdmin];
+const ALL_STATUSES: &[UserStatus] = &[UserStatus::Unverified, UserStatus::PhoneVerified];
+const VERIFIED_STATUSES: &[UserStatus] = &[UserStatus::PhoneVerified];
+
+/// A role gated operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    // http routes
+    Signin,
+    SigninWithPassword,
+    SigninVerifyTotp,
+    BuyerCreateRecoveryCode,
+    BuyerVerifyRecoveryCode,
+    BuyerRegisterPhone,
+    BuyerVerifyPhone,
+    BuyerResendPhoneCode,
+    BuyerSignup,
+    CreateLoginCode,
+    VerifyLoginCode,
+    EventTicketGetVerificationCode,
+    GetEventFromVerificationCode,
+    DownloadTicket,
+    // graphql routes
+    PrivateGraphql,
+    PrivateSchema,
+    PrivateSubscriptions,
+    SellerGraphql,
+    BuyerGraphql,
+    AdminGraphql,
+    ExportMyData,
+    ImportEvents,
+    UploadEventAssets,
+    ExportAttendees,
+    // graphql fields
+    DeleteMyAccount,
+    RotateWalletSecret,
+    ExportWallet,
+    MyNotifications,
+    MintNfts,
+    RegisterEvent,
+    PublishEvent,
+    MyEvents,
+    PurgeEvent,
+    TransferTicket,
+    JoinWaitlist,
+    LeaveWaitlist,
+    RegisterPayoutAccount,
+    ApprovePayout,
+    RejectPayout,
+    CreateCategory,
+    AuditLogs,
+    PayoutRequests,
+    ImpersonateUser,
+    RevokeImpersonation,
+    CreateOrganization,
+    AddOrganizationMember,
+    RemoveOrganizationMember,
+    MyOrganizations,
+    CheckInTickets,
+    CloneEvent,
+    EstimateMint,
+    MintJobs,
+    UpdateProfile,
+    ChangePassword,
+    EnrollTotp,
+    ConfirmTotp,
+    SetSellerSlug,
+    TopUpWallet,
+    EventAnalytics,
+    GiftTickets,
+    ClaimGift,
+    SubmitSellerOnboarding,
+    ReviewSellers,
+    ManagePriceTiers,
+    ManageGallery,
+    ManageWebhook,
+    ManageCancellationWindow,
+    CancelEvent,
+    CancelReservation,
+    ManageEventCollaborators,
+    ListTicketForSale,
+    EventReservations,
+    ManageMaintenance,
+    SystemMetrics,
+    UnlockUser,
+    ViewEvent,
+    AuthorizePusherChannel,
+    ReconcileSignups,
+    CreateEventSeries,
+    ManageSeatMap,
+    HoldSeats,
+    SendSellerPhoneCode,
+    VerifySellerPhone,
+    TransactionStatus,
+    ReviewEvents,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 85] = [
+        Operation::Signin,
+        Operation::SigninWithPassword,
+        Operation::SigninVerifyTotp,
+        Operation::BuyerCreateRecoveryCode,
+        Operation::BuyerVerifyRecoveryCode,
+        Operation::BuyerRegisterPhone,
+        Operation::BuyerVerifyPhone,
+        Operation::BuyerResendPhoneCode,
+        Operation::BuyerSignup,
+        Operation::CreateLoginCode,
+        Operation::VerifyLoginCode,
+        Operation::EventTicketGetVerificationCode,
+        Operation::GetEventFromVerificationCode,
+        Operation::DownloadTicket,
+        Operation::PrivateGraphql,
+        Operation::PrivateSchema,
+        Operation::PrivateSubscriptions,
+        Operation::SellerGraphql,
+        Operation::BuyerGraphql,
+        Operation::AdminGraphql,
+        Operation::ExportMyData,
+        Operation::ImportEvents,
+        Operation::UploadEventAssets,
+        Operation::ExportAttendees,
+        Operation::DeleteMyAccount,
+        Operation::RotateWalletSecret,
+        Operation::ExportWallet,
+        Operation::MyNotifications,
+        Operation::MintNfts,
+        Operation::RegisterEvent,
+        Operation::PublishEvent,
+        Operation::MyEvents,
+        Operation::PurgeEvent,
+        Operation::TransferTicket,
+        Operation::JoinWaitlist,
+        Operation::LeaveWaitlist,
+        Operation::RegisterPayoutAccount,
+        Operation::ApprovePayout,
+        Operation::RejectPayout,
+        Operation::CreateCategory,
+        Operation::AuditLogs,
+        Operation::PayoutRequests,
+        Operation::ImpersonateUser,
+        Operation::RevokeImpersonation,
+        Operation::CreateOrganization,
+        Operation::AddOrganizationMember,
+        Operation::RemoveOrganizationMember,
+        Operation::MyOrganizations,
+        Operation::CheckInTickets,
+        Operation::CloneEvent,
+        Operation::EstimateMint,
+        Operation::MintJobs,
+        Operation::UpdateProfile,
+        Operation::ChangePassword,
+        Operation::EnrollTotp,
+        Operation::ConfirmTotp,
+        Operation::SetSellerSlug,
+        Operation::TopUpWallet,
+        Operation::EventAnalytics,
+        Operation::GiftTickets,
+        Operation::ClaimGift,
+        Operation::SubmitSellerOnboarding,
+        Operation::ReviewSellers,
+        Operation::ManagePriceTiers,
+        Operation::ManageGallery,
+        Operation::ManageWebhook,
+        Operation::ManageCancellationWindow,
+        Operation::CancelEvent,
+        Operation::CancelReservation,
+        Operation::ManageEventCollaborators,
+        Operation::ListTicketForSale,
+        Operation::EventReservations,
+        Operation::ManageMaintenance,
+        Operation::SystemMetrics,
+        Operation::UnlockUser,
+        Operation::ViewEvent,
+        Operation::AuthorizePusherChannel,
+        Operation::ReconcileSignups,
+        Operation::CreateEventSeries,
+        Operation::ManageSeatMap,
+        Operation::HoldSeats,
+        Operation::SendSellerPhoneCode,
+        Operation::VerifySellerPhone,
+        Operation::TransactionStatus,
+        Operation::ReviewEvents,
+    ];
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Signin => write!(f, "signin"),
+            Operation::SigninWithPassword => write!(f, "signin_with_password"),
+            Operation::SigninVerifyTotp => write!(f, "signin_verify_totp"),
+            Operation::BuyerCreateRecoveryCode => write!(f, "buyer_create_recovery_code"),
+            Operation::BuyerVerifyRecoveryCode => write!(f, "buyer_verify_recovery_code"),
+            Operation::BuyerRegisterPhone => write!(f, "buyer_register_phone"),
+            Operation::BuyerVerifyPhone => write!(f, "buyer_verify_phone"),
+            Operation::BuyerResendPhoneCode => write!(f, "buyer_resend_phone_code"),
+            Operation::BuyerSignup => write!(f, "buyer_signup"),
+            Operation::CreateLoginCode => write!(f, "create_login_code"),
+            Operation::VerifyLoginCode => write!(f, "verify_login_code"),
+            Operation::EventTicketGetVerificationCode => {
+                write!(f, "event_ticket_get_verification_code")
+            }
+            Operation::GetEventFromVerificationCode => {
+                write!(f, "get_event_from_verification_code")
+            }
+            Operation::DownloadTicket => write!(f, "download_ticket"),
+            Operation::PrivateGraphql => write!(f, "private_graphql"),
+            Operation::PrivateSchema => write!(f, "private_schema"),
+            Operation::PrivateSubscriptions => write!(f, "private_subscriptions"),
+            Operation::SellerGraphql => write!(f, "seller_graphql"),
+            Operation::BuyerGraphql => write!(f, "buyer_graphql"),
+            Operation::AdminGraphql => write!(f, "admin_graphql"),
+            Operation::ExportMyData => write!(f, "export_my_data"),
+            Operation::ImportEvents => write!(f, "import_events"),
+            Operation::UploadEventAssets => write!(f, "upload_event_assets"),
+            Operation::ExportAttendees => write!(f, "export_attendees"),
+            Operation::DeleteMyAccount => write!(f, "delete_my_account"),
+            Operation::RotateWalletSecret => write!(f, "rotate_wallet_secret"),
+            Operation::ExportWallet => write!(f, "export_wallet"),
+            Operation::MyNotifications => write!(f, "my_notifications"),
+            Operation::MintNfts => write!(f, "mint_nfts"),
+            Operation::RegisterEvent => write!(f, "register_event"),
+            Operation::PublishEvent => write!(f, "publish_event"),
+            Operation::MyEvents => write!(f, "my_events"),
+            Operation::PurgeEvent => write!(f, "purge_event"),
+            Operation::TransferTicket => write!(f, "transfer_ticket"),
+            Operation::JoinWaitlist => write!(f, "join_waitlist"),
+            Operation::LeaveWaitlist => write!(f, "leave_waitlist"),
+            Operation::RegisterPayoutAccount => write!(f, "register_payout_account"),
+            Operation::ApprovePayout => write!(f, "approve_payout"),
+            Operation::RejectPayout => write!(f, "reject_payout"),
+            Operation::CreateCategory => write!(f, "create_category"),
+            Operation::AuditLogs => write!(f, "audit_logs"),
+            Operation::PayoutRequests => write!(f, "payout_requests"),
+            Operation::ImpersonateUser => write!(f, "impersonate_user"),
+            Operation::RevokeImpersonation => write!(f, "revoke_impersonation"),
+            Operation::CreateOrganization => write!(f, "create_organization"),
+            Operation::AddOrganizationMember => write!(f, "add_organization_member"),
+            Operation::RemoveOrganizationMember => write!(f, "remove_organization_member"),
+            Operation::MyOrganizations => write!(f, "my_organizations"),
+            Operation::CheckInTickets => write!(f, "check_in_tickets"),
+            Operation::CloneEvent => write!(f, "clone_event"),
+            Operation::EstimateMint => write!(f, "estimate_mint"),
+            Operation::MintJobs => write!(f, "mint_jobs"),
+            Operation::UpdateProfile => write!(f, "update_profile"),
+            Operation::ChangePassword => write!(f, "change_password"),
+            Operation::EnrollTotp => write!(f, "enroll_totp"),
+            Operation::ConfirmTotp => write!(f, "confirm_totp"),
+            Operation::SetSellerSlug => write!(f, "set_seller_slug"),
+            Operation::TopUpWallet => write!(f, "top_up_wallet"),
+            Operation::EventAnalytics => write!(f, "event_analytics"),
+            Operation::GiftTickets => write!(f, "gift_tickets"),
+            Operation::ClaimGift => write!(f, "claim_gift"),
+            Operation::SubmitSellerOnboarding => write!(f, "submit_seller_onboarding"),
+            Operation::ReviewSellers => write!(f, "review_sellers"),
+            Operation::ManagePriceTiers => write!(f, "manage_price_tiers"),
+            Operation::ManageGallery => write!(f, "manage_gallery"),
+            Operation::ManageWebhook => write!(f, "manage_webhook"),
+            Operation::ManageCancellationWindow => write!(f, "manage_cancellation_window"),
+            Operation::CancelEvent => write!(f, "cancel_event"),
+            Operation::CancelReservation => write!(f, "cancel_reservation"),
+            Operation::ManageEventCollaborators => write!(f, "manage_event_collaborators"),
+            Operation::ListTicketForSale => write!(f, "list_ticket_for_sale"),
+            Operation::EventReservations => write!(f, "event_reservations"),
+            Operation::ManageMaintenance => write!(f, "manage_maintenance"),
+            Operation::SystemMetrics => write!(f, "system_metrics"),
+            Operation::UnlockUser => write!(f, "unlock_user"),
+            Operation::ViewEvent => write!(f, "view_event"),
+            Operation::AuthorizePusherChannel => write!(f, "authorize_pusher_channel"),
+            Operation::ReconcileSignups => write!(f, "reconcile_signups"),
+            Operation::CreateEventSeries => write!(f, "create_event_series"),
+            Operation::ManageSeatMap => write!(f, "manage_seat_map"),
+            Operation::HoldSeats => write!(f, "hold_seats"),
+            Operation::SendSellerPhoneCode => write!(f, "send_seller_phone_code"),
+            Operation::VerifySellerPhone => write!(f, "verify_seller_phone"),
+            Operation::TransactionStatus => write!(f, "transaction_status"),
+            Operation::ReviewEvents => write!(f, "review_events"),
+        }
+    }
+}
+
+/// The roles and statuses allowed to perform an operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    pub roles: &'static [Role],
+    pub statuses: &'static [UserStatus],
+    /// sellers must have passed the onboarding review
+    pub approved_sellers_only: bool,
+    /// only the user themselves, never a super admin impersonating them
+    pub owner_only: bool,
+}
+
+impl Policy {
+    const fn new(roles: &'static [Role]) -> Self {
+        Policy {
+            roles,
+            statuses: ALL_STATUSES,
+            approved_sellers_only: false,
+            owner_only: false,
+        }
+    }
+
+    const fn owner_only(self) -> Self {
+        Policy {
+            owner_only: true,
+            ..self
+        }
+    }
+
+    const fn approved_sellers(self) -> Self {
+        Policy {
+            approved_sellers_only: true,
+            ..self
+        }
+    }
+
+    /// only users who verified their phone, the unverified ones may only verify it
+    const fn verified(self) -> Self {
+        Policy {
+            statuses: VERIFIED_STATUSES,
+            ..self
+        }
+    }
+
+    /// Whether some status does not allow the operation
+    pub fn requires_status(&self) -> bool {
+        ALL_STATUSES
+            .iter()
+            .any(|status| !self.statuses.contains(status))
+    }
+
+    pub fn allows_role(&self, role: &Role) -> bool {
+        self.roles.contains(role)
+    }
+
+    pub fn allows(&self, role: &Role, status: &UserStatus) -> bool {
+        self.allows_role(role) && self.statuses.contains(status)
+    }
+
+    /// Whether the seller status allows the operation, always true for the other roles
+    pub fn allows_seller(&self, role: &Role, seller_status: Option<&SellerStatus>) -> bool {
+        !self.approved_sellers_only
+            || role.ne(&Role::Seller)
+            || seller_status.eq(&Some(&SellerStatus::Approved))
+    }
+}
+
+/// The policy map
+pub const fn policy(operation: Operation) -> Policy {
+    match operation {
+        // unverified sellers sign in to verify their phone
+        Operation::Signin | Operation::SendSellerPhoneCode | Operation::VerifySellerPhone => {
+            Policy::new(SELLERS)
+        }
+        // the password sign-in and its second factor
+        Operation::SigninWithPassword | Operation::SigninVerifyTotp => {
+            Policy::new(&[Role::Seller, Role::Admin])
+        }
+        Operation::EnrollTotp | Operation::ConfirmTotp => {
+            Policy::new(&[Role::Seller, Role::Admin]).owner_only()
+        }
+        Operation::BuyerCreateRecoveryCode
+        | Operation::BuyerVerifyRecoveryCode
+        | Operation::BuyerRegisterPhone
+        | Operation::BuyerVerifyPhone
+        | Operation::BuyerResendPhoneCode
+        | Operation::BuyerSignup
+        | Operation::CreateLoginCode
+        | Operation::VerifyLoginCode
+        | Operation::EventTicketGetVerificationCode
+        | Operation::GetEventFromVerificationCode
+        | Operation::DownloadTicket => Policy::new(BUYERS),
+        Operation::PrivateGraphql
+        | Operation::PrivateSchema
+        | Operation::PrivateSubscriptions
+        | Operation::ExportMyData
+        | Operation::MyNotifications
+        | Operation::ViewEvent
+        | Operation::AuthorizePusherChannel
+        | Operation::TransactionStatus => Policy::new(ALL_ROLES),
+        // the account and its credentials stay with the user
+        Operation::DeleteMyAccount | Operation::UpdateProfile | Operation::ChangePassword => {
+            Policy::new(ALL_ROLES).owner_only()
+        }
+        // only sellers who passed the onboarding review go live
+        Operation::MintNfts | Operation::PublishEvent => {
+            Policy::new(SELLERS).approved_sellers().verified()
+        }
+        // the seller schema and everything else once their phone is verified
+        Operation::SellerGraphql
+        | Operation::ImportEvents
+        | Operation::UploadEventAssets
+        | Operation::RegisterEvent
+        | Operation::SubmitSellerOnboarding
+        | Operation::ManagePriceTiers
+        | Operation::ManageGallery
+        | Operation::ManageWebhook
+        | Operation::ManageCancellationWindow
+        | Operation::CancelEvent
+        | Operation::MyEvents
+        | Operation::RegisterPayoutAccount
+        | Operation::CreateOrganization
+        | Operation::AddOrganizationMember
+        | Operation::RemoveOrganizationMember
+        | Operation::ManageEventCollaborators
+        | Operation::MyOrganizations
+        | Operation::CheckInTickets
+        | Operation::EventReservations
+        | Operation::CloneEvent
+        | Operation::CreateEventSeries
+        | Operation::ManageSeatMap
+        | Operation::EstimateMint
+        | Operation::MintJobs
+        | Operation::SetSellerSlug
+        | Operation::EventAnalytics => Policy::new(SELLERS).verified(),
+        Operation::BuyerGraphql
+        | Operation::TransferTicket
+        | Operation::ListTicketForSale
+        | Operation::CancelReservation
+        | Operation::GiftTickets
+        | Operation::ClaimGift
+        | Operation::JoinWaitlist
+        | Operation::LeaveWaitlist
+        | Operation::HoldSeats => Policy::new(BUYERS),
+        // the wallet's secret key stays with the buyer
+        Operation::RotateWalletSecret | Operation::ExportWallet => Policy::new(BUYERS).owner_only(),
+        // sellers only for their own events
+        Operation::ExportAttendees => Policy::new(&[Role::Seller, Role::Admin, Role::SuperAdmin]),
+        Operation::AdminGraphql
+        | Operation::PurgeEvent
+        | Operation::ApprovePayout
+        | Operation::RejectPayout
+        | Operation::CreateCategory
+        | Operation::AuditLogs
+        | Operation::PayoutRequests
+        | Operation::TopUpWallet
+        | Operation::ReviewSellers
+        | Operation::ReviewEvents
+        | Operation::ManageMaintenance
+        | Operation::UnlockUser
+        | Operation::ReconcileSignups => Policy::new(ADMINS),
+        Operation::ImpersonateUser | Operation::RevokeImpersonation | Operation::SystemMetrics => {
+            Policy::new(SUPER_ADMINS)
+        }
+    }
+}
+
+/// What a user does to an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventAccess {
+    /// edit the event, its tickets, assets and promo codes
+    Edit,
+    /// check in the event's attendees
+    CheckIn,
+    /// cancel the event, refunding its buyers
+    Cancel,
+}
+
+impl fmt::Display for EventAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventAccess::Edit => write!(f, "edit"),
+            EventAccess::CheckIn => write!(f, "check_in"),
+            EventAccess::Cancel => write!(f, "cancel"),
+        }
+    }
+}
+
+/// The organization member and collaborator roles allowed to access an event
+pub const fn event_policy(access: EventAccess) -> &'static [MemberRole] {
+    match access {
+        EventAccess::Edit => &[MemberRole::Owner, MemberRole::Editor],
+        EventAccess::CheckIn => &[MemberRole::Owner, MemberRole::Editor, MemberRole::Scanner],
+        EventAccess::Cancel => &[MemberRole::Owner],
+    }
+}
+
+/// Whether the user may access the event: its creator always may, a member of the organization
+/// managing the event if their role allows it
+pub fn can_access_event(
+    user_id: &uuid::Uuid,
+    db_event: &DbEvent,
+    member: Option<&DbOrganizationMember>,
+    access: EventAccess,
+) -> bool {
+    if db_event.created_by_user.eq(user_id) {
+        return true;
+    }
+    match (db_event.organization_id, member) {
+        (Some(organization_id), Some(member)) => {
+            member.organization_id.eq(&organization_id)
+                && member.user_id.eq(user_id)
+                && event_policy(access).contains(&member.member_role)
+        }
+        _ => false,
+    }
+}
+
+/// Whether a collaborator of the event may access it, their role allows the same as an
+/// organization member's
+pub fn can_collaborate(
+    user_id: &uuid::Uuid,
+    db_event: &DbEvent,
+    collaborator: Option<&DbEventCollaborator>,
+    access: EventAccess,
+) -> bool {
+    collaborator.map_or(false, |collaborator| {
+        collaborator.event_id.eq(&db_event.id)
+            && collaborator.user_id.eq(user_id)
+            && event_policy(access).contains(&collaborator.collaborator_role)
+    })
+}
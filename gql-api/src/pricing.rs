@@ -0,0 +1,85 @@
+//! Ticket price tiers, e.g. an early-bird price before the regular one.
+//!
+//! A ticket costs the price of its earliest starting tier that is open and not sold out, or its
+//! own price when none is. The tier is claimed atomically with its quantity when the tickets are
+//! reserved, so that concurrent reservations cannot exceed its cap.
+use crate::{
+    db::{
+        models::{DbTicket, DbTicketPriceTier},
+        sql::{
+            db_claim_ticket_price_tier, db_get_price_tiers_by_ticket_ids,
+            db_release_ticket_price_tier,
+        },
+    },
+    near::NearAmount,
+};
+use chrono::NaiveDateTime;
+use tokio_postgres::Client;
+
+/// The tier the ticket sells at, `tiers` ordered by their start
+pub fn active_tier<'a>(
+    tiers: &'a [DbTicketPriceTier],
+    now: &NaiveDateTime,
+) -> Option<&'a DbTicketPriceTier> {
+    tiers
+        .iter()
+        .find(|tier| tier.is_open_at(now) && tier.has_quantity_for(1))
+}
+
+/// The tiers that have not started yet, `tiers` ordered by their start
+pub fn upcoming_tiers<'a>(
+    tiers: &'a [DbTicketPriceTier],
+    now: &'a NaiveDateTime,
+) -> impl Iterator<Item = &'a DbTicketPriceTier> {
+    tiers.iter().filter(move |tier| tier.starts_at.gt(now))
+}
+
+/// The price tickets were reserved at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimedPrice {
+    /// the claimed tier, `None` for the ticket's own price
+    pub tier_id: Option<uuid::Uuid>,
+    pub price: Option<NearAmount>,
+}
+
+/// Claims `quantity` tickets of the first tier with enough of them left, falling back to the
+/// ticket's own price
+pub async fn claim_ticket_price(
+    db_client: &Client,
+    db_ticket: &DbTicket,
+    quantity: i32,
+    now: &NaiveDateTime,
+) -> Result<ClaimedPrice, tokio_postgres::Error> {
+    let tiers = db_get_price_tiers_by_ticket_ids(db_client, &[db_ticket.id]).await?;
+    for tier in tiers
+        .iter()
+        .filter(|tier| tier.is_open_at(now) && tier.has_quantity_for(quantity))
+    {
+        // another reservation may have taken the tier's last tickets meanwhile
+        if let Some(claimed) =
+            db_claim_ticket_price_tier(db_client, &tier.id, quantity, now).await?
+        {
+            return Ok(ClaimedPrice {
+                tier_id: Some(claimed.id),
+                price: Some(claimed.price),
+            });
+        }
+    }
+
+    Ok(ClaimedPrice {
+        tier_id: None,
+        price: db_ticket.price,
+    })
+}
+
+/// Gives back the claimed tickets of the tier, when their reservation failed
+pub async fn release_ticket_price(
+    db_client: &Client,
+    claimed: &ClaimedPrice,
+    quantity: i32,
+) -> Result<(), tokio_postgres::Error> {
+    if let Some(tier_id) = claimed.tier_id {
+        db_release_ticket_price_tier(db_client, &tier_id, quantity).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,33 @@
+//! Realtime events to clients, published through pusher.
+use crate::{
+    error::PublisherError,
+    jobs::models::{PusherChannel, PusherEvent},
+};
+use async_trait::async_trait;
+use pusher_client::client::PusherClient;
+
+/// A channel realtime events are published on
+#[async_trait]
+pub trait Publisher: Send + Sync {
+    async fn publish(
+        &self,
+        channel: PusherChannel,
+        event: PusherEvent,
+        data: &str,
+    ) -> Result<(), PublisherError>;
+}
+
+#[async_trait]
+impl Publisher for PusherClient {
+    async fn publish(
+        &self,
+        channel: PusherChannel,
+        event: PusherEvent,
+        data: &str,
+    ) -> Result<(), PublisherError> {
+        self.send(channel.into(), event.into(), &data.to_string())
+            .await
+            .map(|_| ())
+            .map_err(|e| PublisherError::Send(e.to_string()))
+    }
+}
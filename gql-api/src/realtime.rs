@@ -0,0 +1,161 @@
+//! Realtime updates of events, published to their clients over pusher.
+//!
+//! Every event has its own channel (see [`event_channel`]) the clients showing the event
+//! subscribe to. Updates are broadcast through the outbox, written along with the change they
+//! tell about (see [`outbox_message`]), or through pusher jobs, so that a pusher outage does not
+//! fail the mutation that caused them.
+//!
+//! The channels of a single user are private, pusher only lets the clients the api authorized
+//! subscribe to them (see [`parse_auth_channel`]).
+use crate::{
+    db::models::{DbJob, DbOutboxMessage},
+    jobs::{
+        models::{JobPayload, OutboxMessage, PusherChannel, PusherEvent},
+        queue::enqueue,
+    },
+};
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Client;
+use uuid::Uuid;
+
+const EVENT_CHANNEL_PREFIX: &str = "event-";
+const PRIVATE_CHANNEL_PREFIX: &str = "private-";
+const PRESENCE_CHANNEL_PREFIX: &str = "presence-";
+const USER_SCOPE: &str = "user-";
+const LOGIN_SCOPE: &str = "login-";
+
+/// The name of the channel of an event
+pub fn event_channel(event_id: &Uuid) -> String {
+    format!("{}{}", EVENT_CHANNEL_PREFIX, event_id)
+}
+
+/// The name of the private channel of a user's own notifications, e.g. event reminders
+pub fn user_channel(user_id: &Uuid) -> String {
+    format!("{}{}{}", PRIVATE_CHANNEL_PREFIX, USER_SCOPE, user_id)
+}
+
+/// The name of the private channel the jwt of a login code is sent on once the code is verified
+pub fn login_channel(login_code: &str) -> String {
+    format!("{}{}{}", PRIVATE_CHANNEL_PREFIX, LOGIN_SCOPE, login_code)
+}
+
+/// Whom a private or presence channel belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelOwner {
+    /// the user signed in with the jwt, `user-{uuid}`
+    User(Uuid),
+    /// the client that created the login code, `login-{code}`
+    LoginCode(String),
+}
+
+/// A channel a client asks to subscribe to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthChannel {
+    /// a presence channel, whose members are told about each other
+    pub presence: bool,
+    pub owner: ChannelOwner,
+}
+
+/// The owner of a `private-` or `presence-` channel, `None` for the channels no client may
+/// subscribe to
+pub fn parse_auth_channel(channel: &str) -> Option<AuthChannel> {
+    let (presence, scoped) = match channel.strip_prefix(PRIVATE_CHANNEL_PREFIX) {
+        Some(scoped) => (false, scoped),
+        None => (true, channel.strip_prefix(PRESENCE_CHANNEL_PREFIX)?),
+    };
+    let owner = if let Some(user_id) = scoped.strip_prefix(USER_SCOPE) {
+        ChannelOwner::User(Uuid::parse_str(user_id).ok()?)
+    } else {
+        let login_code = scoped.strip_prefix(LOGIN_SCOPE)?;
+        if login_code.is_empty() || !login_code.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        ChannelOwner::LoginCode(login_code.to_string())
+    };
+    Some(AuthChannel { presence, owner })
+}
+
+/// The event of a channel named by [`event_channel`]
+pub fn parse_event_channel(channel: &str) -> Option<Uuid> {
+    channel
+        .strip_prefix(EVENT_CHANNEL_PREFIX)
+        .and_then(|event_id| Uuid::parse_str(event_id).ok())
+}
+
+/// A change of an event its clients are told about
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EventUpdate {
+    /// the event was made public
+    Published,
+    /// a ticket was added to the event
+    TicketAdded { ticket_id: Uuid },
+    /// the last ticket of a ticket type was reserved
+    SoldOut { ticket_id: Uuid },
+    /// the nfts of a ticket type were minted
+    MintingComplete { ticket_id: Uuid, tx_hash: String },
+    /// the mint transaction of a ticket type failed
+    MintingFailed { ticket_id: Uuid, tx_hash: String },
+    /// tickets of a sold out ticket type can be reserved again
+    TicketAvailable { ticket_id: Uuid },
+}
+
+impl EventUpdate {
+    pub fn pusher_event(&self) -> PusherEvent {
+        match self {
+            EventUpdate::Published => PusherEvent::EventPublished,
+            EventUpdate::TicketAdded { .. } => PusherEvent::TicketAdded,
+            EventUpdate::SoldOut { .. } => PusherEvent::TicketSoldOut,
+            EventUpdate::MintingComplete { .. } => PusherEvent::MintingComplete,
+            EventUpdate::MintingFailed { .. } => PusherEvent::MintingFailed,
+            EventUpdate::TicketAvailable { .. } => PusherEvent::TicketAvailable,
+        }
+    }
+
+    /// the json data of the update's pusher event
+    pub fn data(&self, event_id: &Uuid) -> String {
+        let data = match self {
+            EventUpdate::Published => serde_json::json!({ "eventId": event_id }),
+            EventUpdate::TicketAdded { ticket_id }
+            | EventUpdate::SoldOut { ticket_id }
+            | EventUpdate::TicketAvailable { ticket_id } => {
+                serde_json::json!({ "eventId": event_id, "ticketId": ticket_id })
+            }
+            EventUpdate::MintingComplete { ticket_id, tx_hash }
+            | EventUpdate::MintingFailed { ticket_id, tx_hash } => serde_json::json!({
+                "eventId": event_id,
+                "ticketId": ticket_id,
+                "txHash": tx_hash,
+            }),
+        };
+        data.to_string()
+    }
+}
+
+/// The outbox message broadcasting an update to the channel of the event
+pub fn outbox_message(event_id: Uuid, update: &EventUpdate) -> DbOutboxMessage {
+    DbOutboxMessage::new(&OutboxMessage::PusherEvent {
+        channel: PusherChannel::Event(event_id),
+        event: update.pusher_event(),
+        data: update.data(&event_id),
+    })
+}
+
+/// Enqueues the broadcast of an update to the channel of the event
+pub async fn broadcast(
+    db_client: &Client,
+    event_id: Uuid,
+    update: EventUpdate,
+    max_attempts: i32,
+) -> Result<DbJob, tokio_postgres::Error> {
+    enqueue(
+        db_client,
+        JobPayload::PusherEvent {
+            channel: PusherChannel::Event(event_id),
+            event: update.pusher_event(),
+            data: update.data(&event_id),
+        },
+        max_attempts,
+    )
+    .await
+}
@@ -0,0 +1,215 @@
+//! Recurrence rules of event series.
+//!
+//! A subset of the iCalendar RRULE (RFC 5545) is supported: `FREQ` is `DAILY`, `WEEKLY` or
+//! `MONTHLY`, along with `INTERVAL`, either `COUNT` or `UNTIL`, and `BYDAY` as plain weekdays
+//! (`MO,WE`) on weekly rules only. As in the RFC the start of the series is always its first
+//! occurrence and counts towards `COUNT`, the later ones are the dates matching the rule. A monthly
+//! rule skips the months without the start's day, e.g. the 31st.
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
+use displaydoc::Display as DisplayDoc;
+use std::{collections::HashSet, str::FromStr};
+use thiserror::Error;
+
+#[derive(Debug, DisplayDoc, Error, PartialEq, Eq)]
+pub enum RecurrenceError {
+    /// The rule has no FREQ
+    MissingFrequency,
+    /// Unsupported frequency `{0}`, only DAILY, WEEKLY and MONTHLY are
+    UnsupportedFrequency(String),
+    /// Unsupported rule part `{0}`
+    UnsupportedPart(String),
+    /// Malformed rule part `{0}`
+    MalformedPart(String),
+    /// The rule part `{0}` is repeated
+    DuplicatePart(String),
+    /// Invalid value of `{0}`
+    InvalidValue(String),
+    /// COUNT and UNTIL exclude each other
+    CountAndUntil,
+    /// BYDAY is only supported on WEEKLY rules
+    ByDayNotWeekly,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    /// every how many days, weeks or months the rule repeats
+    pub interval: u32,
+    /// how many occurrences, the start included
+    pub count: Option<u32>,
+    /// the last possible occurrence
+    pub until: Option<NaiveDateTime>,
+    /// the weekdays of a weekly rule, from monday on, the start's weekday if empty
+    pub by_day: Vec<Weekday>,
+}
+
+impl FromStr for Recurrence {
+    type Err = RecurrenceError;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let rule = rule.trim();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+        let mut by_day = vec![];
+        let mut seen = HashSet::new();
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| RecurrenceError::MalformedPart(part.to_string()))?;
+            let name = name.trim().to_ascii_uppercase();
+            let value = value.trim().to_ascii_uppercase();
+            if !seen.insert(name.clone()) {
+                return Err(RecurrenceError::DuplicatePart(name));
+            }
+            let invalid = || RecurrenceError::InvalidValue(name.clone());
+
+            match name.as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return Err(RecurrenceError::UnsupportedFrequency(value)),
+                    })
+                }
+                "INTERVAL" => interval = positive(&value).ok_or_else(invalid)?,
+                "COUNT" => count = Some(positive(&value).ok_or_else(invalid)?),
+                "UNTIL" => until = Some(parse_until(&value).ok_or_else(invalid)?),
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(|day| parse_weekday(day.trim()).ok_or_else(invalid))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    by_day.sort_by_key(Weekday::num_days_from_monday);
+                    by_day.dedup();
+                }
+                _ => return Err(RecurrenceError::UnsupportedPart(name)),
+            }
+        }
+
+        let frequency = frequency.ok_or(RecurrenceError::MissingFrequency)?;
+        if count.is_some() && until.is_some() {
+            return Err(RecurrenceError::CountAndUntil);
+        }
+        if !by_day.is_empty() && frequency != Frequency::Weekly {
+            return Err(RecurrenceError::ByDayNotWeekly);
+        }
+
+        Ok(Recurrence {
+            frequency,
+            interval,
+            count,
+            until,
+            by_day,
+        })
+    }
+}
+
+impl Recurrence {
+    /// The occurrences of the rule starting at `start`, in order, at most `max` of them
+    pub fn occurrences(&self, start: NaiveDateTime, max: usize) -> Vec<NaiveDateTime> {
+        let limit = self
+            .count
+            .map_or(max, |count| usize::try_from(count).unwrap_or(max).min(max));
+        let before_until = |date: &NaiveDateTime| self.until.map_or(true, |until| *date <= until);
+
+        let mut occurrences = vec![];
+        if limit == 0 || !before_until(&start) {
+            return occurrences;
+        }
+        occurrences.push(start);
+
+        let mut period = 0;
+        while occurrences.len() < limit {
+            let (period_start, dates) = match self.period(start, period) {
+                Some(period) => period,
+                None => break,
+            };
+            if !before_until(&period_start) {
+                break;
+            }
+            occurrences.extend(
+                dates
+                    .into_iter()
+                    .filter(|date| *date > start && before_until(date))
+                    .take(limit - occurrences.len()),
+            );
+            period += 1;
+        }
+        occurrences
+    }
+
+    /// The start of the `n`th day, week or month of the rule and the dates matching it then, none
+    /// past the dates supported
+    fn period(&self, start: NaiveDateTime, n: i64) -> Option<(NaiveDateTime, Vec<NaiveDateTime>)> {
+        let step = i64::from(self.interval).checked_mul(n)?;
+        match self.frequency {
+            Frequency::Daily => {
+                let day = start.checked_add_signed(Duration::days(step))?;
+                Some((day, vec![day]))
+            }
+            Frequency::Weekly => {
+                let week = start.checked_add_signed(Duration::weeks(step))?;
+                if self.by_day.is_empty() {
+                    return Some((week, vec![week]));
+                }
+                let monday =
+                    week - Duration::days(i64::from(week.weekday().num_days_from_monday()));
+                let days = self
+                    .by_day
+                    .iter()
+                    .map(|day| monday + Duration::days(i64::from(day.num_days_from_monday())))
+                    .collect();
+                Some((monday, days))
+            }
+            Frequency::Monthly => {
+                let months = i64::from(start.year()) * 12 + i64::from(start.month0()) + step;
+                let year = i32::try_from(months.div_euclid(12)).ok()?;
+                let month = u32::try_from(months.rem_euclid(12)).ok()? + 1;
+                let first = NaiveDate::from_ymd_opt(year, month, 1)?.and_time(start.time());
+                let day = NaiveDate::from_ymd_opt(year, month, start.day())
+                    .map(|date| date.and_time(start.time()));
+                Some((first, day.into_iter().collect()))
+            }
+        }
+    }
+}
+
+fn positive(value: &str) -> Option<u32> {
+    value.parse().ok().filter(|value| *value > 0)
+}
+
+/// `UNTIL` as a date, the whole day included, or as a date and time, in UTC with or without `Z`
+fn parse_until(value: &str) -> Option<NaiveDateTime> {
+    let value = value.strip_suffix('Z').unwrap_or(value);
+    match NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        Ok(until) => Some(until),
+        Err(_) => NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(23, 59, 59)),
+    }
+}
+
+fn parse_weekday(day: &str) -> Option<Weekday> {
+    match day {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
@@ -0,0 +1,109 @@
+//! Sanitation of user-generated text.
+//!
+//! Event names and descriptions, venues, ticket names and usernames are shown to other users, so
+//! before they are validated and stored they are trimmed, NFC-normalized so that the same text
+//! typed on different keyboards is stored the same, and stripped of control and bidirectional
+//! formatting characters. Descriptions keep their line breaks. With `escape-html` the free text
+//! is also HTML-escaped, for the clients rendering it as HTML.
+use crate::{config::SanitationConfig, error::SanitizeError};
+use unicode_normalization::UnicodeNormalization;
+
+/// The user-generated text fields, each with its own max length
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextField {
+    EventName,
+    EventDescription,
+    VenueName,
+    VenueLocation,
+    TicketName,
+    TicketDescription,
+    Username,
+}
+
+impl TextField {
+    /// The field named as in the api inputs
+    pub const fn name(self) -> &'static str {
+        match self {
+            TextField::EventName => "event_name",
+            TextField::EventDescription => "event_description",
+            TextField::VenueName => "event_venue_name",
+            TextField::VenueLocation => "event_venue_location",
+            TextField::TicketName => "ticket_name",
+            TextField::TicketDescription => "ticket_description",
+            TextField::Username => "username",
+        }
+    }
+
+    /// Whether the text keeps its line breaks
+    const fn is_multiline(self) -> bool {
+        matches!(
+            self,
+            TextField::EventDescription | TextField::TicketDescription
+        )
+    }
+
+    /// Whether the text may be escaped, usernames are account ids and stay as they are
+    const fn is_escapable(self) -> bool {
+        !matches!(self, TextField::Username)
+    }
+}
+
+/// The bidirectional formatting characters, which reorder the text around them
+const fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}')
+}
+
+/// The text trimmed, NFC-normalized and without control characters. Tabs become spaces, and so
+/// do line breaks unless `multiline`.
+pub fn sanitize_text(text: &str, multiline: bool) -> String {
+    text.replace("\r\n", "\n")
+        .nfc()
+        .map(|c| match c {
+            '\t' => ' ',
+            '\n' if !multiline => ' ',
+            c => c,
+        })
+        .filter(|&c| (multiline && c == '\n') || !(c.is_control() || is_bidi_control(c)))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// The text with the HTML special characters escaped
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The sanitized field, an error if it is empty or longer than its max length once sanitized.
+/// The length is counted in characters before any escaping.
+pub fn sanitize_field(
+    config: &SanitationConfig,
+    field: TextField,
+    text: &str,
+) -> Result<String, SanitizeError> {
+    let sanitized = sanitize_text(text, field.is_multiline());
+    if sanitized.is_empty() {
+        return Err(SanitizeError::Empty(field.name()));
+    }
+    let max_len = config.max_lengths.max_len(field);
+    if sanitized.chars().count() > max_len {
+        return Err(SanitizeError::TooLong(field.name(), max_len));
+    }
+
+    if config.escape_html && field.is_escapable() {
+        Ok(escape_html(&sanitized))
+    } else {
+        Ok(sanitized)
+    }
+}
@@ -0,0 +1,13 @@
+//! Sign-in challenges: a seller proves they hold a key of their wallet by signing a random nonce
+//! issued by the server, so that a captured signature cannot be replayed.
+use rand::{rngs::OsRng, Rng};
+
+/// A random nonce, hex encoded
+pub fn gen_nonce() -> String {
+    hex::encode(OsRng.gen::<[u8; 32]>())
+}
+
+/// The message signed to answer the challenge of a nonce
+pub fn challenge_message(prefix: &str, nonce: &str) -> String {
+    format!("{}{}", prefix, nonce)
+}
@@ -0,0 +1,77 @@
+//! Hashes of the one-time codes sent to buyers, verification and recovery codes alike.
+//!
+//! Only a salted HMAC-SHA256 of a code is stored, so that a leaked session table does not give
+//! the codes away: `v1${salt}${mac}`, the salt and the mac hex encoded and the mac keyed with the
+//! codes secret. The scheme is named by its prefix so that it can be rotated, and hashes keyed
+//! with a previous secret or the plaintext codes stored before hashing still verify until their
+//! sessions expire. Codes are always compared in constant time.
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, Rng};
+use sha2::Sha256;
+
+const SEPARATOR: char = '$';
+const V1: &str = "v1";
+
+/// The keys of the hmac hashing the codes
+#[derive(Clone)]
+pub struct Codes {
+    secret: String,
+    previous_secrets: Vec<String>,
+}
+
+fn mac(secret: &str, salt: &[u8], code: &str) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac takes keys of any size");
+    mac.update(salt);
+    mac.update(code.as_bytes());
+    mac
+}
+
+impl Codes {
+    pub fn new(secret: &str, previous_secrets: &[String]) -> Self {
+        Codes {
+            secret: secret.to_string(),
+            previous_secrets: previous_secrets.to_vec(),
+        }
+    }
+
+    /// The hash of a code to store, salted afresh
+    pub fn hash(&self, code: &str) -> String {
+        let salt = OsRng.gen::<[u8; 16]>();
+        let mac = mac(&self.secret, &salt, code).finalize().into_bytes();
+        format!(
+            "{}{}{}{}{}",
+            V1,
+            SEPARATOR,
+            hex::encode(salt),
+            SEPARATOR,
+            hex::encode(mac)
+        )
+    }
+
+    /// Whether the code is the one a stored hash was made of
+    pub fn verify(&self, stored: &str, code: &str) -> bool {
+        match stored.split_once(SEPARATOR) {
+            Some((V1, hash)) => self.verify_v1(hash, code),
+            Some(_) => false,
+            // stored before the codes were hashed, compared as the macs of both
+            None => mac(&self.secret, &[], stored)
+                .verify_slice(&mac(&self.secret, &[], code).finalize().into_bytes())
+                .is_ok(),
+        }
+    }
+
+    fn verify_v1(&self, hash: &str, code: &str) -> bool {
+        let (salt, expected) = match hash.split_once(SEPARATOR) {
+            Some((salt, expected)) => (salt, expected),
+            None => return false,
+        };
+        let (salt, expected) = match (hex::decode(salt), hex::decode(expected)) {
+            (Ok(salt), Ok(expected)) => (salt, expected),
+            _ => return false,
+        };
+        std::iter::once(&self.secret)
+            .chain(self.previous_secrets.iter())
+            .any(|secret| mac(secret, &salt, code).verify_slice(&expected).is_ok())
+    }
+}
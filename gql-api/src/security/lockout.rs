@@ -0,0 +1,55 @@
+//! Backoff and lockout of repeated failed password sign-ins.
+//!
+//! The failed attempts are counted per user and address. Past the free attempts, an address
+//! waits before each further attempt, the wait doubling every time up to a maximum. Once the
+//! user's failed attempts from all addresses reach the lockout threshold, the account is locked
+//! for everyone until the lockout is over or an admin unlocks it. Attempts are forgotten a while
+//! after the last one.
+use crate::{config::LoginLockoutConfig, db::models::DbLoginAttempt};
+use chrono::{Duration, NaiveDateTime};
+
+/// The wait after the address's `failed_attempts`-th failed attempt, none within the free ones
+pub fn backoff_secs(config: &LoginLockoutConfig, failed_attempts: i32) -> i64 {
+    let doublings = failed_attempts - config.free_attempts - 1;
+    if doublings < 0 {
+        return 0;
+    }
+    // 2^62 already exceeds any maximum
+    let factor = 1i64 << doublings.min(62);
+    config
+        .backoff_base_secs
+        .saturating_mul(factor)
+        .min(config.backoff_max_secs)
+}
+
+/// Until when the user may not sign in with a password from the address, `None` if they may
+pub fn login_locked_until(
+    config: &LoginLockoutConfig,
+    attempts: &[DbLoginAttempt],
+    ip_address: &str,
+    now: &NaiveDateTime,
+) -> Option<NaiveDateTime> {
+    let attempts = attempts.iter().filter(|attempt| attempt.expires_at > *now);
+
+    let mut total = 0;
+    let mut last_failed_at = None;
+    let mut backoff_until = None;
+    for attempt in attempts {
+        total += attempt.failed_attempts;
+        last_failed_at = last_failed_at.max(Some(attempt.last_failed_at));
+        if attempt.ip_address == ip_address {
+            backoff_until = Some(
+                attempt.last_failed_at
+                    + Duration::seconds(backoff_secs(config, attempt.failed_attempts)),
+            );
+        }
+    }
+
+    let lockout_until = last_failed_at
+        .filter(|_| total >= config.lockout_attempts)
+        .map(|last_failed_at| last_failed_at + Duration::seconds(config.lockout_secs));
+
+    backoff_until
+        .max(lockout_until)
+        .filter(|locked_until| locked_until > now)
+}
@@ -1,3 +1,10 @@
 pub mod aes;
+pub mod challenge;
+pub mod codes;
 pub mod crypto;
+pub mod lockout;
 pub mod password;
+pub mod password_policy;
+pub mod pusher_auth;
+pub mod ticket_qr;
+pub mod totp;
@@ -0,0 +1,194 @@
+//! The password policy.
+//!
+//! The passwords users choose, when signing up and when changing them, must be long enough,
+//! contain the configured character classes and be neither a common password nor, with the
+//! breach check on, one of the passwords leaked in known data breaches.
+//!
+//! The breach check uses the k-anonymity range api of Have I Been Pwned: only the first 5 hex
+//! characters of the password's SHA-1 leave the server, the leaked hashes of that range are
+//! compared here. The api being unavailable does not keep users from signing up, the password is
+//! then accepted.
+use crate::{
+    config::{BreachCheckConfig, PasswordPolicyConfig},
+    error::PasswordError,
+};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use std::{fmt, time::Duration};
+
+/// The length of the SHA-1 prefix sent to the range api
+const RANGE_PREFIX_LEN: usize = 5;
+
+/// The most common passwords, nobody may choose them whatever the configuration
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "123456789",
+    "12345678",
+    "1234567890",
+    "1234567",
+    "12345",
+    "111111",
+    "000000",
+    "123123",
+    "654321",
+    "666666",
+    "888888",
+    "987654321",
+    "password",
+    "password1",
+    "password123",
+    "passw0rd",
+    "p@ssw0rd",
+    "qwerty",
+    "qwerty123",
+    "qwertyuiop",
+    "1q2w3e4r",
+    "1q2w3e4r5t",
+    "asdfghjkl",
+    "zxcvbnm",
+    "abc123",
+    "abcd1234",
+    "iloveyou",
+    "admin",
+    "admin123",
+    "welcome",
+    "welcome1",
+    "letmein",
+    "monkey",
+    "dragon",
+    "football",
+    "baseball",
+    "sunshine",
+    "princess",
+    "starwars",
+    "whatever",
+    "trustno1",
+    "superman",
+    "michael",
+    "shadow",
+    "master",
+    "secret",
+    "changeme",
+    "tickets",
+    "near",
+];
+
+/// A class of characters a password may be required to contain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CharacterClass {
+    Lowercase,
+    Uppercase,
+    Digit,
+    Symbol,
+}
+
+impl CharacterClass {
+    fn contains(self, c: char) -> bool {
+        match self {
+            CharacterClass::Lowercase => c.is_lowercase(),
+            CharacterClass::Uppercase => c.is_uppercase(),
+            CharacterClass::Digit => c.is_numeric(),
+            CharacterClass::Symbol => !c.is_alphanumeric() && !c.is_whitespace(),
+        }
+    }
+}
+
+impl fmt::Display for CharacterClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CharacterClass::Lowercase => write!(f, "a lowercase letter"),
+            CharacterClass::Uppercase => write!(f, "an uppercase letter"),
+            CharacterClass::Digit => write!(f, "a digit"),
+            CharacterClass::Symbol => write!(f, "a symbol"),
+        }
+    }
+}
+
+/// Checks the password's length, character classes and that it is not a common one
+pub fn check_password_strength(
+    config: &PasswordPolicyConfig,
+    password: &str,
+) -> Result<(), PasswordError> {
+    let len = password.chars().count();
+    if len < config.min_length {
+        return Err(PasswordError::TooShort(config.min_length));
+    }
+    if len > config.max_length {
+        return Err(PasswordError::TooLong(config.max_length));
+    }
+
+    if let Some(class) = config
+        .required_classes
+        .iter()
+        .find(|class| !password.chars().any(|c| class.contains(c)))
+    {
+        return Err(PasswordError::MissingClass(*class));
+    }
+
+    let lowercase = password.to_lowercase();
+    let common = COMMON_PASSWORDS.iter().any(|common| lowercase == *common)
+        || config
+            .denied
+            .iter()
+            .any(|denied| lowercase == denied.to_lowercase());
+    if common {
+        return Err(PasswordError::Common);
+    }
+
+    Ok(())
+}
+
+/// How often the password appears in the known data breaches
+pub async fn breach_count(
+    config: &BreachCheckConfig,
+    password: &str,
+) -> Result<u64, reqwest::Error> {
+    let hash = hex::encode_upper(Sha1::digest(password.as_bytes()));
+    let (prefix, suffix) = hash.split_at(RANGE_PREFIX_LEN);
+
+    let range = reqwest::Client::builder()
+        .timeout(Duration::from_millis(config.timeout_ms))
+        .build()?
+        .get(format!(
+            "{}/{}",
+            config.api_url.trim_end_matches('/'),
+            prefix
+        ))
+        // padded answers do not tell the size of the range
+        .header("Add-Padding", "true")
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    // a line per leaked hash of the range, `{suffix}:{count}`, the padding counted 0
+    let count = range
+        .lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(leaked, _)| leaked.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, count)| count.trim().parse().ok())
+        .unwrap_or(0);
+    Ok(count)
+}
+
+/// Checks the password against the policy, and against the known data breaches if configured
+pub async fn check_password(
+    config: &PasswordPolicyConfig,
+    password: &str,
+) -> Result<(), PasswordError> {
+    check_password_strength(config, password)?;
+
+    if let Some(breach_check) = &config.breach_check {
+        match breach_count(breach_check, password).await {
+            Ok(0) => {}
+            Ok(_) => return Err(PasswordError::Breached),
+            Err(e) => log::warn!(
+                "Password breach check failed, the password is accepted: {}",
+                e
+            ),
+        }
+    }
+    Ok(())
+}
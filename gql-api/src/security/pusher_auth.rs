@@ -0,0 +1,46 @@
+//! Signatures of the private and presence channel subscriptions of pusher clients.
+//!
+//! A client subscribing to a `private-` or `presence-` channel asks the api to sign its socket
+//! and the channel, pusher checks the signature with the app secret: the HMAC-SHA256 of
+//! `{socket_id}:{channel_name}`, followed by `:{channel_data}` on presence channels, hex encoded
+//! and prefixed with the app key.
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// The app credentials signing the subscriptions
+#[derive(Clone)]
+pub struct PusherAuth {
+    key: String,
+    secret: String,
+}
+
+impl PusherAuth {
+    pub fn new(key: &str, secret: &str) -> Self {
+        PusherAuth {
+            key: key.to_string(),
+            secret: secret.to_string(),
+        }
+    }
+
+    /// The `auth` of a subscription, `{key}:{signature}`
+    pub fn sign(&self, socket_id: &str, channel_name: &str, channel_data: Option<&str>) -> String {
+        let message = match channel_data {
+            Some(channel_data) => format!("{}:{}:{}", socket_id, channel_name, channel_data),
+            None => format!("{}:{}", socket_id, channel_name),
+        };
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())
+            .expect("hmac takes keys of any size");
+        mac.update(message.as_bytes());
+        format!("{}:{}", self.key, hex::encode(mac.finalize().into_bytes()))
+    }
+}
+
+/// Whether the socket id is one pusher hands out, two numbers joined by a dot
+pub fn valid_socket_id(socket_id: &str) -> bool {
+    match socket_id.split_once('.') {
+        Some((left, right)) => [left, right]
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit())),
+        None => false,
+    }
+}
@@ -0,0 +1,89 @@
+//! Time-based one-time passwords (RFC 6238), the second factor of password sign-ins. A code is
+//! 6 digits of an HMAC-SHA1 of the current 30 seconds step, as authenticator apps expect.
+use super::aes::BincodeAesUtils;
+use base32::Alphabet;
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, Rng};
+use reqwest::Url;
+use sha1::Sha1;
+
+/// Digits of a code
+pub const DIGITS: u32 = 6;
+/// Seconds a code is valid for
+pub const STEP_SECS: i64 = 30;
+/// Steps before and after the current one whose codes are accepted, for clock drift
+pub const SKEW_STEPS: i64 = 1;
+
+const SECRET_ALPHABET: Alphabet = Alphabet::RFC4648 { padding: false };
+
+/// A random 160 bits secret, base32 encoded
+pub fn gen_secret() -> String {
+    base32::encode(SECRET_ALPHABET, &OsRng.gen::<[u8; 20]>())
+}
+
+/// The `otpauth://` uri authenticator apps enroll the secret with, usually shown as a qr code
+pub fn provisioning_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let mut uri = Url::parse("otpauth://totp").expect("must be a valid uri");
+    uri.set_path(&format!("{}:{}", issuer, account));
+    uri.query_pairs_mut()
+        .append_pair("secret", secret)
+        .append_pair("issuer", issuer)
+        .append_pair("algorithm", "SHA1")
+        .append_pair("digits", &DIGITS.to_string())
+        .append_pair("period", &STEP_SECS.to_string());
+    uri.to_string()
+}
+
+/// The step a unix timestamp falls in
+pub fn step(timestamp: i64) -> i64 {
+    timestamp.div_euclid(STEP_SECS)
+}
+
+/// The code of a step, `None` if the secret is not valid base32
+pub fn code(secret: &str, step: i64) -> Option<String> {
+    let key = base32::decode(SECRET_ALPHABET, secret)?;
+    let mut mac = Hmac::<Sha1>::new_from_slice(&key).ok()?;
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // dynamic truncation
+    let offset = usize::from(hash[hash.len() - 1] & 0x0f);
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    Some(format!(
+        "{:0width$}",
+        binary % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    ))
+}
+
+/// The step of the code within `SKEW_STEPS` of the timestamp's step and after `last_step`, so a
+/// code is accepted once only. `None` if the code does not match
+pub fn verify(secret: &str, code_to_check: &str, timestamp: i64, last_step: i64) -> Option<i64> {
+    let current = step(timestamp);
+    (current - SKEW_STEPS..=current + SKEW_STEPS)
+        .filter(|step| *step > last_step)
+        .find(|step| {
+            code(secret, *step)
+                .map(|code| constant_time_eq(code.as_bytes(), code_to_check.as_bytes()))
+                .unwrap_or_default()
+        })
+}
+
+/// Encrypts the secret to store it, with the configured encryption secret
+pub fn encrypt_secret(encryption_secret: &str, secret: &str) -> String {
+    BincodeAesUtils::new_from_secret(encryption_secret).encrypt_data(Some(secret.to_string()))
+}
+
+/// Decrypts a stored secret
+pub fn decrypt_secret(encryption_secret: &str, encrypted_secret: &str) -> Option<String> {
+    BincodeAesUtils::new_from_secret(encryption_secret).decrypt_data(encrypted_secret)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
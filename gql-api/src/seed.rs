@@ -0,0 +1,305 @@
+//! The `seed` subcommand, filling a dev database with fake sellers, buyers, events, tickets and
+//! reservations.
+//!
+//! The data is generated from a seed: the same seed and counts generate the same ids, names and
+//! quantities, and dates relative to the time of seeding. Seeding twice with the same seed is
+//! refused, a database is seeded again with another seed.
+use crate::{
+    auth::{Role, SellerStatus, UserStatus},
+    config::NearNetwork,
+    db::{
+        models::{DbEvent, DbTicket, DbTicketReservation, DbUser},
+        sql::{
+            db_get_users_by_username, db_insert_event, db_insert_ticket,
+            db_insert_ticket_reservation, db_insert_user,
+        },
+    },
+    error::SeedError,
+    gql::models::{EventStatus, NewTicket},
+    near::NearAmount,
+    security::password::hash_password,
+};
+use chrono::{Duration, NaiveDateTime};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::fmt;
+use tokio_postgres::Client;
+use uuid::Uuid;
+
+/// The venues the fake events take place at
+const VENUES: [(&str, &str); 4] = [
+    ("Arena Sofia", "Sofia, Bulgaria"),
+    ("NDK Hall 1", "Sofia, Bulgaria"),
+    ("Ancient Theatre", "Plovdiv, Bulgaria"),
+    ("Summer Theatre", "Varna, Bulgaria"),
+];
+
+/// The words the names of the fake events are made of
+const EVENT_WORDS: [&str; 8] = [
+    "Jazz", "Rock", "Summer", "Night", "Festival", "Live", "Comedy", "Classics",
+];
+
+/// The names of the tickets of a fake event, from the cheapest
+const TICKET_NAMES: [&str; 3] = ["Standard", "Premium", "VIP"];
+
+/// How much of what to generate
+#[derive(Clone, Debug)]
+pub struct SeedConfig {
+    pub seed: u64,
+    pub sellers: usize,
+    pub buyers: usize,
+    pub events_per_seller: usize,
+    pub tickets_per_event: usize,
+    pub reservations_per_buyer: usize,
+    /// the password of every generated user
+    pub password: String,
+}
+
+/// The generated rows, in the order they are inserted
+#[derive(Debug)]
+pub struct SeedData {
+    pub users: Vec<DbUser>,
+    pub events: Vec<DbEvent>,
+    pub tickets: Vec<DbTicket>,
+    pub reservations: Vec<DbTicketReservation>,
+}
+
+/// How many rows were inserted
+#[derive(Debug, Default)]
+pub struct SeedReport {
+    pub sellers: usize,
+    pub buyers: usize,
+    pub events: usize,
+    pub tickets: usize,
+    pub reservations: usize,
+}
+
+impl fmt::Display for SeedReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "seeded {} sellers, {} buyers, {} events, {} tickets and {} reservations",
+            self.sellers, self.buyers, self.events, self.tickets, self.reservations
+        )
+    }
+}
+
+/// The username of the n-th generated user of a role, 1-based
+pub fn seed_username(seed: u64, role: Role, n: usize) -> String {
+    format!("seed{}_{}{}", seed, role, n)
+}
+
+/// A v4 uuid drawn from the generator
+fn gen_uuid(rng: &mut StdRng) -> Uuid {
+    uuid::Builder::from_bytes(rng.gen())
+        .set_variant(uuid::Variant::RFC4122)
+        .set_version(uuid::Version::Random)
+        .build()
+}
+
+fn gen_user(
+    rng: &mut StdRng,
+    config: &SeedConfig,
+    network: NearNetwork,
+    role: Role,
+    n: usize,
+    password_hash: &str,
+) -> DbUser {
+    let username = seed_username(config.seed, role, n);
+    let db_user = DbUser::new(
+        gen_uuid(rng),
+        None,
+        username.clone(),
+        None,
+        Some(format!("{}@example.com", username)),
+        Some(password_hash.to_string()),
+        None,
+        role,
+        network.account_id(&username),
+        NearAmount::from_whole_near(rng.gen_range(1, 100)).to_string(),
+        UserStatus::PhoneVerified,
+    );
+    match role {
+        // the events of approved sellers are listed
+        Role::Seller => DbUser {
+            seller_slug: Some(username.replace('_', "-")),
+            seller_status: Some(SellerStatus::Approved),
+            ..db_user
+        },
+        _ => db_user,
+    }
+}
+
+fn gen_event(rng: &mut StdRng, seller: &DbUser, n: usize, now: NaiveDateTime) -> DbEvent {
+    let words = EVENT_WORDS
+        .choose_multiple(rng, 2)
+        .copied()
+        .collect::<Vec<_>>();
+    let event_name = format!("{} {} {}", words.join(" "), seller.username, n);
+    let (venue_name, venue_location) = VENUES.choose(rng).copied().expect("venues");
+    let start_date = now + Duration::days(rng.gen_range(1, 90)) + Duration::hours(19);
+    DbEvent {
+        id: gen_uuid(rng),
+        created_at: now,
+        start_date: Some(start_date),
+        end_date: Some(start_date + Duration::hours(3)),
+        entry_time: Some(start_date - Duration::hours(1)),
+        description: Some(format!("{}, seeded for development", event_name)),
+        is_virtual: Some(false),
+        is_featured: Some(rng.gen_bool(0.2)),
+        venue_name: Some(venue_name.to_string()),
+        venue_location: Some(venue_location.to_string()),
+        event_status: EventStatus::Final,
+        ..DbEvent::new(&event_name, seller.id)
+    }
+}
+
+fn gen_ticket(rng: &mut StdRng, db_event: &DbEvent, n: usize, now: NaiveDateTime) -> DbTicket {
+    let ticket_name = TICKET_NAMES
+        .get(n)
+        .map_or_else(|| format!("Tier {}", n + 1), |name| (*name).to_string());
+    let price = NearAmount::from_whole_near((n as u128 + 1) * rng.gen_range(1, 10));
+    DbTicket {
+        id: gen_uuid(rng),
+        created_at: now,
+        ..DbTicket::new(
+            NewTicket {
+                ticket_name,
+                description: None,
+                price: Some(price),
+                max_release_price: None,
+                quantity_available: Some(rng.gen_range(50, 500)),
+                min_purchase_quantity: Some(1),
+                max_purchase_quantity: Some(10),
+                allow_transfers: Some(true),
+                currency: None,
+                event_id: db_event.id,
+            },
+            db_event,
+        )
+    }
+}
+
+/// Generates the rows of the seed, the dates relative to `now`
+pub fn seed_data(
+    config: &SeedConfig,
+    network: NearNetwork,
+    now: NaiveDateTime,
+    password_hash: &str,
+) -> SeedData {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+
+    let sellers = (1..=config.sellers)
+        .map(|n| gen_user(&mut rng, config, network, Role::Seller, n, password_hash))
+        .collect::<Vec<_>>();
+    let buyers = (1..=config.buyers)
+        .map(|n| gen_user(&mut rng, config, network, Role::Buyer, n, password_hash))
+        .collect::<Vec<_>>();
+
+    let mut events = vec![];
+    for seller in &sellers {
+        for n in 1..=config.events_per_seller {
+            events.push(gen_event(&mut rng, seller, n, now));
+        }
+    }
+
+    let mut tickets = vec![];
+    for db_event in &events {
+        for n in 0..config.tickets_per_event {
+            tickets.push(gen_ticket(&mut rng, db_event, n, now));
+        }
+    }
+
+    // the reserved quantities are counted on the tickets before they are inserted
+    let mut reservations = vec![];
+    if !tickets.is_empty() {
+        for buyer in &buyers {
+            for _ in 0..config.reservations_per_buyer {
+                let index = rng.gen_range(0, tickets.len());
+                let db_ticket = &mut tickets[index];
+                let quantity = rng.gen_range(1, 4);
+                if db_ticket.quantity_remaining().unwrap_or(quantity) < quantity {
+                    continue;
+                }
+                db_ticket.quantity_reserved += quantity;
+
+                let verification_code = (0..6)
+                    .map(|_| rng.gen_range(0, 10).to_string())
+                    .collect::<String>();
+                let created_at = now - Duration::minutes(rng.gen_range(1, 60 * 24 * 30));
+                reservations.push(DbTicketReservation::new(
+                    gen_uuid(&mut rng),
+                    created_at,
+                    &verification_code,
+                    db_ticket.event_id,
+                    db_ticket.id,
+                    buyer.id,
+                    quantity,
+                ));
+            }
+        }
+    }
+
+    SeedData {
+        users: sellers.into_iter().chain(buyers).collect(),
+        events,
+        tickets,
+        reservations,
+    }
+}
+
+/// Inserts the rows of the seed, refused if the seed was inserted already
+pub async fn seed(
+    db_client: &Client,
+    config: &SeedConfig,
+    network: NearNetwork,
+    now: NaiveDateTime,
+) -> Result<SeedReport, SeedError> {
+    // the users of a seed are named after it, they exist if it was inserted
+    let first_user = if config.sellers > 0 {
+        seed_username(config.seed, Role::Seller, 1)
+    } else {
+        seed_username(config.seed, Role::Buyer, 1)
+    };
+    if !db_get_users_by_username(db_client, &first_user)
+        .await
+        .map_err(SeedError::Postgres)?
+        .is_empty()
+    {
+        return Err(SeedError::AlreadySeeded(config.seed));
+    }
+
+    // hashed once, argon2 is slow on purpose
+    let password_hash = hash_password(config.password.as_bytes()).map_err(SeedError::Hash)?;
+    let data = seed_data(config, network, now, &password_hash);
+
+    let mut report = SeedReport::default();
+    for db_user in &data.users {
+        db_insert_user(db_client, db_user)
+            .await
+            .map_err(SeedError::Postgres)?;
+        match db_user.user_type {
+            Role::Seller => report.sellers += 1,
+            _ => report.buyers += 1,
+        }
+    }
+    for db_event in &data.events {
+        db_insert_event(db_client, db_event)
+            .await
+            .map_err(SeedError::Postgres)?;
+        report.events += 1;
+    }
+    for db_ticket in &data.tickets {
+        db_insert_ticket(db_client, db_ticket)
+            .await
+            .map_err(SeedError::Postgres)?;
+        report.tickets += 1;
+    }
+    for db_reservation in &data.reservations {
+        db_insert_ticket_reservation(db_client, db_reservation)
+            .await
+            .map_err(SeedError::Postgres)?;
+        report.reservations += 1;
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,68 @@
+//! Graceful shutdown: once the stop signal is received the server stops accepting connections,
+//! in-flight requests and background workers get a deadline to finish, then the clients are
+//! closed by dropping the resources holding them.
+use futures::future::join_all;
+use std::time::Duration;
+use tokio::{sync::broadcast, task::JoinHandle};
+
+/// Waits for the server task to stop. Once the stop signal is received its in-flight requests
+/// have `timeout` to finish, after which the task is aborted. Returns whether it stopped in time.
+pub async fn drain_server(
+    mut server: JoinHandle<()>,
+    mut stop_rx: broadcast::Receiver<()>,
+    timeout: Duration,
+) -> bool {
+    tokio::select! {
+        res = &mut server => {
+            if let Err(e) = res {
+                log::error!("Server task error: {}", e);
+            }
+            return true;
+        }
+        _ = stop_rx.recv() => {}
+    }
+
+    log::info!("Draining in-flight requests for up to {:?}...", timeout);
+    match tokio::time::timeout(timeout, &mut server).await {
+        Ok(Ok(())) => {
+            log::info!("In-flight requests drained");
+            true
+        }
+        Ok(Err(e)) => {
+            log::error!("Server task error: {}", e);
+            true
+        }
+        Err(_) => {
+            // e.g. open subscriptions, they do not end on their own
+            log::warn!("In-flight requests did not finish in time, aborting them");
+            server.abort();
+            false
+        }
+    }
+}
+
+/// Waits up to `timeout` for the background workers to finish their current work, aborting the
+/// ones still running. Returns whether all of them stopped in time.
+pub async fn join_workers(mut workers: Vec<JoinHandle<()>>, timeout: Duration) -> bool {
+    log::info!(
+        "Waiting up to {:?} for {} background workers...",
+        timeout,
+        workers.len()
+    );
+    match tokio::time::timeout(timeout, join_all(workers.iter_mut())).await {
+        Ok(results) => {
+            for e in results.into_iter().filter_map(Result::err) {
+                log::error!("Background worker error: {}", e);
+            }
+            log::info!("Background workers stopped");
+            true
+        }
+        Err(_) => {
+            log::warn!("Background workers did not stop in time, aborting them");
+            for worker in workers.iter() {
+                worker.abort();
+            }
+            false
+        }
+    }
+}
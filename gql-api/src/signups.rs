@@ -0,0 +1,271 @@
+//! Buyer signups, carried through even when they fail halfway.
+//!
+//! A signup creates the buyer's wallet on the chain, then the user. Before the wallet is created,
+//! what the user is created from is recorded as a [`DbSignupAttempt`], along with the wallet's
+//! creation once it succeeds, so that a signup failing in between is not lost: the signups worker
+//! retries it with a growing delay, until it completes or failed too many times. An admin then
+//! resumes it or rolls it back. A rolled back signup gives its buyer's verified phone back for
+//! another signup, a wallet created meanwhile stays on the chain.
+//!
+//! A signup is only ever carried on by whoever leased it, the request that started it or a retry.
+use crate::{
+    audit::{self, AuditEntity},
+    config::SignupsConfig,
+    db::{
+        models::{DbSignupAttempt, DbUser},
+        sql::{
+            db_claim_signup_attempt, db_fail_signup_attempt, db_get_signup_attempt_by_id,
+            db_get_users_by_ids, db_insert_user, db_release_buyer_signup_session,
+            db_set_signup_attempt_wallet_created, db_settle_signup_attempt, sql_timestamp,
+        },
+    },
+    error::{Error, UserError},
+    gql::{
+        models::{NotificationKind, SignupStatus},
+        schema::Context as ResourcesContext,
+    },
+    inbox,
+    jobs::{
+        models::{JobPayload, PusherChannel, PusherEvent},
+        queue::enqueue,
+    },
+    near::NearAmount,
+};
+use uuid::Uuid;
+
+/// Carries a leased signup through its remaining steps. Returns the created user
+pub async fn complete(
+    ctx: &ResourcesContext,
+    db_signup_attempt: &DbSignupAttempt,
+) -> Result<DbUser, Error> {
+    let mut db_signup_attempt = db_signup_attempt.clone();
+    if db_signup_attempt.wallet_created_at.is_none() {
+        let tx_hash = create_wallet(ctx, &db_signup_attempt).await?;
+        db_signup_attempt = db_set_signup_attempt_wallet_created(
+            &ctx.db_client,
+            &db_signup_attempt.id,
+            tx_hash.as_deref(),
+        )
+        .await
+        .map_err(Error::Postgres)?;
+    }
+
+    // the user may have been created by an earlier try that failed to settle
+    let existing = db_get_users_by_ids(&ctx.db_client, &[db_signup_attempt.user_id])
+        .await
+        .map_err(Error::Postgres)?;
+    let db_user = match existing.into_iter().next() {
+        Some(db_user) => db_user,
+        None => create_user(ctx, &db_signup_attempt).await?,
+    };
+
+    db_settle_signup_attempt(
+        &ctx.db_client,
+        &db_signup_attempt.id,
+        SignupStatus::Completed,
+    )
+    .await
+    .map_err(Error::Postgres)?;
+    Ok(db_user)
+}
+
+/// Creates the signup's wallet unless an earlier try did. Returns the creation transaction hash,
+/// `None` when the wallet was found created
+async fn create_wallet(
+    ctx: &ResourcesContext,
+    db_signup_attempt: &DbSignupAttempt,
+) -> Result<Option<String>, Error> {
+    // a creation that failed to answer may still have gone through
+    let account_keys = ctx
+        .chain
+        .account_keys(&db_signup_attempt.wallet_id)
+        .await
+        .map_err(Error::Chain)?;
+    if account_keys.contains(&db_signup_attempt.public_key) {
+        return Ok(None);
+    }
+    if !account_keys.is_empty() {
+        return Err(Error::User(UserError::WalletCreationFailed));
+    }
+
+    // create account and also send some funds to it (atomically)
+    let create_account_tx = ctx
+        .chain
+        .create_account(
+            &db_signup_attempt.wallet_id,
+            &db_signup_attempt.public_key,
+            &ctx.near.wallet_deposit,
+        )
+        .await
+        .map_err(Error::Chain)?;
+    if create_account_tx.failed {
+        return Err(Error::User(UserError::WalletCreationFailed));
+    }
+    log::info!(
+        "Created wallet with account_id {}. Tx hash: {}",
+        db_signup_attempt.wallet_id,
+        create_account_tx.tx_hash
+    );
+
+    // send account created and funded events over pusher
+    for event in [PusherEvent::AccountCreated, PusherEvent::AccountFunded] {
+        enqueue(
+            &ctx.db_client,
+            JobPayload::PusherEvent {
+                channel: PusherChannel::Account,
+                event,
+                data: db_signup_attempt.wallet_id.clone(),
+            },
+            ctx.jobs.max_attempts,
+        )
+        .await
+        .map_err(Error::Postgres)?;
+    }
+    Ok(Some(create_account_tx.tx_hash))
+}
+
+async fn create_user(
+    ctx: &ResourcesContext,
+    db_signup_attempt: &DbSignupAttempt,
+) -> Result<DbUser, Error> {
+    // the new wallet holds the creation deposit until the balance sync picks it up
+    let wallet_balance =
+        NearAmount::from_near(&ctx.near.wallet_deposit).map_err(Error::NearAmount)?;
+    let new_db_user = db_signup_attempt.user(wallet_balance.to_string());
+
+    db_insert_user(&ctx.db_client, &new_db_user)
+        .await
+        .map_err(Error::Postgres)?;
+    audit::record(
+        &ctx.db_client,
+        Some(new_db_user.id),
+        "signup",
+        AuditEntity::User(new_db_user.id),
+        Some(serde_json::json!({
+            "username": new_db_user.username,
+            "userType": new_db_user.user_type.to_string(),
+        })),
+    )
+    .await;
+    inbox::notify(
+        &ctx.db_client,
+        new_db_user.id,
+        NotificationKind::WalletCreated,
+        format!("Your wallet {} was created", new_db_user.wallet_id),
+        Some(serde_json::json!({ "walletId": new_db_user.wallet_id })),
+    )
+    .await;
+    Ok(new_db_user)
+}
+
+/// Records how a leased signup failed. A wallet creation the chain refused is rolled back right
+/// away, as nothing was created, other failures are retried later
+pub async fn fail(ctx: &ResourcesContext, db_signup_attempt: &DbSignupAttempt, error: &Error) {
+    let recorded = match error {
+        Error::User(UserError::WalletCreationFailed) => roll_back(ctx, db_signup_attempt).await,
+        _ => {
+            let retry_at = sql_timestamp(Some(retry_backoff_secs(
+                &ctx.signups,
+                db_signup_attempt.attempts + 1,
+            )));
+            db_fail_signup_attempt(
+                &ctx.db_client,
+                &db_signup_attempt.id,
+                &error.to_string(),
+                &retry_at,
+                ctx.signups.max_attempts,
+            )
+            .await
+            .map_err(Error::Postgres)
+        }
+    };
+    match recorded {
+        Ok(Some(db_signup_attempt)) => log::warn!(
+            "Signup {} of {} failed, now {}: {}",
+            db_signup_attempt.id,
+            db_signup_attempt.username,
+            db_signup_attempt.signup_status,
+            error
+        ),
+        Ok(None) => {}
+        Err(e) => log::error!(
+            "Failed to record the failure of signup {}: {}",
+            db_signup_attempt.id,
+            e
+        ),
+    }
+}
+
+/// Rolls back a leased signup, giving its session back to the buyer. Returns `None` when the
+/// signup is not pending anymore
+pub async fn roll_back(
+    ctx: &ResourcesContext,
+    db_signup_attempt: &DbSignupAttempt,
+) -> Result<Option<DbSignupAttempt>, Error> {
+    let db_signup_attempt = match db_settle_signup_attempt(
+        &ctx.db_client,
+        &db_signup_attempt.id,
+        SignupStatus::RolledBack,
+    )
+    .await
+    .map_err(Error::Postgres)?
+    {
+        Some(db_signup_attempt) => db_signup_attempt,
+        None => return Ok(None),
+    };
+    // a purged session is not given back
+    db_release_buyer_signup_session(
+        &ctx.db_client,
+        &db_signup_attempt.session_id,
+        &sql_timestamp(Some(ctx.sessions.code_ttl_secs)),
+    )
+    .await
+    .map_err(Error::Postgres)?;
+    Ok(Some(db_signup_attempt))
+}
+
+/// Retries a failed signup, or a pending one nothing is retrying, right away. Returns the signup
+/// as it is afterwards, `None` when it cannot be resumed
+pub async fn resume(ctx: &ResourcesContext, id: &Uuid) -> Result<Option<DbSignupAttempt>, Error> {
+    let db_signup_attempt = match claim(ctx, id).await? {
+        Some(db_signup_attempt) => db_signup_attempt,
+        None => return Ok(None),
+    };
+    if let Err(e) = complete(ctx, &db_signup_attempt).await {
+        fail(ctx, &db_signup_attempt, &e).await;
+    }
+    db_get_signup_attempt_by_id(&ctx.db_client, id)
+        .await
+        .map_err(Error::Postgres)
+}
+
+/// Rolls back a failed signup, or a pending one nothing is retrying. `None` when it cannot be
+/// rolled back
+pub async fn roll_back_stuck(
+    ctx: &ResourcesContext,
+    id: &Uuid,
+) -> Result<Option<DbSignupAttempt>, Error> {
+    match claim(ctx, id).await? {
+        Some(db_signup_attempt) => roll_back(ctx, &db_signup_attempt).await,
+        None => Ok(None),
+    }
+}
+
+async fn claim(ctx: &ResourcesContext, id: &Uuid) -> Result<Option<DbSignupAttempt>, Error> {
+    db_claim_signup_attempt(
+        &ctx.db_client,
+        id,
+        &sql_timestamp(Some(ctx.signups.lease_secs)),
+    )
+    .await
+    .map_err(Error::Postgres)
+}
+
+/// The delay before retrying a signup that failed `attempts` times
+pub fn retry_backoff_secs(config: &SignupsConfig, attempts: i32) -> i64 {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    config
+        .retry_backoff_secs
+        .saturating_mul(2_i64.pow(exponent))
+        .min(config.max_retry_backoff_secs)
+}
@@ -0,0 +1,145 @@
+//! Low-stock and sell-out alerts of tickets, for their sellers.
+//!
+//! After a reservation takes tickets, [`alert_stock`] checks the tickets left against the
+//! configured thresholds, shares of the ticket's available quantity. The seller is alerted on
+//! their pusher channel and, when they registered one, their webhook. A threshold is recorded
+//! before it is alerted, so that it alerts once per ticket however many reservations reach it.
+//! Tickets of unlimited quantity never run low.
+use crate::{
+    db::{
+        models::{DbEvent, DbStockAlert, DbTicket},
+        sql::{db_get_seller_webhook, db_get_ticket_by_id, db_insert_stock_alert},
+    },
+    gql::schema::Context as ResourcesContext,
+    jobs::{
+        models::{JobPayload, PusherChannel, PusherEvent},
+        queue::enqueue,
+    },
+    realtime::user_channel,
+    webhooks::webhook_body,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// The event name of stock alert webhook deliveries
+pub const STOCK_ALERT_EVENT: &str = "ticket.stock_alert";
+
+/// A ticket reaching a threshold, the data of its pusher event and webhook delivery
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StockAlert {
+    pub event_id: Uuid,
+    pub ticket_id: Uuid,
+    pub ticket_name: String,
+    pub threshold_percent: u8,
+    pub quantity_available: i32,
+    pub quantity_remaining: i32,
+    pub sold_out: bool,
+}
+
+/// The thresholds the tickets left are at or below, the lowest first. A threshold is reached
+/// when the tickets left are at most its share of the available quantity, rounded up
+pub fn reached_thresholds(
+    thresholds_percent: &[u8],
+    quantity_available: i32,
+    quantity_remaining: i32,
+) -> Vec<u8> {
+    let mut reached = thresholds_percent
+        .iter()
+        .copied()
+        .filter(|threshold| *threshold <= 100)
+        .filter(|threshold| {
+            let level = (i64::from(quantity_available) * i64::from(*threshold) + 99) / 100;
+            i64::from(quantity_remaining) <= level
+        })
+        .collect::<Vec<_>>();
+    reached.sort_unstable();
+    reached.dedup();
+    reached
+}
+
+/// Alerts the seller of the thresholds the ticket reached since it was last checked.
+///
+/// Failures are logged and swallowed, an alert must never fail the reservation it is about.
+pub async fn alert_stock(ctx: &ResourcesContext, db_event: &DbEvent, ticket_id: &Uuid) {
+    let result = match db_get_ticket_by_id(&ctx.db_client, ticket_id).await {
+        Ok(db_ticket) => check_stock(ctx, db_event, &db_ticket).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        log::error!("Failed to check the stock of ticket {}: {}", ticket_id, e);
+    }
+}
+
+/// Records the thresholds the ticket reached and alerts the lowest one not recorded before,
+/// the higher ones being implied by it. Returns the alert sent, if any
+pub async fn check_stock(
+    ctx: &ResourcesContext,
+    db_event: &DbEvent,
+    db_ticket: &DbTicket,
+) -> Result<Option<StockAlert>, tokio_postgres::Error> {
+    let (quantity_available, quantity_remaining) =
+        match (db_ticket.quantity_available, db_ticket.quantity_remaining()) {
+            (Some(quantity_available), Some(quantity_remaining)) => {
+                (quantity_available, quantity_remaining)
+            }
+            _ => return Ok(None),
+        };
+
+    let mut alerted = None;
+    for threshold in reached_thresholds(
+        &ctx.stock_alerts.thresholds_percent,
+        quantity_available,
+        quantity_remaining,
+    ) {
+        let db_stock_alert = DbStockAlert::new(db_ticket.id, i16::from(threshold));
+        if db_insert_stock_alert(&ctx.db_client, &db_stock_alert).await? && alerted.is_none() {
+            alerted = Some(threshold);
+        }
+    }
+    let threshold_percent = match alerted {
+        Some(threshold_percent) => threshold_percent,
+        None => return Ok(None),
+    };
+
+    let alert = StockAlert {
+        event_id: db_event.id,
+        ticket_id: db_ticket.id,
+        ticket_name: db_ticket.ticket_name.clone(),
+        threshold_percent,
+        quantity_available,
+        quantity_remaining,
+        sold_out: quantity_remaining == 0,
+    };
+    let data = serde_json::to_value(&alert).expect("a stock alert serializes");
+    let seller_id = db_event.created_by_user;
+
+    enqueue(
+        &ctx.db_client,
+        JobPayload::PusherEvent {
+            channel: PusherChannel::Custom(user_channel(&seller_id)),
+            event: PusherEvent::StockAlert,
+            data: data.to_string(),
+        },
+        ctx.jobs.max_attempts,
+    )
+    .await?;
+    // the webhook is looked up again when delivering, it may be replaced in between
+    if db_get_seller_webhook(&ctx.db_client, &seller_id)
+        .await?
+        .is_some()
+    {
+        enqueue(
+            &ctx.db_client,
+            JobPayload::Webhook {
+                seller_id,
+                event: STOCK_ALERT_EVENT.to_string(),
+                body: webhook_body(STOCK_ALERT_EVENT, &data),
+            },
+            ctx.jobs.max_attempts,
+        )
+        .await?;
+    }
+
+    Ok(Some(alert))
+}
@@ -0,0 +1,232 @@
+//! Object storage of event assets and exports, backed by S3.
+//!
+//! The urls of the assets are stored as their public urls. When the bucket is private, they are
+//! swapped for presigned urls whenever they are served, see [`readable_url`].
+use crate::{
+    config::{AssetAccess, AssetUrlsConfig},
+    error::StorageError,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::Stream;
+use s3_uploader::{s3::S3Client, AwsContext};
+use std::{pin::Pin, time::Duration};
+
+/// The content of an object streamed to the storage
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, StorageError>> + Send + Sync>>;
+
+/// A bucket objects are stored in
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// the name of the bucket
+    fn bucket(&self) -> &str;
+
+    /// the public url of the object under the key
+    fn asset_url(&self, key: String) -> String;
+
+    /// the key of the object at the public url, `None` if the url is not one of the bucket
+    fn asset_key(&self, url: &str) -> Option<String> {
+        url.strip_prefix(&self.asset_url(String::new()))
+            .filter(|key| !key.is_empty())
+            .map(str::to_string)
+    }
+
+    /// stores the content under a generated key, returning the key
+    async fn upload(&self, content: Vec<u8>) -> Result<String, StorageError>;
+
+    /// stores the content under the key
+    async fn put(
+        &self,
+        key: &str,
+        content_type: &str,
+        content: Vec<u8>,
+    ) -> Result<(), StorageError>;
+
+    /// stores the streamed content under the key, its length must be known ahead
+    async fn put_stream(
+        &self,
+        key: &str,
+        content_type: &str,
+        content_length: u64,
+        content: ByteStream,
+    ) -> Result<(), StorageError>;
+
+    /// the content of the object under the key
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError>;
+
+    /// whether an object is stored under the key
+    async fn exists(&self, key: &str) -> Result<bool, StorageError>;
+
+    /// a url a client can upload the object under the key to
+    async fn presigned_put_url(
+        &self,
+        key: &str,
+        content_type: &str,
+        expiry: Duration,
+    ) -> Result<String, StorageError>;
+
+    /// a url a client can download the object under the key from
+    async fn presigned_get_url(&self, key: &str, expiry: Duration) -> Result<String, StorageError>;
+}
+
+/// The url clients read the asset at: its public url as is, or a presigned url valid for the
+/// configured ttl when the bucket is private. Urls outside the bucket, e.g. ipfs ones, are kept
+pub async fn readable_url(
+    storage: &dyn Storage,
+    config: &AssetUrlsConfig,
+    url: String,
+) -> Result<String, StorageError> {
+    match (config.access, storage.asset_key(&url)) {
+        (AssetAccess::Presigned, Some(key)) => {
+            storage
+                .presigned_get_url(&key, Duration::from_secs(config.presigned_ttl_secs))
+                .await
+        }
+        _ => Ok(url),
+    }
+}
+
+/// How long the presigned url of a server side put stays valid
+const PUT_URL_EXPIRY_SECS: u64 = 60;
+
+/// Stores objects in an S3 bucket
+pub struct S3Storage {
+    client: S3Client,
+    context: AwsContext,
+    access: AssetAccess,
+}
+
+impl S3Storage {
+    pub fn new(client: S3Client, context: AwsContext) -> Self {
+        S3Storage {
+            client,
+            context,
+            access: AssetAccess::Public,
+        }
+    }
+
+    /// Reads the objects through presigned urls, for private buckets
+    pub fn with_access(mut self, access: AssetAccess) -> Self {
+        self.access = access;
+        self
+    }
+
+    /// the url the server reads the object under the key at
+    async fn read_url(&self, key: &str) -> Result<String, StorageError> {
+        match self.access {
+            AssetAccess::Public => Ok(self.asset_url(key.to_string())),
+            AssetAccess::Presigned => {
+                self.presigned_get_url(key, Duration::from_secs(PUT_URL_EXPIRY_SECS))
+                    .await
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    fn bucket(&self) -> &str {
+        &self.context.bucket
+    }
+
+    fn asset_url(&self, key: String) -> String {
+        self.context.get_asset_url(key)
+    }
+
+    async fn upload(&self, content: Vec<u8>) -> Result<String, StorageError> {
+        self.client
+            .upload(None, content)
+            .await
+            .map_err(|e| StorageError::Request(e.to_string()))
+    }
+
+    async fn put(
+        &self,
+        key: &str,
+        content_type: &str,
+        content: Vec<u8>,
+    ) -> Result<(), StorageError> {
+        // the object is put through a presigned url, like client uploads are
+        let upload_url = self
+            .presigned_put_url(key, content_type, Duration::from_secs(PUT_URL_EXPIRY_SECS))
+            .await?;
+        reqwest::Client::new()
+            .put(&upload_url)
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(content)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| StorageError::Request(e.to_string()))
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        content_type: &str,
+        content_length: u64,
+        content: ByteStream,
+    ) -> Result<(), StorageError> {
+        let upload_url = self
+            .presigned_put_url(key, content_type, Duration::from_secs(PUT_URL_EXPIRY_SECS))
+            .await?;
+        reqwest::Client::new()
+            .put(&upload_url)
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            // S3 takes no chunked puts, the length is sent ahead of the streamed body
+            .header(reqwest::header::CONTENT_LENGTH, content_length)
+            .body(reqwest::Body::wrap_stream(content))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| StorageError::Request(e.to_string()))
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        reqwest::get(&self.read_url(key).await?)
+            .await
+            .and_then(|res| res.error_for_status())
+            .map_err(|e| StorageError::Request(e.to_string()))?
+            .bytes()
+            .await
+            .map(|content| content.to_vec())
+            .map_err(|e| StorageError::Request(e.to_string()))
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, StorageError> {
+        let client = reqwest::Client::new();
+        let request = match self.access {
+            AssetAccess::Public => client.head(&self.asset_url(key.to_string())),
+            // a presigned url is only valid for the method it was signed for
+            AssetAccess::Presigned => client
+                .get(&self.read_url(key).await?)
+                .header(reqwest::header::RANGE, "bytes=0-0"),
+        };
+        request
+            .send()
+            .await
+            .map(|res| res.status().is_success())
+            .map_err(|e| StorageError::Request(e.to_string()))
+    }
+
+    async fn presigned_put_url(
+        &self,
+        key: &str,
+        content_type: &str,
+        expiry: Duration,
+    ) -> Result<String, StorageError> {
+        self.client
+            .presigned_put_url(key, content_type, expiry)
+            .await
+            .map_err(|e| StorageError::Request(e.to_string()))
+    }
+
+    async fn presigned_get_url(&self, key: &str, expiry: Duration) -> Result<String, StorageError> {
+        self.client
+            .presigned_get_url(key, expiry)
+            .await
+            .map_err(|e| StorageError::Request(e.to_string()))
+    }
+}
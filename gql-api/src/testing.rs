@@ -0,0 +1,84 @@
+//! Route builders for handler level tests, behind the `test-harness` feature.
+//!
+//! The routes are bundled and recovered with [`handle_rejection`] the way the binary serves
+//! them, over a resources context the test builds. Backed by the fakes of [`crate::fakes`],
+//! requests go through the handlers without a NEAR api, twilio or S3.
+use crate::{
+    error::{handle_rejection, Error},
+    gql::schema::Context as ResourcesContext,
+    http::routes::{
+        buyer_create_recovery_code_route, buyer_register_phone_route,
+        buyer_resend_phone_code_route, buyer_signup_route, buyer_verify_phone_route,
+        buyer_verify_recovery_code_route, check_username_route, create_login_code_route,
+        signin_challenge_route, signin_route, signin_with_password_route,
+        signin_with_password_verify_totp_route, verify_login_code_route,
+    },
+};
+use std::{convert::Infallible, sync::Arc};
+use warp::{http::StatusCode, test::RequestBuilder, Filter, Reply};
+
+/// The signin, signup and verification routes of buyers and sellers
+pub fn auth_routes(
+    resources_ctx: Arc<ResourcesContext>,
+) -> impl Filter<Extract = impl Reply + 'static, Error = Infallible> + Clone + 'static {
+    let logger = warp::log("gql_api::testing");
+
+    check_username_route(resources_ctx.clone(), logger)
+        .or(buyer_register_phone_route(resources_ctx.clone(), logger))
+        .or(buyer_verify_phone_route(resources_ctx.clone(), logger))
+        .or(buyer_resend_phone_code_route(resources_ctx.clone(), logger))
+        .or(buyer_signup_route(resources_ctx.clone(), logger))
+        .or(buyer_create_recovery_code_route(
+            resources_ctx.clone(),
+            logger,
+        ))
+        .or(buyer_verify_recovery_code_route(
+            resources_ctx.clone(),
+            logger,
+        ))
+        .or(signin_route(resources_ctx.clone(), logger))
+        .or(signin_challenge_route(resources_ctx.clone(), logger))
+        .or(signin_with_password_route(resources_ctx.clone(), logger))
+        .or(signin_with_password_verify_totp_route(
+            resources_ctx.clone(),
+            logger,
+        ))
+        .or(create_login_code_route(resources_ctx.clone(), logger))
+        .or(verify_login_code_route(resources_ctx, logger))
+        .recover(handle_rejection)
+}
+
+/// A route rejecting every request with the error of `error`, to check how it is answered
+pub fn rejecting_route<F>(
+    error: F,
+) -> impl Filter<Extract = impl Reply + 'static, Error = Infallible> + Clone + 'static
+where
+    F: Fn() -> Error + Clone + Send + Sync + 'static,
+{
+    warp::any()
+        .and_then(move || {
+            let error = error();
+            async move { Err::<StatusCode, _>(warp::reject::custom(error)) }
+        })
+        .recover(handle_rejection)
+}
+
+/// A request with a json body
+pub fn json_request(method: &str, path: &str, body: &serde_json::Value) -> RequestBuilder {
+    warp::test::request()
+        .method(method)
+        .path(path)
+        .header("content-type", "application/json")
+        .json(body)
+}
+
+/// The status and json body of the route's reply, `null` when the body is not json
+pub async fn reply_json<F>(request: RequestBuilder, route: &F) -> (StatusCode, serde_json::Value)
+where
+    F: Filter + 'static,
+    F::Extract: Reply + Send,
+{
+    let res = request.reply(route).await;
+    let json = serde_json::from_slice(res.body()).unwrap_or(serde_json::Value::Null);
+    (res.status(), json)
+}
@@ -0,0 +1,63 @@
+//! Username filtering.
+//!
+//! Usernames become NEAR account ids and are shown publicly, so besides being available they must
+//! not impersonate the platform (`admin`, `near`, ...) nor contain offensive words. Look-alike
+//! spellings (`adm1n`, `n3ar`) are compared by their skeleton, and non-ASCII characters are
+//! refused outright since they are the usual homoglyphs (a cyrillic `а` for a latin `a`).
+use crate::{config::UsernamesConfig, error::UserError};
+
+/// The separators ignored when comparing a username to the reserved words
+const SEPARATORS: [char; 3] = ['-', '_', '.'];
+
+/// The lowercase skeleton of a word, with the digits and symbols commonly standing in for letters
+/// replaced by these letters
+pub fn skeleton(word: &str) -> String {
+    word.chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            '0' => 'o',
+            '1' | 'l' | '!' | '|' => 'i',
+            '3' => 'e',
+            '4' | '@' => 'a',
+            '5' | '$' => 's',
+            '7' => 't',
+            '8' => 'b',
+            '9' => 'g',
+            c => c,
+        })
+        .collect()
+}
+
+/// The skeleton of a word without its separators
+fn bare_skeleton(word: &str) -> String {
+    skeleton(word)
+        .chars()
+        .filter(|c| !SEPARATORS.contains(c))
+        .collect()
+}
+
+/// Checks that the username is neither reserved, offensive nor written with look-alike characters
+pub fn check_username(config: &UsernamesConfig, username: &str) -> Result<(), UserError> {
+    let disallowed = || UserError::DisallowedUsername(username.to_string());
+
+    if !username.is_ascii() {
+        return Err(disallowed());
+    }
+
+    let username_skeleton = bare_skeleton(username);
+    if config
+        .reserved
+        .iter()
+        .any(|reserved| bare_skeleton(reserved) == username_skeleton)
+    {
+        return Err(disallowed());
+    }
+    if config
+        .blocked_words
+        .iter()
+        .any(|word| username_skeleton.contains(&bare_skeleton(word)))
+    {
+        return Err(disallowed());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+//! Webhook deliveries to sellers.
+//!
+//! A seller registers an https endpoint and is given a secret. Every delivery is a json `POST`
+//! of `{"event": .., "data": ..}`, its [`SIGNATURE_HEADER`] holding `sha256=` and the hex
+//! HMAC-SHA256 of the body keyed with the secret, for the seller to check a delivery comes from
+//! us. Deliveries are jobs, retried while the endpoint fails.
+use crate::{config::WebhooksConfig, error::WebhookError};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, Rng};
+use reqwest::Url;
+use sha2::Sha256;
+use std::time::Duration;
+
+/// Header of the signature of a delivery's body
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+/// Header of the name of a delivery's event
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+/// Longest url a seller may register
+pub const MAX_WEBHOOK_URL_LENGTH: usize = 2048;
+
+/// A random 256 bits secret, hex encoded
+pub fn gen_webhook_secret() -> String {
+    hex::encode(OsRng.gen::<[u8; 32]>())
+}
+
+/// The value of the signature header of a body
+pub fn webhook_signature(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac takes keys of any size");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// The json body of a delivery
+pub fn webhook_body(event: &str, data: &serde_json::Value) -> String {
+    serde_json::json!({ "event": event, "data": data }).to_string()
+}
+
+/// Whether sellers may register the url, only https endpoints with a host are delivered to
+pub fn is_valid_webhook_url(url: &str) -> bool {
+    url.len() <= MAX_WEBHOOK_URL_LENGTH
+        && Url::parse(url).map_or(false, |url| url.scheme() == "https" && url.host().is_some())
+}
+
+/// Delivers signed webhooks
+#[async_trait]
+pub trait WebhookSender: Send + Sync {
+    async fn deliver(
+        &self,
+        url: &str,
+        secret: &str,
+        event: &str,
+        body: &str,
+    ) -> Result<(), WebhookError>;
+}
+
+/// Delivers webhooks over https, any status but a success failing the delivery
+pub struct HttpWebhookSender {
+    http_client: reqwest::Client,
+}
+
+impl HttpWebhookSender {
+    pub fn new(config: &WebhooksConfig) -> Result<Self, WebhookError> {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            // a redirect would resend the signed body to another endpoint
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| WebhookError::Request(e.to_string()))?;
+        Ok(HttpWebhookSender { http_client })
+    }
+}
+
+#[async_trait]
+impl WebhookSender for HttpWebhookSender {
+    async fn deliver(
+        &self,
+        url: &str,
+        secret: &str,
+        event: &str,
+        body: &str,
+    ) -> Result<(), WebhookError> {
+        let response = self
+            .http_client
+            .post(url)
+            .header("content-type", "application/json")
+            .header(EVENT_HEADER, event)
+            .header(SIGNATURE_HEADER, webhook_signature(secret, body))
+            .body(body.to_string())
+            .send()
+            .await
+            .map_err(|e| WebhookError::Request(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(WebhookError::Response(response.status().to_string()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use gql_api::auth::Role;
+
+mod common;
+
+#[tokio::test]
+async fn test_delete_account_anonymizes_user() {
+    let cfg = common::setup().await;
+    let seller = cfg.event.created_by_user;
+
+    let db_user = gql_api::db::sql::db_get_user_by_id(&cfg.client, &seller)
+        .await
+        .expect("failed to get user");
+
+    let deleted = gql_api::db::sql::db_anonymize_user(&cfg.client, &db_user)
+        .await
+        .expect("failed to anonymize user")
+        .expect("user should not be deleted yet");
+    assert_eq!(db_user.anonymized_username(), deleted.username);
+    assert!(deleted.name.is_none());
+    assert!(deleted.email.is_none());
+    assert!(deleted.phone_number.is_none());
+    assert!(deleted.password.is_none());
+    assert!(deleted.deleted_at.is_some());
+    assert!(deleted.wallet_flagged);
+    assert_eq!(db_user.wallet_id, deleted.wallet_id);
+
+    // deleting twice is a no-op
+    let deleted_again = gql_api::db::sql::db_anonymize_user(&cfg.client, &db_user)
+        .await
+        .expect("failed to anonymize user");
+    assert!(deleted_again.is_none());
+
+    gql_api::db::sql::db_revoke_user_sessions(&cfg.client, &seller, None)
+        .await
+        .expect("failed to revoke sessions");
+
+    // the deleted seller's events are still exported with the account
+    let events = gql_api::db::sql::db_get_events_by_creator(&cfg.client, &seller)
+        .await
+        .expect("failed to get events by creator");
+    assert_eq!(
+        vec![cfg.event.id],
+        events.iter().map(|e| e.id).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_events_by_creator_is_empty_for_buyers() {
+    let cfg = common::setup().await;
+    let buyer = common::create_user(&cfg.client, Role::Buyer).await;
+
+    let events = gql_api::db::sql::db_get_events_by_creator(&cfg.client, &buyer)
+        .await
+        .expect("failed to get events by creator");
+    assert!(events.is_empty());
+}
@@ -0,0 +1,171 @@
+use chrono::Utc;
+use common::TestContextBuilder;
+use gql_api::{
+    auth::Role,
+    config::AnalyticsConfig,
+    db::{
+        models::{DbTicket, DbTicketReservation},
+        sql::{db_insert_ticket, db_reserve_ticket, db_update_event_status},
+    },
+    gql::{
+        models::{EventStatus, NewTicket},
+        mutations::SellerMutationRoot,
+        quiries::SellerQueryRoot,
+        schema::{Context as ResourcesContext, SellerSchema},
+    },
+    http::handlers::event_view,
+    jobs::analytics::aggregate_event_stats,
+};
+use juniper::EmptySubscription;
+use warp::{http::StatusCode, Reply};
+
+mod common;
+
+/// The analytics of the event, `None` if the query failed
+async fn event_analytics(
+    ctx: &ResourcesContext,
+    event_id: uuid::Uuid,
+    from: &str,
+    to: &str,
+) -> Option<serde_json::Value> {
+    let schema = SellerSchema::new(
+        SellerQueryRoot,
+        SellerMutationRoot,
+        EmptySubscription::new(),
+    );
+    let query = format!(
+        r#"query {{ eventAnalytics(eventId: "{}", from: "{}", to: "{}") {{
+            views reservations purchases conversionRate days {{ day views }}
+        }} }}"#,
+        event_id, from, to
+    );
+    let (value, errors) = juniper::execute(&query, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid query");
+    if !errors.is_empty() {
+        return None;
+    }
+    let value = serde_json::to_value(&value).expect("serializable value");
+    Some(value["eventAnalytics"].clone())
+}
+
+#[tokio::test]
+async fn test_event_view_beacon() {
+    let resources = TestContextBuilder::new().build().await;
+    let event = common::create_event(&resources.ctx.db_client).await;
+
+    // drafts and unknown events are not counted
+    assert!(event_view(event.event_slug.clone(), resources.ctx.clone())
+        .await
+        .is_err());
+    assert!(event_view(common::gen_string(20), resources.ctx.clone())
+        .await
+        .is_err());
+
+    db_update_event_status(
+        &resources.ctx.db_client,
+        &event.id,
+        EventStatus::Draft,
+        EventStatus::Final,
+    )
+    .await
+    .expect("failed to publish event")
+    .expect("event should be published");
+    let reply = event_view(event.event_slug.clone(), resources.ctx.clone())
+        .await
+        .expect("view should be counted");
+    assert_eq!(StatusCode::NO_CONTENT, reply.into_response().status());
+}
+
+#[tokio::test]
+async fn test_event_analytics() {
+    let resources = TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+    let event = common::create_event(db_client).await;
+    let seller_ctx = resources.ctx.for_user(Some(event.created_by_user));
+    let today = Utc::now().naive_utc().date().to_string();
+
+    db_update_event_status(db_client, &event.id, EventStatus::Draft, EventStatus::Final)
+        .await
+        .expect("failed to publish event")
+        .expect("event should be published");
+    for _ in 0..4 {
+        event_view(event.event_slug.clone(), resources.ctx.clone())
+            .await
+            .expect("view should be counted");
+    }
+
+    let buyer_id = common::create_user(db_client, Role::Buyer).await;
+    let db_ticket = DbTicket::new(
+        NewTicket {
+            ticket_name: common::gen_string(10),
+            description: None,
+            price: None,
+            max_release_price: None,
+            quantity_available: None,
+            min_purchase_quantity: None,
+            max_purchase_quantity: None,
+            allow_transfers: None,
+            currency: None,
+            event_id: event.id,
+        },
+        &event,
+    );
+    db_insert_ticket(db_client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+    db_reserve_ticket(
+        db_client,
+        &DbTicketReservation::new(
+            uuid::Uuid::new_v4(),
+            Utc::now().naive_utc(),
+            &common::gen_string(6),
+            event.id,
+            db_ticket.id,
+            buyer_id,
+            1,
+        ),
+    )
+    .await
+    .expect("failed to reserve ticket")
+    .expect("ticket should be reserved");
+
+    aggregate_event_stats(&resources.ctx, &AnalyticsConfig::default())
+        .await
+        .expect("failed to aggregate event stats");
+    let analytics = event_analytics(&seller_ctx, event.id, &today, &today)
+        .await
+        .expect("analytics of the seller's event");
+    assert_eq!(4, analytics["views"]);
+    assert_eq!(1, analytics["reservations"]);
+    // a free ticket is not a purchase
+    assert_eq!(0, analytics["purchases"]);
+    assert_eq!(0.25, analytics["conversionRate"]);
+    assert_eq!(today, analytics["days"][0]["day"]);
+
+    // the views are not counted twice
+    aggregate_event_stats(&resources.ctx, &AnalyticsConfig::default())
+        .await
+        .expect("failed to aggregate event stats");
+    let analytics = event_analytics(&seller_ctx, event.id, &today, &today)
+        .await
+        .expect("analytics of the seller's event");
+    assert_eq!(4, analytics["views"]);
+
+    // only for the event's sellers, and over a valid range
+    let other_seller_id = common::create_user(db_client, Role::Seller).await;
+    let other_seller_ctx = resources.ctx.for_user(Some(other_seller_id));
+    assert!(event_analytics(&other_seller_ctx, event.id, &today, &today)
+        .await
+        .is_none());
+    assert!(
+        event_analytics(&seller_ctx, event.id, "2022-05-02", "2022-05-01")
+            .await
+            .is_none()
+    );
+    assert!(
+        event_analytics(&seller_ctx, event.id, "2020-01-01", "2022-01-01")
+            .await
+            .is_none()
+    );
+}
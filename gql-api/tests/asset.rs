@@ -1,4 +1,4 @@
-use gql_api::db::models::AssetFile;
+use gql_api::{db::models::AssetFile, gql::models::AssetRole, jobs::models::EventAssetKind};
 
 mod common;
 use crate::common::gen_asset_file;
@@ -67,3 +67,83 @@ async fn test_asset_files_update() {
         .expect("unable to get expected file");
     assert_eq!(expected, actual);
 }
+
+#[tokio::test]
+async fn test_asset_files_confirm() {
+    let cfg = common::setup().await;
+
+    let pending = AssetFile::new_pending(
+        uuid::Uuid::new_v4(),
+        "some_bucket",
+        format!("events/{}/{}.png", cfg.event.id, common::gen_string(10)),
+        "image/png",
+        cfg.event.id,
+    );
+
+    gql_api::db::sql::insert_asset_file(&cfg.client, &pending)
+        .await
+        .expect("failed to insert pending file");
+
+    let actual = gql_api::db::sql::db_get_asset_file(&cfg.client, &pending.id)
+        .await
+        .expect("unable to get pending file");
+    assert!(!actual.is_confirmed);
+    assert_eq!(Some("image/png".to_string()), actual.content_type);
+
+    assert_eq!(None, actual.asset_role);
+
+    let confirmed =
+        gql_api::db::sql::db_confirm_asset_file(&cfg.client, &pending.id, AssetRole::Thumbnail)
+            .await
+            .expect("failed to confirm file");
+    assert!(confirmed.is_confirmed);
+    assert_eq!(Some(AssetRole::Thumbnail), confirmed.asset_role);
+}
+
+#[tokio::test]
+async fn test_event_ipfs_url() {
+    let cfg = common::setup().await;
+
+    let asset_url = format!("https://some_bucket/{}.png", common::gen_string(10));
+    let updated = gql_api::db::sql::db_update_event_asset_url(
+        &cfg.client,
+        &cfg.event.id,
+        EventAssetKind::CoverPhoto,
+        &asset_url,
+    )
+    .await
+    .expect("failed to update cover photo url");
+    assert_eq!(None, updated.cover_photo_ipfs_url);
+
+    // a pin for an image that has since been replaced is ignored
+    let stale = gql_api::db::sql::db_update_event_ipfs_url(
+        &cfg.client,
+        &cfg.event.id,
+        EventAssetKind::CoverPhoto,
+        "https://some_bucket/replaced.png",
+        "ipfs://stale",
+    )
+    .await
+    .expect("failed to update ipfs url");
+    assert_eq!(0, stale);
+
+    let pinned = gql_api::db::sql::db_update_event_ipfs_url(
+        &cfg.client,
+        &cfg.event.id,
+        EventAssetKind::CoverPhoto,
+        &asset_url,
+        "ipfs://pinned",
+    )
+    .await
+    .expect("failed to update ipfs url");
+    assert_eq!(1, pinned);
+
+    let actual = gql_api::db::sql::db_get_event_by_id(&cfg.client, &cfg.event.id)
+        .await
+        .expect("unable to get event");
+    assert_eq!(
+        Some("ipfs://pinned".to_string()),
+        actual.cover_photo_ipfs_url
+    );
+    assert_eq!(None, actual.thumbnail_ipfs_url);
+}
@@ -0,0 +1,79 @@
+use gql_api::{
+    config::{AssetAccess, AssetUrlsConfig},
+    db::models::DbEvent,
+    fakes::FakeStorage,
+    gql::models::Event,
+    storage::{readable_url, Storage},
+};
+
+fn presigned() -> AssetUrlsConfig {
+    AssetUrlsConfig {
+        access: AssetAccess::Presigned,
+        presigned_ttl_secs: 600,
+    }
+}
+
+#[tokio::test]
+async fn test_readable_url() {
+    let storage = FakeStorage::default();
+    let cover_url = storage.asset_url("events/cover.png".to_string());
+    assert_eq!(
+        Some("events/cover.png".to_string()),
+        storage.asset_key(&cover_url)
+    );
+    assert_eq!(None, storage.asset_key("https://ipfs.io/ipfs/cid"));
+
+    // public buckets serve the stored urls
+    let url = readable_url(&storage, &AssetUrlsConfig::default(), cover_url.clone())
+        .await
+        .expect("a url");
+    assert_eq!(cover_url, url);
+
+    // private ones presigned urls, only of their own objects
+    let url = readable_url(&storage, &presigned(), cover_url.clone())
+        .await
+        .expect("a url");
+    assert_eq!(
+        storage
+            .presigned_get_url("events/cover.png", std::time::Duration::from_secs(600))
+            .await
+            .expect("a url"),
+        url
+    );
+    let url = readable_url(
+        &storage,
+        &presigned(),
+        "https://ipfs.io/ipfs/cid".to_string(),
+    )
+    .await
+    .expect("a url");
+    assert_eq!("https://ipfs.io/ipfs/cid", url);
+}
+
+#[tokio::test]
+async fn test_event_readable_urls() {
+    let storage = FakeStorage::default();
+    let mut db_event = DbEvent::new("Concert", uuid::Uuid::new_v4());
+    db_event.cover_photo_url = Some(storage.asset_url("cover.png".to_string()));
+    db_event.thumbnail_url = Some(storage.asset_url("thumbnail.png".to_string()));
+
+    let event = Event::new(db_event.clone(), vec![])
+        .with_readable_urls(&storage, &AssetUrlsConfig::default())
+        .await
+        .expect("an event");
+    assert_eq!(db_event.cover_photo_url, event.cover_photo_url);
+
+    let event = Event::new(db_event, vec![])
+        .with_readable_urls(&storage, &presigned())
+        .await
+        .expect("an event");
+    assert!(event
+        .cover_photo_url
+        .expect("a cover photo")
+        .ends_with("cover.png?method=GET&expires=600"));
+    assert!(event
+        .thumbnail_url
+        .expect("a thumbnail")
+        .ends_with("thumbnail.png?method=GET&expires=600"));
+    assert_eq!(None, event.image_variants.og_image_url);
+}
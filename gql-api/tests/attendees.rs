@@ -0,0 +1,178 @@
+use chrono::Utc;
+use gql_api::{
+    auth::Role,
+    db::{
+        models::{DbTicket, DbTicketReservation},
+        sql::{db_check_in_ticket_reservations, db_get_event_reservations},
+    },
+    gql::models::{EventReservation, NewTicket},
+    http::export::{attendees_csv, can_export_attendees},
+    phone::mask_phone_number,
+};
+
+mod common;
+
+#[test]
+fn test_mask_phone_number() {
+    assert_eq!("+*********5678", mask_phone_number("+4915112345678"));
+    assert_eq!("123", mask_phone_number("123"));
+}
+
+#[tokio::test]
+async fn test_event_attendees_export() {
+    let cfg = common::setup().await;
+    let buyer = common::create_user(&cfg.client, Role::Buyer).await;
+
+    assert!(can_export_attendees(
+        &Role::Seller,
+        &cfg.event.created_by_user,
+        &cfg.event,
+        None,
+        None
+    ));
+    assert!(!can_export_attendees(
+        &Role::Seller,
+        &buyer,
+        &cfg.event,
+        None,
+        None
+    ));
+    assert!(can_export_attendees(
+        &Role::Admin,
+        &buyer,
+        &cfg.event,
+        None,
+        None
+    ));
+
+    // no attendees yet, only the header
+    let attendees = gql_api::db::sql::db_get_event_attendees(&cfg.client, &cfg.event.id)
+        .await
+        .expect("failed to get attendees");
+    assert!(attendees.is_empty());
+    let csv = String::from_utf8(attendees_csv(&attendees)).expect("csv is utf-8");
+    assert_eq!(1, csv.lines().count());
+    assert!(csv.starts_with("reservation_id,"));
+
+    let ticket_name = common::gen_string(10);
+    let db_ticket = DbTicket::new(
+        NewTicket {
+            ticket_name: ticket_name.clone(),
+            description: None,
+            price: None,
+            max_release_price: None,
+            quantity_available: None,
+            min_purchase_quantity: None,
+            max_purchase_quantity: None,
+            allow_transfers: None,
+            currency: None,
+            event_id: cfg.event.id,
+        },
+        &cfg.event,
+    );
+    gql_api::db::sql::db_insert_ticket(&cfg.client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+    gql_api::db::sql::db_reserve_ticket(
+        &cfg.client,
+        &DbTicketReservation::new(
+            uuid::Uuid::new_v4(),
+            Utc::now().naive_utc(),
+            &common::gen_string(6),
+            cfg.event.id,
+            db_ticket.id,
+            buyer,
+            2,
+        ),
+    )
+    .await
+    .expect("failed to reserve ticket")
+    .expect("ticket should be reserved");
+
+    let attendees = gql_api::db::sql::db_get_event_attendees(&cfg.client, &cfg.event.id)
+        .await
+        .expect("failed to get attendees");
+    assert_eq!(1, attendees.len());
+    assert_eq!(ticket_name, attendees[0].ticket_name);
+    assert_eq!(2, attendees[0].quantity);
+    assert!(attendees[0].checked_in_at.is_none());
+
+    let csv = String::from_utf8(attendees_csv(&attendees)).expect("csv is utf-8");
+    let row = csv.lines().nth(1).expect("an attendee row");
+    assert!(row.contains(&ticket_name));
+    assert!(row.ends_with(",2,false,"));
+}
+
+#[tokio::test]
+async fn test_event_reservations() {
+    let cfg = common::setup().await;
+    let buyer = common::create_user(&cfg.client, Role::Buyer).await;
+
+    let db_ticket = DbTicket::new(
+        NewTicket {
+            ticket_name: common::gen_string(10),
+            description: None,
+            price: None,
+            max_release_price: None,
+            quantity_available: None,
+            min_purchase_quantity: None,
+            max_purchase_quantity: None,
+            allow_transfers: None,
+            currency: None,
+            event_id: cfg.event.id,
+        },
+        &cfg.event,
+    );
+    gql_api::db::sql::db_insert_ticket(&cfg.client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+    let mut verification_codes = vec![];
+    for _ in 0..3 {
+        let verification_code = common::gen_string(6);
+        gql_api::db::sql::db_reserve_ticket(
+            &cfg.client,
+            &DbTicketReservation::new(
+                uuid::Uuid::new_v4(),
+                Utc::now().naive_utc(),
+                &verification_code,
+                cfg.event.id,
+                db_ticket.id,
+                buyer,
+                1,
+            ),
+        )
+        .await
+        .expect("failed to reserve ticket")
+        .expect("ticket should be reserved");
+        verification_codes.push(verification_code);
+    }
+    db_check_in_ticket_reservations(&cfg.client, &cfg.event.id, &verification_codes[0])
+        .await
+        .expect("failed to check in");
+
+    let all = db_get_event_reservations(&cfg.client, &cfg.event.id, None, 10, 0)
+        .await
+        .expect("failed to get reservations");
+    assert_eq!(3, all.len());
+    let redeemed = db_get_event_reservations(&cfg.client, &cfg.event.id, Some(true), 10, 0)
+        .await
+        .expect("failed to get reservations");
+    assert_eq!(1, redeemed.len());
+    let reservation = EventReservation::from(redeemed[0].clone());
+    assert!(reservation.redeemed);
+    assert_eq!(db_ticket.ticket_name, reservation.ticket_name);
+    let unredeemed = db_get_event_reservations(&cfg.client, &cfg.event.id, Some(false), 10, 0)
+        .await
+        .expect("failed to get reservations");
+    assert_eq!(2, unredeemed.len());
+    assert!(unredeemed.iter().all(|r| r.checked_in_at.is_none()));
+
+    // the next page, oldest first
+    let page = db_get_event_reservations(&cfg.client, &cfg.event.id, None, 2, 2)
+        .await
+        .expect("failed to get reservations");
+    assert_eq!(
+        vec![all[2].reservation_id],
+        page.iter().map(|r| r.reservation_id).collect::<Vec<_>>()
+    );
+}
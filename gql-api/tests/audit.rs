@@ -0,0 +1,74 @@
+use gql_api::audit::{self, AuditEntity};
+
+mod common;
+
+#[tokio::test]
+async fn test_audit_log_record() {
+    let cfg = common::setup().await;
+
+    audit::record(
+        &cfg.client,
+        Some(cfg.event.created_by_user),
+        "update_event",
+        AuditEntity::Event(cfg.event.id),
+        Some(serde_json::json!({ "eventName": cfg.event.event_name })),
+    )
+    .await;
+    audit::record(
+        &cfg.client,
+        None,
+        "delete_event",
+        AuditEntity::Event(cfg.event.id),
+        None,
+    )
+    .await;
+
+    let by_entity =
+        gql_api::db::sql::db_get_audit_logs(&cfg.client, &None, &Some(cfg.event.id), 10, 0)
+            .await
+            .expect("failed to get audit logs by entity");
+    assert_eq!(2, by_entity.len());
+    assert!(by_entity.iter().all(|entry| entry.entity_type == "event"));
+
+    let by_actor = gql_api::db::sql::db_get_audit_logs(
+        &cfg.client,
+        &Some(cfg.event.created_by_user),
+        &Some(cfg.event.id),
+        10,
+        0,
+    )
+    .await
+    .expect("failed to get audit logs by actor");
+    assert_eq!(1, by_actor.len());
+    assert_eq!("update_event", by_actor[0].action);
+    assert!(by_actor[0].diff.is_some());
+
+    let paged = gql_api::db::sql::db_get_audit_logs(&cfg.client, &None, &Some(cfg.event.id), 1, 1)
+        .await
+        .expect("failed to page audit logs");
+    assert_eq!(1, paged.len());
+}
+
+#[tokio::test]
+async fn test_audit_log_record_impersonated() {
+    let cfg = common::setup().await;
+    let admin_id = uuid::Uuid::new_v4();
+
+    audit::record_impersonated(
+        &cfg.client,
+        Some(cfg.event.created_by_user),
+        Some(admin_id),
+        "update_event",
+        AuditEntity::Event(cfg.event.id),
+        None,
+    )
+    .await;
+
+    let entries =
+        gql_api::db::sql::db_get_audit_logs(&cfg.client, &None, &Some(cfg.event.id), 10, 0)
+            .await
+            .expect("failed to get audit logs by entity");
+    assert_eq!(1, entries.len());
+    assert_eq!(Some(cfg.event.created_by_user), entries[0].actor_id);
+    assert_eq!(Some(admin_id), entries[0].impersonated_by);
+}
@@ -0,0 +1,69 @@
+use gql_api::{
+    auth::{authorize_bearer, authorize_impersonable, create_impersonation_jwt, create_jwt, Role},
+    error::{AuthError, Error},
+};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+
+#[test]
+fn test_authorize_bearer() {
+    let user_id = uuid::Uuid::new_v4();
+    let jwt = create_jwt(&user_id.to_string(), &Role::Buyer).expect("failed to create jwt");
+
+    let authorized = authorize_bearer(&format!("Bearer {}", jwt), &[Role::Buyer, Role::Seller])
+        .expect("failed to authorize");
+    assert_eq!(user_id, authorized);
+
+    assert!(matches!(
+        authorize_bearer(&jwt, &[Role::Buyer]),
+        Err(Error::Auth(AuthError::InvalidAuthHeaderError))
+    ));
+    assert!(matches!(
+        authorize_bearer(&format!("Bearer {}", jwt), &[Role::Admin]),
+        Err(Error::Auth(AuthError::NoPermissionError))
+    ));
+    assert!(matches!(
+        authorize_bearer("Bearer not-a-jwt", &[Role::Buyer]),
+        Err(Error::Auth(AuthError::JWTTokenError))
+    ));
+}
+
+#[tokio::test]
+async fn test_authorize_impersonation() {
+    let user_id = uuid::Uuid::new_v4();
+    let impersonation_id = uuid::Uuid::new_v4();
+    let expires_at = chrono::Utc::now().timestamp() + 60;
+    let jwt = create_impersonation_jwt(
+        &user_id.to_string(),
+        &Role::Buyer,
+        &impersonation_id,
+        expires_at,
+    )
+    .expect("failed to create jwt");
+
+    // only accepted where the impersonation is checked
+    assert!(matches!(
+        authorize_bearer(&format!("Bearer {}", jwt), &[Role::Buyer]),
+        Err(Error::Auth(AuthError::ImpersonationNotAllowed))
+    ));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", jwt)).expect("valid header"),
+    );
+    let authorized = authorize_impersonable((vec![Role::Buyer], headers.clone()))
+        .await
+        .expect("failed to authorize");
+    assert_eq!((user_id, Some(impersonation_id)), authorized);
+
+    // the user's own jwt has no impersonation
+    let jwt = create_jwt(&user_id.to_string(), &Role::Buyer).expect("failed to create jwt");
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", jwt)).expect("valid header"),
+    );
+    let authorized = authorize_impersonable((vec![Role::Buyer], headers))
+        .await
+        .expect("failed to authorize");
+    assert_eq!((user_id, None), authorized);
+}
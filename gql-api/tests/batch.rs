@@ -0,0 +1,77 @@
+use gql_api::gql::{
+    batch::{batch_response, GraphQLBatchRequest},
+    mutations::PublicMutationRoot,
+    quiries::PublicQueryRoot,
+    routes::graphql_public_route,
+    schema::PublicSchema,
+    subscriptions::PublicSubscriptionRoot,
+};
+use std::sync::Arc;
+
+mod common;
+
+fn batch(json: serde_json::Value) -> GraphQLBatchRequest {
+    serde_json::from_value(json).expect("invalid batch")
+}
+
+#[test]
+fn test_batch_request() {
+    let single = batch(serde_json::json!({ "query": "{ __typename }" }));
+    assert!(!single.is_batch());
+    assert_eq!(1, single.len());
+    assert_eq!(1, single.into_requests(1).expect("a request").len());
+
+    let requests = batch(serde_json::json!([
+        { "query": "{ __typename }" },
+        { "query": "{ events { id } }", "operationName": null },
+    ]));
+    assert!(requests.is_batch());
+    assert_eq!(2, requests.len());
+    assert_eq!(
+        2,
+        requests.clone().into_requests(2).expect("requests").len()
+    );
+    // larger than the max batch size
+    assert!(requests.into_requests(1).is_err());
+
+    let empty = batch(serde_json::json!([]));
+    assert!(empty.is_empty());
+    assert!(empty.into_requests(10).is_err());
+}
+
+#[test]
+fn test_batch_response() {
+    let response = serde_json::json!({ "data": { "__typename": "Query" } });
+    assert_eq!(response, batch_response(vec![response.clone()], false));
+    // a batch of one is still an array
+    assert_eq!(
+        serde_json::json!([response.clone()]),
+        batch_response(vec![response], true)
+    );
+}
+
+#[tokio::test]
+async fn test_batch_errors_are_isolated() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let schema = PublicSchema::new(PublicQueryRoot, PublicMutationRoot, PublicSubscriptionRoot);
+    let route = graphql_public_route(resources.ctx, Arc::new(schema), warp::log("test"));
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/api/v1/graphql/public")
+        .header("content-type", "application/json")
+        .json(&serde_json::json!([
+            { "query": "{ __typename }" },
+            { "query": "{ notAField }" },
+        ]))
+        .reply(&route)
+        .await;
+    assert!(res.status().is_success());
+
+    let json: serde_json::Value = serde_json::from_slice(res.body()).expect("invalid response");
+    let responses = json.as_array().expect("an array of responses");
+    assert_eq!(2, responses.len());
+    assert!(responses[0].get("errors").is_none());
+    assert!(responses[0]["data"]["__typename"].is_string());
+    assert!(responses[1]["errors"].is_array());
+}
@@ -0,0 +1,40 @@
+use gql_api::{error::handle_rejection, filters::with_json_body};
+use warp::{http::StatusCode, hyper::body::Buf, Filter, Reply};
+
+fn body_length(body: impl Buf) -> impl Reply {
+    warp::reply::json(&body.remaining())
+}
+
+async fn post(content_type: Option<&str>, body: &str) -> StatusCode {
+    let route = warp::post()
+        .and(with_json_body(16))
+        .map(body_length)
+        .recover(handle_rejection);
+    let mut request = warp::test::request().method("POST").body(body.to_string());
+    if let Some(content_type) = content_type {
+        request = request.header("content-type", content_type);
+    }
+    request.reply(&route).await.status()
+}
+
+#[tokio::test]
+async fn test_json_body_limits() {
+    assert_eq!(StatusCode::OK, post(Some("application/json"), "{}").await);
+    assert_eq!(
+        StatusCode::OK,
+        post(Some("Application/JSON; charset=utf-8"), "{}").await
+    );
+
+    // too large, whatever its content
+    assert_eq!(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        post(Some("application/json"), &format!("\"{}\"", "a".repeat(64))).await
+    );
+
+    // not json
+    assert_eq!(
+        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        post(Some("text/plain"), "{}").await
+    );
+    assert_eq!(StatusCode::UNSUPPORTED_MEDIA_TYPE, post(None, "{}").await);
+}
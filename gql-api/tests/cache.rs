@@ -0,0 +1,155 @@
+use common::TestContextBuilder;
+use gql_api::{
+    cache::{drive_connection, listen, EventCache, EventKey},
+    config::{db_client_from_config, CacheConfig, PostgresConfig},
+    db::sql::{db_update_event_archived, db_update_event_status},
+    gql::{
+        models::{Event, EventStatus},
+        mutations::PublicMutationRoot,
+        quiries::PublicQueryRoot,
+        schema::{Context as ResourcesContext, PublicSchema},
+        subscriptions::PublicSubscriptionRoot,
+    },
+    metrics::CACHE_LOOKUPS_TOTAL,
+};
+use std::{sync::Arc, time::Duration};
+use uuid::Uuid;
+
+mod common;
+
+fn cache_config(event_ttl_secs: u64, max_events: usize) -> CacheConfig {
+    CacheConfig {
+        event_ttl_secs,
+        max_events,
+    }
+}
+
+async fn event(ctx: &ResourcesContext) -> Event {
+    let db_event = common::create_event(&ctx.db_client).await;
+    Event::new(db_event, vec![])
+}
+
+/// The names of the public events looked up by id
+async fn public_event_names(ctx: &ResourcesContext, event_id: &str) -> Vec<String> {
+    let schema = PublicSchema::new(PublicQueryRoot, PublicMutationRoot, PublicSubscriptionRoot);
+    let query = format!(r#"query {{ events(id: "{}") {{ eventName }} }}"#, event_id);
+    let (value, errors) = juniper::execute(&query, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid query");
+    assert!(errors.is_empty());
+    value
+        .as_object_value()
+        .and_then(|value| value.get_field_value("events"))
+        .and_then(|events| events.as_list_value())
+        .expect("a list of events")
+        .iter()
+        .filter_map(|event| event.as_object_value()?.get_field_value("eventName"))
+        .filter_map(|name| name.as_scalar_value::<String>().cloned())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_event_cache() {
+    let resources = TestContextBuilder::new().build().await;
+    let first = event(&resources.ctx).await;
+    let second = event(&resources.ctx).await;
+    let first_id = first.id;
+
+    let cache = EventCache::new(&cache_config(60, 2));
+    cache.insert(EventKey::Id(first_id), first.clone());
+    cache.insert(EventKey::Slug(first.event_slug.clone()), first.clone());
+    assert_eq!(
+        Some(first.event_name.clone()),
+        cache
+            .get(&EventKey::Slug(first.event_slug.clone()))
+            .map(|event| event.event_name)
+    );
+
+    // the oldest events are evicted
+    cache.insert(EventKey::Slug(second.event_slug.clone()), second.clone());
+    assert_eq!(2, cache.len());
+    assert!(cache.get(&EventKey::Id(first_id)).is_none());
+
+    // invalidated whichever way they were looked up
+    cache.insert(EventKey::Id(first_id), first.clone());
+    cache.invalidate(&first_id);
+    assert_eq!(1, cache.len());
+    cache.notified("not an event id");
+    assert!(cache.is_empty());
+
+    // a zero ttl disables the cache
+    let disabled = EventCache::new(&cache_config(0, 2));
+    assert!(!disabled.is_enabled());
+    disabled.insert(EventKey::Id(first_id), first);
+    assert!(disabled.is_empty());
+}
+
+#[tokio::test]
+async fn test_public_events_cached() {
+    let resources = TestContextBuilder::new()
+        .cache(cache_config(60, 100))
+        .build()
+        .await;
+    let db_event = common::create_event(&resources.ctx.db_client).await;
+    db_update_event_status(
+        &resources.ctx.db_client,
+        &db_event.id,
+        EventStatus::Draft,
+        EventStatus::Final,
+    )
+    .await
+    .expect("unable to publish event");
+    let event_id = db_event.id.to_string();
+
+    let hits = CACHE_LOOKUPS_TOTAL.with_label_values(&["events", "hit"]);
+    let hits_before = hits.get();
+    assert_eq!(
+        vec![db_event.event_name.clone()],
+        public_event_names(&resources.ctx, &event_id).await
+    );
+    assert_eq!(1, resources.ctx.event_cache.len());
+    assert_eq!(
+        vec![db_event.event_name.clone()],
+        public_event_names(&resources.ctx, &event_id).await
+    );
+    assert!(hits.get() > hits_before);
+
+    // unknown events are not cached
+    public_event_names(&resources.ctx, &Uuid::new_v4().to_string()).await;
+    assert_eq!(1, resources.ctx.event_cache.len());
+}
+
+#[tokio::test]
+async fn test_event_cache_notified() {
+    let (db_client, connection) = db_client_from_config(&PostgresConfig {
+        db_host: "127.0.0.1".to_string(),
+        db_port: 5432,
+        db_name: "usersdb".to_string(),
+        db_user: "postgres".to_string(),
+        db_pwd: "postgres".to_string(),
+    })
+    .await
+    .expect("unable to establish a db connection");
+    let cache = Arc::new(EventCache::new(&cache_config(60, 100)));
+    let connection_cache = cache.clone();
+    tokio::spawn(async move { drive_connection(connection, &connection_cache).await });
+    listen(&db_client).await.expect("unable to listen");
+
+    let resources = TestContextBuilder::new().build().await;
+    let event = event(&resources.ctx).await;
+    let event_id = event.id;
+    cache.insert(EventKey::Id(event_id), event);
+
+    // a change made by another connection, e.g. another instance
+    db_update_event_archived(&resources.ctx.db_client, &event_id, true)
+        .await
+        .expect("unable to archive event");
+
+    let invalidated = tokio::time::timeout(Duration::from_secs(5), async {
+        while !cache.is_empty() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await;
+    assert!(invalidated.is_ok());
+}
@@ -0,0 +1,181 @@
+use chrono::Duration;
+use gql_api::{
+    auth::Role,
+    cancellations::{cancel_event, cancel_reservation, refund},
+    db::{
+        models::{DbEvent, DbTicket, DbTicketReservation},
+        sql::{
+            db_get_ticket_by_id, db_get_ticket_cancellations_by_user_id, db_insert_event,
+            db_insert_ticket, db_reserve_ticket, sql_timestamp,
+        },
+    },
+    gql::{
+        models::{EventStatus, NewTicket},
+        validations::check_cancellation_window,
+    },
+    near::NearAmount,
+};
+
+mod common;
+
+fn new_reservation(
+    db_ticket: &DbTicket,
+    user_id: uuid::Uuid,
+    quantity: i32,
+) -> DbTicketReservation {
+    DbTicketReservation::new(
+        uuid::Uuid::new_v4(),
+        sql_timestamp(None),
+        &common::gen_string(6),
+        db_ticket.event_id,
+        db_ticket.id,
+        user_id,
+        quantity,
+    )
+}
+
+#[test]
+fn test_cancellation_window() {
+    let now = sql_timestamp(None);
+    let mut db_event = DbEvent::new("cancellable", uuid::Uuid::new_v4());
+    db_event.start_date = Some(now + Duration::hours(48));
+    // buyers cannot cancel without a window
+    assert!(!db_event.is_cancellable_at(&now));
+
+    db_event.cancellation_window_hours = Some(24);
+    assert!(db_event.is_cancellable_at(&now));
+    assert!(db_event.is_cancellable_at(&(now + Duration::hours(24))));
+    assert!(!db_event.is_cancellable_at(&(now + Duration::hours(25))));
+
+    db_event.event_status = EventStatus::Cancelled;
+    assert!(!db_event.is_cancellable_at(&now));
+
+    assert!(check_cancellation_window(0).is_ok());
+    assert!(check_cancellation_window(-1).is_err());
+    assert!(check_cancellation_window(24 * 366).is_err());
+}
+
+#[tokio::test]
+async fn test_cancellations() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let ctx = &resources.ctx;
+    let db_client = &ctx.db_client;
+
+    let seller = common::create_user(db_client, Role::Seller).await;
+    let db_event = DbEvent {
+        start_date: Some(sql_timestamp(None) + Duration::days(7)),
+        event_status: EventStatus::Final,
+        cancellation_window_hours: Some(24),
+        ..DbEvent::new(&common::gen_string(20), seller)
+    };
+    db_insert_event(db_client, &db_event)
+        .await
+        .expect("failed to insert event");
+    let db_ticket = DbTicket::new(
+        NewTicket {
+            ticket_name: common::gen_string(10),
+            description: None,
+            price: Some(NearAmount::from_whole_near(3)),
+            max_release_price: None,
+            quantity_available: Some(10),
+            min_purchase_quantity: None,
+            max_purchase_quantity: None,
+            allow_transfers: Some(true),
+            currency: None,
+            event_id: db_event.id,
+        },
+        &db_event,
+    );
+    db_insert_ticket(db_client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+
+    let buyer = common::create_user(db_client, Role::Buyer).await;
+    let mut reservations = vec![];
+    for quantity in [2, 1, 4] {
+        reservations.push(
+            db_reserve_ticket(db_client, &new_reservation(&db_ticket, buyer, quantity))
+                .await
+                .expect("failed to reserve")
+                .expect("a reservation"),
+        );
+    }
+
+    // the buyer cancels, the tickets go back on sale and what was paid is owed
+    let db_cancellation = cancel_reservation(ctx, &db_event, &reservations[0].id, &buyer)
+        .await
+        .expect("failed to cancel")
+        .expect("a cancellation");
+    assert_eq!(
+        NearAmount::from_whole_near(6),
+        db_cancellation.refund_amount
+    );
+    assert!(db_cancellation.is_refund_due());
+    let reserved = db_get_ticket_by_id(db_client, &db_ticket.id)
+        .await
+        .expect("failed to get ticket");
+    assert_eq!(5, reserved.quantity_reserved);
+    assert!(
+        cancel_reservation(ctx, &db_event, &reservations[0].id, &buyer)
+            .await
+            .expect("failed to cancel")
+            .is_none()
+    );
+
+    // refunded once
+    let refunded = refund(ctx, &db_cancellation.id)
+        .await
+        .expect("failed to refund")
+        .expect("a refund");
+    assert!(refunded.refund_tx_hash.is_some());
+    assert!(!refunded.is_refund_due());
+    assert!(refund(ctx, &db_cancellation.id)
+        .await
+        .expect("failed to refund")
+        .is_none());
+    assert_eq!(
+        1,
+        resources
+            .near_client
+            .calls()
+            .iter()
+            .filter(|call| call.as_str() == "refund_payment")
+            .count()
+    );
+
+    // the seller cancels the event along with the reservations left
+    let (cancelled_event, db_cancellations) = cancel_event(ctx, &db_event, &seller)
+        .await
+        .expect("failed to cancel event")
+        .expect("a cancelled event");
+    assert_eq!(EventStatus::Cancelled, cancelled_event.event_status);
+    assert_eq!(2, db_cancellations.len());
+    let reserved = db_get_ticket_by_id(db_client, &db_ticket.id)
+        .await
+        .expect("failed to get ticket");
+    assert_eq!(0, reserved.quantity_reserved);
+    assert!(cancel_event(ctx, &db_event, &seller)
+        .await
+        .expect("failed to cancel event")
+        .is_none());
+
+    // no more tickets of a cancelled event are reserved
+    assert!(
+        db_reserve_ticket(db_client, &new_reservation(&db_ticket, buyer, 1))
+            .await
+            .expect("failed to reserve")
+            .is_none()
+    );
+
+    let db_cancellations = db_get_ticket_cancellations_by_user_id(db_client, &buyer)
+        .await
+        .expect("failed to get cancellations");
+    assert_eq!(3, db_cancellations.len());
+    assert_eq!(
+        vec![Some(seller), Some(seller), Some(buyer)],
+        db_cancellations
+            .iter()
+            .map(|db_cancellation| db_cancellation.cancelled_by)
+            .collect::<Vec<_>>()
+    );
+}
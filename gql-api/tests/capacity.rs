@@ -0,0 +1,127 @@
+use chrono::Utc;
+use gql_api::{
+    auth::Role,
+    db::models::{DbTicket, DbTicketReservation},
+    error::TicketError,
+    gql::models::NewTicket,
+    near::NearAmount,
+};
+
+mod common;
+
+fn new_ticket(event_id: uuid::Uuid) -> NewTicket {
+    NewTicket {
+        ticket_name: common::gen_string(10),
+        description: None,
+        price: Some(NearAmount::from_whole_near(10)),
+        max_release_price: None,
+        quantity_available: Some(3),
+        min_purchase_quantity: Some(1),
+        max_purchase_quantity: Some(2),
+        allow_transfers: Some(false),
+        currency: None,
+        event_id,
+    }
+}
+
+fn new_reservation(
+    db_ticket: &DbTicket,
+    user_id: uuid::Uuid,
+    quantity: i32,
+) -> DbTicketReservation {
+    DbTicketReservation::new(
+        uuid::Uuid::new_v4(),
+        Utc::now().naive_utc(),
+        &common::gen_string(6),
+        db_ticket.event_id,
+        db_ticket.id,
+        user_id,
+        quantity,
+    )
+}
+
+#[tokio::test]
+async fn test_check_purchase_quantity() {
+    let cfg = common::setup().await;
+    let mut db_ticket = DbTicket::new(new_ticket(cfg.event.id), &cfg.event);
+    let ticket_id = db_ticket.id.to_string();
+
+    assert_eq!(Ok(()), db_ticket.check_purchase_quantity(2, 0));
+    assert_eq!(
+        Err(TicketError::InvalidQuantity(ticket_id.clone())),
+        db_ticket.check_purchase_quantity(0, 0)
+    );
+    assert_eq!(
+        Err(TicketError::AboveMaxPurchaseQuantity(ticket_id.clone())),
+        db_ticket.check_purchase_quantity(1, 2)
+    );
+
+    db_ticket.min_purchase_quantity = Some(2);
+    assert_eq!(
+        Err(TicketError::BelowMinPurchaseQuantity(ticket_id.clone())),
+        db_ticket.check_purchase_quantity(1, 0)
+    );
+
+    db_ticket.quantity_reserved = 2;
+    assert_eq!(Some(1), db_ticket.quantity_remaining());
+    assert_eq!(
+        Err(TicketError::InsufficientQuantity(ticket_id)),
+        db_ticket.check_purchase_quantity(2, 0)
+    );
+
+    db_ticket.quantity_available = None;
+    assert_eq!(None, db_ticket.quantity_remaining());
+    assert_eq!(Ok(()), db_ticket.check_purchase_quantity(2, 0));
+}
+
+#[tokio::test]
+async fn test_reserve_ticket_limits() {
+    let cfg = common::setup().await;
+    let db_ticket = DbTicket::new(new_ticket(cfg.event.id), &cfg.event);
+    gql_api::db::sql::db_insert_ticket(&cfg.client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+
+    let first_buyer = common::create_user(&cfg.client, Role::Buyer).await;
+    let second_buyer = common::create_user(&cfg.client, Role::Buyer).await;
+
+    let reserved = gql_api::db::sql::db_reserve_ticket(
+        &cfg.client,
+        &new_reservation(&db_ticket, first_buyer, 2),
+    )
+    .await
+    .expect("failed to reserve ticket");
+    assert_eq!(Some(2), reserved.map(|reservation| reservation.quantity));
+
+    // above the maximum purchase quantity per user
+    let reserved = gql_api::db::sql::db_reserve_ticket(
+        &cfg.client,
+        &new_reservation(&db_ticket, first_buyer, 1),
+    )
+    .await
+    .expect("failed to reserve ticket");
+    assert!(reserved.is_none());
+
+    // only one ticket left
+    let reserved = gql_api::db::sql::db_reserve_ticket(
+        &cfg.client,
+        &new_reservation(&db_ticket, second_buyer, 2),
+    )
+    .await
+    .expect("failed to reserve ticket");
+    assert!(reserved.is_none());
+
+    let reserved = gql_api::db::sql::db_reserve_ticket(
+        &cfg.client,
+        &new_reservation(&db_ticket, second_buyer, 1),
+    )
+    .await
+    .expect("failed to reserve ticket");
+    assert!(reserved.is_some());
+
+    let db_ticket = gql_api::db::sql::db_get_ticket_by_id(&cfg.client, &db_ticket.id)
+        .await
+        .expect("failed to get ticket");
+    assert_eq!(3, db_ticket.quantity_reserved);
+    assert_eq!(Some(0), db_ticket.quantity_remaining());
+}
@@ -0,0 +1,106 @@
+use gql_api::{
+    db::{models::DbCategory, sql::EventsFilter},
+    gql::validations::{check_category_name, check_event_tags},
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_event_category_and_tags_filters() {
+    let cfg = common::setup().await;
+
+    let db_category = gql_api::db::sql::db_insert_category(
+        &cfg.client,
+        &DbCategory::new(&common::gen_string(12)),
+    )
+    .await
+    .expect("failed to insert category");
+
+    let found = gql_api::db::sql::db_get_category_by_slug(&cfg.client, &db_category.slug)
+        .await
+        .expect("failed to get category")
+        .expect("category should exist");
+    assert_eq!(db_category.id, found.id);
+
+    let updated = gql_api::db::sql::db_update_event_category(
+        &cfg.client,
+        &cfg.event.id,
+        Some(&db_category.id),
+    )
+    .await
+    .expect("failed to set event category");
+    assert_eq!(Some(db_category.id), updated.category_id);
+
+    let tag = common::gen_string(12).to_lowercase();
+    let tags = check_event_tags(&[tag.clone(), "music".to_string()]).expect("valid tags");
+    gql_api::db::sql::db_set_event_tags(&cfg.client, &cfg.event.id, &tags)
+        .await
+        .expect("failed to set event tags");
+
+    let listed = gql_api::db::sql::db_get_events(
+        &cfg.client,
+        &EventsFilter {
+            category_slug: Some(db_category.slug.clone()),
+            tag: Some(tag.clone()),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to list events");
+    assert_eq!(1, listed.len());
+    assert_eq!(cfg.event.id, listed[0].id);
+
+    let listed = gql_api::db::sql::db_get_events(
+        &cfg.client,
+        &EventsFilter {
+            id: Some(cfg.event.id),
+            tag: Some(common::gen_string(12).to_lowercase()),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to list events");
+    assert!(listed.is_empty());
+
+    // replacing the tags drops the ones no longer submitted
+    gql_api::db::sql::db_set_event_tags(&cfg.client, &cfg.event.id, &[tag.clone()])
+        .await
+        .expect("failed to set event tags");
+    let event_tags = gql_api::db::sql::db_get_event_tags(&cfg.client, &[cfg.event.id])
+        .await
+        .expect("failed to get event tags");
+    assert_eq!(
+        vec![tag.clone()],
+        event_tags.into_iter().map(|t| t.tag).collect::<Vec<_>>()
+    );
+
+    let popular = gql_api::db::sql::db_get_popular_tags(&cfg.client, 1000)
+        .await
+        .expect("failed to get popular tags");
+    assert!(popular
+        .iter()
+        .any(|tag_count| tag_count.tag.eq(&tag) && tag_count.event_count == 1));
+}
+
+#[test]
+fn test_check_event_tags() {
+    assert_eq!(
+        vec!["live-music".to_string(), "rock".to_string()],
+        check_event_tags(&[
+            "Rock".to_string(),
+            "Live Music".to_string(),
+            "rock".to_string()
+        ])
+        .unwrap()
+    );
+    assert!(check_event_tags(&["   ".to_string()]).is_err());
+    assert!(check_event_tags(&["a".repeat(33)]).is_err());
+    assert!(check_event_tags(&(0..11).map(|i| format!("tag{}", i)).collect::<Vec<_>>()).is_err());
+}
+
+#[test]
+fn test_check_category_name() {
+    assert!(check_category_name("Music").is_ok());
+    assert!(check_category_name("").is_err());
+    assert!(check_category_name(&"a".repeat(65)).is_err());
+}
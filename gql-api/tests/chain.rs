@@ -0,0 +1,122 @@
+use gql_api::{
+    chain::{ChainClient, MockChainClient, NearChainClient},
+    fakes::FakeNearClient,
+    grpc::NearClient,
+    security::crypto::NearAccount,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[tokio::test]
+async fn test_mock_chain() {
+    let chain = MockChainClient::default();
+    let account = NearAccount::new_implicit().expect("failed to create an account");
+    let public_key = format!("ed25519:{}", account.pub_key_b58_encoded());
+
+    let tx = chain
+        .create_account("buyer.testnet", &public_key, "0.2")
+        .await
+        .expect("failed to create the account");
+    assert!(!tx.failed);
+    assert!(
+        chain
+            .create_account("buyer.testnet", &public_key, "0.2")
+            .await
+            .expect("failed to create the account")
+            .failed
+    );
+    assert_eq!(
+        vec![public_key.clone()],
+        chain
+            .account_keys("buyer.testnet")
+            .await
+            .expect("failed to get the keys")
+    );
+    assert!(chain
+        .account_keys("unknown.testnet")
+        .await
+        .expect("failed to get the keys")
+        .is_empty());
+
+    // signatures are verified locally
+    let message = b"Sign in with nonce: 42";
+    let (_, signature) = account.sign_message(message);
+    assert!(chain
+        .verify_signature(message, &public_key, &signature)
+        .await
+        .expect("failed to verify"));
+    assert!(!chain
+        .verify_signature(b"another message", &public_key, &signature)
+        .await
+        .expect("failed to verify"));
+    assert!(!chain
+        .verify_signature(message, "not a key", &signature)
+        .await
+        .expect("failed to verify"));
+
+    let key_pair = chain
+        .generate_key_pair()
+        .await
+        .expect("failed to generate keys");
+    assert!(key_pair.public_key.starts_with("ed25519:"));
+    assert_ne!(key_pair.public_key, key_pair.secret_key);
+
+    // funds always go through
+    assert!(
+        !chain
+            .fund_account("buyer.testnet", "1")
+            .await
+            .expect("failed to fund")
+            .failed
+    );
+    assert!(
+        !chain
+            .refund_payment("buyer.testnet", "1", "refund")
+            .await
+            .expect("failed to refund")
+            .failed
+    );
+}
+
+#[tokio::test]
+async fn test_near_chain() {
+    let near_client = FakeNearClient {
+        is_verified: false,
+        ..FakeNearClient::default()
+    };
+    let boxed: Box<dyn NearClient> = Box::new(near_client.clone());
+    let chain = NearChainClient::new(Arc::new(Mutex::new(boxed)));
+
+    assert!(chain
+        .account_keys("seller.testnet")
+        .await
+        .expect("failed to get the keys")
+        .is_empty());
+    assert!(!chain
+        .verify_signature(b"message", "ed25519:seller", "signature")
+        .await
+        .expect("failed to verify"));
+    let tx = chain
+        .transfer_nft("seller.testnet", "buyer.testnet", "ticket", "0")
+        .await
+        .expect("failed to transfer");
+    assert!(!tx.failed);
+    chain
+        .fund_account("buyer.testnet", "1")
+        .await
+        .expect("failed to fund");
+    chain
+        .refund_payment("buyer.testnet", "1", "refund")
+        .await
+        .expect("failed to refund");
+    assert_eq!(
+        vec![
+            "get_account_keys",
+            "verify_signature",
+            "transfer_nft",
+            "fund_account",
+            "refund_payment"
+        ],
+        near_client.calls()
+    );
+}
@@ -0,0 +1,46 @@
+use gql_api::{
+    check::CheckReport,
+    http::{
+        health::{STATUS_DOWN, STATUS_UP},
+        models::DependencyStatus,
+    },
+};
+
+fn dependency(name: &str, error: Option<&str>) -> DependencyStatus {
+    DependencyStatus {
+        name: name.to_string(),
+        status: if error.is_some() {
+            STATUS_DOWN
+        } else {
+            STATUS_UP
+        }
+        .to_string(),
+        latency_ms: 3,
+        error: error.map(str::to_string),
+    }
+}
+
+#[test]
+fn test_check_report() {
+    let report = CheckReport {
+        dependencies: vec![dependency("config", None), dependency("postgres", None)],
+        pending_migrations: vec![],
+    };
+    assert!(report.is_ok());
+    let printed = report.to_string();
+    assert!(printed.contains("no pending migrations"));
+    assert!(printed.ends_with("OK"));
+
+    let report = CheckReport {
+        dependencies: vec![
+            dependency("config", None),
+            dependency("near_api", Some("transport error")),
+        ],
+        pending_migrations: vec!["2022-04-15-00049_unique_slugs".to_string()],
+    };
+    assert!(!report.is_ok());
+    let printed = report.to_string();
+    assert!(printed.contains("transport error"));
+    assert!(printed.contains("  2022-04-15-00049_unique_slugs"));
+    assert!(printed.ends_with("FAILED"));
+}
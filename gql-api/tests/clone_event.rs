@@ -0,0 +1,134 @@
+use chrono::{Duration, Utc};
+use gql_api::{
+    auth::Role,
+    db::{
+        models::{AssetFile, DbEvent, DbTicket},
+        sql::{db_get_event_by_id, db_get_tickets_by_event_id, db_insert_event_with_tickets},
+    },
+    gql::{
+        clone::{clone_asset_file, clone_event, clone_event_name},
+        models::{CloneEventOverrides, EventStatus, NewTicket},
+    },
+};
+
+mod common;
+
+fn new_ticket(db_event: &DbEvent, quantity_available: Option<i32>) -> DbTicket {
+    let mut db_ticket = DbTicket::new(
+        NewTicket {
+            ticket_name: common::gen_string(10),
+            description: None,
+            price: None,
+            max_release_price: None,
+            quantity_available,
+            min_purchase_quantity: None,
+            max_purchase_quantity: None,
+            allow_transfers: Some(true),
+            currency: None,
+            event_id: db_event.id,
+        },
+        db_event,
+    );
+    db_ticket.quantity_reserved = 3;
+    db_ticket
+}
+
+#[test]
+fn test_clone_event_name() {
+    assert_eq!("Concert (copy)", clone_event_name("Concert", 1));
+    assert_eq!("Concert (copy 2)", clone_event_name("Concert", 2));
+}
+
+#[test]
+fn test_clone_event() {
+    let creator = uuid::Uuid::new_v4();
+    let mut source = DbEvent::new("Concert", creator);
+    source.event_status = EventStatus::Final;
+    source.cover_photo_url = Some("https://bucket/cover.png".to_string());
+    source.category_id = Some(uuid::Uuid::new_v4());
+    let mut deleted = new_ticket(&source, None);
+    deleted.deleted_at = Some(Utc::now().naive_utc());
+    let source_tickets = vec![new_ticket(&source, Some(10)), deleted];
+
+    let editor = uuid::Uuid::new_v4();
+    let start_date = Utc::now().naive_utc() + Duration::days(7);
+    let (db_event, db_tickets) = clone_event(
+        &source,
+        &source_tickets,
+        "Concert (copy)",
+        CloneEventOverrides {
+            start_date: Some(start_date),
+            ..Default::default()
+        },
+        editor,
+    )
+    .expect("failed to clone event");
+
+    assert_ne!(source.id, db_event.id);
+    assert_eq!("concert-copy", db_event.event_slug);
+    assert_eq!(EventStatus::Draft, db_event.event_status);
+    assert_eq!(editor, db_event.created_by_user);
+    assert_eq!(Some(start_date), db_event.start_date);
+    assert_eq!(source.cover_photo_url, db_event.cover_photo_url);
+    assert_eq!(source.category_id, db_event.category_id);
+
+    // deleted tickets are left behind, the copies start without reservations
+    assert_eq!(1, db_tickets.len());
+    assert_eq!(db_event.id, db_tickets[0].event_id);
+    assert_eq!(source_tickets[0].ticket_name, db_tickets[0].ticket_name);
+    assert_eq!(Some(10), db_tickets[0].quantity_available);
+    assert_eq!(0, db_tickets[0].quantity_reserved);
+    assert!(db_tickets[0].ticket_slug.starts_with("concert-copy-"));
+
+    // the overridden dates are validated like an update
+    let end_date = Utc::now().naive_utc() + Duration::days(1);
+    assert!(clone_event(
+        &source,
+        &source_tickets,
+        "Concert (copy)",
+        CloneEventOverrides {
+            start_date: Some(start_date),
+            end_date: Some(end_date),
+            ..Default::default()
+        },
+        editor,
+    )
+    .is_err());
+
+    let file = AssetFile::new("bucket", "events/cover.png", None, source.id);
+    let cloned_file = clone_asset_file(&file, db_event.id);
+    assert_ne!(file.id, cloned_file.id);
+    assert_eq!(db_event.id, cloned_file.event_id);
+    assert_eq!(file.s3_absolute_key, cloned_file.s3_absolute_key);
+}
+
+#[tokio::test]
+async fn test_insert_cloned_event() {
+    let cfg = common::setup().await;
+    let source_ticket = new_ticket(&cfg.event, Some(5));
+    let event_name = common::gen_string(12);
+    let editor = common::create_user(&cfg.client, Role::Seller).await;
+
+    let (db_event, db_tickets) = clone_event(
+        &cfg.event,
+        &[source_ticket],
+        &event_name,
+        CloneEventOverrides::default(),
+        editor,
+    )
+    .expect("failed to clone event");
+    db_insert_event_with_tickets(&cfg.client, &db_event, &db_tickets)
+        .await
+        .expect("failed to insert cloned event");
+
+    let stored = db_get_event_by_id(&cfg.client, &db_event.id)
+        .await
+        .expect("failed to get cloned event");
+    assert_eq!(event_name, stored.event_name);
+    assert_eq!(cfg.event.venue_name, stored.venue_name);
+    let stored_tickets = db_get_tickets_by_event_id(&cfg.client, &Some(db_event.id))
+        .await
+        .expect("failed to get cloned tickets");
+    assert_eq!(1, stored_tickets.len());
+    assert_eq!(0, stored_tickets[0].quantity_reserved);
+}
@@ -0,0 +1,44 @@
+use gql_api::security::codes::Codes;
+
+#[test]
+fn test_hash_and_verify() {
+    let codes = Codes::new("secret", &[]);
+    let hash = codes.hash("123456");
+    assert!(hash.starts_with("v1$"));
+    assert!(!hash.contains("123456"));
+    assert!(codes.verify(&hash, "123456"));
+    assert!(!codes.verify(&hash, "123457"));
+    assert!(!codes.verify(&hash, ""));
+
+    // every hash is salted afresh
+    assert_ne!(hash, codes.hash("123456"));
+
+    // and keyed with the secret
+    assert!(!Codes::new("other", &[]).verify(&hash, "123456"));
+}
+
+#[test]
+fn test_rotated_secret() {
+    let old = Codes::new("old", &[]);
+    let hash = old.hash("ABCDEF");
+
+    let codes = Codes::new("new", &["old".to_string()]);
+    assert!(codes.verify(&hash, "ABCDEF"));
+    assert!(!codes.verify(&hash, "ABCDEG"));
+    assert!(codes.verify(&codes.hash("ABCDEF"), "ABCDEF"));
+    assert!(!old.verify(&codes.hash("ABCDEF"), "ABCDEF"));
+}
+
+#[test]
+fn test_plaintext_and_malformed_hashes() {
+    let codes = Codes::new("secret", &[]);
+
+    // the codes stored before hashing
+    assert!(codes.verify("123456", "123456"));
+    assert!(!codes.verify("123456", "654321"));
+
+    assert!(!codes.verify("v2$00$00", "123456"));
+    assert!(!codes.verify("v1$", "123456"));
+    assert!(!codes.verify("v1$zz$00", "123456"));
+    assert!(!codes.verify("v1$00$", "123456"));
+}
@@ -0,0 +1,124 @@
+use gql_api::{
+    auth::Role,
+    db::{
+        models::{DbEvent, DbEventCollaborator},
+        sql::{
+            db_delete_event_collaborator, db_get_event_collaborator, db_get_event_collaborators,
+            db_upsert_event_collaborator,
+        },
+    },
+    gql::models::MemberRole,
+    policy::{can_access_event, can_collaborate, EventAccess},
+};
+
+mod common;
+
+#[test]
+fn test_can_collaborate() {
+    let creator = uuid::Uuid::new_v4();
+    let collaborator = uuid::Uuid::new_v4();
+    let db_event = DbEvent::new("co-hosted", creator);
+
+    // a collaborator has no access through an organization
+    assert!(!can_access_event(
+        &collaborator,
+        &db_event,
+        None,
+        EventAccess::CheckIn
+    ));
+
+    let scanner = DbEventCollaborator::new(db_event.id, collaborator, MemberRole::Scanner);
+    assert!(can_collaborate(
+        &collaborator,
+        &db_event,
+        Some(&scanner),
+        EventAccess::CheckIn
+    ));
+    assert!(!can_collaborate(
+        &collaborator,
+        &db_event,
+        Some(&scanner),
+        EventAccess::Edit
+    ));
+
+    let editor = DbEventCollaborator::new(db_event.id, collaborator, MemberRole::Editor);
+    assert!(can_collaborate(
+        &collaborator,
+        &db_event,
+        Some(&editor),
+        EventAccess::Edit
+    ));
+
+    // a collaboration on another event or of another user does not count
+    assert!(!can_collaborate(
+        &collaborator,
+        &DbEvent::new("other", creator),
+        Some(&editor),
+        EventAccess::Edit
+    ));
+    assert!(!can_collaborate(
+        &creator,
+        &db_event,
+        Some(&editor),
+        EventAccess::Edit
+    ));
+    assert!(!can_collaborate(
+        &collaborator,
+        &db_event,
+        None,
+        EventAccess::CheckIn
+    ));
+}
+
+#[tokio::test]
+async fn test_event_collaborators() {
+    let cfg = common::setup().await;
+    let collaborator = common::create_user(&cfg.client, Role::Seller).await;
+
+    db_upsert_event_collaborator(
+        &cfg.client,
+        &DbEventCollaborator::new(cfg.event.id, collaborator, MemberRole::Scanner),
+    )
+    .await
+    .expect("failed to add collaborator");
+    // adding a collaborator again changes its role
+    db_upsert_event_collaborator(
+        &cfg.client,
+        &DbEventCollaborator::new(cfg.event.id, collaborator, MemberRole::Editor),
+    )
+    .await
+    .expect("failed to update collaborator");
+
+    let db_collaborator = db_get_event_collaborator(&cfg.client, &cfg.event.id, &collaborator)
+        .await
+        .expect("failed to get collaborator")
+        .expect("user should be a collaborator");
+    assert_eq!(MemberRole::Editor, db_collaborator.collaborator_role);
+    assert!(can_collaborate(
+        &collaborator,
+        &cfg.event,
+        Some(&db_collaborator),
+        EventAccess::Edit
+    ));
+
+    let collaborators = db_get_event_collaborators(&cfg.client, &cfg.event.id)
+        .await
+        .expect("failed to get collaborators");
+    assert_eq!(
+        vec![collaborator],
+        collaborators.iter().map(|c| c.user_id).collect::<Vec<_>>()
+    );
+
+    assert_eq!(
+        1,
+        db_delete_event_collaborator(&cfg.client, &cfg.event.id, &collaborator)
+            .await
+            .expect("failed to remove collaborator")
+    );
+    assert!(
+        db_get_event_collaborator(&cfg.client, &cfg.event.id, &collaborator)
+            .await
+            .expect("failed to get collaborator")
+            .is_none()
+    );
+}
@@ -1,19 +1,55 @@
 use chrono::{Local, Utc};
 use gql_api::{
-    auth::{Role, UserStatus},
-    config::{db_client_from_config, PostgresConfig},
+    auth::{Role, SellerStatus, UserStatus},
+    cache::EventCache,
+    chain::NearChainClient,
+    config::{
+        db_client_from_config, AssetUrlsConfig, BodyLimitsConfig, BusinessConfig, CacheConfig,
+        GraphqlConfig, HealthConfig, HttpCacheConfig, JobsConfig, LoginLockoutConfig,
+        MaintenanceConfig, NearConfig, PasswordPolicyConfig, PostgresConfig, SanitationConfig,
+        SeatsConfig, SeriesConfig, SessionsConfig, SignupsConfig, StockAlertsConfig,
+        TicketPdfConfig, TopUpsConfig, TotpConfig, UsernamesConfig,
+    },
     db::models::{AssetFile, DbEvent, DbUser},
-    gql::models::EventStatus,
+    fakes::{
+        FakeGeocoder, FakeNearClient, FakeNotifier, FakePublisher, FakeStorage, FakeWebhookSender,
+    },
+    gql::{
+        models::EventStatus,
+        schema::{Context as ResourcesContext, Resources},
+    },
+    grpc::NearClient,
+    maintenance::Maintenance,
+    security::{codes::Codes, pusher_auth::PusherAuth},
 };
 use rand::Rng;
+use std::sync::Arc;
+use tokio::sync::Mutex;
 use tokio_postgres::Client;
 
+/// The pusher app credentials the test resources sign channel subscriptions with
+pub const PUSHER_KEY: &str = "278d425bdf160c739803";
+pub const PUSHER_SECRET: &str = "7ad3773142a6692b25b8";
+
+/// The key the test resources hash the buyers' codes with
+pub const CODES_SECRET: &str = "codes-secret";
+
 pub struct TestContext {
     pub client: Client,
     pub event: DbEvent,
 }
 
 pub async fn setup() -> TestContext {
+    let db_client = connect().await;
+    let event = create_event(&db_client).await;
+
+    TestContext {
+        client: db_client,
+        event,
+    }
+}
+
+pub async fn connect() -> Client {
     let (db_client, connection) = db_client_from_config(&PostgresConfig {
         db_host: "127.0.0.1".to_string(),
         db_port: 5432,
@@ -30,11 +66,202 @@ pub async fn setup() -> TestContext {
         }
     });
 
-    let event = create_event(&db_client).await;
+    db_client
+}
 
-    TestContext {
-        client: db_client,
-        event,
+/// The resources of a handler level test, the fakes kept to assert on the side effects
+pub struct TestResources {
+    pub ctx: Arc<ResourcesContext>,
+    pub near_client: FakeNearClient,
+    pub publisher: Arc<FakePublisher>,
+    pub notifier: Arc<FakeNotifier>,
+    pub storage: Arc<FakeStorage>,
+    pub geocoder: Arc<FakeGeocoder>,
+    pub webhooks: Arc<FakeWebhookSender>,
+}
+
+/// Builds the resources of a handler level test, backed by the test db and in-memory fakes
+#[derive(Default)]
+pub struct TestContextBuilder {
+    near_client: FakeNearClient,
+    storage: FakeStorage,
+    geocoder: FakeGeocoder,
+    jobs: JobsConfig,
+    sessions: SessionsConfig,
+    graphql: GraphqlConfig,
+    totp: Option<TotpConfig>,
+    ticket_pdf: Option<TicketPdfConfig>,
+    top_ups: TopUpsConfig,
+    signups: SignupsConfig,
+    business: BusinessConfig,
+    stock_alerts: StockAlertsConfig,
+    series: SeriesConfig,
+    seats: SeatsConfig,
+    cache: Option<CacheConfig>,
+    maintenance: MaintenanceConfig,
+    asset_urls: AssetUrlsConfig,
+    password_policy: PasswordPolicyConfig,
+    login_lockout: LoginLockoutConfig,
+}
+
+impl TestContextBuilder {
+    pub fn new() -> Self {
+        TestContextBuilder::default()
+    }
+
+    pub fn near_client(mut self, near_client: FakeNearClient) -> Self {
+        self.near_client = near_client;
+        self
+    }
+
+    pub fn storage(mut self, storage: FakeStorage) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    pub fn geocoder(mut self, geocoder: FakeGeocoder) -> Self {
+        self.geocoder = geocoder;
+        self
+    }
+
+    pub fn jobs(mut self, jobs: JobsConfig) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    pub fn sessions(mut self, sessions: SessionsConfig) -> Self {
+        self.sessions = sessions;
+        self
+    }
+
+    pub fn graphql(mut self, graphql: GraphqlConfig) -> Self {
+        self.graphql = graphql;
+        self
+    }
+
+    pub fn totp(mut self, totp: TotpConfig) -> Self {
+        self.totp = Some(totp);
+        self
+    }
+
+    pub fn ticket_pdf(mut self, ticket_pdf: TicketPdfConfig) -> Self {
+        self.ticket_pdf = Some(ticket_pdf);
+        self
+    }
+
+    pub fn password_policy(mut self, password_policy: PasswordPolicyConfig) -> Self {
+        self.password_policy = password_policy;
+        self
+    }
+
+    pub fn login_lockout(mut self, login_lockout: LoginLockoutConfig) -> Self {
+        self.login_lockout = login_lockout;
+        self
+    }
+
+    pub fn top_ups(mut self, top_ups: TopUpsConfig) -> Self {
+        self.top_ups = top_ups;
+        self
+    }
+
+    pub fn signups(mut self, signups: SignupsConfig) -> Self {
+        self.signups = signups;
+        self
+    }
+
+    pub fn business(mut self, business: BusinessConfig) -> Self {
+        self.business = business;
+        self
+    }
+
+    pub fn stock_alerts(mut self, stock_alerts: StockAlertsConfig) -> Self {
+        self.stock_alerts = stock_alerts;
+        self
+    }
+
+    pub fn series(mut self, series: SeriesConfig) -> Self {
+        self.series = series;
+        self
+    }
+
+    pub fn seats(mut self, seats: SeatsConfig) -> Self {
+        self.seats = seats;
+        self
+    }
+
+    /// The event cache is disabled unless configured, tests read their own writes
+    pub fn cache(mut self, cache: CacheConfig) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn maintenance(mut self, maintenance: MaintenanceConfig) -> Self {
+        self.maintenance = maintenance;
+        self
+    }
+
+    pub fn asset_urls(mut self, asset_urls: AssetUrlsConfig) -> Self {
+        self.asset_urls = asset_urls;
+        self
+    }
+
+    pub async fn build(self) -> TestResources {
+        let publisher = Arc::new(FakePublisher::default());
+        let notifier = Arc::new(FakeNotifier::default());
+        let storage = Arc::new(self.storage);
+        let geocoder = Arc::new(self.geocoder);
+        let webhooks = Arc::new(FakeWebhookSender::default());
+        let grpc_near_client: Arc<Mutex<Box<dyn NearClient>>> =
+            Arc::new(Mutex::new(Box::new(self.near_client.clone())));
+
+        let ctx = Arc::new(ResourcesContext::new(Resources {
+            db_client: connect().await,
+            grpc_near_client: grpc_near_client.clone(),
+            chain: Arc::new(NearChainClient::new(grpc_near_client)),
+            publisher: publisher.clone(),
+            notifier: notifier.clone(),
+            storage: storage.clone(),
+            asset_urls: self.asset_urls,
+            geocoder: geocoder.clone(),
+            webhooks: webhooks.clone(),
+            ipfs_client: None,
+            health: HealthConfig::default(),
+            jobs: self.jobs,
+            sessions: self.sessions,
+            graphql: self.graphql,
+            near: NearConfig::default(),
+            business: self.business,
+            totp: self.totp,
+            ticket_pdf: self.ticket_pdf,
+            body_limits: BodyLimitsConfig::default(),
+            http_cache: HttpCacheConfig::default(),
+            usernames: UsernamesConfig::default(),
+            sanitation: SanitationConfig::default(),
+            password_policy: self.password_policy,
+            login_lockout: self.login_lockout,
+            pusher_auth: PusherAuth::new(PUSHER_KEY, PUSHER_SECRET),
+            codes: Codes::new(CODES_SECRET, &[]),
+            top_ups: self.top_ups,
+            signups: self.signups,
+            stock_alerts: self.stock_alerts,
+            series: self.series,
+            seats: self.seats,
+            event_cache: Arc::new(EventCache::new(&self.cache.unwrap_or(CacheConfig {
+                event_ttl_secs: 0,
+                ..CacheConfig::default()
+            }))),
+            maintenance: Arc::new(Maintenance::new(&self.maintenance)),
+        }));
+
+        TestResources {
+            ctx,
+            near_client: self.near_client,
+            publisher,
+            notifier,
+            storage,
+            geocoder,
+            webhooks,
+        }
     }
 }
 
@@ -42,27 +269,7 @@ pub async fn create_event(db_client: &Client) -> DbEvent {
     let event_name = gen_string(20);
     let now = Local::now();
 
-    let user_id = uuid::Uuid::new_v4();
-
-    gql_api::db::sql::db_insert_user(
-        &db_client,
-        &DbUser {
-            id: user_id,
-            name: None,
-            username: gen_string(20),
-            phone_number: None,
-            email: None,
-            password: None,
-            encrypted_secret_key: None,
-            created_at: Utc::now().naive_utc(),
-            wallet_id: gen_string(20),
-            wallet_balance: "0".to_string(),
-            user_type: Role::Seller,
-            user_status: UserStatus::Unverified,
-        },
-    )
-    .await
-    .expect("unable to create user");
+    let user_id = create_user(db_client, Role::Seller).await;
 
     gql_api::db::sql::db_insert_event(
         &db_client,
@@ -83,6 +290,20 @@ pub async fn create_event(db_client: &Client) -> DbEvent {
             thumbnail_url: None,
             event_status: EventStatus::Draft,
             created_by_user: user_id,
+            cover_photo_ipfs_url: None,
+            thumbnail_ipfs_url: None,
+            archived: false,
+            deleted_at: None,
+            category_id: None,
+            thumbnail_variant_url: None,
+            cover_variant_url: None,
+            og_image_url: None,
+            organization_id: None,
+            latitude: None,
+            longitude: None,
+            cancellation_window_hours: None,
+            modified_at: now.naive_utc(),
+            series_id: None,
         },
     )
     .await
@@ -93,6 +314,43 @@ pub async fn create_event(db_client: &Client) -> DbEvent {
         .expect("unable to fetch event")
 }
 
+pub async fn create_user(db_client: &Client, user_type: Role) -> uuid::Uuid {
+    let user_id = uuid::Uuid::new_v4();
+
+    gql_api::db::sql::db_insert_user(
+        &db_client,
+        &DbUser {
+            id: user_id,
+            name: None,
+            username: gen_string(20),
+            phone_number: None,
+            email: None,
+            password: None,
+            encrypted_secret_key: None,
+            created_at: Utc::now().naive_utc(),
+            wallet_id: gen_string(20),
+            wallet_balance: "0".to_string(),
+            user_type,
+            // fixture users verified their phone, as needed by the seller operations
+            user_status: UserStatus::PhoneVerified,
+            wallet_balance_updated_at: None,
+            deleted_at: None,
+            wallet_flagged: false,
+            seller_slug: None,
+            locale: None,
+            // fixture sellers may publish and mint right away
+            seller_status: (user_type == Role::Seller).then(|| SellerStatus::Approved),
+            seller_rejection_reason: None,
+            event_reminders_opt_out: false,
+            password_changed_at: None,
+        },
+    )
+    .await
+    .expect("unable to create user");
+
+    user_id
+}
+
 pub fn gen_asset_file(bucket: impl Into<String>, event_id: uuid::Uuid) -> AssetFile {
     AssetFile {
         id: uuid::Uuid::new_v4(),
@@ -100,6 +358,13 @@ pub fn gen_asset_file(bucket: impl Into<String>, event_id: uuid::Uuid) -> AssetF
         s3_absolute_key: format!("{}/{}.{}", gen_string(10), gen_string(10), gen_string(3)),
         ipfs_hash: None,
         event_id,
+        content_type: None,
+        is_confirmed: true,
+        thumbnail_key: None,
+        cover_key: None,
+        og_image_key: None,
+        asset_role: None,
+        position: None,
     }
 }
 
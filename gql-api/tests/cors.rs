@@ -0,0 +1,70 @@
+use gql_api::{
+    config::{CorsConfig, ServerEnv},
+    filters::{with_cors, with_security_headers},
+};
+use warp::Filter;
+
+fn cors_config() -> CorsConfig {
+    CorsConfig {
+        allowed_origins: vec!["https://tickets.example.com".to_string()],
+        ..CorsConfig::default()
+    }
+}
+
+async fn preflight(
+    config: &CorsConfig,
+    origin: &str,
+) -> warp::http::Response<warp::hyper::body::Bytes> {
+    let route = warp::path("api").map(warp::reply).with(with_cors(config));
+    warp::test::request()
+        .method("OPTIONS")
+        .path("/api")
+        .header("origin", origin)
+        .header("access-control-request-method", "POST")
+        .header(
+            "access-control-request-headers",
+            "content-type, authorization",
+        )
+        .reply(&route)
+        .await
+}
+
+#[tokio::test]
+async fn test_cors_origins() {
+    let release_config = cors_config().for_env(ServerEnv::Release);
+    let response = preflight(&release_config, "https://tickets.example.com").await;
+    assert!(response.status().is_success());
+    assert_eq!(
+        Some("3600"),
+        response
+            .headers()
+            .get("access-control-max-age")
+            .and_then(|value| value.to_str().ok())
+    );
+    let response = preflight(&release_config, "https://evil.example.com").await;
+    assert!(!response.status().is_success());
+
+    // dev servers allow any origin
+    let dev_config = cors_config().for_env(ServerEnv::Dev);
+    let response = preflight(&dev_config, "http://localhost:3000").await;
+    assert!(response.status().is_success());
+}
+
+#[tokio::test]
+async fn test_security_headers() {
+    let route = warp::any()
+        .map(warp::reply)
+        .with(with_security_headers(ServerEnv::Release));
+    let response = warp::test::request().reply(&route).await;
+    assert_eq!("nosniff", response.headers()["x-content-type-options"]);
+    assert_eq!("DENY", response.headers()["x-frame-options"]);
+    assert!(response.headers().contains_key("strict-transport-security"));
+
+    // dev servers are served over plain http
+    let route = warp::any()
+        .map(warp::reply)
+        .with(with_security_headers(ServerEnv::Dev));
+    let response = warp::test::request().reply(&route).await;
+    assert_eq!("DENY", response.headers()["x-frame-options"]);
+    assert!(!response.headers().contains_key("strict-transport-security"));
+}
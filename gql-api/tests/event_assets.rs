@@ -0,0 +1,96 @@
+use bytes::Bytes;
+use futures::{stream, TryStreamExt};
+use gql_api::{
+    fakes::FakeStorage,
+    http::uploads::{asset_format, spool_asset, SpooledAsset},
+    storage::Storage,
+};
+use image::ImageFormat;
+
+const BOUNDARY: &str = "asset-boundary";
+
+/// The first bytes of a png
+const PNG: &[u8] = &[
+    0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0, 0, 0, 0x0d,
+];
+
+/// Spools the single file part of a form
+async fn spool(content_type: &str, content: &[u8], max_bytes: u64) -> Option<SpooledAsset> {
+    let mut body = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"asset\"\r\nContent-Type: {}\r\n\r\n",
+        BOUNDARY, content_type
+    )
+    .into_bytes();
+    body.extend_from_slice(content);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+
+    let mut form = warp::test::request()
+        .method("POST")
+        .header(
+            "content-type",
+            format!("multipart/form-data; boundary={}", BOUNDARY),
+        )
+        .body(body)
+        .filter(&warp::multipart::form())
+        .await
+        .expect("a form");
+    let part = form
+        .try_next()
+        .await
+        .expect("a valid form")
+        .expect("a part");
+    spool_asset(part, max_bytes).await.ok()
+}
+
+#[test]
+fn test_asset_format() {
+    assert_eq!(Some(("png", ImageFormat::Png)), asset_format("image/png"));
+    assert_eq!(Some(("jpg", ImageFormat::Jpeg)), asset_format("image/jpeg"));
+    assert_eq!(
+        Some(("webp", ImageFormat::WebP)),
+        asset_format("image/webp")
+    );
+    assert_eq!(None, asset_format("image/svg+xml"));
+    assert_eq!(None, asset_format("application/pdf"));
+}
+
+#[tokio::test]
+async fn test_spool_asset() {
+    let spooled = spool("image/png", PNG, 1024).await.expect("a png");
+    assert_eq!("png", spooled.extension);
+    assert_eq!(PNG.len() as u64, spooled.size);
+
+    let storage = FakeStorage::default();
+    let content = spooled.stream().await.expect("a stream");
+    storage
+        .put_stream("events/asset.png", "image/png", spooled.size, content)
+        .await
+        .expect("stored");
+    let stored = storage.object("events/asset.png").expect("an object");
+    assert_eq!(PNG, stored.content.as_slice());
+    assert_eq!(Some("image/png".to_string()), stored.content_type);
+
+    // too large
+    assert!(spool("image/png", PNG, 4).await.is_none());
+    // empty
+    assert!(spool("image/png", &[], 1024).await.is_none());
+    // the content is not the image its content type names
+    assert!(spool("image/jpeg", PNG, 1024).await.is_none());
+    assert!(spool("image/png", b"not an image", 1024).await.is_none());
+    assert!(spool("text/plain", b"text", 1024).await.is_none());
+}
+
+#[tokio::test]
+async fn test_put_stream_length() {
+    let storage = FakeStorage::default();
+    let content = Box::pin(stream::iter(vec![
+        Ok(Bytes::from_static(b"abc")),
+        Ok(Bytes::from_static(b"def")),
+    ]));
+    // the announced length must match the streamed one
+    assert!(storage
+        .put_stream("events/asset.png", "image/png", 5, content)
+        .await
+        .is_err());
+    assert!(storage.object("events/asset.png").is_none());
+}
@@ -0,0 +1,160 @@
+use gql_api::{
+    auth::Role,
+    db::{
+        models::{DbEvent, DbTicket},
+        sql::{db_insert_event, db_insert_ticket},
+    },
+    gql::{
+        models::{EventStatus, NewTicket},
+        mutations::PrivateMutationRoot,
+        quiries::PrivateQueryRoot,
+        schema::{Context as ResourcesContext, PrivateSchema},
+        subscriptions::PrivateSubscriptionRoot,
+    },
+};
+use juniper::GraphQLError;
+
+mod common;
+
+const EVENT_QUERY: &str = r#"query($id: Uuid!) { event(id: $id) {
+    id eventName eventStatus
+    tickets { id ticketName }
+    reservations { id }
+    mintJobs { id }
+    assets { id }
+    collaborators { userId }
+} }"#;
+
+/// The event, as resolved for the caller, along with the paths of the fields that errored
+async fn event(ctx: &ResourcesContext, id: &uuid::Uuid) -> (serde_json::Value, Vec<String>) {
+    let schema = PrivateSchema::new(
+        PrivateQueryRoot,
+        PrivateMutationRoot,
+        PrivateSubscriptionRoot,
+    );
+    let mut variables = juniper::Variables::new();
+    variables.insert(
+        "id".to_string(),
+        juniper::InputValue::scalar(id.to_string()),
+    );
+    let (value, errors) = juniper::execute(EVENT_QUERY, None, &schema, &variables, ctx)
+        .await
+        .expect("invalid query");
+    let value = serde_json::to_value(&value).expect("serializable value");
+    let errors = errors
+        .iter()
+        .map(|error| error.error().message().to_string())
+        .collect();
+    (value["event"].clone(), errors)
+}
+
+async fn insert_event(
+    db_client: &tokio_postgres::Client,
+    seller: uuid::Uuid,
+    event_status: EventStatus,
+) -> DbEvent {
+    let db_event = DbEvent {
+        event_status,
+        ..DbEvent::new(&common::gen_string(20), seller)
+    };
+    db_insert_event(db_client, &db_event)
+        .await
+        .expect("failed to insert event");
+    let db_ticket = DbTicket::new(
+        NewTicket {
+            ticket_name: common::gen_string(10),
+            description: None,
+            price: None,
+            max_release_price: None,
+            quantity_available: Some(10),
+            min_purchase_quantity: None,
+            max_purchase_quantity: None,
+            allow_transfers: None,
+            currency: None,
+            event_id: db_event.id,
+        },
+        &db_event,
+    );
+    db_insert_ticket(db_client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+    db_event
+}
+
+#[tokio::test]
+async fn test_event_details() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+    let seller = common::create_user(db_client, Role::Seller).await;
+    let buyer = common::create_user(db_client, Role::Buyer).await;
+    let seller_ctx = resources.ctx.for_user(Some(seller));
+    let buyer_ctx = resources.ctx.for_user(Some(buyer));
+
+    // the seller sees every field of their draft, others do not see it at all
+    let draft = insert_event(db_client, seller, EventStatus::Draft).await;
+    let (details, errors) = event(&seller_ctx, &draft.id).await;
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(draft.id.to_string(), details["id"]);
+    assert_eq!("DRAFT", details["eventStatus"]);
+    assert_eq!(1, details["tickets"].as_array().expect("tickets").len());
+    assert!(details["reservations"].is_array());
+    assert!(details["mintJobs"].is_array());
+    assert!(details["assets"].is_array());
+    assert!(details["collaborators"].is_array());
+    let (details, errors) = event(&buyer_ctx, &draft.id).await;
+    assert!(details.is_null());
+    assert_eq!(1, errors.len());
+
+    // so are events waiting for their review
+    let pending_review = insert_event(db_client, seller, EventStatus::PendingReview).await;
+    let (details, errors) = event(&seller_ctx, &pending_review.id).await;
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!("PENDING_REVIEW", details["eventStatus"]);
+    let (details, errors) = event(&buyer_ctx, &pending_review.id).await;
+    assert!(details.is_null());
+    assert_eq!(1, errors.len());
+
+    // published events are seen by all, the fields reserved to their sellers are not
+    let published = insert_event(db_client, seller, EventStatus::Final).await;
+    let (details, errors) = event(&buyer_ctx, &published.id).await;
+    assert_eq!(published.event_name, details["eventName"]);
+    assert_eq!(1, details["tickets"].as_array().expect("tickets").len());
+    assert!(details["reservations"].is_null());
+    assert!(details["mintJobs"].is_null());
+    assert!(details["assets"].is_null());
+    assert!(details["collaborators"].is_null());
+    assert_eq!(4, errors.len());
+
+    let (details, errors) = event(&buyer_ctx, &uuid::Uuid::new_v4()).await;
+    assert!(details.is_null());
+    assert_eq!(1, errors.len());
+}
+
+#[tokio::test]
+async fn test_invalid_event_id() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let schema = PrivateSchema::new(
+        PrivateQueryRoot,
+        PrivateMutationRoot,
+        PrivateSubscriptionRoot,
+    );
+
+    // ids are parsed before any resolver runs, the error names the argument
+    let error = juniper::execute(
+        r#"query { event(id: "not-an-id") { id } }"#,
+        None,
+        &schema,
+        &juniper::Variables::new(),
+        &resources.ctx,
+    )
+    .await
+    .expect_err("an invalid id");
+    match error {
+        GraphQLError::ValidationError(errors) => {
+            assert_eq!(1, errors.len());
+            assert!(errors[0].message().contains(r#"argument "id""#));
+            assert!(errors[0].message().contains("Uuid"));
+        }
+        error => panic!("unexpected error: {:?}", error),
+    }
+}
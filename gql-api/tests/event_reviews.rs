@@ -0,0 +1,206 @@
+use gql_api::{
+    auth::Role,
+    db::{
+        models::DbEvent,
+        sql::{
+            db_get_events, db_get_events_pending_review, db_get_notifications_by_user_id,
+            db_insert_event, db_review_event, db_submit_event_for_review, sql_timestamp,
+            EventsFilter,
+        },
+    },
+    gql::{
+        models::{EventStatus, NotificationKind},
+        mutations::AdminMutationRoot,
+        quiries::AdminQueryRoot,
+        schema::{AdminSchema, Context as ResourcesContext},
+    },
+};
+use juniper::EmptySubscription;
+
+mod common;
+
+/// The response of the admin operation, none if it failed
+async fn execute_admin(ctx: &ResourcesContext, operation: &str) -> Option<serde_json::Value> {
+    let schema = AdminSchema::new(AdminQueryRoot, AdminMutationRoot, EmptySubscription::new());
+    let (value, errors) =
+        juniper::execute(operation, None, &schema, &juniper::Variables::new(), ctx)
+            .await
+            .expect("invalid operation");
+    if !errors.is_empty() {
+        return None;
+    }
+    Some(serde_json::to_value(&value).expect("serializable value"))
+}
+
+async fn insert_minted_event(db_client: &tokio_postgres::Client, seller: uuid::Uuid) -> DbEvent {
+    let db_event = DbEvent {
+        event_status: EventStatus::Minting,
+        ..DbEvent::new(&common::gen_string(20), seller)
+    };
+    db_insert_event(db_client, &db_event)
+        .await
+        .expect("failed to insert event");
+    db_event
+}
+
+async fn is_public(db_client: &tokio_postgres::Client, db_event: &DbEvent) -> bool {
+    let db_events = db_get_events(
+        db_client,
+        &EventsFilter {
+            id: Some(db_event.id),
+            statuses: vec![EventStatus::Final],
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to get events");
+    !db_events.is_empty()
+}
+
+async fn notifications(
+    db_client: &tokio_postgres::Client,
+    user_id: &uuid::Uuid,
+) -> Vec<NotificationKind> {
+    db_get_notifications_by_user_id(db_client, user_id, false, 10, 0)
+        .await
+        .expect("failed to get notifications")
+        .into_iter()
+        .map(|db_notification| db_notification.kind)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_submit_event_for_review() {
+    let cfg = common::setup().await;
+    let seller = common::create_user(&cfg.client, Role::Seller).await;
+    let db_event = insert_minted_event(&cfg.client, seller).await;
+
+    let submitted = db_submit_event_for_review(&cfg.client, &db_event.id, &sql_timestamp(None))
+        .await
+        .expect("failed to submit event")
+        .expect("minted events should be submittable");
+    assert_eq!(EventStatus::PendingReview, submitted.event_status);
+    assert!(submitted.review_submitted_at.is_some());
+    assert!(!is_public(&cfg.client, &db_event).await);
+    assert!(db_get_events_pending_review(&cfg.client)
+        .await
+        .expect("failed to get events")
+        .iter()
+        .any(|pending| pending.id == db_event.id));
+    // once
+    assert!(
+        db_submit_event_for_review(&cfg.client, &db_event.id, &sql_timestamp(None))
+            .await
+            .expect("failed to submit event")
+            .is_none()
+    );
+
+    // rejected events go back to the seller with the reason, until resubmitted
+    let rejected = db_review_event(
+        &cfg.client,
+        &db_event.id,
+        EventStatus::Minting,
+        Some("Missing venue"),
+        &[],
+    )
+    .await
+    .expect("failed to review event")
+    .expect("event should be pending review");
+    assert_eq!(EventStatus::Minting, rejected.event_status);
+    assert_eq!(
+        Some("Missing venue".to_string()),
+        rejected.review_rejection_reason
+    );
+    let resubmitted = db_submit_event_for_review(&cfg.client, &db_event.id, &sql_timestamp(None))
+        .await
+        .expect("failed to submit event")
+        .expect("rejected events should be submittable");
+    assert_eq!(None, resubmitted.review_rejection_reason);
+
+    let approved = db_review_event(&cfg.client, &db_event.id, EventStatus::Final, None, &[])
+        .await
+        .expect("failed to review event")
+        .expect("event should be pending review");
+    assert_eq!(EventStatus::Final, approved.event_status);
+    assert!(is_public(&cfg.client, &db_event).await);
+    // reviewed once
+    assert!(
+        db_review_event(&cfg.client, &db_event.id, EventStatus::Final, None, &[])
+            .await
+            .expect("failed to review event")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_review_events() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+    let seller = common::create_user(db_client, Role::Seller).await;
+    let admin = common::create_user(db_client, Role::Admin).await;
+    let admin_ctx = resources.ctx.for_user(Some(admin));
+    let seller_ctx = resources.ctx.for_user(Some(seller));
+
+    let rejected = insert_minted_event(db_client, seller).await;
+    let approved = insert_minted_event(db_client, seller).await;
+    for db_event in [&rejected, &approved] {
+        db_submit_event_for_review(db_client, &db_event.id, &sql_timestamp(None))
+            .await
+            .expect("failed to submit event")
+            .expect("minted events should be submittable");
+    }
+
+    let pending = execute_admin(
+        &admin_ctx,
+        "query { eventsPendingReview { id eventStatus } }",
+    )
+    .await
+    .expect("events pending review");
+    let pending = pending["eventsPendingReview"]
+        .as_array()
+        .expect("events")
+        .clone();
+    assert!(pending
+        .iter()
+        .any(|event| event["id"] == rejected.id.to_string()
+            && event["eventStatus"] == "pending_review"));
+    // admins only
+    assert!(
+        execute_admin(&seller_ctx, "query { eventsPendingReview { id } }")
+            .await
+            .is_none()
+    );
+
+    // a reason is required
+    let reject = |reason: &str| {
+        format!(
+            r#"mutation {{ rejectEvent(id: "{}", reason: "{}") {{ eventStatus reviewRejectionReason }} }}"#,
+            rejected.id, reason
+        )
+    };
+    assert!(execute_admin(&admin_ctx, &reject("  ")).await.is_none());
+    let value = execute_admin(&admin_ctx, &reject(" Missing venue "))
+        .await
+        .expect("a rejected event");
+    assert_eq!("minting", value["rejectEvent"]["eventStatus"]);
+    assert_eq!(
+        "Missing venue",
+        value["rejectEvent"]["reviewRejectionReason"]
+    );
+    assert!(!is_public(db_client, &rejected).await);
+
+    let approve = format!(
+        r#"mutation {{ approveEvent(id: "{}") {{ eventStatus }} }}"#,
+        approved.id
+    );
+    let value = execute_admin(&admin_ctx, &approve)
+        .await
+        .expect("an approved event");
+    assert_eq!("final", value["approveEvent"]["eventStatus"]);
+    assert!(is_public(db_client, &approved).await);
+    assert!(execute_admin(&admin_ctx, &approve).await.is_none());
+
+    let kinds = notifications(db_client, &seller).await;
+    assert!(kinds.contains(&NotificationKind::EventApproved));
+    assert!(kinds.contains(&NotificationKind::EventRejected));
+}
@@ -0,0 +1,250 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, Weekday};
+use gql_api::{
+    db::{
+        models::{DbEvent, DbEventSeries},
+        sql::{
+            db_get_event_series_by_id, db_get_events_by_series_id, db_insert_event_series,
+            db_insert_event_with_tickets,
+        },
+    },
+    gql::{
+        clone::{clone_event, occurrence_event_name, occurrence_overrides},
+        models::EventStatus,
+    },
+    recurrence::{Frequency, Recurrence, RecurrenceError},
+};
+
+mod common;
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd(year, month, day).and_hms(20, 0, 0)
+}
+
+fn occurrences(rule: &str, start: NaiveDateTime, max: usize) -> Vec<NaiveDateTime> {
+    rule.parse::<Recurrence>()
+        .expect("rule should be valid")
+        .occurrences(start, max)
+}
+
+#[test]
+fn test_parse_recurrence() {
+    assert_eq!(
+        Ok(Recurrence {
+            frequency: Frequency::Weekly,
+            interval: 2,
+            count: Some(4),
+            until: None,
+            by_day: vec![Weekday::Mon, Weekday::Fri],
+        }),
+        "RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=FR,MO;COUNT=4".parse::<Recurrence>()
+    );
+    assert_eq!(
+        Ok(Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: Some(NaiveDate::from_ymd(2022, 6, 7).and_hms(12, 0, 0)),
+            by_day: vec![],
+        }),
+        "freq=daily;until=20220607T120000Z".parse::<Recurrence>()
+    );
+
+    let error = |rule: &str| {
+        rule.parse::<Recurrence>()
+            .expect_err("rule should be invalid")
+    };
+    assert_eq!(RecurrenceError::MissingFrequency, error(""));
+    assert_eq!(RecurrenceError::MissingFrequency, error("COUNT=2"));
+    assert_eq!(
+        RecurrenceError::UnsupportedFrequency("YEARLY".to_string()),
+        error("FREQ=YEARLY")
+    );
+    assert_eq!(
+        RecurrenceError::UnsupportedPart("BYSETPOS".to_string()),
+        error("FREQ=MONTHLY;BYSETPOS=1")
+    );
+    assert_eq!(
+        RecurrenceError::MalformedPart("COUNT".to_string()),
+        error("FREQ=DAILY;COUNT")
+    );
+    assert_eq!(
+        RecurrenceError::DuplicatePart("FREQ".to_string()),
+        error("FREQ=DAILY;FREQ=WEEKLY")
+    );
+    assert_eq!(
+        RecurrenceError::InvalidValue("COUNT".to_string()),
+        error("FREQ=DAILY;COUNT=0")
+    );
+    assert_eq!(
+        RecurrenceError::InvalidValue("INTERVAL".to_string()),
+        error("FREQ=DAILY;INTERVAL=-1")
+    );
+    assert_eq!(
+        RecurrenceError::InvalidValue("UNTIL".to_string()),
+        error("FREQ=DAILY;UNTIL=2022-06-07")
+    );
+    // ordinal weekdays are not supported
+    assert_eq!(
+        RecurrenceError::InvalidValue("BYDAY".to_string()),
+        error("FREQ=WEEKLY;BYDAY=1MO")
+    );
+    assert_eq!(
+        RecurrenceError::CountAndUntil,
+        error("FREQ=DAILY;COUNT=2;UNTIL=20220607")
+    );
+    assert_eq!(
+        RecurrenceError::ByDayNotWeekly,
+        error("FREQ=MONTHLY;BYDAY=MO")
+    );
+}
+
+#[test]
+fn test_weekly_occurrences() {
+    // a wednesday, counted although not one of the weekdays
+    let start = date(2022, 6, 1);
+    assert_eq!(
+        vec![start, date(2022, 6, 3), date(2022, 6, 6), date(2022, 6, 10)],
+        occurrences("FREQ=WEEKLY;BYDAY=MO,FR;COUNT=4", start, 52)
+    );
+    assert_eq!(
+        vec![start, date(2022, 6, 15), date(2022, 6, 29)],
+        occurrences("FREQ=WEEKLY;INTERVAL=2;COUNT=3", start, 52)
+    );
+    assert_eq!(
+        vec![start, date(2022, 6, 13), date(2022, 6, 15)],
+        occurrences(
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;UNTIL=20220626",
+            start,
+            52
+        )
+    );
+}
+
+#[test]
+fn test_daily_occurrences_until() {
+    let start = date(2022, 6, 1);
+    // a date includes its whole day
+    assert_eq!(
+        vec![start, date(2022, 6, 4), date(2022, 6, 7)],
+        occurrences("FREQ=DAILY;INTERVAL=3;UNTIL=20220607", start, 52)
+    );
+    assert_eq!(
+        vec![start, date(2022, 6, 4)],
+        occurrences("FREQ=DAILY;INTERVAL=3;UNTIL=20220607T120000Z", start, 52)
+    );
+    assert!(occurrences("FREQ=DAILY;UNTIL=20220531", start, 52).is_empty());
+}
+
+#[test]
+fn test_monthly_occurrences() {
+    // the months without a 31st are skipped
+    let start = date(2022, 1, 31);
+    assert_eq!(
+        vec![start, date(2022, 3, 31), date(2022, 5, 31)],
+        occurrences("FREQ=MONTHLY;COUNT=3", start, 52)
+    );
+    assert_eq!(
+        vec![start, date(2022, 7, 31), date(2023, 1, 31)],
+        occurrences("FREQ=MONTHLY;INTERVAL=6;UNTIL=20230131", start, 52)
+    );
+}
+
+#[test]
+fn test_occurrences_capped() {
+    let start = date(2022, 6, 1);
+    assert_eq!(5, occurrences("FREQ=DAILY", start, 5).len());
+    assert_eq!(3, occurrences("FREQ=DAILY;COUNT=10", start, 3).len());
+    assert!(occurrences("FREQ=DAILY;COUNT=10", start, 0).is_empty());
+}
+
+#[test]
+fn test_occurrence_overrides() {
+    let mut source = DbEvent::new("Jazz Night", uuid::Uuid::new_v4());
+    source.start_date = Some(date(2022, 6, 1));
+    source.end_date = Some(date(2022, 6, 1) + Duration::hours(3));
+    source.entry_time = Some(date(2022, 6, 1) - Duration::minutes(30));
+
+    let start_date = date(2022, 6, 8);
+    let overrides = occurrence_overrides(&source, start_date);
+    assert_eq!(None, overrides.event_name);
+    assert_eq!(Some(start_date), overrides.start_date);
+    assert_eq!(Some(start_date + Duration::hours(3)), overrides.end_date);
+    assert_eq!(
+        Some(start_date - Duration::minutes(30)),
+        overrides.entry_time
+    );
+    assert_eq!(
+        "Jazz Night (2022-06-08)",
+        occurrence_event_name(&source.event_name, &start_date)
+    );
+}
+
+#[tokio::test]
+async fn test_insert_event_series() {
+    let cfg = common::setup().await;
+    let start_date = cfg
+        .event
+        .start_date
+        .expect("event should have a start date");
+
+    let db_series = DbEventSeries::new(
+        &cfg.event.event_name,
+        "FREQ=WEEKLY;COUNT=2",
+        start_date,
+        cfg.event.created_by_user,
+    );
+    let source = db_insert_event_series(&cfg.client, &db_series, &cfg.event.id)
+        .await
+        .expect("failed to insert event series")
+        .expect("event should not be in a series");
+    assert_eq!(Some(db_series.id), source.series_id);
+
+    // an event is an occurrence of a single series
+    let other = DbEventSeries::new(
+        &cfg.event.event_name,
+        "FREQ=DAILY;COUNT=2",
+        start_date,
+        cfg.event.created_by_user,
+    );
+    assert!(db_insert_event_series(&cfg.client, &other, &cfg.event.id)
+        .await
+        .expect("failed to insert event series")
+        .is_none());
+    assert!(db_get_event_series_by_id(&cfg.client, &other.id)
+        .await
+        .expect("failed to get event series")
+        .is_none());
+
+    let occurrence_start_date = start_date + Duration::weeks(1);
+    let (mut db_event, db_tickets) = clone_event(
+        &source,
+        &[],
+        &occurrence_event_name(&common::gen_string(12), &occurrence_start_date),
+        occurrence_overrides(&source, occurrence_start_date),
+        cfg.event.created_by_user,
+    )
+    .expect("failed to clone event");
+    db_event.series_id = Some(db_series.id);
+    db_insert_event_with_tickets(&cfg.client, &db_event, &db_tickets)
+        .await
+        .expect("failed to insert occurrence");
+
+    let stored = db_get_event_series_by_id(&cfg.client, &db_series.id)
+        .await
+        .expect("failed to get event series")
+        .expect("event series should exist");
+    assert_eq!(db_series.rrule, stored.rrule);
+    let occurrences = db_get_events_by_series_id(&cfg.client, &db_series.id, &[])
+        .await
+        .expect("failed to get occurrences");
+    assert_eq!(
+        vec![source.id, db_event.id],
+        occurrences.iter().map(|event| event.id).collect::<Vec<_>>()
+    );
+    assert!(
+        db_get_events_by_series_id(&cfg.client, &db_series.id, &[EventStatus::Final])
+            .await
+            .expect("failed to get occurrences")
+            .is_empty()
+    );
+}
@@ -0,0 +1,111 @@
+use chrono::Duration;
+use gql_api::{
+    db::{models::DbEvent, sql::EventsFilter},
+    gql::models::{EventFilter, EventStatus},
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_events_filters_combine() {
+    let cfg = common::setup().await;
+
+    // a featured, published copy of the setup event by the same seller
+    let event_name = common::gen_string(20);
+    gql_api::db::sql::db_insert_event(
+        &cfg.client,
+        &DbEvent {
+            id: uuid::Uuid::new_v4(),
+            event_name: event_name.clone(),
+            event_slug: common::gen_string(20),
+            is_featured: Some(true),
+            event_status: EventStatus::Final,
+            ..cfg.event.clone()
+        },
+    )
+    .await
+    .expect("unable to create event");
+    let published = gql_api::db::sql::db_get_event_by_name(&cfg.client, &event_name)
+        .await
+        .expect("unable to fetch event");
+
+    // the featured flag and the slug are bound to separate parameters
+    let listed = gql_api::db::sql::db_get_events(
+        &cfg.client,
+        &EventsFilter {
+            event_slug: Some(published.event_slug.clone()),
+            filter: Some(EventFilter::Featured),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to list events");
+    assert_eq!(1, listed.len());
+    assert_eq!(published.id, listed[0].id);
+
+    let listed = gql_api::db::sql::db_get_events(
+        &cfg.client,
+        &EventsFilter {
+            event_slug: Some(published.event_slug.clone()),
+            filter: Some(EventFilter::NoneFeatured),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to list events");
+    assert!(listed.is_empty());
+
+    // drafts are filtered out by status
+    let listed = gql_api::db::sql::db_get_events(
+        &cfg.client,
+        &EventsFilter {
+            created_by_user: Some(cfg.event.created_by_user),
+            statuses: vec![EventStatus::Final],
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to list events");
+    assert_eq!(
+        vec![published.id],
+        listed.iter().map(|event| event.id).collect::<Vec<_>>()
+    );
+
+    let listed = gql_api::db::sql::db_get_events(
+        &cfg.client,
+        &EventsFilter {
+            created_by_user: Some(cfg.event.created_by_user),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to list events");
+    assert_eq!(2, listed.len());
+
+    // the start date window is half open
+    let start_date = published.start_date.expect("event has a start date");
+    let listed = gql_api::db::sql::db_get_events(
+        &cfg.client,
+        &EventsFilter {
+            id: Some(published.id),
+            starts_after: Some(start_date),
+            starts_before: Some(start_date + Duration::days(1)),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to list events");
+    assert_eq!(1, listed.len());
+
+    let listed = gql_api::db::sql::db_get_events(
+        &cfg.client,
+        &EventsFilter {
+            id: Some(published.id),
+            starts_before: Some(start_date),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to list events");
+    assert!(listed.is_empty());
+}
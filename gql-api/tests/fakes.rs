@@ -0,0 +1,98 @@
+use bytes::Bytes;
+use common::TestContextBuilder;
+use gql_api::{
+    fakes::{FakeNearClient, StoredObject},
+    http::{handlers::check_username, models::CheckUsernameResponse},
+    jobs::models::{PusherChannel, PusherEvent},
+    notifier::Notification,
+};
+use std::time::Duration;
+use warp::Reply;
+
+mod common;
+
+async fn check_username_available(near_client: FakeNearClient) -> (bool, Vec<String>) {
+    let resources = TestContextBuilder::new()
+        .near_client(near_client)
+        .build()
+        .await;
+
+    let body = serde_json::json!({ "username": common::gen_string(20) }).to_string();
+    let response = check_username(resources.ctx.clone(), Bytes::from(body))
+        .await
+        .expect("check_username failed")
+        .into_response();
+    let body = warp::hyper::body::to_bytes(response.into_body())
+        .await
+        .expect("unreadable response body");
+    let response: CheckUsernameResponse =
+        serde_json::from_slice(&body).expect("unparsable response");
+
+    (response.available, resources.near_client.calls())
+}
+
+#[tokio::test]
+async fn test_check_username_with_fake_near_client() {
+    let (available, calls) = check_username_available(FakeNearClient::default()).await;
+    assert!(available);
+    assert_eq!(vec!["check_available_account_id".to_string()], calls);
+
+    let (available, _) = check_username_available(FakeNearClient {
+        is_available: false,
+        ..Default::default()
+    })
+    .await;
+    assert!(!available);
+}
+
+#[tokio::test]
+async fn test_fakes_record_side_effects() {
+    let resources = TestContextBuilder::new().build().await;
+
+    resources
+        .ctx
+        .publisher
+        .publish(PusherChannel::Account, PusherEvent::LoggedIn, "{}")
+        .await
+        .expect("publish failed");
+    assert_eq!(1, resources.publisher.published().len());
+
+    let notification = Notification::Verification {
+        code: common::gen_string(6),
+    };
+    notification
+        .send(resources.ctx.notifier.as_ref(), "+359888123456", Some("bg"))
+        .await
+        .expect("send failed");
+    assert_eq!(
+        vec![("+359888123456".to_string(), notification)],
+        resources.notifier.sent()
+    );
+    assert_eq!(
+        vec![Some("bg".to_string())],
+        resources.notifier.sent_locales()
+    );
+
+    let key = format!("exports/{}.csv", common::gen_string(10));
+    assert_eq!(Ok(false), resources.ctx.storage.exists(&key).await);
+    resources
+        .ctx
+        .storage
+        .put(&key, "text/csv", b"a,b".to_vec())
+        .await
+        .expect("put failed");
+    assert_eq!(
+        Some(StoredObject {
+            content_type: Some("text/csv".to_string()),
+            content: b"a,b".to_vec(),
+        }),
+        resources.storage.object(&key)
+    );
+    assert!(resources
+        .ctx
+        .storage
+        .presigned_get_url(&key, Duration::from_secs(60))
+        .await
+        .expect("presigning failed")
+        .contains(&key));
+}
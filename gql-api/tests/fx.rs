@@ -0,0 +1,35 @@
+use gql_api::{
+    fx::{self, Currency},
+    near::NearAmount,
+};
+use std::{collections::HashMap, time::Duration};
+
+// a single test, the rates are a process wide cache
+#[test]
+fn test_fx_rates() {
+    let amount = NearAmount::from_near("2.5").unwrap();
+    assert_eq!(Some(1.0), fx::rate(Currency::Near));
+    assert_eq!(Some("2.5".to_string()), fx::convert(amount, Currency::Near));
+    assert_eq!(None, fx::convert(amount, Currency::Usd));
+
+    let rates = HashMap::from([(Currency::Usd, 3.14)]);
+    fx::update_rates(&rates, None);
+    assert_eq!(Some("7.85".to_string()), fx::convert(amount, Currency::Usd));
+    assert_eq!(None, fx::convert(amount, Currency::Eur));
+
+    // expired rates are not displayed
+    let rates = HashMap::from([(Currency::Eur, 2.9)]);
+    fx::update_rates(&rates, Some(Duration::ZERO));
+    assert_eq!(None, fx::rate(Currency::Eur));
+}
+
+#[test]
+fn test_currency_roundtrip() {
+    for currency in Currency::ALL {
+        assert_eq!(
+            currency,
+            Currency::try_from(i16::from(currency)).expect("valid currency")
+        );
+    }
+    assert!(Currency::try_from(42).is_err());
+}
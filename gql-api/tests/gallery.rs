@@ -0,0 +1,137 @@
+use gql_api::{
+    db::{
+        models::AssetFile,
+        sql::{
+            db_add_gallery_asset, db_get_gallery_assets_by_event_ids, db_remove_gallery_asset,
+            db_reorder_gallery_assets, insert_asset_file,
+        },
+    },
+    fakes::FakeStorage,
+    gql::{
+        models::{AssetRole, GalleryImage},
+        validations::check_gallery_order,
+    },
+    storage::Storage,
+};
+
+mod common;
+use crate::common::gen_asset_file;
+
+async fn gallery_ids(db_client: &tokio_postgres::Client, event_id: uuid::Uuid) -> Vec<uuid::Uuid> {
+    db_get_gallery_assets_by_event_ids(db_client, &[event_id])
+        .await
+        .expect("failed to get gallery")
+        .iter()
+        .map(|asset_file| asset_file.id)
+        .collect()
+}
+
+#[test]
+fn test_gallery_image() {
+    let storage = FakeStorage::default();
+    let asset_file = gen_asset_file(storage.bucket(), uuid::Uuid::new_v4());
+    // an asset outside of the gallery is no gallery image
+    assert!(GalleryImage::new(&asset_file, &storage).is_none());
+    let cover_photo = AssetFile {
+        asset_role: Some(AssetRole::CoverPhoto),
+        ..asset_file.clone()
+    };
+    assert!(GalleryImage::new(&cover_photo, &storage).is_none());
+
+    let gallery_asset = AssetFile {
+        asset_role: Some(AssetRole::Gallery),
+        position: Some(3),
+        ..asset_file
+    };
+    let image = GalleryImage::new(&gallery_asset, &storage).expect("a gallery image");
+    assert_eq!(gallery_asset.id, image.asset_id);
+    assert_eq!(3, image.position);
+    assert_eq!(
+        storage.asset_url(gallery_asset.s3_absolute_key.clone()),
+        image.url
+    );
+}
+
+#[test]
+fn test_gallery_order() {
+    let first = uuid::Uuid::new_v4();
+    let second = uuid::Uuid::new_v4();
+    let gallery = vec![first, second];
+
+    assert_eq!(
+        vec![second, first],
+        check_gallery_order(&gallery, &[second, first]).expect("a valid order")
+    );
+    // every image, each once
+    assert!(check_gallery_order(&gallery, &[first]).is_err());
+    assert!(check_gallery_order(&gallery, &[first, first]).is_err());
+    assert!(check_gallery_order(&gallery, &[first, uuid::Uuid::new_v4()]).is_err());
+}
+
+#[tokio::test]
+async fn test_gallery() {
+    let cfg = common::setup().await;
+
+    let mut asset_ids = vec![];
+    for _ in 0..3 {
+        let asset_file = AssetFile {
+            is_confirmed: false,
+            ..gen_asset_file("gallery", cfg.event.id)
+        };
+        insert_asset_file(&cfg.client, &asset_file)
+            .await
+            .expect("failed to insert asset file");
+        let gallery_asset = db_add_gallery_asset(&cfg.client, &asset_file.id, &cfg.event.id)
+            .await
+            .expect("failed to add gallery image")
+            .expect("asset should join the gallery");
+        assert!(gallery_asset.is_confirmed);
+        assert_eq!(Some(AssetRole::Gallery), gallery_asset.asset_role);
+        assert_eq!(Some(asset_ids.len() as i32 + 1), gallery_asset.position);
+        asset_ids.push(asset_file.id);
+    }
+    assert_eq!(asset_ids, gallery_ids(&cfg.client, cfg.event.id).await);
+
+    // an asset joins the gallery once
+    assert!(
+        db_add_gallery_asset(&cfg.client, &asset_ids[0], &cfg.event.id)
+            .await
+            .expect("failed to add gallery image")
+            .is_none()
+    );
+
+    // the images after a removed one move up
+    let removed = db_remove_gallery_asset(&cfg.client, &asset_ids[0], &cfg.event.id)
+        .await
+        .expect("failed to remove gallery image")
+        .expect("asset should leave the gallery");
+    assert_eq!(None, removed.asset_role);
+    assert_eq!(None, removed.position);
+    let gallery = db_get_gallery_assets_by_event_ids(&cfg.client, &[cfg.event.id])
+        .await
+        .expect("failed to get gallery");
+    assert_eq!(
+        vec![(asset_ids[1], Some(1)), (asset_ids[2], Some(2))],
+        gallery
+            .iter()
+            .map(|asset_file| (asset_file.id, asset_file.position))
+            .collect::<Vec<_>>()
+    );
+    assert!(
+        db_remove_gallery_asset(&cfg.client, &asset_ids[0], &cfg.event.id)
+            .await
+            .expect("failed to remove gallery image")
+            .is_none()
+    );
+
+    assert_eq!(
+        2,
+        db_reorder_gallery_assets(&cfg.client, &cfg.event.id, &[asset_ids[2], asset_ids[1]])
+            .await
+            .expect("failed to reorder gallery")
+    );
+    assert_eq!(
+        vec![asset_ids[2], asset_ids[1]],
+        gallery_ids(&cfg.client, cfg.event.id).await
+    );
+}
@@ -0,0 +1,121 @@
+use gql_api::{
+    db::{
+        models::DbEvent,
+        sql::{db_get_events_near, db_insert_event},
+    },
+    fakes::FakeGeocoder,
+    geo::{distance_km, geocode_venue, Coordinates},
+    gql::{models::EventStatus, validations::check_nearby_payload},
+};
+use rand::Rng;
+
+mod common;
+
+fn coordinates(latitude: f64, longitude: f64) -> Coordinates {
+    Coordinates::new(latitude, longitude).expect("valid coordinates")
+}
+
+#[test]
+fn test_coordinates() {
+    assert!(Coordinates::new(90.0, -180.0).is_some());
+    assert!(Coordinates::new(90.1, 0.0).is_none());
+    assert!(Coordinates::new(0.0, 180.1).is_none());
+    assert!(Coordinates::new(f64::NAN, 0.0).is_none());
+    // both columns or nothing
+    assert!(Coordinates::from_columns(Some(42.0), None).is_none());
+    assert_eq!(
+        Some(coordinates(42.0, 23.0)),
+        Coordinates::from_columns(Some(42.0), Some(23.0))
+    );
+}
+
+#[test]
+fn test_distance_km() {
+    let sofia = coordinates(42.6977, 23.3219);
+    let plovdiv = coordinates(42.1354, 24.7453);
+    assert_eq!(0.0, distance_km(&sofia, &sofia));
+    let distance = distance_km(&sofia, &plovdiv);
+    assert!((distance - 132.5).abs() < 1.0, "{}", distance);
+    assert_eq!(distance, distance_km(&plovdiv, &sofia));
+    // half way around the earth
+    let antipode = distance_km(&coordinates(0.0, 0.0), &coordinates(0.0, 180.0));
+    assert!((antipode - 20015.1).abs() < 1.0, "{}", antipode);
+}
+
+#[test]
+fn test_nearby_payload() {
+    assert!(check_nearby_payload(42.0, 23.0, 10.0).is_ok());
+    assert!(check_nearby_payload(91.0, 23.0, 10.0).is_err());
+    assert!(check_nearby_payload(42.0, 23.0, 0.0).is_err());
+    assert!(check_nearby_payload(42.0, 23.0, 501.0).is_err());
+    assert!(check_nearby_payload(42.0, 23.0, f64::NAN).is_err());
+}
+
+#[tokio::test]
+async fn test_geocode_venue() {
+    let arena = coordinates(42.6853, 23.3190);
+    let geocoder = FakeGeocoder::default().place("Arena, Sofia", arena);
+
+    assert_eq!(
+        Some(arena),
+        geocode_venue(&geocoder, Some(" Arena, Sofia ")).await
+    );
+    assert_eq!(None, geocode_venue(&geocoder, Some("Nowhere")).await);
+    // an empty location is not looked up
+    assert_eq!(None, geocode_venue(&geocoder, Some("  ")).await);
+    assert_eq!(None, geocode_venue(&geocoder, None).await);
+    assert_eq!(vec!["Arena, Sofia", "Nowhere"], geocoder.lookups());
+}
+
+#[tokio::test]
+async fn test_events_near() {
+    let db_client = common::connect().await;
+    let seller = common::create_user(&db_client, gql_api::auth::Role::Seller).await;
+    // a place of its own, away from the events of other runs
+    let center = coordinates(
+        rand::thread_rng().gen_range(-60.0, 60.0),
+        rand::thread_rng().gen_range(-170.0, 170.0),
+    );
+
+    let insert = |latitude_offset: f64, event_status: EventStatus| {
+        let db_event = DbEvent {
+            event_status,
+            latitude: Some(center.latitude + latitude_offset),
+            longitude: Some(center.longitude),
+            ..DbEvent::new(&common::gen_string(20), seller)
+        };
+        let db_client = &db_client;
+        async move {
+            db_insert_event(db_client, &db_event)
+                .await
+                .expect("failed to insert event");
+            db_event
+        }
+    };
+    // a tenth of a degree of latitude is about 11 km
+    let far = insert(1.0, EventStatus::Final).await;
+    let near = insert(0.1, EventStatus::Final).await;
+    let here = insert(0.0, EventStatus::Final).await;
+    let draft = insert(0.0, EventStatus::Draft).await;
+
+    let nearby = db_get_events_near(&db_client, &center, 50.0, 100, 0)
+        .await
+        .expect("failed to get nearby events");
+    let ids = nearby
+        .iter()
+        .map(|(db_event, _)| db_event.id)
+        .filter(|id| [far.id, near.id, here.id, draft.id].contains(id))
+        .collect::<Vec<_>>();
+    // nearest first, drafts and events out of the radius left out
+    assert_eq!(vec![here.id, near.id], ids);
+
+    let (_, near_distance) = nearby
+        .iter()
+        .find(|(db_event, _)| db_event.id == near.id)
+        .expect("the near event");
+    let expected = distance_km(
+        &center,
+        &Coordinates::from_columns(near.latitude, near.longitude).expect("coordinates"),
+    );
+    assert!((near_distance - expected).abs() < 0.01);
+}
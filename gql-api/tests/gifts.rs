@@ -0,0 +1,170 @@
+use common::TestContextBuilder;
+use gql_api::{
+    auth::Role,
+    db::{
+        models::DbTicket,
+        sql::{
+            db_get_ticket_gift_by_reservation_id, db_get_ticket_reservation_by_id,
+            db_insert_ticket, db_update_event_status, db_update_user_phone_number,
+        },
+    },
+    gql::{
+        models::{EventStatus, NewTicket, NewTicketGift},
+        mutations::BuyerMutationRoot,
+        quiries::BuyerQueryRoot,
+        schema::{BuyerSchema, Context as ResourcesContext},
+        validations::{check_ticket_gift_payload, GiftRecipient},
+    },
+    near::NearAmount,
+};
+use juniper::{DefaultScalarValue, EmptySubscription, ExecutionError, Value};
+
+mod common;
+
+fn gen_phone_number() -> String {
+    let subscriber: String = common::gen_string(40)
+        .chars()
+        .filter(char::is_ascii_digit)
+        .chain(std::iter::repeat('7'))
+        .take(5)
+        .collect();
+    format!("+3598882{}", subscriber)
+}
+
+fn new_ticket_gift(to_username: Option<&str>, to_phone_number: Option<&str>) -> NewTicketGift {
+    NewTicketGift {
+        ticket_id: uuid::Uuid::new_v4(),
+        quantity: 1,
+        to_username: to_username.map(str::to_string),
+        to_phone_number: to_phone_number.map(str::to_string),
+    }
+}
+
+async fn execute(
+    ctx: &ResourcesContext,
+    mutation: &str,
+) -> (Value, Vec<ExecutionError<DefaultScalarValue>>) {
+    let schema = BuyerSchema::new(BuyerQueryRoot, BuyerMutationRoot, EmptySubscription::new());
+    juniper::execute(mutation, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid mutation")
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    value.as_object_value()?.get_field_value(name)
+}
+
+fn string_field(value: &Value, name: &str) -> Option<String> {
+    field(value, name)?.as_scalar_value::<String>().cloned()
+}
+
+#[test]
+fn test_gift_recipient() {
+    assert_eq!(
+        GiftRecipient::Username("alice".to_string()),
+        check_ticket_gift_payload(&new_ticket_gift(Some(" alice "), None)).unwrap()
+    );
+    assert_eq!(
+        GiftRecipient::PhoneNumber("+359888123456".to_string()),
+        check_ticket_gift_payload(&new_ticket_gift(None, Some("00359 888 123 456"))).unwrap()
+    );
+    assert!(check_ticket_gift_payload(&new_ticket_gift(None, Some("123"))).is_err());
+    assert!(check_ticket_gift_payload(&new_ticket_gift(None, None)).is_err());
+    assert!(check_ticket_gift_payload(&new_ticket_gift(Some(""), None)).is_err());
+    assert!(
+        check_ticket_gift_payload(&new_ticket_gift(Some("alice"), Some("+359888123456"))).is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_gift_tickets_claimed_by_phone() {
+    let resources = TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+
+    let event = common::create_event(db_client).await;
+    db_update_event_status(db_client, &event.id, EventStatus::Draft, EventStatus::Final)
+        .await
+        .expect("unable to publish event");
+    let db_ticket = DbTicket::new(
+        NewTicket {
+            ticket_name: common::gen_string(10),
+            description: None,
+            price: Some(NearAmount::from_whole_near(10)),
+            max_release_price: None,
+            quantity_available: Some(10),
+            min_purchase_quantity: Some(1),
+            max_purchase_quantity: Some(2),
+            allow_transfers: Some(true),
+            currency: None,
+            event_id: event.id,
+        },
+        &event,
+    );
+    db_insert_ticket(db_client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+
+    let sender_id = common::create_user(db_client, Role::Buyer).await;
+    let sender_ctx = resources.ctx.for_user(Some(sender_id));
+    let phone_number = gen_phone_number();
+    let gift_mutation = |quantity: i32| {
+        format!(
+            r#"mutation {{ giftTickets(newTicketGift: {{ ticketId: "{}", quantity: {}, toPhoneNumber: "{}" }}) {{ reservationId toPhoneNumber claimedAt }} }}"#,
+            db_ticket.id, quantity, phone_number
+        )
+    };
+
+    let (value, errors) = execute(&sender_ctx, &gift_mutation(2)).await;
+    assert!(errors.is_empty());
+    let gift = field(&value, "giftTickets").expect("a gift");
+    assert_eq!(
+        Some(format!(
+            "+********{}",
+            &phone_number[phone_number.len() - 4..]
+        )),
+        string_field(gift, "toPhoneNumber")
+    );
+    let reservation_id = string_field(gift, "reservationId")
+        .and_then(|id| uuid::Uuid::parse_str(&id).ok())
+        .expect("a reservation id");
+
+    // gifts count against the sender's purchase limits
+    let (_, errors) = execute(&sender_ctx, &gift_mutation(1)).await;
+    assert!(!errors.is_empty());
+
+    let db_gift = db_get_ticket_gift_by_reservation_id(db_client, &reservation_id)
+        .await
+        .expect("failed to get gift")
+        .expect("a gift");
+    let claim_mutation = format!(
+        r#"mutation {{ claimGift(claimCode: "{}") {{ claimedBy }} }}"#,
+        db_gift.claim_code
+    );
+
+    // only the owner of the gifted phone may claim it
+    let stranger_id = common::create_user(db_client, Role::Buyer).await;
+    let (_, errors) = execute(&resources.ctx.for_user(Some(stranger_id)), &claim_mutation).await;
+    assert!(!errors.is_empty());
+
+    let recipient_id = common::create_user(db_client, Role::Buyer).await;
+    db_update_user_phone_number(db_client, &recipient_id, &phone_number)
+        .await
+        .expect("failed to set phone number");
+    let recipient_ctx = resources.ctx.for_user(Some(recipient_id));
+    let (value, errors) = execute(&recipient_ctx, &claim_mutation).await;
+    assert!(errors.is_empty());
+    assert_eq!(
+        Some(recipient_id.to_string()),
+        field(&value, "claimGift").and_then(|gift| string_field(gift, "claimedBy"))
+    );
+
+    let db_reservation = db_get_ticket_reservation_by_id(db_client, &reservation_id)
+        .await
+        .expect("failed to get reservation");
+    assert_eq!(recipient_id, db_reservation.user_id);
+    assert_eq!(2, db_reservation.quantity);
+
+    // claimed only once
+    let (_, errors) = execute(&recipient_ctx, &claim_mutation).await;
+    assert!(!errors.is_empty());
+}
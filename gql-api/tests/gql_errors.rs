@@ -0,0 +1,62 @@
+use gql_api::gql::{
+    error::{ErrorCode, GqlError, ValidationError},
+    handlers::with_request_id,
+};
+use juniper::{
+    graphql_value, http::GraphQLResponse, DefaultScalarValue, FieldError, IntoFieldError,
+};
+
+#[test]
+fn test_error_codes() {
+    let not_found = GqlError::NotFound(ValidationError::new(
+        "event_id",
+        "Event with submitted id does not exist",
+    ));
+    assert_eq!(ErrorCode::NotFound, not_found.code());
+    assert_eq!(Some("event_id"), not_found.field());
+
+    let unknown = GqlError::UnknownEventStatus("7".to_string());
+    assert_eq!(ErrorCode::Validation, unknown.code());
+    assert_eq!(None, unknown.field());
+    assert_eq!(
+        ErrorCode::Upstream,
+        GqlError::Storage("timeout".to_string()).code()
+    );
+    assert_eq!(ErrorCode::Internal, GqlError::UnexpectedInternal.code());
+}
+
+#[test]
+fn test_error_extensions() {
+    let error: FieldError<DefaultScalarValue> = GqlError::Conflict(ValidationError::new(
+        "event_status",
+        "Only event with status DRAFT could be edited",
+    ))
+    .into_field_error();
+    assert_eq!(
+        "Only event with status DRAFT could be edited",
+        error.message()
+    );
+    assert_eq!(
+        &graphql_value!({ "code": "CONFLICT", "field": "event_status" }),
+        error.extensions()
+    );
+
+    // upstream details are not leaked to clients
+    let error: FieldError<DefaultScalarValue> =
+        GqlError::Storage("bucket credentials expired".to_string()).into_field_error();
+    assert_eq!("Storage error", error.message());
+    assert_eq!(&graphql_value!({ "code": "UPSTREAM" }), error.extensions());
+}
+
+#[test]
+fn test_with_request_id() {
+    let request_id = uuid::Uuid::new_v4();
+    let res = GraphQLResponse::<DefaultScalarValue>::error(
+        GqlError::UnknownEventStatus("7".to_string()).into_field_error(),
+    );
+
+    let json = with_request_id(&res, &request_id);
+    let extensions = &json["errors"][0]["extensions"];
+    assert_eq!("VALIDATION", extensions["code"]);
+    assert_eq!(request_id.to_string(), extensions["requestId"]);
+}
@@ -0,0 +1,40 @@
+use gql_api::{
+    config::ReconnectConfig,
+    fakes::FakeNearClient,
+    grpc::{is_connection_error, reconnect_backoff, GrpcStatus, NearClient},
+};
+use std::time::Duration;
+
+#[test]
+fn test_reconnect_backoff() {
+    let config = ReconnectConfig {
+        initial_backoff_ms: 100,
+        max_backoff_ms: 1000,
+    };
+    // a broken channel is reconnected right away
+    assert_eq!(Duration::ZERO, reconnect_backoff(&config, 0));
+    assert_eq!(Duration::from_millis(100), reconnect_backoff(&config, 1));
+    assert_eq!(Duration::from_millis(200), reconnect_backoff(&config, 2));
+    assert_eq!(Duration::from_millis(800), reconnect_backoff(&config, 4));
+    assert_eq!(Duration::from_millis(1000), reconnect_backoff(&config, 5));
+    assert_eq!(Duration::from_millis(1000), reconnect_backoff(&config, 100));
+}
+
+#[test]
+fn test_is_connection_error() {
+    assert!(is_connection_error(&tonic::Status::unavailable(
+        "connection refused"
+    )));
+    // the service answered
+    assert!(!is_connection_error(&tonic::Status::internal("failed")));
+    assert!(!is_connection_error(&tonic::Status::not_found("account")));
+}
+
+#[test]
+fn test_fake_status() {
+    assert_eq!(GrpcStatus::Connected, FakeNearClient::default().status());
+    assert_eq!(
+        serde_json::json!("disconnected"),
+        serde_json::to_value(GrpcStatus::Disconnected).expect("a status")
+    );
+}
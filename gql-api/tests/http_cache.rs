@@ -0,0 +1,146 @@
+use chrono::Duration;
+use gql_api::{
+    config::{CachePolicy, HttpCacheConfig},
+    db::{
+        models::DbEvent,
+        sql::{db_get_event_by_id, db_insert_event, db_set_event_tags, sql_timestamp},
+    },
+    gql::models::EventStatus,
+    http::{
+        caching::{etag, format_http_date, parse_http_date, CacheValidators},
+        routes::public_event_route,
+    },
+};
+use warp::http::StatusCode;
+
+mod common;
+
+#[test]
+fn test_cache_validators() {
+    let tag = etag(b"{\"eventName\":\"Concert\"}");
+    assert_eq!(tag, etag(b"{\"eventName\":\"Concert\"}"));
+    assert_ne!(tag, etag(b"{\"eventName\":\"Theatre\"}"));
+
+    let modified_at = sql_timestamp(None);
+    let date = format_http_date(&modified_at);
+    assert!(date.ends_with(" GMT"));
+    assert_eq!(
+        modified_at.timestamp(),
+        parse_http_date(&date).expect("a date").timestamp()
+    );
+
+    let if_none_match = |value: &str| CacheValidators {
+        if_none_match: Some(value.to_string()),
+        ..CacheValidators::default()
+    };
+    assert!(if_none_match(&tag).is_fresh(&tag, None));
+    assert!(if_none_match(&format!("\"other\", W/{}", tag)).is_fresh(&tag, None));
+    assert!(if_none_match("*").is_fresh(&tag, None));
+    assert!(!if_none_match("\"other\"").is_fresh(&tag, Some(&modified_at)));
+
+    let if_modified_since = |value: String| CacheValidators {
+        if_modified_since: Some(value),
+        ..CacheValidators::default()
+    };
+    assert!(if_modified_since(date.clone()).is_fresh(&tag, Some(&modified_at)));
+    assert!(!if_modified_since(date.clone())
+        .is_fresh(&tag, Some(&(modified_at + Duration::seconds(1)))));
+    assert!(!if_modified_since(date).is_fresh(&tag, None));
+    assert!(!if_modified_since("yesterday".to_string()).is_fresh(&tag, Some(&modified_at)));
+
+    // If-None-Match wins over If-Modified-Since
+    let both = CacheValidators {
+        if_none_match: Some("\"other\"".to_string()),
+        if_modified_since: Some(format_http_date(&modified_at)),
+    };
+    assert!(!both.is_fresh(&tag, Some(&modified_at)));
+}
+
+#[test]
+fn test_cache_policies() {
+    let config = HttpCacheConfig::default();
+    assert_eq!(
+        "public, max-age=30, stale-while-revalidate=300",
+        config.policy("event").cache_control()
+    );
+    assert_eq!(
+        "public, max-age=3600",
+        config.policy("openapi").cache_control()
+    );
+    assert_eq!(CachePolicy::default(), config.policy("unknown"));
+    assert_eq!("no-cache", config.policy("unknown").cache_control());
+}
+
+#[tokio::test]
+async fn test_public_event_revalidation() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+    let route = public_event_route(resources.ctx.clone(), warp::log("http_cache"));
+
+    let seller = common::create_user(db_client, gql_api::auth::Role::Seller).await;
+    let db_event = DbEvent {
+        event_status: EventStatus::Final,
+        ..DbEvent::new(&common::gen_string(20), seller)
+    };
+    db_insert_event(db_client, &db_event)
+        .await
+        .expect("failed to insert event");
+    let path = format!("/api/v1/events/{}", db_event.event_slug);
+
+    let response = warp::test::request().path(&path).reply(&route).await;
+    assert_eq!(StatusCode::OK, response.status());
+    let tag = response.headers()["etag"]
+        .to_str()
+        .expect("an etag")
+        .to_string();
+    let last_modified = response.headers()["last-modified"]
+        .to_str()
+        .expect("a last-modified")
+        .to_string();
+    assert_eq!(
+        "public, max-age=30, stale-while-revalidate=300",
+        response.headers()["cache-control"]
+    );
+
+    // unchanged, the client keeps its copy
+    let response = warp::test::request()
+        .path(&path)
+        .header("if-none-match", &tag)
+        .reply(&route)
+        .await;
+    assert_eq!(StatusCode::NOT_MODIFIED, response.status());
+    assert!(response.body().is_empty());
+    let response = warp::test::request()
+        .path(&path)
+        .header("if-modified-since", &last_modified)
+        .reply(&route)
+        .await;
+    assert_eq!(StatusCode::NOT_MODIFIED, response.status());
+
+    // tagging the event changes it
+    db_set_event_tags(db_client, &db_event.id, &["jazz".to_string()])
+        .await
+        .expect("failed to set tags");
+    let response = warp::test::request()
+        .path(&path)
+        .header("if-none-match", &tag)
+        .reply(&route)
+        .await;
+    assert_eq!(StatusCode::OK, response.status());
+    assert_ne!(tag, response.headers()["etag"]);
+    let modified = db_get_event_by_id(db_client, &db_event.id)
+        .await
+        .expect("failed to get event");
+    assert!(modified.modified_at >= db_event.modified_at);
+
+    // drafts are not public
+    let draft = DbEvent::new(&common::gen_string(20), seller);
+    db_insert_event(db_client, &draft)
+        .await
+        .expect("failed to insert event");
+    let response = warp::test::request()
+        .path(&format!("/api/v1/events/{}", draft.event_slug))
+        .reply(&route)
+        .await;
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+}
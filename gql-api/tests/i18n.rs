@@ -0,0 +1,145 @@
+use bytes::Bytes;
+use common::TestContextBuilder;
+use gql_api::{
+    config::{BusinessConfig, SmsTemplates},
+    db::sql::db_get_buyer_signup_session_by_id,
+    gql::handlers::translate_errors,
+    http::handlers::buyer_register_phone,
+    i18n::{negotiate_locale, supported_locale, translate, SmsLocales},
+};
+use warp::Reply;
+
+mod common;
+
+fn business() -> BusinessConfig {
+    toml::from_str(
+        r#"
+        signin-message = "Sign in with nonce: "
+        language = "en"
+
+        [sms.bg]
+        verification = "Вашият код за потвърждение е: {code}"
+
+        [messages.bg]
+        "Invalid UUID" = "Невалиден UUID"
+
+        [messages.fr]
+        "Invalid UUID" = "UUID invalide"
+        "#,
+    )
+    .expect("config should parse")
+}
+
+#[test]
+fn test_supported_locales() {
+    let business = business();
+
+    assert_eq!(Some("bg".to_string()), supported_locale(&business, "bg"));
+    assert_eq!(Some("bg".to_string()), supported_locale(&business, "BG-bg"));
+    assert_eq!(Some("fr".to_string()), supported_locale(&business, "fr_CH"));
+    assert_eq!(Some("en".to_string()), supported_locale(&business, "en-GB"));
+    assert_eq!(None, supported_locale(&business, "de"));
+    assert_eq!(None, supported_locale(&business, ""));
+
+    assert_eq!(
+        Some("fr".to_string()),
+        negotiate_locale(&business, "de-DE, fr;q=0.8, bg;q=0.5")
+    );
+    assert_eq!(
+        Some("bg".to_string()),
+        negotiate_locale(&business, "fr;q=0.4, bg-BG;q=0.9, *;q=1")
+    );
+    assert_eq!(None, negotiate_locale(&business, "de, bg;q=0"));
+}
+
+#[test]
+fn test_translations() {
+    let business = business();
+
+    assert_eq!(
+        "UUID invalide",
+        translate(&business, Some("fr"), "Invalid UUID")
+    );
+    // the configured language without a locale, the message without a translation
+    assert_eq!("Invalid UUID", translate(&business, None, "Invalid UUID"));
+    assert_eq!(
+        "Unexpected error",
+        translate(&business, Some("bg"), "Unexpected error")
+    );
+
+    let mut json = serde_json::json!({
+        "data": null,
+        "errors": [{ "message": "Invalid UUID", "extensions": { "code": "VALIDATION" } }],
+    });
+    translate_errors(&mut json, &business, Some("bg-BG,bg;q=0.9"));
+    assert_eq!("Невалиден UUID", json["errors"][0]["message"]);
+    assert_eq!("VALIDATION", json["errors"][0]["extensions"]["code"]);
+}
+
+#[test]
+fn test_sms_locales() {
+    let sms = SmsLocales::new(&BusinessConfig {
+        language: "bg".to_string(),
+        ..business()
+    });
+
+    assert_eq!(
+        "Вашият код за потвърждение е: 123456",
+        sms.templates(Some("bg")).verification_text("123456")
+    );
+    // the configured language without a locale, english for english speakers
+    assert_eq!(
+        "Вашият код за потвърждение е: 1",
+        sms.templates(None).verification_text("1")
+    );
+    assert_eq!(&SmsTemplates::default(), sms.templates(Some("en")));
+    // missing templates are the english ones
+    assert_eq!(
+        SmsTemplates::default().recovery,
+        sms.templates(Some("bg")).recovery
+    );
+}
+
+#[tokio::test]
+async fn test_signup_session_locale() {
+    let resources = TestContextBuilder::new().business(business()).build().await;
+
+    for (locale, expected) in [
+        (Some("bg-BG"), Some("bg")),
+        (Some("de"), None),
+        (None, None),
+    ] {
+        let subscriber: String = common::gen_string(40)
+            .chars()
+            .filter(char::is_ascii_digit)
+            .chain(std::iter::repeat('3'))
+            .take(5)
+            .collect();
+        let body = serde_json::json!({
+            "phoneNumber": format!("+3598881{}", subscriber),
+            "locale": locale,
+        })
+        .to_string();
+        let response = buyer_register_phone(
+            "buyer".to_string(),
+            resources.ctx.clone(),
+            Bytes::from(body),
+        )
+        .await
+        .expect("phone should be registered")
+        .into_response();
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .expect("unreadable response body");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("unparsable response");
+        let session_id = json["sessionId"]
+            .as_str()
+            .and_then(|id| uuid::Uuid::parse_str(id).ok())
+            .expect("a session id");
+
+        let session = db_get_buyer_signup_session_by_id(&resources.ctx.db_client, &session_id)
+            .await
+            .expect("failed to get session");
+        assert_eq!(expected.map(str::to_string), session.locale);
+    }
+}
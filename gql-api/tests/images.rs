@@ -0,0 +1,142 @@
+use gql_api::{
+    db::sql::{
+        db_get_event_by_id, db_update_asset_file_variant_keys, db_update_event_asset_url,
+        db_update_event_variant_urls, insert_asset_file,
+    },
+    images::{decode_image, image_variant, ImageVariant},
+    jobs::models::EventAssetKind,
+};
+use image::{DynamicImage, GenericImageView, ImageFormat, ImageOutputFormat};
+use std::io::Cursor;
+
+mod common;
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut content = vec![];
+    DynamicImage::new_rgba8(width, height)
+        .write_to(&mut Cursor::new(&mut content), ImageOutputFormat::Png)
+        .expect("unable to encode png");
+    content
+}
+
+#[test]
+fn test_image_variants() {
+    let image = decode_image(&png(800, 600)).expect("unable to decode png");
+    for variant in ImageVariant::ALL {
+        let content = image_variant(&image, variant).expect("unable to resize");
+        assert_eq!(
+            Ok(ImageFormat::WebP),
+            image::guess_format(&content).map_err(|e| e.to_string())
+        );
+        let resized = image::load_from_memory(&content).expect("unable to decode webp");
+        assert_eq!(variant.dimensions(), resized.dimensions());
+    }
+}
+
+#[test]
+fn test_decode_image() {
+    let content = png(10, 10);
+    let encoded = base64::encode(&content);
+    assert!(decode_image(encoded.as_bytes()).is_ok());
+    assert!(decode_image(format!("data:image/png;base64,{}\n", encoded).as_bytes()).is_ok());
+    assert!(decode_image(b"not an image").is_err());
+}
+
+#[test]
+fn test_image_variant_keys() {
+    assert_eq!(
+        "events/1/2.cover.webp",
+        ImageVariant::Cover.key("events/1/2.png")
+    );
+    assert_eq!("a.b/c.og_image.webp", ImageVariant::OgImage.key("a.b/c"));
+    assert_eq!(
+        &[ImageVariant::Thumbnail],
+        ImageVariant::for_kind(EventAssetKind::Thumbnail)
+    );
+}
+
+#[tokio::test]
+async fn test_image_variant_urls() {
+    let cfg = common::setup().await;
+
+    let asset_file = common::gen_asset_file("some_bucket", cfg.event.id);
+    insert_asset_file(&cfg.client, &asset_file)
+        .await
+        .expect("failed to insert s3 file");
+    let variant_keys = vec![
+        (
+            ImageVariant::Cover,
+            ImageVariant::Cover.key(&asset_file.s3_absolute_key),
+        ),
+        (
+            ImageVariant::OgImage,
+            ImageVariant::OgImage.key(&asset_file.s3_absolute_key),
+        ),
+    ];
+    let updated = db_update_asset_file_variant_keys(&cfg.client, &asset_file.id, &variant_keys)
+        .await
+        .expect("failed to update variant keys");
+    assert_eq!(Some(variant_keys[0].1.clone()), updated.cover_key);
+    assert_eq!(Some(variant_keys[1].1.clone()), updated.og_image_key);
+    assert_eq!(None, updated.thumbnail_key);
+
+    let asset_url = "https://bucket/cover.png";
+    db_update_event_asset_url(
+        &cfg.client,
+        &cfg.event.id,
+        EventAssetKind::CoverPhoto,
+        asset_url,
+    )
+    .await
+    .expect("failed to set the cover photo");
+    let variant_urls = vec![
+        (
+            ImageVariant::Cover,
+            "https://bucket/cover.cover.webp".to_string(),
+        ),
+        (
+            ImageVariant::OgImage,
+            "https://bucket/cover.og_image.webp".to_string(),
+        ),
+    ];
+
+    // the variants of a replaced image are dropped
+    let updated = db_update_event_variant_urls(
+        &cfg.client,
+        &cfg.event.id,
+        EventAssetKind::CoverPhoto,
+        "https://bucket/old.png",
+        &variant_urls,
+    )
+    .await
+    .expect("failed to update variant urls");
+    assert_eq!(0, updated);
+
+    let updated = db_update_event_variant_urls(
+        &cfg.client,
+        &cfg.event.id,
+        EventAssetKind::CoverPhoto,
+        asset_url,
+        &variant_urls,
+    )
+    .await
+    .expect("failed to update variant urls");
+    assert_eq!(1, updated);
+    let db_event = db_get_event_by_id(&cfg.client, &cfg.event.id)
+        .await
+        .expect("unable to fetch event");
+    assert_eq!(Some(variant_urls[0].1.clone()), db_event.cover_variant_url);
+    assert_eq!(Some(variant_urls[1].1.clone()), db_event.og_image_url);
+
+    // a new cover photo invalidates its variants
+    let db_event = db_update_event_asset_url(
+        &cfg.client,
+        &cfg.event.id,
+        EventAssetKind::CoverPhoto,
+        "https://bucket/new.png",
+    )
+    .await
+    .expect("failed to set the cover photo");
+    assert_eq!(None, db_event.cover_variant_url);
+    assert_eq!(None, db_event.og_image_url);
+}
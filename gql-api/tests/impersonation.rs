@@ -0,0 +1,137 @@
+use gql_api::{
+    auth::{create_impersonation_jwt, create_jwt, Role},
+    db::{
+        models::DbImpersonation,
+        sql::{
+            db_get_active_impersonation, db_get_user_by_id, db_insert_impersonation,
+            db_revoke_impersonation, sql_timestamp,
+        },
+    },
+    gql::{
+        mutations::PrivateMutationRoot, quiries::PrivateQueryRoot, routes::graphql_private_route,
+        schema::PrivateSchema, subscriptions::PrivateSubscriptionRoot,
+    },
+};
+use std::sync::Arc;
+
+mod common;
+
+#[tokio::test]
+async fn test_revoke_impersonation() {
+    let cfg = common::setup().await;
+    let admin = common::create_user(&cfg.client, Role::SuperAdmin).await;
+    let buyer = common::create_user(&cfg.client, Role::Buyer).await;
+
+    let db_impersonation = DbImpersonation::new(admin, buyer, sql_timestamp(Some(60)));
+    db_insert_impersonation(&cfg.client, &db_impersonation)
+        .await
+        .expect("failed to insert impersonation");
+    let active = db_get_active_impersonation(&cfg.client, &db_impersonation.id)
+        .await
+        .expect("failed to get impersonation")
+        .expect("impersonation should be active");
+    assert_eq!(buyer, active.user_id);
+    assert_eq!(admin, active.admin_id);
+
+    let revoked = db_revoke_impersonation(&cfg.client, &db_impersonation.id)
+        .await
+        .expect("failed to revoke impersonation")
+        .expect("impersonation should be revoked");
+    assert!(revoked.revoked_at.is_some());
+    assert!(
+        db_get_active_impersonation(&cfg.client, &db_impersonation.id)
+            .await
+            .expect("failed to get impersonation")
+            .is_none()
+    );
+
+    // revoking twice is a no-op
+    assert!(db_revoke_impersonation(&cfg.client, &db_impersonation.id)
+        .await
+        .expect("failed to revoke impersonation")
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_impersonation_keeps_off_the_account() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+    let schema = Arc::new(PrivateSchema::new(
+        PrivateQueryRoot,
+        PrivateMutationRoot,
+        PrivateSubscriptionRoot,
+    ));
+    let route = graphql_private_route(resources.ctx.clone(), schema, warp::log("impersonation"));
+
+    let admin = common::create_user(db_client, Role::SuperAdmin).await;
+    let buyer = common::create_user(db_client, Role::Buyer).await;
+    let db_impersonation = DbImpersonation::new(admin, buyer, sql_timestamp(Some(60)));
+    db_insert_impersonation(db_client, &db_impersonation)
+        .await
+        .expect("failed to insert impersonation");
+    let impersonation_jwt = create_impersonation_jwt(
+        &buyer.to_string(),
+        &Role::Buyer,
+        &db_impersonation.id,
+        db_impersonation.expires_at.timestamp(),
+    )
+    .expect("failed to create jwt");
+
+    let execute = |jwt: String, mutation: &str| {
+        warp::test::request()
+            .method("POST")
+            .path("/api/v1/graphql/private")
+            .header("authorization", format!("Bearer {}", jwt))
+            .header("content-type", "application/json")
+            .json(&serde_json::json!({ "query": mutation }))
+            .reply(&route)
+    };
+    let errors = |body: &[u8]| -> Vec<serde_json::Value> {
+        let body: serde_json::Value = serde_json::from_slice(body).expect("invalid response");
+        body.get("errors")
+            .and_then(|errors| errors.as_array())
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    let username = common::gen_string(12);
+    let update_profile = format!(
+        r#"mutation {{ updateProfile(updateProfile: {{ username: "{}" }}) {{ id }} }}"#,
+        username
+    );
+    let delete_my_account = "mutation { deleteMyAccount }";
+
+    let response = execute(impersonation_jwt.clone(), &update_profile).await;
+    assert_eq!(1, errors(response.body()).len());
+    let response = execute(impersonation_jwt, delete_my_account).await;
+    assert_eq!(1, errors(response.body()).len());
+
+    let db_user = db_get_user_by_id(db_client, &buyer)
+        .await
+        .expect("unable to fetch user");
+    assert_ne!(username, db_user.username);
+    assert!(db_user.deleted_at.is_none());
+
+    // the user themselves may
+    let jwt = create_jwt(&buyer.to_string(), &Role::Buyer).expect("failed to create jwt");
+    let response = execute(jwt, &update_profile).await;
+    assert!(errors(response.body()).is_empty());
+}
+
+#[tokio::test]
+async fn test_expired_impersonation() {
+    let cfg = common::setup().await;
+    let admin = common::create_user(&cfg.client, Role::SuperAdmin).await;
+    let buyer = common::create_user(&cfg.client, Role::Buyer).await;
+
+    let db_impersonation = DbImpersonation::new(admin, buyer, sql_timestamp(Some(-1)));
+    db_insert_impersonation(&cfg.client, &db_impersonation)
+        .await
+        .expect("failed to insert impersonation");
+    assert!(
+        db_get_active_impersonation(&cfg.client, &db_impersonation.id)
+            .await
+            .expect("failed to get impersonation")
+            .is_none()
+    );
+}
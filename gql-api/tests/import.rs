@@ -0,0 +1,112 @@
+use gql_api::{
+    auth::Role,
+    config::SanitationConfig,
+    db::models::DbTicket,
+    http::import::{check_import_event, parse_import, ImportFormat},
+};
+
+mod common;
+
+#[test]
+fn test_import_format_detection() {
+    assert_eq!(
+        Some(ImportFormat::Csv),
+        ImportFormat::detect(Some("text/csv; charset=utf-8"), None)
+    );
+    assert_eq!(
+        Some(ImportFormat::Json),
+        ImportFormat::detect(Some("application/octet-stream"), Some("events.JSON"))
+    );
+    assert_eq!(None, ImportFormat::detect(None, Some("events.xlsx")));
+}
+
+#[test]
+fn test_parse_csv_groups_tickets_by_event() {
+    let csv = "event_name,event_description,start_date,end_date,entry_time,is_virtual,venue_name,venue_location,ticket_name,ticket_description,price,max_release_price,quantity_available,min_purchase_quantity,max_purchase_quantity,allow_transfers,currency
+Summer Fest,Open air,,,,false,Park,Sofia,Early,,100,,50,1,4,true,near
+Summer Fest,,,,,,,,Regular,,200,,100,,,,
+Winter Fest,Indoors,,,,true,,,,,,,,,,,
+";
+    let entries = parse_import(ImportFormat::Csv, csv.as_bytes()).expect("failed to parse csv");
+    assert_eq!(2, entries.len());
+    assert_eq!(vec![1, 2], entries[0].rows);
+    assert_eq!("Summer Fest", entries[0].event.event_name);
+    assert_eq!(Some("Open air".to_string()), entries[0].event.description);
+    assert_eq!(2, entries[0].event.tickets.len());
+    assert_eq!(Some(4), entries[0].event.tickets[0].max_purchase_quantity);
+    assert_eq!(vec![3], entries[1].rows);
+    assert!(entries[1].event.tickets.is_empty());
+
+    // a malformed row fails the whole upload
+    let csv = "event_name,quantity_available\nSummer Fest,many\n";
+    assert!(parse_import(ImportFormat::Csv, csv.as_bytes()).is_err());
+}
+
+#[test]
+fn test_check_import_event() {
+    let json = r#"[
+        {"eventName": "Summer Fest", "tickets": [{"ticketName": "Early"}, {"ticketName": "early"}]},
+        {"eventName": "Winter Fest", "tickets": [{"ticketName": "Regular", "quantityAvailable": 0}]},
+        {"eventName": "Autumn Fest", "tickets": [{"ticketName": "Regular", "price": "100"}]}
+    ]"#;
+    let entries = parse_import(ImportFormat::Json, json.as_bytes()).expect("failed to parse json");
+    assert_eq!(3, entries.len());
+
+    let config = SanitationConfig::default();
+    let user_id = uuid::Uuid::new_v4();
+    let mut entries = entries.into_iter();
+    // tickets with the same slug
+    assert!(check_import_event(&config, entries.next().unwrap().event, user_id).is_err());
+    // tickets are validated like the mutations do
+    assert!(check_import_event(&config, entries.next().unwrap().event, user_id).is_err());
+
+    let (db_event, db_tickets) =
+        check_import_event(&config, entries.next().unwrap().event, user_id).expect("valid event");
+    assert_eq!("autumn-fest", db_event.event_slug);
+    assert_eq!(user_id, db_event.created_by_user);
+    assert_eq!(1, db_tickets.len());
+    assert_eq!(db_event.id, db_tickets[0].event_id);
+}
+
+#[tokio::test]
+async fn test_insert_event_with_tickets_is_atomic() {
+    let cfg = common::setup().await;
+    let seller = common::create_user(&cfg.client, Role::Seller).await;
+
+    let json = format!(
+        r#"[{{"eventName": "{}", "tickets": [{{"ticketName": "Early"}}, {{"ticketName": "Late"}}]}}]"#,
+        common::gen_string(12)
+    );
+    let entry = parse_import(ImportFormat::Json, json.as_bytes())
+        .expect("failed to parse json")
+        .remove(0);
+    let (db_event, db_tickets) =
+        check_import_event(&SanitationConfig::default(), entry.event, seller).expect("valid event");
+
+    // a ticket clashing with an existing one fails the event too
+    let mut clashing = db_tickets.clone();
+    clashing[1] = DbTicket {
+        id: clashing[0].id,
+        ..clashing[1].clone()
+    };
+    assert!(
+        gql_api::db::sql::db_insert_event_with_tickets(&cfg.client, &db_event, &clashing)
+            .await
+            .is_err()
+    );
+    assert!(
+        gql_api::db::sql::db_get_event_by_id(&cfg.client, &db_event.id)
+            .await
+            .is_err()
+    );
+
+    let inserted =
+        gql_api::db::sql::db_insert_event_with_tickets(&cfg.client, &db_event, &db_tickets)
+            .await
+            .expect("failed to import event");
+    assert_eq!(2, inserted);
+    let tickets = gql_api::db::sql::db_get_tickets_by_event_ids(&cfg.client, &[db_event.id])
+        .await
+        .expect("failed to get tickets");
+    assert_eq!(2, tickets.len());
+}
@@ -0,0 +1,62 @@
+use gql_api::{
+    db::sql::{db_claim_next_job, db_complete_job, db_fail_job, db_get_job_by_id, sql_timestamp},
+    jobs::{
+        models::{JobPayload, JobStatus, JobType},
+        queue::enqueue,
+    },
+    notifier::Notification,
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_jobs_lifecycle() {
+    let cfg = common::setup().await;
+
+    let payload = JobPayload::Notify {
+        receiver: "+359888123456".to_string(),
+        notification: Notification::Verification {
+            code: common::gen_string(6),
+        },
+        locale: Some("bg".to_string()),
+    };
+    let expected = enqueue(&cfg.client, payload.clone(), 2)
+        .await
+        .expect("failed to enqueue job");
+    assert_eq!(JobType::Notify, expected.job_type);
+    assert_eq!(payload, expected.payload().expect("payload should parse"));
+
+    // other tests may enqueue jobs concurrently, so claim until ours shows up
+    let mut claimed = None;
+    while let Some(db_job) = db_claim_next_job(&cfg.client)
+        .await
+        .expect("failed to claim job")
+    {
+        if db_job.id.eq(&expected.id) {
+            claimed = Some(db_job);
+            break;
+        }
+    }
+    let claimed = claimed.expect("enqueued job should be claimable");
+    assert_eq!(JobStatus::Running, claimed.job_status);
+    assert_eq!(1, claimed.attempts);
+
+    // first failure puts the job back in the queue, in the future
+    db_fail_job(&cfg.client, &claimed.id, "boom", &sql_timestamp(Some(3600)))
+        .await
+        .expect("failed to fail job");
+    let retried = db_get_job_by_id(&cfg.client, &claimed.id)
+        .await
+        .expect("unable to get job");
+    assert_eq!(JobStatus::Pending, retried.job_status);
+    assert_eq!(Some("boom".to_string()), retried.last_error);
+
+    db_complete_job(&cfg.client, &claimed.id)
+        .await
+        .expect("failed to complete job");
+    let done = db_get_job_by_id(&cfg.client, &claimed.id)
+        .await
+        .expect("unable to get job");
+    assert_eq!(JobStatus::Done, done.job_status);
+    assert_eq!(None, done.last_error);
+}
@@ -0,0 +1,157 @@
+use futures::future::join_all;
+use gql_api::{
+    auth::Role,
+    db::{
+        models::{AssetFile, DbTicket},
+        sql::{db_insert_ticket, insert_asset_file},
+    },
+    gql::{loaders::Loader, models::NewTicket},
+};
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_loader_batches() {
+    let loader = Loader::<u32, u32>::default();
+    let fetches = AtomicUsize::new(0);
+    // the even keys are found
+    let fetch = |keys: Vec<u32>| {
+        fetches.fetch_add(1, Ordering::SeqCst);
+        async move {
+            Ok::<_, tokio_postgres::Error>(
+                keys.into_iter()
+                    .filter(|key| key % 2 == 0)
+                    .map(|key| (key, key * 10))
+                    .collect::<HashMap<_, _>>(),
+            )
+        }
+    };
+
+    // the siblings' keys are loaded together
+    let loaded = join_all((0..5).map(|key| loader.load(key, fetch))).await;
+    let loaded = loaded
+        .into_iter()
+        .map(|value| value.expect("a loaded value"))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![Some(0), None, Some(20), None, Some(40)], loaded);
+    assert_eq!(1, fetches.load(Ordering::SeqCst));
+
+    // and kept, found or not
+    assert_eq!(Some(20), loader.load(2, fetch).await.expect("a value"));
+    assert_eq!(None, loader.load(3, fetch).await.expect("a value"));
+    assert_eq!(1, fetches.load(Ordering::SeqCst));
+
+    let loaded = loader.load_many(&[4, 5, 6], fetch).await.expect("values");
+    assert_eq!(Some(&40), loaded.get(&4));
+    assert_eq!(Some(&60), loaded.get(&6));
+    assert_eq!(None, loaded.get(&5));
+    assert_eq!(2, fetches.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn test_request_loaders() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+    let event = common::create_event(db_client).await;
+    let other = common::create_event(db_client).await;
+    let db_ticket = DbTicket::new(
+        NewTicket {
+            ticket_name: common::gen_string(10),
+            description: None,
+            price: None,
+            max_release_price: None,
+            quantity_available: None,
+            min_purchase_quantity: None,
+            max_purchase_quantity: None,
+            allow_transfers: None,
+            currency: None,
+            event_id: event.id,
+        },
+        &event,
+    );
+    db_insert_ticket(db_client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+    let asset_file = AssetFile::new("bucket", common::gen_string(20), None, event.id);
+    insert_asset_file(db_client, &asset_file)
+        .await
+        .expect("failed to insert asset");
+
+    let ctx = resources.ctx.for_user(None);
+    let (tickets, other_tickets) = futures::join!(
+        ctx.loaders.tickets_by_event(db_client, event.id),
+        ctx.loaders.tickets_by_event(db_client, other.id)
+    );
+    let tickets = tickets.expect("tickets");
+    assert_eq!(1, tickets.len());
+    assert_eq!(db_ticket.id, tickets[0].id);
+    assert!(other_tickets.expect("tickets").is_empty());
+    let by_event = ctx
+        .loaders
+        .tickets_by_events(db_client, &[event.id, other.id])
+        .await
+        .expect("tickets");
+    assert_eq!(1, by_event[&event.id].len());
+    assert!(!by_event.contains_key(&other.id));
+
+    let assets = ctx
+        .loaders
+        .assets_by_event(db_client, event.id)
+        .await
+        .expect("assets");
+    assert_eq!(vec![asset_file], assets);
+
+    let seller = ctx
+        .loaders
+        .user_by_id(db_client, event.created_by_user)
+        .await
+        .expect("a user")
+        .expect("the event's seller");
+    assert_eq!(Role::Seller, seller.user_type);
+    assert!(ctx
+        .loaders
+        .user_by_id(db_client, uuid::Uuid::new_v4())
+        .await
+        .expect("no user")
+        .is_none());
+
+    // every request loads afresh
+    db_insert_ticket(
+        db_client,
+        &DbTicket::new(
+            NewTicket {
+                ticket_name: common::gen_string(10),
+                description: None,
+                price: None,
+                max_release_price: None,
+                quantity_available: None,
+                min_purchase_quantity: None,
+                max_purchase_quantity: None,
+                allow_transfers: None,
+                currency: None,
+                event_id: other.id,
+            },
+            &other,
+        ),
+    )
+    .await
+    .expect("failed to insert ticket");
+    let cached = ctx
+        .loaders
+        .tickets_by_event(db_client, other.id)
+        .await
+        .expect("tickets");
+    assert!(cached.is_empty());
+    let fresh = resources
+        .ctx
+        .for_user(None)
+        .loaders
+        .tickets_by_event(db_client, other.id)
+        .await
+        .expect("tickets");
+    assert_eq!(1, fresh.len());
+}
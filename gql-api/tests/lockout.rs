@@ -0,0 +1,206 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use common::TestContextBuilder;
+use gql_api::{
+    auth::{Role, UserStatus},
+    config::LoginLockoutConfig,
+    db::{
+        models::{DbLoginAttempt, DbUser},
+        sql::db_insert_user,
+    },
+    error::handle_rejection,
+    gql::{
+        mutations::AdminMutationRoot,
+        quiries::AdminQueryRoot,
+        schema::{AdminSchema, Context as ResourcesContext},
+    },
+    http::routes::signin_with_password_route,
+    security::{
+        lockout::{backoff_secs, login_locked_until},
+        password::hash_password,
+    },
+};
+use juniper::EmptySubscription;
+use warp::{http::StatusCode, Filter};
+
+mod common;
+
+fn lockout_config() -> LoginLockoutConfig {
+    LoginLockoutConfig {
+        free_attempts: 1,
+        backoff_base_secs: 60,
+        backoff_max_secs: 600,
+        lockout_attempts: 3,
+        lockout_secs: 900,
+        window_secs: 3600,
+        trust_forwarded_for: true,
+    }
+}
+
+fn attempt(
+    ip_address: &str,
+    failed_attempts: i32,
+    last_failed_at: NaiveDateTime,
+) -> DbLoginAttempt {
+    DbLoginAttempt {
+        user_id: uuid::Uuid::nil(),
+        ip_address: ip_address.to_string(),
+        failed_attempts,
+        last_failed_at,
+        expires_at: last_failed_at + Duration::seconds(3600),
+    }
+}
+
+async fn unlock_user(ctx: &ResourcesContext, user_id: &uuid::Uuid) -> Option<bool> {
+    let schema = AdminSchema::new(AdminQueryRoot, AdminMutationRoot, EmptySubscription::new());
+    let query = format!(r#"mutation {{ unlockUser(userId: "{}") }}"#, user_id);
+    let (value, errors) = juniper::execute(&query, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid query");
+    if !errors.is_empty() {
+        return None;
+    }
+    let value = serde_json::to_value(&value).expect("serializable value");
+    value["unlockUser"].as_bool()
+}
+
+#[test]
+fn test_login_locked_until() {
+    let config = LoginLockoutConfig {
+        free_attempts: 3,
+        backoff_base_secs: 1,
+        backoff_max_secs: 300,
+        ..lockout_config()
+    };
+    assert_eq!(0, backoff_secs(&config, 3));
+    assert_eq!(1, backoff_secs(&config, 4));
+    assert_eq!(2, backoff_secs(&config, 5));
+    assert_eq!(256, backoff_secs(&config, 12));
+    assert_eq!(300, backoff_secs(&config, 13));
+    assert_eq!(300, backoff_secs(&config, i32::MAX));
+
+    let config = lockout_config();
+    let now = Utc::now().naive_utc();
+    let a_second_ago = now - Duration::seconds(1);
+    assert_eq!(None, login_locked_until(&config, &[], "10.0.0.1", &now));
+    assert_eq!(
+        None,
+        login_locked_until(
+            &config,
+            &[attempt("10.0.0.1", 1, a_second_ago)],
+            "10.0.0.1",
+            &now
+        )
+    );
+
+    // the address waits, the others do not
+    let attempts = [attempt("10.0.0.1", 2, a_second_ago)];
+    assert_eq!(
+        Some(a_second_ago + Duration::seconds(60)),
+        login_locked_until(&config, &attempts, "10.0.0.1", &now)
+    );
+    assert_eq!(
+        None,
+        login_locked_until(&config, &attempts, "10.0.0.2", &now)
+    );
+    assert_eq!(
+        None,
+        login_locked_until(
+            &config,
+            &attempts,
+            "10.0.0.1",
+            &(now + Duration::seconds(60))
+        )
+    );
+
+    // the account is locked for every address
+    let attempts = [
+        attempt("10.0.0.1", 2, a_second_ago - Duration::seconds(10)),
+        attempt("10.0.0.2", 1, a_second_ago),
+    ];
+    assert_eq!(
+        Some(a_second_ago + Duration::seconds(900)),
+        login_locked_until(&config, &attempts, "10.0.0.3", &now)
+    );
+
+    // forgotten attempts do not count
+    let mut expired = attempt("10.0.0.1", 5, a_second_ago);
+    expired.expires_at = a_second_ago;
+    assert_eq!(
+        None,
+        login_locked_until(&config, &[expired], "10.0.0.1", &now)
+    );
+}
+
+#[tokio::test]
+async fn test_signin_lockout() {
+    let resources = TestContextBuilder::new()
+        .login_lockout(lockout_config())
+        .build()
+        .await;
+    let route = signin_with_password_route(resources.ctx.clone(), warp::log("lockout"))
+        .recover(handle_rejection);
+    let db_user = DbUser::new(
+        uuid::Uuid::new_v4(),
+        None,
+        common::gen_string(20),
+        None,
+        None,
+        Some(hash_password(b"password").expect("unable to hash password")),
+        None,
+        Role::Seller,
+        common::gen_string(20),
+        "0".to_string(),
+        UserStatus::PhoneVerified,
+    );
+    db_insert_user(&resources.ctx.db_client, &db_user)
+        .await
+        .expect("unable to create user");
+
+    let signin = |ip_address: &str, password: &str| {
+        warp::test::request()
+            .method("POST")
+            .path("/api/v1/seller/signin_with_pwd")
+            .header("content-type", "application/json")
+            .header("x-forwarded-for", format!("{}, 10.0.0.254", ip_address))
+            .body(
+                serde_json::json!({ "username": db_user.username, "password": password })
+                    .to_string(),
+            )
+    };
+
+    // past the free attempt, the address waits
+    let response = signin("10.0.0.1", "wrong-password").reply(&route).await;
+    assert_eq!(StatusCode::FORBIDDEN, response.status());
+    let response = signin("10.0.0.1", "wrong-password").reply(&route).await;
+    assert_eq!(StatusCode::FORBIDDEN, response.status());
+    let response = signin("10.0.0.1", "password").reply(&route).await;
+    assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+    let retry_after: i64 = response.headers()["retry-after"]
+        .to_str()
+        .expect("a header value")
+        .parse()
+        .expect("seconds");
+    assert!(retry_after > 0 && retry_after <= 60);
+
+    // other addresses may sign in, until the account is locked
+    let response = signin("10.0.0.2", "password").reply(&route).await;
+    assert_eq!(StatusCode::OK, response.status());
+    let response = signin("10.0.0.2", "wrong-password").reply(&route).await;
+    assert_eq!(StatusCode::FORBIDDEN, response.status());
+    let response = signin("10.0.0.3", "password").reply(&route).await;
+    assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+
+    // only admins unlock
+    let db_client = &resources.ctx.db_client;
+    let buyer = common::create_user(db_client, Role::Buyer).await;
+    assert_eq!(
+        None,
+        unlock_user(&resources.ctx.for_user(Some(buyer)), &db_user.id).await
+    );
+    let admin = common::create_user(db_client, Role::Admin).await;
+    let ctx = resources.ctx.for_user(Some(admin));
+    assert_eq!(Some(true), unlock_user(&ctx, &db_user.id).await);
+    assert_eq!(Some(false), unlock_user(&ctx, &db_user.id).await);
+    let response = signin("10.0.0.1", "password").reply(&route).await;
+    assert_eq!(StatusCode::OK, response.status());
+}
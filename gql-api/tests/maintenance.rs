@@ -0,0 +1,99 @@
+use gql_api::{
+    auth::{create_jwt, Role},
+    config::MaintenanceConfig,
+    error::handle_rejection,
+    filters::with_maintenance,
+    gql::{schema::Context as ResourcesContext, validations::check_maintenance_payload},
+    maintenance::{set_mode, Maintenance},
+};
+use std::sync::Arc;
+use warp::{http::StatusCode, Filter};
+
+mod common;
+
+async fn request(
+    ctx: &Arc<ResourcesContext>,
+    role: Option<Role>,
+) -> warp::http::Response<warp::hyper::body::Bytes> {
+    let route = with_maintenance(ctx.clone())
+        .map(warp::reply)
+        .recover(handle_rejection);
+    let request = warp::test::request().path("/graphql");
+    let request = match role {
+        Some(role) => {
+            let jwt =
+                create_jwt(&uuid::Uuid::new_v4().to_string(), &role).expect("failed to create jwt");
+            request.header("authorization", format!("Bearer {}", jwt))
+        }
+        None => request,
+    };
+    request.reply(&route).await
+}
+
+#[test]
+fn test_maintenance_payload() {
+    assert_eq!(
+        Some("Back soon".to_string()),
+        check_maintenance_payload(Some("  Back soon "), Some(60)).expect("a valid payload")
+    );
+    assert_eq!(
+        None,
+        check_maintenance_payload(Some("  "), None).expect("a valid payload")
+    );
+    assert!(check_maintenance_payload(None, Some(0)).is_err());
+    assert!(check_maintenance_payload(None, Some(24 * 60 * 60 + 1)).is_err());
+    assert!(check_maintenance_payload(Some(&"a".repeat(501)), None).is_err());
+}
+
+#[test]
+fn test_forced_maintenance() {
+    let maintenance = Maintenance::new(&MaintenanceConfig {
+        enabled: true,
+        ..MaintenanceConfig::default()
+    });
+    let mode = maintenance.mode();
+    assert!(mode.enabled);
+    assert!(mode.forced);
+    assert_eq!(300, mode.retry_after_secs);
+}
+
+#[tokio::test]
+async fn test_maintenance_mode() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let ctx = &resources.ctx;
+    let admin = common::create_user(&ctx.db_client, Role::Admin).await;
+
+    let mode = set_mode(ctx, true, Some("Back soon".to_string()), Some(60), &admin)
+        .await
+        .expect("failed to set the maintenance mode");
+    assert!(mode.enabled);
+    assert!(!mode.forced);
+
+    // everyone but the admins is turned away
+    let response = request(ctx, None).await;
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+    assert_eq!("60", response.headers()["retry-after"]);
+    let response = request(ctx, Some(Role::Buyer)).await;
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, response.status());
+    for role in [Role::Admin, Role::SuperAdmin] {
+        let response = request(ctx, Some(role)).await;
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    // the other api instances follow at their next refresh
+    let other = Maintenance::new(&MaintenanceConfig::default());
+    assert!(!other.is_enabled());
+    let mode = other
+        .refresh(&ctx.db_client)
+        .await
+        .expect("failed to refresh the maintenance mode");
+    assert!(mode.enabled);
+    assert_eq!(Some("Back soon".to_string()), mode.message);
+
+    set_mode(ctx, false, None, None, &admin)
+        .await
+        .expect("failed to set the maintenance mode");
+    let response = request(ctx, None).await;
+    assert_eq!(StatusCode::OK, response.status());
+    assert!(!response.headers().contains_key("retry-after"));
+}
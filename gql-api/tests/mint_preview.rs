@@ -0,0 +1,101 @@
+use gql_api::{
+    db::models::{DbEvent, DbTicket},
+    gql::{
+        error::GqlError,
+        mint::{
+            estimate_mint, MintPayload, BASE_TERA_GAS, GAS_PRICE_PER_TERA_GAS,
+            STORAGE_PRICE_PER_BYTE, TERA_GAS_PER_TOKEN, TOKEN_STORAGE_OVERHEAD_BYTES,
+        },
+        models::NewTicket,
+    },
+    near::NearAmount,
+};
+
+fn new_ticket(db_event: &DbEvent, price: Option<NearAmount>) -> DbTicket {
+    DbTicket::new(
+        NewTicket {
+            ticket_name: "General admission".to_string(),
+            description: Some("Standing".to_string()),
+            price,
+            max_release_price: None,
+            quantity_available: Some(4),
+            min_purchase_quantity: None,
+            max_purchase_quantity: None,
+            allow_transfers: Some(true),
+            currency: None,
+            event_id: db_event.id,
+        },
+        db_event,
+    )
+}
+
+#[test]
+fn test_mint_payload() {
+    let mut db_event = DbEvent::new("Concert", uuid::Uuid::new_v4());
+    db_event.cover_photo_url = Some("https://bucket/cover.png".to_string());
+    let db_ticket = new_ticket(&db_event, Some(NearAmount::from_whole_near(2)));
+
+    let payload = MintPayload::new(&db_event, &db_ticket).unwrap();
+    assert_eq!("General admission", payload.title);
+    assert_eq!(db_ticket.ticket_slug, payload.ticket_slug);
+    assert_eq!("Standing", payload.description);
+    assert_eq!(
+        sha256::digest("https://bucket/cover.png"),
+        payload.media_hash
+    );
+    assert_eq!(4, payload.number_of_tickets);
+    assert_eq!(
+        r#"{"price":"2000000000000000000000000"}"#,
+        payload.extra.as_str()
+    );
+}
+
+#[test]
+fn test_mint_payload_requires_price_media_and_quantity() {
+    let mut db_event = DbEvent::new("Concert", uuid::Uuid::new_v4());
+    let db_ticket = new_ticket(&db_event, Some(NearAmount::from_whole_near(2)));
+    assert!(matches!(
+        MintPayload::new(&db_event, &db_ticket),
+        Err(GqlError::Validation(_))
+    ));
+
+    db_event.cover_photo_url = Some("https://bucket/cover.png".to_string());
+    let mut db_ticket = new_ticket(&db_event, None);
+    assert!(MintPayload::new(&db_event, &db_ticket).is_err());
+
+    db_ticket.price = Some(NearAmount::from_whole_near(2));
+    db_ticket.quantity_available = Some(0);
+    assert!(MintPayload::new(&db_event, &db_ticket).is_err());
+}
+
+#[test]
+fn test_estimate_mint() {
+    let mut db_event = DbEvent::new("Concert", uuid::Uuid::new_v4());
+    db_event.cover_photo_url = Some("https://bucket/cover.png".to_string());
+    let db_ticket = new_ticket(&db_event, Some(NearAmount::from_whole_near(2)));
+    let payload = MintPayload::new(&db_event, &db_ticket).unwrap();
+
+    let estimate = estimate_mint(db_ticket.id, &payload);
+    let metadata: serde_json::Value = serde_json::from_str(&estimate.metadata).unwrap();
+    assert_eq!("General admission", metadata["title"]);
+    assert_eq!(4, metadata["copies"]);
+
+    let storage_bytes = 4 * (estimate.metadata.len() + TOKEN_STORAGE_OVERHEAD_BYTES);
+    assert_eq!(storage_bytes as i32, estimate.storage_bytes);
+    assert_eq!(
+        STORAGE_PRICE_PER_BYTE.checked_mul(storage_bytes as u128),
+        Some(estimate.storage_cost)
+    );
+    assert_eq!(
+        (BASE_TERA_GAS + 4 * TERA_GAS_PER_TOKEN) as i32,
+        estimate.tera_gas
+    );
+    assert_eq!(
+        GAS_PRICE_PER_TERA_GAS.checked_mul(estimate.tera_gas as u128),
+        Some(estimate.gas_cost)
+    );
+    assert_eq!(
+        estimate.storage_cost.checked_add(estimate.gas_cost),
+        Some(estimate.total_cost)
+    );
+}
@@ -0,0 +1,126 @@
+use gql_api::{
+    config::MintsConfig,
+    db::{
+        models::{DbMintJob, DbTicket},
+        sql::{
+            db_get_event_by_id, db_get_mint_jobs_by_event_id, db_insert_mint_job, db_insert_ticket,
+            db_update_event_status,
+        },
+    },
+    fakes::FakeNearClient,
+    gql::models::{EventStatus, MintStatus, NewTicket},
+    jobs::mints::{next_mint_status, reconcile_mints, tx_mint_status},
+};
+
+mod common;
+
+fn new_ticket(event_id: uuid::Uuid) -> NewTicket {
+    NewTicket {
+        ticket_name: common::gen_string(10),
+        description: None,
+        price: None,
+        max_release_price: None,
+        quantity_available: Some(10),
+        min_purchase_quantity: None,
+        max_purchase_quantity: None,
+        allow_transfers: None,
+        currency: None,
+        event_id,
+    }
+}
+
+#[test]
+fn test_tx_mint_status() {
+    assert_eq!(Some(MintStatus::Succeeded), tx_mint_status("SUCCESS"));
+    assert_eq!(Some(MintStatus::Failed), tx_mint_status("FAILURE"));
+    assert_eq!(None, tx_mint_status("PENDING"));
+    assert_eq!(None, tx_mint_status(""));
+}
+
+#[test]
+fn test_next_mint_status() {
+    assert_eq!(MintStatus::Pending, next_mint_status(None, 1, 3));
+    assert_eq!(MintStatus::Failed, next_mint_status(None, 3, 3));
+    assert_eq!(
+        MintStatus::Succeeded,
+        next_mint_status(Some(MintStatus::Succeeded), 3, 3)
+    );
+    assert_eq!(
+        MintStatus::Failed,
+        next_mint_status(Some(MintStatus::Failed), 1, 3)
+    );
+}
+
+async fn reconcile(tx_status: &str) -> (EventStatus, DbMintJob) {
+    let resources = common::TestContextBuilder::new()
+        .near_client(FakeNearClient {
+            tx_status: tx_status.to_string(),
+            ..Default::default()
+        })
+        .build()
+        .await;
+    let db_client = &resources.ctx.db_client;
+
+    let db_event = common::create_event(db_client).await;
+    db_update_event_status(
+        db_client,
+        &db_event.id,
+        EventStatus::Draft,
+        EventStatus::Minting,
+    )
+    .await
+    .expect("failed to update event status")
+    .expect("event should be a draft");
+    let db_ticket = DbTicket::new(new_ticket(db_event.id), &db_event);
+    db_insert_ticket(db_client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+    let db_mint_job = DbMintJob::new(&db_ticket, "seller.testnet", &common::gen_string(20), 10);
+    db_insert_mint_job(db_client, &db_mint_job)
+        .await
+        .expect("failed to insert mint job");
+
+    reconcile_mints(&resources.ctx, &MintsConfig::default())
+        .await
+        .expect("failed to reconcile mints");
+    assert!(resources
+        .near_client
+        .calls()
+        .contains(&"get_tx_status".to_string()));
+
+    let db_event = db_get_event_by_id(db_client, &db_event.id)
+        .await
+        .expect("failed to get event");
+    let mut db_mint_jobs = db_get_mint_jobs_by_event_id(db_client, &db_event.id)
+        .await
+        .expect("failed to get mint jobs");
+    assert_eq!(1, db_mint_jobs.len());
+    (db_event.event_status, db_mint_jobs.remove(0))
+}
+
+#[tokio::test]
+async fn test_reconcile_succeeded_mint() {
+    let (event_status, db_mint_job) = reconcile("SUCCESS").await;
+    assert_eq!(MintStatus::Succeeded, db_mint_job.mint_status);
+    assert_eq!(1, db_mint_job.attempts);
+    assert!(db_mint_job.checked_at.is_some());
+    assert_eq!(None, db_mint_job.error);
+    assert_eq!(EventStatus::Minting, event_status);
+}
+
+#[tokio::test]
+async fn test_reconcile_failed_mint() {
+    let (event_status, db_mint_job) = reconcile("FAILURE").await;
+    assert_eq!(MintStatus::Failed, db_mint_job.mint_status);
+    assert!(db_mint_job.error.is_some());
+    // the seller may mint again
+    assert_eq!(EventStatus::Draft, event_status);
+}
+
+#[tokio::test]
+async fn test_reconcile_pending_mint() {
+    let (event_status, db_mint_job) = reconcile("PENDING").await;
+    assert_eq!(MintStatus::Pending, db_mint_job.mint_status);
+    assert_eq!(1, db_mint_job.attempts);
+    assert_eq!(EventStatus::Minting, event_status);
+}
@@ -0,0 +1,99 @@
+use gql_api::{
+    error::NearAmountError,
+    near::{parse_basis_points, NearAmount},
+};
+
+#[test]
+fn test_near_amount_parse() {
+    let amount: NearAmount = "200000000000000000000000".parse().expect("valid amount");
+    assert_eq!(200_000_000_000_000_000_000_000, amount.as_yocto());
+    assert_eq!("200000000000000000000000", amount.to_string());
+
+    assert!(matches!(
+        "-1".parse::<NearAmount>(),
+        Err(NearAmountError::Invalid(_))
+    ));
+    assert!(matches!(
+        "1.5".parse::<NearAmount>(),
+        Err(NearAmountError::Invalid(_))
+    ));
+    assert!(matches!(
+        "".parse::<NearAmount>(),
+        Err(NearAmountError::Invalid(_))
+    ));
+    assert!(matches!(
+        "340282366920938463463374607431768211456".parse::<NearAmount>(),
+        Err(NearAmountError::Overflow(_))
+    ));
+}
+
+#[test]
+fn test_near_amount_from_near() {
+    assert_eq!(
+        NearAmount::from_yocto(200_000_000_000_000_000_000_000),
+        NearAmount::from_near("0.2").expect("valid amount")
+    );
+    assert_eq!(
+        NearAmount::from_yocto(1_500_000_000_000_000_000_000_000),
+        NearAmount::from_near("1.5").expect("valid amount")
+    );
+    assert!(NearAmount::from_near(".").is_err());
+    assert!(NearAmount::from_near("0.0000000000000000000000001").is_err());
+}
+
+#[test]
+fn test_near_amount_serde() {
+    let amount = NearAmount::from_yocto(42);
+    let json = serde_json::to_string(&amount).expect("serializable");
+    assert_eq!("\"42\"", json);
+
+    let parsed: NearAmount = serde_json::from_str(&json).expect("deserializable");
+    assert_eq!(amount, parsed);
+    assert!(serde_json::from_str::<NearAmount>("\"4x\"").is_err());
+}
+
+#[test]
+fn test_near_amount_basis_points() {
+    let amount = NearAmount::from_whole_near(10);
+    assert_eq!(
+        NearAmount::from_near("2.5").unwrap(),
+        amount.basis_points(2_500)
+    );
+    assert_eq!(amount, amount.basis_points(20_000));
+    assert_eq!(
+        NearAmount::default(),
+        NearAmount::from_yocto(9).basis_points(1)
+    );
+    assert_eq!(
+        NearAmount::default(),
+        NearAmount::from_whole_near(1).saturating_sub(amount)
+    );
+}
+
+#[test]
+fn test_near_amount_to_near_string() {
+    assert_eq!("0", NearAmount::default().to_near_string());
+    assert_eq!("12", NearAmount::from_whole_near(12).to_near_string());
+    assert_eq!(
+        "2.5",
+        NearAmount::from_near("2.5").unwrap().to_near_string()
+    );
+    assert_eq!(
+        "0.000000000000000000000001",
+        NearAmount::from_yocto(1).to_near_string()
+    );
+}
+
+#[test]
+fn test_parse_basis_points() {
+    assert_eq!(Some(2_500), parse_basis_points("25"));
+    assert_eq!(Some(1_250), parse_basis_points("12.5"));
+    assert_eq!(Some(1_234), parse_basis_points("12.34"));
+    assert_eq!(Some(50), parse_basis_points(".5"));
+    assert_eq!(Some(10_000), parse_basis_points("100."));
+    assert_eq!(None, parse_basis_points("12.345"));
+    assert_eq!(None, parse_basis_points("-5"));
+    assert_eq!(None, parse_basis_points("1e2"));
+    assert_eq!(None, parse_basis_points("."));
+    assert_eq!(None, parse_basis_points(""));
+}
@@ -0,0 +1,97 @@
+use gql_api::{
+    auth::Role,
+    db::{
+        models::DbNotification,
+        sql::{
+            db_count_unread_notifications, db_get_notifications_by_user_id, db_insert_notification,
+            db_mark_notification_read,
+        },
+    },
+    gql::models::NotificationKind,
+};
+
+mod common;
+
+#[test]
+fn test_notification_kind() {
+    for kind in [
+        NotificationKind::WalletCreated,
+        NotificationKind::TicketsMinted,
+        NotificationKind::EventReminder,
+    ] {
+        assert_eq!(
+            kind,
+            NotificationKind::try_from(i16::from(kind)).expect("a known kind")
+        );
+    }
+    assert!(NotificationKind::try_from(-1).is_err());
+}
+
+#[tokio::test]
+async fn test_notifications() {
+    let db_client = common::connect().await;
+    let user = common::create_user(&db_client, Role::Buyer).await;
+
+    let wallet_created = DbNotification::new(
+        user,
+        NotificationKind::WalletCreated,
+        "Your wallet was created".to_string(),
+        Some(serde_json::json!({ "walletId": "wallet" })),
+    );
+    let reminder = DbNotification::new(
+        user,
+        NotificationKind::EventReminder,
+        "Concert starts at 20:00".to_string(),
+        None,
+    );
+    for db_notification in [&wallet_created, &reminder] {
+        db_insert_notification(&db_client, db_notification)
+            .await
+            .expect("failed to insert notification");
+    }
+    assert_eq!(
+        2,
+        db_count_unread_notifications(&db_client, &user)
+            .await
+            .expect("failed to count notifications")
+    );
+
+    // only its recipient reads a notification
+    assert!(db_mark_notification_read(
+        &db_client,
+        &reminder.id,
+        &common::create_user(&db_client, Role::Buyer).await
+    )
+    .await
+    .expect("failed to mark notification read")
+    .is_none());
+    let read = db_mark_notification_read(&db_client, &reminder.id, &user)
+        .await
+        .expect("failed to mark notification read")
+        .expect("notification should be read");
+    assert!(read.read_at.is_some());
+    // reading it again keeps when it was first read
+    let read_again = db_mark_notification_read(&db_client, &reminder.id, &user)
+        .await
+        .expect("failed to mark notification read")
+        .expect("notification should be read");
+    assert_eq!(read.read_at, read_again.read_at);
+
+    assert_eq!(
+        1,
+        db_count_unread_notifications(&db_client, &user)
+            .await
+            .expect("failed to count notifications")
+    );
+    let unread = db_get_notifications_by_user_id(&db_client, &user, true, 10, 0)
+        .await
+        .expect("failed to get notifications");
+    assert_eq!(
+        vec![wallet_created.id],
+        unread.iter().map(|n| n.id).collect::<Vec<_>>()
+    );
+    let all = db_get_notifications_by_user_id(&db_client, &user, false, 10, 0)
+        .await
+        .expect("failed to get notifications");
+    assert_eq!(2, all.len());
+}
@@ -0,0 +1,135 @@
+use gql_api::{
+    config::{BusinessConfig, NearNetwork, SmsTemplates},
+    notifier::{Gift, LogNotifier, Notification, Receipt, Reminder, WaitlistSpot},
+};
+
+#[tokio::test]
+async fn test_log_notifier() {
+    let receipt = Receipt {
+        event_name: "Concert".to_string(),
+        ticket_name: "VIP".to_string(),
+        quantity: 2,
+        verification_code: "123456".to_string(),
+    };
+    let notifications = vec![
+        Notification::Verification {
+            code: "123456".to_string(),
+        },
+        Notification::Recovery {
+            code: "ABCDEF".to_string(),
+        },
+        Notification::Receipt(receipt.clone()),
+    ];
+
+    for notification in notifications.iter() {
+        assert_eq!(
+            Ok(()),
+            notification
+                .send(&LogNotifier::default(), "+359888123456", None)
+                .await
+        );
+    }
+
+    assert_eq!(
+        "You reserved 2 x VIP for Concert. Your verification code is: 123456",
+        SmsTemplates::default().receipt_text(&receipt)
+    );
+}
+
+#[test]
+fn test_sms_templates() {
+    let templates = SmsTemplates {
+        verification: "Votre code de vérification : {code}".to_string(),
+        recovery: "Code {code}, code {code}".to_string(),
+        receipt: "{event_name}: {quantity} x {ticket_name} ({code})".to_string(),
+        waitlist: "{ticket_name} @ {event_name}".to_string(),
+        gift: "{sender}: {quantity} x {ticket_name} @ {event_name} ({code})".to_string(),
+        reminder: "{event_name} @ {venue}, {starts_at} ({entry_time})".to_string(),
+    };
+    assert_eq!(
+        "Votre code de vérification : 123456",
+        templates.verification_text("123456")
+    );
+    assert_eq!("Code ABC, code ABC", templates.recovery_text("ABC"));
+    assert_eq!(
+        "Concert: 2 x VIP (123456)",
+        templates.receipt_text(&Receipt {
+            event_name: "Concert".to_string(),
+            ticket_name: "VIP".to_string(),
+            quantity: 2,
+            verification_code: "123456".to_string(),
+        })
+    );
+    assert_eq!(
+        "VIP @ Concert",
+        templates.waitlist_text(&WaitlistSpot {
+            event_name: "Concert".to_string(),
+            ticket_name: "VIP".to_string(),
+        })
+    );
+    assert_eq!(
+        "alice: 2 x VIP @ Concert (ABC123)",
+        templates.gift_text(&Gift {
+            sender: "alice".to_string(),
+            event_name: "Concert".to_string(),
+            ticket_name: "VIP".to_string(),
+            quantity: 2,
+            claim_code: "ABC123".to_string(),
+        })
+    );
+    assert_eq!(
+        "Concert @ Arena, 2022-06-01 20:00 (2022-06-01 19:00)",
+        templates.reminder_text(&Reminder {
+            event_name: "Concert".to_string(),
+            venue: "Arena".to_string(),
+            starts_at: "2022-06-01 20:00".to_string(),
+            entry_time: "2022-06-01 19:00".to_string(),
+        })
+    );
+}
+
+#[test]
+fn test_business_config() {
+    let business: BusinessConfig = toml::from_str(
+        r#"
+        signin-message = "Sign in to the ticket shop"
+        language = "fr"
+
+        [sms.fr]
+        recovery = "Votre code de récupération : {code}"
+        "#,
+    )
+    .expect("config should parse");
+    let templates = business.sms_templates();
+    assert_eq!(
+        "Votre code de récupération : 1",
+        templates.recovery_text("1")
+    );
+    // missing templates are the english ones
+    assert_eq!(SmsTemplates::default().verification, templates.verification);
+
+    let business = BusinessConfig {
+        language: "de".to_string(),
+        ..business
+    };
+    assert_eq!(SmsTemplates::default(), business.sms_templates());
+
+    assert_eq!("alice.testnet", NearNetwork::Testnet.account_id("alice"));
+    assert_eq!("alice.near", NearNetwork::Mainnet.account_id("alice"));
+}
+
+#[test]
+fn test_notification_serde() {
+    let notification = Notification::Verification {
+        code: "123456".to_string(),
+    };
+    let json = serde_json::to_value(&notification).expect("notification should serialize");
+    assert_eq!(
+        serde_json::json!({"kind": "verification", "code": "123456"}),
+        json
+    );
+    assert_eq!(
+        notification,
+        serde_json::from_value(json).expect("notification should deserialize")
+    );
+}
@@ -0,0 +1,228 @@
+use common::TestContextBuilder;
+use gql_api::{
+    auth::{Role, SellerStatus, UserStatus},
+    db::{models::DbUser, sql::db_insert_user},
+    fakes::StoredObject,
+    gql::{
+        mutations::{AdminMutationRoot, SellerMutationRoot},
+        quiries::{AdminQueryRoot, SellerQueryRoot},
+        schema::{AdminSchema, Context as ResourcesContext, SellerSchema},
+    },
+    policy::{policy, Operation},
+};
+use juniper::{DefaultScalarValue, EmptySubscription, ExecutionError, Value};
+
+mod common;
+
+fn seller_schema() -> SellerSchema {
+    SellerSchema::new(
+        SellerQueryRoot,
+        SellerMutationRoot,
+        EmptySubscription::new(),
+    )
+}
+
+async fn execute(
+    ctx: &ResourcesContext,
+    operation: &str,
+) -> (Value, Vec<ExecutionError<DefaultScalarValue>>) {
+    juniper::execute(
+        operation,
+        None,
+        &seller_schema(),
+        &juniper::Variables::new(),
+        ctx,
+    )
+    .await
+    .expect("invalid operation")
+}
+
+async fn execute_admin(
+    ctx: &ResourcesContext,
+    operation: &str,
+) -> (Value, Vec<ExecutionError<DefaultScalarValue>>) {
+    let schema = AdminSchema::new(AdminQueryRoot, AdminMutationRoot, EmptySubscription::new());
+    juniper::execute(operation, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid operation")
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    value.as_object_value()?.get_field_value(name)
+}
+
+fn string_field(value: &Value, name: &str) -> Option<String> {
+    field(value, name)?.as_scalar_value::<String>().cloned()
+}
+
+/// The seller status the mutation returned the user with
+fn seller_status(
+    (value, errors): (Value, Vec<ExecutionError<DefaultScalarValue>>),
+    name: &str,
+) -> Option<String> {
+    assert!(errors.is_empty(), "{:?}", errors);
+    field(&value, name).and_then(|user| string_field(user, "sellerStatus"))
+}
+
+fn is_onboarding_error(errors: &[ExecutionError<DefaultScalarValue>]) -> bool {
+    errors
+        .iter()
+        .any(|error| error.error().message() == "Seller onboarding is not approved yet")
+}
+
+#[test]
+fn test_approved_sellers_policy() {
+    let publish = policy(Operation::PublishEvent);
+    assert!(publish.allows_seller(&Role::Seller, Some(&SellerStatus::Approved)));
+    assert!(!publish.allows_seller(&Role::Seller, Some(&SellerStatus::PendingReview)));
+    assert!(!publish.allows_seller(&Role::Seller, None));
+    assert!(
+        !policy(Operation::MintNfts).allows_seller(&Role::Seller, Some(&SellerStatus::Rejected))
+    );
+    // the other seller operations do not wait for the review
+    assert!(policy(Operation::RegisterEvent)
+        .allows_seller(&Role::Seller, Some(&SellerStatus::Onboarding)));
+}
+
+#[tokio::test]
+async fn test_seller_onboarding() {
+    let resources = TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+
+    let db_seller = DbUser::new(
+        uuid::Uuid::new_v4(),
+        None,
+        common::gen_string(20),
+        None,
+        None,
+        None,
+        None,
+        Role::Seller,
+        common::gen_string(20),
+        "0".to_string(),
+        UserStatus::PhoneVerified,
+    );
+    assert_eq!(Some(SellerStatus::Onboarding), db_seller.seller_status);
+    db_insert_user(db_client, &db_seller)
+        .await
+        .expect("unable to create seller");
+    let seller_ctx = resources.ctx.for_user(Some(db_seller.id));
+    let admin_id = common::create_user(db_client, Role::Admin).await;
+    let admin_ctx = resources.ctx.for_user(Some(admin_id));
+
+    let publish = format!(
+        r#"mutation {{ publishEvent(id: "{}") {{ id }} }}"#,
+        uuid::Uuid::new_v4()
+    );
+    let (_, errors) = execute(&seller_ctx, &publish).await;
+    assert!(is_onboarding_error(&errors));
+
+    // nothing to review yet
+    let submit = "mutation { submitSellerOnboarding { sellerStatus } }";
+    let (_, errors) = execute(&seller_ctx, submit).await;
+    assert!(!errors.is_empty());
+
+    let (value, errors) = execute(
+        &seller_ctx,
+        r#"mutation { createSellerDocumentUploadUrl(newSellerDocument: { kind: IDENTITY, contentType: "application/pdf" }) { assetId } }"#,
+    )
+    .await;
+    assert!(errors.is_empty());
+    let document_id = field(&value, "createSellerDocumentUploadUrl")
+        .and_then(|upload_url| string_field(upload_url, "assetId"))
+        .expect("a document id");
+    let confirm = format!(
+        r#"mutation {{ confirmSellerDocument(documentId: "{}") {{ isConfirmed }} }}"#,
+        document_id
+    );
+
+    // confirmed once uploaded
+    let (_, errors) = execute(&seller_ctx, &confirm).await;
+    assert!(!errors.is_empty());
+    resources.storage.insert(
+        format!("sellers/{}/{}.pdf", db_seller.id, document_id),
+        StoredObject {
+            content_type: Some("application/pdf".to_string()),
+            content: b"%PDF".to_vec(),
+        },
+    );
+    let (value, errors) = execute(&seller_ctx, &confirm).await;
+    assert!(errors.is_empty());
+    assert_eq!(
+        Some(&true),
+        field(&value, "confirmSellerDocument")
+            .and_then(|document| field(document, "isConfirmed"))
+            .and_then(|is_confirmed| is_confirmed.as_scalar_value::<bool>())
+    );
+
+    assert_eq!(
+        Some("pending_review".to_string()),
+        seller_status(execute(&seller_ctx, submit).await, "submitSellerOnboarding")
+    );
+    // sellers do not review themselves, the seller schema has no reviews at all
+    let approve = format!(
+        r#"mutation {{ approveSeller(userId: "{}") {{ sellerStatus }} }}"#,
+        db_seller.id
+    );
+    assert!(juniper::execute(
+        &approve,
+        None,
+        &seller_schema(),
+        &juniper::Variables::new(),
+        &seller_ctx
+    )
+    .await
+    .is_err());
+    let (_, errors) = execute_admin(&seller_ctx, &approve).await;
+    assert!(!errors.is_empty());
+
+    let (value, errors) = execute_admin(
+        &admin_ctx,
+        &format!(
+            r#"query {{ sellerDocuments(userId: "{}") {{ kind downloadUrl }} }}"#,
+            db_seller.id
+        ),
+    )
+    .await;
+    assert!(errors.is_empty());
+    let documents = field(&value, "sellerDocuments")
+        .and_then(|documents| documents.as_list_value())
+        .expect("a list of documents");
+    assert_eq!(1, documents.len());
+    assert!(string_field(&documents[0], "downloadUrl").is_some());
+
+    let reject = format!(
+        r#"mutation {{ rejectSeller(userId: "{}", reason: " Blurry photo ") {{ sellerStatus sellerRejectionReason }} }}"#,
+        db_seller.id
+    );
+    let (value, errors) = execute_admin(&admin_ctx, &reject).await;
+    assert!(errors.is_empty());
+    let rejected = field(&value, "rejectSeller").expect("a seller");
+    assert_eq!(
+        Some("rejected".to_string()),
+        string_field(rejected, "sellerStatus")
+    );
+    assert_eq!(
+        Some("Blurry photo".to_string()),
+        string_field(rejected, "sellerRejectionReason")
+    );
+    let (_, errors) = execute(&seller_ctx, &publish).await;
+    assert!(is_onboarding_error(&errors));
+
+    // resubmitted and approved, only once
+    assert_eq!(
+        Some("pending_review".to_string()),
+        seller_status(execute(&seller_ctx, submit).await, "submitSellerOnboarding")
+    );
+    assert_eq!(
+        Some("approved".to_string()),
+        seller_status(execute_admin(&admin_ctx, &approve).await, "approveSeller")
+    );
+    let (_, errors) = execute_admin(&admin_ctx, &approve).await;
+    assert!(!errors.is_empty());
+
+    // the unknown event is all that stops the approved seller now
+    let (_, errors) = execute(&seller_ctx, &publish).await;
+    assert!(!errors.is_empty());
+    assert!(!is_onboarding_error(&errors));
+}
@@ -0,0 +1,115 @@
+use gql_api::{
+    db::models::{DbEvent, DbTicket},
+    gql::models::NewTicket,
+    http::{
+        models::{
+            CheckUsernameResponse, ErrorResponse, FieldError, SigninChallengeResponse, Ticket,
+        },
+        openapi::{components, openapi_document, ApiSchema, API_ROUTES},
+    },
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// Collects every `$ref` of the document
+fn refs(value: &Value, found: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => found.push(reference.clone()),
+                    _ => refs(value, found),
+                }
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|value| refs(value, found)),
+        _ => {}
+    }
+}
+
+/// The schema of the model describes every field of its serialization
+fn assert_schema_matches<T: ApiSchema + Serialize>(model: &T) {
+    let serialized = serde_json::to_value(model).expect("model should serialize");
+    let fields: BTreeSet<&String> = serialized.as_object().unwrap().keys().collect();
+    let schema = T::schema();
+    let properties: BTreeSet<&String> = schema["properties"].as_object().unwrap().keys().collect();
+    assert_eq!(fields, properties, "schema of {}", T::NAME);
+}
+
+#[test]
+fn test_openapi_refs_resolve() {
+    let document = openapi_document();
+    let schemas = components();
+
+    let mut found = vec![];
+    refs(&document, &mut found);
+    assert!(!found.is_empty());
+    for reference in found {
+        let name = reference
+            .strip_prefix("#/components/schemas/")
+            .expect("refs should point to the components");
+        assert!(schemas.contains_key(name), "unknown schema {}", name);
+    }
+}
+
+#[test]
+fn test_openapi_paths() {
+    let document = openapi_document();
+    assert_eq!("3.0.3", document["openapi"]);
+
+    for route in API_ROUTES {
+        let operation = &document["paths"][route.path][route.method];
+        assert_eq!(route.summary, operation["summary"]);
+        assert_eq!(
+            route.operation.is_some(),
+            operation.get("security").is_some()
+        );
+    }
+
+    let signin = &document["paths"]["/api/v1/{role}/signin"]["post"];
+    assert_eq!("role", signin["parameters"][0]["name"]);
+    let attendees = &document["paths"]["/api/v1/seller/events/{event_id}/attendees.csv"]["get"];
+    assert_eq!("export_attendees", attendees["operationId"]);
+    assert!(attendees["responses"]["200"]["content"]["text/csv"].is_object());
+    let ticket = &document["paths"]["/api/v1/buyer/tickets/{reservation_id}.pdf"]["get"];
+    assert_eq!("reservation_id", ticket["parameters"][0]["name"]);
+    assert!(ticket["responses"]["200"]["content"]["application/pdf"].is_object());
+}
+
+#[test]
+fn test_openapi_schemas_match_models() {
+    assert_schema_matches(&CheckUsernameResponse { available: true });
+    assert_schema_matches(&SigninChallengeResponse {
+        nonce: "nonce".to_string(),
+        message: "message".to_string(),
+        expires_at: chrono::Utc::now().naive_utc(),
+    });
+    assert_schema_matches(&ErrorResponse {
+        message: "message".to_string(),
+        status: "400".to_string(),
+        errors: None,
+    });
+    assert_schema_matches(&FieldError {
+        field: "field".to_string(),
+        field_errors: vec![],
+    });
+
+    let db_event = DbEvent::new("Concert", uuid::Uuid::new_v4());
+    let db_ticket = DbTicket::new(
+        NewTicket {
+            ticket_name: "General admission".to_string(),
+            description: None,
+            price: None,
+            max_release_price: None,
+            quantity_available: None,
+            min_purchase_quantity: None,
+            max_purchase_quantity: None,
+            allow_transfers: None,
+            currency: None,
+            event_id: db_event.id,
+        },
+        &db_event,
+    );
+    assert_schema_matches(&Ticket::from(db_ticket));
+}
@@ -0,0 +1,172 @@
+use chrono::Utc;
+use gql_api::{
+    auth::Role,
+    db::{
+        models::{DbEvent, DbOrganization, DbOrganizationMember, DbTicket, DbTicketReservation},
+        sql::{
+            db_check_in_ticket_reservations, db_delete_organization_member, db_get_events,
+            db_get_organization_member, db_get_organizations_by_user_id, db_insert_event,
+            db_insert_organization, db_insert_ticket, db_reserve_ticket,
+            db_upsert_organization_member, EventsFilter,
+        },
+    },
+    gql::models::{MemberRole, NewTicket},
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_organization_members() {
+    let cfg = common::setup().await;
+    let owner = common::create_user(&cfg.client, Role::Seller).await;
+    let scanner = common::create_user(&cfg.client, Role::Seller).await;
+
+    let db_organization = db_insert_organization(
+        &cfg.client,
+        &DbOrganization::new(&common::gen_string(12), owner),
+    )
+    .await
+    .expect("failed to insert organization");
+
+    // the creator is its first owner
+    let db_owner = db_get_organization_member(&cfg.client, &db_organization.id, &owner)
+        .await
+        .expect("failed to get member")
+        .expect("creator should be a member");
+    assert_eq!(MemberRole::Owner, db_owner.member_role);
+
+    db_upsert_organization_member(
+        &cfg.client,
+        &DbOrganizationMember::new(db_organization.id, scanner, MemberRole::Scanner),
+    )
+    .await
+    .expect("failed to add member");
+    // adding a member again changes its role
+    db_upsert_organization_member(
+        &cfg.client,
+        &DbOrganizationMember::new(db_organization.id, scanner, MemberRole::Editor),
+    )
+    .await
+    .expect("failed to update member");
+    let db_member = db_get_organization_member(&cfg.client, &db_organization.id, &scanner)
+        .await
+        .expect("failed to get member")
+        .expect("user should be a member");
+    assert_eq!(MemberRole::Editor, db_member.member_role);
+
+    let organizations = db_get_organizations_by_user_id(&cfg.client, &scanner)
+        .await
+        .expect("failed to get organizations");
+    assert_eq!(
+        vec![db_organization.id],
+        organizations.iter().map(|o| o.id).collect::<Vec<_>>()
+    );
+
+    assert_eq!(
+        1,
+        db_delete_organization_member(&cfg.client, &db_organization.id, &scanner)
+            .await
+            .expect("failed to remove member")
+    );
+    assert!(db_get_organizations_by_user_id(&cfg.client, &scanner)
+        .await
+        .expect("failed to get organizations")
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_organization_events() {
+    let cfg = common::setup().await;
+    let owner = common::create_user(&cfg.client, Role::Seller).await;
+    let editor = common::create_user(&cfg.client, Role::Seller).await;
+
+    let db_organization = db_insert_organization(
+        &cfg.client,
+        &DbOrganization::new(&common::gen_string(12), owner),
+    )
+    .await
+    .expect("failed to insert organization");
+    db_upsert_organization_member(
+        &cfg.client,
+        &DbOrganizationMember::new(db_organization.id, editor, MemberRole::Editor),
+    )
+    .await
+    .expect("failed to add member");
+
+    let mut db_event = DbEvent::new(&common::gen_string(20), owner);
+    db_event.organization_id = Some(db_organization.id);
+    db_insert_event(&cfg.client, &db_event)
+        .await
+        .expect("failed to insert event");
+
+    // the members manage the organization's events
+    let managed = db_get_events(
+        &cfg.client,
+        &EventsFilter {
+            managed_by_user: Some(editor),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to list events");
+    assert_eq!(
+        vec![db_event.id],
+        managed.iter().map(|e| e.id).collect::<Vec<_>>()
+    );
+    assert_eq!(Some(db_organization.id), managed[0].organization_id);
+}
+
+#[tokio::test]
+async fn test_check_in_ticket_reservations() {
+    let cfg = common::setup().await;
+    let buyer = common::create_user(&cfg.client, Role::Buyer).await;
+    let db_ticket = DbTicket::new(
+        NewTicket {
+            ticket_name: common::gen_string(10),
+            description: None,
+            price: None,
+            max_release_price: None,
+            quantity_available: None,
+            min_purchase_quantity: None,
+            max_purchase_quantity: None,
+            allow_transfers: None,
+            currency: None,
+            event_id: cfg.event.id,
+        },
+        &cfg.event,
+    );
+    db_insert_ticket(&cfg.client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+    let verification_code = common::gen_string(6);
+    db_reserve_ticket(
+        &cfg.client,
+        &DbTicketReservation::new(
+            uuid::Uuid::new_v4(),
+            Utc::now().naive_utc(),
+            &verification_code,
+            cfg.event.id,
+            db_ticket.id,
+            buyer,
+            2,
+        ),
+    )
+    .await
+    .expect("failed to reserve ticket")
+    .expect("ticket should be reserved");
+
+    let checked_in =
+        db_check_in_ticket_reservations(&cfg.client, &cfg.event.id, &verification_code)
+            .await
+            .expect("failed to check in");
+    assert_eq!(1, checked_in.len());
+    assert!(checked_in[0].checked_in_at.is_some());
+
+    // a reservation is checked in once
+    assert!(
+        db_check_in_ticket_reservations(&cfg.client, &cfg.event.id, &verification_code)
+            .await
+            .expect("failed to check in")
+            .is_empty()
+    );
+}
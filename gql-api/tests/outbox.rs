@@ -0,0 +1,164 @@
+use gql_api::{
+    config::OutboxConfig,
+    db::{
+        models::{DbEvent, DbOutboxMessage},
+        query::{cond, select, update},
+        sql::{db_update_event_status_with_outbox, with_outbox},
+    },
+    gql::models::EventStatus,
+    jobs::{
+        models::{OutboxMessage, PusherChannel, PusherEvent},
+        outbox::{dispatch, retry_backoff_secs},
+    },
+    realtime::{outbox_message, EventUpdate},
+};
+use tokio_postgres::Client;
+
+mod common;
+
+async fn get_outbox_message(db_client: &Client, id: &uuid::Uuid) -> Option<DbOutboxMessage> {
+    select::<DbOutboxMessage>()
+        .filter(cond("id = {}::UUID").bind(id))
+        .fetch_opt(db_client)
+        .await
+        .expect("failed to get outbox message")
+}
+
+#[test]
+fn test_with_outbox_numbers_placeholders_after_the_change() {
+    let event_id = uuid::Uuid::new_v4();
+    let archived = true;
+    let change = update::<DbEvent>()
+        .set("archived", &archived)
+        .filter(cond("id = {}::UUID").bind(&event_id))
+        .returning();
+    let outbox = vec![
+        outbox_message(event_id, &EventUpdate::Published),
+        outbox_message(event_id, &EventUpdate::Published),
+    ];
+
+    let query = with_outbox(change, &outbox);
+    assert!(query
+        .statement
+        .starts_with("WITH changed AS (UPDATE events SET"));
+    assert!(query.statement.contains("($3::UUID, $4::TIMESTAMP"));
+    assert!(query.statement.contains("($10::UUID, $11::TIMESTAMP"));
+    assert!(query
+        .statement
+        .contains("WHERE EXISTS (SELECT 1 FROM changed)"));
+    assert!(query.statement.ends_with("SELECT * FROM changed"));
+    assert_eq!(2 + 2 * 7, query.params.len());
+
+    // no messages leave the change as it is
+    let change = update::<DbEvent>()
+        .set("archived", &archived)
+        .filter(cond("id = {}::UUID").bind(&event_id))
+        .returning();
+    let statement = change.statement.to_string();
+    assert_eq!(statement, with_outbox(change, &[]).statement);
+}
+
+#[test]
+fn test_retry_backoff() {
+    let config = OutboxConfig {
+        retry_backoff_secs: 5,
+        max_retry_backoff_secs: 60,
+        ..OutboxConfig::default()
+    };
+    assert_eq!(5, retry_backoff_secs(&config, 1));
+    assert_eq!(10, retry_backoff_secs(&config, 2));
+    assert_eq!(40, retry_backoff_secs(&config, 4));
+    assert_eq!(60, retry_backoff_secs(&config, 5));
+    assert_eq!(60, retry_backoff_secs(&config, i32::MAX));
+}
+
+#[tokio::test]
+async fn test_outbox_written_with_the_change_only() {
+    let cfg = common::setup().await;
+
+    // the event is not minting, nothing changes and nothing is written
+    let db_message = outbox_message(cfg.event.id, &EventUpdate::Published);
+    let db_event = db_update_event_status_with_outbox(
+        &cfg.client,
+        &cfg.event.id,
+        EventStatus::Minting,
+        EventStatus::Final,
+        &[db_message.clone()],
+    )
+    .await
+    .expect("failed to update event status");
+    assert!(db_event.is_none());
+    assert!(get_outbox_message(&cfg.client, &db_message.id)
+        .await
+        .is_none());
+
+    let db_event = db_update_event_status_with_outbox(
+        &cfg.client,
+        &cfg.event.id,
+        EventStatus::Draft,
+        EventStatus::Minting,
+        &[db_message.clone()],
+    )
+    .await
+    .expect("failed to update event status")
+    .expect("event should be a draft");
+    assert_eq!(EventStatus::Minting, db_event.event_status);
+    let stored = get_outbox_message(&cfg.client, &db_message.id)
+        .await
+        .expect("outbox message should be written");
+    assert_eq!(
+        OutboxMessage::PusherEvent {
+            channel: PusherChannel::Event(cfg.event.id),
+            event: PusherEvent::EventPublished,
+            data: EventUpdate::Published.data(&cfg.event.id),
+        },
+        stored.message().expect("message should parse")
+    );
+}
+
+#[tokio::test]
+async fn test_dispatch() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+    let db_event = common::create_event(db_client).await;
+
+    let ticket_id = uuid::Uuid::new_v4();
+    let db_message = outbox_message(db_event.id, &EventUpdate::SoldOut { ticket_id });
+    db_update_event_status_with_outbox(
+        db_client,
+        &db_event.id,
+        EventStatus::Draft,
+        EventStatus::Minting,
+        &[db_message.clone()],
+    )
+    .await
+    .expect("failed to update event status")
+    .expect("event should be a draft");
+
+    let dispatched = dispatch(&resources.ctx, &OutboxConfig::default())
+        .await
+        .expect("failed to dispatch the outbox");
+    assert!(dispatched >= 1);
+    assert!(resources.publisher.published().iter().any(|published| {
+        published.channel == PusherChannel::Event(db_event.id)
+            && published.event == PusherEvent::TicketSoldOut
+    }));
+
+    let stored = get_outbox_message(db_client, &db_message.id)
+        .await
+        .expect("outbox message should be kept");
+    assert!(stored.dispatched_at.is_some());
+    assert_eq!(1, stored.attempts);
+
+    // delivered messages are not delivered again
+    let published = resources.publisher.published().len();
+    dispatch(&resources.ctx, &OutboxConfig::default())
+        .await
+        .expect("failed to dispatch the outbox");
+    assert!(!resources
+        .publisher
+        .published()
+        .iter()
+        .skip(published)
+        .any(|event| event.channel == PusherChannel::Event(db_event.id)));
+}
@@ -0,0 +1,120 @@
+use gql_api::{
+    config::{BreachCheckConfig, PasswordPolicyConfig},
+    error::PasswordError,
+    security::password_policy::{
+        breach_count, check_password, check_password_strength, CharacterClass,
+    },
+};
+use sha1::{Digest, Sha1};
+use warp::Filter;
+
+/// Serves the range of the leaked password's hash, and a padding entry, like the range api
+fn serve_range(leaked: &str) -> BreachCheckConfig {
+    let hash = hex::encode_upper(Sha1::digest(leaked.as_bytes()));
+    let (prefix, suffix) = hash.split_at(5);
+    let (prefix, suffix) = (prefix.to_string(), suffix.to_string());
+    let route = warp::path!("range" / String).map(move |requested: String| {
+        if requested == prefix {
+            format!("0000000000000000000000000000000000A:0\r\n{}:42\r\n", suffix)
+        } else {
+            "0000000000000000000000000000000000A:0\r\n".to_string()
+        }
+    });
+    let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    BreachCheckConfig {
+        api_url: format!("http://{}/range", addr),
+        timeout_ms: 2000,
+    }
+}
+
+#[test]
+fn test_password_strength() {
+    let config = PasswordPolicyConfig::default();
+    assert_eq!(Ok(()), check_password_strength(&config, "new-password"));
+    assert_eq!(
+        Err(PasswordError::TooShort(8)),
+        check_password_strength(&config, "pwd")
+    );
+    assert_eq!(
+        Err(PasswordError::TooLong(50)),
+        check_password_strength(&config, &"x".repeat(51))
+    );
+    // common passwords, whatever their case
+    assert_eq!(
+        Err(PasswordError::Common),
+        check_password_strength(&config, "Password123")
+    );
+
+    let config = PasswordPolicyConfig {
+        required_classes: vec![
+            CharacterClass::Lowercase,
+            CharacterClass::Uppercase,
+            CharacterClass::Digit,
+            CharacterClass::Symbol,
+        ],
+        denied: vec!["Tickets2022!".to_string()],
+        ..PasswordPolicyConfig::default()
+    };
+    assert_eq!(
+        Err(PasswordError::MissingClass(CharacterClass::Uppercase)),
+        check_password_strength(&config, "new-password-1")
+    );
+    assert_eq!(
+        Err(PasswordError::MissingClass(CharacterClass::Symbol)),
+        check_password_strength(&config, "NewPassword1")
+    );
+    assert_eq!(Ok(()), check_password_strength(&config, "New-Password-1"));
+    assert_eq!(
+        Err(PasswordError::Common),
+        check_password_strength(&config, "tickets2022!")
+    );
+    assert_eq!(
+        "password must contain an uppercase letter",
+        PasswordError::MissingClass(CharacterClass::Uppercase).to_string()
+    );
+}
+
+#[tokio::test]
+async fn test_password_breach_check() {
+    let breach_check = serve_range("correct-horse-battery");
+    assert_eq!(
+        42,
+        breach_count(&breach_check, "correct-horse-battery")
+            .await
+            .expect("a count")
+    );
+    assert_eq!(
+        0,
+        breach_count(&breach_check, "staple-horse-battery")
+            .await
+            .expect("a count")
+    );
+
+    let config = PasswordPolicyConfig {
+        breach_check: Some(breach_check),
+        ..PasswordPolicyConfig::default()
+    };
+    assert_eq!(
+        Err(PasswordError::Breached),
+        check_password(&config, "correct-horse-battery").await
+    );
+    assert_eq!(
+        Ok(()),
+        check_password(&config, "staple-horse-battery").await
+    );
+
+    // the api being down does not keep users from signing up
+    let config = PasswordPolicyConfig {
+        breach_check: Some(BreachCheckConfig {
+            api_url: "http://127.0.0.1:9/range".to_string(),
+            timeout_ms: 500,
+        }),
+        ..PasswordPolicyConfig::default()
+    };
+    assert_eq!(
+        Ok(()),
+        check_password(&config, "correct-horse-battery").await
+    );
+}
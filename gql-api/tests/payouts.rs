@@ -0,0 +1,214 @@
+use chrono::Utc;
+use gql_api::{
+    auth::Role,
+    db::models::{DbPayoutAccount, DbPayoutRequest, DbTicket, DbTicketReservation},
+    fakes::FakeNearClient,
+    gql::{
+        models::{NewTicket, PayoutStatus},
+        mutations::AdminMutationRoot,
+        quiries::AdminQueryRoot,
+        schema::AdminSchema,
+        validations::check_payout_wallet_id,
+    },
+    near::NearAmount,
+};
+use juniper::EmptySubscription;
+
+mod common;
+
+#[tokio::test]
+async fn test_payouts_workflow() {
+    let cfg = common::setup().await;
+    let seller = cfg.event.created_by_user;
+
+    // sell a ticket of the seller's event
+    let db_ticket = DbTicket::new(
+        NewTicket {
+            ticket_name: common::gen_string(10),
+            description: None,
+            price: Some(NearAmount::from_whole_near(10)),
+            max_release_price: None,
+            quantity_available: Some(10),
+            min_purchase_quantity: Some(1),
+            max_purchase_quantity: Some(2),
+            allow_transfers: Some(false),
+            currency: None,
+            event_id: cfg.event.id,
+        },
+        &cfg.event,
+    );
+    gql_api::db::sql::db_insert_ticket(&cfg.client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+
+    let buyer = common::create_user(&cfg.client, Role::Buyer).await;
+    let reservation = DbTicketReservation::new(
+        uuid::Uuid::new_v4(),
+        Utc::now().naive_utc(),
+        &common::gen_string(6),
+        cfg.event.id,
+        db_ticket.id,
+        buyer,
+        1,
+    );
+    gql_api::db::sql::db_insert_ticket_reservation(&cfg.client, &reservation)
+        .await
+        .expect("failed to insert reservation");
+
+    // registering again replaces the wallet
+    let account = gql_api::db::sql::db_upsert_payout_account(
+        &cfg.client,
+        &DbPayoutAccount::new("first.testnet", seller),
+    )
+    .await
+    .expect("failed to register payout account");
+    let updated = gql_api::db::sql::db_upsert_payout_account(
+        &cfg.client,
+        &DbPayoutAccount::new("second.testnet", seller),
+    )
+    .await
+    .expect("failed to update payout account");
+    assert_eq!(account.id, updated.id);
+    assert_eq!("second.testnet", updated.wallet_id);
+
+    let balance = gql_api::db::sql::db_get_payout_balance(&cfg.client, &seller)
+        .await
+        .expect("failed to get payout balance");
+    assert_eq!(NearAmount::from_whole_near(10), balance.total_sales);
+    assert_eq!(NearAmount::default(), balance.total_requested);
+
+    // a pending request counts against the balance
+    let request = DbPayoutRequest::new(NearAmount::from_whole_near(4), &updated.wallet_id, seller);
+    gql_api::db::sql::db_insert_payout_request(&cfg.client, &request)
+        .await
+        .expect("failed to insert payout request");
+    let balance = gql_api::db::sql::db_get_payout_balance(&cfg.client, &seller)
+        .await
+        .expect("failed to get payout balance");
+    assert_eq!(NearAmount::from_whole_near(6), balance.available());
+
+    // a request is approved once and then paid
+    let admin = common::create_user(&cfg.client, Role::Admin).await;
+    let approved = gql_api::db::sql::db_update_payout_request_status(
+        &cfg.client,
+        &request.id,
+        PayoutStatus::Pending,
+        PayoutStatus::Approved,
+        Some(&admin),
+    )
+    .await
+    .expect("failed to approve payout request")
+    .expect("payout request should be pending");
+    assert_eq!(Some(admin), approved.reviewed_by);
+
+    let approved_twice = gql_api::db::sql::db_update_payout_request_status(
+        &cfg.client,
+        &request.id,
+        PayoutStatus::Pending,
+        PayoutStatus::Approved,
+        Some(&admin),
+    )
+    .await
+    .expect("failed to approve payout request");
+    assert!(approved_twice.is_none());
+
+    let tx_hash = common::gen_string(20);
+    let paid = gql_api::db::sql::db_complete_payout_request(&cfg.client, &request.id, &tx_hash)
+        .await
+        .expect("failed to complete payout request");
+    assert_eq!(PayoutStatus::Paid, paid.payout_status);
+    assert_eq!(Some(tx_hash), paid.tx_hash);
+
+    // rejected requests free the balance again
+    let request = DbPayoutRequest::new(NearAmount::from_whole_near(6), &updated.wallet_id, seller);
+    gql_api::db::sql::db_insert_payout_request(&cfg.client, &request)
+        .await
+        .expect("failed to insert payout request");
+    gql_api::db::sql::db_update_payout_request_status(
+        &cfg.client,
+        &request.id,
+        PayoutStatus::Pending,
+        PayoutStatus::Rejected,
+        Some(&admin),
+    )
+    .await
+    .expect("failed to reject payout request")
+    .expect("payout request should be pending");
+
+    let balance = gql_api::db::sql::db_get_payout_balance(&cfg.client, &seller)
+        .await
+        .expect("failed to get payout balance");
+    assert_eq!(NearAmount::from_whole_near(4), balance.total_requested);
+    assert_eq!(NearAmount::from_whole_near(6), balance.available());
+
+    let requests = gql_api::db::sql::db_get_payout_requests(
+        &cfg.client,
+        &Some(seller),
+        Some(PayoutStatus::Rejected),
+    )
+    .await
+    .expect("failed to list payout requests");
+    assert_eq!(1, requests.len());
+    assert_eq!(request.id, requests[0].id);
+}
+
+#[tokio::test]
+async fn test_failed_payout_is_not_paid_twice() {
+    let near_client = FakeNearClient {
+        is_funding: false,
+        ..FakeNearClient::default()
+    };
+    let resources = common::TestContextBuilder::new()
+        .near_client(near_client.clone())
+        .build()
+        .await;
+    let db_client = &resources.ctx.db_client;
+    let seller = common::create_user(db_client, Role::Seller).await;
+    let admin = common::create_user(db_client, Role::Admin).await;
+    let request = DbPayoutRequest::new(NearAmount::from_whole_near(2), "seller.testnet", seller);
+    gql_api::db::sql::db_insert_payout_request(db_client, &request)
+        .await
+        .expect("failed to insert payout request");
+
+    let schema = AdminSchema::new(AdminQueryRoot, AdminMutationRoot, EmptySubscription::new());
+    let admin_ctx = resources.ctx.for_user(Some(admin));
+    let approve = format!(
+        r#"mutation {{ approvePayout(id: "{}") {{ id }} }}"#,
+        request.id
+    );
+    for _ in 0..2 {
+        let (_, errors) = juniper::execute(
+            &approve,
+            None,
+            &schema,
+            &juniper::Variables::new(),
+            &admin_ctx,
+        )
+        .await
+        .expect("invalid operation");
+        assert_eq!(1, errors.len());
+    }
+
+    // the transfer is only tried once and the request does not go back in the queue
+    assert_eq!(vec!["fund_account"], near_client.calls());
+    let failed = gql_api::db::sql::db_get_payout_request_by_id(db_client, &request.id)
+        .await
+        .expect("failed to get payout request");
+    assert_eq!(PayoutStatus::Failed, failed.payout_status);
+    assert_eq!(Some(admin), failed.reviewed_by);
+    assert!(failed.tx_hash.is_none());
+
+    // its amount stays requested until an admin checks the chain
+    let balance = gql_api::db::sql::db_get_payout_balance(db_client, &seller)
+        .await
+        .expect("failed to get payout balance");
+    assert_eq!(NearAmount::from_whole_near(2), balance.total_requested);
+}
+
+#[test]
+fn test_check_payout_wallet_id() {
+    assert!(check_payout_wallet_id("seller.testnet").is_ok());
+    assert!(check_payout_wallet_id(&"a".repeat(64)).is_ok());
+    assert!(check_payout_wallet_id("system").is_err());
+    assert!(check_payout_wallet_id("Not An Account").is_err());
+}
@@ -0,0 +1,118 @@
+use gql_api::gql::{
+    error::ErrorCode,
+    persisted::{query_hash, resolve_request, PersistedGraphQLRequest, PersistedQueryError},
+};
+
+mod common;
+
+fn request(query: Option<&str>, sha256_hash: Option<&str>) -> PersistedGraphQLRequest {
+    let mut json = serde_json::json!({ "operationName": "Events" });
+    if let Some(query) = query {
+        json["query"] = serde_json::json!(query);
+    }
+    if let Some(sha256_hash) = sha256_hash {
+        json["extensions"] = serde_json::json!({
+            "persistedQuery": { "version": 1, "sha256Hash": sha256_hash }
+        });
+    }
+    serde_json::from_value(json).expect("invalid request")
+}
+
+#[test]
+fn test_query_hash() {
+    assert_eq!(
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        query_hash("abc")
+    );
+}
+
+#[tokio::test]
+async fn test_persisted_queries() {
+    let db_client = common::connect().await;
+    let query = format!(
+        "query Events {{ events(limit: {}) {{ id }} }}",
+        rand_limit()
+    );
+    let sha256_hash = query_hash(&query);
+
+    // a plain query, nothing stored
+    let req = resolve_request(&db_client, request(Some(&query), None), false)
+        .await
+        .expect("failed to resolve request");
+    assert_eq!(query, req.query);
+
+    // the hash alone is not known yet, the client sends the query along
+    let e = resolve_request(&db_client, request(None, Some(&sha256_hash)), false)
+        .await
+        .unwrap_err();
+    assert!(matches!(e, PersistedQueryError::NotFound));
+    assert_eq!(ErrorCode::PersistedQueryNotFound, e.code());
+    assert_eq!("PersistedQueryNotFound", e.to_string());
+
+    let e = resolve_request(
+        &db_client,
+        request(Some("{ events { id } }"), Some(&sha256_hash)),
+        false,
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(e, PersistedQueryError::HashMismatch));
+
+    resolve_request(&db_client, request(Some(&query), Some(&sha256_hash)), false)
+        .await
+        .expect("failed to persist query");
+
+    // then the hash alone is enough, even when only persisted queries are allowed
+    let req = resolve_request(
+        &db_client,
+        request(None, Some(&sha256_hash.to_uppercase())),
+        true,
+    )
+    .await
+    .expect("failed to resolve persisted query");
+    assert_eq!(query, req.query);
+    assert_eq!(Some("Events"), req.operation_name());
+}
+
+#[tokio::test]
+async fn test_persisted_queries_only() {
+    let db_client = common::connect().await;
+    let query = format!(
+        "query Events {{ events(limit: {}) {{ id }} }}",
+        rand_limit()
+    );
+    let sha256_hash = query_hash(&query);
+
+    let e = resolve_request(&db_client, request(Some(&query), None), true)
+        .await
+        .unwrap_err();
+    assert!(matches!(e, PersistedQueryError::Required));
+    assert_eq!(ErrorCode::Forbidden, e.code());
+
+    // new queries are not stored either
+    let e = resolve_request(&db_client, request(Some(&query), Some(&sha256_hash)), true)
+        .await
+        .unwrap_err();
+    assert!(matches!(e, PersistedQueryError::Required));
+    let e = resolve_request(&db_client, request(None, Some(&sha256_hash)), true)
+        .await
+        .unwrap_err();
+    assert!(matches!(e, PersistedQueryError::NotFound));
+
+    let mut req = request(None, Some(&sha256_hash));
+    if let Some(persisted_query) = req
+        .extensions
+        .as_mut()
+        .and_then(|extensions| extensions.persisted_query.as_mut())
+    {
+        persisted_query.version = 2;
+    }
+    let e = resolve_request(&db_client, req, false).await.unwrap_err();
+    assert_eq!(ErrorCode::PersistedQueryNotSupported, e.code());
+}
+
+/// a query not persisted by an earlier run
+fn rand_limit() -> u64 {
+    use rand::Rng;
+    rand::thread_rng().gen()
+}
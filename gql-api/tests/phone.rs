@@ -0,0 +1,56 @@
+use gql_api::{auth::Role, error::UserError, phone::normalize_phone_number};
+
+mod common;
+
+#[test]
+fn test_normalize_phone_number() {
+    let expected = Ok("+4915123456789".to_string());
+    assert_eq!(expected, normalize_phone_number("+4915123456789"));
+    assert_eq!(expected, normalize_phone_number("+49 1512 3456789"));
+    assert_eq!(expected, normalize_phone_number("0049 1512 3456789"));
+    assert_eq!(expected, normalize_phone_number(" 0049-1512-345.6789 "));
+    assert_eq!(
+        Ok("+16502530000".to_string()),
+        normalize_phone_number("+1 (650) 253-0000")
+    );
+
+    for invalid in ["", "12345", "015123456789", "+49 123", "+49 phone"] {
+        assert_eq!(
+            Err(UserError::InvalidPhoneNumber(invalid.to_string())),
+            normalize_phone_number(invalid)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_backfill_phone_numbers() {
+    let cfg = common::setup().await;
+    let buyer = common::create_user(&cfg.client, Role::Buyer).await;
+
+    // a random German mobile number, submitted with the "00" prefix
+    let subscriber: String = common::gen_string(40)
+        .chars()
+        .filter(char::is_ascii_digit)
+        .chain(std::iter::repeat('7'))
+        .take(7)
+        .collect();
+    gql_api::db::sql::db_update_user_phone_number(
+        &cfg.client,
+        &buyer,
+        &format!("0049 1512 {}", subscriber),
+    )
+    .await
+    .expect("failed to set phone number");
+
+    let report = gql_api::phone::backfill_phone_numbers(&cfg.client)
+        .await
+        .expect("failed to backfill phone numbers");
+    assert!(report.normalized + report.duplicates >= 1);
+
+    let db_user = gql_api::db::sql::db_get_user_by_id(&cfg.client, &buyer)
+        .await
+        .expect("failed to get user");
+    if report.duplicates == 0 {
+        assert_eq!(Some(format!("+491512{}", subscriber)), db_user.phone_number);
+    }
+}
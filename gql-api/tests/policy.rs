@@ -0,0 +1,199 @@
+use gql_api::{
+    auth::{Role, UserStatus},
+    db::models::{DbEvent, DbOrganizationMember},
+    gql::models::MemberRole,
+    policy::{can_access_event, event_policy, policy, EventAccess, Operation},
+};
+use std::collections::HashSet;
+
+#[test]
+fn test_every_operation_has_a_policy() {
+    let mut names = HashSet::new();
+    for operation in Operation::ALL {
+        let policy = policy(operation);
+        assert!(!policy.roles.is_empty(), "{} allows no role", operation);
+        assert!(
+            !policy.statuses.is_empty(),
+            "{} allows no status",
+            operation
+        );
+        assert!(
+            names.insert(operation.to_string()),
+            "{} is duplicated",
+            operation
+        );
+    }
+}
+
+#[test]
+fn test_admin_operations() {
+    for operation in [
+        Operation::PurgeEvent,
+        Operation::ApprovePayout,
+        Operation::RejectPayout,
+        Operation::CreateCategory,
+        Operation::AuditLogs,
+        Operation::PayoutRequests,
+    ] {
+        let policy = policy(operation);
+        assert!(policy.allows(&Role::Admin, &UserStatus::Unverified));
+        assert!(policy.allows(&Role::SuperAdmin, &UserStatus::PhoneVerified));
+        assert!(!policy.allows_role(&Role::Seller));
+        assert!(!policy.allows_role(&Role::Buyer));
+    }
+}
+
+#[test]
+fn test_role_scoped_operations() {
+    assert!(policy(Operation::MintNfts).allows_role(&Role::Seller));
+    assert!(!policy(Operation::MintNfts).allows_role(&Role::Admin));
+    assert!(policy(Operation::PublishEvent).allows_role(&Role::Seller));
+    assert!(!policy(Operation::PublishEvent).allows_role(&Role::Buyer));
+    assert!(policy(Operation::TransferTicket).allows_role(&Role::Buyer));
+    assert!(!policy(Operation::TransferTicket).allows_role(&Role::Seller));
+    assert!(policy(Operation::RotateWalletSecret).allows_role(&Role::Buyer));
+    assert!(!policy(Operation::RotateWalletSecret).allows_role(&Role::Seller));
+    assert!(policy(Operation::JoinWaitlist).allows_role(&Role::Buyer));
+    assert!(!policy(Operation::JoinWaitlist).allows_role(&Role::Seller));
+    assert!(policy(Operation::LeaveWaitlist).allows_role(&Role::Buyer));
+    assert!(policy(Operation::ImpersonateUser).allows_role(&Role::SuperAdmin));
+    assert!(!policy(Operation::ImpersonateUser).allows_role(&Role::Admin));
+    assert!(!policy(Operation::RevokeImpersonation).allows_role(&Role::Admin));
+    assert!(policy(Operation::SigninWithPassword).allows_role(&Role::Admin));
+    assert!(!policy(Operation::Signin).allows_role(&Role::Admin));
+    assert!(!policy(Operation::BuyerSignup).allows_role(&Role::Seller));
+    assert!(policy(Operation::CheckInTickets).allows_role(&Role::Seller));
+    assert!(!policy(Operation::CheckInTickets).allows_role(&Role::Buyer));
+    assert!(policy(Operation::AddOrganizationMember).allows_role(&Role::Seller));
+    for role in [Role::Admin, Role::Buyer, Role::Seller, Role::SuperAdmin] {
+        assert!(policy(Operation::PrivateGraphql).allows_role(&role));
+    }
+}
+
+#[test]
+fn test_owner_only_operations() {
+    for operation in Operation::ALL {
+        let owner_only = matches!(
+            operation,
+            Operation::ChangePassword
+                | Operation::DeleteMyAccount
+                | Operation::UpdateProfile
+                | Operation::EnrollTotp
+                | Operation::ConfirmTotp
+                | Operation::RotateWalletSecret
+                | Operation::ExportWallet
+        );
+        assert_eq!(
+            owner_only,
+            policy(operation).owner_only,
+            "{} has the wrong impersonation policy",
+            operation
+        );
+    }
+}
+
+#[test]
+fn test_unverified_sellers() {
+    // unverified sellers may only verify their phone
+    for operation in [
+        Operation::Signin,
+        Operation::SendSellerPhoneCode,
+        Operation::VerifySellerPhone,
+    ] {
+        assert!(!policy(operation).requires_status());
+        assert!(policy(operation).allows(&Role::Seller, &UserStatus::Unverified));
+    }
+    for operation in [
+        Operation::SellerGraphql,
+        Operation::RegisterEvent,
+        Operation::PublishEvent,
+        Operation::MintNfts,
+    ] {
+        assert!(policy(operation).requires_status());
+        assert!(!policy(operation).allows(&Role::Seller, &UserStatus::Unverified));
+        assert!(policy(operation).allows(&Role::Seller, &UserStatus::PhoneVerified));
+    }
+}
+
+#[test]
+fn test_role_schemas() {
+    // every role has its own schema, the others do not reach it
+    for (operation, roles) in [
+        (Operation::SellerGraphql, vec![Role::Seller]),
+        (Operation::BuyerGraphql, vec![Role::Buyer]),
+        (Operation::AdminGraphql, vec![Role::Admin, Role::SuperAdmin]),
+    ] {
+        for role in [Role::Admin, Role::Buyer, Role::Seller, Role::SuperAdmin] {
+            assert_eq!(
+                roles.contains(&role),
+                policy(operation).allows_role(&role),
+                "{} for {:?}",
+                operation,
+                role
+            );
+        }
+    }
+}
+
+#[test]
+fn test_event_access() {
+    assert!(event_policy(EventAccess::Edit).contains(&MemberRole::Editor));
+    assert!(!event_policy(EventAccess::Edit).contains(&MemberRole::Scanner));
+    assert!(event_policy(EventAccess::CheckIn).contains(&MemberRole::Scanner));
+
+    let creator = uuid::Uuid::new_v4();
+    let member = uuid::Uuid::new_v4();
+    let organization_id = uuid::Uuid::new_v4();
+    let mut db_event = DbEvent::new("access", creator);
+
+    // the creator always has access, members only to their organization's events
+    let scanner = DbOrganizationMember::new(organization_id, member, MemberRole::Scanner);
+    assert!(can_access_event(
+        &creator,
+        &db_event,
+        None,
+        EventAccess::Edit
+    ));
+    assert!(!can_access_event(
+        &member,
+        &db_event,
+        Some(&scanner),
+        EventAccess::CheckIn
+    ));
+
+    db_event.organization_id = Some(organization_id);
+    assert!(can_access_event(
+        &member,
+        &db_event,
+        Some(&scanner),
+        EventAccess::CheckIn
+    ));
+    assert!(!can_access_event(
+        &member,
+        &db_event,
+        Some(&scanner),
+        EventAccess::Edit
+    ));
+    let editor = DbOrganizationMember::new(organization_id, member, MemberRole::Editor);
+    assert!(can_access_event(
+        &member,
+        &db_event,
+        Some(&editor),
+        EventAccess::Edit
+    ));
+
+    // a membership of another organization or user does not count
+    let other = DbOrganizationMember::new(uuid::Uuid::new_v4(), member, MemberRole::Owner);
+    assert!(!can_access_event(
+        &member,
+        &db_event,
+        Some(&other),
+        EventAccess::Edit
+    ));
+    assert!(!can_access_event(
+        &creator,
+        &DbEvent::new("other", member),
+        Some(&editor),
+        EventAccess::Edit
+    ));
+}
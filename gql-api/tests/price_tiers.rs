@@ -0,0 +1,269 @@
+use chrono::{Duration, Utc};
+use common::TestContextBuilder;
+use gql_api::{
+    db::{
+        models::{DbTicket, DbTicketPriceTier},
+        sql::{db_get_price_tiers_by_ticket_ids, db_insert_ticket, db_update_event_status},
+    },
+    gql::{
+        models::{EventStatus, NewPriceTier, NewTicket},
+        mutations::{PublicMutationRoot, SellerMutationRoot},
+        quiries::{PublicQueryRoot, SellerQueryRoot},
+        schema::{Context as ResourcesContext, PublicSchema, SellerSchema},
+        subscriptions::PublicSubscriptionRoot,
+    },
+    near::NearAmount,
+    pricing::{active_tier, claim_ticket_price, release_ticket_price, upcoming_tiers},
+};
+use juniper::{DefaultScalarValue, EmptySubscription, ExecutionError, Value};
+
+mod common;
+
+fn new_ticket(event_id: uuid::Uuid) -> NewTicket {
+    NewTicket {
+        ticket_name: common::gen_string(10),
+        description: None,
+        price: Some(NearAmount::from_whole_near(10)),
+        max_release_price: None,
+        quantity_available: Some(100),
+        min_purchase_quantity: None,
+        max_purchase_quantity: None,
+        allow_transfers: None,
+        currency: None,
+        event_id,
+    }
+}
+
+fn tier(
+    db_ticket: &DbTicket,
+    tier_name: &str,
+    starts_in_hours: i64,
+    ends_in_hours: Option<i64>,
+    quantity_cap: Option<i32>,
+) -> DbTicketPriceTier {
+    let now = Utc::now().naive_utc();
+    DbTicketPriceTier::new(
+        NewPriceTier {
+            ticket_id: db_ticket.id,
+            tier_name: tier_name.to_string(),
+            price: NearAmount::from_whole_near(5),
+            starts_at: now + Duration::hours(starts_in_hours),
+            ends_at: ends_in_hours.map(|hours| now + Duration::hours(hours)),
+            quantity_cap,
+        },
+        db_ticket,
+    )
+}
+
+async fn execute_seller(
+    ctx: &ResourcesContext,
+    mutation: &str,
+) -> (Value, Vec<ExecutionError<DefaultScalarValue>>) {
+    let schema = SellerSchema::new(
+        SellerQueryRoot,
+        SellerMutationRoot,
+        EmptySubscription::new(),
+    );
+    juniper::execute(mutation, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid mutation")
+}
+
+fn create_price_tier(
+    ticket_id: &uuid::Uuid,
+    tier_name: &str,
+    starts_at: &str,
+    ends_at: Option<&str>,
+    quantity_cap: Option<i32>,
+) -> String {
+    format!(
+        r#"mutation {{ createPriceTier(newPriceTier: {{ ticketId: "{}", tierName: "{}", price: "{}", startsAt: {}{}{} }}) {{ id quantityRemaining }} }}"#,
+        ticket_id,
+        tier_name,
+        NearAmount::from_whole_near(5),
+        starts_at,
+        ends_at
+            .map(|ends_at| format!(", endsAt: {}", ends_at))
+            .unwrap_or_default(),
+        quantity_cap
+            .map(|quantity_cap| format!(", quantityCap: {}", quantity_cap))
+            .unwrap_or_default(),
+    )
+}
+
+/// A time relative to now, as the float timestamp of the graphql scalar
+fn timestamp(hours: i64) -> String {
+    let timestamp = (Utc::now().naive_utc() + Duration::hours(hours)).timestamp();
+    format!("{}.0", timestamp)
+}
+
+#[test]
+fn test_active_tier() {
+    let event = gql_api::db::models::DbEvent::new("tiers", uuid::Uuid::new_v4());
+    let db_ticket = DbTicket::new(new_ticket(event.id), &event);
+    let now = Utc::now().naive_utc();
+
+    let mut early_bird = tier(&db_ticket, "Early bird", -2, Some(24), Some(2));
+    let regular = tier(&db_ticket, "Regular", -1, None, None);
+    let late = tier(&db_ticket, "Late", 48, None, None);
+    let ended = tier(&db_ticket, "Ended", -4, Some(-3), None);
+    let tiers = vec![ended, early_bird.clone(), regular.clone(), late.clone()];
+
+    assert_eq!(
+        Some(early_bird.id),
+        active_tier(&tiers, &now).map(|tier| tier.id)
+    );
+    assert_eq!(
+        vec![late.id],
+        upcoming_tiers(&tiers, &now)
+            .map(|tier| tier.id)
+            .collect::<Vec<_>>()
+    );
+    assert!(early_bird.has_quantity_for(2));
+    assert!(!early_bird.has_quantity_for(3));
+
+    // sold out tiers give way to the next one
+    early_bird.quantity_sold = 2;
+    assert_eq!(Some(0), early_bird.quantity_remaining());
+    let tiers = vec![early_bird, regular.clone(), late];
+    assert_eq!(
+        Some(regular.id),
+        active_tier(&tiers, &now).map(|tier| tier.id)
+    );
+    assert!(active_tier(&[], &now).is_none());
+}
+
+#[tokio::test]
+async fn test_price_tiers() {
+    let resources = TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+
+    let db_event = common::create_event(db_client).await;
+    let db_ticket = DbTicket::new(new_ticket(db_event.id), &db_event);
+    db_insert_ticket(db_client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+    let seller_ctx = resources.ctx.for_user(Some(db_event.created_by_user));
+
+    for mutation in [
+        create_price_tier(
+            &db_ticket.id,
+            "Early bird",
+            &timestamp(-2),
+            Some(&timestamp(24)),
+            Some(2),
+        ),
+        create_price_tier(&db_ticket.id, "Regular", &timestamp(-1), None, None),
+        create_price_tier(&db_ticket.id, "Late", &timestamp(48), None, None),
+    ] {
+        let (_, errors) = execute_seller(&seller_ctx, &mutation).await;
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+    // a tier ends after it starts
+    let (_, errors) = execute_seller(
+        &seller_ctx,
+        &create_price_tier(
+            &db_ticket.id,
+            "Backwards",
+            &timestamp(2),
+            Some(&timestamp(1)),
+            None,
+        ),
+    )
+    .await;
+    assert!(!errors.is_empty());
+
+    let tiers = db_get_price_tiers_by_ticket_ids(db_client, &[db_ticket.id])
+        .await
+        .expect("failed to get tiers");
+    assert_eq!(
+        vec!["Early bird", "Regular", "Late"],
+        tiers
+            .iter()
+            .map(|tier| tier.tier_name.as_str())
+            .collect::<Vec<_>>()
+    );
+
+    // the early-bird tier sells until its cap
+    let now = Utc::now().naive_utc();
+    let early_bird = claim_ticket_price(db_client, &db_ticket, 2, &now)
+        .await
+        .expect("failed to claim");
+    assert_eq!(Some(tiers[0].id), early_bird.tier_id);
+    let regular = claim_ticket_price(db_client, &db_ticket, 1, &now)
+        .await
+        .expect("failed to claim");
+    assert_eq!(Some(tiers[1].id), regular.tier_id);
+    release_ticket_price(db_client, &early_bird, 1)
+        .await
+        .expect("failed to release");
+    let released = claim_ticket_price(db_client, &db_ticket, 1, &now)
+        .await
+        .expect("failed to claim");
+    assert_eq!(Some(tiers[0].id), released.tier_id);
+    assert_eq!(Some(NearAmount::from_whole_near(5)), released.price);
+
+    // published events show their current and upcoming tiers
+    db_update_event_status(
+        db_client,
+        &db_event.id,
+        EventStatus::Draft,
+        EventStatus::Final,
+    )
+    .await
+    .expect("unable to publish event");
+    let schema = PublicSchema::new(PublicQueryRoot, PublicMutationRoot, PublicSubscriptionRoot);
+    let query = format!(
+        r#"query {{ events(id: "{}") {{ tickets {{ currentTier {{ tierName }} upcomingTiers {{ tierName }} }} }} }}"#,
+        db_event.id
+    );
+    let (value, errors) = juniper::execute(
+        &query,
+        None,
+        &schema,
+        &juniper::Variables::new(),
+        &resources.ctx,
+    )
+    .await
+    .expect("invalid query");
+    assert!(errors.is_empty());
+    let ticket = value
+        .as_object_value()
+        .and_then(|value| value.get_field_value("events"))
+        .and_then(|events| events.as_list_value())
+        .and_then(|events| events.first())
+        .and_then(|event| event.as_object_value()?.get_field_value("tickets"))
+        .and_then(|tickets| tickets.as_list_value())
+        .and_then(|tickets| tickets.first())
+        .and_then(|ticket| ticket.as_object_value())
+        .expect("a ticket");
+    let tier_name = |tier: &Value| {
+        tier.as_object_value()
+            .and_then(|tier| tier.get_field_value("tierName"))
+            .and_then(|name| name.as_scalar_value::<String>())
+            .cloned()
+    };
+    // the early-bird tier is sold out again
+    assert_eq!(
+        Some("Regular".to_string()),
+        ticket.get_field_value("currentTier").and_then(tier_name)
+    );
+    assert_eq!(
+        vec![Some("Late".to_string())],
+        ticket
+            .get_field_value("upcomingTiers")
+            .and_then(|tiers| tiers.as_list_value())
+            .expect("upcoming tiers")
+            .iter()
+            .map(tier_name)
+            .collect::<Vec<_>>()
+    );
+
+    // and no longer change their tiers
+    let (_, errors) = execute_seller(
+        &seller_ctx,
+        &create_price_tier(&db_ticket.id, "Too late", &timestamp(1), None, None),
+    )
+    .await;
+    assert!(!errors.is_empty());
+}
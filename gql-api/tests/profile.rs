@@ -0,0 +1,193 @@
+use common::TestContextBuilder;
+use gql_api::{
+    auth::{create_jwt, Role, UserStatus},
+    db::{
+        models::DbUser,
+        sql::{db_get_user_by_id, db_insert_user},
+    },
+    gql::{
+        mutations::PrivateMutationRoot,
+        quiries::PrivateQueryRoot,
+        routes::graphql_private_route,
+        schema::{Context as ResourcesContext, PrivateSchema},
+        subscriptions::PrivateSubscriptionRoot,
+    },
+    security::password::{hash_password, verify_password},
+};
+use std::{sync::Arc, time::Duration};
+
+mod common;
+
+async fn execute(ctx: &ResourcesContext, mutation: &str) -> bool {
+    let schema = PrivateSchema::new(
+        PrivateQueryRoot,
+        PrivateMutationRoot,
+        PrivateSubscriptionRoot,
+    );
+    let (_, errors) = juniper::execute(mutation, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid mutation");
+    errors.is_empty()
+}
+
+async fn insert_user(ctx: &ResourcesContext, email: Option<String>, password: &str) -> DbUser {
+    let db_user = DbUser::new(
+        uuid::Uuid::new_v4(),
+        None,
+        common::gen_string(20),
+        None,
+        email,
+        Some(hash_password(password.as_bytes()).expect("unable to hash password")),
+        None,
+        Role::Seller,
+        common::gen_string(20),
+        "0".to_string(),
+        UserStatus::PhoneVerified,
+    );
+    db_insert_user(&ctx.db_client, &db_user)
+        .await
+        .expect("unable to create user");
+    db_user
+}
+
+#[tokio::test]
+async fn test_update_profile() {
+    let resources = TestContextBuilder::new().build().await;
+    let email = format!("{}@example.com", common::gen_string(10).to_lowercase());
+    let other = insert_user(&resources.ctx, Some(email.clone()), "password").await;
+    let db_user = insert_user(&resources.ctx, None, "password").await;
+    let ctx = resources.ctx.for_user(Some(db_user.id));
+
+    // nothing to update, or invalid fields
+    assert!(
+        !execute(
+            &ctx,
+            r#"mutation { updateProfile(updateProfile: {}) { id } }"#
+        )
+        .await
+    );
+    assert!(
+        !execute(
+            &ctx,
+            r#"mutation { updateProfile(updateProfile: { email: "not-an-email" }) { id } }"#
+        )
+        .await
+    );
+
+    // the username and email of another user are taken, whatever the email's case
+    let mutation = format!(
+        r#"mutation {{ updateProfile(updateProfile: {{ username: "{}" }}) {{ id }} }}"#,
+        other.username
+    );
+    assert!(!execute(&ctx, &mutation).await);
+    let mutation = format!(
+        r#"mutation {{ updateProfile(updateProfile: {{ email: "{}" }}) {{ id }} }}"#,
+        email.to_uppercase()
+    );
+    assert!(!execute(&ctx, &mutation).await);
+
+    // only the given fields change
+    let username = common::gen_string(12);
+    let mutation = format!(
+        r#"mutation {{ updateProfile(updateProfile: {{ name: " Jane Doe ", username: "{}" }}) {{ id }} }}"#,
+        username
+    );
+    assert!(execute(&ctx, &mutation).await);
+    let updated_db_user = db_get_user_by_id(&resources.ctx.db_client, &db_user.id)
+        .await
+        .expect("unable to fetch user");
+    assert_eq!(Some("Jane Doe".to_string()), updated_db_user.name);
+    assert_eq!(username, updated_db_user.username);
+    assert_eq!(None, updated_db_user.email);
+
+    // the user's own username is not taken
+    assert!(execute(&ctx, &mutation).await);
+}
+
+#[tokio::test]
+async fn test_change_password() {
+    let resources = TestContextBuilder::new().build().await;
+    let db_user = insert_user(&resources.ctx, None, "password").await;
+    let ctx = resources.ctx.for_user(Some(db_user.id));
+
+    let change = |current_password: &str, new_password: &str| {
+        format!(
+            r#"mutation {{ changePassword(changePassword: {{ currentPassword: "{}", newPassword: "{}" }}) }}"#,
+            current_password, new_password
+        )
+    };
+
+    // a super admin impersonating the user cannot change it
+    let impersonated_ctx = resources
+        .ctx
+        .for_user(Some(db_user.id))
+        .impersonated(Some(uuid::Uuid::new_v4()));
+    assert!(!execute(&impersonated_ctx, &change("password", "new-password")).await);
+
+    // the current password must match, and the new one must differ and be long enough
+    assert!(!execute(&ctx, &change("wrong-password", "new-password")).await);
+    assert!(!execute(&ctx, &change("password", "password")).await);
+    assert!(!execute(&ctx, &change("password", "pwd")).await);
+
+    assert!(execute(&ctx, &change("password", "new-password")).await);
+    let updated_db_user = db_get_user_by_id(&resources.ctx.db_client, &db_user.id)
+        .await
+        .expect("unable to fetch user");
+    let hash = updated_db_user.password.expect("password should be set");
+    assert!(verify_password(&hash, b"new-password").expect("unable to verify password"));
+    assert!(!verify_password(&hash, b"password").expect("unable to verify password"));
+
+    // the old password no longer works
+    assert!(!execute(&ctx, &change("password", "other-password")).await);
+
+    // users without a password have none to change
+    let buyer_id = common::create_user(&resources.ctx.db_client, Role::Buyer).await;
+    let buyer_ctx = resources.ctx.for_user(Some(buyer_id));
+    assert!(!execute(&buyer_ctx, &change("password", "new-password")).await);
+}
+
+#[tokio::test]
+async fn test_change_password_rejects_older_jwts() {
+    let resources = TestContextBuilder::new().build().await;
+    let schema = Arc::new(PrivateSchema::new(
+        PrivateQueryRoot,
+        PrivateMutationRoot,
+        PrivateSubscriptionRoot,
+    ));
+    let route = graphql_private_route(resources.ctx.clone(), schema, warp::log("profile"));
+    let db_user = insert_user(&resources.ctx, None, "password").await;
+
+    let execute = |jwt: String, mutation: String| {
+        warp::test::request()
+            .method("POST")
+            .path("/api/v1/graphql/private")
+            .header("authorization", format!("Bearer {}", jwt))
+            .header("content-type", "application/json")
+            .json(&serde_json::json!({ "query": mutation }))
+            .reply(&route)
+    };
+    let is_ok = |body: &[u8]| {
+        let body: serde_json::Value = serde_json::from_slice(body).expect("invalid response");
+        body.get("errors").is_none()
+    };
+    let change = |current_password: &str, new_password: &str| {
+        format!(
+            r#"mutation {{ changePassword(changePassword: {{ currentPassword: "{}", newPassword: "{}" }}) }}"#,
+            current_password, new_password
+        )
+    };
+
+    let old_jwt = create_jwt(&db_user.id.to_string(), &Role::Seller).expect("failed to create jwt");
+    let response = execute(old_jwt.clone(), change("password", "new-password")).await;
+    assert!(is_ok(response.body()));
+
+    // the jwt issued before the change no longer works
+    let response = execute(old_jwt, change("new-password", "other-password")).await;
+    assert!(!is_ok(response.body()));
+
+    // the ones issued after it do, the issue time being in seconds
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let new_jwt = create_jwt(&db_user.id.to_string(), &Role::Seller).expect("failed to create jwt");
+    let response = execute(new_jwt, change("new-password", "other-password")).await;
+    assert!(is_ok(response.body()));
+}
@@ -0,0 +1,125 @@
+use gql_api::{
+    db::models::DbPromoCode,
+    gql::models::{DiscountType, NewPromoCode},
+    near::NearAmount,
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_promo_codes_crud() {
+    let cfg = common::setup().await;
+
+    let expected = DbPromoCode::new(
+        NewPromoCode {
+            code: common::gen_string(10),
+            discount_type: DiscountType::Percentage,
+            discount_value: "25".to_string(),
+            max_uses: Some(2),
+            valid_from: None,
+            valid_until: None,
+            event_id: cfg.event.id,
+        },
+        cfg.event.id,
+        cfg.event.created_by_user,
+    );
+
+    gql_api::db::sql::db_insert_promo_code(&cfg.client, &expected)
+        .await
+        .expect("failed to insert promo code");
+
+    let actual = gql_api::db::sql::db_get_promo_code_by_code(
+        &cfg.client,
+        &cfg.event.id,
+        &expected.code.to_lowercase(),
+    )
+    .await
+    .expect("unable to get promo code by code");
+    assert_eq!(expected.id, actual.id);
+    assert_eq!(0, actual.used_count);
+
+    let disabled =
+        gql_api::db::sql::db_update_promo_code_is_active(&cfg.client, &expected.id, false)
+            .await
+            .expect("failed to disable promo code");
+    assert!(!disabled.is_active);
+}
+
+#[tokio::test]
+async fn test_promo_codes_usage_limit() {
+    let cfg = common::setup().await;
+
+    let promo_code = DbPromoCode::new(
+        NewPromoCode {
+            code: common::gen_string(10),
+            discount_type: DiscountType::Fixed,
+            discount_value: NearAmount::from_near("1.5").unwrap().to_string(),
+            max_uses: Some(2),
+            valid_from: None,
+            valid_until: None,
+            event_id: cfg.event.id,
+        },
+        cfg.event.id,
+        cfg.event.created_by_user,
+    );
+
+    gql_api::db::sql::db_insert_promo_code(&cfg.client, &promo_code)
+        .await
+        .expect("failed to insert promo code");
+
+    let consumed = gql_api::db::sql::db_consume_promo_code(&cfg.client, &promo_code.id, 2)
+        .await
+        .expect("failed to consume promo code")
+        .expect("promo code should have uses left");
+    assert_eq!(2, consumed.used_count);
+    assert_eq!(
+        NearAmount::from_whole_near(1),
+        consumed.apply_discount(NearAmount::from_near("2.5").unwrap())
+    );
+    assert_eq!(
+        NearAmount::default(),
+        consumed.apply_discount(NearAmount::from_whole_near(1))
+    );
+
+    let exhausted = gql_api::db::sql::db_consume_promo_code(&cfg.client, &promo_code.id, 1)
+        .await
+        .expect("failed to consume promo code");
+    assert!(exhausted.is_none());
+
+    // the uses of a reservation that did not go through are given back
+    gql_api::db::sql::db_release_promo_code(&cfg.client, &promo_code.id, 1)
+        .await
+        .expect("failed to release promo code");
+    let consumed = gql_api::db::sql::db_consume_promo_code(&cfg.client, &promo_code.id, 1)
+        .await
+        .expect("failed to consume promo code")
+        .expect("promo code should have a use left");
+    assert_eq!(2, consumed.used_count);
+}
+
+#[test]
+fn test_promo_codes_percentage_discount() {
+    let promo_code = DbPromoCode::new(
+        NewPromoCode {
+            code: common::gen_string(10),
+            discount_type: DiscountType::Percentage,
+            discount_value: "12.5".to_string(),
+            max_uses: None,
+            valid_from: None,
+            valid_until: None,
+            event_id: uuid::Uuid::new_v4(),
+        },
+        uuid::Uuid::new_v4(),
+        uuid::Uuid::new_v4(),
+    );
+
+    assert_eq!(
+        NearAmount::from_near("8.75").unwrap(),
+        promo_code.apply_discount(NearAmount::from_whole_near(10))
+    );
+    // the discount of a yocto amount rounds down
+    assert_eq!(
+        NearAmount::from_yocto(8),
+        promo_code.apply_discount(NearAmount::from_yocto(9))
+    );
+}
@@ -0,0 +1,194 @@
+use common::{TestContextBuilder, PUSHER_KEY, PUSHER_SECRET};
+use gql_api::{
+    auth::{create_jwt, Role},
+    db::{models::DbSession, sql::db_insert_session, sql::sql_timestamp},
+    error::handle_rejection,
+    http::routes::pusher_auth_route,
+    realtime::{
+        event_channel, login_channel, parse_auth_channel, user_channel, AuthChannel, ChannelOwner,
+    },
+    security::pusher_auth::{valid_socket_id, PusherAuth},
+};
+use rand::Rng;
+use warp::{http::StatusCode, Filter};
+
+mod common;
+
+const SOCKET_ID: &str = "1234.1234";
+
+#[test]
+fn test_sign() {
+    let pusher_auth = PusherAuth::new(PUSHER_KEY, PUSHER_SECRET);
+    assert_eq!(
+        "278d425bdf160c739803:58df8b0c36d6982b82c3ecf6b4662e34fe8c25bba48f5369f135bf843651c3a4",
+        pusher_auth.sign(SOCKET_ID, "private-foobar", None)
+    );
+    assert_eq!(
+        "278d425bdf160c739803:fc92c2263fd5b72721e20dd1a06a900e6b3c4fbfac6cb9b098d68eb7911498a2",
+        pusher_auth.sign(
+            SOCKET_ID,
+            "presence-foobar",
+            Some(r#"{"user_id":10,"user_info":{"name":"Mr. Channel"}}"#)
+        )
+    );
+}
+
+#[test]
+fn test_valid_socket_id() {
+    assert!(valid_socket_id("1234.1234"));
+    assert!(!valid_socket_id("1234"));
+    assert!(!valid_socket_id("1234."));
+    assert!(!valid_socket_id("a.1234"));
+    assert!(!valid_socket_id("1234.1234:private-foobar"));
+}
+
+#[test]
+fn test_parse_auth_channel() {
+    let user_id = uuid::Uuid::new_v4();
+    assert_eq!(
+        Some(AuthChannel {
+            presence: false,
+            owner: ChannelOwner::User(user_id),
+        }),
+        parse_auth_channel(&user_channel(&user_id))
+    );
+    assert_eq!(
+        Some(AuthChannel {
+            presence: true,
+            owner: ChannelOwner::User(user_id),
+        }),
+        parse_auth_channel(&format!("presence-user-{}", user_id))
+    );
+    assert_eq!(
+        Some(AuthChannel {
+            presence: false,
+            owner: ChannelOwner::LoginCode("123456".to_string()),
+        }),
+        parse_auth_channel(&login_channel("123456"))
+    );
+
+    // public, unknown or malformed channels are not authorized
+    assert_eq!(None, parse_auth_channel(&event_channel(&user_id)));
+    assert_eq!(None, parse_auth_channel(&format!("user-{}", user_id)));
+    assert_eq!(None, parse_auth_channel("private-user-123"));
+    assert_eq!(None, parse_auth_channel("private-login-"));
+    assert_eq!(None, parse_auth_channel("private-login-12ab"));
+    assert_eq!(None, parse_auth_channel("private-event-123"));
+}
+
+fn auth_request(channel_name: &str, jwt: Option<&str>) -> warp::test::RequestBuilder {
+    let request = warp::test::request()
+        .method("POST")
+        .path("/api/v1/pusher/auth")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(format!(
+            "socket_id={}&channel_name={}",
+            SOCKET_ID, channel_name
+        ));
+    match jwt {
+        Some(jwt) => request.header("authorization", format!("Bearer {}", jwt)),
+        None => request,
+    }
+}
+
+#[tokio::test]
+async fn test_pusher_auth_user_channel() {
+    let resources = TestContextBuilder::new().build().await;
+    let route =
+        pusher_auth_route(resources.ctx.clone(), warp::log("pusher")).recover(handle_rejection);
+    let user_id = common::create_user(&resources.ctx.db_client, Role::Buyer).await;
+    let other_id = common::create_user(&resources.ctx.db_client, Role::Buyer).await;
+    let jwt = create_jwt(&user_id.to_string(), &Role::Buyer).expect("unable to create jwt");
+
+    let channel = user_channel(&user_id);
+    let response = auth_request(&channel, Some(&jwt)).reply(&route).await;
+    assert_eq!(StatusCode::OK, response.status());
+    let body: serde_json::Value =
+        serde_json::from_slice(response.body()).expect("body should be json");
+    assert_eq!(
+        serde_json::json!({
+            "auth": PusherAuth::new(PUSHER_KEY, PUSHER_SECRET).sign(SOCKET_ID, &channel, None)
+        }),
+        body
+    );
+
+    // the members of a presence channel are signed along
+    let channel = format!("presence-user-{}", user_id);
+    let response = auth_request(&channel, Some(&jwt)).reply(&route).await;
+    assert_eq!(StatusCode::OK, response.status());
+    let body: serde_json::Value =
+        serde_json::from_slice(response.body()).expect("body should be json");
+    let channel_data = body["channel_data"]
+        .as_str()
+        .expect("presence channels have channel data");
+    assert_eq!(
+        serde_json::json!({ "user_id": user_id }),
+        serde_json::from_str::<serde_json::Value>(channel_data).expect("channel data is json")
+    );
+    assert_eq!(
+        PusherAuth::new(PUSHER_KEY, PUSHER_SECRET).sign(SOCKET_ID, &channel, Some(channel_data)),
+        body["auth"]
+    );
+
+    // someone else's channel, or no jwt at all
+    let response = auth_request(&user_channel(&other_id), Some(&jwt))
+        .reply(&route)
+        .await;
+    assert_eq!(StatusCode::FORBIDDEN, response.status());
+    let response = auth_request(&user_channel(&user_id), None)
+        .reply(&route)
+        .await;
+    assert_eq!(StatusCode::BAD_REQUEST, response.status());
+    let response = auth_request(&event_channel(&user_id), Some(&jwt))
+        .reply(&route)
+        .await;
+    assert_eq!(StatusCode::FORBIDDEN, response.status());
+}
+
+#[tokio::test]
+async fn test_pusher_auth_login_channel() {
+    let resources = TestContextBuilder::new().build().await;
+    let route =
+        pusher_auth_route(resources.ctx.clone(), warp::log("pusher")).recover(handle_rejection);
+
+    let login_code = (0..12)
+        .map(|_| rand::thread_rng().gen_range(0..10).to_string())
+        .collect::<String>();
+    let response = auth_request(&login_channel(&login_code), None)
+        .reply(&route)
+        .await;
+    assert_eq!(StatusCode::FORBIDDEN, response.status());
+
+    let db_session = DbSession::new(
+        uuid::Uuid::new_v4(),
+        sql_timestamp(Some(60)),
+        login_code.clone(),
+        false,
+        None,
+    );
+    db_insert_session(&resources.ctx.db_client, &db_session)
+        .await
+        .expect("unable to create session");
+
+    // the client waiting for the login has no jwt yet
+    let response = auth_request(&login_channel(&login_code), None)
+        .reply(&route)
+        .await;
+    assert_eq!(StatusCode::OK, response.status());
+    let response = auth_request(&format!("presence-login-{}", login_code), None)
+        .reply(&route)
+        .await;
+    assert_eq!(StatusCode::FORBIDDEN, response.status());
+
+    let response = warp::test::request()
+        .method("POST")
+        .path("/api/v1/pusher/auth")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(format!(
+            "socket_id=abc&channel_name={}",
+            login_channel(&login_code)
+        ))
+        .reply(&route)
+        .await;
+    assert_eq!(StatusCode::BAD_REQUEST, response.status());
+}
@@ -0,0 +1,88 @@
+use gql_api::db::{
+    models::{DbCategory, DbTicket},
+    query::{cond, insert, select, update, Condition, Table},
+};
+
+#[test]
+fn test_select_numbers_placeholders_in_bind_order() {
+    let event_id = uuid::Uuid::new_v4();
+    let slug = "music".to_string();
+    let limit = 10i64;
+    let offset = 20i64;
+
+    let query = select::<DbTicket>()
+        .filter(cond("event_id = {}::UUID").bind(&event_id))
+        .filter(cond("deleted_at IS NULL"))
+        .filter(cond("ticket_slug = {}::VARCHAR").bind(&slug))
+        .order_by("created_at")
+        .limit(&limit)
+        .offset(&offset)
+        .build();
+
+    assert_eq!(
+        format!(
+            "SELECT {} FROM tickets WHERE ((event_id = $1::UUID) AND (deleted_at IS NULL)) \
+             AND (ticket_slug = $2::VARCHAR) ORDER BY created_at LIMIT $3 OFFSET $4",
+            DbTicket::fields()
+        ),
+        query.statement
+    );
+    assert_eq!(4, query.params.len());
+}
+
+#[test]
+fn test_condition_combinators() {
+    let featured = true;
+    let name = "a".to_string();
+
+    let condition = Condition::any(vec![
+        cond("is_featured = {}").bind(&featured),
+        cond("event_name = {}").bind(&name).not(),
+    ]);
+    let query = select::<DbCategory>().filter(condition).build();
+    assert!(query
+        .statement
+        .ends_with("WHERE (is_featured = $1) OR (NOT (event_name = $2))"));
+    assert_eq!(2, query.params.len());
+
+    let query = select::<DbCategory>()
+        .filter(Condition::all(vec![]))
+        .build();
+    assert!(query.statement.ends_with("WHERE TRUE"));
+}
+
+#[test]
+fn test_insert_and_update() {
+    let db_category = DbCategory::new("Live Music");
+
+    let query = insert::<DbCategory>()
+        .values(&[
+            &db_category.id,
+            &db_category.created_at,
+            &db_category.name,
+            &db_category.slug,
+        ])
+        .build();
+    assert_eq!(
+        "INSERT INTO categories (id, created_at, name, slug) VALUES ($1, $2, $3, $4)",
+        query.statement
+    );
+
+    let query = update::<DbCategory>()
+        .set("name", &db_category.name)
+        .set_expr(cond("slug = {}").bind(&db_category.slug))
+        .filter(cond("id = {}::UUID").bind(&db_category.id))
+        .build();
+    assert_eq!(
+        "UPDATE categories SET name = $1, slug = $2 WHERE id = $3::UUID",
+        query.statement
+    );
+    assert_eq!(3, query.params.len());
+}
+
+#[test]
+#[should_panic]
+fn test_insert_binds_every_field() {
+    let db_category = DbCategory::new("Live Music");
+    insert::<DbCategory>().values(&[&db_category.id]).build();
+}
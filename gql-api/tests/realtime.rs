@@ -0,0 +1,71 @@
+use gql_api::{
+    jobs::models::{JobPayload, PusherChannel, PusherEvent},
+    realtime::{broadcast, event_channel, parse_event_channel, EventUpdate},
+};
+
+mod common;
+
+#[test]
+fn test_event_channel() {
+    let event_id = uuid::Uuid::new_v4();
+    let channel = event_channel(&event_id);
+    assert_eq!(format!("event-{}", event_id), channel);
+    assert_eq!(Some(event_id), parse_event_channel(&channel));
+    assert_eq!(None, parse_event_channel(&event_id.to_string()));
+    assert_eq!(None, parse_event_channel("event-123"));
+}
+
+#[test]
+fn test_event_update_data() {
+    let event_id = uuid::Uuid::new_v4();
+    let ticket_id = uuid::Uuid::new_v4();
+
+    let update = EventUpdate::MintingComplete {
+        ticket_id,
+        tx_hash: "hash".to_string(),
+    };
+    assert_eq!(PusherEvent::MintingComplete, update.pusher_event());
+    let failed = EventUpdate::MintingFailed {
+        ticket_id,
+        tx_hash: "hash".to_string(),
+    };
+    assert_eq!(PusherEvent::MintingFailed, failed.pusher_event());
+    assert_eq!(update.data(&event_id), failed.data(&event_id));
+    assert_eq!(
+        serde_json::json!({
+            "eventId": event_id,
+            "ticketId": ticket_id,
+            "txHash": "hash",
+        }),
+        serde_json::from_str::<serde_json::Value>(&update.data(&event_id))
+            .expect("data should be json")
+    );
+    assert_eq!(
+        serde_json::json!({ "eventId": event_id }),
+        serde_json::from_str::<serde_json::Value>(&EventUpdate::Published.data(&event_id))
+            .expect("data should be json")
+    );
+}
+
+#[tokio::test]
+async fn test_broadcast() {
+    let cfg = common::setup().await;
+    let ticket_id = uuid::Uuid::new_v4();
+
+    let db_job = broadcast(
+        &cfg.client,
+        cfg.event.id,
+        EventUpdate::SoldOut { ticket_id },
+        2,
+    )
+    .await
+    .expect("failed to enqueue the broadcast");
+    assert_eq!(
+        JobPayload::PusherEvent {
+            channel: PusherChannel::Event(cfg.event.id),
+            event: PusherEvent::TicketSoldOut,
+            data: EventUpdate::SoldOut { ticket_id }.data(&cfg.event.id),
+        },
+        db_job.payload().expect("payload should parse")
+    );
+}
@@ -0,0 +1,150 @@
+use chrono::{Duration, NaiveDate};
+use gql_api::{
+    auth::Role,
+    config::RemindersConfig,
+    db::{
+        models::{DbEvent, DbEventReminder, DbTicket, DbTicketReservation},
+        sql::{
+            db_get_notifications_by_user_id, db_get_reminder_recipients, db_insert_event,
+            db_insert_event_reminder, db_insert_ticket, db_reserve_ticket,
+            db_update_user_event_reminders_opt_out, sql_timestamp,
+        },
+    },
+    gql::models::{EventStatus, NewTicket, NotificationKind},
+    jobs::reminders::{event_reminder, send_reminders},
+};
+use tokio_postgres::Client;
+
+mod common;
+
+fn new_ticket(event_id: uuid::Uuid) -> NewTicket {
+    NewTicket {
+        ticket_name: common::gen_string(10),
+        description: None,
+        price: None,
+        max_release_price: None,
+        quantity_available: None,
+        min_purchase_quantity: None,
+        max_purchase_quantity: None,
+        allow_transfers: None,
+        currency: None,
+        event_id,
+    }
+}
+
+async fn reserve(db_client: &Client, db_ticket: &DbTicket, buyer: uuid::Uuid) {
+    db_reserve_ticket(
+        db_client,
+        &DbTicketReservation::new(
+            uuid::Uuid::new_v4(),
+            sql_timestamp(None),
+            &common::gen_string(6),
+            db_ticket.event_id,
+            db_ticket.id,
+            buyer,
+            1,
+        ),
+    )
+    .await
+    .expect("failed to reserve ticket")
+    .expect("ticket should be reserved");
+}
+
+#[test]
+fn test_event_reminder() {
+    let start_date = NaiveDate::from_ymd(2022, 6, 1).and_hms(20, 0, 0);
+    let mut db_event = DbEvent {
+        start_date: Some(start_date),
+        venue_name: Some("Arena".to_string()),
+        venue_location: Some("Sofia".to_string()),
+        ..DbEvent::new("Concert", uuid::Uuid::new_v4())
+    };
+
+    let reminder = event_reminder(&db_event);
+    assert_eq!("Concert", reminder.event_name);
+    assert_eq!("Arena, Sofia", reminder.venue);
+    assert_eq!("2022-06-01 20:00", reminder.starts_at);
+    // no entry time, the doors open when the event starts
+    assert_eq!("2022-06-01 20:00", reminder.entry_time);
+
+    db_event.entry_time = Some(start_date - Duration::hours(1));
+    db_event.venue_name = None;
+    db_event.venue_location = None;
+    let reminder = event_reminder(&db_event);
+    assert_eq!("online", reminder.venue);
+    assert_eq!("2022-06-01 19:00", reminder.entry_time);
+}
+
+#[tokio::test]
+async fn test_send_reminders() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+
+    // a published event starting in two hours
+    let seller = common::create_user(db_client, Role::Seller).await;
+    let db_event = DbEvent {
+        start_date: Some(sql_timestamp(Some(7200))),
+        event_status: EventStatus::Final,
+        ..DbEvent::new(&common::gen_string(20), seller)
+    };
+    db_insert_event(db_client, &db_event)
+        .await
+        .expect("failed to insert event");
+    let db_ticket = DbTicket::new(new_ticket(db_event.id), &db_event);
+    db_insert_ticket(db_client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+
+    let buyer = common::create_user(db_client, Role::Buyer).await;
+    let opted_out_buyer = common::create_user(db_client, Role::Buyer).await;
+    reserve(db_client, &db_ticket, buyer).await;
+    reserve(db_client, &db_ticket, buyer).await;
+    reserve(db_client, &db_ticket, opted_out_buyer).await;
+    db_update_user_event_reminders_opt_out(db_client, &opted_out_buyer, true)
+        .await
+        .expect("failed to opt out")
+        .expect("buyer should exist");
+
+    let event_id = db_event.id;
+    let recipients = |window_secs: i64, starts_after_secs: i64| async move {
+        db_get_reminder_recipients(
+            db_client,
+            window_secs,
+            &sql_timestamp(Some(starts_after_secs)),
+            &sql_timestamp(Some(window_secs)),
+        )
+        .await
+        .expect("failed to get reminder recipients")
+        .into_iter()
+        .filter(|recipient| recipient.event_id.eq(&event_id))
+        .map(|recipient| recipient.user_id)
+        .collect::<Vec<_>>()
+    };
+    // once per holder, whatever the number of reservations
+    assert_eq!(vec![buyer], recipients(86400, 3600).await);
+    // not starting within the hour yet
+    assert!(recipients(3600, 0).await.is_empty());
+
+    let config = RemindersConfig::default();
+    assert!(
+        send_reminders(&resources.ctx, &config)
+            .await
+            .expect("failed to send reminders")
+            >= 1
+    );
+
+    // reminded at most once per window
+    assert!(recipients(86400, 3600).await.is_empty());
+    assert!(
+        !db_insert_event_reminder(db_client, &DbEventReminder::new(db_event.id, buyer, 86400))
+            .await
+            .expect("failed to record reminder")
+    );
+
+    // and it lands in the buyer's inbox
+    let notifications = db_get_notifications_by_user_id(db_client, &buyer, true, 10, 0)
+        .await
+        .expect("failed to get notifications");
+    assert_eq!(1, notifications.len());
+    assert_eq!(NotificationKind::EventReminder, notifications[0].kind);
+}
@@ -0,0 +1,161 @@
+use chrono::Utc;
+use gql_api::{
+    auth::Role,
+    db::{
+        models::{DbTicket, DbTicketListing, DbTicketReservation},
+        sql::{
+            db_cancel_ticket_listing, db_cancel_ticket_listings_by_reservation_id,
+            db_get_active_ticket_listing_by_reservation_id,
+            db_get_active_ticket_listings_by_event_id, db_insert_ticket, db_insert_ticket_listing,
+            db_insert_ticket_reservation, unique_violation,
+        },
+    },
+    error::ConflictError,
+    gql::{
+        models::{NewTicket, NewTicketListing},
+        validations::{check_ticket_listing_payload, MAX_TICKET_PRICE},
+    },
+    near::NearAmount,
+};
+
+mod common;
+
+fn new_ticket(
+    event_id: uuid::Uuid,
+    allow_transfers: bool,
+    max_release_price: Option<NearAmount>,
+) -> NewTicket {
+    NewTicket {
+        ticket_name: common::gen_string(10),
+        description: None,
+        price: Some(NearAmount::from_whole_near(10)),
+        max_release_price,
+        quantity_available: Some(10),
+        min_purchase_quantity: Some(1),
+        max_purchase_quantity: Some(2),
+        allow_transfers: Some(allow_transfers),
+        currency: None,
+        event_id,
+    }
+}
+
+fn listing(asking_price: NearAmount) -> NewTicketListing {
+    NewTicketListing {
+        reservation_id: uuid::Uuid::new_v4(),
+        asking_price,
+    }
+}
+
+#[tokio::test]
+async fn test_ticket_listing_payload() {
+    let cfg = common::setup().await;
+    let max_release_price = NearAmount::from_whole_near(12);
+
+    let db_ticket = DbTicket::new(
+        new_ticket(cfg.event.id, true, Some(max_release_price)),
+        &cfg.event,
+    );
+    assert!(check_ticket_listing_payload(&listing(max_release_price), &db_ticket).is_ok());
+    let above_max_release_price = max_release_price
+        .checked_add(NearAmount::from_yocto(1))
+        .unwrap();
+    assert!(check_ticket_listing_payload(&listing(above_max_release_price), &db_ticket).is_err());
+    // free tickets are given away with a transfer
+    assert!(check_ticket_listing_payload(&listing(NearAmount::default()), &db_ticket).is_err());
+
+    // without a max release price, any price a ticket may have
+    let db_ticket = DbTicket::new(new_ticket(cfg.event.id, true, None), &cfg.event);
+    assert!(check_ticket_listing_payload(&listing(MAX_TICKET_PRICE), &db_ticket).is_ok());
+    let above_max_ticket_price = MAX_TICKET_PRICE
+        .checked_add(NearAmount::from_yocto(1))
+        .unwrap();
+    assert!(check_ticket_listing_payload(&listing(above_max_ticket_price), &db_ticket).is_err());
+
+    let db_ticket = DbTicket::new(
+        new_ticket(cfg.event.id, false, Some(max_release_price)),
+        &cfg.event,
+    );
+    assert!(check_ticket_listing_payload(&listing(max_release_price), &db_ticket).is_err());
+}
+
+#[tokio::test]
+async fn test_ticket_listings() {
+    let cfg = common::setup().await;
+
+    let db_ticket = DbTicket::new(
+        new_ticket(cfg.event.id, true, Some(NearAmount::from_whole_near(12))),
+        &cfg.event,
+    );
+    db_insert_ticket(&cfg.client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+
+    let seller = common::create_user(&cfg.client, Role::Buyer).await;
+    let reservation = DbTicketReservation::new(
+        uuid::Uuid::new_v4(),
+        Utc::now().naive_utc(),
+        &common::gen_string(10),
+        cfg.event.id,
+        db_ticket.id,
+        seller,
+        1,
+    );
+    db_insert_ticket_reservation(&cfg.client, &reservation)
+        .await
+        .expect("failed to insert reservation");
+
+    let db_listing = DbTicketListing::new(NearAmount::from_whole_near(11), &reservation);
+    db_insert_ticket_listing(&cfg.client, &db_listing)
+        .await
+        .expect("failed to insert listing");
+    let listings = db_get_active_ticket_listings_by_event_id(&cfg.client, &cfg.event.id)
+        .await
+        .expect("failed to get listings");
+    assert_eq!(
+        vec![db_listing.id],
+        listings.iter().map(|l| l.id).collect::<Vec<_>>()
+    );
+    assert_eq!(seller, listings[0].seller_id);
+
+    // a reservation is listed once at a time
+    let e = db_insert_ticket_listing(
+        &cfg.client,
+        &DbTicketListing::new(NearAmount::from_whole_near(12), &reservation),
+    )
+    .await
+    .expect_err("the reservation is listed already");
+    assert_eq!(Some(ConflictError::TicketListing), unique_violation(&e));
+
+    // only its seller cancels a listing
+    assert!(
+        db_cancel_ticket_listing(&cfg.client, &db_listing.id, &uuid::Uuid::new_v4())
+            .await
+            .expect("failed to cancel listing")
+            .is_none()
+    );
+    let cancelled = db_cancel_ticket_listing(&cfg.client, &db_listing.id, &seller)
+        .await
+        .expect("failed to cancel listing")
+        .expect("listing should be cancelled");
+    assert!(cancelled.cancelled_at.is_some());
+    assert!(
+        db_get_active_ticket_listing_by_reservation_id(&cfg.client, &reservation.id)
+            .await
+            .expect("failed to get listing")
+            .is_none()
+    );
+
+    // listed again, then handed over to someone else
+    db_insert_ticket_listing(
+        &cfg.client,
+        &DbTicketListing::new(NearAmount::from_whole_near(12), &reservation),
+    )
+    .await
+    .expect("failed to list again");
+    assert_eq!(
+        1,
+        db_cancel_ticket_listings_by_reservation_id(&cfg.client, &reservation.id)
+            .await
+            .expect("failed to cancel listings")
+    );
+}
@@ -0,0 +1,196 @@
+use gql_api::{
+    auth::{create_jwt, Role},
+    db::{
+        models::{DbEvent, DbPromoCode, DbTicket},
+        sql::{
+            db_get_promo_code_by_id, db_get_ticket_by_id, db_get_ticket_reservations_by_user_id,
+            db_insert_promo_code, db_insert_ticket, db_update_event_status,
+        },
+    },
+    gql::models::{DiscountType, EventStatus, NewPromoCode, NewTicket},
+    http::routes::event_ticket_get_verification_code_route,
+    near::NearAmount,
+};
+use tokio_postgres::Client;
+use warp::http::StatusCode;
+
+mod common;
+
+async fn insert_ticket(db_client: &Client, db_event: &DbEvent, quantity: i32) -> DbTicket {
+    let db_ticket = DbTicket::new(
+        NewTicket {
+            ticket_name: common::gen_string(10),
+            description: None,
+            price: Some(NearAmount::from_whole_near(10)),
+            max_release_price: None,
+            quantity_available: Some(quantity),
+            min_purchase_quantity: Some(1),
+            max_purchase_quantity: None,
+            allow_transfers: Some(false),
+            currency: None,
+            event_id: db_event.id,
+        },
+        db_event,
+    );
+    db_insert_ticket(db_client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+    db_ticket
+}
+
+async fn reserve(
+    resources: &common::TestResources,
+    buyer: uuid::Uuid,
+    body: serde_json::Value,
+) -> StatusCode {
+    let route =
+        event_ticket_get_verification_code_route(resources.ctx.clone(), warp::log("reservations"));
+    let jwt = create_jwt(&buyer.to_string(), &Role::Buyer).expect("failed to create jwt");
+    warp::test::request()
+        .method("POST")
+        .path("/api/v1/buyer/event_ticket_get_verification_code")
+        .header("authorization", format!("Bearer {}", jwt))
+        .json(&body)
+        .reply(&route)
+        .await
+        .status()
+}
+
+#[tokio::test]
+async fn test_reservation_is_all_or_nothing() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+    let db_event = common::create_event(db_client).await;
+    db_update_event_status(
+        db_client,
+        &db_event.id,
+        EventStatus::Draft,
+        EventStatus::Final,
+    )
+    .await
+    .expect("failed to update event status")
+    .expect("event should be final");
+    let first_ticket = insert_ticket(db_client, &db_event, 3).await;
+    let second_ticket = insert_ticket(db_client, &db_event, 1).await;
+    let db_promo_code = DbPromoCode::new(
+        NewPromoCode {
+            code: common::gen_string(10),
+            discount_type: DiscountType::Percentage,
+            discount_value: "10".to_string(),
+            max_uses: Some(10),
+            valid_from: None,
+            valid_until: None,
+            event_id: db_event.id,
+        },
+        db_event.id,
+        db_event.created_by_user,
+    );
+    db_insert_promo_code(db_client, &db_promo_code)
+        .await
+        .expect("failed to insert promo code");
+    let buyer = common::create_user(db_client, Role::Buyer).await;
+
+    // the second ticket has a single one left, the first is not reserved either
+    let status = reserve(
+        &resources,
+        buyer,
+        serde_json::json!({
+            "eventId": db_event.id.to_string(),
+            "promoCode": db_promo_code.code,
+            "reservations": [
+                { "ticketId": first_ticket.id.to_string(), "quantity": 2 },
+                { "ticketId": second_ticket.id.to_string(), "quantity": 2 },
+            ],
+        }),
+    )
+    .await;
+    assert!(!status.is_success());
+    let db_ticket = db_get_ticket_by_id(db_client, &first_ticket.id)
+        .await
+        .expect("failed to get ticket");
+    assert_eq!(0, db_ticket.quantity_reserved);
+    let db_promo_code = db_get_promo_code_by_id(db_client, &db_promo_code.id)
+        .await
+        .expect("failed to get promo code");
+    assert_eq!(0, db_promo_code.used_count);
+    assert!(db_get_ticket_reservations_by_user_id(db_client, &buyer)
+        .await
+        .expect("failed to get reservations")
+        .is_empty());
+
+    // a quantity out of range is refused rather than read as zero
+    let status = reserve(
+        &resources,
+        buyer,
+        serde_json::json!({
+            "eventId": db_event.id.to_string(),
+            "reservations": [
+                { "ticketId": first_ticket.id.to_string(), "quantity": 4_294_967_297_i64 },
+            ],
+        }),
+    )
+    .await;
+    assert!(!status.is_success());
+
+    let status = reserve(
+        &resources,
+        buyer,
+        serde_json::json!({
+            "eventId": db_event.id.to_string(),
+            "promoCode": db_promo_code.code,
+            "reservations": [
+                { "ticketId": first_ticket.id.to_string(), "quantity": 2 },
+                { "ticketId": second_ticket.id.to_string(), "quantity": 1 },
+            ],
+        }),
+    )
+    .await;
+    assert_eq!(StatusCode::OK, status);
+    let db_promo_code = db_get_promo_code_by_id(db_client, &db_promo_code.id)
+        .await
+        .expect("failed to get promo code");
+    assert_eq!(3, db_promo_code.used_count);
+}
+
+#[tokio::test]
+async fn test_reservation_needs_a_published_event() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+    let db_event = common::create_event(db_client).await;
+    let db_ticket = insert_ticket(db_client, &db_event, 3).await;
+    let buyer = common::create_user(db_client, Role::Buyer).await;
+    let body = serde_json::json!({
+        "eventId": db_event.id.to_string(),
+        "reservations": [{ "ticketId": db_ticket.id.to_string(), "quantity": 1 }],
+    });
+
+    // neither a draft nor an event waiting for its review
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        reserve(&resources, buyer, body.clone()).await
+    );
+    db_update_event_status(
+        db_client,
+        &db_event.id,
+        EventStatus::Draft,
+        EventStatus::PendingReview,
+    )
+    .await
+    .expect("failed to update event status")
+    .expect("event should be pending review");
+    assert_eq!(
+        StatusCode::FORBIDDEN,
+        reserve(&resources, buyer, body.clone()).await
+    );
+
+    db_update_event_status(
+        db_client,
+        &db_event.id,
+        EventStatus::PendingReview,
+        EventStatus::Final,
+    )
+    .await
+    .expect("failed to update event status")
+    .expect("event should be final");
+    assert_eq!(StatusCode::OK, reserve(&resources, buyer, body).await);
+}
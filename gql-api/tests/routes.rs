@@ -0,0 +1,251 @@
+use gql_api::{
+    db::sql::{db_get_buyer_signup_session_by_id, db_update_buyer_signup_session},
+    error::{AuthError, Error, SessionError, UserError},
+    testing::{auth_routes, json_request, rejecting_route, reply_json},
+};
+use rand::Rng;
+use warp::{http::StatusCode, Filter};
+
+mod common;
+
+fn gen_phone_number() -> String {
+    format!("+359888{:06}", rand::thread_rng().gen_range(0, 1_000_000))
+}
+
+#[tokio::test]
+async fn test_rejection_mapping() {
+    let cases: Vec<(fn() -> Error, StatusCode, String)> = vec![
+        (
+            || Error::Auth(AuthError::WrongCredentialsError),
+            StatusCode::FORBIDDEN,
+            AuthError::WrongCredentialsError.to_string(),
+        ),
+        (
+            || Error::Auth(AuthError::NoPermissionError),
+            StatusCode::UNAUTHORIZED,
+            AuthError::NoPermissionError.to_string(),
+        ),
+        (
+            || Error::Session(SessionError::UsedSession("session".to_string())),
+            StatusCode::FORBIDDEN,
+            SessionError::UsedSession("session".to_string()).to_string(),
+        ),
+        (
+            || Error::User(UserError::OnlyBuyer),
+            StatusCode::FORBIDDEN,
+            UserError::OnlyBuyer.to_string(),
+        ),
+        (
+            || Error::UnparsableUuid("not-a-uuid".to_string()),
+            StatusCode::BAD_REQUEST,
+            "not-a-uuid".to_string(),
+        ),
+    ];
+    for (error, expected_status, expected_message) in cases {
+        let (status, json) = reply_json(warp::test::request(), &rejecting_route(error)).await;
+        assert_eq!(expected_status, status, "{}", error());
+        assert_eq!(expected_message, json["message"]);
+        assert_eq!(expected_status.to_string(), json["status"]);
+    }
+
+    // internal errors are not detailed
+    let (status, json) = reply_json(
+        warp::test::request(),
+        &rejecting_route(|| Error::Auth(AuthError::JWTTokenCreationError)),
+    )
+    .await;
+    assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, status);
+    assert_eq!("Internal Server Error", json["message"]);
+
+    // the rejections of warp itself
+    let route = warp::get()
+        .and(warp::path!("known"))
+        .map(warp::reply)
+        .recover(gql_api::error::handle_rejection);
+    let (status, _) = reply_json(warp::test::request().path("/unknown"), &route).await;
+    assert_eq!(StatusCode::NOT_FOUND, status);
+    let (status, _) = reply_json(warp::test::request().method("POST").path("/known"), &route).await;
+    assert_eq!(StatusCode::METHOD_NOT_ALLOWED, status);
+}
+
+#[tokio::test]
+async fn test_auth_route_rejections() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let routes = auth_routes(resources.ctx);
+
+    // a role without signin
+    let (status, _) = reply_json(
+        json_request("POST", "/api/v1/nobody/phone", &serde_json::json!({})),
+        &routes,
+    )
+    .await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+
+    // a body missing fields is answered with its path
+    let (status, json) = reply_json(
+        json_request("POST", "/api/v1/buyer/phone", &serde_json::json!({})),
+        &routes,
+    )
+    .await;
+    assert_eq!(StatusCode::BAD_REQUEST, status);
+    assert!(json["message"]
+        .as_str()
+        .expect("a message")
+        .contains("phoneNumber"));
+
+    // field validations are listed by field
+    let (status, json) = reply_json(
+        json_request(
+            "POST",
+            "/api/v1/seller/signin/challenge",
+            &serde_json::json!({ "walletId": "abc" }),
+        ),
+        &routes,
+    )
+    .await;
+    assert_eq!(StatusCode::BAD_REQUEST, status);
+    assert_eq!("wallet_id", json["errors"][0]["field"]);
+
+    let (status, _) = reply_json(
+        warp::test::request()
+            .method("POST")
+            .path("/api/v1/buyer/phone")
+            .header("content-type", "text/plain")
+            .body("{}"),
+        &routes,
+    )
+    .await;
+    assert_eq!(StatusCode::UNSUPPORTED_MEDIA_TYPE, status);
+
+    let (status, _) = reply_json(
+        json_request("DELETE", "/api/v1/buyer/phone", &serde_json::json!({})),
+        &routes,
+    )
+    .await;
+    assert_eq!(StatusCode::METHOD_NOT_ALLOWED, status);
+}
+
+#[tokio::test]
+async fn test_buyer_signup_flow() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let routes = auth_routes(resources.ctx.clone());
+
+    let (status, json) = reply_json(
+        json_request(
+            "POST",
+            "/api/v1/buyer/phone",
+            &serde_json::json!({ "phoneNumber": gen_phone_number() }),
+        ),
+        &routes,
+    )
+    .await;
+    assert_eq!(StatusCode::OK, status);
+    let session_id = json["sessionId"]
+        .as_str()
+        .expect("a session id")
+        .to_string();
+
+    // signing up needs a verified phone
+    let username = common::gen_string(12).to_lowercase();
+    let signup = |username: &str| {
+        json_request(
+            "POST",
+            "/api/v1/buyer/signup",
+            &serde_json::json!({
+                "username": username,
+                "secret": "secret",
+                "sessionId": session_id,
+            }),
+        )
+    };
+    let (status, _) = reply_json(signup(&username), &routes).await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+
+    let verify = |verification_code: &str| {
+        json_request(
+            "PUT",
+            "/api/v1/buyer/phone",
+            &serde_json::json!({
+                "sessionId": session_id,
+                "verificationCode": verification_code,
+            }),
+        )
+    };
+    // only a hash of the code sent is stored, replace it with the one of a known code
+    let mut db_session = db_get_buyer_signup_session_by_id(
+        &resources.ctx.db_client,
+        &uuid::Uuid::parse_str(&session_id).expect("a uuid"),
+    )
+    .await
+    .expect("failed to get session");
+    assert!(db_session.verification_code_hash.starts_with("v1$"));
+    db_session.verification_code_hash = resources.ctx.codes.hash("123456");
+    db_update_buyer_signup_session(&resources.ctx.db_client, &db_session)
+        .await
+        .expect("failed to update session");
+
+    let (status, _) = reply_json(verify("000000"), &routes).await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+    let (status, json) = reply_json(verify("123456"), &routes).await;
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(Some(true), json["isVerified"].as_bool());
+
+    // a verified code is not verified again
+    let (status, _) = reply_json(verify("123456"), &routes).await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+
+    let (status, json) = reply_json(signup(&username), &routes).await;
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(username, json["username"]);
+    assert!(json["jwt"].is_string());
+    assert!(resources
+        .near_client
+        .calls()
+        .contains(&"create_account".to_string()));
+
+    // a verified phone signs up once
+    let (status, _) = reply_json(signup(&common::gen_string(12).to_lowercase()), &routes).await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+}
+
+#[tokio::test]
+async fn test_seller_signin_flow() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let routes = auth_routes(resources.ctx);
+    let wallet_id = format!("{}.testnet", common::gen_string(12).to_lowercase());
+
+    let (status, json) = reply_json(
+        json_request(
+            "POST",
+            "/api/v1/seller/signin/challenge",
+            &serde_json::json!({ "walletId": wallet_id }),
+        ),
+        &routes,
+    )
+    .await;
+    assert_eq!(StatusCode::OK, status);
+    let nonce = json["nonce"].as_str().expect("a nonce");
+    assert!(json["message"].as_str().expect("a message").contains(nonce));
+
+    // buyers sign up with a phone
+    let signin = |role: &str| {
+        json_request(
+            "POST",
+            &format!("/api/v1/{}/signin", role),
+            &serde_json::json!({
+                "username": common::gen_string(12).to_lowercase(),
+                "walletId": wallet_id,
+                "pubKey": format!("ed25519:{}", common::gen_string(34)),
+                "signature": "signature",
+                "nonce": nonce,
+            }),
+        )
+    };
+    let (status, _) = reply_json(signin("buyer"), &routes).await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+
+    // the fake wallet has no keys, the submitted one is not the wallet's
+    let (status, json) = reply_json(signin("seller"), &routes).await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+    assert_eq!(UserError::WrongWalletPubKey.to_string(), json["message"]);
+}
@@ -0,0 +1,71 @@
+use gql_api::db::{models::DbEvent, query::Table};
+
+mod common;
+
+#[tokio::test]
+async fn test_rows_map_by_column_name() {
+    let cfg = common::setup().await;
+
+    // the columns in reverse order still map to the same event
+    let reversed: Vec<&str> = DbEvent::FIELDS.iter().rev().copied().collect();
+    let query = format!(
+        "SELECT {} FROM {} WHERE id = $1::UUID",
+        reversed.join(", "),
+        DbEvent::TABLE
+    );
+    let row = cfg
+        .client
+        .query_one(&query, &[&cfg.event.id])
+        .await
+        .expect("failed to select event");
+    let db_event = DbEvent::try_from(row).expect("failed to map event");
+
+    assert_eq!(cfg.event.id, db_event.id);
+    assert_eq!(cfg.event.event_name, db_event.event_name);
+    assert_eq!(cfg.event.event_slug, db_event.event_slug);
+    assert_eq!(cfg.event.description, db_event.description);
+    assert_eq!(cfg.event.event_status, db_event.event_status);
+}
+
+#[tokio::test]
+async fn test_rows_reject_mistyped_and_missing_columns() {
+    let cfg = common::setup().await;
+
+    // a mistyped optional column is an error, not a NULL
+    let fields: Vec<String> = DbEvent::FIELDS
+        .iter()
+        .map(|field| match *field {
+            "description" => "1 AS description".to_string(),
+            field => field.to_string(),
+        })
+        .collect();
+    let query = format!(
+        "SELECT {} FROM {} WHERE id = $1::UUID",
+        fields.join(", "),
+        DbEvent::TABLE
+    );
+    let row = cfg
+        .client
+        .query_one(&query, &[&cfg.event.id])
+        .await
+        .expect("failed to select event");
+    assert!(DbEvent::try_from(row).is_err());
+
+    // and so is a missing one
+    let fields: Vec<&str> = DbEvent::FIELDS
+        .iter()
+        .copied()
+        .filter(|field| *field != "venue_name")
+        .collect();
+    let query = format!(
+        "SELECT {} FROM {} WHERE id = $1::UUID",
+        fields.join(", "),
+        DbEvent::TABLE
+    );
+    let row = cfg
+        .client
+        .query_one(&query, &[&cfg.event.id])
+        .await
+        .expect("failed to select event");
+    assert!(DbEvent::try_from(row).is_err());
+}
@@ -0,0 +1,120 @@
+use gql_api::{
+    config::{MaxLengthsConfig, SanitationConfig},
+    db::models::DbEvent,
+    error::SanitizeError,
+    gql::{models::UpdateEvent, validations::update_event_mutation_payload},
+    sanitize::{escape_html, sanitize_field, sanitize_text, TextField},
+};
+
+#[test]
+fn test_sanitize_text() {
+    assert_eq!("Summer Fest", sanitize_text("  Summer Fest\t\n", false));
+    // decomposed "é" is composed
+    assert_eq!("Caf\u{e9}", sanitize_text("Cafe\u{301}", false));
+    // control and bidi override characters are dropped
+    assert_eq!(
+        "evil.exe",
+        sanitize_text("evil\u{0}\u{202e}.exe\u{7}", false)
+    );
+    assert_eq!("one two", sanitize_text("one\ntwo", false));
+    assert_eq!(
+        "first\nsecond",
+        sanitize_text("first\r\nsecond\u{1b}\n", true)
+    );
+}
+
+#[test]
+fn test_escape_html() {
+    assert_eq!(
+        "&lt;b&gt;Tom &amp; Jerry&#x27;s &quot;show&quot;&lt;/b&gt;",
+        escape_html("<b>Tom & Jerry's \"show\"</b>")
+    );
+}
+
+#[test]
+fn test_sanitize_field() {
+    let config = SanitationConfig::default();
+
+    assert_eq!(
+        Err(SanitizeError::Empty("event_name")),
+        sanitize_field(&config, TextField::EventName, " \u{200f} ")
+    );
+    assert_eq!(
+        Err(SanitizeError::TooLong("ticket_name", 20)),
+        sanitize_field(&config, TextField::TicketName, &"a".repeat(21))
+    );
+    // the length is counted in characters, once trimmed
+    assert_eq!(
+        Ok("ж".repeat(20)),
+        sanitize_field(
+            &config,
+            TextField::EventName,
+            &format!(" {} ", "ж".repeat(20))
+        )
+    );
+    // not escaped unless configured to
+    assert_eq!(
+        Ok("<i>Live</i>".to_string()),
+        sanitize_field(&config, TextField::EventDescription, "<i>Live</i>")
+    );
+
+    let config = SanitationConfig {
+        escape_html: true,
+        max_lengths: MaxLengthsConfig {
+            event_description: 5,
+            ..MaxLengthsConfig::default()
+        },
+    };
+    // checked before the escaping
+    assert_eq!(
+        Ok("&lt;b&gt;".to_string()),
+        sanitize_field(&config, TextField::EventDescription, "<b>")
+    );
+    assert!(sanitize_field(&config, TextField::EventDescription, "<b>Live").is_err());
+    // usernames are never escaped
+    assert_eq!(
+        Ok("jane&co".to_string()),
+        sanitize_field(&config, TextField::Username, "jane&co")
+    );
+}
+
+#[test]
+fn test_update_event_is_sanitized() {
+    let config = SanitationConfig::default();
+    let mut db_event = DbEvent::new("Summer Fest", uuid::Uuid::new_v4());
+    let id = db_event.id;
+    let update_event = |event_name: &str, description: &str| UpdateEvent {
+        id,
+        event_name: Some(event_name.to_string()),
+        start_date: None,
+        end_date: None,
+        entry_time: None,
+        description: Some(description.to_string()),
+        is_virtual: None,
+        is_featured: None,
+        venue_name: None,
+        venue_location: None,
+        cover_photo_base64: None,
+        thumbnail_base64: None,
+    };
+
+    let e = update_event_mutation_payload(
+        &config,
+        update_event("\u{0}", "Open air"),
+        &mut db_event.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(Some("event_name"), e.field());
+
+    // descriptions are longer than names
+    let description = format!("  {}\n", "Open air, all day long. ".repeat(20));
+    update_event_mutation_payload(
+        &config,
+        update_event(" Winter\u{202e} Fest ", &description),
+        &mut db_event,
+    )
+    .expect("valid update");
+    assert_eq!("Winter Fest", db_event.event_name);
+    assert_eq!("winter-fest", db_event.event_slug);
+    assert_eq!(Some(description.trim().to_string()), db_event.description);
+}
@@ -0,0 +1,85 @@
+use gql_api::{
+    config::{GraphqlConfig, ServerEnv},
+    gql::{
+        mutations::{
+            AdminMutationRoot, BuyerMutationRoot, PrivateMutationRoot, PublicMutationRoot,
+            SellerMutationRoot,
+        },
+        quiries::{
+            AdminQueryRoot, BuyerQueryRoot, PrivateQueryRoot, PublicQueryRoot, SellerQueryRoot,
+        },
+        schema::{AdminSchema, BuyerSchema, PrivateSchema, PublicSchema, SellerSchema},
+        schema_language::{
+            authenticated_schema_language, is_introspection_query, private_schema_language,
+            public_schema_language,
+        },
+        subscriptions::{PrivateSubscriptionRoot, PublicSubscriptionRoot},
+    },
+};
+use juniper::EmptySubscription;
+
+#[test]
+fn test_schema_language() {
+    let public_schema =
+        PublicSchema::new(PublicQueryRoot, PublicMutationRoot, PublicSubscriptionRoot);
+    let private_schema = PrivateSchema::new(
+        PrivateQueryRoot,
+        PrivateMutationRoot,
+        PrivateSubscriptionRoot,
+    );
+
+    let public_sdl = public_schema_language(&public_schema);
+    let private_sdl = private_schema_language(&private_schema);
+    assert!(public_sdl.contains("schema {"));
+    assert!(private_sdl.contains("changePassword"));
+    assert_ne!(public_sdl, private_sdl);
+}
+
+#[test]
+fn test_role_schema_language() {
+    let seller_sdl = authenticated_schema_language(&SellerSchema::new(
+        SellerQueryRoot,
+        SellerMutationRoot,
+        EmptySubscription::new(),
+    ));
+    let buyer_sdl = authenticated_schema_language(&BuyerSchema::new(
+        BuyerQueryRoot,
+        BuyerMutationRoot,
+        EmptySubscription::new(),
+    ));
+    let admin_sdl = authenticated_schema_language(&AdminSchema::new(
+        AdminQueryRoot,
+        AdminMutationRoot,
+        EmptySubscription::new(),
+    ));
+
+    // a role's fields are only part of its own schema
+    assert!(seller_sdl.contains("scalar NearAmount"));
+    assert!(seller_sdl.contains("registerEvent"));
+    assert!(!buyer_sdl.contains("registerEvent"));
+    assert!(!admin_sdl.contains("registerEvent"));
+    assert!(buyer_sdl.contains("transferTicket"));
+    assert!(!seller_sdl.contains("transferTicket"));
+    assert!(admin_sdl.contains("approveSeller"));
+    assert!(!seller_sdl.contains("approveSeller"));
+    assert!(!buyer_sdl.contains("approveSeller"));
+}
+
+#[test]
+fn test_is_introspection_query() {
+    assert!(is_introspection_query("{ __schema { types { name } } }"));
+    assert!(is_introspection_query(
+        "query { __type(name: \"Event\") { fields { name } } }"
+    ));
+    assert!(!is_introspection_query("{ events { __typename id } }"));
+    assert!(!is_introspection_query("{ __schemaless }"));
+}
+
+#[test]
+fn test_graphql_config_for_env() {
+    let config = GraphqlConfig::default();
+    assert!(!config.for_env(ServerEnv::Release).introspection);
+    assert!(!config.for_env(ServerEnv::Release).graphiql);
+    assert!(config.for_env(ServerEnv::Dev).introspection);
+    assert!(config.for_env(ServerEnv::Dev).graphiql);
+}
@@ -0,0 +1,40 @@
+use gql_api::gql::validations::check_search_text;
+
+mod common;
+
+#[tokio::test]
+async fn test_search_events() {
+    let cfg = common::setup().await;
+
+    let found = gql_api::db::sql::db_search_events(&cfg.client, &cfg.event.event_name, 10, 0)
+        .await
+        .expect("failed to search events");
+    assert_eq!(Some(cfg.event.id), found.first().map(|event| event.id));
+
+    // a typo falls back to trigram similarity
+    let mut typo = cfg.event.event_name.clone();
+    typo.replace_range(0..1, if typo.starts_with('x') { "y" } else { "x" });
+    let found = gql_api::db::sql::db_search_events(&cfg.client, &typo, 10, 0)
+        .await
+        .expect("failed to search events");
+    assert!(found.iter().any(|event| event.id.eq(&cfg.event.id)));
+
+    // archived events are not listed
+    gql_api::db::sql::db_update_event_archived(&cfg.client, &cfg.event.id, true)
+        .await
+        .expect("failed to archive event");
+    let found = gql_api::db::sql::db_search_events(&cfg.client, &cfg.event.event_name, 10, 0)
+        .await
+        .expect("failed to search events");
+    assert!(!found.iter().any(|event| event.id.eq(&cfg.event.id)));
+}
+
+#[test]
+fn test_check_search_text() {
+    assert_eq!(
+        Ok("jazz night"),
+        check_search_text("  jazz night ").map_err(|_| ())
+    );
+    assert!(check_search_text("   ").is_err());
+    assert!(check_search_text(&"a".repeat(201)).is_err());
+}
@@ -0,0 +1,301 @@
+use chrono::Utc;
+use gql_api::{
+    auth::Role,
+    db::{
+        models::{DbSeat, DbSeatSection, DbTicket, DbTicketReservation},
+        sql::{
+            db_get_seats_by_event_id, db_hold_seats, db_insert_seat_section, db_insert_ticket,
+            db_is_ticket_seated, db_release_seats, db_reserve_seats, db_reserve_ticket,
+            sql_timestamp,
+        },
+    },
+    gql::{
+        models::{NewSeatRow, NewSeatSection, NewTicket, SeatSection, SeatStatus},
+        validations::check_new_seat_section_payload,
+    },
+    near::NearAmount,
+};
+use tokio_postgres::Client;
+
+mod common;
+
+fn new_ticket(event_id: uuid::Uuid) -> NewTicket {
+    NewTicket {
+        ticket_name: common::gen_string(10),
+        description: None,
+        price: Some(NearAmount::from_whole_near(10)),
+        max_release_price: None,
+        quantity_available: None,
+        min_purchase_quantity: Some(1),
+        max_purchase_quantity: Some(4),
+        allow_transfers: Some(false),
+        currency: None,
+        event_id,
+    }
+}
+
+fn new_reservation(
+    db_ticket: &DbTicket,
+    user_id: uuid::Uuid,
+    quantity: i32,
+) -> DbTicketReservation {
+    DbTicketReservation::new(
+        uuid::Uuid::new_v4(),
+        Utc::now().naive_utc(),
+        &common::gen_string(6),
+        db_ticket.event_id,
+        db_ticket.id,
+        user_id,
+        quantity,
+    )
+}
+
+/// A ticket sold as the seats of a section of two rows of two seats
+async fn create_seated_ticket(db_client: &Client, db_ticket: &DbTicket) -> Vec<DbSeat> {
+    db_insert_ticket(db_client, db_ticket)
+        .await
+        .expect("failed to insert ticket");
+    let db_section = DbSeatSection::new(db_ticket, "Balcony", 0);
+    let db_seats = ["A", "B"]
+        .iter()
+        .flat_map(|row_label| (1..=2).map(|n| DbSeat::new(&db_section, row_label, n)))
+        .collect::<Vec<_>>();
+    let seated = db_insert_seat_section(db_client, &db_section, &db_seats)
+        .await
+        .expect("failed to insert seat section")
+        .expect("section name should be free");
+    assert_eq!(Some(4), seated.quantity_available);
+    db_seats
+}
+
+fn new_seat_section(rows: &[(&str, i32)]) -> NewSeatSection {
+    NewSeatSection {
+        ticket_id: uuid::Uuid::new_v4(),
+        section_name: "Stalls".to_string(),
+        rows: rows
+            .iter()
+            .map(|(row_label, seats)| NewSeatRow {
+                row_label: row_label.to_string(),
+                seats: *seats,
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn test_check_new_seat_section_payload() {
+    assert!(check_new_seat_section_payload(&new_seat_section(&[("A", 10), ("B", 12)])).is_ok());
+    assert!(check_new_seat_section_payload(&new_seat_section(&[])).is_err());
+    assert!(check_new_seat_section_payload(&new_seat_section(&[("A", 0)])).is_err());
+    assert!(check_new_seat_section_payload(&new_seat_section(&[("A", 1), (" A ", 1)])).is_err());
+    assert!(check_new_seat_section_payload(&new_seat_section(&[(" ", 1)])).is_err());
+    let rows = (0..11)
+        .map(|n| (format!("R{}", n), 200))
+        .collect::<Vec<_>>();
+    let rows = rows
+        .iter()
+        .map(|(row_label, seats)| (row_label.as_str(), *seats))
+        .collect::<Vec<_>>();
+    assert!(check_new_seat_section_payload(&new_seat_section(&rows)).is_err());
+}
+
+#[tokio::test]
+async fn test_insert_seat_section() {
+    let cfg = common::setup().await;
+    let db_ticket = DbTicket::new(new_ticket(cfg.event.id), &cfg.event);
+    assert!(!db_is_ticket_seated(&cfg.client, &db_ticket.id)
+        .await
+        .expect("failed to check ticket"));
+    let db_seats = create_seated_ticket(&cfg.client, &db_ticket).await;
+    assert!(db_is_ticket_seated(&cfg.client, &db_ticket.id)
+        .await
+        .expect("failed to check ticket"));
+
+    // the section names are unique in the event
+    let duplicate = DbSeatSection::new(&db_ticket, "Balcony", 1);
+    assert!(
+        db_insert_seat_section(&cfg.client, &duplicate, &[DbSeat::new(&duplicate, "A", 1)])
+            .await
+            .expect("failed to insert seat section")
+            .is_none()
+    );
+
+    // the quantity counts the seats of all the sections
+    let db_section = DbSeatSection::new(&db_ticket, "Stalls", 1);
+    let seated = db_insert_seat_section(
+        &cfg.client,
+        &db_section,
+        &[DbSeat::new(&db_section, "A", 1)],
+    )
+    .await
+    .expect("failed to insert seat section")
+    .expect("section name should be free");
+    assert_eq!(Some(5), seated.quantity_available);
+
+    let stored = db_get_seats_by_event_id(&cfg.client, &cfg.event.id)
+        .await
+        .expect("failed to get seats");
+    assert_eq!(db_seats.len() + 1, stored.len());
+}
+
+#[tokio::test]
+async fn test_hold_seats() {
+    let cfg = common::setup().await;
+    let db_ticket = DbTicket::new(new_ticket(cfg.event.id), &cfg.event);
+    let db_seats = create_seated_ticket(&cfg.client, &db_ticket).await;
+    let first_buyer = common::create_user(&cfg.client, Role::Buyer).await;
+    let second_buyer = common::create_user(&cfg.client, Role::Buyer).await;
+    let now = sql_timestamp(None);
+    let held_until = sql_timestamp(Some(600));
+
+    let seat_ids = [db_seats[0].id, db_seats[1].id];
+    let held = db_hold_seats(
+        &cfg.client,
+        &cfg.event.id,
+        &seat_ids,
+        &first_buyer,
+        &held_until,
+        &now,
+    )
+    .await
+    .expect("failed to hold seats");
+    assert_eq!(2, held.len());
+    assert!(held
+        .iter()
+        .all(|seat| seat.status(&now) == SeatStatus::Held));
+
+    // all the seats or none
+    let overlapping = [db_seats[1].id, db_seats[2].id];
+    assert!(db_hold_seats(
+        &cfg.client,
+        &cfg.event.id,
+        &overlapping,
+        &second_buyer,
+        &held_until,
+        &now,
+    )
+    .await
+    .expect("failed to hold seats")
+    .is_empty());
+
+    // a hold is over once expired
+    assert_eq!(
+        2,
+        db_hold_seats(
+            &cfg.client,
+            &cfg.event.id,
+            &overlapping,
+            &second_buyer,
+            &held_until,
+            &sql_timestamp(Some(601)),
+        )
+        .await
+        .expect("failed to hold seats")
+        .len()
+    );
+
+    // only the holder releases their seats
+    assert_eq!(
+        0,
+        db_release_seats(&cfg.client, &[db_seats[0].id], &second_buyer)
+            .await
+            .expect("failed to release seats")
+    );
+    assert_eq!(
+        1,
+        db_release_seats(&cfg.client, &[db_seats[0].id], &first_buyer)
+            .await
+            .expect("failed to release seats")
+    );
+}
+
+#[tokio::test]
+async fn test_reserve_seats() {
+    let cfg = common::setup().await;
+    let db_ticket = DbTicket::new(new_ticket(cfg.event.id), &cfg.event);
+    let db_seats = create_seated_ticket(&cfg.client, &db_ticket).await;
+    let buyer = common::create_user(&cfg.client, Role::Buyer).await;
+    let now = sql_timestamp(None);
+
+    // seated tickets are only reserved along with their seats
+    assert!(
+        db_reserve_ticket(&cfg.client, &new_reservation(&db_ticket, buyer, 1))
+            .await
+            .expect("failed to reserve ticket")
+            .is_none()
+    );
+
+    // the seats must be held by the buyer
+    let seat_ids = [db_seats[0].id, db_seats[1].id];
+    assert!(db_reserve_seats(
+        &cfg.client,
+        &new_reservation(&db_ticket, buyer, 2),
+        &seat_ids
+    )
+    .await
+    .expect("failed to reserve seats")
+    .is_none());
+
+    db_hold_seats(
+        &cfg.client,
+        &cfg.event.id,
+        &seat_ids,
+        &buyer,
+        &sql_timestamp(Some(600)),
+        &now,
+    )
+    .await
+    .expect("failed to hold seats");
+
+    // one seat per ticket
+    assert!(db_reserve_seats(
+        &cfg.client,
+        &new_reservation(&db_ticket, buyer, 1),
+        &seat_ids
+    )
+    .await
+    .expect("failed to reserve seats")
+    .is_none());
+
+    let reservation = db_reserve_seats(
+        &cfg.client,
+        &new_reservation(&db_ticket, buyer, 2),
+        &seat_ids,
+    )
+    .await
+    .expect("failed to reserve seats")
+    .expect("held seats should be reservable");
+
+    let seats = db_get_seats_by_event_id(&cfg.client, &cfg.event.id)
+        .await
+        .expect("failed to get seats");
+    let section = SeatSection::new(
+        DbSeatSection {
+            id: db_seats[0].section_id,
+            ..DbSeatSection::new(&db_ticket, "Balcony", 0)
+        },
+        &seats,
+        &now,
+    );
+    assert_eq!(4, section.seats_total);
+    assert_eq!(2, section.seats_reserved);
+    assert_eq!(2, section.seats_available);
+    assert_eq!(0, section.seats_held);
+    assert!(seats
+        .iter()
+        .filter(|seat| seat_ids.contains(&seat.id))
+        .all(|seat| seat.reservation_id == Some(reservation.id) && seat.held_by.is_none()));
+
+    // reserved seats cannot be held again
+    assert!(db_hold_seats(
+        &cfg.client,
+        &cfg.event.id,
+        &seat_ids,
+        &buyer,
+        &sql_timestamp(Some(600)),
+        &now,
+    )
+    .await
+    .expect("failed to hold seats")
+    .is_empty());
+}
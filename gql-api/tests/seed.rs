@@ -0,0 +1,104 @@
+use chrono::Utc;
+use gql_api::{
+    auth::Role,
+    config::NearNetwork,
+    db::sql::{db_get_tickets_by_event_id, db_get_user_by_username},
+    error::SeedError,
+    seed::{seed, seed_data, seed_username, SeedConfig},
+};
+use rand::Rng;
+
+mod common;
+
+fn seed_config(seed: u64) -> SeedConfig {
+    SeedConfig {
+        seed,
+        sellers: 2,
+        buyers: 3,
+        events_per_seller: 2,
+        tickets_per_event: 2,
+        reservations_per_buyer: 2,
+        password: "seed-password".to_string(),
+    }
+}
+
+#[test]
+fn test_seed_data() {
+    let now = Utc::now().naive_utc();
+    let data = seed_data(&seed_config(7), NearNetwork::Testnet, now, "hash");
+    assert_eq!(5, data.users.len());
+    assert_eq!(4, data.events.len());
+    assert_eq!(8, data.tickets.len());
+    assert!(data.reservations.len() <= 6);
+    assert_eq!(seed_username(7, Role::Seller, 1), data.users[0].username);
+    assert_eq!("seed7_seller1.testnet", data.users[0].wallet_id);
+
+    // the reservations are counted on their tickets
+    for db_ticket in &data.tickets {
+        let reserved: i32 = data
+            .reservations
+            .iter()
+            .filter(|r| r.ticket_id == db_ticket.id)
+            .map(|r| r.quantity)
+            .sum();
+        assert_eq!(reserved, db_ticket.quantity_reserved);
+    }
+
+    // the same seed generates the same data, another seed other data
+    let again = seed_data(&seed_config(7), NearNetwork::Testnet, now, "hash");
+    let ids = |data: &gql_api::seed::SeedData| {
+        data.users
+            .iter()
+            .map(|u| u.id)
+            .chain(data.events.iter().map(|e| e.id))
+            .chain(data.tickets.iter().map(|t| t.id))
+            .chain(data.reservations.iter().map(|r| r.id))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&data), ids(&again));
+    assert_eq!(
+        data.events
+            .iter()
+            .map(|e| &e.event_name)
+            .collect::<Vec<_>>(),
+        again
+            .events
+            .iter()
+            .map(|e| &e.event_name)
+            .collect::<Vec<_>>()
+    );
+    let other = seed_data(&seed_config(8), NearNetwork::Testnet, now, "hash");
+    assert_ne!(ids(&data), ids(&other));
+}
+
+#[tokio::test]
+async fn test_seed() {
+    let db_client = common::connect().await;
+    // a seed of its own, away from the seeds of other runs
+    let config = seed_config(rand::thread_rng().gen());
+    let now = Utc::now().naive_utc();
+
+    let report = seed(&db_client, &config, NearNetwork::Testnet, now)
+        .await
+        .expect("failed to seed");
+    assert_eq!(2, report.sellers);
+    assert_eq!(3, report.buyers);
+    assert_eq!(4, report.events);
+    assert_eq!(8, report.tickets);
+
+    let seller = db_get_user_by_username(&db_client, &seed_username(config.seed, Role::Seller, 2))
+        .await
+        .expect("the seller should be inserted");
+    assert_eq!(Role::Seller, seller.user_type);
+    let data = seed_data(&config, NearNetwork::Testnet, now, "hash");
+    let tickets = db_get_tickets_by_event_id(&db_client, &Some(data.events[0].id))
+        .await
+        .expect("failed to get tickets");
+    assert_eq!(2, tickets.len());
+
+    // a seed is inserted once
+    assert!(matches!(
+        seed(&db_client, &config, NearNetwork::Testnet, now).await,
+        Err(SeedError::AlreadySeeded(_))
+    ));
+}
@@ -0,0 +1,176 @@
+use common::TestContextBuilder;
+use gql_api::{
+    auth::{Role, UserStatus},
+    db::{
+        models::{DbSellerPhoneVerification, DbUser},
+        sql::{
+            db_complete_seller_phone_verification, db_get_latest_seller_phone_verification,
+            db_get_user_by_id, db_insert_seller_phone_verification, db_insert_user, sql_timestamp,
+        },
+    },
+    gql::{
+        mutations::PrivateMutationRoot,
+        quiries::PrivateQueryRoot,
+        schema::{Context as ResourcesContext, PrivateSchema},
+        subscriptions::PrivateSubscriptionRoot,
+    },
+};
+
+mod common;
+
+fn gen_phone_number() -> String {
+    let subscriber: String = common::gen_string(40)
+        .chars()
+        .filter(char::is_ascii_digit)
+        .chain(std::iter::repeat('5'))
+        .take(5)
+        .collect();
+    format!("+3598883{}", subscriber)
+}
+
+async fn execute(ctx: &ResourcesContext, mutation: &str) -> bool {
+    let schema = PrivateSchema::new(
+        PrivateQueryRoot,
+        PrivateMutationRoot,
+        PrivateSubscriptionRoot,
+    );
+    let (_, errors) = juniper::execute(mutation, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid mutation");
+    errors.is_empty()
+}
+
+async fn insert_seller(ctx: &ResourcesContext, phone_number: Option<String>) -> DbUser {
+    let db_user = DbUser::new(
+        uuid::Uuid::new_v4(),
+        None,
+        common::gen_string(20),
+        phone_number,
+        None,
+        None,
+        None,
+        Role::Seller,
+        common::gen_string(20),
+        "0".to_string(),
+        UserStatus::Unverified,
+    );
+    db_insert_user(&ctx.db_client, &db_user)
+        .await
+        .expect("unable to create seller");
+    db_user
+}
+
+#[tokio::test]
+async fn test_verify_seller_phone() {
+    let resources = TestContextBuilder::new().build().await;
+    let db_seller = insert_seller(&resources.ctx, None).await;
+    let ctx = resources.ctx.for_user(Some(db_seller.id));
+    let phone_number = gen_phone_number();
+
+    let send_code = format!(
+        r#"mutation {{ sendSellerPhoneCode(phoneNumber: "{}") {{ id }} }}"#,
+        phone_number
+    );
+    assert!(execute(&ctx, &send_code).await);
+    // not again within the cooldown
+    assert!(!execute(&ctx, &send_code).await);
+
+    // the sent code is not known here, verify a known one instead
+    let db_verification = DbSellerPhoneVerification::new(
+        db_seller.id,
+        phone_number.clone(),
+        resources.ctx.codes.hash("123456"),
+        sql_timestamp(Some(600)),
+    );
+    db_insert_seller_phone_verification(&resources.ctx.db_client, &db_verification)
+        .await
+        .expect("unable to insert verification");
+
+    assert!(
+        !execute(
+            &ctx,
+            r#"mutation { verifySellerPhone(code: "654321") { id } }"#
+        )
+        .await
+    );
+    assert!(
+        execute(
+            &ctx,
+            r#"mutation { verifySellerPhone(code: "123456") { id } }"#
+        )
+        .await
+    );
+
+    let verified_db_seller = db_get_user_by_id(&resources.ctx.db_client, &db_seller.id)
+        .await
+        .expect("unable to fetch seller");
+    assert_eq!(UserStatus::PhoneVerified, verified_db_seller.user_status);
+    assert_eq!(Some(phone_number), verified_db_seller.phone_number);
+    let latest = db_get_latest_seller_phone_verification(&resources.ctx.db_client, &db_seller.id)
+        .await
+        .expect("unable to fetch verification")
+        .expect("verification should exist");
+    assert_eq!(2, latest.attempts);
+    assert!(latest.verified_at.is_some());
+
+    // a code is verified once
+    assert!(
+        !execute(
+            &ctx,
+            r#"mutation { verifySellerPhone(code: "123456") { id } }"#
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn test_complete_seller_phone_verification() {
+    let resources = TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+    let phone_number = gen_phone_number();
+    let db_seller = insert_seller(&resources.ctx, None).await;
+
+    // the phone number of another user is not verified
+    let other = insert_seller(&resources.ctx, Some(phone_number.clone())).await;
+    let db_verification = DbSellerPhoneVerification::new(
+        db_seller.id,
+        phone_number.clone(),
+        resources.ctx.codes.hash("123456"),
+        sql_timestamp(Some(600)),
+    );
+    db_insert_seller_phone_verification(db_client, &db_verification)
+        .await
+        .expect("unable to insert verification");
+    assert!(db_complete_seller_phone_verification(
+        db_client,
+        &db_verification.id,
+        &sql_timestamp(None)
+    )
+    .await
+    .expect("unable to complete verification")
+    .is_none());
+
+    let db_verification = DbSellerPhoneVerification::new(
+        other.id,
+        phone_number.clone(),
+        resources.ctx.codes.hash("123456"),
+        sql_timestamp(Some(600)),
+    );
+    db_insert_seller_phone_verification(db_client, &db_verification)
+        .await
+        .expect("unable to insert verification");
+    let verified =
+        db_complete_seller_phone_verification(db_client, &db_verification.id, &sql_timestamp(None))
+            .await
+            .expect("unable to complete verification")
+            .expect("own phone number should be verifiable");
+    assert_eq!(UserStatus::PhoneVerified, verified.user_status);
+    assert!(db_complete_seller_phone_verification(
+        db_client,
+        &db_verification.id,
+        &sql_timestamp(None)
+    )
+    .await
+    .expect("unable to complete verification")
+    .is_none());
+}
@@ -0,0 +1,198 @@
+use gql_api::{
+    auth::Role,
+    db::{
+        models::{DbBuyerRecoverySession, DbBuyerSignupSession, DbSigninChallenge},
+        sql::{db_consume_signin_challenge, db_insert_signin_challenge, sql_timestamp},
+    },
+    security::challenge::{challenge_message, gen_nonce},
+};
+
+mod common;
+
+#[tokio::test]
+async fn test_recovery_session_single_use() {
+    let cfg = common::setup().await;
+    let user_id = common::create_user(&cfg.client, Role::Buyer).await;
+
+    let session = DbBuyerRecoverySession::new(
+        uuid::Uuid::new_v4(),
+        sql_timestamp(None),
+        common::gen_string(12),
+        common::gen_string(12),
+        false,
+        user_id,
+        sql_timestamp(Some(60)),
+    );
+    gql_api::db::sql::db_insert_buyer_recovery_session(&cfg.client, &session)
+        .await
+        .expect("failed to insert recovery session");
+
+    let attempts =
+        gql_api::db::sql::db_increment_buyer_recovery_session_attempts(&cfg.client, &session.id)
+            .await
+            .expect("failed to count attempt");
+    assert_eq!(1, attempts);
+
+    let consumed = gql_api::db::sql::db_consume_buyer_recovery_session(&cfg.client, &session.id)
+        .await
+        .expect("failed to consume recovery session")
+        .expect("recovery session should be consumable once");
+    assert!(consumed.is_recovered);
+    assert!(consumed.is_consumed);
+
+    let again = gql_api::db::sql::db_consume_buyer_recovery_session(&cfg.client, &session.id)
+        .await
+        .expect("failed to consume recovery session");
+    assert!(again.is_none());
+}
+
+#[tokio::test]
+async fn test_signup_session_expiry() {
+    let cfg = common::setup().await;
+
+    let session = DbBuyerSignupSession::new(
+        uuid::Uuid::new_v4(),
+        sql_timestamp(Some(-120)),
+        common::gen_string(12),
+        common::gen_string(12),
+        true,
+        sql_timestamp(Some(-60)),
+    );
+    gql_api::db::sql::db_insert_buyer_signup_session(&cfg.client, &session)
+        .await
+        .expect("failed to insert signup session");
+
+    // expired sessions cannot be consumed
+    let consumed = gql_api::db::sql::db_consume_buyer_signup_session(&cfg.client, &session.id)
+        .await
+        .expect("failed to consume signup session");
+    assert!(consumed.is_none());
+
+    let purged = gql_api::db::sql::db_purge_stale_sessions(&cfg.client, &sql_timestamp(None))
+        .await
+        .expect("failed to purge stale sessions");
+    assert!(purged >= 1);
+    assert!(
+        gql_api::db::sql::db_get_buyer_signup_session_by_id(&cfg.client, &session.id)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_signup_session_resend() {
+    let cfg = common::setup().await;
+
+    let session = DbBuyerSignupSession::new(
+        uuid::Uuid::new_v4(),
+        sql_timestamp(Some(-120)),
+        common::gen_string(12),
+        common::gen_string(12),
+        false,
+        sql_timestamp(Some(60)),
+    );
+    gql_api::db::sql::db_insert_buyer_signup_session(&cfg.client, &session)
+        .await
+        .expect("failed to insert signup session");
+    gql_api::db::sql::db_increment_buyer_signup_session_attempts(&cfg.client, &session.id)
+        .await
+        .expect("failed to count attempt");
+
+    // the code was sent two minutes ago, so a one minute cooldown has passed
+    let code = common::gen_string(12);
+    let resent = gql_api::db::sql::db_resend_buyer_signup_session(
+        &cfg.client,
+        &session.id,
+        &code,
+        &sql_timestamp(Some(900)),
+        &sql_timestamp(Some(-60)),
+        1,
+    )
+    .await
+    .expect("failed to resend signup session")
+    .expect("signup session should be resendable");
+    assert_eq!(code, resent.verification_code_hash);
+    assert_eq!(1, resent.resend_count);
+    assert_eq!(0, resent.attempts);
+
+    // still cooling down
+    let again = gql_api::db::sql::db_resend_buyer_signup_session(
+        &cfg.client,
+        &session.id,
+        &common::gen_string(12),
+        &sql_timestamp(Some(900)),
+        &sql_timestamp(Some(-60)),
+        2,
+    )
+    .await
+    .expect("failed to resend signup session");
+    assert!(again.is_none());
+
+    // out of resends
+    let again = gql_api::db::sql::db_resend_buyer_signup_session(
+        &cfg.client,
+        &session.id,
+        &common::gen_string(12),
+        &sql_timestamp(Some(900)),
+        &sql_timestamp(Some(60)),
+        1,
+    )
+    .await
+    .expect("failed to resend signup session");
+    assert!(again.is_none());
+}
+
+#[tokio::test]
+async fn test_signin_challenge_single_use() {
+    let cfg = common::setup().await;
+    let wallet_id = format!("{}.testnet", common::gen_string(12));
+
+    let challenge = DbSigninChallenge::new(wallet_id.clone(), gen_nonce(), sql_timestamp(Some(60)));
+    db_insert_signin_challenge(&cfg.client, &challenge)
+        .await
+        .expect("failed to insert signin challenge");
+
+    // the nonce is bound to its wallet
+    let other_wallet = db_consume_signin_challenge(&cfg.client, "other.testnet", &challenge.nonce)
+        .await
+        .expect("failed to consume signin challenge");
+    assert!(other_wallet.is_none());
+
+    let consumed = db_consume_signin_challenge(&cfg.client, &wallet_id, &challenge.nonce)
+        .await
+        .expect("failed to consume signin challenge")
+        .expect("signin challenge should be consumable once");
+    assert!(consumed.is_consumed);
+
+    let again = db_consume_signin_challenge(&cfg.client, &wallet_id, &challenge.nonce)
+        .await
+        .expect("failed to consume signin challenge");
+    assert!(again.is_none());
+}
+
+#[tokio::test]
+async fn test_signin_challenge_expiry() {
+    let cfg = common::setup().await;
+    let wallet_id = format!("{}.testnet", common::gen_string(12));
+
+    let challenge = DbSigninChallenge::new(wallet_id.clone(), gen_nonce(), sql_timestamp(Some(-1)));
+    db_insert_signin_challenge(&cfg.client, &challenge)
+        .await
+        .expect("failed to insert signin challenge");
+
+    let expired = db_consume_signin_challenge(&cfg.client, &wallet_id, &challenge.nonce)
+        .await
+        .expect("failed to consume signin challenge");
+    assert!(expired.is_none());
+}
+
+#[test]
+fn test_signin_challenge_nonce() {
+    let nonce = gen_nonce();
+    assert_eq!(64, nonce.len());
+    assert_ne!(nonce, gen_nonce());
+    assert_eq!(
+        format!("Sign in with nonce: {}", nonce),
+        challenge_message("Sign in with nonce: ", &nonce)
+    );
+}
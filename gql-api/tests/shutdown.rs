@@ -0,0 +1,44 @@
+use gql_api::shutdown::{drain_server, join_workers};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+#[tokio::test]
+async fn test_drain_server() {
+    let (stop_tx, _) = broadcast::channel::<()>(1);
+
+    // the server finishes its in-flight requests after the stop signal
+    let mut server_rx = stop_tx.subscribe();
+    let server = tokio::spawn(async move {
+        _ = server_rx.recv().await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    });
+    let drained = tokio::spawn(drain_server(
+        server,
+        stop_tx.subscribe(),
+        Duration::from_secs(5),
+    ));
+    stop_tx.send(()).expect("failed to send stop signal");
+    assert!(drained.await.expect("failed to drain server"));
+}
+
+#[tokio::test]
+async fn test_drain_server_timeout() {
+    let (stop_tx, stop_rx) = broadcast::channel::<()>(1);
+
+    // e.g. an open subscription
+    let server = tokio::spawn(futures::future::pending::<()>());
+    stop_tx.send(()).expect("failed to send stop signal");
+    assert!(!drain_server(server, stop_rx, Duration::from_millis(10)).await);
+}
+
+#[tokio::test]
+async fn test_join_workers() {
+    let workers = vec![tokio::spawn(async {}), tokio::spawn(async {})];
+    assert!(join_workers(workers, Duration::from_secs(5)).await);
+
+    let workers = vec![
+        tokio::spawn(async {}),
+        tokio::spawn(futures::future::pending::<()>()),
+    ];
+    assert!(!join_workers(workers, Duration::from_millis(10)).await);
+}
@@ -0,0 +1,228 @@
+use gql_api::{
+    auth::{Role, UserStatus},
+    config::SignupsConfig,
+    db::{
+        models::{DbBuyerSignupSession, DbSignupAttempt, DbUser},
+        sql::{
+            db_consume_buyer_signup_session, db_fail_signup_attempt,
+            db_get_buyer_signup_session_by_id, db_get_signup_attempt_by_id, db_get_users_by_ids,
+            db_insert_buyer_signup_session, db_insert_signup_attempt,
+            db_set_signup_attempt_wallet_created, sql_timestamp,
+        },
+    },
+    gql::models::SignupStatus,
+    jobs::signups::retry_signups,
+    signups::{resume, retry_backoff_secs, roll_back_stuck},
+};
+use tokio_postgres::Client;
+
+mod common;
+
+/// A signup of a buyer whose session was consumed, due for a retry after `next_attempt_secs`
+async fn create_signup_attempt(db_client: &Client, next_attempt_secs: i64) -> DbSignupAttempt {
+    let session = DbBuyerSignupSession::new(
+        uuid::Uuid::new_v4(),
+        sql_timestamp(None),
+        common::gen_string(12),
+        common::gen_string(12),
+        true,
+        sql_timestamp(Some(60)),
+    );
+    db_insert_buyer_signup_session(db_client, &session)
+        .await
+        .expect("failed to insert signup session");
+    db_consume_buyer_signup_session(db_client, &session.id)
+        .await
+        .expect("failed to consume signup session")
+        .expect("signup session should be consumable");
+
+    let db_user = DbUser::new(
+        uuid::Uuid::new_v4(),
+        None,
+        common::gen_string(20),
+        Some(session.phone_number.clone()),
+        None,
+        Some(common::gen_string(20)),
+        Some(common::gen_string(20)),
+        Role::Buyer,
+        format!("{}.testnet", common::gen_string(20).to_lowercase()),
+        "0".to_string(),
+        UserStatus::PhoneVerified,
+    );
+    let db_signup_attempt = DbSignupAttempt::new(
+        session.id,
+        &db_user,
+        &format!("ed25519:{}", common::gen_string(44)),
+        sql_timestamp(Some(next_attempt_secs)),
+    );
+    db_insert_signup_attempt(db_client, &db_signup_attempt)
+        .await
+        .expect("failed to insert signup attempt");
+    db_signup_attempt
+}
+
+/// Fails a signup for good, so that only an admin resumes or rolls it back and the retries of
+/// the other tests leave it alone
+async fn fail_signup_attempt(db_client: &Client, db_signup_attempt: &DbSignupAttempt) {
+    let failed = db_fail_signup_attempt(
+        db_client,
+        &db_signup_attempt.id,
+        "the near api is unavailable",
+        &sql_timestamp(Some(3600)),
+        1,
+    )
+    .await
+    .expect("failed to fail signup attempt")
+    .expect("signup attempt should be pending");
+    assert_eq!(SignupStatus::Failed, failed.signup_status);
+    assert_eq!(1, failed.attempts);
+}
+
+async fn get_signup_attempt(db_client: &Client, id: &uuid::Uuid) -> DbSignupAttempt {
+    db_get_signup_attempt_by_id(db_client, id)
+        .await
+        .expect("failed to get signup attempt")
+        .expect("signup attempt should exist")
+}
+
+#[test]
+fn test_retry_backoff_secs() {
+    let config = SignupsConfig {
+        retry_backoff_secs: 30,
+        max_retry_backoff_secs: 3600,
+        ..SignupsConfig::default()
+    };
+    assert_eq!(30, retry_backoff_secs(&config, 0));
+    assert_eq!(30, retry_backoff_secs(&config, 1));
+    assert_eq!(60, retry_backoff_secs(&config, 2));
+    assert_eq!(240, retry_backoff_secs(&config, 4));
+    assert_eq!(3600, retry_backoff_secs(&config, 20));
+    assert_eq!(3600, retry_backoff_secs(&config, i32::MAX));
+}
+
+#[tokio::test]
+async fn test_resume_signup() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+
+    // leased by the request carrying it through
+    let db_signup_attempt = create_signup_attempt(db_client, 60).await;
+    assert!(resume(&resources.ctx, &db_signup_attempt.id)
+        .await
+        .expect("failed to resume signup")
+        .is_none());
+
+    let db_signup_attempt = create_signup_attempt(db_client, 60).await;
+    fail_signup_attempt(db_client, &db_signup_attempt).await;
+    let resumed = resume(&resources.ctx, &db_signup_attempt.id)
+        .await
+        .expect("failed to resume signup")
+        .expect("signup should be resumable");
+    assert_eq!(SignupStatus::Completed, resumed.signup_status);
+    assert!(resumed.wallet_created_at.is_some());
+    assert!(resumed.tx_hash.is_some());
+    assert!(resources
+        .near_client
+        .calls()
+        .contains(&"create_account".to_string()));
+
+    let db_users = db_get_users_by_ids(db_client, &[db_signup_attempt.user_id])
+        .await
+        .expect("failed to get users");
+    assert_eq!(1, db_users.len());
+    assert_eq!(db_signup_attempt.username, db_users[0].username);
+    assert_eq!(db_signup_attempt.wallet_id, db_users[0].wallet_id);
+    assert_eq!(Role::Buyer, db_users[0].user_type);
+
+    // a completed signup is not resumed again
+    assert!(resume(&resources.ctx, &db_signup_attempt.id)
+        .await
+        .expect("failed to resume signup")
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_resume_signup_with_created_wallet() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+
+    let db_signup_attempt = create_signup_attempt(db_client, 60).await;
+    fail_signup_attempt(db_client, &db_signup_attempt).await;
+    db_set_signup_attempt_wallet_created(db_client, &db_signup_attempt.id, None)
+        .await
+        .expect("failed to set wallet created");
+
+    let resumed = resume(&resources.ctx, &db_signup_attempt.id)
+        .await
+        .expect("failed to resume signup")
+        .expect("signup should be resumable");
+    assert_eq!(SignupStatus::Completed, resumed.signup_status);
+    assert!(!resources
+        .near_client
+        .calls()
+        .contains(&"create_account".to_string()));
+    assert_eq!(
+        1,
+        db_get_users_by_ids(db_client, &[db_signup_attempt.user_id])
+            .await
+            .expect("failed to get users")
+            .len()
+    );
+}
+
+#[tokio::test]
+async fn test_roll_back_stuck_signup() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+
+    let db_signup_attempt = create_signup_attempt(db_client, 60).await;
+    fail_signup_attempt(db_client, &db_signup_attempt).await;
+    let rolled_back = roll_back_stuck(&resources.ctx, &db_signup_attempt.id)
+        .await
+        .expect("failed to roll back signup")
+        .expect("signup should be stuck");
+    assert_eq!(SignupStatus::RolledBack, rolled_back.signup_status);
+
+    // the buyer may sign up again with their verified phone
+    let session = db_get_buyer_signup_session_by_id(db_client, &db_signup_attempt.session_id)
+        .await
+        .expect("failed to get signup session");
+    assert!(!session.is_consumed);
+    assert!(session.is_verified);
+
+    assert!(roll_back_stuck(&resources.ctx, &db_signup_attempt.id)
+        .await
+        .expect("failed to roll back signup")
+        .is_none());
+    assert!(db_get_users_by_ids(db_client, &[db_signup_attempt.user_id])
+        .await
+        .expect("failed to get users")
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_retry_signups() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+
+    let due = create_signup_attempt(db_client, -1).await;
+    let leased = create_signup_attempt(db_client, 60).await;
+
+    let config = SignupsConfig {
+        batch_size: 1000,
+        ..SignupsConfig::default()
+    };
+    let completed = retry_signups(&resources.ctx, &config)
+        .await
+        .expect("failed to retry signups");
+    assert!(completed >= 1);
+
+    assert_eq!(
+        SignupStatus::Completed,
+        get_signup_attempt(db_client, &due.id).await.signup_status
+    );
+    let leased = get_signup_attempt(db_client, &leased.id).await;
+    assert_eq!(SignupStatus::Pending, leased.signup_status);
+    assert!(leased.wallet_created_at.is_none());
+    assert_eq!(0, leased.attempts);
+}
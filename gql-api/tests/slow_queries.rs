@@ -0,0 +1,44 @@
+use gql_api::{
+    config::{ServerEnv, SlowQueriesConfig},
+    db::slow_queries::{configure, is_read_only, is_slow},
+};
+use std::time::Duration;
+
+#[test]
+fn test_slow_queries() {
+    configure(SlowQueriesConfig {
+        threshold_ms: 100,
+        explain: false,
+    });
+    assert!(!is_slow(Duration::from_millis(99)));
+    assert!(is_slow(Duration::from_millis(100)));
+
+    // no query is slow without a threshold
+    configure(SlowQueriesConfig {
+        threshold_ms: 0,
+        explain: false,
+    });
+    assert!(!is_slow(Duration::from_secs(60)));
+}
+
+#[test]
+fn test_explain_in_dev_only() {
+    let config = SlowQueriesConfig {
+        threshold_ms: 100,
+        explain: true,
+    };
+    assert!(config.for_env(ServerEnv::Dev).explain);
+    assert!(!config.for_env(ServerEnv::Release).explain);
+    assert_eq!(100, config.for_env(ServerEnv::Release).threshold_ms);
+}
+
+#[test]
+fn test_only_reads_are_analyzed() {
+    assert!(is_read_only("  select id FROM users WHERE id = $1::UUID"));
+    assert!(!is_read_only(
+        "UPDATE users SET locale = $2 WHERE id = $1::UUID"
+    ));
+    assert!(!is_read_only(
+        "WITH claimed AS (UPDATE tickets SET quantity_sold = 1) SELECT 1"
+    ));
+}
@@ -0,0 +1,64 @@
+use gql_api::{
+    db::{
+        models::{suffixed_slug, DbEvent, DbTicket},
+        sql::{db_insert_event_with_free_slug, db_insert_ticket_with_free_slug},
+    },
+    gql::models::NewTicket,
+};
+
+mod common;
+
+fn new_ticket(ticket_name: &str, event_id: uuid::Uuid) -> NewTicket {
+    NewTicket {
+        ticket_name: ticket_name.to_string(),
+        description: None,
+        price: None,
+        max_release_price: None,
+        quantity_available: None,
+        min_purchase_quantity: None,
+        max_purchase_quantity: None,
+        allow_transfers: None,
+        currency: None,
+        event_id,
+    }
+}
+
+#[test]
+fn test_suffixed_slug() {
+    assert_eq!("summer-fest", suffixed_slug("summer-fest", 1));
+    assert_eq!("summer-fest-2", suffixed_slug("summer-fest", 2));
+    assert_eq!("summer-fest-10", suffixed_slug("summer-fest", 10));
+}
+
+#[tokio::test]
+async fn test_slug_collisions() {
+    let cfg = common::setup().await;
+    let event_name = common::gen_string(12);
+
+    // names told apart by case only share a slug
+    let mut first = DbEvent::new(&event_name.to_lowercase(), cfg.event.created_by_user);
+    db_insert_event_with_free_slug(&cfg.client, &mut first)
+        .await
+        .expect("failed to insert event");
+    let mut second = DbEvent::new(&event_name.to_uppercase(), cfg.event.created_by_user);
+    db_insert_event_with_free_slug(&cfg.client, &mut second)
+        .await
+        .expect("failed to insert event");
+    let mut third = DbEvent::new(&format!("{}!", event_name), cfg.event.created_by_user);
+    db_insert_event_with_free_slug(&cfg.client, &mut third)
+        .await
+        .expect("failed to insert event");
+    assert_eq!(format!("{}-2", first.event_slug), second.event_slug);
+    assert_eq!(format!("{}-3", first.event_slug), third.event_slug);
+
+    let mut early = DbTicket::new(new_ticket("Early", first.id), &first);
+    db_insert_ticket_with_free_slug(&cfg.client, &mut early, &[])
+        .await
+        .expect("failed to insert ticket");
+    let mut early_again = DbTicket::new(new_ticket("early", first.id), &first);
+    db_insert_ticket_with_free_slug(&cfg.client, &mut early_again, &[])
+        .await
+        .expect("failed to insert ticket");
+    assert_eq!(format!("{}-early", first.event_slug), early.ticket_slug);
+    assert_eq!(format!("{}-2", early.ticket_slug), early_again.ticket_slug);
+}
@@ -0,0 +1,64 @@
+use gql_api::{db::sql::EventsFilter, gql::models::EventFilter};
+
+mod common;
+
+#[tokio::test]
+async fn test_soft_delete_event() {
+    let cfg = common::setup().await;
+
+    let deleted = gql_api::db::sql::db_soft_delete_event_by_id(&cfg.client, &cfg.event.id)
+        .await
+        .expect("failed to soft delete event");
+    assert_eq!(1, deleted);
+
+    // soft-deleted events are hidden from reads
+    assert!(
+        gql_api::db::sql::db_get_event_by_id(&cfg.client, &cfg.event.id)
+            .await
+            .is_err()
+    );
+
+    // but the row is kept around until purged
+    let by_slug = gql_api::db::sql::db_get_event_by_slug(&cfg.client, &cfg.event.event_slug)
+        .await
+        .expect("soft deleted event should still exist");
+    assert!(by_slug.deleted_at.is_some());
+
+    let purged = gql_api::db::sql::db_purge_event_by_id(&cfg.client, &cfg.event.id)
+        .await
+        .expect("failed to purge event");
+    assert_eq!(1, purged);
+}
+
+#[tokio::test]
+async fn test_archive_event() {
+    let cfg = common::setup().await;
+
+    let archived = gql_api::db::sql::db_update_event_archived(&cfg.client, &cfg.event.id, true)
+        .await
+        .expect("failed to archive event");
+    assert!(archived.archived);
+
+    let listed = gql_api::db::sql::db_get_events(
+        &cfg.client,
+        &EventsFilter {
+            id: Some(cfg.event.id),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to list events");
+    assert!(listed.is_empty());
+
+    let listed = gql_api::db::sql::db_get_events(
+        &cfg.client,
+        &EventsFilter {
+            id: Some(cfg.event.id),
+            filter: Some(EventFilter::Archived),
+            ..Default::default()
+        },
+    )
+    .await
+    .expect("failed to list archived events");
+    assert_eq!(1, listed.len());
+}
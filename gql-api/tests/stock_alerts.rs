@@ -0,0 +1,155 @@
+use gql_api::{
+    auth::Role,
+    db::{
+        models::{DbEvent, DbSellerWebhook, DbStockAlert, DbTicket},
+        sql::{
+            db_delete_seller_webhook, db_get_seller_webhook, db_insert_event,
+            db_insert_stock_alert, db_insert_ticket, db_upsert_seller_webhook,
+        },
+    },
+    gql::{models::NewTicket, validations::check_webhook_url},
+    stock_alerts::{check_stock, reached_thresholds},
+    webhooks::{gen_webhook_secret, webhook_body, webhook_signature},
+};
+
+mod common;
+
+fn new_ticket(event_id: uuid::Uuid, quantity_available: Option<i32>) -> NewTicket {
+    NewTicket {
+        ticket_name: common::gen_string(10),
+        description: None,
+        price: None,
+        max_release_price: None,
+        quantity_available,
+        min_purchase_quantity: None,
+        max_purchase_quantity: None,
+        allow_transfers: Some(true),
+        currency: None,
+        event_id,
+    }
+}
+
+#[test]
+fn test_reached_thresholds() {
+    let thresholds = [10, 0, 50];
+    assert!(reached_thresholds(&thresholds, 100, 51).is_empty());
+    assert_eq!(vec![50], reached_thresholds(&thresholds, 100, 50));
+    assert_eq!(vec![10, 50], reached_thresholds(&thresholds, 100, 10));
+    assert_eq!(vec![0, 10, 50], reached_thresholds(&thresholds, 100, 0));
+    // a share of a few tickets is rounded up, 10% of 5 is 1 ticket
+    assert_eq!(vec![10, 50], reached_thresholds(&thresholds, 5, 1));
+    // thresholds over 100% are never reached
+    assert!(reached_thresholds(&[150], 100, 100).is_empty());
+}
+
+#[test]
+fn test_webhooks() {
+    assert!(check_webhook_url(" https://seller.example.com/hooks ").is_ok());
+    assert!(check_webhook_url("http://seller.example.com/hooks").is_err());
+    assert!(check_webhook_url("not a url").is_err());
+    assert!(check_webhook_url(&format!("https://example.com/{}", "a".repeat(2048))).is_err());
+
+    let secret = gen_webhook_secret();
+    assert_eq!(64, secret.len());
+    assert_ne!(secret, gen_webhook_secret());
+
+    let body = webhook_body(
+        "ticket.stock_alert",
+        &serde_json::json!({ "soldOut": true }),
+    );
+    let signature = webhook_signature(&secret, &body);
+    assert!(signature.starts_with("sha256="));
+    assert_eq!(signature, webhook_signature(&secret, &body));
+    assert_ne!(signature, webhook_signature(&gen_webhook_secret(), &body));
+    // RFC 4231 test case 2
+    assert_eq!(
+        "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        webhook_signature("Jefe", "what do ya want for nothing?")
+    );
+}
+
+#[tokio::test]
+async fn test_seller_webhook() {
+    let db_client = common::connect().await;
+    let seller = common::create_user(&db_client, Role::Seller).await;
+
+    let first = DbSellerWebhook::new(seller, "https://one.example.com", &gen_webhook_secret());
+    db_upsert_seller_webhook(&db_client, &first)
+        .await
+        .expect("failed to set webhook");
+    // registering again replaces the url and secret
+    let second = DbSellerWebhook::new(seller, "https://two.example.com", &gen_webhook_secret());
+    db_upsert_seller_webhook(&db_client, &second)
+        .await
+        .expect("failed to set webhook");
+    let db_webhook = db_get_seller_webhook(&db_client, &seller)
+        .await
+        .expect("failed to get webhook")
+        .expect("a webhook");
+    assert_eq!(second.url, db_webhook.url);
+    assert_eq!(second.secret, db_webhook.secret);
+
+    assert!(db_delete_seller_webhook(&db_client, &seller)
+        .await
+        .expect("failed to remove webhook"));
+    assert!(!db_delete_seller_webhook(&db_client, &seller)
+        .await
+        .expect("failed to remove webhook"));
+}
+
+#[tokio::test]
+async fn test_check_stock() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+
+    let seller = common::create_user(db_client, Role::Seller).await;
+    let db_event = DbEvent::new(&common::gen_string(20), seller);
+    db_insert_event(db_client, &db_event)
+        .await
+        .expect("failed to insert event");
+    let mut db_ticket = DbTicket::new(new_ticket(db_event.id, Some(20)), &db_event);
+    db_insert_ticket(db_client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+
+    let mut check = |quantity_reserved: i32| {
+        db_ticket.quantity_reserved = quantity_reserved;
+        let db_ticket = db_ticket.clone();
+        let (ctx, db_event) = (&resources.ctx, &db_event);
+        async move {
+            check_stock(ctx, db_event, &db_ticket)
+                .await
+                .expect("failed to check stock")
+        }
+    };
+
+    assert_eq!(None, check(10).await);
+    let alert = check(18).await.expect("a low-stock alert");
+    assert_eq!(10, alert.threshold_percent);
+    assert_eq!(2, alert.quantity_remaining);
+    assert!(!alert.sold_out);
+    // once per threshold
+    assert_eq!(None, check(19).await);
+
+    let alert = check(20).await.expect("a sell-out alert");
+    assert_eq!(0, alert.threshold_percent);
+    assert!(alert.sold_out);
+    assert_eq!(None, check(20).await);
+    assert!(
+        !db_insert_stock_alert(db_client, &DbStockAlert::new(alert.ticket_id, 0))
+            .await
+            .expect("failed to record stock alert")
+    );
+
+    // tickets of unlimited quantity never run low
+    let unlimited = DbTicket::new(new_ticket(db_event.id, None), &db_event);
+    db_insert_ticket(db_client, &unlimited)
+        .await
+        .expect("failed to insert ticket");
+    assert_eq!(
+        None,
+        check_stock(&resources.ctx, &db_event, &unlimited)
+            .await
+            .expect("failed to check stock")
+    );
+}
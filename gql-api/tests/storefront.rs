@@ -0,0 +1,91 @@
+use common::TestContextBuilder;
+use gql_api::{
+    auth::Role,
+    db::sql::db_update_event_status,
+    gql::{
+        models::EventStatus,
+        mutations::{PublicMutationRoot, SellerMutationRoot},
+        quiries::{PublicQueryRoot, SellerQueryRoot},
+        schema::{Context as ResourcesContext, PublicSchema, SellerSchema},
+        subscriptions::PublicSubscriptionRoot,
+    },
+};
+use juniper::EmptySubscription;
+
+mod common;
+
+async fn set_seller_slug(ctx: &ResourcesContext, seller_slug: &str) -> bool {
+    let schema = SellerSchema::new(
+        SellerQueryRoot,
+        SellerMutationRoot,
+        EmptySubscription::new(),
+    );
+    let mutation = format!(
+        r#"mutation {{ setSellerSlug(sellerSlug: "{}") {{ sellerSlug }} }}"#,
+        seller_slug
+    );
+    let (_, errors) = juniper::execute(&mutation, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid mutation");
+    errors.is_empty()
+}
+
+/// The number of published events of the seller's storefront, `None` without a storefront
+async fn storefront_events(ctx: &ResourcesContext, slug: &str) -> Option<usize> {
+    let schema = PublicSchema::new(PublicQueryRoot, PublicMutationRoot, PublicSubscriptionRoot);
+    let query = format!(
+        r#"query {{ seller(slug: "{}") {{ sellerSlug events {{ id }} }} }}"#,
+        slug
+    );
+    let (value, errors) = juniper::execute(&query, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid query");
+    assert!(errors.is_empty());
+    let seller = value.as_object_value()?.get_field_value("seller")?;
+    let events = seller.as_object_value()?.get_field_value("events")?;
+    events.as_list_value().map(|events| events.len())
+}
+
+#[tokio::test]
+async fn test_seller_storefront() {
+    let resources = TestContextBuilder::new().build().await;
+    let event = common::create_event(&resources.ctx.db_client).await;
+    let seller_ctx = resources.ctx.for_user(Some(event.created_by_user));
+    let seller_slug = common::gen_string(12).to_lowercase();
+
+    // slugs must already be url friendly
+    assert!(!set_seller_slug(&seller_ctx, "My Shop").await);
+    assert!(!set_seller_slug(&seller_ctx, "ab").await);
+    assert!(set_seller_slug(&seller_ctx, &seller_slug).await);
+
+    // only sellers have a storefront, and its slug is theirs
+    let other_seller_id = common::create_user(&resources.ctx.db_client, Role::Seller).await;
+    let other_seller_ctx = resources.ctx.for_user(Some(other_seller_id));
+    assert!(!set_seller_slug(&other_seller_ctx, &seller_slug).await);
+    let buyer_id = common::create_user(&resources.ctx.db_client, Role::Buyer).await;
+    let buyer_ctx = resources.ctx.for_user(Some(buyer_id));
+    assert!(!set_seller_slug(&buyer_ctx, &common::gen_string(12).to_lowercase()).await);
+
+    // drafts are not listed
+    assert_eq!(
+        Some(0),
+        storefront_events(&resources.ctx, &seller_slug).await
+    );
+    db_update_event_status(
+        &resources.ctx.db_client,
+        &event.id,
+        EventStatus::Draft,
+        EventStatus::Final,
+    )
+    .await
+    .expect("unable to publish event");
+    assert_eq!(
+        Some(1),
+        storefront_events(&resources.ctx, &seller_slug).await
+    );
+
+    assert_eq!(
+        None,
+        storefront_events(&resources.ctx, "no-such-seller").await
+    );
+}
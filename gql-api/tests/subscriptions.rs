@@ -0,0 +1,81 @@
+use futures::StreamExt;
+use gql_api::{
+    auth::Role,
+    db::{models::DbEvent, sql::db_insert_event},
+    gql::{
+        models::EventStatus,
+        mutations::PrivateMutationRoot,
+        quiries::PrivateQueryRoot,
+        schema::{Context as ResourcesContext, PrivateSchema},
+        subscriptions::PrivateSubscriptionRoot,
+    },
+};
+use juniper::Value;
+
+mod common;
+
+/// The ids of the events of the first item of the caller's `eventSub`
+async fn subscribed_event_ids(ctx: &ResourcesContext, id: &uuid::Uuid) -> Vec<String> {
+    let schema = PrivateSchema::new(
+        PrivateQueryRoot,
+        PrivateMutationRoot,
+        PrivateSubscriptionRoot,
+    );
+    let subscription = format!(r#"subscription {{ eventSub(id: "{}") {{ id }} }}"#, id);
+    let (value, errors) = juniper::resolve_into_stream(
+        &subscription,
+        None,
+        &schema,
+        &juniper::Variables::new(),
+        ctx,
+    )
+    .await
+    .expect("invalid subscription");
+    assert!(errors.is_empty());
+
+    let mut stream = match value {
+        Value::Object(object) => match object.into_iter().next() {
+            Some((_, Value::Scalar(stream))) => stream,
+            _ => panic!("eventSub should be a stream"),
+        },
+        _ => panic!("eventSub should be a stream"),
+    };
+    let events = stream.next().await.expect("an item").expect("events");
+    let events = serde_json::to_value(&events).expect("serializable value");
+    events
+        .as_array()
+        .expect("events")
+        .iter()
+        .map(|event| event["id"].as_str().expect("an id").to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_private_event_sub() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+    let seller = common::create_user(db_client, Role::Seller).await;
+    let other_seller = common::create_user(db_client, Role::Seller).await;
+    let buyer = common::create_user(db_client, Role::Buyer).await;
+
+    let draft = DbEvent {
+        event_status: EventStatus::Draft,
+        ..DbEvent::new(&common::gen_string(20), seller)
+    };
+    db_insert_event(db_client, &draft)
+        .await
+        .expect("failed to insert event");
+
+    // the seller follows their own events
+    let ctx = resources.ctx.for_user(Some(seller));
+    assert_eq!(
+        vec![draft.id.to_string()],
+        subscribed_event_ids(&ctx, &draft.id).await
+    );
+
+    // nobody else does
+    for user_id in [other_seller, buyer] {
+        let ctx = resources.ctx.for_user(Some(user_id));
+        assert!(subscribed_event_ids(&ctx, &draft.id).await.is_empty());
+    }
+}
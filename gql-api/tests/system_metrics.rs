@@ -0,0 +1,91 @@
+use common::TestContextBuilder;
+use gql_api::{
+    auth::Role,
+    gql::{
+        mutations::AdminMutationRoot,
+        quiries::AdminQueryRoot,
+        schema::{AdminSchema, Context as ResourcesContext},
+    },
+    metrics::{grpc_call_counts, grpc_error, grpc_timer, observe_sms, sms_counts},
+};
+use juniper::EmptySubscription;
+
+mod common;
+
+/// The system metrics, `None` if the query failed
+async fn system_metrics(ctx: &ResourcesContext) -> Option<serde_json::Value> {
+    let schema = AdminSchema::new(AdminQueryRoot, AdminMutationRoot, EmptySubscription::new());
+    let query = r#"query { systemMetrics(days: 7) {
+        users { userType userStatus total }
+        events { eventStatus total }
+        reservationsPerDay { day reservations tickets }
+        smsSent smsFailed
+        grpcCalls { method calls errors errorRate }
+    } }"#;
+    let (value, errors) = juniper::execute(query, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid query");
+    if !errors.is_empty() {
+        return None;
+    }
+    let value = serde_json::to_value(&value).expect("serializable value");
+    Some(value["systemMetrics"].clone())
+}
+
+#[test]
+fn test_registry_counters() {
+    let (sent, failed) = sms_counts();
+    observe_sms(true);
+    observe_sms(false);
+    assert!(sms_counts().0 > sent);
+    assert!(sms_counts().1 > failed);
+
+    drop(grpc_timer("test_metrics_method"));
+    drop(grpc_timer("test_metrics_method"));
+    grpc_error("test_metrics_method");
+    let counts = grpc_call_counts()
+        .into_iter()
+        .find(|counts| counts.method == "test_metrics_method")
+        .expect("the method's counts");
+    assert_eq!(2, counts.calls);
+    assert_eq!(1, counts.errors);
+}
+
+#[tokio::test]
+async fn test_system_metrics() {
+    let resources = TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+    let event = common::create_event(db_client).await;
+    let super_admin = common::create_user(db_client, Role::SuperAdmin).await;
+    let admin = common::create_user(db_client, Role::Admin).await;
+
+    // only superadmins see the metrics
+    assert!(system_metrics(&resources.ctx.for_user(Some(admin)))
+        .await
+        .is_none());
+    assert!(
+        system_metrics(&resources.ctx.for_user(Some(event.created_by_user)))
+            .await
+            .is_none()
+    );
+
+    let metrics = system_metrics(&resources.ctx.for_user(Some(super_admin)))
+        .await
+        .expect("superadmins see the metrics");
+    let super_admins = metrics["users"]
+        .as_array()
+        .expect("user counts")
+        .iter()
+        .filter(|count| count["userType"] == "superadmin")
+        .map(|count| count["total"].as_i64().expect("a total"))
+        .sum::<i64>();
+    assert!(super_admins >= 1);
+    assert!(metrics["events"]
+        .as_array()
+        .expect("event counts")
+        .iter()
+        .any(|count| count["eventStatus"] == "DRAFT"));
+    assert!(metrics["reservationsPerDay"].is_array());
+    assert!(metrics["smsSent"].is_number());
+    assert!(metrics["grpcCalls"].is_array());
+}
@@ -0,0 +1,205 @@
+use chrono::{Duration, Utc};
+use gql_api::{
+    auth::{create_jwt, Role},
+    config::TicketPdfConfig,
+    db::{
+        models::{DbTicket, DbTicketReservation},
+        sql::{db_get_event_by_id, db_insert_ticket, db_reserve_ticket},
+    },
+    gql::models::NewTicket,
+    http::{
+        routes::ticket_pdf_route,
+        ticket_pdf::{render_ticket_pdf, ticket_pdf_key, TicketPdf, TICKET_PDF_CONTENT_TYPE},
+    },
+    security::ticket_qr::{ticket_payload, verify_ticket_payload, TicketPayload},
+};
+use warp::http::StatusCode;
+
+mod common;
+
+const SECRET: &str = "ticket-signing-secret";
+
+fn config() -> TicketPdfConfig {
+    TicketPdfConfig {
+        signing_secret: SECRET.to_string(),
+        storage_prefix: "tickets".to_string(),
+    }
+}
+
+fn new_ticket(event: &gql_api::db::models::DbEvent) -> DbTicket {
+    DbTicket::new(
+        NewTicket {
+            ticket_name: "General admission".to_string(),
+            description: None,
+            price: None,
+            max_release_price: None,
+            quantity_available: None,
+            min_purchase_quantity: None,
+            max_purchase_quantity: None,
+            allow_transfers: None,
+            currency: None,
+            event_id: event.id,
+        },
+        event,
+    )
+}
+
+#[test]
+fn test_ticket_payload() {
+    let payload = TicketPayload {
+        reservation_id: uuid::Uuid::new_v4(),
+        verification_code: "AB12CD".to_string(),
+        quantity: 2,
+    };
+    let signed = ticket_payload(SECRET, &payload);
+    assert!(signed.starts_with(&format!("{}.AB12CD.2.", payload.reservation_id)));
+    assert_eq!(
+        Some(payload.clone()),
+        verify_ticket_payload(SECRET, &signed)
+    );
+
+    // forged and foreign tickets are told apart
+    let forged = signed.replacen(".2.", ".9.", 1);
+    assert_eq!(None, verify_ticket_payload(SECRET, &forged));
+    assert_eq!(None, verify_ticket_payload("another-secret", &signed));
+    assert_eq!(None, verify_ticket_payload(SECRET, "not a ticket"));
+}
+
+#[test]
+fn test_render_ticket_pdf() {
+    let event = gql_api::db::models::DbEvent::new("Concert (Früh)", uuid::Uuid::new_v4());
+    let ticket = new_ticket(&event);
+    let reservation = DbTicketReservation::new(
+        uuid::Uuid::new_v4(),
+        Utc::now().naive_utc(),
+        "AB12CD",
+        event.id,
+        ticket.id,
+        uuid::Uuid::new_v4(),
+        2,
+    );
+    let qr_payload = ticket_payload(
+        SECRET,
+        &TicketPayload {
+            reservation_id: reservation.id,
+            verification_code: reservation.verification_code.clone(),
+            quantity: reservation.quantity,
+        },
+    );
+
+    let pdf = render_ticket_pdf(&TicketPdf {
+        event: &event,
+        ticket: &ticket,
+        reservation: &reservation,
+        holder: "buyer",
+        qr_payload: &qr_payload,
+    })
+    .expect("a pdf");
+    assert!(pdf.starts_with(b"%PDF-1.4\n"));
+    assert!(pdf.ends_with(b"%%EOF\n"));
+    // parentheses escaped, Latin-1 kept as WinAnsi
+    assert!(pdf
+        .windows(17)
+        .any(|window| window == b"(Concert \\(Fr\xfch\\)"));
+
+    // the cached ticket is renewed along with the event
+    let key = ticket_pdf_key("tickets/", &reservation.id, &qr_payload, &event.modified_at);
+    assert!(key.starts_with(&format!("tickets/{}/", reservation.id)));
+    assert!(key.ends_with(".pdf"));
+    assert_eq!(
+        key,
+        ticket_pdf_key("tickets", &reservation.id, &qr_payload, &event.modified_at)
+    );
+    assert_ne!(
+        key,
+        ticket_pdf_key(
+            "tickets",
+            &reservation.id,
+            &qr_payload,
+            &(event.modified_at + Duration::seconds(1))
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_ticket_pdf_route() {
+    let resources = common::TestContextBuilder::new()
+        .ticket_pdf(config())
+        .build()
+        .await;
+    let db_client = &resources.ctx.db_client;
+    let route = ticket_pdf_route(resources.ctx.clone(), warp::log("ticket_pdf"));
+
+    let event = common::create_event(db_client).await;
+    let ticket = new_ticket(&event);
+    db_insert_ticket(db_client, &ticket)
+        .await
+        .expect("failed to insert ticket");
+    let buyer = common::create_user(db_client, Role::Buyer).await;
+    let reservation = DbTicketReservation::new(
+        uuid::Uuid::new_v4(),
+        Utc::now().naive_utc(),
+        &common::gen_string(6),
+        event.id,
+        ticket.id,
+        buyer,
+        1,
+    );
+    db_reserve_ticket(db_client, &reservation)
+        .await
+        .expect("failed to reserve ticket")
+        .expect("ticket should be reserved");
+
+    let request = |user_id: uuid::Uuid, file_name: String| {
+        let jwt = create_jwt(&user_id.to_string(), &Role::Buyer).expect("failed to create jwt");
+        warp::test::request()
+            .path(&format!("/api/v1/buyer/tickets/{}", file_name))
+            .header("authorization", format!("Bearer {}", jwt))
+    };
+    let file_name = format!("{}.pdf", reservation.id);
+
+    let response = request(buyer, file_name.clone()).reply(&route).await;
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!(TICKET_PDF_CONTENT_TYPE, response.headers()["content-type"]);
+    assert_eq!(
+        format!("attachment; filename=\"ticket-{}.pdf\"", reservation.id),
+        response.headers()["content-disposition"]
+    );
+    assert!(response.body().starts_with(b"%PDF-1.4"));
+
+    // the rendered ticket is cached, and served from the storage
+    let qr_payload = ticket_payload(
+        SECRET,
+        &TicketPayload {
+            reservation_id: reservation.id,
+            verification_code: reservation.verification_code.clone(),
+            quantity: reservation.quantity,
+        },
+    );
+    // reserving touched the event
+    let event = db_get_event_by_id(db_client, &event.id)
+        .await
+        .expect("failed to get event");
+    let key = ticket_pdf_key("tickets", &reservation.id, &qr_payload, &event.modified_at);
+    let cached = resources.storage.object(&key).expect("a cached ticket");
+    assert_eq!(
+        Some(TICKET_PDF_CONTENT_TYPE),
+        cached.content_type.as_deref()
+    );
+    assert_eq!(response.body().to_vec(), cached.content);
+    let response = request(buyer, file_name.clone()).reply(&route).await;
+    assert_eq!(cached.content, response.body().to_vec());
+
+    // others' reservations, and unknown ones, are not found
+    let other = common::create_user(db_client, Role::Buyer).await;
+    let response = request(other, file_name).reply(&route).await;
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+    let response = request(buyer, format!("{}.pdf", uuid::Uuid::new_v4()))
+        .reply(&route)
+        .await;
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+    let response = request(buyer, reservation.id.to_string())
+        .reply(&route)
+        .await;
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+}
@@ -0,0 +1,121 @@
+use chrono::Utc;
+use gql_api::{
+    auth::Role,
+    db::models::{DbTicket, DbTicketReservation, DbTicketTransfer},
+    gql::{
+        models::{NewTicket, NewTicketTransfer},
+        validations::check_ticket_transfer_payload,
+    },
+    near::NearAmount,
+};
+
+mod common;
+
+fn new_ticket(event_id: uuid::Uuid, allow_transfers: bool) -> NewTicket {
+    NewTicket {
+        ticket_name: common::gen_string(10),
+        description: None,
+        price: Some(NearAmount::from_whole_near(10)),
+        max_release_price: Some(NearAmount::from_whole_near(12)),
+        quantity_available: Some(10),
+        min_purchase_quantity: Some(1),
+        max_purchase_quantity: Some(2),
+        allow_transfers: Some(allow_transfers),
+        currency: None,
+        event_id,
+    }
+}
+
+#[tokio::test]
+async fn test_ticket_transfers_crud() {
+    let cfg = common::setup().await;
+
+    let db_ticket = DbTicket::new(new_ticket(cfg.event.id, true), &cfg.event);
+    gql_api::db::sql::db_insert_ticket(&cfg.client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+
+    let sender = common::create_user(&cfg.client, Role::Buyer).await;
+    let receiver = common::create_user(&cfg.client, Role::Buyer).await;
+
+    let reservation = DbTicketReservation::new(
+        uuid::Uuid::new_v4(),
+        Utc::now().naive_utc(),
+        &common::gen_string(10),
+        cfg.event.id,
+        db_ticket.id,
+        sender,
+        1,
+    );
+    gql_api::db::sql::db_insert_ticket_reservation(&cfg.client, &reservation)
+        .await
+        .expect("failed to insert reservation");
+
+    let moved = gql_api::db::sql::db_update_ticket_reservation_owner(
+        &cfg.client,
+        &reservation.id,
+        &sender,
+        &receiver,
+    )
+    .await
+    .expect("failed to move reservation")
+    .expect("reservation should belong to the sender");
+    assert_eq!(receiver, moved.user_id);
+
+    // the sender no longer owns the reservation
+    let stale = gql_api::db::sql::db_update_ticket_reservation_owner(
+        &cfg.client,
+        &reservation.id,
+        &sender,
+        &receiver,
+    )
+    .await
+    .expect("failed to move reservation");
+    assert!(stale.is_none());
+
+    let expected = DbTicketTransfer::new(
+        Some(NearAmount::from_whole_near(11)),
+        common::gen_string(20),
+        reservation.id,
+        db_ticket.id,
+        sender,
+        receiver,
+    );
+    gql_api::db::sql::db_insert_ticket_transfer(&cfg.client, &expected)
+        .await
+        .expect("failed to insert ticket transfer");
+
+    let actual =
+        gql_api::db::sql::db_get_ticket_transfers_by_reservation_id(&cfg.client, &reservation.id)
+            .await
+            .expect("unable to get ticket transfers");
+    assert_eq!(1, actual.len());
+    assert_eq!(expected.id, actual[0].id);
+    assert_eq!(expected.tx_hash, actual[0].tx_hash);
+}
+
+#[tokio::test]
+async fn test_ticket_transfer_payload() {
+    let cfg = common::setup().await;
+
+    let transfer = |price: Option<NearAmount>| NewTicketTransfer {
+        reservation_id: uuid::Uuid::new_v4(),
+        to_username: common::gen_string(10),
+        price,
+    };
+
+    let db_ticket = DbTicket::new(new_ticket(cfg.event.id, true), &cfg.event);
+    assert!(check_ticket_transfer_payload(&transfer(None), &db_ticket).is_ok());
+    let max_release_price = NearAmount::from_whole_near(12);
+    assert!(check_ticket_transfer_payload(&transfer(Some(max_release_price)), &db_ticket).is_ok());
+    let above_max_release_price = max_release_price
+        .checked_add(NearAmount::from_yocto(1))
+        .unwrap();
+    assert!(
+        check_ticket_transfer_payload(&transfer(Some(above_max_release_price)), &db_ticket)
+            .is_err()
+    );
+
+    let db_ticket = DbTicket::new(new_ticket(cfg.event.id, false), &cfg.event);
+    assert!(check_ticket_transfer_payload(&transfer(None), &db_ticket).is_err());
+}
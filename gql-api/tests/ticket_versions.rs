@@ -0,0 +1,116 @@
+use gql_api::{
+    db::{
+        models::DbTicket,
+        sql::{db_get_ticket_by_id, db_insert_ticket, db_update_ticket},
+    },
+    error::TicketUpdateError,
+    gql::{
+        error::{ErrorCode, GqlError, CURRENT_EXTENSION},
+        models::NewTicket,
+    },
+};
+use juniper::{graphql_value, DefaultScalarValue, FieldError, IntoFieldError};
+
+mod common;
+
+fn new_ticket(event_id: uuid::Uuid) -> NewTicket {
+    NewTicket {
+        ticket_name: common::gen_string(10),
+        description: None,
+        price: None,
+        max_release_price: None,
+        quantity_available: Some(100),
+        min_purchase_quantity: None,
+        max_purchase_quantity: None,
+        allow_transfers: Some(true),
+        currency: None,
+        event_id,
+    }
+}
+
+#[test]
+fn test_stale_ticket_error() {
+    let db_ticket = DbTicket {
+        version: 3,
+        quantity_reserved: 5,
+        ..DbTicket::new(
+            new_ticket(uuid::Uuid::new_v4()),
+            &gql_api::db::models::DbEvent::new("Concert", uuid::Uuid::new_v4()),
+        )
+    };
+    let error = GqlError::StaleTicket(Box::new(db_ticket.clone()));
+    assert_eq!(ErrorCode::Conflict, error.code());
+    assert_eq!(Some("version"), error.field());
+
+    // the current values come along, to retry the edit on top of them
+    let error: FieldError<DefaultScalarValue> = error.into_field_error();
+    let extensions = error.extensions();
+    assert_eq!(
+        Some(&graphql_value!("CONFLICT")),
+        extensions
+            .as_object_value()
+            .and_then(|e| e.get_field_value("code"))
+    );
+    let current = extensions
+        .as_object_value()
+        .and_then(|e| e.get_field_value(CURRENT_EXTENSION))
+        .and_then(|current| current.as_object_value())
+        .expect("the current values");
+    assert_eq!(Some(&graphql_value!(3)), current.get_field_value("version"));
+    assert_eq!(
+        Some(&graphql_value!(100)),
+        current.get_field_value("quantityAvailable")
+    );
+    assert_eq!(
+        Some(&graphql_value!(5)),
+        current.get_field_value("quantityReserved")
+    );
+    assert_eq!(
+        Some(&graphql_value!(None)),
+        current.get_field_value("maxPurchaseQuantity")
+    );
+}
+
+#[tokio::test]
+async fn test_update_ticket_versions() {
+    let cfg = common::setup().await;
+    let db_ticket = DbTicket::new(new_ticket(cfg.event.id), &cfg.event);
+    db_insert_ticket(&cfg.client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+    assert_eq!(0, db_ticket.version);
+
+    let first_edit = DbTicket {
+        quantity_available: Some(80),
+        ..db_ticket.clone()
+    };
+    let updated = db_update_ticket(&cfg.client, &first_edit, 0)
+        .await
+        .expect("failed to update ticket");
+    assert_eq!(1, updated.version);
+    assert_eq!(Some(80), updated.quantity_available);
+
+    // an edit based on the version before the first one is refused
+    let second_edit = DbTicket {
+        quantity_available: Some(120),
+        ..db_ticket.clone()
+    };
+    match db_update_ticket(&cfg.client, &second_edit, 0).await {
+        Err(TicketUpdateError::VersionConflict(current)) => {
+            assert_eq!(1, current.version);
+            assert_eq!(Some(80), current.quantity_available);
+        }
+        other => panic!("expected a version conflict, got {:?}", other),
+    }
+    let stored = db_get_ticket_by_id(&cfg.client, &db_ticket.id)
+        .await
+        .expect("failed to get ticket");
+    assert_eq!(Some(80), stored.quantity_available);
+
+    // based on the current version it goes through
+    let updated = db_update_ticket(&cfg.client, &second_edit, 1)
+        .await
+        .expect("failed to update ticket");
+    assert_eq!(2, updated.version);
+    assert_eq!(Some(120), updated.quantity_available);
+}
@@ -0,0 +1,102 @@
+use common::TestContextBuilder;
+use gql_api::{
+    auth::Role,
+    config::TopUpsConfig,
+    gql::{
+        mutations::AdminMutationRoot,
+        quiries::AdminQueryRoot,
+        schema::{AdminSchema, Context as ResourcesContext},
+    },
+    jobs::top_ups::TopUpLimits,
+    near::NearAmount,
+};
+use juniper::EmptySubscription;
+
+mod common;
+
+fn near(amount: &str) -> NearAmount {
+    NearAmount::from_near(amount).expect("invalid amount")
+}
+
+/// The amount sent by a manual top-up, `None` if it failed
+async fn top_up_wallet(
+    ctx: &ResourcesContext,
+    user_id: uuid::Uuid,
+    amount: Option<NearAmount>,
+) -> Option<NearAmount> {
+    let schema = AdminSchema::new(AdminQueryRoot, AdminMutationRoot, EmptySubscription::new());
+    let amount = amount
+        .map(|amount| format!(r#", amount: "{}""#, amount))
+        .unwrap_or_default();
+    let mutation = format!(
+        r#"mutation {{ topUpWallet(userId: "{}"{}) {{ amount }} }}"#,
+        user_id, amount
+    );
+    let (value, errors) =
+        juniper::execute(&mutation, None, &schema, &juniper::Variables::new(), ctx)
+            .await
+            .expect("invalid mutation");
+    if !errors.is_empty() {
+        return None;
+    }
+    value
+        .as_object_value()?
+        .get_field_value("topUpWallet")?
+        .as_object_value()?
+        .get_field_value("amount")?
+        .as_scalar_value::<String>()?
+        .parse()
+        .ok()
+}
+
+#[test]
+fn test_top_up_limits() {
+    let limits = TopUpLimits::from_config(&TopUpsConfig::default()).expect("invalid config");
+    assert_eq!(near("0.05"), limits.threshold);
+
+    // only wallets below the threshold
+    assert_eq!(None, limits.top_up_amount(near("0.05"), near("0")));
+    assert_eq!(
+        Some(near("0.1")),
+        limits.top_up_amount(near("0.01"), near("0"))
+    );
+
+    // up to the daily cap
+    assert_eq!(
+        Some(near("0.05")),
+        limits.top_up_amount(near("0.01"), near("0.25"))
+    );
+    assert_eq!(None, limits.top_up_amount(near("0.01"), near("0.3")));
+    assert_eq!(None, limits.capped(near("1"), near("0.4")));
+}
+
+#[tokio::test]
+async fn test_manual_top_up() {
+    let resources = TestContextBuilder::new().build().await;
+    let admin_id = common::create_user(&resources.ctx.db_client, Role::Admin).await;
+    let buyer_id = common::create_user(&resources.ctx.db_client, Role::Buyer).await;
+    let seller_id = common::create_user(&resources.ctx.db_client, Role::Seller).await;
+    let admin_ctx = resources.ctx.for_user(Some(admin_id));
+
+    // admins only, and buyers' wallets only
+    let buyer_ctx = resources.ctx.for_user(Some(buyer_id));
+    assert_eq!(None, top_up_wallet(&buyer_ctx, buyer_id, None).await);
+    assert_eq!(None, top_up_wallet(&admin_ctx, seller_id, None).await);
+    assert!(resources.near_client.calls().is_empty());
+
+    assert_eq!(
+        Some(near("0.25")),
+        top_up_wallet(&admin_ctx, buyer_id, Some(near("0.25"))).await
+    );
+    // the rest of the daily cap
+    assert_eq!(
+        Some(near("0.05")),
+        top_up_wallet(&admin_ctx, buyer_id, None).await
+    );
+    assert_eq!(None, top_up_wallet(&admin_ctx, buyer_id, None).await);
+
+    assert_eq!(
+        vec!["fund_account".to_string(), "fund_account".to_string()],
+        resources.near_client.calls()
+    );
+}
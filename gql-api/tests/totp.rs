@@ -0,0 +1,225 @@
+use bytes::Bytes;
+use common::TestContextBuilder;
+use gql_api::{
+    auth::{Role, UserStatus},
+    config::TotpConfig,
+    db::{models::DbUser, sql::db_insert_user},
+    gql::{
+        mutations::PrivateMutationRoot,
+        quiries::PrivateQueryRoot,
+        schema::{Context as ResourcesContext, PrivateSchema},
+        subscriptions::PrivateSubscriptionRoot,
+    },
+    http::{
+        handlers::{signin_with_password, signin_with_password_verify_totp},
+        models::{SigninResponse, SigninTotpRequiredResponse},
+    },
+    security::{password::hash_password, totp},
+};
+use warp::Reply;
+
+mod common;
+
+/// RFC 6238 test secret, "12345678901234567890" base32 encoded
+const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+fn totp_config() -> TotpConfig {
+    TotpConfig {
+        issuer: "Tickets".to_string(),
+        encryption_secret: "0123456789abcdef0123456789abcdef".to_string(),
+    }
+}
+
+async fn execute(ctx: &ResourcesContext, query: &str) -> Result<juniper::Value, String> {
+    let schema = PrivateSchema::new(
+        PrivateQueryRoot,
+        PrivateMutationRoot,
+        PrivateSubscriptionRoot,
+    );
+    let (value, errors) = juniper::execute(query, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid query");
+    match errors.first() {
+        Some(error) => Err(error.error().message().to_string()),
+        None => Ok(value),
+    }
+}
+
+async fn post<R: Reply>(response: Result<R, warp::Rejection>) -> Option<serde_json::Value> {
+    let response = response.ok()?.into_response();
+    let body = warp::hyper::body::to_bytes(response.into_body())
+        .await
+        .expect("unreadable response body");
+    Some(serde_json::from_slice(&body).expect("unparsable response"))
+}
+
+#[test]
+fn test_totp_codes() {
+    // RFC 6238 SHA1 vectors, truncated to 6 digits
+    assert_eq!(
+        Some("287082".to_string()),
+        totp::code(RFC_SECRET, totp::step(59))
+    );
+    assert_eq!(
+        Some("081804".to_string()),
+        totp::code(RFC_SECRET, totp::step(1111111109))
+    );
+    assert_eq!(
+        Some("050471".to_string()),
+        totp::code(RFC_SECRET, totp::step(1111111111))
+    );
+    assert_eq!(None, totp::code("not base32!", 1));
+
+    // the codes of neighbouring steps are accepted, once each
+    let now = 1111111109;
+    let previous = totp::code(RFC_SECRET, totp::step(now) - 1).unwrap();
+    assert_eq!(
+        Some(totp::step(now) - 1),
+        totp::verify(RFC_SECRET, &previous, now, 0)
+    );
+    assert_eq!(
+        None,
+        totp::verify(RFC_SECRET, &previous, now, totp::step(now) - 1)
+    );
+    let stale = totp::code(RFC_SECRET, totp::step(now) - 2).unwrap();
+    assert_eq!(None, totp::verify(RFC_SECRET, &stale, now, 0));
+    assert_eq!(None, totp::verify(RFC_SECRET, "000000", now, 0));
+}
+
+#[test]
+fn test_totp_secrets() {
+    let secret = totp::gen_secret();
+    assert_eq!(32, secret.len());
+    assert_ne!(secret, totp::gen_secret());
+
+    let encryption_secret = totp_config().encryption_secret;
+    let encrypted = totp::encrypt_secret(&encryption_secret, &secret);
+    assert!(!encrypted.contains(&secret));
+    assert_eq!(
+        Some(secret.clone()),
+        totp::decrypt_secret(&encryption_secret, &encrypted)
+    );
+
+    let uri = totp::provisioning_uri("My Tickets", "jane", &secret);
+    assert!(uri.starts_with("otpauth://totp/My%20Tickets:jane?"));
+    assert!(uri.contains(&format!("secret={}", secret)));
+    assert!(uri.contains("digits=6"));
+    assert!(uri.contains("period=30"));
+}
+
+#[tokio::test]
+async fn test_totp_signin() {
+    let resources = TestContextBuilder::new().totp(totp_config()).build().await;
+    let db_user = DbUser::new(
+        uuid::Uuid::new_v4(),
+        None,
+        common::gen_string(20),
+        None,
+        None,
+        Some(hash_password(b"password").expect("unable to hash password")),
+        None,
+        Role::Seller,
+        common::gen_string(20),
+        "0".to_string(),
+        UserStatus::PhoneVerified,
+    );
+    db_insert_user(&resources.ctx.db_client, &db_user)
+        .await
+        .expect("unable to create user");
+    let ctx = resources.ctx.for_user(Some(db_user.id));
+    let signin_body = serde_json::json!({ "username": db_user.username, "password": "password" });
+
+    // enroll, two-factor authentication is off until confirmed with a code
+    let enrollment = execute(&ctx, "mutation { enrollTotp { secret provisioningUri } }")
+        .await
+        .expect("enrollment failed");
+    let secret = enrollment
+        .as_object_value()
+        .and_then(|value| value.get_field_value("enrollTotp"))
+        .and_then(|value| value.as_object_value())
+        .and_then(|value| value.get_field_value("secret"))
+        .and_then(|value| value.as_scalar_value::<String>())
+        .cloned()
+        .expect("enrollment should return the secret");
+    let response = post(
+        signin_with_password(
+            "seller".to_string(),
+            "127.0.0.1".to_string(),
+            resources.ctx.clone(),
+            Bytes::from(signin_body.to_string()),
+        )
+        .await,
+    )
+    .await
+    .expect("signin failed");
+    serde_json::from_value::<SigninResponse>(response).expect("signin should issue a jwt");
+
+    assert!(execute(&ctx, r#"mutation { confirmTotp(code: "000000") }"#)
+        .await
+        .is_err());
+    let now = chrono::Utc::now().timestamp();
+    let code = totp::code(&secret, totp::step(now)).unwrap();
+    execute(
+        &ctx,
+        &format!(r#"mutation {{ confirmTotp(code: "{}") }}"#, code),
+    )
+    .await
+    .expect("confirmation failed");
+    assert!(execute(&ctx, "mutation { enrollTotp { secret } }")
+        .await
+        .is_err());
+
+    // the password alone is not enough anymore
+    let response = post(
+        signin_with_password(
+            "seller".to_string(),
+            "127.0.0.1".to_string(),
+            resources.ctx.clone(),
+            Bytes::from(signin_body.to_string()),
+        )
+        .await,
+    )
+    .await
+    .expect("signin failed");
+    let totp_required: SigninTotpRequiredResponse =
+        serde_json::from_value(response).expect("signin should ask for a code");
+
+    // the code used to confirm is spent, the next one signs in
+    let verify = |code: String| {
+        serde_json::json!({ "totpToken": totp_required.totp_token, "code": code }).to_string()
+    };
+    assert!(post(
+        signin_with_password_verify_totp(
+            "seller".to_string(),
+            resources.ctx.clone(),
+            Bytes::from(verify(code))
+        )
+        .await
+    )
+    .await
+    .is_none());
+    let next_code = totp::code(&secret, totp::step(now) + 1).unwrap();
+    let response = post(
+        signin_with_password_verify_totp(
+            "seller".to_string(),
+            resources.ctx.clone(),
+            Bytes::from(verify(next_code.clone())),
+        )
+        .await,
+    )
+    .await
+    .expect("second step failed");
+    serde_json::from_value::<SigninResponse>(response).expect("second step should issue a jwt");
+
+    // the challenge is single-use
+    assert!(post(
+        signin_with_password_verify_totp(
+            "seller".to_string(),
+            resources.ctx.clone(),
+            Bytes::from(verify(next_code))
+        )
+        .await
+    )
+    .await
+    .is_none());
+}
@@ -0,0 +1,93 @@
+use common::TestContextBuilder;
+use gql_api::{
+    auth::Role,
+    fakes::FakeNearClient,
+    gql::{
+        models::{TransactionState, TransactionStatus},
+        mutations::PrivateMutationRoot,
+        quiries::PrivateQueryRoot,
+        schema::{Context as ResourcesContext, PrivateSchema},
+        subscriptions::PrivateSubscriptionRoot,
+    },
+};
+
+mod common;
+
+/// The status of the transaction, none if the query failed
+async fn transaction_status(ctx: &ResourcesContext, tx_hash: &str) -> Option<serde_json::Value> {
+    let schema = PrivateSchema::new(
+        PrivateQueryRoot,
+        PrivateMutationRoot,
+        PrivateSubscriptionRoot,
+    );
+    let query = format!(
+        r#"query {{ transactionStatus(txHash: "{}", accountId: "seller.testnet") {{
+            txHash status failureReason
+        }} }}"#,
+        tx_hash
+    );
+    let (value, errors) = juniper::execute(&query, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid query");
+    if !errors.is_empty() {
+        return None;
+    }
+    let value = serde_json::to_value(&value).expect("serializable value");
+    Some(value["transactionStatus"].clone())
+}
+
+fn gen_tx_hash() -> String {
+    bs58::encode(uuid::Uuid::new_v4().as_bytes().repeat(2)).into_string()
+}
+
+#[test]
+fn test_transaction_status() {
+    let status = TransactionStatus::new("hash".to_string(), "SUCCESS", "");
+    assert_eq!(TransactionState::Success, status.status);
+    assert_eq!(None, status.failure_reason);
+
+    let status = TransactionStatus::new("hash".to_string(), "PENDING", "");
+    assert_eq!(TransactionState::Pending, status.status);
+    // anything else is not final yet
+    let status = TransactionStatus::new("hash".to_string(), "", "");
+    assert_eq!(TransactionState::Pending, status.status);
+
+    let status = TransactionStatus::new("hash".to_string(), "FAILURE", "Exceeded the prepaid gas");
+    assert_eq!(TransactionState::Failure, status.status);
+    assert_eq!(
+        Some("Exceeded the prepaid gas".to_string()),
+        status.failure_reason
+    );
+    // failures always have a reason
+    let status = TransactionStatus::new("hash".to_string(), "FAILURE", "");
+    assert!(status.failure_reason.is_some());
+}
+
+#[tokio::test]
+async fn test_transaction_status_query() {
+    let resources = TestContextBuilder::new()
+        .near_client(FakeNearClient {
+            tx_status: "FAILURE".to_string(),
+            tx_error: "Exceeded the prepaid gas".to_string(),
+            ..Default::default()
+        })
+        .build()
+        .await;
+    let seller = common::create_user(&resources.ctx.db_client, Role::Seller).await;
+    let ctx = resources.ctx.for_user(Some(seller));
+
+    let tx_hash = gen_tx_hash();
+    let status = transaction_status(&ctx, &tx_hash)
+        .await
+        .expect("a transaction status");
+    assert_eq!(tx_hash, status["txHash"]);
+    assert_eq!("FAILURE", status["status"]);
+    assert_eq!("Exceeded the prepaid gas", status["failureReason"]);
+    assert!(resources
+        .near_client
+        .calls()
+        .contains(&"get_tx_status".to_string()));
+
+    // not a NEAR transaction hash
+    assert!(transaction_status(&ctx, "not-a-hash").await.is_none());
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::{connect, create_user, gen_string};
+use gql_api::{
+    auth::Role,
+    db::{
+        models::DbUser,
+        sql::{db_get_user_by_id, db_insert_user, unique_violation},
+    },
+    error::{handle_rejection, ConflictError, Error},
+    gql::error::{ErrorCode, GqlError},
+};
+use juniper::{graphql_value, DefaultScalarValue, FieldError, IntoFieldError};
+use tokio_postgres::Client;
+use warp::{http::StatusCode, Filter};
+
+/// Inserts a copy of the user under a new id, only the fields in `change` differ
+async fn insert_copy(
+    db_client: &Client,
+    user: &DbUser,
+    change: impl FnOnce(&mut DbUser),
+) -> tokio_postgres::Error {
+    let mut copy = DbUser {
+        id: uuid::Uuid::new_v4(),
+        username: gen_string(20),
+        wallet_id: gen_string(20),
+        ..user.clone()
+    };
+    change(&mut copy);
+    db_insert_user(db_client, &copy)
+        .await
+        .expect_err("the copy should violate a unique constraint")
+}
+
+#[test]
+fn test_conflict_from_constraint() {
+    assert_eq!(
+        Some(ConflictError::Username),
+        ConflictError::from_constraint("users_username_key")
+    );
+    assert_eq!(
+        Some(ConflictError::EventSlug),
+        ConflictError::from_constraint("events_event_name_event_slug_key")
+    );
+    // not a value users choose, stays an internal error
+    assert_eq!(
+        None,
+        ConflictError::from_constraint("mint_jobs_tx_hash_key")
+    );
+}
+
+#[tokio::test]
+async fn test_unique_violations() {
+    let db_client = connect().await;
+    let user_id = create_user(&db_client, Role::Buyer).await;
+    let user = db_get_user_by_id(&db_client, &user_id).await.unwrap();
+
+    let e = insert_copy(&db_client, &user, |copy| {
+        copy.username = user.username.clone()
+    })
+    .await;
+    assert_eq!(Some(ConflictError::Username), unique_violation(&e));
+
+    let e = insert_copy(&db_client, &user, |copy| {
+        copy.wallet_id = user.wallet_id.clone()
+    })
+    .await;
+    assert_eq!(Some(ConflictError::WalletId), unique_violation(&e));
+
+    // the primary key is not a conflict of the client's making
+    let e = insert_copy(&db_client, &user, |copy| copy.id = user.id).await;
+    assert_eq!(None, unique_violation(&e));
+    assert_eq!(ErrorCode::Internal, GqlError::Database(e).code());
+}
+
+#[tokio::test]
+async fn test_unique_violation_errors() {
+    let db_client = connect().await;
+    let user_id = create_user(&db_client, Role::Buyer).await;
+    let user = db_get_user_by_id(&db_client, &user_id).await.unwrap();
+
+    let e = insert_copy(&db_client, &user, |copy| {
+        copy.username = user.username.clone()
+    })
+    .await;
+    let error = GqlError::Database(e);
+    assert_eq!(ErrorCode::Conflict, error.code());
+    assert_eq!(Some("username"), error.field());
+
+    let error: FieldError<DefaultScalarValue> = error.into_field_error();
+    assert_eq!("Username is already taken", error.message());
+    assert_eq!(
+        &graphql_value!({ "code": "CONFLICT", "field": "username" }),
+        error.extensions()
+    );
+
+    // and the same conflict through the rest api
+    let e = insert_copy(&db_client, &user, |copy| {
+        copy.username = user.username.clone()
+    })
+    .await;
+    let error = std::sync::Arc::new(std::sync::Mutex::new(Some(e)));
+    let route = warp::any()
+        .and_then(move || {
+            let e = error.lock().unwrap().take().unwrap();
+            async move { Err::<String, _>(warp::reject::custom(Error::Postgres(e))) }
+        })
+        .recover(handle_rejection);
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(StatusCode::CONFLICT, res.status());
+    let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!("Username is already taken", body["message"]);
+}
@@ -0,0 +1,70 @@
+use bytes::Bytes;
+use common::TestContextBuilder;
+use gql_api::{
+    config::UsernamesConfig,
+    error::UserError,
+    http::handlers::check_username as check_username_handler,
+    usernames::{check_username, skeleton},
+};
+
+mod common;
+
+#[test]
+fn test_reserved_usernames() {
+    let config = UsernamesConfig::default();
+
+    assert_eq!(Ok(()), check_username(&config, "jane_doe"));
+    assert_eq!(Ok(()), check_username(&config, "admiral"));
+    assert_eq!(Ok(()), check_username(&config, "nearby"));
+
+    // whatever the case, separators or look-alike spelling
+    for username in [
+        "admin", "Admin", "ADMIN", "adm1n", "_admin_", "n3ar", "r00t", "sys-tem",
+    ] {
+        assert_eq!(
+            Err(UserError::DisallowedUsername(username.to_string())),
+            check_username(&config, username),
+            "{} should be reserved",
+            username
+        );
+    }
+}
+
+#[test]
+fn test_offensive_and_homoglyph_usernames() {
+    let config = UsernamesConfig::default();
+
+    assert!(check_username(&config, "sh1tposter").is_err());
+    assert!(check_username(&config, "xx_FUCK_xx").is_err());
+    // a cyrillic "а" in "admin"
+    assert!(check_username(&config, "аdmin").is_err());
+    assert!(check_username(&config, "jösé").is_err());
+
+    assert_eq!("admin", skeleton("4dm1n"));
+}
+
+#[test]
+fn test_configured_usernames() {
+    let config = UsernamesConfig {
+        reserved: vec!["tickets-team".to_string()],
+        blocked_words: vec![],
+    };
+
+    assert!(check_username(&config, "ticketsteam").is_err());
+    // the defaults are replaced
+    assert_eq!(Ok(()), check_username(&config, "admin"));
+}
+
+#[tokio::test]
+async fn test_check_username_disallowed() {
+    let resources = TestContextBuilder::new().build().await;
+    let body = serde_json::json!({ "username": "adm1n" }).to_string();
+
+    assert!(
+        check_username_handler(resources.ctx.clone(), Bytes::from(body))
+            .await
+            .is_err()
+    );
+    // the near client is not asked
+    assert!(resources.near_client.calls().is_empty());
+}
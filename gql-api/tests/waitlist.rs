@@ -0,0 +1,190 @@
+use gql_api::{
+    auth::Role,
+    config::WaitlistConfig,
+    db::{
+        models::{DbTicket, DbWaitlistEntry, DbWaitlistOpening},
+        sql::{
+            db_delete_waitlist_entry, db_get_waitlist_entry, db_get_waitlist_openings,
+            db_insert_ticket, db_insert_waitlist_entry, db_notify_waitlist_entries, sql_timestamp,
+        },
+    },
+    gql::models::NewTicket,
+    jobs::waitlist::notify_waitlists,
+};
+
+mod common;
+
+fn new_ticket(event_id: uuid::Uuid, quantity_available: Option<i32>) -> NewTicket {
+    NewTicket {
+        ticket_name: common::gen_string(10),
+        description: None,
+        price: None,
+        max_release_price: None,
+        quantity_available,
+        min_purchase_quantity: None,
+        max_purchase_quantity: None,
+        allow_transfers: None,
+        currency: None,
+        event_id,
+    }
+}
+
+#[test]
+fn test_waitlist_openings() {
+    let mut opening = DbWaitlistOpening {
+        ticket_id: uuid::Uuid::new_v4(),
+        event_id: uuid::Uuid::new_v4(),
+        quantity_remaining: Some(3),
+        pending_offers: 1,
+    };
+    assert_eq!(2, opening.openings(10));
+    assert_eq!(1, opening.openings(1));
+
+    // the notified buyers may still take every ticket left
+    opening.pending_offers = 5;
+    assert_eq!(0, opening.openings(10));
+
+    opening.quantity_remaining = None;
+    assert_eq!(10, opening.openings(10));
+}
+
+#[tokio::test]
+async fn test_join_and_leave_waitlist() {
+    let cfg = common::setup().await;
+    let db_ticket = DbTicket::new(new_ticket(cfg.event.id, Some(1)), &cfg.event);
+    db_insert_ticket(&cfg.client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+    let buyer = common::create_user(&cfg.client, Role::Buyer).await;
+
+    let db_entry = DbWaitlistEntry::new(db_ticket.id, buyer);
+    assert_eq!(
+        1,
+        db_insert_waitlist_entry(&cfg.client, &db_entry)
+            .await
+            .expect("failed to join waitlist")
+    );
+    // joining again keeps the original place
+    assert_eq!(
+        0,
+        db_insert_waitlist_entry(&cfg.client, &DbWaitlistEntry::new(db_ticket.id, buyer))
+            .await
+            .expect("failed to join waitlist")
+    );
+    let stored = db_get_waitlist_entry(&cfg.client, &db_ticket.id, &buyer)
+        .await
+        .expect("failed to get waitlist entry")
+        .expect("buyer should be waitlisted");
+    assert_eq!(db_entry.id, stored.id);
+    assert_eq!(None, stored.notified_at);
+
+    assert_eq!(
+        1,
+        db_delete_waitlist_entry(&cfg.client, &db_ticket.id, &buyer)
+            .await
+            .expect("failed to leave waitlist")
+    );
+    assert!(db_get_waitlist_entry(&cfg.client, &db_ticket.id, &buyer)
+        .await
+        .expect("failed to get waitlist entry")
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_notify_waitlist_entries() {
+    let cfg = common::setup().await;
+    let db_ticket = DbTicket::new(new_ticket(cfg.event.id, Some(2)), &cfg.event);
+    db_insert_ticket(&cfg.client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+
+    let mut buyers = vec![];
+    for i in 0..3 {
+        let buyer = common::create_user(&cfg.client, Role::Buyer).await;
+        let mut db_entry = DbWaitlistEntry::new(db_ticket.id, buyer);
+        db_entry.created_at = sql_timestamp(Some(i - 10));
+        db_insert_waitlist_entry(&cfg.client, &db_entry)
+            .await
+            .expect("failed to join waitlist");
+        buyers.push(buyer);
+    }
+
+    let offered_after = sql_timestamp(Some(-60));
+    let openings = db_get_waitlist_openings(&cfg.client, &offered_after)
+        .await
+        .expect("failed to get waitlist openings");
+    let opening = openings
+        .iter()
+        .find(|opening| opening.ticket_id.eq(&db_ticket.id))
+        .expect("ticket should have openings");
+    assert_eq!(Some(2), opening.quantity_remaining);
+    assert_eq!(0, opening.pending_offers);
+
+    // the longest waiting buyer is notified first
+    let notified = db_notify_waitlist_entries(&cfg.client, &db_ticket.id, 1)
+        .await
+        .expect("failed to notify waitlist");
+    assert_eq!(
+        vec![buyers[0]],
+        notified.iter().map(|e| e.user_id).collect::<Vec<_>>()
+    );
+    assert!(notified[0].notified_at.is_some());
+
+    let openings = db_get_waitlist_openings(&cfg.client, &offered_after)
+        .await
+        .expect("failed to get waitlist openings");
+    let opening = openings
+        .iter()
+        .find(|opening| opening.ticket_id.eq(&db_ticket.id))
+        .expect("ticket should have openings");
+    assert_eq!(1, opening.pending_offers);
+    assert_eq!(1, opening.openings(10));
+}
+
+#[tokio::test]
+async fn test_notify_waitlists() {
+    let resources = common::TestContextBuilder::new().build().await;
+    let db_client = &resources.ctx.db_client;
+    let db_event = common::create_event(db_client).await;
+    let db_ticket = DbTicket::new(new_ticket(db_event.id, Some(1)), &db_event);
+    db_insert_ticket(db_client, &db_ticket)
+        .await
+        .expect("failed to insert ticket");
+
+    let first_buyer = common::create_user(db_client, Role::Buyer).await;
+    let second_buyer = common::create_user(db_client, Role::Buyer).await;
+    for (i, buyer) in [first_buyer, second_buyer].iter().enumerate() {
+        let mut db_entry = DbWaitlistEntry::new(db_ticket.id, *buyer);
+        db_entry.created_at = sql_timestamp(Some(i as i64 - 10));
+        db_insert_waitlist_entry(db_client, &db_entry)
+            .await
+            .expect("failed to join waitlist");
+    }
+
+    let config = WaitlistConfig::default();
+    notify_waitlists(&resources.ctx, &config)
+        .await
+        .expect("failed to notify waitlists");
+
+    // one ticket left, so only the first buyer is offered it
+    let first_entry = db_get_waitlist_entry(db_client, &db_ticket.id, &first_buyer)
+        .await
+        .expect("failed to get waitlist entry")
+        .expect("buyer should be waitlisted");
+    assert!(first_entry.notified_at.is_some());
+    let second_entry = db_get_waitlist_entry(db_client, &db_ticket.id, &second_buyer)
+        .await
+        .expect("failed to get waitlist entry")
+        .expect("buyer should be waitlisted");
+    assert_eq!(None, second_entry.notified_at);
+
+    // the offer is still pending on the next run
+    notify_waitlists(&resources.ctx, &config)
+        .await
+        .expect("failed to notify waitlists");
+    let second_entry = db_get_waitlist_entry(db_client, &db_ticket.id, &second_buyer)
+        .await
+        .expect("failed to get waitlist entry")
+        .expect("buyer should be waitlisted");
+    assert_eq!(None, second_entry.notified_at);
+}
@@ -0,0 +1,170 @@
+use common::TestContextBuilder;
+use gql_api::{
+    auth::{Role, UserStatus},
+    db::{
+        models::{DbBuyerRecoverySession, DbUser},
+        sql::{db_get_user_by_id, db_insert_buyer_recovery_session, db_insert_user, sql_timestamp},
+    },
+    fakes::fake_cypher,
+    gql::{
+        mutations::BuyerMutationRoot,
+        quiries::BuyerQueryRoot,
+        schema::{BuyerSchema, Context as ResourcesContext},
+    },
+};
+use juniper::EmptySubscription;
+
+mod common;
+
+const SECRET_KEY: &str = "ed25519:secret";
+
+async fn rotate(ctx: &ResourcesContext, current_secret: &str, new_secret: &str) -> bool {
+    let schema = BuyerSchema::new(BuyerQueryRoot, BuyerMutationRoot, EmptySubscription::new());
+    let mutation = format!(
+        r#"mutation {{ rotateWalletSecret(rotateWalletSecret: {{ currentSecret: "{}", newSecret: "{}" }}) {{ id }} }}"#,
+        current_secret, new_secret
+    );
+    let (_, errors) = juniper::execute(&mutation, None, &schema, &juniper::Variables::new(), ctx)
+        .await
+        .expect("invalid mutation");
+    errors.is_empty()
+}
+
+/// The exported secret key, `None` if the export failed
+async fn export(ctx: &ResourcesContext, secret: &str, verification: &str) -> Option<String> {
+    let schema = BuyerSchema::new(BuyerQueryRoot, BuyerMutationRoot, EmptySubscription::new());
+    let mutation = format!(
+        r#"mutation {{ exportWallet(exportWallet: {{ secret: "{}", {} }}) {{ walletId secretKey }} }}"#,
+        secret, verification
+    );
+    let (value, errors) =
+        juniper::execute(&mutation, None, &schema, &juniper::Variables::new(), ctx)
+            .await
+            .expect("invalid mutation");
+    if !errors.is_empty() {
+        return None;
+    }
+    value
+        .as_object_value()?
+        .get_field_value("exportWallet")?
+        .as_object_value()?
+        .get_field_value("secretKey")?
+        .as_scalar_value::<String>()
+        .cloned()
+}
+
+async fn recovery_session(ctx: &ResourcesContext, db_user: &DbUser, code: &str) -> String {
+    let db_session = DbBuyerRecoverySession::new(
+        uuid::Uuid::new_v4(),
+        sql_timestamp(None),
+        ctx.codes.hash(code),
+        db_user.phone_number.clone().unwrap_or_default(),
+        false,
+        db_user.id,
+        sql_timestamp(Some(900)),
+    );
+    db_insert_buyer_recovery_session(&ctx.db_client, &db_session)
+        .await
+        .expect("unable to create recovery session");
+    format!(
+        r#"recoverySessionId: "{}", recoveryCode: "{}""#,
+        db_session.id, code
+    )
+}
+
+#[tokio::test]
+async fn test_export_wallet() {
+    let resources = TestContextBuilder::new().build().await;
+
+    let db_user = DbUser::new(
+        uuid::Uuid::new_v4(),
+        None,
+        common::gen_string(20),
+        None,
+        None,
+        None,
+        Some(fake_cypher("1234", SECRET_KEY)),
+        Role::Buyer,
+        common::gen_string(20),
+        "0".to_string(),
+        UserStatus::PhoneVerified,
+    );
+    db_insert_user(&resources.ctx.db_client, &db_user)
+        .await
+        .expect("unable to create user");
+    let ctx = resources.ctx.for_user(Some(db_user.id));
+
+    // re-verified one way only, the buyer has no password
+    assert!(export(&ctx, "1234", r#"password: "secret""#)
+        .await
+        .is_none());
+    let verification = recovery_session(&ctx, &db_user, "123456").await;
+    assert!(export(
+        &ctx,
+        "1234",
+        &format!(r#"{}, password: "secret""#, verification)
+    )
+    .await
+    .is_none());
+
+    assert_eq!(
+        Some(SECRET_KEY.to_string()),
+        export(&ctx, "1234", &verification).await
+    );
+    // the recovery code is used up
+    assert!(export(&ctx, "1234", &verification).await.is_none());
+
+    // the failed attempts count against the daily limit as well
+    let verification = recovery_session(&ctx, &db_user, "654321").await;
+    assert!(export(&ctx, "1234", &verification).await.is_none());
+
+    // the recovery sessions of other buyers do not verify anyone else
+    let other_id = common::create_user(&resources.ctx.db_client, Role::Buyer).await;
+    let other_ctx = resources.ctx.for_user(Some(other_id));
+    assert!(export(&other_ctx, "1234", &verification).await.is_none());
+}
+
+#[tokio::test]
+async fn test_rotate_wallet_secret() {
+    let resources = TestContextBuilder::new().build().await;
+
+    let db_user = DbUser::new(
+        uuid::Uuid::new_v4(),
+        None,
+        common::gen_string(20),
+        None,
+        None,
+        None,
+        Some(fake_cypher("1234", SECRET_KEY)),
+        Role::Buyer,
+        common::gen_string(20),
+        "0".to_string(),
+        UserStatus::PhoneVerified,
+    );
+    db_insert_user(&resources.ctx.db_client, &db_user)
+        .await
+        .expect("unable to create user");
+    let ctx = resources.ctx.for_user(Some(db_user.id));
+
+    // the current secret must decrypt the key, and the new one must differ
+    assert!(!rotate(&ctx, "9999", "5678").await);
+    assert!(!rotate(&ctx, "1234", "1234").await);
+    assert!(!rotate(&ctx, "1234", "12").await);
+
+    assert!(rotate(&ctx, "1234", "5678").await);
+    let updated_db_user = db_get_user_by_id(&resources.ctx.db_client, &db_user.id)
+        .await
+        .expect("unable to fetch user");
+    assert_eq!(
+        Some(fake_cypher("5678", SECRET_KEY)),
+        updated_db_user.encrypted_secret_key
+    );
+
+    // the old secret no longer works
+    assert!(!rotate(&ctx, "1234", "0000").await);
+
+    // sellers have no wallet secret to rotate
+    let seller_id = common::create_user(&resources.ctx.db_client, Role::Seller).await;
+    let seller_ctx = resources.ctx.for_user(Some(seller_id));
+    assert!(!rotate(&seller_ctx, "1234", "5678").await);
+}